@@ -0,0 +1,160 @@
+//! Benchmarks of block accesses in chunks and sub chunks, with the three palette strategies
+//! of sub chunks: linear (few states), hash (up to 256 states) and global.
+
+use std::sync::Arc;
+
+use criterion::{criterion_group, criterion_main, Criterion, black_box};
+
+use mc_core::block::{GlobalBlocks, BlockState};
+use mc_core::biome::GlobalBiomes;
+use mc_core::entity::GlobalEntities;
+use mc_core::heightmap::GlobalHeightmaps;
+use mc_core::world::level::LevelEnv;
+use mc_core::world::chunk::{Chunk, ChunkHeight, SubChunk};
+use mc_core::world::source::ChunkLoadRequest;
+use mc_core::pos::BoundingBox;
+
+
+mc_core::blocks_properties! {
+    PROP_VARIANT: int("variant", 200);
+}
+
+mc_core::blocks_specs! {
+    SPEC_VARIANT: [PROP_VARIANT];
+}
+
+mc_core::blocks!(BENCH_BLOCKS "bench" [
+    AIR "air",
+    STONE "stone",
+    VARIANT "variant" SPEC_VARIANT,
+    SHADE "shade" SPEC_VARIANT
+]);
+
+mc_core::biomes!(BENCH_BIOMES "bench" [
+    VOID "void" 0,
+]);
+
+
+fn build_env() -> Arc<LevelEnv> {
+    Arc::new(LevelEnv::new(
+        GlobalBlocks::with_all(&BENCH_BLOCKS).unwrap(),
+        GlobalBiomes::with_all(&BENCH_BIOMES).unwrap(),
+        GlobalEntities::new(),
+        GlobalHeightmaps::new()
+    ))
+}
+
+/// Return the given number of distinct states, AIR and STONE first.
+fn get_states(count: usize) -> Vec<&'static BlockState> {
+    let variants = (0..200).map(|i| VARIANT.get_default_state().with(&PROP_VARIANT, i as u8).unwrap());
+    let shades = (0..200).map(|i| SHADE.get_default_state().with(&PROP_VARIANT, i as u8).unwrap());
+    [AIR.get_default_state(), STONE.get_default_state()].iter().copied()
+        .chain(variants)
+        .chain(shades)
+        .take(count)
+        .collect()
+}
+
+/// Build a chunk of 16 sub chunks where all blocks are set from the given states.
+fn build_chunk(env: &Arc<LevelEnv>, states: &[&'static BlockState]) -> Chunk {
+    let mut chunk = ChunkLoadRequest { env: Arc::clone(env), height: ChunkHeight::new(0, 15), cx: 0, cz: 0 }.build_chunk();
+    for y in 0..256 {
+        for z in 0..16 {
+            for x in 0..16 {
+                let i = x as usize + z as usize * 16 + y as usize * 256;
+                chunk.set_block(x, y, z, states[i % states.len()]).unwrap();
+            }
+        }
+    }
+    chunk
+}
+
+
+fn bench_sub_chunk(c: &mut Criterion) {
+
+    let env = build_env();
+
+    for &(name, count) in &[("linear", 2), ("hash", 64), ("global", 400)] {
+
+        let states = get_states(count);
+        let mut sub_chunk = SubChunk::new(Arc::clone(&env));
+        for (i, &state) in states.iter().cycle().take(4096).enumerate() {
+            sub_chunk.set_block((i & 15) as u8, (i >> 8) as u8, ((i >> 4) & 15) as u8, state).unwrap();
+        }
+
+        c.bench_function(&format!("sub_chunk_get_block_{}", name), |b| b.iter(|| {
+            for y in 0..16 {
+                for z in 0..16 {
+                    for x in 0..16 {
+                        black_box(sub_chunk.get_block(x, y, z));
+                    }
+                }
+            }
+        }));
+
+        c.bench_function(&format!("sub_chunk_set_block_{}", name), |b| b.iter(|| {
+            let mut states = states.iter().cycle();
+            for y in 0..16 {
+                for z in 0..16 {
+                    for x in 0..16 {
+                        sub_chunk.set_block(x, y, z, states.next().unwrap()).unwrap();
+                    }
+                }
+            }
+        }));
+
+        c.bench_function(&format!("sub_chunk_iter_blocks_{}", name), |b| b.iter(|| {
+            for state in sub_chunk.iter_blocks() {
+                black_box(state);
+            }
+        }));
+
+    }
+
+}
+
+
+fn bench_chunk(c: &mut Criterion) {
+
+    let env = build_env();
+
+    for &(name, count) in &[("linear", 2), ("hash", 64)] {
+
+        let states = get_states(count);
+        let mut chunk = build_chunk(&env, &states);
+
+        c.bench_function(&format!("chunk_get_block_{}", name), |b| b.iter(|| {
+            for y in 0..256 {
+                for z in 0..16 {
+                    for x in 0..16 {
+                        black_box(chunk.get_block(x, y, z).unwrap());
+                    }
+                }
+            }
+        }));
+
+        c.bench_function(&format!("chunk_set_block_{}", name), |b| b.iter(|| {
+            let mut states = states.iter().cycle();
+            for y in 0..256 {
+                for z in 0..16 {
+                    for x in 0..16 {
+                        chunk.set_block(x, y, z, states.next().unwrap()).unwrap();
+                    }
+                }
+            }
+        }));
+
+        let region = BoundingBox::new_chunk(0, 0, 0, 255);
+        c.bench_function(&format!("chunk_iter_region_blocks_{}", name), |b| b.iter(|| {
+            for block in chunk.iter_region_blocks(&region) {
+                black_box(block);
+            }
+        }));
+
+    }
+
+}
+
+
+criterion_group!(benches, bench_sub_chunk, bench_chunk);
+criterion_main!(benches);
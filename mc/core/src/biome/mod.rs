@@ -1,6 +1,8 @@
 use std::collections::HashMap;
+use std::ops::Deref;
+use std::sync::Arc;
 
-use crate::util::OpaquePtr;
+use crate::util::{OpaquePtr, PtrBuildHasher, GlobalPalette};
 
 
 /// A basic biome structure. This structure is made for static definition.
@@ -53,7 +55,7 @@ impl Eq for &'static Biome {}
 /// arrays defined using the macro `biomes!`.
 pub struct GlobalBiomes {
     next_sid: u16,
-    biome_to_sid: HashMap<BiomeKey, u16>,
+    biome_to_sid: HashMap<BiomeKey, u16, PtrBuildHasher>,
     sid_to_biome: Vec<&'static Biome>,
     name_to_biome: HashMap<&'static str, &'static Biome>,
     id_to_biome: HashMap<i32, &'static Biome>
@@ -64,7 +66,7 @@ impl GlobalBiomes {
     pub fn new() -> Self {
         Self {
             next_sid: 0,
-            biome_to_sid: HashMap::new(),
+            biome_to_sid: HashMap::default(),
             sid_to_biome: Vec::new(),
             name_to_biome: HashMap::new(),
             id_to_biome: HashMap::new()
@@ -140,6 +142,52 @@ impl GlobalBiomes {
         self.sid_to_biome.len()
     }
 
+    /// Freeze this palette into an immutable handle that can be shared between threads.
+    pub fn freeze(self) -> Arc<FrozenBiomes> {
+        Arc::new(FrozenBiomes { inner: self })
+    }
+
+}
+
+
+/// An immutable biomes palette returned by `GlobalBiomes::freeze`, all lookups of the palette
+/// are available through `Deref`.
+pub struct FrozenBiomes {
+    inner: GlobalBiomes
+}
+
+impl Deref for FrozenBiomes {
+
+    type Target = GlobalBiomes;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+
+}
+
+impl GlobalPalette<Biome> for FrozenBiomes {
+
+    #[inline]
+    fn get_global_sid(&self, item: &'static Biome) -> Option<u32> {
+        self.get_sid_from(item).map(|sid| sid as u32)
+    }
+
+    #[inline]
+    fn get_global_item(&self, sid: u32) -> Option<&'static Biome> {
+        if sid <= u16::MAX as u32 {
+            self.get_biome_from(sid as u16)
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn global_items_count(&self) -> usize {
+        self.biomes_count()
+    }
+
 }
 
 
@@ -1,12 +1,15 @@
 use std::collections::HashMap;
 use std::ptr::NonNull;
 use std::fmt::Debug;
+use std::ops::Deref;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use once_cell::sync::OnceCell;
 use bit_vec::BitVec;
 
 use crate::tag::{TagType, TagTypeKey};
-use crate::util::OpaquePtr;
+use crate::util::{OpaquePtr, PtrBuildHasher, GlobalPalette};
 
 mod state;
 mod property;
@@ -34,17 +37,18 @@ pub type BlockKey = OpaquePtr<Block>;
 
 
 /// Internal enumeration to avoid allocation over-head for single block. This allows
-/// blocks with no properties to avoid allocating a `Vec` and a `HashMap`.
+/// blocks with no properties to avoid allocating the states and properties tables.
 #[derive(Debug)]
 enum BlockStorage {
     /// Storage for a single state.
     Single(BlockState),
     /// Storage when there is single or multiple properties. This type of storage
     /// implies that all owned states must have BlockStateProperties::Some.
-    /// By using this storage you assert that properties map is not empty.
+    /// By using this storage you assert that properties table is not empty.
     Complex {
         states: Vec<BlockState>,
-        properties: HashMap<&'static str, SharedProperty>,
+        /// Properties table shared by all states, in the order of the block's spec.
+        properties: Vec<SharedProperty>,
         default_state_index: usize
     }
 }
@@ -85,6 +89,13 @@ impl Block {
         OpaquePtr::new(self)
     }
 
+    /// Return true if the states of this block have already been built, they are built on
+    /// first access or when the block is registered in a `GlobalBlocks` palette.
+    #[inline]
+    pub fn is_initialized(&self) -> bool {
+        self.states.get().is_some()
+    }
+
     fn get_storage(&'static self) -> &'static BlockStorage {
         self.states.get_or_init(|| self.make_storage())
     }
@@ -156,6 +167,18 @@ impl Block {
         self.get_storage().get_states()
     }
 
+    /// Return the approximate memory size in bytes of the states and properties tables of
+    /// this block, this forces the states to be built.
+    pub fn get_storage_size(&'static self) -> usize {
+        std::mem::size_of::<BlockStorage>() + match self.get_storage() {
+            BlockStorage::Single(_) => 0,
+            BlockStorage::Complex { states, properties, .. } => {
+                states.capacity() * std::mem::size_of::<BlockState>()
+                    + properties.capacity() * std::mem::size_of::<SharedProperty>()
+            }
+        }
+    }
+
 }
 
 
@@ -193,13 +216,13 @@ impl BlockStorage {
             BlockStorage::Single(_) => None,
             BlockStorage::Complex {
                 properties, ..
-            } => properties.get(name)
+            } => properties.iter().find(|shared| shared.get_name() == name)
         }
     }
 
     /// Internal method for Debug implementation of `BlockState` and values iteration.
     /// None is returned if there is no properties and the block has a single state.
-    fn get_shared_props(&self) -> Option<&HashMap<&'static str, SharedProperty>> {
+    fn get_shared_props(&self) -> Option<&[SharedProperty]> {
         match self {
             BlockStorage::Single(_) => None,
             BlockStorage::Complex {
@@ -229,14 +252,14 @@ pub struct GlobalBlocks {
     next_sid: u32,
     /// Each registered block is mapped to a tuple (index, sid), where index is the index of
     /// insertion of the block and sid being the save ID of the first state of this block.
-    block_to_indices: HashMap<BlockKey, (usize, u32)>,
+    block_to_indices: HashMap<BlockKey, (usize, u32), PtrBuildHasher>,
     /// A vector storing references to each block state, the index of each state is called
     /// its "save ID".
     ordered_states: Vec<&'static BlockState>,
     /// A mapping of block's names to them.
     name_to_blocks: HashMap<&'static str, &'static Block>,
     /// Contains stores of each tag type. For each tag, either small of big stores are used.
-    tag_stores: HashMap<TagTypeKey, TagStore>
+    tag_stores: HashMap<TagTypeKey, TagStore, PtrBuildHasher>
 }
 
 impl GlobalBlocks {
@@ -244,13 +267,56 @@ impl GlobalBlocks {
     pub fn new() -> Self {
         Self {
             next_sid: 0,
-            block_to_indices: HashMap::new(),
+            block_to_indices: HashMap::default(),
             ordered_states: Vec::new(),
             name_to_blocks: HashMap::new(),
-            tag_stores: HashMap::new()
+            tag_stores: HashMap::default()
         }
     }
 
+    /// Force the states of all given blocks to be built now, instead of on their first access.
+    /// Blocks are split between the given number of worker threads, states are built on the
+    /// current thread if less than 2 workers are given.
+    ///
+    /// States are already built when blocks are registered in a palette, so this is mainly
+    /// useful to pay this cost at startup for blocks used before the palette is created,
+    /// for example by generators' static settings.
+    pub fn init_all_states(blocks: &[&'static Block], workers: usize) -> StatesInitReport {
+
+        fn init_states(blocks: &[&'static Block]) -> usize {
+            let mut count = 0;
+            for &block in blocks {
+                if !block.is_initialized() {
+                    block.get_storage();
+                    count += 1;
+                }
+            }
+            count
+        }
+
+        let start = Instant::now();
+
+        let built_blocks_count = if workers < 2 || blocks.len() < 2 {
+            init_states(blocks)
+        } else {
+            let chunk_size = blocks.len().div_ceil(workers);
+            std::thread::scope(|scope| {
+                let handles: Vec<_> = blocks.chunks(chunk_size)
+                    .map(|chunk| scope.spawn(move || init_states(chunk)))
+                    .collect();
+                handles.into_iter().map(|handle| handle.join().unwrap()).sum()
+            })
+        };
+
+        StatesInitReport {
+            blocks_count: blocks.len(),
+            built_blocks_count,
+            states_count: blocks.iter().map(|block| block.get_states().len()).sum(),
+            duration: start.elapsed()
+        }
+
+    }
+
     /// A simple constructor to directly call `register_all` with given blocks slice.
     pub fn with_all(slice: &[&'static Block]) -> Result<Self, ()> {
         let mut blocks = Self::new();
@@ -326,6 +392,11 @@ impl GlobalBlocks {
         self.name_to_blocks.get(name).cloned()
     }
 
+    /// Iterate over all blocks of the palette, in no particular order.
+    pub fn iter_blocks(&self) -> impl Iterator<Item = &'static Block> + '_ {
+        self.name_to_blocks.values().copied()
+    }
+
     /// Return true if the palette contains the given block.
     pub fn has_block(&self, block: &'static Block) -> bool {
         self.block_to_indices.contains_key(&block.get_key())
@@ -347,6 +418,13 @@ impl GlobalBlocks {
         self.tag_stores.insert(tag_type.get_key(), TagStore::Small(Vec::new()));
     }
 
+    /// Get a registered tag type from its name.
+    pub fn get_tag_type_from_name(&self, name: &str) -> Option<&'static TagType> {
+        self.tag_stores.keys()
+            .map(|&key| key.inner())
+            .find(|tag_type| tag_type.name == name)
+    }
+
     /// Set or unset a tag to some blocks.
     pub fn set_blocks_tag<I>(&mut self, tag_type: &'static TagType, enabled: bool, blocks: I) -> Result<(), ()>
     where
@@ -419,8 +497,79 @@ impl GlobalBlocks {
         self.tag_stores.len()
     }
 
+    /// Freeze this palette into an immutable handle that can be shared between threads. Small
+    /// tag stores are converted to bit vectors in order to make all tag lookups constant time.
+    pub fn freeze(mut self) -> Arc<FrozenBlocks> {
+        let count = self.block_to_indices.len();
+        let block_to_indices = &self.block_to_indices;
+        for store in self.tag_stores.values_mut() {
+            if let TagStore::Small(vec) = store {
+                let mut new_vec = BitVec::from_elem(count, false);
+                for block in vec.iter() {
+                    if let Some(&(idx, _)) = block_to_indices.get(&block.get_key()) {
+                        new_vec.set(idx, true);
+                    }
+                }
+                *store = TagStore::Big(new_vec);
+            }
+        }
+        Arc::new(FrozenBlocks { inner: self })
+    }
+
+}
+
+/// Report returned by `GlobalBlocks::init_all_states`.
+#[derive(Debug, Clone)]
+pub struct StatesInitReport {
+    /// Number of given blocks.
+    pub blocks_count: usize,
+    /// Number of blocks whose states were built by this call, the others were already built.
+    pub built_blocks_count: usize,
+    /// Total number of states of the given blocks.
+    pub states_count: usize,
+    /// Time taken to build the states.
+    pub duration: Duration
+}
+
+
+/// An immutable blocks palette returned by `GlobalBlocks::freeze`, all lookups of the palette
+/// are available through `Deref`. This is the form used by level environments, it's shared
+/// between level sources and generation threads without any lock.
+pub struct FrozenBlocks {
+    inner: GlobalBlocks
 }
 
+impl Deref for FrozenBlocks {
+
+    type Target = GlobalBlocks;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+
+}
+
+impl GlobalPalette<BlockState> for FrozenBlocks {
+
+    #[inline]
+    fn get_global_sid(&self, item: &'static BlockState) -> Option<u32> {
+        self.get_sid_from(item)
+    }
+
+    #[inline]
+    fn get_global_item(&self, sid: u32) -> Option<&'static BlockState> {
+        self.get_state_from(sid)
+    }
+
+    #[inline]
+    fn global_items_count(&self) -> usize {
+        self.states_count()
+    }
+
+}
+
+
 #[derive(Debug)]
 enum TagStore {
     Small(Vec<&'static Block>),
@@ -462,3 +611,35 @@ macro_rules! _blocks_spec {
     () => { $crate::block::BlockSpec::Single };
     ($spec_id:ident) => { $crate::block::BlockSpec::Complex(&$spec_id) }
 }
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    static PROP_LIT: BoolProperty = BoolProperty("lit");
+    static PROP_AGE: IntProperty = IntProperty("age", 3);
+
+    blocks_specs! {
+        SPEC_CROP: [PROP_LIT, PROP_AGE];
+    }
+
+    blocks!(TEST_BLOCKS "test" [
+        ROCK "rock",
+        CROP "crop" SPEC_CROP
+    ]);
+
+    #[test]
+    fn init_all_states() {
+
+        let report = GlobalBlocks::init_all_states(&TEST_BLOCKS, 2);
+        assert_eq!(report.blocks_count, 2);
+        assert_eq!(report.built_blocks_count, 2);
+        assert_eq!(report.states_count, 7);
+        assert!(TEST_BLOCKS.iter().all(|block| block.is_initialized()));
+        assert_eq!(GlobalBlocks::init_all_states(&TEST_BLOCKS, 1).built_blocks_count, 0);
+
+    }
+
+}
@@ -1,5 +1,4 @@
 use std::fmt::{Debug, Formatter, Result as FmtResult};
-use std::collections::HashMap;
 use std::ptr::NonNull;
 
 use super::{Block, UntypedProperty, Property, PropertySerializable};
@@ -10,25 +9,40 @@ use crate::util::OpaquePtr;
 pub const MAX_STATES_COUNT: usize = 0x10000;
 
 
+/// A property of a block, shared by all states of the block. The value of the property
+/// for a state is computed from the index of the state, the states being ordered like the
+/// digits of a number where each property has its own base (its length).
 #[derive(Debug)]
 pub(crate) struct SharedProperty {
     prop: &'static dyn UntypedProperty,
-    index: usize,
     length: u8,
     period: usize
 }
 
+impl SharedProperty {
+
+    #[inline]
+    pub(super) fn get_name(&self) -> &'static str {
+        self.prop.name()
+    }
+
+    /// Get the encoded value of this property for the state at the given index.
+    #[inline]
+    fn get_value(&self, state_index: u16) -> u8 {
+        ((state_index as usize / self.period) % self.length as usize) as u8
+    }
+
+}
+
 
 /// Represent a particular state of a block, this block state also know
 /// all its neighbors by their properties and values.
 ///
-/// To build states, use `BlockStateContainerBuilder` and add all wanted
-/// properties.
+/// States don't store their property values, these are computed from the state's index
+/// and the properties table shared by all states of the block.
 pub struct BlockState {
     /// The index of this state within the shared data's states vector.
     index: u16,
-    /// Array of property encoded values.
-    properties: Vec<u8>,
     /// Circular reference back to the owner
     block: NonNull<Block>
 }
@@ -44,59 +58,41 @@ impl BlockState {
     pub(crate) fn build_singleton() -> BlockState {
         BlockState {
             index: 0,
-            properties: Vec::new(),
             block: NonNull::dangling()
         }
     }
 
-    pub(crate) fn build_complex(properties: &[&'static dyn UntypedProperty]) -> (HashMap<&'static str, SharedProperty>, Vec<BlockState>) {
+    pub(crate) fn build_complex(properties: &[&'static dyn UntypedProperty]) -> (Vec<SharedProperty>, Vec<BlockState>) {
 
         debug_assert!(!properties.is_empty(), "building complex states without properties is not allowed");
 
-        let mut states_count = 1;
-        let mut properties_periods = Vec::with_capacity(properties.len());
-
-        for &prop in properties {
-            let length = prop.len();
-            states_count *= length as usize;
-            properties_periods.push((prop, length, 1usize));
-        }
+        let states_count = properties.iter()
+            .map(|prop| prop.len() as usize)
+            .product::<usize>();
 
         if states_count > MAX_STATES_COUNT {
             panic!("Too many properties for this state, the maximum number is {}.", MAX_STATES_COUNT);
         }
 
-        let mut shared_properties = HashMap::with_capacity(properties.len());
+        let mut shared_properties = Vec::with_capacity(properties.len());
 
-        let mut next_period = 1;
-        for (i, (prop, length, period)) in properties_periods.iter_mut().enumerate().rev() {
-            let prop = *prop;
-            *period = next_period;
-            next_period *= *length as usize;
-            shared_properties.insert(prop.name(), SharedProperty {
+        let mut next_period = states_count;
+        for &prop in properties {
+            let length = prop.len();
+            next_period /= length as usize;
+            shared_properties.push(SharedProperty {
                 prop,
-                index: i,
-                length: *length,
-                period: *period
+                length,
+                period: next_period
             });
         }
 
-        let mut shared_states = Vec::with_capacity(states_count);
-
-        for i in 0..states_count {
-
-            let mut state_properties = Vec::with_capacity(properties.len());
-            for (_, length, period) in &properties_periods {
-                state_properties.push(((i / *period) % (*length as usize)) as u8);
-            }
-
-            shared_states.push(BlockState {
+        let shared_states = (0..states_count)
+            .map(|i| BlockState {
                 index: i as u16,
-                properties: state_properties,
                 block: NonNull::dangling()
-            });
-
-        }
+            })
+            .collect();
 
         (shared_properties, shared_states)
 
@@ -146,7 +142,7 @@ impl BlockState {
 
         let prop = self.get_block_shared_prop(&property.name())?;
         if prop.prop.type_id() == property.type_id() {
-            property.decode(self.properties[prop.index])
+            property.decode(prop.get_value(self.index))
         } else {
             None
         }
@@ -185,7 +181,7 @@ impl BlockState {
     fn with_unchecked(&self, prop: &SharedProperty, prop_value: u8) -> Option<&BlockState> {
 
         let new_value = prop_value as isize;
-        let current_value = self.properties[prop.index] as isize;
+        let current_value = prop.get_value(self.index) as isize;
 
         Some(if new_value == current_value {
             self
@@ -201,9 +197,9 @@ impl BlockState {
     /// No iterator is returned if the underlying block as no other state other than this one.
     pub fn iter_raw_states<'a>(&'a self) -> Option<impl Iterator<Item = (&'static str, String)> + 'a> {
         self.get_block().get_storage().get_shared_props().map(move |props| {
-            props.iter().map(move |(&name, shared)| {
-                let raw_value = self.properties[shared.index];
-                (name, shared.prop.prop_to_string(raw_value).unwrap())
+            props.iter().map(move |shared| {
+                let raw_value = shared.get_value(self.index);
+                (shared.prop.name(), shared.prop.prop_to_string(raw_value).unwrap())
             })
         })
     }
@@ -230,13 +226,71 @@ impl Debug for BlockState {
             None => Vec::with_capacity(0)
         };
 
+        let raw_reprs: Vec<u8> = match self.get_block().get_storage().get_shared_props() {
+            Some(props) => props.iter().map(|shared| shared.get_value(self.index)).collect(),
+            None => Vec::with_capacity(0)
+        };
+
         f.debug_struct("BlockState")
             .field("block", &self.get_block().get_name())
             .field("index", &self.index)
             .field("properties", &reprs)
-            .field("raw_properties", &self.properties)
+            .field("raw_properties", &raw_reprs)
             .finish()
 
     }
 
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::block::{BoolProperty, IntProperty};
+
+    static PROP_LIT: BoolProperty = BoolProperty("lit");
+    static PROP_AGE: IntProperty = IntProperty("age", 3);
+
+    crate::blocks_specs! {
+        SPEC_CROP: [PROP_LIT, PROP_AGE];
+    }
+
+    crate::blocks!(TEST_BLOCKS "test" [
+        CROP "crop" SPEC_CROP
+    ]);
+
+    #[test]
+    fn shared_properties() {
+
+        let states = TEST_BLOCKS[0].get_states();
+        assert_eq!(states.len(), 6);
+        assert_eq!(CROP.get_default_state().get_index(), 0);
+
+        for (i, state) in states.iter().enumerate() {
+
+            // Properties are iterated in the order of the spec, the first property having
+            // the largest period.
+            assert_eq!(state.get_index() as usize, i);
+            let raw: Vec<(&'static str, String)> = state.iter_raw_states().unwrap().collect();
+            assert_eq!(raw, vec![
+                ("lit", (i >= 3).to_string()),
+                ("age", (i % 3).to_string())
+            ]);
+
+            let lit = state.expect(&PROP_LIT);
+            let age = state.expect(&PROP_AGE);
+            assert_eq!(state.with(&PROP_LIT, lit).unwrap(), state);
+            assert_eq!(state.with(&PROP_AGE, age).unwrap(), state);
+            assert_eq!(state.with(&PROP_LIT, !lit).unwrap().get_index() as usize, (i + 3) % 6);
+            assert_eq!(state.with_raw("age", "1").unwrap().get_index() as usize, i - i % 3 + 1);
+            assert_eq!(state.with_raw("lit", &lit.to_string()).unwrap(), state);
+
+        }
+
+        assert!(CROP.get_default_state().with(&PROP_AGE, 3).is_none());
+        assert!(CROP.get_default_state().with_raw("age", "3").is_none());
+        assert!(CROP.get_default_state().with_raw("facing", "north").is_none());
+        assert!(CROP.get_default_state().get(&IntProperty("lit", 2)).is_none());
+
+    }
+
+}
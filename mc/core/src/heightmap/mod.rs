@@ -9,6 +9,7 @@ use std::collections::HashMap;
 /// The heightmap's predicate must not return true upon "null block", this restriction is needed
 /// at the current state of the crate because the "null block" is currently used to optimize
 /// storage.
+#[derive(Debug)]
 pub struct HeightmapType {
     pub name: &'static str,
     pub predicate: fn(&'static BlockState, &GlobalBlocks) -> bool
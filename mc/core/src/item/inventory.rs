@@ -0,0 +1,143 @@
+use super::{Item, ItemStack};
+
+
+/// A fixed size container of item stacks, it can be used as an entity component for entities
+/// that can collect items, like players.
+#[derive(Debug, Clone)]
+pub struct Inventory {
+    slots: Vec<Option<ItemStack>>
+}
+
+impl Inventory {
+
+    pub fn new(size: usize) -> Self {
+        Self {
+            slots: vec![None; size]
+        }
+    }
+
+    #[inline]
+    pub fn get_size(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn get_stack(&self, index: usize) -> Option<&ItemStack> {
+        self.slots.get(index)?.as_ref()
+    }
+
+    /// Set the stack of a slot and return the previous one, the index must be valid.
+    pub fn set_stack(&mut self, index: usize, stack: Option<ItemStack>) -> Option<ItemStack> {
+        std::mem::replace(&mut self.slots[index], stack)
+    }
+
+    /// Take the stack of a slot, leaving it empty, the index must be valid.
+    pub fn take_stack(&mut self, index: usize) -> Option<ItemStack> {
+        self.slots[index].take()
+    }
+
+    /// Insert a stack in this inventory, it's first merged into existing stacks of the same
+    /// item and then split over empty slots, without exceeding the stack size of the item.
+    /// The remaining stack is returned if it doesn't fit.
+    pub fn insert(&mut self, stack: ItemStack) -> Option<ItemStack> {
+
+        let mut remaining = stack;
+
+        for slot_stack in self.slots.iter_mut().flatten() {
+            // Returning if the stack has been fully merged.
+            remaining = slot_stack.merge(remaining)?;
+        }
+
+        let stack_size = remaining.get_item().get_stack_size();
+
+        for slot in self.slots.iter_mut().filter(|slot| slot.is_none()) {
+            if remaining.get_count() <= stack_size {
+                *slot = Some(remaining);
+                return None;
+            }
+            let mut slot_stack = remaining.clone();
+            slot_stack.set_count(stack_size);
+            remaining.set_count(remaining.get_count() - stack_size);
+            *slot = Some(slot_stack);
+        }
+
+        Some(remaining)
+
+    }
+
+    /// Return true if at least one item of the given stack can be inserted.
+    pub fn can_insert(&self, stack: &ItemStack) -> bool {
+        self.slots.iter().any(|slot| match slot {
+            Some(slot_stack) => !slot_stack.is_full() && slot_stack.is_stackable_with(stack),
+            None => true
+        })
+    }
+
+    /// Iterate over all non empty slots with their index.
+    pub fn iter_stacks(&self) -> impl Iterator<Item = (usize, &ItemStack)> + '_ {
+        self.slots.iter()
+            .enumerate()
+            .filter_map(|(index, slot)| slot.as_ref().map(|stack| (index, stack)))
+    }
+
+    /// Return the total number of the given item in this inventory.
+    pub fn count_item(&self, item: &'static Item) -> usize {
+        self.iter_stacks()
+            .filter(|(_, stack)| std::ptr::eq(stack.get_item(), item))
+            .map(|(_, stack)| stack.get_count() as usize)
+            .sum()
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    crate::items!(TEST_ITEMS "test" [
+        STONE "stone",
+        PEARL "pearl" 16
+    ]);
+
+    #[test]
+    fn inventory_insert() {
+
+        assert_eq!(TEST_ITEMS.map(Item::get_stack_size), [64, 16]);
+
+        let mut inv = Inventory::new(2);
+        assert!(inv.insert(ItemStack::with_item_count(&PEARL, 10)).is_none());
+        assert!(inv.insert(ItemStack::with_item_count(&PEARL, 10)).is_none());
+        assert_eq!(inv.get_stack(0).unwrap().get_count(), 16);
+        assert_eq!(inv.get_stack(1).unwrap().get_count(), 4);
+
+        // Stacks with different tags are not merged.
+        let mut tagged = ItemStack::with_item_count(&PEARL, 2);
+        tagged.get_tag_mut().insert_i32("Damage", 1);
+        assert!(!inv.can_insert(&tagged));
+        assert_eq!(inv.insert(tagged).unwrap().get_count(), 2);
+
+        let remaining = inv.insert(ItemStack::with_item_count(&PEARL, 20)).unwrap();
+        assert_eq!(remaining.get_count(), 8);
+        assert_eq!(inv.count_item(&PEARL), 32);
+        assert_eq!(inv.count_item(&STONE), 0);
+
+        assert_eq!(inv.take_stack(0).unwrap().get_count(), 16);
+        assert!(inv.insert(ItemStack::with_item(&STONE)).is_none());
+        assert!(std::ptr::eq(inv.get_stack(0).unwrap().get_item(), &STONE));
+
+        // Oversized stacks are split over empty slots.
+        let mut inv = Inventory::new(2);
+        assert_eq!(inv.insert(ItemStack::with_item_count(&PEARL, 40)).unwrap().get_count(), 8);
+        assert_eq!(inv.get_stack(0).unwrap().get_count(), 16);
+        assert_eq!(inv.get_stack(1).unwrap().get_count(), 16);
+
+        let mut inv = Inventory::new(3);
+        assert!(inv.insert(ItemStack::with_item_count(&STONE, 100)).is_none());
+        assert_eq!(inv.get_stack(0).unwrap().get_count(), 64);
+        assert_eq!(inv.get_stack(1).unwrap().get_count(), 36);
+        assert!(inv.get_stack(2).is_none());
+
+    }
+
+}
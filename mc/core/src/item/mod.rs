@@ -1,19 +1,40 @@
 
+use crate::block::Block;
+
 mod stack;
 pub use stack::*;
 
+mod inventory;
+pub use inventory::*;
+
 pub mod behaviour;
 
 
+#[derive(Debug)]
 pub struct Item {
     name: &'static str,
-    stack_size: u16
+    stack_size: u16,
+    max_damage: u16,
+    block: Option<&'static Block>
 }
 
 impl Item {
 
     pub const fn new(name: &'static str, stack_size: u16) -> Self {
-        Self { name, stack_size }
+        Self { name, stack_size, max_damage: 0, block: None }
+    }
+
+    /// Make this item damageable with the given durability, damageable items should not be
+    /// stackable.
+    pub const fn with_max_damage(mut self, max_damage: u16) -> Self {
+        self.max_damage = max_damage;
+        self
+    }
+
+    /// Link this item to the block it places.
+    pub const fn with_block(mut self, block: &'static Block) -> Self {
+        self.block = Some(block);
+        self
     }
 
     #[inline]
@@ -26,20 +47,38 @@ impl Item {
         self.stack_size
     }
 
+    /// Get the durability of this item, zero if the item is not damageable.
+    #[inline]
+    pub fn get_max_damage(&self) -> u16 {
+        self.max_damage
+    }
+
+    #[inline]
+    pub fn is_damageable(&self) -> bool {
+        self.max_damage != 0
+    }
+
+    /// Get the block placed by this item, if any. Note that the block's name may differ
+    /// from the item's name, like `minecraft:redstone` placing `minecraft:redstone_wire`.
+    #[inline]
+    pub fn get_block(&self) -> Option<&'static Block> {
+        self.block
+    }
+
 }
 
 
 #[macro_export]
 macro_rules! items {
     ($global_vis:vis $static_id:ident $namespace:literal [
-        $($item_id:ident $item_name:literal $($stack_size:literal)?),*
+        $($item_id:ident $item_name:literal $($stack_size:literal)? $(damage $max_damage:literal)? $(block $block:path)?),*
         $(,)?
     ]) => {
 
         $($global_vis static $item_id: $crate::item::Item = $crate::item::Item::new(
             concat!($namespace, ':', $item_name),
             $crate::inner_items_stack_size!($($stack_size)?)
-        );)*
+        )$(.with_max_damage($max_damage))?$(.with_block(&$block))?;)*
 
         $global_vis static $static_id: [&'static $crate::item::Item; $crate::count!($($item_id)*)] = [
             $(&$item_id),*
@@ -1,7 +1,10 @@
 use super::Item;
 use nbt::CompoundTag;
 
+use crate::nbt::compound_tag_eq;
 
+
+#[derive(Debug, Clone)]
 pub struct ItemStack {
     item: &'static Item,
     count: u16,
@@ -38,13 +41,50 @@ impl ItemStack {
         self.count
     }
 
+    #[inline]
+    pub fn set_count(&mut self, count: u16) {
+        debug_assert_ne!(count, 0, "Item stack can't have a count of zero.");
+        self.count = count;
+    }
+
     #[inline]
     pub fn get_tag(&self) -> Option<&CompoundTag> {
         self.tag.as_ref()
     }
 
     pub fn get_tag_mut(&mut self) -> &mut CompoundTag {
-        self.tag.get_or_insert_with(|| CompoundTag::new())
+        self.tag.get_or_insert_with(CompoundTag::new)
+    }
+
+    /// Return true if this stack has reached the stack size of its item.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.count >= self.item.get_stack_size()
+    }
+
+    /// Return true if both stacks have the same item and the same tag, and so can be merged.
+    pub fn is_stackable_with(&self, other: &ItemStack) -> bool {
+        std::ptr::eq(self.item, other.item) && match (&self.tag, &other.tag) {
+            (None, None) => true,
+            (Some(a), Some(b)) => compound_tag_eq(a, b),
+            _ => false
+        }
+    }
+
+    /// Move as many items as possible from the other stack to this one, up to the stack size
+    /// of the item. The remaining stack is returned if some items could not be moved.
+    pub fn merge(&mut self, mut other: ItemStack) -> Option<ItemStack> {
+        if !self.is_stackable_with(&other) {
+            return Some(other);
+        }
+        let moved = other.count.min(self.item.get_stack_size().saturating_sub(self.count));
+        self.count += moved;
+        other.count -= moved;
+        if other.count == 0 {
+            None
+        } else {
+            Some(other)
+        }
     }
 
 }
@@ -27,15 +27,19 @@ pub mod biome;
 pub mod block;
 pub mod heightmap;
 pub mod entity;
-// pub mod item;
+pub mod item;
+pub mod sound;
+pub mod particle;
 
 pub mod world;
 
 pub mod rand;
 pub mod util;
+pub mod nbt;
 pub mod perf;
 pub mod tag;
 
 pub mod math;
+pub mod noise;
 pub mod pos;
 
@@ -0,0 +1,417 @@
+use std::io::{self, Write};
+use std::convert::TryInto;
+use std::borrow::Cow;
+
+use ::nbt::encode::write_inner_compound_tag;
+use ::nbt::{CompoundTag, Tag};
+use thiserror::Error;
+
+use super::MAX_DEPTH;
+
+
+const TAG_END: u8 = 0;
+const TAG_BYTE: u8 = 1;
+const TAG_SHORT: u8 = 2;
+const TAG_INT: u8 = 3;
+const TAG_LONG: u8 = 4;
+const TAG_FLOAT: u8 = 5;
+const TAG_DOUBLE: u8 = 6;
+const TAG_BYTE_ARRAY: u8 = 7;
+const TAG_STRING: u8 = 8;
+const TAG_LIST: u8 = 9;
+const TAG_COMPOUND: u8 = 10;
+const TAG_INT_ARRAY: u8 = 11;
+const TAG_LONG_ARRAY: u8 = 12;
+
+
+#[derive(Error, Debug)]
+pub enum NbtError {
+    #[error("Unexpected end of the NBT data.")]
+    UnexpectedEnd,
+    #[error("Unknown tag type: {0}")]
+    UnknownTagType(u8),
+    #[error("The root tag must be a compound, but its type is {0}.")]
+    RootMustBeCompound(u8),
+    #[error("Invalid negative length: {0}")]
+    InvalidLength(i32),
+    #[error("Tags are nested deeper than {}.", MAX_DEPTH)]
+    TooDeep
+}
+
+
+/// Return the type identifier of a tag.
+pub fn get_tag_type(tag: &Tag) -> u8 {
+    match tag {
+        Tag::Byte(_) => TAG_BYTE,
+        Tag::Short(_) => TAG_SHORT,
+        Tag::Int(_) => TAG_INT,
+        Tag::Long(_) => TAG_LONG,
+        Tag::Float(_) => TAG_FLOAT,
+        Tag::Double(_) => TAG_DOUBLE,
+        Tag::ByteArray(_) => TAG_BYTE_ARRAY,
+        Tag::String(_) => TAG_STRING,
+        Tag::List(_) => TAG_LIST,
+        Tag::Compound(_) => TAG_COMPOUND,
+        Tag::IntArray(_) => TAG_INT_ARRAY,
+        Tag::LongArray(_) => TAG_LONG_ARRAY
+    }
+}
+
+
+/// A NBT reader working directly on a byte slice, without intermediate buffers. Lengths
+/// are checked against the remaining data before allocating, so this reader is suitable
+/// for untrusted data received from the network. Strings are borrowed from the slice when
+/// only their value is needed (see `peek_root_name`), but decoded tags own their values.
+pub struct NbtReader<'a> {
+    data: &'a [u8],
+    pos: usize
+}
+
+impl<'a> NbtReader<'a> {
+
+    pub fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0
+        }
+    }
+
+    /// Return the number of bytes already read.
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Return the data that has not been read yet.
+    #[inline]
+    pub fn remaining(&self) -> &'a [u8] {
+        &self.data[self.pos..]
+    }
+
+    /// Read a root compound tag with its name, like in files.
+    pub fn read_compound_tag(&mut self) -> Result<CompoundTag, NbtError> {
+        let tag_type = self.read_u8()?;
+        if tag_type != TAG_COMPOUND {
+            return Err(NbtError::RootMustBeCompound(tag_type));
+        }
+        let name = self.read_str()?.into_owned();
+        let mut tag = self.read_compound(0)?;
+        tag.name = Some(name);
+        Ok(tag)
+    }
+
+    /// Read a root tag of the network variant, the root tag has a type but no name. `None`
+    /// is returned if the root is an end tag, used by the protocol for absent data.
+    pub fn read_network_tag(&mut self) -> Result<Option<Tag>, NbtError> {
+        match self.read_u8()? {
+            TAG_END => Ok(None),
+            tag_type => self.read_payload(tag_type, 0).map(Some)
+        }
+    }
+
+    /// Read a root compound tag of the network variant, see `read_network_tag`.
+    pub fn read_network_compound_tag(&mut self) -> Result<Option<CompoundTag>, NbtError> {
+        match self.read_u8()? {
+            TAG_END => Ok(None),
+            TAG_COMPOUND => self.read_compound(0).map(Some),
+            tag_type => Err(NbtError::RootMustBeCompound(tag_type))
+        }
+    }
+
+    /// Return the name of the root compound tag without reading it, the name is borrowed
+    /// from the data if it's valid UTF-8.
+    pub fn peek_root_name(&self) -> Result<Cow<'a, str>, NbtError> {
+        let mut reader = Self { data: self.data, pos: self.pos };
+        match reader.read_u8()? {
+            TAG_COMPOUND => reader.read_str(),
+            tag_type => Err(NbtError::RootMustBeCompound(tag_type))
+        }
+    }
+
+    /// Skip a root compound tag with its name without decoding it.
+    pub fn skip_compound_tag(&mut self) -> Result<(), NbtError> {
+        let tag_type = self.read_u8()?;
+        if tag_type != TAG_COMPOUND {
+            return Err(NbtError::RootMustBeCompound(tag_type));
+        }
+        self.read_str()?;
+        self.skip_payload(TAG_COMPOUND, 0)
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], NbtError> {
+        let end = self.pos.checked_add(len).ok_or(NbtError::UnexpectedEnd)?;
+        let bytes = self.data.get(self.pos..end).ok_or(NbtError::UnexpectedEnd)?;
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    #[inline]
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], NbtError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    #[inline]
+    fn read_u8(&mut self) -> Result<u8, NbtError> {
+        Ok(self.take_array::<1>()?[0])
+    }
+
+    fn read_len(&mut self) -> Result<usize, NbtError> {
+        let len = i32::from_be_bytes(self.take_array()?);
+        if len < 0 {
+            Err(NbtError::InvalidLength(len))
+        } else {
+            Ok(len as usize)
+        }
+    }
+
+    /// Take the bytes of an array of the given length and element size.
+    fn take_elements(&mut self, size: usize) -> Result<&'a [u8], NbtError> {
+        let len = self.read_len()?;
+        self.take(len.checked_mul(size).ok_or(NbtError::UnexpectedEnd)?)
+    }
+
+    fn read_str(&mut self) -> Result<Cow<'a, str>, NbtError> {
+        let len = u16::from_be_bytes(self.take_array()?) as usize;
+        Ok(String::from_utf8_lossy(self.take(len)?))
+    }
+
+    fn read_compound(&mut self, depth: usize) -> Result<CompoundTag, NbtError> {
+        if depth >= MAX_DEPTH {
+            return Err(NbtError::TooDeep);
+        }
+        let mut tag = CompoundTag::new();
+        loop {
+            let tag_type = self.read_u8()?;
+            if tag_type == TAG_END {
+                break Ok(tag);
+            }
+            let name = self.read_str()?.into_owned();
+            tag.insert(name, self.read_payload(tag_type, depth + 1)?);
+        }
+    }
+
+    fn read_payload(&mut self, tag_type: u8, depth: usize) -> Result<Tag, NbtError> {
+        Ok(match tag_type {
+            TAG_BYTE => Tag::Byte(self.read_u8()? as i8),
+            TAG_SHORT => Tag::Short(i16::from_be_bytes(self.take_array()?)),
+            TAG_INT => Tag::Int(i32::from_be_bytes(self.take_array()?)),
+            TAG_LONG => Tag::Long(i64::from_be_bytes(self.take_array()?)),
+            TAG_FLOAT => Tag::Float(f32::from_be_bytes(self.take_array()?)),
+            TAG_DOUBLE => Tag::Double(f64::from_be_bytes(self.take_array()?)),
+            TAG_BYTE_ARRAY => Tag::ByteArray(self.take_elements(1)?.iter().map(|&b| b as i8).collect()),
+            TAG_STRING => Tag::String(self.read_str()?.into_owned()),
+            TAG_LIST => {
+                if depth >= MAX_DEPTH {
+                    return Err(NbtError::TooDeep);
+                }
+                let elements_type = self.read_u8()?;
+                let len = self.read_len()?;
+                if elements_type == TAG_END && len != 0 {
+                    return Err(NbtError::UnknownTagType(TAG_END));
+                }
+                // Each element takes at least one byte, so the capacity is bounded by the
+                // remaining length of the data.
+                let mut elements = Vec::with_capacity(len.min(self.data.len() - self.pos));
+                for _ in 0..len {
+                    elements.push(self.read_payload(elements_type, depth + 1)?);
+                }
+                Tag::List(elements)
+            }
+            TAG_COMPOUND => Tag::Compound(self.read_compound(depth)?),
+            TAG_INT_ARRAY => Tag::IntArray(self.take_elements(4)?
+                .chunks_exact(4)
+                .map(|b| i32::from_be_bytes(b.try_into().unwrap()))
+                .collect()),
+            TAG_LONG_ARRAY => Tag::LongArray(self.take_elements(8)?
+                .chunks_exact(8)
+                .map(|b| i64::from_be_bytes(b.try_into().unwrap()))
+                .collect()),
+            _ => return Err(NbtError::UnknownTagType(tag_type))
+        })
+    }
+
+    fn skip_payload(&mut self, tag_type: u8, depth: usize) -> Result<(), NbtError> {
+        if depth >= MAX_DEPTH {
+            return Err(NbtError::TooDeep);
+        }
+        match tag_type {
+            TAG_BYTE => { self.take(1)?; }
+            TAG_SHORT => { self.take(2)?; }
+            TAG_INT | TAG_FLOAT => { self.take(4)?; }
+            TAG_LONG | TAG_DOUBLE => { self.take(8)?; }
+            TAG_BYTE_ARRAY => { self.take_elements(1)?; }
+            TAG_STRING => { self.read_str()?; }
+            TAG_LIST => {
+                let elements_type = self.read_u8()?;
+                for _ in 0..self.read_len()? {
+                    self.skip_payload(elements_type, depth + 1)?;
+                }
+            }
+            TAG_COMPOUND => loop {
+                let tag_type = self.read_u8()?;
+                if tag_type == TAG_END {
+                    break;
+                }
+                self.read_str()?;
+                self.skip_payload(tag_type, depth + 1)?;
+            }
+            TAG_INT_ARRAY => { self.take_elements(4)?; }
+            TAG_LONG_ARRAY => { self.take_elements(8)?; }
+            _ => return Err(NbtError::UnknownTagType(tag_type))
+        }
+        Ok(())
+    }
+
+}
+
+
+/// Write a root tag of the network variant, the type of the root is written but not its
+/// name, see `NbtReader::read_network_tag`.
+pub fn write_network_tag<W: Write>(writer: &mut W, tag: &Tag) -> io::Result<()> {
+    writer.write_all(&[get_tag_type(tag)])?;
+    write_payload(writer, tag)
+}
+
+/// Write a root compound tag of the network variant, its name is ignored.
+pub fn write_network_compound_tag<W: Write>(writer: &mut W, tag: &CompoundTag) -> io::Result<()> {
+    writer.write_all(&[TAG_COMPOUND])?;
+    write_inner_compound_tag(writer, tag)
+}
+
+/// Write the absent root tag of the network variant, a single end tag.
+pub fn write_network_empty_tag<W: Write>(writer: &mut W) -> io::Result<()> {
+    writer.write_all(&[TAG_END])
+}
+
+fn write_payload<W: Write>(writer: &mut W, tag: &Tag) -> io::Result<()> {
+    match tag {
+        Tag::Byte(value) => writer.write_all(&value.to_be_bytes()),
+        Tag::Short(value) => writer.write_all(&value.to_be_bytes()),
+        Tag::Int(value) => writer.write_all(&value.to_be_bytes()),
+        Tag::Long(value) => writer.write_all(&value.to_be_bytes()),
+        Tag::Float(value) => writer.write_all(&value.to_be_bytes()),
+        Tag::Double(value) => writer.write_all(&value.to_be_bytes()),
+        Tag::ByteArray(values) => {
+            writer.write_all(&(values.len() as i32).to_be_bytes())?;
+            values.iter().try_for_each(|value| writer.write_all(&value.to_be_bytes()))
+        }
+        Tag::String(value) => {
+            // Java readers decode the length as modified UTF-8, which is never shorter.
+            if modified_utf8_len(value) > u16::MAX as usize {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "string is too long for a string tag"));
+            }
+            writer.write_all(&(value.len() as u16).to_be_bytes())?;
+            writer.write_all(value.as_bytes())
+        }
+        Tag::List(tags) => {
+            let tag_type = tags.first().map_or(TAG_END, get_tag_type);
+            if tags.iter().any(|tag| get_tag_type(tag) != tag_type) {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "list tag elements must all have the same type"));
+            }
+            writer.write_all(&[tag_type])?;
+            writer.write_all(&(tags.len() as i32).to_be_bytes())?;
+            tags.iter().try_for_each(|tag| write_payload(writer, tag))
+        }
+        Tag::Compound(tag) => write_inner_compound_tag(writer, tag),
+        Tag::IntArray(values) => {
+            writer.write_all(&(values.len() as i32).to_be_bytes())?;
+            values.iter().try_for_each(|value| writer.write_all(&value.to_be_bytes()))
+        }
+        Tag::LongArray(values) => {
+            writer.write_all(&(values.len() as i32).to_be_bytes())?;
+            values.iter().try_for_each(|value| writer.write_all(&value.to_be_bytes()))
+        }
+    }
+}
+
+
+/// Return the length of the given string once encoded in Java's modified UTF-8, where
+/// the null character takes 2 bytes and supplementary characters take 6 bytes.
+fn modified_utf8_len(s: &str) -> usize {
+    s.chars().map(|c| match c as u32 {
+        0 => 2,
+        0x01..=0x7F => 1,
+        0x80..=0x7FF => 2,
+        0x800..=0xFFFF => 3,
+        _ => 6
+    }).sum()
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use ::nbt::encode::write_compound_tag;
+
+    use super::*;
+
+    #[test]
+    fn binary_read_write() {
+
+        let mut tag_inner = CompoundTag::new();
+        tag_inner.insert_str("name", "Bananrama");
+        tag_inner.insert_i64_vec("longs", vec![i64::MIN, 0, i64::MAX]);
+
+        let mut tag_root = CompoundTag::named("hello world");
+        tag_root.insert_i8("byte", -5);
+        tag_root.insert_f64("double", 0.5);
+        tag_root.insert_i8_vec("bytes", vec![1, -2, 3]);
+        tag_root.insert_i32_vec("ints", vec![-1, 1 << 20]);
+        tag_root.insert_compound_tag_vec("list", vec![tag_inner.clone(), tag_inner]);
+        tag_root.insert("empty", Tag::List(Vec::new()));
+
+        let mut data = Vec::new();
+        write_compound_tag(&mut data, &tag_root).unwrap();
+
+        let mut reader = NbtReader::new(&data);
+        assert_eq!(reader.peek_root_name().unwrap(), "hello world");
+        assert!(matches!(reader.peek_root_name().unwrap(), Cow::Borrowed(_)));
+        let tag_read = reader.read_compound_tag().unwrap();
+        assert_eq!(reader.position(), data.len());
+        assert_eq!(tag_read.name.as_deref(), Some("hello world"));
+        assert_eq!(tag_read.get_f64("double").unwrap(), 0.5);
+        assert_eq!(tag_read.get_i32_vec("ints").unwrap(), &vec![-1, 1 << 20]);
+        assert_eq!(tag_read.get_compound_tag_vec("list").unwrap()[1].get_i64_vec("longs").unwrap()[2], i64::MAX);
+
+        let mut reader = NbtReader::new(&data);
+        reader.skip_compound_tag().unwrap();
+        assert!(reader.remaining().is_empty());
+
+        // The network variant is the same without the root name.
+        let mut network_data = Vec::new();
+        write_network_compound_tag(&mut network_data, &tag_root).unwrap();
+        assert_eq!(network_data[0], TAG_COMPOUND);
+        assert_eq!(&network_data[1..], &data[(3 + "hello world".len())..]);
+
+        let mut reader = NbtReader::new(&network_data);
+        let mut reencoded_data = Vec::new();
+        write_network_compound_tag(&mut reencoded_data, &reader.read_network_compound_tag().unwrap().unwrap()).unwrap();
+        assert_eq!(reencoded_data, network_data);
+
+        let mut network_data = Vec::new();
+        write_network_empty_tag(&mut network_data).unwrap();
+        write_network_tag(&mut network_data, &Tag::String("foo".to_string())).unwrap();
+        let mut reader = NbtReader::new(&network_data);
+        assert!(reader.read_network_tag().unwrap().is_none());
+        assert!(matches!(reader.read_network_tag().unwrap(), Some(Tag::String(s)) if s == "foo"));
+
+        // Invalid data must not panic or allocate unchecked lengths.
+        assert!(matches!(NbtReader::new(&data[..data.len() - 1]).read_compound_tag(), Err(NbtError::UnexpectedEnd)));
+        assert!(matches!(NbtReader::new(&[TAG_INT]).read_compound_tag(), Err(NbtError::RootMustBeCompound(TAG_INT))));
+        assert!(matches!(NbtReader::new(&[TAG_INT_ARRAY, 0x7F, 0xFF, 0xFF, 0xFF]).read_network_tag(), Err(NbtError::UnexpectedEnd)));
+        assert!(matches!(NbtReader::new(&[TAG_LIST, 13, 0, 0, 0, 1]).read_network_tag(), Err(NbtError::UnknownTagType(13))));
+        let mut deep_data = vec![TAG_LIST];
+        deep_data.extend([TAG_LIST, 0, 0, 0, 1].repeat(MAX_DEPTH + 1));
+        assert!(matches!(NbtReader::new(&deep_data).read_network_tag(), Err(NbtError::TooDeep)));
+
+        // Tags that can't be represented must be rejected instead of written truncated.
+        let invalid_input = |tag: &Tag| write_network_tag(&mut Vec::new(), tag).unwrap_err().kind() == io::ErrorKind::InvalidInput;
+        assert!(write_network_tag(&mut Vec::new(), &Tag::String("a".repeat(65535))).is_ok());
+        assert!(invalid_input(&Tag::String("a".repeat(65536))));
+        assert!(invalid_input(&Tag::String("\0".repeat(40000))));
+        assert!(invalid_input(&Tag::String("\u{1F600}".repeat(11000))));
+        assert!(invalid_input(&Tag::List(vec![Tag::Int(1), Tag::Byte(2)])));
+
+    }
+
+}
@@ -0,0 +1,50 @@
+//! First-party NBT utilities, built on top of the tag structures of the `named-binary-tag`
+//! crate that are re-exported here. This module adds a binary reader working directly on
+//! byte slices, the network variant of the format where the root compound is unnamed (used
+//! by the protocol since 1.20.2) and SNBT (stringified NBT) parsing and printing, as used
+//! by commands and convenient for test fixtures.
+
+mod binary;
+mod snbt;
+
+pub use ::nbt::{CompoundTag, CompoundTagError, Tag};
+pub use ::nbt::decode::{read_compound_tag, read_gzip_compound_tag, read_zlib_compound_tag, TagDecodeError};
+pub use ::nbt::encode::{write_compound_tag, write_gzip_compound_tag, write_zlib_compound_tag};
+
+pub use binary::*;
+pub use snbt::*;
+
+
+/// Maximum depth of nested compounds and lists, like vanilla.
+pub const MAX_DEPTH: usize = 512;
+
+
+/// Structural equality of two tags, the tags of the `named-binary-tag` crate don't implement
+/// `PartialEq`. Compounds are equal if they have the same entries, in any order.
+pub fn tag_eq(a: &Tag, b: &Tag) -> bool {
+    match (a, b) {
+        (Tag::Byte(a), Tag::Byte(b)) => a == b,
+        (Tag::Short(a), Tag::Short(b)) => a == b,
+        (Tag::Int(a), Tag::Int(b)) => a == b,
+        (Tag::Long(a), Tag::Long(b)) => a == b,
+        (Tag::Float(a), Tag::Float(b)) => a == b,
+        (Tag::Double(a), Tag::Double(b)) => a == b,
+        (Tag::ByteArray(a), Tag::ByteArray(b)) => a == b,
+        (Tag::String(a), Tag::String(b)) => a == b,
+        (Tag::List(a), Tag::List(b)) => a.len() == b.len() && a.iter().zip(b).all(|(a, b)| tag_eq(a, b)),
+        (Tag::Compound(a), Tag::Compound(b)) => compound_tag_eq(a, b),
+        (Tag::IntArray(a), Tag::IntArray(b)) => a == b,
+        (Tag::LongArray(a), Tag::LongArray(b)) => a == b,
+        _ => false
+    }
+}
+
+/// Structural equality of two compound tags, see `tag_eq`.
+pub fn compound_tag_eq(a: &CompoundTag, b: &CompoundTag) -> bool {
+    let mut a_entries: Vec<(&String, &Tag)> = a.iter().collect();
+    let mut b_entries: Vec<(&String, &Tag)> = b.iter().collect();
+    a_entries.sort_by_key(|&(name, _)| name);
+    b_entries.sort_by_key(|&(name, _)| name);
+    a_entries.len() == b_entries.len() && a_entries.iter().zip(&b_entries)
+        .all(|((a_name, a_tag), (b_name, b_tag))| a_name == b_name && tag_eq(a_tag, b_tag))
+}
@@ -0,0 +1,470 @@
+use std::fmt::{self, Write};
+
+use ::nbt::{CompoundTag, Tag};
+use thiserror::Error;
+
+use super::binary::get_tag_type;
+use super::MAX_DEPTH;
+
+
+#[derive(Error, Debug)]
+#[error("{message} at position {position}.")]
+pub struct SnbtError {
+    /// Byte position of the error in the parsed string.
+    pub position: usize,
+    pub message: String
+}
+
+
+/// Parse a tag from its SNBT representation, like vanilla commands. Unquoted values are
+/// typed from their suffix (`1b`, `1s`, `1L`, `1.0f`, `1.0d`), numbers without suffix
+/// are integers or doubles and other unquoted values are strings.
+pub fn parse_snbt(src: &str) -> Result<Tag, SnbtError> {
+    let mut parser = SnbtParser { src, pos: 0 };
+    let tag = parser.parse_value(0)?;
+    parser.skip_whitespaces();
+    if parser.pos != src.len() {
+        return Err(parser.error("Trailing data"));
+    }
+    Ok(tag)
+}
+
+/// Parse a compound tag from its SNBT representation, see `parse_snbt`.
+pub fn parse_snbt_compound(src: &str) -> Result<CompoundTag, SnbtError> {
+    match parse_snbt(src)? {
+        Tag::Compound(tag) => Ok(tag),
+        _ => Err(SnbtError { position: 0, message: "Expected a compound".to_string() })
+    }
+}
+
+/// Print a tag in its compact SNBT representation, that can be parsed back by `parse_snbt`
+/// (except for non-finite floats).
+pub fn to_snbt(tag: &Tag) -> String {
+    let mut out = String::new();
+    write_snbt(&mut out, tag).unwrap();
+    out
+}
+
+/// Print a compound tag in its compact SNBT representation, see `to_snbt`.
+pub fn compound_to_snbt(tag: &CompoundTag) -> String {
+    let mut out = String::new();
+    write_compound_snbt(&mut out, tag).unwrap();
+    out
+}
+
+/// Write a tag in its compact SNBT representation to the given writer.
+pub fn write_snbt<W: Write>(out: &mut W, tag: &Tag) -> fmt::Result {
+    match tag {
+        Tag::Byte(value) => write!(out, "{}b", value),
+        Tag::Short(value) => write!(out, "{}s", value),
+        Tag::Int(value) => write!(out, "{}", value),
+        Tag::Long(value) => write!(out, "{}L", value),
+        Tag::Float(value) => write!(out, "{:?}f", value),
+        Tag::Double(value) => write!(out, "{:?}d", value),
+        Tag::ByteArray(values) => write_array(out, "B", "B", values),
+        Tag::String(value) => write_quoted(out, value),
+        Tag::List(tags) => {
+            out.write_char('[')?;
+            for (i, tag) in tags.iter().enumerate() {
+                if i != 0 {
+                    out.write_char(',')?;
+                }
+                write_snbt(out, tag)?;
+            }
+            out.write_char(']')
+        }
+        Tag::Compound(tag) => write_compound_snbt(out, tag),
+        Tag::IntArray(values) => write_array(out, "I", "", values),
+        Tag::LongArray(values) => write_array(out, "L", "L", values)
+    }
+}
+
+/// Write a compound tag in its compact SNBT representation to the given writer.
+pub fn write_compound_snbt<W: Write>(out: &mut W, tag: &CompoundTag) -> fmt::Result {
+    out.write_char('{')?;
+    for (i, (key, tag)) in tag.iter().enumerate() {
+        if i != 0 {
+            out.write_char(',')?;
+        }
+        if !key.is_empty() && key.chars().all(is_unquoted_char) {
+            out.write_str(key)?;
+        } else {
+            write_quoted(out, key)?;
+        }
+        out.write_char(':')?;
+        write_snbt(out, tag)?;
+    }
+    out.write_char('}')
+}
+
+fn write_array<W: Write, T: fmt::Display>(out: &mut W, prefix: &str, suffix: &str, values: &[T]) -> fmt::Result {
+    write!(out, "[{};", prefix)?;
+    for (i, value) in values.iter().enumerate() {
+        if i != 0 {
+            out.write_char(',')?;
+        }
+        write!(out, "{}{}", value, suffix)?;
+    }
+    out.write_char(']')
+}
+
+/// Write a quoted string, double quotes are used unless the string contains double quotes
+/// but no single quote, like vanilla.
+fn write_quoted<W: Write>(out: &mut W, value: &str) -> fmt::Result {
+    let quote = if value.contains('"') && !value.contains('\'') { '\'' } else { '"' };
+    out.write_char(quote)?;
+    for c in value.chars() {
+        if c == quote || c == '\\' {
+            out.write_char('\\')?;
+        }
+        out.write_char(c)?;
+    }
+    out.write_char(quote)
+}
+
+#[inline]
+fn is_unquoted_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '+')
+}
+
+
+/// Internal recursive descent parser for SNBT.
+struct SnbtParser<'a> {
+    src: &'a str,
+    pos: usize
+}
+
+impl<'a> SnbtParser<'a> {
+
+    fn error(&self, message: &str) -> SnbtError {
+        SnbtError {
+            position: self.pos,
+            message: message.to_string()
+        }
+    }
+
+    #[inline]
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn skip_whitespaces(&mut self) {
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() {
+                self.pos += c.len_utf8();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Skip whitespaces and consume the given character if it's the next one.
+    fn accept(&mut self, expected: char) -> bool {
+        self.skip_whitespaces();
+        if self.peek() == Some(expected) {
+            self.pos += expected.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), SnbtError> {
+        if self.accept(expected) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected '{}'", expected)))
+        }
+    }
+
+    fn parse_value(&mut self, depth: usize) -> Result<Tag, SnbtError> {
+        self.skip_whitespaces();
+        match self.peek() {
+            Some('{') => self.parse_compound(depth).map(Tag::Compound),
+            Some('[') => self.parse_list_or_array(depth),
+            Some('"' | '\'') => self.parse_quoted().map(Tag::String),
+            Some(_) => {
+                let start = self.pos;
+                let value = self.parse_unquoted();
+                if value.is_empty() {
+                    self.pos = start;
+                    Err(self.error("Expected a value"))
+                } else {
+                    Ok(type_unquoted(value))
+                }
+            }
+            None => Err(self.error("Expected a value"))
+        }
+    }
+
+    fn parse_key(&mut self) -> Result<String, SnbtError> {
+        self.skip_whitespaces();
+        match self.peek() {
+            Some('"' | '\'') => self.parse_quoted(),
+            _ => {
+                let key = self.parse_unquoted();
+                if key.is_empty() {
+                    Err(self.error("Expected a key"))
+                } else {
+                    Ok(key.to_string())
+                }
+            }
+        }
+    }
+
+    fn parse_unquoted(&mut self) -> &'a str {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if is_unquoted_char(c) {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        &self.src[start..self.pos]
+    }
+
+    fn parse_quoted(&mut self) -> Result<String, SnbtError> {
+        let quote = self.peek().unwrap();
+        self.pos += 1;
+        let mut value = String::new();
+        let mut escaped = false;
+        loop {
+            let c = self.peek().ok_or_else(|| self.error("Unterminated string"))?;
+            self.pos += c.len_utf8();
+            if escaped {
+                if c != quote && c != '\\' {
+                    self.pos -= c.len_utf8();
+                    return Err(self.error("Invalid escape sequence"));
+                }
+                value.push(c);
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == quote {
+                break Ok(value);
+            } else {
+                value.push(c);
+            }
+        }
+    }
+
+    fn parse_compound(&mut self, depth: usize) -> Result<CompoundTag, SnbtError> {
+        if depth >= MAX_DEPTH {
+            return Err(self.error("Tags are nested too deep"));
+        }
+        self.expect('{')?;
+        let mut tag = CompoundTag::new();
+        if self.accept('}') {
+            return Ok(tag);
+        }
+        loop {
+            let key = self.parse_key()?;
+            self.expect(':')?;
+            tag.insert(key, self.parse_value(depth + 1)?);
+            if !self.accept(',') {
+                self.expect('}')?;
+                break Ok(tag);
+            }
+        }
+    }
+
+    fn parse_list_or_array(&mut self, depth: usize) -> Result<Tag, SnbtError> {
+
+        if depth >= MAX_DEPTH {
+            return Err(self.error("Tags are nested too deep"));
+        }
+
+        self.expect('[')?;
+
+        let rest = &self.src.as_bytes()[self.pos..];
+        if let [array_type @ (b'B' | b'I' | b'L'), b';', ..] = rest {
+            let array_type = *array_type as char;
+            self.pos += 2;
+            return self.parse_array(array_type);
+        }
+
+        let mut tags = Vec::new();
+        if self.accept(']') {
+            return Ok(Tag::List(tags));
+        }
+
+        loop {
+            self.skip_whitespaces();
+            let start = self.pos;
+            let tag = self.parse_value(depth + 1)?;
+            if let Some(first) = tags.first() {
+                if get_tag_type(first) != get_tag_type(&tag) {
+                    self.pos = start;
+                    return Err(self.error("Inconsistent types in list"));
+                }
+            }
+            tags.push(tag);
+            if !self.accept(',') {
+                self.expect(']')?;
+                break Ok(Tag::List(tags));
+            }
+        }
+
+    }
+
+    fn parse_array(&mut self, array_type: char) -> Result<Tag, SnbtError> {
+        let mut values = Vec::new();
+        if !self.accept(']') {
+            loop {
+                self.skip_whitespaces();
+                let start = self.pos;
+                let tag = self.parse_value(MAX_DEPTH)?;
+                let value = match (array_type, tag) {
+                    ('B', Tag::Byte(v)) => v as i64,
+                    ('I', Tag::Int(v)) => v as i64,
+                    ('L', Tag::Long(v)) => v,
+                    _ => {
+                        self.pos = start;
+                        return Err(self.error("Invalid value type in array"));
+                    }
+                };
+                values.push(value);
+                if !self.accept(',') {
+                    self.expect(']')?;
+                    break;
+                }
+            }
+        }
+        Ok(match array_type {
+            'B' => Tag::ByteArray(values.into_iter().map(|v| v as i8).collect()),
+            'I' => Tag::IntArray(values.into_iter().map(|v| v as i32).collect()),
+            _ => Tag::LongArray(values)
+        })
+    }
+
+}
+
+
+/// Return the tag of an unquoted value, from its format and suffix like vanilla. Numbers
+/// out of range are kept as strings.
+fn type_unquoted(value: &str) -> Tag {
+
+    if value.eq_ignore_ascii_case("true") {
+        return Tag::Byte(1);
+    } else if value.eq_ignore_ascii_case("false") {
+        return Tag::Byte(0);
+    }
+
+    let (body, suffix) = value.split_at(value.len() - 1);
+    let suffix = suffix.chars().next().unwrap().to_ascii_lowercase();
+
+    let tag = match suffix {
+        'b' if is_integer(body) => body.parse().ok().map(Tag::Byte),
+        's' if is_integer(body) => body.parse().ok().map(Tag::Short),
+        'l' if is_integer(body) => body.parse().ok().map(Tag::Long),
+        'f' if is_decimal(body, false) => body.parse().ok().map(Tag::Float),
+        'd' if is_decimal(body, false) => body.parse().ok().map(Tag::Double),
+        _ if is_integer(value) => value.parse().ok().map(Tag::Int),
+        _ if is_decimal(value, true) => value.parse().ok().map(Tag::Double),
+        _ => None
+    };
+
+    tag.unwrap_or_else(|| Tag::String(value.to_string()))
+
+}
+
+/// Return the given string without its leading sign.
+#[inline]
+fn strip_sign(value: &str) -> &str {
+    value.strip_prefix(['-', '+']).unwrap_or(value)
+}
+
+/// Check if the value matches `[-+]?(0|[1-9][0-9]*)`.
+fn is_integer(value: &str) -> bool {
+    let digits = strip_sign(value);
+    match digits.as_bytes() {
+        [b'0'] => true,
+        [b'1'..=b'9', rest @ ..] => rest.iter().all(u8::is_ascii_digit),
+        _ => false
+    }
+}
+
+/// Check if the value matches `[-+]?([0-9]+[.]?|[0-9]*[.][0-9]+)(e[-+]?[0-9]+)?`, if a dot
+/// is required, the integer part must be followed by a dot.
+fn is_decimal(value: &str, require_dot: bool) -> bool {
+
+    let value = strip_sign(value);
+    let (mantissa, exponent) = match value.find(['e', 'E']) {
+        Some(idx) => (&value[..idx], Some(strip_sign(&value[idx + 1..]))),
+        None => (value, None)
+    };
+
+    if let Some(exponent) = exponent {
+        if exponent.is_empty() || !exponent.bytes().all(|b| b.is_ascii_digit()) {
+            return false;
+        }
+    }
+
+    let (int_part, frac_part) = match mantissa.find('.') {
+        Some(idx) => (&mantissa[..idx], Some(&mantissa[idx + 1..])),
+        None => (mantissa, None)
+    };
+
+    if !int_part.bytes().all(|b| b.is_ascii_digit()) {
+        return false;
+    }
+
+    match frac_part {
+        None => !require_dot && !int_part.is_empty(),
+        Some(frac_part) => frac_part.bytes().all(|b| b.is_ascii_digit()) && (!int_part.is_empty() || !frac_part.is_empty())
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn snbt() {
+
+        let tag = parse_snbt_compound(r#"{
+            byte: 1b, short: -2S, int: 3, long: 4l, float: 0.5f, double: 1.5, double2: 3d,
+            "quoted key": 'it\'s', str: "say \"hi\"", unquoted: minecraft.stone, bool: true,
+            exp: 1e3f, dot: 2., big: 3000000000, list: [1, 2, 3], nested: [{a: []}, {}],
+            bytes: [B; 1b, -2b], ints: [I;], longs: [L; 1L, 2L]
+        }"#).unwrap();
+
+        assert_eq!(tag.get_i8("byte").unwrap(), 1);
+        assert_eq!(tag.get_i16("short").unwrap(), -2);
+        assert_eq!(tag.get_i32("int").unwrap(), 3);
+        assert_eq!(tag.get_i64("long").unwrap(), 4);
+        assert_eq!(tag.get_f32("float").unwrap(), 0.5);
+        assert_eq!(tag.get_f64("double").unwrap(), 1.5);
+        assert_eq!(tag.get_f64("double2").unwrap(), 3.0);
+        assert_eq!(tag.get_str("quoted key").unwrap(), "it's");
+        assert_eq!(tag.get_str("str").unwrap(), "say \"hi\"");
+        assert_eq!(tag.get_str("unquoted").unwrap(), "minecraft.stone");
+        assert!(tag.get_bool("bool").unwrap());
+        assert_eq!(tag.get_f32("exp").unwrap(), 1000.0);
+        assert_eq!(tag.get_f64("dot").unwrap(), 2.0);
+        assert_eq!(tag.get_str("big").unwrap(), "3000000000");
+        assert_eq!(tag.get_i8_vec("bytes").unwrap(), &vec![1, -2]);
+        assert!(tag.get_i32_vec("ints").unwrap().is_empty());
+        assert_eq!(tag.get_i64_vec("longs").unwrap(), &vec![1, 2]);
+
+        let snbt = compound_to_snbt(&tag);
+        assert_eq!(snbt, concat!(
+            r#"{byte:1b,short:-2s,int:3,long:4L,float:0.5f,double:1.5d,double2:3.0d,"quoted key":"it's","#,
+            r#"str:'say "hi"',unquoted:"minecraft.stone",bool:1b,exp:1000.0f,dot:2.0d,big:"3000000000","#,
+            r#"list:[1,2,3],nested:[{a:[]},{}],bytes:[B;1B,-2B],ints:[I;],longs:[L;1L,2L]}"#
+        ));
+        assert_eq!(compound_to_snbt(&parse_snbt_compound(&snbt).unwrap()), snbt);
+
+        assert_eq!(parse_snbt("[1, 2b]").unwrap_err().position, 4);
+        assert_eq!(parse_snbt("[B; 1, 2]").unwrap_err().position, 4);
+        assert_eq!(parse_snbt("{a: 1").unwrap_err().position, 5);
+        assert_eq!(parse_snbt("{a: 1} b").unwrap_err().position, 7);
+        assert!(parse_snbt(&"[".repeat(MAX_DEPTH + 1)).is_err());
+
+    }
+
+}
@@ -0,0 +1,204 @@
+//! Noise generators shared by world generation and by vanilla mechanics depending on noises,
+//! such as biome temperatures.
+
+use once_cell::sync::Lazy;
+
+use crate::rand::JavaRandom;
+
+
+const SIMPLEX_GRADIENTS: [[f64; 2]; 12] = [
+    [1.0, 1.0], [-1.0, 1.0], [1.0, -1.0], [-1.0, -1.0],
+    [1.0, 0.0], [-1.0, 0.0], [1.0, 0.0], [-1.0, 0.0],
+    [0.0, 1.0], [0.0, -1.0], [0.0, 1.0], [0.0, -1.0]
+];
+
+const SIMPLEX_SQRT_3: f64 = 1.7320508075688772;
+const SIMPLEX_F2: f64 = 0.5 * (SIMPLEX_SQRT_3 - 1.0);
+const SIMPLEX_G2: f64 = (3.0 - SIMPLEX_SQRT_3) / 6.0;
+
+
+/// Simplex noise generator, only the 2D variant is currently implemented.
+///
+/// Valid for: 1.7.2 to 1.17.1
+pub struct SimplexNoise {
+    permutations: Box<[u16; 256]>
+}
+
+impl SimplexNoise {
+
+    /// Construct a new simplex noise generator, the RNG is consumed the same way as for
+    /// `PerlinNoise::new`.
+    pub fn new(rand: &mut JavaRandom) -> Self {
+
+        let mut permutations = [0; 256];
+
+        // Coordinates offsets are unused by the 2D variant, but still consumed.
+        for _ in 0..3 {
+            rand.next_double();
+        }
+
+        for (i, permutation) in permutations.iter_mut().enumerate() {
+            *permutation = i as u16;
+        }
+
+        for i in 0..256usize {
+            let n = rand.next_int_bounded(256 - i as i32) as usize + i;
+            permutations.swap(i, n);
+        }
+
+        Self {
+            permutations: Box::new(permutations)
+        }
+
+    }
+
+    #[inline]
+    fn permutation(&self, index: i32) -> i32 {
+        self.permutations[(index & 0xff) as usize] as i32
+    }
+
+    #[inline]
+    fn corner_noise(gradient: i32, x: f64, y: f64) -> f64 {
+        let t = 0.5 - x * x - y * y;
+        if t < 0.0 {
+            0.0
+        } else {
+            let t = t * t;
+            let [gx, gy] = SIMPLEX_GRADIENTS[gradient as usize];
+            t * t * (gx * x + gy * y)
+        }
+    }
+
+    /// Get the 2D noise value at the given coordinates, roughly in range -1 to 1.
+    pub fn get_value_2d(&self, x: f64, y: f64) -> f64 {
+
+        let skew = (x + y) * SIMPLEX_F2;
+        let i = (x + skew).floor() as i32;
+        let j = (y + skew).floor() as i32;
+
+        let unskew = (i + j) as f64 * SIMPLEX_G2;
+        let x0 = x - (i as f64 - unskew);
+        let y0 = y - (j as f64 - unskew);
+
+        let (i1, j1) = if x0 > y0 { (1, 0) } else { (0, 1) };
+
+        let x1 = x0 - i1 as f64 + SIMPLEX_G2;
+        let y1 = y0 - j1 as f64 + SIMPLEX_G2;
+        let x2 = x0 - 1.0 + 2.0 * SIMPLEX_G2;
+        let y2 = y0 - 1.0 + 2.0 * SIMPLEX_G2;
+
+        let ii = i & 0xff;
+        let jj = j & 0xff;
+
+        let g0 = self.permutation(ii + self.permutation(jj)) % 12;
+        let g1 = self.permutation(ii + i1 + self.permutation(jj + j1)) % 12;
+        let g2 = self.permutation(ii + 1 + self.permutation(jj + 1)) % 12;
+
+        70.0 * (Self::corner_noise(g0, x0, y0) + Self::corner_noise(g1, x1, y1) + Self::corner_noise(g2, x2, y2))
+
+    }
+
+}
+
+
+/// Sum of simplex noise octaves, octaves are given as frequency exponents where 0 is the
+/// base frequency and negative octaves have lower frequencies but higher amplitudes. Only
+/// octaves lower or equal to 0 are supported.
+///
+/// Valid for: 1.14 to 1.17.1
+pub struct SimplexNoiseOctaves {
+    /// Noise levels ordered from the highest frequency, missing octaves are `None`.
+    levels: Vec<Option<SimplexNoise>>,
+    value_factor: f64
+}
+
+impl SimplexNoiseOctaves {
+
+    /// Construct simplex noise octaves from a non-empty list of octaves, the RNG is consumed
+    /// the same way as vanilla, even for missing octaves.
+    pub fn new(rand: &mut JavaRandom, octaves: &[i32]) -> Self {
+
+        let min_octave = *octaves.iter().min().expect("octaves must not be empty");
+        assert!(octaves.iter().all(|&octave| octave <= 0), "octaves must be lower or equal to 0");
+
+        let count = (1 - min_octave) as usize;
+        let mut levels = Vec::with_capacity(count);
+
+        // The noise of the first octave is always constructed.
+        let first = SimplexNoise::new(rand);
+        levels.push(if octaves.contains(&0) { Some(first) } else { None });
+
+        for i in 1..count {
+            if octaves.contains(&-(i as i32)) {
+                levels.push(Some(SimplexNoise::new(rand)));
+            } else {
+                for _ in 0..262 {
+                    rand.next_blank();
+                }
+                levels.push(None);
+            }
+        }
+
+        Self {
+            levels,
+            value_factor: 1.0 / ((1u64 << count) - 1) as f64
+        }
+
+    }
+
+    /// Get the 2D noise value at the given coordinates.
+    pub fn get_value_2d(&self, x: f64, y: f64) -> f64 {
+
+        let mut value = 0.0;
+        let mut input_factor = 1.0;
+        let mut value_factor = self.value_factor;
+
+        for level in &self.levels {
+            if let Some(noise) = level {
+                value += noise.get_value_2d(x * input_factor, y * input_factor) * value_factor;
+            }
+            input_factor /= 2.0;
+            value_factor *= 2.0;
+        }
+
+        value
+
+    }
+
+}
+
+
+/// Simplex noise shared by biome decorations, always seeded with 2345. It's used to select
+/// flowers of some biomes and to bias the count of some ocean features.
+pub static BIOME_INFO_NOISE: Lazy<SimplexNoise> = Lazy::new(|| {
+    SimplexNoise::new(&mut JavaRandom::new(2345))
+});
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn simplex_octaves() {
+
+        // A single octave is the raw simplex noise.
+        let noise = SimplexNoise::new(&mut JavaRandom::new(1234));
+        let octaves = SimplexNoiseOctaves::new(&mut JavaRandom::new(1234), &[0]);
+        for i in 0..64 {
+            let (x, y) = (i as f64 * 0.37, i as f64 * -1.13);
+            assert_eq!(octaves.get_value_2d(x, y), noise.get_value_2d(x, y));
+        }
+
+        // Lower octaves are sampled at lower frequencies.
+        let mut rand = JavaRandom::new(3456);
+        let first = SimplexNoise::new(&mut rand);
+        let second = SimplexNoise::new(&mut rand);
+        let octaves = SimplexNoiseOctaves::new(&mut JavaRandom::new(3456), &[-1, 0]);
+        let expected = first.get_value_2d(3.0, 5.0) / 3.0 + second.get_value_2d(1.5, 2.5) * 2.0 / 3.0;
+        assert!((octaves.get_value_2d(3.0, 5.0) - expected).abs() < 1e-12);
+
+    }
+
+}
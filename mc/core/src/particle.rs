@@ -0,0 +1,55 @@
+//! Particle types, statically defined using the `particles!` macro with their protocol ID.
+
+
+/// A particle type, with its protocol ID for the latest version supported by the crates.
+#[derive(Debug)]
+pub struct Particle {
+    name: &'static str,
+    id: i32
+}
+
+impl Particle {
+
+    pub const fn new(name: &'static str, id: i32) -> Self {
+        Self { name, id }
+    }
+
+    #[inline]
+    pub fn get_name(&self) -> &'static str {
+        self.name
+    }
+
+    #[inline]
+    pub fn get_id(&self) -> i32 {
+        self.id
+    }
+
+}
+
+impl PartialEq for &'static Particle {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(*self, *other)
+    }
+}
+
+impl Eq for &'static Particle {}
+
+
+#[macro_export]
+macro_rules! particles {
+    ($global_vis:vis $static_id:ident $namespace:literal [
+        $($particle_id:ident $particle_name:literal $particle_numeric_id:literal),*
+        $(,)?
+    ]) => {
+
+        $($global_vis static $particle_id: $crate::particle::Particle = $crate::particle::Particle::new(
+            concat!($namespace, ':', $particle_name),
+            $particle_numeric_id
+        );)*
+
+        $global_vis static $static_id: [&'static $crate::particle::Particle; $crate::count!($($particle_id)*)] = [
+            $(&$particle_id),*
+        ];
+
+    };
+}
@@ -117,6 +117,145 @@ impl From<&'_ EntityPos> for BlockPos {
 }
 
 
+/// An axis-aligned bounding box in block coordinates, both minimum and maximum are inclusive.
+/// This is used to describe regions of levels, like the volume occupied by structure pieces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BoundingBox {
+    pub min_x: i32,
+    pub min_y: i32,
+    pub min_z: i32,
+    pub max_x: i32,
+    pub max_y: i32,
+    pub max_z: i32,
+}
+
+impl BoundingBox {
+
+    /// Construct a new bounding box from two corners, the coordinates are sorted so that
+    /// the order of the corners doesn't matter.
+    pub fn new(x0: i32, y0: i32, z0: i32, x1: i32, y1: i32, z1: i32) -> Self {
+        Self {
+            min_x: x0.min(x1),
+            min_y: y0.min(y1),
+            min_z: z0.min(z1),
+            max_x: x0.max(x1),
+            max_y: y0.max(y1),
+            max_z: z0.max(z1),
+        }
+    }
+
+    /// Construct the bounding box of a piece of the given size, oriented toward the given
+    /// horizontal direction from the given position. The offset is relative to the
+    /// orientation, the X offset is toward the right of the piece.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_oriented(x: i32, y: i32, z: i32, off_x: i32, off_y: i32, off_z: i32, size_x: i32, size_y: i32, size_z: i32, direction: Direction) -> Self {
+        match direction {
+            Direction::South => Self::new(x + off_x, y + off_y, z + off_z, x + size_x - 1 + off_x, y + size_y - 1 + off_y, z + size_z - 1 + off_z),
+            Direction::West => Self::new(x - size_z + 1 + off_z, y + off_y, z + off_x, x + off_z, y + size_y - 1 + off_y, z + size_x - 1 + off_x),
+            Direction::East => Self::new(x + off_z, y + off_y, z + off_x, x + size_z - 1 + off_z, y + size_y - 1 + off_y, z + size_x - 1 + off_x),
+            _ => Self::new(x + off_x, y + off_y, z - size_z + 1 + off_z, x + size_x - 1 + off_x, y + size_y - 1 + off_y, z + off_z)
+        }
+    }
+
+    /// Construct a bounding box covering the whole vertical range of the given chunk.
+    pub fn new_chunk(cx: i32, cz: i32, min_y: i32, max_y: i32) -> Self {
+        Self::new(cx * 16, min_y, cz * 16, cx * 16 + 15, max_y, cz * 16 + 15)
+    }
+
+    #[inline]
+    pub fn get_size_x(&self) -> i32 {
+        self.max_x - self.min_x + 1
+    }
+
+    #[inline]
+    pub fn get_size_y(&self) -> i32 {
+        self.max_y - self.min_y + 1
+    }
+
+    #[inline]
+    pub fn get_size_z(&self) -> i32 {
+        self.max_z - self.min_z + 1
+    }
+
+    /// Return the center of this bounding box, rounded toward negative infinity.
+    pub fn get_center(&self) -> (i32, i32, i32) {
+        (
+            self.min_x + (self.max_x - self.min_x + 1) / 2,
+            self.min_y + (self.max_y - self.min_y + 1) / 2,
+            self.min_z + (self.max_z - self.min_z + 1) / 2
+        )
+    }
+
+    /// Return true if the given block position is inside this bounding box.
+    pub fn contains(&self, x: i32, y: i32, z: i32) -> bool {
+        x >= self.min_x && x <= self.max_x &&
+        y >= self.min_y && y <= self.max_y &&
+        z >= self.min_z && z <= self.max_z
+    }
+
+    /// Return true if the two bounding boxes share at least one block.
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        self.max_x >= other.min_x && self.min_x <= other.max_x &&
+        self.max_y >= other.min_y && self.min_y <= other.max_y &&
+        self.max_z >= other.min_z && self.min_z <= other.max_z
+    }
+
+    /// Return true if this bounding box intersects the given chunk column, ignoring Y.
+    pub fn intersects_chunk(&self, cx: i32, cz: i32) -> bool {
+        self.max_x >= cx * 16 && self.min_x <= cx * 16 + 15 &&
+        self.max_z >= cz * 16 && self.min_z <= cz * 16 + 15
+    }
+
+    /// Return the intersection of the two bounding boxes, if any.
+    pub fn intersection(&self, other: &BoundingBox) -> Option<BoundingBox> {
+        if self.intersects(other) {
+            Some(BoundingBox {
+                min_x: self.min_x.max(other.min_x),
+                min_y: self.min_y.max(other.min_y),
+                min_z: self.min_z.max(other.min_z),
+                max_x: self.max_x.min(other.max_x),
+                max_y: self.max_y.min(other.max_y),
+                max_z: self.max_z.min(other.max_z),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Grow this bounding box in order to also contain the other one.
+    pub fn encapsulate(&mut self, other: &BoundingBox) {
+        self.min_x = self.min_x.min(other.min_x);
+        self.min_y = self.min_y.min(other.min_y);
+        self.min_z = self.min_z.min(other.min_z);
+        self.max_x = self.max_x.max(other.max_x);
+        self.max_y = self.max_y.max(other.max_y);
+        self.max_z = self.max_z.max(other.max_z);
+    }
+
+    /// Move this bounding box by the given offset.
+    pub fn offset(&mut self, dx: i32, dy: i32, dz: i32) {
+        self.min_x += dx;
+        self.min_y += dy;
+        self.min_z += dz;
+        self.max_x += dx;
+        self.max_y += dy;
+        self.max_z += dz;
+    }
+
+    /// Return true if the given position is on one of the 12 edges of this bounding box.
+    pub fn is_on_edge(&self, x: i32, y: i32, z: i32) -> bool {
+        if !self.contains(x, y, z) {
+            return false;
+        }
+        let on_x = (x == self.min_x || x == self.max_x) as u8;
+        let on_y = (y == self.min_y || y == self.max_y) as u8;
+        let on_z = (z == self.min_z || z == self.max_z) as u8;
+        on_x + on_y + on_z >= 2
+    }
+
+}
+
+
 /// Cardinal direction used in-game.
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum Direction {
@@ -180,4 +319,44 @@ impl Axis {
         }
     }
 
-}
\ No newline at end of file
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn bounding_box() {
+
+        let mut bb = BoundingBox::new(4, 10, 4, 0, 0, 0);
+        assert_eq!(bb, BoundingBox::new(0, 0, 0, 4, 10, 4));
+        assert_eq!(bb.get_size_x(), 5);
+        assert_eq!(bb.get_size_y(), 11);
+
+        assert!(bb.contains(4, 10, 0));
+        assert!(!bb.contains(5, 10, 0));
+        assert!(bb.intersects_chunk(0, 0));
+        assert!(!bb.intersects_chunk(1, 0));
+
+        let other = BoundingBox::new(2, 2, 2, 20, 2, 20);
+        assert!(bb.intersects(&other));
+        assert_eq!(bb.intersection(&other), Some(BoundingBox::new(2, 2, 2, 4, 2, 4)));
+
+        bb.encapsulate(&other);
+        assert_eq!(bb, BoundingBox::new(0, 0, 0, 20, 10, 20));
+
+        bb.offset(16, 0, 0);
+        assert!(bb.intersects_chunk(1, 0));
+        assert!(!bb.intersects_chunk(0, 0));
+
+        let bb = BoundingBox::new(0, 0, 0, 3, 3, 3);
+        assert!(bb.is_on_edge(0, 0, 2));
+        assert!(bb.is_on_edge(3, 1, 3));
+        assert!(!bb.is_on_edge(0, 1, 2));
+        assert!(!bb.is_on_edge(1, 1, 1));
+
+    }
+
+}
@@ -114,6 +114,11 @@ impl JavaRandom {
         ((self.next(32) as i64) << 32).wrapping_add(self.next(32) as i64)
     }
 
+    #[inline]
+    pub fn next_boolean(&mut self) -> bool {
+        self.next(1) != 0
+    }
+
     pub fn next_float(&mut self) -> f32 {
         self.next(24) as f32 / FLOAT_DIV
     }
@@ -0,0 +1,100 @@
+//! Sound events and their categories. Sound events are statically defined using the
+//! `sounds!` macro and are identified by their namespaced name.
+
+
+/// A sound event, its name is the name of the event defined by resource packs, not the
+/// path of a sound file.
+#[derive(Debug)]
+pub struct Sound {
+    name: &'static str
+}
+
+impl Sound {
+
+    pub const fn new(name: &'static str) -> Self {
+        Self { name }
+    }
+
+    #[inline]
+    pub fn get_name(&self) -> &'static str {
+        self.name
+    }
+
+}
+
+impl PartialEq for &'static Sound {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(*self, *other)
+    }
+}
+
+impl Eq for &'static Sound {}
+
+
+/// The category of a played sound, each category has its own volume in clients' options.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum SoundCategory {
+    Master,
+    Music,
+    Record,
+    Weather,
+    Block,
+    Hostile,
+    Neutral,
+    Player,
+    Ambient,
+    Voice
+}
+
+impl SoundCategory {
+
+    pub fn get_id(self) -> i32 {
+        match self {
+            Self::Master => 0,
+            Self::Music => 1,
+            Self::Record => 2,
+            Self::Weather => 3,
+            Self::Block => 4,
+            Self::Hostile => 5,
+            Self::Neutral => 6,
+            Self::Player => 7,
+            Self::Ambient => 8,
+            Self::Voice => 9
+        }
+    }
+
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Self::Master => "master",
+            Self::Music => "music",
+            Self::Record => "record",
+            Self::Weather => "weather",
+            Self::Block => "block",
+            Self::Hostile => "hostile",
+            Self::Neutral => "neutral",
+            Self::Player => "player",
+            Self::Ambient => "ambient",
+            Self::Voice => "voice"
+        }
+    }
+
+}
+
+
+#[macro_export]
+macro_rules! sounds {
+    ($global_vis:vis $static_id:ident $namespace:literal [
+        $($sound_id:ident $sound_name:literal),*
+        $(,)?
+    ]) => {
+
+        $($global_vis static $sound_id: $crate::sound::Sound = $crate::sound::Sound::new(
+            concat!($namespace, ':', $sound_name)
+        );)*
+
+        $global_vis static $static_id: [&'static $crate::sound::Sound; $crate::count!($($sound_id)*)] = [
+            $(&$sound_id),*
+        ];
+
+    };
+}
@@ -71,7 +71,7 @@ impl NbtExt for CompoundTag {
     }
 
     fn insert_uuid(&mut self, name: impl ToString, value: &Uuid) {
-        let mut uuid_values = Vec::with_capacity(4);
+        let mut uuid_values = vec![0; 4];
         let uuid_raw = value.as_u128();
         uuid_values[0] = ((uuid_raw >> 96) & 0xFFFFFFFF) as i32;
         uuid_values[1] = ((uuid_raw >> 64) & 0xFFFFFFFF) as i32;
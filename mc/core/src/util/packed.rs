@@ -107,11 +107,69 @@ impl PackedArray {
     /// Set the value at a specific index and ensure that the given value can fit into it, if
     /// not, the packed array is resized to a new byte size and the value is inserted.
     pub fn set_with_resize(&mut self, index: usize, value: u64) -> u64 {
-        let mask = Self::calc_mask(self.byte_size);
-        if value > mask {
+        if value > Self::calc_mask(self.byte_size) {
             self.resize_byte(Self::calc_min_byte_size(value));
         }
-        self.internal_set(index, value, mask)
+        self.internal_set(index, value, Self::calc_mask(self.byte_size))
+    }
+
+    /// Set every value of this packed array to the given value.
+    ///
+    /// # Panics
+    /// If the given value cannot fit in the current byte size.
+    pub fn fill(&mut self, value: u64) {
+        let mask = Self::calc_mask(self.byte_size);
+        assert!(value <= mask, "Given value {} does not fit in {} bits.", value, self.byte_size);
+        if value == 0 {
+            // The content is zeroed but the allocation is kept for future modifications.
+            self.cells.iter_mut().for_each(|c| *c = 0);
+        } else {
+            self.ensure_cells();
+            let vpc = Self::calc_values_per_cell(self.byte_size);
+            let mut cell = value;
+            for _ in 1..vpc {
+                cell <<= self.byte_size;
+                cell |= value;
+            }
+            self.cells.iter_mut().for_each(|c| *c = cell);
+        }
+    }
+
+    /// Copy all values from another packed array of the same length, the byte size of the
+    /// other array can be different from this one, in such case values are repacked.
+    ///
+    /// # Panics
+    /// If both arrays have different lengths or if a value of the other array cannot fit in
+    /// the current byte size.
+    pub fn copy_from(&mut self, other: &PackedArray) {
+
+        assert_eq!(self.length, other.length, "Packed arrays have different lengths.");
+
+        if !other.is_allocated() {
+            self.fill(0);
+        } else if self.byte_size == other.byte_size {
+            self.cells.clear();
+            self.cells.extend_from_slice(&other.cells[..Self::calc_cells_capacity(self.length, self.byte_size)]);
+        } else {
+
+            self.ensure_cells();
+
+            let mask = Self::calc_mask(self.byte_size);
+            let vpc = Self::calc_values_per_cell(self.byte_size);
+            let byte_size = self.byte_size as usize;
+            let mut values = other.iter();
+
+            for cell in &mut self.cells {
+                let mut new_cell = 0;
+                for (value_index, value) in values.by_ref().take(vpc).enumerate() {
+                    assert!(value <= mask, "Given value {} does not fit in {} bits.", value, byte_size);
+                    new_cell |= value << (value_index * byte_size);
+                }
+                *cell = new_cell;
+            }
+
+        }
+
     }
 
     #[inline]
@@ -199,9 +257,11 @@ impl PackedArray {
         let old_byte_size = self.byte_size;
         self.byte_size = new_byte_size;
 
-        // Resize internal cell.
+        // Resize internal cell, the exact capacity is reserved to avoid over-allocation
+        // since the length of the array is fixed.
         let new_cells_cap = Self::calc_cells_capacity(self.length, new_byte_size);
         let old_cells_cap = self.cells.len();
+        self.cells.reserve_exact(new_cells_cap - old_cells_cap);
         self.cells.resize(new_cells_cap, 0u64);
 
         // If was not allocated, so we can just use replace.
@@ -624,6 +684,46 @@ mod tests {
 
     }
 
+    #[test]
+    fn bulk_operations() {
+
+        let mut array = PackedArray::new(20, 5, None);
+        array.fill(0);
+        assert!(!array.is_allocated());
+
+        array.fill(17);
+        assert!(array.iter().all(|v| v == 17), "fill failed");
+        array.set(3, 2);
+
+        let mut small = PackedArray::new(20, 3, None);
+        small.fill(5);
+        array.copy_from(&small);
+        assert!(array.iter().all(|v| v == 5), "copy from smaller byte size failed");
+
+        let mut big = PackedArray::new(20, 12, None);
+        big.copy_from(&array);
+        assert_eq!(big.cells_len(), 4);
+        assert!(big.iter().all(|v| v == 5), "copy to bigger byte size failed");
+
+        big.copy_from(&PackedArray::new(20, 7, None));
+        assert!(big.iter().all(|v| v == 0), "copy from unallocated failed");
+
+        let mut array = PackedArray::new(20, 2, None);
+        array.set(19, 3);
+        array.set_with_resize(0, 100);
+        assert_eq!(array.byte_size(), 7);
+        assert_eq!(array.get(0), Some(100));
+        assert_eq!(array.get(19), Some(3));
+
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_copy() {
+        let mut array = PackedArray::new(4, 4, None);
+        array.copy_from(&PackedArray::new(4, 8, Some(16)));
+    }
+
     #[test]
     fn iter_unpack_aligned() {
 
@@ -1,3 +1,9 @@
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use super::{PackedArray, PtrBuildHasher};
+
+
 
 /// A palette is a vector with limited capacity that allows searching indices of elements.
 pub struct Palette<T> {
@@ -94,6 +100,405 @@ where
 
 }
 
+
+
+/// A global palette that maps every possible item of a paletted container to a unique save
+/// ID, this is used as the last strategy of paletted containers when local palettes are
+/// too big to be efficient.
+pub trait GlobalPalette<T: 'static> {
+
+    /// Get the save ID of the given item, `None` if the item is not supported.
+    fn get_global_sid(&self, item: &'static T) -> Option<u32>;
+
+    /// Get the item from its save ID, `None` if the save ID is invalid.
+    fn get_global_item(&self, sid: u32) -> Option<&'static T>;
+
+    /// Return the number of items in this palette.
+    fn global_items_count(&self) -> usize;
+
+}
+
+
+/// A packed array of static items associated to a palette of these items, items are
+/// compared by their address and not by value. The palette strategy is changed when
+/// needed from a linear palette (small and fast), to a hash palette (bigger) and then
+/// to the global palette given to each method.
+///
+/// Strategies are only upgraded when inserting new items, use `optimize` after large
+/// edits to remove unused items and downgrade the strategy if possible.
+pub struct PalettedContainer<T: 'static> {
+    strategy: PaletteStrategy<T>,
+    data: PackedArray,
+    linear_capacity: usize,
+    hash_capacity: usize,
+    min_byte_size: u8
+}
+
+/// Internal palette strategy of a paletted container.
+enum PaletteStrategy<T: 'static> {
+    Linear(Palette<PaletteItem<T>>),
+    Hash {
+        items: Vec<&'static T>,
+        indices: HashMap<PaletteItem<T>, u32, PtrBuildHasher>
+    },
+    Global
+}
+
+/// Internal wrapper for palette items, implementing equality and hash by address.
+struct PaletteItem<T: 'static>(&'static T);
+
+impl<T: 'static> Clone for PaletteItem<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: 'static> Copy for PaletteItem<T> {}
+
+impl<T: 'static> PartialEq for PaletteItem<T> {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self.0, other.0)
+    }
+}
+
+impl<T: 'static> Eq for PaletteItem<T> {}
+
+impl<T: 'static> Hash for PaletteItem<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::ptr::hash(self.0, state)
+    }
+}
+
+impl<T: 'static> PalettedContainer<T> {
+
+    /// Create a new paletted container of the given length, filled with the given default
+    /// item. By default the linear palette can contain 16 items and the hash palette 256.
+    pub fn new(length: usize, default: &'static T) -> Self {
+        Self {
+            strategy: PaletteStrategy::Linear(Palette::with_default(PaletteItem(default), 16)),
+            data: PackedArray::new(length, 1, None),
+            linear_capacity: 16,
+            hash_capacity: 256,
+            min_byte_size: 1
+        }
+    }
+
+    /// Change the capacities of the linear and hash palettes, if the hash capacity is not
+    /// greater than the linear one, the hash strategy is never used.
+    pub fn with_capacities(mut self, linear_capacity: usize, hash_capacity: usize) -> Self {
+        assert_ne!(linear_capacity, 0, "Given linear capacity is zero.");
+        let default = self.get_palette_item(0).unwrap();
+        self.strategy = PaletteStrategy::Linear(Palette::with_default(PaletteItem(default), linear_capacity));
+        self.linear_capacity = linear_capacity;
+        self.hash_capacity = hash_capacity;
+        self
+    }
+
+    /// Change the minimum size in bits of each value in the internal packed array.
+    pub fn with_min_byte_size(mut self, min_byte_size: u8) -> Self {
+        self.data = PackedArray::new(self.data.len(), min_byte_size, None);
+        self.min_byte_size = min_byte_size;
+        self
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Return the current size in bits of each value in the internal packed array.
+    #[inline]
+    pub fn byte_size(&self) -> u8 {
+        self.data.byte_size()
+    }
+
+    /// Return the length of the local palette, `None` if the global palette is used.
+    pub fn palette_len(&self) -> Option<usize> {
+        match &self.strategy {
+            PaletteStrategy::Linear(palette) => Some(palette.len()),
+            PaletteStrategy::Hash { items, .. } => Some(items.len()),
+            PaletteStrategy::Global => None
+        }
+    }
+
+    #[inline]
+    pub fn is_global(&self) -> bool {
+        matches!(self.strategy, PaletteStrategy::Global)
+    }
+
+    /// Get the item at the given index, `None` if the index is out of range.
+    pub fn get<G>(&self, index: usize, global: &G) -> Option<&'static T>
+    where
+        G: GlobalPalette<T> + ?Sized
+    {
+        let sid = self.data.get(index)? as u32;
+        Some(self.get_item(sid, global))
+    }
+
+    /// Set the item at the given index and return the previous one, `None` is returned if
+    /// the given item is not supported by the global palette. The palette strategy can be
+    /// upgraded by this method.
+    ///
+    /// # Panics
+    /// If the index is out of range.
+    pub fn set<G>(&mut self, index: usize, item: &'static T, global: &G) -> Option<&'static T>
+    where
+        G: GlobalPalette<T> + ?Sized
+    {
+        let sid = self.ensure_sid(item, global)?;
+        let old_sid = self.data.set(index, sid as u64) as u32;
+        Some(self.get_item(old_sid, global))
+    }
+
+    /// Fill the whole container with the given item, the palette is reset to a linear one
+    /// only containing this item. Returns `false` if the given item is not supported.
+    pub fn fill<G>(&mut self, item: &'static T, global: &G) -> bool
+    where
+        G: GlobalPalette<T> + ?Sized
+    {
+        if global.get_global_sid(item).is_none() {
+            return false;
+        }
+        self.strategy = PaletteStrategy::Linear(Palette::with_default(PaletteItem(item), self.linear_capacity));
+        unsafe {
+            // SAFETY: The old content is not relevant and every value is set to 0 after that,
+            // which points to the given item in the new palette.
+            self.data.resize_raw(self.min_byte_size);
+        }
+        self.data.fill(0);
+        true
+    }
+
+    /// Replace the whole content of this container from a palette and an iterator of indices
+    /// in this palette, missing indices are set to the first item of the palette. Returns
+    /// `false` if the palette is empty or contains an unsupported item, in such case this
+    /// container is not modified.
+    ///
+    /// # Panics
+    /// If the iterator returns an index out of the given palette.
+    pub fn set_raw<G, I>(&mut self, palette: &[&'static T], mut indices: I, global: &G) -> bool
+    where
+        G: GlobalPalette<T> + ?Sized,
+        I: Iterator<Item = usize>
+    {
+
+        if palette.is_empty() || !palette.iter().all(|&item| global.get_global_sid(item).is_some()) {
+            return false;
+        }
+
+        self.strategy = self.new_strategy(palette.iter().copied());
+
+        let byte_size = self.get_strategy_byte_size(&self.strategy, palette.len(), global);
+        let sids: Vec<u32> = match self.strategy {
+            PaletteStrategy::Global => palette.iter().map(|&item| global.get_global_sid(item).unwrap()).collect(),
+            _ => (0..palette.len() as u32).collect()
+        };
+
+        unsafe {
+            // SAFETY: The old content is not relevant and every value is replaced just after.
+            self.data.resize_raw(byte_size);
+        }
+        self.data.replace(move |_, _| sids[indices.next().unwrap_or(0)] as u64);
+        true
+
+    }
+
+    /// Iterate over all items in this container.
+    pub fn iter<'a, G>(&'a self, global: &'a G) -> impl Iterator<Item = &'static T> + 'a
+    where
+        G: GlobalPalette<T> + ?Sized
+    {
+        // The last item is cached because consecutive values are often the same.
+        let mut last: Option<(u64, &'static T)> = None;
+        self.data.iter().map(move |sid| {
+            match last {
+                Some((last_sid, item)) if last_sid == sid => item,
+                _ => last.insert((sid, self.get_item(sid as u32, global))).1
+            }
+        })
+    }
+
+    /// Garbage collect unused items of the palette and downgrade the palette strategy if
+    /// the remaining items allow it. This is an expensive operation that should be used
+    /// after large edits.
+    pub fn optimize<G>(&mut self, global: &G)
+    where
+        G: GlobalPalette<T> + ?Sized
+    {
+
+        let mut used: Vec<&'static T> = Vec::new();
+        let mut used_indices: HashMap<u32, u32> = HashMap::new();
+
+        for sid in self.data.iter() {
+            let sid = sid as u32;
+            used_indices.entry(sid).or_insert_with(|| {
+                used.push(self.get_item(sid, global));
+                used.len() as u32 - 1
+            });
+        }
+
+        let strategy = self.new_strategy(used.iter().copied());
+        let byte_size = self.get_strategy_byte_size(&strategy, used.len(), global);
+
+        let mut data = PackedArray::new(self.data.len(), byte_size, None);
+        {
+            let mut old_values = self.data.iter();
+            data.replace(|_, _| {
+                let sid = old_values.next().unwrap() as u32;
+                match strategy {
+                    PaletteStrategy::Global => sid as u64,
+                    _ => used_indices[&sid] as u64
+                }
+            });
+        }
+
+        self.strategy = strategy;
+        self.data = data;
+
+    }
+
+    /// Internal method to get a local palette item from its save ID.
+    fn get_palette_item(&self, sid: u32) -> Option<&'static T> {
+        match &self.strategy {
+            PaletteStrategy::Linear(palette) => palette.get_item(sid as usize).map(|item| item.0),
+            PaletteStrategy::Hash { items, .. } => items.get(sid as usize).copied(),
+            PaletteStrategy::Global => None
+        }
+    }
+
+    /// Internal method to get an item from its save ID, this method panics if the save ID
+    /// is not valid, this should not happen because the palette is kept consistent.
+    #[inline]
+    fn get_item<G>(&self, sid: u32, global: &G) -> &'static T
+    where
+        G: GlobalPalette<T> + ?Sized
+    {
+        match self.strategy {
+            PaletteStrategy::Global => global.get_global_item(sid),
+            _ => self.get_palette_item(sid)
+        }.expect("Invalid save ID in paletted container.")
+    }
+
+    /// Internal method to create the smallest strategy able to contain the given items.
+    fn new_strategy<I>(&self, items: I) -> PaletteStrategy<T>
+    where
+        I: ExactSizeIterator<Item = &'static T>
+    {
+        let len = items.len();
+        if len <= self.linear_capacity {
+            let items = items.map(PaletteItem).collect();
+            PaletteStrategy::Linear(Palette::from_raw(items, self.linear_capacity))
+        } else if len <= self.hash_capacity {
+            let items: Vec<&'static T> = items.collect();
+            let indices = items.iter()
+                .enumerate()
+                .map(|(i, &item)| (PaletteItem(item), i as u32))
+                .collect();
+            PaletteStrategy::Hash { items, indices }
+        } else {
+            PaletteStrategy::Global
+        }
+    }
+
+    /// Internal method to compute the byte size of the packed array for the given strategy
+    /// and the given number of items in the local palette.
+    fn get_strategy_byte_size<G>(&self, strategy: &PaletteStrategy<T>, items_count: usize, global: &G) -> u8
+    where
+        G: GlobalPalette<T> + ?Sized
+    {
+        let max_sid = match strategy {
+            PaletteStrategy::Global => global.global_items_count(),
+            _ => items_count
+        }.max(1) - 1;
+        PackedArray::calc_min_byte_size(max_sid as u64).max(self.min_byte_size)
+    }
+
+    /// Internal method to get the save ID of a given item, inserting it in the palette if
+    /// needed and upgrading the strategy if the palette is full.
+    fn ensure_sid<G>(&mut self, item: &'static T, global: &G) -> Option<u32>
+    where
+        G: GlobalPalette<T> + ?Sized
+    {
+
+        let sid = match &mut self.strategy {
+            PaletteStrategy::Linear(palette) => {
+                if let Some(sid) = palette.search_index(PaletteItem(item)) {
+                    return Some(sid as u32);
+                }
+                global.get_global_sid(item)?;
+                match palette.insert_index(PaletteItem(item)) {
+                    Some(sid) => sid as u32,
+                    None => {
+                        self.upgrade_strategy(global);
+                        return self.ensure_sid(item, global);
+                    }
+                }
+            }
+            PaletteStrategy::Hash { items, indices } => {
+                if let Some(&sid) = indices.get(&PaletteItem(item)) {
+                    return Some(sid);
+                }
+                global.get_global_sid(item)?;
+                if items.len() < self.hash_capacity {
+                    let sid = items.len() as u32;
+                    items.push(item);
+                    indices.insert(PaletteItem(item), sid);
+                    sid
+                } else {
+                    self.upgrade_strategy(global);
+                    return self.ensure_sid(item, global);
+                }
+            }
+            PaletteStrategy::Global => return global.get_global_sid(item)
+        };
+
+        if sid as u64 > self.data.max_value() {
+            self.data.resize_byte(self.data.byte_size() + 1);
+        }
+
+        Some(sid)
+
+    }
+
+    /// Internal method to upgrade a full local palette to the next strategy.
+    fn upgrade_strategy<G>(&mut self, global: &G)
+    where
+        G: GlobalPalette<T> + ?Sized
+    {
+        match &self.strategy {
+            PaletteStrategy::Linear(palette) if self.hash_capacity > self.linear_capacity => {
+                let items: Vec<&'static T> = palette.iter().map(|item| item.0).collect();
+                let indices = palette.iter()
+                    .enumerate()
+                    .map(|(i, item)| (item, i as u32))
+                    .collect();
+                // Save IDs are kept the same, so the packed array is not modified.
+                self.strategy = PaletteStrategy::Hash { items, indices };
+            }
+            PaletteStrategy::Global => {}
+            _ => {
+                let items: Vec<&'static T> = match &self.strategy {
+                    PaletteStrategy::Linear(palette) => palette.iter().map(|item| item.0).collect(),
+                    PaletteStrategy::Hash { items, .. } => items.clone(),
+                    PaletteStrategy::Global => unreachable!()
+                };
+                self.strategy = PaletteStrategy::Global;
+                let byte_size = self.get_strategy_byte_size(&self.strategy, 0, global).max(self.data.byte_size());
+                self.data.resize_byte_and_replace(byte_size, move |_, sid| {
+                    global.get_global_sid(items[sid as usize]).unwrap() as u64
+                });
+            }
+        }
+    }
+
+}
+
+
 #[cfg(test)]
 mod tests {
 
@@ -121,4 +526,71 @@ mod tests {
 
     }
 
+    static ITEMS: [u8; 300] = [0; 300];
+
+    struct TestGlobalPalette;
+
+    impl GlobalPalette<u8> for TestGlobalPalette {
+
+        fn get_global_sid(&self, item: &'static u8) -> Option<u32> {
+            ITEMS.iter().position(|global_item| std::ptr::eq(item, global_item)).map(|sid| sid as u32)
+        }
+
+        fn get_global_item(&self, sid: u32) -> Option<&'static u8> {
+            ITEMS.get(sid as usize)
+        }
+
+        fn global_items_count(&self) -> usize {
+            ITEMS.len()
+        }
+
+    }
+
+    #[test]
+    fn paletted_container() {
+
+        let global = TestGlobalPalette;
+        let mut container = PalettedContainer::new(64, &ITEMS[0]).with_capacities(4, 16);
+        assert!(std::ptr::eq(container.get(0, &global).unwrap(), &ITEMS[0]));
+        assert!(container.get(64, &global).is_none());
+
+        for (i, item) in ITEMS.iter().enumerate().take(4).skip(1) {
+            container.set(i, item, &global).unwrap();
+        }
+        assert_eq!(container.palette_len(), Some(4));
+        assert_eq!(container.byte_size(), 2);
+
+        for (i, item) in ITEMS.iter().enumerate().take(16).skip(4) {
+            container.set(i, item, &global).unwrap();
+            assert_eq!(container.palette_len(), Some(i + 1));
+        }
+        assert_eq!(container.byte_size(), 4);
+
+        container.set(16, &ITEMS[16], &global).unwrap();
+        assert!(container.is_global());
+        assert_eq!(container.byte_size(), 9);
+        assert!(container.iter(&global).enumerate().all(|(i, item)| std::ptr::eq(item, &ITEMS[if i < 17 { i } else { 0 }])));
+
+        for i in 0..16 {
+            container.set(i, &ITEMS[16], &global).unwrap();
+        }
+
+        container.optimize(&global);
+        assert_eq!(container.palette_len(), Some(2));
+        assert_eq!(container.byte_size(), 1);
+        assert!(std::ptr::eq(container.get(5, &global).unwrap(), &ITEMS[16]));
+        assert!(std::ptr::eq(container.get(30, &global).unwrap(), &ITEMS[0]));
+
+        let palette: Vec<&'static u8> = ITEMS.iter().take(10).collect();
+        assert!(container.set_raw(&palette, (0..64).map(|i| i % 10), &global));
+        assert_eq!(container.palette_len(), Some(10));
+        assert!(container.iter(&global).enumerate().all(|(i, item)| std::ptr::eq(item, &ITEMS[i % 10])));
+        assert!(!container.set_raw(&[], std::iter::empty(), &global));
+
+        assert!(container.fill(&ITEMS[42], &global));
+        assert_eq!(container.palette_len(), Some(1));
+        assert!(container.iter(&global).all(|item| std::ptr::eq(item, &ITEMS[42])));
+
+    }
+
 }
\ No newline at end of file
@@ -1,4 +1,4 @@
-use std::hash::{Hash, Hasher};
+use std::hash::{Hash, Hasher, BuildHasherDefault};
 
 
 /// This structure can be used to store a statically bound reference `&'static T` in a
@@ -8,9 +8,18 @@ use std::hash::{Hash, Hasher};
 ///
 /// This opaque pointer also implements `Hash` and `Eq` to be usable as a map key.
 #[repr(transparent)]
-#[derive(Copy, Clone)]
 pub struct OpaquePtr<T>(*const T);
 
+// Not derived because the derive would require `T` to be copyable.
+impl<T> Clone for OpaquePtr<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for OpaquePtr<T> {}
+
 unsafe impl<T> Send for OpaquePtr<T> {}
 unsafe impl<T> Sync for OpaquePtr<T> {}
 
@@ -43,3 +52,42 @@ impl<T> PartialEq for OpaquePtr<T> {
 }
 
 impl<T> Eq for OpaquePtr<T> {}
+
+
+/// A hasher for maps keyed by static addresses, like `OpaquePtr`, it is much faster than
+/// the default hasher. Addresses are not chosen by an attacker so the default protection
+/// against collision attacks is not needed.
+#[derive(Default)]
+pub struct PtrHasher(u64);
+
+impl Hasher for PtrHasher {
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.write_u64(byte as u64);
+        }
+    }
+
+    #[inline]
+    fn write_u64(&mut self, i: u64) {
+        // Fibonacci hashing, the multiplication only carries entropy toward the high bits
+        // and keeps the alignment zeros of addresses in the low bits, but hash maps pick
+        // buckets from the low bits, so the high bits are folded down.
+        let h = (self.0.rotate_left(5) ^ i).wrapping_mul(0x9E3779B97F4A7C15);
+        self.0 = h ^ (h >> 32);
+    }
+
+    #[inline]
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+}
+
+/// A build hasher for `PtrHasher`, to use with `HashMap::with_hasher` or `HashMap::default`.
+pub type PtrBuildHasher = BuildHasherDefault<PtrHasher>;
@@ -6,16 +6,21 @@ use nbt::{CompoundTag, Tag, CompoundTagError};
 use hecs::EntityBuilder;
 use thiserror::Error;
 
-use crate::world::level::{LevelEnv, BaseEntity};
-use crate::world::chunk::{ChunkStatus, Light};
+use crate::world::level::{LevelEnv, BaseEntity, BaseBlockEntity};
+use crate::world::chunk::{ChunkStatus, ChunkHeight, Light};
 use crate::world::source::ProtoChunk;
 use crate::entity::GlobalEntities;
 use crate::block::BlockState;
 use crate::biome::Biome;
 use crate::util::{NbtExt, PackedIterator};
+use crate::debug;
 
+use super::schema::{ChunkSchema, R112Chunk, R112Section, R116Chunk, R118Chunk};
+use super::fix::{upgrade_biomes_2d, flatten_legacy_blocks, get_nibble, FlattenedBlocks, LegacyBlockResolver};
 
-/// The only supported data version for decoding. Current is `1.18.1`.
+
+/// The latest supported data version for decoding. Current is `1.18.1`, older layouts are
+/// also supported, see `schema` module.
 pub const DATA_VERSION: i32 = 2865;
 
 
@@ -23,6 +28,8 @@ pub const DATA_VERSION: i32 = 2865;
 pub enum DecodeError {
     #[error("Data version {0} is not supported.")]
     UnsupportedDataVersion(i32),
+    #[error("Legacy block {0}:{1} cannot be mapped to a block state.")]
+    UnsupportedLegacyBlock(u16, u8),
     #[error("Unknown block state '{0}' in the palette for the chunk environments.")]
     UnknownBlockState(String),
     #[error("Unknown property value: {0}")]
@@ -51,74 +58,148 @@ pub fn decode_chunk_from_reader(reader: &mut impl Read, chunk: &mut ProtoChunk)
     decode_chunk(&read_compound_tag(reader)?, chunk)
 }
 
-/// Decode a chunk from its NBT data.
+/// Decode a chunk from its NBT data, the layout of the data is selected from its data
+/// version, see `ChunkSchema`.
 pub fn decode_chunk(tag_root: &CompoundTag, chunk: &mut ProtoChunk) -> Result<(), DecodeError> {
+    match ChunkSchema::from_nbt(tag_root)? {
+        ChunkSchema::R112(tag_chunk) => decode_r112_chunk(&tag_chunk, chunk),
+        ChunkSchema::R116(tag_chunk) => decode_r116_chunk(&tag_chunk, chunk),
+        ChunkSchema::R118(tag_chunk) => decode_r118_chunk(&tag_chunk, chunk)
+    }
+}
+
+fn decode_r112_chunk(tag_chunk: &R112Chunk, chunk: &mut ProtoChunk) -> Result<(), DecodeError> {
+
+    check_position(chunk, tag_chunk.cx, tag_chunk.cz)?;
+
+    // Chunks that are not populated have their terrain but no features yet.
+    chunk.set_status(if tag_chunk.terrain_populated {
+        ChunkStatus::Full
+    } else {
+        ChunkStatus::LiquidCarvers
+    });
+
+    chunk.set_inhabited_time(tag_chunk.inhabited_time.max(0) as u64);
+    chunk.set_old_noise(true);
+
+    let env = Arc::clone(chunk.get_env());
+    let height = chunk.get_height();
 
-    let data_version = tag_root.get_i32("DataVersion")?;
-    if data_version != DATA_VERSION {
-        return Err(DecodeError::UnsupportedDataVersion(data_version));
+    if let Some(tag_biomes) = tag_chunk.biomes {
+        decode_biome_ids(chunk, &upgrade_biomes_2d(tag_biomes), &env)?;
     }
 
-    // Removed in 1.18 data version
-    // let tag_level = tag_root.get_compound_tag("Level")?;
+    for tag_section in &tag_chunk.sections {
 
-    let cx = tag_root.get_i32("xPos")?;
-    let _lower_cy = tag_root.get_i32("yPos")?;
-    let cz = tag_root.get_i32("zPos")?;
-    check_position(chunk, cx, cz)?;
+        let cy = tag_section.y;
+        if !check_section_height(cy, height, true)? {
+            continue;
+        }
 
-    chunk.set_status(match tag_root.get_str("Status")? {
-        "empty" => ChunkStatus::Empty,
-        "structure_starts" => ChunkStatus::StructureStarts,
-        "structure_references" => ChunkStatus::StructureReferences,
-        "biomes" => ChunkStatus::Biomes,
-        "noise" => ChunkStatus::Noise,
-        "surface" => ChunkStatus::Surface,
-        "carvers" => ChunkStatus::Carvers,
-        "liquid_carvers" => ChunkStatus::LiquidCarvers,
-        "features" => ChunkStatus::Features,
-        "light" => ChunkStatus::Light,
-        "spawn" => ChunkStatus::Spawn,
-        "heightmaps " => ChunkStatus::Heightmaps,
-        "full" => ChunkStatus::Full,
-        unknown_status => {
-            return Err(DecodeError::Malformed(format!("Unknown status: {}.", unknown_status)));
+        // Numeric ids are mapped to block states with the flattening table of 1.13 given by
+        // the environment, without it we only accept sections filled with air.
+        match env.legacy_blocks {
+            Some(resolver) => decode_legacy_section_blocks(chunk, cy, tag_section, resolver, &env)?,
+            None => {
+                for (i, &block) in tag_section.blocks.iter().enumerate() {
+                    let add = tag_section.add.map(|add| get_nibble(add, i)).unwrap_or(0);
+                    let block_id = (block as u8 as u16) | ((add as u16) << 8);
+                    if block_id != 0 {
+                        return Err(DecodeError::UnsupportedLegacyBlock(block_id, get_nibble(tag_section.data, i)));
+                    }
+                }
+            }
         }
-    });
+
+        decode_section_lights(chunk, cy, tag_section.block_light, tag_section.sky_light);
+
+    }
+
+    for &tag_block_entity in &tag_chunk.tile_entities {
+        decode_block_entity(tag_block_entity, &env.entities, chunk)?;
+    }
+
+    Ok(())
+
+}
+
+fn decode_r116_chunk(tag_chunk: &R116Chunk, chunk: &mut ProtoChunk) -> Result<(), DecodeError> {
+
+    check_position(chunk, tag_chunk.cx, tag_chunk.cz)?;
+    chunk.set_status(decode_status(tag_chunk.status)?);
+    chunk.set_inhabited_time(tag_chunk.inhabited_time.max(0) as u64);
+    chunk.set_old_noise(true);
+
+    let env = Arc::clone(chunk.get_env());
+    let height = chunk.get_height();
+
+    if let Some(tag_biomes) = tag_chunk.biomes {
+        decode_biome_ids(chunk, tag_biomes, &env)?;
+    }
+
+    for tag_section in &tag_chunk.sections {
+
+        let cy = tag_section.y;
+        if !check_section_height(cy, height, tag_section.palette.is_some())? {
+            continue;
+        }
+
+        if let Some(tag_palette) = &tag_section.palette {
+            let mut blocks_palette = Vec::new();
+            for &tag_block in tag_palette {
+                blocks_palette.push(decode_block_state(tag_block, &env)?);
+            }
+            decode_section_blocks(chunk, cy, blocks_palette, tag_section.block_states, &env);
+        }
+
+        decode_section_lights(chunk, cy, tag_section.block_light, tag_section.sky_light);
+
+    }
+
+    for &tag_block_entity in &tag_chunk.tile_entities {
+        decode_block_entity(tag_block_entity, &env.entities, chunk)?;
+    }
+
+    Ok(())
+
+}
+
+fn decode_r118_chunk(tag_chunk: &R118Chunk, chunk: &mut ProtoChunk) -> Result<(), DecodeError> {
+
+    check_position(chunk, tag_chunk.cx, tag_chunk.cz)?;
+    chunk.set_status(decode_status(tag_chunk.status)?);
+    chunk.set_inhabited_time(tag_chunk.inhabited_time.max(0) as u64);
+    chunk.set_old_noise(tag_chunk.old_noise);
 
     // Common environment
     let env = Arc::clone(chunk.get_env());
     let height = chunk.get_height();
 
-    /*if height.min != lower_cy {
+    /*if height.min != tag_chunk.cy {
         return Err(DecodeError::Malformed(
-            format!("The environment's height minimum Y ({}) is not valid for decoding chunk with minimum Y if {}.", height.min, lower_cy)
+            format!("The environment's height minimum Y ({}) is not valid for decoding chunk with minimum Y if {}.", height.min, tag_chunk.cy)
         ));
     }*/
 
-    // Sections
-    let tag_sections = tag_root.get_compound_tag_vec("sections")?;
-    for tag_section in tag_sections {
+    for tag_section in &tag_chunk.sections {
 
         // Sub chunk height
-        let cy = tag_section.get_i8("Y")?;
-
-        if cy < height.min || cy > height.max {
-            return Err(DecodeError::Malformed(format!("Invalid section at Y {}, supported height is {:?}", cy, height)));
+        let cy = tag_section.y;
+        let has_content = tag_section.biomes.is_some() || tag_section.block_states.is_some();
+        if !check_section_height(cy, height, has_content)? {
+            continue;
         }
 
-        if let Ok(tag_biomes) = tag_section.get_compound_tag("biomes") {
-
-            let tag_palette = tag_biomes.get_str_vec("palette")?;
+        if let Some(tag_biomes) = &tag_section.biomes {
 
             let mut biomes_palette = Vec::new();
-            for tag_biome in tag_palette {
+            for &tag_biome in &tag_biomes.palette {
                 biomes_palette.push(decode_biome(tag_biome, &env)?);
             }
 
             let biomes_offset = (cy - height.min) as usize * 64;
 
-            if let Ok(tag_data) = tag_biomes.get_i64_vec("data") {
+            if let Some(tag_data) = tag_biomes.data {
 
                 let bits = tag_data.len() as u8; // Simplified the (len * 64 / 64)
                 let unpacked_biomes = tag_data.iter()
@@ -145,75 +226,213 @@ pub fn decode_chunk(tag_root: &CompoundTag, chunk: &mut ProtoChunk) -> Result<()
 
         }
 
-        if let Ok(tag_block_states) = tag_section.get_compound_tag("block_states") {
-
-            let tag_palette = tag_block_states.get_compound_tag_vec("palette")?;
-
+        if let Some(tag_block_states) = &tag_section.block_states {
             let mut blocks_palette = Vec::new();
-            for tag_block in tag_palette {
+            for &tag_block in &tag_block_states.palette {
                 blocks_palette.push(decode_block_state(tag_block, &env)?);
             }
+            decode_section_blocks(chunk, cy, blocks_palette, tag_block_states.data, &env);
+        }
 
-            if let Ok(tag_data) = tag_block_states.get_i64_vec("data") {
+        decode_section_lights(chunk, cy, tag_section.block_light, tag_section.sky_light);
 
-                let bits = (tag_data.len() * 64 / 4096) as u8;
-                let unpacked_blocks = tag_data.iter()
-                    .map(|&v| v as u64)
-                    .unpack_aligned(bits)
-                    .take(4096)
-                    .map(|v| v as usize);
+    }
 
-                if let Ok(sub_chunk) = chunk.ensure_sub_chunk(cy) {
-                    unsafe {
-                        sub_chunk.set_blocks_raw(blocks_palette, unpacked_blocks);
-                    }
-                }
+    // TODO: Heightmaps
 
-            } else {
+    for &tag_block_entity in &tag_chunk.block_entities {
+        decode_block_entity(tag_block_entity, &env.entities, chunk)?;
+    }
 
-                // Because it is unclear what is expected when data tag is absent, I suppose that we
-                // should create and fill the sub chunk if the palette contains only one block state.
-                // We only fill the sub chunk if the only block is not the null block.
-                if blocks_palette.len() == 1 && env.blocks.get_sid_from(&blocks_palette[0]).unwrap() != 0 {
-                    if let Ok(sub_chunk) = chunk.ensure_sub_chunk(cy) {
-                        sub_chunk.fill_block(blocks_palette[0]).unwrap();
-                    }
-                }
+    Ok(())
+
+}
 
+/// Decode 3D biomes of numeric ids of layouts prior to `1.18`, see `fix::BIOMES_3D_LEN`.
+/// These biomes start at Y 0, the lowest and highest layers are repeated to fill the parts
+/// of the chunk height out of this range.
+fn decode_biome_ids(chunk: &mut ProtoChunk, biome_ids: &[i32], env: &LevelEnv) -> Result<(), DecodeError> {
+
+    let mut biomes_palette = Vec::new();
+    let mut biomes_indices = Vec::with_capacity(biome_ids.len());
+
+    for &biome_id in biome_ids {
+        let biome = decode_biome_id(biome_id, env)?;
+        biomes_indices.push(match biomes_palette.iter().position(|&b| b == biome) {
+            Some(idx) => idx,
+            None => {
+                biomes_palette.push(biome);
+                biomes_palette.len() - 1
             }
+        });
+    }
 
-        }
+    let min_layer = chunk.get_height().min as isize * 4;
+    let max_layer = (biomes_indices.len() / 16) as isize - 1;
 
-        #[inline]
-        fn iter_light_slice(slice: &[i8]) -> impl Iterator<Item = u8> + '_ {
-            slice.iter().flat_map(|&v| [(v as u8) & 0xF, ((v as u8) & 0xF0) >> 4])
+    unsafe {
+        chunk.set_biomes_raw(0, biomes_palette, (0..).map(|i| {
+            let layer = (min_layer + (i / 16) as isize).clamp(0, max_layer) as usize;
+            biomes_indices[layer * 16 + i % 16]
+        }));
+    }
+
+    Ok(())
+
+}
+
+/// Decode the status of a chunk, shared by layouts since `1.13`.
+fn decode_status(status: &str) -> Result<ChunkStatus, DecodeError> {
+    Ok(match status {
+        "empty" => ChunkStatus::Empty,
+        "structure_starts" => ChunkStatus::StructureStarts,
+        "structure_references" => ChunkStatus::StructureReferences,
+        "biomes" => ChunkStatus::Biomes,
+        "noise" => ChunkStatus::Noise,
+        "surface" => ChunkStatus::Surface,
+        "carvers" => ChunkStatus::Carvers,
+        "liquid_carvers" => ChunkStatus::LiquidCarvers,
+        "features" => ChunkStatus::Features,
+        "light" => ChunkStatus::Light,
+        "spawn" => ChunkStatus::Spawn,
+        "heightmaps " => ChunkStatus::Heightmaps,
+        "full" => ChunkStatus::Full,
+        unknown_status => {
+            return Err(DecodeError::Malformed(format!("Unknown status: {}.", unknown_status)));
         }
+    })
+}
 
-        if let Ok(tag_block_light) = tag_section.get_i8_vec("BlockLight") {
-            if let Ok(sub_chunk) = chunk.ensure_sub_chunk(cy) {
-                unsafe {
-                    sub_chunk.set_lights_raw(Light::Block, iter_light_slice(&tag_block_light[..]));
-                }
+/// Check if a section is in the chunk's height and should be decoded. Sections out of the
+/// height are ignored if they have no content (game stores light one section above and
+/// below), otherwise an error is returned.
+fn check_section_height(cy: i8, height: ChunkHeight, has_content: bool) -> Result<bool, DecodeError> {
+    if cy >= height.min && cy <= height.max {
+        Ok(true)
+    } else if has_content {
+        Err(DecodeError::Malformed(format!("Invalid section at Y {}, supported height is {:?}", cy, height)))
+    } else {
+        Ok(false)
+    }
+}
+
+/// Decode the blocks of a section from its palette and its indices, packed without spanning
+/// between longs.
+fn decode_section_blocks(chunk: &mut ProtoChunk, cy: i8, blocks_palette: Vec<&'static BlockState>, tag_data: Option<&[i64]>, env: &LevelEnv) {
+
+    if let Some(tag_data) = tag_data {
+
+        let bits = (tag_data.len() * 64 / 4096) as u8;
+        let unpacked_blocks = tag_data.iter()
+            .map(|&v| v as u64)
+            .unpack_aligned(bits)
+            .take(4096)
+            .map(|v| v as usize);
+
+        if let Ok(sub_chunk) = chunk.ensure_sub_chunk(cy) {
+            unsafe {
+                sub_chunk.set_blocks_raw(blocks_palette, unpacked_blocks);
             }
         }
 
-        if let Ok(tag_sky_light) = tag_section.get_i8_vec("SkyLight") {
+    } else {
+
+        // Because it is unclear what is expected when data tag is absent, I suppose that we
+        // should create and fill the sub chunk if the palette contains only one block state.
+        // We only fill the sub chunk if the only block is not the null block.
+        if blocks_palette.len() == 1 && env.blocks.get_sid_from(blocks_palette[0]).unwrap() != 0 {
             if let Ok(sub_chunk) = chunk.ensure_sub_chunk(cy) {
-                unsafe {
-                    sub_chunk.set_lights_raw(Light::Block, iter_light_slice(&tag_sky_light[..]));
-                }
+                sub_chunk.fill_block(blocks_palette[0]).unwrap();
             }
         }
 
     }
 
-    // TODO: Heightmaps
-    // TODO: Block entities
+}
+
+fn decode_legacy_section_blocks(chunk: &mut ProtoChunk, cy: i8, tag_section: &R112Section, resolver: LegacyBlockResolver, env: &LevelEnv) -> Result<(), DecodeError> {
+
+    let FlattenedBlocks { palette: blocks_palette, blocks } = flatten_legacy_blocks(tag_section.blocks, tag_section.add, tag_section.data, resolver)
+        .map_err(|(id, meta)| DecodeError::UnsupportedLegacyBlock(id, meta))?;
+
+    for &state in &blocks_palette {
+        if env.blocks.get_sid_from(state).is_none() {
+            return Err(DecodeError::UnknownBlockState(state.get_block().get_name().to_string()));
+        }
+    }
+
+    // Like sections without data, sections only made of the null block are not created.
+    if blocks_palette.len() > 1 || env.blocks.get_sid_from(blocks_palette[0]).unwrap() != 0 {
+        if let Ok(sub_chunk) = chunk.ensure_sub_chunk(cy) {
+            // SAFETY: All states of the palette have been checked above.
+            unsafe {
+                sub_chunk.set_blocks_raw(blocks_palette, blocks.into_iter());
+            }
+        }
+    }
 
     Ok(())
 
 }
 
+fn decode_section_lights(chunk: &mut ProtoChunk, cy: i8, block_light: Option<&[i8]>, sky_light: Option<&[i8]>) {
+
+    #[inline]
+    fn iter_light_slice(slice: &[i8]) -> impl Iterator<Item = u8> + '_ {
+        slice.iter().flat_map(|&v| [(v as u8) & 0xF, ((v as u8) & 0xF0) >> 4])
+    }
+
+    if let Some(tag_block_light) = block_light {
+        if let Ok(sub_chunk) = chunk.ensure_sub_chunk(cy) {
+            unsafe {
+                sub_chunk.set_lights_raw(Light::Block, iter_light_slice(tag_block_light));
+            }
+        }
+    }
+
+    if let Some(tag_sky_light) = sky_light {
+        if let Ok(sub_chunk) = chunk.ensure_sub_chunk(cy) {
+            unsafe {
+                sub_chunk.set_lights_raw(Light::Sky, iter_light_slice(tag_sky_light));
+            }
+        }
+    }
+
+}
+
+#[inline]
+/// Decode a block entity and add it to the proto chunk. Unlike entities, block entities of
+/// types that are not supported by the environment are ignored, this allows loading chunks
+/// with block entities that are not yet implemented. The index of the entity builder in the
+/// proto chunk is returned if the block entity was decoded.
+pub fn decode_block_entity(tag_block_entity: &CompoundTag, entities: &GlobalEntities, chunk: &mut ProtoChunk) -> Result<Option<usize>, DecodeError> {
+
+    let entity_id = tag_block_entity.get_str("id")?;
+
+    let (entity_type, entity_codecs) = match entities.get_entity_type_and_codecs(entity_id) {
+        Some(type_and_codecs) => type_and_codecs,
+        None => {
+            debug!("Ignored unsupported block entity type: {}", entity_id);
+            return Ok(None);
+        }
+    };
+
+    let pos = tag_block_entity.get_split_block_pos("x", "y", "z")?;
+
+    let mut entity_builder = EntityBuilder::new();
+
+    for &entity_codec in entity_codecs {
+        entity_codec.decode(tag_block_entity, &mut entity_builder).map_err(|msg| {
+            DecodeError::MalformedEntity(msg)
+        })?;
+    }
+
+    entity_builder.add(BaseBlockEntity::new(entity_type, pos));
+
+    Ok(Some(chunk.add_proto_entity(entity_builder)))
+
+}
+
 /// Decode block state from a state compound tag.
 pub fn decode_block_state(tag_block: &CompoundTag, env: &LevelEnv) -> Result<&'static BlockState, DecodeError> {
 
@@ -243,6 +462,11 @@ pub fn decode_biome(name: &str, env: &LevelEnv) -> Result<&'static Biome, Decode
     env.biomes.get_biome_from_name(name).ok_or_else(|| DecodeError::UnknownBiome(name.to_string()))
 }
 
+/// Decode biome from its numeric ID, used by layouts prior to `1.18`.
+pub fn decode_biome_id(id: i32, env: &LevelEnv) -> Result<&'static Biome, DecodeError> {
+    env.biomes.get_biome_from_id(id).ok_or_else(|| DecodeError::UnknownBiome(id.to_string()))
+}
+
 /// Decode chunk entities stored in there own files.
 pub fn decode_entities(tag_root: &CompoundTag, chunk: &mut ProtoChunk) -> Result<(), DecodeError> {
 
@@ -260,6 +484,8 @@ pub fn decode_entities(tag_root: &CompoundTag, chunk: &mut ProtoChunk) -> Result
 
     let tag_entities = tag_root.get_compound_tag_vec("Entities")?;
 
+    chunk.set_inhabited_time(tag_root.get_i64_or("InhabitedTime", 0).max(0) as u64);
+
     // Common environment
     let env = Arc::clone(chunk.get_env());
 
@@ -324,3 +550,138 @@ fn check_position(chunk: &ProtoChunk, cx: i32, cz: i32) -> Result<(), DecodeErro
         Ok(())
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::world::source::ChunkLoadRequest;
+    use crate::world::level::LevelEnvBuilder;
+    use crate::world::anvil::encode::encode_legacy_biome_ids;
+
+    crate::blocks!(TEST_BLOCKS "test" [
+        AIR "air",
+        STONE "stone"
+    ]);
+
+    crate::biomes!(TEST_BIOMES "test" [
+        VOID "void" 0,
+        PLAINS "plains" 1,
+    ]);
+
+    fn get_legacy_block(id: u16, meta: u8) -> Option<&'static BlockState> {
+        match (id, meta) {
+            (0, _) => Some(AIR.get_default_state()),
+            (1, 0) => Some(STONE.get_default_state()),
+            _ => None
+        }
+    }
+
+    fn build_proto_chunk() -> ProtoChunk {
+        build_proto_chunk_with(LevelEnv::builder().with_legacy_blocks(get_legacy_block))
+    }
+
+    fn build_proto_chunk_with(builder: LevelEnvBuilder) -> ProtoChunk {
+        ChunkLoadRequest {
+            env: Arc::new(builder
+                .register_blocks(&TEST_BLOCKS)
+                .register_biomes(&TEST_BIOMES)
+                .build()
+                .unwrap()),
+            height: ChunkHeight::new(-1, 2),
+            cx: 0,
+            cz: 0
+        }.build_proto_chunk()
+    }
+
+    fn build_root(data_version: i32, tag_level: CompoundTag) -> CompoundTag {
+        let mut tag_root = CompoundTag::new();
+        tag_root.insert_i32("DataVersion", data_version);
+        tag_root.insert_compound_tag("Level", tag_level);
+        tag_root
+    }
+
+    #[test]
+    fn decode_schemas() {
+
+        // 1.16 layout with a light only section out of the chunk's height.
+        let mut tag_level = CompoundTag::new();
+        tag_level.insert_i32("xPos", 0);
+        tag_level.insert_i32("zPos", 0);
+        tag_level.insert_str("Status", "full");
+        tag_level.insert_i32_vec("Biomes", (0..1024).map(|i| if i < 64 { 1 } else { 0 }).collect());
+        tag_level.insert_compound_tag_vec("Sections", vec![
+            {
+                let mut tag_section = CompoundTag::new();
+                tag_section.insert_i8("Y", 0);
+                tag_section.insert_compound_tag_vec("Palette", ["test:air", "test:stone"].iter().map(|&name| {
+                    let mut tag_block = CompoundTag::new();
+                    tag_block.insert_str("Name", name);
+                    tag_block
+                }));
+                let mut block_states = vec![0i64; 256];
+                block_states[0] = 1;
+                tag_section.insert_i64_vec("BlockStates", block_states);
+                tag_section
+            },
+            {
+                let mut tag_section = CompoundTag::new();
+                tag_section.insert_i8("Y", 5);
+                tag_section.insert_i8_vec("SkyLight", vec![-1; 2048]);
+                tag_section
+            }
+        ]);
+
+        let mut chunk = build_proto_chunk();
+        decode_chunk(&build_root(2586, tag_level), &mut chunk).unwrap();
+        assert!(matches!(chunk.get_status(), ChunkStatus::Full));
+        assert_eq!(chunk.get_block(0, 0, 0).unwrap(), STONE.get_default_state());
+        assert_eq!(chunk.get_block(1, 0, 0).unwrap(), AIR.get_default_state());
+        assert_eq!(chunk.get_biome_at(0, 0, 0).unwrap(), &PLAINS);
+        assert_eq!(chunk.get_biome_at(0, 16, 0).unwrap(), &VOID);
+        assert_eq!(chunk.get_biome_at(0, -16, 0).unwrap(), &PLAINS);
+
+        // 1.12 layout, numeric ids are flattened with the table of the environment.
+        let mut tag_level = CompoundTag::new();
+        tag_level.insert_i32("xPos", 0);
+        tag_level.insert_i32("zPos", 0);
+        tag_level.insert_bool("TerrainPopulated", true);
+        tag_level.insert_i8_vec("Biomes", vec![1; 256]);
+        let build_section = |block: i8, meta: i8| {
+            let mut tag_section = CompoundTag::new();
+            tag_section.insert_i8("Y", 0);
+            let mut blocks = vec![block; 4096];
+            blocks[0] = 0;
+            tag_section.insert_i8_vec("Blocks", blocks);
+            tag_section.insert_i8_vec("Data", vec![meta; 2048]);
+            tag_section
+        };
+        tag_level.insert_compound_tag_vec("Sections", vec![build_section(0, 0)]);
+
+        let mut chunk = build_proto_chunk();
+        decode_chunk(&build_root(1343, tag_level.clone()), &mut chunk).unwrap();
+        assert!(matches!(chunk.get_status(), ChunkStatus::Full));
+        assert!(chunk.get_sub_chunk(0).is_none());
+        assert_eq!(chunk.get_biome_at(0, 32, 0).unwrap(), &PLAINS);
+        assert_eq!(chunk.get_biome_at(0, -16, 0).unwrap(), &PLAINS);
+        assert_eq!(encode_legacy_biome_ids(&chunk), vec![1; 256]);
+
+        tag_level.insert_compound_tag_vec("Sections", vec![build_section(1, 0)]);
+        let mut chunk = build_proto_chunk();
+        decode_chunk(&build_root(1343, tag_level.clone()), &mut chunk).unwrap();
+        assert_eq!(chunk.get_block(0, 0, 0).unwrap(), AIR.get_default_state());
+        assert_eq!(chunk.get_block(1, 0, 0).unwrap(), STONE.get_default_state());
+        assert_eq!(chunk.get_block(15, 15, 15).unwrap(), STONE.get_default_state());
+
+        // Without flattening table, only air can be decoded from numeric ids.
+        assert!(matches!(decode_chunk(&build_root(1343, tag_level.clone()), &mut build_proto_chunk_with(LevelEnv::builder())), Err(DecodeError::UnsupportedLegacyBlock(1, 0))));
+
+        tag_level.insert_compound_tag_vec("Sections", vec![build_section(1, 0x22)]);
+        assert!(matches!(decode_chunk(&build_root(1343, tag_level), &mut build_proto_chunk()), Err(DecodeError::UnsupportedLegacyBlock(1, 2))));
+
+        assert!(matches!(decode_chunk(&build_root(100, CompoundTag::new()), &mut build_proto_chunk()), Err(DecodeError::UnsupportedDataVersion(100))));
+
+    }
+
+}
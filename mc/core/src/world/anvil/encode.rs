@@ -2,13 +2,19 @@ use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::io::Write;
 
-use crate::util::{PackedArray, PackedIterator};
+use crate::util::{PackedArray, PackedIterator, NbtExt};
 use crate::world::chunk::{Chunk, ChunkStatus};
+use crate::world::level::BaseBlockEntity;
+use crate::entity::GlobalEntities;
 use crate::heightmap::HeightmapType;
 use crate::block::BlockState;
+use crate::debug;
+
+use super::fix::{downgrade_biomes_3d, BIOMES_3D_LEN, SEA_LEVEL_BIOMES_LAYER};
 
 use nbt::encode::write_compound_tag;
 use nbt::CompoundTag;
+use hecs::EntityRef;
 
 
 /// The only supported data version for encoding. Current is `1.18.1`.
@@ -16,13 +22,15 @@ pub const DATA_VERSION: i32 = 2865;
 
 
 /// Decode the NBT data from a reader and delegate chunk decoding to `decode_chunk`.
-pub fn encode_chunk_to_writer(writer: &mut impl Write, chunk: &Chunk) {
+pub fn encode_chunk_to_writer(writer: &mut impl Write, chunk: &Chunk, block_entities: &[CompoundTag]) {
     let mut root = CompoundTag::new();
-    encode_chunk(&mut root, chunk);
+    encode_chunk(&mut root, chunk, block_entities);
     write_compound_tag(writer, &root).unwrap();
 }
 
-pub fn encode_chunk(tag_root: &mut CompoundTag, chunk: &Chunk) {
+/// Encode a chunk to its NBT data, block entities must be already encoded because they are
+/// stored in the level's ECS and not in the chunk, see `encode_block_entity`.
+pub fn encode_chunk(tag_root: &mut CompoundTag, chunk: &Chunk, block_entities: &[CompoundTag]) {
 
     let (cx, cz) = chunk.get_position();
     let height = chunk.get_height();
@@ -48,6 +56,14 @@ pub fn encode_chunk(tag_root: &mut CompoundTag, chunk: &Chunk) {
         ChunkStatus::Full => "full"
     });
 
+    tag_root.insert_i64("InhabitedTime", chunk.get_inhabited_time() as i64);
+
+    if chunk.has_old_noise() {
+        let mut tag_blending = CompoundTag::new();
+        tag_blending.insert_bool("old_noise", true);
+        tag_root.insert_compound_tag("blending_data", tag_blending);
+    }
+
     tag_root.insert_compound_tag_vec("sections", {
 
         let mut biomes_it = chunk.iter_biomes();
@@ -106,7 +122,8 @@ pub fn encode_chunk(tag_root: &mut CompoundTag, chunk: &Chunk) {
 
                     let mut block_states_palette = Vec::new();
 
-                    if let Some(sub_chunk) = chunk.get_sub_chunk(cy) {
+                    // Empty sub chunks are saved like unloaded ones, only with the null block.
+                    if let Some(sub_chunk) = chunk.get_sub_chunk(cy).filter(|sc| !sc.is_empty()) {
 
                         block_states_indices.clear();
                         block_states_tmp.clear();
@@ -124,7 +141,7 @@ pub fn encode_chunk(tag_root: &mut CompoundTag, chunk: &Chunk) {
 
                         if block_states_palette.len() > 1 {
 
-                            let bits = PackedArray::calc_min_byte_size((block_states_tmp.len() - 1) as u64)
+                            let bits = PackedArray::calc_min_byte_size((block_states_palette.len() - 1) as u64)
                                 .max(4); // Minimum byte size of 4
 
                             tag_block_states.insert_i64_vec("data", block_states_tmp.iter()
@@ -140,7 +157,7 @@ pub fn encode_chunk(tag_root: &mut CompoundTag, chunk: &Chunk) {
                         block_states_palette.push(encode_block_state(chunk.get_env().blocks.get_state_from(0).unwrap()));
                     }
 
-                    tag_block_states.insert_str_vec("palette", block_states_palette);
+                    tag_block_states.insert_compound_tag_vec("palette", block_states_palette);
                     tag_block_states
 
                 });
@@ -159,6 +176,32 @@ pub fn encode_chunk(tag_root: &mut CompoundTag, chunk: &Chunk) {
         }
     }
 
+    tag_root.insert_compound_tag_vec("block_entities", block_entities.iter().cloned());
+
+}
+
+/// Encode a block entity, this returns `None` if the given entity is not a block entity.
+/// Codecs that fail to encode their components are ignored.
+pub fn encode_block_entity(entity: &EntityRef, entities: &GlobalEntities) -> Option<CompoundTag> {
+
+    let base_block_entity = entity.get::<BaseBlockEntity>()?;
+    let entity_type = base_block_entity.entity_type;
+
+    let mut tag_block_entity = CompoundTag::new();
+    tag_block_entity.insert_str("id", entity_type.name);
+    tag_block_entity.insert_split_block_pos("x", "y", "z", &base_block_entity.pos);
+    tag_block_entity.insert_bool("keepPacked", false);
+
+    if let Some(entity_codecs) = entities.get_codecs(entity_type) {
+        for &entity_codec in entity_codecs {
+            if let Err(msg) = entity_codec.encode(entity, &mut tag_block_entity) {
+                debug!("Failed to encode block entity component of {}: {}", entity_type.name, msg);
+            }
+        }
+    }
+
+    Some(tag_block_entity)
+
 }
 
 pub fn encode_block_state(state: &'static BlockState) -> CompoundTag {
@@ -183,3 +226,23 @@ pub fn encode_heightmap(chunk: &Chunk, heightmap_type: &'static HeightmapType) -
     let (byte_size, it) = chunk.iter_heightmap_raw_columns(heightmap_type)?;
     Some(it.pack_aligned(byte_size).map(|v| v as i64).collect())
 }
+
+/// Encode the biomes of a chunk as 3D numeric ids from Y 0 to 255, the layout used from
+/// `1.15` to `1.17`, see `fix::BIOMES_3D_LEN`. Cells out of the chunk height take the biome
+/// of the nearest layer of the chunk.
+pub fn encode_biome_ids(chunk: &Chunk) -> Vec<i32> {
+    let height = chunk.get_height();
+    let (min_layer, max_layer) = (height.min as i32 * 4, height.max as i32 * 4 + 3);
+    (0..BIOMES_3D_LEN)
+        .map(|i| {
+            let layer = ((i >> 4) as i32).clamp(min_layer, max_layer);
+            chunk.get_biome((i & 3) as u8, layer, ((i >> 2) & 3) as u8).unwrap().get_id()
+        })
+        .collect()
+}
+
+/// Encode the biomes of a chunk as 2D numeric ids, the layout used up to `1.14`, the 3D
+/// biomes are down-converted from the layer at the sea level, see `fix::downgrade_biomes_3d`.
+pub fn encode_legacy_biome_ids(chunk: &Chunk) -> Vec<i8> {
+    downgrade_biomes_3d(&encode_biome_ids(chunk), SEA_LEVEL_BIOMES_LAYER)
+}
@@ -0,0 +1,145 @@
+//! Data fixes converting chunk data between the layouts of different releases, decoders
+//! apply these steps to older layouts before reading them, and they can be applied in
+//! reverse to produce older layouts.
+
+use std::collections::HashMap;
+
+use crate::block::BlockState;
+
+/// Number of biomes of the 2D layout used up to `1.14`, one per block column.
+pub const BIOMES_2D_LEN: usize = 256;
+/// Number of biomes of the 3D layout used from `1.15` to `1.17`, one per 4x4x4 cell from
+/// Y 0 to 255.
+pub const BIOMES_3D_LEN: usize = 1024;
+/// The layer of 3D biomes used by `downgrade_biomes_3d` when producing 2D biomes, the cells
+/// around the sea level are the closest to the surface biomes of older releases.
+pub const SEA_LEVEL_BIOMES_LAYER: usize = 16;
+
+
+/// A function mapping a legacy block id and metadata, used up to `1.12`, to the block state
+/// it has been flattened to in `1.13`. The flattening table depends on the blocks of the
+/// environment, so it is given by the crate defining them, see `LevelEnvBuilder`.
+pub type LegacyBlockResolver = fn(u16, u8) -> Option<&'static BlockState>;
+
+
+/// Convert 2D biomes indexed by `z << 4 | x` to 3D biomes indexed by `y << 4 | z << 2 | x`,
+/// like vanilla each cell takes the biome at the center of its 4x4 column, and the layer
+/// is replicated vertically. The given slice must contain `BIOMES_2D_LEN` biomes.
+pub fn upgrade_biomes_2d(biomes: &[i8]) -> Vec<i32> {
+
+    debug_assert_eq!(biomes.len(), BIOMES_2D_LEN);
+
+    let layer: Vec<i32> = (0..16)
+        .map(|i| {
+            let (x, z) = ((i & 3) << 2 | 2, (i >> 2) << 2 | 2);
+            biomes[z << 4 | x] as u8 as i32
+        })
+        .collect();
+
+    layer.iter()
+        .copied()
+        .cycle()
+        .take(BIOMES_3D_LEN)
+        .collect()
+
+}
+
+/// Convert 3D biomes to 2D biomes from the cells of the given layer, each block column takes
+/// the biome of its cell. The given slice must contain `BIOMES_3D_LEN` biomes.
+pub fn downgrade_biomes_3d(biomes: &[i32], layer: usize) -> Vec<i8> {
+
+    debug_assert_eq!(biomes.len(), BIOMES_3D_LEN);
+
+    let layer = &biomes[layer * 16..][..16];
+    (0..BIOMES_2D_LEN)
+        .map(|i| {
+            let (x, z) = (i & 15, i >> 4);
+            layer[(z >> 2) << 2 | (x >> 2)] as u8 as i8
+        })
+        .collect()
+
+}
+
+/// Blocks of a section flattened by `flatten_legacy_blocks`, in the layout expected by
+/// `SubChunk::set_blocks_raw`.
+#[derive(Debug)]
+pub struct FlattenedBlocks {
+    pub palette: Vec<&'static BlockState>,
+    /// Index in the palette of each block, indexed by `y << 8 | z << 4 | x`.
+    pub blocks: Vec<usize>
+}
+
+/// Flatten the 4096 legacy blocks of a section, given by the lower 8 bits of their ids, the
+/// optional upper 4 bits and their metadata, both as nibbles. The id and metadata of the
+/// first block that can't be resolved is returned as error.
+pub fn flatten_legacy_blocks(
+    blocks: &[i8],
+    add: Option<&[i8]>,
+    data: &[i8],
+    resolver: LegacyBlockResolver
+) -> Result<FlattenedBlocks, (u16, u8)> {
+
+    let mut palette = Vec::new();
+    let mut palette_indices = HashMap::new();
+    let mut indices = Vec::with_capacity(blocks.len());
+
+    for (i, &block) in blocks.iter().enumerate() {
+        let add = add.map(|add| get_nibble(add, i)).unwrap_or(0);
+        let legacy = ((block as u8 as u16) | ((add as u16) << 8), get_nibble(data, i));
+        let index = match palette_indices.get(&legacy) {
+            Some(&index) => index,
+            None => {
+                palette.push(resolver(legacy.0, legacy.1).ok_or(legacy)?);
+                palette_indices.insert(legacy, palette.len() - 1);
+                palette.len() - 1
+            }
+        };
+        indices.push(index);
+    }
+
+    Ok(FlattenedBlocks {
+        palette,
+        blocks: indices
+    })
+
+}
+
+/// Get a nibble from a slice of nibbles packed by two, the lower one first.
+pub(super) fn get_nibble(slice: &[i8], index: usize) -> u8 {
+    let byte = slice[index >> 1] as u8;
+    if index & 1 == 0 { byte & 0xF } else { byte >> 4 }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn biomes_2d_3d() {
+
+        // Each 4x4 column has its own biome, except its center which is the one sampled.
+        let biomes: Vec<i8> = (0..BIOMES_2D_LEN)
+            .map(|i| {
+                let (x, z) = (i & 15, i >> 4);
+                let cell = ((z >> 2) << 2 | (x >> 2)) as i8;
+                if x & 3 == 2 && z & 3 == 2 { cell } else { -1 }
+            })
+            .collect();
+
+        let upgraded = upgrade_biomes_2d(&biomes);
+        assert_eq!(upgraded.len(), BIOMES_3D_LEN);
+        assert_eq!(&upgraded[..16], &(0..16).collect::<Vec<_>>()[..]);
+        assert_eq!(&upgraded[16 * 63..], &upgraded[..16]);
+
+        let downgraded = downgrade_biomes_3d(&upgraded, SEA_LEVEL_BIOMES_LAYER);
+        assert_eq!(downgraded.len(), BIOMES_2D_LEN);
+        assert_eq!(downgraded[0], 0);
+        assert_eq!(downgraded[15 << 4 | 15], 15);
+        assert_eq!(downgraded[5 << 4 | 9], 6);
+        assert_eq!(upgrade_biomes_2d(&downgraded), upgraded);
+
+    }
+
+}
@@ -0,0 +1,126 @@
+//! Streaming iteration over the chunks stored in the region files of a directory. Region
+//! files are opened one at a time and chunks are read lazily, so that whole worlds can be
+//! walked with a bounded memory usage.
+
+use std::path::{Path, PathBuf};
+use std::vec::IntoIter;
+use std::{fs, io};
+
+use nbt::decode::{read_compound_tag, TagDecodeError};
+use nbt::CompoundTag;
+use thiserror::Error;
+
+use super::region::{RegionFile, RegionError};
+
+
+#[derive(Error, Debug)]
+pub enum ChunkIterError {
+    #[error("Failed to read a region file: {0}")]
+    Region(#[from] RegionError),
+    #[error("The NBT raw data cannot be decoded: {0}")]
+    Nbt(#[from] TagDecodeError)
+}
+
+
+/// The raw data of a chunk read from a region file.
+#[derive(Debug, Clone)]
+pub struct RegionChunk {
+    pub cx: i32,
+    pub cz: i32,
+    pub tag: CompoundTag
+}
+
+
+/// An iterator over all chunks of the region files of a directory, regions are sorted by
+/// their position and chunks are returned in storage order. Errors are returned for chunks
+/// and region files that can't be read, the iteration continues after them.
+pub struct RegionChunkIter {
+    regions_dir: PathBuf,
+    regions: IntoIter<(i32, i32)>,
+    current: Option<(RegionFile, i32, i32)>,
+    index: usize
+}
+
+impl RegionChunkIter {
+
+    /// Iterate over the region files of the given directory, usually the `region` or the
+    /// `entities` sub directory of a level. A missing directory has no chunks.
+    pub fn new<P: AsRef<Path>>(regions_dir: P) -> io::Result<Self> {
+
+        let regions_dir = regions_dir.as_ref().to_path_buf();
+        let mut regions = Vec::new();
+
+        match fs::read_dir(&regions_dir) {
+            Ok(entries) => {
+                for entry in entries {
+                    if let Some(pos) = parse_region_file_name(&entry?.file_name().to_string_lossy()) {
+                        regions.push(pos);
+                    }
+                }
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err)
+        }
+
+        regions.sort_unstable();
+
+        Ok(Self {
+            regions_dir,
+            regions: regions.into_iter(),
+            current: None,
+            index: 0
+        })
+
+    }
+
+}
+
+impl Iterator for RegionChunkIter {
+
+    type Item = Result<RegionChunk, ChunkIterError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+
+        loop {
+
+            if let Some((region, rx, rz)) = &mut self.current {
+                while self.index < 1024 {
+                    let cx = *rx * 32 + (self.index & 31) as i32;
+                    let cz = *rz * 32 + (self.index >> 5) as i32;
+                    self.index += 1;
+                    if region.has_chunk(cx, cz) {
+                        let tag = region.get_chunk_reader(cx, cz)
+                            .map_err(ChunkIterError::from)
+                            .and_then(|mut reader| Ok(read_compound_tag(&mut reader)?));
+                        return Some(tag.map(|tag| RegionChunk { cx, cz, tag }));
+                    }
+                }
+                self.current = None;
+            }
+
+            let (rx, rz) = self.regions.next()?;
+            match RegionFile::new(self.regions_dir.clone(), rx, rz, false) {
+                Ok(region) => {
+                    self.current = Some((region, rx, rz));
+                    self.index = 0;
+                }
+                Err(err) => return Some(Err(err.into()))
+            }
+
+        }
+
+    }
+
+}
+
+
+/// Parse the position of a region from a file name like `r.-1.2.mca`.
+fn parse_region_file_name(name: &str) -> Option<(i32, i32)> {
+    let mut parts = name.strip_prefix("r.")?.strip_suffix(".mca")?.split('.');
+    let rx = parts.next()?.parse().ok()?;
+    let rz = parts.next()?.parse().ok()?;
+    match parts.next() {
+        Some(_) => None,
+        None => Some((rx, rz))
+    }
+}
@@ -1,4 +1,11 @@
 pub mod region;
 pub mod source;
 pub mod decode;
+pub mod schema;
+pub mod fix;
 pub mod encode;
+pub mod ticket;
+pub mod pregen;
+pub mod session;
+pub mod iter;
+pub mod stats;
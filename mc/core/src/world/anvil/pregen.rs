@@ -0,0 +1,360 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::world::source::{LevelGeneratorBuilder, WorkerGenLevelSource, LevelSource, ChunkLoadRequest};
+use crate::world::level::LevelEnv;
+use crate::world::chunk::ChunkHeight;
+
+use super::region::{RegionFile, RegionResult, RegionError, calc_region_pos};
+use super::encode::encode_chunk_to_writer;
+
+
+/// The shape of the area to pre-generate around the center chunk.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PregenShape {
+    /// All chunks at most `radius` chunks away from the center on both axes.
+    Square(u32),
+    /// All chunks at most `radius` chunks away from the center, in euclidean distance.
+    Circle(u32)
+}
+
+impl PregenShape {
+
+    /// Return true if the chunk at the given offset from the center is in this shape.
+    pub fn contains(self, dx: i32, dz: i32) -> bool {
+        match self {
+            PregenShape::Square(radius) => dx.unsigned_abs() <= radius && dz.unsigned_abs() <= radius,
+            PregenShape::Circle(radius) => {
+                let (dx, dz, radius) = (dx as i64, dz as i64, radius as i64);
+                dx * dx + dz * dz <= radius * radius
+            }
+        }
+    }
+
+    /// Return the radius of the square bounding this shape.
+    pub fn get_radius(self) -> u32 {
+        match self {
+            PregenShape::Square(radius) | PregenShape::Circle(radius) => radius
+        }
+    }
+
+    /// Return the positions of all chunks in this shape around the given center, sorted by
+    /// region so region files are completed one after another.
+    pub fn get_chunks(self, cx: i32, cz: i32) -> Vec<(i32, i32)> {
+        let radius = self.get_radius() as i32;
+        let mut chunks = Vec::new();
+        for dz in -radius..=radius {
+            for dx in -radius..=radius {
+                if self.contains(dx, dz) {
+                    chunks.push((cx + dx, cz + dz));
+                }
+            }
+        }
+        chunks.sort_by_key(|&(cx, cz)| {
+            let (rx, rz) = calc_region_pos(cx, cz);
+            (rz, rx, cz, cx)
+        });
+        chunks
+    }
+
+}
+
+
+/// The progress of a pre-generation, given to the progress callback of `Pregenerator::run`.
+#[derive(Debug, Clone, Default)]
+pub struct PregenProgress {
+    /// Number of chunks in the pre-generated shape.
+    pub total: usize,
+    /// Number of chunks skipped because they are already present in region files.
+    pub skipped: usize,
+    /// Number of chunks generated and written to region files.
+    pub generated: usize,
+    /// Number of chunks that failed to generate.
+    pub failed: usize,
+    /// Duration since the first chunk was requested to the generator.
+    pub elapsed: Duration
+}
+
+impl PregenProgress {
+
+    /// Return the number of chunks that are still to be generated.
+    pub fn get_remaining(&self) -> usize {
+        self.total - self.skipped - self.generated - self.failed
+    }
+
+    /// Estimate the remaining duration from the average time taken by chunks processed so
+    /// far, `None` if no chunk has been processed yet.
+    pub fn get_eta(&self) -> Option<Duration> {
+        let processed = (self.generated + self.failed) as u32;
+        if processed == 0 {
+            None
+        } else {
+            Some(self.elapsed / processed * self.get_remaining() as u32)
+        }
+    }
+
+    #[inline]
+    pub fn is_done(&self) -> bool {
+        self.get_remaining() == 0
+    }
+
+}
+
+
+/// A region file being pre-generated, with the number of chunks still to be written to it.
+struct PregenRegion {
+    file: Option<RegionFile>,
+    remaining: usize
+}
+
+
+/// A chunk pre-generator that generates all chunks of a shape around a center and writes
+/// them straight to region files, without going through a level. Chunks already present in
+/// region files are skipped, so an interrupted pre-generation can be resumed by running it
+/// again.
+///
+/// Entities of generated chunks are not saved, only the terrain is.
+pub struct Pregenerator<B> {
+    /// The level directory, region files are written in its `region` sub directory.
+    dir: PathBuf,
+    env: Arc<LevelEnv>,
+    height: ChunkHeight,
+    generator_builder: B,
+    center: (i32, i32),
+    shape: PregenShape,
+    workers_count: usize
+}
+
+impl<B> Pregenerator<B>
+where
+    B: LevelGeneratorBuilder + Send + Sync + 'static
+{
+
+    /// Create a pre-generator for the given level directory, by default only the chunk at
+    /// the origin is generated, with one worker thread per available CPU.
+    pub fn new<P: AsRef<Path>>(dir: P, env: Arc<LevelEnv>, height: ChunkHeight, generator_builder: B) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            env,
+            height,
+            generator_builder,
+            center: (0, 0),
+            shape: PregenShape::Square(0),
+            workers_count: std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+        }
+    }
+
+    pub fn with_center(mut self, cx: i32, cz: i32) -> Self {
+        self.center = (cx, cz);
+        self
+    }
+
+    pub fn with_shape(mut self, shape: PregenShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    pub fn with_workers_count(mut self, workers_count: usize) -> Self {
+        self.workers_count = workers_count.max(1);
+        self
+    }
+
+    /// Run the pre-generation, blocking until all chunks are generated. The callback is called
+    /// once missing chunks are known and then after each chunk processed. The final progress
+    /// is returned, chunks that failed to generate are only counted, but errors of region
+    /// files abort the pre-generation.
+    pub fn run<F>(self, mut callback: F) -> RegionResult<PregenProgress>
+    where
+        F: FnMut(&PregenProgress)
+    {
+
+        let regions_dir = self.dir.join("region");
+        let chunks = self.shape.get_chunks(self.center.0, self.center.1);
+
+        let mut progress = PregenProgress {
+            total: chunks.len(),
+            ..Default::default()
+        };
+
+        let mut regions: HashMap<(i32, i32), PregenRegion> = HashMap::new();
+        let mut pending = Vec::new();
+        let mut last_region_pos = None;
+
+        for (cx, cz) in chunks {
+
+            // Chunks are sorted by region, so we can close the file of the previous region,
+            // it is opened again when its first chunk is written.
+            let (rx, rz) = calc_region_pos(cx, cz);
+            if let Some(last_region_pos) = last_region_pos.replace((rx, rz)) {
+                if last_region_pos != (rx, rz) {
+                    regions.get_mut(&last_region_pos).unwrap().file = None;
+                }
+            }
+
+            let region = match regions.entry((rx, rz)) {
+                Entry::Occupied(o) => o.into_mut(),
+                Entry::Vacant(v) => {
+                    let file = match RegionFile::new(regions_dir.clone(), rx, rz, false) {
+                        Ok(file) => Some(file),
+                        Err(RegionError::FileNotFound(_)) => None,
+                        Err(e) => return Err(e)
+                    };
+                    v.insert(PregenRegion { file, remaining: 0 })
+                }
+            };
+
+            if region.file.as_ref().is_some_and(|file| file.has_chunk(cx, cz)) {
+                progress.skipped += 1;
+            } else {
+                region.remaining += 1;
+                pending.push((cx, cz));
+            }
+
+        }
+
+        regions.retain(|_, region| {
+            region.file = None;
+            region.remaining != 0
+        });
+
+        callback(&progress);
+
+        if pending.is_empty() {
+            return Ok(progress);
+        }
+
+        let start = Instant::now();
+        let mut source = WorkerGenLevelSource::new(self.generator_builder, self.workers_count);
+
+        for &(cx, cz) in &pending {
+            // SAFETY: Unwrap should be safe because this source never rejects requests.
+            source.request_chunk_load(ChunkLoadRequest {
+                env: Arc::clone(&self.env),
+                height: self.height,
+                cx,
+                cz
+            }).unwrap();
+        }
+
+        for _ in 0..pending.len() {
+
+            let (cx, cz) = match source.recv_chunk() {
+                Some(Ok(chunk)) => {
+
+                    let (cx, cz) = chunk.get_position();
+                    let (rx, rz) = calc_region_pos(cx, cz);
+                    // SAFETY: Unwrap should be safe because all pending chunks have a region.
+                    let region = regions.get_mut(&(rx, rz)).unwrap();
+
+                    let file = match &mut region.file {
+                        Some(file) => file,
+                        file => file.insert(RegionFile::new(regions_dir.clone(), rx, rz, true)?)
+                    };
+
+                    let mut writer = file.get_chunk_writer(cx, cz, Default::default());
+                    encode_chunk_to_writer(&mut writer, &chunk, &[]);
+                    writer.write_chunk()?;
+
+                    progress.generated += 1;
+                    (cx, cz)
+
+                },
+                Some(Err((_, req))) => {
+                    progress.failed += 1;
+                    (req.cx, req.cz)
+                },
+                None => break
+            };
+
+            let region_pos = calc_region_pos(cx, cz);
+            if let Entry::Occupied(mut o) = regions.entry(region_pos) {
+                o.get_mut().remaining -= 1;
+                if o.get().remaining == 0 {
+                    o.remove();
+                }
+            }
+
+            progress.elapsed = start.elapsed();
+            callback(&progress);
+
+        }
+
+        Ok(progress)
+
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::world::source::SuperFlatGenerator;
+    use crate::world::anvil::decode::decode_chunk_from_reader;
+
+    crate::blocks!(TEST_BLOCKS "test" [
+        AIR "air",
+        STONE "stone"
+    ]);
+
+    crate::biomes!(TEST_BIOMES "test" [
+        VOID "void" 0,
+    ]);
+
+    #[test]
+    fn pregenerate() {
+
+        let dir = std::env::temp_dir().join(format!("mc-core-pregen-{}", std::process::id()));
+        let env = Arc::new(LevelEnv::builder()
+            .register_blocks(&TEST_BLOCKS)
+            .register_biomes(&TEST_BIOMES)
+            .build()
+            .unwrap());
+        let height = ChunkHeight::new(0, 3);
+
+        let mut generator = SuperFlatGenerator::new();
+        generator.add_layer(STONE.get_default_state(), 0, 2);
+
+        // This circle overlaps two regions.
+        let mut calls = 0;
+        let progress = Pregenerator::new(&dir, Arc::clone(&env), height, generator.clone())
+            .with_center(31, 0)
+            .with_shape(PregenShape::Circle(2))
+            .with_workers_count(2)
+            .run(|_| calls += 1)
+            .unwrap();
+
+        assert_eq!(progress.total, 13);
+        assert_eq!(progress.generated, 13);
+        assert!(progress.is_done());
+        assert_eq!(calls, 14);
+
+        let mut region = RegionFile::new(dir.join("region"), 1, 0, false).unwrap();
+        assert!(region.has_chunk(33, 0));
+        assert!(!region.has_chunk(33, 1));
+
+        let mut chunk = ChunkLoadRequest { env: Arc::clone(&env), height, cx: 33, cz: 0 }.build_proto_chunk();
+        decode_chunk_from_reader(&mut region.get_chunk_reader(33, 0).unwrap(), &mut chunk).unwrap();
+        assert_eq!(chunk.get_block(0, 1, 0).unwrap(), STONE.get_default_state());
+        assert_eq!(chunk.get_block(0, 2, 0).unwrap(), AIR.get_default_state());
+
+        // Resuming with a larger shape only generates missing chunks.
+        let progress = Pregenerator::new(&dir, env, height, generator)
+            .with_center(31, 0)
+            .with_shape(PregenShape::Square(2))
+            .run(|_| {})
+            .unwrap();
+
+        assert_eq!(progress.total, 25);
+        assert_eq!(progress.skipped, 13);
+        assert_eq!(progress.generated, 12);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+    }
+
+}
@@ -0,0 +1,251 @@
+//! Typed views of the chunk NBT layouts used by different releases of the game. Each layout
+//! borrows its data from the root compound tag, it only checks the structure of the chunk
+//! and doesn't resolve any block or biome, this is done by the decoder.
+
+use std::ops::RangeInclusive;
+
+use nbt::CompoundTag;
+
+use crate::util::NbtExt;
+
+use super::decode::DecodeError;
+
+
+/// Data versions using the `1.12` layout, numeric block ids in a `Level` compound.
+pub const R112_DATA_VERSIONS: RangeInclusive<i32> = 1139..=1343;
+/// Data versions using the `1.16` layout, block states palettes in a `Level` compound.
+pub const R116_DATA_VERSIONS: RangeInclusive<i32> = 2566..=2586;
+/// Data versions using the `1.18` layout, paletted blocks and biomes at the root.
+pub const R118_DATA_VERSIONS: RangeInclusive<i32> = 2860..=2865;
+
+
+/// The chunk NBT layout selected from its data version.
+#[derive(Debug)]
+pub enum ChunkSchema<'a> {
+    R112(R112Chunk<'a>),
+    R116(R116Chunk<'a>),
+    R118(R118Chunk<'a>)
+}
+
+impl<'a> ChunkSchema<'a> {
+
+    /// Read the data version of the given chunk root tag and parse it with the matching
+    /// layout, returning `UnsupportedDataVersion` if no layout is known for it.
+    pub fn from_nbt(tag_root: &'a CompoundTag) -> Result<Self, DecodeError> {
+        let data_version = tag_root.get_i32("DataVersion")?;
+        if R118_DATA_VERSIONS.contains(&data_version) {
+            Ok(Self::R118(R118Chunk::from_nbt(tag_root)?))
+        } else if R116_DATA_VERSIONS.contains(&data_version) {
+            Ok(Self::R116(R116Chunk::from_nbt(tag_root.get_compound_tag("Level")?)?))
+        } else if R112_DATA_VERSIONS.contains(&data_version) {
+            Ok(Self::R112(R112Chunk::from_nbt(tag_root.get_compound_tag("Level")?)?))
+        } else {
+            Err(DecodeError::UnsupportedDataVersion(data_version))
+        }
+    }
+
+}
+
+
+/// Chunk layout up to `1.12.2`, fields are stored in the `Level` compound.
+#[derive(Debug)]
+pub struct R112Chunk<'a> {
+    pub cx: i32,
+    pub cz: i32,
+    pub terrain_populated: bool,
+    pub inhabited_time: i64,
+    /// 2D biomes of numeric ids, 256 bytes indexed by `z * 16 + x`.
+    pub biomes: Option<&'a [i8]>,
+    pub sections: Vec<R112Section<'a>>,
+    pub tile_entities: Vec<&'a CompoundTag>
+}
+
+/// Section of a `1.12` chunk, blocks are stored as numeric ids and metadata nibbles.
+#[derive(Debug)]
+pub struct R112Section<'a> {
+    pub y: i8,
+    /// Lower 8 bits of the 4096 block ids, indexed by `y << 8 | z << 4 | x`.
+    pub blocks: &'a [i8],
+    /// Optional upper 4 bits of block ids, as nibbles.
+    pub add: Option<&'a [i8]>,
+    /// Block metadata, as nibbles.
+    pub data: &'a [i8],
+    pub block_light: Option<&'a [i8]>,
+    pub sky_light: Option<&'a [i8]>
+}
+
+impl<'a> R112Chunk<'a> {
+
+    pub fn from_nbt(tag_level: &'a CompoundTag) -> Result<Self, DecodeError> {
+
+        let mut sections = Vec::new();
+        for tag_section in tag_level.get_compound_tag_vec("Sections")? {
+            sections.push(R112Section {
+                y: tag_section.get_i8("Y")?,
+                blocks: check_len(tag_section.get_i8_vec("Blocks")?, 4096, "Blocks")?,
+                add: tag_section.get_i8_vec("Add").ok().map(|add| check_len(add, 2048, "Add")).transpose()?,
+                data: check_len(tag_section.get_i8_vec("Data")?, 2048, "Data")?,
+                block_light: tag_section.get_i8_vec("BlockLight").ok().map(|v| &v[..]),
+                sky_light: tag_section.get_i8_vec("SkyLight").ok().map(|v| &v[..])
+            });
+        }
+
+        Ok(Self {
+            cx: tag_level.get_i32("xPos")?,
+            cz: tag_level.get_i32("zPos")?,
+            terrain_populated: tag_level.get_bool_or("TerrainPopulated", false),
+            inhabited_time: tag_level.get_i64_or("InhabitedTime", 0),
+            biomes: tag_level.get_i8_vec("Biomes").ok().map(|biomes| check_len(biomes, 256, "Biomes")).transpose()?,
+            sections,
+            tile_entities: tag_level.get_compound_tag_vec("TileEntities").unwrap_or_default()
+        })
+
+    }
+
+}
+
+
+/// Chunk layout of `1.16`, fields are stored in the `Level` compound.
+#[derive(Debug)]
+pub struct R116Chunk<'a> {
+    pub cx: i32,
+    pub cz: i32,
+    pub status: &'a str,
+    pub inhabited_time: i64,
+    /// 3D biomes of numeric ids, 1024 integers indexed by `y << 4 | z << 2 | x` for each
+    /// 4x4x4 cell from Y 0 to 255.
+    pub biomes: Option<&'a [i32]>,
+    pub sections: Vec<R116Section<'a>>,
+    pub tile_entities: Vec<&'a CompoundTag>
+}
+
+/// Section of a `1.16` chunk, the block states palette and data are absent from sections
+/// only containing light.
+#[derive(Debug)]
+pub struct R116Section<'a> {
+    pub y: i8,
+    pub palette: Option<Vec<&'a CompoundTag>>,
+    /// Block states indices packed without spanning between longs.
+    pub block_states: Option<&'a [i64]>,
+    pub block_light: Option<&'a [i8]>,
+    pub sky_light: Option<&'a [i8]>
+}
+
+impl<'a> R116Chunk<'a> {
+
+    pub fn from_nbt(tag_level: &'a CompoundTag) -> Result<Self, DecodeError> {
+
+        let mut sections = Vec::new();
+        for tag_section in tag_level.get_compound_tag_vec("Sections")? {
+            sections.push(R116Section {
+                y: tag_section.get_i8("Y")?,
+                palette: tag_section.get_compound_tag_vec("Palette").ok(),
+                block_states: tag_section.get_i64_vec("BlockStates").ok().map(|v| &v[..]),
+                block_light: tag_section.get_i8_vec("BlockLight").ok().map(|v| &v[..]),
+                sky_light: tag_section.get_i8_vec("SkyLight").ok().map(|v| &v[..])
+            });
+        }
+
+        Ok(Self {
+            cx: tag_level.get_i32("xPos")?,
+            cz: tag_level.get_i32("zPos")?,
+            status: tag_level.get_str("Status")?,
+            inhabited_time: tag_level.get_i64_or("InhabitedTime", 0),
+            biomes: tag_level.get_i32_vec("Biomes").ok().map(|biomes| check_len(biomes, 1024, "Biomes")).transpose()?,
+            sections,
+            tile_entities: tag_level.get_compound_tag_vec("TileEntities").unwrap_or_default()
+        })
+
+    }
+
+}
+
+
+/// Chunk layout since `1.18`, fields are stored in the root compound.
+#[derive(Debug)]
+pub struct R118Chunk<'a> {
+    pub cx: i32,
+    pub cy: i32,
+    pub cz: i32,
+    pub status: &'a str,
+    pub inhabited_time: i64,
+    /// True if the chunk has been upgraded from an older layout, from `blending_data`.
+    pub old_noise: bool,
+    pub sections: Vec<R118Section<'a>>,
+    pub block_entities: Vec<&'a CompoundTag>
+}
+
+/// Section of a `1.18` chunk, with both blocks and biomes in paletted containers.
+#[derive(Debug)]
+pub struct R118Section<'a> {
+    pub y: i8,
+    pub biomes: Option<PalettedData<'a, &'a str>>,
+    pub block_states: Option<PalettedData<'a, &'a CompoundTag>>,
+    pub block_light: Option<&'a [i8]>,
+    pub sky_light: Option<&'a [i8]>
+}
+
+/// A palette with its packed indices, data is absent when the palette has a single value.
+#[derive(Debug)]
+pub struct PalettedData<'a, T> {
+    pub palette: Vec<T>,
+    pub data: Option<&'a [i64]>
+}
+
+impl<'a> R118Chunk<'a> {
+
+    pub fn from_nbt(tag_root: &'a CompoundTag) -> Result<Self, DecodeError> {
+
+        let mut sections = Vec::new();
+        for tag_section in tag_root.get_compound_tag_vec("sections")? {
+
+            let biomes = match tag_section.get_compound_tag("biomes") {
+                Ok(tag_biomes) => Some(PalettedData {
+                    palette: tag_biomes.get_str_vec("palette")?,
+                    data: tag_biomes.get_i64_vec("data").ok().map(|v| &v[..])
+                }),
+                Err(_) => None
+            };
+
+            let block_states = match tag_section.get_compound_tag("block_states") {
+                Ok(tag_block_states) => Some(PalettedData {
+                    palette: tag_block_states.get_compound_tag_vec("palette")?,
+                    data: tag_block_states.get_i64_vec("data").ok().map(|v| &v[..])
+                }),
+                Err(_) => None
+            };
+
+            sections.push(R118Section {
+                y: tag_section.get_i8("Y")?,
+                biomes,
+                block_states,
+                block_light: tag_section.get_i8_vec("BlockLight").ok().map(|v| &v[..]),
+                sky_light: tag_section.get_i8_vec("SkyLight").ok().map(|v| &v[..])
+            });
+
+        }
+
+        Ok(Self {
+            cx: tag_root.get_i32("xPos")?,
+            cy: tag_root.get_i32("yPos")?,
+            cz: tag_root.get_i32("zPos")?,
+            status: tag_root.get_str("Status")?,
+            inhabited_time: tag_root.get_i64_or("InhabitedTime", 0),
+            old_noise: tag_root.get_compound_tag("blending_data")
+                .is_ok_and(|tag_blending| tag_blending.get_bool_or("old_noise", false)),
+            sections,
+            block_entities: tag_root.get_compound_tag_vec("block_entities").unwrap_or_default()
+        })
+
+    }
+
+}
+
+
+fn check_len<'a, T>(vec: &'a [T], len: usize, name: &str) -> Result<&'a [T], DecodeError> {
+    if vec.len() == len {
+        Ok(vec)
+    } else {
+        Err(DecodeError::Malformed(format!("Invalid '{}' length, expected {}, got {}.", name, len, vec.len())))
+    }
+}
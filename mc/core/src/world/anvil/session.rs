@@ -0,0 +1,130 @@
+//! Ownership of a level directory through its `session.lock` file. Like vanilla since 1.16
+//! the file is locked by the operating system, so that two processes can't open the same
+//! level. Like older releases, the time of opening is also written in the file, releases
+//! that don't lock the file overwrite it when opening the level, which is detected as a
+//! takeover of the session.
+
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use thiserror::Error;
+
+
+#[derive(Error, Debug)]
+pub enum SessionLockError {
+    #[error("The level at {0} is already opened by another process.")]
+    AlreadyLocked(PathBuf),
+    #[error("The level at {0} has been opened by another process since this session started.")]
+    TakenOver(PathBuf),
+    #[error("Failed to access the session lock file: {0}")]
+    Io(#[from] io::Error)
+}
+
+
+/// The lock of a level directory, the lock is released when dropped.
+#[derive(Debug)]
+pub struct SessionLock {
+    path: PathBuf,
+    file: File,
+    timestamp: i64
+}
+
+impl SessionLock {
+
+    /// Lock the level in the given directory, the directory is created if missing. An error
+    /// is returned if the level is already locked, by this process or another one.
+    pub fn acquire<P: AsRef<Path>>(dir: P) -> Result<Self, SessionLockError> {
+
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+
+        let path = dir.join("session.lock");
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => return Err(SessionLockError::AlreadyLocked(dir.to_path_buf())),
+            Err(TryLockError::Error(err)) => return Err(err.into())
+        }
+
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_millis() as i64);
+
+        file.set_len(0)?;
+        file.write_all(&timestamp.to_be_bytes())?;
+        file.sync_all()?;
+
+        Ok(Self { path, file, timestamp })
+
+    }
+
+    /// The time this session started at, in milliseconds since the Unix epoch.
+    pub fn get_timestamp(&self) -> i64 {
+        self.timestamp
+    }
+
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Check that the session is still owned, it must be checked before saving data to the
+    /// level, the level must no longer be saved after a takeover.
+    pub fn check(&self) -> Result<(), SessionLockError> {
+
+        let mut file = &self.file;
+        let mut buf = [0; 8];
+        file.seek(SeekFrom::Start(0))?;
+
+        match file.read_exact(&mut buf) {
+            Ok(()) if i64::from_be_bytes(buf) == self.timestamp => Ok(()),
+            Ok(()) => Err(self.taken_over()),
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => Err(self.taken_over()),
+            Err(err) => Err(err.into())
+        }
+
+    }
+
+    fn taken_over(&self) -> SessionLockError {
+        let dir = self.path.parent().unwrap_or(&self.path);
+        SessionLockError::TakenOver(dir.to_path_buf())
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn session_lock() {
+
+        let dir = std::env::temp_dir().join(format!("mc-core-session-lock-{}", std::process::id()));
+
+        let lock = SessionLock::acquire(&dir).unwrap();
+        assert_eq!(fs::read(lock.get_path()).unwrap(), lock.get_timestamp().to_be_bytes());
+        assert!(lock.check().is_ok());
+        assert!(matches!(SessionLock::acquire(&dir), Err(SessionLockError::AlreadyLocked(_))));
+
+        // An older release overwriting the timestamp without locking.
+        fs::write(lock.get_path(), (lock.get_timestamp() + 1).to_be_bytes()).unwrap();
+        assert!(matches!(lock.check(), Err(SessionLockError::TakenOver(_))));
+
+        drop(lock);
+        let lock = SessionLock::acquire(&dir).unwrap();
+        assert!(lock.check().is_ok());
+
+        drop(lock);
+        fs::remove_dir_all(&dir).unwrap();
+
+    }
+
+}
@@ -3,6 +3,7 @@ use std::time::{Instant, Duration};
 use std::path::{PathBuf, Path};
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use crossbeam_channel::{Sender, Receiver, RecvTimeoutError, unbounded, bounded};
 
@@ -13,11 +14,14 @@ use crate::debug;
 use super::region::{RegionFile, RegionResult, RegionError, calc_region_pos};
 use super::decode::{decode_chunk_from_reader};
 use super::encode::{encode_chunk_to_writer};
+use super::session::{SessionLock, SessionLockError};
 
 
 enum Request {
     Load(ChunkLoadRequest),
-    Save(ChunkSaveRequest)
+    Save(ChunkSaveRequest),
+    /// Answered once all previous requests are processed.
+    Flush(Sender<()>)
 }
 
 
@@ -26,12 +30,27 @@ enum Request {
 /// remains opened for `REGIONS_CACHE_TIME` duration.
 pub struct AnvilLevelSource {
     request_sender: Sender<Request>,
-    result_receiver: Receiver<Result<ProtoChunk, (LevelSourceError, ChunkLoadRequest)>>
+    result_receiver: Receiver<Result<ProtoChunk, (LevelSourceError, ChunkLoadRequest)>>,
+    session: Option<Arc<SessionLock>>
 }
 
 impl AnvilLevelSource {
 
+    /// Create a source for the level in the given directory without owning its session,
+    /// prefer `open` if chunks are saved to the level.
     pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self::new_internal(dir.as_ref(), None)
+    }
+
+    /// Open the level in the given directory and own its session until the source is
+    /// dropped, an error is returned if the level is already opened by another process.
+    /// Chunks are no longer saved if another process takes over the session.
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self, SessionLockError> {
+        let session = SessionLock::acquire(dir.as_ref())?;
+        Ok(Self::new_internal(dir.as_ref(), Some(Arc::new(session))))
+    }
+
+    fn new_internal(dir: &Path, session: Option<Arc<SessionLock>>) -> Self {
 
         let (
             request_sender,
@@ -39,17 +58,24 @@ impl AnvilLevelSource {
         ) = unbounded();
 
         let result_receiver = Worker::new(
-            dir.as_ref().join("region"),
-            request_receiver
+            dir.join("region"),
+            request_receiver,
+            session.clone()
         );
 
         Self {
             request_sender,
-            result_receiver
+            result_receiver,
+            session
         }
 
     }
 
+    /// Return the session lock of the level if opened with `open`.
+    pub fn get_session(&self) -> Option<&SessionLock> {
+        self.session.as_deref()
+    }
+
 }
 
 impl LevelSource for AnvilLevelSource {
@@ -69,6 +95,16 @@ impl LevelSource for AnvilLevelSource {
         Ok(())
     }
 
+    fn flush(&mut self) -> Result<(), LevelSourceError> {
+        let (done_sender, done_receiver) = bounded(1);
+        self.request_sender.send(Request::Flush(done_sender)).unwrap();
+        done_receiver.recv().map_err(LevelSourceError::new_custom)?;
+        match &self.session {
+            Some(session) => session.check().map_err(LevelSourceError::new_custom),
+            None => Ok(())
+        }
+    }
+
 }
 
 
@@ -80,7 +116,10 @@ struct Worker {
     request_receiver: Receiver<Request>,
     result_sender: Sender<Result<ProtoChunk, (LevelSourceError, ChunkLoadRequest)>>,
     regions: HashMap<(i32, i32), TimedCache<RegionFile>>,
-    last_cache_check: Instant
+    last_cache_check: Instant,
+    session: Option<Arc<SessionLock>>,
+    /// Set once the session has been taken over, chunks are no longer saved.
+    session_lost: bool
 }
 
 impl Worker {
@@ -88,7 +127,8 @@ impl Worker {
     /// Internal constructor for worker, you must give the regions directory, not level directory.
     fn new(
         regions_dir: PathBuf,
-        request_receiver: Receiver<Request>
+        request_receiver: Receiver<Request>,
+        session: Option<Arc<SessionLock>>
     ) -> Receiver<Result<ProtoChunk, (LevelSourceError, ChunkLoadRequest)>> {
 
         let (
@@ -101,7 +141,9 @@ impl Worker {
             request_receiver,
             result_sender,
             regions: HashMap::new(),
-            last_cache_check: Instant::now()
+            last_cache_check: Instant::now(),
+            session,
+            session_lost: false
         };
 
         ThreadBuilder::new()
@@ -130,6 +172,9 @@ impl Worker {
                     debug!("Received chunk save request for {}/{}", req.cx, req.cz);
                     self.save_chunk(req);
                 }
+                Ok(Request::Flush(done_sender)) => {
+                    let _ = done_sender.send(());
+                }
                 Err(RecvTimeoutError::Timeout) => {},
                 Err(RecvTimeoutError::Disconnected) => break
             }
@@ -180,6 +225,10 @@ impl Worker {
 
     fn save_chunk(&mut self, req: ChunkSaveRequest) {
 
+        if !self.check_session() {
+            return;
+        }
+
         let chunk = req.chunk.read().unwrap();
         let (cx, cz) = chunk.get_position();
         let (rx, rz) = calc_region_pos(cx, cz);
@@ -190,12 +239,23 @@ impl Worker {
         };
 
         let mut writer = region.get_chunk_writer(cx, cz, Default::default());
-        encode_chunk_to_writer(&mut writer, &*chunk);
+        encode_chunk_to_writer(&mut writer, &*chunk, &req.block_entities);
         writer.write_chunk().unwrap();
         // debug!("Chunk at {}/{} saved", cx, cz);
 
     }
 
+    /// Check that the session is still owned before saving, like vanilla.
+    fn check_session(&mut self) -> bool {
+        if !self.session_lost {
+            if let Some(Err(err)) = self.session.as_ref().map(|session| session.check()) {
+                debug!("Chunks are no longer saved: {}", err);
+                self.session_lost = true;
+            }
+        }
+        !self.session_lost
+    }
+
     fn check_cache(&mut self) {
         if self.last_cache_check.elapsed() >= REGIONS_CACHE_TIME {
             self.regions.retain(|(rx, rz), region| {
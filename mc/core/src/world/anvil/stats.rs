@@ -0,0 +1,307 @@
+//! Aggregate statistics of the chunks of a level directory: block states histogram, entity
+//! counts per type, structure starts per kind and the range of built heights.
+//!
+//! Chunks are decoded with the level environment to count blocks, while entities and
+//! structure starts are counted from the raw chunk data, so that entities and structures
+//! unknown to the environment are also counted.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::io;
+
+use nbt::{CompoundTag, Tag};
+
+use crate::block::{Block, BlockState, BlockStateKey};
+use crate::world::chunk::{Chunk, ChunkHeight};
+use crate::world::level::LevelEnv;
+use crate::world::source::ChunkLoadRequest;
+
+use super::iter::{RegionChunkIter, RegionChunk};
+use super::decode::decode_chunk;
+
+
+/// Statistics of the chunks of a level.
+#[derive(Clone, Default)]
+pub struct WorldStats {
+    chunks_count: u64,
+    failed_chunks_count: u64,
+    block_states: HashMap<BlockStateKey, u64>,
+    entities: HashMap<String, u64>,
+    structure_starts: HashMap<String, u64>,
+    built_heights: Option<(i32, i32)>
+}
+
+impl WorldStats {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Walk all chunks of the level in the given directory, chunks are decoded with the
+    /// given environment and height. Entities are also read from the `entities` directory
+    /// used since 1.17. Chunks that can't be read or decoded are counted as failed.
+    pub fn from_level_dir<P: AsRef<Path>>(dir: P, env: &Arc<LevelEnv>, height: ChunkHeight) -> io::Result<Self> {
+
+        let dir = dir.as_ref();
+        let mut stats = Self::new();
+
+        for chunk in RegionChunkIter::new(dir.join("region"))? {
+            match chunk {
+                Ok(chunk) => stats.add_chunk_data(env, height, &chunk),
+                Err(_) => stats.failed_chunks_count += 1
+            }
+        }
+
+        for chunk in RegionChunkIter::new(dir.join("entities"))? {
+            match chunk {
+                Ok(chunk) => stats.add_entities_data(&chunk.tag),
+                Err(_) => stats.failed_chunks_count += 1
+            }
+        }
+
+        Ok(stats)
+
+    }
+
+    /// Add the raw data of a chunk to these statistics, the chunk is decoded to count its
+    /// blocks, entities and structure starts are counted even if decoding fails.
+    pub fn add_chunk_data(&mut self, env: &Arc<LevelEnv>, height: ChunkHeight, chunk: &RegionChunk) {
+
+        // Chunks before 1.18 store their data in a 'Level' compound.
+        let tag_chunk = chunk.tag.get_compound_tag("Level").unwrap_or(&chunk.tag);
+        self.add_entities_data(tag_chunk);
+
+        let tag_starts = tag_chunk.get_compound_tag("Structures")
+            .or_else(|_| tag_chunk.get_compound_tag("structures"))
+            .and_then(|tag_structures| tag_structures.get_compound_tag("Starts")
+                .or_else(|_| tag_structures.get_compound_tag("starts")));
+
+        if let Ok(tag_starts) = tag_starts {
+            for (name, tag_start) in tag_starts.iter() {
+                if let Tag::Compound(tag_start) = tag_start {
+                    // Chunks without the structure have an invalid start.
+                    if tag_start.get_str("id").unwrap_or("INVALID") != "INVALID" {
+                        *self.structure_starts.entry(name.clone()).or_default() += 1;
+                    }
+                }
+            }
+        }
+
+        let mut proto_chunk = ChunkLoadRequest {
+            env: Arc::clone(env),
+            height,
+            cx: chunk.cx,
+            cz: chunk.cz
+        }.build_proto_chunk();
+
+        match decode_chunk(&chunk.tag, &mut proto_chunk) {
+            Ok(()) => self.add_chunk(&proto_chunk),
+            Err(_) => self.failed_chunks_count += 1
+        }
+
+    }
+
+    /// Add the blocks of a decoded chunk to these statistics, null blocks (air in vanilla)
+    /// are not counted.
+    pub fn add_chunk(&mut self, chunk: &Chunk) {
+
+        self.chunks_count += 1;
+
+        let null_block = chunk.get_env().blocks.get_state_from(0);
+        for (cy, sub_chunk) in chunk.iter_loaded_sub_chunks() {
+            if sub_chunk.is_empty() {
+                continue;
+            }
+            for (i, state) in sub_chunk.iter_blocks().enumerate() {
+                if null_block.is_some_and(|null_block| std::ptr::eq(state, null_block)) {
+                    continue;
+                }
+                *self.block_states.entry(state.get_key()).or_default() += 1;
+                let y = cy as i32 * 16 + (i >> 8) as i32;
+                self.built_heights = Some(match self.built_heights {
+                    Some((min, max)) => (min.min(y), max.max(y)),
+                    None => (y, y)
+                });
+            }
+        }
+
+    }
+
+    /// Add the entities of the given compound to these statistics, this can be a chunk
+    /// compound of releases before 1.17 or the root compound of an entities file, riding
+    /// passengers are also counted.
+    pub fn add_entities_data(&mut self, tag: &CompoundTag) {
+        if let Ok(tag_entities) = tag.get_compound_tag_vec("Entities") {
+            self.add_entities(&tag_entities);
+        }
+    }
+
+    fn add_entities(&mut self, tag_entities: &[&CompoundTag]) {
+        for tag_entity in tag_entities {
+            if let Ok(id) = tag_entity.get_str("id") {
+                *self.entities.entry(id.to_string()).or_default() += 1;
+            }
+            if let Ok(tag_passengers) = tag_entity.get_compound_tag_vec("Passengers") {
+                self.add_entities(&tag_passengers);
+            }
+        }
+    }
+
+    /// Number of chunks successfully decoded.
+    pub fn get_chunks_count(&self) -> u64 {
+        self.chunks_count
+    }
+
+    /// Number of chunks that couldn't be read or decoded.
+    pub fn get_failed_chunks_count(&self) -> u64 {
+        self.failed_chunks_count
+    }
+
+    pub fn get_block_state_count(&self, state: &'static BlockState) -> u64 {
+        self.block_states.get(&state.get_key()).copied().unwrap_or(0)
+    }
+
+    /// Return the count of all states of the given block.
+    pub fn get_block_count(&self, block: &'static Block) -> u64 {
+        self.iter_block_states()
+            .filter(|(state, _)| state.is_block(block))
+            .map(|(_, count)| count)
+            .sum()
+    }
+
+    /// Iterate over the block states histogram, in no particular order.
+    pub fn iter_block_states(&self) -> impl Iterator<Item = (&'static BlockState, u64)> + '_ {
+        self.block_states.iter().map(|(state, &count)| (state.inner(), count))
+    }
+
+    pub fn get_entity_count(&self, id: &str) -> u64 {
+        self.entities.get(id).copied().unwrap_or(0)
+    }
+
+    /// Iterate over the entity counts per entity ID, in no particular order.
+    pub fn iter_entities(&self) -> impl Iterator<Item = (&str, u64)> + '_ {
+        self.entities.iter().map(|(id, &count)| (id.as_str(), count))
+    }
+
+    pub fn get_structure_starts_count(&self, name: &str) -> u64 {
+        self.structure_starts.get(name).copied().unwrap_or(0)
+    }
+
+    /// Iterate over the structure starts counts per structure name, in no particular order.
+    pub fn iter_structure_starts(&self) -> impl Iterator<Item = (&str, u64)> + '_ {
+        self.structure_starts.iter().map(|(name, &count)| (name.as_str(), count))
+    }
+
+    /// Return the lowest and highest heights with a non-null block, if any.
+    pub fn get_built_heights(&self) -> Option<(i32, i32)> {
+        self.built_heights
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::world::anvil::region::RegionFile;
+    use crate::world::anvil::encode::encode_chunk;
+    use nbt::encode::write_compound_tag;
+
+    crate::blocks!(TEST_BLOCKS "test" [
+        AIR "air",
+        STONE "stone",
+        DIRT "dirt"
+    ]);
+
+    crate::biomes!(TEST_BIOMES "test" [
+        VOID "void" 0,
+    ]);
+
+    fn write_chunk(dir: &Path, cx: i32, cz: i32, tag: &CompoundTag) {
+        let mut region = RegionFile::new(dir.to_path_buf(), cx >> 5, cz >> 5, true).unwrap();
+        let mut writer = region.get_chunk_writer(cx, cz, Default::default());
+        write_compound_tag(&mut writer, tag).unwrap();
+        writer.write_chunk().unwrap();
+    }
+
+    fn entity(id: &str, passengers: Vec<CompoundTag>) -> CompoundTag {
+        let mut tag = CompoundTag::new();
+        tag.insert_str("id", id);
+        if !passengers.is_empty() {
+            tag.insert_compound_tag_vec("Passengers", passengers);
+        }
+        tag
+    }
+
+    #[test]
+    fn world_stats() {
+
+        let dir = std::env::temp_dir().join(format!("mc-core-world-stats-{}", std::process::id()));
+        let env = Arc::new(LevelEnv::builder()
+            .register_blocks(&TEST_BLOCKS)
+            .register_biomes(&TEST_BIOMES)
+            .build()
+            .unwrap());
+        let height = ChunkHeight::new(-1, 3);
+
+        // Two chunks in different regions, the second has a village start.
+        for &(cx, cz) in &[(0, 0), (-33, 5)] {
+            let mut chunk = ChunkLoadRequest { env: Arc::clone(&env), height, cx, cz }.build_chunk();
+            chunk.set_block(0, -3, 0, STONE.get_default_state()).unwrap();
+            chunk.set_block(1, 10, 0, STONE.get_default_state()).unwrap();
+            if cx != 0 {
+                chunk.set_block(2, 40, 3, DIRT.get_default_state()).unwrap();
+            }
+            let mut tag = CompoundTag::new();
+            encode_chunk(&mut tag, &chunk, &[]);
+            if cx != 0 {
+                let mut tag_village = CompoundTag::new();
+                tag_village.insert_str("id", "minecraft:village");
+                let mut tag_invalid = CompoundTag::new();
+                tag_invalid.insert_str("id", "INVALID");
+                let mut tag_starts = CompoundTag::new();
+                tag_starts.insert_compound_tag("minecraft:village", tag_village);
+                tag_starts.insert_compound_tag("minecraft:mineshaft", tag_invalid);
+                let mut tag_structures = CompoundTag::new();
+                tag_structures.insert_compound_tag("starts", tag_starts);
+                tag.insert_compound_tag("structures", tag_structures);
+            }
+            write_chunk(&dir.join("region"), cx, cz, &tag);
+        }
+
+        // A chunk that can't be decoded.
+        write_chunk(&dir.join("region"), 3, 3, &CompoundTag::new());
+
+        let mut tag_entities = CompoundTag::new();
+        tag_entities.insert_compound_tag_vec("Entities", vec![
+            entity("minecraft:cow", Vec::new()),
+            entity("minecraft:pig", vec![entity("minecraft:zombie", Vec::new())]),
+            entity("minecraft:cow", Vec::new())
+        ]);
+        write_chunk(&dir.join("entities"), 0, 0, &tag_entities);
+
+        let chunks: Vec<_> = RegionChunkIter::new(dir.join("region")).unwrap().collect();
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks.iter().filter_map(|c| c.as_ref().ok()).map(|c| (c.cx, c.cz)).collect::<Vec<_>>(), vec![(-33, 5), (0, 0), (3, 3)]);
+        assert_eq!(RegionChunkIter::new(dir.join("missing")).unwrap().count(), 0);
+
+        let stats = WorldStats::from_level_dir(&dir, &env, height).unwrap();
+        assert_eq!(stats.get_chunks_count(), 2);
+        assert_eq!(stats.get_failed_chunks_count(), 1);
+        assert_eq!(stats.get_block_state_count(STONE.get_default_state()), 4);
+        assert_eq!(stats.get_block_count(&DIRT), 1);
+        assert_eq!(stats.get_block_count(&AIR), 0);
+        assert_eq!(stats.iter_block_states().count(), 2);
+        assert_eq!(stats.get_entity_count("minecraft:cow"), 2);
+        assert_eq!(stats.get_entity_count("minecraft:zombie"), 1);
+        assert_eq!(stats.get_structure_starts_count("minecraft:village"), 1);
+        assert_eq!(stats.get_structure_starts_count("minecraft:mineshaft"), 0);
+        assert_eq!(stats.get_built_heights(), Some((-3, 40)));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+    }
+
+}
@@ -0,0 +1,98 @@
+//! Persistence of forced chunks in the `data/chunks.dat` file of a level directory.
+
+use std::fs::{self, File};
+use std::path::Path;
+use std::io;
+
+use nbt::decode::{read_gzip_compound_tag, TagDecodeError};
+use nbt::encode::write_gzip_compound_tag;
+use nbt::CompoundTag;
+use thiserror::Error;
+
+use crate::world::ticket::ChunkTickets;
+
+use super::encode::DATA_VERSION;
+
+
+#[derive(Error, Debug)]
+pub enum ForcedChunksError {
+    #[error("Failed to access the forced chunks file: {0}")]
+    Io(#[from] io::Error),
+    #[error("The NBT raw data cannot be decoded: {0}")]
+    Nbt(#[from] TagDecodeError),
+    #[error("The forced chunks file is malformed: {0}")]
+    Malformed(String)
+}
+
+
+/// Load forced chunks of the level in the given directory into the given tickets, if the
+/// file doesn't exist, the level has no forced chunks.
+pub fn load_forced_chunks<P: AsRef<Path>>(dir: P, tickets: &mut ChunkTickets) -> Result<(), ForcedChunksError> {
+
+    let path = dir.as_ref().join("data").join("chunks.dat");
+
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            tickets.decode_forced(&CompoundTag::new());
+            return Ok(());
+        }
+        Err(err) => return Err(err.into())
+    };
+
+    let tag_root = read_gzip_compound_tag(&mut file)?;
+    let tag_data = tag_root.get_compound_tag("data")
+        .map_err(|err| ForcedChunksError::Malformed(format!("{}", err)))?;
+
+    tickets.decode_forced(tag_data);
+    Ok(())
+
+}
+
+/// Save forced chunks of the given tickets to the level in the given directory.
+pub fn save_forced_chunks<P: AsRef<Path>>(dir: P, tickets: &mut ChunkTickets) -> Result<(), ForcedChunksError> {
+
+    let data_dir = dir.as_ref().join("data");
+    fs::create_dir_all(&data_dir)?;
+
+    let mut tag_data = CompoundTag::new();
+    tickets.encode_forced(&mut tag_data);
+
+    let mut tag_root = CompoundTag::new();
+    tag_root.insert_i32("DataVersion", DATA_VERSION);
+    tag_root.insert_compound_tag("data", tag_data);
+
+    let mut file = File::create(data_dir.join("chunks.dat"))?;
+    write_gzip_compound_tag(&mut file, &tag_root)?;
+    Ok(())
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn forced_chunks_file() {
+
+        let dir = std::env::temp_dir().join(format!("mc-core-forced-chunks-{}", std::process::id()));
+
+        let mut tickets = ChunkTickets::new();
+        load_forced_chunks(&dir, &mut tickets).unwrap();
+        assert_eq!(tickets.iter_forced().count(), 0);
+
+        tickets.add_forced(12, -5);
+        save_forced_chunks(&dir, &mut tickets).unwrap();
+
+        let mut loaded = ChunkTickets::new();
+        load_forced_chunks(&dir, &mut loaded).unwrap();
+        assert!(loaded.is_forced(12, -5));
+        assert_eq!(loaded.iter_forced().count(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+
+    }
+
+}
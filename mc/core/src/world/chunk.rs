@@ -5,9 +5,10 @@ use std::sync::Arc;
 use thiserror::Error;
 use hecs::Entity;
 
-use crate::util::{PackedArray, Palette, Rect, cast_vec_ref_to_ptr};
+use crate::util::{PackedArray, PalettedContainer, Rect};
 use crate::heightmap::HeightmapType;
 use crate::block::BlockState;
+use crate::pos::{BlockPos, BoundingBox};
 use crate::biome::Biome;
 use crate::perf;
 
@@ -87,14 +88,14 @@ pub struct Chunk {
     sub_chunks: Vec<Option<SubChunk>>,
     /// The offset of the lower chunk.
     sub_chunks_offset: i8,
-    /// Modern cube biomes array, this array does not use any palette since the global palette
-    /// should be small enough to only take 7 to 8 bits per point. Since there 64 biomes in
-    /// a sub chunk, this make only 64 octets to store a sub chunk.
+    /// Modern cube biomes array, most chunks only contain a few biomes so a small linear
+    /// palette is used before switching to the global palette, which should be small enough
+    /// to only take 7 to 8 bits per point.
     ///
     /// Biomes are stored separately from sub chunks because sub chunks are not necessarily
     /// existing for empty sub chunks even at valid Y positions. But biomes must always be
     /// defined for all the height.
-    biomes: PackedArray,
+    biomes: PalettedContainer<Biome>,
     /// Array containing data for all registered heightmaps in the level environment. For memory
     /// efficiency we store all our heightmaps in the same packed array.
     heightmaps: PackedArray,
@@ -103,19 +104,31 @@ pub struct Chunk {
     /// Total number of ticks players has been in this chunk, this increase faster when
     /// more players are in the chunk.
     inhabited_time: u64,
+    /// True if the terrain of this chunk has been generated by a version older than `1.18`,
+    /// new chunks generated around it should blend their terrain with it.
+    old_noise: bool,
     /// A list of entity handles that are located in this vertical chunk.
     entities: HashSet<Entity>,
     /// Last save instant.
-    last_save: Instant
+    last_save: Instant,
+    /// Dirty counter at the last save, `None` if the chunk has never been saved.
+    saved_dirty_counter: Option<u32>,
+    /// Counter incremented on each mutable access to the content of this chunk.
+    dirty_counter: u32
 }
 
+// Capacity of the linear biomes palette, the global palette is used if a chunk contains more
+// biomes, which is really unusual.
+const BIOMES_PALETTE_CAPACITY: usize = 8;
+
 impl Chunk {
 
     pub(super) fn new(env: Arc<LevelEnv>, height: ChunkHeight, cx: i32, cz: i32) -> Self {
 
-        // This is 7 bits for current vanilla biomes, this only take 64 octets of storage.
-        // Subtracting 1 because the maximum biome save ID is the maximum value.
-        let biomes_byte_size = PackedArray::calc_min_byte_size(env.biomes.biomes_count() as u64 - 1);
+        let default_biome = env.biomes.get_biome_from(0)
+            .expect("An empty global biomes palette is not supported by Chunk.");
+        let biomes = PalettedContainer::new(height.len() * BIOMES_DATA_SIZE, default_biome)
+            .with_capacities(BIOMES_PALETTE_CAPACITY, 0);
 
         let heightmap_byte_size = PackedArray::calc_min_byte_size((height.len() * 16) as u64);
         // Added 256 for the additional internal non null heightmap HEIGHTMAP_NON_NULL.
@@ -128,11 +141,14 @@ impl Chunk {
             status: ChunkStatus::Empty,
             sub_chunks: (0..height.len()).map(|_| None).collect(),
             sub_chunks_offset: height.min,
-            biomes: PackedArray::new(height.len() * 64, biomes_byte_size, None),
+            biomes,
             heightmaps: PackedArray::new(heightmap_len, heightmap_byte_size, None),
             inhabited_time: 0,
+            old_noise: false,
             entities: HashSet::new(),
-            last_save: Instant::now()
+            last_save: Instant::now(),
+            saved_dirty_counter: None,
+            dirty_counter: 0
         }
 
     }
@@ -168,14 +184,50 @@ impl Chunk {
         self.inhabited_time = time;
     }
 
+    /// Return true if the terrain of this chunk has been generated by an older version.
+    #[inline]
+    pub fn has_old_noise(&self) -> bool {
+        self.old_noise
+    }
+
+    #[inline]
+    pub fn set_old_noise(&mut self, old_noise: bool) {
+        self.old_noise = old_noise;
+    }
+
     #[inline]
     pub fn get_last_save(&self) -> Instant {
         self.last_save
     }
 
+    /// Update the last save instant, the chunk is no longer considered modified since its
+    /// last save until its dirty counter changes.
     #[inline]
     pub fn update_last_save(&mut self) {
         self.last_save = Instant::now();
+        self.saved_dirty_counter = Some(self.dirty_counter);
+    }
+
+    /// Return true if this chunk may have been modified since its last save, or if it has
+    /// never been saved.
+    #[inline]
+    pub fn is_modified_since_save(&self) -> bool {
+        self.saved_dirty_counter != Some(self.dirty_counter)
+    }
+
+    /// Return the dirty counter of this chunk, it is incremented each time the blocks, biomes,
+    /// lights or heightmaps of this chunk may have been modified. Comparing two values of this
+    /// counter tells if the chunk has changed in between.
+    #[inline]
+    pub fn get_dirty_counter(&self) -> u32 {
+        self.dirty_counter
+    }
+
+    /// Increment the dirty counter of this chunk, this is already done by all methods giving
+    /// mutable access to the content of the chunk.
+    #[inline]
+    pub fn mark_dirty(&mut self) {
+        self.dirty_counter = self.dirty_counter.wrapping_add(1);
     }
 
     // Height //
@@ -214,6 +266,7 @@ impl Chunk {
     /// is out of the height of the level, `Err(ChunkError::SubChunkOutOfRange)` is returned.
     pub fn ensure_sub_chunk(&mut self, cy: i8) -> ChunkResult<&mut SubChunk> {
 
+        self.mark_dirty();
         let offset = self.calc_sub_chunk_offset(cy).ok_or(ChunkError::SubChunkOutOfRange)?;
 
         match self.sub_chunks.get_mut(offset) {
@@ -237,6 +290,7 @@ impl Chunk {
     /// The method panics if the given sub chunk has not the same sub chunk environment as self.
     pub fn replace_sub_chunk(&mut self, cy: i8, sub_chunk: SubChunk) -> ChunkResult<&mut SubChunk> {
         debug_assert!(Arc::ptr_eq(&self.env, &sub_chunk.env));
+        self.mark_dirty();
         let offset = self.calc_sub_chunk_offset(cy).ok_or(ChunkError::SubChunkOutOfRange)?;
         let container = self.sub_chunks.get_mut(offset).ok_or(ChunkError::SubChunkOutOfRange)?;
         Ok(container.insert(sub_chunk))
@@ -258,6 +312,7 @@ impl Chunk {
 
     /// Get a sub chunk mutable reference at a specified index.
     pub fn get_sub_chunk_mut(&mut self, cy: i8) -> Option<&mut SubChunk> {
+        self.mark_dirty();
         let offset = self.calc_sub_chunk_offset(cy)?;
         match self.sub_chunks.get_mut(offset) {
             Some(Some(chunk)) => Some(chunk),
@@ -296,6 +351,21 @@ impl Chunk {
             })
     }
 
+    /// Return `true` if the sub chunk at the given Y chunk coordinate only contains null blocks,
+    /// this is also the case of unloaded sub chunks and sub chunks out of the chunk height.
+    /// This is a constant time check using the non null blocks count of sub chunks.
+    pub fn is_sub_chunk_empty(&self, cy: i8) -> bool {
+        match self.get_sub_chunk(cy) {
+            Some(sub_chunk) => sub_chunk.is_empty(),
+            None => true
+        }
+    }
+
+    #[inline]
+    pub fn is_sub_chunk_empty_at(&self, y: i32) -> bool {
+        self.is_sub_chunk_empty((y >> 4) as i8)
+    }
+
     /// Return the index of the first sub chunk (starting from the top sub chunk) that is loaded
     /// AND contains a non null block.
     pub fn get_highest_non_null_sub_chunk(&self) -> i8 {
@@ -373,6 +443,133 @@ impl Chunk {
         self.set_block((x & 15) as u8, y, (z & 15) as u8, state)
     }
 
+    // REGIONS //
+
+    /// Return the intersection of the given region with this chunk, also bounded by the chunk
+    /// height, `None` if they don't intersect.
+    fn clip_region(&self, region: &BoundingBox) -> Option<BoundingBox> {
+        let height = self.get_height();
+        region.intersection(&BoundingBox::new_chunk(self.cx, self.cz, height.get_min_block(), height.get_max_block()))
+    }
+
+    /// Set all blocks of the given region that are in this chunk to the given state, sub chunks
+    /// fully covered by the region are filled at once. Heightmaps of modified columns are
+    /// recomputed after all blocks have been set.
+    ///
+    /// Return the number of blocks set, or `Err(ChunkError::IllegalBlock)` if the given state
+    /// is not registered in the current world.
+    pub fn fill_region(&mut self, region: &BoundingBox, state: &'static BlockState) -> ChunkResult<usize> {
+        self.edit_region(region, state, None::<fn(&'static BlockState) -> bool>)
+    }
+
+    /// Same as `fill_region` but only replace blocks matching the given predicate.
+    pub fn replace_region<P>(&mut self, region: &BoundingBox, predicate: P, state: &'static BlockState) -> ChunkResult<usize>
+    where
+        P: FnMut(&'static BlockState) -> bool
+    {
+        self.edit_region(region, state, Some(predicate))
+    }
+
+    fn edit_region<P>(&mut self, region: &BoundingBox, state: &'static BlockState, mut predicate: Option<P>) -> ChunkResult<usize>
+    where
+        P: FnMut(&'static BlockState) -> bool
+    {
+
+        let region = match self.clip_region(region) {
+            Some(region) => region,
+            None => return Ok(0)
+        };
+
+        if !self.env.blocks.has_state(state) {
+            return Err(ChunkError::IllegalBlock);
+        }
+
+        let null_block = self.env.blocks.get_state_from(0).unwrap();
+        let mut count = 0;
+
+        for cy in (region.min_y >> 4)..=(region.max_y >> 4) {
+
+            let sub_chunk_bb = BoundingBox::new(self.cx * 16, cy * 16, self.cz * 16, self.cx * 16 + 15, cy * 16 + 15, self.cz * 16 + 15);
+            let sub_region = region.intersection(&sub_chunk_bb).unwrap();
+            let volume = (sub_region.get_size_x() * sub_region.get_size_y() * sub_region.get_size_z()) as usize;
+
+            let cy = cy as i8;
+            let empty = self.is_sub_chunk_empty(cy);
+
+            if empty {
+                // Empty sub chunks only contain null blocks, so we check the predicate once.
+                if !predicate.as_mut().is_none_or(|predicate| predicate(null_block)) {
+                    continue;
+                } else if std::ptr::eq(state, null_block) {
+                    count += volume;
+                    continue;
+                }
+            }
+
+            let sub_chunk = self.ensure_sub_chunk(cy)?;
+
+            if volume == BLOCKS_DATA_SIZE && (empty || predicate.is_none()) {
+                sub_chunk.fill_block(state)?;
+                count += volume;
+                continue;
+            }
+
+            for y in sub_region.min_y..=sub_region.max_y {
+                for z in sub_region.min_z..=sub_region.max_z {
+                    for x in sub_region.min_x..=sub_region.max_x {
+                        let (bx, by, bz) = ((x & 15) as u8, (y & 15) as u8, (z & 15) as u8);
+                        if let Some(predicate) = &mut predicate {
+                            if !predicate(sub_chunk.get_block(bx, by, bz)) {
+                                continue;
+                            }
+                        }
+                        sub_chunk.set_block(bx, by, bz, state)?;
+                        count += 1;
+                    }
+                }
+            }
+
+        }
+
+        if count != 0 {
+            for z in region.min_z..=region.max_z {
+                for x in region.min_x..=region.max_x {
+                    self.recompute_heightmap_column((x & 15) as u8, (z & 15) as u8);
+                }
+            }
+        }
+
+        Ok(count)
+
+    }
+
+    /// Iterate over all non null blocks of the given region that are in this chunk, empty sub
+    /// chunks are skipped and blocks are read sub chunk by sub chunk. Blocks are ordered by
+    /// sub chunk, and then by X, Z and Y.
+    pub fn iter_region_blocks(&self, region: &BoundingBox) -> impl Iterator<Item = (BlockPos, &'static BlockState)> + '_ {
+        let (cx, cz) = (self.cx, self.cz);
+        let null_block = self.env.blocks.get_state_from(0).unwrap();
+        self.clip_region(region).into_iter().flat_map(move |region| {
+            self.iter_loaded_sub_chunks()
+                .filter(move |(cy, sub_chunk)| {
+                    let min_y = *cy as i32 * 16;
+                    !sub_chunk.is_empty() && min_y + 15 >= region.min_y && min_y <= region.max_y
+                })
+                .flat_map(move |(cy, sub_chunk)| {
+                    sub_chunk.iter_blocks().enumerate().filter_map(move |(i, state)| {
+                        let x = cx * 16 + (i & 15) as i32;
+                        let z = cz * 16 + ((i >> 4) & 15) as i32;
+                        let y = cy as i32 * 16 + (i >> 8) as i32;
+                        if !std::ptr::eq(state, null_block) && region.contains(x, y, z) {
+                            Some((BlockPos::new(x, y, z), state))
+                        } else {
+                            None
+                        }
+                    })
+                })
+        })
+    }
+
     // BIOMES //
 
     fn calc_biome_offset(&self, x: u8, y: i32, z: u8) -> usize {
@@ -391,13 +588,20 @@ impl Chunk {
     /// This method panics if either X or Z is higher than 3.
     pub fn get_biome(&self, x: u8, y: i32, z: u8) -> ChunkResult<&'static Biome> {
         let offset = self.calc_biome_offset(x, y, z);
-        let sid = self.biomes.get(offset).ok_or(ChunkError::SubChunkOutOfRange)? as u16;
-        Ok(self.env.biomes.get_biome_from(sid).unwrap())
+        self.biomes.get(offset, &*self.env.biomes).ok_or(ChunkError::SubChunkOutOfRange)
+    }
+
+    /// Get a biome at the given quart coordinates, quart coordinates are block coordinates
+    /// divided by 4, aligned to the 4x4x4 biomes grid sent to vanilla clients. X and Z
+    /// coordinates are wrapped to this chunk, so absolute quart coordinates can be given.
+    #[inline]
+    pub fn get_biome_quart(&self, x: i32, y: i32, z: i32) -> ChunkResult<&'static Biome> {
+        self.get_biome((x & 3) as u8, y, (z & 3) as u8)
     }
 
     #[inline]
     pub fn get_biome_at(&self, x: i32, y: i32, z: i32) -> ChunkResult<&'static Biome> {
-        self.get_biome(((x >> 2) & 3) as u8, y >> 2, ((z >> 2) & 3) as u8)
+        self.get_biome_quart(x >> 2, y >> 2, z >> 2)
     }
 
     /// Get a biome at specific biome coordinates, biome coordinates are different from block
@@ -411,10 +615,10 @@ impl Chunk {
     /// # Panics (debug-only)
     /// This method panics if either X or Z is higher than 3.
     pub fn set_biome(&mut self, x: u8, y: i32, z: u8, biome: &'static Biome) -> ChunkResult<()> {
+        self.mark_dirty();
         let offset = self.calc_biome_offset(x, y, z);
-        let sid = self.env.biomes.get_sid_from(biome).ok_or(ChunkError::IllegalBiome)?;
         if offset < self.biomes.len() {
-            self.biomes.set(offset, sid as u64);
+            self.biomes.set(offset, biome, &*self.env.biomes).ok_or(ChunkError::IllegalBiome)?;
             Ok(())
         } else {
             Err(ChunkError::SubChunkOutOfRange)
@@ -433,18 +637,23 @@ impl Chunk {
     pub fn set_biomes_2d(&mut self, biomes: &Rect<&'static Biome>) -> ChunkResult<()> {
 
         assert!(biomes.x_size >= 16 && biomes.z_size >= 16, "Given biomes rectangle is too small.");
+        self.mark_dirty();
 
-        let mut layer_biomes = [0; 16];
+        let mut layer_biomes = [biomes.data[0]; 16];
 
         for z in 0..4 {
             for x in 0..4 {
                 let idx = x + z * 4;
                 let biome = biomes.data[idx * 4];
-                layer_biomes[idx] = self.env.biomes.get_sid_from(biome).ok_or(ChunkError::IllegalBiome)?;
+                self.env.biomes.get_sid_from(biome).ok_or(ChunkError::IllegalBiome)?;
+                layer_biomes[idx] = biome;
             }
         }
 
-        self.biomes.replace(move |i, _| layer_biomes[i % 16] as u64);
+        for i in 0..self.biomes.len() {
+            self.biomes.set(i, layer_biomes[i % 16], &*self.env.biomes);
+        }
+
         Ok(())
 
     }
@@ -452,10 +661,10 @@ impl Chunk {
     #[deprecated]
     pub fn set_biomes_3d(&mut self, biomes: &[&'static Biome]) -> ChunkResult<()> {
         assert_eq!(biomes.len(), self.sub_chunks.len() * 64, "Given biomes array must be {} biomes long.", self.sub_chunks.len() * 64);
-        let env_biomes = &self.env.biomes;
-        self.biomes.replace(move |i, old| {
-            env_biomes.get_sid_from(biomes[i]).map(|v| v as u64).unwrap_or(old)
-        });
+        self.mark_dirty();
+        for (i, &biome) in biomes.iter().enumerate() {
+            self.biomes.set(i, biome, &*self.env.biomes);
+        }
         Ok(())
     }
 
@@ -468,22 +677,14 @@ impl Chunk {
     /// environment.
     ///
     /// The biomes iterator must return only valid indices for the given palette.
-    pub unsafe fn set_biomes_raw<I>(&mut self, offset: usize, palette: Vec<&'static Biome>, mut biomes: I)
+    pub unsafe fn set_biomes_raw<I>(&mut self, offset: usize, palette: Vec<&'static Biome>, biomes: I)
     where
         I: Iterator<Item = usize>
     {
-        let env_biomes = &self.env.biomes;
-        self.biomes.replace(move |i, old| {
-            if i >= offset {
-                if let Some(biome_idx) = biomes.next() {
-                    env_biomes.get_sid_from(palette[biome_idx]).unwrap() as u64
-                } else {
-                    old
-                }
-            } else {
-                old
-            }
-        });
+        self.mark_dirty();
+        for (i, biome_idx) in (offset..self.biomes.len()).zip(biomes) {
+            self.biomes.set(i, palette[biome_idx], &*self.env.biomes);
+        }
     }
 
     pub fn get_biomes_count(&self) -> usize {
@@ -493,8 +694,16 @@ impl Chunk {
     /// Expose internal biomes storage, retuning an iterator with each biomes in this chunk,
     /// ordered by X, Z then Y.
     pub fn iter_biomes(&self) -> impl Iterator<Item = &'static Biome> + '_ {
-        let biomes = &self.env.biomes;
-        self.biomes.iter().map(move |v| biomes.get_biome_from(v as u16).unwrap())
+        self.biomes.iter(&*self.env.biomes)
+    }
+
+    /// Remove unused biomes and block states from the palettes of this chunk and all of its
+    /// sub chunks, this should be used after large edits of the chunk.
+    pub fn optimize(&mut self) {
+        self.biomes.optimize(&*self.env.biomes);
+        for sub_chunk in self.sub_chunks.iter_mut().flatten() {
+            sub_chunk.optimize();
+        }
     }
 
     // HEIGHTMAPS //
@@ -511,6 +720,7 @@ impl Chunk {
     /// that manually, you should ensure that the condition of the given heightmap type are kept.
     pub fn set_heightmap_column(&mut self, heightmap_type: &'static HeightmapType, x: u8, z: u8, y: i32) -> ChunkResult<()> {
         let column_index = self.get_heightmap_column_index(heightmap_type, x, z)?;
+        self.mark_dirty();
         self.heightmaps.set(column_index, (y - self.get_height().get_min_block()) as u64);
         Ok(())
     }
@@ -655,6 +865,31 @@ impl Chunk {
         Some((self.heightmaps.byte_size(), self.heightmaps.iter().skip(offset).take(256)))
     }
 
+    // LIGHTS //
+
+    /// Get the light of the given type at a specific position.
+    ///
+    /// Returns `Err(ChunkError::SubChunkOutOfRange)` if the given y coordinate is out of the
+    /// chunk height or `Err(ChunkError::SubChunkUnloaded)` if the sub chunk is not loaded.
+    ///
+    /// # Panics (debug-only)
+    /// This method panics if either X or Z is higher than 15.
+    pub fn get_light(&self, x: u8, y: i32, z: u8, typ: Light) -> ChunkResult<u8> {
+        if !self.get_height().contains((y >> 4) as i8) {
+            return Err(ChunkError::SubChunkOutOfRange);
+        }
+        match self.get_sub_chunk((y >> 4) as i8) {
+            Some(sub_chunk) => Ok(sub_chunk.get_light(x, (y & 15) as u8, z, typ)),
+            None => Err(ChunkError::SubChunkUnloaded)
+        }
+    }
+
+    /// Same description as `get_light` but accept level coordinates instead of relative ones.
+    #[inline]
+    pub fn get_light_at(&self, x: i32, y: i32, z: i32, typ: Light) -> ChunkResult<u8> {
+        self.get_light((x & 15) as u8, y, (z & 15) as u8, typ)
+    }
+
     // ENTITIES //
 
     #[inline]
@@ -672,47 +907,16 @@ impl Chunk {
         self.entities.contains(&entity)
     }
 
-}
-
-
-/// Internal sub chunks blocks palette.
-enum SubChunkBlocks {
-    /// Local blocks palette.
-    Local {
-        /// Blocks palette. It is limited to 128 blocks in order to support most blocks in a natural
-        /// generation, which is the case of most of the sub chunks that are untouched by players.
-        /// In case of "artificial chunks" made by players, the block palette is likely to overflow
-        /// the 128 block states limit, in this case it switch to the global palette (`GlobalBlocks`
-        /// in the level environment).
-        /// The palette uses a raw pointer in order to use the pointer equality instead of value eq.
-        palette: Palette<*const BlockState>,
-        /// Save ID of the null block in the palette. Set to `u32::MAX` if the null block is not
-        /// present in the local palette. This value is safe because the palette has a limited
-        /// capacity of `BLOCKS_PALETTE_CAPACITY` block states.
-        null_block_sid: u32
-    },
-    /// Global blocks palette.
-    Global
-}
-
-impl SubChunkBlocks {
-
-    /// Compute and return the effective null block save ID from the enum variant.
-    fn get_null_block_sid(&self) -> Option<u32> {
-        match self {
-            Self::Local { null_block_sid: u32::MAX, .. } => None,
-            Self::Local { null_block_sid, .. } => Some(*null_block_sid),
-            Self::Global => Some(0)
-        }
+    /// Iterate over handles of entities and block entities in this chunk.
+    #[inline]
+    pub fn iter_entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.entities.iter().copied()
     }
 
 }
 
-// Implemented because palette of '*const BlockState' prevent it by default.
-unsafe impl Send for SubChunkBlocks {}
-unsafe impl Sync for SubChunkBlocks {}
-
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum Light {
     Block = 0,
     Sky = 1
@@ -723,10 +927,8 @@ pub enum Light {
 pub struct SubChunk {
     /// A local shared pointer to the level environment.
     env: Arc<LevelEnv>,
-    /// Local of global blocks palette.
-    blocks_palette: SubChunkBlocks,
-    /// Cube blocks array.
-    blocks: PackedArray,
+    /// Cube blocks array with its local or global blocks palette.
+    blocks: PalettedContainer<BlockState>,
     /// Both block and sky lights combined, 4 bits per block.
     lights: PackedArray,
     /// Non-null blocks count. "Null block" is a shorthand for the block state at save ID 0 in the
@@ -735,9 +937,11 @@ pub struct SubChunk {
     non_null_blocks_count: u16,
 }
 
-// Palette capacity must ensure that most of the chunks will be supported AND that the palette
-// lookup is fast, but because the lookup is O(N) we have to find a balanced capacity.
-const BLOCKS_PALETTE_CAPACITY: usize = 128;
+// The linear palette lookup is O(N) so it's only used for small palettes, most of the natural
+// sub chunks are supported by the hash palette, other "artificial" sub chunks made by players
+// are likely to overflow it and switch to the global palette.
+const BLOCKS_LINEAR_PALETTE_CAPACITY: usize = 16;
+const BLOCKS_HASH_PALETTE_CAPACITY: usize = 256;
 
 // Minimum size (in bits) of bytes representing block indices to local or global palette.
 const BLOCKS_ARRAY_MIN_BYTE_SIZE: u8 = 4;
@@ -761,21 +965,19 @@ impl SubChunk {
         // and they must have a corresponding valid value in palettes, at least at the beginning.
         SubChunk {
             env,
-            blocks_palette: SubChunkBlocks::Local {
-                palette: Palette::with_default(null_block, BLOCKS_PALETTE_CAPACITY),
-                null_block_sid: 0
-            },
-            blocks: PackedArray::new(BLOCKS_DATA_SIZE, BLOCKS_ARRAY_MIN_BYTE_SIZE, None),
+            blocks: PalettedContainer::new(BLOCKS_DATA_SIZE, null_block)
+                .with_capacities(BLOCKS_LINEAR_PALETTE_CAPACITY, BLOCKS_HASH_PALETTE_CAPACITY)
+                .with_min_byte_size(BLOCKS_ARRAY_MIN_BYTE_SIZE),
             lights: PackedArray::new(BLOCKS_DATA_SIZE * 2, LIGHTS_ARRAY_BYTE_SIZE, None),
             non_null_blocks_count: 0,
         }
 
     }
 
+    /// Internal method to get the null block, see `non_null_blocks_count`.
     #[inline]
-    fn get_blocks_byte_size(env: &LevelEnv) -> u8 {
-        PackedArray::calc_min_byte_size(env.blocks.states_count() as u64 - 1)
-            .max(BLOCKS_ARRAY_MIN_BYTE_SIZE)
+    fn get_null_block(&self) -> &'static BlockState {
+        self.env.blocks.get_state_from(0).unwrap()
     }
 
     // BLOCKS //
@@ -783,135 +985,33 @@ impl SubChunk {
     pub fn get_block(&self, x: u8, y: u8, z: u8) -> &'static BlockState {
         // SAFETY: The unwrap should be safe because the block index is expected to be right,
         //         moreover it is checked in debug mode.
-        let sid = self.blocks.get(calc_block_index(x, y, z)).unwrap() as u32;
-        self.get_block_from_sid(sid)
+        self.blocks.get(calc_block_index(x, y, z), &*self.env.blocks).unwrap()
     }
 
     /// # Caution
     /// This method will not update heightmaps of the owner chunk.
     pub fn set_block(&mut self, x: u8, y: u8, z: u8, state: &'static BlockState) -> ChunkResult<()> {
         let idx = calc_block_index(x, y, z);
-        match self.ensure_block_sid(state) {
-            Some(sid) => {
-                let old_sid = self.blocks.set(idx, sid as u64) as u32;
-                if let Some(null_block_sid) = self.blocks_palette.get_null_block_sid() {
-                    let was_null = old_sid == null_block_sid;
-                    let is_null = sid == null_block_sid;
-                    self.non_null_blocks_count = (self.non_null_blocks_count as i16 + was_null as i16 - is_null as i16) as u16;
-                }
+        let null_block = self.get_null_block();
+        match self.blocks.set(idx, state, &*self.env.blocks) {
+            Some(old_state) => {
+                let was_null = std::ptr::eq(old_state, null_block);
+                let is_null = std::ptr::eq(state, null_block);
+                self.non_null_blocks_count = (self.non_null_blocks_count as i16 + was_null as i16 - is_null as i16) as u16;
                 Ok(())
             },
             None => Err(ChunkError::IllegalBlock)
         }
     }
 
-    /// Internal method to get the block state reference from its save ID. It is internal because
-    /// calling this method with invalid save ID for the sub chunk will panic.
-    #[inline]
-    fn get_block_from_sid(&self, sid: u32) -> &'static BlockState {
-        // SAFETY: The unwraps should be safe in this case because the `set_block` ensure
-        //         that the palette is updated according to the blocks contents. If the user
-        //         directly manipulate the contents of palette or blocks array it MUST ensure
-        //         that is correct, if not this will panic here.
-        match self.blocks_palette {
-            SubChunkBlocks::Local { ref palette, .. } => unsafe {
-                // Here we transmute a `*const BlockState` to `&'static BlockState`, this is safe.
-                std::mem::transmute(palette.get_item(sid as usize).unwrap())
-            },
-            SubChunkBlocks::Global => self.env.blocks.get_state_from(sid).unwrap()
-        }
-    }
-
-    /// Internal method to get the save ID of a given block state within this chunk. If this
-    /// block state is not currently registered, a new save ID will be allocated. If there
-    /// are more than `BLOCKS_PALETTE_CAPACITY` blocks in the local palette, the whole blocks
-    /// packed array will be resized in order to store global save ID instead of local ones.
-    fn ensure_block_sid(&mut self, state: &'static BlockState) -> Option<u32> {
-
-        if let SubChunkBlocks::Local {
-            ref mut palette,
-            ..
-        } = self.blocks_palette {
-
-            let state_ptr = state as *const BlockState;
-
-            // Here we intentionally dont use palette.ensure_index, because we want to check if
-            // the given state is supported by the underlying global palette before actually
-            // inserting it to the local palette.
-            match palette.search_index(state_ptr) {
-                Some(sid) => return Some(sid as u32),
-                None => {
-                    if self.env.blocks.has_state(state) {
-                        match palette.insert_index(state_ptr) {
-                            Some(sid) => {
-                                if sid as u64 > self.blocks.max_value() {
-                                    self.blocks.resize_byte(self.blocks.byte_size() + 1);
-                                }
-                                return Some(sid as u32);
-                            },
-                            None => {
-                                // In this case, the local palette is full, we have to switch to
-                                // the global one. So we don't return anything to skip the match.
-                            }
-                        }
-                    } else {
-                        return None;
-                    }
-                }
-            }
-
-            self.use_global_blocks();
-
-        }
-
-        self.env.blocks.get_sid_from(state)
-
-    }
-
-    fn use_global_blocks(&mut self) {
-        if let SubChunkBlocks::Local {
-            ref palette,
-            ..
-        } = self.blocks_palette {
-            let new_byte_size = Self::get_blocks_byte_size(&self.env);
-            let global_palette = &self.env.blocks;
-            self.blocks.resize_byte_and_replace(new_byte_size, move |_, sid| {
-                let state = palette.get_item(sid as usize).unwrap();
-                global_palette.get_sid_from(unsafe { std::mem::transmute(state) }).unwrap() as u64
-            });
-            self.blocks_palette = SubChunkBlocks::Global;
-        }
-    }
-
     /// Force fill all the sub chunk with the given block state.
     pub fn fill_block(&mut self, state: &'static BlockState) -> ChunkResult<()> {
-
-        let null_block_sid = match self.env.blocks.get_sid_from(state) {
-            None => return Err(ChunkError::IllegalBlock),
-            Some(0) => {
-                self.non_null_blocks_count = 0;
-                0
-            }
-            Some(_) => {
-                self.non_null_blocks_count = 4096;
-                u32::MAX
-            }
-        };
-
-        self.blocks_palette = SubChunkBlocks::Local {
-            palette: Palette::with_default(state, BLOCKS_PALETTE_CAPACITY),
-            null_block_sid
-        };
-
-        unsafe {
-            // SAFETY: We don't care of the old content, and clearing all cells will set every
-            // value to 0, which points to the new state at save ID 0 in the palette.
-            self.blocks.resize_raw(BLOCKS_ARRAY_MIN_BYTE_SIZE);
-            self.blocks.clear_cells();
+        if self.blocks.fill(state, &*self.env.blocks) {
+            self.non_null_blocks_count = if std::ptr::eq(state, self.get_null_block()) { 0 } else { 4096 };
+            Ok(())
+        } else {
+            Err(ChunkError::IllegalBlock)
         }
-
-        Ok(())
-
     }
 
     /// # Safety:
@@ -919,75 +1019,32 @@ impl SubChunk {
     /// chunk's level's environment.
     ///
     /// The blocks iterator must return only valid indices for the given palette.
-    pub unsafe fn set_blocks_raw<I>(&mut self, palette: Vec<&'static BlockState>, mut blocks: I)
+    pub unsafe fn set_blocks_raw<I>(&mut self, palette: Vec<&'static BlockState>, blocks: I)
     where
         I: Iterator<Item = usize>
     {
-
         assert_ne!(palette.len(), 0, "Palette length is zero.");
-
-        if palette.len() <= BLOCKS_PALETTE_CAPACITY {
-
-            let palette = cast_vec_ref_to_ptr(palette);
-            let byte_size = PackedArray::calc_min_byte_size(palette.len() as u64 - 1)
-                .max(BLOCKS_ARRAY_MIN_BYTE_SIZE);
-
-            self.blocks_palette = SubChunkBlocks::Local {
-                null_block_sid: {
-                    let null_block = self.env.blocks.get_state_from(0).unwrap();
-                    palette.iter()
-                        .position(move |&v| v == null_block)
-                        .map(|idx| idx as u32)
-                        .unwrap_or(u32::MAX)
-                },
-                palette: Palette::from_raw(palette, BLOCKS_PALETTE_CAPACITY)
-            };
-
-            // We resize raw because we don't care of the old content, and the new byte size might
-            // be smaller than the old one.
-            self.blocks.resize_raw(byte_size);
-            self.blocks.replace(move |_, _| {
-                blocks.next().unwrap_or(0) as u64
-            });
-
-        } else {
-
-            self.blocks_palette = SubChunkBlocks::Global;
-
-            let byte_size = Self::get_blocks_byte_size(&self.env);
-            let global_blocks = &self.env.blocks;
-
-            self.blocks.resize_raw(byte_size);
-            self.blocks.replace(move |_, _| {
-                let palette_idx = blocks.next().unwrap_or(0);
-                let state = palette[palette_idx];
-                // SAFETY: We can unwrap because this is an safety condition of the method.
-                global_blocks.get_sid_from(state).unwrap() as u64
-            });
-
-        }
-
+        self.blocks.set_raw(&palette, blocks, &*self.env.blocks);
         self.refresh_non_null_blocks_count();
-
     }
 
-    /// Iterate over all blocks in this chunk, ordered by X, Z then Y.
+    /// Iterate over all blocks in this sub chunk in storage order, X varying the fastest,
+    /// then Z and then Y.
     pub fn iter_blocks(&self) -> impl Iterator<Item = &'static BlockState> + '_ {
-        SubChunkBlocksIter {
-            inner: self.blocks.iter(),
-            sub_chunk: self,
-            last_block: None
-        }
+        self.blocks.iter(&*self.env.blocks)
+    }
+
+    /// Remove unused block states from the palette of this sub chunk, this can reduce the
+    /// memory used by the sub chunk after large edits.
+    pub fn optimize(&mut self) {
+        self.blocks.optimize(&*self.env.blocks);
     }
 
     fn refresh_non_null_blocks_count(&mut self) {
-        if let Some(null_block_sid) = self.blocks_palette.get_null_block_sid() {
-            self.non_null_blocks_count = self.blocks.iter()
-                .filter(move |&sid| sid != null_block_sid as u64)
-                .count() as u16;
-        } else {
-            self.non_null_blocks_count = 4096; // The is no null block.
-        }
+        let null_block = self.get_null_block();
+        self.non_null_blocks_count = self.iter_blocks()
+            .filter(move |&state| !std::ptr::eq(state, null_block))
+            .count() as u16;
     }
 
     // Lights //
@@ -1003,6 +1060,14 @@ impl SubChunk {
         self.lights.set(light_idx, level as u64);
     }
 
+    /// Iterate over the 4096 individual lights of the given type, ordered from X, Z and Y.
+    pub fn iter_lights(&self, typ: Light) -> impl Iterator<Item = u8> + '_ {
+        self.lights.iter()
+            .skip(typ as usize * BLOCKS_DATA_SIZE)
+            .take(BLOCKS_DATA_SIZE)
+            .map(|light| light as u8)
+    }
+
     /// # Safety:
     /// The lights iterator must give 4096 individual lights, ordered from X, Z and Y. Each value
     /// must be between 0 and 15 (included).
@@ -1038,42 +1103,17 @@ impl SubChunk {
         self.non_null_blocks_count != 0
     }
 
-}
-
-
-/// An efficient iterator over all blocks in a sub chunk.
-pub struct SubChunkBlocksIter<'a, I> {
-    inner: I,
-    sub_chunk: &'a SubChunk,
-    last_block: Option<(u64, &'static BlockState)>
-}
-
-impl<'a, I> Iterator for SubChunkBlocksIter<'a, I>
-where
-    I: Iterator<Item = u64>
-{
-
-    type Item = &'static BlockState;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        match self.inner.next() {
-            None => None,
-            Some(sid) => {
-                match self.last_block {
-                    Some((last_sid, state)) if last_sid == sid => Some(state),
-                    ref mut last_block => {
-                        Some(&last_block.insert((sid, self.sub_chunk.get_block_from_sid(sid as u32))).1)
-                    }
-                }
-            }
-        }
+    /// Return `true` if this sub chunk only contains null blocks.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.non_null_blocks_count == 0
     }
 
 }
 
 
 /// A little structure that stores the height of a level (and so a chunk).
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub struct ChunkHeight {
     /// Inclusive lower bound.
     pub min: i8,
@@ -1120,6 +1160,9 @@ impl ChunkHeight {
 }
 
 
+/// Blocks of sub chunks are stored in Y-Z-X order, X being the fastest varying coordinate,
+/// so iterating Y, then Z, then X in nested loops reads values contiguously in the packed
+/// array, and a 16 blocks row along X fits in a single cell for small palettes.
 #[inline]
 fn calc_block_index(x: u8, y: u8, z: u8) -> usize {
     debug_assert!(x < 16 && y < 16 && z < 16, "x: {}, y: {}, z: {}", x, y, z);
@@ -1158,6 +1201,7 @@ mod tests {
 
     crate::biomes!(TEST_BIOMES "test" [
         VOID "void" 0,
+        PLAINS "plains" 1,
     ]);
 
     fn heightmap_test(state: &'static BlockState, _blocks: &GlobalBlocks) -> bool {
@@ -1189,6 +1233,20 @@ mod tests {
         assert!(matches!(chunk.ensure_sub_chunk(3), Err(ChunkError::SubChunkOutOfRange)));
     }
 
+    #[test]
+    fn dirty_counter() {
+        let mut chunk = build_chunk();
+        let counter = chunk.get_dirty_counter();
+        assert!(chunk.is_modified_since_save());
+        chunk.update_last_save();
+        assert!(!chunk.is_modified_since_save());
+        assert_eq!(chunk.get_block(0, 0, 0).unwrap(), AIR.get_default_state());
+        assert_eq!(chunk.get_dirty_counter(), counter);
+        chunk.set_block(0, 0, 0, STONE.get_default_state()).unwrap();
+        assert_ne!(chunk.get_dirty_counter(), counter);
+        assert!(chunk.is_modified_since_save());
+    }
+
     #[test]
     fn valid_set_get() {
         let mut chunk = build_chunk();
@@ -1252,4 +1310,62 @@ mod tests {
 
     }
 
+    #[test]
+    fn valid_region_edit() {
+
+        let mut chunk = build_chunk();
+
+        let region = BoundingBox::new(-4, -16, 2, 7, 15, 3);
+        assert_eq!(chunk.fill_region(&region, STONE.get_default_state()).unwrap(), 8 * 32 * 2);
+        assert_eq!(chunk.get_block(7, 15, 3).unwrap(), STONE.get_default_state());
+        assert_eq!(chunk.get_block(8, 15, 3).unwrap(), AIR.get_default_state());
+        assert!(matches!(chunk.get_heightmap_column(&TEST, 0, 2), Ok(16)));
+        assert!(chunk.is_sub_chunk_empty(1));
+
+        let full = BoundingBox::new(0, 16, 0, 15, 31, 15);
+        assert_eq!(chunk.fill_region(&full, DIRT.get_default_state()).unwrap(), 4096);
+        assert_eq!(chunk.get_sub_chunk(1).unwrap().non_null_blocks_count(), 4096);
+
+        let replaced = chunk.replace_region(&BoundingBox::new(0, -100, 0, 15, 100, 15), |state| {
+            state == STONE.get_default_state()
+        }, DIRT.get_default_state()).unwrap();
+        assert_eq!(replaced, 8 * 32 * 2);
+        assert!(matches!(chunk.get_heightmap_column(&TEST, 0, 2), Ok(-16)));
+
+        let blocks: Vec<_> = chunk.iter_region_blocks(&BoundingBox::new(0, 0, 0, 1, 16, 15)).collect();
+        assert_eq!(blocks.len(), 2 * 2 * 16 + 2 * 16);
+        assert!(blocks.iter().all(|(_, state)| *state == DIRT.get_default_state()));
+        assert!(blocks.iter().any(|(pos, _)| *pos == BlockPos::new(1, 16, 15)));
+
+        assert_eq!(chunk.fill_region(&BoundingBox::new(16, 0, 0, 20, 0, 0), STONE.get_default_state()).unwrap(), 0);
+
+    }
+
+    #[test]
+    fn valid_biome_quart() {
+        let mut chunk = build_chunk();
+        chunk.set_biome(1, -3, 2, &PLAINS).unwrap();
+        assert_eq!(chunk.get_biome_quart(1, -3, 2).unwrap(), &PLAINS);
+        // Absolute quart coordinates are wrapped to the chunk.
+        assert_eq!(chunk.get_biome_quart(-7, -3, 6).unwrap(), &PLAINS);
+        assert_eq!(chunk.get_biome_quart(1, -2, 2).unwrap(), &VOID);
+        // All blocks of the quart have its biome.
+        assert_eq!(chunk.get_biome_at(7, -9, 11).unwrap(), &PLAINS);
+        assert_eq!(chunk.get_biome_at(4, -12, 8).unwrap(), &PLAINS);
+        assert!(matches!(chunk.get_biome_quart(0, -5, 0), Err(ChunkError::SubChunkOutOfRange)));
+    }
+
+    #[test]
+    fn valid_sub_chunk_empty() {
+        let mut chunk = build_chunk();
+        assert!(chunk.is_sub_chunk_empty(0));
+        assert!(chunk.is_sub_chunk_empty(5));
+        chunk.set_block(0, 20, 0, STONE.get_default_state()).unwrap();
+        assert!(!chunk.is_sub_chunk_empty(1));
+        assert!(!chunk.is_sub_chunk_empty_at(31));
+        assert!(chunk.is_sub_chunk_empty_at(15));
+        chunk.set_block(0, 20, 0, AIR.get_default_state()).unwrap();
+        assert!(chunk.is_sub_chunk_empty(1));
+    }
+
 }
\ No newline at end of file
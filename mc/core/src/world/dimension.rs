@@ -0,0 +1,328 @@
+//! Dimension types, a dimension type defines the height and the rendering properties of
+//! levels using it. Dimension types can be loaded from datapacks JSON files and are sent to
+//! clients in the registry codec of the Join Game packet.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::fs::{self, File};
+use std::sync::Arc;
+use std::io;
+
+use nbt::CompoundTag;
+use serde_json::Value;
+use thiserror::Error;
+
+use super::chunk::ChunkHeight;
+
+
+#[derive(Error, Debug)]
+pub enum DimensionTypeError {
+    #[error("Failed to read the dimension type file: {0}")]
+    Io(#[from] io::Error),
+    #[error("Invalid dimension type JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Missing or invalid field '{0}' in dimension type.")]
+    InvalidField(&'static str)
+}
+
+
+/// A dimension type, field names are the same as in datapacks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DimensionType {
+    /// The namespaced name of this dimension type.
+    pub name: String,
+    /// The minimum Y block coordinate, must be a multiple of 16.
+    pub min_y: i32,
+    /// The total height in blocks, must be a multiple of 16.
+    pub height: i32,
+    /// The maximum height for portals and chorus fruits teleportation.
+    pub logical_height: i32,
+    /// The light level of blocks without light, between 0 and 1.
+    pub ambient_light: f32,
+    pub has_skylight: bool,
+    pub has_ceiling: bool,
+    /// The scale of coordinates when traveling through nether portals.
+    pub coordinate_scale: f64,
+    /// The sky effects rendered by the client, like `minecraft:overworld`.
+    pub effects: String,
+    /// The block tag of blocks burning forever.
+    pub infiniburn: String,
+    /// If some, the time of day is fixed to this value.
+    pub fixed_time: Option<i64>,
+    /// Compasses are working and nether portals spawn zombified piglins.
+    pub natural: bool,
+    /// Water evaporates and lava spreads faster.
+    pub ultrawarm: bool,
+    pub piglin_safe: bool,
+    pub bed_works: bool,
+    pub respawn_anchor_works: bool,
+    pub has_raids: bool
+}
+
+impl DimensionType {
+
+    /// Create a dimension type with the properties of the vanilla overworld, with the given
+    /// name and height.
+    pub fn new(name: impl Into<String>, height: ChunkHeight) -> Self {
+        let min_y = height.get_min_block();
+        let height = height.get_max_block() - min_y + 1;
+        Self {
+            name: name.into(),
+            min_y,
+            height,
+            logical_height: height,
+            ambient_light: 0.0,
+            has_skylight: true,
+            has_ceiling: false,
+            coordinate_scale: 1.0,
+            effects: "minecraft:overworld".to_string(),
+            infiniburn: "minecraft:infiniburn_overworld".to_string(),
+            fixed_time: None,
+            natural: true,
+            ultrawarm: false,
+            piglin_safe: false,
+            bed_works: true,
+            respawn_anchor_works: false,
+            has_raids: true
+        }
+    }
+
+    /// Return the chunk height required by levels using this dimension type.
+    pub fn get_chunk_height(&self) -> ChunkHeight {
+        ChunkHeight::new((self.min_y >> 4) as i8, ((self.min_y + self.height - 1) >> 4) as i8)
+    }
+
+    /// Decode a dimension type from its datapack JSON representation.
+    pub fn from_json(name: impl Into<String>, json: &Value) -> Result<Self, DimensionTypeError> {
+
+        fn get_bool(json: &Value, field: &'static str) -> Result<bool, DimensionTypeError> {
+            json[field].as_bool().ok_or(DimensionTypeError::InvalidField(field))
+        }
+
+        fn get_i32(json: &Value, field: &'static str) -> Result<i32, DimensionTypeError> {
+            json[field].as_i64().map(|v| v as i32).ok_or(DimensionTypeError::InvalidField(field))
+        }
+
+        fn get_f64(json: &Value, field: &'static str) -> Result<f64, DimensionTypeError> {
+            json[field].as_f64().ok_or(DimensionTypeError::InvalidField(field))
+        }
+
+        fn get_string(json: &Value, field: &'static str) -> Result<String, DimensionTypeError> {
+            json[field].as_str().map(str::to_string).ok_or(DimensionTypeError::InvalidField(field))
+        }
+
+        let fixed_time = match &json["fixed_time"] {
+            Value::Null => None,
+            value => Some(value.as_i64().ok_or(DimensionTypeError::InvalidField("fixed_time"))?)
+        };
+
+        Ok(Self {
+            name: name.into(),
+            min_y: get_i32(json, "min_y")?,
+            height: get_i32(json, "height")?,
+            logical_height: get_i32(json, "logical_height")?,
+            ambient_light: get_f64(json, "ambient_light")? as f32,
+            has_skylight: get_bool(json, "has_skylight")?,
+            has_ceiling: get_bool(json, "has_ceiling")?,
+            coordinate_scale: get_f64(json, "coordinate_scale")?,
+            effects: json["effects"].as_str().unwrap_or("minecraft:overworld").to_string(),
+            infiniburn: get_string(json, "infiniburn")?,
+            fixed_time,
+            natural: get_bool(json, "natural")?,
+            ultrawarm: get_bool(json, "ultrawarm")?,
+            piglin_safe: get_bool(json, "piglin_safe")?,
+            bed_works: get_bool(json, "bed_works")?,
+            respawn_anchor_works: get_bool(json, "respawn_anchor_works")?,
+            has_raids: get_bool(json, "has_raids")?
+        })
+
+    }
+
+    /// Encode this dimension type to the NBT element sent to clients, the `min_y` and
+    /// `height` fields are only supported since 1.17.
+    pub fn encode(&self, dst: &mut CompoundTag, with_height: bool) {
+        dst.insert_bool("piglin_safe", self.piglin_safe);
+        dst.insert_bool("natural", self.natural);
+        dst.insert_f32("ambient_light", self.ambient_light);
+        if let Some(fixed_time) = self.fixed_time {
+            dst.insert_i64("fixed_time", fixed_time);
+        }
+        dst.insert_str("infiniburn", &self.infiniburn);
+        dst.insert_bool("respawn_anchor_works", self.respawn_anchor_works);
+        dst.insert_bool("has_skylight", self.has_skylight);
+        dst.insert_bool("bed_works", self.bed_works);
+        dst.insert_str("effects", &self.effects);
+        dst.insert_bool("has_raids", self.has_raids);
+        dst.insert_i32("logical_height", self.logical_height);
+        if with_height {
+            dst.insert_i32("min_y", self.min_y);
+            dst.insert_i32("height", self.height);
+        }
+        dst.insert_f64("coordinate_scale", self.coordinate_scale);
+        dst.insert_bool("ultrawarm", self.ultrawarm);
+        dst.insert_bool("has_ceiling", self.has_ceiling);
+    }
+
+}
+
+
+/// A registry of dimension types, dimension types are identified by their name and their
+/// registration order.
+#[derive(Debug, Default)]
+pub struct DimensionTypes {
+    dimension_types: Vec<Arc<DimensionType>>,
+    name_to_index: HashMap<String, usize>
+}
+
+impl DimensionTypes {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a dimension type, a previous dimension type with the same name is replaced
+    /// and keeps its ID.
+    pub fn register(&mut self, dimension_type: Arc<DimensionType>) {
+        match self.name_to_index.get(&dimension_type.name) {
+            Some(&index) => self.dimension_types[index] = dimension_type,
+            None => {
+                self.name_to_index.insert(dimension_type.name.clone(), self.dimension_types.len());
+                self.dimension_types.push(dimension_type);
+            }
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Arc<DimensionType>> {
+        self.name_to_index.get(name).map(|&index| &self.dimension_types[index])
+    }
+
+    pub fn get_id(&self, name: &str) -> Option<usize> {
+        self.name_to_index.get(name).copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<DimensionType>> + '_ {
+        self.dimension_types.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.dimension_types.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dimension_types.is_empty()
+    }
+
+    /// Load all dimension types of the datapack in the given directory, dimension types are
+    /// stored in `data/<namespace>/dimension_type/<name>.json`. Return the number of loaded
+    /// dimension types.
+    pub fn load_datapack<P: AsRef<Path>>(&mut self, dir: P) -> Result<usize, DimensionTypeError> {
+
+        let data_dir = dir.as_ref().join("data");
+        if !data_dir.is_dir() {
+            return Ok(0);
+        }
+
+        let mut count = 0;
+
+        for namespace_entry in fs::read_dir(data_dir)? {
+
+            let namespace_entry = namespace_entry?;
+            let dimension_types_dir = namespace_entry.path().join("dimension_type");
+            if !dimension_types_dir.is_dir() {
+                continue;
+            }
+
+            let namespace = namespace_entry.file_name();
+            let namespace = namespace.to_string_lossy();
+
+            for entry in fs::read_dir(dimension_types_dir)? {
+                let path = entry?.path();
+                if path.extension().is_some_and(|ext| ext == "json") {
+                    if let Some(stem) = path.file_stem() {
+                        let name = format!("{}:{}", namespace, stem.to_string_lossy());
+                        let json: Value = serde_json::from_reader(File::open(&path)?)?;
+                        self.register(Arc::new(DimensionType::from_json(name, &json)?));
+                        count += 1;
+                    }
+                }
+            }
+
+        }
+
+        Ok(count)
+
+    }
+
+    /// Encode the `minecraft:dimension_type` registry of the registry codec sent in the Join
+    /// Game packet.
+    pub fn encode_registry(&self, with_height: bool) -> CompoundTag {
+        let mut tag_registry = CompoundTag::new();
+        tag_registry.insert_str("type", "minecraft:dimension_type");
+        tag_registry.insert_compound_tag_vec("value", self.dimension_types.iter()
+            .enumerate()
+            .map(|(id, dimension_type)| {
+                let mut tag_value = CompoundTag::new();
+                tag_value.insert_str("name", &dimension_type.name);
+                tag_value.insert_i32("id", id as i32);
+                tag_value.insert_compound_tag("element", {
+                    let mut tag_element = CompoundTag::new();
+                    dimension_type.encode(&mut tag_element, with_height);
+                    tag_element
+                });
+                tag_value
+            }));
+        tag_registry
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn dimension_types() {
+
+        let json: Value = serde_json::from_str(r#"{
+            "ultrawarm": false,
+            "natural": false,
+            "piglin_safe": false,
+            "respawn_anchor_works": false,
+            "bed_works": false,
+            "has_raids": false,
+            "has_skylight": false,
+            "has_ceiling": true,
+            "coordinate_scale": 2,
+            "ambient_light": 0.5,
+            "fixed_time": 1000,
+            "logical_height": 64,
+            "effects": "minecraft:the_nether",
+            "infiniburn": "minecraft:infiniburn_nether",
+            "min_y": -64,
+            "height": 128
+        }"#).unwrap();
+
+        let caves = DimensionType::from_json("test:caves", &json).unwrap();
+        assert_eq!(caves.coordinate_scale, 2.0);
+        assert_eq!(caves.fixed_time, Some(1000));
+        assert_eq!(caves.get_chunk_height(), ChunkHeight::new(-4, 3));
+        assert!(matches!(DimensionType::from_json("test:invalid", &Value::Null), Err(DimensionTypeError::InvalidField(_))));
+
+        let mut dimension_types = DimensionTypes::new();
+        dimension_types.register(Arc::new(DimensionType::new("minecraft:overworld", ChunkHeight::new(0, 15))));
+        dimension_types.register(Arc::new(caves));
+        assert_eq!(dimension_types.get_id("test:caves"), Some(1));
+        assert_eq!(dimension_types.get("minecraft:overworld").unwrap().height, 256);
+
+        let tag_registry = dimension_types.encode_registry(true);
+        let tag_values = tag_registry.get_compound_tag_vec("value").unwrap();
+        assert_eq!(tag_values.len(), 2);
+        let tag_element = tag_values[1].get_compound_tag("element").unwrap();
+        assert_eq!(tag_element.get_i32("min_y").unwrap(), -64);
+        assert_eq!(tag_element.get_i64("fixed_time").unwrap(), 1000);
+
+    }
+
+}
@@ -1,52 +1,81 @@
 use std::sync::{RwLock, Arc, RwLockReadGuard, RwLockWriteGuard};
 use std::collections::{HashMap, HashSet};
-use std::collections::hash_map::Entry;
 use std::fmt::{Debug, Formatter};
+use std::ops::{Deref, DerefMut};
 use std::time::Duration;
 
 use hecs::{World as EcsWorld, EntityBuilder, Entity, EntityRef};
+use thiserror::Error;
 use uuid::Uuid;
+use nbt::CompoundTag;
 
 use crate::entity::{GlobalEntities, EntityType};
-use crate::block::{GlobalBlocks, BlockState};
-use crate::biome::GlobalBiomes;
-use crate::heightmap::GlobalHeightmaps;
-use crate::pos::{EntityPos, BlockPos};
+use crate::block::{GlobalBlocks, FrozenBlocks, BlockState, Block};
+use crate::biome::{GlobalBiomes, FrozenBiomes, Biome};
+use crate::heightmap::{GlobalHeightmaps, HeightmapType};
+use crate::tag::TagType;
+use crate::pos::{EntityPos, BlockPos, BoundingBox};
 use crate::debug;
 
 use super::source::{LevelSource, ChunkLoadRequest, ChunkSaveRequest, LevelSourceError, ProtoChunk};
 use super::chunk::{Chunk, ChunkHeight, ChunkResult, ChunkError};
+use super::anvil::encode::encode_block_entity;
+use super::anvil::fix::LegacyBlockResolver;
+use super::ticket::ChunkTickets;
+use super::dimension::DimensionType;
+use super::light::{LightProperties, relight_chunks};
 
 
 /// A structure that contains the static environment of a World, this can be used for multiple
-/// `Level`s through an `Arc<LevelEnv>`.
+/// `Level`s through an `Arc<LevelEnv>`. Blocks and biomes palettes are frozen and can also
+/// be shared on their own.
 pub struct LevelEnv {
     /// Global blocks palette.
-    pub blocks: GlobalBlocks,
+    pub blocks: Arc<FrozenBlocks>,
     /// Global biomes palette.
-    pub biomes: GlobalBiomes,
+    pub biomes: Arc<FrozenBiomes>,
     /// Global entity types palette.
     pub entities: GlobalEntities,
     /// Global heightmap types palette.
-    pub heightmaps: GlobalHeightmaps
+    pub heightmaps: GlobalHeightmaps,
+    /// Flattening table of legacy block ids, used to decode chunks saved before 1.13.
+    pub legacy_blocks: Option<LegacyBlockResolver>
 }
 
 impl LevelEnv {
 
+    /// Construct an environment from the given palettes, blocks and biomes are frozen.
     pub fn new(
         blocks: GlobalBlocks,
         biomes: GlobalBiomes,
         entities: GlobalEntities,
         heightmaps: GlobalHeightmaps
+    ) -> Self {
+        Self::with_frozen(blocks.freeze(), biomes.freeze(), entities, heightmaps)
+    }
+
+    /// Construct an environment from already frozen blocks and biomes palettes, these can
+    /// be shared with other environments.
+    pub fn with_frozen(
+        blocks: Arc<FrozenBlocks>,
+        biomes: Arc<FrozenBiomes>,
+        entities: GlobalEntities,
+        heightmaps: GlobalHeightmaps
     ) -> Self {
         LevelEnv {
             blocks,
             biomes,
             entities,
-            heightmaps
+            heightmaps,
+            legacy_blocks: None
         }
     }
 
+    /// Return a new builder to construct an environment from its registries.
+    pub fn builder() -> LevelEnvBuilder {
+        LevelEnvBuilder::new()
+    }
+
 }
 
 impl Debug for LevelEnv {
@@ -61,9 +90,142 @@ impl Debug for LevelEnv {
 }
 
 
+/// Errors that can happen while building a `LevelEnv` with a `LevelEnvBuilder`.
+#[derive(Error, Debug)]
+pub enum LevelEnvError {
+    #[error("Too many block states to register the block '{0}'.")]
+    TooManyBlockStates(&'static str),
+    #[error("Too many biomes to register the biome '{0}'.")]
+    TooManyBiomes(&'static str),
+    #[error("Failed to set the tag '{0}', some blocks are not registered.")]
+    InvalidBlockTag(&'static str),
+}
+
+
+/// A builder for `LevelEnv` that bundles all global registries and block tag types. The
+/// first error encountered while registering is kept and returned by `build`.
+pub struct LevelEnvBuilder {
+    blocks: GlobalBlocks,
+    biomes: GlobalBiomes,
+    entities: GlobalEntities,
+    heightmaps: GlobalHeightmaps,
+    legacy_blocks: Option<LegacyBlockResolver>,
+    error: Option<LevelEnvError>
+}
+
+impl LevelEnvBuilder {
+
+    pub fn new() -> Self {
+        Self {
+            blocks: GlobalBlocks::new(),
+            biomes: GlobalBiomes::new(),
+            entities: GlobalEntities::new(),
+            heightmaps: GlobalHeightmaps::new(),
+            legacy_blocks: None,
+            error: None
+        }
+    }
+
+    /// Replace the whole blocks palette, previously registered blocks and tags are lost.
+    pub fn with_blocks(mut self, blocks: GlobalBlocks) -> Self {
+        self.blocks = blocks;
+        self
+    }
+
+    /// Replace the whole biomes palette, previously registered biomes are lost.
+    pub fn with_biomes(mut self, biomes: GlobalBiomes) -> Self {
+        self.biomes = biomes;
+        self
+    }
+
+    /// Replace the whole entity types palette, previously registered entity types are lost.
+    pub fn with_entities(mut self, entities: GlobalEntities) -> Self {
+        self.entities = entities;
+        self
+    }
+
+    /// Replace the whole heightmaps palette, previously registered heightmaps are lost.
+    pub fn with_heightmaps(mut self, heightmaps: GlobalHeightmaps) -> Self {
+        self.heightmaps = heightmaps;
+        self
+    }
+
+    /// Set the flattening table used to decode legacy block ids of chunks saved before 1.13,
+    /// it must only return states of the registered blocks.
+    pub fn with_legacy_blocks(mut self, resolver: LegacyBlockResolver) -> Self {
+        self.legacy_blocks = Some(resolver);
+        self
+    }
+
+    pub fn register_blocks(mut self, slice: &[&'static Block]) -> Self {
+        for &block in slice {
+            if self.error.is_none() && self.blocks.register(block).is_err() {
+                self.error = Some(LevelEnvError::TooManyBlockStates(block.get_name()));
+            }
+        }
+        self
+    }
+
+    pub fn register_biomes(mut self, slice: &[&'static Biome]) -> Self {
+        for &biome in slice {
+            if self.error.is_none() && self.biomes.register(biome).is_err() {
+                self.error = Some(LevelEnvError::TooManyBiomes(biome.get_name()));
+            }
+        }
+        self
+    }
+
+    pub fn register_entities(mut self, slice: &[&'static EntityType]) -> Self {
+        self.entities.register_all(slice);
+        self
+    }
+
+    pub fn register_heightmaps(mut self, slice: &[&'static HeightmapType]) -> Self {
+        self.heightmaps.register_all(slice);
+        self
+    }
+
+    /// Register a block tag type and set it on the given blocks, these blocks must be
+    /// registered before.
+    pub fn register_block_tag<I>(mut self, tag_type: &'static TagType, blocks: I) -> Self
+    where
+        I: IntoIterator<Item = &'static Block>
+    {
+        self.blocks.register_tag_type(tag_type);
+        if self.error.is_none() {
+            let blocks: Vec<&'static Block> = blocks.into_iter().collect();
+            if !blocks.iter().all(|&block| self.blocks.has_block(block)) ||
+                self.blocks.set_blocks_tag(tag_type, true, blocks).is_err() {
+                self.error = Some(LevelEnvError::InvalidBlockTag(tag_type.name));
+            }
+        }
+        self
+    }
+
+    /// Build the environment, returning the first error encountered, if any.
+    pub fn build(self) -> Result<LevelEnv, LevelEnvError> {
+        match self.error {
+            Some(err) => Err(err),
+            None => {
+                let mut env = LevelEnv::new(self.blocks, self.biomes, self.entities, self.heightmaps);
+                env.legacy_blocks = self.legacy_blocks;
+                Ok(env)
+            }
+        }
+    }
+
+}
+
+impl Default for LevelEnvBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
 /// Main storage for a level, part of a World. This structure is intentionally not `Sync + Send`,
-/// however each chunk is stored in a `RwLock` in order to make them shared across threads if
-/// you want.
+/// however its chunk storage can be shared across threads through its `Arc`, see
+/// `ChunkStorage`.
 pub struct Level {
     /// The unique ID of this level (among all levels of the world).
     id: String,
@@ -77,34 +239,43 @@ pub struct Level {
     loading_chunks: HashSet<(i32, i32)>,
     /// The configured height of this level.
     height: ChunkHeight,
-    /// Chunk storage.
-    pub chunks: ChunkStorage,
+    /// The dimension type of this level, sent to clients.
+    dimension_type: Arc<DimensionType>,
+    /// Chunk storage, shared to allow concurrent accesses to chunks from other threads.
+    pub chunks: Arc<ChunkStorage>,
     /// Entities storage.
-    pub entities: EntityStorage
+    pub entities: EntityStorage,
+    /// Tickets of chunks that must be kept loaded.
+    pub tickets: ChunkTickets
 }
 
 impl Level {
 
-    pub fn new<S>(id: String, env: Arc<LevelEnv>, height: ChunkHeight, source: S) -> Self
+    /// Construct a new level, the environment can be given directly or through an `Arc` if
+    /// it's shared with other levels.
+    pub fn new<E, S>(id: String, env: E, height: ChunkHeight, source: S) -> Self
     where
+        E: Into<Arc<LevelEnv>>,
         S: LevelSource + 'static,
     {
 
+        let env = env.into();
+
         assert_ne!(env.blocks.states_count(), 0, "The given environment has no state, a level requires at least one block state.");
         assert_ne!(env.biomes.biomes_count(), 0, "The given environment has no biome, a level requires at least one biome.");
 
         Level {
             id,
             height,
+            dimension_type: Arc::new(DimensionType::new("minecraft:overworld", height)),
             source: Box::new(source),
             loading_chunks: HashSet::new(),
-            chunks: ChunkStorage {
-                chunks: HashMap::new()
-            },
+            chunks: Arc::new(ChunkStorage::new()),
             entities: EntityStorage {
                 ecs: EcsWorld::new(),
                 builder: EntityBuilder::new()
             },
+            tickets: ChunkTickets::new(),
             env,
         }
 
@@ -126,6 +297,20 @@ impl Level {
         self.height
     }
 
+    /// Set the dimension type of this level, by default levels use an overworld-like
+    /// dimension type with the level's height. The height of the dimension type should
+    /// match the level's height.
+    pub fn with_dimension_type(mut self, dimension_type: Arc<DimensionType>) -> Self {
+        debug_assert_eq!(dimension_type.get_chunk_height(), self.height, "The dimension type height doesn't match the level height.");
+        self.dimension_type = dimension_type;
+        self
+    }
+
+    /// Return the dimension type of this level.
+    pub fn get_dimension_type(&self) -> &Arc<DimensionType> {
+        &self.dimension_type
+    }
+
     // CHUNKS LOADING (FROM SOURCE) //
 
     /// Request internal level source to load the given chunk.
@@ -149,6 +334,17 @@ impl Level {
         }
     }
 
+    /// Request loading of all chunks with a ticket that are not loaded or loading, return
+    /// the number of requested chunks.
+    pub fn request_ticketed_chunks_load(&mut self) -> usize {
+        let chunks: Vec<(i32, i32)> = self.tickets.iter_chunks()
+            .filter(|&(cx, cz)| !self.chunks.is_chunk_loaded(cx, cz))
+            .collect();
+        chunks.into_iter()
+            .filter(|&(cx, cz)| self.request_chunk_load(cx, cz))
+            .count()
+    }
+
     /// Poll loaded chunks from internal level source, all successfully loaded chunks
     /// are added to the underlying `LevelStorage`. The callback is called for each
     /// loaded chunks or loading error.
@@ -190,21 +386,28 @@ impl Level {
                             // Here we don't check is passengers is empty because ProtoChunk only
                             // set 'Some' if there are passengers.
 
-                            // SAFETY: Unwrap is safe because the entity was just created with `BaseEntity` component.
-                            let mut base_entity = self.entities.ecs.get_mut::<BaseEntity>(entities_handles[i]).unwrap();
+                            let vehicle = entities_handles[i];
                             let mut passengers_handles = Vec::with_capacity(passengers.len());
 
                             for passenger_proto_index in passengers {
-                                passengers_handles.push(entities_handles[passenger_proto_index]);
+                                let passenger = entities_handles[passenger_proto_index];
+                                self.entities.ecs.insert_one(passenger, Passenger { vehicle }).unwrap();
+                                passengers_handles.push(passenger);
                             }
 
-                            base_entity.passengers = Some(passengers_handles);
+                            // SAFETY: Unwrap is safe because the entity was just created with `BaseEntity` component.
+                            self.entities.ecs.get_mut::<BaseEntity>(vehicle).unwrap().passengers = Some(passengers_handles);
 
                         }
                     }
 
+                    // Chunks loaded unchanged from their source are already saved.
+                    if !dirty {
+                        chunk.update_last_save();
+                    }
+
                     let chunk_arc = self.chunks.insert_chunk(chunk);
-                    callback(cx, cz, Ok(chunk_arc));
+                    callback(cx, cz, Ok(&chunk_arc));
 
                     if dirty {
                         self.request_chunk_save(cx, cz);
@@ -247,18 +450,65 @@ impl Level {
 
     // CHUNK SAVING (TO SOURCE) //
 
+    /// Request saving of the chunk at the given position to the level source, returns true
+    /// if the request was accepted, in such case the chunk is considered saved.
     pub fn request_chunk_save(&mut self, cx: i32, cz: i32) -> bool {
         if let Some(chunk) = self.chunks.get_chunk_arc(cx, cz) {
-            self.source.request_chunk_save(ChunkSaveRequest {
+            // Block entities are encoded here because the source can't access the ECS.
+            let block_entities = chunk.read().unwrap().iter_entities()
+                .filter_map(|entity| self.entities.ecs.entity(entity).ok())
+                .filter_map(|entity_ref| encode_block_entity(&entity_ref, &self.env.entities))
+                .collect();
+            let accepted = self.source.request_chunk_save(ChunkSaveRequest {
                 cx,
                 cz,
-                chunk
-            }).is_ok()
+                chunk: Arc::clone(&chunk),
+                block_entities
+            }).is_ok();
+            if accepted {
+                chunk.write().unwrap().update_last_save();
+            }
+            accepted
         } else {
             false
         }
     }
 
+    /// Request saving of all loaded chunks modified since their last save, returns the number
+    /// of accepted save requests.
+    pub fn request_modified_chunks_save(&mut self) -> usize {
+        let chunks: Vec<(i32, i32)> = self.chunks.iter_chunks()
+            .filter_map(|chunk| {
+                let chunk = chunk.read().unwrap();
+                chunk.is_modified_since_save().then(|| chunk.get_position())
+            })
+            .collect();
+        chunks.into_iter()
+            .filter(|&(cx, cz)| self.request_chunk_save(cx, cz))
+            .count()
+    }
+
+    /// Save all loaded chunks modified since their last save and block until the level source
+    /// has written them, returns the number of saved chunks.
+    pub fn save_all(&mut self) -> Result<usize, LevelSourceError> {
+        let count = self.request_modified_chunks_save();
+        self.source.flush()?;
+        Ok(count)
+    }
+
+    // LIGHTS //
+
+    /// Recompute all lights of the given loaded chunks from scratch, this can be used to repair
+    /// chunks with corrupted or missing light data, see `light::relight_chunks`. Sky lights are
+    /// only computed if the dimension type of this level has sky light. Returns the number of
+    /// relit chunks, unloaded chunks are ignored.
+    pub fn relight_chunks<I>(&self, chunks: I, props: &dyn LightProperties) -> usize
+    where
+        I: IntoIterator<Item = (i32, i32)>
+    {
+        relight_chunks(&self.chunks, chunks, props, self.dimension_type.has_skylight)
+    }
+
     // ENTITIES //
 
     pub fn spawn_entity(&mut self, entity_type: &'static EntityType, pos: EntityPos) -> Option<Entity> {
@@ -282,79 +532,303 @@ impl Level {
 
     }
 
+    /// Spawn a block entity at the given position, the block entity is added to the chunk at
+    /// this position if it's loaded. Return `None` if the block entity type is not supported by
+    /// the level's environment.
+    pub fn spawn_block_entity(&mut self, entity_type: &'static EntityType, pos: BlockPos) -> Option<Entity> {
+
+        if !self.env.entities.has_entity_type(entity_type) {
+            return None;
+        }
+
+        let chunk = self.chunks.get_chunk_at_block_mut(pos.clone());
+        let entity = unsafe { self.entities.spawn_block_entity_unchecked(entity_type, pos) };
+
+        if let Some(mut chunk) = chunk {
+            unsafe {
+                chunk.add_entity_unchecked(entity);
+            }
+        }
+
+        Some(entity)
+
+    }
+
+    /// Move an entity to the given position, the entity handle is also moved between chunks
+    /// if it crosses a chunk border. Return false if the entity doesn't exist.
+    pub fn move_entity(&mut self, entity: Entity, pos: EntityPos) -> bool {
+
+        let mut base_entity = match self.entities.ecs.get_mut::<BaseEntity>(entity) {
+            Ok(base_entity) => base_entity,
+            Err(_) => return false
+        };
+
+        let from = BlockPos::from(&base_entity.pos);
+        let to = BlockPos::from(&pos);
+        base_entity.pos = pos;
+
+        if (from.x >> 4, from.z >> 4) != (to.x >> 4, to.z >> 4) {
+            if let Some(mut chunk) = self.chunks.get_chunk_at_block_mut(from) {
+                unsafe {
+                    chunk.remove_entity_unchecked(entity);
+                }
+            }
+            if let Some(mut chunk) = self.chunks.get_chunk_at_block_mut(to) {
+                unsafe {
+                    chunk.add_entity_unchecked(entity);
+                }
+            }
+        }
+
+        true
+
+    }
+
+    /// Transfer an entity to another level at the given position, components are moved by
+    /// encoding and decoding them with the codecs of the entity type, so components without
+    /// codec are lost and must be moved by the caller. The UUID of the entity is kept, but
+    /// the entity dismounts its vehicle and its passengers are ejected.
+    ///
+    /// Return the handle of the entity in the destination level, `None` if the entity doesn't
+    /// exist, if its type is not supported by the destination level or if its components
+    /// can't be encoded, in which case the entity is not removed.
+    pub fn transfer_entity(&mut self, dst: &mut Level, entity: Entity, pos: EntityPos) -> Option<Entity> {
+
+        let (entity_type, uuid) = {
+            let base_entity = self.entities.ecs.get::<BaseEntity>(entity).ok()?;
+            (base_entity.entity_type, base_entity.uuid)
+        };
+
+        if !dst.env.entities.has_entity_type(entity_type) {
+            return None;
+        }
+
+        let mut tag = CompoundTag::new();
+        let entity_ref = self.entities.get_entity_ref(entity)?;
+        for &codec in entity_type.codecs {
+            codec.encode(&entity_ref, &mut tag).ok()?;
+        }
+
+        let mut builder = EntityBuilder::new();
+        for &codec in entity_type.codecs {
+            codec.decode(&tag, &mut builder).ok()?;
+        }
+
+        self.remove_entity(entity);
+
+        builder.add(BaseEntity::new(entity_type, uuid, pos.clone()));
+        let new_entity = unsafe { dst.entities.add_entity_unchecked(&mut builder) };
+        if let Some(mut chunk) = dst.chunks.get_chunk_at_block_mut(BlockPos::from(&pos)) {
+            unsafe {
+                chunk.add_entity_unchecked(new_entity);
+            }
+        }
+
+        Some(new_entity)
+
+    }
+
+    /// Remove an entity from the level and from the chunk it's located in, the entity
+    /// dismounts its vehicle and its passengers are ejected. Return false if the entity
+    /// doesn't exist.
+    pub fn remove_entity(&mut self, entity: Entity) -> bool {
+
+        let pos = match self.entities.ecs.get::<BaseEntity>(entity) {
+            Ok(base_entity) => BlockPos::from(&base_entity.pos),
+            Err(_) => return false
+        };
+
+        self.dismount_entity(entity);
+        self.eject_passengers(entity);
+
+        if let Some(mut chunk) = self.chunks.get_chunk_at_block_mut(pos) {
+            unsafe {
+                chunk.remove_entity_unchecked(entity);
+            }
+        }
+
+        self.entities.remove_entity(entity)
+
+    }
+
+    /// Make an entity ride a vehicle, the entity first dismounts its current vehicle. Return
+    /// false if one of the entities doesn't exist, or if the vehicle is the entity itself or
+    /// is riding it.
+    pub fn mount_entity(&mut self, passenger: Entity, vehicle: Entity) -> bool {
+
+        let ecs = &self.entities.ecs;
+        if ecs.get::<BaseEntity>(passenger).is_err() || ecs.get::<BaseEntity>(vehicle).is_err() {
+            return false;
+        }
+
+        let mut current = Some(vehicle);
+        while let Some(entity) = current {
+            if entity == passenger {
+                return false;
+            }
+            current = self.get_vehicle(entity);
+        }
+
+        self.dismount_entity(passenger);
+        self.entities.ecs.get_mut::<BaseEntity>(vehicle).unwrap()
+            .passengers.get_or_insert_with(Vec::new)
+            .push(passenger);
+        self.entities.ecs.insert_one(passenger, Passenger { vehicle }).unwrap();
+        true
+
+    }
+
+    /// Make an entity stop riding its vehicle, the entity keeps its position. Return the
+    /// vehicle, `None` if the entity was not riding.
+    pub fn dismount_entity(&mut self, passenger: Entity) -> Option<Entity> {
+
+        let Passenger { vehicle } = self.entities.ecs.remove_one::<Passenger>(passenger).ok()?;
+
+        if let Ok(mut base_entity) = self.entities.ecs.get_mut::<BaseEntity>(vehicle) {
+            if let Some(passengers) = &mut base_entity.passengers {
+                passengers.retain(|&other| other != passenger);
+                if passengers.is_empty() {
+                    base_entity.passengers = None;
+                }
+            }
+        }
+
+        Some(vehicle)
+
+    }
+
+    /// Make all passengers of a vehicle dismount it, the dismounted passengers are returned.
+    pub fn eject_passengers(&mut self, vehicle: Entity) -> Vec<Entity> {
+
+        let passengers = match self.entities.ecs.get_mut::<BaseEntity>(vehicle) {
+            Ok(mut base_entity) => base_entity.passengers.take().unwrap_or_default(),
+            Err(_) => return Vec::new()
+        };
+
+        for &passenger in &passengers {
+            let _ = self.entities.ecs.remove_one::<Passenger>(passenger);
+        }
+
+        passengers
+
+    }
+
+    /// Return the vehicle ridden by an entity, if any.
+    pub fn get_vehicle(&self, entity: Entity) -> Option<Entity> {
+        self.entities.ecs.get::<Passenger>(entity).ok().map(|passenger| passenger.vehicle)
+    }
+
+    /// Return the lowest vehicle of the stack of vehicles ridden by an entity, the entity
+    /// itself if it's not riding.
+    pub fn get_root_vehicle(&self, entity: Entity) -> Entity {
+        let mut root = entity;
+        while let Some(vehicle) = self.get_vehicle(root) {
+            root = vehicle;
+        }
+        root
+    }
+
 }
 
 
+/// Number of shards of a chunk storage, must be a power of two.
+const CHUNK_STORAGE_SHARDS: usize = 16;
+
+/// A shard of a chunk storage, mapping chunk positions to chunks.
+type ChunkShard = RwLock<HashMap<(i32, i32), Arc<RwLock<Chunk>>>>;
+
+/// Chunk storage of a level, chunks are spread over multiple shards, each shard being a map
+/// protected by its own lock, and each chunk is also protected by its own lock. All methods
+/// take a shared reference, so the storage can be shared between threads (for example with
+/// the network thread) and distant chunks can be ticked in parallel.
+///
+/// Chunk guards returned by this storage keep their chunk alive but don't lock any shard, so
+/// chunks can be inserted or removed while guards are held.
 pub struct ChunkStorage {
     /// Storing all cached chunks that were loaded from source.
-    chunks: HashMap<(i32, i32), Arc<RwLock<Chunk>>>,
+    shards: Box<[ChunkShard]>
 }
 
 impl ChunkStorage {
 
+    pub fn new() -> Self {
+        Self {
+            shards: (0..CHUNK_STORAGE_SHARDS).map(|_| RwLock::new(HashMap::new())).collect()
+        }
+    }
+
+    /// Internal method to get the shard of a chunk, the 4x4 chunks around any chunk are all
+    /// in different shards.
+    #[inline]
+    fn get_shard(&self, cx: i32, cz: i32) -> &ChunkShard {
+        &self.shards[((cx & 3) | ((cz & 3) << 2)) as usize & (CHUNK_STORAGE_SHARDS - 1)]
+    }
+
     // CHUNKS //
 
     pub fn get_chunks_count(&self) -> usize {
-        self.chunks.len()
+        self.shards.iter().map(|shard| shard.read().unwrap().len()).sum()
     }
 
-    pub fn iter_chunks(&self) -> impl Iterator<Item = &Arc<RwLock<Chunk>>> + '_ {
-        self.chunks.values()
+    /// Iterate over all chunks, shards are locked one after the other and their chunks are
+    /// collected, so no lock is held by the iterator.
+    pub fn iter_chunks(&self) -> impl Iterator<Item = Arc<RwLock<Chunk>>> + '_ {
+        self.shards.iter().flat_map(|shard| {
+            shard.read().unwrap().values().cloned().collect::<Vec<_>>()
+        })
     }
 
-    /// Insert a chunk at a specific position.
-    pub fn insert_chunk(&mut self, chunk: Chunk) -> &Arc<RwLock<Chunk>> {
-        let pos = chunk.get_position();
+    /// Insert a chunk at a specific position, replacing the previous one if any.
+    pub fn insert_chunk(&self, chunk: Chunk) -> Arc<RwLock<Chunk>> {
+        let (cx, cz) = chunk.get_position();
         let arc = Arc::new(RwLock::new(chunk));
-        match self.chunks.entry(pos) {
-            Entry::Occupied(mut o) => {
-                o.insert(arc);
-                o.into_mut()
-            },
-            Entry::Vacant(v) => {
-                v.insert(arc)
-            }
-        }
+        self.get_shard(cx, cz).write().unwrap().insert((cx, cz), Arc::clone(&arc));
+        arc
+    }
+
+    /// Remove the chunk at a specific position, the chunk is dropped when all its guards are.
+    pub fn remove_chunk(&self, cx: i32, cz: i32) -> Option<Arc<RwLock<Chunk>>> {
+        self.get_shard(cx, cz).write().unwrap().remove(&(cx, cz))
     }
 
     pub fn get_chunk_arc(&self, cx: i32, cz: i32) -> Option<Arc<RwLock<Chunk>>> {
-        self.chunks.get(&(cx, cz)).map(Arc::clone)
+        self.get_shard(cx, cz).read().unwrap().get(&(cx, cz)).map(Arc::clone)
     }
 
     /// Return true if a chunk is loaded at a specific position.
     pub fn is_chunk_loaded(&self, cx: i32, cz: i32) -> bool {
-        self.chunks.contains_key(&(cx, cz))
+        self.get_shard(cx, cz).read().unwrap().contains_key(&(cx, cz))
     }
 
     /// Get a chunk reference at specific coordinates.
-    pub fn get_chunk(&self, cx: i32, cz: i32) -> Option<RwLockReadGuard<Chunk>> {
-        self.chunks.get(&(cx, cz)).map(|arc| arc.read().unwrap())
+    pub fn get_chunk(&self, cx: i32, cz: i32) -> Option<ChunkReadGuard> {
+        self.get_chunk_arc(cx, cz).map(ChunkReadGuard::new)
     }
 
     /// Get a mutable chunk reference at specific coordinates.
-    pub fn get_chunk_mut(&self, cx: i32, cz: i32) -> Option<RwLockWriteGuard<Chunk>> {
-        self.chunks.get(&(cx, cz)).map(|arc| arc.write().unwrap())
+    pub fn get_chunk_mut(&self, cx: i32, cz: i32) -> Option<ChunkWriteGuard> {
+        self.get_chunk_arc(cx, cz).map(ChunkWriteGuard::new)
     }
 
     /// Get a chunk reference at specific blocks coordinates.
     #[inline]
-    pub fn get_chunk_at(&self, x: i32, z: i32) -> Option<RwLockReadGuard<Chunk>> {
+    pub fn get_chunk_at(&self, x: i32, z: i32) -> Option<ChunkReadGuard> {
         self.get_chunk(x >> 4, z >> 4)
     }
 
     /// Get a mutable chunk reference at specific blocks coordinates.
     #[inline]
-    pub fn get_chunk_at_mut(&self, x: i32, z: i32) -> Option<RwLockWriteGuard<Chunk>> {
+    pub fn get_chunk_at_mut(&self, x: i32, z: i32) -> Option<ChunkWriteGuard> {
         self.get_chunk_mut(x >> 4, z >> 4)
     }
 
     #[inline]
-    pub fn get_chunk_at_block(&self, block_pos: BlockPos) -> Option<RwLockReadGuard<Chunk>> {
+    pub fn get_chunk_at_block(&self, block_pos: BlockPos) -> Option<ChunkReadGuard> {
         self.get_chunk_at(block_pos.x, block_pos.z)
     }
 
     #[inline]
-    pub fn get_chunk_at_block_mut(&self, block_pos: BlockPos) -> Option<RwLockWriteGuard<Chunk>> {
+    pub fn get_chunk_at_block_mut(&self, block_pos: BlockPos) -> Option<ChunkWriteGuard> {
         self.get_chunk_at_mut(block_pos.x, block_pos.z)
     }
 
@@ -376,6 +850,135 @@ impl ChunkStorage {
         }
     }
 
+    /// Get a biome at the given quart coordinates, see `Chunk::get_biome_quart`.
+    pub fn get_biome_quart(&self, x: i32, y: i32, z: i32) -> ChunkResult<&'static Biome> {
+        if let Some(chunk) = self.get_chunk(x >> 2, z >> 2) {
+            chunk.get_biome_quart(x, y, z)
+        } else {
+            Err(ChunkError::ChunkUnloaded)
+        }
+    }
+
+    #[inline]
+    pub fn get_biome_at(&self, x: i32, y: i32, z: i32) -> ChunkResult<&'static Biome> {
+        self.get_biome_quart(x >> 2, y >> 2, z >> 2)
+    }
+
+    // REGIONS //
+
+    /// Iterate over the positions of all chunks intersecting the given region.
+    fn iter_region_chunks(region: &BoundingBox) -> impl Iterator<Item = (i32, i32)> {
+        let (min_cx, max_cx) = (region.min_x >> 4, region.max_x >> 4);
+        let (min_cz, max_cz) = (region.min_z >> 4, region.max_z >> 4);
+        (min_cz..=max_cz).flat_map(move |cz| (min_cx..=max_cx).map(move |cx| (cx, cz)))
+    }
+
+    /// Set all blocks of the given region to the given state, see `Chunk::fill_region`. All
+    /// chunks intersecting the region must be loaded, if not `Err(ChunkError::ChunkUnloaded)`
+    /// is returned and no block is modified. Returns the number of blocks set.
+    pub fn fill_region(&self, region: &BoundingBox, state: &'static BlockState) -> ChunkResult<usize> {
+        self.edit_region(region, |chunk| chunk.fill_region(region, state))
+    }
+
+    /// Same as `fill_region` but only replace blocks matching the given predicate.
+    pub fn replace_region<P>(&self, region: &BoundingBox, mut predicate: P, state: &'static BlockState) -> ChunkResult<usize>
+    where
+        P: FnMut(&'static BlockState) -> bool
+    {
+        self.edit_region(region, |chunk| chunk.replace_region(region, &mut predicate, state))
+    }
+
+    fn edit_region<F>(&self, region: &BoundingBox, mut func: F) -> ChunkResult<usize>
+    where
+        F: FnMut(&mut Chunk) -> ChunkResult<usize>
+    {
+        if !Self::iter_region_chunks(region).all(|(cx, cz)| self.is_chunk_loaded(cx, cz)) {
+            return Err(ChunkError::ChunkUnloaded);
+        }
+        let mut count = 0;
+        for (cx, cz) in Self::iter_region_chunks(region) {
+            count += func(&mut self.get_chunk_mut(cx, cz).unwrap())?;
+        }
+        Ok(count)
+    }
+
+    /// Iterate over all non null blocks in the loaded chunks of the given region, see
+    /// `Chunk::iter_region_blocks`. Chunks are locked one after the other and blocks of a
+    /// chunk are collected before being returned, so no lock is held by the iterator.
+    pub fn iter_region_blocks<'a>(&'a self, region: &'a BoundingBox) -> impl Iterator<Item = (BlockPos, &'static BlockState)> + 'a {
+        Self::iter_region_chunks(region).flat_map(move |(cx, cz)| {
+            self.get_chunk(cx, cz)
+                .map(|chunk| chunk.iter_region_blocks(region).collect::<Vec<_>>())
+                .unwrap_or_default()
+        })
+    }
+
+}
+
+
+impl Default for ChunkStorage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// A shared guard of a chunk returned by `ChunkStorage`, it keeps the chunk alive even if it
+/// is removed from the storage.
+pub struct ChunkReadGuard {
+    // The guard must be declared first in order to be dropped before the lock.
+    guard: RwLockReadGuard<'static, Chunk>,
+    _lock: Arc<RwLock<Chunk>>
+}
+
+impl ChunkReadGuard {
+    fn new(lock: Arc<RwLock<Chunk>>) -> Self {
+        // SAFETY: The lock is never moved because it's in an `Arc`, and this `Arc` is kept
+        //         with the guard, which is dropped first.
+        let guard = unsafe {
+            std::mem::transmute::<RwLockReadGuard<'_, Chunk>, RwLockReadGuard<'static, Chunk>>(lock.read().unwrap())
+        };
+        Self { guard, _lock: lock }
+    }
+}
+
+impl Deref for ChunkReadGuard {
+    type Target = Chunk;
+    fn deref(&self) -> &Chunk {
+        &self.guard
+    }
+}
+
+
+/// An exclusive guard of a chunk returned by `ChunkStorage`, it keeps the chunk alive even if
+/// it is removed from the storage.
+pub struct ChunkWriteGuard {
+    // The guard must be declared first in order to be dropped before the lock.
+    guard: RwLockWriteGuard<'static, Chunk>,
+    _lock: Arc<RwLock<Chunk>>
+}
+
+impl ChunkWriteGuard {
+    fn new(lock: Arc<RwLock<Chunk>>) -> Self {
+        // SAFETY: Same as `ChunkReadGuard::new`.
+        let guard = unsafe {
+            std::mem::transmute::<RwLockWriteGuard<'_, Chunk>, RwLockWriteGuard<'static, Chunk>>(lock.write().unwrap())
+        };
+        Self { guard, _lock: lock }
+    }
+}
+
+impl Deref for ChunkWriteGuard {
+    type Target = Chunk;
+    fn deref(&self) -> &Chunk {
+        &self.guard
+    }
+}
+
+impl DerefMut for ChunkWriteGuard {
+    fn deref_mut(&mut self) -> &mut Chunk {
+        &mut self.guard
+    }
 }
 
 
@@ -456,6 +1059,33 @@ impl BaseEntity {
         }
     }
 
+    /// Get the passengers riding this entity, in their mounting order. Passengers are
+    /// changed with `Level::mount_entity` and `Level::dismount_entity`.
+    pub fn get_passengers(&self) -> &[Entity] {
+        self.passengers.as_deref().unwrap_or(&[])
+    }
+
+    #[inline]
+    pub fn has_passengers(&self) -> bool {
+        self.passengers.is_some()
+    }
+
+}
+
+/// Entity component added to entities riding a vehicle, the vehicle also references them
+/// in its `BaseEntity` passengers.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Passenger {
+    vehicle: Entity
+}
+
+impl Passenger {
+
+    #[inline]
+    pub fn get_vehicle(&self) -> Entity {
+        self.vehicle
+    }
+
 }
 
 /// Base block entity component, present in all block entities of a level, must not be removed.
@@ -471,3 +1101,200 @@ impl BaseBlockEntity {
     }
 
 }
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    crate::blocks!(TEST_BLOCKS "test" [
+        AIR "air",
+        STONE "stone"
+    ]);
+
+    crate::blocks!(OTHER_BLOCKS "test" [
+        DIRT "dirt"
+    ]);
+
+    crate::biomes!(TEST_BIOMES "test" [
+        VOID "void" 0,
+    ]);
+
+    crate::entities!(TEST_ENTITIES "test" [
+        BOAT "boat" [],
+        PLAYER "player" []
+    ]);
+
+    static TAG_SOLID: TagType = TagType::new_logical("test:solid");
+
+    #[test]
+    fn env_builder() {
+
+        let env = LevelEnv::builder()
+            .register_blocks(&TEST_BLOCKS)
+            .register_block_tag(&TAG_SOLID, [&STONE])
+            .build()
+            .unwrap();
+
+        assert_eq!(env.blocks.blocks_count(), 2);
+        assert!(env.blocks.has_block_tag(&STONE, &TAG_SOLID));
+        assert!(!env.blocks.has_block_tag(&AIR, &TAG_SOLID));
+
+        let res = LevelEnv::builder()
+            .register_blocks(&TEST_BLOCKS)
+            .register_block_tag(&TAG_SOLID, [&STONE])
+            .register_block_tag(&TAG_SOLID, [&DIRT])
+            .build();
+
+        assert!(matches!(res, Err(LevelEnvError::InvalidBlockTag("test:solid"))));
+
+        // Blocks of other slices can be tagged once registered.
+        let env = LevelEnv::builder()
+            .register_blocks(&TEST_BLOCKS)
+            .register_blocks(&OTHER_BLOCKS)
+            .register_block_tag(&TAG_SOLID, [&STONE, &DIRT])
+            .build()
+            .unwrap();
+
+        assert_eq!(env.blocks.blocks_count(), 3);
+        assert!(env.blocks.has_block_tag(&DIRT, &TAG_SOLID));
+
+    }
+
+    #[test]
+    fn frozen_env() {
+
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+        let env = LevelEnv::builder()
+            .register_blocks(&TEST_BLOCKS)
+            .register_block_tag(&TAG_SOLID, [&STONE])
+            .build()
+            .unwrap();
+
+        let other_env = LevelEnv::with_frozen(Arc::clone(&env.blocks), Arc::clone(&env.biomes), GlobalEntities::new(), GlobalHeightmaps::new());
+        assert_send_sync(&other_env.blocks);
+        assert!(Arc::ptr_eq(&env.blocks, &other_env.blocks));
+
+        let blocks = Arc::clone(&env.blocks);
+        let handle = std::thread::spawn(move || blocks.has_block_tag(&STONE, &TAG_SOLID) && !blocks.has_block_tag(&AIR, &TAG_SOLID));
+        assert!(handle.join().unwrap());
+
+    }
+
+    #[test]
+    fn region_edit() {
+
+        let env = Arc::new(LevelEnv::builder()
+            .register_blocks(&TEST_BLOCKS)
+            .register_biomes(&TEST_BIOMES)
+            .build()
+            .unwrap());
+
+        let storage = ChunkStorage::new();
+        storage.insert_chunk(Chunk::new(Arc::clone(&env), ChunkHeight::new(0, 3), 0, 0));
+
+        let region = BoundingBox::new(10, 0, 0, 20, 3, 0);
+        assert!(matches!(storage.fill_region(&region, STONE.get_default_state()), Err(ChunkError::ChunkUnloaded)));
+        assert_eq!(storage.get_block_at(10, 0, 0).unwrap(), AIR.get_default_state());
+
+        storage.insert_chunk(Chunk::new(Arc::clone(&env), ChunkHeight::new(0, 3), 1, 0));
+        assert_eq!(storage.fill_region(&region, STONE.get_default_state()).unwrap(), 11 * 4);
+        assert_eq!(storage.get_block_at(20, 3, 0).unwrap(), STONE.get_default_state());
+        assert_eq!(storage.iter_region_blocks(&BoundingBox::new(0, 0, 0, 31, 63, 15)).count(), 11 * 4);
+        assert_eq!(storage.get_biome_quart(5, 0, 0).unwrap(), &VOID);
+        assert!(matches!(storage.get_biome_quart(8, 0, 0), Err(ChunkError::ChunkUnloaded)));
+
+        let replaced = storage.replace_region(&BoundingBox::new(0, 0, 0, 31, 0, 15), |state| {
+            state == STONE.get_default_state()
+        }, AIR.get_default_state()).unwrap();
+        assert_eq!(replaced, 11);
+        assert_eq!(storage.iter_region_blocks(&region).count(), 11 * 3);
+
+    }
+
+    #[test]
+    fn concurrent_chunks() {
+
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+        let env = Arc::new(LevelEnv::builder()
+            .register_blocks(&TEST_BLOCKS)
+            .register_biomes(&TEST_BIOMES)
+            .build()
+            .unwrap());
+
+        let storage = Arc::new(ChunkStorage::new());
+        assert_send_sync(&storage);
+        for cx in 0..8 {
+            storage.insert_chunk(Chunk::new(Arc::clone(&env), ChunkHeight::new(0, 0), cx, 0));
+        }
+        assert_eq!(storage.get_chunks_count(), 8);
+
+        // Each thread edits its own chunk while reading the first one.
+        let reader = storage.get_chunk(0, 0).unwrap();
+        let handles: Vec<_> = (1..8).map(|cx| {
+            let storage = Arc::clone(&storage);
+            std::thread::spawn(move || {
+                let mut chunk = storage.get_chunk_mut(cx, 0).unwrap();
+                chunk.set_block(0, 0, 0, STONE.get_default_state()).unwrap();
+                storage.get_block_at(0, 0, 0).unwrap() == AIR.get_default_state()
+            })
+        }).collect();
+        assert!(handles.into_iter().all(|handle| handle.join().unwrap()));
+
+        // Removing a chunk while a guard is held keeps the chunk alive.
+        assert!(storage.remove_chunk(0, 0).is_some());
+        assert!(!storage.is_chunk_loaded(0, 0));
+        assert_eq!(reader.get_position(), (0, 0));
+        drop(reader);
+
+        assert_eq!(storage.iter_chunks().count(), 7);
+        assert_eq!(storage.iter_region_blocks(&BoundingBox::new(0, 0, 0, 127, 15, 15)).count(), 7);
+
+    }
+
+    #[test]
+    fn riding() {
+
+        use super::super::source::NullLevelSource;
+
+        let env = LevelEnv::builder()
+            .register_blocks(&TEST_BLOCKS)
+            .register_biomes(&TEST_BIOMES)
+            .register_entities(&TEST_ENTITIES)
+            .build()
+            .unwrap();
+
+        let mut level = Level::new("test".to_string(), env, ChunkHeight::new(0, 0), NullLevelSource);
+        let boat = level.spawn_entity(&BOAT, EntityPos::new(0.0, 0.0, 0.0)).unwrap();
+        let other_boat = level.spawn_entity(&BOAT, EntityPos::new(4.0, 0.0, 0.0)).unwrap();
+        let player = level.spawn_entity(&PLAYER, EntityPos::new(0.0, 0.0, 0.0)).unwrap();
+        let other_player = level.spawn_entity(&PLAYER, EntityPos::new(0.0, 0.0, 0.0)).unwrap();
+
+        assert!(level.mount_entity(player, boat));
+        assert!(level.mount_entity(other_player, player));
+        assert_eq!(level.get_vehicle(other_player), Some(player));
+        assert_eq!(level.get_root_vehicle(other_player), boat);
+        // An entity can't ride itself or one of its passengers.
+        assert!(!level.mount_entity(boat, other_player));
+        assert!(!level.mount_entity(boat, boat));
+
+        // Mounting another vehicle dismounts the previous one.
+        assert!(level.mount_entity(player, other_boat));
+        assert!(!level.entities.ecs.get::<BaseEntity>(boat).unwrap().has_passengers());
+        assert_eq!(level.entities.ecs.get::<BaseEntity>(other_boat).unwrap().get_passengers(), &[player]);
+
+        assert_eq!(level.dismount_entity(other_player), Some(player));
+        assert_eq!(level.dismount_entity(other_player), None);
+        assert!(level.mount_entity(other_player, other_boat));
+
+        // Removed vehicles eject their passengers.
+        assert!(level.remove_entity(other_boat));
+        assert_eq!(level.get_vehicle(player), None);
+        assert_eq!(level.get_vehicle(other_player), None);
+
+    }
+
+}
@@ -0,0 +1,379 @@
+//! Recomputation of block and sky lights of chunks from scratch, used to repair levels with
+//! corrupted or missing light data.
+//!
+//! Lights of each chunk are first computed independently, in parallel, then propagated across
+//! the borders of chunks. Light decreases by the opacity of the block it enters, and at least
+//! by one, except sky light that goes straight down without decreasing through transparent
+//! blocks.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, RwLock};
+
+use crate::block::BlockState;
+
+use super::chunk::{Chunk, ChunkHeight, Light, BLOCKS_DATA_SIZE};
+use super::level::ChunkStorage;
+
+
+/// Light properties of block states, implemented by the environment providing blocks.
+pub trait LightProperties: Sync {
+
+    /// Return the amount of light absorbed by the given state, between 0 and 15.
+    fn get_opacity(&self, state: &'static BlockState) -> u8;
+
+    /// Return the block light emitted by the given state, between 0 and 15.
+    fn get_emission(&self, state: &'static BlockState) -> u8;
+
+}
+
+
+/// Recompute all lights of the given loaded chunks, unloaded chunks are ignored. Loaded chunks
+/// around them are only used as light sources, their lights are not modified. Returns the
+/// number of relit chunks.
+///
+/// Unloaded sub chunks of neighbor chunks are considered to be fully lit by the sky.
+pub fn relight_chunks<I>(storage: &ChunkStorage, chunks: I, props: &dyn LightProperties, has_skylight: bool) -> usize
+where
+    I: IntoIterator<Item = (i32, i32)>
+{
+
+    let mut positions: Vec<(i32, i32)> = chunks.into_iter().collect::<HashSet<_>>().into_iter().collect();
+    positions.sort_unstable();
+
+    let arcs: Vec<Arc<RwLock<Chunk>>> = positions.iter()
+        .filter_map(|&(cx, cz)| storage.get_chunk_arc(cx, cz))
+        .collect();
+
+    if arcs.is_empty() {
+        return 0;
+    }
+
+    // Lights of each chunk are computed in parallel, without their neighbors.
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get()).min(arcs.len());
+    let batch_size = arcs.len().div_ceil(threads);
+    let mut lights: HashMap<(i32, i32), ChunkLights> = std::thread::scope(|scope| {
+        let handles: Vec<_> = arcs.chunks(batch_size)
+            .map(|batch| scope.spawn(move || {
+                batch.iter()
+                    .map(|arc| ChunkLights::compute(&arc.read().unwrap(), props, has_skylight))
+                    .collect::<Vec<_>>()
+            }))
+            .collect();
+        handles.into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .map(|lights| ((lights.cx, lights.cz), lights))
+            .collect()
+    });
+
+    for typ in [Light::Block, Light::Sky] {
+        propagate_borders(storage, &mut lights, typ, has_skylight);
+    }
+
+    for arc in &arcs {
+        let mut chunk = arc.write().unwrap();
+        lights[&chunk.get_position()].apply(&mut chunk, has_skylight);
+    }
+
+    arcs.len()
+
+}
+
+
+/// Lights and opacities of a whole chunk, stored like sub chunks in X, Z and Y order.
+struct ChunkLights {
+    cx: i32,
+    cz: i32,
+    height: ChunkHeight,
+    opacity: Vec<u8>,
+    block: Vec<u8>,
+    sky: Vec<u8>
+}
+
+impl ChunkLights {
+
+    fn compute(chunk: &Chunk, props: &dyn LightProperties, has_skylight: bool) -> Self {
+
+        let (cx, cz) = chunk.get_position();
+        let height = chunk.get_height();
+        let len = height.len() * BLOCKS_DATA_SIZE;
+
+        let mut lights = Self {
+            cx,
+            cz,
+            height,
+            opacity: vec![0; len],
+            block: vec![0; len],
+            sky: vec![0; len]
+        };
+
+        // Unloaded sub chunks are filled with the null block.
+        let null_state = chunk.get_env().blocks.get_state_from(0).unwrap();
+        let (null_opacity, null_emission) = (props.get_opacity(null_state), props.get_emission(null_state));
+
+        for (cy, sub_chunk) in chunk.iter_sub_chunks() {
+            let offset = (cy - height.min) as usize * BLOCKS_DATA_SIZE;
+            let range = offset..offset + BLOCKS_DATA_SIZE;
+            match sub_chunk {
+                Some(sub_chunk) => {
+                    for (i, state) in range.zip(sub_chunk.iter_blocks()) {
+                        lights.opacity[i] = props.get_opacity(state).min(15);
+                        lights.block[i] = props.get_emission(state).min(15);
+                    }
+                }
+                None => {
+                    lights.opacity[range.clone()].fill(null_opacity.min(15));
+                    lights.block[range].fill(null_emission.min(15));
+                }
+            }
+        }
+
+        let mut queue: VecDeque<usize> = (0..len).filter(|&i| lights.block[i] > 1).collect();
+        lights.propagate(Light::Block, &mut queue);
+
+        if has_skylight {
+            for column in 0..256 {
+                let mut level = 15u8;
+                for i in (column..len).step_by(256).rev() {
+                    level = level.saturating_sub(lights.opacity[i]);
+                    lights.sky[i] = level;
+                    if level == 0 {
+                        break;
+                    }
+                }
+            }
+            let mut queue: VecDeque<usize> = (0..len).filter(|&i| lights.sky[i] > 1).collect();
+            lights.propagate(Light::Sky, &mut queue);
+        }
+
+        lights
+
+    }
+
+    #[inline]
+    fn get_lights_mut(&mut self, typ: Light) -> (&[u8], &mut [u8]) {
+        match typ {
+            Light::Block => (&self.opacity, &mut self.block),
+            Light::Sky => (&self.opacity, &mut self.sky)
+        }
+    }
+
+    /// Raise the light at the given index from a neighbor light, returns true if raised.
+    #[inline]
+    fn raise(&mut self, typ: Light, i: usize, from: u8) -> bool {
+        let (opacity, lights) = self.get_lights_mut(typ);
+        let level = from.saturating_sub(opacity[i].max(1));
+        if level > lights[i] {
+            lights[i] = level;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Propagate lights from the queued indices inside of this chunk.
+    fn propagate(&mut self, typ: Light, queue: &mut VecDeque<usize>) {
+        let len = self.opacity.len();
+        while let Some(i) = queue.pop_front() {
+            let level = self.get_lights_mut(typ).1[i];
+            let (x, z) = (i & 15, (i >> 4) & 15);
+            let neighbors = [
+                (x > 0).then(|| i - 1),
+                (x < 15).then(|| i + 1),
+                (z > 0).then(|| i - 16),
+                (z < 15).then(|| i + 16),
+                (i >= 256).then(|| i - 256),
+                (i + 256 < len).then(|| i + 256)
+            ];
+            for &n in neighbors.iter().flatten() {
+                if self.raise(typ, n, level) {
+                    queue.push_back(n);
+                }
+            }
+        }
+    }
+
+    /// Write the lights to the chunk, unloaded sub chunks are only loaded if their lights
+    /// differ from the default ones.
+    fn apply(&self, chunk: &mut Chunk, has_skylight: bool) {
+        let default_sky = if has_skylight { 15 } else { 0 };
+        for cy in self.height.iter() {
+            let offset = (cy - self.height.min) as usize * BLOCKS_DATA_SIZE;
+            let range = offset..offset + BLOCKS_DATA_SIZE;
+            let (block, sky) = (&self.block[range.clone()], &self.sky[range]);
+            if chunk.get_sub_chunk(cy).is_none() && block.iter().all(|&l| l == 0) && sky.iter().all(|&l| l == default_sky) {
+                continue;
+            }
+            let sub_chunk = chunk.ensure_sub_chunk(cy).unwrap();
+            // SAFETY: Lights are in the right order and all of them are clamped to 15.
+            unsafe {
+                sub_chunk.set_lights_raw(Light::Block, block.iter().copied());
+                sub_chunk.set_lights_raw(Light::Sky, sky.iter().copied());
+            }
+        }
+    }
+
+}
+
+
+/// Propagate lights across the borders of the relit chunks, from their relit or loaded
+/// neighbors.
+fn propagate_borders(storage: &ChunkStorage, lights: &mut HashMap<(i32, i32), ChunkLights>, typ: Light, has_skylight: bool) {
+
+    // Border lights are sampled for each side of each chunk before being applied.
+    let mut sources = Vec::new();
+    for (&(cx, cz), chunk_lights) in lights.iter() {
+        let len = chunk_lights.opacity.len();
+        for &(dx, dz) in &[(-1, 0), (1, 0), (0, -1), (0, 1)] {
+            let (ncx, ncz) = (cx + dx, cz + dz);
+            // Index of a border cell from its index along the border.
+            let border = move |i: usize, to_neighbor: bool| {
+                let (side, y) = (i & 15, i >> 4);
+                let edge = if (dx + dz > 0) != to_neighbor { 15 } else { 0 };
+                let (x, z) = if dx != 0 { (edge, side) } else { (side, edge) };
+                (y << 8) | (z << 4) | x
+            };
+            let levels: Vec<u8> = if let Some(neighbor) = lights.get(&(ncx, ncz)) {
+                (0..len / 16).map(|i| match typ {
+                    Light::Block => neighbor.block[border(i, true)],
+                    Light::Sky => neighbor.sky[border(i, true)]
+                }).collect()
+            } else if let Some(neighbor) = storage.get_chunk(ncx, ncz) {
+                let min_y = chunk_lights.height.get_min_block();
+                (0..len / 16).map(|i| {
+                    let j = border(i, true);
+                    let (x, y, z) = ((j & 15) as u8, min_y + (j >> 8) as i32, ((j >> 4) & 15) as u8);
+                    neighbor.get_light(x, y, z, typ).unwrap_or(match typ {
+                        Light::Sky if has_skylight => 15,
+                        _ => 0
+                    })
+                }).collect()
+            } else {
+                continue;
+            };
+            sources.extend(levels.into_iter()
+                .enumerate()
+                .filter(|&(_, level)| level > 1)
+                .map(|(i, level)| ((cx, cz), border(i, false), level)));
+        }
+    }
+
+    let mut queue = VecDeque::new();
+    for (pos, i, level) in sources {
+        if lights.get_mut(&pos).unwrap().raise(typ, i, level) {
+            queue.push_back((pos, i));
+        }
+    }
+
+    while let Some(((cx, cz), i)) = queue.pop_front() {
+        let chunk_lights = &lights[&(cx, cz)];
+        let level = match typ {
+            Light::Block => chunk_lights.block[i],
+            Light::Sky => chunk_lights.sky[i]
+        };
+        let len = chunk_lights.opacity.len();
+        let (x, z) = (i & 15, (i >> 4) & 15);
+        let neighbors = [
+            if x > 0 { ((cx, cz), i - 1) } else { ((cx - 1, cz), i + 15) },
+            if x < 15 { ((cx, cz), i + 1) } else { ((cx + 1, cz), i - 15) },
+            if z > 0 { ((cx, cz), i - 16) } else { ((cx, cz - 1), i + 240) },
+            if z < 15 { ((cx, cz), i + 16) } else { ((cx, cz + 1), i - 240) },
+        ];
+        let vertical = [(i >= 256).then(|| i - 256), (i + 256 < len).then(|| i + 256)];
+        let neighbors = neighbors.iter().copied()
+            .chain(vertical.iter().flatten().map(|&n| ((cx, cz), n)));
+        for (pos, n) in neighbors {
+            if let Some(neighbor) = lights.get_mut(&pos) {
+                if neighbor.raise(typ, n, level) {
+                    queue.push_back((pos, n));
+                }
+            }
+        }
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::world::chunk::ChunkError;
+    use crate::world::level::LevelEnv;
+    use crate::world::source::ChunkLoadRequest;
+
+    crate::blocks!(TEST_BLOCKS "test" [
+        AIR "air",
+        STONE "stone",
+        TORCH "torch"
+    ]);
+
+    crate::biomes!(TEST_BIOMES "test" [
+        VOID "void" 0,
+    ]);
+
+    struct TestLightProperties;
+
+    impl LightProperties for TestLightProperties {
+
+        fn get_opacity(&self, state: &'static BlockState) -> u8 {
+            if state.is_block(&STONE) { 15 } else { 0 }
+        }
+
+        fn get_emission(&self, state: &'static BlockState) -> u8 {
+            if state.is_block(&TORCH) { 14 } else { 0 }
+        }
+
+    }
+
+    #[test]
+    fn relight() {
+
+        let env = Arc::new(LevelEnv::builder()
+            .register_blocks(&TEST_BLOCKS)
+            .register_biomes(&TEST_BIOMES)
+            .build()
+            .unwrap());
+
+        let height = ChunkHeight::new(0, 3);
+        let storage = ChunkStorage::new();
+
+        // Two chunks with a stone roof at Y 20, a torch is placed under the roof of the first
+        // chunk near the border with the second.
+        for cx in 0..2 {
+            let mut chunk = ChunkLoadRequest { env: Arc::clone(&env), height, cx, cz: 0 }.build_chunk();
+            for x in 0..16 {
+                for z in 0..16 {
+                    chunk.set_block(x, 20, z, STONE.get_default_state()).unwrap();
+                }
+            }
+            if cx == 0 {
+                chunk.set_block(15, 10, 8, TORCH.get_default_state()).unwrap();
+            }
+            storage.insert_chunk(chunk);
+        }
+
+        assert_eq!(relight_chunks(&storage, vec![(0, 0), (1, 0), (5, 5)], &TestLightProperties, true), 2);
+
+        let chunk0 = storage.get_chunk(0, 0).unwrap();
+        let chunk1 = storage.get_chunk(1, 0).unwrap();
+        assert_eq!(chunk0.get_light(15, 10, 8, Light::Block).unwrap(), 14);
+        assert_eq!(chunk0.get_light(15, 12, 8, Light::Block).unwrap(), 12);
+        assert_eq!(chunk1.get_light(0, 10, 8, Light::Block).unwrap(), 13);
+        assert_eq!(chunk1.get_light(5, 10, 10, Light::Block).unwrap(), 6);
+        assert_eq!(chunk1.get_light(5, 21, 10, Light::Block).unwrap(), 0);
+        assert_eq!(chunk0.get_light(3, 21, 3, Light::Sky).unwrap(), 15);
+        assert_eq!(chunk0.get_light(3, 20, 3, Light::Sky).unwrap(), 0);
+        assert_eq!(chunk0.get_light(3, 5, 3, Light::Sky).unwrap(), 0);
+        assert!(matches!(chunk1.get_light(0, 40, 0, Light::Sky), Err(ChunkError::SubChunkUnloaded)));
+        drop((chunk0, chunk1));
+
+        // Relighting only the second chunk takes the torch light from the first one.
+        let mut chunk1 = storage.get_chunk_mut(1, 0).unwrap();
+        chunk1.ensure_sub_chunk(0).unwrap().set_light(0, 10, 8, Light::Block, 0);
+        drop(chunk1);
+        assert_eq!(relight_chunks(&storage, vec![(1, 0)], &TestLightProperties, true), 1);
+        assert_eq!(storage.get_chunk(1, 0).unwrap().get_light(0, 10, 8, Light::Block).unwrap(), 13);
+
+    }
+
+}
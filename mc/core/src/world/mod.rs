@@ -1,5 +1,8 @@
 pub mod level;
 pub mod chunk;
 pub mod source;
+pub mod ticket;
+pub mod dimension;
+pub mod light;
 
 pub mod anvil;
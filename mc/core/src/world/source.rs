@@ -1,3 +1,4 @@
+use std::collections::{HashMap, VecDeque};
 use std::ops::{Deref, DerefMut};
 use std::sync::{Arc, RwLock, Mutex};
 use std::time::{Instant, Duration};
@@ -7,11 +8,15 @@ use std::fmt::{Debug, Formatter};
 use crossbeam_channel::{Sender, Receiver, unbounded, bounded};
 use hecs::EntityBuilder;
 use thiserror::Error;
+use nbt::CompoundTag;
 
 use super::chunk::{Chunk, ChunkHeight};
+use super::anvil::encode::encode_chunk;
+use super::anvil::decode::decode_chunk;
 use crate::world::chunk::ChunkStatus;
 use crate::world::level::LevelEnv;
 use crate::block::BlockState;
+use crate::biome::Biome;
 
 
 /// Common level source error.
@@ -61,6 +66,12 @@ pub trait LevelSource {
         Err(LevelSourceError::UnsupportedChunkSave)
     }
 
+    /// Block until all previously requested chunk saves are written. By default, this does
+    /// nothing because saves are expected to be synchronous.
+    fn flush(&mut self) -> Result<(), LevelSourceError> {
+        Ok(())
+    }
+
 }
 
 
@@ -95,7 +106,10 @@ impl ChunkLoadRequest {
 pub struct ChunkSaveRequest {
     pub cx: i32,
     pub cz: i32,
-    pub chunk: Arc<RwLock<Chunk>>
+    pub chunk: Arc<RwLock<Chunk>>,
+    /// Block entities of the chunk, already encoded by the level because they are stored in
+    /// the level's ECS which cannot be accessed by sources.
+    pub block_entities: Vec<CompoundTag>
 }
 
 
@@ -159,6 +173,143 @@ pub struct NullLevelSource;
 impl LevelSource for NullLevelSource {}
 
 
+/// A call received by a `MemoryLevelSource`, these are recorded in order to be inspected
+/// later, for example in unit tests.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MemorySourceCall {
+    Load(i32, i32),
+    Save(i32, i32)
+}
+
+
+/// A level source that stores its chunks in memory, chunks are kept encoded (using the anvil
+/// NBT format) in order to be fully independent of the level they were saved from.
+///
+/// When a chunk that is not stored is requested, the optional generator is used to build it,
+/// if no generator is set, an `UnsupportedChunkPosition` error is returned, this allows this
+/// source to be used as the loader of a `LoadOrGenLevelSource`.
+///
+/// Every load and save request is recorded and can be inspected with `get_calls`, this is
+/// mainly intended for unit tests and debugging tools.
+pub struct MemoryLevelSource {
+    chunks: HashMap<(i32, i32), CompoundTag>,
+    generator: Option<Box<dyn LevelGenerator + Send>>,
+    results: VecDeque<Result<ProtoChunk, (LevelSourceError, ChunkLoadRequest)>>,
+    calls: Vec<MemorySourceCall>
+}
+
+impl MemoryLevelSource {
+
+    /// Construct a new empty memory source without generator.
+    pub fn new() -> Self {
+        Self {
+            chunks: HashMap::new(),
+            generator: None,
+            results: VecDeque::new(),
+            calls: Vec::new()
+        }
+    }
+
+    /// Construct a new empty memory source, chunks that are not stored are generated with
+    /// the given generator.
+    pub fn with_generator<G>(generator: G) -> Self
+    where
+        G: LevelGenerator + Send + 'static
+    {
+        let mut source = Self::new();
+        source.generator = Some(Box::new(generator));
+        source
+    }
+
+    /// Store a copy of the given chunk in this source, replacing any previous chunk at its
+    /// position. This can be used to pre-seed the source with custom content.
+    pub fn insert_chunk(&mut self, chunk: &Chunk) {
+        self.insert_chunk_with_block_entities(chunk, &[]);
+    }
+
+    /// Same as `insert_chunk` but also store the given encoded block entities with the chunk.
+    pub fn insert_chunk_with_block_entities(&mut self, chunk: &Chunk, block_entities: &[CompoundTag]) {
+        let mut tag_root = CompoundTag::new();
+        encode_chunk(&mut tag_root, chunk, block_entities);
+        self.chunks.insert(chunk.get_position(), tag_root);
+    }
+
+    /// Remove the chunk stored at the given position, returning true if it was present.
+    pub fn remove_chunk(&mut self, cx: i32, cz: i32) -> bool {
+        self.chunks.remove(&(cx, cz)).is_some()
+    }
+
+    #[inline]
+    pub fn has_chunk(&self, cx: i32, cz: i32) -> bool {
+        self.chunks.contains_key(&(cx, cz))
+    }
+
+    #[inline]
+    pub fn get_chunks_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Return all calls received by this source since its creation or the last call
+    /// to `clear_calls`, in the order they were received.
+    #[inline]
+    pub fn get_calls(&self) -> &[MemorySourceCall] {
+        &self.calls[..]
+    }
+
+    pub fn clear_calls(&mut self) {
+        self.calls.clear();
+    }
+
+    /// Return the number of load requests received for the given chunk position.
+    pub fn get_load_count(&self, cx: i32, cz: i32) -> usize {
+        self.calls.iter().filter(|&&call| call == MemorySourceCall::Load(cx, cz)).count()
+    }
+
+    /// Return the number of save requests received for the given chunk position.
+    pub fn get_save_count(&self, cx: i32, cz: i32) -> usize {
+        self.calls.iter().filter(|&&call| call == MemorySourceCall::Save(cx, cz)).count()
+    }
+
+}
+
+impl Default for MemoryLevelSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LevelSource for MemoryLevelSource {
+
+    fn request_chunk_load(&mut self, req: ChunkLoadRequest) -> Result<(), (LevelSourceError, ChunkLoadRequest)> {
+        self.calls.push(MemorySourceCall::Load(req.cx, req.cz));
+        let res = if let Some(tag_root) = self.chunks.get(&(req.cx, req.cz)) {
+            let mut chunk = req.build_proto_chunk();
+            match decode_chunk(tag_root, &mut chunk) {
+                Ok(_) => Ok(chunk),
+                Err(err) => Err((LevelSourceError::new_custom(err), req))
+            }
+        } else if let Some(generator) = &mut self.generator {
+            generator.generate(req)
+        } else {
+            return Err((LevelSourceError::UnsupportedChunkPosition, req));
+        };
+        self.results.push_back(res);
+        Ok(())
+    }
+
+    fn poll_chunk(&mut self) -> Option<Result<ProtoChunk, (LevelSourceError, ChunkLoadRequest)>> {
+        self.results.pop_front()
+    }
+
+    fn request_chunk_save(&mut self, req: ChunkSaveRequest) -> Result<(), LevelSourceError> {
+        self.calls.push(MemorySourceCall::Save(req.cx, req.cz));
+        self.insert_chunk_with_block_entities(&req.chunk.read().unwrap(), &req.block_entities);
+        Ok(())
+    }
+
+}
+
+
 /// A load or generate LevelSource variant.
 ///
 /// This can be used for exemple with an anvil region source as the loader and a super-flat
@@ -237,6 +388,10 @@ where
         self.loader.request_chunk_save(req)
     }
 
+    fn flush(&mut self) -> Result<(), LevelSourceError> {
+        self.loader.flush()
+    }
+
 }
 
 
@@ -316,6 +471,12 @@ impl WorkerGenLevelSource {
 
     }
 
+    /// Block until a chunk has been generated by a worker, unlike `poll_chunk`. `None` is
+    /// returned if all workers have stopped.
+    pub fn recv_chunk(&mut self) -> Option<Result<ProtoChunk, (LevelSourceError, ChunkLoadRequest)>> {
+        self.result_receiver.recv().ok()
+    }
+
 }
 
 impl LevelSource for WorkerGenLevelSource {
@@ -409,3 +570,221 @@ impl LevelGenerator for SuperFlatGenerator {
     }
 
 }
+
+impl LevelGeneratorBuilder for SuperFlatGenerator {
+
+    type Generator = Self;
+
+    fn build(&mut self) -> Self::Generator {
+        self.clone()
+    }
+
+}
+
+
+/// A void generator, all generated chunks are filled with the null block (usually air), an
+/// optional square platform can be generated around the origin of the level, this is useful
+/// for lobby servers and for benchmarking parts of the stack that are not related to level
+/// generation.
+#[derive(Debug, Clone)]
+pub struct VoidGenerator {
+    biome: Option<&'static Biome>,
+    platform: Option<(&'static BlockState, i32, u32)>
+}
+
+impl VoidGenerator {
+
+    pub fn new() -> Self {
+        Self {
+            biome: None,
+            platform: None
+        }
+    }
+
+    /// Set the biome to fill generated chunks with, if not set, chunks keep their default
+    /// biome (the first one of the environment).
+    pub fn set_biome(&mut self, biome: &'static Biome) {
+        self.biome = Some(biome);
+    }
+
+    /// Set the square platform to generate at the given Y coordinate, the platform is
+    /// centered on X/Z 0/0 and spread in each direction by the given radius. For example
+    /// a radius of 0 generates a single block at 0/Y/0.
+    pub fn set_platform(&mut self, state: &'static BlockState, y: i32, radius: u32) {
+        self.platform = Some((state, y, radius));
+    }
+
+}
+
+impl Default for VoidGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LevelGenerator for VoidGenerator {
+
+    fn generate(&mut self, info: ChunkLoadRequest) -> Result<ProtoChunk, (LevelSourceError, ChunkLoadRequest)> {
+
+        let mut chunk = info.build_proto_chunk();
+        let (cx, cz) = chunk.get_position();
+
+        if let Some(biome) = self.biome {
+            let height = chunk.get_height();
+            for y in (height.get_min_block()..=height.get_max_block()).step_by(4) {
+                for x in (0..16).step_by(4) {
+                    for z in (0..16).step_by(4) {
+                        if let Err(err) = chunk.set_biome_at(x, y, z, biome) {
+                            return Err((LevelSourceError::new_custom(err), info));
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some((state, y, radius)) = self.platform {
+            let radius = radius as i32;
+            let (min_x, min_z) = ((-radius).max(cx << 4), (-radius).max(cz << 4));
+            let (max_x, max_z) = (radius.min((cx << 4) + 15), radius.min((cz << 4) + 15));
+            for x in min_x..=max_x {
+                for z in min_z..=max_z {
+                    if let Err(err) = chunk.set_block_at(x, y, z, state) {
+                        return Err((LevelSourceError::new_custom(err), info));
+                    }
+                }
+            }
+        }
+
+        chunk.set_status(ChunkStatus::Full);
+        Ok(chunk)
+
+    }
+
+}
+
+impl LevelGeneratorBuilder for VoidGenerator {
+
+    type Generator = Self;
+
+    fn build(&mut self) -> Self::Generator {
+        self.clone()
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    crate::blocks!(TEST_BLOCKS "test" [
+        AIR "air",
+        STONE "stone"
+    ]);
+
+    crate::biomes!(TEST_BIOMES "test" [
+        VOID "void" 0,
+        PLAINS "plains" 1,
+    ]);
+
+    fn build_request(cx: i32, cz: i32) -> ChunkLoadRequest {
+        ChunkLoadRequest {
+            env: Arc::new(LevelEnv::builder()
+                .register_blocks(&TEST_BLOCKS)
+                .register_biomes(&TEST_BIOMES)
+                .build()
+                .unwrap()),
+            height: ChunkHeight::new(0, 3),
+            cx,
+            cz
+        }
+    }
+
+    #[test]
+    fn memory_source_unsupported() {
+        let mut source = MemoryLevelSource::new();
+        assert!(matches!(source.request_chunk_load(build_request(0, 0)), Err((LevelSourceError::UnsupportedChunkPosition, _))));
+        assert!(source.poll_chunk().is_none());
+        assert_eq!(source.get_calls(), &[MemorySourceCall::Load(0, 0)]);
+    }
+
+    #[test]
+    fn memory_source_generator() {
+
+        let mut generator = SuperFlatGenerator::new();
+        generator.add_layer(STONE.get_default_state(), 0, 2);
+
+        let mut source = MemoryLevelSource::with_generator(generator);
+        source.request_chunk_load(build_request(1, -1)).unwrap();
+
+        let chunk = source.poll_chunk().unwrap().unwrap();
+        assert_eq!(chunk.get_position(), (1, -1));
+        assert_eq!(chunk.get_block(3, 1, 7).unwrap(), STONE.get_default_state());
+        assert_eq!(chunk.get_block(3, 2, 7).unwrap(), AIR.get_default_state());
+        assert!(source.poll_chunk().is_none());
+        assert_eq!(source.get_load_count(1, -1), 1);
+
+    }
+
+    #[test]
+    fn memory_source_save_load() {
+
+        let mut source = MemoryLevelSource::new();
+
+        let mut chunk = build_request(2, 3).build_chunk();
+        chunk.set_block(4, 20, 5, STONE.get_default_state()).unwrap();
+        chunk.set_inhabited_time(1234);
+        source.insert_chunk(&chunk);
+        assert!(source.has_chunk(2, 3));
+
+        source.request_chunk_load(build_request(2, 3)).unwrap();
+        let mut loaded = source.poll_chunk().unwrap().unwrap();
+        assert_eq!(loaded.get_block(4, 20, 5).unwrap(), STONE.get_default_state());
+        assert_eq!(loaded.get_block(4, 21, 5).unwrap(), AIR.get_default_state());
+        assert_eq!(loaded.get_inhabited_time(), 1234);
+
+        loaded.set_block(4, 21, 5, STONE.get_default_state()).unwrap();
+        source.request_chunk_save(ChunkSaveRequest {
+            cx: 2,
+            cz: 3,
+            chunk: Arc::new(RwLock::new(*loaded.inner)),
+            block_entities: Vec::new()
+        }).unwrap();
+
+        source.request_chunk_load(build_request(2, 3)).unwrap();
+        let loaded = source.poll_chunk().unwrap().unwrap();
+        assert_eq!(loaded.get_block(4, 21, 5).unwrap(), STONE.get_default_state());
+
+        assert_eq!(source.get_calls(), &[
+            MemorySourceCall::Load(2, 3),
+            MemorySourceCall::Save(2, 3),
+            MemorySourceCall::Load(2, 3)
+        ]);
+
+    }
+
+    #[test]
+    fn void_generator() {
+
+        let mut generator = VoidGenerator::new();
+        generator.set_biome(&PLAINS);
+        generator.set_platform(STONE.get_default_state(), 10, 16);
+
+        let chunk = generator.generate(build_request(0, -1)).unwrap();
+        assert_eq!(chunk.get_block(0, 10, 0).unwrap(), STONE.get_default_state());
+        assert_eq!(chunk.get_block(15, 10, 15).unwrap(), STONE.get_default_state());
+        assert_eq!(chunk.get_block(0, 11, 0).unwrap(), AIR.get_default_state());
+        assert_eq!(chunk.get_biome_at(8, 40, 8).unwrap(), &PLAINS);
+
+        let chunk = generator.generate(build_request(1, 1)).unwrap();
+        assert_eq!(chunk.get_block(0, 10, 0).unwrap(), STONE.get_default_state());
+        assert_eq!(chunk.get_block(1, 10, 0).unwrap(), AIR.get_default_state());
+        assert_eq!(chunk.get_block(0, 10, 1).unwrap(), AIR.get_default_state());
+
+        let chunk = generator.generate(build_request(3, 0)).unwrap();
+        assert!(chunk.iter_loaded_sub_chunks().all(|(_, sub_chunk)| !sub_chunk.has_non_null_block()));
+
+    }
+
+}
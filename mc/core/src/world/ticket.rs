@@ -0,0 +1,166 @@
+//! Chunk tickets, a chunk with at least one ticket must be kept loaded by its level. Tickets
+//! are given to spawn chunks and to chunks forced by the `/forceload` command.
+
+use std::collections::HashSet;
+
+use nbt::CompoundTag;
+
+
+/// The default radius of spawn chunks, like vanilla.
+pub const SPAWN_CHUNKS_RADIUS: u8 = 11;
+
+
+/// The source of a chunk ticket.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ChunkTicket {
+    /// The chunk is in the spawn chunks radius.
+    Spawn,
+    /// The chunk is forced to stay loaded, forced chunks are persisted with the level.
+    Forced
+}
+
+
+/// Tickets of the chunks of a level.
+#[derive(Debug, Default)]
+pub struct ChunkTickets {
+    /// Center and radius of spawn chunks.
+    spawn: Option<((i32, i32), u8)>,
+    forced: HashSet<(i32, i32)>,
+    /// True if forced chunks have changed since the last encoding.
+    dirty: bool
+}
+
+impl ChunkTickets {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the center chunk of spawn chunks and their radius, all chunks in this square
+    /// receive a spawn ticket.
+    pub fn set_spawn(&mut self, cx: i32, cz: i32, radius: u8) {
+        self.spawn = Some(((cx, cz), radius));
+    }
+
+    pub fn clear_spawn(&mut self) {
+        self.spawn = None;
+    }
+
+    pub fn get_spawn(&self) -> Option<((i32, i32), u8)> {
+        self.spawn
+    }
+
+    /// Force a chunk to stay loaded, return false if the chunk was already forced.
+    pub fn add_forced(&mut self, cx: i32, cz: i32) -> bool {
+        let added = self.forced.insert((cx, cz));
+        self.dirty |= added;
+        added
+    }
+
+    /// Stop forcing a chunk to stay loaded, return false if the chunk was not forced.
+    pub fn remove_forced(&mut self, cx: i32, cz: i32) -> bool {
+        let removed = self.forced.remove(&(cx, cz));
+        self.dirty |= removed;
+        removed
+    }
+
+    pub fn is_forced(&self, cx: i32, cz: i32) -> bool {
+        self.forced.contains(&(cx, cz))
+    }
+
+    pub fn iter_forced(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        self.forced.iter().copied()
+    }
+
+    fn is_spawn(&self, cx: i32, cz: i32) -> bool {
+        match self.spawn {
+            Some(((scx, scz), radius)) => (cx - scx).abs() <= radius as i32 && (cz - scz).abs() <= radius as i32,
+            None => false
+        }
+    }
+
+    /// Iterate over tickets of the given chunk.
+    pub fn iter_tickets(&self, cx: i32, cz: i32) -> impl Iterator<Item = ChunkTicket> {
+        let spawn = self.is_spawn(cx, cz).then_some(ChunkTicket::Spawn);
+        let forced = self.is_forced(cx, cz).then_some(ChunkTicket::Forced);
+        spawn.into_iter().chain(forced)
+    }
+
+    /// Return true if the given chunk has at least one ticket.
+    pub fn has_ticket(&self, cx: i32, cz: i32) -> bool {
+        self.is_spawn(cx, cz) || self.is_forced(cx, cz)
+    }
+
+    /// Iterate over all chunks with at least one ticket, each chunk is returned once.
+    pub fn iter_chunks(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        let spawn_chunks = self.spawn.into_iter().flat_map(|((cx, cz), radius)| {
+            let radius = radius as i32;
+            ((cx - radius)..=(cx + radius)).flat_map(move |x| ((cz - radius)..=(cz + radius)).map(move |z| (x, z)))
+        });
+        let forced_chunks = self.forced.iter()
+            .copied()
+            .filter(move |&(cx, cz)| !self.is_spawn(cx, cz));
+        spawn_chunks.chain(forced_chunks)
+    }
+
+    /// Return true if forced chunks have changed since the last call to `encode_forced`.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Encode forced chunks to the `data` compound of vanilla `chunks.dat` file, chunk
+    /// positions are packed in longs.
+    pub fn encode_forced(&mut self, tag_data: &mut CompoundTag) {
+        tag_data.insert_i64_vec("Forced", self.forced.iter()
+            .map(|&(cx, cz)| (cx as u32 as i64) | ((cz as i64) << 32))
+            .collect());
+        self.dirty = false;
+    }
+
+    /// Decode forced chunks from the `data` compound of vanilla `chunks.dat` file, previous
+    /// forced chunks are replaced.
+    pub fn decode_forced(&mut self, tag_data: &CompoundTag) {
+        self.forced = tag_data.get_i64_vec("Forced")
+            .map(|forced| forced.iter()
+                .map(|&packed| (packed as i32, (packed >> 32) as i32))
+                .collect())
+            .unwrap_or_default();
+        self.dirty = false;
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn chunk_tickets() {
+
+        let mut tickets = ChunkTickets::new();
+        tickets.set_spawn(0, 0, 1);
+        assert!(tickets.add_forced(-3, 7));
+        assert!(tickets.add_forced(1, 1));
+        assert!(!tickets.add_forced(1, 1));
+
+        assert_eq!(tickets.iter_chunks().count(), 10);
+        assert_eq!(tickets.iter_tickets(1, 1).collect::<Vec<_>>(), vec![ChunkTicket::Spawn, ChunkTicket::Forced]);
+        assert_eq!(tickets.iter_tickets(-3, 7).collect::<Vec<_>>(), vec![ChunkTicket::Forced]);
+        assert!(!tickets.has_ticket(2, 0));
+
+        assert!(tickets.is_dirty());
+        let mut tag_data = CompoundTag::new();
+        tickets.encode_forced(&mut tag_data);
+        assert!(!tickets.is_dirty());
+
+        let mut decoded = ChunkTickets::new();
+        decoded.decode_forced(&tag_data);
+        assert!(decoded.is_forced(-3, 7));
+        assert!(decoded.is_forced(1, 1));
+        assert_eq!(decoded.iter_forced().count(), 2);
+
+    }
+
+}
@@ -0,0 +1,336 @@
+//! Command dispatching, commands are registered by name in a `CommandDispatcher` world
+//! component and executed with `execute_command` by players, command blocks or functions.
+//! Command lines being typed are completed with `complete_command`, using the suggestion
+//! providers registered for the arguments of each command.
+
+use std::collections::HashMap;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use mc_core::world::level::Level;
+use mc_core::pos::EntityPos;
+use hecs::Entity;
+use thiserror::Error;
+
+use crate::world::World;
+
+
+/// The result of a command, the integer is the result value of the command, it's used by
+/// command blocks as their success count.
+pub type CommandResult = Result<i32, CommandError>;
+
+/// A command handler, called with the source of the command and its arguments, which are
+/// the remaining of the command line after the command name.
+pub type CommandHandler = fn(&mut World, &CommandSource, &str) -> CommandResult;
+
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum CommandError {
+    #[error("Unknown command '{0}'.")]
+    Unknown(String),
+    #[error("Invalid arguments: {0}")]
+    InvalidArguments(String),
+    #[error("{0}")]
+    Failed(String)
+}
+
+
+/// The source executing a command, commands are executed in a level at a position.
+#[derive(Clone)]
+pub struct CommandSource {
+    /// The name of the source, `@` for command blocks and the name of players.
+    pub name: String,
+    pub level: Rc<RefCell<Level>>,
+    pub pos: EntityPos,
+    /// The entity executing the command, if any.
+    pub entity: Option<Entity>
+}
+
+impl CommandSource {
+
+    pub fn new(name: String, level: Rc<RefCell<Level>>, pos: EntityPos) -> Self {
+        Self {
+            name,
+            level,
+            pos,
+            entity: None
+        }
+    }
+
+    pub fn with_entity(mut self, entity: Entity) -> Self {
+        self.entity = Some(entity);
+        self
+    }
+
+}
+
+
+/// A source of suggestions for an argument of a command, registered to the dispatcher with
+/// `CommandDispatcher::register_suggestions`.
+pub trait SuggestionProvider {
+    /// Add the suggestions for the given argument being typed. Suggestions don't need to be
+    /// filtered, `complete_command` only keeps the ones that start with the argument.
+    fn suggest(&self, world: &World, source: &CommandSource, arg: &str, suggestions: &mut Vec<String>);
+}
+
+
+/// Suggest the names of all blocks of the source's level.
+pub struct BlockSuggestions;
+
+impl SuggestionProvider for BlockSuggestions {
+    fn suggest(&self, _world: &World, source: &CommandSource, _arg: &str, suggestions: &mut Vec<String>) {
+        let level = source.level.borrow();
+        suggestions.extend(level.get_env().blocks.iter_blocks().map(|block| block.get_name().to_string()));
+    }
+}
+
+
+/// Suggestions to complete a command line, the text from `start` to the end of the line is
+/// replaced by the chosen match.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Suggestions {
+    /// Byte index in the command line of the text to replace.
+    pub start: usize,
+    /// Sorted matches.
+    pub matches: Vec<String>
+}
+
+
+/// A suggestion provider for the argument at the given index of a command.
+type ArgumentSuggestions = (usize, Box<dyn SuggestionProvider>);
+
+
+/// A world component registering all commands that can be executed.
+#[derive(Default)]
+pub struct CommandDispatcher {
+    commands: HashMap<&'static str, CommandHandler>,
+    /// Suggestion providers of each command, with the index of their argument.
+    providers: HashMap<&'static str, Vec<ArgumentSuggestions>>
+}
+
+impl CommandDispatcher {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_command(mut self, name: &'static str, handler: CommandHandler) -> Self {
+        self.register(name, handler);
+        self
+    }
+
+    /// Register a command, replacing any previous command with the same name.
+    pub fn register(&mut self, name: &'static str, handler: CommandHandler) {
+        self.commands.insert(name, handler);
+    }
+
+    pub fn get_handler(&self, name: &str) -> Option<CommandHandler> {
+        self.commands.get(name).copied()
+    }
+
+    pub fn has_command(&self, name: &str) -> bool {
+        self.commands.contains_key(name)
+    }
+
+    pub fn iter_commands(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.commands.keys().copied()
+    }
+
+    pub fn with_suggestions<P>(mut self, name: &'static str, index: usize, provider: P) -> Self
+    where
+        P: SuggestionProvider + 'static
+    {
+        self.register_suggestions(name, index, provider);
+        self
+    }
+
+    /// Register a suggestion provider for the argument at the given index of a command,
+    /// multiple providers can be registered for the same argument.
+    pub fn register_suggestions<P>(&mut self, name: &'static str, index: usize, provider: P)
+    where
+        P: SuggestionProvider + 'static
+    {
+        self.providers.entry(name).or_default().push((index, Box::new(provider)));
+    }
+
+}
+
+
+/// Execute a command line with the given source, the leading slash is optional. The command
+/// is dispatched to the handler registered in the world's `CommandDispatcher` component.
+pub fn execute_command(world: &mut World, source: &CommandSource, command: &str) -> CommandResult {
+
+    let command = command.trim();
+    let command = command.strip_prefix('/').unwrap_or(command);
+    let (name, args) = match command.split_once(' ') {
+        Some((name, args)) => (name, args.trim_start()),
+        None => (command, "")
+    };
+
+    // The dispatcher is released before calling the handler, which may execute commands.
+    let handler = world.components.get::<CommandDispatcher>().ok()
+        .and_then(|dispatcher| dispatcher.get_handler(name))
+        .ok_or_else(|| CommandError::Unknown(name.to_string()))?;
+
+    handler(world, source, args)
+
+}
+
+
+/// Return true if a suggestion matches the argument being typed, the default `minecraft`
+/// namespace can be omitted from the argument.
+fn is_matching(suggestion: &str, arg: &str) -> bool {
+    suggestion.starts_with(arg) || suggestion.strip_prefix("minecraft:")
+        .is_some_and(|path| path.starts_with(arg))
+}
+
+
+/// Complete a command line being typed, the leading slash is optional. The command name is
+/// completed from the registered commands, and arguments from the suggestion providers of
+/// the command. Only the last word of the line is completed.
+pub fn complete_command(world: &World, source: &CommandSource, command: &str) -> Suggestions {
+
+    let offset = if command.starts_with('/') { 1 } else { 0 };
+    let line = &command[offset..];
+
+    let dispatcher = match world.components.get::<CommandDispatcher>() {
+        Ok(dispatcher) => dispatcher,
+        Err(_) => return Suggestions { start: offset, matches: Vec::new() }
+    };
+
+    let mut matches = Vec::new();
+
+    let start = match line.split_once(' ') {
+        None => {
+            matches.extend(dispatcher.iter_commands()
+                .filter(|name| name.starts_with(line))
+                .map(|name| name.to_string()));
+            offset
+        }
+        Some((name, args)) => {
+            let arg_start = args.rfind(' ').map_or(0, |idx| idx + 1);
+            let index = args[..arg_start].split_whitespace().count();
+            let arg = &args[arg_start..];
+            if let Some(providers) = dispatcher.providers.get(name) {
+                for (_, provider) in providers.iter().filter(|&&(idx, _)| idx == index) {
+                    provider.suggest(world, source, arg, &mut matches);
+                }
+            }
+            matches.retain(|suggestion| is_matching(suggestion, arg));
+            command.len() - arg.len()
+        }
+    };
+
+    matches.sort_unstable();
+    matches.dedup();
+    Suggestions { start, matches }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_core::world::level::LevelEnv;
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::source::NullLevelSource;
+
+    use super::*;
+
+    mc_core::blocks!(TEST_BLOCKS "test" [
+        AIR "air"
+    ]);
+
+    mc_core::biomes!(TEST_BIOMES "test" [
+        VOID "void" 0,
+    ]);
+
+    struct Counter(i32);
+
+    fn command_add(world: &mut World, _source: &CommandSource, args: &str) -> CommandResult {
+        let value = args.parse::<i32>().map_err(|_| CommandError::InvalidArguments(args.to_string()))?;
+        let mut counter = world.get_component_mut::<Counter>().unwrap();
+        counter.0 += value;
+        Ok(counter.0)
+    }
+
+    fn command_twice(world: &mut World, source: &CommandSource, args: &str) -> CommandResult {
+        execute_command(world, source, args)?;
+        execute_command(world, source, args)
+    }
+
+    #[test]
+    fn dispatch() {
+
+        let env = LevelEnv::builder()
+            .register_blocks(&TEST_BLOCKS)
+            .register_biomes(&TEST_BIOMES)
+            .build()
+            .unwrap();
+
+        let level = Level::new("overworld".to_string(), env, ChunkHeight::new(0, 0), NullLevelSource);
+        let source = CommandSource::new("@".to_string(), Rc::new(RefCell::new(level)), EntityPos::new(0.5, 0.5, 0.5));
+
+        let mut world = World::new();
+        world.insert_component(Counter(0));
+
+        // Without dispatcher, all commands are unknown.
+        assert_eq!(execute_command(&mut world, &source, "add 1"), Err(CommandError::Unknown("add".to_string())));
+
+        world.insert_component(CommandDispatcher::new()
+            .with_command("add", command_add)
+            .with_command("twice", command_twice));
+
+        assert_eq!(execute_command(&mut world, &source, "/add 2"), Ok(2));
+        assert_eq!(execute_command(&mut world, &source, "  twice  add 3"), Ok(8));
+        assert_eq!(execute_command(&mut world, &source, "add x"), Err(CommandError::InvalidArguments("x".to_string())));
+        assert_eq!(execute_command(&mut world, &source, "remove 1"), Err(CommandError::Unknown("remove".to_string())));
+
+    }
+
+    struct NumberSuggestions;
+
+    impl SuggestionProvider for NumberSuggestions {
+        fn suggest(&self, _world: &World, _source: &CommandSource, _arg: &str, suggestions: &mut Vec<String>) {
+            suggestions.extend(["1", "10", "2"].iter().map(|s| s.to_string()));
+        }
+    }
+
+    #[test]
+    fn complete() {
+
+        let env = LevelEnv::builder()
+            .register_blocks(&TEST_BLOCKS)
+            .register_biomes(&TEST_BIOMES)
+            .build()
+            .unwrap();
+
+        let level = Level::new("overworld".to_string(), env, ChunkHeight::new(0, 0), NullLevelSource);
+        let source = CommandSource::new("@".to_string(), Rc::new(RefCell::new(level)), EntityPos::new(0.5, 0.5, 0.5));
+
+        let mut world = World::new();
+        world.insert_component(CommandDispatcher::new()
+            .with_command("add", command_add)
+            .with_command("twice", command_twice)
+            .with_suggestions("add", 0, NumberSuggestions)
+            .with_suggestions("twice", 1, BlockSuggestions));
+
+        let complete = |command| complete_command(&world, &source, command);
+        let suggestions = |start, matches: &[&str]| Suggestions {
+            start,
+            matches: matches.iter().map(|s| s.to_string()).collect()
+        };
+
+        assert_eq!(complete("/"), suggestions(1, &["add", "twice"]));
+        assert_eq!(complete("/tw"), suggestions(1, &["twice"]));
+        assert_eq!(complete("/add 1"), suggestions(5, &["1", "10"]));
+        assert_eq!(complete("add "), suggestions(4, &["1", "10", "2"]));
+        // Only the first argument of add has suggestions.
+        assert_eq!(complete("/add 1 "), suggestions(7, &[]));
+        assert_eq!(complete("/twice add te"), suggestions(11, &["test:air"]));
+        assert_eq!(complete("/twice add air"), suggestions(11, &[]));
+        assert_eq!(complete("/remove "), suggestions(8, &[]));
+
+    }
+
+}
@@ -0,0 +1,77 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use mc_core::world::level::Level;
+use mc_core::pos::{EntityPos, BlockPos, Direction};
+use hecs::Entity;
+
+
+/// Event pushed when a player movement has been validated and applied to the entity.
+pub struct EntityMovedEvent {
+    pub level: Rc<RefCell<Level>>,
+    pub entity: Entity,
+    pub from: EntityPos,
+    pub to: EntityPos,
+    /// The new yaw and pitch, if the look was updated.
+    pub look: Option<(f32, f32)>,
+    pub on_ground: bool
+}
+
+
+/// Event pushed when a player movement has been rejected, the entity has not been moved
+/// and the client must be teleported back to the given authoritative position.
+pub struct MovementRejectedEvent {
+    pub level: Rc<RefCell<Level>>,
+    pub entity: Entity,
+    pub pos: EntityPos,
+    pub reason: MovementRejection
+}
+
+
+/// The reason why a movement was rejected.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum MovementRejection {
+    /// The requested position or look contains non-finite or out of world values.
+    InvalidValue,
+    /// The entity moved faster than allowed in a single movement.
+    TooFast,
+    /// The entity moved into a colliding block.
+    Collision,
+    /// The entity moved into an unloaded chunk.
+    UnloadedChunk
+}
+
+
+/// Event pushed by `system_view_tracking` when the chunks or entities seen by a viewer
+/// entity have changed, only contains what entered or left the view since the last tick.
+pub struct ViewChangedEvent {
+    pub level: Rc<RefCell<Level>>,
+    /// The viewer entity.
+    pub entity: Entity,
+    /// Chunks entering the view, sorted from the nearest to the farthest.
+    pub entered_chunks: Vec<(i32, i32)>,
+    pub left_chunks: Vec<(i32, i32)>,
+    pub entered_entities: Vec<Entity>,
+    pub left_entities: Vec<Entity>
+}
+
+
+/// Event pushed by `system_projectile` when a projectile hits a block or an entity, the
+/// projectile may have been removed from the level when this event is polled.
+pub struct ProjectileHitEvent {
+    pub level: Rc<RefCell<Level>>,
+    /// The projectile entity.
+    pub entity: Entity,
+    /// The exact position of the impact.
+    pub pos: EntityPos,
+    pub hit: ProjectileHit
+}
+
+
+/// What a projectile has hit.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ProjectileHit {
+    /// A block, with the face of the block that was hit.
+    Block(BlockPos, Direction),
+    Entity(Entity)
+}
@@ -1,2 +1,5 @@
 mod level;
 pub use level::*;
+
+mod entity;
+pub use entity::*;
@@ -0,0 +1,448 @@
+//! Tick budgets, limiting the time spent by systems on each level per tick. Budgeted systems
+//! push their work (entities, chunks) into a `WorkQueue` and process it within the budget of
+//! the level, the remaining work spills to the next ticks. Work near inhabitants (players)
+//! is processed first, so an overloaded level doesn't stall the whole server and players
+//! still see the world around them being ticked.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+use std::hash::Hash;
+
+use mc_core::world::level::{Level, BaseEntity};
+use mc_core::pos::EntityPos;
+
+use crate::world::World;
+
+use super::Inhabitant;
+
+
+/// Default time budget of each level per tick, a tick lasts 50 ms.
+pub const DEFAULT_LEVEL_BUDGET: Duration = Duration::from_millis(30);
+/// Default distance in blocks from inhabitants within which work has priority.
+pub const DEFAULT_PRIORITY_DISTANCE: f64 = 32.0;
+
+
+/// World component with the time budget of each level, reset by `system_tick_budgets`.
+pub struct TickBudgets {
+    /// Time that budgeted systems can spend on each level per tick.
+    level_budget: Duration,
+    /// Work within this distance in blocks from an inhabitant has priority.
+    priority_distance: f64,
+    /// Budget of each level, by level index.
+    levels: Vec<LevelBudget>
+}
+
+impl TickBudgets {
+
+    pub fn new() -> Self {
+        Self {
+            level_budget: DEFAULT_LEVEL_BUDGET,
+            priority_distance: DEFAULT_PRIORITY_DISTANCE,
+            levels: Vec::new()
+        }
+    }
+
+    pub fn with_level_budget(mut self, level_budget: Duration) -> Self {
+        self.level_budget = level_budget;
+        self
+    }
+
+    pub fn with_priority_distance(mut self, priority_distance: f64) -> Self {
+        self.priority_distance = priority_distance;
+        self
+    }
+
+    #[inline]
+    pub fn get_level_budget(&self) -> Duration {
+        self.level_budget
+    }
+
+    #[inline]
+    pub fn get_priority_distance(&self) -> f64 {
+        self.priority_distance
+    }
+
+    /// Get the budget of the level at the given index, `None` if not yet ticked.
+    pub fn get_level(&self, level_idx: usize) -> Option<&LevelBudget> {
+        self.levels.get(level_idx)
+    }
+
+    /// Get the budget of the level at the given index, it is created if missing.
+    pub fn get_level_mut(&mut self, level_idx: usize) -> &mut LevelBudget {
+        while self.levels.len() <= level_idx {
+            self.levels.push(LevelBudget::new(self.level_budget));
+        }
+        &mut self.levels[level_idx]
+    }
+
+    /// Iterate over the budgets of levels, with their index.
+    pub fn iter_levels(&self) -> impl Iterator<Item = (usize, &LevelBudget)> + '_ {
+        self.levels.iter().enumerate()
+    }
+
+}
+
+impl Default for TickBudgets {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// Metrics of the budget of a level, updated on each tick.
+#[derive(Debug, Clone, Default)]
+pub struct BudgetMetrics {
+    /// Total number of work items processed.
+    pub processed: u64,
+    /// Number of work items deferred to the next tick at the end of the last tick.
+    pub deferred: usize,
+    /// Number of ticks where the budget has been exhausted.
+    pub exhausted_ticks: u64,
+    /// Time spent in budgeted systems during the last tick.
+    pub last_spent: Duration,
+    /// Maximum time spent in budgeted systems in a single tick.
+    pub max_spent: Duration
+}
+
+
+/// The budget of a level for the current tick.
+#[derive(Debug)]
+pub struct LevelBudget {
+    budget: Duration,
+    spent: Duration,
+    /// Number of work items deferred by queues during the current tick.
+    deferred: usize,
+    /// Positions of the inhabitants of the level, taken at the beginning of the tick.
+    inhabitants: Vec<EntityPos>,
+    priority_distance: f64,
+    metrics: BudgetMetrics
+}
+
+impl LevelBudget {
+
+    fn new(budget: Duration) -> Self {
+        Self {
+            budget,
+            spent: Duration::ZERO,
+            deferred: 0,
+            inhabitants: Vec::new(),
+            priority_distance: DEFAULT_PRIORITY_DISTANCE,
+            metrics: BudgetMetrics::default()
+        }
+    }
+
+    #[inline]
+    pub fn get_budget(&self) -> Duration {
+        self.budget
+    }
+
+    #[inline]
+    pub fn get_spent(&self) -> Duration {
+        self.spent
+    }
+
+    #[inline]
+    pub fn get_remaining(&self) -> Duration {
+        self.budget.saturating_sub(self.spent)
+    }
+
+    #[inline]
+    pub fn is_exhausted(&self) -> bool {
+        self.spent >= self.budget
+    }
+
+    #[inline]
+    pub fn get_metrics(&self) -> &BudgetMetrics {
+        &self.metrics
+    }
+
+    /// Return true if work at the given position has priority, because it's near an
+    /// inhabitant of the level.
+    pub fn is_priority(&self, pos: &EntityPos) -> bool {
+        let distance_sq = self.priority_distance * self.priority_distance;
+        self.inhabitants.iter().any(|inhabitant| {
+            let (dx, dz) = (inhabitant.x - pos.x, inhabitant.z - pos.z);
+            dx * dx + dz * dz <= distance_sq
+        })
+    }
+
+    /// Same as `is_priority` but for the center of a chunk.
+    pub fn is_chunk_priority(&self, cx: i32, cz: i32) -> bool {
+        self.is_priority(&EntityPos::new((cx * 16 + 8) as f64, 0.0, (cz * 16 + 8) as f64))
+    }
+
+    /// Start a new tick, the metrics of the previous tick are updated.
+    fn begin_tick(&mut self, level: &Level, budget: Duration, priority_distance: f64) {
+
+        let metrics = &mut self.metrics;
+        metrics.deferred = self.deferred;
+        metrics.last_spent = self.spent;
+        metrics.max_spent = metrics.max_spent.max(self.spent);
+        if self.spent >= self.budget {
+            metrics.exhausted_ticks += 1;
+        }
+
+        self.budget = budget;
+        self.spent = Duration::ZERO;
+        self.deferred = 0;
+        self.priority_distance = priority_distance;
+        self.inhabitants.clear();
+        self.inhabitants.extend(level.entities.ecs.query::<(&BaseEntity, &Inhabitant)>()
+            .iter()
+            .map(|(_, (base, _))| base.pos.clone()));
+
+    }
+
+}
+
+
+/// A queue of work items of a budgeted system, items are processed in order, priority items
+/// first, until the budget of the level is exhausted. Items are unique in the queue, so the
+/// whole work can be pushed on each tick and deferred items keep their place.
+#[derive(Debug)]
+pub struct WorkQueue<T> {
+    priority: VecDeque<(T, u64)>,
+    normal: VecDeque<(T, u64)>,
+    /// The ticket of the valid entry of each queued item and if it has priority, entries
+    /// with another ticket are stale and skipped when popped.
+    queued: HashMap<T, (u64, bool)>,
+    next_ticket: u64
+}
+
+impl<T: Eq + Hash + Clone> WorkQueue<T> {
+
+    pub fn new() -> Self {
+        Self {
+            priority: VecDeque::new(),
+            normal: VecDeque::new(),
+            queued: HashMap::new(),
+            next_ticket: 0
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.queued.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.queued.is_empty()
+    }
+
+    #[inline]
+    pub fn contains(&self, item: &T) -> bool {
+        self.queued.contains_key(item)
+    }
+
+    /// Push an item at the end of the queue, return false if the item is already queued.
+    /// An already queued item that now has priority is moved at the end of priority items.
+    pub fn push(&mut self, item: T, priority: bool) -> bool {
+        let ticket = self.next_ticket;
+        let added = match self.queued.get_mut(&item) {
+            None => {
+                self.queued.insert(item.clone(), (ticket, priority));
+                true
+            }
+            // The normal entry of the item becomes stale.
+            Some(queued) if priority && !queued.1 => {
+                *queued = (ticket, true);
+                false
+            }
+            Some(_) => return false
+        };
+        self.next_ticket += 1;
+        if priority {
+            self.priority.push_back((item, ticket));
+        } else {
+            self.normal.push_back((item, ticket));
+        }
+        added
+    }
+
+    /// Process items with the given function until the budget is exhausted, the time spent
+    /// is added to the budget. At least one item is processed to ensure progress, and all
+    /// items are processed without budget. Return the number of processed items.
+    pub fn process<F>(&mut self, mut budget: Option<&mut LevelBudget>, mut func: F) -> usize
+    where
+        F: FnMut(T)
+    {
+
+        let start = Instant::now();
+        let mut count = 0;
+
+        loop {
+
+            if count > 0 {
+                if let Some(budget) = &budget {
+                    if budget.spent + start.elapsed() >= budget.budget {
+                        break;
+                    }
+                }
+            }
+
+            let (item, ticket) = match self.priority.pop_front().or_else(|| self.normal.pop_front()) {
+                Some(entry) => entry,
+                None => break
+            };
+
+            if !matches!(self.queued.get(&item), Some(&(queued_ticket, _)) if queued_ticket == ticket) {
+                continue;
+            }
+
+            self.queued.remove(&item);
+            func(item);
+            count += 1;
+
+        }
+
+        if let Some(budget) = &mut budget {
+            budget.spent += start.elapsed();
+            budget.deferred += self.len();
+            budget.metrics.processed += count as u64;
+        }
+
+        count
+
+    }
+
+}
+
+impl<T: Eq + Hash + Clone> Default for WorkQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// Call this function with a mutable reference to a World to register the `TickBudgets`
+/// component, budgeted systems process all their work without it.
+pub fn register_tick_budgets(world: &mut World, budgets: TickBudgets) {
+    world.insert_component(budgets);
+}
+
+/// A system starting a new tick for the budget of each level, it must run before budgeted
+/// systems.
+pub fn system_tick_budgets(world: &mut World) {
+
+    let mut budgets = match world.components.get_mut::<TickBudgets>() {
+        Ok(budgets) => budgets,
+        Err(_) => return
+    };
+
+    let (level_budget, priority_distance) = (budgets.level_budget, budgets.priority_distance);
+    for (level_idx, level) in world.levels.iter().enumerate() {
+        budgets.get_level_mut(level_idx).begin_tick(&level.borrow(), level_budget, priority_distance);
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_core::world::level::LevelEnv;
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::source::NullLevelSource;
+
+    use super::*;
+
+    mc_core::blocks!(TEST_BLOCKS "test" [
+        AIR "air"
+    ]);
+
+    mc_core::biomes!(TEST_BIOMES "test" [
+        VOID "void" 0,
+    ]);
+
+    mc_core::entities!(TEST_ENTITIES "test" [
+        PLAYER "player" []
+    ]);
+
+    #[test]
+    fn work_queue_budget() {
+
+        let env = LevelEnv::builder()
+            .register_blocks(&TEST_BLOCKS)
+            .register_biomes(&TEST_BIOMES)
+            .register_entities(&TEST_ENTITIES)
+            .build()
+            .unwrap();
+
+        let mut level = Level::new("overworld".to_string(), env, ChunkHeight::new(0, 0), NullLevelSource);
+        let player = level.spawn_entity(&PLAYER, EntityPos::new(8.0, 0.0, 8.0)).unwrap();
+        level.entities.ecs.insert_one(player, Inhabitant).unwrap();
+
+        let mut world = World::new();
+        world.add_level(level);
+        register_tick_budgets(&mut world, TickBudgets::new().with_level_budget(Duration::from_millis(5)));
+        system_tick_budgets(&mut world);
+
+        let mut budgets = world.components.get_mut::<TickBudgets>().unwrap();
+        let budget = budgets.get_level_mut(0);
+        assert!(budget.is_chunk_priority(1, 1));
+        assert!(!budget.is_chunk_priority(10, 0));
+
+        // Items are unique, priority items are processed first.
+        let mut queue = WorkQueue::new();
+        for cx in (0..10).rev() {
+            assert!(queue.push((cx, 0), budget.is_chunk_priority(cx, 0)));
+        }
+        assert!(!queue.push((0, 0), true));
+
+        // At least one item is processed even if it exhausts the budget.
+        let mut processed = Vec::new();
+        assert_eq!(queue.process(Some(&mut *budget), |pos| {
+            processed.push(pos);
+            std::thread::sleep(Duration::from_millis(6));
+        }), 1);
+        assert!(budget.is_exhausted());
+        assert_eq!(queue.len(), 9);
+
+        drop(budgets);
+        system_tick_budgets(&mut world);
+        let mut budgets = world.components.get_mut::<TickBudgets>().unwrap();
+        let budget = budgets.get_level_mut(0);
+        assert_eq!(budget.get_metrics().processed, 1);
+        assert_eq!(budget.get_metrics().deferred, 9);
+        assert_eq!(budget.get_metrics().exhausted_ticks, 1);
+        assert!(!budget.is_exhausted());
+
+        // Deferred items are processed on the next tick.
+        assert_eq!(queue.process(Some(&mut *budget), |pos| processed.push(pos)), 9);
+        assert!(queue.is_empty());
+        assert_eq!(&processed[..4], &[(2, 0), (1, 0), (0, 0), (9, 0)]);
+
+    }
+
+    #[test]
+    fn work_queue_promote() {
+
+        let mut queue = WorkQueue::new();
+        assert!(queue.push(1, false));
+        assert!(queue.push(2, false));
+        assert!(queue.push(3, true));
+
+        // Queued items that gain priority are moved, but never duplicated.
+        assert!(!queue.push(2, true));
+        assert!(!queue.push(3, false));
+        assert_eq!(queue.len(), 3);
+
+        let mut processed = Vec::new();
+        assert_eq!(queue.process(None, |item| processed.push(item)), 3);
+        assert_eq!(processed, [3, 2, 1]);
+
+        // Stale entries of promoted items are skipped, even if the item is queued again.
+        assert!(queue.push(1, false));
+        assert!(queue.push(2, false));
+        assert!(!queue.push(2, true));
+        let mut budget = LevelBudget::new(Duration::ZERO);
+        assert_eq!(queue.process(Some(&mut budget), |item| processed.push(item)), 1);
+        assert!(queue.push(2, false));
+        assert_eq!(queue.process(None, |item| processed.push(item)), 2);
+        assert_eq!(processed[3..], [2, 1, 2]);
+        assert!(queue.is_empty());
+
+    }
+
+}
@@ -0,0 +1,314 @@
+//! Collisions between entities, overlapping entities are pushed apart a bit every tick like
+//! vanilla crowds. Entities are found through the entity lists of their chunks, so only
+//! entities in the 3x3 chunks around each entity are checked.
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use mc_core::world::level::{Level, BaseEntity, Passenger};
+use mc_core::entity::EntityType;
+use mc_core::util::OpaquePtr;
+use mc_core::pos::EntityPos;
+use hecs::Entity;
+
+use crate::event::EntityMovedEvent;
+use crate::world::World;
+
+use super::NoClip;
+
+
+/// How an entity type collides with other entities.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum EntityCollision {
+    /// The entity never collides, like item frames or paintings.
+    None,
+    /// The entity is softly pushed away from other entities, like mobs.
+    Push,
+    /// Other entities can't overlap this entity, which is only pushed by other solid
+    /// entities, like boats.
+    Solid
+}
+
+
+/// The collision box and behaviour of an entity type, the box is centered on the entity's
+/// position horizontally and starts at its position vertically.
+#[derive(Debug, Copy, Clone)]
+pub struct CollisionShape {
+    pub width: f64,
+    pub height: f64,
+    pub collision: EntityCollision
+}
+
+
+/// World component storing the collision shapes of entity types, entities of unregistered
+/// types never collide.
+pub struct EntityCollisions {
+    shapes: HashMap<OpaquePtr<EntityType>, CollisionShape>,
+    /// Distance entities are pushed apart every tick when fully overlapping.
+    push_strength: f64
+}
+
+impl EntityCollisions {
+
+    pub fn new() -> Self {
+        Self {
+            shapes: HashMap::new(),
+            push_strength: 0.05
+        }
+    }
+
+    pub fn with_push_strength(mut self, push_strength: f64) -> Self {
+        self.push_strength = push_strength;
+        self
+    }
+
+    /// Register the collision shape of an entity type, replacing any previous shape.
+    pub fn with_entity_type(mut self, entity_type: &'static EntityType, width: f64, height: f64, collision: EntityCollision) -> Self {
+        self.shapes.insert(OpaquePtr::new(entity_type), CollisionShape { width, height, collision });
+        self
+    }
+
+    pub fn get_shape(&self, entity_type: &'static EntityType) -> Option<&CollisionShape> {
+        self.shapes.get(&OpaquePtr::new(entity_type))
+    }
+
+    /// Compute the horizontal displacement of every colliding entity of a level, entities
+    /// that are not moved are absent from the returned map.
+    pub fn compute_pushes(&self, level: &Level) -> HashMap<Entity, (f64, f64)> {
+
+        // Passengers don't collide, they would push their vehicle.
+        let mut entities = HashMap::new();
+        for (entity, base_entity) in level.entities.ecs.query::<&BaseEntity>().without::<NoClip>().without::<Passenger>().iter() {
+            if let Some(&shape) = self.get_shape(base_entity.entity_type) {
+                if shape.collision != EntityCollision::None {
+                    entities.insert(entity, (base_entity.pos.clone(), shape));
+                }
+            }
+        }
+
+        let mut pushes: HashMap<Entity, (f64, f64)> = HashMap::new();
+
+        for (&entity, (pos, shape)) in &entities {
+
+            let (cx, cz) = ((pos.x.floor() as i32) >> 4, (pos.z.floor() as i32) >> 4);
+
+            for ncx in (cx - 1)..=(cx + 1) {
+                for ncz in (cz - 1)..=(cz + 1) {
+
+                    let chunk = match level.chunks.get_chunk(ncx, ncz) {
+                        Some(chunk) => chunk,
+                        None => continue
+                    };
+
+                    for other in chunk.iter_entities() {
+
+                        // Each pair is only resolved once.
+                        if other <= entity {
+                            continue;
+                        }
+
+                        let (other_pos, other_shape) = match entities.get(&other) {
+                            Some(other) => other,
+                            None => continue
+                        };
+
+                        if let Some((dx, dz)) = self.compute_push(pos, shape, other_pos, other_shape) {
+                            // The push is split between both entities depending on their
+                            // collision, solid entities are only pushed by solid ones.
+                            let (ratio, other_ratio) = match (shape.collision, other_shape.collision) {
+                                (EntityCollision::Solid, EntityCollision::Push) => (0.0, 1.0),
+                                (EntityCollision::Push, EntityCollision::Solid) => (1.0, 0.0),
+                                _ => (0.5, 0.5)
+                            };
+                            if ratio > 0.0 {
+                                let push = pushes.entry(entity).or_default();
+                                push.0 -= dx * ratio;
+                                push.1 -= dz * ratio;
+                            }
+                            if other_ratio > 0.0 {
+                                let push = pushes.entry(other).or_default();
+                                push.0 += dx * other_ratio;
+                                push.1 += dz * other_ratio;
+                            }
+                        }
+
+                    }
+
+                }
+            }
+
+        }
+
+        pushes
+
+    }
+
+    /// Compute the total horizontal distance to put between two entities, from the first one
+    /// to the second one, `None` if they don't overlap.
+    fn compute_push(&self, pos: &EntityPos, shape: &CollisionShape, other_pos: &EntityPos, other_shape: &CollisionShape) -> Option<(f64, f64)> {
+
+        let half_widths = (shape.width + other_shape.width) * 0.5;
+        let mut dx = other_pos.x - pos.x;
+        let mut dz = other_pos.z - pos.z;
+
+        if dx.abs() >= half_widths || dz.abs() >= half_widths
+            || other_pos.y >= pos.y + shape.height || pos.y >= other_pos.y + other_shape.height {
+            return None;
+        }
+
+        if shape.collision == EntityCollision::Solid || other_shape.collision == EntityCollision::Solid {
+            // Entities overlapping a solid one are moved out on the axis with less overlap.
+            if dx.abs() > dz.abs() {
+                Some(((half_widths - dx.abs()).copysign(dx), 0.0))
+            } else if dz != 0.0 {
+                Some((0.0, (half_widths - dz.abs()).copysign(dz)))
+            } else {
+                Some((half_widths, 0.0))
+            }
+        } else {
+            // Vanilla push, entities at the same position are not pushed.
+            let distance = dx.abs().max(dz.abs());
+            if distance < 0.01 {
+                return None;
+            }
+            let distance = distance.sqrt();
+            let factor = (1.0 / distance).min(1.0) / distance * self.push_strength * 2.0;
+            dx *= factor;
+            dz *= factor;
+            Some((dx, dz))
+        }
+
+    }
+
+}
+
+impl Default for EntityCollisions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// Call this function with a mutable reference to a World to register the `EntityCollisions`
+/// component which is required to run the system `system_entity_collision`.
+pub fn register_entity_collision(world: &mut World, collisions: EntityCollisions) {
+    world.insert_component(collisions);
+}
+
+/// A system that pushes apart overlapping entities and triggers an `EntityMovedEvent` for
+/// each pushed entity. This system should run after entities are moved for the tick.
+pub fn system_entity_collision(world: &mut World) {
+
+    let collisions = match world.components.get::<EntityCollisions>() {
+        Ok(collisions) => collisions,
+        Err(_) => return
+    };
+
+    for level_rc in &world.levels {
+
+        let mut level = level_rc.borrow_mut();
+        let pushes = collisions.compute_pushes(&level);
+
+        for (entity, (dx, dz)) in pushes {
+
+            let from = match level.entities.ecs.get::<BaseEntity>(entity) {
+                Ok(base_entity) => base_entity.pos.clone(),
+                Err(_) => continue
+            };
+
+            let to = EntityPos::new(from.x + dx, from.y, from.z + dz);
+            level.move_entity(entity, to.clone());
+            world.event_tracker.push_event(EntityMovedEvent {
+                level: Rc::clone(level_rc),
+                entity,
+                from,
+                to,
+                look: None,
+                on_ground: false
+            });
+
+        }
+
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_core::world::level::LevelEnv;
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::source::{ChunkLoadRequest, NullLevelSource};
+
+    use super::*;
+
+    mc_core::blocks!(TEST_BLOCKS "test" [
+        AIR "air"
+    ]);
+
+    mc_core::biomes!(TEST_BIOMES "test" [
+        VOID "void" 0,
+    ]);
+
+    mc_core::entities!(TEST_ENTITIES "test" [
+        PIG "pig" [],
+        BOAT "boat" [],
+        ITEM_FRAME "item_frame" []
+    ]);
+
+    fn get_pos(world: &World, entity: Entity) -> EntityPos {
+        world.levels[0].borrow().entities.ecs.get::<BaseEntity>(entity).unwrap().pos.clone()
+    }
+
+    #[test]
+    fn entity_collision() {
+
+        let env = LevelEnv::builder()
+            .register_blocks(&TEST_BLOCKS)
+            .register_biomes(&TEST_BIOMES)
+            .register_entities(&TEST_ENTITIES)
+            .build()
+            .unwrap();
+
+        let height = ChunkHeight::new(0, 0);
+        let mut level = Level::new("overworld".to_string(), env, height, NullLevelSource);
+        for cx in -1..=0 {
+            let chunk = ChunkLoadRequest { env: level.get_env().clone(), height, cx, cz: 0 }.build_chunk();
+            level.chunks.insert_chunk(chunk);
+        }
+
+        // Two pigs overlapping across a chunk border.
+        let pig0 = level.spawn_entity(&PIG, EntityPos::new(-0.2, 0.0, 8.0)).unwrap();
+        let pig1 = level.spawn_entity(&PIG, EntityPos::new(0.2, 0.0, 8.0)).unwrap();
+        // A pig overlapping a boat and an item frame.
+        let boat = level.spawn_entity(&BOAT, EntityPos::new(8.0, 0.0, 8.0)).unwrap();
+        let pig2 = level.spawn_entity(&PIG, EntityPos::new(9.0, 0.0, 8.0)).unwrap();
+        let frame = level.spawn_entity(&ITEM_FRAME, EntityPos::new(9.0, 0.0, 8.0)).unwrap();
+        // A pig far above the others.
+        let pig3 = level.spawn_entity(&PIG, EntityPos::new(8.0, 10.0, 8.0)).unwrap();
+
+        let mut world = World::new();
+        world.add_level(level);
+        register_entity_collision(&mut world, EntityCollisions::new()
+            .with_entity_type(&PIG, 0.9, 0.9, EntityCollision::Push)
+            .with_entity_type(&BOAT, 1.375, 0.5625, EntityCollision::Solid)
+            .with_entity_type(&ITEM_FRAME, 0.5, 0.5, EntityCollision::None));
+
+        system_entity_collision(&mut world);
+        assert_eq!(world.event_tracker.poll_events::<EntityMovedEvent>().count(), 3);
+
+        let (pos0, pos1) = (get_pos(&world, pig0), get_pos(&world, pig1));
+        assert!(pos0.x < -0.2 && pos1.x > 0.2);
+        assert_eq!(pos0.x + pos1.x, 0.0);
+        assert_eq!(pos0.z, 8.0);
+
+        // The pig is moved out of the boat, which doesn't move.
+        assert_eq!(get_pos(&world, boat), EntityPos::new(8.0, 0.0, 8.0));
+        assert_eq!(get_pos(&world, pig2).x, 8.0 + (1.375 + 0.9) * 0.5);
+        assert_eq!(get_pos(&world, frame), EntityPos::new(9.0, 0.0, 8.0));
+        assert_eq!(get_pos(&world, pig3), EntityPos::new(8.0, 10.0, 8.0));
+
+    }
+
+}
@@ -0,0 +1,120 @@
+//! Chunks inhabited time, the time spent by inhabitants (usually players) near chunks is
+//! accumulated in each chunk and used by the regional difficulty.
+
+use std::collections::HashSet;
+
+use mc_core::world::level::BaseEntity;
+
+use crate::world::World;
+
+
+/// Chunks within this distance in blocks from an inhabitant are inhabited, like vanilla
+/// which uses the distance of mobs spawning.
+const INHABITED_DISTANCE: f64 = 128.0;
+
+
+/// An entity component marking entities that inhabit chunks around them, this component
+/// should be added to players that are not spectators.
+#[derive(Debug, Default)]
+pub struct Inhabitant;
+
+
+/// A system that increments the inhabited time of every loaded chunk with an inhabitant
+/// within 128 blocks of its center, the time is incremented once per tick even if there
+/// are multiple inhabitants.
+pub fn system_inhabited_time(world: &mut World) {
+
+    let chunk_radius = (INHABITED_DISTANCE as i32 >> 4) + 1;
+
+    for level in &world.levels {
+
+        let level = level.borrow();
+        let mut inhabited_chunks = HashSet::new();
+
+        for (_, (base_entity, _)) in level.entities.ecs.query::<(&BaseEntity, &Inhabitant)>().iter() {
+            let pos = &base_entity.pos;
+            let (pcx, pcz) = ((pos.x.floor() as i32) >> 4, (pos.z.floor() as i32) >> 4);
+            for cx in (pcx - chunk_radius)..=(pcx + chunk_radius) {
+                for cz in (pcz - chunk_radius)..=(pcz + chunk_radius) {
+                    let dx = (cx * 16 + 8) as f64 - pos.x;
+                    let dz = (cz * 16 + 8) as f64 - pos.z;
+                    if dx * dx + dz * dz < INHABITED_DISTANCE * INHABITED_DISTANCE {
+                        inhabited_chunks.insert((cx, cz));
+                    }
+                }
+            }
+        }
+
+        for (cx, cz) in inhabited_chunks {
+            if let Some(mut chunk) = level.chunks.get_chunk_mut(cx, cz) {
+                let inhabited_time = chunk.get_inhabited_time();
+                chunk.set_inhabited_time(inhabited_time + 1);
+            }
+        }
+
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_core::world::level::{Level, LevelEnv};
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::source::{ChunkLoadRequest, NullLevelSource};
+    use mc_core::pos::EntityPos;
+
+    use super::*;
+
+    mc_core::blocks!(TEST_BLOCKS "test" [
+        AIR "air"
+    ]);
+
+    mc_core::biomes!(TEST_BIOMES "test" [
+        VOID "void" 0,
+    ]);
+
+    mc_core::entities!(TEST_ENTITIES "test" [
+        PLAYER "player" []
+    ]);
+
+    #[test]
+    fn inhabited_time() {
+
+        let env = LevelEnv::builder()
+            .register_blocks(&TEST_BLOCKS)
+            .register_biomes(&TEST_BIOMES)
+            .register_entities(&TEST_ENTITIES)
+            .build()
+            .unwrap();
+
+        let height = ChunkHeight::new(0, 0);
+        let mut level = Level::new("overworld".to_string(), env, height, NullLevelSource);
+
+        for cx in 0..=10 {
+            let chunk = ChunkLoadRequest { env: level.get_env().clone(), height, cx, cz: 0 }.build_chunk();
+            level.chunks.insert_chunk(chunk);
+        }
+
+        let player = level.spawn_entity(&PLAYER, EntityPos::new(8.0, 0.0, 8.0)).unwrap();
+        level.spawn_entity(&PLAYER, EntityPos::new(24.0, 0.0, 8.0)).unwrap();
+        level.entities.ecs.insert_one(player, Inhabitant).unwrap();
+
+        let mut world = World::new();
+        world.add_level(level);
+
+        system_inhabited_time(&mut world);
+        system_inhabited_time(&mut world);
+
+        let level = world.levels[0].borrow();
+        let get_inhabited_time = |cx| level.chunks.get_chunk(cx, 0).unwrap().get_inhabited_time();
+        assert_eq!(get_inhabited_time(0), 2);
+        assert_eq!(get_inhabited_time(7), 2);
+        // The center of this chunk is exactly 128 blocks away.
+        assert_eq!(get_inhabited_time(8), 0);
+        assert_eq!(get_inhabited_time(10), 0);
+
+    }
+
+}
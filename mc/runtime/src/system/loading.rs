@@ -26,3 +26,12 @@ pub fn system_load_chunks(world: &mut World) {
     }
 
 }
+
+
+/// A system that requests loading of chunks with a ticket in every level, this includes
+/// spawn chunks and forced chunks.
+pub fn system_chunk_tickets(world: &mut World) {
+    for level in &world.levels {
+        level.borrow_mut().request_ticketed_chunks_load();
+    }
+}
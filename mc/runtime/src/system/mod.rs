@@ -5,3 +5,24 @@ pub use loading::*;
 
 mod entity;
 pub use entity::*;
+
+mod movement;
+pub use movement::*;
+
+mod collision;
+pub use collision::*;
+
+mod projectile;
+pub use projectile::*;
+
+mod tracking;
+pub use tracking::*;
+
+mod inhabited;
+pub use inhabited::*;
+
+mod riding;
+pub use riding::*;
+
+mod budget;
+pub use budget::*;
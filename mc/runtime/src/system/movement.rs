@@ -0,0 +1,353 @@
+//! Server authoritative movement of players, movements requested by clients are queued in
+//! the `PlayerMovements` component and validated by `system_player_movement`, accepted
+//! movements are applied to the entity while rejected ones must snap the client back.
+
+use std::rc::Rc;
+
+use mc_core::world::level::{Level, BaseEntity};
+use mc_core::world::chunk::ChunkError;
+use mc_core::pos::EntityPos;
+use mc_core::tag::TagType;
+use hecs::Entity;
+
+use crate::event::{EntityMovedEvent, MovementRejectedEvent, MovementRejection};
+use crate::world::World;
+
+
+/// Half the width of a player's bounding box.
+const PLAYER_HALF_WIDTH: f64 = 0.3;
+/// The height of a player's bounding box.
+const PLAYER_HEIGHT: f64 = 1.8;
+/// Horizontal coordinates are limited to the world border, like vanilla.
+const MAX_HORIZONTAL_COORD: f64 = 3.0e7;
+
+
+/// A movement requested by a player, if the position is none only the look is changed.
+#[derive(Debug, Clone)]
+pub struct MovementRequest {
+    pub level_idx: usize,
+    pub entity: Entity,
+    pub pos: Option<EntityPos>,
+    /// The requested yaw and pitch.
+    pub look: Option<(f32, f32)>,
+    pub on_ground: bool
+}
+
+
+/// Marker component for entities passing through blocks and other entities, their movements
+/// are not checked for collisions, they are not pushed by other entities and projectiles
+/// don't hit them. Used for players in spectator mode.
+pub struct NoClip;
+
+
+/// World component where network layers queue the movements requested by players.
+pub struct PlayerMovements {
+    /// Pending requests, processed on the next run of `system_player_movement`.
+    requests: Vec<MovementRequest>,
+    /// Maximum distance allowed in a single movement.
+    max_distance: f64,
+    /// Optional tag of blocks that players can walk through, collisions are not checked
+    /// if no tag is given.
+    passable_tag: Option<&'static TagType>
+}
+
+impl PlayerMovements {
+
+    pub fn new() -> Self {
+        Self {
+            requests: Vec::new(),
+            // Vanilla reports players that "moved too quickly" over 10 blocks.
+            max_distance: 10.0,
+            passable_tag: None
+        }
+    }
+
+    pub fn with_max_distance(mut self, max_distance: f64) -> Self {
+        self.max_distance = max_distance;
+        self
+    }
+
+    /// Enable collision checks, blocks without the given tag are colliding.
+    pub fn with_passable_tag(mut self, tag: &'static TagType) -> Self {
+        self.passable_tag = Some(tag);
+        self
+    }
+
+    /// Queue a movement request to be processed on the next tick.
+    pub fn push_request(&mut self, request: MovementRequest) {
+        self.requests.push(request);
+    }
+
+    /// Check if a movement from a position to another is valid, ignoring collisions.
+    pub fn check_no_clip_movement(&self, from: &EntityPos, to: &EntityPos) -> Result<(), MovementRejection> {
+
+        if !is_valid_pos(to) {
+            return Err(MovementRejection::InvalidValue);
+        }
+
+        let dx = to.x - from.x;
+        let dy = to.y - from.y;
+        let dz = to.z - from.z;
+        if dx * dx + dy * dy + dz * dz > self.max_distance * self.max_distance {
+            return Err(MovementRejection::TooFast);
+        }
+
+        Ok(())
+
+    }
+
+    /// Check if a movement from a position to another is valid in the given level.
+    pub fn check_movement(&self, level: &Level, from: &EntityPos, to: &EntityPos) -> Result<(), MovementRejection> {
+
+        self.check_no_clip_movement(from, to)?;
+
+        if let Some(passable_tag) = self.passable_tag {
+            // Players already stuck in blocks are allowed to move out of them.
+            if is_colliding(level, to, passable_tag)? && !is_colliding(level, from, passable_tag)? {
+                return Err(MovementRejection::Collision);
+            }
+        }
+
+        Ok(())
+
+    }
+
+}
+
+impl Default for PlayerMovements {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+fn is_valid_pos(pos: &EntityPos) -> bool {
+    pos.x.is_finite() && pos.y.is_finite() && pos.z.is_finite()
+        && pos.x.abs() <= MAX_HORIZONTAL_COORD
+        && pos.z.abs() <= MAX_HORIZONTAL_COORD
+}
+
+
+/// Return true if a player's bounding box at the given position intersects a colliding
+/// block, blocks out of the level's height are not colliding.
+fn is_colliding(level: &Level, pos: &EntityPos, passable_tag: &'static TagType) -> Result<bool, MovementRejection> {
+
+    let blocks = &level.get_env().blocks;
+    // Empty sub chunks are skipped if the null block is passable.
+    let null_passable = blocks.has_block_tag(blocks.get_state_from(0).unwrap().get_block(), passable_tag);
+
+    let min_x = (pos.x - PLAYER_HALF_WIDTH).floor() as i32;
+    let max_x = (pos.x + PLAYER_HALF_WIDTH).ceil() as i32;
+    let min_y = pos.y.floor() as i32;
+    let max_y = (pos.y + PLAYER_HEIGHT).ceil() as i32;
+    let min_z = (pos.z - PLAYER_HALF_WIDTH).floor() as i32;
+    let max_z = (pos.z + PLAYER_HALF_WIDTH).ceil() as i32;
+
+    for x in min_x..max_x {
+        for z in min_z..max_z {
+            let chunk = level.chunks.get_chunk_at(x, z)
+                .ok_or(MovementRejection::UnloadedChunk)?;
+            for y in min_y..max_y {
+                if null_passable && chunk.is_sub_chunk_empty_at(y) {
+                    continue;
+                }
+                match chunk.get_block_at(x, y, z) {
+                    Ok(state) => if !blocks.has_block_tag(state.get_block(), passable_tag) {
+                        return Ok(true);
+                    },
+                    Err(ChunkError::SubChunkOutOfRange) => {}
+                    Err(_) => return Err(MovementRejection::UnloadedChunk)
+                }
+            }
+        }
+    }
+
+    Ok(false)
+
+}
+
+
+/// Call this function with a mutable reference to a World to register the `PlayerMovements`
+/// component which is required to run the system `system_player_movement`.
+pub fn register_player_movement(world: &mut World, movements: PlayerMovements) {
+    world.insert_component(movements);
+}
+
+/// A system that validates all queued movements, valid movements are applied to entities and
+/// trigger an `EntityMovedEvent`, invalid ones trigger a `MovementRejectedEvent` with the
+/// current position of the entity.
+pub fn system_player_movement(world: &mut World) {
+
+    let mut movements = match world.components.get_mut::<PlayerMovements>() {
+        Ok(movements) => movements,
+        Err(_) => return
+    };
+
+    let requests = std::mem::take(&mut movements.requests);
+
+    for request in requests {
+
+        let level_rc = match world.levels.get(request.level_idx) {
+            Some(level) => level,
+            None => continue
+        };
+
+        let mut level = level_rc.borrow_mut();
+
+        let from = match level.entities.ecs.get::<BaseEntity>(request.entity) {
+            Ok(base_entity) => base_entity.pos.clone(),
+            Err(_) => continue
+        };
+
+        let to = request.pos.unwrap_or_else(|| from.clone());
+        let no_clip = level.entities.ecs.get::<NoClip>(request.entity).is_ok();
+
+        let valid_look = request.look.is_none_or(|(yaw, pitch)| yaw.is_finite() && pitch.is_finite());
+        let res = if !valid_look {
+            Err(MovementRejection::InvalidValue)
+        } else if no_clip {
+            movements.check_no_clip_movement(&from, &to)
+        } else {
+            movements.check_movement(&level, &from, &to)
+        };
+
+        match res {
+            Ok(()) => {
+                level.move_entity(request.entity, to.clone());
+                world.event_tracker.push_event(EntityMovedEvent {
+                    level: Rc::clone(level_rc),
+                    entity: request.entity,
+                    from,
+                    to,
+                    look: request.look,
+                    on_ground: request.on_ground
+                });
+            }
+            Err(reason) => {
+                world.event_tracker.push_event(MovementRejectedEvent {
+                    level: Rc::clone(level_rc),
+                    entity: request.entity,
+                    pos: from,
+                    reason
+                });
+            }
+        }
+
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use mc_core::world::level::LevelEnv;
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::source::{ChunkLoadRequest, NullLevelSource};
+
+    use super::*;
+
+    mc_core::blocks!(TEST_BLOCKS "test" [
+        AIR "air",
+        STONE "stone"
+    ]);
+
+    mc_core::biomes!(TEST_BIOMES "test" [
+        VOID "void" 0,
+    ]);
+
+    mc_core::entities!(TEST_ENTITIES "test" [
+        PLAYER "player" []
+    ]);
+
+    static TAG_PASSABLE: TagType = TagType::new_logical("test:passable");
+
+    #[test]
+    fn player_movement() {
+
+        let env = LevelEnv::builder()
+            .register_blocks(&TEST_BLOCKS)
+            .register_block_tag(&TAG_PASSABLE, [&AIR])
+            .register_biomes(&TEST_BIOMES)
+            .register_entities(&TEST_ENTITIES)
+            .build()
+            .unwrap();
+
+        let height = ChunkHeight::new(0, 3);
+        let mut level = Level::new("overworld".to_string(), env, height, NullLevelSource);
+
+        // A single chunk with a stone floor and a pillar at 4/4.
+        let mut chunk = ChunkLoadRequest { env: Arc::clone(level.get_env()), height, cx: 0, cz: 0 }.build_chunk();
+        for x in 0..16 {
+            for z in 0..16 {
+                chunk.set_block(x, 0, z, STONE.get_default_state()).unwrap();
+            }
+        }
+        chunk.set_block(4, 1, 4, STONE.get_default_state()).unwrap();
+        level.chunks.insert_chunk(chunk);
+
+        let entity = level.spawn_entity(&PLAYER, EntityPos::new(0.5, 1.0, 0.5)).unwrap();
+
+        let mut world = World::new();
+        world.add_level(level);
+        register_player_movement(&mut world, PlayerMovements::new().with_passable_tag(&TAG_PASSABLE));
+
+        let requests = [
+            (EntityPos::new(1.5, 1.0, 1.5), None),
+            (EntityPos::new(4.5, 1.0, 4.5), Some(MovementRejection::Collision)),
+            (EntityPos::new(1.5, 0.5, 1.5), Some(MovementRejection::Collision)),
+            (EntityPos::new(1.5, 1.0, 14.0), Some(MovementRejection::TooFast)),
+            (EntityPos::new(1.5, f64::NAN, 1.5), Some(MovementRejection::InvalidValue)),
+            (EntityPos::new(9.0, 1.0, 1.5), None),
+            (EntityPos::new(17.0, 1.0, 1.5), Some(MovementRejection::UnloadedChunk)),
+        ];
+
+        for (pos, expected) in requests.iter().cloned() {
+
+            world.get_component_mut::<PlayerMovements>().unwrap().push_request(MovementRequest {
+                level_idx: 0,
+                entity,
+                pos: Some(pos.clone()),
+                look: None,
+                on_ground: true
+            });
+
+            system_player_movement(&mut world);
+
+            match expected {
+                None => {
+                    let event = world.event_tracker.poll_events::<EntityMovedEvent>().next().unwrap();
+                    assert_eq!(event.to, pos);
+                }
+                Some(reason) => {
+                    let event = world.event_tracker.poll_events::<MovementRejectedEvent>().next().unwrap();
+                    assert_eq!(event.reason, reason);
+                }
+            }
+
+            world.event_tracker.clear_events();
+
+        }
+
+        assert_eq!(world.levels[0].borrow().entities.ecs.get::<BaseEntity>(entity).unwrap().pos, EntityPos::new(9.0, 1.0, 1.5));
+
+        // Entities without clipping can move through the floor, but not too fast.
+        world.levels[0].borrow_mut().entities.ecs.insert_one(entity, NoClip).unwrap();
+        let movements = world.get_component::<PlayerMovements>().unwrap();
+        assert_eq!(movements.check_no_clip_movement(&EntityPos::new(9.0, 1.0, 1.5), &EntityPos::new(9.0, 0.5, 1.5)), Ok(()));
+        drop(movements);
+        world.get_component_mut::<PlayerMovements>().unwrap().push_request(MovementRequest {
+            level_idx: 0,
+            entity,
+            pos: Some(EntityPos::new(4.5, 1.0, 4.5)),
+            look: None,
+            on_ground: false
+        });
+        system_player_movement(&mut world);
+        assert_eq!(world.event_tracker.poll_events::<EntityMovedEvent>().count(), 1);
+
+    }
+
+}
@@ -0,0 +1,571 @@
+//! Projectiles simulation, entities with a `Projectile` component are moved by their velocity
+//! every tick, slowed down by drag and pulled down by gravity. The path of each projectile
+//! is checked against blocks and entities, a `ProjectileHitEvent` is pushed on impact.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use mc_core::world::level::{Level, BaseEntity};
+use mc_core::world::chunk::ChunkError;
+use mc_core::pos::{EntityPos, BlockPos, Direction};
+use mc_core::tag::TagType;
+use hecs::Entity;
+
+use crate::event::{EntityMovedEvent, ProjectileHitEvent, ProjectileHit};
+use crate::util::EventTracker;
+use crate::world::World;
+
+use super::{EntityCollisions, EntityCollision, NoClip, TickBudgets, WorkQueue};
+
+
+/// Margin added around entities' collision boxes when checking if a projectile hits them,
+/// like vanilla arrows.
+const ENTITY_HIT_MARGIN: f64 = 0.3;
+
+
+/// Physics and behaviour of a kind of projectile.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ProjectileKind {
+    /// Velocity removed from the Y axis every tick.
+    pub gravity: f64,
+    /// Factor applied to the velocity every tick.
+    pub drag: f64,
+    /// True if the projectile sticks into hit blocks, it is removed otherwise.
+    pub sticks: bool,
+    /// True if the projectile bounces back off hit entities, it is removed otherwise.
+    pub bounces: bool
+}
+
+impl ProjectileKind {
+
+    pub const ARROW: Self = Self {
+        gravity: 0.05,
+        drag: 0.99,
+        sticks: true,
+        bounces: false
+    };
+
+    pub const SNOWBALL: Self = Self {
+        gravity: 0.03,
+        drag: 0.99,
+        sticks: false,
+        bounces: false
+    };
+
+    pub const TRIDENT: Self = Self {
+        gravity: 0.05,
+        drag: 0.99,
+        sticks: true,
+        bounces: true
+    };
+
+}
+
+
+/// An entity component for entities simulated as projectiles.
+#[derive(Debug, Clone)]
+pub struct Projectile {
+    kind: ProjectileKind,
+    /// Velocity in blocks per tick.
+    pub velocity: EntityPos,
+    /// The entity that shot this projectile, it can't be hit by it.
+    owner: Option<Entity>,
+    /// The block this projectile is stuck into.
+    stuck_block: Option<BlockPos>,
+    /// Number of ticks since the projectile is stuck.
+    stuck_ticks: u32
+}
+
+impl Projectile {
+
+    pub fn new(kind: ProjectileKind, velocity: EntityPos) -> Self {
+        Self {
+            kind,
+            velocity,
+            owner: None,
+            stuck_block: None,
+            stuck_ticks: 0
+        }
+    }
+
+    pub fn with_owner(mut self, owner: Entity) -> Self {
+        self.owner = Some(owner);
+        self
+    }
+
+    #[inline]
+    pub fn get_kind(&self) -> &ProjectileKind {
+        &self.kind
+    }
+
+    #[inline]
+    pub fn get_owner(&self) -> Option<Entity> {
+        self.owner
+    }
+
+    #[inline]
+    pub fn get_stuck_block(&self) -> Option<&BlockPos> {
+        self.stuck_block.as_ref()
+    }
+
+    #[inline]
+    pub fn is_stuck(&self) -> bool {
+        self.stuck_block.is_some()
+    }
+
+}
+
+
+/// World component with the settings used by `system_projectile`.
+pub struct Projectiles {
+    /// Projectiles go through blocks with this tag.
+    passable_tag: &'static TagType,
+    /// Number of ticks before stuck projectiles are removed.
+    stuck_lifetime: u32,
+    /// Projectiles waiting for their tick in each level, by level index.
+    queues: Vec<WorkQueue<Entity>>
+}
+
+impl Projectiles {
+
+    pub fn new(passable_tag: &'static TagType) -> Self {
+        Self {
+            passable_tag,
+            // Vanilla arrows are removed after one minute.
+            stuck_lifetime: 1200,
+            queues: Vec::new()
+        }
+    }
+
+    pub fn with_stuck_lifetime(mut self, stuck_lifetime: u32) -> Self {
+        self.stuck_lifetime = stuck_lifetime;
+        self
+    }
+
+    /// Return true if the block at the given position stops projectiles, blocks out of the
+    /// level's height are passable. Return an error if the chunk is not loaded.
+    fn is_block_colliding(&self, level: &Level, pos: &BlockPos) -> Result<bool, ChunkError> {
+        let chunk = level.chunks.get_chunk_at_block(pos.clone()).ok_or(ChunkError::ChunkUnloaded)?;
+        match chunk.get_block_at(pos.x, pos.y, pos.z) {
+            Ok(state) => Ok(!level.get_env().blocks.has_block_tag(state.get_block(), self.passable_tag)),
+            Err(ChunkError::SubChunkOutOfRange) => Ok(false),
+            Err(e) => Err(e)
+        }
+    }
+
+    /// Find the first colliding block crossed by the segment between the two positions, the
+    /// block containing the start position is ignored. The returned hit contains the fraction
+    /// of the segment before the hit, the block position and its hit face.
+    fn raycast_blocks(&self, level: &Level, from: &EntityPos, to: &EntityPos) -> Result<Option<(f64, BlockPos, Direction)>, ChunkError> {
+
+        let delta = [to.x - from.x, to.y - from.y, to.z - from.z];
+        let start = from.into_array();
+        let mut block = [start[0].floor() as i32, start[1].floor() as i32, start[2].floor() as i32];
+
+        let mut step = [0; 3];
+        let mut t_max = [f64::INFINITY; 3];
+        let mut t_delta = [f64::INFINITY; 3];
+
+        for axis in 0..3 {
+            if delta[axis] > 0.0 {
+                step[axis] = 1;
+                t_max[axis] = (block[axis] as f64 + 1.0 - start[axis]) / delta[axis];
+                t_delta[axis] = 1.0 / delta[axis];
+            } else if delta[axis] < 0.0 {
+                step[axis] = -1;
+                t_max[axis] = (block[axis] as f64 - start[axis]) / delta[axis];
+                t_delta[axis] = -1.0 / delta[axis];
+            }
+        }
+
+        loop {
+
+            let axis = if t_max[0] <= t_max[1] && t_max[0] <= t_max[2] {
+                0
+            } else if t_max[1] <= t_max[2] {
+                1
+            } else {
+                2
+            };
+
+            let t = t_max[axis];
+            if t > 1.0 {
+                return Ok(None);
+            }
+
+            block[axis] += step[axis];
+            t_max[axis] += t_delta[axis];
+
+            let pos = BlockPos::new(block[0], block[1], block[2]);
+            if self.is_block_colliding(level, &pos)? {
+                let face = match (axis, step[axis] > 0) {
+                    (0, true) => Direction::West,
+                    (0, false) => Direction::East,
+                    (1, true) => Direction::Down,
+                    (1, false) => Direction::Up,
+                    (2, true) => Direction::North,
+                    _ => Direction::South
+                };
+                return Ok(Some((t, pos, face)));
+            }
+
+        }
+
+    }
+
+    /// Move the given projectile and resolve its impact, events are pushed to the given
+    /// tracker. Projectiles in unloaded chunks are frozen.
+    fn tick_projectile(&self, level_rc: &Rc<RefCell<Level>>, level: &mut Level, events: &mut EventTracker, collisions: Option<&EntityCollisions>, entity: Entity) {
+
+        let (from, mut projectile) = match level.entities.ecs.query_one_mut::<(&BaseEntity, &Projectile)>(entity) {
+            Ok((base_entity, projectile)) => (base_entity.pos.clone(), projectile.clone()),
+            Err(_) => return
+        };
+
+        if let Some(stuck_block) = &projectile.stuck_block {
+            match self.is_block_colliding(level, stuck_block) {
+                Ok(true) => {
+                    projectile.stuck_ticks += 1;
+                    if projectile.stuck_ticks >= self.stuck_lifetime {
+                        level.remove_entity(entity);
+                    } else {
+                        *level.entities.ecs.get_mut::<Projectile>(entity).unwrap() = projectile;
+                    }
+                    return;
+                }
+                Ok(false) => {
+                    // The block has been removed, the projectile falls.
+                    projectile.stuck_block = None;
+                    projectile.stuck_ticks = 0;
+                }
+                Err(_) => return
+            }
+        }
+
+        let velocity = &projectile.velocity;
+        let mut to = EntityPos::new(from.x + velocity.x, from.y + velocity.y, from.z + velocity.z);
+
+        let block_hit = match self.raycast_blocks(level, &from, &to) {
+            Ok(block_hit) => block_hit,
+            Err(_) => return
+        };
+
+        let entity_hit = collisions
+            .and_then(|collisions| sweep_entities(level, collisions, entity, projectile.owner, &from, &to))
+            .filter(|&(t, _)| block_hit.as_ref().is_none_or(|&(block_t, _, _)| t < block_t));
+
+        let mut removed = false;
+
+        if let Some((t, hit_entity)) = entity_hit {
+            to = lerp_pos(&from, &to, t);
+            events.push_event(ProjectileHitEvent {
+                level: Rc::clone(level_rc),
+                entity,
+                pos: to.clone(),
+                hit: ProjectileHit::Entity(hit_entity)
+            });
+            if projectile.kind.bounces {
+                let velocity = &mut projectile.velocity;
+                velocity.x *= -0.01;
+                velocity.y *= -0.1;
+                velocity.z *= -0.01;
+            } else {
+                removed = true;
+            }
+        } else if let Some((t, block, face)) = block_hit {
+            to = lerp_pos(&from, &to, t);
+            events.push_event(ProjectileHitEvent {
+                level: Rc::clone(level_rc),
+                entity,
+                pos: to.clone(),
+                hit: ProjectileHit::Block(block.clone(), face)
+            });
+            if projectile.kind.sticks {
+                projectile.stuck_block = Some(block);
+                projectile.velocity = EntityPos::nil();
+            } else {
+                removed = true;
+            }
+        } else {
+            let velocity = &mut projectile.velocity;
+            velocity.x *= projectile.kind.drag;
+            velocity.y = velocity.y * projectile.kind.drag - projectile.kind.gravity;
+            velocity.z *= projectile.kind.drag;
+        }
+
+        if removed {
+            level.remove_entity(entity);
+            return;
+        }
+
+        level.move_entity(entity, to.clone());
+        *level.entities.ecs.get_mut::<Projectile>(entity).unwrap() = projectile;
+
+        events.push_event(EntityMovedEvent {
+            level: Rc::clone(level_rc),
+            entity,
+            from,
+            to,
+            look: None,
+            on_ground: false
+        });
+
+    }
+
+}
+
+
+/// Find the nearest entity crossed by the segment between the two positions, entities are
+/// found in the chunks around the segment. The returned hit contains the fraction of the
+/// segment before the hit.
+fn sweep_entities(level: &Level, collisions: &EntityCollisions, projectile: Entity, owner: Option<Entity>, from: &EntityPos, to: &EntityPos) -> Option<(f64, Entity)> {
+
+    let delta = [to.x - from.x, to.y - from.y, to.z - from.z];
+    let start = from.into_array();
+
+    let min_cx = ((from.x.min(to.x) - 2.0).floor() as i32) >> 4;
+    let max_cx = ((from.x.max(to.x) + 2.0).floor() as i32) >> 4;
+    let min_cz = ((from.z.min(to.z) - 2.0).floor() as i32) >> 4;
+    let max_cz = ((from.z.max(to.z) + 2.0).floor() as i32) >> 4;
+
+    let mut nearest: Option<(f64, Entity)> = None;
+
+    for cx in min_cx..=max_cx {
+        for cz in min_cz..=max_cz {
+
+            let chunk = match level.chunks.get_chunk(cx, cz) {
+                Some(chunk) => chunk,
+                None => continue
+            };
+
+            for entity in chunk.iter_entities() {
+
+                if entity == projectile || Some(entity) == owner
+                    || level.entities.ecs.get::<Projectile>(entity).is_ok()
+                    || level.entities.ecs.get::<NoClip>(entity).is_ok() {
+                    continue;
+                }
+
+                let base_entity = match level.entities.ecs.get::<BaseEntity>(entity) {
+                    Ok(base_entity) => base_entity,
+                    Err(_) => continue
+                };
+
+                let shape = match collisions.get_shape(base_entity.entity_type) {
+                    Some(shape) if shape.collision != EntityCollision::None => shape,
+                    _ => continue
+                };
+
+                let half_width = shape.width * 0.5 + ENTITY_HIT_MARGIN;
+                let pos = &base_entity.pos;
+                let min = [pos.x - half_width, pos.y - ENTITY_HIT_MARGIN, pos.z - half_width];
+                let max = [pos.x + half_width, pos.y + shape.height + ENTITY_HIT_MARGIN, pos.z + half_width];
+
+                if let Some(t) = intersect_box(start, delta, min, max) {
+                    if nearest.is_none_or(|(nearest_t, _)| t < nearest_t) {
+                        nearest = Some((t, entity));
+                    }
+                }
+
+            }
+
+        }
+    }
+
+    nearest
+
+}
+
+
+/// Return the fraction of the segment before it enters the given box, `None` if the segment
+/// doesn't cross the box.
+fn intersect_box(start: [f64; 3], delta: [f64; 3], min: [f64; 3], max: [f64; 3]) -> Option<f64> {
+    let mut t_enter: f64 = 0.0;
+    let mut t_exit: f64 = 1.0;
+    for axis in 0..3 {
+        if delta[axis] == 0.0 {
+            if start[axis] < min[axis] || start[axis] > max[axis] {
+                return None;
+            }
+        } else {
+            let t0 = (min[axis] - start[axis]) / delta[axis];
+            let t1 = (max[axis] - start[axis]) / delta[axis];
+            t_enter = t_enter.max(t0.min(t1));
+            t_exit = t_exit.min(t0.max(t1));
+            if t_enter > t_exit {
+                return None;
+            }
+        }
+    }
+    Some(t_enter)
+}
+
+
+#[inline]
+fn lerp_pos(from: &EntityPos, to: &EntityPos, t: f64) -> EntityPos {
+    EntityPos::new(
+        from.x + (to.x - from.x) * t,
+        from.y + (to.y - from.y) * t,
+        from.z + (to.z - from.z) * t
+    )
+}
+
+
+/// Call this function with a mutable reference to a World to register the `Projectiles`
+/// component which is required to run the system `system_projectile`.
+pub fn register_projectiles(world: &mut World, projectiles: Projectiles) {
+    world.insert_component(projectiles);
+}
+
+/// A system that moves all projectiles and resolves their impacts, projectiles only hit
+/// entities with a collision shape in the `EntityCollisions` component, if registered.
+/// Projectiles in unloaded chunks are frozen. If the `TickBudgets` component is registered,
+/// projectiles are moved within the budget of their level, projectiles near inhabitants
+/// first, the others are deferred to the next ticks.
+pub fn system_projectile(world: &mut World) {
+
+    let mut projectiles = match world.components.get_mut::<Projectiles>() {
+        Ok(projectiles) => projectiles,
+        Err(_) => return
+    };
+
+    let collisions = world.components.get::<EntityCollisions>().ok();
+    let mut budgets = world.components.get_mut::<TickBudgets>().ok();
+    let events = &mut world.event_tracker;
+
+    for (level_idx, level_rc) in world.levels.iter().enumerate() {
+
+        let mut level = level_rc.borrow_mut();
+        let budget = budgets.as_mut().map(|budgets| budgets.get_level_mut(level_idx));
+
+        if projectiles.queues.len() <= level_idx {
+            projectiles.queues.resize_with(level_idx + 1, WorkQueue::new);
+        }
+
+        // The queue is taken out while processing because ticking borrows the settings.
+        let mut queue = std::mem::take(&mut projectiles.queues[level_idx]);
+        for (entity, (base_entity, _)) in level.entities.ecs.query::<(&BaseEntity, &Projectile)>().iter() {
+            queue.push(entity, budget.as_ref().is_some_and(|budget| budget.is_priority(&base_entity.pos)));
+        }
+
+        queue.process(budget, |entity| {
+            projectiles.tick_projectile(level_rc, &mut level, events, collisions.as_deref(), entity);
+        });
+
+        projectiles.queues[level_idx] = queue;
+
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use mc_core::world::level::LevelEnv;
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::source::{ChunkLoadRequest, NullLevelSource};
+
+    use super::*;
+
+    mc_core::blocks!(TEST_BLOCKS "test" [
+        AIR "air",
+        STONE "stone"
+    ]);
+
+    mc_core::biomes!(TEST_BIOMES "test" [
+        VOID "void" 0,
+    ]);
+
+    mc_core::entities!(TEST_ENTITIES "test" [
+        PIG "pig" [],
+        ARROW "arrow" [],
+        SNOWBALL "snowball" []
+    ]);
+
+    static TAG_PASSABLE: TagType = TagType::new_logical("test:passable");
+
+    fn poll_hit(world: &mut World) -> Option<(EntityPos, ProjectileHit)> {
+        let res = world.event_tracker.poll_events::<ProjectileHitEvent>().next()
+            .map(|event| (event.pos.clone(), event.hit.clone()));
+        world.event_tracker.clear_events();
+        res
+    }
+
+    #[test]
+    fn projectile() {
+
+        let env = LevelEnv::builder()
+            .register_blocks(&TEST_BLOCKS)
+            .register_block_tag(&TAG_PASSABLE, [&AIR])
+            .register_biomes(&TEST_BIOMES)
+            .register_entities(&TEST_ENTITIES)
+            .build()
+            .unwrap();
+
+        let height = ChunkHeight::new(0, 3);
+        let mut level = Level::new("overworld".to_string(), env, height, NullLevelSource);
+
+        // A single chunk with a stone floor.
+        let mut chunk = ChunkLoadRequest { env: Arc::clone(level.get_env()), height, cx: 0, cz: 0 }.build_chunk();
+        for x in 0..16 {
+            for z in 0..16 {
+                chunk.set_block(x, 0, z, STONE.get_default_state()).unwrap();
+            }
+        }
+        level.chunks.insert_chunk(chunk);
+
+        let pig = level.spawn_entity(&PIG, EntityPos::new(8.0, 1.0, 12.5)).unwrap();
+        let arrow = level.spawn_entity(&ARROW, EntityPos::new(1.5, 3.0, 2.5)).unwrap();
+        let snowball = level.spawn_entity(&SNOWBALL, EntityPos::new(2.0, 1.5, 12.5)).unwrap();
+        level.entities.ecs.insert_one(arrow, Projectile::new(ProjectileKind::ARROW, EntityPos::new(0.5, 0.0, 0.0))).unwrap();
+        level.entities.ecs.insert_one(snowball, Projectile::new(ProjectileKind::SNOWBALL, EntityPos::new(2.0, 0.0, 0.0))).unwrap();
+
+        let mut world = World::new();
+        world.add_level(level);
+        register_projectiles(&mut world, Projectiles::new(&TAG_PASSABLE).with_stuck_lifetime(2));
+        world.insert_component(EntityCollisions::new()
+            .with_entity_type(&PIG, 0.9, 0.9, EntityCollision::Push));
+
+        // The snowball hits the pig on the third tick and is removed.
+        system_projectile(&mut world);
+        system_projectile(&mut world);
+        assert!(poll_hit(&mut world).is_none());
+        system_projectile(&mut world);
+        let (pos, hit) = poll_hit(&mut world).unwrap();
+        assert_eq!(hit, ProjectileHit::Entity(pig));
+        assert!((pos.x - (8.0 - 0.45 - ENTITY_HIT_MARGIN)).abs() < 1e-9);
+        assert!(world.levels[0].borrow().entities.ecs.get::<Projectile>(snowball).is_err());
+
+        // The arrow falls and sticks into the floor.
+        let mut block_hit = None;
+        for _ in 0..20 {
+            system_projectile(&mut world);
+            if let Some(hit) = poll_hit(&mut world) {
+                block_hit = Some(hit);
+                break;
+            }
+        }
+
+        let (pos, hit) = block_hit.unwrap();
+        assert!((pos.y - 1.0).abs() < 1e-9);
+        assert!(matches!(hit, ProjectileHit::Block(BlockPos { y: 0, .. }, Direction::Up)));
+        {
+            let level = world.levels[0].borrow();
+            let projectile = level.entities.ecs.get::<Projectile>(arrow).unwrap();
+            assert!(projectile.is_stuck());
+            assert_eq!(level.entities.ecs.get::<BaseEntity>(arrow).unwrap().pos, pos);
+        }
+
+        // The stuck arrow is removed after its lifetime.
+        system_projectile(&mut world);
+        assert!(world.levels[0].borrow().entities.ecs.get::<Projectile>(arrow).is_ok());
+        system_projectile(&mut world);
+        assert!(world.levels[0].borrow().entities.ecs.get::<Projectile>(arrow).is_err());
+        assert!(!world.levels[0].borrow().chunks.get_chunk(0, 0).unwrap().has_entity(arrow));
+
+    }
+
+}
@@ -0,0 +1,188 @@
+//! Passengers riding vehicles, passengers are moved to the position of their vehicle every
+//! tick. Passengers are raised above their vehicle by the riding offsets of both entity
+//! types, all passengers of a vehicle are placed at the same position.
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use mc_core::world::level::{BaseEntity, Passenger};
+use mc_core::entity::EntityType;
+use mc_core::util::OpaquePtr;
+use mc_core::pos::EntityPos;
+use hecs::Entity;
+
+use crate::event::EntityMovedEvent;
+use crate::world::World;
+
+
+/// World component storing the riding offsets of entity types, the offset of unregistered
+/// types is zero.
+pub struct Riding {
+    vehicle_offsets: HashMap<OpaquePtr<EntityType>, f64>,
+    passenger_offsets: HashMap<OpaquePtr<EntityType>, f64>
+}
+
+impl Riding {
+
+    pub fn new() -> Self {
+        Self {
+            vehicle_offsets: HashMap::new(),
+            passenger_offsets: HashMap::new()
+        }
+    }
+
+    /// Register the height of the seat of vehicles of an entity type, above their position.
+    pub fn with_vehicle_offset(mut self, entity_type: &'static EntityType, offset: f64) -> Self {
+        self.vehicle_offsets.insert(OpaquePtr::new(entity_type), offset);
+        self
+    }
+
+    /// Register the height added to the seat position for passengers of an entity type,
+    /// this is usually negative.
+    pub fn with_passenger_offset(mut self, entity_type: &'static EntityType, offset: f64) -> Self {
+        self.passenger_offsets.insert(OpaquePtr::new(entity_type), offset);
+        self
+    }
+
+    /// Return the height of a passenger above the position of its vehicle.
+    pub fn get_riding_offset(&self, vehicle_type: &'static EntityType, passenger_type: &'static EntityType) -> f64 {
+        self.vehicle_offsets.get(&OpaquePtr::new(vehicle_type)).copied().unwrap_or(0.0)
+            + self.passenger_offsets.get(&OpaquePtr::new(passenger_type)).copied().unwrap_or(0.0)
+    }
+
+}
+
+impl Default for Riding {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// Call this function with a mutable reference to a World to register the `Riding`
+/// component which is required to run the system `system_riding`.
+pub fn register_riding(world: &mut World, riding: Riding) {
+    world.insert_component(riding);
+}
+
+/// A system that moves passengers to their vehicle and triggers an `EntityMovedEvent` for
+/// each moved passenger. This system should run after vehicles are moved for the tick.
+pub fn system_riding(world: &mut World) {
+
+    let riding = match world.components.get::<Riding>() {
+        Ok(riding) => riding,
+        Err(_) => return
+    };
+
+    for level_rc in &world.levels {
+
+        let mut level = level_rc.borrow_mut();
+
+        // Vehicles are moved before their passengers when riding a stack of vehicles.
+        let mut passengers: Vec<(usize, Entity, Entity)> = level.entities.ecs.query::<&Passenger>()
+            .iter()
+            .map(|(entity, passenger)| {
+                let mut depth = 0;
+                let mut current = entity;
+                while let Some(vehicle) = level.get_vehicle(current) {
+                    depth += 1;
+                    current = vehicle;
+                }
+                (depth, entity, passenger.get_vehicle())
+            })
+            .collect();
+
+        passengers.sort_unstable_by_key(|&(depth, _, _)| depth);
+
+        for (_, entity, vehicle) in passengers {
+
+            let (from, to) = {
+                let ecs = &level.entities.ecs;
+                match (ecs.get::<BaseEntity>(entity), ecs.get::<BaseEntity>(vehicle)) {
+                    (Ok(base_entity), Ok(vehicle)) => {
+                        let offset = riding.get_riding_offset(vehicle.entity_type, base_entity.entity_type);
+                        let to = EntityPos::new(vehicle.pos.x, vehicle.pos.y + offset, vehicle.pos.z);
+                        (base_entity.pos.clone(), to)
+                    }
+                    _ => continue
+                }
+            };
+
+            if to != from {
+                level.move_entity(entity, to.clone());
+                world.event_tracker.push_event(EntityMovedEvent {
+                    level: Rc::clone(level_rc),
+                    entity,
+                    from,
+                    to,
+                    look: None,
+                    on_ground: false
+                });
+            }
+
+        }
+
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_core::world::level::{Level, LevelEnv};
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::source::NullLevelSource;
+
+    use super::*;
+
+    mc_core::blocks!(TEST_BLOCKS "test" [
+        AIR "air"
+    ]);
+
+    mc_core::biomes!(TEST_BIOMES "test" [
+        VOID "void" 0,
+    ]);
+
+    mc_core::entities!(TEST_ENTITIES "test" [
+        BOAT "boat" [],
+        PLAYER "player" []
+    ]);
+
+    #[test]
+    fn riding() {
+
+        let env = LevelEnv::builder()
+            .register_blocks(&TEST_BLOCKS)
+            .register_biomes(&TEST_BIOMES)
+            .register_entities(&TEST_ENTITIES)
+            .build()
+            .unwrap();
+
+        let mut level = Level::new("overworld".to_string(), env, ChunkHeight::new(0, 0), NullLevelSource);
+        let boat = level.spawn_entity(&BOAT, EntityPos::new(2.0, 1.0, 3.0)).unwrap();
+        let player = level.spawn_entity(&PLAYER, EntityPos::new(0.0, 0.0, 0.0)).unwrap();
+        let other_player = level.spawn_entity(&PLAYER, EntityPos::new(0.0, 0.0, 0.0)).unwrap();
+        assert!(level.mount_entity(player, boat));
+        assert!(level.mount_entity(other_player, player));
+
+        let mut world = World::new();
+        world.add_level(level);
+        register_riding(&mut world, Riding::new()
+            .with_vehicle_offset(&BOAT, -0.1)
+            .with_vehicle_offset(&PLAYER, 1.5)
+            .with_passenger_offset(&PLAYER, -0.35));
+
+        let get_pos = |world: &World, entity| {
+            world.levels[0].borrow().entities.ecs.get::<BaseEntity>(entity).unwrap().pos.clone()
+        };
+
+        // The whole stack is moved in a single tick.
+        system_riding(&mut world);
+        assert_eq!(get_pos(&world, player), EntityPos::new(2.0, 1.0 - 0.1 - 0.35, 3.0));
+        assert_eq!(get_pos(&world, other_player), EntityPos::new(2.0, 1.0 - 0.45 + 1.15, 3.0));
+        assert_eq!(world.event_tracker.poll_events::<EntityMovedEvent>().count(), 2);
+
+    }
+
+}
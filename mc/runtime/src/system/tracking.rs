@@ -0,0 +1,219 @@
+//! Interest management, entities with a `ViewTracker` component track the chunks and
+//! entities within their view distance. Changes are pushed every tick as `ViewChangedEvent`
+//! for network layers to load/unload chunks and spawn/destroy entities on clients.
+
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use mc_core::world::level::BaseEntity;
+use mc_core::pos::EntityPos;
+use hecs::Entity;
+
+use crate::event::ViewChangedEvent;
+use crate::world::World;
+
+
+/// An entity component tracking the chunks and entities seen by an entity, usually a player.
+/// Only loaded chunks are seen, chunks loaded later enter the view when loaded.
+#[derive(Debug)]
+pub struct ViewTracker {
+    /// The view distance in chunks around the entity's chunk.
+    view_distance: u8,
+    /// Chunks currently in view.
+    chunks: HashSet<(i32, i32)>,
+    /// Entities currently in view, the viewer itself is excluded.
+    entities: HashSet<Entity>
+}
+
+impl ViewTracker {
+
+    pub fn new(view_distance: u8) -> Self {
+        Self {
+            view_distance,
+            chunks: HashSet::new(),
+            entities: HashSet::new()
+        }
+    }
+
+    #[inline]
+    pub fn get_view_distance(&self) -> u8 {
+        self.view_distance
+    }
+
+    /// Change the view distance, the view is updated on the next tick.
+    #[inline]
+    pub fn set_view_distance(&mut self, view_distance: u8) {
+        self.view_distance = view_distance;
+    }
+
+    pub fn has_chunk(&self, cx: i32, cz: i32) -> bool {
+        self.chunks.contains(&(cx, cz))
+    }
+
+    pub fn has_entity(&self, entity: Entity) -> bool {
+        self.entities.contains(&entity)
+    }
+
+    pub fn iter_chunks(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        self.chunks.iter().copied()
+    }
+
+    pub fn iter_entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.entities.iter().copied()
+    }
+
+}
+
+
+#[inline]
+fn get_chunk_pos(pos: &EntityPos) -> (i32, i32) {
+    ((pos.x.floor() as i32) >> 4, (pos.z.floor() as i32) >> 4)
+}
+
+
+/// A system that updates every `ViewTracker` from the position of its entity and pushes a
+/// `ViewChangedEvent` for each tracker with chunks or entities entering or leaving its view.
+pub fn system_view_tracking(world: &mut World) {
+
+    let event_tracker = &mut world.event_tracker;
+
+    for level_rc in &world.levels {
+
+        let mut level = level_rc.borrow_mut();
+        let level = &mut *level;
+
+        let entities: Vec<(Entity, (i32, i32))> = level.entities.ecs.query::<&BaseEntity>()
+            .iter()
+            .map(|(entity, base_entity)| (entity, get_chunk_pos(&base_entity.pos)))
+            .collect();
+
+        for (viewer, (base_entity, tracker)) in level.entities.ecs.query_mut::<(&BaseEntity, &mut ViewTracker)>() {
+
+            let (cx, cz) = get_chunk_pos(&base_entity.pos);
+            let distance = tracker.view_distance as i32;
+
+            let mut chunks = HashSet::new();
+            for rcx in (cx - distance)..=(cx + distance) {
+                for rcz in (cz - distance)..=(cz + distance) {
+                    if level.chunks.is_chunk_loaded(rcx, rcz) {
+                        chunks.insert((rcx, rcz));
+                    }
+                }
+            }
+
+            let entities: HashSet<Entity> = entities.iter()
+                .filter(|(entity, chunk_pos)| *entity != viewer && chunks.contains(chunk_pos))
+                .map(|&(entity, _)| entity)
+                .collect();
+
+            let mut entered_chunks: Vec<(i32, i32)> = chunks.difference(&tracker.chunks).copied().collect();
+            entered_chunks.sort_by_key(|&(rcx, rcz)| (rcx - cx).pow(2) + (rcz - cz).pow(2));
+            let left_chunks: Vec<(i32, i32)> = tracker.chunks.difference(&chunks).copied().collect();
+            let entered_entities: Vec<Entity> = entities.difference(&tracker.entities).copied().collect();
+            let left_entities: Vec<Entity> = tracker.entities.difference(&entities).copied().collect();
+
+            tracker.chunks = chunks;
+            tracker.entities = entities;
+
+            if !entered_chunks.is_empty() || !left_chunks.is_empty() || !entered_entities.is_empty() || !left_entities.is_empty() {
+                event_tracker.push_event(ViewChangedEvent {
+                    level: Rc::clone(level_rc),
+                    entity: viewer,
+                    entered_chunks,
+                    left_chunks,
+                    entered_entities,
+                    left_entities
+                });
+            }
+
+        }
+
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_core::world::level::{Level, LevelEnv};
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::source::{ChunkLoadRequest, NullLevelSource};
+
+    use super::*;
+
+    mc_core::blocks!(TEST_BLOCKS "test" [
+        AIR "air"
+    ]);
+
+    mc_core::biomes!(TEST_BIOMES "test" [
+        VOID "void" 0,
+    ]);
+
+    mc_core::entities!(TEST_ENTITIES "test" [
+        PLAYER "player" []
+    ]);
+
+    fn poll_view_event(world: &mut World) -> Option<(usize, usize, usize, usize)> {
+        let res = world.event_tracker.poll_events::<ViewChangedEvent>().next().map(|event| {
+            (event.entered_chunks.len(), event.left_chunks.len(), event.entered_entities.len(), event.left_entities.len())
+        });
+        world.event_tracker.clear_events();
+        res
+    }
+
+    #[test]
+    fn view_tracking() {
+
+        let env = LevelEnv::builder()
+            .register_blocks(&TEST_BLOCKS)
+            .register_biomes(&TEST_BIOMES)
+            .register_entities(&TEST_ENTITIES)
+            .build()
+            .unwrap();
+
+        let height = ChunkHeight::new(0, 0);
+        let mut level = Level::new("overworld".to_string(), env, height, NullLevelSource);
+
+        for cx in -1..=4 {
+            for cz in -1..=1 {
+                let chunk = ChunkLoadRequest { env: level.get_env().clone(), height, cx, cz }.build_chunk();
+                level.chunks.insert_chunk(chunk);
+            }
+        }
+
+        let viewer = level.spawn_entity(&PLAYER, EntityPos::new(0.0, 0.0, 0.0)).unwrap();
+        let other = level.spawn_entity(&PLAYER, EntityPos::new(20.0, 0.0, 0.0)).unwrap();
+        level.entities.ecs.insert_one(viewer, ViewTracker::new(1)).unwrap();
+
+        let mut world = World::new();
+        world.add_level(level);
+
+        system_view_tracking(&mut world);
+        assert_eq!(poll_view_event(&mut world), Some((9, 0, 1, 0)));
+
+        // Nothing changed.
+        system_view_tracking(&mut world);
+        assert_eq!(poll_view_event(&mut world), None);
+
+        // Moving four chunks away, the other entity leaves the view.
+        world.levels[0].borrow_mut().move_entity(viewer, EntityPos::new(70.0, 0.0, 0.0));
+        system_view_tracking(&mut world);
+        assert_eq!(poll_view_event(&mut world), Some((6, 9, 0, 1)));
+
+        {
+            let level = world.levels[0].borrow();
+            let tracker = level.entities.ecs.get::<ViewTracker>(viewer).unwrap();
+            // Chunks at X 5 are not loaded.
+            assert_eq!(tracker.iter_chunks().count(), 6);
+            assert!(tracker.has_chunk(4, 1));
+            assert!(!tracker.has_entity(other));
+        }
+
+        world.levels[0].borrow_mut().move_entity(other, EntityPos::new(60.0, 0.0, 0.0));
+        system_view_tracking(&mut world);
+        assert_eq!(poll_view_event(&mut world), Some((0, 0, 1, 0)));
+
+    }
+
+}
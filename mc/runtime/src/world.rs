@@ -1,8 +1,11 @@
 use std::cell::{Ref, RefMut, RefCell};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::any::Any;
 use std::rc::Rc;
 
 use mc_core::world::level::Level;
+use mc_core::world::source::LevelSourceError;
 
 use crate::util::{Components, ComponentError, SystemExecutor, EventTracker, tick_loop};
 
@@ -10,12 +13,38 @@ use crate::util::{Components, ComponentError, SystemExecutor, EventTracker, tick
 /// Type alias for a `SystemExecutor` that take a `World` context.
 pub type WorldSystemExecutor = SystemExecutor<World>;
 
+/// Type alias for functions called by `World::save_all`.
+pub type SaveHandler = fn(&mut World);
+
+
+/// A handle that can be shared with other threads to request the shutdown of a running
+/// `WorldContext`, the world is saved before returning from the tick loop.
+#[derive(Debug, Clone, Default)]
+pub struct ShutdownHandle(Arc<AtomicBool>);
+
+impl ShutdownHandle {
+
+    #[inline]
+    pub fn request(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn is_requested(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+}
+
+
 /// The world executor, containing a world with its executor.
 pub struct WorldContext {
     /// The executor of the internal world.
     pub executor: WorldSystemExecutor,
     /// The internal world run by the executor.
-    pub world: World
+    pub world: World,
+    /// Shutdown handle checked on each tick.
+    shutdown: ShutdownHandle
 }
 
 impl WorldContext {
@@ -23,10 +52,24 @@ impl WorldContext {
     pub fn new() -> Self {
         Self {
             executor: SystemExecutor::new(),
-            world: World::new()
+            world: World::new(),
+            shutdown: ShutdownHandle::default()
         }
     }
 
+    /// Return a handle to request the shutdown of this context from another thread.
+    pub fn get_shutdown_handle(&self) -> ShutdownHandle {
+        self.shutdown.clone()
+    }
+
+    /// Request the shutdown of this context when the process receives Ctrl-C or SIGTERM.
+    /// This can only be called once per process.
+    #[cfg(feature = "signal")]
+    pub fn shutdown_on_signal(&self) -> Result<(), ctrlc::Error> {
+        let shutdown = self.get_shutdown_handle();
+        ctrlc::set_handler(move || shutdown.request())
+    }
+
     /// Call the given function with a mutable reference to the world
     /// and the executor as parameters.
     pub fn register<F>(&mut self, func: F)
@@ -36,15 +79,22 @@ impl WorldContext {
         (func)(&mut self.world, &mut self.executor);
     }
 
+    /// Run the world until it is stopped, either by setting `World::running` to false or by
+    /// requesting a shutdown, the world is then saved with `World::save_all`.
     pub fn run_simple(&mut self) {
         let executor = &mut self.executor;
         let world = &mut self.world;
+        let shutdown = &self.shutdown;
         world.running = true;
-        tick_loop(move |_info| {
+        tick_loop(|_info| {
             executor.tick(world);
             world.event_tracker.clear_events();
-            world.running
+            world.running && !shutdown.is_requested()
         }, 20.0);
+        world.running = false;
+        if let Err(err) = world.save_all() {
+            println!("Failed to save the world: {}", err);
+        }
     }
 
 }
@@ -59,7 +109,9 @@ pub struct World {
     /// Internal event tracker for the world.
     pub event_tracker: EventTracker,
     /// World's levels.
-    pub levels: Vec<Rc<RefCell<Level>>>
+    pub levels: Vec<Rc<RefCell<Level>>>,
+    /// Functions called before saving levels.
+    save_handlers: Vec<SaveHandler>
 }
 
 impl World {
@@ -69,7 +121,8 @@ impl World {
             running: false,
             components: Components::new(),
             event_tracker: EventTracker::new(),
-            levels: Vec::new()
+            levels: Vec::new(),
+            save_handlers: Vec::new()
         }
     }
 
@@ -79,6 +132,42 @@ impl World {
         self.levels.push(Rc::new(RefCell::new(level)));
     }
 
+    // Saving
+
+    /// Add a function called by `save_all` before saving levels, it can be used to save data
+    /// that are not stored in chunks, like players or level data.
+    pub fn add_save_handler(&mut self, handler: SaveHandler) {
+        self.save_handlers.push(handler);
+    }
+
+    /// Save the whole world, save handlers are called first and then chunks modified since
+    /// their last save are saved for each level. This method blocks until level sources have
+    /// written all chunks, the total number of saved chunks is returned. If a level source
+    /// fails, the remaining levels are still saved and the first error is returned.
+    pub fn save_all(&mut self) -> Result<usize, LevelSourceError> {
+
+        for handler in self.save_handlers.clone() {
+            handler(self);
+        }
+
+        let mut count = 0;
+        let mut error = None;
+        for level in &self.levels {
+            match level.borrow_mut().save_all() {
+                Ok(level_count) => count += level_count,
+                Err(err) => {
+                    error.get_or_insert(err);
+                }
+            }
+        }
+
+        match error {
+            Some(err) => Err(err),
+            None => Ok(count)
+        }
+
+    }
+
     // Components
 
     #[inline]
@@ -102,3 +191,67 @@ impl World {
     }
 
 }
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_core::world::level::LevelEnv;
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::source::{ChunkLoadRequest, MemoryLevelSource};
+
+    use super::*;
+
+    mc_core::blocks!(TEST_BLOCKS "test" [
+        AIR "air",
+        STONE "stone"
+    ]);
+
+    mc_core::biomes!(TEST_BIOMES "test" [
+        VOID "void" 0,
+    ]);
+
+    /// Component counting calls to the save handler.
+    struct SaveCount(u32);
+
+    fn save_handler(world: &mut World) {
+        world.get_component_mut::<SaveCount>().unwrap().0 += 1;
+    }
+
+    #[test]
+    fn save_all() {
+
+        let env = LevelEnv::builder()
+            .register_blocks(&TEST_BLOCKS)
+            .register_biomes(&TEST_BIOMES)
+            .build()
+            .unwrap();
+
+        let height = ChunkHeight::new(0, 0);
+        let level = Level::new("overworld".to_string(), env, height, MemoryLevelSource::new());
+        for cx in 0..4 {
+            let chunk = ChunkLoadRequest { env: level.get_env().clone(), height, cx, cz: 0 }.build_chunk();
+            level.chunks.insert_chunk(chunk);
+        }
+
+        let mut ctx = WorldContext::new();
+        ctx.world.add_level(level);
+        ctx.world.insert_component(SaveCount(0));
+        ctx.world.add_save_handler(save_handler);
+
+        assert_eq!(ctx.world.save_all().unwrap(), 4);
+        assert_eq!(ctx.world.save_all().unwrap(), 0);
+        assert_eq!(ctx.world.get_component::<SaveCount>().unwrap().0, 2);
+
+        ctx.world.levels[0].borrow().chunks.set_block_at(16, 0, 0, STONE.get_default_state()).unwrap();
+        assert_eq!(ctx.world.save_all().unwrap(), 1);
+
+        // A requested shutdown stops the tick loop after the first tick and saves the world.
+        ctx.get_shutdown_handle().request();
+        ctx.run_simple();
+        assert!(!ctx.world.running);
+        assert_eq!(ctx.world.get_component::<SaveCount>().unwrap().0, 4);
+
+    }
+
+}
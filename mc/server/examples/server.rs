@@ -4,8 +4,9 @@ use std::net::SocketAddr;
 use mc_server::packet::{PacketServer, Event, RawPacket};
 use mc_server::packet::serial::*;
 
-use mc_server::protocol::{ClientState, Packet};
+use mc_server::protocol::{ClientState, ReadablePacket};
 use mc_server::protocol::handshake::*;
+use mc_server::protocol::version::ProtocolVersion;
 
 use mc_core::pos::BlockPos;
 
@@ -49,16 +50,16 @@ fn main() {
                     profile: None
                 });
             }
-            Event::Packet(mut packet) => {
+            Event::Packet(packet) => {
 
                 if let Some(client) = clients.get_mut(&packet.addr) {
 
-                    println!("[{}] [{:?}] Packet#{} {:02X?}", packet.addr, client.state, packet.id, &packet.data.get_ref()[..]);
+                    println!("[{}] [{:?}] Packet#{} {:02X?}", packet.addr, client.state, packet.id, &packet.data[..]);
 
                     match (client.state, packet.id) {
                         (Handshake, 0x00) => {
 
-                            let handshake = HandshakePacket::decode_raw(&mut packet).unwrap();
+                            let handshake = HandshakePacket::read_packet(packet.get_cursor(), ProtocolVersion::V1_16_5).unwrap();
 
                             println!(" => protocol version: {}, addr: '{}', port: {}, next state: {:?}",
                                      handshake.protocol_version,
@@ -75,7 +76,7 @@ fn main() {
                         }
                         (Status, 0x00) => {
 
-                            let mut res_packet = packet.response(0x00);
+                            let mut res_packet = RawPacket::blank(packet.addr, 0x00);
                             let json_payload = json!({
                                         "version": {
                                             "name": "1.16.5",
@@ -97,14 +98,14 @@ fn main() {
 
                         }
                         (Status, 0x01) => {
-                            let token = packet.data.read_i64().unwrap();
-                            let mut res_packet = packet.response(0x01);
+                            let token = packet.get_cursor().read_i64().unwrap();
+                            let mut res_packet = RawPacket::blank(packet.addr, 0x01);
                             res_packet.data.write_i64(token).unwrap();
                             server.send(res_packet);
                         }
                         (Login, 0x00) => {
 
-                            let username = packet.data.read_string().unwrap();
+                            let username = packet.get_cursor().read_string().unwrap();
                             if username.chars().count() > 16 {
                                 println!(" => invalid username length");
                             } else {
@@ -115,7 +116,7 @@ fn main() {
                                 let eid = entity_id;
                                 entity_id += 1;
 
-                                let mut login_success_packet = packet.response(0x02);
+                                let mut login_success_packet = RawPacket::blank(packet.addr, 0x02);
                                 login_success_packet.data.write_uuid(&uuid).unwrap();
                                 login_success_packet.data.write_string(username.as_str()).unwrap();
                                 client.profile = Some(ClientProfile {
@@ -194,7 +195,7 @@ fn main() {
                                     biome_reg
                                 });
 
-                                let mut join_packet = packet.response(0x24);
+                                let mut join_packet = RawPacket::blank(packet.addr, 0x24);
                                 join_packet.data.write_i32(eid);
                                 join_packet.data.write_bool(false);
                                 join_packet.data.write_i8(1);
@@ -217,17 +218,17 @@ fn main() {
                                 join_packet.data.write_bool(false);
                                 server.send(join_packet);
 
-                                let mut spawn_packet = packet.response(0x42);
+                                let mut spawn_packet = RawPacket::blank(packet.addr, 0x42);
                                 spawn_packet.data.write_block_pos(&BlockPos::new(0, 0, 0));
                                 server.send(spawn_packet);
 
-                                let mut abilities_packet = packet.response(0x30);
+                                let mut abilities_packet = RawPacket::blank(packet.addr, 0x30);
                                 abilities_packet.data.write_u8(1 | 2 | 4 | 8);
                                 abilities_packet.data.write_f32(0.05);
                                 abilities_packet.data.write_f32(0.1);
                                 server.send(abilities_packet);
 
-                                let mut pos_packet = packet.response(0x34);
+                                let mut pos_packet = RawPacket::blank(packet.addr, 0x34);
                                 pos_packet.data.write_f64(0.0);
                                 pos_packet.data.write_f64(0.0);
                                 pos_packet.data.write_f64(0.0);
@@ -1,17 +1,17 @@
 use mc_runtime::world::WorldContext;
-use mc_server::packet::PacketServer;
+use mc_server::config::{ServerProperties, PROPERTIES_FILE};
 
 use mc_core::world::source::{WorkerGenLevelSource, SuperFlatGenerator};
 use mc_core::world::level::{Level, LevelEnv};
 use mc_core::world::chunk::ChunkHeight;
 
-use mc_vanilla::ext::WithVanilla;
+use mc_vanilla::ext::VanillaLevelEnv;
 use mc_vanilla::block::*;
-use std::sync::Arc;
 
 fn main() {
 
-    let server = PacketServer::bind("0.0.0.0", 25565).unwrap();
+    let properties = ServerProperties::load_or_create(PROPERTIES_FILE).unwrap();
+    let server = properties.bind_server().unwrap();
 
     let mut super_flat = SuperFlatGenerator::new();
     super_flat.add_layer(BEDROCK.get_default_state(), 0, 1);
@@ -20,19 +20,17 @@ fn main() {
 
     let super_flat_source = WorkerGenLevelSource::new(super_flat, 1);
 
-    let env = Arc::new(LevelEnv::with_vanilla());
+    let env = LevelEnv::vanilla();
     let mut level = Level::new("minecraft:overworld".to_string(), env, ChunkHeight::new(0, 15), super_flat_source);
 
-    for cx in -2..2 {
-        for cz in -2..2 {
-            level.request_chunk_load(cx, cz);
-        }
-    }
+    level.tickets.set_spawn(0, 0, 2);
 
     let mut ctx = WorldContext::new();
     ctx.world.add_level(level);
     ctx.world.insert_component(server);
+    ctx.world.insert_component(properties);
     ctx.register(mc_server::system::register_systems);
+    ctx.shutdown_on_signal().expect("failed to register the shutdown signal handler");
     ctx.run_simple();
 
 }
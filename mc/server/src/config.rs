@@ -0,0 +1,365 @@
+//! Server configuration, read from and written to a `server.properties` file in the same
+//! format as vanilla servers. Properties that are not typed are kept and written back, so
+//! a vanilla file can be used as is.
+
+use std::path::Path;
+use std::{fs, io};
+
+use mc_vanilla::util::{GameMode, Difficulty};
+
+use crate::packet::PacketServer;
+
+
+pub const PROPERTIES_FILE: &str = "server.properties";
+
+
+/// The typed properties of a `server.properties` file, missing or invalid values are set to
+/// the vanilla defaults.
+#[derive(Debug, Clone)]
+pub struct ServerProperties {
+    /// The address to bind, empty to bind all addresses.
+    pub server_ip: String,
+    pub server_port: u16,
+    pub motd: String,
+    pub max_players: u32,
+    pub view_distance: u8,
+    pub online_mode: bool,
+    pub white_list: bool,
+    pub gamemode: GameMode,
+    pub difficulty: Difficulty,
+    pub hardcore: bool,
+    pub pvp: bool,
+    pub level_name: String,
+    /// The seed of the level, see `get_seed`.
+    pub level_seed: String,
+    pub level_type: String,
+    pub generate_structures: bool,
+    pub spawn_protection: u32,
+    /// Properties that are not typed, with their raw values.
+    others: Vec<(String, String)>
+}
+
+impl ServerProperties {
+
+    pub fn new() -> Self {
+        Self {
+            server_ip: String::new(),
+            server_port: 25565,
+            motd: "A Minecraft Server".to_string(),
+            max_players: 20,
+            view_distance: 10,
+            online_mode: true,
+            white_list: false,
+            gamemode: GameMode::Survival,
+            difficulty: Difficulty::Easy,
+            hardcore: false,
+            pvp: true,
+            level_name: "world".to_string(),
+            level_seed: String::new(),
+            level_type: "default".to_string(),
+            generate_structures: true,
+            spawn_protection: 16,
+            others: Vec::new()
+        }
+    }
+
+    /// Parse the content of a properties file.
+    pub fn parse(data: &str) -> Self {
+
+        let mut properties = Self::new();
+
+        for (key, value) in parse_properties(data) {
+            let value = value.as_str();
+            match key.as_str() {
+                "server-ip" => properties.server_ip = value.to_string(),
+                "server-port" => parse_into(value, &mut properties.server_port),
+                "motd" => properties.motd = value.to_string(),
+                "max-players" => parse_into(value, &mut properties.max_players),
+                "view-distance" => parse_into(value, &mut properties.view_distance),
+                "online-mode" => parse_into(value, &mut properties.online_mode),
+                "white-list" => parse_into(value, &mut properties.white_list),
+                // Numeric IDs are still accepted by vanilla.
+                "gamemode" => if let Some(gamemode) = GameMode::from_name(value)
+                    .or_else(|| value.parse().ok().filter(|&id| id <= 3).map(GameMode::from_id)) {
+                    properties.gamemode = gamemode;
+                }
+                "difficulty" => if let Some(difficulty) = Difficulty::from_name(value)
+                    .or_else(|| value.parse().ok().filter(|&id| id <= 3).map(Difficulty::from_id)) {
+                    properties.difficulty = difficulty;
+                }
+                "hardcore" => parse_into(value, &mut properties.hardcore),
+                "pvp" => parse_into(value, &mut properties.pvp),
+                "level-name" => properties.level_name = value.to_string(),
+                "level-seed" => properties.level_seed = value.to_string(),
+                "level-type" => properties.level_type = value.to_string(),
+                "generate-structures" => parse_into(value, &mut properties.generate_structures),
+                "spawn-protection" => parse_into(value, &mut properties.spawn_protection),
+                _ => properties.set(key, value.to_string())
+            }
+        }
+
+        properties
+
+    }
+
+    /// Load the properties from a file.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self::parse(&fs::read_to_string(path)?))
+    }
+
+    /// Load the properties from a file and write it back, this creates the file with the
+    /// default properties if it doesn't exist and adds missing properties, like vanilla.
+    pub fn load_or_create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let properties = match fs::read_to_string(path.as_ref()) {
+            Ok(data) => Self::parse(&data),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Self::new(),
+            Err(e) => return Err(e)
+        };
+        properties.save(path)?;
+        Ok(properties)
+    }
+
+    /// Write the properties to a file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.write())
+    }
+
+    /// Write the content of a properties file, properties are sorted by key.
+    pub fn write(&self) -> String {
+
+        let mut entries = vec![
+            ("server-ip".to_string(), self.server_ip.clone()),
+            ("server-port".to_string(), self.server_port.to_string()),
+            ("motd".to_string(), self.motd.clone()),
+            ("max-players".to_string(), self.max_players.to_string()),
+            ("view-distance".to_string(), self.view_distance.to_string()),
+            ("online-mode".to_string(), self.online_mode.to_string()),
+            ("white-list".to_string(), self.white_list.to_string()),
+            ("gamemode".to_string(), self.gamemode.get_name().to_string()),
+            ("difficulty".to_string(), self.difficulty.get_name().to_string()),
+            ("hardcore".to_string(), self.hardcore.to_string()),
+            ("pvp".to_string(), self.pvp.to_string()),
+            ("level-name".to_string(), self.level_name.clone()),
+            ("level-seed".to_string(), self.level_seed.clone()),
+            ("level-type".to_string(), self.level_type.clone()),
+            ("generate-structures".to_string(), self.generate_structures.to_string()),
+            ("spawn-protection".to_string(), self.spawn_protection.to_string())
+        ];
+
+        entries.extend(self.others.iter().cloned());
+        entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+
+        let mut data = String::from("#Minecraft server properties\n");
+        for (key, value) in entries {
+            escape_into(&key, true, &mut data);
+            data.push('=');
+            escape_into(&value, false, &mut data);
+            data.push('\n');
+        }
+        data
+
+    }
+
+    /// Get the raw value of a property that is not typed.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.others.iter()
+            .find(|(other, _)| other == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Set the raw value of a property that is not typed.
+    pub fn set(&mut self, key: String, value: String) {
+        match self.others.iter_mut().find(|(other, _)| *other == key) {
+            Some((_, old)) => *old = value,
+            None => self.others.push((key, value))
+        }
+    }
+
+    /// Return the seed of the level, none if it should be random. Like vanilla, seeds that
+    /// are not numbers are hashed.
+    pub fn get_seed(&self) -> Option<i64> {
+        if self.level_seed.is_empty() {
+            None
+        } else if let Ok(seed) = self.level_seed.parse() {
+            Some(seed)
+        } else {
+            // Java's String::hashCode
+            Some(self.level_seed.encode_utf16()
+                .fold(0i32, |hash, c| hash.wrapping_mul(31).wrapping_add(c as i32)) as i64)
+        }
+    }
+
+    /// Bind a packet server to the configured address and port.
+    pub fn bind_server(&self) -> io::Result<PacketServer> {
+        let ip = if self.server_ip.is_empty() { "0.0.0.0" } else { self.server_ip.as_str() };
+        PacketServer::bind(ip, self.server_port)
+    }
+
+}
+
+impl Default for ServerProperties {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// Internal function to parse a property value, keeping the current one if invalid.
+fn parse_into<T: std::str::FromStr>(value: &str, dst: &mut T) {
+    if let Ok(value) = value.trim().parse() {
+        *dst = value;
+    }
+}
+
+
+/// Parse the key/value pairs of a Java properties file. Comments start with `#` or `!`, keys
+/// are separated from values by `=`, `:` or whitespaces and lines ending with a backslash
+/// continue on the next line.
+fn parse_properties(data: &str) -> Vec<(String, String)> {
+
+    let mut pairs = Vec::new();
+    let mut lines = data.lines();
+
+    while let Some(line) = lines.next() {
+
+        let line = line.trim_start();
+        if line.is_empty() || line.starts_with('#') || line.starts_with('!') {
+            continue;
+        }
+
+        // Join continued lines, leading whitespaces of continuation lines are ignored.
+        let mut logical = line.to_string();
+        while ends_with_escape(&logical) {
+            logical.pop();
+            match lines.next() {
+                Some(next) => logical.push_str(next.trim_start()),
+                None => break
+            }
+        }
+
+        let mut chars = logical.chars().peekable();
+        let mut key = String::new();
+        while let Some(&c) = chars.peek() {
+            if c == '=' || c == ':' || c.is_whitespace() {
+                break;
+            }
+            chars.next();
+            unescape_char(c, &mut chars, &mut key);
+        }
+
+        // Skip whitespaces around a single separator.
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.next_if(|&c| c == '=' || c == ':').is_some() {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        }
+
+        let mut value = String::new();
+        while let Some(c) = chars.next() {
+            unescape_char(c, &mut chars, &mut value);
+        }
+
+        pairs.push((key, value));
+
+    }
+
+    pairs
+
+}
+
+/// Return true if the line ends with an odd number of backslashes.
+fn ends_with_escape(line: &str) -> bool {
+    line.chars().rev().take_while(|&c| c == '\\').count() % 2 == 1
+}
+
+/// Push a character to the destination, decoding it if it's an escape sequence.
+fn unescape_char<I: Iterator<Item = char>>(c: char, chars: &mut I, dst: &mut String) {
+    if c != '\\' {
+        dst.push(c);
+        return;
+    }
+    match chars.next() {
+        Some('t') => dst.push('\t'),
+        Some('n') => dst.push('\n'),
+        Some('r') => dst.push('\r'),
+        Some('f') => dst.push('\u{c}'),
+        Some('u') => {
+            let hex: String = chars.take(4).collect();
+            if let Some(c) = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                dst.push(c);
+            }
+        }
+        Some(c) => dst.push(c),
+        None => {}
+    }
+}
+
+/// Escape a key or a value like Java's `Properties::store`, all spaces are escaped in keys
+/// but only the leading one in values. Characters out of ASCII are written as unicode
+/// escapes.
+fn escape_into(s: &str, key: bool, dst: &mut String) {
+    for (i, c) in s.chars().enumerate() {
+        match c {
+            ' ' if key || i == 0 => dst.push_str("\\ "),
+            '\\' => dst.push_str("\\\\"),
+            '\t' => dst.push_str("\\t"),
+            '\n' => dst.push_str("\\n"),
+            '\r' => dst.push_str("\\r"),
+            '\u{c}' => dst.push_str("\\f"),
+            '=' | ':' | '#' | '!' => {
+                dst.push('\\');
+                dst.push(c);
+            }
+            ' '..='~' => dst.push(c),
+            _ => {
+                let mut buf = [0u16; 2];
+                for unit in c.encode_utf16(&mut buf) {
+                    dst.push_str(&format!("\\u{:04X}", unit));
+                }
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn server_properties() {
+
+        let data = "#Minecraft server properties\n\
+            #Tue Jul 06 12:30:00 CEST 2021\n\
+            server-port=25566\n\
+            motd=A Minecraft Server \\u00E9\\:)\n\
+            view-distance = 12\n\
+            max-players=invalid\n\
+            gamemode=1\n\
+            difficulty=hard\n\
+            level-seed=hello\n\
+            resource-pack=https\\://example.com/\\\n    pack.zip\n\
+            enable-rcon=false\n";
+
+        let properties = ServerProperties::parse(data);
+        assert_eq!(properties.server_port, 25566);
+        assert_eq!(properties.motd, "A Minecraft Server é:)");
+        assert_eq!(properties.view_distance, 12);
+        assert_eq!(properties.max_players, 20);
+        assert_eq!(properties.gamemode, GameMode::Creative);
+        assert_eq!(properties.difficulty, Difficulty::Hard);
+        assert_eq!(properties.get_seed(), Some(99162322));
+        assert_eq!(properties.get("resource-pack"), Some("https://example.com/pack.zip"));
+        assert_eq!(properties.get("enable-rcon"), Some("false"));
+
+        let written = properties.write();
+        assert!(written.contains("\nmotd=A Minecraft Server \\u00E9\\:)\n"));
+        assert!(written.contains("\ngamemode=creative\n"));
+
+        let reparsed = ServerProperties::parse(&written);
+        assert_eq!(reparsed.motd, properties.motd);
+        assert_eq!(reparsed.write(), written);
+
+    }
+
+}
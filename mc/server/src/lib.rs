@@ -1,4 +1,5 @@
 pub mod packet;
 pub mod protocol;
+pub mod config;
 pub mod system;
 pub mod util;
@@ -42,6 +42,7 @@ pub trait PacketReadExt {
     fn read_string(&mut self) -> IoResult<String>;
     fn read_block_pos(&mut self) -> IoResult<BlockPos>;
     fn read_nbt(&mut self) -> IoResult<CompoundTag>;
+    fn read_bit_set(&mut self) -> IoResult<Vec<u64>>;
 
 }
 
@@ -149,6 +150,15 @@ where
         })
     }
 
+    fn read_bit_set(&mut self) -> IoResult<Vec<u64>> {
+        let len = self.read_var_int()? as usize;
+        let mut bits = Vec::with_capacity(len);
+        for _ in 0..len {
+            bits.push(PacketReadExt::read_i64(self)? as u64);
+        }
+        Ok(bits)
+    }
+
 }
 
 
@@ -172,6 +182,7 @@ pub trait PacketWriteExt {
     fn write_string(&mut self, s: &str) -> IoResult<()>;
     fn write_block_pos(&mut self, pos: &BlockPos) -> IoResult<()>;
     fn write_nbt(&mut self, nbt: &CompoundTag) -> IoResult<()>;
+    fn write_bit_set(&mut self, bits: &[u64]) -> IoResult<()>;
 
 }
 
@@ -262,4 +273,14 @@ where
         nbt::encode::write_compound_tag(self, nbt)
     }
 
+    fn write_bit_set(&mut self, bits: &[u64]) -> IoResult<()> {
+        // Trailing zero longs are not required.
+        let len = bits.iter().rposition(|&l| l != 0).map(|i| i + 1).unwrap_or(0);
+        self.write_var_int(len as i32)?;
+        for &l in &bits[..len] {
+            PacketWriteExt::write_i64(self, l as i64)?;
+        }
+        Ok(())
+    }
+
 }
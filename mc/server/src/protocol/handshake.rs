@@ -1,4 +1,5 @@
 use super::{ReadablePacket, ClientState, PacketResult};
+use super::version::ProtocolVersion;
 use crate::packet::serial::*;
 use std::io::Cursor;
 
@@ -11,8 +12,17 @@ pub struct HandshakePacket {
     pub next_state: ClientState
 }
 
+impl HandshakePacket {
+
+    /// Return the protocol version of the client, or none if not supported by the server.
+    pub fn get_version(&self) -> Option<ProtocolVersion> {
+        ProtocolVersion::from_id(self.protocol_version as i32)
+    }
+
+}
+
 impl ReadablePacket for HandshakePacket {
-    fn read_packet(mut src: Cursor<&Vec<u8>>) -> PacketResult<Self> {
+    fn read_packet(mut src: Cursor<&Vec<u8>>, _version: ProtocolVersion) -> PacketResult<Self> {
         Ok(Self {
             protocol_version: src.read_var_int()? as u16,
             server_addr: src.read_string()?,
@@ -1,4 +1,5 @@
 use super::{ReadablePacket, WritablePacket, PacketResult, PacketError};
+use super::version::ProtocolVersion;
 use crate::packet::serial::*;
 
 use std::io::Cursor;
@@ -11,7 +12,7 @@ pub struct LoginStartPacket {
 }
 
 impl ReadablePacket for LoginStartPacket {
-    fn read_packet(mut src: Cursor<&Vec<u8>>) -> PacketResult<Self> {
+    fn read_packet(mut src: Cursor<&Vec<u8>>, _version: ProtocolVersion) -> PacketResult<Self> {
         Ok(Self {
             username: {
                 let s = src.read_string()?;
@@ -33,9 +34,23 @@ pub struct LoginSuccessPacket {
 }
 
 impl WritablePacket for LoginSuccessPacket {
-    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>) -> PacketResult<()> {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, _version: ProtocolVersion) -> PacketResult<()> {
         dst.write_uuid(&self.uuid)?;
         dst.write_string(self.username.as_str())?;
         Ok(())
     }
 }
+
+
+/// Sent instead of `LoginSuccessPacket` when the player is not allowed to join.
+#[derive(Debug)]
+pub struct LoginDisconnectPacket {
+    pub reason: String
+}
+
+impl WritablePacket for LoginDisconnectPacket {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, _version: ProtocolVersion) -> PacketResult<()> {
+        dst.write_string(serde_json::json!({ "text": self.reason }).to_string().as_str())?;
+        Ok(())
+    }
+}
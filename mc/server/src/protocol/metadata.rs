@@ -0,0 +1,388 @@
+//! Entity metadata (also known as data watcher) serialization, metadata is built from the
+//! vanilla ECS components of an entity, each entry being identified by its index in the
+//! entity type's hierarchy.
+//!
+//! Source: https://wiki.vg/index.php?title=Entity_metadata&oldid=16539
+
+use std::io::{Result as IoResult, Write};
+
+use mc_core::pos::{BlockPos, Direction};
+use mc_vanilla::entity::{
+    VanillaEntity, NamedEntity, LivingEntity, MobEntity, PlayerEntity, BreedableEntity,
+    CreeperEntity, SlimeEntity, PigEntity, SheepEntity
+};
+use mc_vanilla::entity::decoration::{ItemFrameEntity, ArmorStandEntity};
+
+use super::version::ProtocolVersion;
+use crate::packet::serial::*;
+
+use hecs::{World as EcsWorld, Entity};
+use nbt::CompoundTag;
+use uuid::Uuid;
+
+
+/// Pose of an entity, used by the client to select the hitbox and animation.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Pose {
+    Standing,
+    FallFlying,
+    Sleeping,
+    Swimming,
+    SpinAttack,
+    Sneaking,
+    Dying
+}
+
+impl Pose {
+
+    pub fn get_id(self, version: ProtocolVersion) -> i32 {
+        match self {
+            Self::Standing => 0,
+            Self::FallFlying => 1,
+            Self::Sleeping => 2,
+            Self::Swimming => 3,
+            Self::SpinAttack => 4,
+            Self::Sneaking => 5,
+            // Long jumping pose was added before dying in 1.17.
+            Self::Dying if version >= ProtocolVersion::V1_17_1 => 7,
+            Self::Dying => 6
+        }
+    }
+
+}
+
+
+/// A typed value of an entity metadata entry. Slots and particles are not yet supported.
+#[derive(Debug, Clone)]
+pub enum MetadataValue {
+    Byte(i8),
+    VarInt(i32),
+    Float(f32),
+    String(String),
+    /// A JSON text component.
+    Chat(String),
+    OptChat(Option<String>),
+    Boolean(bool),
+    Rotation(f32, f32, f32),
+    Position(BlockPos),
+    OptPosition(Option<BlockPos>),
+    Direction(Direction),
+    OptUuid(Option<Uuid>),
+    /// A block state ID, zero being absent.
+    OptBlockId(Option<u32>),
+    Nbt(CompoundTag),
+    VillagerData {
+        typ: i32,
+        profession: i32,
+        level: i32
+    },
+    OptVarInt(Option<i32>),
+    Pose(Pose)
+}
+
+impl MetadataValue {
+
+    /// Return the type ID of this value, the same IDs are used by 1.16.5 and 1.17.1.
+    pub fn get_type_id(&self) -> i32 {
+        match self {
+            Self::Byte(_) => 0,
+            Self::VarInt(_) => 1,
+            Self::Float(_) => 2,
+            Self::String(_) => 3,
+            Self::Chat(_) => 4,
+            Self::OptChat(_) => 5,
+            Self::Boolean(_) => 7,
+            Self::Rotation(..) => 8,
+            Self::Position(_) => 9,
+            Self::OptPosition(_) => 10,
+            Self::Direction(_) => 11,
+            Self::OptUuid(_) => 12,
+            Self::OptBlockId(_) => 13,
+            Self::Nbt(_) => 14,
+            Self::VillagerData { .. } => 16,
+            Self::OptVarInt(_) => 17,
+            Self::Pose(_) => 18
+        }
+    }
+
+    fn write_value<W: Write>(&self, dst: &mut W, version: ProtocolVersion) -> IoResult<()> {
+        match self {
+            Self::Byte(val) => dst.write_i8(*val),
+            Self::VarInt(val) => dst.write_var_int(*val),
+            Self::Float(val) => dst.write_f32(*val),
+            Self::String(val) | Self::Chat(val) => dst.write_string(val.as_str()),
+            Self::OptChat(val) => {
+                dst.write_bool(val.is_some())?;
+                match val {
+                    Some(val) => dst.write_string(val.as_str()),
+                    None => Ok(())
+                }
+            }
+            Self::Boolean(val) => dst.write_bool(*val),
+            Self::Rotation(x, y, z) => {
+                dst.write_f32(*x)?;
+                dst.write_f32(*y)?;
+                dst.write_f32(*z)
+            }
+            Self::Position(pos) => dst.write_block_pos(pos),
+            Self::OptPosition(pos) => {
+                dst.write_bool(pos.is_some())?;
+                match pos {
+                    Some(pos) => dst.write_block_pos(pos),
+                    None => Ok(())
+                }
+            }
+            Self::Direction(direction) => dst.write_var_int(match direction {
+                Direction::Down => 0,
+                Direction::Up => 1,
+                Direction::North => 2,
+                Direction::South => 3,
+                Direction::West => 4,
+                Direction::East => 5
+            }),
+            Self::OptUuid(uuid) => {
+                dst.write_bool(uuid.is_some())?;
+                match uuid {
+                    Some(uuid) => dst.write_uuid(uuid),
+                    None => Ok(())
+                }
+            }
+            Self::OptBlockId(id) => dst.write_var_int(id.map(|id| id as i32).unwrap_or(0)),
+            Self::Nbt(nbt) => dst.write_nbt(nbt),
+            Self::VillagerData { typ, profession, level } => {
+                dst.write_var_int(*typ)?;
+                dst.write_var_int(*profession)?;
+                dst.write_var_int(*level)
+            }
+            // The value is shifted to keep zero for absent values.
+            Self::OptVarInt(val) => dst.write_var_int(val.map(|val| val + 1).unwrap_or(0)),
+            Self::Pose(pose) => dst.write_var_int(pose.get_id(version))
+        }
+    }
+
+}
+
+
+/// A list of entity metadata entries, sorted by index.
+#[derive(Debug, Clone, Default)]
+pub struct EntityMetadata {
+    entries: Vec<(u8, MetadataValue)>
+}
+
+impl EntityMetadata {
+
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new()
+        }
+    }
+
+    /// Set the value at the given index, replacing any previous value.
+    pub fn set(&mut self, index: u8, value: MetadataValue) {
+        match self.entries.binary_search_by_key(&index, |&(idx, _)| idx) {
+            Ok(pos) => self.entries[pos].1 = value,
+            Err(pos) => self.entries.insert(pos, (index, value))
+        }
+    }
+
+    pub fn get(&self, index: u8) -> Option<&MetadataValue> {
+        self.entries.binary_search_by_key(&index, |&(idx, _)| idx)
+            .ok()
+            .map(|pos| &self.entries[pos].1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Write all entries followed by the terminating index.
+    pub fn write_metadata<W: Write>(&self, dst: &mut W, version: ProtocolVersion) -> IoResult<()> {
+        for (index, value) in &self.entries {
+            dst.write_u8(*index)?;
+            dst.write_var_int(value.get_type_id())?;
+            value.write_value(dst, version)?;
+        }
+        dst.write_u8(0xFF)
+    }
+
+}
+
+
+/// Build the metadata of an entity from its vanilla components. Indices depend on the entity
+/// type's hierarchy, which is deduced from the components of the entity:
+///
+/// - Entity: flags, air, custom name, silent, no gravity and pose from index 0, and ticks
+///   frozen at index 7 since 1.17;
+/// - Living entity: health and bed location (7 to 13 before 1.17);
+/// - Player (14 to 19), armor stand (14 to 20) or mob (14) and its specific entries from
+///   index 15;
+/// - Item frame rotation (8 before 1.17), its item is not sent yet.
+pub fn build_entity_metadata(ecs: &EcsWorld, entity: Entity, version: ProtocolVersion) -> EntityMetadata {
+
+    let mut metadata = EntityMetadata::new();
+    // Each index after the entity ones are shifted by the 1.17 ticks frozen entry.
+    let shift = if version >= ProtocolVersion::V1_17_1 { 1 } else { 0 };
+
+    let living = ecs.get::<LivingEntity>(entity).ok();
+
+    if let Ok(base) = ecs.get::<VanillaEntity>(entity) {
+
+        let mut flags = 0;
+        if base.is_on_fire() || base.has_visual_fire() {
+            flags |= 0x01;
+        }
+        if base.is_glowing() {
+            flags |= 0x40;
+        }
+        if living.as_ref().is_some_and(|living| living.is_fall_flying()) {
+            flags |= 0x80;
+        }
+
+        metadata.set(0, MetadataValue::Byte(flags as i8));
+        metadata.set(1, MetadataValue::VarInt(base.get_air() as i32));
+        metadata.set(4, MetadataValue::Boolean(base.is_silent()));
+        metadata.set(5, MetadataValue::Boolean(base.has_no_gravity()));
+
+        if version >= ProtocolVersion::V1_17_1 {
+            metadata.set(7, MetadataValue::VarInt(base.get_ticks_frozen() as i32));
+        }
+
+    }
+
+    if let Ok(named) = ecs.get::<NamedEntity>(entity) {
+        metadata.set(2, MetadataValue::OptChat(named.get_custom_name().map(|name| name.to_string())));
+        metadata.set(3, MetadataValue::Boolean(named.is_custom_name_visible()));
+    }
+
+    metadata.set(6, MetadataValue::Pose(match &living {
+        Some(living) if living.get_health() <= 0.0 => Pose::Dying,
+        Some(living) if living.get_sleeping_pos().is_some() => Pose::Sleeping,
+        Some(living) if living.is_fall_flying() => Pose::FallFlying,
+        _ => Pose::Standing
+    }));
+
+    if let Some(living) = &living {
+        metadata.set(8 + shift, MetadataValue::Float(living.get_health()));
+        metadata.set(13 + shift, MetadataValue::OptPosition(living.get_sleeping_pos().cloned()));
+    }
+
+    if let Ok(player) = ecs.get::<PlayerEntity>(entity) {
+        let absorption = living.as_ref().map_or(0.0, |living| living.get_absorption_amount());
+        metadata.set(14 + shift, MetadataValue::Float(absorption));
+        metadata.set(15 + shift, MetadataValue::VarInt(player.get_score() as i32));
+        metadata.set(16 + shift, MetadataValue::Byte(0x7F)); // All skin parts
+        metadata.set(17 + shift, MetadataValue::Byte(1)); // Right main hand
+    }
+
+    if let Ok(stand) = ecs.get::<ArmorStandEntity>(entity) {
+        let mut flags = 0;
+        if stand.is_small() {
+            flags |= 0x01;
+        }
+        if stand.has_arms() {
+            flags |= 0x04;
+        }
+        if !stand.has_base_plate() {
+            flags |= 0x08;
+        }
+        if stand.is_marker() {
+            flags |= 0x10;
+        }
+        metadata.set(14 + shift, MetadataValue::Byte(flags));
+        let pose = stand.get_pose();
+        let parts = [&pose.head, &pose.body, &pose.left_arm, &pose.right_arm, &pose.left_leg, &pose.right_leg];
+        for (index, part) in (15..).zip(parts) {
+            metadata.set(index + shift, MetadataValue::Rotation(part.x, part.y, part.z));
+        }
+    }
+
+    if let Ok(frame) = ecs.get::<ItemFrameEntity>(entity) {
+        metadata.set(8 + shift, MetadataValue::VarInt(frame.get_rotation() as i32));
+    }
+
+    if let Ok(mob) = ecs.get::<MobEntity>(entity) {
+        let mut flags = 0;
+        if mob.has_no_ai() {
+            flags |= 0x01;
+        }
+        if mob.is_left_handed() {
+            flags |= 0x02;
+        }
+        metadata.set(14 + shift, MetadataValue::Byte(flags));
+    }
+
+    if let Ok(breedable) = ecs.get::<BreedableEntity>(entity) {
+        metadata.set(15 + shift, MetadataValue::Boolean(breedable.is_baby()));
+    }
+
+    if let Ok(pig) = ecs.get::<PigEntity>(entity) {
+        metadata.set(16 + shift, MetadataValue::Boolean(pig.has_saddle()));
+        metadata.set(17 + shift, MetadataValue::VarInt(0)); // Boost time
+    }
+
+    if let Ok(sheep) = ecs.get::<SheepEntity>(entity) {
+        let sheared = if sheep.is_sheared() { 0x10 } else { 0 };
+        metadata.set(16 + shift, MetadataValue::Byte((sheep.get_color().get_id() | sheared) as i8));
+    }
+
+    if let Ok(creeper) = ecs.get::<CreeperEntity>(entity) {
+        metadata.set(15 + shift, MetadataValue::VarInt(if creeper.is_ignited() { 1 } else { -1 }));
+        metadata.set(16 + shift, MetadataValue::Boolean(creeper.is_powered()));
+        metadata.set(17 + shift, MetadataValue::Boolean(creeper.is_ignited()));
+    }
+
+    if let Ok(slime) = ecs.get::<SlimeEntity>(entity) {
+        metadata.set(15 + shift, MetadataValue::VarInt(slime.get_size() as i32));
+    }
+
+    metadata
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use std::io::Cursor;
+
+    use mc_vanilla::entity::CREEPER;
+
+    use super::*;
+
+    #[test]
+    fn creeper_metadata() {
+
+        let mut builder = hecs::EntityBuilder::new();
+        for &codec in CREEPER.codecs {
+            codec.default(&mut builder);
+        }
+
+        let mut ecs = EcsWorld::new();
+        let creeper = ecs.spawn(builder.build());
+
+        let metadata = build_entity_metadata(&ecs, creeper, ProtocolVersion::V1_16_5);
+        assert!(matches!(metadata.get(6), Some(MetadataValue::Pose(Pose::Dying))));
+        assert!(matches!(metadata.get(14), Some(MetadataValue::Byte(0))));
+        assert!(matches!(metadata.get(15), Some(MetadataValue::VarInt(-1))));
+        assert!(matches!(metadata.get(17), Some(MetadataValue::Boolean(false))));
+        assert!(metadata.get(18).is_none());
+
+        // Ticks frozen is added in 1.17, shifting the next indices.
+        let metadata_v17 = build_entity_metadata(&ecs, creeper, ProtocolVersion::V1_17_1);
+        assert!(matches!(metadata_v17.get(7), Some(MetadataValue::VarInt(0))));
+        assert!(matches!(metadata_v17.get(18), Some(MetadataValue::Boolean(false))));
+        assert_eq!(metadata_v17.len(), metadata.len() + 1);
+
+        let mut data = Vec::new();
+        metadata.write_metadata(&mut Cursor::new(&mut data), ProtocolVersion::V1_16_5).unwrap();
+        // Entity flags: index 0, byte type, value.
+        assert_eq!(&data[..3], &[0, 0, 0]);
+        assert_eq!(data.last(), Some(&0xFF));
+
+    }
+
+}
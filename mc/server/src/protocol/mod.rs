@@ -1,6 +1,5 @@
-//! Protocol implementation in Rust for Minecraft 1.16.5
-//!
-//! Source: https://wiki.vg/index.php?title=Protocol&oldid=16681
+//! Protocol implementation in Rust for Minecraft 1.16.5 and 1.17.1, packets are written
+//! and read for a specific `ProtocolVersion`, see the `version` module.
 
 use std::io::Cursor;
 
@@ -10,6 +9,11 @@ pub mod handshake;
 pub mod status;
 pub mod login;
 pub mod play;
+pub mod metadata;
+pub mod spawn;
+pub mod version;
+
+use version::ProtocolVersion;
 
 
 #[derive(Debug, Error)]
@@ -25,26 +29,28 @@ pub enum PacketError {
 pub type PacketResult<T> = Result<T, PacketError>;
 
 
-/// To implement for packets that could be written.
+/// To implement for packets that could be written, the packet must be encoded for the
+/// given protocol version.
 pub trait WritablePacket {
-    fn write_packet(&mut self, dst: Cursor<&mut Vec<u8>>) -> PacketResult<()>;
+    fn write_packet(&mut self, dst: Cursor<&mut Vec<u8>>, version: ProtocolVersion) -> PacketResult<()>;
 }
 
-/// To implement for packets that could be read.
+/// To implement for packets that could be read, the packet must be decoded for the given
+/// protocol version.
 pub trait ReadablePacket: Sized {
-    fn read_packet(src: Cursor<&Vec<u8>>) -> PacketResult<Self>;
+    fn read_packet(src: Cursor<&Vec<u8>>, version: ProtocolVersion) -> PacketResult<Self>;
 }
 
 // Implementations for empty packets //
 
 impl WritablePacket for () {
-    fn write_packet(&mut self, _dst: Cursor<&mut Vec<u8>>) -> PacketResult<()> {
+    fn write_packet(&mut self, _dst: Cursor<&mut Vec<u8>>, _version: ProtocolVersion) -> PacketResult<()> {
         Ok(())
     }
 }
 
 impl ReadablePacket for () {
-    fn read_packet(_src: Cursor<&Vec<u8>>) -> PacketResult<Self> {
+    fn read_packet(_src: Cursor<&Vec<u8>>, _version: ProtocolVersion) -> PacketResult<Self> {
         Ok(())
     }
 }
@@ -1,18 +1,27 @@
-use std::io::{Cursor, Write, Read};
+use std::io::{Cursor, Write, Read, Result as IoResult};
+use std::sync::Arc;
 
 use super::{ReadablePacket, WritablePacket, PacketResult, PacketError};
+use super::version::ProtocolVersion;
+use super::metadata::EntityMetadata;
 use crate::packet::serial::*;
 
 use mc_core::util::PackedIterator;
-use mc_core::world::chunk::Chunk;
-use mc_core::pos::BlockPos;
+use mc_core::world::chunk::{Chunk, SubChunk, Light};
+use mc_core::world::dimension::{DimensionTypes, DimensionType};
+use mc_core::pos::{BlockPos, Direction};
+use mc_core::sound::{Sound, SoundCategory};
+use mc_core::particle::Particle;
 
 use mc_runtime::world::World;
 use mc_vanilla::biome::VANILLA_BIOMES;
 use mc_vanilla::heightmap::MOTION_BLOCKING;
 use mc_vanilla::util::GameMode;
+use mc_vanilla::entity::PlayerAbilities;
+use mc_vanilla::map::{MapDecoration, MapPatch};
 
 use nbt::CompoundTag;
+use uuid::Uuid;
 
 
 /// Client bound
@@ -28,7 +37,7 @@ pub struct JoinGamePacket<'a> {
 }
 
 impl<'a> WritablePacket for JoinGamePacket<'a> {
-    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>) -> PacketResult<()> {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, version: ProtocolVersion) -> PacketResult<()> {
 
         if self.level_index >= self.world.levels.len() {
             return Err(PacketError::InvalidField("given level index is out of bounds"));
@@ -44,37 +53,15 @@ impl<'a> WritablePacket for JoinGamePacket<'a> {
             dst.write_string((**level).borrow().get_id().as_str()).unwrap();
         }
 
+        let with_height = version >= ProtocolVersion::V1_17_1;
+
+        let mut dimension_types = DimensionTypes::new();
+        for level in &self.world.levels {
+            dimension_types.register(Arc::clone((**level).borrow().get_dimension_type()));
+        }
+
         let mut dimension_codec = CompoundTag::new();
-        dimension_codec.insert_compound_tag("minecraft:dimension_type", {
-            let mut dimension_type = CompoundTag::new();
-            dimension_type.insert_str("type", "minecraft:dimension_type");
-            dimension_type.insert_compound_tag_vec("value", self.world.levels.iter()
-                .enumerate()
-                .map(|(i, level)| {
-                    let mut dimension_value = CompoundTag::new();
-                    dimension_value.insert_str("name", (**level).borrow().get_id());
-                    dimension_value.insert_i32("id", i as i32);
-                    dimension_value.insert_compound_tag("element", {
-                        let mut dimension_element = CompoundTag::new();
-                        dimension_element.insert_bool("piglin_safe", false);
-                        dimension_element.insert_bool("natural", true);
-                        dimension_element.insert_f32("ambient_light", 0.0);
-                        dimension_element.insert_str("infiniburn", "minecraft:infiniburn_overworld");
-                        dimension_element.insert_bool("respawn_anchor_works", false);
-                        dimension_element.insert_bool("has_skylight", true);
-                        dimension_element.insert_bool("bed_works", true);
-                        dimension_element.insert_str("effects", "minecraft:overworld");
-                        dimension_element.insert_bool("has_raids", true);
-                        dimension_element.insert_i32("logical_height", 256);
-                        dimension_element.insert_f32("coordinate_scale", 1.0);
-                        dimension_element.insert_bool("ultrawarm", false);
-                        dimension_element.insert_bool("has_ceiling", false);
-                        dimension_element
-                    });
-                    dimension_value
-                }));
-            dimension_type
-        });
+        dimension_codec.insert_compound_tag("minecraft:dimension_type", dimension_types.encode_registry(with_height));
         dimension_codec.insert_compound_tag("minecraft:worldgen/biome", {
 
             let mut biome_reg = CompoundTag::new();
@@ -121,10 +108,10 @@ impl<'a> WritablePacket for JoinGamePacket<'a> {
         });
 
         dst.write_nbt(&dimension_codec).unwrap();
-        dst.write_nbt({
-            dimension_codec.get_compound_tag("minecraft:dimension_type").unwrap()
-                .get_compound_tag_vec("value").unwrap()[self.level_index]
-                .get_compound_tag("element").unwrap()
+        dst.write_nbt(&{
+            let mut dimension_element = CompoundTag::new();
+            (*self.world.levels[self.level_index]).borrow().get_dimension_type().encode(&mut dimension_element, with_height);
+            dimension_element
         }).unwrap();
 
         dst.write_string((*self.world.levels[self.level_index]).borrow().get_id().as_str()).unwrap();
@@ -146,23 +133,56 @@ impl<'a> WritablePacket for JoinGamePacket<'a> {
 /// Client & server bound
 pub enum PluginMessage {
     Brand(String),
+    /// Channels the sender is listening to.
+    Register(Vec<String>),
+    /// Channels the sender is no longer listening to.
+    Unregister(Vec<String>),
     Custom {
         id: String,
         data: Vec<u8>
     }
 }
 
-impl WritablePacket for PluginMessage {
-    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>) -> PacketResult<()> {
+impl PluginMessage {
 
-        dst.write_string(match self {
-            Self::Brand(_) => "minecraft:brand",
+    pub const BRAND_CHANNEL: &'static str = "minecraft:brand";
+    pub const REGISTER_CHANNEL: &'static str = "minecraft:register";
+    pub const UNREGISTER_CHANNEL: &'static str = "minecraft:unregister";
+
+    /// Return the channel of this message.
+    pub fn get_channel(&self) -> &str {
+        match self {
+            Self::Brand(_) => Self::BRAND_CHANNEL,
+            Self::Register(_) => Self::REGISTER_CHANNEL,
+            Self::Unregister(_) => Self::UNREGISTER_CHANNEL,
             Self::Custom { id, .. } => id.as_str()
-        }).unwrap();
+        }
+    }
+
+    /// Encode the payload of this message, as sent after the channel.
+    pub fn get_data(&self) -> Vec<u8> {
+        match self {
+            Self::Brand(brand) => {
+                let mut data = Vec::with_capacity(brand.len() + 1);
+                data.write_string(brand.as_str()).unwrap();
+                data
+            }
+            Self::Register(channels) |
+            Self::Unregister(channels) => channels.join("\0").into_bytes(),
+            Self::Custom { data, .. } => data.clone()
+        }
+    }
+
+}
+
+impl WritablePacket for PluginMessage {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, _version: ProtocolVersion) -> PacketResult<()> {
+
+        dst.write_string(self.get_channel()).unwrap();
 
         match self {
-            Self::Brand(brand) => dst.write_string(brand.as_str()).unwrap(),
-            Self::Custom { data, .. } => dst.write_all(&data[..]).unwrap()
+            Self::Custom { data, .. } => dst.write_all(&data[..]).unwrap(),
+            _ => dst.write_all(&self.get_data()[..]).unwrap()
         }
 
         Ok(())
@@ -171,10 +191,23 @@ impl WritablePacket for PluginMessage {
 }
 
 impl ReadablePacket for PluginMessage {
-    fn read_packet(mut src: Cursor<&Vec<u8>>) -> PacketResult<Self> {
+    fn read_packet(mut src: Cursor<&Vec<u8>>, _version: ProtocolVersion) -> PacketResult<Self> {
         let channel = src.read_string()?;
         Ok(match channel.as_str() {
-            "minecraft:brand" => Self::Brand(src.read_string()?),
+            Self::BRAND_CHANNEL => Self::Brand(src.read_string()?),
+            Self::REGISTER_CHANNEL | Self::UNREGISTER_CHANNEL => {
+                let mut data = String::new();
+                src.read_to_string(&mut data)?;
+                let channels = data.split('\0')
+                    .filter(|channel| !channel.is_empty())
+                    .map(|channel| channel.to_string())
+                    .collect();
+                if channel == Self::REGISTER_CHANNEL {
+                    Self::Register(channels)
+                } else {
+                    Self::Unregister(channels)
+                }
+            }
             channel => Self::Custom {
                 id: channel.to_string(),
                 data: {
@@ -190,12 +223,17 @@ impl ReadablePacket for PluginMessage {
 
 /// Client bound
 pub struct SpawnPositionPacket {
-    pub pos: BlockPos
+    pub pos: BlockPos,
+    /// Only sent since 1.17.
+    pub angle: f32
 }
 
 impl WritablePacket for SpawnPositionPacket {
-    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>) -> PacketResult<()> {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, version: ProtocolVersion) -> PacketResult<()> {
         dst.write_block_pos(&self.pos).unwrap();
+        if version >= ProtocolVersion::V1_17_1 {
+            dst.write_f32(self.angle).unwrap();
+        }
         Ok(())
     }
 }
@@ -212,7 +250,7 @@ pub struct PlayerAbilitiesPacket {
 }
 
 impl WritablePacket for PlayerAbilitiesPacket {
-    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>) -> PacketResult<()> {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, _version: ProtocolVersion) -> PacketResult<()> {
         dst.write_u8(crate::build_flags!(
             self.invulnerable,
             self.flying,
@@ -225,6 +263,349 @@ impl WritablePacket for PlayerAbilitiesPacket {
     }
 }
 
+impl PlayerAbilitiesPacket {
+
+    /// Create the packet synchronizing the given abilities, the field of view modifier is
+    /// the walking speed.
+    pub fn new(abilities: &PlayerAbilities) -> Self {
+        Self {
+            invulnerable: abilities.invulnerable,
+            flying: abilities.flying,
+            allow_flying: abilities.may_fly,
+            instant_break: abilities.instant_build,
+            flying_speed: abilities.fly_speed,
+            fov_modifier: abilities.walk_speed
+        }
+    }
+
+}
+
+
+/// Server bound, sent when the player starts or stops flying.
+pub struct ClientPlayerAbilitiesPacket {
+    pub flying: bool
+}
+
+impl ReadablePacket for ClientPlayerAbilitiesPacket {
+    fn read_packet(mut src: Cursor<&Vec<u8>>, _version: ProtocolVersion) -> PacketResult<Self> {
+        Ok(Self {
+            flying: src.read_u8()? & 0x02 != 0
+        })
+    }
+}
+
+
+/// A change of the game state, only some changes are supported.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum GameStateChange {
+    /// The spawn point of the player was missing or obstructed when respawning.
+    NoRespawnBlockAvailable,
+    ChangeGameMode(GameMode)
+}
+
+/// Client bound
+pub struct ChangeGameStatePacket {
+    pub change: GameStateChange
+}
+
+impl WritablePacket for ChangeGameStatePacket {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, _version: ProtocolVersion) -> PacketResult<()> {
+        let (reason, value) = match self.change {
+            GameStateChange::NoRespawnBlockAvailable => (0, 0.0),
+            GameStateChange::ChangeGameMode(game_mode) => (3, game_mode.get_id() as f32)
+        };
+        dst.write_u8(reason).unwrap();
+        dst.write_f32(value).unwrap();
+        Ok(())
+    }
+}
+
+
+/// Server bound, sent when the player changes its selected hotbar slot.
+pub struct HeldItemChangePacket {
+    pub slot: i16
+}
+
+impl ReadablePacket for HeldItemChangePacket {
+    fn read_packet(mut src: Cursor<&Vec<u8>>, _version: ProtocolVersion) -> PacketResult<Self> {
+        Ok(Self {
+            slot: src.read_i16()?
+        })
+    }
+}
+
+
+/// The action of a player digging packet.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DiggingStatus {
+    StartedDigging,
+    CancelledDigging,
+    FinishedDigging,
+    DropItemStack,
+    DropItem,
+    /// Shooting arrows, finishing eating or drinking.
+    ReleaseUseItem,
+    SwapItemInHand
+}
+
+impl DiggingStatus {
+
+    pub fn from_id(id: i32) -> Option<Self> {
+        Some(match id {
+            0 => Self::StartedDigging,
+            1 => Self::CancelledDigging,
+            2 => Self::FinishedDigging,
+            3 => Self::DropItemStack,
+            4 => Self::DropItem,
+            5 => Self::ReleaseUseItem,
+            6 => Self::SwapItemInHand,
+            _ => return None
+        })
+    }
+
+}
+
+/// Server bound, sent when the player digs a block, the position and face are only
+/// meaningful for digging statuses.
+pub struct PlayerDiggingPacket {
+    pub status: DiggingStatus,
+    pub pos: BlockPos,
+    pub face: Direction
+}
+
+impl ReadablePacket for PlayerDiggingPacket {
+    fn read_packet(mut src: Cursor<&Vec<u8>>, _version: ProtocolVersion) -> PacketResult<Self> {
+        let status = DiggingStatus::from_id(src.read_var_int()?)
+            .ok_or(PacketError::InvalidField("status"))?;
+        let pos = src.read_block_pos()?;
+        let face = face_from_id(src.read_u8()? as i32)
+            .ok_or(PacketError::InvalidField("face"))?;
+        Ok(Self { status, pos, face })
+    }
+}
+
+
+/// The hand used by a player.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Hand {
+    MainHand,
+    OffHand
+}
+
+/// Server bound, sent when the player right clicks a block, to use it or to use the item
+/// in hand against it. The cursor position is relative to the block.
+pub struct PlayerBlockPlacementPacket {
+    pub hand: Hand,
+    pub pos: BlockPos,
+    pub face: Direction,
+    pub cursor_x: f32,
+    pub cursor_y: f32,
+    pub cursor_z: f32,
+    /// True if the head of the player is inside the block.
+    pub inside_block: bool
+}
+
+impl ReadablePacket for PlayerBlockPlacementPacket {
+    fn read_packet(mut src: Cursor<&Vec<u8>>, _version: ProtocolVersion) -> PacketResult<Self> {
+        let hand = match src.read_var_int()? {
+            0 => Hand::MainHand,
+            1 => Hand::OffHand,
+            _ => return Err(PacketError::InvalidField("hand"))
+        };
+        let pos = src.read_block_pos()?;
+        let face = face_from_id(src.read_var_int()?)
+            .ok_or(PacketError::InvalidField("face"))?;
+        Ok(Self {
+            hand,
+            pos,
+            face,
+            cursor_x: src.read_f32()?,
+            cursor_y: src.read_f32()?,
+            cursor_z: src.read_f32()?,
+            inside_block: src.read_bool()?
+        })
+    }
+}
+
+/// Decode the face of a block sent by clients.
+fn face_from_id(id: i32) -> Option<Direction> {
+    Some(match id {
+        0 => Direction::Down,
+        1 => Direction::Up,
+        2 => Direction::North,
+        3 => Direction::South,
+        4 => Direction::West,
+        5 => Direction::East,
+        _ => return None
+    })
+}
+
+/// Return the protocol ID of a face, as sent for block faces and item frames.
+pub(crate) fn face_to_id(face: Direction) -> i32 {
+    match face {
+        Direction::Down => 0,
+        Direction::Up => 1,
+        Direction::North => 2,
+        Direction::South => 3,
+        Direction::West => 4,
+        Direction::East => 5
+    }
+}
+
+
+/// The kind of interaction of a player with an entity.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum EntityInteraction {
+    Interact(Hand),
+    Attack,
+    /// Interaction at a position relative to the entity, sent before `Interact`.
+    InteractAt {
+        x: f32,
+        y: f32,
+        z: f32,
+        hand: Hand
+    }
+}
+
+/// Server bound, sent when the player right or left clicks an entity.
+pub struct InteractEntityPacket {
+    pub eid: i32,
+    pub interaction: EntityInteraction,
+    pub sneaking: bool
+}
+
+impl ReadablePacket for InteractEntityPacket {
+    fn read_packet(mut src: Cursor<&Vec<u8>>, _version: ProtocolVersion) -> PacketResult<Self> {
+        let eid = src.read_var_int()?;
+        let kind = src.read_var_int()?;
+        let interaction = match kind {
+            0 | 2 => {
+                let (x, y, z) = if kind == 2 {
+                    (src.read_f32()?, src.read_f32()?, src.read_f32()?)
+                } else {
+                    (0.0, 0.0, 0.0)
+                };
+                let hand = match src.read_var_int()? {
+                    0 => Hand::MainHand,
+                    1 => Hand::OffHand,
+                    _ => return Err(PacketError::InvalidField("hand"))
+                };
+                if kind == 2 {
+                    EntityInteraction::InteractAt { x, y, z, hand }
+                } else {
+                    EntityInteraction::Interact(hand)
+                }
+            }
+            1 => EntityInteraction::Attack,
+            _ => return Err(PacketError::InvalidField("type"))
+        };
+        Ok(Self {
+            eid,
+            interaction,
+            sneaking: src.read_bool()?
+        })
+    }
+}
+
+
+/// Server bound, sent by players riding a vehicle with their movement inputs.
+pub struct SteerVehiclePacket {
+    pub sideways: f32,
+    pub forward: f32,
+    pub jump: bool,
+    pub unmount: bool
+}
+
+impl ReadablePacket for SteerVehiclePacket {
+    fn read_packet(mut src: Cursor<&Vec<u8>>, _version: ProtocolVersion) -> PacketResult<Self> {
+        let sideways = src.read_f32()?;
+        let forward = src.read_f32()?;
+        let flags = src.read_u8()?;
+        Ok(Self {
+            sideways,
+            forward,
+            jump: flags & 0x01 != 0,
+            unmount: flags & 0x02 != 0
+        })
+    }
+}
+
+
+/// The action of a client status packet.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ClientStatusAction {
+    /// The player clicked the respawn button of the death screen.
+    PerformRespawn,
+    /// The player opened the statistics menu.
+    RequestStats
+}
+
+/// Server bound
+pub struct ClientStatusPacket {
+    pub action: ClientStatusAction
+}
+
+impl ReadablePacket for ClientStatusPacket {
+    fn read_packet(mut src: Cursor<&Vec<u8>>, _version: ProtocolVersion) -> PacketResult<Self> {
+        let action = match src.read_var_int()? {
+            0 => ClientStatusAction::PerformRespawn,
+            1 => ClientStatusAction::RequestStats,
+            _ => return Err(PacketError::InvalidField("action"))
+        };
+        Ok(Self { action })
+    }
+}
+
+
+/// Client bound, sent to respawn the player or to move it to another level.
+pub struct RespawnPacket {
+    pub dimension_type: Arc<DimensionType>,
+    pub level_id: String,
+    pub hashed_seed: u64,
+    pub game_mode: GameMode,
+    pub previous_game_mode: Option<GameMode>,
+    /// Keep the entity metadata of the player, like vanilla when changing level.
+    pub copy_metadata: bool
+}
+
+impl WritablePacket for RespawnPacket {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, version: ProtocolVersion) -> PacketResult<()> {
+        let mut dimension_element = CompoundTag::new();
+        self.dimension_type.encode(&mut dimension_element, version >= ProtocolVersion::V1_17_1);
+        dst.write_nbt(&dimension_element).unwrap();
+        dst.write_string(self.level_id.as_str()).unwrap();
+        dst.write_i64(self.hashed_seed as i64).unwrap();
+        dst.write_u8(self.game_mode.get_id()).unwrap();
+        dst.write_i8(self.previous_game_mode.map(|gm| gm.get_id() as i8).unwrap_or(-1)).unwrap();
+        dst.write_bool(false).unwrap();
+        dst.write_bool(false).unwrap();
+        dst.write_bool(self.copy_metadata).unwrap();
+        Ok(())
+    }
+}
+
+
+/// Client bound, opens the death screen of the player with the given message.
+pub struct DeathCombatEventPacket {
+    pub player_eid: i32,
+    /// The entity ID of the killer, -1 if none.
+    pub killer_eid: i32,
+    pub message: String
+}
+
+impl WritablePacket for DeathCombatEventPacket {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, version: ProtocolVersion) -> PacketResult<()> {
+        if version < ProtocolVersion::V1_17_1 {
+            // The combat event packet with the entity dead event.
+            dst.write_var_int(2).unwrap();
+        }
+        dst.write_var_int(self.player_eid).unwrap();
+        dst.write_i32(self.killer_eid).unwrap();
+        dst.write_string(serde_json::json!({ "text": self.message }).to_string().as_str()).unwrap();
+        Ok(())
+    }
+}
+
 
 /// Client bound
 pub struct PlayerPosAndLook {
@@ -238,11 +619,13 @@ pub struct PlayerPosAndLook {
     pub rel_z: bool,
     pub rel_yaw: bool,
     pub rel_pitch: bool,
-    pub tp_id: u32
+    pub tp_id: u32,
+    /// Only sent since 1.17.
+    pub dismount_vehicle: bool
 }
 
 impl WritablePacket for PlayerPosAndLook {
-    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>) -> PacketResult<()> {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, version: ProtocolVersion) -> PacketResult<()> {
         dst.write_f64(self.x).unwrap();
         dst.write_f64(self.y).unwrap();
         dst.write_f64(self.z).unwrap();
@@ -256,86 +639,1380 @@ impl WritablePacket for PlayerPosAndLook {
             self.rel_pitch
         )).unwrap();
         dst.write_var_int(self.tp_id as i32).unwrap();
+        if version >= ProtocolVersion::V1_17_1 {
+            dst.write_bool(self.dismount_vehicle).unwrap();
+        }
         Ok(())
     }
 }
 
 
-pub struct UpdateViewPositionPacket {
-    pub cx: i32,
-    pub cz: i32
+/// Server bound
+pub struct PlayerPositionPacket {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub on_ground: bool
 }
 
-impl WritablePacket for UpdateViewPositionPacket {
-    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>) -> PacketResult<()> {
-        dst.write_var_int(self.cx).unwrap();
-        dst.write_var_int(self.cz).unwrap();
-        Ok(())
+impl ReadablePacket for PlayerPositionPacket {
+    fn read_packet(mut src: Cursor<&Vec<u8>>, _version: ProtocolVersion) -> PacketResult<Self> {
+        Ok(Self {
+            x: src.read_f64()?,
+            y: src.read_f64()?,
+            z: src.read_f64()?,
+            on_ground: src.read_bool()?
+        })
     }
 }
 
 
-pub struct ChunkDataPacket<'a> {
-    chunk: &'a Chunk,
-    parts: Option<u64>
+/// Server bound
+pub struct PlayerPositionAndRotationPacket {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub on_ground: bool
 }
 
-impl<'a> ChunkDataPacket<'a> {
-
-    pub fn new(chunk: &'a Chunk) -> Self {
-        Self {
-            chunk,
-            parts: None
-        }
+impl ReadablePacket for PlayerPositionAndRotationPacket {
+    fn read_packet(mut src: Cursor<&Vec<u8>>, _version: ProtocolVersion) -> PacketResult<Self> {
+        Ok(Self {
+            x: src.read_f64()?,
+            y: src.read_f64()?,
+            z: src.read_f64()?,
+            yaw: src.read_f32()?,
+            pitch: src.read_f32()?,
+            on_ground: src.read_bool()?
+        })
     }
+}
+
 
+/// Server bound
+pub struct PlayerRotationPacket {
+    pub yaw: f32,
+    pub pitch: f32,
+    pub on_ground: bool
 }
 
-impl<'a> WritablePacket for ChunkDataPacket<'a> {
-    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>) -> PacketResult<()> {
+impl ReadablePacket for PlayerRotationPacket {
+    fn read_packet(mut src: Cursor<&Vec<u8>>, _version: ProtocolVersion) -> PacketResult<Self> {
+        Ok(Self {
+            yaw: src.read_f32()?,
+            pitch: src.read_f32()?,
+            on_ground: src.read_bool()?
+        })
+    }
+}
 
-        let (cx, cz) = self.chunk.get_position();
-        dst.write_var_int(cx).unwrap();
-        dst.write_var_int(cz).unwrap();
 
-        if self.parts.is_none() {
+/// A player entry of the player info (tab) list.
+#[derive(Debug, Clone)]
+pub struct PlayerInfoEntry {
+    pub uuid: Uuid,
+    pub username: String,
+    pub game_mode: GameMode,
+    pub latency: i32
+}
 
-            dst.write_bool(true).unwrap(); // Full chunk
+/// The action of a player info packet, all players of the packet share the same action.
+#[derive(Debug, Clone)]
+pub enum PlayerInfoAction {
+    AddPlayer(Vec<PlayerInfoEntry>),
+    UpdateGameMode(Vec<(Uuid, GameMode)>),
+    UpdateLatency(Vec<(Uuid, i32)>),
+    RemovePlayer(Vec<Uuid>)
+}
 
-            let mut sub_chunk_mask = 0u64;
-            let mut mask = 1;
-            for (_, sub_chunk) in self.chunk.iter_sub_chunks() {
-                if sub_chunk.is_some() {
-                    sub_chunk_mask |= mask;
+/// Client bound
+pub struct PlayerInfoPacket {
+    pub action: PlayerInfoAction
+}
+
+impl WritablePacket for PlayerInfoPacket {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, _version: ProtocolVersion) -> PacketResult<()> {
+        match &self.action {
+            PlayerInfoAction::AddPlayer(entries) => {
+                dst.write_var_int(0).unwrap();
+                dst.write_var_int(entries.len() as i32).unwrap();
+                for entry in entries {
+                    dst.write_uuid(&entry.uuid).unwrap();
+                    dst.write_string(entry.username.as_str()).unwrap();
+                    dst.write_var_int(0).unwrap(); // No properties
+                    dst.write_var_int(entry.game_mode.get_id() as i32).unwrap();
+                    dst.write_var_int(entry.latency).unwrap();
+                    dst.write_bool(false).unwrap(); // No display name
+                }
+            }
+            PlayerInfoAction::UpdateGameMode(entries) => {
+                dst.write_var_int(1).unwrap();
+                dst.write_var_int(entries.len() as i32).unwrap();
+                for (uuid, game_mode) in entries {
+                    dst.write_uuid(uuid).unwrap();
+                    dst.write_var_int(game_mode.get_id() as i32).unwrap();
+                }
+            }
+            PlayerInfoAction::UpdateLatency(entries) => {
+                dst.write_var_int(2).unwrap();
+                dst.write_var_int(entries.len() as i32).unwrap();
+                for (uuid, latency) in entries {
+                    dst.write_uuid(uuid).unwrap();
+                    dst.write_var_int(*latency).unwrap();
+                }
+            }
+            PlayerInfoAction::RemovePlayer(uuids) => {
+                dst.write_var_int(4).unwrap();
+                dst.write_var_int(uuids.len() as i32).unwrap();
+                for uuid in uuids {
+                    dst.write_uuid(uuid).unwrap();
                 }
-                mask <<= 1;
             }
+        }
+        Ok(())
+    }
+}
 
-            dst.write_var_int(sub_chunk_mask as i32).unwrap();
 
-            let mut nbt = CompoundTag::new();
-            nbt.insert_i64_vec("MOTION_BLOCKING", {
-                let (
-                    byte_size,
-                    it
-                ) = self.chunk.iter_heightmap_raw_columns(&MOTION_BLOCKING).unwrap();
-                it.pack_aligned(byte_size).map(|val| val as i64).collect()
-            });
+/// Client bound
+pub struct EntityMetadataPacket {
+    pub eid: i32,
+    pub metadata: EntityMetadata
+}
 
-            dst.write_nbt(&nbt).unwrap();
+impl WritablePacket for EntityMetadataPacket {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, version: ProtocolVersion) -> PacketResult<()> {
+        dst.write_var_int(self.eid).unwrap();
+        self.metadata.write_metadata(&mut dst, version).unwrap();
+        Ok(())
+    }
+}
 
-            dst.write_var_int(self.chunk.get_biomes_count() as i32).unwrap();
-            for biome in self.chunk.iter_biomes() {
-                dst.write_var_int(biome.get_id()).unwrap();
-            }
 
-            for (cy, sub_chunk) in self.chunk.iter_loaded_sub_chunks() {
-                dst.write_i16(10); // TODO: Replace with real blocks count.
-            }
+/// Client bound
+pub struct SpawnPlayerPacket {
+    pub eid: i32,
+    pub uuid: Uuid,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub yaw: f32,
+    pub pitch: f32
+}
 
-        }
+impl WritablePacket for SpawnPlayerPacket {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, _version: ProtocolVersion) -> PacketResult<()> {
+        dst.write_var_int(self.eid).unwrap();
+        dst.write_uuid(&self.uuid).unwrap();
+        dst.write_f64(self.x).unwrap();
+        dst.write_f64(self.y).unwrap();
+        dst.write_f64(self.z).unwrap();
+        dst.write_u8(to_angle(self.yaw)).unwrap();
+        dst.write_u8(to_angle(self.pitch)).unwrap();
+        Ok(())
+    }
+}
+
+
+/// Client bound, spawn a non-living entity. The object data depends on the entity type, for
+/// example the facing of item frames or the block state of falling blocks.
+pub struct SpawnEntityPacket {
+    pub eid: i32,
+    pub uuid: Uuid,
+    pub entity_type: i32,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub pitch: f32,
+    pub yaw: f32,
+    pub data: i32,
+    /// Velocity in blocks per tick.
+    pub velocity: (f64, f64, f64)
+}
 
+impl WritablePacket for SpawnEntityPacket {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, _version: ProtocolVersion) -> PacketResult<()> {
+        dst.write_var_int(self.eid).unwrap();
+        dst.write_uuid(&self.uuid).unwrap();
+        dst.write_var_int(self.entity_type).unwrap();
+        dst.write_f64(self.x).unwrap();
+        dst.write_f64(self.y).unwrap();
+        dst.write_f64(self.z).unwrap();
+        dst.write_u8(to_angle(self.pitch)).unwrap();
+        dst.write_u8(to_angle(self.yaw)).unwrap();
+        dst.write_i32(self.data).unwrap();
+        write_velocity(&mut dst, self.velocity).unwrap();
         Ok(())
+    }
+}
+
+
+/// Client bound, paintings are spawned at the block they cover with their own packet.
+pub struct SpawnPaintingPacket {
+    pub eid: i32,
+    pub uuid: Uuid,
+    pub motive: i32,
+    /// The center block of the painting, rounded to the left and above for even sizes.
+    pub pos: BlockPos,
+    /// Horizontal facing of the painting.
+    pub facing: Direction
+}
 
+impl WritablePacket for SpawnPaintingPacket {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, _version: ProtocolVersion) -> PacketResult<()> {
+        dst.write_var_int(self.eid).unwrap();
+        dst.write_uuid(&self.uuid).unwrap();
+        dst.write_var_int(self.motive).unwrap();
+        dst.write_block_pos(&self.pos).unwrap();
+        dst.write_u8(match self.facing {
+            Direction::West => 1,
+            Direction::North => 2,
+            Direction::East => 3,
+            _ => 0
+        }).unwrap();
+        Ok(())
     }
 }
+
+
+/// Client bound, spawn a living entity other than a player.
+pub struct SpawnLivingEntityPacket {
+    pub eid: i32,
+    pub uuid: Uuid,
+    pub entity_type: i32,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub head_yaw: f32,
+    /// Velocity in blocks per tick.
+    pub velocity: (f64, f64, f64)
+}
+
+impl WritablePacket for SpawnLivingEntityPacket {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, _version: ProtocolVersion) -> PacketResult<()> {
+        dst.write_var_int(self.eid).unwrap();
+        dst.write_uuid(&self.uuid).unwrap();
+        dst.write_var_int(self.entity_type).unwrap();
+        dst.write_f64(self.x).unwrap();
+        dst.write_f64(self.y).unwrap();
+        dst.write_f64(self.z).unwrap();
+        dst.write_u8(to_angle(self.yaw)).unwrap();
+        dst.write_u8(to_angle(self.pitch)).unwrap();
+        dst.write_u8(to_angle(self.head_yaw)).unwrap();
+        write_velocity(&mut dst, self.velocity).unwrap();
+        Ok(())
+    }
+}
+
+
+/// Client bound
+pub struct DestroyEntitiesPacket {
+    pub eids: Vec<i32>
+}
+
+impl WritablePacket for DestroyEntitiesPacket {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, _version: ProtocolVersion) -> PacketResult<()> {
+        dst.write_var_int(self.eids.len() as i32).unwrap();
+        for &eid in &self.eids {
+            dst.write_var_int(eid).unwrap();
+        }
+        Ok(())
+    }
+}
+
+
+/// Client bound, the passengers of a vehicle, sent again each time the passengers change.
+pub struct SetPassengersPacket {
+    pub eid: i32,
+    pub passengers: Vec<i32>
+}
+
+impl WritablePacket for SetPassengersPacket {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, _version: ProtocolVersion) -> PacketResult<()> {
+        dst.write_var_int(self.eid).unwrap();
+        dst.write_var_int(self.passengers.len() as i32).unwrap();
+        for &eid in &self.passengers {
+            dst.write_var_int(eid).unwrap();
+        }
+        Ok(())
+    }
+}
+
+
+/// Client bound, the entity holding the leash of an entity.
+pub struct AttachEntityPacket {
+    pub eid: i32,
+    /// The holding entity, or none to remove the leash.
+    pub holder: Option<i32>
+}
+
+impl WritablePacket for AttachEntityPacket {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, _version: ProtocolVersion) -> PacketResult<()> {
+        dst.write_i32(self.eid).unwrap();
+        dst.write_i32(self.holder.unwrap_or(-1)).unwrap();
+        Ok(())
+    }
+}
+
+
+/// Internal function to convert an angle in degrees to a protocol angle, in 1/256 of a turn.
+fn to_angle(degrees: f32) -> u8 {
+    (degrees.rem_euclid(360.0) * 256.0 / 360.0) as i32 as u8
+}
+
+/// Internal function to write a velocity in blocks per tick, each axis is clamped like the
+/// vanilla server does and is sent in units of 1/8000 block per tick.
+fn write_velocity<W: Write>(dst: &mut W, (x, y, z): (f64, f64, f64)) -> IoResult<()> {
+    const MAX_VELOCITY: f64 = 3.9;
+    for val in [x, y, z] {
+        dst.write_i16((val.clamp(-MAX_VELOCITY, MAX_VELOCITY) * 8000.0) as i16)?;
+    }
+    Ok(())
+}
+
+
+/// Client bound
+pub struct UnloadChunkPacket {
+    pub cx: i32,
+    pub cz: i32
+}
+
+impl WritablePacket for UnloadChunkPacket {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, _version: ProtocolVersion) -> PacketResult<()> {
+        dst.write_i32(self.cx).unwrap();
+        dst.write_i32(self.cz).unwrap();
+        Ok(())
+    }
+}
+
+
+pub struct UpdateViewPositionPacket {
+    pub cx: i32,
+    pub cz: i32
+}
+
+impl WritablePacket for UpdateViewPositionPacket {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, _version: ProtocolVersion) -> PacketResult<()> {
+        dst.write_var_int(self.cx).unwrap();
+        dst.write_var_int(self.cz).unwrap();
+        Ok(())
+    }
+}
+
+
+pub struct ChunkDataPacket<'a> {
+    chunk: &'a Chunk,
+    parts: Option<u64>
+}
+
+impl<'a> ChunkDataPacket<'a> {
+
+    pub fn new(chunk: &'a Chunk) -> Self {
+        Self {
+            chunk,
+            parts: None
+        }
+    }
+
+}
+
+impl<'a> WritablePacket for ChunkDataPacket<'a> {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, version: ProtocolVersion) -> PacketResult<()> {
+
+        let (cx, cz) = self.chunk.get_position();
+        dst.write_var_int(cx).unwrap();
+        dst.write_var_int(cz).unwrap();
+
+        if self.parts.is_none() {
+
+            // Since 1.17, chunk data is always a full chunk.
+            if version < ProtocolVersion::V1_17_1 {
+                dst.write_bool(true).unwrap(); // Full chunk
+            }
+
+            let mut sub_chunk_mask = 0u64;
+            let mut mask = 1;
+            for (_, sub_chunk) in self.chunk.iter_sub_chunks() {
+                if sub_chunk.is_some_and(|sc| !sc.is_empty()) {
+                    sub_chunk_mask |= mask;
+                }
+                mask <<= 1;
+            }
+
+            if version >= ProtocolVersion::V1_17_1 {
+                dst.write_bit_set(&[sub_chunk_mask]).unwrap();
+            } else {
+                dst.write_var_int(sub_chunk_mask as i32).unwrap();
+            }
+
+            let mut nbt = CompoundTag::new();
+            nbt.insert_i64_vec("MOTION_BLOCKING", {
+                let (
+                    byte_size,
+                    it
+                ) = self.chunk.iter_heightmap_raw_columns(&MOTION_BLOCKING).unwrap();
+                it.pack_aligned(byte_size).map(|val| val as i64).collect()
+            });
+
+            dst.write_nbt(&nbt).unwrap();
+
+            // Biomes are sent at quart resolution, ordered by Y, Z and then X.
+            let height = self.chunk.get_height();
+            dst.write_var_int(self.chunk.get_biomes_count() as i32).unwrap();
+            for qy in (height.get_min_block() >> 2)..=(height.get_max_block() >> 2) {
+                for qz in 0..4 {
+                    for qx in 0..4 {
+                        dst.write_var_int(self.chunk.get_biome_quart(qx, qy, qz).unwrap().get_id()).unwrap();
+                    }
+                }
+            }
+
+            // Empty sub chunks are not sent, the client considers them full of air.
+            for (_, sub_chunk) in self.chunk.iter_loaded_sub_chunks().filter(|(_, sc)| !sc.is_empty()) {
+                dst.write_i16(sub_chunk.non_null_blocks_count() as i16).unwrap();
+            }
+
+        }
+
+        Ok(())
+
+    }
+}
+
+
+/// Client bound, sent before the chunk data packet to give the lights of all sections
+/// from Y -1 to 16 (18 sections), each bit of a mask being a section. Since 1.17, masks
+/// are bit sets and arrays are prefixed by their count.
+///
+/// Loaded sub chunks send their nibble arrays, or set the empty mask if the whole section
+/// is unlit. Missing sub chunks have no block light and are flagged as empty, but their
+/// sky light is left unset so the client derives it from the sections above.
+pub struct UpdateLightPacket<'a> {
+    chunk: &'a Chunk,
+    trust_edges: bool
+}
+
+impl<'a> UpdateLightPacket<'a> {
+
+    /// Number of light sections sent, including one below and above.
+    const SECTIONS_COUNT: i8 = 18;
+
+    pub fn new(chunk: &'a Chunk) -> Self {
+        Self {
+            chunk,
+            trust_edges: true
+        }
+    }
+
+    pub fn with_trust_edges(mut self, trust_edges: bool) -> Self {
+        self.trust_edges = trust_edges;
+        self
+    }
+
+    /// Pack the lights of the given type into a nibble array, returning none if all lights
+    /// are zero.
+    fn pack_lights(sub_chunk: &SubChunk, typ: Light) -> Option<Vec<u8>> {
+        let mut data = vec![0u8; 2048];
+        let mut any = false;
+        for (i, light) in sub_chunk.iter_lights(typ).enumerate() {
+            any |= light != 0;
+            data[i >> 1] |= light << ((i & 1) << 2);
+        }
+        any.then_some(data)
+    }
+
+}
+
+impl<'a> WritablePacket for UpdateLightPacket<'a> {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, version: ProtocolVersion) -> PacketResult<()> {
+
+        let (cx, cz) = self.chunk.get_position();
+        dst.write_var_int(cx).unwrap();
+        dst.write_var_int(cz).unwrap();
+        dst.write_bool(self.trust_edges).unwrap();
+
+        let mut sky_mask = 0i32;
+        let mut block_mask = 0i32;
+        let mut empty_sky_mask = 0i32;
+        let mut empty_block_mask = 0i32;
+        let mut sky_arrays = Vec::new();
+        let mut block_arrays = Vec::new();
+
+        for (bit, cy) in (-1..Self::SECTIONS_COUNT - 1).enumerate() {
+            let mask = 1 << bit;
+            match self.chunk.get_sub_chunk(cy) {
+                Some(sub_chunk) => {
+                    match Self::pack_lights(sub_chunk, Light::Sky) {
+                        Some(data) => {
+                            sky_mask |= mask;
+                            sky_arrays.push(data);
+                        }
+                        None => empty_sky_mask |= mask
+                    }
+                    match Self::pack_lights(sub_chunk, Light::Block) {
+                        Some(data) => {
+                            block_mask |= mask;
+                            block_arrays.push(data);
+                        }
+                        None => empty_block_mask |= mask
+                    }
+                }
+                None => empty_block_mask |= mask
+            }
+        }
+
+        let masks = [sky_mask, block_mask, empty_sky_mask, empty_block_mask];
+        if version >= ProtocolVersion::V1_17_1 {
+            for mask in masks {
+                dst.write_bit_set(&[mask as u64]).unwrap();
+            }
+        } else {
+            for mask in masks {
+                dst.write_var_int(mask).unwrap();
+            }
+        }
+
+        for arrays in [&sky_arrays, &block_arrays] {
+            if version >= ProtocolVersion::V1_17_1 {
+                dst.write_var_int(arrays.len() as i32).unwrap();
+            }
+            for data in arrays {
+                dst.write_var_int(data.len() as i32).unwrap();
+                dst.write_all(&data[..]).unwrap();
+            }
+        }
+
+        Ok(())
+
+    }
+}
+
+
+
+/// An item stack as encoded in packets, the item is given by its network ID.
+#[derive(Debug, Clone)]
+pub struct SlotData {
+    pub item_id: i32,
+    pub count: i8,
+    pub tag: Option<CompoundTag>
+}
+
+/// Internal function to write an optional slot, the tag is replaced by an end tag if absent.
+fn write_slot(dst: &mut Cursor<&mut Vec<u8>>, slot: Option<&SlotData>) -> std::io::Result<()> {
+    dst.write_bool(slot.is_some())?;
+    if let Some(slot) = slot {
+        dst.write_var_int(slot.item_id)?;
+        dst.write_i8(slot.count)?;
+        match &slot.tag {
+            Some(tag) => dst.write_nbt(tag)?,
+            None => dst.write_u8(0)?
+        }
+    }
+    Ok(())
+}
+
+/// Internal function to read an optional slot.
+fn read_slot(src: &mut Cursor<&Vec<u8>>) -> std::io::Result<Option<SlotData>> {
+    if !src.read_bool()? {
+        return Ok(None);
+    }
+    let item_id = src.read_var_int()?;
+    let count = src.read_i8()?;
+    let tag = if src.read_u8()? == 0 {
+        None
+    } else {
+        // The tag type is part of the compound tag, so we go back to read it.
+        src.set_position(src.position() - 1);
+        Some(src.read_nbt()?)
+    };
+    Ok(Some(SlotData { item_id, count, tag }))
+}
+
+
+/// Client bound
+pub struct OpenWindowPacket {
+    pub window_id: u8,
+    pub window_type: i32,
+    pub title: String
+}
+
+impl WritablePacket for OpenWindowPacket {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, _version: ProtocolVersion) -> PacketResult<()> {
+        dst.write_var_int(self.window_id as i32).unwrap();
+        dst.write_var_int(self.window_type).unwrap();
+        dst.write_string(serde_json::json!({ "text": self.title }).to_string().as_str()).unwrap();
+        Ok(())
+    }
+}
+
+
+/// Client bound, the state ID and the carried item are only sent since 1.17.1, before this
+/// version the carried item must be sent with a set slot packet.
+pub struct WindowItemsPacket {
+    pub window_id: u8,
+    pub state_id: i32,
+    pub slots: Vec<Option<SlotData>>,
+    pub carried: Option<SlotData>
+}
+
+impl WritablePacket for WindowItemsPacket {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, version: ProtocolVersion) -> PacketResult<()> {
+        dst.write_u8(self.window_id).unwrap();
+        if version >= ProtocolVersion::V1_17_1 {
+            dst.write_var_int(self.state_id).unwrap();
+            dst.write_var_int(self.slots.len() as i32).unwrap();
+        } else {
+            dst.write_i16(self.slots.len() as i16).unwrap();
+        }
+        for slot in &self.slots {
+            write_slot(&mut dst, slot.as_ref()).unwrap();
+        }
+        if version >= ProtocolVersion::V1_17_1 {
+            write_slot(&mut dst, self.carried.as_ref()).unwrap();
+        }
+        Ok(())
+    }
+}
+
+
+/// Client bound, the state ID is only sent since 1.17.1. The carried item is set using the
+/// window ID -1 and the slot -1.
+pub struct SetSlotPacket {
+    pub window_id: i8,
+    pub state_id: i32,
+    pub slot: i16,
+    pub data: Option<SlotData>
+}
+
+impl SetSlotPacket {
+
+    pub const CARRIED_WINDOW_ID: i8 = -1;
+    pub const CARRIED_SLOT: i16 = -1;
+
+}
+
+impl WritablePacket for SetSlotPacket {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, version: ProtocolVersion) -> PacketResult<()> {
+        dst.write_i8(self.window_id).unwrap();
+        if version >= ProtocolVersion::V1_17_1 {
+            dst.write_var_int(self.state_id).unwrap();
+        }
+        dst.write_i16(self.slot).unwrap();
+        write_slot(&mut dst, self.data.as_ref()).unwrap();
+        Ok(())
+    }
+}
+
+
+/// Client & server bound, used before 1.17 to accept or reject clicks, the client must
+/// answer a rejection with the same packet before its next clicks are accepted. Since 1.17
+/// the ping and pong packets replace it and only carry the action number.
+pub struct WindowConfirmationPacket {
+    pub window_id: i8,
+    pub action: i16,
+    pub accepted: bool
+}
+
+impl WritablePacket for WindowConfirmationPacket {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, version: ProtocolVersion) -> PacketResult<()> {
+        if version >= ProtocolVersion::V1_17_1 {
+            dst.write_i32(self.action as i32).unwrap();
+        } else {
+            dst.write_i8(self.window_id).unwrap();
+            dst.write_i16(self.action).unwrap();
+            dst.write_bool(self.accepted).unwrap();
+        }
+        Ok(())
+    }
+}
+
+impl ReadablePacket for WindowConfirmationPacket {
+    fn read_packet(mut src: Cursor<&Vec<u8>>, version: ProtocolVersion) -> PacketResult<Self> {
+        if version >= ProtocolVersion::V1_17_1 {
+            Ok(Self {
+                window_id: 0,
+                action: src.read_i32()? as i16,
+                accepted: true
+            })
+        } else {
+            Ok(Self {
+                window_id: src.read_i8()?,
+                action: src.read_i16()?,
+                accepted: src.read_bool()?
+            })
+        }
+    }
+}
+
+
+/// Client & server bound
+pub struct CloseWindowPacket {
+    pub window_id: u8
+}
+
+impl WritablePacket for CloseWindowPacket {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, _version: ProtocolVersion) -> PacketResult<()> {
+        dst.write_u8(self.window_id).unwrap();
+        Ok(())
+    }
+}
+
+impl ReadablePacket for CloseWindowPacket {
+    fn read_packet(mut src: Cursor<&Vec<u8>>, _version: ProtocolVersion) -> PacketResult<Self> {
+        Ok(Self {
+            window_id: src.read_u8()?
+        })
+    }
+}
+
+
+/// The mode of a window click, the meaning of the button depends on it.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ClickMode {
+    /// Left (0) or right (1) click.
+    Click,
+    /// Shift + left (0) or right (1) click.
+    ShiftClick,
+    /// Hotbar key, from 0 to 8, or 40 for the offhand.
+    NumberKey,
+    /// Middle click (2).
+    MiddleClick,
+    /// Drop one (0) or the whole stack (1).
+    Drop,
+    /// Painting mode, the button gives the stage and the kind of drag.
+    Drag,
+    /// Double click (0).
+    DoubleClick
+}
+
+impl ClickMode {
+
+    pub fn from_id(id: i32) -> Option<Self> {
+        Some(match id {
+            0 => Self::Click,
+            1 => Self::ShiftClick,
+            2 => Self::NumberKey,
+            3 => Self::MiddleClick,
+            4 => Self::Drop,
+            5 => Self::Drag,
+            6 => Self::DoubleClick,
+            _ => return None
+        })
+    }
+
+}
+
+/// Server bound, the action number is only sent before 1.17, the state ID and the slots
+/// changed by the click are only sent since 1.17.1.
+pub struct ClickWindowPacket {
+    pub window_id: u8,
+    pub state_id: i32,
+    pub slot: i16,
+    pub button: i8,
+    pub action: i16,
+    pub mode: ClickMode,
+    pub changed_slots: Vec<(i16, Option<SlotData>)>,
+    /// Before 1.17, the stack in the clicked slot before the click. Since 1.17, the stack
+    /// carried by the cursor after the click.
+    pub item: Option<SlotData>
+}
+
+impl ReadablePacket for ClickWindowPacket {
+    fn read_packet(mut src: Cursor<&Vec<u8>>, version: ProtocolVersion) -> PacketResult<Self> {
+
+        let window_id = src.read_u8()?;
+        let with_state = version >= ProtocolVersion::V1_17_1;
+        let state_id = if with_state { src.read_var_int()? } else { 0 };
+        let slot = src.read_i16()?;
+        let button = src.read_i8()?;
+        let action = if with_state { 0 } else { src.read_i16()? };
+        let mode = ClickMode::from_id(src.read_var_int()?)
+            .ok_or(PacketError::InvalidField("mode"))?;
+
+        let mut changed_slots = Vec::new();
+        if with_state {
+            let count = src.read_var_int()?;
+            if count < 0 {
+                return Err(PacketError::InvalidField("changed slots"));
+            }
+            for _ in 0..count {
+                changed_slots.push((src.read_i16()?, read_slot(&mut src)?));
+            }
+        }
+
+        Ok(Self {
+            window_id,
+            state_id,
+            slot,
+            button,
+            action,
+            mode,
+            changed_slots,
+            item: read_slot(&mut src)?
+        })
+
+    }
+}
+
+
+/// Client bound, the colors and decorations of a filled map, colors are updated with a
+/// patch of the map.
+pub struct MapDataPacket {
+    pub map_id: i32,
+    pub scale: u8,
+    pub tracking_position: bool,
+    pub locked: bool,
+    /// Decorations replacing the current ones, `None` to keep them. Clients before 1.17
+    /// always replace their decorations.
+    pub decorations: Option<Vec<MapDecoration>>,
+    pub patch: Option<MapPatch>
+}
+
+impl WritablePacket for MapDataPacket {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, version: ProtocolVersion) -> PacketResult<()> {
+        dst.write_var_int(self.map_id).unwrap();
+        dst.write_u8(self.scale).unwrap();
+        if version >= ProtocolVersion::V1_17_1 {
+            dst.write_bool(self.locked).unwrap();
+            dst.write_bool(self.decorations.is_some()).unwrap();
+        } else {
+            dst.write_bool(self.tracking_position).unwrap();
+            dst.write_bool(self.locked).unwrap();
+        }
+        if version < ProtocolVersion::V1_17_1 || self.decorations.is_some() {
+            let decorations = self.decorations.as_deref().unwrap_or(&[]);
+            dst.write_var_int(decorations.len() as i32).unwrap();
+            for decoration in decorations {
+                dst.write_var_int(decoration.kind.get_id() as i32).unwrap();
+                dst.write_i8(decoration.x).unwrap();
+                dst.write_i8(decoration.z).unwrap();
+                dst.write_u8(decoration.rotation & 15).unwrap();
+                // Decorations have no display name.
+                dst.write_bool(false).unwrap();
+            }
+        }
+        match &self.patch {
+            Some(patch) if patch.width > 0 => {
+                dst.write_u8(patch.width).unwrap();
+                dst.write_u8(patch.height).unwrap();
+                dst.write_u8(patch.x).unwrap();
+                dst.write_u8(patch.z).unwrap();
+                dst.write_var_int(patch.colors.len() as i32).unwrap();
+                dst.write_all(&patch.colors).unwrap();
+            }
+            _ => dst.write_u8(0).unwrap()
+        }
+        Ok(())
+    }
+}
+
+
+/// An offer of a merchant as encoded in the trade list packet, the first input is the base
+/// cost before adjusting its price.
+#[derive(Debug, Clone)]
+pub struct TradeData {
+    pub input_a: Option<SlotData>,
+    pub output: Option<SlotData>,
+    pub input_b: Option<SlotData>,
+    pub disabled: bool,
+    pub uses: i32,
+    pub max_uses: i32,
+    pub xp: i32,
+    pub special_price: i32,
+    pub price_multiplier: f32,
+    pub demand: i32
+}
+
+/// Client bound, the offers of the merchant of an opened trading window, it must be sent
+/// after the window is opened.
+pub struct TradeListPacket {
+    pub window_id: u8,
+    pub trades: Vec<TradeData>,
+    pub villager_level: i32,
+    pub xp: i32,
+    /// True to show the level and the experience bar of villagers.
+    pub regular_villager: bool,
+    pub can_restock: bool
+}
+
+impl WritablePacket for TradeListPacket {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, _version: ProtocolVersion) -> PacketResult<()> {
+        if self.trades.len() > u8::MAX as usize {
+            return Err(PacketError::InvalidField("too many trades"));
+        }
+        dst.write_var_int(self.window_id as i32).unwrap();
+        dst.write_u8(self.trades.len() as u8).unwrap();
+        for trade in &self.trades {
+            write_slot(&mut dst, trade.input_a.as_ref()).unwrap();
+            write_slot(&mut dst, trade.output.as_ref()).unwrap();
+            dst.write_bool(trade.input_b.is_some()).unwrap();
+            if trade.input_b.is_some() {
+                write_slot(&mut dst, trade.input_b.as_ref()).unwrap();
+            }
+            dst.write_bool(trade.disabled).unwrap();
+            dst.write_i32(trade.uses).unwrap();
+            dst.write_i32(trade.max_uses).unwrap();
+            dst.write_i32(trade.xp).unwrap();
+            dst.write_i32(trade.special_price).unwrap();
+            dst.write_f32(trade.price_multiplier).unwrap();
+            dst.write_i32(trade.demand).unwrap();
+        }
+        dst.write_var_int(self.villager_level).unwrap();
+        dst.write_var_int(self.xp).unwrap();
+        dst.write_bool(self.regular_villager).unwrap();
+        dst.write_bool(self.can_restock).unwrap();
+        Ok(())
+    }
+}
+
+
+/// Server bound, sent when the player selects an offer in the trading window.
+pub struct SelectTradePacket {
+    pub index: i32
+}
+
+impl ReadablePacket for SelectTradePacket {
+    fn read_packet(mut src: Cursor<&Vec<u8>>, _version: ProtocolVersion) -> PacketResult<Self> {
+        Ok(Self {
+            index: src.read_var_int()?
+        })
+    }
+}
+
+
+/// Client bound, sounds are sent by name so they don't depend on the protocol version.
+pub struct NamedSoundEffectPacket {
+    pub sound: &'static Sound,
+    pub category: SoundCategory,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub volume: f32,
+    pub pitch: f32
+}
+
+impl WritablePacket for NamedSoundEffectPacket {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, _version: ProtocolVersion) -> PacketResult<()> {
+        dst.write_string(self.sound.get_name()).unwrap();
+        dst.write_var_int(self.category.get_id()).unwrap();
+        // Positions are sent as fixed-point numbers with 3 fraction bits.
+        dst.write_i32((self.x * 8.0) as i32).unwrap();
+        dst.write_i32((self.y * 8.0) as i32).unwrap();
+        dst.write_i32((self.z * 8.0) as i32).unwrap();
+        dst.write_f32(self.volume).unwrap();
+        dst.write_f32(self.pitch).unwrap();
+        Ok(())
+    }
+}
+
+
+/// Particle types of 1.16.5, in order of their protocol IDs.
+const PARTICLES_1_16_5: [&str; 72] = [
+    "ambient_entity_effect", "angry_villager", "barrier", "block",
+    "bubble", "cloud", "crit", "damage_indicator",
+    "dragon_breath", "dripping_lava", "falling_lava", "landing_lava",
+    "dripping_water", "falling_water", "dust", "effect",
+    "elder_guardian", "enchanted_hit", "enchant", "end_rod",
+    "entity_effect", "explosion_emitter", "explosion", "falling_dust",
+    "firework", "fishing", "flame", "soul_fire_flame",
+    "soul", "flash", "happy_villager", "composter",
+    "heart", "instant_effect", "item", "item_slime",
+    "item_snowball", "large_smoke", "lava", "mycelium",
+    "note", "poof", "portal", "rain",
+    "smoke", "sneeze", "spit", "squid_ink",
+    "sweep_attack", "totem_of_undying", "underwater", "splash",
+    "witch", "bubble_pop", "current_down", "bubble_column_up",
+    "nautilus", "dolphin", "campfire_cosy_smoke", "campfire_signal_smoke",
+    "dripping_honey", "falling_honey", "landing_honey", "falling_nectar",
+    "ash", "crimson_spore", "warped_spore", "dripping_obsidian_tear",
+    "falling_obsidian_tear", "landing_obsidian_tear", "reverse_portal", "white_ash"
+];
+
+/// Return the protocol ID of a particle for the given version, particles are defined with
+/// their IDs for the latest version. Particles added in 1.17 have no ID for 1.16.5.
+pub fn get_particle_id(particle: &'static Particle, version: ProtocolVersion) -> Option<i32> {
+    if version >= ProtocolVersion::V1_17_1 {
+        Some(particle.get_id())
+    } else {
+        let name = particle.get_name().strip_prefix("minecraft:")?;
+        PARTICLES_1_16_5.iter().position(|&legacy| legacy == name).map(|id| id as i32)
+    }
+}
+
+/// Additional data of particles, the data must match the particle type. Particles requiring
+/// a block state or an item are not supported yet.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ParticleData {
+    None,
+    /// Color components between 0 and 1, and a scale between 0.01 and 4.
+    Dust {
+        red: f32,
+        green: f32,
+        blue: f32,
+        scale: f32
+    }
+}
+
+/// Client bound, nothing is written if the particle doesn't exist in the client's version,
+/// use `get_particle_id` to check it before.
+pub struct ParticlePacket {
+    pub particle: &'static Particle,
+    /// Long distance particles are visible up to 65536 blocks instead of 256.
+    pub long_distance: bool,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub offset_x: f32,
+    pub offset_y: f32,
+    pub offset_z: f32,
+    /// The speed of most particles, or their color for some particles.
+    pub particle_data: f32,
+    pub count: i32,
+    pub data: ParticleData
+}
+
+impl WritablePacket for ParticlePacket {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, version: ProtocolVersion) -> PacketResult<()> {
+        let id = get_particle_id(self.particle, version)
+            .ok_or(PacketError::InvalidField("particle"))?;
+        dst.write_i32(id).unwrap();
+        dst.write_bool(self.long_distance).unwrap();
+        dst.write_f64(self.x).unwrap();
+        dst.write_f64(self.y).unwrap();
+        dst.write_f64(self.z).unwrap();
+        dst.write_f32(self.offset_x).unwrap();
+        dst.write_f32(self.offset_y).unwrap();
+        dst.write_f32(self.offset_z).unwrap();
+        dst.write_f32(self.particle_data).unwrap();
+        dst.write_i32(self.count).unwrap();
+        if let ParticleData::Dust { red, green, blue, scale } = self.data {
+            dst.write_f32(red).unwrap();
+            dst.write_f32(green).unwrap();
+            dst.write_f32(blue).unwrap();
+            dst.write_f32(scale).unwrap();
+        }
+        Ok(())
+    }
+}
+
+/// Server bound, a chat message or a command (starting with a slash) sent by a player.
+pub struct ClientChatMessagePacket {
+    pub message: String
+}
+
+impl ClientChatMessagePacket {
+    /// Maximum length of messages sent by clients.
+    pub const MAX_LENGTH: usize = 256;
+}
+
+impl ReadablePacket for ClientChatMessagePacket {
+    fn read_packet(mut src: Cursor<&Vec<u8>>, _version: ProtocolVersion) -> PacketResult<Self> {
+        let message = src.read_string()?;
+        if message.chars().count() > Self::MAX_LENGTH {
+            Err(PacketError::InvalidField("message"))
+        } else {
+            Ok(Self { message })
+        }
+    }
+}
+
+
+/// Position of a chat message on the client's screen.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ChatPosition {
+    /// A message in the chat box, hidden if the player only shows commands.
+    Chat = 0,
+    /// A system message in the chat box, like command feedbacks.
+    System = 1,
+    /// A message above the hotbar, also known as action bar.
+    GameInfo = 2
+}
+
+
+/// Client bound, a text message shown to the player.
+pub struct ChatMessagePacket {
+    pub message: String,
+    pub position: ChatPosition,
+    /// The UUID of the sender, used by clients to hide messages of blocked players. It's nil
+    /// for system messages.
+    pub sender: Uuid
+}
+
+impl WritablePacket for ChatMessagePacket {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, _version: ProtocolVersion) -> PacketResult<()> {
+        dst.write_string(serde_json::json!({ "text": self.message }).to_string().as_str()).unwrap();
+        dst.write_u8(self.position as u8).unwrap();
+        dst.write_uuid(&self.sender).unwrap();
+        Ok(())
+    }
+}
+
+
+/// Server bound, sent by the client to get suggestions for the command being typed. Clients
+/// only send it for arguments declared with server suggestions.
+pub struct RequestCommandCompletionsPacket {
+    pub transaction_id: i32,
+    /// The command line being typed, with the leading slash.
+    pub text: String
+}
+
+impl ReadablePacket for RequestCommandCompletionsPacket {
+    fn read_packet(mut src: Cursor<&Vec<u8>>, _version: ProtocolVersion) -> PacketResult<Self> {
+        Ok(Self {
+            transaction_id: src.read_var_int()?,
+            text: src.read_string()?
+        })
+    }
+}
+
+
+/// Client bound, answer of `RequestCommandCompletionsPacket` with the same transaction ID.
+/// The start and length of the replaced text are counted in UTF-16 code units.
+pub struct CommandSuggestionsPacket {
+    pub transaction_id: i32,
+    pub start: i32,
+    pub length: i32,
+    pub matches: Vec<String>
+}
+
+impl WritablePacket for CommandSuggestionsPacket {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, _version: ProtocolVersion) -> PacketResult<()> {
+        dst.write_var_int(self.transaction_id).unwrap();
+        dst.write_var_int(self.start).unwrap();
+        dst.write_var_int(self.length).unwrap();
+        dst.write_var_int(self.matches.len() as i32).unwrap();
+        for suggestion in &self.matches {
+            dst.write_string(suggestion.as_str()).unwrap();
+            dst.write_bool(false).unwrap(); // No tooltip
+        }
+        Ok(())
+    }
+}
+
+
+/// Client bound, declare the commands available to the client. Each command is declared as a
+/// literal node followed by a greedy string argument, whose suggestions are requested to the
+/// server with `RequestCommandCompletionsPacket`.
+pub struct DeclareCommandsPacket {
+    pub commands: Vec<String>
+}
+
+impl WritablePacket for DeclareCommandsPacket {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, _version: ProtocolVersion) -> PacketResult<()> {
+
+        const ROOT: u8 = 0x00;
+        const LITERAL: u8 = 0x01;
+        const ARGUMENT: u8 = 0x02;
+        const EXECUTABLE: u8 = 0x04;
+        const HAS_SUGGESTIONS: u8 = 0x10;
+
+        // The root node is followed by the literal and argument nodes of each command.
+        dst.write_var_int(1 + 2 * self.commands.len() as i32).unwrap();
+
+        dst.write_u8(ROOT).unwrap();
+        dst.write_var_int(self.commands.len() as i32).unwrap();
+        for i in 0..self.commands.len() {
+            dst.write_var_int(1 + 2 * i as i32).unwrap();
+        }
+
+        for (i, command) in self.commands.iter().enumerate() {
+
+            dst.write_u8(LITERAL | EXECUTABLE).unwrap();
+            dst.write_var_int(1).unwrap();
+            dst.write_var_int(2 + 2 * i as i32).unwrap();
+            dst.write_string(command.as_str()).unwrap();
+
+            dst.write_u8(ARGUMENT | EXECUTABLE | HAS_SUGGESTIONS).unwrap();
+            dst.write_var_int(0).unwrap();
+            dst.write_string("args").unwrap();
+            dst.write_string("brigadier:string").unwrap();
+            dst.write_var_int(2).unwrap(); // Greedy phrase
+            dst.write_string("minecraft:ask_server").unwrap();
+
+        }
+
+        // Index of the root node.
+        dst.write_var_int(0).unwrap();
+        Ok(())
+
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::source::ChunkLoadRequest;
+    use mc_core::world::level::LevelEnv;
+    use mc_vanilla::ext::VanillaLevelEnv;
+
+    use super::*;
+
+    #[test]
+    fn plugin_message() {
+
+        let mut data = Vec::new();
+        PluginMessage::Register(vec!["bungeecord:main".to_string(), "mod:sync".to_string()])
+            .write_packet(Cursor::new(&mut data), ProtocolVersion::LATEST).unwrap();
+
+        let mut src = Cursor::new(&data);
+        assert_eq!(src.read_string().unwrap(), "minecraft:register");
+        assert_eq!(&data[src.position() as usize..], b"bungeecord:main\0mod:sync");
+
+        match PluginMessage::read_packet(Cursor::new(&data), ProtocolVersion::LATEST).unwrap() {
+            PluginMessage::Register(channels) => assert_eq!(channels, ["bungeecord:main", "mod:sync"]),
+            _ => panic!("expected register message")
+        }
+
+        let mut data = Vec::new();
+        PluginMessage::Brand("vanilla".to_string())
+            .write_packet(Cursor::new(&mut data), ProtocolVersion::LATEST).unwrap();
+        let brand = PluginMessage::read_packet(Cursor::new(&data), ProtocolVersion::LATEST).unwrap();
+        assert_eq!(brand.get_channel(), "minecraft:brand");
+        assert!(matches!(brand, PluginMessage::Brand(ref brand) if brand == "vanilla"));
+
+    }
+
+    #[test]
+    fn sound_and_particle() {
+
+        use mc_vanilla::sound::ENTITY_GENERIC_EXPLODE;
+        use mc_vanilla::particle::{FLAME, GLOW, WHITE_ASH};
+
+        let mut data = Vec::new();
+        NamedSoundEffectPacket {
+            sound: &ENTITY_GENERIC_EXPLODE,
+            category: SoundCategory::Block,
+            x: 1.5,
+            y: -2.0,
+            z: 0.0,
+            volume: 4.0,
+            pitch: 1.0
+        }.write_packet(Cursor::new(&mut data), ProtocolVersion::LATEST).unwrap();
+
+        let mut src = Cursor::new(&data);
+        assert_eq!(src.read_string().unwrap(), "minecraft:entity.generic.explode");
+        assert_eq!(src.read_var_int().unwrap(), 4);
+        assert_eq!(src.read_i32().unwrap(), 12);
+        assert_eq!(src.read_i32().unwrap(), -16);
+
+        assert_eq!(get_particle_id(&FLAME, ProtocolVersion::V1_16_5), Some(26));
+        assert_eq!(get_particle_id(&FLAME, ProtocolVersion::V1_17_1), Some(28));
+        assert_eq!(get_particle_id(&WHITE_ASH, ProtocolVersion::V1_16_5), Some(71));
+        assert_eq!(get_particle_id(&GLOW, ProtocolVersion::V1_16_5), None);
+
+        let mut packet = ParticlePacket {
+            particle: &GLOW,
+            long_distance: false,
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            offset_x: 0.0,
+            offset_y: 0.0,
+            offset_z: 0.0,
+            particle_data: 0.0,
+            count: 1,
+            data: ParticleData::None
+        };
+        assert!(packet.write_packet(Cursor::new(&mut Vec::new()), ProtocolVersion::V1_16_5).is_err());
+        assert!(packet.write_packet(Cursor::new(&mut Vec::new()), ProtocolVersion::V1_17_1).is_ok());
+
+    }
+
+    #[test]
+    fn update_light() {
+
+        let mut chunk = ChunkLoadRequest {
+            env: LevelEnv::vanilla(),
+            height: ChunkHeight::new(0, 15),
+            cx: 2,
+            cz: -3
+        }.build_chunk();
+        let sub_chunk = chunk.ensure_sub_chunk(1).unwrap();
+        sub_chunk.set_light(1, 0, 0, Light::Sky, 15);
+        sub_chunk.set_light(0, 0, 0, Light::Block, 7);
+
+        let mut data = Vec::new();
+        UpdateLightPacket::new(&chunk).write_packet(Cursor::new(&mut data), ProtocolVersion::V1_16_5).unwrap();
+
+        let mut src = Cursor::new(&data);
+        assert_eq!(src.read_var_int().unwrap(), 2);
+        assert_eq!(src.read_var_int().unwrap(), -3);
+        assert!(src.read_bool().unwrap());
+        // Sub chunk 1 is the third section.
+        assert_eq!(src.read_var_int().unwrap(), 0b100);
+        assert_eq!(src.read_var_int().unwrap(), 0b100);
+        assert_eq!(src.read_var_int().unwrap(), 0);
+        assert_eq!(src.read_var_int().unwrap(), 0x3FFFF & !0b100);
+
+        assert_eq!(src.read_var_int().unwrap(), 2048);
+        let mut sky = vec![0; 2048];
+        src.read_exact(&mut sky[..]).unwrap();
+        assert_eq!(sky[0], 0xF0);
+
+        assert_eq!(src.read_var_int().unwrap(), 2048);
+        let mut block = vec![0; 2048];
+        src.read_exact(&mut block[..]).unwrap();
+        assert_eq!(block[0], 0x07);
+        assert!(block[1..].iter().all(|&b| b == 0));
+        assert_eq!(src.position() as usize, data.len());
+
+        let mut data_v17 = Vec::new();
+        UpdateLightPacket::new(&chunk).write_packet(Cursor::new(&mut data_v17), ProtocolVersion::V1_17_1).unwrap();
+
+        let mut src = Cursor::new(&data_v17);
+        assert_eq!(src.read_var_int().unwrap(), 2);
+        assert_eq!(src.read_var_int().unwrap(), -3);
+        assert!(src.read_bool().unwrap());
+        assert_eq!(src.read_bit_set().unwrap(), vec![0b100]);
+        assert_eq!(src.read_bit_set().unwrap(), vec![0b100]);
+        assert!(src.read_bit_set().unwrap().is_empty());
+        assert_eq!(src.read_bit_set().unwrap(), vec![0x3FFFF & !0b100]);
+        assert_eq!(src.read_var_int().unwrap(), 1);
+        assert_eq!(src.read_var_int().unwrap(), 2048);
+        assert_eq!(&data_v17[src.position() as usize..][..2048], &sky[..]);
+        // Same arrays, with their counts.
+        assert_eq!(data_v17.len(), src.position() as usize + 2048 + 1 + 2 + 2048);
+
+    }
+
+    #[test]
+    fn map_data() {
+
+        use mc_vanilla::map::MapDecorationType;
+
+        let mut packet = MapDataPacket {
+            map_id: 3,
+            scale: 2,
+            tracking_position: true,
+            locked: false,
+            decorations: Some(vec![MapDecoration { kind: MapDecorationType::PlayerOffMap, x: -128, z: 10, rotation: 0 }]),
+            patch: Some(MapPatch { x: 4, z: 5, width: 2, height: 1, colors: vec![45, 50] })
+        };
+
+        let mut data = Vec::new();
+        packet.write_packet(Cursor::new(&mut data), ProtocolVersion::V1_16_5).unwrap();
+        assert_eq!(data, [3, 2, 1, 0, 1, 6, 0x80, 10, 0, 0, 2, 1, 4, 5, 2, 45, 50]);
+
+        let mut data = Vec::new();
+        packet.write_packet(Cursor::new(&mut data), ProtocolVersion::V1_17_1).unwrap();
+        assert_eq!(data, [3, 2, 0, 1, 1, 6, 0x80, 10, 0, 0, 2, 1, 4, 5, 2, 45, 50]);
+
+        // Decorations are kept by 1.17 clients, but always replaced by older ones.
+        packet.decorations = None;
+        packet.patch = None;
+        let mut data = Vec::new();
+        packet.write_packet(Cursor::new(&mut data), ProtocolVersion::V1_17_1).unwrap();
+        assert_eq!(data, [3, 2, 0, 0, 0]);
+        let mut data = Vec::new();
+        packet.write_packet(Cursor::new(&mut data), ProtocolVersion::V1_16_5).unwrap();
+        assert_eq!(data, [3, 2, 1, 0, 0, 0]);
+
+    }
+
+}
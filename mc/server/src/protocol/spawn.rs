@@ -0,0 +1,269 @@
+//! Entity spawn packets, built from the components of an entity. Living entities are spawned
+//! with the spawn living entity packet, players with the spawn player packet, paintings with
+//! the spawn painting packet and all others with the spawn entity packet, entity types are
+//! identified by their network IDs.
+//!
+//! Source: https://wiki.vg/index.php?title=Entity_metadata&oldid=16539#Mobs
+
+use std::io::Cursor;
+
+use mc_core::entity::EntityType;
+use mc_core::world::level::BaseEntity;
+use mc_core::pos::EntityPos;
+use mc_vanilla::entity::{VanillaEntity, LivingEntity, PlayerEntity};
+use mc_vanilla::entity::item::ItemEntity;
+use mc_vanilla::entity::vehicle::{Minecart, Boat};
+use mc_vanilla::entity::explosive::PrimedTnt;
+use mc_vanilla::entity::decoration::{HangingEntity, ItemFrameEntity, PaintingEntity};
+
+use super::play::{SpawnEntityPacket, SpawnLivingEntityPacket, SpawnPlayerPacket, SpawnPaintingPacket, face_to_id};
+use super::version::{ProtocolVersion, ClientboundPacket};
+use super::{WritablePacket, PacketResult};
+
+use hecs::{World as EcsWorld, Entity};
+
+
+/// Entity types of 1.16.5, in order of their protocol IDs.
+const ENTITY_TYPES_1_16_5: [&str; 108] = [
+    "area_effect_cloud", "armor_stand", "arrow", "bat", "bee", "blaze", "boat", "cat",
+    "cave_spider", "chicken", "cod", "cow", "creeper", "dolphin", "donkey", "dragon_fireball",
+    "drowned", "elder_guardian", "end_crystal", "ender_dragon", "enderman", "endermite", "evoker", "evoker_fangs",
+    "experience_orb", "eye_of_ender", "falling_block", "firework_rocket", "fox", "ghast", "giant", "guardian",
+    "hoglin", "horse", "husk", "illusioner", "iron_golem", "item", "item_frame", "fireball",
+    "leash_knot", "lightning_bolt", "llama", "llama_spit", "magma_cube", "minecart", "chest_minecart", "command_block_minecart",
+    "furnace_minecart", "hopper_minecart", "spawner_minecart", "tnt_minecart", "mule", "mooshroom", "ocelot", "painting",
+    "panda", "parrot", "phantom", "pig", "piglin", "piglin_brute", "pillager", "polar_bear",
+    "tnt", "pufferfish", "rabbit", "ravager", "salmon", "sheep", "shulker", "shulker_bullet",
+    "silverfish", "skeleton", "skeleton_horse", "slime", "small_fireball", "snow_golem", "snowball", "spectral_arrow",
+    "spider", "squid", "stray", "strider", "egg", "ender_pearl", "experience_bottle", "potion",
+    "trident", "trader_llama", "tropical_fish", "turtle", "vex", "villager", "vindicator", "wandering_trader",
+    "witch", "wither", "wither_skeleton", "wither_skull", "wolf", "zoglin", "zombie", "zombie_horse",
+    "zombie_villager", "zombified_piglin", "player", "fishing_bobber"
+];
+
+/// Entity types of 1.17.1, in order of their protocol IDs.
+const ENTITY_TYPES_1_17_1: [&str; 113] = [
+    "area_effect_cloud", "armor_stand", "arrow", "axolotl", "bat", "bee", "blaze", "boat",
+    "cat", "cave_spider", "chicken", "cod", "cow", "creeper", "dolphin", "donkey",
+    "dragon_fireball", "drowned", "elder_guardian", "end_crystal", "ender_dragon", "enderman", "endermite", "evoker",
+    "evoker_fangs", "experience_orb", "eye_of_ender", "falling_block", "firework_rocket", "fox", "ghast", "giant",
+    "glow_item_frame", "glow_squid", "goat", "guardian", "hoglin", "horse", "husk", "illusioner",
+    "iron_golem", "item", "item_frame", "fireball", "leash_knot", "lightning_bolt", "llama", "llama_spit",
+    "magma_cube", "marker", "minecart", "chest_minecart", "command_block_minecart", "furnace_minecart", "hopper_minecart", "spawner_minecart",
+    "tnt_minecart", "mule", "mooshroom", "ocelot", "painting", "panda", "parrot", "phantom",
+    "pig", "piglin", "piglin_brute", "pillager", "polar_bear", "tnt", "pufferfish", "rabbit",
+    "ravager", "salmon", "sheep", "shulker", "shulker_bullet", "silverfish", "skeleton", "skeleton_horse",
+    "slime", "small_fireball", "snow_golem", "snowball", "spectral_arrow", "spider", "squid", "stray",
+    "strider", "egg", "ender_pearl", "experience_bottle", "potion", "trident", "trader_llama", "tropical_fish",
+    "turtle", "vex", "villager", "vindicator", "wandering_trader", "witch", "wither", "wither_skeleton",
+    "wither_skull", "wolf", "zoglin", "zombie", "zombie_horse", "zombie_villager", "zombified_piglin", "player",
+    "fishing_bobber"
+];
+
+/// Return the protocol ID of an entity type for the given version, entity types added in
+/// 1.17 have no ID for 1.16.5.
+pub fn get_entity_type_id(entity_type: &'static EntityType, version: ProtocolVersion) -> Option<i32> {
+    let name = entity_type.name.strip_prefix("minecraft:")?;
+    let types: &[&str] = match version {
+        ProtocolVersion::V1_16_5 => &ENTITY_TYPES_1_16_5,
+        ProtocolVersion::V1_17_1 => &ENTITY_TYPES_1_17_1
+    };
+    types.iter().position(|&typ| typ == name).map(|id| id as i32)
+}
+
+
+/// A spawn packet of one of the three entity spawn packets.
+pub enum EntitySpawn {
+    Entity(SpawnEntityPacket),
+    Living(SpawnLivingEntityPacket),
+    Player(SpawnPlayerPacket),
+    Painting(SpawnPaintingPacket)
+}
+
+impl EntitySpawn {
+
+    /// Return the high level identifier of the packet to send.
+    pub fn get_packet(&self) -> ClientboundPacket {
+        match self {
+            Self::Entity(_) => ClientboundPacket::SpawnEntity,
+            Self::Living(_) => ClientboundPacket::SpawnLivingEntity,
+            Self::Player(_) => ClientboundPacket::SpawnPlayer,
+            Self::Painting(_) => ClientboundPacket::SpawnPainting
+        }
+    }
+
+}
+
+impl WritablePacket for EntitySpawn {
+    fn write_packet(&mut self, dst: Cursor<&mut Vec<u8>>, version: ProtocolVersion) -> PacketResult<()> {
+        match self {
+            Self::Entity(packet) => packet.write_packet(dst, version),
+            Self::Living(packet) => packet.write_packet(dst, version),
+            Self::Player(packet) => packet.write_packet(dst, version),
+            Self::Painting(packet) => packet.write_packet(dst, version)
+        }
+    }
+}
+
+
+/// Internal function to get the velocity of an entity in blocks per tick, entities moved by
+/// a vanilla system store their velocity in their own component.
+fn get_velocity(ecs: &EcsWorld, entity: Entity) -> EntityPos {
+    if let Ok(item) = ecs.get::<ItemEntity>(entity) {
+        item.velocity.clone()
+    } else if let Ok(minecart) = ecs.get::<Minecart>(entity) {
+        minecart.velocity.clone()
+    } else if let Ok(boat) = ecs.get::<Boat>(entity) {
+        boat.velocity.clone()
+    } else if let Ok(tnt) = ecs.get::<PrimedTnt>(entity) {
+        tnt.velocity.clone()
+    } else if let Ok(base) = ecs.get::<VanillaEntity>(entity) {
+        base.get_motion().clone()
+    } else {
+        EntityPos::default()
+    }
+}
+
+
+/// Build the spawn packet of an entity from its components, the packet is chosen from the
+/// `LivingEntity`, `PlayerEntity` and `PaintingEntity` components. None is returned if the
+/// entity type has no ID in the given version, or for experience orbs because they use their
+/// own spawn packet that is not supported yet.
+pub fn build_entity_spawn(ecs: &EcsWorld, entity: Entity, version: ProtocolVersion) -> Option<EntitySpawn> {
+
+    let base = ecs.get::<BaseEntity>(entity).ok()?;
+    let (yaw, pitch) = ecs.get::<VanillaEntity>(entity)
+        .map_or((0.0, 0.0), |base| (base.get_rotation_yaw(), base.get_rotation_pitch()));
+
+    let eid = entity.id() as i32;
+    let pos = &base.pos;
+
+    if ecs.get::<PlayerEntity>(entity).is_ok() {
+        return Some(EntitySpawn::Player(SpawnPlayerPacket {
+            eid,
+            uuid: base.uuid,
+            x: pos.x,
+            y: pos.y,
+            z: pos.z,
+            yaw,
+            pitch
+        }));
+    }
+
+    if let (Ok(painting), Ok(hanging)) = (ecs.get::<PaintingEntity>(entity), ecs.get::<HangingEntity>(entity)) {
+        return Some(EntitySpawn::Painting(SpawnPaintingPacket {
+            eid,
+            uuid: base.uuid,
+            motive: painting.get_motive().get_id(),
+            pos: hanging.get_tile().clone(),
+            facing: painting.get_facing()
+        }));
+    }
+
+    if base.entity_type.name == "minecraft:experience_orb" {
+        return None;
+    }
+
+    let entity_type = get_entity_type_id(base.entity_type, version)?;
+    let velocity = get_velocity(ecs, entity);
+    let velocity = (velocity.x, velocity.y, velocity.z);
+
+    if ecs.get::<LivingEntity>(entity).is_ok() {
+        // The head rotation is not tracked yet, so the head looks in front of the body.
+        Some(EntitySpawn::Living(SpawnLivingEntityPacket {
+            eid,
+            uuid: base.uuid,
+            entity_type,
+            x: pos.x,
+            y: pos.y,
+            z: pos.z,
+            yaw,
+            pitch,
+            head_yaw: yaw,
+            velocity
+        }))
+    } else {
+        // Object data is also meaningful for falling blocks, fishing bobbers and projectiles,
+        // whose block state and owner components don't exist yet, zero is also the vanilla
+        // default for them.
+        let data = ecs.get::<ItemFrameEntity>(entity).map_or(0, |frame| face_to_id(frame.get_facing()));
+        Some(EntitySpawn::Entity(SpawnEntityPacket {
+            eid,
+            uuid: base.uuid,
+            entity_type,
+            x: pos.x,
+            y: pos.y,
+            z: pos.z,
+            pitch,
+            yaw,
+            data,
+            velocity
+        }))
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_core::item::ItemStack;
+    use mc_vanilla::entity::{PIG, AXOLOTL, ITEM, PAINTING};
+    use uuid::Uuid;
+
+    use super::*;
+
+    mc_core::items!(TEST_ITEMS "test" [
+        STONE "stone"
+    ]);
+
+    fn spawn(ecs: &mut EcsWorld, entity_type: &'static EntityType) -> Entity {
+        let mut builder = hecs::EntityBuilder::new();
+        builder.add(BaseEntity::new(entity_type, Uuid::nil(), EntityPos::new(1.0, 2.0, 3.0)));
+        for &codec in entity_type.codecs {
+            codec.default(&mut builder);
+        }
+        ecs.spawn(builder.build())
+    }
+
+    #[test]
+    fn entity_spawn() {
+
+        let mut ecs = EcsWorld::new();
+
+        let pig = spawn(&mut ecs, &PIG);
+        assert_eq!(get_entity_type_id(&PIG, ProtocolVersion::V1_16_5), Some(59));
+        assert_eq!(get_entity_type_id(&PIG, ProtocolVersion::V1_17_1), Some(64));
+        match build_entity_spawn(&ecs, pig, ProtocolVersion::V1_17_1) {
+            Some(EntitySpawn::Living(packet)) => assert_eq!(packet.entity_type, 64),
+            _ => panic!("expected a living entity spawn")
+        }
+
+        // Axolotls were added in 1.17 and paintings have their own packet.
+        let axolotl = spawn(&mut ecs, &AXOLOTL);
+        assert!(build_entity_spawn(&ecs, axolotl, ProtocolVersion::V1_16_5).is_none());
+        assert!(build_entity_spawn(&ecs, axolotl, ProtocolVersion::V1_17_1).is_some());
+        let painting = spawn(&mut ecs, &PAINTING);
+        let painting_spawn = build_entity_spawn(&ecs, painting, ProtocolVersion::V1_17_1).unwrap();
+        assert_eq!(painting_spawn.get_packet(), ClientboundPacket::SpawnPainting);
+
+        let item = spawn(&mut ecs, &ITEM);
+        let velocity = EntityPos::new(0.5, 10.0, -0.25);
+        ecs.insert_one(item, ItemEntity::new(ItemStack::new(&STONE, 1, None)).with_velocity(velocity)).unwrap();
+
+        let mut spawn = build_entity_spawn(&ecs, item, ProtocolVersion::V1_16_5).unwrap();
+        assert_eq!(spawn.get_packet(), ClientboundPacket::SpawnEntity);
+
+        let mut data = Vec::new();
+        spawn.write_packet(Cursor::new(&mut data), ProtocolVersion::V1_16_5).unwrap();
+        // Velocity is the last field, with the Y axis clamped to 3.9 blocks per tick.
+        let velocity: Vec<i16> = data[data.len() - 6..].chunks(2)
+            .map(|raw| i16::from_be_bytes([raw[0], raw[1]]))
+            .collect();
+        assert_eq!(velocity, vec![4000, 31200, -2000]);
+        // The entity type ID follows the entity ID and the UUID.
+        assert_eq!(data[17], 37);
+
+    }
+
+}
@@ -1,4 +1,5 @@
 use super::{ReadablePacket, WritablePacket, PacketResult};
+use super::version::ProtocolVersion;
 use crate::packet::serial::*;
 
 use std::io::Cursor;
@@ -8,7 +9,7 @@ use std::io::Cursor;
 pub struct RequestStatusPacket;
 
 impl ReadablePacket for RequestStatusPacket {
-    fn read_packet(_src: Cursor<&Vec<u8>>) -> PacketResult<Self> {
+    fn read_packet(_src: Cursor<&Vec<u8>>, _version: ProtocolVersion) -> PacketResult<Self> {
         Ok(Self)
     }
 }
@@ -24,7 +25,7 @@ pub struct StatusPacket {
 }
 
 impl WritablePacket for StatusPacket {
-    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>) -> PacketResult<()> {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, _version: ProtocolVersion) -> PacketResult<()> {
 
         let payload = serde_json::json!({
             "version": {
@@ -64,7 +65,7 @@ impl PingPacket {
 }
 
 impl ReadablePacket for PingPacket {
-    fn read_packet(mut src: Cursor<&Vec<u8>>) -> PacketResult<Self> {
+    fn read_packet(mut src: Cursor<&Vec<u8>>, _version: ProtocolVersion) -> PacketResult<Self> {
         Ok(Self {
             token: src.read_i64()?
         })
@@ -77,7 +78,7 @@ pub struct PongPacket {
 }
 
 impl WritablePacket for PongPacket {
-    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>) -> PacketResult<()> {
+    fn write_packet(&mut self, mut dst: Cursor<&mut Vec<u8>>, _version: ProtocolVersion) -> PacketResult<()> {
         dst.write_i64(self.token)?;
         Ok(())
     }
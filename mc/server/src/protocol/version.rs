@@ -0,0 +1,366 @@
+//! Protocol versions supported by the server, packet IDs and codec details are changing
+//! between versions, so high level packets are identified with `ClientboundPacket` and
+//! `ServerboundPacket` and their actual IDs are resolved from the client's version.
+//!
+//! Sources:
+//! - 1.16.5: https://wiki.vg/index.php?title=Protocol&oldid=16681
+//! - 1.17.1: https://wiki.vg/index.php?title=Protocol&oldid=16918
+
+use super::ClientState;
+
+
+/// A protocol version supported by the server.
+#[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum ProtocolVersion {
+    V1_16_5,
+    V1_17_1
+}
+
+impl ProtocolVersion {
+
+    /// All supported versions, from the oldest to the latest.
+    pub const ALL: [ProtocolVersion; 2] = [Self::V1_16_5, Self::V1_17_1];
+
+    /// The latest supported version.
+    pub const LATEST: ProtocolVersion = Self::V1_17_1;
+
+    /// Return the version from its protocol number, as sent by clients in the handshake.
+    pub fn from_id(id: i32) -> Option<Self> {
+        Self::ALL.iter().copied().find(|version| version.get_id() == id)
+    }
+
+    /// Return the protocol number of this version.
+    pub fn get_id(self) -> i32 {
+        match self {
+            Self::V1_16_5 => 754,
+            Self::V1_17_1 => 756
+        }
+    }
+
+    /// Return the game version name of this protocol version.
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Self::V1_16_5 => "1.16.5",
+            Self::V1_17_1 => "1.17.1"
+        }
+    }
+
+    /// Return the ID of the given client bound packet for this version.
+    pub fn get_clientbound_id(self, packet: ClientboundPacket) -> u16 {
+        use ClientboundPacket::*;
+        match self {
+            Self::V1_16_5 => match packet {
+                StatusResponse => 0x00,
+                Pong => 0x01,
+                LoginDisconnect => 0x00,
+                LoginSuccess => 0x02,
+                PluginMessage => 0x17,
+                ChunkData => 0x20,
+                UpdateLight => 0x23,
+                JoinGame => 0x24,
+                PlayerAbilities => 0x30,
+                PlayerInfo => 0x32,
+                PlayerPosAndLook => 0x34,
+                UpdateViewPosition => 0x40,
+                SpawnPosition => 0x42,
+                EntityMetadata => 0x44,
+                SpawnEntity => 0x00,
+                SpawnLivingEntity => 0x02,
+                SpawnPlayer => 0x04,
+                DestroyEntities => 0x36,
+                UnloadChunk => 0x1C,
+                OpenWindow => 0x2D,
+                WindowItems => 0x13,
+                SetSlot => 0x15,
+                WindowConfirmation => 0x11,
+                CloseWindow => 0x12,
+                NamedSoundEffect => 0x18,
+                Particle => 0x22,
+                CommandSuggestions => 0x0F,
+                DeclareCommands => 0x10,
+                ChatMessage => 0x0E,
+                ChangeGameState => 0x1D,
+                Respawn => 0x39,
+                DeathCombatEvent => 0x31,
+                TradeList => 0x26,
+                MapData => 0x25,
+                SetPassengers => 0x4B,
+                SpawnPainting => 0x03,
+                AttachEntity => 0x45
+            },
+            Self::V1_17_1 => match packet {
+                StatusResponse => 0x00,
+                Pong => 0x01,
+                LoginDisconnect => 0x00,
+                LoginSuccess => 0x02,
+                PluginMessage => 0x18,
+                ChunkData => 0x22,
+                UpdateLight => 0x25,
+                JoinGame => 0x26,
+                PlayerAbilities => 0x32,
+                PlayerInfo => 0x36,
+                PlayerPosAndLook => 0x38,
+                UpdateViewPosition => 0x49,
+                SpawnPosition => 0x4B,
+                EntityMetadata => 0x4D,
+                SpawnEntity => 0x00,
+                SpawnLivingEntity => 0x02,
+                SpawnPlayer => 0x04,
+                DestroyEntities => 0x3A,
+                UnloadChunk => 0x1D,
+                OpenWindow => 0x2E,
+                WindowItems => 0x14,
+                SetSlot => 0x16,
+                // Replaced by the ping packet.
+                WindowConfirmation => 0x30,
+                CloseWindow => 0x13,
+                NamedSoundEffect => 0x19,
+                Particle => 0x24,
+                CommandSuggestions => 0x11,
+                DeclareCommands => 0x12,
+                ChatMessage => 0x0F,
+                ChangeGameState => 0x1E,
+                Respawn => 0x3D,
+                DeathCombatEvent => 0x35,
+                TradeList => 0x28,
+                MapData => 0x27,
+                SetPassengers => 0x54,
+                SpawnPainting => 0x03,
+                AttachEntity => 0x4E
+            }
+        }
+    }
+
+    /// Return the ID of the given server bound packet for this version.
+    pub fn get_serverbound_id(self, packet: ServerboundPacket) -> u16 {
+        use ServerboundPacket::*;
+        match self {
+            Self::V1_16_5 => match packet {
+                Handshake => 0x00,
+                StatusRequest => 0x00,
+                Ping => 0x01,
+                LoginStart => 0x00,
+                PluginMessage => 0x0B,
+                PlayerPosition => 0x12,
+                PlayerPositionAndRotation => 0x13,
+                PlayerRotation => 0x14,
+                WindowConfirmation => 0x07,
+                ClickWindow => 0x09,
+                CloseWindow => 0x0A,
+                RequestCommandCompletions => 0x06,
+                ChatMessage => 0x03,
+                PlayerAbilities => 0x1A,
+                PlayerDigging => 0x1B,
+                HeldItemChange => 0x25,
+                PlayerBlockPlacement => 0x2E,
+                ClientStatus => 0x04,
+                InteractEntity => 0x0E,
+                SelectTrade => 0x23,
+                SteerVehicle => 0x1D
+            },
+            Self::V1_17_1 => match packet {
+                Handshake => 0x00,
+                StatusRequest => 0x00,
+                Ping => 0x01,
+                LoginStart => 0x00,
+                PluginMessage => 0x0A,
+                PlayerPosition => 0x11,
+                PlayerPositionAndRotation => 0x12,
+                PlayerRotation => 0x13,
+                // Replaced by the pong packet.
+                WindowConfirmation => 0x1D,
+                ClickWindow => 0x08,
+                CloseWindow => 0x09,
+                RequestCommandCompletions => 0x06,
+                ChatMessage => 0x03,
+                PlayerAbilities => 0x19,
+                PlayerDigging => 0x1A,
+                HeldItemChange => 0x25,
+                PlayerBlockPlacement => 0x2E,
+                ClientStatus => 0x04,
+                InteractEntity => 0x0D,
+                SelectTrade => 0x23,
+                SteerVehicle => 0x1C
+            }
+        }
+    }
+
+    /// Return the server bound packet received with the given ID in the given state, or
+    /// none if this packet is unknown to the server.
+    pub fn get_serverbound_packet(self, state: ClientState, id: u16) -> Option<ServerboundPacket> {
+        ServerboundPacket::ALL.iter()
+            .copied()
+            .find(|&packet| packet.get_state() == state && self.get_serverbound_id(packet) == id)
+    }
+
+}
+
+
+/// High level identifiers of packets sent by the server.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ClientboundPacket {
+    // Status
+    StatusResponse,
+    Pong,
+    // Login
+    LoginDisconnect,
+    LoginSuccess,
+    // Play
+    PluginMessage,
+    ChunkData,
+    UpdateLight,
+    JoinGame,
+    PlayerAbilities,
+    PlayerInfo,
+    PlayerPosAndLook,
+    UpdateViewPosition,
+    SpawnPosition,
+    EntityMetadata,
+    SpawnEntity,
+    SpawnLivingEntity,
+    SpawnPlayer,
+    DestroyEntities,
+    UnloadChunk,
+    OpenWindow,
+    WindowItems,
+    SetSlot,
+    WindowConfirmation,
+    CloseWindow,
+    NamedSoundEffect,
+    Particle,
+    CommandSuggestions,
+    DeclareCommands,
+    ChatMessage,
+    ChangeGameState,
+    Respawn,
+    /// The combat event packet before 1.17.
+    DeathCombatEvent,
+    TradeList,
+    MapData,
+    SetPassengers,
+    SpawnPainting,
+    AttachEntity
+}
+
+
+/// High level identifiers of packets sent by clients.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ServerboundPacket {
+    // Handshake
+    Handshake,
+    // Status
+    StatusRequest,
+    Ping,
+    // Login
+    LoginStart,
+    // Play
+    PluginMessage,
+    PlayerPosition,
+    PlayerPositionAndRotation,
+    PlayerRotation,
+    WindowConfirmation,
+    ClickWindow,
+    CloseWindow,
+    RequestCommandCompletions,
+    ChatMessage,
+    PlayerAbilities,
+    PlayerDigging,
+    HeldItemChange,
+    PlayerBlockPlacement,
+    ClientStatus,
+    InteractEntity,
+    SelectTrade,
+    SteerVehicle
+}
+
+impl ServerboundPacket {
+
+    pub const ALL: [ServerboundPacket; 21] = [
+        Self::Handshake,
+        Self::StatusRequest,
+        Self::Ping,
+        Self::LoginStart,
+        Self::PluginMessage,
+        Self::PlayerPosition,
+        Self::PlayerPositionAndRotation,
+        Self::PlayerRotation,
+        Self::WindowConfirmation,
+        Self::ClickWindow,
+        Self::CloseWindow,
+        Self::RequestCommandCompletions,
+        Self::ChatMessage,
+        Self::PlayerAbilities,
+        Self::PlayerDigging,
+        Self::HeldItemChange,
+        Self::PlayerBlockPlacement,
+        Self::ClientStatus,
+        Self::InteractEntity,
+        Self::SelectTrade,
+        Self::SteerVehicle
+    ];
+
+    /// Return the client state in which this packet can be received.
+    pub fn get_state(self) -> ClientState {
+        match self {
+            Self::Handshake => ClientState::Handshake,
+            Self::StatusRequest | Self::Ping => ClientState::Status,
+            Self::LoginStart => ClientState::Login,
+            Self::PluginMessage |
+            Self::PlayerPosition |
+            Self::PlayerPositionAndRotation |
+            Self::PlayerRotation |
+            Self::WindowConfirmation |
+            Self::ClickWindow |
+            Self::CloseWindow |
+            Self::RequestCommandCompletions |
+            Self::ChatMessage |
+            Self::PlayerAbilities |
+            Self::PlayerDigging |
+            Self::HeldItemChange |
+            Self::PlayerBlockPlacement |
+            Self::ClientStatus |
+            Self::InteractEntity |
+            Self::SelectTrade |
+            Self::SteerVehicle => ClientState::Play
+        }
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn packet_ids() {
+
+        assert_eq!(ProtocolVersion::from_id(754), Some(ProtocolVersion::V1_16_5));
+        assert_eq!(ProtocolVersion::from_id(756), Some(ProtocolVersion::V1_17_1));
+        assert_eq!(ProtocolVersion::from_id(755), None);
+
+        assert_eq!(ProtocolVersion::V1_16_5.get_clientbound_id(ClientboundPacket::JoinGame), 0x24);
+        assert_eq!(ProtocolVersion::V1_17_1.get_clientbound_id(ClientboundPacket::JoinGame), 0x26);
+
+        // The same ID has different meanings depending on the state.
+        for &version in &ProtocolVersion::ALL {
+            assert_eq!(version.get_serverbound_packet(ClientState::Handshake, 0x00), Some(ServerboundPacket::Handshake));
+            assert_eq!(version.get_serverbound_packet(ClientState::Login, 0x00), Some(ServerboundPacket::LoginStart));
+            assert_eq!(version.get_serverbound_packet(ClientState::Play, 0x00), None);
+        }
+
+        assert_eq!(ProtocolVersion::V1_16_5.get_serverbound_packet(ClientState::Play, 0x0B), Some(ServerboundPacket::PluginMessage));
+        assert_eq!(ProtocolVersion::V1_17_1.get_serverbound_packet(ClientState::Play, 0x0A), Some(ServerboundPacket::PluginMessage));
+        assert_eq!(ProtocolVersion::V1_16_5.get_serverbound_packet(ClientState::Play, 0x13), Some(ServerboundPacket::PlayerPositionAndRotation));
+        assert_eq!(ProtocolVersion::V1_17_1.get_serverbound_packet(ClientState::Play, 0x13), Some(ServerboundPacket::PlayerRotation));
+        assert_eq!(ProtocolVersion::V1_16_5.get_serverbound_packet(ClientState::Play, 0x09), Some(ServerboundPacket::ClickWindow));
+        assert_eq!(ProtocolVersion::V1_17_1.get_serverbound_packet(ClientState::Play, 0x08), Some(ServerboundPacket::ClickWindow));
+        assert_eq!(ProtocolVersion::V1_17_1.get_serverbound_packet(ClientState::Play, 0x04), Some(ServerboundPacket::ClientStatus));
+        assert_eq!(ProtocolVersion::V1_16_5.get_serverbound_packet(ClientState::Play, 0x2E), Some(ServerboundPacket::PlayerBlockPlacement));
+        assert_eq!(ProtocolVersion::V1_16_5.get_serverbound_packet(ClientState::Play, 0x0E), Some(ServerboundPacket::InteractEntity));
+        assert_eq!(ProtocolVersion::V1_17_1.get_serverbound_packet(ClientState::Play, 0x0D), Some(ServerboundPacket::InteractEntity));
+
+    }
+
+}
@@ -0,0 +1,530 @@
+//! Whitelist, operators and ban lists of the server. These lists are stored in the same
+//! JSON files as vanilla servers: `whitelist.json`, `ops.json`, `banned-players.json` and
+//! `banned-ips.json`. The `AccessLists` component is consulted when players log in.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::net::IpAddr;
+use std::path::Path;
+use std::{fs, io};
+
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+
+pub const WHITELIST_FILE: &str = "whitelist.json";
+pub const OPS_FILE: &str = "ops.json";
+pub const BANNED_PLAYERS_FILE: &str = "banned-players.json";
+pub const BANNED_IPS_FILE: &str = "banned-ips.json";
+
+/// Default permission level of operators.
+pub const DEFAULT_OP_LEVEL: u8 = 4;
+
+/// Default source and reason of bans, the same as vanilla.
+const DEFAULT_BAN_SOURCE: &str = "Server";
+const DEFAULT_BAN_REASON: &str = "Banned by an operator.";
+/// Expiration of permanent bans in files.
+const BAN_FOREVER: &str = "forever";
+
+
+/// A player profile stored in the lists.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserProfile {
+    pub uuid: Uuid,
+    pub name: String
+}
+
+impl UserProfile {
+
+    pub fn new(uuid: Uuid, name: String) -> Self {
+        Self { uuid, name }
+    }
+
+    /// Return true if this is the profile of the given player. The UUID is only compared if
+    /// known because offline players have no stable UUID, names are case-insensitive.
+    pub fn is_player(&self, uuid: Option<Uuid>, name: &str) -> bool {
+        uuid == Some(self.uuid) || self.name.eq_ignore_ascii_case(name)
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "uuid": self.uuid.to_string(),
+            "name": self.name
+        })
+    }
+
+    fn from_json(value: &Value) -> Option<Self> {
+        Some(Self {
+            uuid: Uuid::parse_str(value.get("uuid")?.as_str()?).ok()?,
+            name: value.get("name")?.as_str()?.to_string()
+        })
+    }
+
+}
+
+
+/// An operator of the server with its permission level, from 1 to 4.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpEntry {
+    pub profile: UserProfile,
+    pub level: u8,
+    pub bypasses_player_limit: bool
+}
+
+impl OpEntry {
+
+    fn to_json(&self) -> Value {
+        let mut value = self.profile.to_json();
+        value["level"] = json!(self.level);
+        value["bypassesPlayerLimit"] = json!(self.bypasses_player_limit);
+        value
+    }
+
+    fn from_json(value: &Value) -> Option<Self> {
+        Some(Self {
+            profile: UserProfile::from_json(value)?,
+            level: value.get("level").and_then(Value::as_u64).map_or(DEFAULT_OP_LEVEL, |level| level as u8),
+            bypasses_player_limit: value.get("bypassesPlayerLimit").and_then(Value::as_bool).unwrap_or(false)
+        })
+    }
+
+}
+
+
+/// Details of a ban, dates are UNIX timestamps in seconds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BanEntry {
+    created: i64,
+    source: String,
+    expires: Option<i64>,
+    reason: String
+}
+
+impl BanEntry {
+
+    /// Create a permanent ban starting now, with the default source and reason.
+    pub fn new() -> Self {
+        Self {
+            created: current_time(),
+            source: DEFAULT_BAN_SOURCE.to_string(),
+            expires: None,
+            reason: DEFAULT_BAN_REASON.to_string()
+        }
+    }
+
+    pub fn with_source(mut self, source: String) -> Self {
+        self.source = source;
+        self
+    }
+
+    pub fn with_reason(mut self, reason: String) -> Self {
+        self.reason = reason;
+        self
+    }
+
+    /// Set the time at which the ban expires.
+    pub fn with_expires(mut self, expires: i64) -> Self {
+        self.expires = Some(expires);
+        self
+    }
+
+    pub fn get_created(&self) -> i64 {
+        self.created
+    }
+
+    pub fn get_source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn get_expires(&self) -> Option<i64> {
+        self.expires
+    }
+
+    pub fn get_reason(&self) -> &str {
+        &self.reason
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+
+    /// Internal method to get the message shown to banned players.
+    fn get_message(&self, banned: &str) -> String {
+        let mut message = format!("{}\nReason: {}", banned, self.reason);
+        if let Some(expires) = self.expires {
+            message.push_str(&format!("\nYour ban will be removed on {}", format_date(expires)));
+        }
+        message
+    }
+
+    fn write_json(&self, value: &mut Value) {
+        value["created"] = json!(format_date(self.created));
+        value["source"] = json!(self.source);
+        value["expires"] = json!(self.expires.map_or_else(|| BAN_FOREVER.to_string(), format_date));
+        value["reason"] = json!(self.reason);
+    }
+
+    fn from_json(value: &Value) -> Self {
+        let get_str = |key: &str| value.get(key).and_then(Value::as_str);
+        Self {
+            created: get_str("created").and_then(parse_date).unwrap_or(0),
+            source: get_str("source").unwrap_or(DEFAULT_BAN_SOURCE).to_string(),
+            expires: get_str("expires").and_then(parse_date),
+            reason: get_str("reason").unwrap_or(DEFAULT_BAN_REASON).to_string()
+        }
+    }
+
+}
+
+impl Default for BanEntry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// A world component holding the whitelist, operators and ban lists. Mutators only change
+/// the lists in memory, `save` must be called to write them back to their files.
+#[derive(Debug, Default)]
+pub struct AccessLists {
+    whitelist_enabled: bool,
+    whitelist: Vec<UserProfile>,
+    ops: Vec<OpEntry>,
+    banned_players: Vec<(UserProfile, BanEntry)>,
+    banned_ips: Vec<(IpAddr, BanEntry)>
+}
+
+impl AccessLists {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_whitelist_enabled(mut self, enabled: bool) -> Self {
+        self.whitelist_enabled = enabled;
+        self
+    }
+
+    /// Load the lists from the files of the given server directory, missing files are
+    /// considered empty and invalid entries are ignored.
+    pub fn load<P: AsRef<Path>>(dir: P) -> io::Result<Self> {
+
+        let dir = dir.as_ref();
+        let mut lists = Self::new();
+
+        lists.whitelist = read_list(&dir.join(WHITELIST_FILE))?.iter()
+            .filter_map(UserProfile::from_json)
+            .collect();
+
+        lists.ops = read_list(&dir.join(OPS_FILE))?.iter()
+            .filter_map(OpEntry::from_json)
+            .collect();
+
+        lists.banned_players = read_list(&dir.join(BANNED_PLAYERS_FILE))?.iter()
+            .filter_map(|value| Some((UserProfile::from_json(value)?, BanEntry::from_json(value))))
+            .collect();
+
+        lists.banned_ips = read_list(&dir.join(BANNED_IPS_FILE))?.iter()
+            .filter_map(|value| {
+                let ip = value.get("ip")?.as_str()?.parse().ok()?;
+                Some((ip, BanEntry::from_json(value)))
+            })
+            .collect();
+
+        Ok(lists)
+
+    }
+
+    /// Save all lists to their files in the given server directory.
+    pub fn save<P: AsRef<Path>>(&self, dir: P) -> io::Result<()> {
+
+        let dir = dir.as_ref();
+
+        write_list(&dir.join(WHITELIST_FILE), self.whitelist.iter()
+            .map(UserProfile::to_json))?;
+
+        write_list(&dir.join(OPS_FILE), self.ops.iter()
+            .map(OpEntry::to_json))?;
+
+        write_list(&dir.join(BANNED_PLAYERS_FILE), self.banned_players.iter()
+            .map(|(profile, ban)| {
+                let mut value = profile.to_json();
+                ban.write_json(&mut value);
+                value
+            }))?;
+
+        write_list(&dir.join(BANNED_IPS_FILE), self.banned_ips.iter()
+            .map(|(ip, ban)| {
+                let mut value = json!({ "ip": ip.to_string() });
+                ban.write_json(&mut value);
+                value
+            }))
+
+    }
+
+    // Whitelist //
+
+    pub fn is_whitelist_enabled(&self) -> bool {
+        self.whitelist_enabled
+    }
+
+    pub fn set_whitelist_enabled(&mut self, enabled: bool) {
+        self.whitelist_enabled = enabled;
+    }
+
+    /// Add a player to the whitelist, returning false if already whitelisted.
+    pub fn add_to_whitelist(&mut self, profile: UserProfile) -> bool {
+        if self.whitelist.iter().any(|entry| entry.is_player(Some(profile.uuid), &profile.name)) {
+            false
+        } else {
+            self.whitelist.push(profile);
+            true
+        }
+    }
+
+    /// Remove a player from the whitelist by name, returning false if not whitelisted.
+    pub fn remove_from_whitelist(&mut self, name: &str) -> bool {
+        let len = self.whitelist.len();
+        self.whitelist.retain(|entry| !entry.is_player(None, name));
+        self.whitelist.len() != len
+    }
+
+    /// Return true if the player is allowed by the whitelist, operators are always allowed.
+    pub fn is_whitelisted(&self, uuid: Option<Uuid>, name: &str) -> bool {
+        !self.whitelist_enabled
+            || self.whitelist.iter().any(|entry| entry.is_player(uuid, name))
+            || self.get_op_level(uuid, name).is_some()
+    }
+
+    pub fn iter_whitelist(&self) -> impl Iterator<Item = &UserProfile> + '_ {
+        self.whitelist.iter()
+    }
+
+    // Operators //
+
+    /// Make a player operator with the given level, or change the level of an operator.
+    pub fn add_op(&mut self, profile: UserProfile, level: u8) {
+        match self.ops.iter_mut().find(|entry| entry.profile.is_player(Some(profile.uuid), &profile.name)) {
+            Some(entry) => entry.level = level,
+            None => self.ops.push(OpEntry {
+                profile,
+                level,
+                bypasses_player_limit: false
+            })
+        }
+    }
+
+    /// Remove an operator by name, returning false if the player is not an operator.
+    pub fn remove_op(&mut self, name: &str) -> bool {
+        let len = self.ops.len();
+        self.ops.retain(|entry| !entry.profile.is_player(None, name));
+        self.ops.len() != len
+    }
+
+    /// Return the permission level of the player if operator.
+    pub fn get_op_level(&self, uuid: Option<Uuid>, name: &str) -> Option<u8> {
+        self.ops.iter()
+            .find(|entry| entry.profile.is_player(uuid, name))
+            .map(|entry| entry.level)
+    }
+
+    pub fn iter_ops(&self) -> impl Iterator<Item = &OpEntry> + '_ {
+        self.ops.iter()
+    }
+
+    // Bans //
+
+    /// Ban a player, replacing any previous ban of this player.
+    pub fn ban_player(&mut self, profile: UserProfile, ban: BanEntry) {
+        self.banned_players.retain(|(entry, _)| !entry.is_player(Some(profile.uuid), &profile.name));
+        self.banned_players.push((profile, ban));
+    }
+
+    /// Remove the ban of a player by name, returning false if the player is not banned.
+    pub fn pardon_player(&mut self, name: &str) -> bool {
+        let len = self.banned_players.len();
+        self.banned_players.retain(|(entry, _)| !entry.is_player(None, name));
+        self.banned_players.len() != len
+    }
+
+    /// Return the ban of a player if banned, expired bans are ignored.
+    pub fn get_player_ban(&self, uuid: Option<Uuid>, name: &str) -> Option<&BanEntry> {
+        let now = current_time();
+        self.banned_players.iter()
+            .find(|(entry, ban)| entry.is_player(uuid, name) && !ban.is_expired(now))
+            .map(|(_, ban)| ban)
+    }
+
+    /// Ban an IP address, replacing any previous ban of this address.
+    pub fn ban_ip(&mut self, ip: IpAddr, ban: BanEntry) {
+        self.banned_ips.retain(|&(entry, _)| entry != ip);
+        self.banned_ips.push((ip, ban));
+    }
+
+    /// Remove the ban of an IP address, returning false if the address is not banned.
+    pub fn pardon_ip(&mut self, ip: IpAddr) -> bool {
+        let len = self.banned_ips.len();
+        self.banned_ips.retain(|&(entry, _)| entry != ip);
+        self.banned_ips.len() != len
+    }
+
+    /// Return the ban of an IP address if banned, expired bans are ignored.
+    pub fn get_ip_ban(&self, ip: IpAddr) -> Option<&BanEntry> {
+        let now = current_time();
+        self.banned_ips.iter()
+            .find(|&&(entry, ref ban)| entry == ip && !ban.is_expired(now))
+            .map(|(_, ban)| ban)
+    }
+
+    /// Check if a player can join the server, the error is the message of the disconnection
+    /// screen shown to the player.
+    pub fn check_login(&self, uuid: Option<Uuid>, name: &str, ip: IpAddr) -> Result<(), String> {
+        if let Some(ban) = self.get_player_ban(uuid, name) {
+            Err(ban.get_message("You are banned from this server."))
+        } else if !self.is_whitelisted(uuid, name) {
+            Err("You are not white-listed on this server!".to_string())
+        } else if let Some(ban) = self.get_ip_ban(ip) {
+            Err(ban.get_message("Your IP address is banned from this server."))
+        } else {
+            Ok(())
+        }
+    }
+
+}
+
+
+/// Internal function to read a JSON array file, a missing file is an empty list.
+fn read_list(path: &Path) -> io::Result<Vec<Value>> {
+    match fs::read_to_string(path) {
+        Ok(data) => match serde_json::from_str(&data) {
+            Ok(Value::Array(values)) => Ok(values),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, format!("{} is not a JSON array", path.display())))
+        },
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e)
+    }
+}
+
+/// Internal function to write a JSON array file.
+fn write_list(path: &Path, values: impl Iterator<Item = Value>) -> io::Result<()> {
+    let data = serde_json::to_string_pretty(&Value::Array(values.collect()))?;
+    fs::write(path, data)
+}
+
+
+/// Internal function to get the current UNIX timestamp in seconds.
+fn current_time() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_secs() as i64)
+}
+
+/// Format a UNIX timestamp in seconds as a vanilla date, for example `2021-07-06 12:30:00 +0000`.
+pub fn format_date(time: i64) -> String {
+    let (days, secs) = (time.div_euclid(86400), time.rem_euclid(86400));
+    // Civil from days, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02} +0000", year, month, day, secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// Parse a vanilla date to a UNIX timestamp in seconds, the time zone offset is applied.
+pub fn parse_date(date: &str) -> Option<i64> {
+
+    let mut parts = date.split(' ');
+    let mut ymd = parts.next()?.split('-').map(str::parse::<i64>);
+    let mut hms = parts.next()?.split(':').map(str::parse::<i64>);
+    let zone = parts.next()?;
+
+    let (year, month, day) = (ymd.next()?.ok()?, ymd.next()?.ok()?, ymd.next()?.ok()?);
+    let (hour, minute, second) = (hms.next()?.ok()?, hms.next()?.ok()?, hms.next()?.ok()?);
+
+    let sign = match zone.get(..1)? {
+        "+" => 1,
+        "-" => -1,
+        _ => return None
+    };
+    let zone_hours: i64 = zone.get(1..3)?.parse().ok()?;
+    let zone_minutes: i64 = zone.get(3..5)?.parse().ok()?;
+
+    // Days from civil, see http://howardhinnant.github.io/date_algorithms.html
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146097 + doe - 719468;
+
+    let offset = sign * (zone_hours * 3600 + zone_minutes * 60);
+    Some(days * 86400 + hour * 3600 + minute * 60 + second - offset)
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use std::net::Ipv4Addr;
+
+    use super::*;
+
+    #[test]
+    fn dates() {
+        assert_eq!(format_date(0), "1970-01-01 00:00:00 +0000");
+        assert_eq!(format_date(1625574600), "2021-07-06 12:30:00 +0000");
+        assert_eq!(parse_date("2021-07-06 12:30:00 +0000"), Some(1625574600));
+        assert_eq!(parse_date("2021-07-06 14:30:00 +0200"), Some(1625574600));
+        assert_eq!(parse_date("2000-02-29 00:00:00 -0100"), Some(951786000));
+        assert_eq!(parse_date(BAN_FOREVER), None);
+    }
+
+    #[test]
+    fn access_lists() {
+
+        let ip = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1));
+        let alice = UserProfile::new(Uuid::from_u128(1), "Alice".to_string());
+        let bob = UserProfile::new(Uuid::from_u128(2), "Bob".to_string());
+
+        let mut lists = AccessLists::new();
+        assert!(lists.check_login(None, "alice", ip).is_ok());
+
+        lists.set_whitelist_enabled(true);
+        assert!(lists.add_to_whitelist(alice.clone()));
+        assert!(!lists.add_to_whitelist(alice.clone()));
+        assert!(lists.check_login(None, "alice", ip).is_ok());
+        assert!(lists.check_login(None, "Bob", ip).is_err());
+
+        // Operators bypass the whitelist.
+        lists.add_op(bob.clone(), DEFAULT_OP_LEVEL);
+        assert_eq!(lists.get_op_level(Some(bob.uuid), "Robert"), Some(DEFAULT_OP_LEVEL));
+        assert!(lists.check_login(None, "Bob", ip).is_ok());
+
+        lists.ban_player(bob.clone(), BanEntry::new().with_reason("Griefing".to_string()));
+        assert_eq!(lists.check_login(None, "bob", ip), Err("You are banned from this server.\nReason: Griefing".to_string()));
+        assert!(lists.pardon_player("BOB"));
+
+        // Expired bans are ignored.
+        lists.ban_ip(ip, BanEntry::new().with_expires(current_time() - 10));
+        assert!(lists.check_login(None, "Bob", ip).is_ok());
+        lists.ban_ip(ip, BanEntry::new().with_expires(current_time() + 3600));
+        assert!(lists.check_login(None, "Bob", ip).unwrap_err().starts_with("Your IP address is banned"));
+
+        let dir = std::env::temp_dir().join(format!("mc-server-access-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        lists.save(&dir).unwrap();
+        let loaded = AccessLists::load(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(loaded.whitelist, lists.whitelist);
+        assert_eq!(loaded.ops, lists.ops);
+        assert_eq!(loaded.banned_ips, lists.banned_ips);
+        // The whitelist switch is not stored with the lists.
+        assert!(!loaded.is_whitelist_enabled());
+
+    }
+
+}
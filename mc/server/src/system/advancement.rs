@@ -0,0 +1,64 @@
+//! Advancements of players, their progress is tracked by the vanilla advancement system and
+//! done advancements are announced in the chat.
+
+use mc_runtime::world::World;
+use mc_vanilla::advancement::{AdvancementDoneEvent, AdvancementFrame, get_plain_text};
+
+use super::protocol::{ProtocolServer, ProtocolPlayerEntity};
+use super::chat::broadcast_system_message;
+
+
+/// Return the chat announcement of a player that has done an advancement, like vanilla the
+/// sentence depends on the frame of the advancement.
+pub fn format_announcement(name: &str, frame: AdvancementFrame, title: &str) -> String {
+    let action = match frame {
+        AdvancementFrame::Task => "has made the advancement",
+        AdvancementFrame::Challenge => "has completed the challenge",
+        AdvancementFrame::Goal => "has reached the goal"
+    };
+    format!("{} {} [{}]", name, action, title)
+}
+
+
+/// System announcing advancements done by players in the chat, if their display allows it.
+pub fn system_advancement_announce(world: &mut World) {
+
+    let proto_server = world.components.get::<ProtocolServer>().unwrap();
+
+    for event in world.event_tracker.poll_events::<AdvancementDoneEvent>() {
+
+        let display = match event.advancement.get_display() {
+            Some(display) if display.announce_to_chat => display,
+            _ => continue
+        };
+
+        let addr = match event.level.borrow().entities.ecs.get::<ProtocolPlayerEntity>(event.entity) {
+            Ok(proto_player) => proto_player.addr,
+            Err(_) => continue
+        };
+
+        let name = proto_server.get_client(addr)
+            .and_then(|client| client.get_username())
+            .unwrap_or_default();
+
+        let message = format_announcement(name, display.frame, &get_plain_text(&display.title));
+        println!("[{}] {}", addr, message);
+        broadcast_system_message(&proto_server, &message);
+
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn announcement() {
+        assert_eq!(format_announcement("Steve", AdvancementFrame::Task, "Stone Age"), "Steve has made the advancement [Stone Age]");
+        assert_eq!(format_announcement("Alex", AdvancementFrame::Challenge, "Arbalistic"), "Alex has completed the challenge [Arbalistic]");
+    }
+
+}
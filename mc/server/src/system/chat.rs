@@ -0,0 +1,306 @@
+//! Chat of players, messages received from clients are validated and queued in the `Chat`
+//! component, then pushed as cancellable `ChatEvent`s before being broadcast. Messages
+//! starting with a slash are executed as commands instead.
+
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use mc_runtime::command::{CommandSource, execute_command};
+use mc_runtime::world::World;
+use mc_core::world::level::{Level, BaseEntity};
+
+use super::protocol::ProtocolServer;
+use crate::protocol::play::{ClientChatMessagePacket, ChatMessagePacket, ChatPosition};
+use crate::protocol::version::{ClientboundPacket, ServerboundPacket};
+
+use hecs::Entity;
+use uuid::Uuid;
+
+
+/// The vanilla format of chat messages, see `format_chat`.
+pub const CHAT_FORMAT: &str = "<{name}> {message}";
+
+
+/// A hook called with each chat message before it is pushed as an event, the event can be
+/// modified and the message is cancelled if false is returned.
+pub type ChatHook = fn(&World, &mut ChatEvent) -> bool;
+
+
+struct ReceivedMessage {
+    addr: SocketAddr,
+    level_idx: usize,
+    entity: Entity,
+    uuid: Uuid,
+    name: String,
+    message: String
+}
+
+
+/// A world component queuing received chat messages until they are processed by
+/// `system_chat_receive`, it also keeps track of muted players.
+pub struct Chat {
+    received: Vec<ReceivedMessage>,
+    muted: HashSet<Uuid>,
+    hooks: Vec<ChatHook>,
+    format: String
+}
+
+impl Chat {
+
+    pub fn new() -> Self {
+        Self {
+            received: Vec::new(),
+            muted: HashSet::new(),
+            hooks: Vec::new(),
+            format: CHAT_FORMAT.to_string()
+        }
+    }
+
+    /// Set the default format of chat events, see `format_chat`.
+    pub fn with_format(mut self, format: String) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_hook(mut self, hook: ChatHook) -> Self {
+        self.add_hook(hook);
+        self
+    }
+
+    /// Add a hook called with all chat messages, hooks are called in insertion order.
+    pub fn add_hook(&mut self, hook: ChatHook) {
+        self.hooks.push(hook);
+    }
+
+    /// Prevent a player from sending chat messages, commands are still executed.
+    pub fn mute(&mut self, uuid: Uuid) {
+        self.muted.insert(uuid);
+    }
+
+    pub fn unmute(&mut self, uuid: Uuid) {
+        self.muted.remove(&uuid);
+    }
+
+    pub fn is_muted(&self, uuid: Uuid) -> bool {
+        self.muted.contains(&uuid)
+    }
+
+}
+
+impl Default for Chat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// Event pushed by `system_chat_receive` for each chat message sent by a player, messages
+/// are broadcast by `system_chat_broadcast` unless their event is cancelled.
+pub struct ChatEvent {
+    pub level: Rc<RefCell<Level>>,
+    pub entity: Entity,
+    pub addr: SocketAddr,
+    pub uuid: Uuid,
+    /// The name of the sender, as shown in the message.
+    pub name: String,
+    pub message: String,
+    /// The format of the broadcast message, see `format_chat`.
+    pub format: String
+}
+
+impl ChatEvent {
+
+    /// Return the message to broadcast, formatted with the format of this event.
+    pub fn format_message(&self) -> String {
+        format_chat(&self.format, &self.name, &self.message)
+    }
+
+}
+
+
+/// Format a chat message, the `{name}` and `{message}` placeholders of the format are replaced
+/// by the sender's name and the message. Placeholders in the replaced values are kept as is.
+pub fn format_chat(format: &str, name: &str, message: &str) -> String {
+    let mut formatted = String::with_capacity(format.len() + name.len() + message.len());
+    let mut remaining = format;
+    while let Some(start) = remaining.find('{') {
+        formatted.push_str(&remaining[..start]);
+        remaining = &remaining[start..];
+        if let Some(rest) = remaining.strip_prefix("{name}") {
+            formatted.push_str(name);
+            remaining = rest;
+        } else if let Some(rest) = remaining.strip_prefix("{message}") {
+            formatted.push_str(message);
+            remaining = rest;
+        } else {
+            formatted.push('{');
+            remaining = &remaining[1..];
+        }
+    }
+    formatted.push_str(remaining);
+    formatted
+}
+
+
+/// Return true if a message can be sent by a client, like vanilla the section sign and
+/// control characters are not allowed.
+pub fn is_valid_message(message: &str) -> bool {
+    !message.chars().any(|c| c == '§' || c.is_control())
+}
+
+
+/// Send a system message to a client, shown in the chat box even if the player hides chat
+/// messages.
+pub fn send_system_message(server: &ProtocolServer, addr: SocketAddr, message: &str) {
+    server.send_packet(addr, ClientboundPacket::ChatMessage, &mut ChatMessagePacket {
+        message: message.to_string(),
+        position: ChatPosition::System,
+        sender: Uuid::nil()
+    });
+}
+
+/// Send a system message to all playing clients.
+pub fn broadcast_system_message(server: &ProtocolServer, message: &str) {
+    server.broadcast_packet(ClientboundPacket::ChatMessage, &mut ChatMessagePacket {
+        message: message.to_string(),
+        position: ChatPosition::System,
+        sender: Uuid::nil()
+    });
+}
+
+/// Send a message to a client, shown above its hotbar.
+pub fn send_actionbar(server: &ProtocolServer, addr: SocketAddr, message: &str) {
+    server.send_packet(addr, ClientboundPacket::ChatMessage, &mut ChatMessagePacket {
+        message: message.to_string(),
+        position: ChatPosition::GameInfo,
+        sender: Uuid::nil()
+    });
+}
+
+
+/// System processing received chat messages, commands are executed and their errors are sent
+/// back to the sender, other messages go through the chat hooks and are pushed as events.
+pub fn system_chat_receive(world: &mut World) {
+
+    let (received, hooks, format) = match world.components.get_mut::<Chat>() {
+        Ok(mut chat) if !chat.received.is_empty() => {
+            (std::mem::take(&mut chat.received), chat.hooks.clone(), chat.format.clone())
+        }
+        _ => return
+    };
+
+    for received in received {
+
+        let level = match world.levels.get(received.level_idx) {
+            Some(level) => Rc::clone(level),
+            None => continue
+        };
+
+        if let Some(command) = received.message.strip_prefix('/') {
+            let pos = match level.borrow().entities.ecs.get::<BaseEntity>(received.entity) {
+                Ok(base) => base.pos.clone(),
+                Err(_) => continue
+            };
+            let source = CommandSource::new(received.name, level, pos).with_entity(received.entity);
+            if let Err(e) = execute_command(world, &source, command) {
+                let server = world.components.get::<ProtocolServer>().unwrap();
+                send_system_message(&server, received.addr, &e.to_string());
+            }
+            continue;
+        }
+
+        if world.components.get::<Chat>().unwrap().is_muted(received.uuid) {
+            let server = world.components.get::<ProtocolServer>().unwrap();
+            send_system_message(&server, received.addr, "You are muted.");
+            continue;
+        }
+
+        let mut event = ChatEvent {
+            level,
+            entity: received.entity,
+            addr: received.addr,
+            uuid: received.uuid,
+            name: received.name,
+            message: received.message,
+            format: format.clone()
+        };
+
+        if hooks.iter().all(|hook| hook(world, &mut event)) {
+            world.event_tracker.push_event(event);
+        }
+
+    }
+
+}
+
+
+/// System broadcasting chat messages of non-cancelled chat events to all players.
+pub fn system_chat_broadcast(world: &mut World) {
+    let server = world.components.get::<ProtocolServer>().unwrap();
+    for event in world.event_tracker.poll_events::<ChatEvent>() {
+        let message = event.format_message();
+        println!("[{}] Chat: {}", event.addr, message);
+        server.broadcast_packet(ClientboundPacket::ChatMessage, &mut ChatMessagePacket {
+            message,
+            position: ChatPosition::Chat,
+            sender: event.uuid
+        });
+    }
+}
+
+
+pub(super) fn register_listeners(server: &mut ProtocolServer) {
+
+    server.add_listener::<_, ClientChatMessagePacket>(ServerboundPacket::ChatMessage, |e| {
+
+        let (level_idx, entity) = match e.get_player() {
+            Some(player) => player,
+            None => return
+        };
+
+        // Like vanilla, clients sending illegal characters are kicked.
+        let message = e.packet.message.trim();
+        if !is_valid_message(message) {
+            println!("[{}] Illegal characters in chat.", e.client.get_addr());
+            e.disconnect();
+            return;
+        }
+
+        if message.is_empty() {
+            return;
+        }
+
+        if let Ok(mut chat) = e.world.get_component_mut::<Chat>() {
+            chat.received.push(ReceivedMessage {
+                addr: e.client.get_addr(),
+                level_idx,
+                entity,
+                uuid: e.client.get_uuid().unwrap_or_default(),
+                name: e.client.get_username().unwrap_or_default().to_string(),
+                message: message.to_string()
+            });
+        }
+
+    });
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn chat_format() {
+        assert_eq!(format_chat(CHAT_FORMAT, "Steve", "hello"), "<Steve> hello");
+        assert_eq!(format_chat("{name}: {message} {", "{message}", "{name}"), "{message}: {name} {");
+        assert_eq!(format_chat("[{rank}] {name}", "Alex", ""), "[{rank}] Alex");
+        assert!(is_valid_message("Hello world!"));
+        assert!(!is_valid_message("§chello"));
+        assert!(!is_valid_message("hello\u{7f}"));
+    }
+
+}
@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use mc_runtime::world::World;
+use mc_runtime::event::ChunkLoadedEvent;
+use mc_core::world::chunk::Chunk;
+
+use crate::protocol::play::{ChunkDataPacket, UpdateLightPacket};
+use crate::protocol::version::ProtocolVersion;
+use crate::protocol::{WritablePacket, PacketResult};
+
+
+/// The light and data packets of a chunk, encoded for a specific protocol version.
+#[derive(Debug)]
+pub struct EncodedChunk {
+    pub light: Vec<u8>,
+    pub data: Vec<u8>
+}
+
+impl EncodedChunk {
+
+    pub fn encode(chunk: &Chunk, version: ProtocolVersion) -> PacketResult<Self> {
+        let mut light = Vec::new();
+        UpdateLightPacket::new(chunk).write_packet(Cursor::new(&mut light), version)?;
+        let mut data = Vec::new();
+        ChunkDataPacket::new(chunk).write_packet(Cursor::new(&mut data), version)?;
+        Ok(Self { light, data })
+    }
+
+}
+
+
+/// Cached packets of a chunk, for all protocol versions requested since the chunk last
+/// changed.
+struct CacheEntry {
+    dirty_counter: u32,
+    encoded: Vec<(ProtocolVersion, EncodedChunk)>
+}
+
+
+/// A cache of encoded chunk packets, shared by all players, so a chunk sent to many players
+/// is only encoded once per protocol version. An entry is re-encoded when the dirty counter
+/// of its chunk changes, see `Chunk::get_dirty_counter`.
+pub struct ChunkPacketCache {
+    /// Entries mapped by level ID, then by chunk position.
+    levels: HashMap<String, HashMap<(i32, i32), CacheEntry>>
+}
+
+impl ChunkPacketCache {
+
+    pub fn new() -> Self {
+        Self {
+            levels: HashMap::new()
+        }
+    }
+
+    /// Get the encoded packets of the given chunk if they are cached and the chunk has not
+    /// changed since.
+    pub fn get(&self, level_id: &str, chunk: &Chunk, version: ProtocolVersion) -> Option<&EncodedChunk> {
+        let entry = self.levels.get(level_id)?.get(&chunk.get_position())?;
+        if entry.dirty_counter != chunk.get_dirty_counter() {
+            return None;
+        }
+        entry.encoded.iter()
+            .find(|(entry_version, _)| *entry_version == version)
+            .map(|(_, encoded)| encoded)
+    }
+
+    /// Get the encoded packets of the given chunk, they are encoded and cached if absent or if
+    /// the chunk has changed since they were encoded.
+    pub fn get_or_encode(&mut self, level_id: &str, chunk: &Chunk, version: ProtocolVersion) -> PacketResult<&EncodedChunk> {
+
+        if !self.levels.contains_key(level_id) {
+            self.levels.insert(level_id.to_string(), HashMap::new());
+        }
+
+        let dirty_counter = chunk.get_dirty_counter();
+        let entry = self.levels.get_mut(level_id).unwrap()
+            .entry(chunk.get_position())
+            .or_insert_with(|| CacheEntry {
+                dirty_counter,
+                encoded: Vec::new()
+            });
+
+        if entry.dirty_counter != dirty_counter {
+            entry.dirty_counter = dirty_counter;
+            entry.encoded.clear();
+        }
+
+        let index = match entry.encoded.iter().position(|(entry_version, _)| *entry_version == version) {
+            Some(index) => index,
+            None => {
+                entry.encoded.push((version, EncodedChunk::encode(chunk, version)?));
+                entry.encoded.len() - 1
+            }
+        };
+
+        Ok(&entry.encoded[index].1)
+
+    }
+
+    /// Remove the cached packets of a chunk, this must be used when a chunk is replaced by
+    /// another instance, because dirty counters of both instances are unrelated.
+    pub fn invalidate(&mut self, level_id: &str, cx: i32, cz: i32) {
+        if let Some(entries) = self.levels.get_mut(level_id) {
+            entries.remove(&(cx, cz));
+        }
+    }
+
+    /// Only keep the chunks of the given level for which the predicate returns true.
+    pub fn retain_level<F>(&mut self, level_id: &str, mut predicate: F)
+    where
+        F: FnMut(i32, i32) -> bool
+    {
+        if let Some(entries) = self.levels.get_mut(level_id) {
+            entries.retain(|&(cx, cz), _| predicate(cx, cz));
+        }
+    }
+
+    /// Return the number of chunks cached in all levels.
+    pub fn len(&self) -> usize {
+        self.levels.values().map(|entries| entries.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+}
+
+impl Default for ChunkPacketCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// System keeping the `ChunkPacketCache` component coherent with the levels, cached packets of
+/// reloaded chunks are invalidated and those of unloaded chunks are removed. This system must
+/// run after chunks are loaded.
+pub fn system_chunk_packet_cache(world: &mut World) {
+
+    let mut cache = world.components.get_mut::<ChunkPacketCache>().unwrap();
+
+    for event in world.event_tracker.poll_events::<ChunkLoadedEvent>() {
+        cache.invalidate(event.level.borrow().get_id(), event.cx, event.cz);
+    }
+
+    for level in &world.levels {
+        let level = level.borrow();
+        cache.retain_level(level.get_id(), |cx, cz| level.chunks.is_chunk_loaded(cx, cz));
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::source::ChunkLoadRequest;
+    use mc_core::world::level::LevelEnv;
+    use mc_vanilla::ext::VanillaLevelEnv;
+    use mc_vanilla::block::STONE;
+
+    use super::*;
+
+    #[test]
+    fn chunk_packet_cache() {
+
+        let mut chunk = ChunkLoadRequest {
+            env: LevelEnv::vanilla(),
+            height: ChunkHeight::new(0, 15),
+            cx: 1,
+            cz: 2
+        }.build_chunk();
+
+        let mut cache = ChunkPacketCache::new();
+        assert!(cache.get("overworld", &chunk, ProtocolVersion::LATEST).is_none());
+
+        let data = cache.get_or_encode("overworld", &chunk, ProtocolVersion::LATEST).unwrap().data.clone();
+        assert_eq!(cache.get("overworld", &chunk, ProtocolVersion::LATEST).unwrap().data, data);
+        assert!(cache.get("nether", &chunk, ProtocolVersion::LATEST).is_none());
+        assert!(cache.get("overworld", &chunk, ProtocolVersion::V1_16_5).is_none());
+        assert_eq!(cache.len(), 1);
+
+        // A modified chunk is encoded again.
+        chunk.set_block(0, 0, 0, STONE.get_default_state()).unwrap();
+        assert!(cache.get("overworld", &chunk, ProtocolVersion::LATEST).is_none());
+        assert_ne!(cache.get_or_encode("overworld", &chunk, ProtocolVersion::LATEST).unwrap().data, data);
+
+        cache.retain_level("overworld", |_, _| false);
+        assert!(cache.is_empty());
+
+    }
+
+}
@@ -0,0 +1,73 @@
+//! Command completion for players, commands of the `CommandDispatcher` are declared to clients
+//! when they join, and their arguments are completed by the server on request.
+
+use mc_runtime::command::{CommandDispatcher, CommandSource, SuggestionProvider, complete_command};
+use mc_runtime::world::World;
+use mc_core::world::level::BaseEntity;
+
+use super::protocol::ProtocolServer;
+use super::player_list::PlayerList;
+use crate::protocol::play::{RequestCommandCompletionsPacket, CommandSuggestionsPacket, DeclareCommandsPacket};
+use crate::protocol::version::{ClientboundPacket, ServerboundPacket};
+
+
+/// Suggest the usernames of all players in the player list.
+pub struct PlayerNameSuggestions;
+
+impl SuggestionProvider for PlayerNameSuggestions {
+    fn suggest(&self, world: &World, _source: &CommandSource, _arg: &str, suggestions: &mut Vec<String>) {
+        if let Ok(player_list) = world.get_component::<PlayerList>() {
+            suggestions.extend(player_list.iter_players().map(|entry| entry.username.clone()));
+        }
+    }
+}
+
+
+/// Return the packet declaring all commands of the world's dispatcher, sorted by name.
+pub fn get_declare_commands_packet(world: &World) -> DeclareCommandsPacket {
+    let mut commands: Vec<String> = world.get_component::<CommandDispatcher>()
+        .map(|dispatcher| dispatcher.iter_commands().map(|name| name.to_string()).collect())
+        .unwrap_or_default();
+    commands.sort_unstable();
+    DeclareCommandsPacket { commands }
+}
+
+
+/// Internal function to convert a byte index of a string to an index in UTF-16 code units,
+/// as expected by the client.
+fn to_utf16_index(text: &str, idx: usize) -> i32 {
+    text[..idx].encode_utf16().count() as i32
+}
+
+
+pub(super) fn register_listeners(server: &mut ProtocolServer) {
+
+    server.add_listener::<_, RequestCommandCompletionsPacket>(ServerboundPacket::RequestCommandCompletions, |e| {
+
+        let (level_idx, entity) = match e.get_player() {
+            Some(player) => player,
+            None => return
+        };
+
+        let level = &e.world.levels[level_idx];
+        let pos = match level.borrow().entities.ecs.get::<BaseEntity>(entity) {
+            Ok(base) => base.pos.clone(),
+            Err(_) => return
+        };
+
+        let name = e.client.get_username().unwrap_or_default().to_string();
+        let source = CommandSource::new(name, level.clone(), pos).with_entity(entity);
+
+        let text = &e.packet.text;
+        let suggestions = complete_command(e.world, &source, text);
+
+        e.answer_packet(ClientboundPacket::CommandSuggestions, &mut CommandSuggestionsPacket {
+            transaction_id: e.packet.transaction_id,
+            start: to_utf16_index(text, suggestions.start),
+            length: to_utf16_index(text, text.len()) - to_utf16_index(text, suggestions.start),
+            matches: suggestions.matches
+        });
+
+    });
+
+}
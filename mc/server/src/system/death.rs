@@ -0,0 +1,163 @@
+//! Death screen and respawn of players, deaths detected by the vanilla death system open the
+//! death screen of their clients and respawn requests move the player entity to its respawn
+//! point, possibly in another level.
+
+use mc_runtime::system::{ViewTracker, Inhabitant, NoClip};
+use mc_runtime::world::World;
+use mc_vanilla::death::{Dead, PlayerDeathEvent, find_respawn_point, respawn_player};
+use mc_vanilla::entity::PlayerEntity;
+use mc_vanilla::advancement::AdvancementProgress;
+
+use super::protocol::{ProtocolServer, ProtocolPlayerEntity};
+use super::window::PlayerWindows;
+use super::game_mode::SyncedAbilities;
+use super::chat::broadcast_system_message;
+use crate::protocol::play::{ClientStatusPacket, ClientStatusAction, DeathCombatEventPacket, RespawnPacket};
+use crate::protocol::play::{ChangeGameStatePacket, GameStateChange, PlayerAbilitiesPacket, PlayerPosAndLook};
+use crate::protocol::version::{ClientboundPacket, ServerboundPacket};
+
+
+/// System opening the death screen of players that died during this tick, the death message
+/// is also broadcast to all players if enabled.
+pub fn system_player_deaths(world: &mut World) {
+
+    let proto_server = world.components.get::<ProtocolServer>().unwrap();
+
+    for event in world.event_tracker.poll_events::<PlayerDeathEvent>() {
+
+        let addr = match event.level.borrow().entities.ecs.get::<ProtocolPlayerEntity>(event.entity) {
+            Ok(proto_player) => proto_player.addr,
+            Err(_) => continue
+        };
+
+        let name = proto_server.get_client(addr)
+            .and_then(|client| client.get_username())
+            .unwrap_or_default();
+        let message = format!("{} died", name);
+        println!("[{}] {}", addr, message);
+
+        proto_server.send_packet(addr, ClientboundPacket::DeathCombatEvent, &mut DeathCombatEventPacket {
+            player_eid: event.entity.id() as i32,
+            killer_eid: -1,
+            message: message.clone()
+        });
+
+        if event.show_message {
+            broadcast_system_message(&proto_server, &message);
+        }
+
+    }
+
+}
+
+
+pub(super) fn register_listeners(server: &mut ProtocolServer) {
+
+    server.add_listener::<_, ClientStatusPacket>(ServerboundPacket::ClientStatus, |e| {
+
+        if e.packet.action != ClientStatusAction::PerformRespawn {
+            return;
+        }
+
+        let (level_idx, entity) = match e.get_player() {
+            Some(player) => player,
+            None => return
+        };
+
+        if e.world.levels[level_idx].borrow().entities.ecs.get::<Dead>(entity).is_err() {
+            return;
+        }
+
+        let point = match find_respawn_point(e.world, level_idx, entity) {
+            Some(point) => point,
+            None => return
+        };
+
+        // Components without codec are moved by hand when the player changes
+        // level and the view is reset because clients forget everything when respawning.
+        let (server_components, no_clip) = {
+            let mut level = e.world.levels[level_idx].borrow_mut();
+            let view_distance = match level.entities.ecs.get::<ViewTracker>(entity) {
+                Ok(tracker) => tracker.get_view_distance(),
+                Err(_) => return
+            };
+            if point.level_idx == level_idx {
+                level.entities.ecs.insert_one(entity, ViewTracker::new(view_distance)).unwrap();
+                (None, false)
+            } else {
+                let no_clip = level.entities.ecs.remove_one::<NoClip>(entity).is_ok();
+                match level.entities.ecs.remove::<(ProtocolPlayerEntity, PlayerWindows, SyncedAbilities, AdvancementProgress)>(entity) {
+                    Ok(components) => (Some((components, view_distance)), no_clip),
+                    Err(_) => return
+                }
+            }
+        };
+
+        let new_entity = respawn_player(e.world, level_idx, entity, &point);
+
+        let (player_level_idx, player_entity) = match new_entity {
+            Some(new_entity) => (point.level_idx, new_entity),
+            None => (level_idx, entity)
+        };
+
+        {
+            let mut level = e.world.levels[player_level_idx].borrow_mut();
+            let ecs = &mut level.entities.ecs;
+            if let Some((components, view_distance)) = server_components {
+                ecs.insert(player_entity, components).unwrap();
+                ecs.insert(player_entity, (ViewTracker::new(view_distance), Inhabitant)).unwrap();
+                if no_clip {
+                    ecs.insert_one(player_entity, NoClip).unwrap();
+                }
+            }
+            if let Ok(mut proto_player) = ecs.get_mut::<ProtocolPlayerEntity>(player_entity) {
+                proto_player.last_chunk_pos = None;
+            };
+        }
+
+        if new_entity.is_none() {
+            println!("[{}] Respawn failed.", e.client.get_addr());
+            return;
+        }
+
+        // The entity ID known by the client is not updated when the player changes level.
+        e.client.set_player(player_level_idx, player_entity);
+
+        let level = e.world.levels[player_level_idx].borrow();
+        let player = level.entities.ecs.get::<PlayerEntity>(player_entity).unwrap();
+
+        e.answer_packet(ClientboundPacket::Respawn, &mut RespawnPacket {
+            dimension_type: level.get_dimension_type().clone(),
+            level_id: level.get_id().clone(),
+            hashed_seed: 0,
+            game_mode: player.get_game_mode(),
+            previous_game_mode: player.get_previous_game_mode(),
+            copy_metadata: false
+        });
+
+        if point.spawn_point_missing {
+            e.answer_packet(ClientboundPacket::ChangeGameState, &mut ChangeGameStatePacket {
+                change: GameStateChange::NoRespawnBlockAvailable
+            });
+        }
+
+        e.answer_packet(ClientboundPacket::PlayerAbilities, &mut PlayerAbilitiesPacket::new(player.get_abilities()));
+
+        e.answer_packet(ClientboundPacket::PlayerPosAndLook, &mut PlayerPosAndLook {
+            x: point.pos.x,
+            y: point.pos.y,
+            z: point.pos.z,
+            yaw: point.yaw,
+            pitch: 0.0,
+            rel_x: false,
+            rel_y: false,
+            rel_z: false,
+            rel_yaw: false,
+            rel_pitch: false,
+            tp_id: 0,
+            dismount_vehicle: false
+        });
+
+    });
+
+}
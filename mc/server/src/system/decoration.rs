@@ -0,0 +1,263 @@
+//! Players interacting with decoration entities and leashes. Attacking a painting, an item
+//! frame or a leash knot breaks it, the item of an item frame is dropped first. Interacting
+//! with an item frame places the held item in it or rotates its item, and interacting with
+//! a leash knot ties the mobs of the player to it. Holding a lead while interacting with a
+//! mob leashes it to the player, interacting again removes the leash.
+//!
+//! Leashes and the rotation of items in frames are sent to the players viewing them each
+//! time they change, the items of frames are not sent yet.
+
+use std::collections::HashSet;
+use std::net::SocketAddr;
+
+use mc_runtime::world::World;
+use mc_runtime::system::ViewTracker;
+use mc_core::world::level::{Level, BaseEntity};
+use mc_core::item::Inventory;
+use mc_vanilla::entity::decoration::{
+    HangingEntity, ItemFrameEntity, Leashed,
+    break_hanging_entity, can_be_leashed, leash_mob, unleash_mob, tie_leashed_mobs
+};
+use mc_vanilla::entity::{PlayerEntity, LEASH_KNOT};
+use mc_vanilla::item::LEAD;
+use mc_vanilla::death::Dead;
+use hecs::Entity;
+
+use super::protocol::{ProtocolServer, ProtocolPlayerEntity};
+use super::window::spawn_dropped;
+use crate::protocol::play::{InteractEntityPacket, EntityInteraction, Hand, AttachEntityPacket, EntityMetadataPacket};
+use crate::protocol::metadata::build_entity_metadata;
+use crate::protocol::version::{ClientboundPacket, ServerboundPacket};
+
+
+/// Maximum distance between a player and the entity it interacts with.
+const INTERACT_DISTANCE: f64 = 6.0;
+
+
+/// An entity component for leashed mobs, the holder last sent to clients.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncedLeash {
+    holder: Entity
+}
+
+/// An entity component for item frames, the rotation last sent to clients.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncedFrame {
+    rotation: u8
+}
+
+
+/// Return true if the player can interact with the entity, both must be alive and close
+/// enough.
+fn can_interact_with(level: &Level, player: Entity, target: Entity) -> bool {
+    let ecs = &level.entities.ecs;
+    if ecs.get::<Dead>(player).is_ok() || ecs.get::<Dead>(target).is_ok() {
+        return false;
+    }
+    if !ecs.get::<PlayerEntity>(player).is_ok_and(|player| player.can_interact()) {
+        return false;
+    }
+    match (ecs.get::<BaseEntity>(player), ecs.get::<BaseEntity>(target)) {
+        (Ok(player_base), Ok(target_base)) => {
+            let (a, b) = (&player_base.pos, &target_base.pos);
+            let (dx, dy, dz) = (a.x - b.x, a.y - b.y, a.z - b.z);
+            dx * dx + dy * dy + dz * dz < INTERACT_DISTANCE * INTERACT_DISTANCE
+        }
+        _ => false
+    }
+}
+
+/// Return true if the player holds a lead in its main hand.
+fn is_holding_lead(level: &Level, player: Entity) -> bool {
+    let ecs = &level.entities.ecs;
+    match (ecs.get::<PlayerEntity>(player), ecs.get::<Inventory>(player)) {
+        (Ok(player), Ok(inventory)) => inventory.get_stack(player.get_selected_slot() as usize)
+            .is_some_and(|stack| std::ptr::eq(stack.get_item(), &LEAD)),
+        _ => false
+    }
+}
+
+/// Return true if interacting with the mob leashes it to the player or removes its leash,
+/// in which case the interaction has no other effect.
+pub fn is_leash_interaction(level: &Level, player: Entity, mob: Entity) -> bool {
+    let leashed_to_player = level.entities.ecs.get::<Leashed>(mob).is_ok_and(|leashed| leashed.get_holder() == player);
+    leashed_to_player || (is_holding_lead(level, player) && can_be_leashed(level, mob))
+}
+
+/// Internal function to interact with an item frame, the held item is placed in the frame
+/// if it is empty, its item is rotated otherwise. Fixed frames can't be changed.
+fn interact_item_frame(level: &mut Level, player: Entity, frame: Entity) {
+
+    let empty = match level.entities.ecs.get::<ItemFrameEntity>(frame) {
+        Ok(frame) if frame.is_fixed() => return,
+        Ok(frame) => frame.get_item().is_none(),
+        Err(_) => return
+    };
+
+    if !empty {
+        level.entities.ecs.get_mut::<ItemFrameEntity>(frame).unwrap().rotate();
+        return;
+    }
+
+    let placed = match level.entities.ecs.query_one_mut::<(&PlayerEntity, &mut Inventory)>(player) {
+        Ok((player, inventory)) => {
+            let slot = player.get_selected_slot() as usize;
+            inventory.get_stack(slot).cloned()
+                .filter(|_| player.consume_item(inventory, slot, 1))
+                .map(|mut stack| {
+                    stack.set_count(1);
+                    stack
+                })
+        }
+        Err(_) => None
+    };
+
+    if let Some(stack) = placed {
+        level.entities.ecs.get_mut::<ItemFrameEntity>(frame).unwrap().set_item(Some(stack));
+    }
+
+}
+
+
+/// Return the attach entity packet of a leashed mob.
+pub fn build_attach_entity(level: &Level, mob: Entity) -> AttachEntityPacket {
+    AttachEntityPacket {
+        eid: mob.id() as i32,
+        holder: level.entities.ecs.get::<Leashed>(mob).ok().map(|leashed| leashed.get_holder().id() as i32)
+    }
+}
+
+
+/// System sending the leashes of mobs and the rotation of items in frames to the players
+/// viewing them when they change.
+pub fn system_decoration_sync(world: &mut World) {
+
+    let proto_server = world.components.get::<ProtocolServer>().unwrap();
+
+    for level in &world.levels {
+
+        let mut level = level.borrow_mut();
+
+        // Mobs whose holder changed, an absent synced component means no holder.
+        let leashes: Vec<(Entity, Option<Entity>)> = level.entities.ecs.query::<(Option<&Leashed>, Option<&SyncedLeash>)>()
+            .iter()
+            .filter_map(|(entity, (leashed, synced))| {
+                let holder = leashed.map(Leashed::get_holder);
+                (holder != synced.map(|synced| synced.holder)).then_some((entity, holder))
+            })
+            .collect();
+
+        let frames: Vec<(Entity, u8)> = level.entities.ecs.query::<(&ItemFrameEntity, Option<&SyncedFrame>)>()
+            .iter()
+            .filter(|(_, (frame, synced))| frame.get_rotation() != synced.map_or(0, |synced| synced.rotation))
+            .map(|(entity, (frame, _))| (entity, frame.get_rotation()))
+            .collect();
+
+        for &(mob, holder) in &leashes {
+            let mut packet = build_attach_entity(&level, mob);
+            for addr in get_viewers(&level, mob) {
+                proto_server.send_packet(addr, ClientboundPacket::AttachEntity, &mut packet);
+            }
+            match holder {
+                Some(holder) => level.entities.ecs.insert_one(mob, SyncedLeash { holder }).unwrap(),
+                None => {
+                    let _ = level.entities.ecs.remove_one::<SyncedLeash>(mob);
+                }
+            }
+        }
+
+        for &(frame, rotation) in &frames {
+            for addr in get_viewers(&level, frame) {
+                if let Some(client) = proto_server.get_client(addr) {
+                    proto_server.send_packet(addr, ClientboundPacket::EntityMetadata, &mut EntityMetadataPacket {
+                        eid: frame.id() as i32,
+                        metadata: build_entity_metadata(&level.entities.ecs, frame, client.get_version())
+                    });
+                }
+            }
+            level.entities.ecs.insert_one(frame, SyncedFrame { rotation }).unwrap();
+        }
+
+    }
+
+}
+
+/// Internal function to get the addresses of the players viewing an entity.
+fn get_viewers(level: &Level, entity: Entity) -> HashSet<SocketAddr> {
+    level.entities.ecs.query::<(&ProtocolPlayerEntity, &ViewTracker)>()
+        .iter()
+        .filter(|(_, (_, tracker))| tracker.has_entity(entity))
+        .map(|(_, (player, _))| player.addr)
+        .collect()
+}
+
+
+pub(super) fn register_listeners(server: &mut ProtocolServer) {
+
+    server.add_listener::<_, InteractEntityPacket>(ServerboundPacket::InteractEntity, |e| {
+
+        let (level_idx, entity) = match e.get_player() {
+            Some(player) => player,
+            None => return
+        };
+
+        let mut level = e.world.levels[level_idx].borrow_mut();
+        let target = match level.entities.ecs.query::<&BaseEntity>()
+            .iter()
+            .map(|(entity, _)| entity)
+            .find(|entity| entity.id() as i32 == e.packet.eid) {
+            Some(target) => target,
+            None => return
+        };
+
+        if !can_interact_with(&level, entity, target) {
+            return;
+        }
+
+        let is_knot = level.entities.ecs.get::<BaseEntity>(target).is_ok_and(|base| std::ptr::eq(base.entity_type, &LEASH_KNOT));
+
+        match e.packet.interaction {
+            EntityInteraction::Attack => {
+                // The item of a frame is dropped before the frame itself is broken.
+                let dropped = match level.entities.ecs.get_mut::<ItemFrameEntity>(target) {
+                    Ok(frame) if frame.is_fixed() => return,
+                    Ok(mut frame) => frame.set_item(None),
+                    Err(_) => None
+                };
+                match dropped {
+                    Some(stack) => {
+                        let pos = level.entities.ecs.get::<BaseEntity>(target).unwrap().pos.clone();
+                        spawn_dropped(&mut level, &pos, vec![stack]);
+                    }
+                    None => {
+                        break_hanging_entity(&mut level, target);
+                    }
+                }
+            }
+            EntityInteraction::Interact(Hand::MainHand) => {
+                if level.entities.ecs.get::<ItemFrameEntity>(target).is_ok() {
+                    interact_item_frame(&mut level, entity, target);
+                } else if is_knot {
+                    // The knot is broken if the player has no mob to tie to it.
+                    let tile = level.entities.ecs.get::<HangingEntity>(target).unwrap().get_tile().clone();
+                    if tie_leashed_mobs(&mut level, entity, &tile) == 0 {
+                        break_hanging_entity(&mut level, target);
+                    }
+                } else if level.entities.ecs.get::<Leashed>(target).is_ok_and(|leashed| leashed.get_holder() == entity) {
+                    unleash_mob(&mut level, target, true);
+                } else if is_holding_lead(&level, entity) && can_be_leashed(&level, target) {
+                    let consumed = match level.entities.ecs.query_one_mut::<(&PlayerEntity, &mut Inventory)>(entity) {
+                        Ok((player, inventory)) => player.consume_item(inventory, player.get_selected_slot() as usize, 1),
+                        Err(_) => false
+                    };
+                    if consumed {
+                        leash_mob(&mut level, target, entity);
+                    }
+                }
+            }
+            _ => {}
+        }
+
+    });
+
+}
@@ -0,0 +1,199 @@
+//! Sound and particle effects emitted by the game logic. Effects are queued in the `Effects`
+//! component and sent to the players close enough to hear or see them.
+
+use mc_runtime::world::World;
+use mc_core::world::level::BaseEntity;
+use mc_core::sound::{Sound, SoundCategory};
+use mc_core::particle::Particle;
+use mc_core::pos::EntityPos;
+
+use super::protocol::{ProtocolServer, ProtocolPlayerEntity};
+use crate::protocol::play::{NamedSoundEffectPacket, ParticlePacket, ParticleData, get_particle_id};
+use crate::protocol::version::ClientboundPacket;
+
+
+/// Distance from which players hear sounds with a volume of 1 or less, sounds with greater
+/// volumes are heard from further.
+pub const SOUND_RANGE: f64 = 16.0;
+/// Distance from which players see particles.
+pub const PARTICLE_RANGE: f64 = 32.0;
+/// Distance from which players see long distance particles.
+pub const LONG_DISTANCE_PARTICLE_RANGE: f64 = 512.0;
+
+
+struct SoundEffect {
+    level_idx: usize,
+    pos: EntityPos,
+    sound: &'static Sound,
+    category: SoundCategory,
+    volume: f32,
+    pitch: f32
+}
+
+
+/// A particle effect, multiple particles can be spawned randomly around its position.
+#[derive(Debug, Clone)]
+pub struct ParticleEffect {
+    particle: &'static Particle,
+    pos: EntityPos,
+    offset: (f32, f32, f32),
+    speed: f32,
+    count: i32,
+    data: ParticleData,
+    long_distance: bool
+}
+
+impl ParticleEffect {
+
+    pub fn new(particle: &'static Particle, pos: EntityPos) -> Self {
+        Self {
+            particle,
+            pos,
+            offset: (0.0, 0.0, 0.0),
+            speed: 0.0,
+            count: 1,
+            data: ParticleData::None,
+            long_distance: false
+        }
+    }
+
+    /// Set the maximum offset of particles from the position on each axis.
+    pub fn with_offset(mut self, x: f32, y: f32, z: f32) -> Self {
+        self.offset = (x, y, z);
+        self
+    }
+
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn with_count(mut self, count: i32) -> Self {
+        self.count = count;
+        self
+    }
+
+    pub fn with_data(mut self, data: ParticleData) -> Self {
+        self.data = data;
+        self
+    }
+
+    pub fn with_long_distance(mut self, long_distance: bool) -> Self {
+        self.long_distance = long_distance;
+        self
+    }
+
+    fn get_range(&self) -> f64 {
+        if self.long_distance { LONG_DISTANCE_PARTICLE_RANGE } else { PARTICLE_RANGE }
+    }
+
+}
+
+
+/// A world component queuing effects until they are sent by `system_effects`.
+pub struct Effects {
+    sounds: Vec<SoundEffect>,
+    particles: Vec<(usize, ParticleEffect)>
+}
+
+impl Effects {
+
+    pub fn new() -> Self {
+        Self {
+            sounds: Vec::new(),
+            particles: Vec::new()
+        }
+    }
+
+    /// Play a sound at a position in the level with the given index.
+    pub fn play_sound(&mut self, level_idx: usize, pos: EntityPos, sound: &'static Sound, category: SoundCategory, volume: f32, pitch: f32) {
+        self.sounds.push(SoundEffect {
+            level_idx,
+            pos,
+            sound,
+            category,
+            volume,
+            pitch
+        });
+    }
+
+    /// Spawn a particle effect in the level with the given index.
+    pub fn spawn_particle(&mut self, level_idx: usize, effect: ParticleEffect) {
+        self.particles.push((level_idx, effect));
+    }
+
+}
+
+impl Default for Effects {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// Internal function to check if a position is in range of another one.
+fn in_range(a: &EntityPos, b: &EntityPos, range: f64) -> bool {
+    let (dx, dy, dz) = (a.x - b.x, a.y - b.y, a.z - b.z);
+    dx * dx + dy * dy + dz * dz <= range * range
+}
+
+
+/// System sending queued effects to players in range, particles that don't exist in the
+/// protocol version of a player are not sent to it.
+pub fn system_effects(world: &mut World) {
+
+    let proto_server = world.components.get::<ProtocolServer>().unwrap();
+    let mut effects = match world.components.get_mut::<Effects>() {
+        Ok(effects) => effects,
+        Err(_) => return
+    };
+
+    for sound in effects.sounds.drain(..) {
+        let level = match world.levels.get(sound.level_idx) {
+            Some(level) => level.borrow(),
+            None => continue
+        };
+        let range = SOUND_RANGE * (sound.volume as f64).max(1.0);
+        for (_, (base, player)) in level.entities.ecs.query::<(&BaseEntity, &ProtocolPlayerEntity)>().iter() {
+            if in_range(&base.pos, &sound.pos, range) {
+                proto_server.send_packet(player.addr, ClientboundPacket::NamedSoundEffect, &mut NamedSoundEffectPacket {
+                    sound: sound.sound,
+                    category: sound.category,
+                    x: sound.pos.x,
+                    y: sound.pos.y,
+                    z: sound.pos.z,
+                    volume: sound.volume,
+                    pitch: sound.pitch
+                });
+            }
+        }
+    }
+
+    for (level_idx, effect) in effects.particles.drain(..) {
+        let level = match world.levels.get(level_idx) {
+            Some(level) => level.borrow(),
+            None => continue
+        };
+        let range = effect.get_range();
+        for (_, (base, player)) in level.entities.ecs.query::<(&BaseEntity, &ProtocolPlayerEntity)>().iter() {
+            let supported = proto_server.get_client(player.addr)
+                .is_some_and(|client| get_particle_id(effect.particle, client.get_version()).is_some());
+            if supported && in_range(&base.pos, &effect.pos, range) {
+                proto_server.send_packet(player.addr, ClientboundPacket::Particle, &mut ParticlePacket {
+                    particle: effect.particle,
+                    long_distance: effect.long_distance,
+                    x: effect.pos.x,
+                    y: effect.pos.y,
+                    z: effect.pos.z,
+                    offset_x: effect.offset.0,
+                    offset_y: effect.offset.1,
+                    offset_z: effect.offset.2,
+                    particle_data: effect.speed,
+                    count: effect.count,
+                    data: effect.data
+                });
+            }
+        }
+    }
+
+}
@@ -0,0 +1,205 @@
+//! Game modes and abilities of players, the `PlayerEntity` component is the authority and
+//! changes made to it are synchronized to clients by `system_player_abilities`. Digging
+//! packets are validated against the game mode and the held item before breaking blocks,
+//! and spectators pass through blocks and entities.
+
+use mc_runtime::world::World;
+use mc_runtime::system::NoClip;
+use mc_core::world::level::BaseEntity;
+use mc_core::item::Inventory;
+use mc_core::pos::{EntityPos, BlockPos};
+use mc_vanilla::entity::{PlayerEntity, PlayerAbilities};
+use mc_vanilla::util::GameMode;
+use mc_vanilla::block::AIR;
+
+use super::protocol::{ProtocolServer, ProtocolPlayerEntity};
+use super::player_list::PlayerList;
+use crate::protocol::play::{PlayerAbilitiesPacket, ClientPlayerAbilitiesPacket, ChangeGameStatePacket, GameStateChange};
+use crate::protocol::play::{PlayerDiggingPacket, DiggingStatus, HeldItemChangePacket};
+use crate::protocol::version::{ClientboundPacket, ServerboundPacket};
+
+
+/// Maximum distance between the eyes of a player and the center of a block it breaks.
+pub const BLOCK_REACH: f64 = 6.0;
+/// Height of the point from which the reach of players is computed, like vanilla.
+const REACH_HEIGHT: f64 = 1.5;
+
+
+/// An entity component storing the game mode and abilities last sent to the client of a
+/// player entity.
+pub struct SyncedAbilities {
+    game_mode: GameMode,
+    abilities: PlayerAbilities
+}
+
+impl SyncedAbilities {
+
+    /// Create the synchronized state of a player, the given game mode and abilities must
+    /// have been sent to the client at login.
+    pub fn new(player: &PlayerEntity) -> Self {
+        Self {
+            game_mode: player.get_game_mode(),
+            abilities: player.get_abilities().clone()
+        }
+    }
+
+}
+
+
+/// Return true if a block is in reach of a player at the given position.
+pub fn is_in_reach(player_pos: &EntityPos, block_pos: &BlockPos) -> bool {
+    let dx = player_pos.x - (block_pos.x as f64 + 0.5);
+    let dy = player_pos.y + REACH_HEIGHT - (block_pos.y as f64 + 0.5);
+    let dz = player_pos.z - (block_pos.z as f64 + 0.5);
+    dx * dx + dy * dy + dz * dz <= BLOCK_REACH * BLOCK_REACH
+}
+
+
+/// Return true if entities of players in the given game mode must pass through blocks and
+/// entities.
+pub fn is_no_clip(game_mode: GameMode) -> bool {
+    game_mode == GameMode::Spectator
+}
+
+
+/// System sending game mode and abilities changes of player entities to their clients.
+pub fn system_player_abilities(world: &mut World) {
+
+    let proto_server = world.components.get::<ProtocolServer>().unwrap();
+    let mut player_list = world.components.get_mut::<PlayerList>().ok();
+
+    for level in &world.levels {
+
+        let mut level = level.borrow_mut();
+        let mut no_clip_changes = Vec::new();
+
+        for (entity, (base, player, proto_player, synced)) in level.entities.ecs.query_mut::<(&BaseEntity, &PlayerEntity, &ProtocolPlayerEntity, &mut SyncedAbilities)>() {
+
+            let game_mode = player.get_game_mode();
+            if synced.game_mode != game_mode {
+                if is_no_clip(synced.game_mode) != is_no_clip(game_mode) {
+                    no_clip_changes.push((entity, is_no_clip(game_mode)));
+                }
+                synced.game_mode = game_mode;
+                proto_server.send_packet(proto_player.addr, ClientboundPacket::ChangeGameState, &mut ChangeGameStatePacket {
+                    change: GameStateChange::ChangeGameMode(game_mode)
+                });
+                if let Some(player_list) = &mut player_list {
+                    player_list.update_game_mode(base.uuid, game_mode);
+                }
+            }
+
+            let abilities = player.get_abilities();
+            if &synced.abilities != abilities {
+                synced.abilities = abilities.clone();
+                proto_server.send_packet(proto_player.addr, ClientboundPacket::PlayerAbilities, &mut PlayerAbilitiesPacket::new(abilities));
+            }
+
+        }
+
+        for (entity, no_clip) in no_clip_changes {
+            if no_clip {
+                let _ = level.entities.ecs.insert_one(entity, NoClip);
+            } else {
+                let _ = level.entities.ecs.remove_one::<NoClip>(entity);
+            }
+        }
+
+    }
+
+}
+
+
+pub(super) fn register_listeners(server: &mut ProtocolServer) {
+
+    server.add_listener::<_, ClientPlayerAbilitiesPacket>(ServerboundPacket::PlayerAbilities, |e| {
+        if let Some((level_idx, entity)) = e.get_player() {
+            let mut level = e.world.levels[level_idx].borrow_mut();
+            if let Ok((player, synced)) = level.entities.ecs.query_one_mut::<(&mut PlayerEntity, &mut SyncedAbilities)>(entity) {
+                // The client is now in the requested state, if the player is not allowed to
+                // fly the abilities are sent again by the synchronization system.
+                synced.abilities.flying = e.packet.flying;
+                let abilities = player.get_abilities_mut();
+                if abilities.may_fly || !e.packet.flying {
+                    abilities.flying = e.packet.flying;
+                }
+            }
+        }
+    });
+
+    server.add_listener::<_, HeldItemChangePacket>(ServerboundPacket::HeldItemChange, |e| {
+        if let Some((level_idx, entity)) = e.get_player() {
+            let level = e.world.levels[level_idx].borrow();
+            if let Ok(mut player) = level.entities.ecs.get_mut::<PlayerEntity>(entity) {
+                if (0..9).contains(&e.packet.slot) {
+                    player.set_selected_slot(e.packet.slot as u8);
+                }
+            };
+        }
+    });
+
+    server.add_listener::<_, PlayerDiggingPacket>(ServerboundPacket::PlayerDigging, |e| {
+
+        let (level_idx, entity) = match e.get_player() {
+            Some(player) => player,
+            None => return
+        };
+
+        let level = e.world.levels[level_idx].borrow();
+        let ecs = &level.entities.ecs;
+        let (player, base) = match (ecs.get::<PlayerEntity>(entity), ecs.get::<BaseEntity>(entity)) {
+            (Ok(player), Ok(base)) => (player, base),
+            _ => return
+        };
+
+        // Blocks are broken instantly by players building instantly, otherwise once the
+        // client has finished digging, the digging time is not checked because block
+        // hardness is not defined.
+        let instant_build = player.get_abilities().instant_build;
+        let breaking = match e.packet.status {
+            DiggingStatus::StartedDigging => instant_build,
+            DiggingStatus::FinishedDigging => !instant_build,
+            _ => false
+        };
+
+        if !breaking {
+            return;
+        }
+
+        let pos = &e.packet.pos;
+        let state = match level.chunks.get_block_at(pos.x, pos.y, pos.z) {
+            Ok(state) => state,
+            Err(_) => return
+        };
+
+        let inventory = ecs.get::<Inventory>(entity).ok();
+        let held = inventory.as_ref().and_then(|inventory| inventory.get_stack(player.get_selected_slot() as usize));
+        let blocks = &level.get_env().blocks;
+
+        if is_in_reach(&base.pos, pos) && player.can_break_block(held, blocks, state.get_block()) {
+            let _ = level.chunks.set_block_at(pos.x, pos.y, pos.z, AIR.get_default_state());
+        } else {
+            println!("[{}] Block break rejected at {:?}.", e.client.get_addr(), pos);
+        }
+
+    });
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn block_reach() {
+        let pos = EntityPos::new(0.5, 64.0, 0.5);
+        assert!(is_in_reach(&pos, &BlockPos::new(0, 63, 0)));
+        assert!(is_in_reach(&pos, &BlockPos::new(4, 65, 3)));
+        assert!(!is_in_reach(&pos, &BlockPos::new(6, 64, 0)));
+        assert!(is_no_clip(GameMode::Spectator));
+        assert!(!is_no_clip(GameMode::Creative));
+    }
+
+}
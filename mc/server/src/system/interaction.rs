@@ -0,0 +1,225 @@
+//! Use of blocks by players, block placement packets are dispatched to the vanilla use block
+//! behaviours and the windows requested by blocks are opened. Chests lend their inventory
+//! to the window of the player using them, the inventory is given back when the window is
+//! closed, when the player dies or when it disconnects.
+//!
+//! Block changes made by behaviours are not sent to clients yet, and items held by players
+//! are not used when a block has no use. Lectern windows are not opened yet.
+
+use mc_runtime::world::World;
+use mc_core::world::level::{Level, BaseEntity};
+use mc_core::item::Inventory;
+use mc_core::pos::{EntityPos, BlockPos};
+use mc_vanilla::block::interaction::{UseBlockResult, use_block};
+use mc_vanilla::block_entity::ChestBlockEntity;
+use mc_vanilla::sleep::BedInteraction;
+use mc_vanilla::entity::PlayerEntity;
+use mc_vanilla::death::Dead;
+use mc_vanilla::advancement::get_plain_text;
+use hecs::Entity;
+
+use super::protocol::ProtocolServer;
+use super::window::{PlayerWindows, WindowType, spawn_dropped};
+use super::game_mode::is_in_reach;
+use super::merchant::close_merchant_window;
+use crate::protocol::play::{PlayerBlockPlacementPacket, Hand, ChatMessagePacket, ChatPosition};
+use crate::protocol::version::{ClientboundPacket, ServerboundPacket};
+
+
+/// An entity component for players, the block whose window is opened by the player.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockWindow {
+    /// A chest whose inventory is in the container window.
+    Chest {
+        entity: Entity,
+        pos: BlockPos
+    },
+    /// A crafting table, the crafting grid is emptied in the player inventory on close.
+    CraftingTable
+}
+
+
+/// Return the message shown above the hotbar of a player after using a bed.
+pub fn get_bed_message(interaction: &BedInteraction) -> Option<&'static str> {
+    match interaction {
+        BedInteraction::NotPossibleNow => Some("You can sleep only at night and during thunderstorms"),
+        BedInteraction::Occupied => Some("This bed is occupied"),
+        BedInteraction::TooFarAway => Some("You may not rest now; the bed is too far away"),
+        BedInteraction::Obstructed => Some("This bed is obstructed"),
+        BedInteraction::Sleeping | BedInteraction::Explode(_) => None
+    }
+}
+
+
+/// Give back the inventory of a closed block window. Stacks that can't be given back are
+/// dropped, at the chest if it has been removed, at the player otherwise.
+fn release_block_window(level: &mut Level, player: Entity, window: BlockWindow, mut container: Inventory) {
+
+    let (pos, stacks) = match window {
+        BlockWindow::Chest { entity, pos } => {
+            if let Ok(mut chest) = level.entities.ecs.get_mut::<ChestBlockEntity>(entity) {
+                chest.put_inventory(container);
+                return;
+            }
+            let pos = EntityPos::new(pos.x as f64 + 0.5, pos.y as f64, pos.z as f64 + 0.5);
+            (pos, (0..container.get_size()).filter_map(|i| container.take_stack(i)).collect())
+        }
+        BlockWindow::CraftingTable => {
+            let (base, inventory) = match level.entities.ecs.query_one_mut::<(&BaseEntity, &mut Inventory)>(player) {
+                Ok(comps) => comps,
+                Err(_) => return
+            };
+            let stacks = (0..container.get_size())
+                .filter_map(|i| container.take_stack(i))
+                .filter_map(|stack| inventory.insert(stack))
+                .collect();
+            (base.pos.clone(), stacks)
+        }
+    };
+
+    spawn_dropped(level, &pos, stacks);
+
+}
+
+/// Close the block window of a player and give back its inventory, this must be called
+/// before removing a player entity from its level.
+pub fn close_block_window(level: &mut Level, player: Entity) {
+
+    let window = match level.entities.ecs.remove_one::<BlockWindow>(player) {
+        Ok(window) => window,
+        Err(_) => return
+    };
+
+    let container = match level.entities.ecs.query_one_mut::<(&mut PlayerWindows, &mut Inventory)>(player) {
+        Ok((windows, inventory)) => windows.close_container(inventory)
+            .or_else(|| windows.take_closed_containers().pop()),
+        Err(_) => None
+    };
+
+    if let Some(container) = container {
+        release_block_window(level, player, window, container);
+    }
+
+}
+
+
+/// System giving back the inventories of the block windows closed by players, the windows
+/// of dead players are closed.
+pub fn system_block_windows(world: &mut World) {
+
+    for level in &world.levels {
+
+        let mut level = level.borrow_mut();
+        let mut closed = Vec::new();
+
+        for (entity, (windows, inventory, dead)) in level.entities.ecs.query_mut::<(&mut PlayerWindows, &mut Inventory, Option<&Dead>)>().with::<BlockWindow>() {
+            if dead.is_some() {
+                if let Some(container) = windows.close_container(inventory) {
+                    closed.push((entity, container));
+                }
+            }
+            closed.extend(windows.take_closed_containers().into_iter().map(|container| (entity, container)));
+        }
+
+        for (entity, container) in closed {
+            if let Ok(window) = level.entities.ecs.remove_one::<BlockWindow>(entity) {
+                release_block_window(&mut level, entity, window, container);
+            }
+        }
+
+    }
+
+}
+
+
+pub(super) fn register_listeners(server: &mut ProtocolServer) {
+
+    server.add_listener::<_, PlayerBlockPlacementPacket>(ServerboundPacket::PlayerBlockPlacement, |e| {
+
+        // Clients send the packet again with the offhand if the main hand had no use.
+        if e.packet.hand != Hand::MainHand {
+            return;
+        }
+
+        let (level_idx, entity) = match e.get_player() {
+            Some(player) => player,
+            None => return
+        };
+
+        let pos = &e.packet.pos;
+
+        {
+            let level = e.world.levels[level_idx].borrow();
+            let ecs = &level.entities.ecs;
+            let can_use = match (ecs.get::<PlayerEntity>(entity), ecs.get::<BaseEntity>(entity)) {
+                (Ok(player), Ok(base)) => player.can_interact() && is_in_reach(&base.pos, pos),
+                _ => false
+            };
+            if !can_use || ecs.get::<Dead>(entity).is_ok() || ecs.get::<PlayerWindows>(entity).is_err() {
+                println!("[{}] Block use rejected at {:?}.", e.client.get_addr(), pos);
+                return;
+            }
+        }
+
+        let result = use_block(e.world, level_idx, entity, pos, e.packet.face);
+
+        let mut level = e.world.levels[level_idx].borrow_mut();
+        let (window, window_type, title, container) = match result {
+            UseBlockResult::Pass | UseBlockResult::Success | UseBlockResult::OpenLectern(_) => return,
+            UseBlockResult::Bed(interaction) => {
+                if let Some(message) = get_bed_message(&interaction) {
+                    e.answer_packet(ClientboundPacket::ChatMessage, &mut ChatMessagePacket {
+                        message: message.to_string(),
+                        position: ChatPosition::GameInfo,
+                        sender: uuid::Uuid::nil()
+                    });
+                }
+                return;
+            }
+            UseBlockResult::OpenChest(chest_entity) => {
+                let mut chest = match level.entities.ecs.get_mut::<ChestBlockEntity>(chest_entity) {
+                    Ok(chest) => chest,
+                    Err(_) => return
+                };
+                let title = chest.get_custom_name()
+                    .and_then(|name| serde_json::from_str(name).ok())
+                    .map_or_else(|| "Chest".to_string(), |name| get_plain_text(&name));
+                match chest.take_inventory() {
+                    Some(container) => (BlockWindow::Chest { entity: chest_entity, pos: pos.clone() }, WindowType::Generic9x3, title, container),
+                    None => {
+                        println!("[{}] Chest at {:?} is already opened.", e.client.get_addr(), pos);
+                        return;
+                    }
+                }
+            }
+            UseBlockResult::OpenCraftingTable => {
+                let window_type = WindowType::Crafting;
+                (BlockWindow::CraftingTable, window_type, "Crafting".to_string(), Inventory::new(window_type.get_size()))
+            }
+        };
+
+        // The previously opened windows are given back before opening the new one.
+        close_block_window(&mut level, entity);
+        close_merchant_window(&mut level, entity);
+
+        let ecs = &mut level.entities.ecs;
+        ecs.get_mut::<PlayerWindows>(entity).unwrap().open_container(window_type, title, container);
+        ecs.insert_one(entity, window).unwrap();
+
+    });
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn bed_messages() {
+        assert_eq!(get_bed_message(&BedInteraction::Sleeping), None);
+        assert_eq!(get_bed_message(&BedInteraction::Occupied), Some("This bed is occupied"));
+    }
+
+}
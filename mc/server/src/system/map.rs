@@ -0,0 +1,134 @@
+//! Synchronization of filled maps with clients. Maps carried in the inventory of a player
+//! are fully sent the first time they are carried, their changed colors are then sent on
+//! each tick. The decorations of the players carrying a map are sent every 5 ticks.
+
+use std::collections::{HashMap, HashSet};
+
+use mc_runtime::world::World;
+use mc_runtime::event::EntityMovedEvent;
+use mc_core::world::level::BaseEntity;
+use mc_core::item::Inventory;
+use mc_vanilla::map::{Maps, MapPatch, get_map_id};
+
+use super::protocol::{ProtocolServer, ProtocolPlayerEntity};
+use crate::protocol::play::MapDataPacket;
+use crate::protocol::version::ClientboundPacket;
+
+
+/// Interval in ticks between two updates of map decorations.
+const DECORATIONS_INTERVAL: u32 = 5;
+
+
+/// An entity component for players, the maps already sent to the client and the last yaw
+/// of the player, used for its decoration on maps.
+#[derive(Debug, Clone, Default)]
+pub struct MapViewer {
+    known: HashSet<i32>,
+    yaw: f32
+}
+
+impl MapViewer {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return true if the map with the given ID has already been sent to the client.
+    pub fn is_known(&self, id: i32) -> bool {
+        self.known.contains(&id)
+    }
+
+}
+
+
+/// System sending the maps carried by players and their updates.
+pub fn system_map_sync(world: &mut World) {
+
+    let proto_server = world.components.get::<ProtocolServer>().unwrap();
+    let mut maps = match world.components.get_mut::<Maps>() {
+        Ok(maps) => maps,
+        Err(_) => return
+    };
+
+    for event in world.event_tracker.poll_events::<EntityMovedEvent>() {
+        if let Some((yaw, _)) = event.look {
+            let level = event.level.borrow();
+            if let Ok(mut viewer) = level.entities.ecs.get_mut::<MapViewer>(event.entity) {
+                viewer.yaw = yaw;
+            };
+        }
+    }
+
+    let patches: HashMap<i32, MapPatch> = maps.iter_maps_mut()
+        .filter_map(|(id, map)| map.take_dirty_patch().map(|patch| (id, patch)))
+        .collect();
+
+    let send_decorations = maps.get_ticks() % DECORATIONS_INTERVAL == 0;
+
+    for level in &world.levels {
+
+        let level = level.borrow();
+
+        // Players carrying maps, with the IDs of their maps.
+        let carriers: Vec<_> = level.entities.ecs.query::<(&BaseEntity, &Inventory, &MapViewer)>()
+            .iter()
+            .filter_map(|(entity, (base, inventory, viewer))| {
+                let mut ids: Vec<i32> = (0..inventory.get_size())
+                    .filter_map(|slot| inventory.get_stack(slot).and_then(get_map_id))
+                    .collect();
+                ids.sort_unstable();
+                ids.dedup();
+                (!ids.is_empty()).then_some((entity, base.pos.x, base.pos.z, viewer.yaw, ids))
+            })
+            .collect();
+
+        for (entity, _, _, _, ids) in &carriers {
+
+            let addr = match level.entities.ecs.get::<ProtocolPlayerEntity>(*entity) {
+                Ok(player) => player.addr,
+                Err(_) => continue
+            };
+
+            let mut viewer = level.entities.ecs.get_mut::<MapViewer>(*entity).unwrap();
+
+            for &id in ids {
+
+                let map = match maps.get_map(id) {
+                    Some(map) => map,
+                    None => continue
+                };
+
+                let patch = if viewer.known.insert(id) {
+                    Some(map.get_full_patch())
+                } else {
+                    patches.get(&id).cloned()
+                };
+
+                if patch.is_none() && !(send_decorations && map.is_tracking_position()) {
+                    continue;
+                }
+
+                // Only the players in the level of the map are displayed on it.
+                let decorations = map.is_tracking_position().then(|| {
+                    carriers.iter()
+                        .filter(|(_, _, _, _, ids)| map.get_dimension() == level.get_id() && ids.contains(&id))
+                        .filter_map(|&(_, x, z, yaw, _)| map.get_player_decoration(x, z, yaw))
+                        .collect()
+                });
+
+                proto_server.send_packet(addr, ClientboundPacket::MapData, &mut MapDataPacket {
+                    map_id: id,
+                    scale: map.get_scale(),
+                    tracking_position: map.is_tracking_position(),
+                    locked: map.is_locked(),
+                    decorations,
+                    patch
+                });
+
+            }
+
+        }
+
+    }
+
+}
@@ -0,0 +1,234 @@
+//! Trading of players with merchants, interacting with a villager or a wandering trader opens
+//! its trading window. Offers traded in the window are recorded on the merchant by the
+//! system, and the payment left in the window is given back to the player when the window
+//! is closed, when the player dies or disconnects, or when the merchant is no longer
+//! available.
+//!
+//! Experience orbs rewarded by trades are not spawned yet.
+
+use std::rc::Rc;
+
+use mc_runtime::world::World;
+use mc_core::world::level::{Level, BaseEntity};
+use mc_core::item::Inventory;
+use mc_vanilla::entity::merchant::{MerchantEntity, MerchantTradeEvent, MERCHANT_PAYMENT_A_SLOT, MERCHANT_PAYMENT_B_SLOT};
+use mc_vanilla::entity::{PlayerEntity, VILLAGER};
+use mc_vanilla::death::Dead;
+use hecs::Entity;
+
+use super::protocol::ProtocolServer;
+use super::window::{PlayerWindows, spawn_dropped};
+use super::interaction::close_block_window;
+use crate::protocol::play::{InteractEntityPacket, EntityInteraction, Hand, SelectTradePacket};
+use crate::protocol::version::ServerboundPacket;
+
+
+/// Maximum distance between a player and the merchant it trades with.
+const TRADE_DISTANCE: f64 = 8.0;
+
+
+/// An entity component for players, the merchant whose trading window is opened by the
+/// player.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MerchantWindow {
+    pub merchant: Entity
+}
+
+
+/// Return the merchant entity with the given network ID.
+fn find_merchant(level: &Level, eid: i32) -> Option<Entity> {
+    level.entities.ecs.query::<&MerchantEntity>()
+        .iter()
+        .map(|(entity, _)| entity)
+        .find(|entity| entity.id() as i32 == eid)
+}
+
+/// Return true if the merchant is alive and close enough to trade with the player.
+fn is_merchant_available(level: &Level, player: Entity, merchant: Entity) -> bool {
+    let ecs = &level.entities.ecs;
+    if ecs.get::<MerchantEntity>(merchant).is_err() || ecs.get::<Dead>(merchant).is_ok() {
+        return false;
+    }
+    match (ecs.get::<BaseEntity>(player), ecs.get::<BaseEntity>(merchant)) {
+        (Ok(player_base), Ok(merchant_base)) => {
+            let (a, b) = (&player_base.pos, &merchant_base.pos);
+            let (dx, dy, dz) = (a.x - b.x, a.y - b.y, a.z - b.z);
+            dx * dx + dy * dy + dz * dz < TRADE_DISTANCE * TRADE_DISTANCE
+        }
+        _ => false
+    }
+}
+
+
+/// Give back the payment of a closed merchant window to the player, stacks that don't fit
+/// in the player inventory are dropped. The merchant can trade with other players again.
+fn release_merchant_window(level: &mut Level, player: Entity, window: MerchantWindow, mut container: Inventory) {
+
+    if let Ok(mut merchant) = level.entities.ecs.get_mut::<MerchantEntity>(window.merchant) {
+        if merchant.get_trader() == Some(player) {
+            merchant.set_trader(None);
+        }
+    }
+
+    let (base, inventory) = match level.entities.ecs.query_one_mut::<(&BaseEntity, &mut Inventory)>(player) {
+        Ok(comps) => comps,
+        Err(_) => return
+    };
+
+    let stacks = [MERCHANT_PAYMENT_A_SLOT, MERCHANT_PAYMENT_B_SLOT].iter()
+        .filter_map(|&slot| container.take_stack(slot))
+        .filter_map(|stack| inventory.insert(stack))
+        .collect();
+
+    let pos = base.pos.clone();
+    spawn_dropped(level, &pos, stacks);
+
+}
+
+/// Close the merchant window of a player and give back its payment, this must be called
+/// before removing a player entity from its level.
+pub fn close_merchant_window(level: &mut Level, player: Entity) {
+
+    let window = match level.entities.ecs.remove_one::<MerchantWindow>(player) {
+        Ok(window) => window,
+        Err(_) => return
+    };
+
+    let container = match level.entities.ecs.query_one_mut::<(&mut PlayerWindows, &mut Inventory)>(player) {
+        Ok((windows, inventory)) => windows.close_container(inventory)
+            .or_else(|| windows.take_closed_containers().pop()),
+        Err(_) => None
+    };
+
+    if let Some(container) = container {
+        release_merchant_window(level, player, window, container);
+    } else if let Ok(mut merchant) = level.entities.ecs.get_mut::<MerchantEntity>(window.merchant) {
+        merchant.set_trader(None);
+    }
+
+}
+
+
+/// System recording the trades of players on merchants, a `MerchantTradeEvent` is pushed
+/// for each trade. Merchant windows closed by players are given back, and the windows of
+/// dead players or of unavailable merchants are closed.
+pub fn system_merchant_windows(world: &mut World) {
+
+    for level_rc in &world.levels {
+
+        let mut level = level_rc.borrow_mut();
+        let mut trades = Vec::new();
+        let mut closed = Vec::new();
+
+        for (entity, (windows, inventory, window, dead)) in level.entities.ecs.query_mut::<(&mut PlayerWindows, &mut Inventory, &MerchantWindow, Option<&Dead>)>() {
+            trades.push((entity, window.merchant, windows.take_trades()));
+            if dead.is_some() {
+                if let Some(container) = windows.close_container(inventory) {
+                    closed.push((entity, container));
+                }
+            }
+            closed.extend(windows.take_closed_containers().into_iter().map(|container| (entity, container)));
+        }
+
+        for (player, merchant_entity, indices) in trades {
+            if let Ok(mut merchant) = level.entities.ecs.get_mut::<MerchantEntity>(merchant_entity) {
+                for index in indices {
+                    if let Some(offer) = merchant.complete_trade(index) {
+                        world.event_tracker.push_event(MerchantTradeEvent {
+                            level: Rc::clone(level_rc),
+                            merchant: merchant_entity,
+                            player,
+                            offer: offer.clone()
+                        });
+                    }
+                }
+            }
+        }
+
+        for (entity, container) in closed {
+            if let Ok(window) = level.entities.ecs.remove_one::<MerchantWindow>(entity) {
+                release_merchant_window(&mut level, entity, window, container);
+            }
+        }
+
+        let unavailable: Vec<Entity> = level.entities.ecs.query::<&MerchantWindow>()
+            .iter()
+            .filter(|&(entity, window)| !is_merchant_available(&level, entity, window.merchant))
+            .map(|(entity, _)| entity)
+            .collect();
+
+        for entity in unavailable {
+            close_merchant_window(&mut level, entity);
+        }
+
+    }
+
+}
+
+
+pub(super) fn register_listeners(server: &mut ProtocolServer) {
+
+    server.add_listener::<_, InteractEntityPacket>(ServerboundPacket::InteractEntity, |e| {
+
+        // Clients also send the interaction with the offhand and the interaction position.
+        if e.packet.interaction != EntityInteraction::Interact(Hand::MainHand) {
+            return;
+        }
+
+        let (level_idx, entity) = match e.get_player() {
+            Some(player) => player,
+            None => return
+        };
+
+        let mut level = e.world.levels[level_idx].borrow_mut();
+        let merchant_entity = match find_merchant(&level, e.packet.eid) {
+            Some(merchant) => merchant,
+            None => return
+        };
+
+        let (trade, title) = {
+            let ecs = &level.entities.ecs;
+            let can_trade = ecs.get::<PlayerEntity>(entity).is_ok_and(|player| player.can_interact())
+                && ecs.get::<Dead>(entity).is_err()
+                && ecs.get::<PlayerWindows>(entity).is_ok()
+                && is_merchant_available(&level, entity, merchant_entity);
+            let merchant = ecs.get::<MerchantEntity>(merchant_entity).unwrap();
+            if !can_trade || !merchant.can_trade_with(entity) {
+                return;
+            }
+            let regular = std::ptr::eq(ecs.get::<BaseEntity>(merchant_entity).unwrap().entity_type, &VILLAGER);
+            let title = if regular { "Villager" } else { "Wandering Trader" };
+            (merchant.start_trade(regular), title.to_string())
+        };
+
+        // The previously opened windows are given back before opening the new one.
+        close_block_window(&mut level, entity);
+        close_merchant_window(&mut level, entity);
+
+        let ecs = &mut level.entities.ecs;
+        ecs.get_mut::<MerchantEntity>(merchant_entity).unwrap().set_trader(Some(entity));
+        ecs.get_mut::<PlayerWindows>(entity).unwrap().open_merchant(title, trade);
+        ecs.insert_one(entity, MerchantWindow { merchant: merchant_entity }).unwrap();
+
+    });
+
+    server.add_listener::<_, SelectTradePacket>(ServerboundPacket::SelectTrade, |e| {
+
+        let (level_idx, entity) = match e.get_player() {
+            Some(player) => player,
+            None => return
+        };
+
+        if e.packet.index < 0 {
+            return;
+        }
+
+        // The moved payment is sent to the client by the window synchronization.
+        let mut level = e.world.levels[level_idx].borrow_mut();
+        if let Ok((windows, inventory, _)) = level.entities.ecs.query_one_mut::<(&mut PlayerWindows, &mut Inventory, &MerchantWindow)>(entity) {
+            windows.select_trade(inventory, e.packet.index as usize);
+        }
+
+    });
+
+}
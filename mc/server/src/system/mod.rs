@@ -1,15 +1,156 @@
+use mc_core::block::GlobalBlocks;
 use mc_runtime::world::{World, WorldSystemExecutor};
+use mc_runtime::command::CommandDispatcher;
+use mc_runtime::system::{PlayerMovements, EntityCollisions, EntityCollision, Projectiles, Riding, TickBudgets};
+use mc_vanilla::block::VANILLA_BLOCKS;
+use mc_vanilla::block::material::{TAG_NON_BLOCKING, VANILLA_BLOCK_MATERIALS};
+use mc_vanilla::sleep::SleepTracker;
+use mc_vanilla::death::Deaths;
+use mc_vanilla::hunger::HungerRules;
+use mc_vanilla::advancement::Advancements;
+use mc_vanilla::block::interaction::UseBlockBehaviours;
+use mc_vanilla::block::contact::ContactBlocks;
+use mc_vanilla::block::tick::RandomTicks;
+use mc_vanilla::ext::WithVanilla;
+use mc_vanilla::entity::explosive::Explosives;
+use mc_vanilla::entity::lifecycle::MobLifecycle;
+use mc_vanilla::entity::breeding::Breeding;
+use mc_vanilla::command::function::Functions;
+use mc_vanilla::map::Maps;
+use mc_vanilla::entity::{BOAT, MINECART, CHICKEN, COW, PIG, SHEEP, VILLAGER, ZOMBIE, ITEM_FRAME};
+use mc_vanilla::entity::{HORSE, DONKEY, MULE, PLAYER};
+
+use crate::config::ServerProperties;
 
 pub mod protocol;
 pub mod player;
+pub mod player_list;
+pub mod chunk_cache;
+pub mod window;
+pub mod effect;
+pub mod command;
+pub mod access;
+pub mod chat;
+pub mod game_mode;
+pub mod death;
+pub mod advancement;
+pub mod interaction;
+pub mod merchant;
+pub mod map;
+pub mod riding;
+pub mod decoration;
 
 
 /// Register all systems required for the server to run.
 pub fn register_systems(world: &mut World, executor: &mut WorldSystemExecutor) {
 
+    // Build all block states now rather than on their first access while ticking.
+    GlobalBlocks::init_all_states(&VANILLA_BLOCKS, 4);
     protocol::register_systems(world, executor);
+    world.insert_component(player_list::PlayerList::new());
+    world.insert_component(chunk_cache::ChunkPacketCache::new());
+    // Vanilla items are not defined yet, so no item can be sent in windows.
+    world.insert_component(window::NetworkItems::new());
+    world.insert_component(effect::Effects::new());
+    world.insert_component(chat::Chat::new());
+    // Properties can be inserted before registering systems, defaults are used otherwise.
+    if world.get_component::<ServerProperties>().is_err() {
+        world.insert_component(ServerProperties::new());
+    }
+    // Lists can be loaded from a server directory with `AccessLists::load`.
+    let white_list = world.get_component::<ServerProperties>().unwrap().white_list;
+    world.insert_component(access::AccessLists::new().with_whitelist_enabled(white_list));
+    mc_runtime::system::register_player_movement(world, PlayerMovements::new()
+        .with_passable_tag(&TAG_NON_BLOCKING));
+    mc_runtime::system::register_projectiles(world, Projectiles::new(&TAG_NON_BLOCKING));
+    // Projectiles and random ticks of each level are limited to this budget per tick.
+    mc_runtime::system::register_tick_budgets(world, TickBudgets::new());
+    // Players are not registered because their clients push them.
+    mc_runtime::system::register_entity_collision(world, EntityCollisions::new()
+        .with_entity_type(&BOAT, 1.375, 0.5625, EntityCollision::Solid)
+        .with_entity_type(&MINECART, 0.98, 0.7, EntityCollision::Push)
+        .with_entity_type(&CHICKEN, 0.4, 0.7, EntityCollision::Push)
+        .with_entity_type(&COW, 0.9, 1.4, EntityCollision::Push)
+        .with_entity_type(&PIG, 0.9, 0.9, EntityCollision::Push)
+        .with_entity_type(&SHEEP, 0.9, 1.3, EntityCollision::Push)
+        .with_entity_type(&VILLAGER, 0.6, 1.95, EntityCollision::Push)
+        .with_entity_type(&ZOMBIE, 0.6, 1.95, EntityCollision::Push)
+        .with_entity_type(&ITEM_FRAME, 0.5, 0.5, EntityCollision::None));
+    // Offsets are the heights of the seats of vehicles, and the sitting heights of riders.
+    mc_runtime::system::register_riding(world, Riding::new()
+        .with_vehicle_offset(&BOAT, -0.1)
+        .with_vehicle_offset(&MINECART, 0.0)
+        .with_vehicle_offset(&PIG, 0.675)
+        .with_vehicle_offset(&HORSE, 1.2)
+        .with_vehicle_offset(&DONKEY, 1.125)
+        .with_vehicle_offset(&MULE, 1.2)
+        .with_passenger_offset(&PLAYER, -0.35));
+    // Levels must be added to the tracker with their time to allow skipping nights.
+    mc_vanilla::sleep::register_sleep(world, SleepTracker::new());
+    mc_vanilla::death::register_deaths(world, Deaths::new());
+    mc_vanilla::advancement::register_advancements(world, Advancements::new());
+    mc_vanilla::entity::explosive::register_explosives(world, Explosives::new(&VANILLA_BLOCK_MATERIALS));
+    mc_vanilla::block::interaction::register_use_block(world, UseBlockBehaviours::with_vanilla());
+    mc_vanilla::block::contact::register_contact_blocks(world, ContactBlocks::with_vanilla());
+    mc_vanilla::block::tick::register_random_ticks(world, RandomTicks::with_vanilla());
+    let difficulty = world.get_component::<ServerProperties>().unwrap().difficulty;
+    mc_vanilla::entity::lifecycle::register_mob_lifecycle(world, MobLifecycle::with_vanilla()
+        .with_difficulty(difficulty));
+    mc_vanilla::hunger::register_hunger(world, HungerRules::new()
+        .with_difficulty(difficulty));
+    mc_vanilla::entity::breeding::register_breeding(world, Breeding::with_vanilla());
+    // Maps can be loaded from a world directory with `Maps::load` before registering systems.
+    if world.get_component::<Maps>().is_err() {
+        mc_vanilla::map::register_maps(world, Maps::new());
+    }
+    let mut dispatcher = CommandDispatcher::new();
+    mc_vanilla::command::register_vanilla_commands(&mut dispatcher);
+    world.insert_component(dispatcher);
+    // Datapack functions can be loaded into this component.
+    world.insert_component(Functions::new());
 
+    executor.add_system(mc_runtime::system::system_tick_budgets);
+    executor.add_system(chat::system_chat_receive);
+    executor.add_system(mc_runtime::system::system_player_movement);
+    executor.add_system(mc_runtime::system::system_projectile);
+    executor.add_system(mc_vanilla::entity::vehicle::system_vehicles);
+    executor.add_system(mc_vanilla::entity::item::system_item_entities);
+    executor.add_system(mc_vanilla::entity::lifecycle::system_mob_lifecycle);
+    executor.add_system(mc_vanilla::entity::breeding::system_breeding);
+    executor.add_system(mc_vanilla::entity::decoration::system_decorations);
+    executor.add_system(mc_vanilla::block::interaction::system_block_ticks);
+    executor.add_system(mc_vanilla::block::contact::system_contact_blocks);
+    executor.add_system(mc_vanilla::block::tick::system_random_ticks);
+    executor.add_system(mc_vanilla::block::beehive::system_beehives);
+    executor.add_system(mc_vanilla::block::lectern::system_lecterns);
+    executor.add_system(mc_vanilla::sleep::system_sleep);
+    executor.add_system(mc_vanilla::entity::explosive::system_explosives);
+    executor.add_system(mc_vanilla::command::block::system_command_blocks);
+    executor.add_system(mc_vanilla::hunger::system_hunger);
+    executor.add_system(mc_vanilla::map::system_maps);
+    executor.add_system(mc_vanilla::death::system_deaths);
+    executor.add_system(death::system_player_deaths);
+    executor.add_system(mc_vanilla::advancement::system_advancements);
+    executor.add_system(advancement::system_advancement_announce);
+    executor.add_system(mc_runtime::system::system_entity_collision);
+    executor.add_system(mc_runtime::system::system_riding);
+    executor.add_system(player::system_player_movement_sync);
     executor.add_system(player::system_player_view);
+    executor.add_system(mc_runtime::system::system_view_tracking);
+    executor.add_system(player::system_player_tracking);
+    executor.add_system(riding::system_passengers_sync);
+    executor.add_system(decoration::system_decoration_sync);
+    executor.add_system(game_mode::system_player_abilities);
+    executor.add_system(player_list::system_player_list);
+    executor.add_system(chat::system_chat_broadcast);
+    executor.add_system(interaction::system_block_windows);
+    executor.add_system(merchant::system_merchant_windows);
+    executor.add_system(window::system_window_sync);
+    executor.add_system(map::system_map_sync);
+    executor.add_system(effect::system_effects);
+    executor.add_system(mc_runtime::system::system_inhabited_time);
+    executor.add_system(mc_runtime::system::system_chunk_tickets);
     executor.add_system(mc_runtime::system::system_load_chunks);
+    executor.add_system(chunk_cache::system_chunk_packet_cache);
 
 }
@@ -1,42 +1,152 @@
 use mc_runtime::world::World;
+use mc_runtime::event::{EntityMovedEvent, MovementRejectedEvent, ViewChangedEvent};
+use mc_core::world::level::BaseEntity;
+use mc_vanilla::entity::decoration::Leashed;
 
 use super::protocol::{ProtocolServer, ProtocolPlayerEntity};
-use crate::protocol::play::{ChunkDataPacket, UpdateViewPositionPacket};
+use super::chunk_cache::ChunkPacketCache;
+use super::riding::build_set_passengers;
+use super::decoration::build_attach_entity;
+use crate::protocol::play::{UpdateViewPositionPacket, PlayerPosAndLook};
+use crate::protocol::play::{UnloadChunkPacket, EntityMetadataPacket, DestroyEntitiesPacket};
+use crate::protocol::spawn::build_entity_spawn;
+use crate::protocol::metadata::build_entity_metadata;
+use crate::protocol::version::ClientboundPacket;
 
-const LOAD_DISTANCE: i32 = 8;
+/// The view distance of players in chunks, if not defined by the `ServerProperties`.
+pub const VIEW_DISTANCE: u8 = 8;
 
 
+/// System notifying players of the chunk they are in, this chunk is the center of the
+/// chunks loaded by the client.
 pub fn system_player_view(world: &mut World) {
 
     let proto_server = world.components.get::<ProtocolServer>().unwrap();
 
     for level in &world.levels {
-        let mut level = &mut *level.borrow_mut();
-        for (_, comp) in level.entities.ecs.query_mut::<(&mut ProtocolPlayerEntity)>() {
-
-            let (cx, cz): (i32, i32) = comp.chunk_pos;
-
-            if comp.last_chunk_pos.is_none()  {
-
-                proto_server.send_packet(comp.addr, 0x40, &mut UpdateViewPositionPacket {
+        let mut level = level.borrow_mut();
+        for (_, comp) in level.entities.ecs.query_mut::<&mut ProtocolPlayerEntity>() {
+            if comp.last_chunk_pos != Some(comp.chunk_pos) {
+                let (cx, cz) = comp.chunk_pos;
+                proto_server.send_packet(comp.addr, ClientboundPacket::UpdateViewPosition, &mut UpdateViewPositionPacket {
                     cx,
                     cz
                 });
+                comp.last_chunk_pos = Some(comp.chunk_pos);
+            }
+        }
+    }
+
+}
 
-                for rcx in (cx - LOAD_DISTANCE)..(cx + LOAD_DISTANCE) {
-                    for rcz in (cz - LOAD_DISTANCE)..(cz + LOAD_DISTANCE) {
-                        if let Some(chunk) = level.chunks.get_chunk(rcx, rcz) {
-                            proto_server.send_packet(comp.addr, 0x20, &mut ChunkDataPacket::new(&*chunk));
-                            println!("Sending chunk {}/{} to {}.", rcx, rcz, comp.addr);
-                        }
-                    }
-                }
 
+/// System translating view changes of players to chunk load/unload and entity spawn/destroy
+/// packets. Chunk packets are encoded through the `ChunkPacketCache` component, entities
+/// are spawned with their metadata.
+pub fn system_player_tracking(world: &mut World) {
+
+    let proto_server = world.components.get::<ProtocolServer>().unwrap();
+    let mut chunk_cache = world.components.get_mut::<ChunkPacketCache>().unwrap();
+
+    for event in world.event_tracker.poll_events::<ViewChangedEvent>() {
+
+        let level = event.level.borrow();
+        let addr = match level.entities.ecs.get::<ProtocolPlayerEntity>(event.entity) {
+            Ok(comp) => comp.addr,
+            Err(_) => continue
+        };
+
+        let version = match proto_server.get_client(addr) {
+            Some(client) => client.get_version(),
+            None => continue
+        };
+
+        for &(cx, cz) in &event.left_chunks {
+            proto_server.send_packet(addr, ClientboundPacket::UnloadChunk, &mut UnloadChunkPacket { cx, cz });
+        }
+
+        for &(cx, cz) in &event.entered_chunks {
+            if let Some(chunk) = level.chunks.get_chunk(cx, cz) {
+                let encoded = chunk_cache.get_or_encode(level.get_id(), &chunk, version).unwrap();
+                proto_server.send_raw_packet(addr, ClientboundPacket::UpdateLight, encoded.light.clone());
+                proto_server.send_raw_packet(addr, ClientboundPacket::ChunkData, encoded.data.clone());
             }
+        }
 
-            comp.last_chunk_pos = Some(comp.chunk_pos);
+        if !event.left_entities.is_empty() {
+            proto_server.send_packet(addr, ClientboundPacket::DestroyEntities, &mut DestroyEntitiesPacket {
+                eids: event.left_entities.iter().map(|entity| entity.id() as i32).collect()
+            });
+        }
 
+        for &entity in &event.entered_entities {
+            if let Some(mut spawn) = build_entity_spawn(&level.entities.ecs, entity, version) {
+                proto_server.send_packet(addr, spawn.get_packet(), &mut spawn);
+                proto_server.send_packet(addr, ClientboundPacket::EntityMetadata, &mut EntityMetadataPacket {
+                    eid: entity.id() as i32,
+                    metadata: build_entity_metadata(&level.entities.ecs, entity, version)
+                });
+            }
         }
+
+        // Passengers are sent once both the vehicles and their passengers are spawned.
+        let mut vehicles: Vec<_> = event.entered_entities.iter()
+            .flat_map(|&entity| [Some(entity), level.get_vehicle(entity)])
+            .flatten()
+            .filter(|&vehicle| level.entities.ecs.get::<BaseEntity>(vehicle).is_ok_and(|base| base.has_passengers()))
+            .collect();
+        vehicles.sort_unstable();
+        vehicles.dedup();
+
+        for vehicle in vehicles {
+            proto_server.send_packet(addr, ClientboundPacket::SetPassengers, &mut build_set_passengers(&level, vehicle));
+        }
+
+        // Clients keep the leash until its holder is spawned.
+        for &entity in &event.entered_entities {
+            if level.entities.ecs.get::<Leashed>(entity).is_ok() {
+                proto_server.send_packet(addr, ClientboundPacket::AttachEntity, &mut build_attach_entity(&level, entity));
+            }
+        }
+
+    }
+
+}
+
+
+/// System applying results of the runtime movement system to players' connections, players
+/// with rejected movements are teleported back to their authoritative position.
+pub fn system_player_movement_sync(world: &mut World) {
+
+    let proto_server = world.components.get::<ProtocolServer>().unwrap();
+
+    for event in world.event_tracker.poll_events::<EntityMovedEvent>() {
+        let level = event.level.borrow();
+        if let Ok(mut comp) = level.entities.ecs.get_mut::<ProtocolPlayerEntity>(event.entity) {
+            comp.chunk_pos = ((event.to.x.floor() as i32) >> 4, (event.to.z.floor() as i32) >> 4);
+        };
+    }
+
+    for event in world.event_tracker.poll_events::<MovementRejectedEvent>() {
+        let level = event.level.borrow();
+        if let Ok(comp) = level.entities.ecs.get::<ProtocolPlayerEntity>(event.entity) {
+            println!("[{}] Movement rejected: {:?}", comp.addr, event.reason);
+            // The look is kept relative with no change, only the position is corrected.
+            proto_server.send_packet(comp.addr, ClientboundPacket::PlayerPosAndLook, &mut PlayerPosAndLook {
+                x: event.pos.x,
+                y: event.pos.y,
+                z: event.pos.z,
+                yaw: 0.0,
+                pitch: 0.0,
+                rel_x: false,
+                rel_y: false,
+                rel_z: false,
+                rel_yaw: true,
+                rel_pitch: true,
+                tp_id: 0,
+                dismount_vehicle: false
+            });
+        };
     }
 
 }
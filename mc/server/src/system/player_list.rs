@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+
+use mc_runtime::world::World;
+use mc_vanilla::util::GameMode;
+
+use crate::protocol::play::{PlayerInfoPacket, PlayerInfoAction, PlayerInfoEntry};
+use crate::protocol::version::ClientboundPacket;
+
+use super::protocol::ProtocolServer;
+
+use uuid::Uuid;
+
+
+/// The player info (tab) list, shared by all players. This component keeps track of all
+/// listed players and of the changes made since the last tick, these changes are then sent
+/// to all players by `system_player_list`.
+pub struct PlayerList {
+    /// All players currently in the list.
+    entries: HashMap<Uuid, PlayerInfoEntry>,
+    /// Players added since the last update.
+    added: Vec<Uuid>,
+    /// Players removed since the last update.
+    removed: Vec<Uuid>,
+    /// Players with a game mode changed since the last update.
+    game_mode_changed: Vec<Uuid>,
+    /// Players with a latency changed since the last update.
+    latency_changed: Vec<Uuid>
+}
+
+impl PlayerList {
+
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+            added: Vec::new(),
+            removed: Vec::new(),
+            game_mode_changed: Vec::new(),
+            latency_changed: Vec::new()
+        }
+    }
+
+    /// Add a player to the list, replacing any previous player with the same UUID.
+    pub fn add_player(&mut self, uuid: Uuid, username: String, game_mode: GameMode) {
+        self.entries.insert(uuid, PlayerInfoEntry {
+            uuid,
+            username,
+            game_mode,
+            latency: 0
+        });
+        if !self.added.contains(&uuid) {
+            self.added.push(uuid);
+        }
+    }
+
+    /// Remove a player from the list, returning false if this player was not listed.
+    pub fn remove_player(&mut self, uuid: Uuid) -> bool {
+        if self.entries.remove(&uuid).is_none() {
+            return false;
+        }
+        self.game_mode_changed.retain(|&changed| changed != uuid);
+        self.latency_changed.retain(|&changed| changed != uuid);
+        if let Some(idx) = self.added.iter().position(|&added| added == uuid) {
+            // Players have not yet been notified of this player.
+            self.added.remove(idx);
+        } else {
+            self.removed.push(uuid);
+        }
+        true
+    }
+
+    /// Update the game mode of a player, returning false if this player is not listed.
+    pub fn update_game_mode(&mut self, uuid: Uuid, game_mode: GameMode) -> bool {
+        Self::update_entry(&mut self.entries, &mut self.game_mode_changed, uuid, |entry| {
+            entry.game_mode = game_mode;
+        })
+    }
+
+    /// Update the latency of a player in milliseconds, returning false if this player is
+    /// not listed.
+    pub fn update_latency(&mut self, uuid: Uuid, latency: i32) -> bool {
+        Self::update_entry(&mut self.entries, &mut self.latency_changed, uuid, |entry| {
+            entry.latency = latency;
+        })
+    }
+
+    fn update_entry<F>(entries: &mut HashMap<Uuid, PlayerInfoEntry>, changed: &mut Vec<Uuid>, uuid: Uuid, func: F) -> bool
+    where
+        F: FnOnce(&mut PlayerInfoEntry)
+    {
+        match entries.get_mut(&uuid) {
+            Some(entry) => {
+                func(entry);
+                if !changed.contains(&uuid) {
+                    changed.push(uuid);
+                }
+                true
+            }
+            None => false
+        }
+    }
+
+    pub fn get_player(&self, uuid: Uuid) -> Option<&PlayerInfoEntry> {
+        self.entries.get(&uuid)
+    }
+
+    pub fn iter_players(&self) -> impl Iterator<Item = &PlayerInfoEntry> + '_ {
+        self.entries.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Return a packet adding all players of the list, to be sent to joining players.
+    pub fn get_full_packet(&self) -> PlayerInfoPacket {
+        PlayerInfoPacket {
+            action: PlayerInfoAction::AddPlayer(self.entries.values().cloned().collect())
+        }
+    }
+
+    /// Take all changes made since the last call and convert them to packets to send to
+    /// all players.
+    pub fn take_update_packets(&mut self) -> Vec<PlayerInfoPacket> {
+
+        let mut packets = Vec::new();
+
+        if !self.removed.is_empty() {
+            packets.push(PlayerInfoAction::RemovePlayer(std::mem::take(&mut self.removed)));
+        }
+
+        // Added players are sent with their current game mode and latency.
+        let added = std::mem::take(&mut self.added);
+        self.game_mode_changed.retain(|uuid| !added.contains(uuid));
+        self.latency_changed.retain(|uuid| !added.contains(uuid));
+
+        let entries = &self.entries;
+        if !added.is_empty() {
+            packets.push(PlayerInfoAction::AddPlayer(added.iter()
+                .map(|uuid| entries[uuid].clone())
+                .collect()));
+        }
+
+        if !self.game_mode_changed.is_empty() {
+            packets.push(PlayerInfoAction::UpdateGameMode(self.game_mode_changed.drain(..)
+                .map(|uuid| (uuid, entries[&uuid].game_mode))
+                .collect()));
+        }
+
+        if !self.latency_changed.is_empty() {
+            packets.push(PlayerInfoAction::UpdateLatency(self.latency_changed.drain(..)
+                .map(|uuid| (uuid, entries[&uuid].latency))
+                .collect()));
+        }
+
+        packets.into_iter().map(|action| PlayerInfoPacket { action }).collect()
+
+    }
+
+}
+
+impl Default for PlayerList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// System sending the changes of the `PlayerList` component to all players.
+pub fn system_player_list(world: &mut World) {
+
+    let mut player_list = world.components.get_mut::<PlayerList>().unwrap();
+    let proto_server = world.components.get::<ProtocolServer>().unwrap();
+
+    for mut packet in player_list.take_update_packets() {
+        proto_server.broadcast_packet(ClientboundPacket::PlayerInfo, &mut packet);
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn count_players(packet: &PlayerInfoPacket) -> usize {
+        match &packet.action {
+            PlayerInfoAction::AddPlayer(entries) => entries.len(),
+            PlayerInfoAction::UpdateGameMode(entries) => entries.len(),
+            PlayerInfoAction::UpdateLatency(entries) => entries.len(),
+            PlayerInfoAction::RemovePlayer(uuids) => uuids.len()
+        }
+    }
+
+    #[test]
+    fn player_list() {
+
+        let alice = Uuid::new_v4();
+        let bob = Uuid::new_v4();
+
+        let mut list = PlayerList::new();
+        list.add_player(alice, "Alice".to_string(), GameMode::Survival);
+        list.add_player(bob, "Bob".to_string(), GameMode::Survival);
+        assert!(list.update_latency(alice, 42));
+        assert!(!list.update_latency(Uuid::new_v4(), 42));
+
+        // Updates of added players are merged in the add action.
+        let packets = list.take_update_packets();
+        assert_eq!(packets.len(), 1);
+        assert!(matches!(packets[0].action, PlayerInfoAction::AddPlayer(_)));
+        assert_eq!(count_players(&packets[0]), 2);
+        assert_eq!(list.get_player(alice).unwrap().latency, 42);
+        assert!(list.take_update_packets().is_empty());
+
+        assert!(list.update_game_mode(bob, GameMode::Creative));
+        assert!(list.update_latency(bob, 10));
+        assert!(list.remove_player(alice));
+        assert!(!list.remove_player(alice));
+        let packets = list.take_update_packets();
+        assert_eq!(packets.len(), 3);
+        assert!(matches!(packets[0].action, PlayerInfoAction::RemovePlayer(_)));
+        assert!(matches!(packets[1].action, PlayerInfoAction::UpdateGameMode(_)));
+        assert!(matches!(packets[2].action, PlayerInfoAction::UpdateLatency(_)));
+
+        // A player added and removed in the same tick is never sent.
+        list.add_player(alice, "Alice".to_string(), GameMode::Survival);
+        list.remove_player(alice);
+        assert!(list.take_update_packets().is_empty());
+        assert_eq!(list.len(), 1);
+        assert_eq!(count_players(&list.get_full_packet()), 1);
+
+    }
+
+}
@@ -1,20 +1,36 @@
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::marker::PhantomData;
 use std::net::SocketAddr;
 
 use mc_runtime::world::{World, WorldSystemExecutor};
+use mc_runtime::system::{PlayerMovements, MovementRequest, ViewTracker, Inhabitant, NoClip};
 use mc_core::world::level::BaseEntity;
+use mc_core::item::Inventory;
+use mc_core::pos::EntityPos;
 use mc_vanilla::util::GameMode;
-use mc_vanilla::entity::PLAYER;
+use mc_vanilla::entity::{PlayerEntity, LivingEntity, PLAYER};
+use mc_vanilla::death::PLAYER_MAX_HEALTH;
+use mc_vanilla::sleep::Sleeper;
+use mc_vanilla::advancement::AdvancementProgress;
 
 use crate::packet::{PacketServer, Event, RawPacket};
 use crate::protocol::{ClientState, ReadablePacket, WritablePacket, PacketResult};
+use crate::config::ServerProperties;
+use crate::protocol::version::{ProtocolVersion, ClientboundPacket, ServerboundPacket};
 
 use crate::protocol::handshake::HandshakePacket;
 use crate::protocol::status::{RequestStatusPacket, StatusPacket, PingPacket};
-use crate::protocol::login::{LoginStartPacket, LoginSuccessPacket};
+use crate::protocol::login::{LoginStartPacket, LoginSuccessPacket, LoginDisconnectPacket};
 use crate::protocol::play::{JoinGamePacket, SpawnPositionPacket, PlayerAbilitiesPacket, PlayerPosAndLook, PluginMessage};
+use crate::protocol::play::{PlayerPositionPacket, PlayerPositionAndRotationPacket, PlayerRotationPacket};
+
+use super::player_list::PlayerList;
+use super::access::AccessLists;
+use super::player::VIEW_DISTANCE;
+use super::window::{PlayerWindows, PLAYER_INVENTORY_SIZE};
+use super::map::MapViewer;
+use super::game_mode::{SyncedAbilities, is_no_clip};
 
 use hecs::Entity;
 use uuid::Uuid;
@@ -27,8 +43,63 @@ pub struct ProtocolClient {
     /// The client protocol state, this is an really important information with the packet ID,
     /// but the state is not sent with it, so we must track it.
     state: ClientState,
+    /// The protocol version of the client, given in the handshake, the latest version is
+    /// used until the handshake or if the client's version is not supported.
+    version: ProtocolVersion,
     /// Optional profile when logged-in.
-    profile: Option<PlayProfile>
+    profile: Option<PlayProfile>,
+    /// The client brand, sent by the client on the brand channel.
+    brand: Option<String>,
+    /// Plugin channels registered by the client.
+    channels: HashSet<String>
+}
+
+impl ProtocolClient {
+
+    #[inline]
+    pub fn get_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    #[inline]
+    pub fn get_version(&self) -> ProtocolVersion {
+        self.version
+    }
+
+    /// Return the username of the client, if it is playing.
+    pub fn get_username(&self) -> Option<&str> {
+        self.profile.as_ref().map(|profile| profile.username.as_str())
+    }
+
+    /// Return the UUID of the client, if it is playing.
+    pub fn get_uuid(&self) -> Option<Uuid> {
+        self.profile.as_ref().map(|profile| profile.uuid)
+    }
+
+    /// Set the level and the entity of a playing client, when its entity has been moved to
+    /// another level.
+    pub(super) fn set_player(&mut self, level_idx: usize, entity: Entity) {
+        if let Some(profile) = &mut self.profile {
+            profile.level_idx = level_idx;
+            profile.entity = entity;
+        }
+    }
+
+    /// Return the client brand, if the client has sent it.
+    pub fn get_brand(&self) -> Option<&str> {
+        self.brand.as_deref()
+    }
+
+    /// Return true if the client has registered the given plugin channel.
+    pub fn has_channel(&self, channel: &str) -> bool {
+        self.channels.contains(channel)
+    }
+
+    /// Iterate over all plugin channels registered by the client.
+    pub fn iter_channels(&self) -> impl Iterator<Item = &str> + '_ {
+        self.channels.iter().map(|channel| channel.as_str())
+    }
+
 }
 
 /// The profile of the client once in play state.
@@ -55,17 +126,53 @@ pub struct ProtocolServer {
     /// Mapping all client's addresses to a structure storing their state.
     clients: HashMap<SocketAddr, ProtocolClient>,
     /// Packet listeners.
-    packet_listeners: HashMap<(ClientState, u16), Vec<Box<dyn PacketListener>>>
+    packet_listeners: HashMap<ServerboundPacket, Vec<Box<dyn PacketListener>>>
 }
 
 impl ProtocolServer {
 
-    #[inline]
-    pub fn send_packet<P>(&self, addr: SocketAddr, id: u16, packet: &mut P)
+    /// Send a packet to the given client, encoded for its protocol version. Nothing is sent
+    /// if the client is not connected.
+    pub fn send_packet<P>(&self, addr: SocketAddr, kind: ClientboundPacket, packet: &mut P)
     where
         P: WritablePacket
     {
-        self.server.send(write_packet(addr, id, packet).unwrap());
+        if let Some(client) = self.clients.get(&addr) {
+            self.server.send(write_packet(addr, client.version, kind, packet).unwrap());
+        }
+    }
+
+    /// Send packet data already encoded for the client's protocol version, this is used to
+    /// send the same encoded packet to many clients, see `ChunkPacketCache`. Nothing is sent
+    /// if the client is not connected.
+    pub fn send_raw_packet(&self, addr: SocketAddr, kind: ClientboundPacket, data: Vec<u8>) {
+        if let Some(client) = self.clients.get(&addr) {
+            self.server.send(RawPacket {
+                addr,
+                id: client.version.get_clientbound_id(kind),
+                data
+            });
+        }
+    }
+
+    /// Send a packet to all clients in play state, encoded for each client's version.
+    pub fn broadcast_packet<P>(&self, kind: ClientboundPacket, packet: &mut P)
+    where
+        P: WritablePacket
+    {
+        for client in self.clients.values() {
+            if client.state == ClientState::Play {
+                self.server.send(write_packet(client.addr, client.version, kind, packet).unwrap());
+            }
+        }
+    }
+
+    /// Send a custom payload to the given client on a plugin channel.
+    pub fn send_plugin_message(&self, addr: SocketAddr, channel: &str, data: Vec<u8>) {
+        self.send_packet(addr, ClientboundPacket::PluginMessage, &mut PluginMessage::Custom {
+            id: channel.to_string(),
+            data
+        });
     }
 
     pub fn get_client(&self, addr: SocketAddr) -> Option<&ProtocolClient> {
@@ -76,12 +183,12 @@ impl ProtocolServer {
         self.clients.get_mut(&addr)
     }
 
-    pub fn add_listener<F, P>(&mut self, state: ClientState, id: u16, func: F)
+    pub fn add_listener<F, P>(&mut self, kind: ServerboundPacket, func: F)
     where
         F: FnMut(PacketEvent<P>) + 'static,
         P: ReadablePacket + 'static
     {
-        match self.packet_listeners.entry((state, id)) {
+        match self.packet_listeners.entry(kind) {
             Entry::Occupied(o) => o.into_mut(),
             Entry::Vacant(v) => v.insert(Vec::new())
         }.push(Box::new(PacketListenerWrapper {
@@ -90,6 +197,32 @@ impl ProtocolServer {
         }));
     }
 
+    /// Subscribe to a plugin channel, the given function is called for each message
+    /// received on this channel, with its raw payload. Built-in channels (brand and
+    /// registration) are also dispatched, with their raw payload.
+    pub fn add_channel_listener<F>(&mut self, channel: &str, mut func: F)
+    where
+        F: FnMut(PacketEvent<ChannelMessage>) + 'static
+    {
+        let channel = channel.to_string();
+        self.add_listener::<_, PluginMessage>(ServerboundPacket::PluginMessage, move |e| {
+            if e.packet.get_channel() == channel {
+                func(e.map_packet(|packet| ChannelMessage {
+                    channel: packet.get_channel().to_string(),
+                    data: packet.get_data()
+                }));
+            }
+        });
+    }
+
+}
+
+
+/// A message received on a plugin channel, given to channel listeners.
+#[derive(Debug)]
+pub struct ChannelMessage {
+    pub channel: String,
+    pub data: Vec<u8>
 }
 
 
@@ -105,32 +238,83 @@ pub struct PacketEvent<'a, 'b, P> {
 
 impl<'a, 'b, P> PacketEvent<'a, 'b, P> {
 
+    /// Send a packet to any client, the packet is encoded for the given protocol version
+    /// which must be the version of the receiving client.
     #[inline]
-    pub fn send_packet<R>(&self, addr: SocketAddr, id: u16, packet: &mut R)
+    pub fn send_packet<R>(&self, addr: SocketAddr, version: ProtocolVersion, kind: ClientboundPacket, packet: &mut R)
     where
         R: WritablePacket
     {
-        self.server.send(write_packet(addr, id, packet).unwrap());
+        self.server.send(write_packet(addr, version, kind, packet).unwrap());
     }
 
     #[inline]
-    pub fn answer_packet<R>(&self, id: u16, packet: &mut R)
+    pub fn answer_packet<R>(&self, kind: ClientboundPacket, packet: &mut R)
     where
         R: WritablePacket
     {
-        self.send_packet(self.client.addr, id, packet);
+        self.send_packet(self.client.addr, self.client.version, kind, packet);
+    }
+
+    /// Send a custom payload on a plugin channel to the client that sent the packet.
+    pub fn answer_plugin_message(&self, channel: &str, data: Vec<u8>) {
+        self.answer_packet(ClientboundPacket::PluginMessage, &mut PluginMessage::Custom {
+            id: channel.to_string(),
+            data
+        });
+    }
+
+    /// Disconnect the client that sent the packet.
+    #[inline]
+    pub fn disconnect(&self) {
+        self.server.kick(self.client.addr);
+    }
+
+    /// Queue a movement requested by the client that sent the packet, it is then validated
+    /// by the runtime movement system. Nothing is done if the client is not playing.
+    pub fn request_movement(&self, pos: Option<EntityPos>, look: Option<(f32, f32)>, on_ground: bool) {
+        if let Some(profile) = &self.client.profile {
+            if let Ok(mut movements) = self.world.get_component_mut::<PlayerMovements>() {
+                movements.push_request(MovementRequest {
+                    level_idx: profile.level_idx,
+                    entity: profile.entity,
+                    pos,
+                    look,
+                    on_ground
+                });
+            }
+        }
+    }
+
+    /// Return the level index and the entity of the client that sent the packet, if it is
+    /// playing.
+    pub fn get_player(&self) -> Option<(usize, Entity)> {
+        self.client.profile.as_ref().map(|profile| (profile.level_idx, profile.entity))
+    }
+
+    /// Internal method to convert the packet of this event.
+    fn map_packet<Q, F>(self, func: F) -> PacketEvent<'a, 'b, Q>
+    where
+        F: FnOnce(P) -> Q
+    {
+        PacketEvent {
+            world: self.world,
+            client: self.client,
+            server: self.server,
+            packet: func(self.packet)
+        }
     }
 
 }
 
 
-/// Internal function to write a packet to a raw packet.
-fn write_packet<P>(addr: SocketAddr, id: u16, packet: &mut P) -> PacketResult<RawPacket>
+/// Internal function to write a packet to a raw packet for the given protocol version.
+fn write_packet<P>(addr: SocketAddr, version: ProtocolVersion, kind: ClientboundPacket, packet: &mut P) -> PacketResult<RawPacket>
 where
     P: WritablePacket
 {
-    let mut raw_packet = RawPacket::blank(addr, id);
-    packet.write_packet(raw_packet.get_cursor_mut())?;
+    let mut raw_packet = RawPacket::blank(addr, version.get_clientbound_id(kind));
+    packet.write_packet(raw_packet.get_cursor_mut(), version)?;
     Ok(raw_packet)
 }
 
@@ -153,7 +337,7 @@ where
 {
     fn accept_packet<'a, 'b>(&mut self, world: &'a World, server: &'b PacketServer, client: &'b mut ProtocolClient, raw_packet: &RawPacket) {
         // TODO: We should not unwrap un the future.
-        let packet = P::read_packet(raw_packet.get_cursor()).unwrap();
+        let packet = P::read_packet(raw_packet.get_cursor(), client.version).unwrap();
         (self.func)(PacketEvent {
             world,
             client,
@@ -178,7 +362,10 @@ fn system_packet_server(world: &mut World) {
                 proto_server.clients.insert(addr, ProtocolClient {
                     addr,
                     state: ClientState::Handshake,
-                    profile: None
+                    version: ProtocolVersion::LATEST,
+                    profile: None,
+                    brand: None,
+                    channels: HashSet::new()
                 });
             }
             Event::Packet(packet) => {
@@ -186,7 +373,9 @@ fn system_packet_server(world: &mut World) {
                 // prevent multiple mutable reference to multiple structure's fields.
                 let proto_server = &mut *proto_server;
                 let client = proto_server.clients.get_mut(&packet.addr).unwrap();
-                if let Some(listeners) = proto_server.packet_listeners.get_mut(&(client.state, packet.id)) {
+                let kind = client.version.get_serverbound_packet(client.state, packet.id);
+                let packet_listeners = &mut proto_server.packet_listeners;
+                if let Some(listeners) = kind.and_then(|kind| packet_listeners.get_mut(&kind)) {
                     for listener in listeners {
                         listener.accept_packet(world, &proto_server.server, client, &packet);
                     }
@@ -196,7 +385,13 @@ fn system_packet_server(world: &mut World) {
                 println!("[{}] Disconnected.", addr);
                 let client = proto_server.clients.remove(&addr).unwrap();
                 if let Some(ref play_profile) = client.profile {
-                    world.levels[play_profile.level_idx].borrow_mut().entities.remove_entity(play_profile.entity);
+                    if let Ok(mut player_list) = world.components.get_mut::<PlayerList>() {
+                        player_list.remove_player(play_profile.uuid);
+                    }
+                    let mut level = world.levels[play_profile.level_idx].borrow_mut();
+                    super::interaction::close_block_window(&mut level, play_profile.entity);
+                    super::merchant::close_merchant_window(&mut level, play_profile.entity);
+                    level.entities.remove_entity(play_profile.entity);
                 }
             }
         }
@@ -224,87 +419,146 @@ pub fn register_systems(world: &mut World, executor: &mut WorldSystemExecutor) {
         packet_listeners: HashMap::new()
     };
 
-    server.add_listener::<_, HandshakePacket>(ClientState::Handshake, 0x00, |e| {
-        if let ClientState::Status | ClientState::Login = e.packet.next_state {
-            e.client.state = e.packet.next_state;
+    server.add_listener::<_, HandshakePacket>(ServerboundPacket::Handshake, |e| {
+        let version = e.packet.get_version();
+        e.client.version = version.unwrap_or(ProtocolVersion::LATEST);
+        match e.packet.next_state {
+            ClientState::Status => e.client.state = ClientState::Status,
+            ClientState::Login if version.is_some() => e.client.state = ClientState::Login,
+            ClientState::Login => {
+                println!("[{}] Unsupported protocol version: {}", e.client.addr, e.packet.protocol_version);
+                e.disconnect();
+            }
+            _ => {}
         }
     });
 
-    server.add_listener::<_, RequestStatusPacket>(ClientState::Status, 0x00, |e| {
-        e.answer_packet(0x00, &mut StatusPacket {
-            game_version: "1.16.5",
-            protocol_version: 754,
-            max_players: 10,
-            online_players: 0,
-            description: "Minecraft Rust server".to_string()
+    server.add_listener::<_, RequestStatusPacket>(ServerboundPacket::StatusRequest, |e| {
+        let (max_players, description) = match e.world.get_component::<ServerProperties>() {
+            Ok(properties) => (properties.max_players, properties.motd.clone()),
+            Err(_) => (10, "Minecraft Rust server".to_string())
+        };
+        e.answer_packet(ClientboundPacket::StatusResponse, &mut StatusPacket {
+            game_version: e.client.version.get_name(),
+            protocol_version: e.client.version.get_id() as u16,
+            max_players,
+            online_players: e.world.get_component::<PlayerList>().map_or(0, |list| list.len() as u32),
+            description
         });
     });
 
-    server.add_listener::<_, PingPacket>(ClientState::Status, 0x01, |e| {
-        e.answer_packet(0x01, &mut e.packet.get_pong());
+    server.add_listener::<_, PingPacket>(ServerboundPacket::Ping, |e| {
+        e.answer_packet(ClientboundPacket::Pong, &mut e.packet.get_pong());
     });
 
-    server.add_listener::<_, LoginStartPacket>(ClientState::Login, 0x00, |e| {
+    server.add_listener::<_, PluginMessage>(ServerboundPacket::PluginMessage, |e| {
+        match e.packet {
+            PluginMessage::Brand(brand) => {
+                println!("[{}] Brand: {}", e.client.addr, brand);
+                e.client.brand = Some(brand);
+            }
+            PluginMessage::Register(channels) => {
+                e.client.channels.extend(channels);
+            }
+            PluginMessage::Unregister(channels) => {
+                for channel in channels {
+                    e.client.channels.remove(&channel);
+                }
+            }
+            PluginMessage::Custom { .. } => {}
+        }
+    });
+
+    server.add_listener::<_, LoginStartPacket>(ServerboundPacket::LoginStart, |e| {
 
         println!("[{}] Login: {}", e.client.addr, e.packet.username);
 
-        let profile = {
+        if let Ok(access) = e.world.get_component::<AccessLists>() {
+            // The server is in offline mode, players have no stable UUID so they are only
+            // identified by their names.
+            if let Err(reason) = access.check_login(None, &e.packet.username, e.client.addr.ip()) {
+                println!("[{}] Login denied: {}", e.client.addr, reason);
+                e.answer_packet(ClientboundPacket::LoginDisconnect, &mut LoginDisconnectPacket { reason });
+                e.disconnect();
+                return;
+            }
+        }
+
+        let (view_distance, game_mode) = e.world.get_component::<ServerProperties>()
+            .map_or((VIEW_DISTANCE, GameMode::Survival), |properties| (properties.view_distance, properties.gamemode));
+
+        let (profile, abilities) = {
 
             let mut level = e.world.levels[0].borrow_mut();
             let entity = level.spawn_entity(&PLAYER, Default::default()).unwrap();
 
-            level.entities.ecs.insert_one(entity, ProtocolPlayerEntity {
+            level.entities.ecs.insert(entity, (ProtocolPlayerEntity {
                 addr: e.client.addr,
                 chunk_pos: (0, 0),
                 last_chunk_pos: None
-            });
+            }, ViewTracker::new(view_distance), Inhabitant)).unwrap();
+            level.entities.ecs.insert(entity, (Inventory::new(PLAYER_INVENTORY_SIZE), PlayerWindows::new(), Sleeper::new(), AdvancementProgress::new(), MapViewer::new())).unwrap();
+            level.entities.ecs.get_mut::<LivingEntity>(entity).unwrap().set_health(PLAYER_MAX_HEALTH);
+
+            let mut player = level.entities.ecs.get_mut::<PlayerEntity>(entity).unwrap();
+            player.set_game_mode(game_mode);
+            let abilities = player.get_abilities().clone();
+            let synced = SyncedAbilities::new(&player);
+            drop(player);
+            level.entities.ecs.insert_one(entity, synced).unwrap();
+            if is_no_clip(game_mode) {
+                level.entities.ecs.insert_one(entity, NoClip).unwrap();
+            }
 
             let base_entity = level.entities.ecs.get::<BaseEntity>(entity).unwrap();
 
-            PlayProfile {
+            (PlayProfile {
                 level_idx: 0,
                 entity,
                 username: e.packet.username.clone(),
                 uuid: base_entity.uuid
-            }
+            }, abilities)
 
         };
 
-        e.answer_packet(0x02, &mut LoginSuccessPacket {
+        e.answer_packet(ClientboundPacket::LoginSuccess, &mut LoginSuccessPacket {
             username: profile.username.clone(),
             uuid: profile.uuid
         });
 
+        let eid = profile.entity.id() as i32;
         e.client.state = ClientState::Play;
         e.client.profile = Some(profile);
 
-        e.answer_packet(0x24, &mut JoinGamePacket {
-            eid: 1234,
+        e.answer_packet(ClientboundPacket::JoinGame, &mut JoinGamePacket {
+            eid,
             hardcore: false,
-            game_mode: GameMode::Survival,
+            game_mode,
             last_game_mode: None,
             world: e.world,
             level_index: 0,
             hashed_seed: 0,
-            view_distance: 8
+            view_distance
         });
 
-        e.answer_packet(0x17, &mut PluginMessage::Brand("MinecraftRS".to_string()));
+        e.answer_packet(ClientboundPacket::PluginMessage, &mut PluginMessage::Brand("MinecraftRS".to_string()));
 
-        e.answer_packet(0x42, &mut SpawnPositionPacket {
-            pos: Default::default()
-        });
+        if let Ok(mut player_list) = e.world.get_component_mut::<PlayerList>() {
+            let profile = e.client.profile.as_ref().unwrap();
+            player_list.add_player(profile.uuid, profile.username.clone(), game_mode);
+            e.answer_packet(ClientboundPacket::PlayerInfo, &mut player_list.get_full_packet());
+        }
+
+        e.answer_packet(ClientboundPacket::DeclareCommands, &mut super::command::get_declare_commands_packet(e.world));
 
-        e.answer_packet(0x30, &mut PlayerAbilitiesPacket {
-            invulnerable: true,
-            flying: true,
-            allow_flying: true,
-            instant_break: true,
-            flying_speed: 0.05,
-            fov_modifier: 0.1
+        e.answer_packet(ClientboundPacket::SpawnPosition, &mut SpawnPositionPacket {
+            pos: Default::default(),
+            angle: 0.0
         });
 
-        e.answer_packet(0x34, &mut PlayerPosAndLook {
+        e.answer_packet(ClientboundPacket::PlayerAbilities, &mut PlayerAbilitiesPacket::new(&abilities));
+
+        e.answer_packet(ClientboundPacket::PlayerPosAndLook, &mut PlayerPosAndLook {
             x: 0.0,
             y: 0.0,
             z: 0.0,
@@ -315,11 +569,37 @@ pub fn register_systems(world: &mut World, executor: &mut WorldSystemExecutor) {
             rel_z: false,
             rel_yaw: false,
             rel_pitch: false,
-            tp_id: 0
+            tp_id: 0,
+            dismount_vehicle: false
         });
 
     });
 
+    server.add_listener::<_, PlayerPositionPacket>(ServerboundPacket::PlayerPosition, |e| {
+        let pos = EntityPos::new(e.packet.x, e.packet.y, e.packet.z);
+        e.request_movement(Some(pos), None, e.packet.on_ground);
+    });
+
+    server.add_listener::<_, PlayerPositionAndRotationPacket>(ServerboundPacket::PlayerPositionAndRotation, |e| {
+        let pos = EntityPos::new(e.packet.x, e.packet.y, e.packet.z);
+        e.request_movement(Some(pos), Some((e.packet.yaw, e.packet.pitch)), e.packet.on_ground);
+    });
+
+    server.add_listener::<_, PlayerRotationPacket>(ServerboundPacket::PlayerRotation, |e| {
+        e.request_movement(None, Some((e.packet.yaw, e.packet.pitch)), e.packet.on_ground);
+    });
+
+    super::window::register_listeners(&mut server);
+    super::command::register_listeners(&mut server);
+    super::chat::register_listeners(&mut server);
+    super::game_mode::register_listeners(&mut server);
+    super::death::register_listeners(&mut server);
+    super::interaction::register_listeners(&mut server);
+    super::merchant::register_listeners(&mut server);
+    super::riding::register_listeners(&mut server);
+    // Registered after riding, which checks leashes before they are changed.
+    super::decoration::register_listeners(&mut server);
+
     world.insert_component(server);
 
     executor.add_system(system_packet_server);
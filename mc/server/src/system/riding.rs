@@ -0,0 +1,179 @@
+//! Players riding vehicles, interacting with a boat, a minecart, a horse or a saddled pig
+//! mounts the player on it, unless the interaction leashes it or removes its leash, and the
+//! unmount input of the player dismounts it. The passengers of vehicles are sent to the
+//! players viewing them each time they change.
+//!
+//! Vehicles are not controlled by their passengers yet.
+
+use std::collections::HashSet;
+
+use mc_runtime::world::World;
+use mc_runtime::system::ViewTracker;
+use mc_core::world::level::{Level, BaseEntity};
+use mc_vanilla::entity::{PlayerEntity, PigEntity, BOAT, MINECART, PIG, HORSE, DONKEY, MULE};
+use mc_vanilla::death::Dead;
+use hecs::Entity;
+
+use super::protocol::{ProtocolServer, ProtocolPlayerEntity};
+use super::decoration::is_leash_interaction;
+use crate::protocol::play::{InteractEntityPacket, EntityInteraction, Hand, SteerVehiclePacket, SetPassengersPacket};
+use crate::protocol::version::{ClientboundPacket, ServerboundPacket};
+
+
+/// Maximum distance between a player and the vehicle it mounts.
+const MOUNT_DISTANCE: f64 = 6.0;
+
+
+/// An entity component for vehicles, the passengers last sent to clients.
+#[derive(Debug, Clone, Default)]
+pub struct SyncedPassengers {
+    passengers: Vec<Entity>
+}
+
+
+/// Return the number of passengers a vehicle can carry, zero if players can't mount it.
+fn get_max_passengers(level: &Level, vehicle: Entity) -> usize {
+    let ecs = &level.entities.ecs;
+    let entity_type = match ecs.get::<BaseEntity>(vehicle) {
+        Ok(base) => base.entity_type,
+        Err(_) => return 0
+    };
+    if ecs.get::<Dead>(vehicle).is_ok() {
+        0
+    } else if std::ptr::eq(entity_type, &BOAT) {
+        2
+    } else if std::ptr::eq(entity_type, &PIG) {
+        ecs.get::<PigEntity>(vehicle).is_ok_and(|pig| pig.has_saddle()) as usize
+    } else if [&MINECART, &HORSE, &DONKEY, &MULE].iter().any(|&t| std::ptr::eq(entity_type, t)) {
+        1
+    } else {
+        0
+    }
+}
+
+/// Return the set passengers packet of a vehicle.
+pub fn build_set_passengers(level: &Level, vehicle: Entity) -> SetPassengersPacket {
+    SetPassengersPacket {
+        eid: vehicle.id() as i32,
+        passengers: level.entities.ecs.get::<BaseEntity>(vehicle)
+            .map(|base| base.get_passengers().iter().map(|entity| entity.id() as i32).collect())
+            .unwrap_or_default()
+    }
+}
+
+
+/// System sending the passengers of vehicles to the players viewing them and to their
+/// passengers when they change.
+pub fn system_passengers_sync(world: &mut World) {
+
+    let proto_server = world.components.get::<ProtocolServer>().unwrap();
+
+    for level in &world.levels {
+
+        let mut level = level.borrow_mut();
+
+        // Changed vehicles, with the passengers previously sent.
+        let changed: Vec<(Entity, Vec<Entity>)> = level.entities.ecs.query::<(&BaseEntity, Option<&SyncedPassengers>)>()
+            .iter()
+            .filter_map(|(entity, (base, synced))| {
+                let old = synced.map(|synced| synced.passengers.as_slice()).unwrap_or_default();
+                (old != base.get_passengers()).then(|| (entity, old.to_vec()))
+            })
+            .collect();
+
+        for (vehicle, old) in changed {
+
+            let mut packet = build_set_passengers(&level, vehicle);
+
+            let mut addrs = HashSet::new();
+            for (_, (player, tracker)) in level.entities.ecs.query::<(&ProtocolPlayerEntity, &ViewTracker)>().iter() {
+                if tracker.has_entity(vehicle) {
+                    addrs.insert(player.addr);
+                }
+            }
+
+            let passengers = level.entities.ecs.get::<BaseEntity>(vehicle).unwrap().get_passengers().to_vec();
+            for passenger in old.iter().chain(&passengers) {
+                if let Ok(player) = level.entities.ecs.get::<ProtocolPlayerEntity>(*passenger) {
+                    addrs.insert(player.addr);
+                }
+            }
+
+            for addr in addrs {
+                proto_server.send_packet(addr, ClientboundPacket::SetPassengers, &mut packet);
+            }
+
+            if passengers.is_empty() {
+                let _ = level.entities.ecs.remove_one::<SyncedPassengers>(vehicle);
+            } else {
+                level.entities.ecs.insert_one(vehicle, SyncedPassengers { passengers }).unwrap();
+            }
+
+        }
+
+    }
+
+}
+
+
+pub(super) fn register_listeners(server: &mut ProtocolServer) {
+
+    server.add_listener::<_, InteractEntityPacket>(ServerboundPacket::InteractEntity, |e| {
+
+        if e.packet.interaction != EntityInteraction::Interact(Hand::MainHand) || e.packet.sneaking {
+            return;
+        }
+
+        let (level_idx, entity) = match e.get_player() {
+            Some(player) => player,
+            None => return
+        };
+
+        let mut level = e.world.levels[level_idx].borrow_mut();
+        let vehicle = match level.entities.ecs.query::<&BaseEntity>()
+            .iter()
+            .map(|(entity, _)| entity)
+            .find(|entity| entity.id() as i32 == e.packet.eid) {
+            Some(vehicle) => vehicle,
+            None => return
+        };
+
+        let can_mount = {
+            let ecs = &level.entities.ecs;
+            let in_reach = match (ecs.get::<BaseEntity>(entity), ecs.get::<BaseEntity>(vehicle)) {
+                (Ok(player_base), Ok(vehicle_base)) => {
+                    let (a, b) = (&player_base.pos, &vehicle_base.pos);
+                    let (dx, dy, dz) = (a.x - b.x, a.y - b.y, a.z - b.z);
+                    dx * dx + dy * dy + dz * dz < MOUNT_DISTANCE * MOUNT_DISTANCE
+                }
+                _ => false
+            };
+            let passengers = ecs.get::<BaseEntity>(vehicle).map(|base| base.get_passengers().len()).unwrap_or(0);
+            in_reach
+                && ecs.get::<PlayerEntity>(entity).is_ok_and(|player| player.can_interact())
+                && ecs.get::<Dead>(entity).is_err()
+                && level.get_vehicle(entity).is_none()
+                && passengers < get_max_passengers(&level, vehicle)
+                && !is_leash_interaction(&level, entity, vehicle)
+        };
+
+        if can_mount {
+            level.mount_entity(entity, vehicle);
+        }
+
+    });
+
+    server.add_listener::<_, SteerVehiclePacket>(ServerboundPacket::SteerVehicle, |e| {
+
+        let (level_idx, entity) = match e.get_player() {
+            Some(player) => player,
+            None => return
+        };
+
+        if e.packet.unmount {
+            e.world.levels[level_idx].borrow_mut().dismount_entity(entity);
+        }
+
+    });
+
+}
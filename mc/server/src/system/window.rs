@@ -0,0 +1,1445 @@
+//! Container windows of players. Each player has an inventory window which is always open,
+//! and can open one container window at a time. Clicks sent by clients are replayed on the
+//! player's `Inventory` and on the container's inventory, the client is then resynchronized
+//! if its prediction differs from the server.
+//!
+//! Trading windows have a `MerchantTrade`, stacks can't be placed in their result slot and
+//! taking the result completes the trade, the traded offers are then recorded on merchants
+//! by the merchant system.
+//!
+//! Before 1.17, each click is accepted or rejected with a window confirmation, the clicks
+//! following a rejection are ignored until the client acknowledges it. Since 1.17.1, the
+//! client sends the slots it has changed and the state ID of the window, the whole window
+//! is sent again if the state ID is outdated.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::ops::Range;
+
+use mc_runtime::world::World;
+use mc_core::world::level::{Level, BaseEntity};
+use mc_core::item::{Item, ItemStack, Inventory};
+use mc_core::nbt::compound_tag_eq;
+use mc_core::pos::EntityPos;
+use mc_vanilla::entity::item::ItemEntity;
+use mc_vanilla::entity::{PlayerEntity, ITEM};
+use mc_vanilla::entity::merchant::{MerchantTrade, MERCHANT_CONTAINER_SIZE, MERCHANT_RESULT_SLOT};
+
+use super::protocol::{ProtocolServer, ProtocolPlayerEntity, PacketEvent};
+use crate::protocol::WritablePacket;
+use crate::protocol::play::{SlotData, ClickMode, ClickWindowPacket, CloseWindowPacket, WindowConfirmationPacket};
+use crate::protocol::play::{OpenWindowPacket, WindowItemsPacket, SetSlotPacket, TradeListPacket, TradeData};
+use crate::protocol::version::{ProtocolVersion, ClientboundPacket, ServerboundPacket};
+
+
+/// Size of the player inventory, slots 0 to 8 are the hotbar and 9 to 35 the main inventory.
+pub const PLAYER_INVENTORY_SIZE: usize = 36;
+/// The ID of the player inventory window.
+pub const PLAYER_WINDOW_ID: u8 = 0;
+/// The slot sent by clients when clicking outside of the window.
+pub const OUTSIDE_SLOT: i16 = -999;
+
+/// Number of slots of the player window before the player inventory, the crafting grid
+/// and the armor, the offhand is the last slot after the player inventory.
+const PLAYER_WINDOW_EXTRA_SLOTS: usize = 9;
+/// Pickup delay of items dropped from windows, in ticks.
+const DROPPED_PICKUP_DELAY: u32 = 40;
+/// Height above player's feet where dropped items are spawned.
+const DROPPED_HEIGHT: f64 = 1.32;
+
+
+/// Network IDs of items, items are given IDs in their registration order, items that are
+/// not registered are sent as empty slots.
+pub struct NetworkItems {
+    items: Vec<&'static Item>,
+    ids: HashMap<&'static str, i32>
+}
+
+impl NetworkItems {
+
+    pub fn new() -> Self {
+        Self {
+            items: Vec::new(),
+            ids: HashMap::new()
+        }
+    }
+
+    /// Register the given items, they must be given in the order of their network IDs.
+    pub fn with_items(mut self, items: &[&'static Item]) -> Self {
+        for &item in items {
+            self.ids.insert(item.get_name(), self.items.len() as i32);
+            self.items.push(item);
+        }
+        self
+    }
+
+    pub fn get_id(&self, item: &'static Item) -> Option<i32> {
+        self.ids.get(item.get_name()).copied()
+    }
+
+    pub fn get_item(&self, id: i32) -> Option<&'static Item> {
+        if id < 0 {
+            None
+        } else {
+            self.items.get(id as usize).copied()
+        }
+    }
+
+    /// Encode a stack for packets, its count is clamped to fit a byte.
+    pub fn encode_stack(&self, stack: Option<&ItemStack>) -> Option<SlotData> {
+        let stack = stack?;
+        Some(SlotData {
+            item_id: self.get_id(stack.get_item())?,
+            count: stack.get_count().min(i8::MAX as u16) as i8,
+            tag: stack.get_tag().cloned()
+        })
+    }
+
+    /// Decode a stack from packets, slots with unknown items or no items are empty.
+    pub fn decode_stack(&self, data: Option<&SlotData>) -> Option<ItemStack> {
+        let data = data?;
+        if data.count <= 0 {
+            return None;
+        }
+        Some(ItemStack::new(self.get_item(data.item_id)?, data.count as u16, data.tag.clone()))
+    }
+
+}
+
+impl Default for NetworkItems {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// Types of container windows, as opened on clients.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum WindowType {
+    Generic9x1,
+    Generic9x2,
+    Generic9x3,
+    Generic9x4,
+    Generic9x5,
+    Generic9x6,
+    Generic3x3,
+    /// The crafting table, its container is the result slot followed by the crafting grid.
+    Crafting,
+    /// The trading window of merchants, its container is the two payment slots followed by
+    /// the result slot.
+    Merchant
+}
+
+impl WindowType {
+
+    pub fn get_id(self) -> i32 {
+        match self {
+            Self::Generic9x1 => 0,
+            Self::Generic9x2 => 1,
+            Self::Generic9x3 => 2,
+            Self::Generic9x4 => 3,
+            Self::Generic9x5 => 4,
+            Self::Generic9x6 => 5,
+            Self::Generic3x3 => 6,
+            Self::Crafting => 11,
+            Self::Merchant => 18
+        }
+    }
+
+    /// Return the number of container slots of this type of window.
+    pub fn get_size(self) -> usize {
+        match self {
+            Self::Generic9x1 => 9,
+            Self::Generic9x2 => 18,
+            Self::Generic9x3 => 27,
+            Self::Generic9x4 => 36,
+            Self::Generic9x5 => 45,
+            Self::Generic9x6 => 54,
+            Self::Generic3x3 => 9,
+            Self::Crafting => 10,
+            Self::Merchant => MERCHANT_CONTAINER_SIZE
+        }
+    }
+
+}
+
+
+/// A window slot resolved to a slot of the container or of the player inventory.
+#[derive(Debug, Copy, Clone)]
+enum WindowSlot {
+    Container(usize),
+    Player(usize)
+}
+
+/// A window, its slots are the slots of its container followed by the slots of the player
+/// inventory, the main inventory first and the hotbar last.
+#[derive(Debug)]
+pub struct Window {
+    id: u8,
+    /// The type of a container window, none for the player window.
+    window_type: Option<WindowType>,
+    title: String,
+    container: Inventory,
+    /// Index of the first player inventory slot in this window.
+    player_start: usize,
+    /// The stacks as known by the client.
+    remote: Vec<Option<ItemStack>>,
+    /// Incremented each time the server sends slots, only used since 1.17.1.
+    state_id: i32,
+    /// The trade of a merchant window.
+    trade: Option<MerchantTrade>
+}
+
+impl Window {
+
+    fn new(id: u8, window_type: Option<WindowType>, title: String, container: Inventory, player_start: usize) -> Self {
+        Self {
+            id,
+            window_type,
+            title,
+            remote: vec![None; container.get_size() + PLAYER_INVENTORY_SIZE],
+            container,
+            player_start,
+            state_id: 0,
+            trade: None
+        }
+    }
+
+    /// The player window, its container is made of the crafting grid, the armor and the
+    /// offhand slots.
+    fn player() -> Self {
+        Self::new(PLAYER_WINDOW_ID, None, String::new(), Inventory::new(PLAYER_WINDOW_EXTRA_SLOTS + 1), PLAYER_WINDOW_EXTRA_SLOTS)
+    }
+
+    #[inline]
+    pub fn get_id(&self) -> u8 {
+        self.id
+    }
+
+    #[inline]
+    pub fn get_window_type(&self) -> Option<WindowType> {
+        self.window_type
+    }
+
+    pub fn get_title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn get_container(&self) -> &Inventory {
+        &self.container
+    }
+
+    pub fn get_container_mut(&mut self) -> &mut Inventory {
+        &mut self.container
+    }
+
+    /// Return the number of slots of this window.
+    pub fn get_size(&self) -> usize {
+        self.remote.len()
+    }
+
+    #[inline]
+    pub fn get_state_id(&self) -> i32 {
+        self.state_id
+    }
+
+    pub fn get_trade(&self) -> Option<&MerchantTrade> {
+        self.trade.as_ref()
+    }
+
+    /// Return true if the given slot is the result slot of a merchant window.
+    fn is_result_slot(&self, index: usize) -> bool {
+        self.trade.is_some() && index == MERCHANT_RESULT_SLOT
+    }
+
+    /// Update the result slot of a merchant window from its payment slots.
+    fn update_trade(&mut self) {
+        if let Some(trade) = &mut self.trade {
+            trade.update_result(&mut self.container);
+        }
+    }
+
+    fn get_slot(&self, index: usize, player: &Inventory) -> Option<WindowSlot> {
+        let slot = if index < self.player_start {
+            WindowSlot::Container(index)
+        } else if index < self.player_start + 27 {
+            WindowSlot::Player(index - self.player_start + 9)
+        } else if index < self.player_start + PLAYER_INVENTORY_SIZE {
+            WindowSlot::Player(index - self.player_start - 27)
+        } else {
+            WindowSlot::Container(index - PLAYER_INVENTORY_SIZE)
+        };
+        match slot {
+            WindowSlot::Container(i) if i < self.container.get_size() => Some(slot),
+            WindowSlot::Player(i) if i < player.get_size() => Some(slot),
+            _ => None
+        }
+    }
+
+    /// Return the stack in the given slot of this window.
+    pub fn get_stack<'a>(&'a self, player: &'a Inventory, index: usize) -> Option<&'a ItemStack> {
+        match self.get_slot(index, player)? {
+            WindowSlot::Container(i) => self.container.get_stack(i),
+            WindowSlot::Player(i) => player.get_stack(i)
+        }
+    }
+
+    /// Set the stack in the given slot of this window and return the previous one, the
+    /// stack is returned if the slot is not valid.
+    pub fn set_stack(&mut self, player: &mut Inventory, index: usize, stack: Option<ItemStack>) -> Option<ItemStack> {
+        match self.get_slot(index, player) {
+            Some(WindowSlot::Container(i)) => self.container.set_stack(i, stack),
+            Some(WindowSlot::Player(i)) => player.set_stack(i, stack),
+            None => stack
+        }
+    }
+
+    /// Return the slots where a stack is moved when shift clicked, and if they are filled
+    /// in reverse order.
+    fn get_quick_move_target(&self, index: usize) -> (Range<usize>, bool) {
+        let player_range = self.player_start..self.player_start + PLAYER_INVENTORY_SIZE;
+        let hotbar_start = self.player_start + 27;
+        if matches!(self.window_type, None | Some(WindowType::Crafting | WindowType::Merchant)) {
+            // In the player, crafting and merchant windows, stacks are moved between the main
+            // inventory and the hotbar, other slots are moved to the player inventory.
+            if (self.player_start..hotbar_start).contains(&index) {
+                (hotbar_start..player_range.end, false)
+            } else if player_range.contains(&index) {
+                (self.player_start..hotbar_start, false)
+            } else {
+                (player_range, false)
+            }
+        } else if index < self.player_start {
+            (player_range, true)
+        } else {
+            (0..self.player_start, false)
+        }
+    }
+
+}
+
+
+/// The kind of a drag, middle drags are only available in creative mode.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum DragKind {
+    /// Split the cursor stack evenly between slots.
+    Split,
+    /// Place one item in each slot.
+    One
+}
+
+#[derive(Debug)]
+struct Drag {
+    kind: DragKind,
+    slots: Vec<usize>
+}
+
+
+/// An entity component for players, tracking their windows, the stack carried by their
+/// cursor and their synchronization state. Player entities must also have an `Inventory`
+/// of `PLAYER_INVENTORY_SIZE` slots.
+#[derive(Debug)]
+pub struct PlayerWindows {
+    player: Window,
+    container: Option<Window>,
+    cursor: Option<ItemStack>,
+    remote_cursor: Option<ItemStack>,
+    drag: Option<Drag>,
+    next_id: u8,
+    /// Set when a container has been opened and not yet sent to the client.
+    opening: bool,
+    /// The ID of a container window closed by the server, to send to the client.
+    closing: Option<u8>,
+    /// The window ID and action number of the last rejected click, before 1.17.
+    rejected: Option<(u8, i16)>,
+    /// Inventories of containers closed by the client.
+    closed: Vec<Inventory>,
+    /// Stacks dropped by the player, to spawn in the level.
+    dropped: Vec<ItemStack>,
+    /// Indices of the offers traded in merchant windows.
+    trades: Vec<usize>
+}
+
+impl PlayerWindows {
+
+    pub fn new() -> Self {
+        Self {
+            player: Window::player(),
+            container: None,
+            cursor: None,
+            remote_cursor: None,
+            drag: None,
+            next_id: 1,
+            opening: false,
+            closing: None,
+            rejected: None,
+            closed: Vec::new(),
+            dropped: Vec::new(),
+            trades: Vec::new()
+        }
+    }
+
+    pub fn get_player_window(&self) -> &Window {
+        &self.player
+    }
+
+    pub fn get_player_window_mut(&mut self) -> &mut Window {
+        &mut self.player
+    }
+
+    pub fn get_container(&self) -> Option<&Window> {
+        self.container.as_ref()
+    }
+
+    pub fn get_container_mut(&mut self) -> Option<&mut Window> {
+        self.container.as_mut()
+    }
+
+    /// Return the container window if open, or the player window.
+    pub fn get_active_window(&self) -> &Window {
+        self.container.as_ref().unwrap_or(&self.player)
+    }
+
+    pub fn get_cursor(&self) -> Option<&ItemStack> {
+        self.cursor.as_ref()
+    }
+
+    /// Open a container window with the given inventory, the inventory of the previously
+    /// opened container is returned. The window is sent to the client on the next tick.
+    pub fn open_container(&mut self, window_type: WindowType, title: String, inventory: Inventory) -> Option<Inventory> {
+        let id = self.next_id;
+        self.next_id = id % 100 + 1;
+        let player_start = inventory.get_size();
+        let previous = self.container.replace(Window::new(id, Some(window_type), title, inventory, player_start));
+        self.drag = None;
+        self.opening = true;
+        previous.map(|window| window.container)
+    }
+
+    /// Open a merchant window for the given trade, the inventory of the previously opened
+    /// container is returned. The offers are sent to the client with the window.
+    pub fn open_merchant(&mut self, title: String, trade: MerchantTrade) -> Option<Inventory> {
+        let previous = self.open_container(WindowType::Merchant, title, Inventory::new(MERCHANT_CONTAINER_SIZE));
+        self.container.as_mut().unwrap().trade = Some(trade);
+        previous
+    }
+
+    /// Select an offer of the opened merchant window, its cost is moved from the player
+    /// inventory to the payment slots. Return false if no merchant window is open.
+    pub fn select_trade(&mut self, inventory: &mut Inventory, index: usize) -> bool {
+        match &mut self.container {
+            Some(Window { trade: Some(trade), container, .. }) => {
+                trade.select(container, inventory, index);
+                true
+            }
+            _ => false
+        }
+    }
+
+    /// Take the indices of the offers traded since the last call, the merchant system uses
+    /// it to record trades on merchants.
+    pub fn take_trades(&mut self) -> Vec<usize> {
+        std::mem::take(&mut self.trades)
+    }
+
+    /// Close the container window from the server and return its inventory, the cursor
+    /// stack is put back in the player inventory.
+    pub fn close_container(&mut self, inventory: &mut Inventory) -> Option<Inventory> {
+        let window = self.container.take()?;
+        if !std::mem::take(&mut self.opening) {
+            self.closing = Some(window.id);
+        }
+        self.return_cursor(inventory);
+        Some(window.container)
+    }
+
+    /// Take the inventories of the containers closed by the client, the systems owning
+    /// these containers should use it to get their content back.
+    pub fn take_closed_containers(&mut self) -> Vec<Inventory> {
+        std::mem::take(&mut self.closed)
+    }
+
+    /// Take the stacks dropped by the player since the last call.
+    pub fn take_dropped(&mut self) -> Vec<ItemStack> {
+        std::mem::take(&mut self.dropped)
+    }
+
+    /// Handle a window closed by the client, the cursor stack is put back in the player
+    /// inventory and the container window is closed if it has the given ID.
+    pub fn handle_close(&mut self, inventory: &mut Inventory, window_id: u8) {
+        if window_id != PLAYER_WINDOW_ID && matches!(&self.container, Some(window) if window.id == window_id) {
+            self.closed.push(self.container.take().unwrap().container);
+            self.opening = false;
+        }
+        self.return_cursor(inventory);
+    }
+
+    /// Put back the cursor stack in the player inventory, dropping what doesn't fit.
+    fn return_cursor(&mut self, inventory: &mut Inventory) {
+        self.drag = None;
+        if let Some(stack) = self.cursor.take() {
+            if let Some(remaining) = inventory.insert(stack) {
+                self.dropped.push(remaining);
+            }
+        }
+    }
+
+    /// Return the stack of the given slot of the given window, if open.
+    pub fn get_stack<'a>(&'a self, inventory: &'a Inventory, window_id: u8, slot: i16) -> Option<&'a ItemStack> {
+        let window = self.get_window(window_id)?;
+        if slot < 0 {
+            None
+        } else {
+            window.get_stack(inventory, slot as usize)
+        }
+    }
+
+    fn get_window(&self, window_id: u8) -> Option<&Window> {
+        match window_id {
+            PLAYER_WINDOW_ID => Some(&self.player),
+            id => self.container.as_ref().filter(|window| window.id == id)
+        }
+    }
+
+    fn get_window_mut(&mut self, window_id: u8) -> Option<&mut Window> {
+        match window_id {
+            PLAYER_WINDOW_ID => Some(&mut self.player),
+            id => self.container.as_mut().filter(|window| window.id == id)
+        }
+    }
+
+    /// Replay a click of the client on the given window, return false if the click is not
+    /// valid, in such case the window is left untouched.
+    pub fn click(&mut self, inventory: &mut Inventory, window_id: u8, slot: i16, button: i8, mode: ClickMode) -> bool {
+        let valid = self.replay_click(inventory, window_id, slot, button, mode);
+        if let Some(window) = self.get_window_mut(window_id) {
+            window.update_trade();
+        }
+        valid
+    }
+
+    fn replay_click(&mut self, inventory: &mut Inventory, window_id: u8, slot: i16, button: i8, mode: ClickMode) -> bool {
+
+        // Any other click cancels the current drag.
+        if mode != ClickMode::Drag && self.drag.take().is_some() {
+            return false;
+        }
+
+        let Self { player, container, cursor, drag, dropped, trades, .. } = self;
+        let (window, offhand) = match window_id {
+            PLAYER_WINDOW_ID => (player, None),
+            id => match container {
+                Some(window) if window.id == id => (window, Some(&mut player.container)),
+                _ => return false
+            }
+        };
+
+        let mut click = Click {
+            window,
+            player: inventory,
+            offhand,
+            cursor,
+            dropped,
+            trades
+        };
+
+        if mode == ClickMode::Drag {
+            let valid = click.drag(drag, slot, button);
+            if !valid {
+                *drag = None;
+            }
+            return valid;
+        }
+
+        if slot == OUTSIDE_SLOT {
+            return match (mode, button) {
+                (ClickMode::Click, 0) => {
+                    click.dropped.extend(click.cursor.take());
+                    true
+                }
+                (ClickMode::Click, 1) => {
+                    if let Some(stack) = click.cursor.take() {
+                        let (one, remaining) = split_stack(stack, 1);
+                        click.dropped.extend(one);
+                        *click.cursor = remaining;
+                    }
+                    true
+                }
+                (ClickMode::Click, _) => false,
+                _ => true
+            };
+        } else if slot < 0 {
+            // Clicks on the border of the window.
+            return true;
+        }
+
+        let index = slot as usize;
+        if click.window.get_slot(index, click.player).is_none() {
+            return false;
+        }
+
+        match (mode, button) {
+            (ClickMode::Click, 0 | 1) => click.pickup(index, button == 1),
+            (ClickMode::ShiftClick, 0 | 1) => click.quick_move(index),
+            (ClickMode::NumberKey, 0..=8 | 40) => return click.swap_hotbar(index, button as usize),
+            // Cloning stacks is only available in creative mode.
+            (ClickMode::MiddleClick, 2) => {}
+            (ClickMode::Drop, 0 | 1) => click.throw(index, button == 1),
+            (ClickMode::DoubleClick, 0) => click.pickup_all(index),
+            _ => return false
+        }
+
+        true
+
+    }
+
+    /// Update the stacks known by the client from the slots it has changed and its carried
+    /// stack, since 1.17.1.
+    fn set_remote(&mut self, window_id: u8, changed_slots: Vec<(i16, Option<ItemStack>)>, carried: Option<ItemStack>) {
+        if let Some(window) = self.get_window_mut(window_id) {
+            for (slot, stack) in changed_slots {
+                if let Some(remote) = window.remote.get_mut(slot as usize) {
+                    *remote = stack;
+                }
+            }
+            self.remote_cursor = carried;
+        }
+    }
+
+    /// Consider that the client knows the current stacks of the active window.
+    fn set_synchronized(&mut self, inventory: &Inventory) {
+        self.remote_cursor = self.cursor.clone();
+        let window = self.container.as_mut().unwrap_or(&mut self.player);
+        window.remote = (0..window.remote.len())
+            .map(|index| window.get_stack(inventory, index).cloned())
+            .collect();
+    }
+
+    /// Build the packets sending the whole active window to the client.
+    fn get_full_state(&mut self, inventory: &Inventory, items: &NetworkItems) -> (WindowItemsPacket, SetSlotPacket) {
+        self.set_synchronized(inventory);
+        let carried = items.encode_stack(self.cursor.as_ref());
+        let window = self.container.as_mut().unwrap_or(&mut self.player);
+        window.state_id = window.state_id.wrapping_add(1);
+        (WindowItemsPacket {
+            window_id: window.id,
+            state_id: window.state_id,
+            slots: window.remote.iter().map(|stack| items.encode_stack(stack.as_ref())).collect(),
+            carried: carried.clone()
+        }, SetSlotPacket {
+            window_id: SetSlotPacket::CARRIED_WINDOW_ID,
+            state_id: window.state_id,
+            slot: SetSlotPacket::CARRIED_SLOT,
+            data: carried
+        })
+    }
+
+    /// Build the packets sending the slots of the active window, and the cursor, that are
+    /// different from what the client knows.
+    fn get_changes(&mut self, inventory: &Inventory, items: &NetworkItems) -> Vec<SetSlotPacket> {
+
+        let mut packets = Vec::new();
+        let window = self.container.as_mut().unwrap_or(&mut self.player);
+
+        for index in 0..window.remote.len() {
+            let stack = window.get_stack(inventory, index).cloned();
+            if !stacks_eq(stack.as_ref(), window.remote[index].as_ref()) {
+                window.state_id = window.state_id.wrapping_add(1);
+                packets.push(SetSlotPacket {
+                    window_id: window.id as i8,
+                    state_id: window.state_id,
+                    slot: index as i16,
+                    data: items.encode_stack(stack.as_ref())
+                });
+                window.remote[index] = stack;
+            }
+        }
+
+        if !stacks_eq(self.cursor.as_ref(), self.remote_cursor.as_ref()) {
+            self.remote_cursor = self.cursor.clone();
+            packets.push(SetSlotPacket {
+                window_id: SetSlotPacket::CARRIED_WINDOW_ID,
+                state_id: window.state_id,
+                slot: SetSlotPacket::CARRIED_SLOT,
+                data: items.encode_stack(self.cursor.as_ref())
+            });
+        }
+
+        packets
+
+    }
+
+}
+
+impl Default for PlayerWindows {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// Internal structure used to replay a click on a window.
+struct Click<'a> {
+    window: &'a mut Window,
+    player: &'a mut Inventory,
+    /// The container of the player window, holding the offhand slot, when the click is
+    /// made in a container window.
+    offhand: Option<&'a mut Inventory>,
+    cursor: &'a mut Option<ItemStack>,
+    dropped: &'a mut Vec<ItemStack>,
+    trades: &'a mut Vec<usize>
+}
+
+impl<'a> Click<'a> {
+
+    fn get(&self, index: usize) -> Option<&ItemStack> {
+        self.window.get_stack(self.player, index)
+    }
+
+    fn take(&mut self, index: usize) -> Option<ItemStack> {
+        self.window.set_stack(self.player, index, None)
+    }
+
+    fn set(&mut self, index: usize, stack: Option<ItemStack>) {
+        self.window.set_stack(self.player, index, stack);
+    }
+
+    fn is_valid(&self, index: usize) -> bool {
+        self.window.get_slot(index, self.player).is_some()
+    }
+
+    /// Take the stack of the result slot of a merchant window and complete the trade.
+    fn take_result(&mut self, index: usize) -> Option<ItemStack> {
+        let result = self.take(index)?;
+        let Window { trade, container, .. } = &mut *self.window;
+        if let Some(index) = trade.as_mut().and_then(|trade| trade.complete(container)) {
+            self.trades.push(index);
+        }
+        Some(result)
+    }
+
+    /// The whole result is taken if the cursor can hold it, stacks are never placed.
+    fn pickup_result(&mut self, index: usize) {
+        let count = match (self.get(index), self.cursor.as_ref()) {
+            (None, _) => return,
+            (Some(_), None) => 0,
+            (Some(result), Some(carried)) => {
+                let count = carried.get_count() + result.get_count();
+                if !carried.is_stackable_with(result) || count > carried.get_item().get_stack_size() {
+                    return;
+                }
+                count
+            }
+        };
+        let mut result = self.take_result(index).unwrap();
+        if count > 0 {
+            result.set_count(count);
+        }
+        *self.cursor = Some(result);
+    }
+
+    /// Left click takes or places the whole stack, right click takes half of the stack or
+    /// places one item. Stacks that cannot be merged are swapped.
+    fn pickup(&mut self, index: usize, right: bool) {
+        if self.window.is_result_slot(index) {
+            return self.pickup_result(index);
+        }
+        let (slot, cursor) = match (self.take(index), self.cursor.take()) {
+            (None, None) => (None, None),
+            (Some(stack), None) if right => {
+                let half = stack.get_count().div_ceil(2);
+                let (taken, remaining) = split_stack(stack, half);
+                (remaining, taken)
+            }
+            (Some(stack), None) => (None, Some(stack)),
+            (None, Some(carried)) if right => split_stack(carried, 1),
+            (None, Some(carried)) => (Some(carried), None),
+            (Some(mut stack), Some(carried)) if stack.is_stackable_with(&carried) => {
+                if right {
+                    if stack.is_full() {
+                        (Some(stack), Some(carried))
+                    } else {
+                        let (one, remaining) = split_stack(carried, 1);
+                        stack.set_count(stack.get_count() + one.map_or(0, |one| one.get_count()));
+                        (Some(stack), remaining)
+                    }
+                } else {
+                    let remaining = stack.merge(carried);
+                    (Some(stack), remaining)
+                }
+            }
+            (Some(stack), Some(carried)) => (Some(carried), Some(stack))
+        };
+        self.set(index, slot);
+        *self.cursor = cursor;
+    }
+
+    /// Move the stack of a slot to the other part of the window.
+    fn quick_move(&mut self, index: usize) {
+        if self.window.is_result_slot(index) {
+            // The result is only taken if the player inventory can hold all of it.
+            match self.get(index) {
+                Some(result) if self.player.clone().insert(result.clone()).is_none() => {}
+                _ => return
+            }
+            let result = self.take_result(index).unwrap();
+            let (range, reverse) = self.window.get_quick_move_target(index);
+            let remaining = self.move_to_range(result, range, reverse);
+            self.dropped.extend(remaining);
+        } else if let Some(stack) = self.take(index) {
+            let (range, reverse) = self.window.get_quick_move_target(index);
+            let remaining = self.move_to_range(stack, range, reverse);
+            self.set(index, remaining);
+        }
+    }
+
+    /// Merge a stack into the stacks of the given slots and then put it in the first empty
+    /// slot, the remaining stack is returned.
+    fn move_to_range(&mut self, stack: ItemStack, range: Range<usize>, reverse: bool) -> Option<ItemStack> {
+
+        let indices: Vec<usize> = if reverse { range.rev().collect() } else { range.collect() };
+        let mut remaining = stack;
+
+        for &index in &indices {
+            if let Some(mut slot_stack) = self.take(index) {
+                let merged = slot_stack.merge(remaining);
+                self.set(index, Some(slot_stack));
+                remaining = merged?;
+            }
+        }
+
+        for &index in &indices {
+            if self.is_valid(index) && self.get(index).is_none() {
+                self.set(index, Some(remaining));
+                return None;
+            }
+        }
+
+        Some(remaining)
+
+    }
+
+    /// Swap a slot with a hotbar slot, or with the offhand slot.
+    fn swap_hotbar(&mut self, index: usize, button: usize) -> bool {
+        let target = if button == 40 {
+            if self.offhand.is_some() {
+                return self.swap_offhand(index);
+            }
+            self.window.player_start + PLAYER_INVENTORY_SIZE
+        } else {
+            self.window.player_start + 27 + button
+        };
+        if self.window.is_result_slot(index) {
+            if self.get(target).is_none() {
+                let result = self.take_result(index);
+                self.set(target, result);
+            }
+            return true;
+        }
+        let stack = self.take(index);
+        let target_stack = self.take(target);
+        self.set(index, target_stack);
+        self.set(target, stack);
+        true
+    }
+
+    /// Swap a slot of a container window with the offhand slot, which is not part of the
+    /// window but is the last slot of the player window container.
+    fn swap_offhand(&mut self, index: usize) -> bool {
+        if self.window.is_result_slot(index) {
+            if self.offhand.as_deref().unwrap().get_stack(PLAYER_WINDOW_EXTRA_SLOTS).is_none() {
+                let result = self.take_result(index);
+                self.offhand.as_deref_mut().unwrap().set_stack(PLAYER_WINDOW_EXTRA_SLOTS, result);
+            }
+            return true;
+        }
+        let stack = self.take(index);
+        let offhand_stack = self.offhand.as_deref_mut().unwrap().set_stack(PLAYER_WINDOW_EXTRA_SLOTS, stack);
+        self.set(index, offhand_stack);
+        true
+    }
+
+    /// Drop one item or the whole stack of a slot, only when the cursor is empty.
+    fn throw(&mut self, index: usize, all: bool) {
+        if self.cursor.is_none() {
+            if self.window.is_result_slot(index) {
+                let result = self.take_result(index);
+                self.dropped.extend(result);
+            } else if let Some(stack) = self.take(index) {
+                let (thrown, remaining) = if all { (Some(stack), None) } else { split_stack(stack, 1) };
+                self.dropped.extend(thrown);
+                self.set(index, remaining);
+            }
+        }
+    }
+
+    /// Gather the stacks matching the cursor stack, first from slots that are not full.
+    fn pickup_all(&mut self, index: usize) {
+
+        if self.get(index).is_some() {
+            return;
+        }
+
+        let mut carried = match self.cursor.take() {
+            Some(carried) => carried,
+            None => return
+        };
+
+        for full_pass in [false, true] {
+            for index in 0..self.window.get_size() {
+                if carried.is_full() {
+                    break;
+                } else if self.window.is_result_slot(index) {
+                    continue;
+                }
+                match self.get(index) {
+                    Some(stack) if stack.is_stackable_with(&carried) && stack.is_full() == full_pass => {}
+                    _ => continue
+                }
+                let space = carried.get_item().get_stack_size().saturating_sub(carried.get_count());
+                let (taken, remaining) = split_stack(self.take(index).unwrap(), space);
+                carried.set_count(carried.get_count() + taken.map_or(0, |taken| taken.get_count()));
+                self.set(index, remaining);
+            }
+        }
+
+        *self.cursor = Some(carried);
+
+    }
+
+    /// Handle a stage of a drag, the button gives the stage (start, add slot and end) in
+    /// its lower 2 bits and the kind of drag in the next 2 bits.
+    fn drag(&mut self, drag: &mut Option<Drag>, slot: i16, button: i8) -> bool {
+
+        let kind = match (button >> 2) & 3 {
+            0 => DragKind::Split,
+            1 => DragKind::One,
+            _ => return false
+        };
+
+        match button & 3 {
+            0 => {
+                if drag.is_some() || self.cursor.is_none() {
+                    return false;
+                }
+                *drag = Some(Drag { kind, slots: Vec::new() });
+                true
+            }
+            1 => {
+                let current = match drag {
+                    Some(current) if current.kind == kind => current,
+                    _ => return false
+                };
+                if slot < 0 || !self.is_valid(slot as usize) {
+                    return false;
+                }
+                let index = slot as usize;
+                let carried = match self.cursor.as_ref() {
+                    Some(carried) => carried,
+                    None => return false
+                };
+                // Slots that cannot receive the stack are ignored, as done by the client.
+                let accepted = !self.window.is_result_slot(index)
+                    && self.get(index).is_none_or(|stack| stack.is_stackable_with(carried));
+                if accepted && carried.get_count() as usize > current.slots.len() && !current.slots.contains(&index) {
+                    current.slots.push(index);
+                }
+                true
+            }
+            2 => {
+                let current = match drag.take() {
+                    Some(current) if current.kind == kind => current,
+                    _ => return false
+                };
+                if current.slots.len() == 1 {
+                    self.pickup(current.slots[0], kind == DragKind::One);
+                } else if !current.slots.is_empty() {
+                    self.distribute(&current);
+                }
+                true
+            }
+            _ => false
+        }
+
+    }
+
+    /// Distribute the cursor stack between the slots of a drag.
+    fn distribute(&mut self, drag: &Drag) {
+
+        let carried = match self.cursor.take() {
+            Some(carried) => carried,
+            None => return
+        };
+
+        let per_slot = match drag.kind {
+            DragKind::Split => carried.get_count() / drag.slots.len() as u16,
+            DragKind::One => 1
+        };
+
+        let stack_size = carried.get_item().get_stack_size();
+        let mut remaining = carried.get_count();
+
+        for &index in &drag.slots {
+            let current = match self.get(index) {
+                Some(stack) if stack.is_stackable_with(&carried) => stack.get_count(),
+                Some(_) => continue,
+                None => 0
+            };
+            let added = per_slot.min(stack_size.saturating_sub(current)).min(remaining);
+            if added > 0 {
+                let mut stack = carried.clone();
+                stack.set_count(current + added);
+                self.set(index, Some(stack));
+                remaining -= added;
+            }
+        }
+
+        *self.cursor = split_stack(carried, remaining).0;
+
+    }
+
+}
+
+
+/// Split a stack in two, the first stack having at most the given count.
+fn split_stack(mut stack: ItemStack, count: u16) -> (Option<ItemStack>, Option<ItemStack>) {
+    if count == 0 {
+        (None, Some(stack))
+    } else if count >= stack.get_count() {
+        (Some(stack), None)
+    } else {
+        let mut taken = stack.clone();
+        taken.set_count(count);
+        stack.set_count(stack.get_count() - count);
+        (Some(taken), Some(stack))
+    }
+}
+
+/// Return true if both stacks have the same item, count and tag.
+fn stacks_eq(a: Option<&ItemStack>, b: Option<&ItemStack>) -> bool {
+    match (a, b) {
+        (None, None) => true,
+        (Some(a), Some(b)) => {
+            std::ptr::eq(a.get_item(), b.get_item()) && a.get_count() == b.get_count() && match (a.get_tag(), b.get_tag()) {
+                (None, None) => true,
+                (Some(a), Some(b)) => compound_tag_eq(a, b),
+                _ => false
+            }
+        }
+        _ => false
+    }
+}
+
+
+/// Internal trait to send window packets from packet listeners and systems.
+trait WindowPacketSender {
+    fn send<P: WritablePacket>(&self, kind: ClientboundPacket, packet: &mut P);
+}
+
+impl<'a, 'b, P> WindowPacketSender for PacketEvent<'a, 'b, P> {
+    fn send<R: WritablePacket>(&self, kind: ClientboundPacket, packet: &mut R) {
+        self.answer_packet(kind, packet);
+    }
+}
+
+struct ServerSender<'a> {
+    server: &'a ProtocolServer,
+    addr: SocketAddr
+}
+
+impl<'a> WindowPacketSender for ServerSender<'a> {
+    fn send<P: WritablePacket>(&self, kind: ClientboundPacket, packet: &mut P) {
+        self.server.send_packet(self.addr, kind, packet);
+    }
+}
+
+/// Send the whole active window, before 1.17.1 the cursor stack is sent separately.
+fn send_full_state<S: WindowPacketSender>(sender: &S, version: ProtocolVersion, windows: &mut PlayerWindows, inventory: &Inventory, items: &NetworkItems) {
+    let (mut window_items, mut carried) = windows.get_full_state(inventory, items);
+    sender.send(ClientboundPacket::WindowItems, &mut window_items);
+    if version < ProtocolVersion::V1_17_1 {
+        sender.send(ClientboundPacket::SetSlot, &mut carried);
+    }
+}
+
+fn send_changes<S: WindowPacketSender>(sender: &S, windows: &mut PlayerWindows, inventory: &Inventory, items: &NetworkItems) {
+    for mut packet in windows.get_changes(inventory, items) {
+        sender.send(ClientboundPacket::SetSlot, &mut packet);
+    }
+}
+
+/// Build the packet sending the offers of a merchant window.
+fn get_trade_list(window_id: u8, trade: &MerchantTrade, items: &NetworkItems) -> TradeListPacket {
+    // Other merchants than villagers are shown at level 1, without experience bar.
+    let (villager_level, xp, regular_villager) = match trade.get_villager() {
+        Some((level, xp)) => (level as i32, xp as i32, true),
+        None => (1, 0, false)
+    };
+    TradeListPacket {
+        window_id,
+        trades: trade.get_offers().iter().map(|offer| TradeData {
+            input_a: items.encode_stack(Some(offer.get_base_cost_a())),
+            output: items.encode_stack(Some(offer.get_result())),
+            input_b: items.encode_stack(offer.get_cost_b()),
+            disabled: offer.is_out_of_stock(),
+            uses: offer.get_uses() as i32,
+            max_uses: offer.get_max_uses() as i32,
+            xp: offer.get_xp() as i32,
+            special_price: offer.get_special_price(),
+            price_multiplier: offer.get_price_multiplier(),
+            demand: offer.get_demand()
+        }).collect(),
+        villager_level,
+        xp,
+        regular_villager,
+        can_restock: regular_villager
+    }
+}
+
+/// Spawn item entities for stacks dropped by a player.
+pub(super) fn spawn_dropped(level: &mut Level, pos: &EntityPos, stacks: Vec<ItemStack>) {
+    for stack in stacks {
+        let pos = EntityPos::new(pos.x, pos.y + DROPPED_HEIGHT, pos.z);
+        if let Some(entity) = level.spawn_entity(&ITEM, pos) {
+            let item = ItemEntity::new(stack).with_pickup_delay(DROPPED_PICKUP_DELAY);
+            level.entities.ecs.insert_one(entity, item).unwrap();
+        }
+    }
+}
+
+/// Spawn the stacks dropped by the given player entity.
+fn spawn_player_dropped(level: &mut Level, entity: hecs::Entity) {
+    let (pos, dropped) = match level.entities.ecs.query_one_mut::<(&BaseEntity, &mut PlayerWindows)>(entity) {
+        Ok((base, windows)) => (base.pos.clone(), windows.take_dropped()),
+        Err(_) => return
+    };
+    spawn_dropped(level, &pos, dropped);
+}
+
+
+/// Register the packet listeners for window clicks, confirmations and closing.
+pub(super) fn register_listeners(server: &mut ProtocolServer) {
+
+    server.add_listener::<_, ClickWindowPacket>(ServerboundPacket::ClickWindow, |e| {
+
+        let (level_idx, entity) = match e.get_player() {
+            Some(player) => player,
+            None => return
+        };
+
+        let items = match e.world.get_component::<NetworkItems>() {
+            Ok(items) => items,
+            Err(_) => return
+        };
+
+        let version = e.client.get_version();
+        let mut level = e.world.levels[level_idx].borrow_mut();
+
+        {
+
+            let (windows, inventory, player) = match level.entities.ecs.query_one_mut::<(&mut PlayerWindows, &mut Inventory, Option<&PlayerEntity>)>(entity) {
+                Ok(comps) => comps,
+                Err(_) => return
+            };
+
+            let packet = &e.packet;
+            // Spectators can look in windows but can't modify them.
+            let can_interact = player.is_none_or(PlayerEntity::can_interact);
+
+            if version < ProtocolVersion::V1_17_1 {
+
+                // Clicks are ignored until the client acknowledges the last rejection.
+                if windows.rejected.is_some() {
+                    return;
+                }
+
+                let expected = items.decode_stack(packet.item.as_ref());
+                let accepted = can_interact
+                    && stacks_eq(windows.get_stack(inventory, packet.window_id, packet.slot), expected.as_ref())
+                    && windows.click(inventory, packet.window_id, packet.slot, packet.button, packet.mode);
+
+                e.answer_packet(ClientboundPacket::WindowConfirmation, &mut WindowConfirmationPacket {
+                    window_id: packet.window_id as i8,
+                    action: packet.action,
+                    accepted
+                });
+
+                if accepted {
+                    windows.set_synchronized(inventory);
+                } else {
+                    windows.rejected = Some((packet.window_id, packet.action));
+                    send_full_state(&e, version, windows, inventory, &items);
+                }
+
+            } else {
+
+                let state_id = windows.get_window(packet.window_id).map(Window::get_state_id);
+                let accepted = can_interact && windows.click(inventory, packet.window_id, packet.slot, packet.button, packet.mode);
+
+                windows.set_remote(
+                    packet.window_id,
+                    packet.changed_slots.iter().map(|(slot, data)| (*slot, items.decode_stack(data.as_ref()))).collect(),
+                    items.decode_stack(packet.item.as_ref())
+                );
+
+                if !accepted || state_id != Some(packet.state_id) {
+                    send_full_state(&e, version, windows, inventory, &items);
+                } else {
+                    send_changes(&e, windows, inventory, &items);
+                }
+
+            }
+
+        }
+
+        spawn_player_dropped(&mut level, entity);
+
+    });
+
+    server.add_listener::<_, WindowConfirmationPacket>(ServerboundPacket::WindowConfirmation, |e| {
+        if let Some((level_idx, entity)) = e.get_player() {
+            let level = e.world.levels[level_idx].borrow();
+            if let Ok(mut windows) = level.entities.ecs.get_mut::<PlayerWindows>(entity) {
+                if windows.rejected == Some((e.packet.window_id as u8, e.packet.action)) {
+                    windows.rejected = None;
+                }
+            };
+        }
+    });
+
+    server.add_listener::<_, CloseWindowPacket>(ServerboundPacket::CloseWindow, |e| {
+        if let Some((level_idx, entity)) = e.get_player() {
+            let mut level = e.world.levels[level_idx].borrow_mut();
+            if let Ok((windows, inventory)) = level.entities.ecs.query_one_mut::<(&mut PlayerWindows, &mut Inventory)>(entity) {
+                windows.handle_close(inventory, e.packet.window_id);
+            }
+            spawn_player_dropped(&mut level, entity);
+        }
+    });
+
+}
+
+
+/// System sending opened and closed containers to players, and the slots of their active
+/// window modified by the server, for example when items are collected.
+pub fn system_window_sync(world: &mut World) {
+
+    let proto_server = world.components.get::<ProtocolServer>().unwrap();
+    let items = match world.components.get::<NetworkItems>() {
+        Ok(items) => items,
+        Err(_) => return
+    };
+
+    for level in &world.levels {
+
+        let mut level = level.borrow_mut();
+        let mut dropped = Vec::new();
+
+        for (_, (base, player, windows, inventory)) in level.entities.ecs.query_mut::<(&BaseEntity, &ProtocolPlayerEntity, &mut PlayerWindows, &Inventory)>() {
+
+            let version = match proto_server.get_client(player.addr) {
+                Some(client) => client.get_version(),
+                None => continue
+            };
+
+            let sender = ServerSender {
+                server: &proto_server,
+                addr: player.addr
+            };
+
+            if let Some(window_id) = windows.closing.take() {
+                sender.send(ClientboundPacket::CloseWindow, &mut CloseWindowPacket { window_id });
+            }
+
+            if std::mem::take(&mut windows.opening) {
+                let window = windows.container.as_ref().unwrap();
+                sender.send(ClientboundPacket::OpenWindow, &mut OpenWindowPacket {
+                    window_id: window.id,
+                    window_type: window.window_type.unwrap().get_id(),
+                    title: window.title.clone()
+                });
+                send_full_state(&sender, version, windows, inventory, &items);
+                let window = windows.container.as_ref().unwrap();
+                if let Some(trade) = &window.trade {
+                    sender.send(ClientboundPacket::TradeList, &mut get_trade_list(window.id, trade, &items));
+                }
+            } else {
+                send_changes(&sender, windows, inventory, &items);
+            }
+
+            if !windows.dropped.is_empty() {
+                dropped.push((base.pos.clone(), windows.take_dropped()));
+            }
+
+        }
+
+        for (pos, stacks) in dropped {
+            spawn_dropped(&mut level, &pos, stacks);
+        }
+
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    mc_core::items!(TEST_ITEMS "test" [
+        STONE "stone",
+        PEARL "pearl" 16
+    ]);
+
+    fn count_at(windows: &PlayerWindows, inventory: &Inventory, window_id: u8, slot: i16) -> u16 {
+        windows.get_stack(inventory, window_id, slot).map_or(0, ItemStack::get_count)
+    }
+
+    #[test]
+    fn window_clicks() {
+
+        let mut windows = PlayerWindows::new();
+        let mut inventory = Inventory::new(PLAYER_INVENTORY_SIZE);
+        inventory.set_stack(0, Some(ItemStack::with_item_count(&STONE, 10)));
+
+        // Player inventory slot 0 is the first hotbar slot, window slot 36.
+        assert_eq!(count_at(&windows, &inventory, 0, 36), 10);
+        assert!(windows.click(&mut inventory, 0, 36, 1, ClickMode::Click));
+        assert_eq!(windows.get_cursor().unwrap().get_count(), 5);
+        assert_eq!(count_at(&windows, &inventory, 0, 36), 5);
+
+        // Place one item with a right click, and the rest with a left click.
+        assert!(windows.click(&mut inventory, 0, 9, 1, ClickMode::Click));
+        assert!(windows.click(&mut inventory, 0, 36, 0, ClickMode::Click));
+        assert!(windows.get_cursor().is_none());
+        assert_eq!(count_at(&windows, &inventory, 0, 9), 1);
+        assert_eq!(count_at(&windows, &inventory, 0, 36), 9);
+
+        // Shift click from the hotbar to the main inventory, merging stacks.
+        assert!(windows.click(&mut inventory, 0, 36, 0, ClickMode::ShiftClick));
+        assert_eq!(inventory.get_stack(9).unwrap().get_count(), 10);
+        assert!(inventory.get_stack(0).is_none());
+
+        // Split the stack evenly by dragging over 3 slots.
+        assert!(windows.click(&mut inventory, 0, 9, 0, ClickMode::Click));
+        assert!(windows.click(&mut inventory, 0, OUTSIDE_SLOT, 0, ClickMode::Drag));
+        for slot in [36, 37, 38] {
+            assert!(windows.click(&mut inventory, 0, slot, 1, ClickMode::Drag));
+        }
+        assert!(windows.click(&mut inventory, 0, OUTSIDE_SLOT, 2, ClickMode::Drag));
+        assert_eq!([0, 1, 2].map(|i| inventory.get_stack(i).unwrap().get_count()), [3, 3, 3]);
+        assert_eq!(windows.get_cursor().unwrap().get_count(), 1);
+
+        // Another click during a drag cancels it.
+        assert!(windows.click(&mut inventory, 0, OUTSIDE_SLOT, 4, ClickMode::Drag));
+        assert!(!windows.click(&mut inventory, 0, 36, 0, ClickMode::Click));
+        assert!(!windows.click(&mut inventory, 0, OUTSIDE_SLOT, 6, ClickMode::Drag));
+
+        // Gather all stones on the cursor.
+        assert!(windows.click(&mut inventory, 0, 20, 0, ClickMode::DoubleClick));
+        assert_eq!(windows.get_cursor().unwrap().get_count(), 10);
+        assert_eq!(inventory.count_item(&STONE), 0);
+
+        // Drop one item outside of the window, the rest is put back on close.
+        assert!(windows.click(&mut inventory, 0, OUTSIDE_SLOT, 1, ClickMode::Click));
+        windows.handle_close(&mut inventory, 0);
+        assert_eq!(windows.take_dropped()[0].get_count(), 1);
+        assert_eq!(inventory.count_item(&STONE), 9);
+
+        // Container windows, shift clicks move stacks to the hotbar first.
+        let mut chest = Inventory::new(9);
+        chest.set_stack(4, Some(ItemStack::with_item_count(&PEARL, 16)));
+        assert!(windows.open_container(WindowType::Generic9x1, "Chest".to_string(), chest).is_none());
+        let window_id = windows.get_container().unwrap().get_id();
+        assert!(windows.click(&mut inventory, window_id, 4, 0, ClickMode::ShiftClick));
+        assert_eq!(inventory.get_stack(8).unwrap().get_count(), 16);
+
+        // Swap with the last hotbar slot, and with the offhand that is not part of the window.
+        assert!(windows.click(&mut inventory, window_id, 0, 8, ClickMode::NumberKey));
+        assert!(inventory.get_stack(8).is_none());
+        assert_eq!(windows.get_container().unwrap().get_container().get_stack(0).unwrap().get_count(), 16);
+        assert!(windows.click(&mut inventory, window_id, 0, 40, ClickMode::NumberKey));
+        assert!(windows.get_container().unwrap().get_container().get_stack(0).is_none());
+        assert_eq!(windows.get_player_window().get_stack(&inventory, 45).unwrap().get_count(), 16);
+        assert!(windows.click(&mut inventory, window_id, 0, 40, ClickMode::NumberKey));
+        assert!(windows.get_player_window().get_stack(&inventory, 45).is_none());
+        assert_eq!(windows.get_container().unwrap().get_container().get_stack(0).unwrap().get_count(), 16);
+        assert!(!windows.click(&mut inventory, window_id, 45, 0, ClickMode::Click));
+
+        // The client closes the container, its inventory is given back.
+        windows.handle_close(&mut inventory, window_id);
+        assert!(windows.get_container().is_none());
+        assert_eq!(windows.take_closed_containers()[0].count_item(&PEARL), 16);
+
+    }
+
+    #[test]
+    fn merchant_window() {
+
+        use mc_vanilla::entity::merchant::MerchantOffer;
+
+        let mut windows = PlayerWindows::new();
+        let mut inventory = Inventory::new(PLAYER_INVENTORY_SIZE);
+        inventory.set_stack(0, Some(ItemStack::with_item_count(&STONE, 12)));
+
+        let offer = MerchantOffer::new(ItemStack::with_item_count(&STONE, 4), ItemStack::with_item(&PEARL), 2, 1, 0.05);
+        windows.open_merchant("Villager".to_string(), MerchantTrade::new(vec![offer]));
+        let window_id = windows.get_container().unwrap().get_id();
+
+        // Selecting the offer moves the payment, the result is shown.
+        assert!(windows.select_trade(&mut inventory, 0));
+        assert_eq!(count_at(&windows, &inventory, window_id, 0), 12);
+        assert_eq!(count_at(&windows, &inventory, window_id, 2), 1);
+
+        // Stacks can't be placed in the result slot, taking it completes the trade.
+        assert!(windows.click(&mut inventory, window_id, 2, 0, ClickMode::Click));
+        assert_eq!(windows.get_cursor().unwrap().get_count(), 1);
+        assert_eq!(count_at(&windows, &inventory, window_id, 0), 8);
+        assert!(windows.click(&mut inventory, window_id, 2, 0, ClickMode::Click));
+        assert_eq!(windows.get_cursor().unwrap().get_count(), 2);
+        assert_eq!(windows.take_trades(), vec![0, 0]);
+
+        // The offer is out of stock after 2 uses.
+        assert_eq!(count_at(&windows, &inventory, window_id, 0), 4);
+        assert_eq!(count_at(&windows, &inventory, window_id, 2), 0);
+        assert!(windows.click(&mut inventory, window_id, 2, 0, ClickMode::Click));
+        assert_eq!(windows.get_cursor().unwrap().get_count(), 2);
+        assert_eq!(windows.get_container().unwrap().get_trade().unwrap().get_offers()[0].get_uses(), 2);
+
+        // Clicking the payment slot updates the result.
+        assert!(windows.click(&mut inventory, window_id, 0, 0, ClickMode::ShiftClick));
+        assert_eq!(count_at(&windows, &inventory, window_id, 0), 0);
+        assert!(windows.take_trades().is_empty());
+
+    }
+
+    #[test]
+    fn window_sync() {
+
+        let items = NetworkItems::new().with_items(&TEST_ITEMS);
+        assert_eq!(items.get_id(&PEARL), Some(1));
+
+        let mut windows = PlayerWindows::new();
+        let mut inventory = Inventory::new(PLAYER_INVENTORY_SIZE);
+        let (window_items, _) = windows.get_full_state(&inventory, &items);
+        assert_eq!(window_items.slots.len(), 46);
+        assert!(windows.get_changes(&inventory, &items).is_empty());
+
+        // Stacks collected by the player are sent to the client.
+        inventory.insert(ItemStack::with_item_count(&PEARL, 3));
+        let changes = windows.get_changes(&inventory, &items);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].slot, 36);
+        assert_eq!(changes[0].data.as_ref().unwrap().item_id, 1);
+        assert_eq!(changes[0].state_id, 2);
+        assert!(windows.get_changes(&inventory, &items).is_empty());
+
+        // A click predicted by the client doesn't need to be sent.
+        windows.click(&mut inventory, 0, 36, 0, ClickMode::Click);
+        windows.set_remote(0, vec![(36, None)], Some(ItemStack::with_item_count(&PEARL, 3)));
+        assert!(windows.get_changes(&inventory, &items).is_empty());
+
+    }
+
+}
@@ -8,6 +8,8 @@ use std::mem::size_of;
 fn main() {
 
     println!("==== LOADING STATES ====");
+    let report = GlobalBlocks::init_all_states(&VANILLA_BLOCKS, 4);
+    println!("Vanilla states built in {}us ({} blocks)", report.duration.as_micros(), report.built_blocks_count);
     let start = Instant::now();
     let blocks = GlobalBlocks::with_vanilla();
     println!("Vanilla blocks registered in {}us", start.elapsed().as_micros());
@@ -51,6 +53,8 @@ fn main() {
     let state_sizeof = size_of::<BlockState>();
     println!("Block sizeof: {} (total: {}ko)", block_sizeof, (block_sizeof * blocks_count) as f32 / 1000.0);
     println!("State sizeof: {} (total: {}ko)", state_sizeof, (state_sizeof * states_count) as f32 / 1000.0);
+    let storage_size: usize = VANILLA_BLOCKS.iter().map(|block| block.get_storage_size()).sum();
+    println!("States storage: {}ko (with properties tables)", storage_size as f32 / 1000.0);
     println!("========================");
 
 }
@@ -1,10 +1,9 @@
 use mc_core::world::level::{LevelEnv, Level};
 use mc_core::world::chunk::{Chunk, SubChunk, ChunkHeight};
 use mc_core::world::anvil::source::{AnvilLevelSource};
-use mc_vanilla::ext::WithVanilla;
+use mc_vanilla::ext::VanillaLevelEnv;
 
 use std::mem::size_of;
-use std::sync::Arc;
 use std::time::Duration;
 
 
@@ -31,7 +30,7 @@ fn main() {
     println!("====== ANVIL TEST ======");
 
     let level_dir = std::env::var("MCRS_LEVEL_DIR").unwrap();
-    let env = Arc::new(LevelEnv::with_vanilla());
+    let env = LevelEnv::vanilla();
     let source = AnvilLevelSource::new(level_dir);
     let height = ChunkHeight {
         min: -4,
@@ -0,0 +1,534 @@
+//! Advancements of datapacks, an advancement is loaded from a file
+//! `data/<namespace>/advancements/<path>.json` of a datapack and named `<namespace>:<path>`.
+//! Its criteria are granted to players when triggers fired by the game match their
+//! conditions, see the `trigger` module. Progress of players is stored in their
+//! `AdvancementProgress` component.
+
+use std::collections::{HashMap, HashSet};
+use std::cell::RefCell;
+use std::path::Path;
+use std::rc::Rc;
+use std::{fs, io};
+
+use mc_core::world::level::Level;
+use hecs::Entity;
+
+use mc_runtime::world::World;
+
+use serde_json::Value;
+
+use crate::entity::item::ItemPickupEvent;
+
+pub mod trigger;
+use trigger::{Trigger, TickTrigger, InventoryChangedTrigger};
+
+
+/// The frame of an advancement in the advancements screen.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AdvancementFrame {
+    Task,
+    Challenge,
+    Goal
+}
+
+impl AdvancementFrame {
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "task" => Some(Self::Task),
+            "challenge" => Some(Self::Challenge),
+            "goal" => Some(Self::Goal),
+            _ => None
+        }
+    }
+
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Self::Task => "task",
+            Self::Challenge => "challenge",
+            Self::Goal => "goal"
+        }
+    }
+
+}
+
+
+/// How an advancement is displayed to players, advancements without display are hidden and
+/// only used to track progress.
+#[derive(Debug, Clone)]
+pub struct AdvancementDisplay {
+    /// The title, as a JSON text component.
+    pub title: Value,
+    /// The description, as a JSON text component.
+    pub description: Value,
+    /// The name of the icon item.
+    pub icon: String,
+    pub frame: AdvancementFrame,
+    /// The background texture, only for root advancements.
+    pub background: Option<String>,
+    pub show_toast: bool,
+    pub announce_to_chat: bool,
+    /// The advancement and its children are hidden until it's done.
+    pub hidden: bool
+}
+
+
+/// Rewards given to players when an advancement is done.
+#[derive(Debug, Clone, Default)]
+pub struct AdvancementRewards {
+    pub experience: i32,
+    pub recipes: Vec<String>,
+    pub loot: Vec<String>,
+    /// A function executed as the player.
+    pub function: Option<String>
+}
+
+
+/// A criterion of an advancement, granted when a trigger with the given name matches the
+/// conditions.
+#[derive(Debug, Clone)]
+pub struct Criterion {
+    pub trigger: String,
+    pub conditions: Value
+}
+
+
+/// An advancement loaded from a datapack.
+#[derive(Debug, Clone)]
+pub struct Advancement {
+    id: String,
+    parent: Option<String>,
+    display: Option<AdvancementDisplay>,
+    criteria: HashMap<String, Criterion>,
+    /// Each requirement is a list of criteria, at least one of them must be granted.
+    requirements: Vec<Vec<String>>,
+    rewards: AdvancementRewards
+}
+
+impl Advancement {
+
+    /// Decode an advancement from its datapack JSON representation. Like vanilla, all
+    /// criteria are required if requirements are not given, and requirements must reference
+    /// all criteria. An error message is returned if the advancement is invalid.
+    pub fn from_json(id: String, json: &Value) -> Result<Self, String> {
+
+        let parent = match &json["parent"] {
+            Value::Null => None,
+            parent => Some(parent.as_str().ok_or("Invalid parent.")?.to_string())
+        };
+
+        let display = match &json["display"] {
+            Value::Null => None,
+            display => Some(AdvancementDisplay {
+                title: display["title"].clone(),
+                description: display["description"].clone(),
+                icon: display["icon"]["item"].as_str().ok_or("Missing icon item.")?.to_string(),
+                frame: match display["frame"].as_str() {
+                    Some(frame) => AdvancementFrame::from_name(frame).ok_or_else(|| format!("Invalid frame '{}'.", frame))?,
+                    None => AdvancementFrame::Task
+                },
+                background: display["background"].as_str().map(str::to_string),
+                show_toast: display["show_toast"].as_bool().unwrap_or(true),
+                announce_to_chat: display["announce_to_chat"].as_bool().unwrap_or(true),
+                hidden: display["hidden"].as_bool().unwrap_or(false)
+            })
+        };
+
+        let mut criteria = HashMap::new();
+        for (name, criterion) in json["criteria"].as_object().ok_or("Missing criteria.")? {
+            let trigger = criterion["trigger"].as_str()
+                .ok_or_else(|| format!("Missing trigger of criterion '{}'.", name))?;
+            criteria.insert(name.clone(), Criterion {
+                trigger: trigger.to_string(),
+                conditions: criterion["conditions"].clone()
+            });
+        }
+
+        if criteria.is_empty() {
+            return Err("Advancement criteria cannot be empty.".to_string());
+        }
+
+        let requirements = match &json["requirements"] {
+            Value::Null => {
+                let mut names: Vec<_> = criteria.keys().cloned().collect();
+                names.sort_unstable();
+                names.into_iter().map(|name| vec![name]).collect()
+            }
+            requirements => {
+                let requirements: Vec<Vec<String>> = requirements.as_array()
+                    .ok_or("Invalid requirements.")?
+                    .iter()
+                    .map(|requirement| requirement.as_array()
+                        .and_then(|names| names.iter().map(|name| name.as_str().map(str::to_string)).collect()))
+                    .collect::<Option<_>>()
+                    .ok_or("Invalid requirements.")?;
+                let required: HashSet<_> = requirements.iter().flatten().collect();
+                if let Some(name) = required.iter().find(|&&name| !criteria.contains_key(name)) {
+                    return Err(format!("Unknown required criterion '{}'.", name));
+                } else if required.len() != criteria.len() {
+                    return Err("Requirements must reference all criteria.".to_string());
+                }
+                requirements
+            }
+        };
+
+        let rewards = &json["rewards"];
+        let get_str_vec = |field: &str| rewards[field].as_array()
+            .map(|values| values.iter().filter_map(Value::as_str).map(str::to_string).collect())
+            .unwrap_or_default();
+
+        Ok(Self {
+            id,
+            parent,
+            display,
+            criteria,
+            requirements,
+            rewards: AdvancementRewards {
+                experience: rewards["experience"].as_i64().unwrap_or(0) as i32,
+                recipes: get_str_vec("recipes"),
+                loot: get_str_vec("loot"),
+                function: rewards["function"].as_str().map(str::to_string)
+            }
+        })
+
+    }
+
+    #[inline]
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn get_parent(&self) -> Option<&str> {
+        self.parent.as_deref()
+    }
+
+    pub fn get_display(&self) -> Option<&AdvancementDisplay> {
+        self.display.as_ref()
+    }
+
+    pub fn get_criterion(&self, name: &str) -> Option<&Criterion> {
+        self.criteria.get(name)
+    }
+
+    pub fn iter_criteria(&self) -> impl Iterator<Item = (&String, &Criterion)> + '_ {
+        self.criteria.iter()
+    }
+
+    pub fn get_requirements(&self) -> &[Vec<String>] {
+        &self.requirements
+    }
+
+    pub fn get_rewards(&self) -> &AdvancementRewards {
+        &self.rewards
+    }
+
+    /// Return true if the given granted criteria fulfill all requirements.
+    pub fn is_done_with(&self, granted: &HashSet<String>) -> bool {
+        self.requirements.iter().all(|requirement| requirement.iter().any(|name| granted.contains(name)))
+    }
+
+}
+
+
+/// An entity component storing the criteria granted to a player for each advancement.
+#[derive(Debug, Clone, Default)]
+pub struct AdvancementProgress {
+    criteria: HashMap<String, HashSet<String>>,
+    done: HashSet<String>
+}
+
+impl AdvancementProgress {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_done(&self, advancement: &str) -> bool {
+        self.done.contains(advancement)
+    }
+
+    pub fn is_criterion_granted(&self, advancement: &str, criterion: &str) -> bool {
+        self.criteria.get(advancement).is_some_and(|granted| granted.contains(criterion))
+    }
+
+    pub fn iter_done(&self) -> impl Iterator<Item = &String> + '_ {
+        self.done.iter()
+    }
+
+    /// Grant a criterion of an advancement, return true if the advancement is now done.
+    pub fn grant_criterion(&mut self, advancement: &Advancement, criterion: &str) -> bool {
+        if self.done.contains(&advancement.id) || !advancement.criteria.contains_key(criterion) {
+            return false;
+        }
+        let granted = self.criteria.entry(advancement.id.clone()).or_default();
+        granted.insert(criterion.to_string());
+        if advancement.is_done_with(granted) {
+            self.done.insert(advancement.id.clone());
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Revoke all criteria of an advancement.
+    pub fn revoke(&mut self, advancement: &str) {
+        self.criteria.remove(advancement);
+        self.done.remove(advancement);
+    }
+
+}
+
+
+/// A world component storing the advancements loaded from datapacks and the triggers fired
+/// since the last run of `system_advancements`.
+#[derive(Default)]
+pub struct Advancements {
+    advancements: HashMap<String, Rc<Advancement>>,
+    /// Advancements and their criteria for each trigger name.
+    criteria_by_trigger: HashMap<String, Vec<(Rc<Advancement>, String)>>,
+    fired: Vec<FiredTrigger>
+}
+
+
+/// A trigger fired for an entity, waiting to be checked.
+struct FiredTrigger {
+    level: Rc<RefCell<Level>>,
+    entity: Entity,
+    trigger: Box<dyn Trigger>
+}
+
+impl Advancements {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load all advancements of the datapack at the given path, advancements with the same
+    /// name as already loaded advancements replace them. Return the number of advancements
+    /// loaded.
+    pub fn load_datapack<P: AsRef<Path>>(&mut self, path: P) -> io::Result<usize> {
+
+        let mut count = 0;
+
+        for namespace in fs::read_dir(path.as_ref().join("data"))? {
+            let namespace = namespace?;
+            let advancements_dir = namespace.path().join("advancements");
+            if let (Some(namespace), true) = (namespace.file_name().to_str(), advancements_dir.is_dir()) {
+                count += self.load_advancements_dir(&advancements_dir, &format!("{}:", namespace))?;
+            }
+        }
+
+        Ok(count)
+
+    }
+
+    fn load_advancements_dir(&mut self, dir: &Path, prefix: &str) -> io::Result<usize> {
+
+        let mut count = 0;
+
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            let file_name = match entry.file_name().to_str() {
+                Some(file_name) => file_name.to_string(),
+                None => continue
+            };
+            if entry.file_type()?.is_dir() {
+                count += self.load_advancements_dir(&path, &format!("{}{}/", prefix, file_name))?;
+            } else if let Some(name) = file_name.strip_suffix(".json") {
+                let id = format!("{}{}", prefix, name);
+                let json: Value = serde_json::from_str(&fs::read_to_string(&path)?)?;
+                let advancement = Advancement::from_json(id, &json)
+                    .map_err(|message| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", name, message)))?;
+                self.add_advancement(advancement);
+                count += 1;
+            }
+        }
+
+        Ok(count)
+
+    }
+
+    /// Add an advancement, replacing the advancement with the same ID.
+    pub fn add_advancement(&mut self, advancement: Advancement) {
+        let advancement = Rc::new(advancement);
+        for criteria in self.criteria_by_trigger.values_mut() {
+            criteria.retain(|(other, _)| other.id != advancement.id);
+        }
+        for (name, criterion) in &advancement.criteria {
+            self.criteria_by_trigger.entry(criterion.trigger.clone())
+                .or_default()
+                .push((Rc::clone(&advancement), name.clone()));
+        }
+        self.advancements.insert(advancement.id.clone(), advancement);
+    }
+
+    pub fn get_advancement(&self, id: &str) -> Option<&Advancement> {
+        self.advancements.get(id).map(|advancement| &**advancement)
+    }
+
+    pub fn get_advancements_count(&self) -> usize {
+        self.advancements.len()
+    }
+
+    /// Return true if some criterion uses the trigger with the given name.
+    pub fn has_trigger(&self, name: &str) -> bool {
+        self.criteria_by_trigger.contains_key(name)
+    }
+
+    /// Fire a trigger for an entity, criteria are checked and granted on the next run of
+    /// `system_advancements`.
+    pub fn fire<T: Trigger + 'static>(&mut self, level: Rc<RefCell<Level>>, entity: Entity, trigger: T) {
+        if self.has_trigger(trigger.get_name()) {
+            self.fired.push(FiredTrigger { level, entity, trigger: Box::new(trigger) });
+        }
+    }
+
+}
+
+
+/// Event pushed by `system_advancements` when a player has done an advancement, the rewards
+/// of the advancement must be given by the event consumer.
+pub struct AdvancementDoneEvent {
+    pub level: Rc<RefCell<Level>>,
+    pub entity: Entity,
+    pub advancement: Rc<Advancement>
+}
+
+
+/// Return the plain text of a JSON text component, translated components are replaced by
+/// their translation key.
+pub fn get_plain_text(text: &Value) -> String {
+    match text {
+        Value::String(text) => text.clone(),
+        Value::Array(texts) => texts.iter().map(get_plain_text).collect(),
+        Value::Object(component) => {
+            let mut plain = component.get("text")
+                .or_else(|| component.get("translate"))
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            if let Some(extra) = component.get("extra") {
+                plain.push_str(&get_plain_text(extra));
+            }
+            plain
+        }
+        _ => String::new()
+    }
+}
+
+
+/// Call this function with a mutable reference to a World to register the `Advancements`
+/// component which is required to run the system `system_advancements`.
+pub fn register_advancements(world: &mut World, advancements: Advancements) {
+    world.insert_component(advancements);
+}
+
+/// A system checking the fired triggers against the criteria of advancements and granting
+/// them to entities with an `AdvancementProgress` component. The tick trigger is fired for
+/// all these entities and the inventory changed trigger for collectors of items. An
+/// `AdvancementDoneEvent` is pushed for each newly done advancement.
+pub fn system_advancements(world: &mut World) {
+
+    let mut advancements = match world.components.get_mut::<Advancements>() {
+        Ok(advancements) => advancements,
+        Err(_) => return
+    };
+
+    if advancements.has_trigger(TickTrigger::NAME) {
+        for level in &world.levels {
+            let entities: Vec<_> = level.borrow().entities.ecs.query::<&AdvancementProgress>()
+                .iter()
+                .map(|(entity, _)| entity)
+                .collect();
+            for entity in entities {
+                advancements.fire(Rc::clone(level), entity, TickTrigger);
+            }
+        }
+    }
+
+    for event in world.event_tracker.poll_events::<ItemPickupEvent>() {
+        advancements.fire(Rc::clone(&event.level), event.collector, InventoryChangedTrigger);
+    }
+
+    for FiredTrigger { level: level_rc, entity, trigger } in std::mem::take(&mut advancements.fired) {
+
+        let level = level_rc.borrow();
+        let mut progress = match level.entities.ecs.get_mut::<AdvancementProgress>(entity) {
+            Ok(progress) => progress,
+            Err(_) => continue
+        };
+
+        for (advancement, criterion) in &advancements.criteria_by_trigger[trigger.get_name()] {
+            if progress.is_done(&advancement.id) || progress.is_criterion_granted(&advancement.id, criterion) {
+                continue;
+            }
+            if trigger.test(&level, entity, &advancement.criteria[criterion].conditions)
+                && progress.grant_criterion(advancement, criterion) {
+                world.event_tracker.push_event(AdvancementDoneEvent {
+                    level: Rc::clone(&level_rc),
+                    entity,
+                    advancement: Rc::clone(advancement)
+                });
+            }
+        }
+
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn advancement_json() {
+
+        let json = serde_json::json!({
+            "parent": "minecraft:story/root",
+            "display": {
+                "icon": { "item": "minecraft:stone_pickaxe" },
+                "title": { "translate": "advancements.story.mine_stone.title" },
+                "description": "Mine Stone with your new Pickaxe",
+                "frame": "goal"
+            },
+            "criteria": {
+                "get_stone": { "trigger": "minecraft:inventory_changed" },
+                "get_cobblestone": { "trigger": "minecraft:inventory_changed" },
+                "placed": { "trigger": "minecraft:placed_block" }
+            },
+            "requirements": [["get_stone", "get_cobblestone"], ["placed"]],
+            "rewards": { "experience": 10, "recipes": ["minecraft:furnace"] }
+        });
+
+        let advancement = Advancement::from_json("minecraft:story/mine_stone".to_string(), &json).unwrap();
+        assert_eq!(advancement.get_parent(), Some("minecraft:story/root"));
+        let display = advancement.get_display().unwrap();
+        assert_eq!(display.frame, AdvancementFrame::Goal);
+        assert!(display.announce_to_chat && !display.hidden);
+        assert_eq!(get_plain_text(&display.title), "advancements.story.mine_stone.title");
+        assert_eq!(advancement.get_rewards().experience, 10);
+        assert_eq!(advancement.get_rewards().recipes, vec!["minecraft:furnace".to_string()]);
+
+        let mut progress = AdvancementProgress::new();
+        assert!(!progress.grant_criterion(&advancement, "get_cobblestone"));
+        assert!(!progress.grant_criterion(&advancement, "unknown"));
+        assert!(progress.is_criterion_granted("minecraft:story/mine_stone", "get_cobblestone"));
+        assert!(progress.grant_criterion(&advancement, "placed"));
+        assert!(progress.is_done("minecraft:story/mine_stone"));
+        assert!(!progress.grant_criterion(&advancement, "get_stone"));
+
+        // All criteria are required without requirements.
+        let json = serde_json::json!({ "criteria": { "a": { "trigger": "minecraft:tick" }, "b": { "trigger": "minecraft:tick" } } });
+        let advancement = Advancement::from_json("test:all".to_string(), &json).unwrap();
+        assert_eq!(advancement.get_requirements(), &[vec!["a".to_string()], vec!["b".to_string()]]);
+
+        let json = serde_json::json!({ "criteria": { "a": { "trigger": "minecraft:tick" } }, "requirements": [["b"]] });
+        assert!(Advancement::from_json("test:invalid".to_string(), &json).is_err());
+        assert!(Advancement::from_json("test:empty".to_string(), &serde_json::json!({ "criteria": {} })).is_err());
+
+    }
+
+}
@@ -0,0 +1,211 @@
+//! Triggers fired by the game to grant criteria of advancements, each trigger checks the
+//! conditions of the criteria using its name. Only some conditions are supported, criteria
+//! with unsupported conditions are never granted by triggers.
+
+use mc_core::world::level::Level;
+use mc_core::block::Block;
+use mc_core::item::{ItemStack, Inventory};
+use mc_core::pos::BlockPos;
+use hecs::Entity;
+
+use serde_json::Value;
+
+
+/// A trigger fired for an entity, see `Advancements::fire`.
+pub trait Trigger {
+
+    /// The name of the trigger, used by criteria, like `minecraft:inventory_changed`.
+    fn get_name(&self) -> &'static str;
+
+    /// Return true if the trigger fired for the given entity matches the conditions of a
+    /// criterion, conditions are null if not given.
+    fn test(&self, level: &Level, entity: Entity, conditions: &Value) -> bool;
+
+}
+
+
+/// Fired every tick for each player.
+pub struct TickTrigger;
+
+impl TickTrigger {
+    pub const NAME: &'static str = "minecraft:tick";
+}
+
+impl Trigger for TickTrigger {
+
+    fn get_name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn test(&self, _level: &Level, _entity: Entity, conditions: &Value) -> bool {
+        has_only_fields(conditions, &[])
+    }
+
+}
+
+
+/// Fired when the inventory of a player has changed, the `items` condition requires each
+/// item predicate to match a stack of the `Inventory` component of the player.
+pub struct InventoryChangedTrigger;
+
+impl InventoryChangedTrigger {
+    pub const NAME: &'static str = "minecraft:inventory_changed";
+}
+
+impl Trigger for InventoryChangedTrigger {
+
+    fn get_name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn test(&self, level: &Level, entity: Entity, conditions: &Value) -> bool {
+
+        if !has_only_fields(conditions, &["items"]) {
+            return false;
+        }
+
+        let inventory = match level.entities.ecs.get::<Inventory>(entity) {
+            Ok(inventory) => inventory,
+            Err(_) => return false
+        };
+
+        match &conditions["items"] {
+            Value::Null => true,
+            Value::Array(predicates) => predicates.iter().all(|predicate| {
+                inventory.iter_stacks().any(|(_, stack)| matches_item(predicate, stack))
+            }),
+            _ => false
+        }
+
+    }
+
+}
+
+
+/// Fired when a player has placed a block, the `block` condition is the name of the block.
+pub struct PlacedBlockTrigger {
+    pub block: &'static Block,
+    pub pos: BlockPos
+}
+
+impl PlacedBlockTrigger {
+    pub const NAME: &'static str = "minecraft:placed_block";
+}
+
+impl Trigger for PlacedBlockTrigger {
+
+    fn get_name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn test(&self, _level: &Level, _entity: Entity, conditions: &Value) -> bool {
+        has_only_fields(conditions, &["block"]) && match &conditions["block"] {
+            Value::Null => true,
+            Value::String(name) => self.block.get_name() == with_namespace(name),
+            _ => false
+        }
+    }
+
+}
+
+
+/// Fired when a player has eaten or drunk an item, the `item` condition is an item
+/// predicate.
+pub struct ConsumeItemTrigger {
+    pub stack: ItemStack
+}
+
+impl ConsumeItemTrigger {
+    pub const NAME: &'static str = "minecraft:consume_item";
+}
+
+impl Trigger for ConsumeItemTrigger {
+
+    fn get_name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn test(&self, _level: &Level, _entity: Entity, conditions: &Value) -> bool {
+        has_only_fields(conditions, &["item"]) && match &conditions["item"] {
+            Value::Null => true,
+            predicate => matches_item(predicate, &self.stack)
+        }
+    }
+
+}
+
+
+/// Return true if the conditions are null or an object with only the given fields.
+fn has_only_fields(conditions: &Value, fields: &[&str]) -> bool {
+    match conditions {
+        Value::Null => true,
+        Value::Object(object) => object.keys().all(|key| fields.contains(&key.as_str())),
+        _ => false
+    }
+}
+
+/// Add the default `minecraft` namespace to a name without namespace.
+fn with_namespace(name: &str) -> String {
+    if name.contains(':') {
+        name.to_string()
+    } else {
+        format!("minecraft:{}", name)
+    }
+}
+
+/// Check an item predicate, the `items` list (or `item` before 1.17) and the `count` (exact
+/// or with `min` and `max` bounds) are supported.
+pub fn matches_item(predicate: &Value, stack: &ItemStack) -> bool {
+
+    if !has_only_fields(predicate, &["items", "item", "count"]) {
+        return false;
+    }
+
+    let name = stack.get_item().get_name();
+    let item_matches = match (&predicate["items"], &predicate["item"]) {
+        (Value::Array(items), _) => items.iter().any(|item| item.as_str().is_some_and(|item| with_namespace(item) == name)),
+        (Value::Null, Value::String(item)) => with_namespace(item) == name,
+        (Value::Null, Value::Null) => true,
+        _ => false
+    };
+
+    let count = stack.get_count() as i64;
+    let count_matches = match &predicate["count"] {
+        Value::Null => true,
+        Value::Number(exact) => exact.as_i64() == Some(count),
+        Value::Object(range) => {
+            range.get("min").and_then(Value::as_i64).is_none_or(|min| count >= min)
+                && range.get("max").and_then(Value::as_i64).is_none_or(|max| count <= max)
+        }
+        _ => false
+    };
+
+    item_matches && count_matches
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use serde_json::json;
+
+    use super::*;
+
+    mc_core::items!(TEST_ITEMS "minecraft" [
+        COBBLESTONE "cobblestone",
+        DIRT "dirt"
+    ]);
+
+    #[test]
+    fn item_predicates() {
+        let stack = ItemStack::with_item_count(&COBBLESTONE, 8);
+        assert!(matches_item(&json!({}), &stack));
+        assert!(matches_item(&json!({ "items": ["dirt", "minecraft:cobblestone"] }), &stack));
+        assert!(matches_item(&json!({ "item": "cobblestone", "count": { "min": 4 } }), &stack));
+        assert!(!matches_item(&json!({ "items": ["minecraft:cobblestone"], "count": 9 }), &stack));
+        assert!(!matches_item(&json!({ "items": ["minecraft:dirt"] }), &stack));
+        assert!(!matches_item(&json!({ "tag": "minecraft:stone_crafting_materials" }), &stack));
+    }
+
+}
@@ -0,0 +1,300 @@
+//! Vanilla biome climates, used by weather to choose between rain and snow and to color
+//! grass and foliage.
+//!
+//! Temperatures are the 1.17 base temperatures of biomes, 1.18 biomes use their 1.18
+//! temperature. Temperatures at a position are adjusted by the altitude and by the frozen
+//! temperature modifier of frozen oceans, using the same noises as vanilla.
+
+use std::collections::HashMap;
+
+use mc_core::biome::{Biome, BiomeKey};
+use mc_core::noise::{SimplexNoise, SimplexNoiseOctaves, BIOME_INFO_NOISE};
+use mc_core::rand::JavaRandom;
+use mc_core::pos::BlockPos;
+use once_cell::sync::Lazy;
+
+use super::*;
+
+
+/// Above this height, the temperature of biomes decreases.
+const TEMPERATURE_DECREASE_HEIGHT: i32 = 64;
+/// Below this temperature, snow falls instead of rain and water freezes.
+const SNOW_TEMPERATURE: f32 = 0.15;
+/// Temperature of the warm patches of frozen oceans.
+const FROZEN_PATCH_TEMPERATURE: f32 = 0.2;
+
+/// Noise slightly shifting the height where temperature starts to decrease.
+static TEMPERATURE_NOISE: Lazy<SimplexNoise> = Lazy::new(|| {
+    SimplexNoise::new(&mut JavaRandom::new(1234))
+});
+
+/// Noise used with the biome info noise to place warm patches in frozen oceans.
+static FROZEN_TEMPERATURE_NOISE: Lazy<SimplexNoiseOctaves> = Lazy::new(|| {
+    SimplexNoiseOctaves::new(&mut JavaRandom::new(3456), &[-2, -1, 0])
+});
+
+
+/// The kind of precipitation falling in a biome when raining.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Precipitation {
+    None,
+    Rain,
+    Snow
+}
+
+
+/// A modifier of the base temperature of a biome depending on the horizontal position.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TemperatureModifier {
+    None,
+    /// Used by frozen oceans, some patches of the ocean are warm enough to rain.
+    Frozen
+}
+
+impl TemperatureModifier {
+
+    /// Apply this modifier to the given temperature at a horizontal position.
+    pub fn modify_temperature(self, x: i32, z: i32, temperature: f32) -> f32 {
+        match self {
+            TemperatureModifier::None => temperature,
+            TemperatureModifier::Frozen => {
+                let (x, z) = (x as f64, z as f64);
+                let frozen = FROZEN_TEMPERATURE_NOISE.get_value_2d(x * 0.05, z * 0.05) * 7.0;
+                let info = BIOME_INFO_NOISE.get_value_2d(x * 0.2, z * 0.2);
+                if frozen + info < 0.3 && BIOME_INFO_NOISE.get_value_2d(x * 0.09, z * 0.09) < 0.8 {
+                    FROZEN_PATCH_TEMPERATURE
+                } else {
+                    temperature
+                }
+            }
+        }
+    }
+
+}
+
+
+/// The climate of a biome.
+#[derive(Debug, Copy, Clone)]
+pub struct BiomeClimate {
+    /// The base temperature of the biome.
+    pub temperature: f32,
+    pub temperature_modifier: TemperatureModifier,
+    /// The humidity of the biome, between 0 and 1, used with the temperature to color grass
+    /// and foliage.
+    pub downfall: f32,
+    /// The precipitation of the biome, `Rain` and `Snow` both fall as rain or snow depending
+    /// on the temperature at the position.
+    pub precipitation: Precipitation
+}
+
+impl BiomeClimate {
+
+    /// Return the temperature at the given position, the temperature modifier is applied and
+    /// the temperature then decreases above the height 64, slightly shifted by a noise.
+    pub fn get_temperature_at(&self, pos: &BlockPos) -> f32 {
+        let temperature = self.temperature_modifier.modify_temperature(pos.x, pos.z, self.temperature);
+        if pos.y > TEMPERATURE_DECREASE_HEIGHT {
+            let noise = (TEMPERATURE_NOISE.get_value_2d(pos.x as f64 / 8.0, pos.z as f64 / 8.0) * 4.0) as f32;
+            temperature - (noise + (pos.y - TEMPERATURE_DECREASE_HEIGHT) as f32) * 0.05 / 30.0
+        } else {
+            temperature
+        }
+    }
+
+    /// Return true if it's cold enough to snow at the given position.
+    pub fn is_cold_enough_to_snow(&self, pos: &BlockPos) -> bool {
+        self.get_temperature_at(pos) < SNOW_TEMPERATURE
+    }
+
+    /// Return the precipitation falling at the given position, biomes with precipitation
+    /// have snow where it's cold enough and rain elsewhere.
+    pub fn get_precipitation_at(&self, pos: &BlockPos) -> Precipitation {
+        match self.precipitation {
+            Precipitation::None => Precipitation::None,
+            _ if self.is_cold_enough_to_snow(pos) => Precipitation::Snow,
+            _ => Precipitation::Rain
+        }
+    }
+
+}
+
+
+/// A trait implemented on `Biome` to query the vanilla climate of biomes.
+pub trait VanillaBiomeClimate {
+
+    fn get_climate(&'static self) -> BiomeClimate;
+
+    /// Shortcut for `BiomeClimate::get_temperature_at` on the climate of this biome.
+    fn get_temperature_at(&'static self, pos: &BlockPos) -> f32 {
+        self.get_climate().get_temperature_at(pos)
+    }
+
+    /// Shortcut for `BiomeClimate::get_precipitation_at` on the climate of this biome.
+    fn get_precipitation_at(&'static self, pos: &BlockPos) -> Precipitation {
+        self.get_climate().get_precipitation_at(pos)
+    }
+
+}
+
+impl VanillaBiomeClimate for Biome {
+    fn get_climate(&'static self) -> BiomeClimate {
+        get_biome_climate(self)
+    }
+}
+
+
+/// Return the climate of a vanilla biome, the climate of plains is returned for unknown
+/// biomes.
+pub fn get_biome_climate(biome: &'static Biome) -> BiomeClimate {
+    VANILLA_BIOME_CLIMATES.get(&biome.get_key()).copied().unwrap_or(BiomeClimate {
+        temperature: 0.8,
+        temperature_modifier: TemperatureModifier::None,
+        downfall: 0.4,
+        precipitation: Precipitation::Rain
+    })
+}
+
+
+static VANILLA_BIOME_CLIMATES: Lazy<HashMap<BiomeKey, BiomeClimate>> = Lazy::new(|| {
+
+    use Precipitation::*;
+
+    let climates: [(&'static Biome, f32, f32, Precipitation); 87] = [
+        (&THE_VOID, 0.5, 0.5, None),
+        (&BADLANDS, 2.0, 0.0, None),
+        (&BADLANDS_PLATEAU, 2.0, 0.0, None),
+        (&BAMBOO_JUNGLE, 0.95, 0.9, Rain),
+        (&BAMBOO_JUNGLE_HILLS, 0.95, 0.9, Rain),
+        (&BASALT_DELTAS, 2.0, 0.0, None),
+        (&BEACH, 0.8, 0.4, Rain),
+        (&BIRCH_FOREST, 0.6, 0.6, Rain),
+        (&BIRCH_FOREST_HILLS, 0.6, 0.6, Rain),
+        (&COLD_OCEAN, 0.5, 0.5, Rain),
+        (&CRIMSON_FOREST, 2.0, 0.0, None),
+        (&DARK_FOREST, 0.7, 0.8, Rain),
+        (&DARK_FOREST_HILLS, 0.7, 0.8, Rain),
+        (&DEEP_COLD_OCEAN, 0.5, 0.5, Rain),
+        (&DEEP_FROZEN_OCEAN, 0.5, 0.5, Rain),
+        (&DEEP_LUKEWARM_OCEAN, 0.5, 0.5, Rain),
+        (&DEEP_OCEAN, 0.5, 0.5, Rain),
+        (&DEEP_WARM_OCEAN, 0.5, 0.5, Rain),
+        (&DESERT, 2.0, 0.0, None),
+        (&DESERT_HILLS, 2.0, 0.0, None),
+        (&DESERT_LAKES, 2.0, 0.0, None),
+        (&DRIPSTONE_CAVES, 0.8, 0.4, Rain),
+        (&END_BARRENS, 0.5, 0.5, None),
+        (&END_HIGHLANDS, 0.5, 0.5, None),
+        (&END_MIDLANDS, 0.5, 0.5, None),
+        (&ERODED_BADLANDS, 2.0, 0.0, None),
+        (&FLOWER_FOREST, 0.7, 0.8, Rain),
+        (&FOREST, 0.7, 0.8, Rain),
+        (&FROZEN_OCEAN, 0.0, 0.5, Snow),
+        (&FROZEN_RIVER, 0.0, 0.5, Snow),
+        (&FROZEN_PEAKS, -0.7, 0.9, Snow),
+        (&GIANT_SPRUCE_TAIGA, 0.25, 0.8, Rain),
+        (&GIANT_SPRUCE_TAIGA_HILLS, 0.25, 0.8, Rain),
+        (&GIANT_TREE_TAIGA, 0.3, 0.8, Rain),
+        (&GIANT_TREE_TAIGA_HILLS, 0.3, 0.8, Rain),
+        (&GRAVELLY_MOUNTAINS, 0.2, 0.3, Rain),
+        (&GROVE, -0.2, 0.8, Snow),
+        (&ICE_SPIKES, 0.0, 0.5, Snow),
+        (&JUNGLE, 0.95, 0.9, Rain),
+        (&JUNGLE_EDGE, 0.95, 0.8, Rain),
+        (&JUNGLE_HILLS, 0.95, 0.9, Rain),
+        (&JAGGED_PEAKS, -0.7, 0.9, Snow),
+        (&LUKEWARM_OCEAN, 0.5, 0.5, Rain),
+        (&LUSH_CAVES, 0.5, 0.5, Rain),
+        (&MODIFIED_BADLANDS_PLATEAU, 2.0, 0.0, None),
+        (&MODIFIED_JUNGLE, 0.95, 0.9, Rain),
+        (&MODIFIED_JUNGLE_EDGE, 0.95, 0.8, Rain),
+        (&MODIFIED_WOODED_BADLANDS_PLATEAU, 2.0, 0.0, None),
+        (&MODIFIED_GRAVELLY_MOUNTAINS, 0.2, 0.3, Rain),
+        (&MOUNTAIN_EDGE, 0.2, 0.3, Rain),
+        (&MOUNTAINS, 0.2, 0.3, Rain),
+        (&MUSHROOM_FIELD_SHORE, 0.9, 1.0, Rain),
+        (&MUSHROOM_FIELDS, 0.9, 1.0, Rain),
+        (&MEADOW, 0.5, 0.8, Rain),
+        (&NETHER_WASTES, 2.0, 0.0, None),
+        (&OCEAN, 0.5, 0.5, Rain),
+        (&PLAINS, 0.8, 0.4, Rain),
+        (&RIVER, 0.5, 0.5, Rain),
+        (&SAVANNA, 1.2, 0.0, None),
+        (&SAVANNA_PLATEAU, 1.0, 0.0, None),
+        (&SHATTERED_SAVANNA, 1.1, 0.0, None),
+        (&SHATTERED_SAVANNA_PLATEAU, 1.0, 0.0, None),
+        (&SMALL_END_ISLANDS, 0.5, 0.5, None),
+        (&SNOWY_SHORE, 0.05, 0.3, Snow),
+        (&SNOWY_MOUNTAINS, 0.0, 0.5, Snow),
+        (&SNOWY_TAIGA, -0.5, 0.4, Snow),
+        (&SNOWY_TAIGA_HILLS, -0.5, 0.4, Snow),
+        (&SNOWY_TAIGA_MOUNTAINS, -0.5, 0.4, Snow),
+        (&SNOWY_PLAINS, 0.0, 0.5, Snow),
+        (&SNOWY_SLOPES, -0.3, 0.9, Snow),
+        (&SOUL_SAND_VALLEY, 2.0, 0.0, None),
+        (&STONE_SHORE, 0.2, 0.3, Rain),
+        (&STONY_PEAKS, 1.0, 0.3, Rain),
+        (&SUNFLOWER_PLAINS, 0.8, 0.4, Rain),
+        (&SWAMP, 0.8, 0.9, Rain),
+        (&SWAMP_HILLS, 0.8, 0.9, Rain),
+        (&TAIGA, 0.25, 0.8, Rain),
+        (&TAIGA_HILLS, 0.25, 0.8, Rain),
+        (&TAIGA_MOUNTAINS, 0.25, 0.8, Rain),
+        (&TALL_BIRCH_FOREST, 0.6, 0.6, Rain),
+        (&TALL_BIRCH_HILLS, 0.6, 0.6, Rain),
+        (&THE_END, 0.5, 0.5, None),
+        (&WARM_OCEAN, 0.5, 0.5, Rain),
+        (&WARPED_FOREST, 2.0, 0.0, None),
+        (&WOODED_BADLANDS_PLATEAU, 2.0, 0.0, None),
+        (&WOODED_HILLS, 0.7, 0.8, Rain),
+        (&WOODED_MOUNTAINS, 0.2, 0.3, Rain),
+    ];
+
+    climates.iter()
+        .map(|&(biome, temperature, downfall, precipitation)| {
+            let temperature_modifier = if biome == &FROZEN_OCEAN || biome == &DEEP_FROZEN_OCEAN {
+                TemperatureModifier::Frozen
+            } else {
+                TemperatureModifier::None
+            };
+            (biome.get_key(), BiomeClimate { temperature, temperature_modifier, downfall, precipitation })
+        })
+        .collect()
+
+});
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn biome_climate() {
+
+        let pos = BlockPos::new(0, 64, 0);
+        assert_eq!(PLAINS.get_precipitation_at(&pos), Precipitation::Rain);
+        assert_eq!(SNOWY_PLAINS.get_precipitation_at(&pos), Precipitation::Snow);
+        assert_eq!(DESERT.get_precipitation_at(&pos), Precipitation::None);
+        assert_eq!(PLAINS.get_temperature_at(&pos), 0.8);
+
+        // Mountains are only snowy at high altitudes, the altitude noise slightly shifts the
+        // snow limit around the height 94.
+        for x in 0..64 {
+            assert_eq!(MOUNTAINS.get_precipitation_at(&BlockPos::new(x * 7, 85, 0)), Precipitation::Rain);
+            assert_eq!(MOUNTAINS.get_precipitation_at(&BlockPos::new(x * 7, 100, 0)), Precipitation::Snow);
+        }
+        let limits: Vec<i32> = (0..64)
+            .map(|x| (85..100).find(|&y| MOUNTAINS.get_precipitation_at(&BlockPos::new(x * 7, y, 0)) == Precipitation::Snow).unwrap())
+            .collect();
+        assert!(limits.iter().any(|&y| y != limits[0]));
+
+        // Frozen oceans have warm patches where it rains.
+        let precipitations: Vec<Precipitation> = (0..256)
+            .map(|x| FROZEN_OCEAN.get_precipitation_at(&BlockPos::new(x * 4, 63, 0)))
+            .collect();
+        assert!(precipitations.contains(&Precipitation::Snow));
+        assert!(precipitations.contains(&Precipitation::Rain));
+
+    }
+
+}
@@ -0,0 +1,323 @@
+//! Vanilla grass, foliage and water colors of biomes, colors are `0xRRGGBB` integers.
+//!
+//! Like vanilla, colors are looked up in a colormap from the temperature and downfall of
+//! biomes. The colormaps are textures of the client, by default they are approximated by
+//! interpolating the colors of the three corners of their triangle, the red component can
+//! be up to 16 units lower than the textures. For exact colors, a `ColorMap` can be created
+//! from the pixels of the textures.
+
+use std::collections::HashMap;
+
+use mc_core::biome::{Biome, BiomeKey};
+use mc_core::noise::BIOME_INFO_NOISE;
+use mc_core::pos::BlockPos;
+use once_cell::sync::Lazy;
+
+use super::climate::{BiomeClimate, get_biome_climate};
+use super::*;
+
+
+/// Width and height of colormaps.
+pub const COLOR_MAP_SIZE: usize = 256;
+
+/// Color returned by colormaps for coordinates outside of their texture.
+const MISSING_COLOR: u32 = 0xFF00FF;
+
+/// Water color of biomes without specific water color.
+pub const DEFAULT_WATER_COLOR: u32 = 0x3F76E4;
+
+/// Default colormap for grass, rebuilt from its corners.
+pub static GRASS_COLOR_MAP: Lazy<ColorMap> = Lazy::new(|| {
+    ColorMap::from_corners(0x47CD33, 0xBFB755, 0x80B497)
+});
+
+/// Default colormap for foliage, rebuilt from its corners.
+pub static FOLIAGE_COLOR_MAP: Lazy<ColorMap> = Lazy::new(|| {
+    ColorMap::from_corners(0x1ABF00, 0xAEA42A, 0x60A17B)
+});
+
+
+/// A colormap texture indexed by temperature and downfall, only the lower left triangle of
+/// the texture is used because the downfall is multiplied by the temperature.
+#[derive(Debug, Clone)]
+pub struct ColorMap {
+    pixels: Vec<u32>
+}
+
+impl ColorMap {
+
+    /// Create a colormap from the pixels of its texture, rows after rows.
+    ///
+    /// # Panics
+    ///
+    /// If the number of pixels isn't `COLOR_MAP_SIZE` squared.
+    pub fn new(pixels: Vec<u32>) -> Self {
+        assert_eq!(pixels.len(), COLOR_MAP_SIZE * COLOR_MAP_SIZE, "Invalid colormap size.");
+        Self { pixels }
+    }
+
+    /// Create a colormap by interpolating the colors of its triangle corners: the warm and
+    /// wet corner at the top left, the warm and dry corner at the bottom left and the cold
+    /// corner at the bottom right.
+    pub fn from_corners(wet: u32, dry: u32, cold: u32) -> Self {
+        let max = (COLOR_MAP_SIZE - 1) as f32;
+        let pixels = (0..COLOR_MAP_SIZE * COLOR_MAP_SIZE)
+            .map(|i| {
+                let (x, y) = ((i % COLOR_MAP_SIZE) as f32, (i / COLOR_MAP_SIZE) as f32);
+                let cold_weight = x.min(y) / max;
+                let dry_weight = (y - x).max(0.0) / max;
+                let wet_weight = 1.0 - cold_weight - dry_weight;
+                [16, 8, 0].iter()
+                    .map(|&shift| {
+                        let component = |color: u32| ((color >> shift) & 0xFF) as f32;
+                        let value = component(wet) * wet_weight + component(dry) * dry_weight + component(cold) * cold_weight;
+                        (value.round() as u32).min(0xFF) << shift
+                    })
+                    .sum()
+            })
+            .collect();
+        Self { pixels }
+    }
+
+    /// Get the color for the given temperature and downfall, both are clamped between 0
+    /// and 1 by the caller in vanilla.
+    pub fn get_color(&self, temperature: f32, downfall: f32) -> u32 {
+        let downfall = downfall * temperature;
+        let x = ((1.0 - temperature) * 255.0) as i32;
+        let y = ((1.0 - downfall) * 255.0) as i32;
+        let index = y << 8 | x;
+        if x < 0 || y < 0 || index as usize >= self.pixels.len() {
+            MISSING_COLOR
+        } else {
+            self.pixels[index as usize]
+        }
+    }
+
+}
+
+
+/// A modifier of the grass color of biomes.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum GrassColorModifier {
+    None,
+    /// Darkens the color, used by dark forests.
+    DarkForest,
+    /// Two fixed colors chosen by a noise, used by swamps.
+    Swamp
+}
+
+impl GrassColorModifier {
+
+    /// Apply this modifier to the given grass color at a horizontal position.
+    pub fn modify_color(self, x: i32, z: i32, color: u32) -> u32 {
+        match self {
+            GrassColorModifier::None => color,
+            GrassColorModifier::DarkForest => ((color & 0xFEFEFE) + 0x28340A) >> 1,
+            GrassColorModifier::Swamp => {
+                if BIOME_INFO_NOISE.get_value_2d(x as f64 * 0.0225, z as f64 * 0.0225) < -0.1 {
+                    0x4C763C
+                } else {
+                    0x6A7039
+                }
+            }
+        }
+    }
+
+}
+
+
+/// The color effects of a biome, named like the `effects` of data pack biomes.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct BiomeColors {
+    /// A fixed grass color, used instead of the colormap.
+    pub grass_color: Option<u32>,
+    /// A fixed foliage color, used instead of the colormap.
+    pub foliage_color: Option<u32>,
+    pub grass_color_modifier: GrassColorModifier,
+    pub water_color: u32
+}
+
+impl Default for BiomeColors {
+    fn default() -> Self {
+        Self {
+            grass_color: None,
+            foliage_color: None,
+            grass_color_modifier: GrassColorModifier::None,
+            water_color: DEFAULT_WATER_COLOR
+        }
+    }
+}
+
+impl BiomeColors {
+
+    /// Return the grass color at the given position using the given colormap, the color
+    /// only depends on the position through the grass color modifier.
+    pub fn get_grass_color_with(&self, map: &ColorMap, climate: &BiomeClimate, x: i32, z: i32) -> u32 {
+        let color = self.grass_color.unwrap_or_else(|| get_climate_color(map, climate));
+        self.grass_color_modifier.modify_color(x, z, color)
+    }
+
+    /// Return the foliage color using the given colormap.
+    pub fn get_foliage_color_with(&self, map: &ColorMap, climate: &BiomeClimate) -> u32 {
+        self.foliage_color.unwrap_or_else(|| get_climate_color(map, climate))
+    }
+
+    /// Return the grass color at the given position using the default grass colormap.
+    pub fn get_grass_color_at(&self, climate: &BiomeClimate, x: i32, z: i32) -> u32 {
+        self.get_grass_color_with(&GRASS_COLOR_MAP, climate, x, z)
+    }
+
+    /// Return the foliage color using the default foliage colormap.
+    pub fn get_foliage_color(&self, climate: &BiomeClimate) -> u32 {
+        self.get_foliage_color_with(&FOLIAGE_COLOR_MAP, climate)
+    }
+
+}
+
+
+/// A trait implemented on `Biome` to query the vanilla grass and foliage colors of biomes.
+pub trait VanillaBiomeColors {
+
+    fn get_colors(&'static self) -> BiomeColors;
+
+    /// Return the grass color of this biome at the given position.
+    fn get_grass_color_at(&'static self, pos: &BlockPos) -> u32;
+
+    /// Return the foliage color of this biome.
+    fn get_foliage_color(&'static self) -> u32;
+
+    fn get_water_color(&'static self) -> u32;
+
+}
+
+impl VanillaBiomeColors for Biome {
+
+    fn get_colors(&'static self) -> BiomeColors {
+        get_biome_colors(self)
+    }
+
+    fn get_grass_color_at(&'static self, pos: &BlockPos) -> u32 {
+        self.get_colors().get_grass_color_at(&get_biome_climate(self), pos.x, pos.z)
+    }
+
+    fn get_foliage_color(&'static self) -> u32 {
+        self.get_colors().get_foliage_color(&get_biome_climate(self))
+    }
+
+    fn get_water_color(&'static self) -> u32 {
+        self.get_colors().water_color
+    }
+
+}
+
+
+/// Colormaps use the base temperature of biomes, without the altitude and the modifiers.
+fn get_climate_color(map: &ColorMap, climate: &BiomeClimate) -> u32 {
+    map.get_color(climate.temperature.clamp(0.0, 1.0), climate.downfall.clamp(0.0, 1.0))
+}
+
+
+/// Return the color effects of a vanilla biome, biomes without specific effects use the
+/// colormaps without modifier.
+pub fn get_biome_colors(biome: &'static Biome) -> BiomeColors {
+    VANILLA_BIOME_COLORS.get(&biome.get_key()).copied().unwrap_or_default()
+}
+
+
+static VANILLA_BIOME_COLORS: Lazy<HashMap<BiomeKey, BiomeColors>> = Lazy::new(|| {
+
+    let badlands = BiomeColors {
+        grass_color: Some(0x90814D),
+        foliage_color: Some(0x9E814D),
+        ..BiomeColors::default()
+    };
+
+    let dark_forest = BiomeColors {
+        grass_color_modifier: GrassColorModifier::DarkForest,
+        ..BiomeColors::default()
+    };
+
+    let swamp = BiomeColors {
+        grass_color: None,
+        foliage_color: Some(0x6A7039),
+        grass_color_modifier: GrassColorModifier::Swamp,
+        water_color: 0x617B64
+    };
+
+    let water = |water_color| BiomeColors { water_color, ..BiomeColors::default() };
+
+    let colors: [(&'static Biome, BiomeColors); 19] = [
+        (&BADLANDS, badlands),
+        (&BADLANDS_PLATEAU, badlands),
+        (&ERODED_BADLANDS, badlands),
+        (&MODIFIED_BADLANDS_PLATEAU, badlands),
+        (&MODIFIED_WOODED_BADLANDS_PLATEAU, badlands),
+        (&WOODED_BADLANDS_PLATEAU, badlands),
+        (&DARK_FOREST, dark_forest),
+        (&DARK_FOREST_HILLS, dark_forest),
+        (&SWAMP, swamp),
+        (&SWAMP_HILLS, swamp),
+        (&WARM_OCEAN, water(0x43D5EE)),
+        (&DEEP_WARM_OCEAN, water(0x43D5EE)),
+        (&LUKEWARM_OCEAN, water(0x45ADF2)),
+        (&DEEP_LUKEWARM_OCEAN, water(0x45ADF2)),
+        (&COLD_OCEAN, water(0x3D57D6)),
+        (&DEEP_COLD_OCEAN, water(0x3D57D6)),
+        (&FROZEN_OCEAN, water(0x3938C9)),
+        (&DEEP_FROZEN_OCEAN, water(0x3938C9)),
+        (&FROZEN_RIVER, water(0x3938C9)),
+    ];
+
+    colors.iter()
+        .map(|&(biome, colors)| (biome.get_key(), colors))
+        .collect()
+
+});
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    /// Assert that two colors are close enough for the approximated colormaps.
+    fn assert_color_near(color: u32, expected: u32) {
+        for shift in [16, 8, 0] {
+            let (a, b) = ((color >> shift) & 0xFF, (expected >> shift) & 0xFF);
+            assert!(a.abs_diff(b) <= 16, "{:06X} is not near {:06X}", color, expected);
+        }
+    }
+
+    #[test]
+    fn biome_colors() {
+
+        let pos = BlockPos::new(0, 64, 0);
+
+        // Colors of the vanilla colormap textures.
+        assert_color_near(PLAINS.get_grass_color_at(&pos), 0x91BD59);
+        assert_color_near(PLAINS.get_foliage_color(), 0x77AB2F);
+        assert_color_near(FOREST.get_grass_color_at(&pos), 0x79C05A);
+        assert_color_near(DESERT.get_grass_color_at(&pos), 0xBFB755);
+        assert_color_near(JUNGLE.get_foliage_color(), 0x30BB0B);
+        assert_color_near(SNOWY_PLAINS.get_grass_color_at(&pos), 0x80B497);
+
+        assert_eq!(BADLANDS.get_grass_color_at(&pos), 0x90814D);
+        assert_eq!(WOODED_BADLANDS_PLATEAU.get_foliage_color(), 0x9E814D);
+        assert_eq!(SWAMP.get_foliage_color(), 0x6A7039);
+        assert_eq!(SWAMP.get_water_color(), 0x617B64);
+        assert_eq!(PLAINS.get_water_color(), DEFAULT_WATER_COLOR);
+        assert_eq!(GrassColorModifier::DarkForest.modify_color(0, 0, 0x79C05A), 0x507A32);
+
+        let swamp_colors: Vec<u32> = (0..64)
+            .map(|x| SWAMP.get_grass_color_at(&BlockPos::new(x * 16, 64, 0)))
+            .collect();
+        assert!(swamp_colors.contains(&0x4C763C));
+        assert!(swamp_colors.contains(&0x6A7039));
+
+        // A colormap loaded from a texture.
+        let map = ColorMap::new(vec![0x123456; COLOR_MAP_SIZE * COLOR_MAP_SIZE]);
+        assert_eq!(PLAINS.get_colors().get_grass_color_with(&map, &get_biome_climate(&PLAINS), 0, 0), 0x123456);
+
+    }
+
+}
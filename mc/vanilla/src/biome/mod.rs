@@ -1,5 +1,11 @@
 use mc_core::biomes;
 
+pub mod version;
+pub use version::{get_biome_for_version, get_biome_id_for_version, get_biome_from_name_for_version, get_biome_name_for_version};
+
+pub mod climate;
+pub mod color;
+
 
 biomes!(pub VANILLA_BIOMES "minecraft" [
     THE_VOID "the_void" 127, // Moved here to be the first default biome
@@ -0,0 +1,317 @@
+//! Historical biome ids and names. Biome ids and registry names changed across versions
+//! (e.g. `hell` became `nether` in 1.13, then `nether_wastes` in 1.16), this module provides
+//! per-version tables used to map these ids and names to the biomes defined in this crate.
+//!
+//! Biome numeric ids are fixed until 1.18, since then they depend on the registry sent by
+//! the server. For 1.18 and later, the tables keep the legacy ids defined in this crate.
+//! Registry names only exist since 1.9, older tables only provide numeric ids.
+
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+
+use mc_core::biome::{Biome, BiomeKey};
+use mc_core::util::{Version, VersionType};
+
+use super::*;
+
+
+const V1_2: Version = Version(VersionType::Release, 1, 2, 0);
+const V1_7: Version = Version(VersionType::Release, 1, 7, 0);
+const V1_9: Version = Version(VersionType::Release, 1, 9, 0);
+const V1_13: Version = Version(VersionType::Release, 1, 13, 0);
+const V1_14: Version = Version(VersionType::Release, 1, 14, 0);
+const V1_16: Version = Version(VersionType::Release, 1, 16, 0);
+const V1_17: Version = Version(VersionType::Release, 1, 17, 0);
+const V1_18: Version = Version(VersionType::Release, 1, 18, 0);
+
+/// Versions at which at least one biome id or name changed, in ascending order. A table
+/// is built for each of these versions.
+const BREAKPOINTS: [Version; 8] = [V1_2, V1_7, V1_9, V1_13, V1_14, V1_16, V1_17, V1_18];
+
+
+/// History of a single biome, its id, the versions where it exists and its names.
+struct BiomeHistory {
+    biome: &'static Biome,
+    id: i32,
+    /// First version including this biome.
+    since: Version,
+    /// First version where this biome has been removed, if any.
+    until: Option<Version>,
+    /// Registry names in ascending versions order, each name is valid from its version
+    /// until the next one.
+    names: &'static [(Version, &'static str)],
+}
+
+impl BiomeHistory {
+
+    fn exists_in(&self, version: Version) -> bool {
+        version >= self.since && self.until.is_none_or(|until| version < until)
+    }
+
+    fn get_name_in(&self, version: Version) -> Option<&'static str> {
+        self.names.iter()
+            .rev()
+            .find(|&&(since, _)| version >= since)
+            .map(|&(_, name)| name)
+    }
+
+}
+
+macro_rules! history {
+    ($biome:ident $id:literal $since:ident $(.. $until:ident)? [$($name_since:ident $name:literal),*]) => {
+        BiomeHistory {
+            biome: &$biome,
+            id: $id,
+            since: $since,
+            until: history!(@until $($until)?),
+            names: &[$(($name_since, $name)),*]
+        }
+    };
+    (@until) => { None };
+    (@until $until:ident) => { Some($until) };
+}
+
+static HISTORY: [BiomeHistory; 87] = [
+    history!(OCEAN 0 V1_2 [V1_9 "ocean"]),
+    history!(PLAINS 1 V1_2 [V1_9 "plains"]),
+    history!(DESERT 2 V1_2 [V1_9 "desert"]),
+    history!(MOUNTAINS 3 V1_2 [V1_9 "extreme_hills", V1_13 "mountains", V1_18 "windswept_hills"]),
+    history!(FOREST 4 V1_2 [V1_9 "forest"]),
+    history!(TAIGA 5 V1_2 [V1_9 "taiga"]),
+    history!(SWAMP 6 V1_2 [V1_9 "swampland", V1_13 "swamp"]),
+    history!(RIVER 7 V1_2 [V1_9 "river"]),
+    history!(NETHER_WASTES 8 V1_2 [V1_9 "hell", V1_13 "nether", V1_16 "nether_wastes"]),
+    history!(THE_END 9 V1_2 [V1_9 "sky", V1_13 "the_end"]),
+    history!(FROZEN_OCEAN 10 V1_2 [V1_9 "frozen_ocean"]),
+    history!(FROZEN_RIVER 11 V1_2 [V1_9 "frozen_river"]),
+    history!(SNOWY_PLAINS 12 V1_2 [V1_9 "ice_flats", V1_13 "snowy_tundra", V1_18 "snowy_plains"]),
+    history!(SNOWY_MOUNTAINS 13 V1_2..V1_18 [V1_9 "ice_mountains", V1_13 "snowy_mountains"]),
+    history!(MUSHROOM_FIELDS 14 V1_2 [V1_9 "mushroom_island", V1_13 "mushroom_fields"]),
+    history!(MUSHROOM_FIELD_SHORE 15 V1_2..V1_18 [V1_9 "mushroom_island_shore", V1_13 "mushroom_field_shore"]),
+    history!(BEACH 16 V1_2 [V1_9 "beaches", V1_13 "beach"]),
+    history!(DESERT_HILLS 17 V1_2..V1_18 [V1_9 "desert_hills"]),
+    history!(WOODED_HILLS 18 V1_2..V1_18 [V1_9 "forest_hills", V1_13 "wooded_hills"]),
+    history!(TAIGA_HILLS 19 V1_2..V1_18 [V1_9 "taiga_hills"]),
+    history!(MOUNTAIN_EDGE 20 V1_2..V1_18 [V1_9 "smaller_extreme_hills", V1_13 "mountain_edge"]),
+    history!(JUNGLE 21 V1_2 [V1_9 "jungle"]),
+    history!(JUNGLE_HILLS 22 V1_2..V1_18 [V1_9 "jungle_hills"]),
+    history!(JUNGLE_EDGE 23 V1_7 [V1_9 "jungle_edge", V1_18 "sparse_jungle"]),
+    history!(DEEP_OCEAN 24 V1_7 [V1_9 "deep_ocean"]),
+    history!(STONE_SHORE 25 V1_7 [V1_9 "stone_beach", V1_13 "stone_shore", V1_18 "stony_shore"]),
+    history!(SNOWY_SHORE 26 V1_7 [V1_9 "cold_beach", V1_13 "snowy_beach"]),
+    history!(BIRCH_FOREST 27 V1_7 [V1_9 "birch_forest"]),
+    history!(BIRCH_FOREST_HILLS 28 V1_7..V1_18 [V1_9 "birch_forest_hills"]),
+    history!(DARK_FOREST 29 V1_7 [V1_9 "roofed_forest", V1_13 "dark_forest"]),
+    history!(SNOWY_TAIGA 30 V1_7 [V1_9 "taiga_cold", V1_13 "snowy_taiga"]),
+    history!(SNOWY_TAIGA_HILLS 31 V1_7..V1_18 [V1_9 "taiga_cold_hills", V1_13 "snowy_taiga_hills"]),
+    history!(GIANT_TREE_TAIGA 32 V1_7 [V1_9 "redwood_taiga", V1_13 "giant_tree_taiga", V1_18 "old_growth_pine_taiga"]),
+    history!(GIANT_TREE_TAIGA_HILLS 33 V1_7..V1_18 [V1_9 "redwood_taiga_hills", V1_13 "giant_tree_taiga_hills"]),
+    history!(WOODED_MOUNTAINS 34 V1_7 [V1_9 "extreme_hills_with_trees", V1_13 "wooded_mountains", V1_18 "windswept_forest"]),
+    history!(SAVANNA 35 V1_7 [V1_9 "savanna"]),
+    history!(SAVANNA_PLATEAU 36 V1_7 [V1_9 "savanna_rock", V1_13 "savanna_plateau"]),
+    history!(BADLANDS 37 V1_7 [V1_9 "mesa", V1_13 "badlands"]),
+    history!(WOODED_BADLANDS_PLATEAU 38 V1_7 [V1_9 "mesa_rock", V1_13 "wooded_badlands_plateau", V1_18 "wooded_badlands"]),
+    history!(BADLANDS_PLATEAU 39 V1_7..V1_18 [V1_9 "mesa_clear_rock", V1_13 "badlands_plateau"]),
+    history!(SMALL_END_ISLANDS 40 V1_13 [V1_13 "small_end_islands"]),
+    history!(END_MIDLANDS 41 V1_13 [V1_13 "end_midlands"]),
+    history!(END_HIGHLANDS 42 V1_13 [V1_13 "end_highlands"]),
+    history!(END_BARRENS 43 V1_13 [V1_13 "end_barrens"]),
+    history!(WARM_OCEAN 44 V1_13 [V1_13 "warm_ocean"]),
+    history!(LUKEWARM_OCEAN 45 V1_13 [V1_13 "lukewarm_ocean"]),
+    history!(COLD_OCEAN 46 V1_13 [V1_13 "cold_ocean"]),
+    history!(DEEP_WARM_OCEAN 47 V1_13..V1_18 [V1_13 "deep_warm_ocean"]),
+    history!(DEEP_LUKEWARM_OCEAN 48 V1_13 [V1_13 "deep_lukewarm_ocean"]),
+    history!(DEEP_COLD_OCEAN 49 V1_13 [V1_13 "deep_cold_ocean"]),
+    history!(DEEP_FROZEN_OCEAN 50 V1_13 [V1_13 "deep_frozen_ocean"]),
+    history!(THE_VOID 127 V1_9 [V1_9 "void", V1_13 "the_void"]),
+    history!(SUNFLOWER_PLAINS 129 V1_7 [V1_9 "mutated_plains", V1_13 "sunflower_plains"]),
+    history!(DESERT_LAKES 130 V1_7..V1_18 [V1_9 "mutated_desert", V1_13 "desert_lakes"]),
+    history!(GRAVELLY_MOUNTAINS 131 V1_7 [V1_9 "mutated_extreme_hills", V1_13 "gravelly_mountains", V1_18 "windswept_gravelly_hills"]),
+    history!(FLOWER_FOREST 132 V1_7 [V1_9 "mutated_forest", V1_13 "flower_forest"]),
+    history!(TAIGA_MOUNTAINS 133 V1_7..V1_18 [V1_9 "mutated_taiga", V1_13 "taiga_mountains"]),
+    history!(SWAMP_HILLS 134 V1_7..V1_18 [V1_9 "mutated_swampland", V1_13 "swamp_hills"]),
+    history!(ICE_SPIKES 140 V1_7 [V1_9 "mutated_ice_flats", V1_13 "ice_spikes"]),
+    history!(MODIFIED_JUNGLE 149 V1_7..V1_18 [V1_9 "mutated_jungle", V1_13 "modified_jungle"]),
+    history!(MODIFIED_JUNGLE_EDGE 151 V1_7..V1_18 [V1_9 "mutated_jungle_edge", V1_13 "modified_jungle_edge"]),
+    history!(TALL_BIRCH_FOREST 155 V1_7 [V1_9 "mutated_birch_forest", V1_13 "tall_birch_forest", V1_18 "old_growth_birch_forest"]),
+    history!(TALL_BIRCH_HILLS 156 V1_7..V1_18 [V1_9 "mutated_birch_forest_hills", V1_13 "tall_birch_hills"]),
+    history!(DARK_FOREST_HILLS 157 V1_7..V1_18 [V1_9 "mutated_roofed_forest", V1_13 "dark_forest_hills"]),
+    history!(SNOWY_TAIGA_MOUNTAINS 158 V1_7..V1_18 [V1_9 "mutated_taiga_cold", V1_13 "snowy_taiga_mountains"]),
+    history!(GIANT_SPRUCE_TAIGA 160 V1_7 [V1_9 "mutated_redwood_taiga", V1_13 "giant_spruce_taiga", V1_18 "old_growth_spruce_taiga"]),
+    history!(GIANT_SPRUCE_TAIGA_HILLS 161 V1_7..V1_18 [V1_9 "mutated_redwood_taiga_hills", V1_13 "giant_spruce_taiga_hills"]),
+    history!(MODIFIED_GRAVELLY_MOUNTAINS 162 V1_7..V1_18 [V1_9 "mutated_extreme_hills_with_trees", V1_13 "modified_gravelly_mountains"]),
+    history!(SHATTERED_SAVANNA 163 V1_7 [V1_9 "mutated_savanna", V1_13 "shattered_savanna", V1_18 "windswept_savanna"]),
+    history!(SHATTERED_SAVANNA_PLATEAU 164 V1_7..V1_18 [V1_9 "mutated_savanna_rock", V1_13 "shattered_savanna_plateau"]),
+    history!(ERODED_BADLANDS 165 V1_7 [V1_9 "mutated_mesa", V1_13 "eroded_badlands"]),
+    history!(MODIFIED_WOODED_BADLANDS_PLATEAU 166 V1_7..V1_18 [V1_9 "mutated_mesa_rock", V1_13 "modified_wooded_badlands_plateau"]),
+    history!(MODIFIED_BADLANDS_PLATEAU 167 V1_7..V1_18 [V1_9 "mutated_mesa_clear_rock", V1_13 "modified_badlands_plateau"]),
+    history!(BAMBOO_JUNGLE 168 V1_14 [V1_14 "bamboo_jungle"]),
+    history!(BAMBOO_JUNGLE_HILLS 169 V1_14..V1_18 [V1_14 "bamboo_jungle_hills"]),
+    history!(SOUL_SAND_VALLEY 170 V1_16 [V1_16 "soul_sand_valley"]),
+    history!(CRIMSON_FOREST 171 V1_16 [V1_16 "crimson_forest"]),
+    history!(WARPED_FOREST 172 V1_16 [V1_16 "warped_forest"]),
+    history!(BASALT_DELTAS 173 V1_16 [V1_16 "basalt_deltas"]),
+    history!(DRIPSTONE_CAVES 174 V1_17 [V1_17 "dripstone_caves"]),
+    history!(LUSH_CAVES 175 V1_17 [V1_17 "lush_caves"]),
+    history!(GROVE 200 V1_18 [V1_18 "grove"]),
+    history!(SNOWY_SLOPES 201 V1_18 [V1_18 "snowy_slopes"]),
+    history!(JAGGED_PEAKS 202 V1_18 [V1_18 "jagged_peaks"]),
+    history!(FROZEN_PEAKS 203 V1_18 [V1_18 "frozen_peaks"]),
+    history!(STONY_PEAKS 204 V1_18 [V1_18 "stony_peaks"]),
+    history!(MEADOW 205 V1_18 [V1_18 "meadow"]),
+];
+
+
+/// Biome ids and names valid for a range of versions.
+pub struct BiomeVersionTable {
+    version: Version,
+    id_to_biome: HashMap<i32, &'static Biome>,
+    biome_to_id: HashMap<BiomeKey, i32>,
+    name_to_biome: HashMap<&'static str, &'static Biome>,
+    biome_to_name: HashMap<BiomeKey, &'static str>,
+}
+
+impl BiomeVersionTable {
+
+    fn new(version: Version) -> Self {
+
+        let mut table = Self {
+            version,
+            id_to_biome: HashMap::new(),
+            biome_to_id: HashMap::new(),
+            name_to_biome: HashMap::new(),
+            biome_to_name: HashMap::new(),
+        };
+
+        for history in HISTORY.iter().filter(|history| history.exists_in(version)) {
+            table.id_to_biome.insert(history.id, history.biome);
+            table.biome_to_id.insert(history.biome.get_key(), history.id);
+            if let Some(name) = history.get_name_in(version) {
+                table.name_to_biome.insert(name, history.biome);
+                table.biome_to_name.insert(history.biome.get_key(), name);
+            }
+        }
+
+        table
+
+    }
+
+    /// Return the first version where this table is valid, it's valid until the next table.
+    #[inline]
+    pub fn get_version(&self) -> Version {
+        self.version
+    }
+
+    pub fn get_biome_from_id(&self, id: i32) -> Option<&'static Biome> {
+        self.id_to_biome.get(&id).copied()
+    }
+
+    pub fn get_biome_id(&self, biome: &'static Biome) -> Option<i32> {
+        self.biome_to_id.get(&biome.get_key()).copied()
+    }
+
+    /// Get a biome from its registry name, the `minecraft:` namespace is optional.
+    pub fn get_biome_from_name(&self, name: &str) -> Option<&'static Biome> {
+        let name = name.strip_prefix("minecraft:").unwrap_or(name);
+        self.name_to_biome.get(name).copied()
+    }
+
+    /// Get the registry name of a biome, without namespace.
+    pub fn get_biome_name(&self, biome: &'static Biome) -> Option<&'static str> {
+        self.biome_to_name.get(&biome.get_key()).copied()
+    }
+
+    pub fn get_biomes_count(&self) -> usize {
+        self.id_to_biome.len()
+    }
+
+}
+
+
+static TABLES: Lazy<Vec<BiomeVersionTable>> = Lazy::new(|| {
+    BREAKPOINTS.iter().map(|&version| BiomeVersionTable::new(version)).collect()
+});
+
+
+/// Get the biome table valid for the given version, only releases since 1.2 are supported
+/// because older versions didn't save biomes.
+pub fn get_biome_table_for_version(version: Version) -> Option<&'static BiomeVersionTable> {
+    if version.version_type() != VersionType::Release {
+        return None;
+    }
+    TABLES.iter().rev().find(|table| version >= table.version)
+}
+
+/// Get the biome with the given numeric id in the given version.
+pub fn get_biome_for_version(id: i32, version: Version) -> Option<&'static Biome> {
+    get_biome_table_for_version(version)?.get_biome_from_id(id)
+}
+
+/// Get the numeric id of a biome in the given version, `None` if the biome doesn't exist
+/// in this version.
+pub fn get_biome_id_for_version(biome: &'static Biome, version: Version) -> Option<i32> {
+    get_biome_table_for_version(version)?.get_biome_id(biome)
+}
+
+/// Get the biome with the given registry name in the given version.
+pub fn get_biome_from_name_for_version(name: &str, version: Version) -> Option<&'static Biome> {
+    get_biome_table_for_version(version)?.get_biome_from_name(name)
+}
+
+/// Get the registry name of a biome in the given version, `None` if the biome doesn't exist
+/// in this version or if this version has no registry names (before 1.9).
+pub fn get_biome_name_for_version(biome: &'static Biome, version: Version) -> Option<&'static str> {
+    get_biome_table_for_version(version)?.get_biome_name(biome)
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn history_consistency() {
+        for history in HISTORY.iter() {
+            assert_eq!(history.id, history.biome.get_id(), "wrong id for {}", history.biome.get_name());
+            assert!(history.names.windows(2).all(|w| w[0].0 < w[1].0));
+        }
+    }
+
+    #[test]
+    fn nether_renames() {
+
+        let v1_12 = Version(VersionType::Release, 1, 12, 2);
+        let v1_15 = Version(VersionType::Release, 1, 15, 2);
+        let v1_16 = Version(VersionType::Release, 1, 16, 5);
+
+        assert_eq!(get_biome_for_version(8, v1_12), Some(&NETHER_WASTES));
+        assert_eq!(get_biome_name_for_version(&NETHER_WASTES, v1_12), Some("hell"));
+        assert_eq!(get_biome_name_for_version(&NETHER_WASTES, v1_15), Some("nether"));
+        assert_eq!(get_biome_name_for_version(&NETHER_WASTES, v1_16), Some("nether_wastes"));
+        assert_eq!(get_biome_from_name_for_version("minecraft:nether", v1_15), Some(&NETHER_WASTES));
+        assert_eq!(get_biome_from_name_for_version("nether", v1_16), None);
+
+        assert_eq!(get_biome_for_version(170, v1_15), None);
+        assert_eq!(get_biome_for_version(170, v1_16), Some(&SOUL_SAND_VALLEY));
+
+    }
+
+    #[test]
+    fn old_versions() {
+
+        assert!(get_biome_table_for_version(Version(VersionType::Beta, 1, 8, 0)).is_none());
+        assert!(get_biome_table_for_version(Version(VersionType::Release, 1, 1, 0)).is_none());
+
+        assert_eq!(get_biome_for_version(21, Version::RELEASE_1_2_5), Some(&JUNGLE));
+        assert_eq!(get_biome_for_version(23, Version::RELEASE_1_2_5), None);
+        assert_eq!(get_biome_name_for_version(&JUNGLE, Version::RELEASE_1_2_5), None);
+
+        let v1_18 = Version(VersionType::Release, 1, 18, 1);
+        assert_eq!(get_biome_id_for_version(&DESERT_HILLS, v1_18), None);
+        assert_eq!(get_biome_from_name_for_version("windswept_hills", v1_18), Some(&MOUNTAINS));
+
+    }
+
+}
@@ -0,0 +1,284 @@
+//! Beehives and bee nests. Bees enter their hive and stay inside for a while before being
+//! released in front of it, the nectar they carry raises the honey level of the hive. Bees
+//! stay inside at night and while it's raining, unless released in emergency, like when
+//! honey is harvested from a hive that is not smoked by a campfire below it.
+//!
+//! Released bees are not angered yet.
+
+use nbt::CompoundTag;
+use hecs::{Entity, EntityBuilder};
+
+use mc_core::block::BlockState;
+use mc_core::world::level::{Level, BaseEntity, BaseBlockEntity};
+use mc_core::pos::{BlockPos, EntityPos};
+use mc_runtime::world::World;
+
+use crate::block::*;
+use crate::block::material::VANILLA_BLOCK_MATERIALS;
+use crate::block::interaction::find_block_entity;
+use crate::block_entity::{BeehiveBlockEntity, HiveBee, NECTAR_OCCUPATION_TICKS, MIN_OCCUPATION_TICKS, BEEHIVE as BEEHIVE_ENTITY};
+use crate::entity::{BeeEntity, BEE};
+use crate::sleep::SleepTracker;
+use crate::time::LevelTime;
+use crate::weather::LevelWeather;
+
+
+/// Maximum honey level of hives, hives can be harvested at this level.
+pub const MAX_HONEY_LEVEL: u8 = 5;
+/// Maximum distance between a hive and the lit campfire smoking it.
+pub const SMOKE_DISTANCE: i32 = 5;
+/// Number of ticks bees released in emergency can't enter a hive again.
+pub const EMERGENCY_ENTER_COOLDOWN: i32 = 400;
+
+
+/// Return true if the given state is a beehive or a bee nest.
+pub fn is_hive(state: &'static BlockState) -> bool {
+    state.is_block(&BEEHIVE) || state.is_block(&BEE_NEST)
+}
+
+/// Return true if the hive at the given position is smoked by a lit campfire below it, the
+/// smoke is stopped by blocks preventing motion.
+pub fn is_smoked(level: &Level, pos: &BlockPos) -> bool {
+    for dy in 1..=SMOKE_DISTANCE {
+        let state = match level.chunks.get_block_at(pos.x, pos.y - dy, pos.z) {
+            Ok(state) => state,
+            Err(_) => return false
+        };
+        if state.is_block(&CAMPFIRE) || state.is_block(&SOUL_CAMPFIRE) {
+            return state.expect(&PROP_LIT);
+        } else if VANILLA_BLOCK_MATERIALS.blocks_motion(state.get_block()) {
+            return false;
+        }
+    }
+    false
+}
+
+/// Return true if bees stay in their hive, at night and while it's raining.
+pub fn should_stay_in_hive(time: &LevelTime, weather: &LevelWeather) -> bool {
+    weather.is_raining() || time.get_sky_darken(weather.get_rain_level(), weather.get_thunder_level()) >= 4
+}
+
+/// Return the beehive block entity of the hive at the given position, it is spawned if
+/// missing. Return `None` if the block is not a hive.
+pub fn get_hive(level: &mut Level, pos: &BlockPos) -> Option<Entity> {
+    match level.chunks.get_block_at(pos.x, pos.y, pos.z) {
+        Ok(state) if is_hive(state) => {}
+        _ => return None
+    }
+    find_block_entity::<BeehiveBlockEntity>(level, pos)
+        .or_else(|| level.spawn_block_entity(&BEEHIVE_ENTITY, pos.clone()))
+}
+
+/// Make a bee enter the hive at the given position, the bee entity is removed from the level
+/// and kept in the hive. Return false if the hive is full or if the bee can't enter a hive.
+pub fn enter_hive(level: &mut Level, bee: Entity, pos: &BlockPos) -> bool {
+
+    let entity_type = match (level.entities.ecs.get::<BaseEntity>(bee), level.entities.ecs.get::<BeeEntity>(bee)) {
+        (Ok(base), Ok(bee)) if bee.can_enter_hive() => base.entity_type,
+        _ => return false
+    };
+
+    let hive = match get_hive(level, pos) {
+        Some(hive) => hive,
+        None => return false
+    };
+
+    if level.entities.ecs.get::<BeehiveBlockEntity>(hive).unwrap().is_full() {
+        return false;
+    }
+
+    let mut entity_data = CompoundTag::new();
+    {
+        let entity_ref = match level.entities.get_entity_ref(bee) {
+            Some(entity_ref) => entity_ref,
+            None => return false
+        };
+        for &codec in entity_type.codecs {
+            if codec.encode(&entity_ref, &mut entity_data).is_err() {
+                return false;
+            }
+        }
+    }
+
+    let hive_bee = HiveBee::new(entity_data, if level.entities.ecs.get::<BeeEntity>(bee).unwrap().has_nectar() {
+        NECTAR_OCCUPATION_TICKS
+    } else {
+        MIN_OCCUPATION_TICKS
+    });
+
+    level.remove_entity(bee);
+    level.entities.ecs.get_mut::<BeehiveBlockEntity>(hive).unwrap().add_bee(hive_bee)
+
+}
+
+/// Release the bees of the hive at the given position in front of it, only the bees that
+/// stayed long enough are released unless in emergency. Bees stay inside if the front of
+/// the hive is obstructed, unless in emergency. Bees that bring nectar raise the honey level
+/// of the hive, except in emergency. Return the released bee entities.
+pub fn release_bees(level: &mut Level, pos: &BlockPos, emergency: bool) -> Vec<Entity> {
+
+    let state = match level.chunks.get_block_at(pos.x, pos.y, pos.z) {
+        Ok(state) if is_hive(state) => state,
+        _ => return Vec::new()
+    };
+
+    let hive = match find_block_entity::<BeehiveBlockEntity>(level, pos) {
+        Some(hive) => hive,
+        None => return Vec::new()
+    };
+
+    let facing = state.expect(&PROP_HORIZONTAL_FACING);
+    let front = pos.relative(facing, 1);
+    let obstructed = match level.chunks.get_block_at(front.x, front.y, front.z) {
+        Ok(front_state) => VANILLA_BLOCK_MATERIALS.blocks_motion(front_state.get_block()),
+        Err(_) => true
+    };
+
+    if obstructed && !emergency {
+        return Vec::new();
+    }
+
+    let bees = {
+        let mut hive = level.entities.ecs.get_mut::<BeehiveBlockEntity>(hive).unwrap();
+        if emergency { hive.take_bees() } else { hive.take_ready_bees() }
+    };
+
+    let (dx, _, dz) = facing.normal(1);
+    let spawn_pos = EntityPos::new(
+        pos.x as f64 + 0.5 + dx as f64 * 0.55,
+        pos.y as f64 + 0.2,
+        pos.z as f64 + 0.5 + dz as f64 * 0.55
+    );
+
+    let mut honey = state.expect(&PROP_HONEY_LEVEL);
+    let mut released = Vec::new();
+
+    for hive_bee in bees {
+
+        let entity = match level.spawn_entity(&BEE, spawn_pos.clone()) {
+            Some(entity) => entity,
+            None => continue
+        };
+
+        let mut builder = EntityBuilder::new();
+        for &codec in BEE.codecs {
+            let _ = codec.decode(hive_bee.get_entity_data(), &mut builder);
+        }
+        level.entities.ecs.insert(entity, builder.build()).unwrap();
+
+        let mut bee = level.entities.ecs.get_mut::<BeeEntity>(entity).unwrap();
+        bee.set_hive_pos(pos.clone());
+        if emergency {
+            bee.set_enter_hive_cooldown(EMERGENCY_ENTER_COOLDOWN);
+        } else if bee.has_nectar() {
+            bee.set_has_nectar(false);
+            honey = (honey + 1).min(MAX_HONEY_LEVEL);
+        }
+
+        released.push(entity);
+
+    }
+
+    let _ = level.chunks.set_block_at(pos.x, pos.y, pos.z, state.with(&PROP_HONEY_LEVEL, honey).unwrap());
+    released
+
+}
+
+
+/// A system ticking the bees inside hives and releasing them when they stayed long enough.
+/// The time and weather of levels are taken from the `SleepTracker`, bees are released at
+/// any time in levels that are not tracked.
+pub fn system_beehives(world: &mut World) {
+
+    let sleep = world.components.get::<SleepTracker>().ok();
+
+    for level in &world.levels {
+
+        let mut level = level.borrow_mut();
+        let stay_in_hive = sleep.as_ref()
+            .and_then(|sleep| sleep.get_level(level.get_id()))
+            .is_some_and(|level_sleep| should_stay_in_hive(&level_sleep.time, &level_sleep.weather));
+
+        let mut ready = Vec::new();
+        for (_, (base, hive)) in level.entities.ecs.query_mut::<(&BaseBlockEntity, &mut BeehiveBlockEntity)>() {
+            hive.tick();
+            if hive.get_bees().iter().any(HiveBee::is_ready) {
+                ready.push(base.pos.clone());
+            }
+        }
+
+        if !stay_in_hive {
+            for pos in ready {
+                release_bees(&mut level, &pos, false);
+            }
+        }
+
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_core::world::level::LevelEnv;
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::source::{ChunkLoadRequest, NullLevelSource};
+    use mc_core::pos::Direction;
+
+    use crate::ext::VanillaLevelEnv;
+
+    use super::*;
+
+    #[test]
+    fn bees_enter_and_leave_hives() {
+
+        let env = LevelEnv::vanilla();
+        let height = ChunkHeight::new(0, 0);
+        let mut level = Level::new("overworld".to_string(), env.clone(), height, NullLevelSource);
+        level.chunks.insert_chunk(ChunkLoadRequest { env, height, cx: 0, cz: 0 }.build_chunk());
+
+        // The smoke of the campfire is stopped by the stone between it and the hive.
+        let hive_pos = BlockPos::new(4, 2, 4);
+        level.chunks.set_block_at(4, 2, 4, BEEHIVE.get_default_state().with(&PROP_HORIZONTAL_FACING, Direction::South).unwrap()).unwrap();
+        level.chunks.set_block_at(4, 1, 4, STONE.get_default_state()).unwrap();
+        level.chunks.set_block_at(4, 0, 4, CAMPFIRE.get_default_state().with(&PROP_LIT, true).unwrap()).unwrap();
+        assert!(!is_smoked(&level, &hive_pos));
+        level.chunks.set_block_at(4, 1, 4, AIR.get_default_state()).unwrap();
+        assert!(is_smoked(&level, &hive_pos));
+
+        let bees: Vec<Entity> = (0..4).map(|_| level.spawn_entity(&BEE, EntityPos::new(4.5, 2.0, 6.5)).unwrap()).collect();
+        level.entities.ecs.get_mut::<BeeEntity>(bees[0]).unwrap().set_has_nectar(true);
+
+        // Only three bees fit in a hive.
+        assert!(bees.iter().take(3).all(|&bee| enter_hive(&mut level, bee, &hive_pos)));
+        assert!(!enter_hive(&mut level, bees[3], &hive_pos));
+        assert!(level.entities.ecs.get::<BaseEntity>(bees[0]).is_err());
+
+        let mut world = World::new();
+        world.add_level(level);
+
+        for _ in 0..MIN_OCCUPATION_TICKS {
+            system_beehives(&mut world);
+        }
+        assert_eq!(world.levels[0].borrow().entities.ecs.query::<&BeeEntity>().iter().count(), 1);
+
+        system_beehives(&mut world);
+        {
+            let level = world.levels[0].borrow();
+            assert_eq!(level.entities.ecs.query::<&BeeEntity>().iter().count(), 3);
+            assert_eq!(level.chunks.get_block_at(4, 2, 4).unwrap().expect(&PROP_HONEY_LEVEL), 0);
+        }
+
+        // The bee with nectar brings honey when released.
+        for _ in 0..NECTAR_OCCUPATION_TICKS - MIN_OCCUPATION_TICKS {
+            system_beehives(&mut world);
+        }
+        let level = world.levels[0].borrow();
+        assert_eq!(level.entities.ecs.query::<&BeeEntity>().iter().count(), 4);
+        assert_eq!(level.chunks.get_block_at(4, 2, 4).unwrap().expect(&PROP_HONEY_LEVEL), 1);
+        assert!(level.entities.ecs.query::<&BeeEntity>().iter().all(|(_, bee)| !bee.has_nectar()));
+
+    }
+
+}
@@ -0,0 +1,200 @@
+//! Colors of tinted blocks, like grass, leaves, water or redstone wire, colors are `0xRRGGBB`
+//! integers. Tinted blocks have a color provider registered in `BlockColors`, this color
+//! multiplies the faces of their models that have a tint index. Colors depending on the
+//! biome are not blended with the surrounding biomes like the client does.
+
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+
+use mc_core::block::{Block, BlockKey, BlockState};
+use mc_core::biome::Biome;
+use mc_core::world::level::Level;
+use mc_core::pos::BlockPos;
+
+use crate::biome::color::{VanillaBiomeColors, GRASS_COLOR_MAP, DEFAULT_WATER_COLOR};
+use crate::ext::WithVanilla;
+use super::*;
+
+
+/// Default color of foliage, used without biome.
+pub const DEFAULT_FOLIAGE_COLOR: u32 = 0x48B518;
+
+/// Color of attached pumpkin and melon stems.
+const ATTACHED_STEM_COLOR: u32 = 0xE0C71C;
+
+
+/// A color provider of a tinted block.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum BlockTint {
+    /// The grass color of the biome.
+    Grass,
+    /// The foliage color of the biome.
+    Foliage,
+    /// The water color of the biome.
+    Water,
+    /// A color depending on the redstone power of the wire.
+    RedstoneWire,
+    /// A color depending on the age of the stem.
+    Stem,
+    /// A fixed color.
+    Fixed(u32)
+}
+
+impl BlockTint {
+
+    /// Return the color of the given state at a position in the given biome.
+    pub fn get_color(self, state: &'static BlockState, biome: &'static Biome, pos: &BlockPos) -> u32 {
+        match self {
+            BlockTint::Grass => biome.get_grass_color_at(pos),
+            BlockTint::Foliage => biome.get_foliage_color(),
+            BlockTint::Water => biome.get_water_color(),
+            _ => self.get_default_color(state)
+        }
+    }
+
+    /// Return the color of the given state without biome, used by items for example.
+    pub fn get_default_color(self, state: &'static BlockState) -> u32 {
+        match self {
+            BlockTint::Grass => GRASS_COLOR_MAP.get_color(0.5, 1.0),
+            BlockTint::Foliage => DEFAULT_FOLIAGE_COLOR,
+            BlockTint::Water => DEFAULT_WATER_COLOR,
+            BlockTint::RedstoneWire => get_redstone_wire_color(state.get(&PROP_REDSTONE_POWER).unwrap_or(0)),
+            BlockTint::Stem => {
+                let age = state.get::<u8, _>(&PROP_AGE_8).unwrap_or(0) as u32;
+                ((age * 32) << 16) | ((255 - age * 8) << 8) | (age * 4)
+            }
+            BlockTint::Fixed(color) => color
+        }
+    }
+
+}
+
+
+/// Return the color of a redstone wire with the given power, between 0 and 15.
+pub fn get_redstone_wire_color(power: u8) -> u32 {
+    let f = power.min(15) as f32 / 15.0;
+    let r = f * 0.6 + if f > 0.0 { 0.4 } else { 0.3 };
+    let g = (f * f * 0.7 - 0.5).clamp(0.0, 1.0);
+    let b = (f * f * 0.6 - 0.7).clamp(0.0, 1.0);
+    (((r * 255.0) as u32) << 16) | (((g * 255.0) as u32) << 8) | (b * 255.0) as u32
+}
+
+
+/// A registry associating tinted blocks to their color provider, blocks that are not
+/// registered are not tinted.
+pub struct BlockColors {
+    blocks: HashMap<BlockKey, BlockTint>
+}
+
+impl BlockColors {
+
+    pub fn new() -> Self {
+        Self {
+            blocks: HashMap::new()
+        }
+    }
+
+    /// Register the color provider of a block, replacing any previous one.
+    pub fn register(&mut self, block: &'static Block, tint: BlockTint) {
+        self.blocks.insert(block.get_key(), tint);
+    }
+
+    pub fn register_all(&mut self, blocks: &[&'static Block], tint: BlockTint) {
+        for &block in blocks {
+            self.register(block, tint);
+        }
+    }
+
+    pub fn get_tint(&self, block: &'static Block) -> Option<BlockTint> {
+        self.blocks.get(&block.get_key()).copied()
+    }
+
+    /// Return the color of the given state at a position in the given biome, `None` if the
+    /// block is not tinted.
+    pub fn get_color(&self, state: &'static BlockState, biome: &'static Biome, pos: &BlockPos) -> Option<u32> {
+        self.get_tint(state.get_block()).map(|tint| tint.get_color(state, biome, pos))
+    }
+
+    /// Return the color of the given state without biome, `None` if the block is not tinted.
+    pub fn get_default_color(&self, state: &'static BlockState) -> Option<u32> {
+        self.get_tint(state.get_block()).map(|tint| tint.get_default_color(state))
+    }
+
+    /// Return the color of the block at the given position in a level, `None` if the block
+    /// is not tinted or if its chunk is not loaded.
+    pub fn get_color_at(&self, level: &Level, pos: &BlockPos) -> Option<u32> {
+        let state = level.chunks.get_block_at(pos.x, pos.y, pos.z).ok()?;
+        let tint = self.get_tint(state.get_block())?;
+        let biome = level.chunks.get_biome_at(pos.x, pos.y, pos.z).ok()?;
+        Some(tint.get_color(state, biome, pos))
+    }
+
+    /// Iterate over all tinted blocks with their color provider, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static Block, BlockTint)> + '_ {
+        self.blocks.iter().map(|(block, &tint)| (block.inner(), tint))
+    }
+
+    pub fn blocks_count(&self) -> usize {
+        self.blocks.len()
+    }
+
+}
+
+impl Default for BlockColors {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// Vanilla block colors, this is lazily built on first use with `WithVanilla`.
+pub static VANILLA_BLOCK_COLORS: Lazy<BlockColors> = Lazy::new(BlockColors::with_vanilla);
+
+
+pub(crate) fn register_vanilla_colors(colors: &mut BlockColors) {
+    colors.register_all(&[
+        &GRASS_BLOCK, &GRASS, &FERN, &TALL_GRASS, &LARGE_FERN, &POTTED_FERN, &SUGAR_CANE
+    ], BlockTint::Grass);
+    colors.register_all(&[
+        &OAK_LEAVES, &JUNGLE_LEAVES, &ACACIA_LEAVES, &DARK_OAK_LEAVES, &VINE
+    ], BlockTint::Foliage);
+    colors.register(&SPRUCE_LEAVES, BlockTint::Fixed(0x619961));
+    colors.register(&BIRCH_LEAVES, BlockTint::Fixed(0x80A755));
+    colors.register_all(&[&WATER, &BUBBLE_COLUMN, &WATER_CAULDRON], BlockTint::Water);
+    colors.register(&REDSTONE_WIRE, BlockTint::RedstoneWire);
+    colors.register_all(&[&PUMPKIN_STEM, &MELON_STEM], BlockTint::Stem);
+    colors.register_all(&[&ATTACHED_PUMPKIN_STEM, &ATTACHED_MELON_STEM], BlockTint::Fixed(ATTACHED_STEM_COLOR));
+    colors.register(&LILY_PAD, BlockTint::Fixed(0x208030));
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::biome::{BADLANDS, SWAMP};
+
+    #[test]
+    fn block_colors() {
+
+        let colors = &*VANILLA_BLOCK_COLORS;
+        let pos = BlockPos::new(0, 64, 0);
+
+        assert_eq!(colors.get_tint(&STONE), None);
+        assert_eq!(colors.get_color(STONE.get_default_state(), &SWAMP, &pos), None);
+        assert_eq!(colors.get_color(GRASS_BLOCK.get_default_state(), &BADLANDS, &pos), Some(0x90814D));
+        assert_eq!(colors.get_color(WATER.get_default_state(), &SWAMP, &pos), Some(0x617B64));
+        assert_eq!(colors.get_color(SPRUCE_LEAVES.get_default_state(), &SWAMP, &pos), Some(0x619961));
+        assert_eq!(colors.get_default_color(OAK_LEAVES.get_default_state()), Some(DEFAULT_FOLIAGE_COLOR));
+
+        let stem = PUMPKIN_STEM.get_default_state().with(&PROP_AGE_8, 7u8).unwrap();
+        assert_eq!(colors.get_default_color(stem), Some(0xE0C71C));
+        assert_eq!(get_redstone_wire_color(0), 0x4C0000);
+        assert_eq!(get_redstone_wire_color(15), 0xFF3200);
+
+        assert!(colors.iter().any(|(block, tint)| block == &VINE && tint == BlockTint::Foliage));
+
+    }
+
+}
@@ -0,0 +1,537 @@
+//! Blocks triggered by entities touching them: pressure plates, tripwires and detector
+//! rails. Like vanilla physics, entities are handled as points, an entity touches a block if
+//! its position is inside the contact box of the block. Entities are looked up through the
+//! entity lists of chunks.
+//!
+//! Triggered blocks stay powered while entities touch them, they are checked again after a
+//! delay depending on the block. Only the states of the blocks are updated, there is no
+//! redstone propagation to neighbour blocks.
+
+use std::collections::HashMap;
+
+use mc_core::block::{Block, BlockKey, BlockState};
+use mc_core::world::level::{Level, BaseEntity};
+use mc_core::pos::{EntityPos, BlockPos, Direction};
+use hecs::Entity;
+
+use mc_runtime::world::World;
+use mc_runtime::system::NoClip;
+
+use crate::block::*;
+use crate::entity::LivingEntity;
+use crate::entity::vehicle::Minecart;
+use crate::entity::physics::get_block_pos;
+
+
+/// Maximum number of tripwires between two connected tripwire hooks, plus one.
+pub const MAX_TRIPWIRE_LENGTH: i32 = 42;
+
+
+/// A box relative to the position of a block.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ContactBox {
+    pub min: (f64, f64, f64),
+    pub max: (f64, f64, f64)
+}
+
+impl ContactBox {
+
+    pub const fn new(min: (f64, f64, f64), max: (f64, f64, f64)) -> Self {
+        Self { min, max }
+    }
+
+    /// Return true if the given position is inside this box placed at the given block.
+    pub fn contains(&self, block: &BlockPos, pos: &EntityPos) -> bool {
+        let (x, y, z) = (pos.x - block.x as f64, pos.y - block.y as f64, pos.z - block.z as f64);
+        x >= self.min.0 && x <= self.max.0
+            && y >= self.min.1 && y <= self.max.1
+            && z >= self.min.2 && z <= self.max.2
+    }
+
+}
+
+
+/// A behaviour of a block triggered by entities touching it.
+pub trait ContactBehaviour {
+
+    /// The box touched by entities, relative to the block.
+    fn get_contact_box(&self) -> &ContactBox;
+
+    /// Number of ticks before checking again if entities still touch the powered block.
+    fn get_check_delay(&self) -> u32;
+
+    /// Return true if the given entity triggers this block.
+    fn is_triggered_by(&self, level: &Level, entity: Entity) -> bool;
+
+    /// Return the power of the block, between 0 and 15, when touched by the given number of
+    /// entities.
+    fn get_power(&self, count: usize) -> u8 {
+        if count > 0 { 15 } else { 0 }
+    }
+
+    /// Return the current power of the block state.
+    fn get_state_power(&self, state: &'static BlockState) -> u8 {
+        if state.expect(&PROP_POWERED) { 15 } else { 0 }
+    }
+
+    /// Set the power of the block, this can also update other blocks.
+    fn set_power(&self, level: &Level, pos: &BlockPos, state: &'static BlockState, power: u8) {
+        let _ = level.chunks.set_block_at(pos.x, pos.y, pos.z, state.with(&PROP_POWERED, power > 0).unwrap());
+    }
+
+}
+
+
+/// Which entities trigger a pressure plate.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PlateSensitivity {
+    /// All entities, for wooden pressure plates.
+    Everything,
+    /// Only living entities, for stone pressure plates.
+    Mobs
+}
+
+
+/// Pressure plates, powered while entities are on them.
+pub struct PressurePlateBehaviour {
+    sensitivity: PlateSensitivity
+}
+
+impl PressurePlateBehaviour {
+
+    pub const fn new(sensitivity: PlateSensitivity) -> Self {
+        Self { sensitivity }
+    }
+
+    pub fn get_sensitivity(&self) -> PlateSensitivity {
+        self.sensitivity
+    }
+
+}
+
+impl ContactBehaviour for PressurePlateBehaviour {
+
+    fn get_contact_box(&self) -> &ContactBox {
+        &PRESSURE_PLATE_BOX
+    }
+
+    fn get_check_delay(&self) -> u32 {
+        20
+    }
+
+    fn is_triggered_by(&self, level: &Level, entity: Entity) -> bool {
+        match self.sensitivity {
+            PlateSensitivity::Everything => true,
+            PlateSensitivity::Mobs => level.entities.ecs.get::<LivingEntity>(entity).is_ok()
+        }
+    }
+
+}
+
+
+/// Weighted pressure plates, their power depends on the number of entities on them. The
+/// weight is the number of entities giving one level of power.
+pub struct WeightedPressurePlateBehaviour {
+    weight: usize
+}
+
+impl WeightedPressurePlateBehaviour {
+
+    pub const fn new(weight: usize) -> Self {
+        Self { weight }
+    }
+
+    pub fn get_weight(&self) -> usize {
+        self.weight
+    }
+
+}
+
+impl ContactBehaviour for WeightedPressurePlateBehaviour {
+
+    fn get_contact_box(&self) -> &ContactBox {
+        &PRESSURE_PLATE_BOX
+    }
+
+    fn get_check_delay(&self) -> u32 {
+        10
+    }
+
+    fn is_triggered_by(&self, _level: &Level, _entity: Entity) -> bool {
+        true
+    }
+
+    fn get_power(&self, count: usize) -> u8 {
+        count.div_ceil(self.weight).min(15) as u8
+    }
+
+    fn get_state_power(&self, state: &'static BlockState) -> u8 {
+        state.expect(&PROP_REDSTONE_POWER)
+    }
+
+    fn set_power(&self, level: &Level, pos: &BlockPos, state: &'static BlockState, power: u8) {
+        let _ = level.chunks.set_block_at(pos.x, pos.y, pos.z, state.with(&PROP_REDSTONE_POWER, power).unwrap());
+    }
+
+}
+
+
+/// Tripwires, powering the tripwire hooks they connect while entities touch them.
+pub struct TripwireBehaviour;
+
+impl ContactBehaviour for TripwireBehaviour {
+
+    fn get_contact_box(&self) -> &ContactBox {
+        &TRIPWIRE_BOX
+    }
+
+    fn get_check_delay(&self) -> u32 {
+        10
+    }
+
+    fn is_triggered_by(&self, _level: &Level, _entity: Entity) -> bool {
+        true
+    }
+
+    fn set_power(&self, level: &Level, pos: &BlockPos, state: &'static BlockState, power: u8) {
+
+        let _ = level.chunks.set_block_at(pos.x, pos.y, pos.z, state.with(&PROP_POWERED, power > 0).unwrap());
+
+        for dir in [Direction::North, Direction::South, Direction::West, Direction::East] {
+            for i in 1..MAX_TRIPWIRE_LENGTH {
+                let hook_pos = pos.relative(dir, i);
+                match level.chunks.get_block_at(hook_pos.x, hook_pos.y, hook_pos.z) {
+                    Ok(state) if state.is_block(&TRIPWIRE_HOOK) => {
+                        if state.expect(&PROP_HORIZONTAL_FACING) == dir.opposite() {
+                            update_tripwire_hook(level, &hook_pos);
+                        }
+                        break;
+                    }
+                    Ok(state) if state.is_block(&TRIPWIRE) => continue,
+                    _ => break
+                }
+            }
+        }
+
+    }
+
+}
+
+
+/// Detector rails, powered while minecarts are on them.
+pub struct DetectorRailBehaviour;
+
+impl ContactBehaviour for DetectorRailBehaviour {
+
+    fn get_contact_box(&self) -> &ContactBox {
+        &DETECTOR_RAIL_BOX
+    }
+
+    fn get_check_delay(&self) -> u32 {
+        20
+    }
+
+    fn is_triggered_by(&self, level: &Level, entity: Entity) -> bool {
+        level.entities.ecs.get::<Minecart>(entity).is_ok()
+    }
+
+}
+
+
+/// Update the tripwire hook at the given position and the hook it's connected to. Hooks
+/// are attached if they face each other with only tripwires between them, and powered if
+/// one of these tripwires is powered and not disarmed.
+pub fn update_tripwire_hook(level: &Level, pos: &BlockPos) {
+
+    let state = match level.chunks.get_block_at(pos.x, pos.y, pos.z) {
+        Ok(state) if state.is_block(&TRIPWIRE_HOOK) => state,
+        _ => return
+    };
+
+    let facing = state.expect(&PROP_HORIZONTAL_FACING);
+    let mut wires = Vec::new();
+    let mut other = None;
+
+    for i in 1..MAX_TRIPWIRE_LENGTH {
+        let wire_pos = pos.relative(facing, i);
+        match level.chunks.get_block_at(wire_pos.x, wire_pos.y, wire_pos.z) {
+            Ok(wire) if wire.is_block(&TRIPWIRE) => wires.push((wire_pos, wire)),
+            Ok(hook) if hook.is_block(&TRIPWIRE_HOOK) => {
+                if hook.expect(&PROP_HORIZONTAL_FACING) == facing.opposite() {
+                    other = Some((wire_pos, hook));
+                }
+                break;
+            }
+            _ => break
+        }
+    }
+
+    let attached = other.is_some() && !wires.is_empty();
+    let powered = attached && wires.iter().any(|(_, wire)| {
+        wire.expect(&PROP_POWERED) && !wire.expect(&PROP_DISARMED)
+    });
+
+    let mut hooks = vec![(pos.clone(), state)];
+    if let Some(other) = other {
+        hooks.push(other);
+    }
+
+    for (hook_pos, hook) in hooks {
+        let hook = hook.with(&PROP_ATTACHED, attached).unwrap().with(&PROP_POWERED, powered).unwrap();
+        let _ = level.chunks.set_block_at(hook_pos.x, hook_pos.y, hook_pos.z, hook);
+    }
+
+    for (wire_pos, wire) in wires {
+        let _ = level.chunks.set_block_at(wire_pos.x, wire_pos.y, wire_pos.z, wire.with(&PROP_ATTACHED, attached).unwrap());
+    }
+
+}
+
+
+/// A triggered block waiting to be checked again.
+struct PoweredContact {
+    level_idx: usize,
+    pos: BlockPos,
+    remaining: u32
+}
+
+/// World component required by `system_contact_blocks`, associating blocks to their
+/// contact behaviour and tracking powered blocks.
+pub struct ContactBlocks {
+    blocks: HashMap<BlockKey, &'static dyn ContactBehaviour>,
+    powered: Vec<PoweredContact>
+}
+
+impl ContactBlocks {
+
+    pub fn new() -> Self {
+        Self {
+            blocks: HashMap::new(),
+            powered: Vec::new()
+        }
+    }
+
+    /// Register the contact behaviour of a block, replacing any previous behaviour.
+    pub fn register(&mut self, block: &'static Block, behaviour: &'static dyn ContactBehaviour) {
+        self.blocks.insert(block.get_key(), behaviour);
+    }
+
+    pub fn register_all(&mut self, blocks: &[&'static Block], behaviour: &'static dyn ContactBehaviour) {
+        for &block in blocks {
+            self.register(block, behaviour);
+        }
+    }
+
+    pub fn get(&self, block: &'static Block) -> Option<&'static dyn ContactBehaviour> {
+        self.blocks.get(&block.get_key()).copied()
+    }
+
+    /// Return true if the block at the given position has been triggered and is waiting to
+    /// be checked again.
+    pub fn is_powered(&self, level_idx: usize, pos: &BlockPos) -> bool {
+        self.powered.iter().any(|contact| contact.level_idx == level_idx && contact.pos == *pos)
+    }
+
+    /// Count the entities touching the block and update its power, return the check delay
+    /// if the block is powered.
+    fn update(&self, level: &Level, pos: &BlockPos) -> Option<u32> {
+
+        let state = level.chunks.get_block_at(pos.x, pos.y, pos.z).ok()?;
+        let behaviour = self.get(state.get_block())?;
+        let ecs = &level.entities.ecs;
+
+        // The chunk guard must be released before changing blocks.
+        let count = level.chunks.get_chunk_at(pos.x, pos.z)?.iter_entities()
+            .filter(|&entity| ecs.get::<NoClip>(entity).is_err())
+            .filter(|&entity| match ecs.get::<BaseEntity>(entity) {
+                Ok(base) => behaviour.get_contact_box().contains(pos, &base.pos),
+                Err(_) => false
+            })
+            .filter(|&entity| behaviour.is_triggered_by(level, entity))
+            .count();
+
+        let power = behaviour.get_power(count);
+        if power != behaviour.get_state_power(state) {
+            behaviour.set_power(level, pos, state, power);
+        }
+
+        (power > 0).then(|| behaviour.get_check_delay())
+
+    }
+
+}
+
+impl Default for ContactBlocks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// Call this function with a mutable reference to a World to register the `ContactBlocks`
+/// component which is required to run the system `system_contact_blocks`.
+pub fn register_contact_blocks(world: &mut World, contacts: ContactBlocks) {
+    world.insert_component(contacts);
+}
+
+/// A system triggering the contact blocks touched by entities, entities without collisions
+/// like spectators don't trigger blocks. Powered blocks are checked again after their delay.
+pub fn system_contact_blocks(world: &mut World) {
+
+    let mut contacts = match world.components.get_mut::<ContactBlocks>() {
+        Ok(contacts) => contacts,
+        Err(_) => return
+    };
+
+    let contacts = &mut *contacts;
+
+    for (level_idx, level) in world.levels.iter().enumerate() {
+
+        let level = level.borrow();
+        let mut touched = Vec::new();
+
+        for (_, base) in level.entities.ecs.query::<&BaseEntity>().without::<NoClip>().iter() {
+            let pos = get_block_pos(&base.pos);
+            if let Ok(state) = level.chunks.get_block_at(pos.x, pos.y, pos.z) {
+                if let Some(behaviour) = contacts.get(state.get_block()) {
+                    if behaviour.get_contact_box().contains(&pos, &base.pos) && !touched.contains(&pos) {
+                        touched.push(pos);
+                    }
+                }
+            }
+        }
+
+        // Already powered blocks are only checked after their delay.
+        for pos in touched {
+            if !contacts.is_powered(level_idx, &pos) {
+                if let Some(delay) = contacts.update(&level, &pos) {
+                    contacts.powered.push(PoweredContact { level_idx, pos, remaining: delay });
+                }
+            }
+        }
+
+        for i in (0..contacts.powered.len()).rev() {
+            let contact = &mut contacts.powered[i];
+            if contact.level_idx != level_idx {
+                continue;
+            }
+            contact.remaining -= 1;
+            if contact.remaining == 0 {
+                let pos = contact.pos.clone();
+                match contacts.update(&level, &pos) {
+                    Some(delay) => contacts.powered[i].remaining = delay,
+                    None => {
+                        contacts.powered.swap_remove(i);
+                    }
+                }
+            }
+        }
+
+    }
+
+}
+
+
+static PRESSURE_PLATE_BOX: ContactBox = ContactBox::new((0.0625, 0.0, 0.0625), (0.9375, 0.25, 0.9375));
+static TRIPWIRE_BOX: ContactBox = ContactBox::new((0.0, 0.0, 0.0), (1.0, 0.5, 1.0));
+static DETECTOR_RAIL_BOX: ContactBox = ContactBox::new((0.2, 0.0, 0.2), (0.8, 0.8, 0.8));
+
+static WOODEN_PLATE_BEHAVIOUR: PressurePlateBehaviour = PressurePlateBehaviour::new(PlateSensitivity::Everything);
+static STONE_PLATE_BEHAVIOUR: PressurePlateBehaviour = PressurePlateBehaviour::new(PlateSensitivity::Mobs);
+static LIGHT_WEIGHTED_PLATE_BEHAVIOUR: WeightedPressurePlateBehaviour = WeightedPressurePlateBehaviour::new(1);
+static HEAVY_WEIGHTED_PLATE_BEHAVIOUR: WeightedPressurePlateBehaviour = WeightedPressurePlateBehaviour::new(10);
+static TRIPWIRE_BEHAVIOUR: TripwireBehaviour = TripwireBehaviour;
+static DETECTOR_RAIL_BEHAVIOUR: DetectorRailBehaviour = DetectorRailBehaviour;
+
+
+pub(crate) fn register_vanilla_contacts(contacts: &mut ContactBlocks) {
+
+    contacts.register_all(&[
+        &OAK_PRESSURE_PLATE, &SPRUCE_PRESSURE_PLATE, &BIRCH_PRESSURE_PLATE, &JUNGLE_PRESSURE_PLATE,
+        &ACACIA_PRESSURE_PLATE, &DARK_OAK_PRESSURE_PLATE, &CRIMSON_PRESSURE_PLATE, &WARPED_PRESSURE_PLATE
+    ], &WOODEN_PLATE_BEHAVIOUR);
+
+    contacts.register_all(&[&STONE_PRESSURE_PLATE, &POLISHED_BLACKSTONE_PRESSURE_PLATE], &STONE_PLATE_BEHAVIOUR);
+    contacts.register(&LIGHT_WEIGHTED_PRESSURE_PLATE, &LIGHT_WEIGHTED_PLATE_BEHAVIOUR);
+    contacts.register(&HEAVY_WEIGHTED_PRESSURE_PLATE, &HEAVY_WEIGHTED_PLATE_BEHAVIOUR);
+    contacts.register(&TRIPWIRE, &TRIPWIRE_BEHAVIOUR);
+    contacts.register(&DETECTOR_RAIL, &DETECTOR_RAIL_BEHAVIOUR);
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_core::world::level::LevelEnv;
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::source::{ChunkLoadRequest, NullLevelSource};
+
+    use crate::ext::{VanillaLevelEnv, WithVanilla};
+    use crate::entity::{PLAYER, ITEM};
+
+    use super::*;
+
+    fn get_state(world: &World, x: i32, z: i32) -> &'static BlockState {
+        world.levels[0].borrow().chunks.get_block_at(x, 1, z).unwrap()
+    }
+
+    #[test]
+    fn contact_blocks() {
+
+        let env = LevelEnv::vanilla();
+        let height = ChunkHeight::new(0, 0);
+        let mut level = Level::new("overworld".to_string(), env.clone(), height, NullLevelSource);
+        level.chunks.insert_chunk(ChunkLoadRequest { env, height, cx: 0, cz: 0 }.build_chunk());
+
+        level.chunks.set_block_at(1, 1, 1, STONE_PRESSURE_PLATE.get_default_state()).unwrap();
+        level.chunks.set_block_at(3, 1, 1, LIGHT_WEIGHTED_PRESSURE_PLATE.get_default_state()).unwrap();
+
+        // Two hooks facing each other with two tripwires between them.
+        let hook = TRIPWIRE_HOOK.get_default_state();
+        level.chunks.set_block_at(5, 1, 4, hook.with(&PROP_HORIZONTAL_FACING, Direction::East).unwrap()).unwrap();
+        level.chunks.set_block_at(8, 1, 4, hook.with(&PROP_HORIZONTAL_FACING, Direction::West).unwrap()).unwrap();
+        level.chunks.set_block_at(6, 1, 4, TRIPWIRE.get_default_state()).unwrap();
+        level.chunks.set_block_at(7, 1, 4, TRIPWIRE.get_default_state()).unwrap();
+        update_tripwire_hook(&level, &BlockPos::new(5, 1, 4));
+
+        // Items don't trigger stone pressure plates.
+        level.spawn_entity(&ITEM, EntityPos::new(1.5, 1.0, 1.5)).unwrap();
+        let player = level.spawn_entity(&PLAYER, EntityPos::new(1.5, 1.0, 1.5)).unwrap();
+        for _ in 0..3 {
+            level.spawn_entity(&ITEM, EntityPos::new(3.5, 1.0, 1.5)).unwrap();
+        }
+        let item = level.spawn_entity(&ITEM, EntityPos::new(6.5, 1.2, 4.5)).unwrap();
+
+        let mut world = World::new();
+        world.add_level(level);
+        register_contact_blocks(&mut world, ContactBlocks::with_vanilla());
+
+        assert!(get_state(&world, 8, 4).expect(&PROP_ATTACHED));
+        assert!(get_state(&world, 7, 4).expect(&PROP_ATTACHED));
+        assert!(!get_state(&world, 8, 4).expect(&PROP_POWERED));
+
+        system_contact_blocks(&mut world);
+        assert!(get_state(&world, 1, 1).expect(&PROP_POWERED));
+        assert_eq!(get_state(&world, 3, 1).expect(&PROP_REDSTONE_POWER), 3);
+        assert!(get_state(&world, 6, 4).expect(&PROP_POWERED));
+        assert!(get_state(&world, 5, 4).expect(&PROP_POWERED));
+        assert!(get_state(&world, 8, 4).expect(&PROP_POWERED));
+
+        // Blocks are released once checked again without entities.
+        world.levels[0].borrow_mut().remove_entity(player);
+        world.levels[0].borrow_mut().remove_entity(item);
+        for _ in 0..10 {
+            system_contact_blocks(&mut world);
+        }
+        assert!(get_state(&world, 1, 1).expect(&PROP_POWERED));
+        assert!(!get_state(&world, 5, 4).expect(&PROP_POWERED));
+        for _ in 0..10 {
+            system_contact_blocks(&mut world);
+        }
+        assert!(!get_state(&world, 1, 1).expect(&PROP_POWERED));
+        assert_eq!(get_state(&world, 3, 1).expect(&PROP_REDSTONE_POWER), 3);
+
+    }
+
+}
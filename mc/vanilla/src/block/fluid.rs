@@ -0,0 +1,201 @@
+//! Fluid states derived from block states. Like vanilla, fluids are not stored separately
+//! from blocks: liquid blocks, waterlogged blocks and blocks that are always in water (like
+//! kelp or seagrass) all have a fluid state, so that consumers don't need to special-case
+//! each of these blocks.
+
+use mc_core::block::BlockState;
+use mc_core::world::chunk::{Chunk, ChunkResult};
+use mc_core::world::level::Level;
+
+use super::*;
+
+
+/// Maximum amount of fluid in a block, the amount of sources.
+pub const MAX_FLUID_AMOUNT: u8 = 8;
+
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Fluid {
+    Empty,
+    Water,
+    Lava
+}
+
+
+/// The fluid in a block with its amount, between 1 and 8 for non-empty fluids.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct FluidState {
+    fluid: Fluid,
+    amount: u8,
+    source: bool,
+    falling: bool
+}
+
+impl FluidState {
+
+    /// The state of blocks without fluid.
+    pub const EMPTY: Self = Self { fluid: Fluid::Empty, amount: 0, source: false, falling: false };
+
+    /// A source of the given fluid, full and not falling.
+    pub fn source(fluid: Fluid) -> Self {
+        match fluid {
+            Fluid::Empty => Self::EMPTY,
+            _ => Self { fluid, amount: MAX_FLUID_AMOUNT, source: true, falling: false }
+        }
+    }
+
+    /// A flowing fluid with the given amount, clamped between 1 and 8.
+    pub fn flowing(fluid: Fluid, amount: u8, falling: bool) -> Self {
+        match fluid {
+            Fluid::Empty => Self::EMPTY,
+            _ => Self { fluid, amount: amount.clamp(1, MAX_FLUID_AMOUNT), source: false, falling }
+        }
+    }
+
+    /// Decode the `level` property of liquid blocks: 0 for sources, 1 to 7 for flowing
+    /// liquids with a decreasing amount and 8 or more for falling liquids.
+    pub fn from_liquid_level(fluid: Fluid, level: u8) -> Self {
+        match level {
+            0 => Self::source(fluid),
+            1..=7 => Self::flowing(fluid, MAX_FLUID_AMOUNT - level, false),
+            _ => Self::flowing(fluid, MAX_FLUID_AMOUNT, true)
+        }
+    }
+
+    /// Encode this state to the `level` property of liquid blocks.
+    pub fn get_liquid_level(&self) -> u8 {
+        if self.source {
+            0
+        } else {
+            MAX_FLUID_AMOUNT - self.amount.min(MAX_FLUID_AMOUNT) + if self.falling { 8 } else { 0 }
+        }
+    }
+
+    #[inline]
+    pub fn get_fluid(&self) -> Fluid {
+        self.fluid
+    }
+
+    #[inline]
+    pub fn get_amount(&self) -> u8 {
+        self.amount
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.fluid == Fluid::Empty
+    }
+
+    #[inline]
+    pub fn is_source(&self) -> bool {
+        self.source
+    }
+
+    #[inline]
+    pub fn is_falling(&self) -> bool {
+        self.falling
+    }
+
+    #[inline]
+    pub fn is_water(&self) -> bool {
+        self.fluid == Fluid::Water
+    }
+
+    #[inline]
+    pub fn is_lava(&self) -> bool {
+        self.fluid == Fluid::Lava
+    }
+
+    /// Height of the fluid surface in its block, between 0 and 1, sources are not full.
+    pub fn get_height(&self) -> f32 {
+        self.amount as f32 / 9.0
+    }
+
+    /// Return the liquid block state of this fluid, `None` for empty fluids.
+    pub fn get_block_state(&self) -> Option<&'static BlockState> {
+        let block = match self.fluid {
+            Fluid::Empty => return None,
+            Fluid::Water => &WATER,
+            Fluid::Lava => &LAVA
+        };
+        block.get_default_state().with(&PROP_LIQUID_LEVEL, self.get_liquid_level())
+    }
+
+}
+
+impl Default for FluidState {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+
+/// Return the fluid state of a vanilla block state.
+pub fn get_fluid_state(state: &'static BlockState) -> FluidState {
+    let block = state.get_block();
+    if block == &WATER || block == &LAVA {
+        let fluid = if block == &WATER { Fluid::Water } else { Fluid::Lava };
+        FluidState::from_liquid_level(fluid, state.get(&PROP_LIQUID_LEVEL).unwrap_or(0))
+    } else if block == &BUBBLE_COLUMN || block == &SEAGRASS || block == &TALL_SEAGRASS ||
+        block == &KELP || block == &KELP_PLANT || state.get(&PROP_WATERLOGGED) == Some(true) {
+        FluidState::source(Fluid::Water)
+    } else {
+        FluidState::EMPTY
+    }
+}
+
+
+/// A trait implemented on `Level` and `Chunk` to query the fluid states of their blocks.
+pub trait VanillaFluids {
+
+    /// Get the fluid state of the block at the given level coordinates, see `get_block_at`.
+    fn get_fluid_at(&self, x: i32, y: i32, z: i32) -> ChunkResult<FluidState>;
+
+}
+
+impl VanillaFluids for Chunk {
+    fn get_fluid_at(&self, x: i32, y: i32, z: i32) -> ChunkResult<FluidState> {
+        self.get_block_at(x, y, z).map(get_fluid_state)
+    }
+}
+
+impl VanillaFluids for Level {
+    fn get_fluid_at(&self, x: i32, y: i32, z: i32) -> ChunkResult<FluidState> {
+        self.chunks.get_block_at(x, y, z).map(get_fluid_state)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn fluid_states() {
+
+        assert_eq!(get_fluid_state(WATER.get_default_state()), FluidState::source(Fluid::Water));
+        assert_eq!(get_fluid_state(STONE.get_default_state()), FluidState::EMPTY);
+        assert_eq!(get_fluid_state(KELP.get_default_state()), FluidState::source(Fluid::Water));
+
+        let waterlogged = OAK_SLAB.get_default_state().with(&PROP_WATERLOGGED, true).unwrap();
+        assert!(get_fluid_state(waterlogged).is_water());
+        assert!(get_fluid_state(waterlogged).is_source());
+        assert!(get_fluid_state(OAK_SLAB.get_default_state()).is_empty());
+
+        for level in 0..16 {
+            let state = LAVA.get_default_state().with(&PROP_LIQUID_LEVEL, level).unwrap();
+            let fluid = get_fluid_state(state);
+            assert!(fluid.is_lava());
+            assert_eq!(fluid.get_liquid_level(), level.min(8));
+            assert_eq!(fluid.is_falling(), level >= 8);
+        }
+
+        let flowing = FluidState::from_liquid_level(Fluid::Water, 3);
+        assert_eq!(flowing.get_amount(), 5);
+        assert_eq!(flowing.get_block_state(), WATER.get_default_state().with(&PROP_LIQUID_LEVEL, 3));
+        assert_eq!(FluidState::EMPTY.get_block_state(), None);
+
+    }
+
+}
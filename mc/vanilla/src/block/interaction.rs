@@ -0,0 +1,802 @@
+//! Use of blocks by entities, like players right clicking a block. Behaviours are registered
+//! per block in the `UseBlockBehaviours` world component and are invoked with `use_block`,
+//! the network layer is responsible for opening windows requested by behaviours. Blocks
+//! can schedule a tick to come back later, like buttons to unpress, these ticks are run by
+//! `system_block_ticks`.
+
+use std::collections::HashMap;
+
+use mc_core::block::{Block, BlockKey, BlockState};
+use mc_core::world::level::{Level, BaseBlockEntity};
+use mc_core::item::{Inventory, ItemStack};
+use mc_core::pos::{BlockPos, EntityPos, Direction};
+use mc_core::rand::JavaRandom;
+use hecs::{Entity, Component};
+
+use mc_runtime::world::World;
+
+use crate::block::*;
+use crate::block::beehive::{MAX_HONEY_LEVEL, is_smoked, release_bees};
+use crate::block::lectern::{is_lectern_book, place_book};
+use crate::block_entity::{ChestBlockEntity, LecternBlockEntity, CHEST as CHEST_ENTITY, TRAPPED_CHEST as TRAPPED_CHEST_ENTITY};
+use crate::entity::{PlayerEntity, ITEM};
+use crate::entity::item::ItemEntity;
+use crate::entity::explosive::Explosives;
+use crate::entity::decoration::{LEASH_FENCES, tie_leashed_mobs};
+use crate::item::{BONE_MEAL, SHEARS, GLASS_BOTTLE, HONEY_BOTTLE, HONEYCOMB};
+use crate::item::compost::VANILLA_COMPOSTABLES;
+use crate::sleep::{SleepTracker, BedInteraction, use_bed};
+use crate::time::LevelTime;
+use crate::weather::LevelWeather;
+
+
+/// Number of ticks a stone button stays pressed.
+pub const STONE_BUTTON_PRESS_TICKS: u32 = 20;
+/// Number of ticks a wooden button stays pressed.
+pub const WOODEN_BUTTON_PRESS_TICKS: u32 = 30;
+/// Composter level reached by the last compost, the composter is ready after a tick.
+pub const COMPOSTER_FULL_LEVEL: u8 = 7;
+/// Composter level when ready, bone meal is then taken by using the composter.
+pub const COMPOSTER_READY_LEVEL: u8 = 8;
+/// Number of ticks for a full composter to become ready.
+pub const COMPOSTER_READY_TICKS: u32 = 20;
+/// Number of honeycombs dropped when harvesting a hive with shears.
+pub const HONEYCOMB_COUNT: u16 = 3;
+
+
+/// The block used by an entity, given to `UseBlockBehaviour::use_block`. No level of the
+/// world is borrowed when behaviours are called.
+pub struct UseBlockContext<'a> {
+    pub world: &'a World,
+    pub level_idx: usize,
+    pub entity: Entity,
+    pub pos: BlockPos,
+    pub state: &'static BlockState,
+    /// The face of the block that has been clicked.
+    pub face: Direction
+}
+
+impl<'a> UseBlockContext<'a> {
+
+    /// Replace the used block state, this does nothing if the chunk is not loaded.
+    pub fn set_state(&self, state: &'static BlockState) {
+        let level = self.world.levels[self.level_idx].borrow();
+        let _ = level.chunks.set_block_at(self.pos.x, self.pos.y, self.pos.z, state);
+    }
+
+    /// Schedule a tick of the used block after the given number of ticks, see `BlockTicks`.
+    pub fn schedule_tick(&self, delay: u32) {
+        if let Ok(mut ticks) = self.world.components.get_mut::<BlockTicks>() {
+            ticks.schedule(self.level_idx, self.pos.clone(), self.state.get_block(), delay);
+        };
+    }
+
+}
+
+
+/// The result of using a block.
+#[derive(Debug, Clone, PartialEq)]
+pub enum UseBlockResult {
+    /// The block has no use, the item held by the entity may be used instead.
+    Pass,
+    /// The block has been used.
+    Success,
+    /// The inventory of the given chest block entity must be opened in a window.
+    OpenChest(Entity),
+    /// A crafting table window must be opened.
+    OpenCraftingTable,
+    /// The book of the given lectern block entity must be opened in a lectern window.
+    OpenLectern(Entity),
+    /// A bed has been used, the explosion of beds in levels where they don't work is already
+    /// queued in `Explosives`.
+    Bed(BedInteraction)
+}
+
+
+/// A behaviour of a block when used by an entity.
+pub trait UseBlockBehaviour {
+
+    /// Use the block, this is called after the caller has checked that the entity can
+    /// interact with the block.
+    fn use_block(&self, ctx: &UseBlockContext) -> UseBlockResult;
+
+    /// Called when a tick scheduled for this block expires, if the block has not been
+    /// replaced since then.
+    fn scheduled_tick(&self, _level: &mut Level, _pos: &BlockPos, _state: &'static BlockState) {}
+
+}
+
+
+/// A registry associating blocks to their use behaviour, unregistered blocks have no use.
+pub struct UseBlockBehaviours {
+    blocks: HashMap<BlockKey, &'static dyn UseBlockBehaviour>
+}
+
+impl UseBlockBehaviours {
+
+    pub fn new() -> Self {
+        Self {
+            blocks: HashMap::new()
+        }
+    }
+
+    /// Register the behaviour of a block, replacing any previous behaviour.
+    pub fn register(&mut self, block: &'static Block, behaviour: &'static dyn UseBlockBehaviour) {
+        self.blocks.insert(block.get_key(), behaviour);
+    }
+
+    pub fn register_all(&mut self, blocks: &[&'static Block], behaviour: &'static dyn UseBlockBehaviour) {
+        for &block in blocks {
+            self.register(block, behaviour);
+        }
+    }
+
+    pub fn get(&self, block: &'static Block) -> Option<&'static dyn UseBlockBehaviour> {
+        self.blocks.get(&block.get_key()).copied()
+    }
+
+}
+
+impl Default for UseBlockBehaviours {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// A tick scheduled for a block at a position.
+struct ScheduledTick {
+    level_idx: usize,
+    pos: BlockPos,
+    block: &'static Block,
+    time: u64
+}
+
+/// World component storing ticks scheduled by blocks, see `system_block_ticks`.
+pub struct BlockTicks {
+    time: u64,
+    scheduled: Vec<ScheduledTick>
+}
+
+impl BlockTicks {
+
+    pub fn new() -> Self {
+        Self {
+            time: 0,
+            scheduled: Vec::new()
+        }
+    }
+
+    /// Schedule a tick of a block in the level at the given index, after the given number of
+    /// ticks. The tick is ignored if the block has been replaced by another block.
+    pub fn schedule(&mut self, level_idx: usize, pos: BlockPos, block: &'static Block, delay: u32) {
+        self.scheduled.push(ScheduledTick {
+            level_idx,
+            pos,
+            block,
+            time: self.time + delay as u64
+        });
+    }
+
+    /// Return true if a tick is scheduled for the given block at this position.
+    pub fn is_scheduled(&self, level_idx: usize, pos: &BlockPos, block: &'static Block) -> bool {
+        self.scheduled.iter().any(|tick| {
+            tick.level_idx == level_idx && tick.pos == *pos && tick.block == block
+        })
+    }
+
+    /// Advance the time and return the expired ticks.
+    fn take_expired(&mut self) -> Vec<ScheduledTick> {
+        self.time += 1;
+        let time = self.time;
+        let (expired, scheduled) = std::mem::take(&mut self.scheduled)
+            .into_iter()
+            .partition(|tick| tick.time <= time);
+        self.scheduled = scheduled;
+        expired
+    }
+
+}
+
+impl Default for BlockTicks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// Doors that can be opened by hand, iron doors are only opened by redstone.
+pub struct DoorBehaviour;
+
+impl UseBlockBehaviour for DoorBehaviour {
+
+    fn use_block(&self, ctx: &UseBlockContext) -> UseBlockResult {
+
+        let open = !ctx.state.expect(&PROP_OPEN);
+        let other_pos = match ctx.state.expect(&PROP_DOUBLE_BLOCK_HALF) {
+            DoubleBlockHalf::Lower => ctx.pos.above(1),
+            DoubleBlockHalf::Upper => ctx.pos.below(1)
+        };
+
+        let level = ctx.world.levels[ctx.level_idx].borrow();
+        for pos in [&ctx.pos, &other_pos] {
+            if let Ok(state) = level.chunks.get_block_at(pos.x, pos.y, pos.z) {
+                if state.is_block(ctx.state.get_block()) {
+                    let _ = level.chunks.set_block_at(pos.x, pos.y, pos.z, state.with(&PROP_OPEN, open).unwrap());
+                }
+            }
+        }
+
+        UseBlockResult::Success
+
+    }
+
+}
+
+
+/// Trapdoors that can be opened by hand, iron trapdoors are only opened by redstone.
+pub struct TrapdoorBehaviour;
+
+impl UseBlockBehaviour for TrapdoorBehaviour {
+    fn use_block(&self, ctx: &UseBlockContext) -> UseBlockResult {
+        ctx.set_state(ctx.state.with(&PROP_OPEN, !ctx.state.expect(&PROP_OPEN)).unwrap());
+        UseBlockResult::Success
+    }
+}
+
+
+/// Levers, toggled on each use.
+pub struct LeverBehaviour;
+
+impl UseBlockBehaviour for LeverBehaviour {
+    fn use_block(&self, ctx: &UseBlockContext) -> UseBlockResult {
+        ctx.set_state(ctx.state.with(&PROP_POWERED, !ctx.state.expect(&PROP_POWERED)).unwrap());
+        UseBlockResult::Success
+    }
+}
+
+
+/// Buttons, powered when used and unpressed by a scheduled tick.
+pub struct ButtonBehaviour {
+    press_ticks: u32
+}
+
+impl ButtonBehaviour {
+
+    /// Create a button behaviour staying pressed for the given number of ticks.
+    pub const fn new(press_ticks: u32) -> Self {
+        Self { press_ticks }
+    }
+
+    pub fn get_press_ticks(&self) -> u32 {
+        self.press_ticks
+    }
+
+}
+
+impl UseBlockBehaviour for ButtonBehaviour {
+
+    fn use_block(&self, ctx: &UseBlockContext) -> UseBlockResult {
+        if !ctx.state.expect(&PROP_POWERED) {
+            ctx.set_state(ctx.state.with(&PROP_POWERED, true).unwrap());
+            ctx.schedule_tick(self.press_ticks);
+        }
+        UseBlockResult::Success
+    }
+
+    fn scheduled_tick(&self, level: &mut Level, pos: &BlockPos, state: &'static BlockState) {
+        let _ = level.chunks.set_block_at(pos.x, pos.y, pos.z, state.with(&PROP_POWERED, false).unwrap());
+    }
+
+}
+
+
+/// Cakes, a bite is eaten on each use and the cake is removed after the last bite. Eating
+/// is not restricted by the hunger of the entity.
+pub struct CakeBehaviour;
+
+impl UseBlockBehaviour for CakeBehaviour {
+    fn use_block(&self, ctx: &UseBlockContext) -> UseBlockResult {
+        let bites = ctx.state.expect(&PROP_CAKE_BITES);
+        match ctx.state.with(&PROP_CAKE_BITES, bites + 1) {
+            Some(state) => ctx.set_state(state),
+            None => ctx.set_state(AIR.get_default_state())
+        }
+        UseBlockResult::Success
+    }
+}
+
+
+/// Beds, see `use_bed`. The time and weather of the level are taken from the `SleepTracker`
+/// component, the level is considered at day if not tracked.
+pub struct BedBehaviour;
+
+impl UseBlockBehaviour for BedBehaviour {
+
+    fn use_block(&self, ctx: &UseBlockContext) -> UseBlockResult {
+
+        let mut level = ctx.world.levels[ctx.level_idx].borrow_mut();
+
+        let interaction = {
+            let tracker = ctx.world.components.get::<SleepTracker>().ok();
+            let level_sleep = tracker.as_ref().and_then(|tracker| tracker.get_level(level.get_id()));
+            match level_sleep {
+                Some(level_sleep) => use_bed(&mut level, &level_sleep.time, &level_sleep.weather, ctx.entity, &ctx.pos),
+                None => use_bed(&mut level, &LevelTime::new(), &LevelWeather::new(), ctx.entity, &ctx.pos)
+            }
+        };
+
+        match interaction {
+            Some(interaction) => {
+                if let BedInteraction::Explode(explosion) = &interaction {
+                    if let Ok(mut explosives) = ctx.world.components.get_mut::<Explosives>() {
+                        explosives.queue_explosion(ctx.level_idx, explosion.clone());
+                    }
+                }
+                UseBlockResult::Bed(interaction)
+            }
+            None => UseBlockResult::Pass
+        }
+
+    }
+
+}
+
+
+/// Chests and trapped chests, their block entity is spawned on first use if missing. Each
+/// half of a double chest is opened separately.
+pub struct ChestBehaviour;
+
+impl UseBlockBehaviour for ChestBehaviour {
+
+    fn use_block(&self, ctx: &UseBlockContext) -> UseBlockResult {
+
+        let mut level = ctx.world.levels[ctx.level_idx].borrow_mut();
+        if let Some(entity) = find_chest(&level, &ctx.pos) {
+            return UseBlockResult::OpenChest(entity);
+        }
+
+        let entity_type = if ctx.state.is_block(&TRAPPED_CHEST) { &TRAPPED_CHEST_ENTITY } else { &CHEST_ENTITY };
+        match level.spawn_block_entity(entity_type, ctx.pos.clone()) {
+            Some(entity) => UseBlockResult::OpenChest(entity),
+            None => UseBlockResult::Pass
+        }
+
+    }
+
+}
+
+
+/// Crafting tables, opening the crafting window.
+pub struct CraftingTableBehaviour;
+
+impl UseBlockBehaviour for CraftingTableBehaviour {
+    fn use_block(&self, _ctx: &UseBlockContext) -> UseBlockResult {
+        UseBlockResult::OpenCraftingTable
+    }
+}
+
+
+/// Fences, the mobs held by the entity using the fence are tied to it with a leash knot.
+pub struct FenceBehaviour;
+
+impl UseBlockBehaviour for FenceBehaviour {
+    fn use_block(&self, ctx: &UseBlockContext) -> UseBlockResult {
+        let mut level = ctx.world.levels[ctx.level_idx].borrow_mut();
+        if tie_leashed_mobs(&mut level, ctx.entity, &ctx.pos) > 0 {
+            UseBlockResult::Success
+        } else {
+            UseBlockResult::Pass
+        }
+    }
+}
+
+
+/// Composters, compostable items held by the entity have a chance to raise the level of the
+/// composter, the bone meal of a ready composter is dropped on top of it. The first compost
+/// of an empty composter always raises its level.
+pub struct ComposterBehaviour;
+
+impl UseBlockBehaviour for ComposterBehaviour {
+
+    fn use_block(&self, ctx: &UseBlockContext) -> UseBlockResult {
+
+        let mut level = ctx.world.levels[ctx.level_idx].borrow_mut();
+        let composter_level = ctx.state.expect(&PROP_COMPOSTER_LEVEL);
+
+        if composter_level == COMPOSTER_READY_LEVEL {
+            let pos = EntityPos::new(ctx.pos.x as f64 + 0.5, ctx.pos.y as f64 + 1.0, ctx.pos.z as f64 + 0.5);
+            drop_stack(&mut level, pos, ItemStack::with_item(&BONE_MEAL));
+            let _ = level.chunks.set_block_at(ctx.pos.x, ctx.pos.y, ctx.pos.z, ctx.state.with(&PROP_COMPOSTER_LEVEL, 0u8).unwrap());
+            return UseBlockResult::Success;
+        } else if composter_level >= COMPOSTER_FULL_LEVEL {
+            return UseBlockResult::Pass;
+        }
+
+        let chance = match get_held_stack(&level, ctx.entity).and_then(|stack| VANILLA_COMPOSTABLES.get_chance(stack.get_item())) {
+            Some(chance) => chance,
+            None => return UseBlockResult::Pass
+        };
+
+        // The item is consumed even if the level is not raised.
+        if !consume_held_item(&mut level, ctx.entity) {
+            return UseBlockResult::Pass;
+        }
+
+        if (composter_level == 0 && chance > 0.0) || JavaRandom::new_seeded().next_float() < chance {
+            let composter_level = composter_level + 1;
+            let _ = level.chunks.set_block_at(ctx.pos.x, ctx.pos.y, ctx.pos.z, ctx.state.with(&PROP_COMPOSTER_LEVEL, composter_level).unwrap());
+            if composter_level == COMPOSTER_FULL_LEVEL {
+                ctx.schedule_tick(COMPOSTER_READY_TICKS);
+            }
+        }
+
+        UseBlockResult::Success
+
+    }
+
+    fn scheduled_tick(&self, level: &mut Level, pos: &BlockPos, state: &'static BlockState) {
+        if state.expect(&PROP_COMPOSTER_LEVEL) == COMPOSTER_FULL_LEVEL {
+            let _ = level.chunks.set_block_at(pos.x, pos.y, pos.z, state.with(&PROP_COMPOSTER_LEVEL, COMPOSTER_READY_LEVEL).unwrap());
+        }
+    }
+
+}
+
+
+/// Beehives and bee nests full of honey, harvested with shears for honeycombs or with a
+/// glass bottle for a honey bottle. The bees inside are released in emergency if the hive
+/// is not smoked, see `release_bees`.
+pub struct BeehiveBehaviour;
+
+impl UseBlockBehaviour for BeehiveBehaviour {
+
+    fn use_block(&self, ctx: &UseBlockContext) -> UseBlockResult {
+
+        if ctx.state.expect(&PROP_HONEY_LEVEL) < MAX_HONEY_LEVEL {
+            return UseBlockResult::Pass;
+        }
+
+        let mut level = ctx.world.levels[ctx.level_idx].borrow_mut();
+        let item = match get_held_stack(&level, ctx.entity) {
+            Some(stack) => stack.get_item(),
+            None => return UseBlockResult::Pass
+        };
+
+        let pos = EntityPos::new(ctx.pos.x as f64 + 0.5, ctx.pos.y as f64 + 1.0, ctx.pos.z as f64 + 0.5);
+        if std::ptr::eq(item, &SHEARS) {
+            drop_stack(&mut level, pos, ItemStack::with_item_count(&HONEYCOMB, HONEYCOMB_COUNT));
+        } else if std::ptr::eq(item, &GLASS_BOTTLE) && consume_held_item(&mut level, ctx.entity) {
+            let remaining = match level.entities.ecs.get_mut::<Inventory>(ctx.entity) {
+                Ok(mut inventory) => inventory.insert(ItemStack::with_item(&HONEY_BOTTLE)),
+                Err(_) => Some(ItemStack::with_item(&HONEY_BOTTLE))
+            };
+            if let Some(stack) = remaining {
+                drop_stack(&mut level, pos, stack);
+            }
+        } else {
+            return UseBlockResult::Pass;
+        }
+
+        let _ = level.chunks.set_block_at(ctx.pos.x, ctx.pos.y, ctx.pos.z, ctx.state.with(&PROP_HONEY_LEVEL, 0u8).unwrap());
+        if !is_smoked(&level, &ctx.pos) {
+            release_bees(&mut level, &ctx.pos, true);
+        }
+
+        UseBlockResult::Success
+
+    }
+
+}
+
+
+/// Lecterns, a held book is placed on an empty lectern and the book of a lectern is opened
+/// in a window. Lecterns are unpowered by the tick scheduled on page turns, see
+/// `system_lecterns`.
+pub struct LecternBehaviour;
+
+impl UseBlockBehaviour for LecternBehaviour {
+
+    fn use_block(&self, ctx: &UseBlockContext) -> UseBlockResult {
+
+        let mut level = ctx.world.levels[ctx.level_idx].borrow_mut();
+
+        if ctx.state.expect(&PROP_HAS_BOOK) {
+            let lectern = find_block_entity::<LecternBlockEntity>(&level, &ctx.pos)
+                .filter(|&lectern| level.entities.ecs.get::<LecternBlockEntity>(lectern).unwrap().has_book());
+            return match lectern {
+                Some(lectern) => UseBlockResult::OpenLectern(lectern),
+                None => UseBlockResult::Pass
+            };
+        }
+
+        let book = match get_held_stack(&level, ctx.entity) {
+            Some(mut book) if is_lectern_book(&book) => {
+                book.set_count(1);
+                book
+            }
+            _ => return UseBlockResult::Pass
+        };
+
+        if consume_held_item(&mut level, ctx.entity) && place_book(&mut level, &ctx.pos, book) {
+            UseBlockResult::Success
+        } else {
+            UseBlockResult::Pass
+        }
+
+    }
+
+    fn scheduled_tick(&self, level: &mut Level, pos: &BlockPos, state: &'static BlockState) {
+        let _ = level.chunks.set_block_at(pos.x, pos.y, pos.z, state.with(&PROP_POWERED, false).unwrap());
+    }
+
+}
+
+
+/// Find the block entity with the given component at the given position.
+pub fn find_block_entity<C: Component>(level: &Level, pos: &BlockPos) -> Option<Entity> {
+    let ecs = &level.entities.ecs;
+    let chunk = level.chunks.get_chunk_at(pos.x, pos.z)?;
+    let found = chunk.iter_entities().find(|&entity| {
+        matches!(ecs.get::<BaseBlockEntity>(entity), Ok(base) if base.pos == *pos)
+            && ecs.get::<C>(entity).is_ok()
+    });
+    found
+}
+
+/// Find the chest block entity at the given position.
+pub fn find_chest(level: &Level, pos: &BlockPos) -> Option<Entity> {
+    find_block_entity::<ChestBlockEntity>(level, pos)
+}
+
+/// Internal function to get a copy of the stack held by a player in its main hand.
+fn get_held_stack(level: &Level, entity: Entity) -> Option<ItemStack> {
+    let ecs = &level.entities.ecs;
+    match (ecs.get::<PlayerEntity>(entity), ecs.get::<Inventory>(entity)) {
+        (Ok(player), Ok(inventory)) => inventory.get_stack(player.get_selected_slot() as usize).cloned(),
+        _ => None
+    }
+}
+
+/// Internal function to consume one item held by a player in its main hand, return false
+/// if nothing can be consumed. Nothing is consumed for players in instant build.
+fn consume_held_item(level: &mut Level, entity: Entity) -> bool {
+    match level.entities.ecs.query_one_mut::<(&PlayerEntity, &mut Inventory)>(entity) {
+        Ok((player, inventory)) => player.consume_item(inventory, player.get_selected_slot() as usize, 1),
+        Err(_) => false
+    }
+}
+
+/// Internal function to drop a stack at the given position.
+fn drop_stack(level: &mut Level, pos: EntityPos, stack: ItemStack) {
+    if let Some(dropped) = level.spawn_entity(&ITEM, pos) {
+        level.entities.ecs.insert_one(dropped, ItemEntity::new(stack)).unwrap();
+    }
+}
+
+
+/// Use the block at the given position with the behaviour registered in the
+/// `UseBlockBehaviours` component. No level must be borrowed when calling this function.
+pub fn use_block(world: &World, level_idx: usize, entity: Entity, pos: &BlockPos, face: Direction) -> UseBlockResult {
+
+    let state = match world.levels[level_idx].borrow().chunks.get_block_at(pos.x, pos.y, pos.z) {
+        Ok(state) => state,
+        Err(_) => return UseBlockResult::Pass
+    };
+
+    let behaviour = match world.components.get::<UseBlockBehaviours>() {
+        Ok(behaviours) => behaviours.get(state.get_block()),
+        Err(_) => return UseBlockResult::Pass
+    };
+
+    match behaviour {
+        Some(behaviour) => behaviour.use_block(&UseBlockContext {
+            world,
+            level_idx,
+            entity,
+            pos: pos.clone(),
+            state,
+            face
+        }),
+        None => UseBlockResult::Pass
+    }
+
+}
+
+
+/// Call this function with a mutable reference to a World to register the
+/// `UseBlockBehaviours` component required by `use_block`, and the `BlockTicks` component
+/// required to run the system `system_block_ticks`.
+pub fn register_use_block(world: &mut World, behaviours: UseBlockBehaviours) {
+    world.insert_component(behaviours);
+    world.insert_component(BlockTicks::new());
+}
+
+/// A system running the expired ticks scheduled by blocks, with the behaviour of the block.
+pub fn system_block_ticks(world: &mut World) {
+
+    let expired = match world.components.get_mut::<BlockTicks>() {
+        Ok(mut ticks) => ticks.take_expired(),
+        Err(_) => return
+    };
+
+    let behaviours = match world.components.get::<UseBlockBehaviours>() {
+        Ok(behaviours) => behaviours,
+        Err(_) => return
+    };
+
+    for tick in expired {
+
+        let behaviour = match behaviours.get(tick.block) {
+            Some(behaviour) => behaviour,
+            None => continue
+        };
+
+        let mut level = match world.levels.get(tick.level_idx) {
+            Some(level) => level.borrow_mut(),
+            None => continue
+        };
+
+        if let Ok(state) = level.chunks.get_block_at(tick.pos.x, tick.pos.y, tick.pos.z) {
+            if state.is_block(tick.block) {
+                behaviour.scheduled_tick(&mut level, &tick.pos, state);
+            }
+        }
+
+    }
+
+}
+
+
+static DOOR_BEHAVIOUR: DoorBehaviour = DoorBehaviour;
+static TRAPDOOR_BEHAVIOUR: TrapdoorBehaviour = TrapdoorBehaviour;
+static LEVER_BEHAVIOUR: LeverBehaviour = LeverBehaviour;
+static STONE_BUTTON_BEHAVIOUR: ButtonBehaviour = ButtonBehaviour::new(STONE_BUTTON_PRESS_TICKS);
+static WOODEN_BUTTON_BEHAVIOUR: ButtonBehaviour = ButtonBehaviour::new(WOODEN_BUTTON_PRESS_TICKS);
+static CAKE_BEHAVIOUR: CakeBehaviour = CakeBehaviour;
+static BED_BEHAVIOUR: BedBehaviour = BedBehaviour;
+static CHEST_BEHAVIOUR: ChestBehaviour = ChestBehaviour;
+static CRAFTING_TABLE_BEHAVIOUR: CraftingTableBehaviour = CraftingTableBehaviour;
+static FENCE_BEHAVIOUR: FenceBehaviour = FenceBehaviour;
+static COMPOSTER_BEHAVIOUR: ComposterBehaviour = ComposterBehaviour;
+static BEEHIVE_BEHAVIOUR: BeehiveBehaviour = BeehiveBehaviour;
+static LECTERN_BEHAVIOUR: LecternBehaviour = LecternBehaviour;
+
+
+pub(crate) fn register_vanilla_behaviours(behaviours: &mut UseBlockBehaviours) {
+
+    behaviours.register_all(&[
+        &OAK_DOOR, &SPRUCE_DOOR, &BIRCH_DOOR, &JUNGLE_DOOR, &ACACIA_DOOR, &DARK_OAK_DOOR,
+        &CRIMSON_DOOR, &WARPED_DOOR
+    ], &DOOR_BEHAVIOUR);
+
+    behaviours.register_all(&[
+        &OAK_TRAPDOOR, &SPRUCE_TRAPDOOR, &BIRCH_TRAPDOOR, &JUNGLE_TRAPDOOR, &ACACIA_TRAPDOOR,
+        &DARK_OAK_TRAPDOOR, &CRIMSON_TRAPDOOR, &WARPED_TRAPDOOR
+    ], &TRAPDOOR_BEHAVIOUR);
+
+    behaviours.register(&LEVER, &LEVER_BEHAVIOUR);
+    behaviours.register_all(&[&STONE_BUTTON, &POLISHED_BLACKSTONE_BUTTON], &STONE_BUTTON_BEHAVIOUR);
+    behaviours.register_all(&[
+        &OAK_BUTTON, &SPRUCE_BUTTON, &BIRCH_BUTTON, &JUNGLE_BUTTON, &ACACIA_BUTTON,
+        &DARK_OAK_BUTTON, &CRIMSON_BUTTON, &WARPED_BUTTON
+    ], &WOODEN_BUTTON_BEHAVIOUR);
+
+    behaviours.register(&CAKE, &CAKE_BEHAVIOUR);
+    behaviours.register_all(&[
+        &WHITE_BED, &ORANGE_BED, &MAGENTA_BED, &LIGHT_BLUE_BED, &YELLOW_BED, &LIME_BED,
+        &PINK_BED, &GRAY_BED, &LIGHT_GRAY_BED, &CYAN_BED, &PURPLE_BED, &BLUE_BED, &BROWN_BED,
+        &GREEN_BED, &RED_BED, &BLACK_BED
+    ], &BED_BEHAVIOUR);
+
+    behaviours.register_all(&[&CHEST, &TRAPPED_CHEST], &CHEST_BEHAVIOUR);
+    behaviours.register(&CRAFTING_TABLE, &CRAFTING_TABLE_BEHAVIOUR);
+    behaviours.register_all(&LEASH_FENCES, &FENCE_BEHAVIOUR);
+    behaviours.register(&COMPOSTER, &COMPOSTER_BEHAVIOUR);
+    behaviours.register_all(&[&BEEHIVE, &BEE_NEST], &BEEHIVE_BEHAVIOUR);
+    behaviours.register(&LECTERN, &LECTERN_BEHAVIOUR);
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_core::world::level::LevelEnv;
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::source::{ChunkLoadRequest, NullLevelSource};
+    use mc_core::pos::EntityPos;
+
+    use crate::ext::{VanillaLevelEnv, WithVanilla};
+    use crate::entity::PLAYER;
+    use crate::item::{PUMPKIN_PIE, WRITABLE_BOOK};
+
+    use super::*;
+
+    fn get_state(world: &World, x: i32, y: i32, z: i32) -> &'static BlockState {
+        world.levels[0].borrow().chunks.get_block_at(x, y, z).unwrap()
+    }
+
+    #[test]
+    fn use_blocks() {
+
+        let env = LevelEnv::vanilla();
+        let height = ChunkHeight::new(0, 0);
+        let mut level = Level::new("overworld".to_string(), env.clone(), height, NullLevelSource);
+        level.chunks.insert_chunk(ChunkLoadRequest { env, height, cx: 0, cz: 0 }.build_chunk());
+
+        let door = OAK_DOOR.get_default_state();
+        level.chunks.set_block_at(0, 1, 0, door.with(&PROP_DOUBLE_BLOCK_HALF, DoubleBlockHalf::Lower).unwrap()).unwrap();
+        level.chunks.set_block_at(0, 2, 0, door.with(&PROP_DOUBLE_BLOCK_HALF, DoubleBlockHalf::Upper).unwrap()).unwrap();
+        level.chunks.set_block_at(1, 1, 0, STONE_BUTTON.get_default_state()).unwrap();
+        level.chunks.set_block_at(2, 1, 0, CAKE.get_default_state().with(&PROP_CAKE_BITES, 5u8).unwrap()).unwrap();
+        level.chunks.set_block_at(3, 1, 0, CHEST.get_default_state()).unwrap();
+        level.chunks.set_block_at(4, 1, 0, IRON_DOOR.get_default_state()).unwrap();
+        level.chunks.set_block_at(5, 1, 0, COMPOSTER.get_default_state().with(&PROP_COMPOSTER_LEVEL, 6u8).unwrap()).unwrap();
+        level.chunks.set_block_at(6, 1, 0, LECTERN.get_default_state()).unwrap();
+        let player = level.spawn_entity(&PLAYER, EntityPos::new(2.5, 1.0, 2.5)).unwrap();
+        level.entities.ecs.insert_one(player, Inventory::new(36)).unwrap();
+
+        let mut world = World::new();
+        world.add_level(level);
+        register_use_block(&mut world, UseBlockBehaviours::with_vanilla());
+
+        let use_at = |world: &World, x: i32| use_block(world, 0, player, &BlockPos::new(x, 1, 0), Direction::North);
+
+        // Both halves of the door are opened.
+        assert_eq!(use_at(&world, 0), UseBlockResult::Success);
+        assert!(get_state(&world, 0, 1, 0).expect(&PROP_OPEN));
+        assert!(get_state(&world, 0, 2, 0).expect(&PROP_OPEN));
+        assert_eq!(use_at(&world, 4), UseBlockResult::Pass);
+
+        // The button is unpressed after its scheduled tick.
+        assert_eq!(use_at(&world, 1), UseBlockResult::Success);
+        assert!(get_state(&world, 1, 1, 0).expect(&PROP_POWERED));
+        for _ in 0..STONE_BUTTON_PRESS_TICKS - 1 {
+            system_block_ticks(&mut world);
+        }
+        assert!(get_state(&world, 1, 1, 0).expect(&PROP_POWERED));
+        system_block_ticks(&mut world);
+        assert!(!get_state(&world, 1, 1, 0).expect(&PROP_POWERED));
+
+        // The last bites remove the cake.
+        use_at(&world, 2);
+        assert_eq!(get_state(&world, 2, 1, 0).expect(&PROP_CAKE_BITES), 6);
+        use_at(&world, 2);
+        assert!(get_state(&world, 2, 1, 0).is_block(&AIR));
+
+        // The chest block entity is spawned on first use only.
+        let chest = match use_at(&world, 3) {
+            UseBlockResult::OpenChest(chest) => chest,
+            result => panic!("unexpected result {:?}", result)
+        };
+        assert_eq!(use_at(&world, 3), UseBlockResult::OpenChest(chest));
+        assert!(world.levels[0].borrow().entities.ecs.get::<ChestBlockEntity>(chest).unwrap().get_inventory().is_some());
+
+        let hold = |world: &World, stack: ItemStack| {
+            world.levels[0].borrow().entities.ecs.get_mut::<Inventory>(player).unwrap().set_stack(0, Some(stack));
+        };
+
+        // The composter is ready after a tick, its bone meal is then dropped.
+        assert_eq!(use_at(&world, 5), UseBlockResult::Pass);
+        hold(&world, ItemStack::with_item(&PUMPKIN_PIE));
+        assert_eq!(use_at(&world, 5), UseBlockResult::Success);
+        assert_eq!(get_state(&world, 5, 1, 0).expect(&PROP_COMPOSTER_LEVEL), COMPOSTER_FULL_LEVEL);
+        for _ in 0..COMPOSTER_READY_TICKS {
+            system_block_ticks(&mut world);
+        }
+        assert_eq!(get_state(&world, 5, 1, 0).expect(&PROP_COMPOSTER_LEVEL), COMPOSTER_READY_LEVEL);
+        assert_eq!(use_at(&world, 5), UseBlockResult::Success);
+        assert_eq!(get_state(&world, 5, 1, 0).expect(&PROP_COMPOSTER_LEVEL), 0);
+
+        // The held book is placed on the lectern, which is then opened.
+        assert_eq!(use_at(&world, 6), UseBlockResult::Pass);
+        hold(&world, ItemStack::with_item(&WRITABLE_BOOK));
+        assert_eq!(use_at(&world, 6), UseBlockResult::Success);
+        assert!(get_state(&world, 6, 1, 0).expect(&PROP_HAS_BOOK));
+        assert!(world.levels[0].borrow().entities.ecs.get::<Inventory>(player).unwrap().get_stack(0).is_none());
+        assert!(matches!(use_at(&world, 6), UseBlockResult::OpenLectern(_)));
+
+    }
+
+}
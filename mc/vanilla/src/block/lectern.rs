@@ -0,0 +1,153 @@
+//! Lecterns holding books. A writable or written book is placed on a lectern by using it,
+//! the book is then read in a lectern window whose buttons turn the pages and take the book.
+//! Turning a page emits a short redstone pulse and pushes a `LecternPageEvent`, both done by
+//! `system_lecterns`.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use mc_core::world::level::{Level, BaseBlockEntity};
+use mc_core::item::ItemStack;
+use mc_core::pos::BlockPos;
+use mc_runtime::world::World;
+use hecs::Entity;
+
+use crate::block::*;
+use crate::block::interaction::{BlockTicks, find_block_entity};
+use crate::block_entity::{LecternBlockEntity, LECTERN as LECTERN_ENTITY};
+use crate::item::{WRITABLE_BOOK, WRITTEN_BOOK};
+
+
+/// Number of ticks a lectern stays powered after a page turn.
+pub const PAGE_TURN_POWER_TICKS: u32 = 2;
+
+/// Lectern window button turning to the previous page.
+pub const BUTTON_PREVIOUS_PAGE: u32 = 1;
+/// Lectern window button turning to the next page.
+pub const BUTTON_NEXT_PAGE: u32 = 2;
+/// Lectern window button taking the book.
+pub const BUTTON_TAKE_BOOK: u32 = 3;
+/// Lectern window buttons from this one open the book at the page `button - BUTTON_PAGE`.
+pub const BUTTON_PAGE: u32 = 100;
+
+
+/// Event pushed by `system_lecterns` when the page of a lectern's book has been turned.
+pub struct LecternPageEvent {
+    pub level: Rc<RefCell<Level>>,
+    pub lectern: Entity,
+    pub pos: BlockPos,
+    pub page: u32
+}
+
+
+/// Return true if the given item can be placed on a lectern.
+pub fn is_lectern_book(stack: &ItemStack) -> bool {
+    std::ptr::eq(stack.get_item(), &WRITABLE_BOOK) || std::ptr::eq(stack.get_item(), &WRITTEN_BOOK)
+}
+
+/// Place a book on the lectern at the given position, its block entity is spawned if
+/// missing. Return false if the block is not an empty lectern or if the item is not a book.
+pub fn place_book(level: &mut Level, pos: &BlockPos, book: ItemStack) -> bool {
+
+    let state = match level.chunks.get_block_at(pos.x, pos.y, pos.z) {
+        Ok(state) if state.is_block(&LECTERN) && !state.expect(&PROP_HAS_BOOK) => state,
+        _ => return false
+    };
+
+    if !is_lectern_book(&book) {
+        return false;
+    }
+
+    let lectern = match find_block_entity::<LecternBlockEntity>(level, pos)
+        .or_else(|| level.spawn_block_entity(&LECTERN_ENTITY, pos.clone())) {
+        Some(lectern) => lectern,
+        None => return false
+    };
+
+    level.entities.ecs.get_mut::<LecternBlockEntity>(lectern).unwrap().set_book(Some(book));
+    let _ = level.chunks.set_block_at(pos.x, pos.y, pos.z, state.with(&PROP_HAS_BOOK, true).unwrap());
+    true
+
+}
+
+/// Take the book of the lectern at the given position.
+pub fn take_book(level: &mut Level, pos: &BlockPos) -> Option<ItemStack> {
+
+    let lectern = find_block_entity::<LecternBlockEntity>(level, pos)?;
+    let book = level.entities.ecs.get_mut::<LecternBlockEntity>(lectern).unwrap().take_book();
+
+    if let Ok(state) = level.chunks.get_block_at(pos.x, pos.y, pos.z) {
+        if state.is_block(&LECTERN) {
+            let _ = level.chunks.set_block_at(pos.x, pos.y, pos.z, state.with(&PROP_HAS_BOOK, false).unwrap());
+        }
+    }
+
+    book
+
+}
+
+/// Click a button of the window of the lectern at the given position, see the `BUTTON_*`
+/// constants. Return the book if taken by the button, it's up to the caller to give it to
+/// the player.
+pub fn click_lectern_button(level: &mut Level, pos: &BlockPos, button: u32) -> Option<ItemStack> {
+
+    if button == BUTTON_TAKE_BOOK {
+        return take_book(level, pos);
+    }
+
+    let lectern = find_block_entity::<LecternBlockEntity>(level, pos)?;
+    let mut lectern = level.entities.ecs.get_mut::<LecternBlockEntity>(lectern).unwrap();
+    let page = lectern.get_page();
+
+    match button {
+        BUTTON_PREVIOUS_PAGE if page > 0 => lectern.set_page(page - 1),
+        BUTTON_NEXT_PAGE => lectern.set_page(page + 1),
+        _ if button >= BUTTON_PAGE => lectern.set_page(button - BUTTON_PAGE),
+        _ => false
+    };
+
+    None
+
+}
+
+
+/// A system powering lecterns whose page has been turned, they are unpowered by a tick
+/// scheduled in `BlockTicks`, and pushing a `LecternPageEvent` for each.
+pub fn system_lecterns(world: &mut World) {
+
+    let mut events = Vec::new();
+
+    for (level_idx, level_rc) in world.levels.iter().enumerate() {
+
+        let mut level = level_rc.borrow_mut();
+        let turned: Vec<(Entity, BlockPos, u32)> = level.entities.ecs.query_mut::<(&BaseBlockEntity, &mut LecternBlockEntity)>()
+            .into_iter()
+            .filter_map(|(entity, (base, lectern))| {
+                lectern.take_page_turned().then(|| (entity, base.pos.clone(), lectern.get_page()))
+            })
+            .collect();
+
+        for (lectern, pos, page) in turned {
+            if let Ok(state) = level.chunks.get_block_at(pos.x, pos.y, pos.z) {
+                if state.is_block(&LECTERN) {
+                    let _ = level.chunks.set_block_at(pos.x, pos.y, pos.z, state.with(&PROP_POWERED, true).unwrap());
+                    if let Ok(mut ticks) = world.components.get_mut::<BlockTicks>() {
+                        ticks.schedule(level_idx, pos.clone(), &LECTERN, PAGE_TURN_POWER_TICKS);
+                    }
+                }
+            }
+            events.push(LecternPageEvent {
+                level: Rc::clone(level_rc),
+                lectern,
+                pos,
+                page
+            });
+        }
+
+    }
+
+    for event in events {
+        world.event_tracker.push_event(event);
+    }
+
+}
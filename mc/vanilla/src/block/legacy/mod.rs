@@ -0,0 +1,204 @@
+//! Legacy block ids and metadata used until 1.12, and their mapping to the flattened block
+//! states introduced in 1.13. This module is used to convert pre-1.13 worlds and to emit
+//! modern states from legacy generators.
+//!
+//! Flattened states are given by their 1.13 string representation, and can also be resolved
+//! to the states of this crate, blocks renamed after 1.13 are upgraded to their new name.
+
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+
+use mc_core::block::{Block, BlockKey, BlockState};
+
+use super::VANILLA_BLOCKS;
+
+mod table;
+use table::FLATTENING;
+
+
+/// Blocks renamed since 1.13, with their new name.
+static RENAMES: [(&str, &str); 4] = [
+    ("minecraft:sign", "minecraft:oak_sign"),
+    ("minecraft:wall_sign", "minecraft:oak_wall_sign"),
+    ("minecraft:stone_slab", "minecraft:smooth_stone_slab"),
+    ("minecraft:grass_path", "minecraft:dirt_path"),
+];
+
+
+/// Get the 1.13 string representation of the block state flattened from the given legacy
+/// block id and metadata, for example `minecraft:oak_log[axis=x]` for `17:4`.
+pub fn get_flattened_state_name(id: u16, meta: u8) -> Option<&'static str> {
+    LEGACY_STATES.names.get(&(id, meta)).copied()
+}
+
+/// Get the legacy block id and metadata of a 1.13 block state string representation. Only
+/// the properties stored in the metadata must be given, in alphabetical order, like returned
+/// by `get_flattened_state_name`.
+pub fn get_legacy_from_state_name(name: &str) -> Option<(u16, u8)> {
+    LEGACY_STATES.names_ids.get(name).copied()
+}
+
+/// Get the block state flattened from the given legacy block id and metadata. Unknown
+/// metadata of a known block fall back to the first metadata of the block, because 1.12
+/// ignored unused metadata bits.
+pub fn get_flattened_state(id: u16, meta: u8) -> Option<&'static BlockState> {
+    let states = &LEGACY_STATES.states;
+    states.get(&(id, meta)).or_else(|| {
+        let &(_, first_meta) = LEGACY_STATES.ids.get(&id)?;
+        states.get(&(id, first_meta))
+    }).copied()
+}
+
+/// Get the legacy block id and metadata of a block state. Properties that were not stored
+/// in the metadata, like connections of fences, are ignored.
+pub fn get_legacy_from_state(state: &'static BlockState) -> Option<(u16, u8)> {
+    LEGACY_STATES.blocks.get(&state.get_block().get_key())?
+        .iter()
+        .find(|legacy| legacy.properties.iter().all(|&(name, value)| {
+            matches!(state.with_raw(name, value), Some(same) if std::ptr::eq(same, state))
+        }))
+        .map(|legacy| (legacy.id, legacy.meta))
+}
+
+
+/// A legacy state with the properties of its flattened state that were stored in the
+/// metadata.
+struct LegacyState {
+    id: u16,
+    meta: u8,
+    properties: Vec<(&'static str, &'static str)>
+}
+
+struct LegacyStates {
+    names: HashMap<(u16, u8), &'static str>,
+    names_ids: HashMap<&'static str, (u16, u8)>,
+    /// First legacy state of each legacy block id.
+    ids: HashMap<u16, (u16, u8)>,
+    states: HashMap<(u16, u8), &'static BlockState>,
+    /// Legacy states of each block, in ascending id and metadata order.
+    blocks: HashMap<BlockKey, Vec<LegacyState>>
+}
+
+static LEGACY_STATES: Lazy<LegacyStates> = Lazy::new(|| {
+
+    let blocks_by_name: HashMap<&'static str, &'static Block> = VANILLA_BLOCKS.iter()
+        .map(|&block| (block.get_name(), block))
+        .collect();
+
+    let mut legacy_states = LegacyStates {
+        names: HashMap::with_capacity(FLATTENING.len()),
+        names_ids: HashMap::with_capacity(FLATTENING.len()),
+        ids: HashMap::new(),
+        states: HashMap::with_capacity(FLATTENING.len()),
+        blocks: HashMap::new()
+    };
+
+    for &(id, meta, name) in &FLATTENING {
+
+        legacy_states.names.insert((id, meta), name);
+        legacy_states.names_ids.entry(name).or_insert((id, meta));
+        legacy_states.ids.entry(id).or_insert((id, meta));
+
+        let (block_name, mut properties) = parse_state_name(name);
+        let block_name = upgrade_block_name(block_name, &mut properties);
+
+        // SAFETY: The table is checked by tests to only contain valid states.
+        let mut state = blocks_by_name[block_name].get_default_state();
+        for &(prop_name, prop_value) in &properties {
+            state = state.with_raw(prop_name, prop_value).unwrap();
+        }
+
+        legacy_states.states.insert((id, meta), state);
+        legacy_states.blocks.entry(state.get_block().get_key())
+            .or_default()
+            .push(LegacyState { id, meta, properties });
+
+    }
+
+    legacy_states
+
+});
+
+
+/// Split a block state string representation into its block name and its properties.
+fn parse_state_name(name: &'static str) -> (&'static str, Vec<(&'static str, &'static str)>) {
+    match name.split_once('[') {
+        Some((block_name, properties)) => {
+            let properties = properties.trim_end_matches(']')
+                .split(',')
+                .filter_map(|prop| prop.split_once('='))
+                .collect();
+            (block_name, properties)
+        }
+        None => (name, Vec::new())
+    }
+}
+
+/// Upgrade a 1.13 block name and its properties to the blocks of this crate.
+fn upgrade_block_name(name: &'static str, properties: &mut Vec<(&'static str, &'static str)>) -> &'static str {
+    if name == "minecraft:cauldron" {
+        // Since 1.17, filled cauldrons are a separate block.
+        let level = properties.iter().position(|&(prop_name, _)| prop_name == "level");
+        return match level {
+            Some(index) if properties[index].1 != "0" => "minecraft:water_cauldron",
+            Some(index) => {
+                properties.remove(index);
+                name
+            }
+            None => name
+        };
+    }
+    RENAMES.iter()
+        .find(|&&(old, _)| old == name)
+        .map(|&(_, new)| new)
+        .unwrap_or(name)
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_core::world::level::LevelEnv;
+    use mc_core::pos::Axis;
+
+    use crate::ext::VanillaLevelEnv;
+    use crate::block::*;
+
+    use super::*;
+
+    #[test]
+    fn flattening() {
+
+        // All states of the table must be valid and map back to their first legacy state.
+        for &(id, meta, name) in &FLATTENING {
+            let state = get_flattened_state(id, meta).unwrap();
+            let (legacy_id, legacy_meta) = get_legacy_from_state(state).unwrap();
+            assert_eq!(get_flattened_state(legacy_id, legacy_meta), Some(state), "{}:{} {}", id, meta, name);
+            assert_eq!(get_flattened_state_name(id, meta), Some(name));
+        }
+
+        assert_eq!(get_flattened_state_name(17, 4), Some("minecraft:oak_log[axis=x]"));
+        assert_eq!(get_legacy_from_state_name("minecraft:oak_log[axis=x]"), Some((17, 4)));
+        assert_eq!(get_flattened_state(17, 4), OAK_LOG.get_default_state().with(&PROP_AXIS, Axis::X));
+        assert_eq!(get_flattened_state(1, 0), Some(STONE.get_default_state()));
+        assert_eq!(get_flattened_state(63, 0), Some(OAK_SIGN.get_default_state()));
+        assert_eq!(get_flattened_state(118, 3).unwrap().get_block(), &WATER_CAULDRON);
+        assert_eq!(get_flattened_state(118, 0), Some(CAULDRON.get_default_state()));
+
+        // Unused metadata bits are ignored.
+        assert_eq!(get_flattened_state(54, 0), get_flattened_state(54, 2));
+        assert_eq!(get_flattened_state(253, 0), None);
+
+        // Properties not stored in the metadata are ignored.
+        let fence = OAK_FENCE.get_default_state().with(&PROP_EAST, true).unwrap();
+        assert_eq!(get_legacy_from_state(fence), Some((85, 0)));
+        assert_eq!(get_legacy_from_state(STONE.get_default_state()), Some((1, 0)));
+
+        // The vanilla environment decodes legacy chunks with this table.
+        let resolver = LevelEnv::vanilla().legacy_blocks.unwrap();
+        assert_eq!(resolver(17, 4), get_flattened_state(17, 4));
+
+    }
+
+}
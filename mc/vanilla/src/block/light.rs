@@ -0,0 +1,116 @@
+//! Light emitted by vanilla blocks, and light properties of vanilla blocks used to relight
+//! chunks, see `mc_core::world::light`.
+
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+
+use mc_core::block::{BlockKey, BlockState};
+use mc_core::world::light::LightProperties;
+
+use super::material::BlockMaterials;
+use super::*;
+
+
+/// Return the block light emitted by a vanilla block state, between 0 and 15.
+pub fn get_light_emission(state: &'static BlockState) -> u8 {
+
+    let block = state.get_block();
+    let lit = || state.get(&PROP_LIT).unwrap_or(false);
+
+    if block == &FURNACE || block == &SMOKER || block == &BLAST_FURNACE {
+        if lit() { 13 } else { 0 }
+    } else if block == &REDSTONE_ORE || block == &DEEPSLATE_REDSTONE_ORE {
+        if lit() { 9 } else { 0 }
+    } else if block == &REDSTONE_TORCH || block == &REDSTONE_WALL_TORCH {
+        if lit() { 7 } else { 0 }
+    } else if block == &REDSTONE_LAMP || block == &CAMPFIRE {
+        if lit() { 15 } else { 0 }
+    } else if block == &SOUL_CAMPFIRE {
+        if lit() { 10 } else { 0 }
+    } else if block == &SEA_PICKLE {
+        // Sea pickles out of water are dead and don't emit light.
+        if state.get(&PROP_WATERLOGGED).unwrap_or(false) {
+            3 + 3 * state.get::<u8, _>(&PROP_PICKLES).unwrap_or(1)
+        } else {
+            0
+        }
+    } else if block == &RESPAWN_ANCHOR {
+        let charges: u8 = state.get(&PROP_CHARGES).unwrap_or(0);
+        (charges.min(4) as u32 * 15 / 4) as u8
+    } else if block == &LIGHT {
+        state.get::<u8, _>(&PROP_LIGHT_LEVEL).unwrap_or(15).min(15)
+    } else if block == &CAVE_VINES || block == &CAVE_VINES_PLANT {
+        if state.get(&PROP_BERRIES).unwrap_or(false) { 14 } else { 0 }
+    } else if let Some(candles) = state.get::<u8, _>(&PROP_CANDLES) {
+        if lit() { 3 * candles } else { 0 }
+    } else if block.get_name().ends_with("candle_cake") {
+        if lit() { 3 } else { 0 }
+    } else {
+        VANILLA_LIGHT_EMISSIONS.get(&block.get_key()).copied().unwrap_or(0)
+    }
+
+}
+
+
+/// Vanilla blocks emitting light independently of their state.
+static VANILLA_LIGHT_EMISSIONS: Lazy<HashMap<BlockKey, u8>> = Lazy::new(|| {
+    VANILLA_BLOCKS.iter()
+        .filter_map(|&block| {
+            let name = block.get_name().strip_prefix("minecraft:").unwrap_or(block.get_name());
+            get_vanilla_emission(name).map(|emission| (block.get_key(), emission))
+        })
+        .collect()
+});
+
+
+fn get_vanilla_emission(name: &str) -> Option<u8> {
+    Some(match name {
+        "glowstone" | "lava" | "fire" | "sea_lantern" | "lantern" | "jack_o_lantern" |
+        "beacon" | "end_gateway" | "end_portal" | "conduit" | "shroomlight" | "lava_cauldron" => 15,
+        "torch" | "wall_torch" | "end_rod" => 14,
+        "nether_portal" => 11,
+        "soul_torch" | "soul_wall_torch" | "soul_fire" | "soul_lantern" | "crying_obsidian" => 10,
+        "glow_lichen" | "enchanting_table" | "ender_chest" => 7,
+        "amethyst_cluster" => 5,
+        "large_amethyst_bud" => 4,
+        "magma_block" => 3,
+        "medium_amethyst_bud" => 2,
+        "small_amethyst_bud" | "brewing_stand" | "brown_mushroom" | "dragon_egg" |
+        "end_portal_frame" | "sculk_sensor" => 1,
+        _ => return None
+    })
+}
+
+
+impl LightProperties for BlockMaterials {
+
+    fn get_opacity(&self, state: &'static BlockState) -> u8 {
+        BlockMaterials::get_opacity(self, state.get_block())
+    }
+
+    fn get_emission(&self, state: &'static BlockState) -> u8 {
+        get_light_emission(state)
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn light_emission() {
+        assert_eq!(get_light_emission(TORCH.get_default_state()), 14);
+        assert_eq!(get_light_emission(STONE.get_default_state()), 0);
+        assert_eq!(get_light_emission(FURNACE.get_default_state()), 0);
+        assert_eq!(get_light_emission(FURNACE.get_default_state().with(&PROP_LIT, true).unwrap()), 13);
+        let candles = CANDLE.get_default_state().with(&PROP_CANDLES, 3u8).unwrap().with(&PROP_LIT, true).unwrap();
+        assert_eq!(get_light_emission(candles), 9);
+        assert_eq!(get_light_emission(LIGHT.get_default_state().with(&PROP_LIGHT_LEVEL, 7u8).unwrap()), 7);
+        assert_eq!(get_light_emission(RESPAWN_ANCHOR.get_default_state().with(&PROP_CHARGES, 2u8).unwrap()), 7);
+    }
+
+}
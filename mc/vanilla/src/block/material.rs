@@ -1,8 +1,16 @@
 //! Base module for block's materials. This module defines multiple tags that are included in the
-//! vanilla module.
+//! vanilla module, and the `BlockMaterials` registry that associates each block to a material
+//! defining its physical properties (solidity, flammability, opacity, piston reaction...).
 
-use mc_core::block::{Block, GlobalBlocks};
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+
+use mc_core::block::{Block, BlockKey, BlockState, GlobalBlocks};
 use mc_core::tag::TagType;
+
+use crate::ext::WithVanilla;
+use crate::util::DyeColor;
 use crate::block::*;
 
 
@@ -12,6 +20,7 @@ pub static TAG_LIQUID: TagType = TagType::new_logical("minecraft:rust:liquid");
 pub static TAG_LEAVES: TagType = TagType::new_logical("minecraft:rust:leaves");
 pub static TAG_LOG: TagType = TagType::new_logical("minecraft:rust:log");
 pub static TAG_SAPLING: TagType = TagType::new_logical("minecraft:rust:sapling");
+pub static TAG_BED: TagType = TagType::new_logical("minecraft:rust:bed");
 
 
 pub(crate) fn register_tags(blocks: &mut GlobalBlocks) {
@@ -60,6 +69,26 @@ pub(crate) fn register_tags(blocks: &mut GlobalBlocks) {
         &DARK_OAK_SAPLING,
     ]).unwrap();
 
+    blocks.register_tag_type(&TAG_BED);
+    blocks.set_blocks_tag(&TAG_BED, true, [
+        &WHITE_BED,
+        &ORANGE_BED,
+        &MAGENTA_BED,
+        &LIGHT_BLUE_BED,
+        &YELLOW_BED,
+        &LIME_BED,
+        &PINK_BED,
+        &GRAY_BED,
+        &LIGHT_GRAY_BED,
+        &CYAN_BED,
+        &PURPLE_BED,
+        &BLUE_BED,
+        &BROWN_BED,
+        &GREEN_BED,
+        &RED_BED,
+        &BLACK_BED
+    ]).unwrap();
+
 }
 
 
@@ -269,3 +298,722 @@ static NON_BLOCKING_BLOCKS: &[&'static Block] = &[
     &COBWEB,
     &BAMBOO_SAPLING,
 ];
+
+
+// MATERIALS //
+
+/// Reaction of a block when pushed by a piston.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum PistonReaction {
+    /// The block is moved.
+    Normal,
+    /// The block is destroyed and dropped.
+    Destroy,
+    /// The block can't be moved and blocks the piston.
+    Block,
+    /// The block is ignored, only used by some entities in vanilla.
+    Ignore,
+    /// The block can be pushed but not pulled by sticky pistons.
+    PushOnly
+}
+
+
+/// A map color, as used to render blocks on map items. The inner value is the base color
+/// id used in the map data, it should be multiplied by 4 and offset by the brightness.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct MapColor(pub u8);
+
+impl MapColor {
+
+    pub const NONE: Self = Self(0);
+    pub const GRASS: Self = Self(1);
+    pub const SAND: Self = Self(2);
+    pub const WOOL: Self = Self(3);
+    pub const FIRE: Self = Self(4);
+    pub const ICE: Self = Self(5);
+    pub const METAL: Self = Self(6);
+    pub const PLANT: Self = Self(7);
+    pub const SNOW: Self = Self(8);
+    pub const CLAY: Self = Self(9);
+    pub const DIRT: Self = Self(10);
+    pub const STONE: Self = Self(11);
+    pub const WATER: Self = Self(12);
+    pub const WOOD: Self = Self(13);
+    pub const QUARTZ: Self = Self(14);
+    pub const COLOR_ORANGE: Self = Self(15);
+    pub const COLOR_MAGENTA: Self = Self(16);
+    pub const COLOR_LIGHT_BLUE: Self = Self(17);
+    pub const COLOR_YELLOW: Self = Self(18);
+    pub const COLOR_LIGHT_GREEN: Self = Self(19);
+    pub const COLOR_PINK: Self = Self(20);
+    pub const COLOR_GRAY: Self = Self(21);
+    pub const COLOR_LIGHT_GRAY: Self = Self(22);
+    pub const COLOR_CYAN: Self = Self(23);
+    pub const COLOR_PURPLE: Self = Self(24);
+    pub const COLOR_BLUE: Self = Self(25);
+    pub const COLOR_BROWN: Self = Self(26);
+    pub const COLOR_GREEN: Self = Self(27);
+    pub const COLOR_RED: Self = Self(28);
+    pub const COLOR_BLACK: Self = Self(29);
+    pub const GOLD: Self = Self(30);
+    pub const DIAMOND: Self = Self(31);
+    pub const LAPIS: Self = Self(32);
+    pub const EMERALD: Self = Self(33);
+    pub const PODZOL: Self = Self(34);
+    pub const NETHER: Self = Self(35);
+    pub const TERRACOTTA_WHITE: Self = Self(36);
+    pub const CRIMSON_NYLIUM: Self = Self(52);
+    pub const CRIMSON_STEM: Self = Self(53);
+    pub const CRIMSON_HYPHAE: Self = Self(54);
+    pub const WARPED_NYLIUM: Self = Self(55);
+    pub const WARPED_STEM: Self = Self(56);
+    pub const WARPED_HYPHAE: Self = Self(57);
+    pub const WARPED_WART_BLOCK: Self = Self(58);
+    pub const DEEPSLATE: Self = Self(59);
+    pub const RAW_IRON: Self = Self(60);
+    pub const GLOW_LICHEN: Self = Self(61);
+
+    const RGB: [u32; 62] = [
+        0x000000, 0x7FB238, 0xF7E9A3, 0xC7C7C7, 0xFF0000, 0xA0A0FF, 0xA7A7A7, 0x007C00,
+        0xFFFFFF, 0xA4A8B8, 0x976D4D, 0x707070, 0x4040FF, 0x8F7748, 0xFFFCF5, 0xD87F33,
+        0xB24CD8, 0x6699D8, 0xE5E533, 0x7FCC19, 0xF27FA5, 0x4C4C4C, 0x999999, 0x4C7F99,
+        0x7F3FB2, 0x334CB2, 0x664C33, 0x667F33, 0x993333, 0x191919, 0xFAEE4D, 0x5CDBD5,
+        0x4A80FF, 0x00D93A, 0x815631, 0x700200, 0xD1B1A1, 0x9F5224, 0x95576C, 0x706C8A,
+        0xBA8524, 0x677535, 0xA04D4E, 0x392923, 0x876B62, 0x575C5C, 0x7A4958, 0x4C3E5C,
+        0x4C3223, 0x4C522A, 0x8E3C2E, 0x251610, 0xBD3031, 0x943F61, 0x5C191D, 0x167E86,
+        0x3A8E8C, 0x562C3E, 0x14B485, 0x646464, 0xD8AF93, 0x7FA796,
+    ];
+
+    /// Map color of blocks dyed with the given color (wool, concrete, stained glass...).
+    pub fn from_dye(color: DyeColor) -> Self {
+        match color {
+            DyeColor::White => Self::SNOW,
+            color => Self(14 + color.get_id())
+        }
+    }
+
+    /// Map color of terracotta dyed with the given color.
+    pub fn from_terracotta(color: DyeColor) -> Self {
+        Self(Self::TERRACOTTA_WHITE.0 + color.get_id())
+    }
+
+    #[inline]
+    pub fn get_id(self) -> u8 {
+        self.0
+    }
+
+    /// Get the base RGB color, `0` for unknown or transparent colors.
+    pub fn get_rgb(self) -> u32 {
+        Self::RGB.get(self.0 as usize).copied().unwrap_or(0)
+    }
+
+}
+
+
+/// A block material, defining common physical properties shared by multiple blocks.
+#[derive(Debug)]
+pub struct Material {
+    pub name: &'static str,
+    pub color: MapColor,
+    pub liquid: bool,
+    pub solid: bool,
+    pub blocks_motion: bool,
+    pub solid_blocking: bool,
+    pub flammable: bool,
+    pub replaceable: bool,
+    pub piston_reaction: PistonReaction,
+}
+
+macro_rules! materials {
+    ($($id:ident $name:literal $color:ident [$($flag:ident)*];)*) => {
+        $(pub static $id: Material = materials!(@build $name $color [$($flag)*]);)*
+        pub static VANILLA_MATERIALS_LIST: [&'static Material; mc_core::count!($($id)*)] = [$(&$id),*];
+    };
+    (@build $name:literal $color:ident [$($flag:ident)*]) => {{
+        #[allow(unused_mut)]
+        let mut mat = Material {
+            name: $name,
+            color: MapColor::$color,
+            liquid: false,
+            solid: true,
+            blocks_motion: true,
+            solid_blocking: true,
+            flammable: false,
+            replaceable: false,
+            piston_reaction: PistonReaction::Normal,
+        };
+        $(materials!(@flag mat $flag);)*
+        mat
+    }};
+    (@flag $m:ident liquid) => { $m.liquid = true; };
+    (@flag $m:ident non_solid) => { $m.solid = false; };
+    (@flag $m:ident no_collider) => { $m.blocks_motion = false; };
+    (@flag $m:ident not_solid_blocking) => { $m.solid_blocking = false; };
+    (@flag $m:ident flammable) => { $m.flammable = true; };
+    (@flag $m:ident replaceable) => { $m.replaceable = true; };
+    (@flag $m:ident destroy_on_push) => { $m.piston_reaction = PistonReaction::Destroy; };
+    (@flag $m:ident not_pushable) => { $m.piston_reaction = PistonReaction::Block; };
+}
+
+materials! {
+    MAT_AIR "air" NONE [no_collider not_solid_blocking non_solid replaceable];
+    MAT_STRUCTURAL_AIR "structural_air" NONE [no_collider not_solid_blocking non_solid replaceable];
+    MAT_PORTAL "portal" NONE [no_collider not_solid_blocking non_solid not_pushable];
+    MAT_CLOTH_DECORATION "cloth_decoration" WOOL [no_collider not_solid_blocking non_solid flammable];
+    MAT_PLANT "plant" PLANT [no_collider not_solid_blocking non_solid destroy_on_push];
+    MAT_WATER_PLANT "water_plant" WATER [no_collider not_solid_blocking non_solid destroy_on_push];
+    MAT_REPLACEABLE_PLANT "replaceable_plant" PLANT [no_collider not_solid_blocking non_solid destroy_on_push replaceable flammable];
+    MAT_REPLACEABLE_FIREPROOF_PLANT "replaceable_fireproof_plant" PLANT [no_collider not_solid_blocking non_solid destroy_on_push replaceable];
+    MAT_REPLACEABLE_WATER_PLANT "replaceable_water_plant" WATER [no_collider not_solid_blocking non_solid destroy_on_push replaceable];
+    MAT_WATER "water" WATER [no_collider not_solid_blocking non_solid destroy_on_push replaceable liquid];
+    MAT_BUBBLE_COLUMN "bubble_column" WATER [no_collider not_solid_blocking non_solid destroy_on_push replaceable liquid];
+    MAT_LAVA "lava" FIRE [no_collider not_solid_blocking non_solid destroy_on_push replaceable liquid];
+    MAT_TOP_SNOW "top_snow" SNOW [no_collider not_solid_blocking non_solid destroy_on_push replaceable];
+    MAT_FIRE "fire" NONE [no_collider not_solid_blocking non_solid destroy_on_push replaceable];
+    MAT_DECORATION "decoration" NONE [no_collider not_solid_blocking non_solid destroy_on_push];
+    MAT_WEB "web" WOOL [no_collider not_solid_blocking destroy_on_push];
+    MAT_SCULK "sculk" COLOR_BLACK [];
+    MAT_BUILDABLE_GLASS "buildable_glass" NONE [];
+    MAT_CLAY "clay" CLAY [];
+    MAT_DIRT "dirt" DIRT [];
+    MAT_GRASS "grass" GRASS [];
+    MAT_ICE_SOLID "ice_solid" ICE [];
+    MAT_SAND "sand" SAND [];
+    MAT_SPONGE "sponge" COLOR_YELLOW [];
+    MAT_SHULKER_SHELL "shulker_shell" COLOR_PURPLE [];
+    MAT_WOOD "wood" WOOD [flammable];
+    MAT_NETHER_WOOD "nether_wood" WOOD [];
+    MAT_BAMBOO_SAPLING "bamboo_sapling" WOOD [flammable destroy_on_push no_collider];
+    MAT_BAMBOO "bamboo" WOOD [flammable destroy_on_push];
+    MAT_WOOL "wool" WOOL [flammable];
+    MAT_EXPLOSIVE "explosive" FIRE [flammable not_solid_blocking];
+    MAT_LEAVES "leaves" PLANT [flammable not_solid_blocking destroy_on_push];
+    MAT_GLASS "glass" NONE [not_solid_blocking];
+    MAT_ICE "ice" ICE [not_solid_blocking];
+    MAT_CACTUS "cactus" PLANT [not_solid_blocking destroy_on_push];
+    MAT_STONE "stone" STONE [];
+    MAT_METAL "metal" METAL [];
+    MAT_SNOW "snow" SNOW [];
+    MAT_HEAVY_METAL "heavy_metal" METAL [not_pushable];
+    MAT_BARRIER "barrier" NONE [not_pushable];
+    MAT_PISTON "piston" STONE [not_pushable];
+    MAT_MOSS "moss" PLANT [destroy_on_push];
+    MAT_VEGETABLE "vegetable" PLANT [destroy_on_push];
+    MAT_EGG "egg" PLANT [destroy_on_push];
+    MAT_CAKE "cake" NONE [destroy_on_push];
+    MAT_AMETHYST "amethyst" COLOR_PURPLE [];
+    MAT_POWDER_SNOW "powder_snow" SNOW [non_solid not_solid_blocking no_collider];
+}
+
+
+/// Properties of a block registered in `BlockMaterials`.
+#[derive(Debug, Copy, Clone)]
+struct BlockMaterial {
+    material: &'static Material,
+    color: Option<MapColor>,
+    opacity: Option<u8>,
+    explosion_resistance: Option<f32>,
+}
+
+
+/// A registry associating blocks to their material, with optional per-block overrides of the
+/// map color and of the light opacity. Unregistered blocks use the default material.
+///
+/// Queries are made on blocks, use `BlockState::get_block` for states.
+pub struct BlockMaterials {
+    blocks: HashMap<BlockKey, BlockMaterial>,
+    default: &'static Material,
+}
+
+impl BlockMaterials {
+
+    /// Construct an empty registry, unregistered blocks will use the given material.
+    pub fn new(default: &'static Material) -> Self {
+        Self {
+            blocks: HashMap::new(),
+            default,
+        }
+    }
+
+    /// Register the material of a block, this resets previous overrides for this block.
+    pub fn register(&mut self, block: &'static Block, material: &'static Material) {
+        self.blocks.insert(block.get_key(), BlockMaterial {
+            material,
+            color: None,
+            opacity: None,
+            explosion_resistance: None
+        });
+    }
+
+    pub fn register_all(&mut self, blocks: &[&'static Block], material: &'static Material) {
+        for &block in blocks {
+            self.register(block, material);
+        }
+    }
+
+    /// Override the map color of a block, the block is registered with the default material
+    /// if not already registered.
+    pub fn set_map_color(&mut self, block: &'static Block, color: MapColor) {
+        self.ensure_block(block).color = Some(color);
+    }
+
+    /// Override the light opacity of a block, between 0 and 15.
+    pub fn set_opacity(&mut self, block: &'static Block, opacity: u8) {
+        debug_assert!(opacity <= 15);
+        self.ensure_block(block).opacity = Some(opacity);
+    }
+
+    /// Override the explosion resistance of a block.
+    pub fn set_explosion_resistance(&mut self, block: &'static Block, resistance: f32) {
+        debug_assert!(resistance >= 0.0);
+        self.ensure_block(block).explosion_resistance = Some(resistance);
+    }
+
+    fn ensure_block(&mut self, block: &'static Block) -> &mut BlockMaterial {
+        let default = self.default;
+        self.blocks.entry(block.get_key()).or_insert(BlockMaterial {
+            material: default,
+            color: None,
+            opacity: None,
+            explosion_resistance: None
+        })
+    }
+
+    #[inline]
+    pub fn has_block(&self, block: &'static Block) -> bool {
+        self.blocks.contains_key(&block.get_key())
+    }
+
+    pub fn get_material(&self, block: &'static Block) -> &'static Material {
+        self.blocks.get(&block.get_key()).map_or(self.default, |b| b.material)
+    }
+
+    pub fn is_solid(&self, block: &'static Block) -> bool {
+        self.get_material(block).solid
+    }
+
+    pub fn is_liquid(&self, block: &'static Block) -> bool {
+        self.get_material(block).liquid
+    }
+
+    pub fn is_flammable(&self, block: &'static Block) -> bool {
+        self.get_material(block).flammable
+    }
+
+    /// Return true if the block can be replaced when placing another block over it, like
+    /// air, tall grass or water.
+    pub fn is_replaceable(&self, block: &'static Block) -> bool {
+        self.get_material(block).replaceable
+    }
+
+    /// Return true if the block prevents entities from moving through it. This is used by
+    /// `MOTION_BLOCKING` heightmaps together with `is_liquid`.
+    pub fn blocks_motion(&self, block: &'static Block) -> bool {
+        self.get_material(block).blocks_motion
+    }
+
+    pub fn get_piston_reaction(&self, block: &'static Block) -> PistonReaction {
+        self.get_material(block).piston_reaction
+    }
+
+    pub fn get_map_color(&self, block: &'static Block) -> MapColor {
+        match self.blocks.get(&block.get_key()) {
+            Some(b) => b.color.unwrap_or(b.material.color),
+            None => self.default.color
+        }
+    }
+
+    /// Get the light opacity of a block, between 0 (transparent) and 15 (opaque). If not
+    /// overridden, this is 15 for solid blocking materials and 0 otherwise.
+    pub fn get_opacity(&self, block: &'static Block) -> u8 {
+        let (material, opacity) = match self.blocks.get(&block.get_key()) {
+            Some(b) => (b.material, b.opacity),
+            None => (self.default, None)
+        };
+        opacity.unwrap_or(if material.solid_blocking { 15 } else { 0 })
+    }
+
+    /// Get the explosion resistance of a block. If not overridden, this is 0 for non solid
+    /// materials, 100 for liquids and 1 otherwise.
+    pub fn get_explosion_resistance(&self, block: &'static Block) -> f32 {
+        let (material, resistance) = match self.blocks.get(&block.get_key()) {
+            Some(b) => (b.material, b.explosion_resistance),
+            None => (self.default, None)
+        };
+        resistance.unwrap_or(if material.liquid {
+            100.0
+        } else if material.solid {
+            1.0
+        } else {
+            0.0
+        })
+    }
+
+    /// Placement validation helper, return true if a block can be placed in place of the
+    /// given state.
+    pub fn can_place_over(&self, state: &'static BlockState) -> bool {
+        self.is_replaceable(state.get_block())
+    }
+
+    pub fn blocks_count(&self) -> usize {
+        self.blocks.len()
+    }
+
+}
+
+
+/// Vanilla block materials, this is lazily built on first use with `WithVanilla`.
+pub static VANILLA_BLOCK_MATERIALS: Lazy<BlockMaterials> = Lazy::new(BlockMaterials::with_vanilla);
+
+
+pub(crate) fn register_vanilla_materials(materials: &mut BlockMaterials) {
+
+    for &block in VANILLA_BLOCKS.iter() {
+
+        let name = block.get_name().strip_prefix("minecraft:").unwrap_or(block.get_name());
+        materials.register(block, get_vanilla_material(name));
+
+        if let Some(color) = get_vanilla_map_color(name) {
+            materials.set_map_color(block, color);
+        }
+
+        if let Some(opacity) = get_vanilla_opacity(name) {
+            materials.set_opacity(block, opacity);
+        }
+
+        if let Some(resistance) = get_vanilla_explosion_resistance(name) {
+            materials.set_explosion_resistance(block, resistance);
+        }
+
+    }
+
+}
+
+const DYE_PREFIXES: [(&str, DyeColor); 16] = [
+    ("white_", DyeColor::White),
+    ("orange_", DyeColor::Orange),
+    ("magenta_", DyeColor::Magenta),
+    ("light_blue_", DyeColor::LightBlue),
+    ("yellow_", DyeColor::Yellow),
+    ("lime_", DyeColor::Lime),
+    ("pink_", DyeColor::Pink),
+    ("gray_", DyeColor::Gray),
+    ("light_gray_", DyeColor::LightGray),
+    ("cyan_", DyeColor::Cyan),
+    ("purple_", DyeColor::Purple),
+    ("blue_", DyeColor::Blue),
+    ("brown_", DyeColor::Brown),
+    ("green_", DyeColor::Green),
+    ("red_", DyeColor::Red),
+    ("black_", DyeColor::Black),
+];
+
+const WOOD_PREFIXES: [&str; 7] = ["oak_", "spruce_", "birch_", "jungle_", "acacia_", "dark_oak_", "stripped_"];
+
+fn get_dye_color(name: &str) -> Option<DyeColor> {
+    // Longest prefixes must be tested first ("light_blue_" before "blue_").
+    DYE_PREFIXES.iter()
+        .filter(|(prefix, _)| name.starts_with(prefix))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|&(_, color)| color)
+}
+
+/// Classify a vanilla block from its name (without namespace) into its material.
+fn get_vanilla_material(name: &str) -> &'static Material {
+
+    let is_wood = WOOD_PREFIXES.iter().any(|prefix| name.starts_with(prefix));
+    let is_nether_wood = name.starts_with("crimson_") || name.starts_with("warped_") ||
+        name.starts_with("stripped_crimson_") || name.starts_with("stripped_warped_");
+
+    match name {
+        "air" | "cave_air" | "void_air" | "light" => &MAT_AIR,
+        "structure_void" => &MAT_STRUCTURAL_AIR,
+        "nether_portal" | "end_portal" | "end_gateway" => &MAT_PORTAL,
+        "water" => &MAT_WATER,
+        "bubble_column" => &MAT_BUBBLE_COLUMN,
+        "lava" => &MAT_LAVA,
+        "snow" => &MAT_TOP_SNOW,
+        "fire" | "soul_fire" => &MAT_FIRE,
+        "cobweb" => &MAT_WEB,
+        "powder_snow" => &MAT_POWDER_SNOW,
+        "barrier" => &MAT_BARRIER,
+        "piston" | "sticky_piston" | "piston_head" | "moving_piston" => &MAT_PISTON,
+        "tnt" => &MAT_EXPLOSIVE,
+        "cactus" => &MAT_CACTUS,
+        "bamboo_sapling" => &MAT_BAMBOO_SAPLING,
+        "bamboo" => &MAT_BAMBOO,
+        "sponge" | "wet_sponge" => &MAT_SPONGE,
+        "clay" | "slime_block" | "honey_block" | "honeycomb_block" => &MAT_CLAY,
+        "ice" | "frosted_ice" => &MAT_ICE,
+        "packed_ice" | "blue_ice" => &MAT_ICE_SOLID,
+        "snow_block" => &MAT_SNOW,
+        "redstone_lamp" => &MAT_BUILDABLE_GLASS,
+        "glass" | "glass_pane" | "glowstone" | "sea_lantern" | "beacon" => &MAT_GLASS,
+        "dragon_egg" | "turtle_egg" => &MAT_EGG,
+        "pumpkin" | "carved_pumpkin" | "jack_o_lantern" | "melon" => &MAT_VEGETABLE,
+        "moss_block" => &MAT_MOSS,
+        "grass_block" | "mycelium" | "hay_block" | "nether_wart_block" | "warped_wart_block" |
+        "shroomlight" | "dried_kelp_block" | "target" => &MAT_GRASS,
+        "dirt" | "coarse_dirt" | "podzol" | "farmland" | "dirt_path" | "rooted_dirt" => &MAT_DIRT,
+        "sand" | "red_sand" | "gravel" | "soul_sand" | "soul_soil" => &MAT_SAND,
+        "anvil" | "chipped_anvil" | "damaged_anvil" | "lodestone" => &MAT_HEAVY_METAL,
+        "iron_block" | "gold_block" | "diamond_block" | "emerald_block" | "netherite_block" |
+        "lapis_block" | "redstone_block" | "iron_door" | "iron_trapdoor" | "iron_bars" |
+        "chain" | "hopper" | "cauldron" | "water_cauldron" | "lava_cauldron" |
+        "powder_snow_cauldron" | "brewing_stand" | "lantern" | "soul_lantern" | "bell" |
+        "light_weighted_pressure_plate" | "heavy_weighted_pressure_plate" => &MAT_METAL,
+        "chest" | "trapped_chest" | "crafting_table" | "bookshelf" | "jukebox" | "note_block" |
+        "barrel" | "loom" | "composter" | "lectern" | "cartography_table" | "fletching_table" |
+        "smithing_table" | "campfire" | "soul_campfire" | "beehive" | "bee_nest" |
+        "daylight_detector" | "brown_mushroom_block" | "red_mushroom_block" | "mushroom_stem" => &MAT_WOOD,
+        "grass" | "fern" | "dead_bush" | "vine" | "glow_lichen" | "tall_grass" | "large_fern" |
+        "sunflower" | "lilac" | "rose_bush" | "peony" | "hanging_roots" => &MAT_REPLACEABLE_PLANT,
+        "warped_roots" | "crimson_roots" | "nether_sprouts" => &MAT_REPLACEABLE_FIREPROOF_PLANT,
+        "seagrass" | "tall_seagrass" => &MAT_REPLACEABLE_WATER_PLANT,
+        "kelp" | "kelp_plant" | "sea_pickle" => &MAT_WATER_PLANT,
+        "dandelion" | "poppy" | "blue_orchid" | "allium" | "azure_bluet" | "red_tulip" |
+        "orange_tulip" | "white_tulip" | "pink_tulip" | "oxeye_daisy" | "cornflower" |
+        "wither_rose" | "lily_of_the_valley" | "brown_mushroom" | "red_mushroom" | "wheat" |
+        "sugar_cane" | "attached_pumpkin_stem" | "attached_melon_stem" | "pumpkin_stem" |
+        "melon_stem" | "lily_pad" | "nether_wart" | "cocoa" | "carrots" | "potatoes" |
+        "chorus_plant" | "chorus_flower" | "beetroots" | "sweet_berry_bush" | "warped_fungus" |
+        "crimson_fungus" | "weeping_vines" | "weeping_vines_plant" | "twisting_vines" |
+        "twisting_vines_plant" | "cave_vines" | "cave_vines_plant" | "spore_blossom" | "azalea" |
+        "flowering_azalea" | "big_dripleaf" | "big_dripleaf_stem" | "small_dripleaf" => &MAT_PLANT,
+        "torch" | "wall_torch" | "soul_torch" | "soul_wall_torch" | "redstone_torch" |
+        "redstone_wall_torch" | "redstone_wire" | "ladder" | "lever" | "repeater" | "comparator" |
+        "tripwire" | "tripwire_hook" | "flower_pot" | "end_rod" | "scaffolding" | "rail" |
+        "powered_rail" | "detector_rail" | "activator_rail" | "stone_button" |
+        "polished_blackstone_button" => &MAT_DECORATION,
+        _ => {
+            if name.ends_with("_leaves") {
+                &MAT_LEAVES
+            } else if name.ends_with("_wool") || name.ends_with("_bed") {
+                &MAT_WOOL
+            } else if name.ends_with("_carpet") && name != "moss_carpet" {
+                &MAT_CLOTH_DECORATION
+            } else if name == "moss_carpet" {
+                &MAT_PLANT
+            } else if name.ends_with("_stained_glass") || name.ends_with("_stained_glass_pane") {
+                &MAT_GLASS
+            } else if name.ends_with("_concrete_powder") {
+                &MAT_SAND
+            } else if name.ends_with("shulker_box") {
+                &MAT_SHULKER_SHELL
+            } else if name.ends_with("candle_cake") || name == "cake" {
+                &MAT_CAKE
+            } else if name.ends_with("candle") || name.starts_with("potted_") ||
+                name.ends_with("_head") || name.ends_with("_skull") || name.ends_with("_button") {
+                &MAT_DECORATION
+            } else if name.contains("coral") && !name.ends_with("_block") {
+                &MAT_WATER_PLANT
+            } else if name.ends_with("_banner") || name.ends_with("_sign") {
+                if is_nether_wood { &MAT_NETHER_WOOD } else { &MAT_WOOD }
+            } else if is_nether_wood && !name.ends_with("_nylium") {
+                &MAT_NETHER_WOOD
+            } else if is_wood && !name.ends_with("_sapling") {
+                &MAT_WOOD
+            } else if name.ends_with("_sapling") {
+                &MAT_PLANT
+            } else if name.contains("amethyst") {
+                &MAT_AMETHYST
+            } else if name.starts_with("sculk") {
+                &MAT_SCULK
+            } else {
+                &MAT_STONE
+            }
+        }
+    }
+
+}
+
+/// Get the map color of a vanilla block if it differs from its material's color.
+fn get_vanilla_map_color(name: &str) -> Option<MapColor> {
+
+    const DYED_SUFFIXES: [&str; 9] = [
+        "_wool", "_carpet", "_concrete", "_concrete_powder", "_stained_glass",
+        "_stained_glass_pane", "_shulker_box", "_candle", "_bed"
+    ];
+
+    if let Some(color) = get_dye_color(name) {
+        if name.ends_with("_glazed_terracotta") || DYED_SUFFIXES.iter().any(|suffix| name.ends_with(suffix)) {
+            return Some(MapColor::from_dye(color));
+        } else if name.ends_with("_terracotta") {
+            return Some(MapColor::from_terracotta(color));
+        }
+    }
+
+    Some(match name {
+        "terracotta" => MapColor::COLOR_ORANGE,
+        "gold_block" => MapColor::GOLD,
+        "diamond_block" => MapColor::DIAMOND,
+        "lapis_block" => MapColor::LAPIS,
+        "emerald_block" => MapColor::EMERALD,
+        "redstone_block" | "tnt" | "fire" => MapColor::FIRE,
+        "podzol" => MapColor::PODZOL,
+        "red_sand" => MapColor::COLOR_ORANGE,
+        "netherrack" | "nether_bricks" | "nether_gold_ore" | "nether_quartz_ore" => MapColor::NETHER,
+        "quartz_block" | "chiseled_quartz_block" | "quartz_pillar" | "quartz_bricks" |
+        "smooth_quartz" | "diorite" | "polished_diorite" => MapColor::QUARTZ,
+        "crimson_nylium" => MapColor::CRIMSON_NYLIUM,
+        "crimson_stem" | "stripped_crimson_stem" => MapColor::CRIMSON_STEM,
+        "crimson_hyphae" | "stripped_crimson_hyphae" => MapColor::CRIMSON_HYPHAE,
+        "warped_nylium" => MapColor::WARPED_NYLIUM,
+        "warped_stem" | "stripped_warped_stem" => MapColor::WARPED_STEM,
+        "warped_hyphae" | "stripped_warped_hyphae" => MapColor::WARPED_HYPHAE,
+        "warped_wart_block" => MapColor::WARPED_WART_BLOCK,
+        "glow_lichen" => MapColor::GLOW_LICHEN,
+        "raw_iron_block" => MapColor::RAW_IRON,
+        "obsidian" | "crying_obsidian" | "coal_block" | "blackstone" => MapColor::COLOR_BLACK,
+        _ if name.starts_with("deepslate") || name.contains("_deepslate") => MapColor::DEEPSLATE,
+        _ if name.starts_with("spruce_") => MapColor::PODZOL,
+        _ if name.starts_with("birch_") => MapColor::SAND,
+        _ if name.starts_with("jungle_") => MapColor::DIRT,
+        _ if name.starts_with("acacia_") => MapColor::COLOR_ORANGE,
+        _ if name.starts_with("dark_oak_") => MapColor::COLOR_BROWN,
+        _ => return None
+    })
+
+}
+
+/// Get the light opacity of a vanilla block if it differs from its material's default.
+fn get_vanilla_opacity(name: &str) -> Option<u8> {
+    match name {
+        "water" | "bubble_column" | "ice" | "frosted_ice" | "cobweb" => Some(1),
+        _ if name.ends_with("_leaves") => Some(1),
+        "spawner" | "glowstone" | "sea_lantern" | "beacon" | "farmland" | "dirt_path" |
+        "iron_bars" | "chain" | "hopper" | "cauldron" | "water_cauldron" | "lava_cauldron" |
+        "powder_snow_cauldron" | "brewing_stand" | "lantern" | "soul_lantern" | "bell" |
+        "anvil" | "chipped_anvil" | "damaged_anvil" | "chest" | "trapped_chest" | "ender_chest" |
+        "enchanting_table" | "end_portal_frame" | "daylight_detector" | "campfire" |
+        "soul_campfire" | "lectern" | "grindstone" | "stonecutter" | "conduit" | "composter" |
+        "cake" | "dragon_egg" | "turtle_egg" | "slime_block" | "honey_block" |
+        "heavy_weighted_pressure_plate" | "light_weighted_pressure_plate" => Some(0),
+        _ if name.ends_with("_slab") || name.ends_with("_stairs") || name.ends_with("_fence") ||
+            name.ends_with("_fence_gate") || name.ends_with("_wall") || name.ends_with("_door") ||
+            name.ends_with("_trapdoor") || name.ends_with("_pane") || name.ends_with("_bed") ||
+            name.ends_with("_banner") || name.ends_with("_sign") || name.ends_with("_pressure_plate") ||
+            name.ends_with("shulker_box") || name.ends_with("candle_cake") => Some(0),
+        _ => None
+    }
+}
+
+/// Get the explosion resistance of a vanilla block if it differs from its material's default.
+fn get_vanilla_explosion_resistance(name: &str) -> Option<f32> {
+    Some(match name {
+        "bedrock" | "barrier" | "end_portal" | "end_portal_frame" | "end_gateway" |
+        "command_block" | "chain_command_block" | "repeating_command_block" | "structure_block" |
+        "jigsaw" | "moving_piston" => 3600000.0,
+        "obsidian" | "crying_obsidian" | "respawn_anchor" | "ender_chest" | "anvil" |
+        "chipped_anvil" | "damaged_anvil" | "netherite_block" | "ancient_debris" |
+        "enchanting_table" => 1200.0,
+        "end_stone" | "end_stone_bricks" => 9.0,
+        "iron_door" | "iron_trapdoor" | "iron_bars" | "chain" | "iron_block" | "gold_block" |
+        "diamond_block" | "emerald_block" | "redstone_block" | "coal_block" | "spawner" |
+        "bell" | "conduit" | "lodestone" | "stone" | "cobblestone" | "mossy_cobblestone" |
+        "bricks" | "stone_bricks" | "mossy_stone_bricks" | "cracked_stone_bricks" |
+        "chiseled_stone_bricks" | "granite" | "polished_granite" | "diorite" | "polished_diorite" |
+        "andesite" | "polished_andesite" | "nether_bricks" | "red_nether_bricks" | "blackstone" |
+        "polished_blackstone" | "polished_blackstone_bricks" | "purpur_block" | "purpur_pillar" |
+        "prismarine" | "prismarine_bricks" | "dark_prismarine" | "deepslate" |
+        "cobbled_deepslate" | "polished_deepslate" | "deepslate_bricks" | "deepslate_tiles" |
+        "hopper" | "stonecutter" => 6.0,
+        "terracotta" | "basalt" | "polished_basalt" | "smooth_basalt" => 4.2,
+        "furnace" | "blast_furnace" | "smoker" => 3.5,
+        "coal_ore" | "iron_ore" | "gold_ore" | "diamond_ore" | "emerald_ore" | "lapis_ore" |
+        "redstone_ore" | "copper_ore" | "nether_gold_ore" | "nether_quartz_ore" => 3.0,
+        "chest" | "trapped_chest" | "crafting_table" | "barrel" => 2.5,
+        "blue_ice" => 2.8,
+        "bookshelf" | "lectern" => 1.5,
+        "cobweb" => 4.0,
+        "pumpkin" | "carved_pumpkin" | "jack_o_lantern" | "melon" | "note_block" => 1.0,
+        "sandstone" | "red_sandstone" | "quartz_block" | "quartz_pillar" |
+        "chiseled_quartz_block" => 0.8,
+        "clay" | "gravel" | "grass_block" | "mycelium" | "podzol" | "farmland" | "dirt_path" |
+        "sponge" | "wet_sponge" => 0.6,
+        "sand" | "red_sand" | "dirt" | "coarse_dirt" | "rooted_dirt" | "soul_sand" | "soul_soil" |
+        "ice" | "packed_ice" | "frosted_ice" | "hay_block" | "cake" => 0.5,
+        "netherrack" | "crimson_nylium" | "warped_nylium" => 0.4,
+        "glass" | "glass_pane" | "glowstone" | "sea_lantern" | "redstone_lamp" | "beacon" => 0.3,
+        "snow_block" => 0.2,
+        "tnt" | "slime_block" | "honey_block" => 0.0,
+        _ if name.starts_with("deepslate_") && name.ends_with("_ore") => 3.0,
+        _ if name.ends_with("_concrete") => 1.8,
+        _ if name.ends_with("_glazed_terracotta") => 1.4,
+        _ if name.ends_with("_terracotta") => 4.2,
+        _ if name.ends_with("_wool") => 0.8,
+        _ if name.ends_with("_bed") => 0.2,
+        _ if name.ends_with("_leaves") => 0.2,
+        _ if name.ends_with("_stained_glass") || name.ends_with("_stained_glass_pane") => 0.3,
+        _ if name.ends_with("_concrete_powder") => 0.5,
+        _ if name.ends_with("_log") || name.ends_with("_wood") || name.ends_with("_hyphae") ||
+            name.ends_with("crimson_stem") || name.ends_with("warped_stem") => 2.0,
+        _ if name.ends_with("_planks") || name.ends_with("_fence") || name.ends_with("_fence_gate") => 3.0,
+        _ if name.ends_with("_slab") || name.ends_with("_stairs") || name.ends_with("_wall") => {
+            if name.contains("sandstone") || name.contains("quartz") { 0.8 } else { 6.0 }
+        }
+        _ => return None
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::heightmap::{OCEAN_FLOOR, MOTION_BLOCKING, MOTION_BLOCKING_NO_LEAVES};
+
+    use super::*;
+
+    #[test]
+    fn vanilla_materials() {
+
+        let materials = &*VANILLA_BLOCK_MATERIALS;
+        assert_eq!(materials.blocks_count(), VANILLA_BLOCKS.len());
+
+        assert!(std::ptr::eq(materials.get_material(&AIR), &MAT_AIR));
+        assert!(std::ptr::eq(materials.get_material(&OAK_PLANKS), &MAT_WOOD));
+        assert!(std::ptr::eq(materials.get_material(&CRIMSON_PLANKS), &MAT_NETHER_WOOD));
+        assert!(std::ptr::eq(materials.get_material(&OAK_LEAVES), &MAT_LEAVES));
+        assert!(std::ptr::eq(materials.get_material(&RED_SAND), &MAT_SAND));
+        assert!(std::ptr::eq(materials.get_material(&STONE), &MAT_STONE));
+
+        assert!(materials.is_liquid(&WATER) && materials.is_replaceable(&WATER));
+        assert!(materials.is_replaceable(&GRASS) && materials.is_flammable(&GRASS));
+        assert!(!materials.is_replaceable(&STONE) && materials.is_solid(&STONE));
+        assert!(!materials.is_flammable(&CRIMSON_STEM));
+        assert!(materials.can_place_over(AIR.get_default_state()));
+
+        assert_eq!(materials.get_piston_reaction(&OBSIDIAN), PistonReaction::Normal);
+        assert_eq!(materials.get_piston_reaction(&PISTON_HEAD), PistonReaction::Block);
+        assert_eq!(materials.get_piston_reaction(&POPPY), PistonReaction::Destroy);
+
+        assert_eq!(materials.get_opacity(&STONE), 15);
+        assert_eq!(materials.get_opacity(&GLASS), 0);
+        assert_eq!(materials.get_opacity(&WATER), 1);
+        assert_eq!(materials.get_opacity(&OAK_SLAB), 0);
+
+        assert_eq!(materials.get_map_color(&GRASS_BLOCK), MapColor::GRASS);
+        assert_eq!(materials.get_map_color(&WHITE_WOOL), MapColor::SNOW);
+        assert_eq!(materials.get_map_color(&LIGHT_BLUE_WOOL), MapColor::COLOR_LIGHT_BLUE);
+        assert_eq!(materials.get_map_color(&RED_TERRACOTTA).get_rgb(), 0x8E3C2E);
+        assert_eq!(materials.get_map_color(&LIGTH_BLUE_TERRACOTTA), MapColor::from_terracotta(DyeColor::LightBlue));
+        assert_eq!(materials.get_map_color(&RED_SAND), MapColor::COLOR_ORANGE);
+
+        assert_eq!(materials.get_explosion_resistance(&AIR), 0.0);
+        assert_eq!(materials.get_explosion_resistance(&WATER), 100.0);
+        assert_eq!(materials.get_explosion_resistance(&STONE), 6.0);
+        assert_eq!(materials.get_explosion_resistance(&OBSIDIAN), 1200.0);
+        assert_eq!(materials.get_explosion_resistance(&OAK_LOG), 2.0);
+        assert_eq!(materials.get_explosion_resistance(&POPPY), 0.0);
+
+        // Heightmaps are built from the materials.
+        let blocks = GlobalBlocks::with_vanilla();
+        assert!(!OCEAN_FLOOR.check_block(WATER.get_default_state(), &blocks));
+        assert!(MOTION_BLOCKING.check_block(WATER.get_default_state(), &blocks));
+        assert!(!MOTION_BLOCKING.check_block(GRASS.get_default_state(), &blocks));
+        assert!(MOTION_BLOCKING.check_block(OAK_LEAVES.get_default_state(), &blocks));
+        assert!(!MOTION_BLOCKING_NO_LEAVES.check_block(OAK_LEAVES.get_default_state(), &blocks));
+
+    }
+
+}
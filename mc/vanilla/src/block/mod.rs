@@ -4,6 +4,16 @@ use mc_core::pos::{Axis, Direction};
 use crate::util::DyeColor;
 
 pub mod material;
+pub mod light;
+pub mod fluid;
+pub mod color;
+pub mod legacy;
+pub mod sky;
+pub mod interaction;
+pub mod contact;
+pub mod tick;
+pub mod beehive;
+pub mod lectern;
 
 
 impl_enum_serializable!(DyeColor {
@@ -0,0 +1,141 @@
+//! Behaviours of blocks depending on the sky, daylight detectors are powered depending on
+//! the sky light and on the time of day, and beacons need an access to the sky.
+
+use mc_core::world::chunk::{Chunk, Light};
+
+use crate::time::LevelTime;
+use super::material::BlockMaterials;
+use super::{DAYLIGHT_DETECTOR, BEDROCK, PROP_INVERTED, PROP_REDSTONE_POWER};
+
+
+/// Compute the redstone power of a daylight detector from the sky light at its position,
+/// the sky darkening and the sun angle, like vanilla. Inverted detectors are powered from
+/// the darkness instead.
+pub fn get_daylight_power(sky_light: u8, sky_darken: u8, sun_angle: f32, inverted: bool) -> u8 {
+    let mut power = sky_light as i32 - sky_darken as i32;
+    if inverted {
+        power = 15 - power;
+    } else if power > 0 {
+        // The sun angle is pulled toward noon to extend the powered period.
+        let noon_angle = if sun_angle < std::f32::consts::PI { 0.0 } else { std::f32::consts::PI * 2.0 };
+        let angle = sun_angle + (noon_angle - sun_angle) * 0.2;
+        power = (power as f32 * angle.cos()).round() as i32;
+    }
+    power.clamp(0, 15) as u8
+}
+
+
+/// Update the power of the daylight detector at the given position in the chunk, the sky
+/// darkening must be computed from the level time and weather. Vanilla updates detectors
+/// every 20 ticks. Return true if the power of the detector has changed.
+pub fn update_daylight_detector(chunk: &mut Chunk, x: i32, y: i32, z: i32, time: &LevelTime, sky_darken: u8) -> bool {
+
+    let state = match chunk.get_block_at(x, y, z) {
+        Ok(state) if state.get_block() == &DAYLIGHT_DETECTOR => state,
+        _ => return false
+    };
+
+    let sky_light = chunk.get_light_at(x, y, z, Light::Sky).unwrap_or(0);
+    let inverted = state.expect(&PROP_INVERTED);
+    let power = get_daylight_power(sky_light, sky_darken, time.get_sun_angle(), inverted);
+
+    if state.expect::<u8, _>(&PROP_REDSTONE_POWER) == power {
+        return false;
+    }
+
+    let new_state = state.with(&PROP_REDSTONE_POWER, power).unwrap();
+    chunk.set_block_at(x, y, z, new_state).is_ok()
+
+}
+
+
+/// Return true if the beam of a beacon at the given position can reach the top of the chunk,
+/// the beam is stopped by fully opaque blocks excepted bedrock, like vanilla.
+pub fn has_beacon_sky_access(chunk: &Chunk, x: i32, y: i32, z: i32, materials: &BlockMaterials) -> bool {
+    let max_y = chunk.get_height().get_max_block();
+    let mut by = y + 1;
+    while by <= max_y {
+        if chunk.is_sub_chunk_empty_at(by) {
+            // Empty sub chunks only contain air, the beam goes through the whole sub chunk.
+            by = (by | 15) + 1;
+            continue;
+        }
+        if let Ok(state) = chunk.get_block_at(x, by, z) {
+            let block = state.get_block();
+            if block != &BEDROCK && materials.get_opacity(block) >= 15 {
+                return false;
+            }
+        }
+        by += 1;
+    }
+    true
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::source::ChunkLoadRequest;
+    use mc_core::world::level::LevelEnv;
+
+    use crate::block::material::VANILLA_BLOCK_MATERIALS;
+    use crate::block::{BEACON, STONE, GLASS};
+    use crate::ext::VanillaLevelEnv;
+    use super::*;
+
+    #[test]
+    fn daylight_detector() {
+
+        let noon = LevelTime::new().with_day_time(6000);
+        let midnight = LevelTime::new().with_day_time(18000);
+
+        let darken = noon.get_sky_darken(0.0, 0.0);
+        assert_eq!(get_daylight_power(15, darken, noon.get_sun_angle(), false), 15);
+        assert_eq!(get_daylight_power(15, darken, noon.get_sun_angle(), true), 0);
+        assert_eq!(get_daylight_power(7, darken, noon.get_sun_angle(), false), 7);
+
+        let darken = midnight.get_sky_darken(0.0, 0.0);
+        assert_eq!(get_daylight_power(15, darken, midnight.get_sun_angle(), false), 0);
+        assert_eq!(get_daylight_power(15, darken, midnight.get_sun_angle(), true), 11);
+        assert_eq!(get_daylight_power(0, darken, midnight.get_sun_angle(), true), 15);
+
+        let mut chunk = ChunkLoadRequest {
+            env: LevelEnv::vanilla(),
+            height: ChunkHeight::new(0, 3),
+            cx: 0,
+            cz: 0
+        }.build_chunk();
+
+        chunk.set_block_at(3, 10, 3, DAYLIGHT_DETECTOR.get_default_state()).unwrap();
+        chunk.get_sub_chunk_mut(0).unwrap().set_light(3, 10, 3, Light::Sky, 15);
+
+        assert!(update_daylight_detector(&mut chunk, 3, 10, 3, &noon, 0));
+        assert!(!update_daylight_detector(&mut chunk, 3, 10, 3, &noon, 0));
+        assert_eq!(chunk.get_block_at(3, 10, 3).unwrap().expect::<u8, _>(&PROP_REDSTONE_POWER), 15);
+        assert!(!update_daylight_detector(&mut chunk, 4, 10, 3, &noon, 0));
+
+    }
+
+    #[test]
+    fn beacon_sky_access() {
+
+        let mut chunk = ChunkLoadRequest {
+            env: LevelEnv::vanilla(),
+            height: ChunkHeight::new(0, 3),
+            cx: 0,
+            cz: 0
+        }.build_chunk();
+
+        chunk.set_block_at(3, 10, 3, BEACON.get_default_state()).unwrap();
+        chunk.set_block_at(3, 20, 3, GLASS.get_default_state()).unwrap();
+        chunk.set_block_at(3, 30, 3, BEDROCK.get_default_state()).unwrap();
+        assert!(has_beacon_sky_access(&chunk, 3, 10, 3, &VANILLA_BLOCK_MATERIALS));
+
+        chunk.set_block_at(3, 40, 3, STONE.get_default_state()).unwrap();
+        assert!(!has_beacon_sky_access(&chunk, 3, 10, 3, &VANILLA_BLOCK_MATERIALS));
+        assert!(has_beacon_sky_access(&chunk, 3, 41, 3, &VANILLA_BLOCK_MATERIALS));
+
+    }
+
+}
@@ -0,0 +1,743 @@
+//! Random ticks of blocks whose state changes over time without a block entity. Each tick,
+//! a few random blocks of every loaded sub chunk are picked, like the `randomTickSpeed`
+//! game rule, and the behaviour registered for their block in the `RandomTicks` world
+//! component is run by `system_random_ticks`.
+//!
+//! Vanilla behaviours cover turtle eggs hatching on sand, snow layers and ice melting under
+//! block light, water evaporating from cauldrons in ultrawarm levels and vines spreading.
+//! Living entities walking on turtle eggs also crack them. Glow lichen doesn't tick, it is
+//! spread by bone meal with `spread_glow_lichen`.
+
+use std::collections::HashMap;
+
+use mc_core::block::{Block, BlockKey, BlockState, BoolProperty};
+use mc_core::world::level::{Level, BaseEntity};
+use mc_core::world::chunk::Light;
+use mc_core::pos::{BlockPos, EntityPos, Direction, Axis};
+use mc_core::rand::JavaRandom;
+
+use mc_runtime::system::{TickBudgets, WorkQueue};
+use mc_runtime::world::World;
+
+use crate::block::*;
+use crate::block::material::VANILLA_BLOCK_MATERIALS;
+use crate::entity::{LivingEntity, BreedableEntity, TurtleEntity, Age, TURTLE, BAT};
+use crate::entity::breeding::BABY_GROW_TICKS;
+use crate::entity::physics::{get_block, get_block_pos};
+use crate::death::Dead;
+use crate::sleep::SleepTracker;
+use crate::time::LevelTime;
+
+
+/// Default number of blocks randomly ticked per sub chunk and per tick.
+pub const DEFAULT_RANDOM_TICK_SPEED: u32 = 3;
+/// Snow layers and ice melt above this block light, minus the opacity of ice.
+pub const MELT_LIGHT: u8 = 11;
+/// Maximum number of vines around a vine for it to spread horizontally or upward.
+pub const MAX_VINES_AROUND: usize = 4;
+
+
+/// The block randomly ticked, given to `RandomTickBehaviour::random_tick`.
+pub struct RandomTickContext<'a> {
+    pub level: &'a mut Level,
+    pub pos: BlockPos,
+    pub state: &'static BlockState,
+    /// The time of the level, if the level is tracked by the `SleepTracker`.
+    pub time: Option<&'a LevelTime>,
+    pub rand: &'a mut JavaRandom
+}
+
+impl<'a> RandomTickContext<'a> {
+
+    /// Replace the ticked block state, this does nothing if the chunk is not loaded.
+    pub fn set_state(&self, state: &'static BlockState) {
+        let _ = self.level.chunks.set_block_at(self.pos.x, self.pos.y, self.pos.z, state);
+    }
+
+    /// Return the block light at the ticked block.
+    pub fn get_block_light(&self) -> u8 {
+        self.level.chunks.get_chunk_at(self.pos.x, self.pos.z)
+            .and_then(|chunk| chunk.get_light_at(self.pos.x, self.pos.y, self.pos.z, Light::Block).ok())
+            .unwrap_or(0)
+    }
+
+}
+
+
+/// A behaviour of a block when randomly ticked.
+pub trait RandomTickBehaviour {
+
+    /// Tick the block, the state of the context is the current state of the block.
+    fn random_tick(&self, ctx: &mut RandomTickContext);
+
+}
+
+
+/// World component registering the random tick behaviours of blocks, unregistered blocks
+/// are never ticked.
+pub struct RandomTicks {
+    blocks: HashMap<BlockKey, &'static dyn RandomTickBehaviour>,
+    speed: u32,
+    rand: JavaRandom,
+    /// Chunks waiting for their random ticks in each level, by level index.
+    queues: Vec<WorkQueue<(i32, i32)>>
+}
+
+impl RandomTicks {
+
+    pub fn new() -> Self {
+        Self {
+            blocks: HashMap::new(),
+            speed: DEFAULT_RANDOM_TICK_SPEED,
+            rand: JavaRandom::new_seeded(),
+            queues: Vec::new()
+        }
+    }
+
+    pub fn with_seed(mut self, seed: i64) -> Self {
+        self.rand = JavaRandom::new(seed);
+        self
+    }
+
+    /// Set the number of blocks ticked per sub chunk and per tick, like the
+    /// `randomTickSpeed` game rule, zero disables random ticks.
+    pub fn with_speed(mut self, speed: u32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    #[inline]
+    pub fn get_speed(&self) -> u32 {
+        self.speed
+    }
+
+    /// Register the behaviour of a block, replacing any previous behaviour.
+    pub fn register(&mut self, block: &'static Block, behaviour: &'static dyn RandomTickBehaviour) {
+        self.blocks.insert(block.get_key(), behaviour);
+    }
+
+    pub fn register_all(&mut self, blocks: &[&'static Block], behaviour: &'static dyn RandomTickBehaviour) {
+        for &block in blocks {
+            self.register(block, behaviour);
+        }
+    }
+
+    pub fn get(&self, block: &'static Block) -> Option<&'static dyn RandomTickBehaviour> {
+        self.blocks.get(&block.get_key()).copied()
+    }
+
+    /// Tick random blocks of each sub chunk of the given chunk, if loaded.
+    fn tick_chunk(&mut self, level: &mut Level, time: Option<&LevelTime>, cx: i32, cz: i32) {
+
+        // Positions are picked first, so the chunk is not locked while ticking.
+        let mut picked = Vec::new();
+        if let Some(chunk) = level.chunks.get_chunk(cx, cz) {
+            for (cy, sub_chunk) in chunk.iter_sub_chunks() {
+                if sub_chunk.is_none() {
+                    continue;
+                }
+                for _ in 0..self.speed {
+                    let x = cx * 16 + self.rand.next_int_bounded(16);
+                    let y = cy as i32 * 16 + self.rand.next_int_bounded(16);
+                    let z = cz * 16 + self.rand.next_int_bounded(16);
+                    if let Ok(state) = chunk.get_block_at(x, y, z) {
+                        if self.blocks.contains_key(&state.get_block().get_key()) {
+                            picked.push(BlockPos::new(x, y, z));
+                        }
+                    }
+                }
+            }
+        }
+
+        for pos in picked {
+
+            // Blocks may have been changed by previous ticks.
+            let state = match get_block(level, &pos) {
+                Some(state) => state,
+                None => continue
+            };
+
+            if let Some(behaviour) = self.get(state.get_block()) {
+                behaviour.random_tick(&mut RandomTickContext {
+                    level,
+                    pos,
+                    state,
+                    time,
+                    rand: &mut self.rand
+                });
+            }
+
+        }
+
+    }
+
+}
+
+impl Default for RandomTicks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// Turtle eggs, their hatch stage grows on sand before dawn or rarely at other times, the
+/// turtles hatch after the third stage.
+pub struct TurtleEggBehaviour;
+
+impl RandomTickBehaviour for TurtleEggBehaviour {
+
+    fn random_tick(&self, ctx: &mut RandomTickContext) {
+
+        // Eggs hatch faster shortly before dawn.
+        let dawn = ctx.time.is_some_and(|time| {
+            let time_of_day = time.get_time_of_day();
+            time_of_day > 0.65 && time_of_day < 0.69
+        });
+
+        if !dawn && ctx.rand.next_int_bounded(500) != 0 {
+            return;
+        }
+
+        if !get_block(ctx.level, &ctx.pos.below(1)).is_some_and(|below| is_sand(below.get_block())) {
+            return;
+        }
+
+        let hatch: u8 = ctx.state.expect(&PROP_HATCH);
+        if hatch < 2 {
+            ctx.set_state(ctx.state.with(&PROP_HATCH, hatch + 1).unwrap());
+        } else {
+            ctx.set_state(AIR.get_default_state());
+            let eggs: u8 = ctx.state.expect(&PROP_EGGS);
+            for i in 0..eggs {
+                let pos = EntityPos::new(ctx.pos.x as f64 + 0.3 + i as f64 * 0.2, ctx.pos.y as f64, ctx.pos.z as f64 + 0.3);
+                if let Some(turtle) = ctx.level.spawn_entity(&TURTLE, pos) {
+                    let ecs = &mut ctx.level.entities.ecs;
+                    if let Ok(mut breedable) = ecs.get_mut::<BreedableEntity>(turtle) {
+                        breedable.set_age(Age::Baby { ticks_remaining: BABY_GROW_TICKS, breed_cooldown_once_adult: None });
+                    }
+                    if let Ok(mut turtle) = ecs.get_mut::<TurtleEntity>(turtle) {
+                        turtle.set_home_pos(ctx.pos.clone());
+                    }
+                }
+            }
+        }
+
+    }
+
+}
+
+
+/// Snow layers, melted by block light.
+pub struct SnowLayerBehaviour;
+
+impl RandomTickBehaviour for SnowLayerBehaviour {
+
+    fn random_tick(&self, ctx: &mut RandomTickContext) {
+        if ctx.get_block_light() > MELT_LIGHT {
+            ctx.set_state(AIR.get_default_state());
+        }
+    }
+
+}
+
+
+/// Ice, melted by block light into water, or removed in ultrawarm levels.
+pub struct IceBehaviour;
+
+impl RandomTickBehaviour for IceBehaviour {
+
+    fn random_tick(&self, ctx: &mut RandomTickContext) {
+        let opacity = VANILLA_BLOCK_MATERIALS.get_opacity(ctx.state.get_block());
+        if ctx.get_block_light() > MELT_LIGHT.saturating_sub(opacity) {
+            if ctx.level.get_dimension_type().ultrawarm {
+                ctx.set_state(AIR.get_default_state());
+            } else {
+                ctx.set_state(WATER.get_default_state());
+            }
+        }
+    }
+
+}
+
+
+/// Water cauldrons, their water evaporates one level at a time in ultrawarm levels, where
+/// water can't be placed.
+pub struct WaterCauldronBehaviour;
+
+impl RandomTickBehaviour for WaterCauldronBehaviour {
+
+    fn random_tick(&self, ctx: &mut RandomTickContext) {
+        if ctx.level.get_dimension_type().ultrawarm {
+            let level: u8 = ctx.state.expect(&PROP_CAULDRON_LEVEL);
+            if level > 1 {
+                ctx.set_state(ctx.state.with(&PROP_CAULDRON_LEVEL, level - 1).unwrap());
+            } else {
+                ctx.set_state(CAULDRON.get_default_state());
+            }
+        }
+    }
+
+}
+
+
+/// Vines, they spread to the sides along walls, upward and downward. Vines don't spread
+/// horizontally or upward if there are already too many vines around.
+pub struct VineBehaviour;
+
+impl RandomTickBehaviour for VineBehaviour {
+
+    fn random_tick(&self, ctx: &mut RandomTickContext) {
+
+        if ctx.rand.next_int_bounded(4) != 0 {
+            return;
+        }
+
+        let level = &*ctx.level;
+        let (pos, state) = (&ctx.pos, ctx.state);
+        let direction = RANDOM_DIRECTIONS[ctx.rand.next_int_bounded(6) as usize];
+        let above = pos.above(1);
+
+        if direction.axis() != Axis::Y && !state.expect(get_face_property(direction)) {
+
+            if !can_vine_spread(level, pos) {
+                return;
+            }
+
+            let target = pos.relative(direction, 1);
+            if is_air_at(level, &target) {
+
+                let (cw, ccw) = (get_clockwise(direction), get_clockwise(direction).opposite());
+                let (has_cw, has_ccw) = (state.expect(get_face_property(cw)), state.expect(get_face_property(ccw)));
+                let (target_cw, target_ccw) = (target.relative(cw, 1), target.relative(ccw, 1));
+                let opposite = direction.opposite();
+
+                // The vine turns around corners or continues along the wall it grows on.
+                let spread = if has_cw && is_acceptable_neighbour(level, &target_cw) {
+                    Some((target, cw))
+                } else if has_ccw && is_acceptable_neighbour(level, &target_ccw) {
+                    Some((target, ccw))
+                } else if has_cw && is_air_at(level, &target_cw) && is_acceptable_neighbour(level, &pos.relative(cw, 1)) {
+                    Some((target_cw, opposite))
+                } else if has_ccw && is_air_at(level, &target_ccw) && is_acceptable_neighbour(level, &pos.relative(ccw, 1)) {
+                    Some((target_ccw, opposite))
+                } else if ctx.rand.next_float() < 0.05 && is_acceptable_neighbour(level, &target.above(1)) {
+                    Some((target, Direction::Up))
+                } else {
+                    None
+                };
+
+                if let Some((spread_pos, face)) = spread {
+                    let new_state = VINE.get_default_state().with(get_face_property(face), true).unwrap();
+                    let _ = level.chunks.set_block_at(spread_pos.x, spread_pos.y, spread_pos.z, new_state);
+                }
+
+            } else if is_acceptable_neighbour(level, &target) {
+                ctx.set_state(state.with(get_face_property(direction), true).unwrap());
+            }
+
+            return;
+
+        }
+
+        if direction == Direction::Up && pos.y < level.get_height().get_max_block() {
+
+            if is_acceptable_neighbour(level, &above) {
+                ctx.set_state(state.with(&PROP_UP, true).unwrap());
+                return;
+            }
+
+            if is_air_at(level, &above) {
+                if !can_vine_spread(level, pos) {
+                    return;
+                }
+                let mut new_state = state;
+                for direction in HORIZONTAL_DIRECTIONS {
+                    if ctx.rand.next_boolean() || !is_acceptable_neighbour(level, &above.relative(direction, 1)) {
+                        new_state = new_state.with(get_face_property(direction), false).unwrap();
+                    }
+                }
+                if has_horizontal_face(new_state) {
+                    let _ = level.chunks.set_block_at(above.x, above.y, above.z, new_state);
+                }
+                return;
+            }
+
+        }
+
+        if pos.y > level.get_height().get_min_block() {
+            let below = pos.below(1);
+            let below_state = match get_block(level, &below) {
+                Some(state) => state,
+                None => return
+            };
+            let base = if is_air(below_state.get_block()) {
+                VINE.get_default_state()
+            } else if below_state.is_block(&VINE) {
+                below_state
+            } else {
+                return;
+            };
+            let mut new_state = base;
+            for direction in HORIZONTAL_DIRECTIONS {
+                let prop = get_face_property(direction);
+                if ctx.rand.next_boolean() && state.expect(prop) {
+                    new_state = new_state.with(prop, true).unwrap();
+                }
+            }
+            if !std::ptr::eq(new_state, base) && has_horizontal_face(new_state) {
+                let _ = level.chunks.set_block_at(below.x, below.y, below.z, new_state);
+            }
+        }
+
+    }
+
+}
+
+
+/// Order of directions picked randomly by vanilla.
+static RANDOM_DIRECTIONS: [Direction; 6] = [
+    Direction::Down, Direction::Up, Direction::North, Direction::South, Direction::West, Direction::East
+];
+
+static HORIZONTAL_DIRECTIONS: [Direction; 4] = [Direction::North, Direction::East, Direction::South, Direction::West];
+
+
+/// Internal function to get the property of a vine or glow lichen face.
+fn get_face_property(direction: Direction) -> &'static BoolProperty {
+    match direction {
+        Direction::East => &PROP_EAST,
+        Direction::West => &PROP_WEST,
+        Direction::South => &PROP_SOUTH,
+        Direction::North => &PROP_NORTH,
+        Direction::Up => &PROP_UP,
+        Direction::Down => &PROP_DOWN
+    }
+}
+
+/// Internal function to rotate a horizontal direction by a quarter clockwise.
+fn get_clockwise(direction: Direction) -> Direction {
+    match direction {
+        Direction::North => Direction::East,
+        Direction::East => Direction::South,
+        Direction::South => Direction::West,
+        Direction::West => Direction::North,
+        other => other
+    }
+}
+
+fn is_air(block: &'static Block) -> bool {
+    block == &AIR || block == &CAVE_AIR || block == &VOID_AIR
+}
+
+fn is_air_at(level: &Level, pos: &BlockPos) -> bool {
+    get_block(level, pos).is_some_and(|state| is_air(state.get_block()))
+}
+
+fn is_sand(block: &'static Block) -> bool {
+    block == &SAND || block == &RED_SAND
+}
+
+/// Internal function to check if vines and glow lichen can grow against the block at the
+/// given position, the block must have a full face, approximated by blocks stopping motion.
+fn is_acceptable_neighbour(level: &Level, pos: &BlockPos) -> bool {
+    get_block(level, pos).is_some_and(|state| VANILLA_BLOCK_MATERIALS.blocks_motion(state.get_block()))
+}
+
+fn has_horizontal_face(state: &'static BlockState) -> bool {
+    HORIZONTAL_DIRECTIONS.iter().any(|&direction| state.expect(get_face_property(direction)))
+}
+
+/// Internal function to check that there are not too many vines around a vine for it to
+/// spread, in a 9x3x9 area.
+fn can_vine_spread(level: &Level, pos: &BlockPos) -> bool {
+    let mut count = 0;
+    for x in -4..=4 {
+        for z in -4..=4 {
+            for y in -1..=1 {
+                if get_block(level, &pos.add(x, y, z)).is_some_and(|state| state.is_block(&VINE)) {
+                    count += 1;
+                    if count > MAX_VINES_AROUND {
+                        return false;
+                    }
+                }
+            }
+        }
+    }
+    true
+}
+
+
+/// Remove an egg from the turtle eggs at the given position, the block is removed with its
+/// last egg. Return false if there is no turtle egg at this position.
+pub fn crack_turtle_egg(level: &Level, pos: &BlockPos) -> bool {
+    let state = match get_block(level, pos) {
+        Some(state) if state.is_block(&TURTLE_EGG) => state,
+        _ => return false
+    };
+    let eggs: u8 = state.expect(&PROP_EGGS);
+    let new_state = if eggs > 1 {
+        state.with(&PROP_EGGS, eggs - 1).unwrap()
+    } else {
+        AIR.get_default_state()
+    };
+    level.chunks.set_block_at(pos.x, pos.y, pos.z, new_state).is_ok()
+}
+
+/// Internal function to crack turtle eggs walked on by living entities, except turtles
+/// and bats, each entity has a 1/100 chance to crack an egg each tick.
+fn trample_turtle_eggs(level: &Level, rand: &mut JavaRandom) {
+
+    let positions: Vec<BlockPos> = level.entities.ecs.query::<(&BaseEntity, &LivingEntity)>()
+        .without::<Dead>()
+        .iter()
+        .filter(|(_, (base, _))| !std::ptr::eq(base.entity_type, &TURTLE) && !std::ptr::eq(base.entity_type, &BAT))
+        .map(|(_, (base, _))| get_block_pos(&base.pos))
+        .collect();
+
+    for pos in positions {
+        if get_block(level, &pos).is_some_and(|state| state.is_block(&TURTLE_EGG)) && rand.next_int_bounded(100) == 0 {
+            crack_turtle_egg(level, &pos);
+        }
+    }
+
+}
+
+
+/// Spread glow lichen like bone meal does, one of its faces spreads to another face of the
+/// same block, to the same face of a neighbour block or around the corner of the block it
+/// grows on. Return false if the lichen can't spread.
+pub fn spread_glow_lichen(level: &Level, pos: &BlockPos, rand: &mut JavaRandom) -> bool {
+
+    let state = match get_block(level, pos) {
+        Some(state) if state.is_block(&GLOW_LICHEN) => state,
+        _ => return false
+    };
+
+    let mut faces: Vec<Direction> = RANDOM_DIRECTIONS.iter()
+        .copied()
+        .filter(|&face| state.expect(get_face_property(face)))
+        .collect();
+    shuffle(&mut faces, rand);
+
+    for face in faces {
+
+        let mut directions: Vec<Direction> = RANDOM_DIRECTIONS.iter()
+            .copied()
+            .filter(|direction| direction.axis() != face.axis())
+            .collect();
+        shuffle(&mut directions, rand);
+
+        for direction in directions {
+            let around = pos.relative(direction, 1);
+            let candidates = [
+                (pos.clone(), direction),
+                (around.clone(), face),
+                (around.relative(face, 1), direction.opposite())
+            ];
+            for (target, target_face) in candidates {
+                if try_place_lichen_face(level, &target, target_face) {
+                    return true;
+                }
+            }
+        }
+
+    }
+
+    false
+
+}
+
+/// Internal function to add a face to the glow lichen at the given position, or to place a
+/// new glow lichen in air or water. The block on that face must be acceptable.
+fn try_place_lichen_face(level: &Level, pos: &BlockPos, face: Direction) -> bool {
+
+    let state = match get_block(level, pos) {
+        Some(state) => state,
+        None => return false
+    };
+
+    let base = if state.is_block(&GLOW_LICHEN) {
+        if state.expect(get_face_property(face)) {
+            return false;
+        }
+        state
+    } else if is_air(state.get_block()) {
+        GLOW_LICHEN.get_default_state()
+    } else if state.is_block(&WATER) && state.get::<u8, _>(&PROP_LIQUID_LEVEL).unwrap_or(0) == 0 {
+        GLOW_LICHEN.get_default_state().with(&PROP_WATERLOGGED, true).unwrap()
+    } else {
+        return false;
+    };
+
+    if !is_acceptable_neighbour(level, &pos.relative(face, 1)) {
+        return false;
+    }
+
+    let new_state = base.with(get_face_property(face), true).unwrap();
+    level.chunks.set_block_at(pos.x, pos.y, pos.z, new_state).is_ok()
+
+}
+
+/// Internal function to shuffle directions like Java collections.
+fn shuffle(directions: &mut [Direction], rand: &mut JavaRandom) {
+    for i in (1..directions.len()).rev() {
+        directions.swap(i, rand.next_int_bounded(i as i32 + 1) as usize);
+    }
+}
+
+
+/// Call this function with a mutable reference to a World to register the `RandomTicks`
+/// component required to run the system `system_random_ticks`.
+pub fn register_random_ticks(world: &mut World, ticks: RandomTicks) {
+    world.insert_component(ticks);
+}
+
+/// A system ticking random blocks of each loaded sub chunk with their behaviour, and turtle
+/// eggs walked on by entities. The time of levels is taken from the `SleepTracker`. If the
+/// `TickBudgets` component is registered, chunks are ticked within the budget of their
+/// level, chunks near inhabitants first, the others are deferred to the next ticks.
+pub fn system_random_ticks(world: &mut World) {
+
+    let mut ticks = match world.components.get_mut::<RandomTicks>() {
+        Ok(ticks) => ticks,
+        Err(_) => return
+    };
+
+    let ticks = &mut *ticks;
+    let sleep = world.components.get::<SleepTracker>().ok();
+    let mut budgets = world.components.get_mut::<TickBudgets>().ok();
+
+    for (level_idx, level) in world.levels.iter().enumerate() {
+
+        let mut level = level.borrow_mut();
+        let time = sleep.as_ref()
+            .and_then(|sleep| sleep.get_level(level.get_id()))
+            .map(|level_sleep| &level_sleep.time);
+        let budget = budgets.as_mut().map(|budgets| budgets.get_level_mut(level_idx));
+
+        trample_turtle_eggs(&level, &mut ticks.rand);
+
+        if ticks.queues.len() <= level_idx {
+            ticks.queues.resize_with(level_idx + 1, WorkQueue::new);
+        }
+
+        // The queue is taken out while processing because ticking borrows the component.
+        let mut queue = std::mem::take(&mut ticks.queues[level_idx]);
+        for chunk in level.chunks.iter_chunks() {
+            let (cx, cz) = chunk.read().unwrap().get_position();
+            queue.push((cx, cz), budget.as_ref().is_some_and(|budget| budget.is_chunk_priority(cx, cz)));
+        }
+
+        queue.process(budget, |(cx, cz)| {
+            ticks.tick_chunk(&mut level, time, cx, cz);
+        });
+
+        ticks.queues[level_idx] = queue;
+
+    }
+
+}
+
+
+static TURTLE_EGG_BEHAVIOUR: TurtleEggBehaviour = TurtleEggBehaviour;
+static SNOW_LAYER_BEHAVIOUR: SnowLayerBehaviour = SnowLayerBehaviour;
+static ICE_BEHAVIOUR: IceBehaviour = IceBehaviour;
+static WATER_CAULDRON_BEHAVIOUR: WaterCauldronBehaviour = WaterCauldronBehaviour;
+static VINE_BEHAVIOUR: VineBehaviour = VineBehaviour;
+
+
+pub(crate) fn register_vanilla_ticks(ticks: &mut RandomTicks) {
+    ticks.register(&TURTLE_EGG, &TURTLE_EGG_BEHAVIOUR);
+    ticks.register(&SNOW, &SNOW_LAYER_BEHAVIOUR);
+    ticks.register(&ICE, &ICE_BEHAVIOUR);
+    ticks.register(&WATER_CAULDRON, &WATER_CAULDRON_BEHAVIOUR);
+    ticks.register(&VINE, &VINE_BEHAVIOUR);
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use mc_core::world::level::LevelEnv;
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::source::{ChunkLoadRequest, NullLevelSource};
+    use mc_core::world::dimension::DimensionType;
+
+    use crate::ext::{VanillaLevelEnv, WithVanilla};
+
+    use super::*;
+
+    fn tick(level: &mut Level, pos: BlockPos, time: Option<&LevelTime>, rand: &mut JavaRandom) {
+        let state = get_block(level, &pos).unwrap();
+        let behaviour = RandomTicks::with_vanilla().get(state.get_block()).unwrap();
+        behaviour.random_tick(&mut RandomTickContext { level, pos, state, time, rand });
+    }
+
+    #[test]
+    fn random_ticks() {
+
+        let env = LevelEnv::vanilla();
+        let height = ChunkHeight::new(0, 0);
+        let dimension_type = DimensionType {
+            ultrawarm: true,
+            ..DimensionType::new("test:dimension", height)
+        };
+        let mut level = Level::new("test".to_string(), env.clone(), height, NullLevelSource)
+            .with_dimension_type(Arc::new(dimension_type));
+        level.chunks.insert_chunk(ChunkLoadRequest { env, height, cx: 0, cz: 0 }.build_chunk());
+
+        let mut rand = JavaRandom::new(0);
+        let set = |level: &Level, x: i32, y: i32, z: i32, state: &'static BlockState| {
+            level.chunks.set_block_at(x, y, z, state).unwrap();
+        };
+
+        // Eggs always progress shortly before dawn, and hatch after the third stage.
+        let before_dawn = LevelTime::new().with_day_time(21500);
+        set(&level, 0, 0, 0, SAND.get_default_state());
+        set(&level, 0, 1, 0, TURTLE_EGG.get_default_state().with(&PROP_EGGS, 2u8).unwrap());
+        for hatch in 1..=2u8 {
+            tick(&mut level, BlockPos::new(0, 1, 0), Some(&before_dawn), &mut rand);
+            assert_eq!(get_block(&level, &BlockPos::new(0, 1, 0)).unwrap().expect(&PROP_HATCH), hatch);
+        }
+        tick(&mut level, BlockPos::new(0, 1, 0), Some(&before_dawn), &mut rand);
+        assert!(get_block(&level, &BlockPos::new(0, 1, 0)).unwrap().is_block(&AIR));
+        let turtles: Vec<_> = level.entities.ecs.query::<(&BreedableEntity, &TurtleEntity)>()
+            .iter()
+            .map(|(_, (breedable, turtle))| (breedable.is_baby(), turtle.get_home_pos().clone()))
+            .collect();
+        assert_eq!(turtles, vec![(true, BlockPos::new(0, 1, 0)); 2]);
+
+        // Cracked eggs are removed with the last one.
+        set(&level, 1, 1, 0, TURTLE_EGG.get_default_state().with(&PROP_EGGS, 2u8).unwrap());
+        assert!(crack_turtle_egg(&level, &BlockPos::new(1, 1, 0)));
+        assert_eq!(get_block(&level, &BlockPos::new(1, 1, 0)).unwrap().expect(&PROP_EGGS), 1);
+        assert!(crack_turtle_egg(&level, &BlockPos::new(1, 1, 0)));
+        assert!(!crack_turtle_egg(&level, &BlockPos::new(1, 1, 0)));
+
+        // Water evaporates from cauldrons in ultrawarm levels.
+        set(&level, 2, 1, 0, WATER_CAULDRON.get_default_state().with(&PROP_CAULDRON_LEVEL, 2u8).unwrap());
+        tick(&mut level, BlockPos::new(2, 1, 0), None, &mut rand);
+        assert_eq!(get_block(&level, &BlockPos::new(2, 1, 0)).unwrap().expect(&PROP_CAULDRON_LEVEL), 1);
+        tick(&mut level, BlockPos::new(2, 1, 0), None, &mut rand);
+        assert!(get_block(&level, &BlockPos::new(2, 1, 0)).unwrap().is_block(&CAULDRON));
+
+        // Vines eventually grow down along their wall.
+        set(&level, 5, 8, 5, STONE.get_default_state());
+        set(&level, 5, 7, 5, STONE.get_default_state());
+        set(&level, 5, 8, 6, VINE.get_default_state().with(&PROP_NORTH, true).unwrap());
+        for _ in 0..200 {
+            tick(&mut level, BlockPos::new(5, 8, 6), None, &mut rand);
+        }
+        let below = get_block(&level, &BlockPos::new(5, 7, 6)).unwrap();
+        assert!(below.is_block(&VINE) && below.expect(&PROP_NORTH));
+
+        // Glow lichen spreads to the stone next to it.
+        set(&level, 10, 1, 10, STONE.get_default_state());
+        set(&level, 10, 2, 10, GLOW_LICHEN.get_default_state().with(&PROP_DOWN, true).unwrap());
+        assert!(spread_glow_lichen(&level, &BlockPos::new(10, 2, 10), &mut rand));
+
+    }
+
+}
@@ -0,0 +1,85 @@
+use mc_core::entity::SingleEntityCodec;
+use mc_core::entity_component;
+use nbt::CompoundTag;
+
+use crate::util::DyeColor;
+
+
+/// A pattern layer of a banner, the pattern is identified by its short code, for example
+/// `bs` for the base or `cre` for the creeper charge.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BannerPattern {
+    pub pattern: String,
+    pub color: DyeColor
+}
+
+
+#[derive(Debug, Default)]
+pub struct BannerBlockEntity {
+    /// The optional custom name JSON text component of this banner, shown on maps.
+    custom_name: Option<String>, // TODO: Replace with a struct like "TextComponent".
+    /// Pattern layers of the banner, from bottom to top. The base color of the banner is
+    /// defined by its block.
+    patterns: Vec<BannerPattern>
+}
+
+impl BannerBlockEntity {
+
+    pub fn get_custom_name(&self) -> Option<&str> {
+        self.custom_name.as_deref()
+    }
+
+    pub fn set_custom_name(&mut self, custom_name: Option<String>) {
+        self.custom_name = custom_name;
+    }
+
+    pub fn get_patterns(&self) -> &[BannerPattern] {
+        &self.patterns[..]
+    }
+
+    pub fn add_pattern(&mut self, pattern: BannerPattern) {
+        self.patterns.push(pattern);
+    }
+
+    pub fn clear_patterns(&mut self) {
+        self.patterns.clear();
+    }
+
+}
+
+entity_component!(BannerBlockEntity: BannerBlockEntityCodec);
+
+pub struct BannerBlockEntityCodec;
+impl SingleEntityCodec for BannerBlockEntityCodec {
+
+    type Comp = BannerBlockEntity;
+
+    fn encode(&self, src: &Self::Comp, dst: &mut CompoundTag) {
+        if let Some(custom_name) = &src.custom_name {
+            dst.insert_str("CustomName", custom_name);
+        }
+        if !src.patterns.is_empty() {
+            dst.insert_compound_tag_vec("Patterns", src.patterns.iter().map(|pattern| {
+                let mut tag_pattern = CompoundTag::new();
+                tag_pattern.insert_str("Pattern", &pattern.pattern);
+                tag_pattern.insert_i32("Color", pattern.color.get_id() as i32);
+                tag_pattern
+            }));
+        }
+    }
+
+    fn decode(&self, src: &CompoundTag) -> Self::Comp {
+        BannerBlockEntity {
+            custom_name: src.get_str("CustomName").ok().map(str::to_string),
+            patterns: src.get_compound_tag_vec("Patterns")
+                .map(|tag_patterns| tag_patterns.into_iter()
+                    .filter_map(|tag_pattern| Some(BannerPattern {
+                        pattern: tag_pattern.get_str("Pattern").ok()?.to_string(),
+                        color: DyeColor::from_id(tag_pattern.get_i32("Color").ok()? as u8)
+                    }))
+                    .collect())
+                .unwrap_or_default()
+        }
+    }
+
+}
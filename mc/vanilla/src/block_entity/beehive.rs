@@ -0,0 +1,156 @@
+use mc_core::entity::SingleEntityCodec;
+use mc_core::pos::BlockPos;
+use mc_core::util::NbtExt;
+use mc_core::entity_component;
+use nbt::CompoundTag;
+
+
+/// Maximum number of bees in a beehive or a bee nest.
+pub const MAX_BEES: usize = 3;
+/// Minimum number of ticks spent in the hive by bees carrying nectar.
+pub const NECTAR_OCCUPATION_TICKS: i32 = 2400;
+/// Minimum number of ticks spent in the hive by bees without nectar.
+pub const MIN_OCCUPATION_TICKS: i32 = 600;
+
+
+/// A bee inside a hive, the bee entity is removed from the level while inside and its
+/// data is kept to spawn it again on release.
+#[derive(Debug, Clone)]
+pub struct HiveBee {
+    /// The data of the bee entity, encoded with the codecs of its entity type.
+    entity_data: CompoundTag,
+    ticks_in_hive: i32,
+    min_occupation_ticks: i32
+}
+
+impl HiveBee {
+
+    pub fn new(entity_data: CompoundTag, min_occupation_ticks: i32) -> Self {
+        Self {
+            entity_data,
+            ticks_in_hive: 0,
+            min_occupation_ticks
+        }
+    }
+
+    pub fn get_entity_data(&self) -> &CompoundTag {
+        &self.entity_data
+    }
+
+    pub fn get_ticks_in_hive(&self) -> i32 {
+        self.ticks_in_hive
+    }
+
+    pub fn get_min_occupation_ticks(&self) -> i32 {
+        self.min_occupation_ticks
+    }
+
+    /// Return true if the bee carries nectar, its honey is added to the hive on release.
+    pub fn has_nectar(&self) -> bool {
+        self.entity_data.get_bool_or("HasNectar", false)
+    }
+
+    /// Return true if the bee has stayed long enough in the hive to leave it.
+    pub fn is_ready(&self) -> bool {
+        self.ticks_in_hive > self.min_occupation_ticks
+    }
+
+}
+
+
+/// Block entity of beehives and bee nests, storing the bees inside.
+#[derive(Debug, Default)]
+pub struct BeehiveBlockEntity {
+    bees: Vec<HiveBee>,
+    /// The flower last visited by a bee of this hive.
+    flower_pos: Option<BlockPos>
+}
+
+impl BeehiveBlockEntity {
+
+    pub fn get_bees(&self) -> &[HiveBee] {
+        &self.bees
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.bees.len() >= MAX_BEES
+    }
+
+    /// Add a bee in the hive, return false if the hive is full.
+    pub fn add_bee(&mut self, bee: HiveBee) -> bool {
+        if self.is_full() {
+            false
+        } else {
+            self.bees.push(bee);
+            true
+        }
+    }
+
+    /// Increment the time spent in the hive by each bee.
+    pub fn tick(&mut self) {
+        for bee in &mut self.bees {
+            bee.ticks_in_hive += 1;
+        }
+    }
+
+    /// Take the bees that have stayed long enough in the hive.
+    pub fn take_ready_bees(&mut self) -> Vec<HiveBee> {
+        let (ready, bees) = std::mem::take(&mut self.bees)
+            .into_iter()
+            .partition(HiveBee::is_ready);
+        self.bees = bees;
+        ready
+    }
+
+    /// Take all bees out of the hive.
+    pub fn take_bees(&mut self) -> Vec<HiveBee> {
+        std::mem::take(&mut self.bees)
+    }
+
+    pub fn get_flower_pos(&self) -> Option<&BlockPos> {
+        self.flower_pos.as_ref()
+    }
+
+    pub fn set_flower_pos(&mut self, flower_pos: Option<BlockPos>) {
+        self.flower_pos = flower_pos;
+    }
+
+}
+
+entity_component!(BeehiveBlockEntity: BeehiveBlockEntityCodec);
+
+pub struct BeehiveBlockEntityCodec;
+impl SingleEntityCodec for BeehiveBlockEntityCodec {
+
+    type Comp = BeehiveBlockEntity;
+
+    fn encode(&self, src: &Self::Comp, dst: &mut CompoundTag) {
+        dst.insert_compound_tag_vec("Bees", src.bees.iter().map(|bee| {
+            let mut tag_bee = CompoundTag::new();
+            tag_bee.insert_compound_tag("EntityData", bee.entity_data.clone());
+            tag_bee.insert_i32("TicksInHive", bee.ticks_in_hive);
+            tag_bee.insert_i32("MinOccupationTicks", bee.min_occupation_ticks);
+            tag_bee
+        }));
+        if let Some(flower_pos) = &src.flower_pos {
+            dst.insert_block_pos("FlowerPos", flower_pos);
+        }
+    }
+
+    fn decode(&self, src: &CompoundTag) -> Self::Comp {
+        BeehiveBlockEntity {
+            bees: src.get_compound_tag_vec("Bees")
+                .map(|tag_bees| tag_bees.into_iter()
+                    .filter_map(|tag_bee| Some(HiveBee {
+                        entity_data: tag_bee.get_compound_tag("EntityData").ok()?.clone(),
+                        ticks_in_hive: tag_bee.get_i32_or("TicksInHive", 0),
+                        min_occupation_ticks: tag_bee.get_i32_or("MinOccupationTicks", MIN_OCCUPATION_TICKS)
+                    }))
+                    .take(MAX_BEES)
+                    .collect())
+                .unwrap_or_default(),
+            flower_pos: src.get_block_pos("FlowerPos").ok()
+        }
+    }
+
+}
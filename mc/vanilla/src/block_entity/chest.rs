@@ -0,0 +1,85 @@
+use mc_core::entity::SingleEntityCodec;
+use mc_core::item::Inventory;
+use mc_core::entity_component;
+use nbt::CompoundTag;
+
+
+/// Number of slots of a single chest.
+pub const CHEST_SIZE: usize = 27;
+
+
+/// Block entity of chests and trapped chests. The inventory is taken out of the chest while
+/// a player has it open in a window and put back when the window is closed.
+#[derive(Debug)]
+pub struct ChestBlockEntity {
+    /// The custom name of the chest, as a JSON text component.
+    custom_name: Option<String>,
+    /// The inventory of the chest, none while taken by a window.
+    inventory: Option<Inventory>
+}
+
+impl Default for ChestBlockEntity {
+    fn default() -> Self {
+        Self {
+            custom_name: None,
+            inventory: Some(Inventory::new(CHEST_SIZE))
+        }
+    }
+}
+
+impl ChestBlockEntity {
+
+    pub fn get_custom_name(&self) -> Option<&str> {
+        self.custom_name.as_deref()
+    }
+
+    /// Set the custom name of the chest, the name must be a JSON text component.
+    pub fn set_custom_name(&mut self, custom_name: Option<String>) {
+        self.custom_name = custom_name;
+    }
+
+    /// Get the inventory of the chest, `None` if taken by a window.
+    pub fn get_inventory(&self) -> Option<&Inventory> {
+        self.inventory.as_ref()
+    }
+
+    pub fn get_inventory_mut(&mut self) -> Option<&mut Inventory> {
+        self.inventory.as_mut()
+    }
+
+    /// Take the inventory out of the chest to open it in a window, `None` if the inventory
+    /// is already taken.
+    pub fn take_inventory(&mut self) -> Option<Inventory> {
+        self.inventory.take()
+    }
+
+    /// Put back the inventory taken with `take_inventory`.
+    pub fn put_inventory(&mut self, inventory: Inventory) {
+        self.inventory = Some(inventory);
+    }
+
+}
+
+entity_component!(ChestBlockEntity: ChestBlockEntityCodec);
+
+pub struct ChestBlockEntityCodec;
+impl SingleEntityCodec for ChestBlockEntityCodec {
+
+    type Comp = ChestBlockEntity;
+
+    // Items are not saved because stacks can't be decoded without an item registry.
+
+    fn encode(&self, src: &Self::Comp, dst: &mut CompoundTag) {
+        if let Some(custom_name) = &src.custom_name {
+            dst.insert_str("CustomName", custom_name);
+        }
+    }
+
+    fn decode(&self, src: &CompoundTag) -> Self::Comp {
+        ChestBlockEntity {
+            custom_name: src.get_str("CustomName").ok().map(str::to_string),
+            ..ChestBlockEntity::default()
+        }
+    }
+
+}
@@ -0,0 +1,181 @@
+use mc_core::entity::SingleEntityCodec;
+use mc_core::util::NbtExt;
+use mc_core::entity_component;
+use nbt::CompoundTag;
+
+
+/// Default custom name of command blocks, as a JSON text component.
+const DEFAULT_NAME: &str = r#"{"text":"@"}"#;
+
+
+#[derive(Debug)]
+pub struct CommandBlockEntity {
+    command: String,
+    /// The custom name of the command block, as a JSON text component, used as the name of
+    /// the command source.
+    custom_name: String,
+    /// The result of the last command execution, 0 if it failed.
+    success_count: i32,
+    /// The output of the last command, only kept if the output is tracked.
+    last_output: String,
+    track_output: bool,
+    /// True if the command block is receiving redstone power.
+    powered: bool,
+    /// True if the command block is always active, without redstone power.
+    auto: bool,
+    /// For conditional command blocks, true if the command block behind this one succeeded
+    /// the last time the condition was checked.
+    condition_met: bool,
+    /// Set when an impulse command block has been activated and must execute its command.
+    pub(crate) pending: bool
+}
+
+impl Default for CommandBlockEntity {
+    fn default() -> Self {
+        Self {
+            command: String::new(),
+            custom_name: DEFAULT_NAME.to_string(),
+            success_count: 0,
+            last_output: String::new(),
+            track_output: true,
+            powered: false,
+            auto: false,
+            condition_met: false,
+            pending: false
+        }
+    }
+}
+
+impl CommandBlockEntity {
+
+    pub fn get_command(&self) -> &str {
+        &self.command
+    }
+
+    pub fn set_command(&mut self, command: String) {
+        self.command = command;
+    }
+
+    pub fn get_custom_name(&self) -> &str {
+        &self.custom_name
+    }
+
+    pub fn set_custom_name(&mut self, custom_name: String) {
+        self.custom_name = custom_name;
+    }
+
+    #[inline]
+    pub fn get_success_count(&self) -> i32 {
+        self.success_count
+    }
+
+    #[inline]
+    pub fn set_success_count(&mut self, success_count: i32) {
+        self.success_count = success_count;
+    }
+
+    pub fn get_last_output(&self) -> &str {
+        &self.last_output
+    }
+
+    /// Set the output of the last command, ignored if the output is not tracked.
+    pub fn set_last_output(&mut self, last_output: String) {
+        if self.track_output {
+            self.last_output = last_output;
+        }
+    }
+
+    pub fn is_tracking_output(&self) -> bool {
+        self.track_output
+    }
+
+    /// Set if the output of commands is tracked, the last output is cleared if not.
+    pub fn set_track_output(&mut self, track_output: bool) {
+        self.track_output = track_output;
+        if !track_output {
+            self.last_output.clear();
+        }
+    }
+
+    #[inline]
+    pub fn is_powered(&self) -> bool {
+        self.powered
+    }
+
+    /// Set if the command block is receiving redstone power, an impulse command block is
+    /// activated when it starts receiving power.
+    pub fn set_powered(&mut self, powered: bool) {
+        if powered && !self.powered && !self.auto {
+            self.pending = true;
+        }
+        self.powered = powered;
+    }
+
+    #[inline]
+    pub fn is_auto(&self) -> bool {
+        self.auto
+    }
+
+    /// Set if the command block is always active, an impulse command block is activated
+    /// when it becomes always active.
+    pub fn set_auto(&mut self, auto: bool) {
+        if auto && !self.auto && !self.powered {
+            self.pending = true;
+        }
+        self.auto = auto;
+    }
+
+    /// Return true if the command block is active, it's powered or always active.
+    #[inline]
+    pub fn is_active(&self) -> bool {
+        self.powered || self.auto
+    }
+
+    #[inline]
+    pub fn is_condition_met(&self) -> bool {
+        self.condition_met
+    }
+
+    #[inline]
+    pub fn set_condition_met(&mut self, condition_met: bool) {
+        self.condition_met = condition_met;
+    }
+
+}
+
+entity_component!(CommandBlockEntity: CommandBlockEntityCodec);
+
+pub struct CommandBlockEntityCodec;
+impl SingleEntityCodec for CommandBlockEntityCodec {
+
+    type Comp = CommandBlockEntity;
+
+    fn encode(&self, src: &Self::Comp, dst: &mut CompoundTag) {
+        dst.insert_str("Command", &src.command);
+        dst.insert_str("CustomName", &src.custom_name);
+        dst.insert_i32("SuccessCount", src.success_count);
+        dst.insert_bool("TrackOutput", src.track_output);
+        if src.track_output {
+            dst.insert_str("LastOutput", &src.last_output);
+        }
+        dst.insert_bool("powered", src.powered);
+        dst.insert_bool("auto", src.auto);
+        dst.insert_bool("conditionMet", src.condition_met);
+    }
+
+    fn decode(&self, src: &CompoundTag) -> Self::Comp {
+        let track_output = src.get_bool_or("TrackOutput", true);
+        CommandBlockEntity {
+            command: src.get_str("Command").unwrap_or_default().to_string(),
+            custom_name: src.get_str("CustomName").unwrap_or(DEFAULT_NAME).to_string(),
+            success_count: src.get_i32_or("SuccessCount", 0),
+            last_output: if track_output { src.get_str("LastOutput").unwrap_or_default().to_string() } else { String::new() },
+            track_output,
+            powered: src.get_bool_or("powered", false),
+            auto: src.get_bool_or("auto", false),
+            condition_met: src.get_bool_or("conditionMet", false),
+            pending: false
+        }
+    }
+
+}
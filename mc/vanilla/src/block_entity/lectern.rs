@@ -0,0 +1,95 @@
+use mc_core::entity::SingleEntityCodec;
+use mc_core::item::ItemStack;
+use mc_core::util::NbtExt;
+use mc_core::entity_component;
+use nbt::CompoundTag;
+
+
+/// Block entity of lecterns, holding a book opened at a page.
+#[derive(Debug, Default)]
+pub struct LecternBlockEntity {
+    /// The book on the lectern, a writable or a written book.
+    book: Option<ItemStack>,
+    /// The page the book is opened at.
+    page: u32,
+    /// True if the page has been turned since the last `take_page_turned`.
+    page_turned: bool
+}
+
+impl LecternBlockEntity {
+
+    pub fn get_book(&self) -> Option<&ItemStack> {
+        self.book.as_ref()
+    }
+
+    pub fn has_book(&self) -> bool {
+        self.book.is_some()
+    }
+
+    /// Put a book on the lectern, it is opened at the first page.
+    pub fn set_book(&mut self, book: Option<ItemStack>) {
+        self.book = book;
+        self.page = 0;
+    }
+
+    /// Take the book out of the lectern.
+    pub fn take_book(&mut self) -> Option<ItemStack> {
+        self.page = 0;
+        self.book.take()
+    }
+
+    pub fn get_page(&self) -> u32 {
+        self.page
+    }
+
+    /// Return the number of pages of the book, zero without book.
+    pub fn get_page_count(&self) -> u32 {
+        self.book.as_ref()
+            .and_then(ItemStack::get_tag)
+            .and_then(|tag| tag.get_string_vec("pages").ok())
+            .map_or(0, |pages| pages.len() as u32)
+    }
+
+    /// Open the book at the given page, return true if the page has changed. The page is
+    /// clamped to the pages of the book.
+    pub fn set_page(&mut self, page: u32) -> bool {
+        let page = page.min(self.get_page_count().saturating_sub(1));
+        if page != self.page {
+            self.page = page;
+            self.page_turned = true;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Return true if the page has been turned since the last call.
+    pub fn take_page_turned(&mut self) -> bool {
+        std::mem::take(&mut self.page_turned)
+    }
+
+}
+
+entity_component!(LecternBlockEntity: LecternBlockEntityCodec);
+
+pub struct LecternBlockEntityCodec;
+impl SingleEntityCodec for LecternBlockEntityCodec {
+
+    type Comp = LecternBlockEntity;
+
+    // The book is not saved because stacks can't be decoded without an item registry.
+
+    fn encode(&self, src: &Self::Comp, dst: &mut CompoundTag) {
+        if src.book.is_some() {
+            dst.insert_i32("Page", src.page as i32);
+        }
+    }
+
+    fn decode(&self, src: &CompoundTag) -> Self::Comp {
+        LecternBlockEntity {
+            page: src.get_i32_or("Page", 0).max(0) as u32,
+            ..LecternBlockEntity::default()
+        }
+    }
+
+}
@@ -0,0 +1,161 @@
+//! This is the base module for defining vanilla block entities and their ECS components.
+//! Block entities are stored in the level's ECS like entities, with a `BaseBlockEntity`
+//! component. The ECS components are NBT structures that can be found on the wiki:
+//! - https://minecraft.fandom.com/wiki/Chunk_format#Block_entity_format
+
+mod sign;
+mod banner;
+mod skull;
+mod command_block;
+mod structure_block;
+mod chest;
+mod beehive;
+mod lectern;
+
+pub use sign::*;
+pub use banner::*;
+pub use skull::*;
+pub use command_block::*;
+pub use structure_block::*;
+pub use chest::*;
+pub use beehive::*;
+pub use lectern::*;
+
+
+mc_core::entities!(pub VANILLA_BLOCK_ENTITIES "minecraft" [
+    SIGN "sign" [SignBlockEntity],
+    BANNER "banner" [BannerBlockEntity],
+    SKULL "skull" [SkullBlockEntity],
+    COMMAND_BLOCK "command_block" [CommandBlockEntity],
+    STRUCTURE_BLOCK "structure_block" [StructureBlockEntity],
+    CHEST "chest" [ChestBlockEntity],
+    TRAPPED_CHEST "trapped_chest" [ChestBlockEntity],
+    BEEHIVE "beehive" [BeehiveBlockEntity],
+    LECTERN "lectern" [LecternBlockEntity]
+]);
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_core::world::source::{MemoryLevelSource, VoidGenerator};
+    use mc_core::world::level::{Level, LevelEnv, BaseBlockEntity};
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::pos::BlockPos;
+    use uuid::Uuid;
+    use nbt::CompoundTag;
+
+    use crate::ext::VanillaLevelEnv;
+    use crate::util::DyeColor;
+    use crate::block::StructureMode;
+    use super::*;
+
+    #[test]
+    fn block_entities_round_trip() {
+
+        let source = MemoryLevelSource::with_generator(VoidGenerator::new());
+        let mut level = Level::new("overworld".to_string(), LevelEnv::vanilla(), ChunkHeight::new(0, 3), source);
+        level.request_chunk_load(0, 0);
+        level.load_chunks();
+
+        let sign = level.spawn_block_entity(&SIGN, BlockPos::new(1, 2, 3)).unwrap();
+        {
+            let mut sign = level.entities.ecs.get_mut::<SignBlockEntity>(sign).unwrap();
+            sign.set_line(1, r#"{"text":"Hello"}"#.to_string());
+            sign.set_color(DyeColor::Red);
+            sign.set_glowing(true);
+        }
+
+        let banner = level.spawn_block_entity(&BANNER, BlockPos::new(4, 5, 6)).unwrap();
+        level.entities.ecs.get_mut::<BannerBlockEntity>(banner).unwrap().add_pattern(BannerPattern {
+            pattern: "cre".to_string(),
+            color: DyeColor::Lime
+        });
+
+        let owner = GameProfile {
+            uuid: Some(Uuid::from_u128(0x0123456789ABCDEF0123456789ABCDEF)),
+            name: Some("Steve".to_string()),
+            properties: vec![ProfileProperty {
+                name: "textures".to_string(),
+                value: "dGV4dHVyZXM=".to_string(),
+                signature: None
+            }]
+        };
+
+        let skull = level.spawn_block_entity(&SKULL, BlockPos::new(7, 8, 9)).unwrap();
+        level.entities.ecs.get_mut::<SkullBlockEntity>(skull).unwrap().set_owner(Some(owner.clone()));
+
+        let command_block = level.spawn_block_entity(&COMMAND_BLOCK, BlockPos::new(10, 11, 12)).unwrap();
+        {
+            let mut command_block = level.entities.ecs.get_mut::<CommandBlockEntity>(command_block).unwrap();
+            command_block.set_command("say hello".to_string());
+            command_block.set_success_count(2);
+            command_block.set_auto(true);
+        }
+
+        let structure_block = level.spawn_block_entity(&STRUCTURE_BLOCK, BlockPos::new(13, 14, 15)).unwrap();
+        {
+            let mut structure_block = level.entities.ecs.get_mut::<StructureBlockEntity>(structure_block).unwrap();
+            structure_block.set_name("test:house".to_string());
+            structure_block.set_mode(StructureMode::Save);
+            structure_block.set_size(BlockPos::new(5, 64, 3));
+            structure_block.set_rotation(StructureRotation::Clockwise90);
+            structure_block.set_integrity(0.5);
+        }
+
+        let beehive = level.spawn_block_entity(&BEEHIVE, BlockPos::new(0, 16, 0)).unwrap();
+        {
+            let mut beehive = level.entities.ecs.get_mut::<BeehiveBlockEntity>(beehive).unwrap();
+            let mut entity_data = CompoundTag::new();
+            entity_data.insert_bool("HasNectar", true);
+            beehive.add_bee(HiveBee::new(entity_data, NECTAR_OCCUPATION_TICKS));
+            beehive.set_flower_pos(Some(BlockPos::new(2, 16, 2)));
+        }
+
+        // Saving and loading again the chunk spawns decoded copies of the block entities.
+        assert!(level.request_chunk_save(0, 0));
+        level.request_chunk_load(0, 0);
+        level.load_chunks();
+
+        let find = |pos: BlockPos, original| {
+            level.entities.ecs.query::<&BaseBlockEntity>()
+                .iter()
+                .find(|&(entity, base)| entity != original && base.pos == pos)
+                .map(|(entity, _)| entity)
+                .unwrap()
+        };
+
+        let sign = level.entities.ecs.get::<SignBlockEntity>(find(BlockPos::new(1, 2, 3), sign)).unwrap();
+        assert_eq!(sign.get_line(0), r#"{"text":""}"#);
+        assert_eq!(sign.get_line(1), r#"{"text":"Hello"}"#);
+        assert_eq!(sign.get_color(), DyeColor::Red);
+        assert!(sign.is_glowing());
+
+        let banner = level.entities.ecs.get::<BannerBlockEntity>(find(BlockPos::new(4, 5, 6), banner)).unwrap();
+        assert_eq!(banner.get_patterns(), &[BannerPattern { pattern: "cre".to_string(), color: DyeColor::Lime }]);
+
+        let skull = level.entities.ecs.get::<SkullBlockEntity>(find(BlockPos::new(7, 8, 9), skull)).unwrap();
+        assert_eq!(skull.get_owner(), Some(&owner));
+
+        let command_block = level.entities.ecs.get::<CommandBlockEntity>(find(BlockPos::new(10, 11, 12), command_block)).unwrap();
+        assert_eq!(command_block.get_command(), "say hello");
+        assert_eq!(command_block.get_success_count(), 2);
+        assert!(command_block.is_auto() && !command_block.is_powered());
+
+        let structure_block = level.entities.ecs.get::<StructureBlockEntity>(find(BlockPos::new(13, 14, 15), structure_block)).unwrap();
+        assert_eq!(structure_block.get_name(), "test:house");
+        assert_eq!(structure_block.get_mode(), StructureMode::Save);
+        assert_eq!(structure_block.get_offset(), &BlockPos::new(0, 1, 0));
+        assert_eq!(structure_block.get_size(), &BlockPos::new(5, MAX_STRUCTURE_SIZE, 3));
+        assert_eq!(structure_block.get_rotation(), StructureRotation::Clockwise90);
+        assert_eq!(structure_block.get_integrity(), 0.5);
+
+        let beehive = level.entities.ecs.get::<BeehiveBlockEntity>(find(BlockPos::new(0, 16, 0), beehive)).unwrap();
+        assert_eq!(beehive.get_bees().len(), 1);
+        assert!(beehive.get_bees()[0].has_nectar());
+        assert_eq!(beehive.get_bees()[0].get_min_occupation_ticks(), NECTAR_OCCUPATION_TICKS);
+        assert_eq!(beehive.get_flower_pos(), Some(&BlockPos::new(2, 16, 2)));
+
+    }
+
+}
@@ -0,0 +1,103 @@
+use mc_core::entity::SingleEntityCodec;
+use mc_core::util::NbtExt;
+use mc_core::entity_component;
+use nbt::CompoundTag;
+
+use crate::util::DyeColor;
+
+
+/// An empty JSON text component, used for empty lines of signs.
+const EMPTY_TEXT: &str = r#"{"text":""}"#;
+const LINES_NAMES: [&str; 4] = ["Text1", "Text2", "Text3", "Text4"];
+
+
+#[derive(Debug)]
+pub struct SignBlockEntity {
+    /// The four lines of the sign, as JSON text components.
+    lines: [String; 4], // TODO: Replace with a struct like "TextComponent".
+    /// The color of the text, changed by using a dye on the sign.
+    color: DyeColor,
+    /// True if the text is glowing, after using a glow ink sac on the sign.
+    glowing: bool
+}
+
+impl Default for SignBlockEntity {
+    fn default() -> Self {
+        Self {
+            lines: [EMPTY_TEXT.to_string(), EMPTY_TEXT.to_string(), EMPTY_TEXT.to_string(), EMPTY_TEXT.to_string()],
+            color: DyeColor::Black,
+            glowing: false
+        }
+    }
+}
+
+impl SignBlockEntity {
+
+    /// Get a line of the sign as a JSON text component.
+    ///
+    /// # Panics
+    /// This method panics if the index is not lower than 4.
+    pub fn get_line(&self, index: usize) -> &str {
+        &self.lines[index]
+    }
+
+    /// Set a line of the sign, the text must be a JSON text component.
+    ///
+    /// # Panics
+    /// This method panics if the index is not lower than 4.
+    pub fn set_line(&mut self, index: usize, text: String) {
+        self.lines[index] = text;
+    }
+
+    pub fn get_lines(&self) -> &[String; 4] {
+        &self.lines
+    }
+
+    pub fn get_color(&self) -> DyeColor {
+        self.color
+    }
+
+    pub fn set_color(&mut self, color: DyeColor) {
+        self.color = color;
+    }
+
+    pub fn is_glowing(&self) -> bool {
+        self.glowing
+    }
+
+    pub fn set_glowing(&mut self, glowing: bool) {
+        self.glowing = glowing;
+    }
+
+}
+
+entity_component!(SignBlockEntity: SignBlockEntityCodec);
+
+pub struct SignBlockEntityCodec;
+impl SingleEntityCodec for SignBlockEntityCodec {
+
+    type Comp = SignBlockEntity;
+
+    fn encode(&self, src: &Self::Comp, dst: &mut CompoundTag) {
+        for (name, line) in LINES_NAMES.iter().zip(&src.lines) {
+            dst.insert_str(name, line);
+        }
+        dst.insert_str("Color", src.color.get_name());
+        dst.insert_bool("GlowingText", src.glowing);
+    }
+
+    fn decode(&self, src: &CompoundTag) -> Self::Comp {
+        let mut sign = SignBlockEntity::default();
+        for (name, line) in LINES_NAMES.iter().zip(&mut sign.lines) {
+            if let Ok(text) = src.get_str(name) {
+                *line = text.to_string();
+            }
+        }
+        sign.color = src.get_str("Color").ok()
+            .and_then(DyeColor::from_name)
+            .unwrap_or(DyeColor::Black);
+        sign.glowing = src.get_bool_or("GlowingText", false);
+        sign
+    }
+
+}
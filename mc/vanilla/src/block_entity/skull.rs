@@ -0,0 +1,136 @@
+use mc_core::entity::SingleEntityCodec;
+use mc_core::util::NbtExt;
+use mc_core::entity_component;
+use nbt::CompoundTag;
+use uuid::Uuid;
+
+
+/// A property of a game profile, the `textures` property contains the base64-encoded skin
+/// of the player, signed by Mojang.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ProfileProperty {
+    pub name: String,
+    pub value: String,
+    pub signature: Option<String>
+}
+
+
+/// A player game profile, as stored by player heads. A profile can be incomplete, in this
+/// case vanilla completes it with the name or the UUID when the head is placed.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct GameProfile {
+    pub uuid: Option<Uuid>,
+    pub name: Option<String>,
+    pub properties: Vec<ProfileProperty>
+}
+
+
+#[derive(Debug, Default)]
+pub struct SkullBlockEntity {
+    /// The owner of the head, only used by player heads.
+    owner: Option<GameProfile>
+}
+
+impl SkullBlockEntity {
+
+    pub fn get_owner(&self) -> Option<&GameProfile> {
+        self.owner.as_ref()
+    }
+
+    pub fn set_owner(&mut self, owner: Option<GameProfile>) {
+        self.owner = owner;
+    }
+
+}
+
+entity_component!(SkullBlockEntity: SkullBlockEntityCodec);
+
+pub struct SkullBlockEntityCodec;
+impl SingleEntityCodec for SkullBlockEntityCodec {
+
+    type Comp = SkullBlockEntity;
+
+    fn encode(&self, src: &Self::Comp, dst: &mut CompoundTag) {
+        if let Some(owner) = &src.owner {
+            dst.insert_compound_tag("SkullOwner", encode_game_profile(owner));
+        }
+    }
+
+    fn decode(&self, src: &CompoundTag) -> Self::Comp {
+        SkullBlockEntity {
+            owner: src.get_compound_tag("SkullOwner").ok().map(decode_game_profile)
+        }
+    }
+
+}
+
+
+fn encode_game_profile(profile: &GameProfile) -> CompoundTag {
+
+    let mut tag_profile = CompoundTag::new();
+
+    if let Some(uuid) = &profile.uuid {
+        tag_profile.insert_uuid("Id", uuid);
+    }
+
+    if let Some(name) = &profile.name {
+        tag_profile.insert_str("Name", name);
+    }
+
+    if !profile.properties.is_empty() {
+
+        // Properties are grouped by name, each name having a list of values.
+        let mut tag_properties = CompoundTag::new();
+        let mut names: Vec<&str> = profile.properties.iter().map(|prop| prop.name.as_str()).collect();
+        names.sort_unstable();
+        names.dedup();
+
+        for name in names {
+            tag_properties.insert_compound_tag_vec(name, profile.properties.iter()
+                .filter(|prop| prop.name == name)
+                .map(|prop| {
+                    let mut tag_property = CompoundTag::new();
+                    tag_property.insert_str("Value", &prop.value);
+                    if let Some(signature) = &prop.signature {
+                        tag_property.insert_str("Signature", signature);
+                    }
+                    tag_property
+                }));
+        }
+
+        tag_profile.insert_compound_tag("Properties", tag_properties);
+
+    }
+
+    tag_profile
+
+}
+
+
+fn decode_game_profile(tag_profile: &CompoundTag) -> GameProfile {
+
+    let mut properties = Vec::new();
+
+    if let Ok(tag_properties) = tag_profile.get_compound_tag("Properties") {
+        for (name, _) in tag_properties.iter() {
+            if let Ok(tag_values) = tag_properties.get_compound_tag_vec(name) {
+                for tag_value in tag_values {
+                    if let Ok(value) = tag_value.get_str("Value") {
+                        properties.push(ProfileProperty {
+                            name: name.clone(),
+                            value: value.to_string(),
+                            signature: tag_value.get_str("Signature").ok().map(str::to_string)
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    GameProfile {
+        uuid: tag_profile.get_uuid("Id").ok(),
+        name: tag_profile.get_str("Name").ok().map(str::to_string),
+        properties
+    }
+
+}
@@ -0,0 +1,342 @@
+use mc_core::entity::SingleEntityCodec;
+use mc_core::block::PropertySerializable;
+use mc_core::util::NbtExt;
+use mc_core::entity_component;
+use mc_core::pos::BlockPos;
+use nbt::CompoundTag;
+
+use crate::block::StructureMode;
+
+
+/// Maximum size of the structure of a structure block, and maximum distance between the
+/// structure block and its structure, on each axis.
+pub const MAX_STRUCTURE_SIZE: i32 = 48;
+
+
+/// Rotation of the structure loaded by a structure block.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum StructureRotation {
+    None,
+    Clockwise90,
+    Clockwise180,
+    CounterClockwise90
+}
+
+impl StructureRotation {
+
+    /// Return the vanilla name of this rotation, used in NBT data.
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Self::None => "NONE",
+            Self::Clockwise90 => "CLOCKWISE_90",
+            Self::Clockwise180 => "CLOCKWISE_180",
+            Self::CounterClockwise90 => "COUNTERCLOCKWISE_90"
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Self::None, Self::Clockwise90, Self::Clockwise180, Self::CounterClockwise90].iter()
+            .copied()
+            .find(|rotation| rotation.get_name() == name)
+    }
+
+}
+
+
+/// Mirror of the structure loaded by a structure block.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum StructureMirror {
+    None,
+    /// Swap north and south.
+    LeftRight,
+    /// Swap east and west.
+    FrontBack
+}
+
+impl StructureMirror {
+
+    /// Return the vanilla name of this mirror, used in NBT data.
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Self::None => "NONE",
+            Self::LeftRight => "LEFT_RIGHT",
+            Self::FrontBack => "FRONT_BACK"
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Self::None, Self::LeftRight, Self::FrontBack].iter()
+            .copied()
+            .find(|mirror| mirror.get_name() == name)
+    }
+
+}
+
+
+#[derive(Debug)]
+pub struct StructureBlockEntity {
+    /// Name of the structure, like `minecraft:village/plains/houses/plains_small_house_1`.
+    name: String,
+    author: String,
+    /// Metadata of a data structure block, used by structures to place additional content.
+    metadata: String,
+    /// Position of the structure relative to the structure block.
+    offset: BlockPos,
+    size: BlockPos,
+    rotation: StructureRotation,
+    mirror: StructureMirror,
+    /// The mode of the structure block, it should be the same as the mode of its block.
+    mode: StructureMode,
+    ignore_entities: bool,
+    /// True if the structure block is receiving redstone power.
+    powered: bool,
+    show_air: bool,
+    show_bounding_box: bool,
+    /// The probability for each block of the structure to be loaded.
+    integrity: f32,
+    /// The seed used when loading a structure with an integrity lower than 1, a seed of 0
+    /// means a random seed.
+    seed: i64,
+    /// Set when the structure block starts receiving redstone power.
+    triggered: bool
+}
+
+impl Default for StructureBlockEntity {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            author: String::new(),
+            metadata: String::new(),
+            offset: BlockPos::new(0, 1, 0),
+            size: BlockPos::new(0, 0, 0),
+            rotation: StructureRotation::None,
+            mirror: StructureMirror::None,
+            mode: StructureMode::Data,
+            ignore_entities: true,
+            powered: false,
+            show_air: false,
+            show_bounding_box: true,
+            integrity: 1.0,
+            seed: 0,
+            triggered: false
+        }
+    }
+}
+
+impl StructureBlockEntity {
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
+    pub fn get_author(&self) -> &str {
+        &self.author
+    }
+
+    pub fn set_author(&mut self, author: String) {
+        self.author = author;
+    }
+
+    pub fn get_metadata(&self) -> &str {
+        &self.metadata
+    }
+
+    pub fn set_metadata(&mut self, metadata: String) {
+        self.metadata = metadata;
+    }
+
+    pub fn get_offset(&self) -> &BlockPos {
+        &self.offset
+    }
+
+    /// Set the position of the structure relative to the structure block, each coordinate is
+    /// clamped between `-MAX_STRUCTURE_SIZE` and `MAX_STRUCTURE_SIZE`.
+    pub fn set_offset(&mut self, offset: BlockPos) {
+        self.offset = clamp_pos(offset, -MAX_STRUCTURE_SIZE);
+    }
+
+    pub fn get_size(&self) -> &BlockPos {
+        &self.size
+    }
+
+    /// Set the size of the structure, each coordinate is clamped between 0 and
+    /// `MAX_STRUCTURE_SIZE`.
+    pub fn set_size(&mut self, size: BlockPos) {
+        self.size = clamp_pos(size, 0);
+    }
+
+    /// Return true if the structure has a size greater than 0 on each axis.
+    pub fn has_size(&self) -> bool {
+        self.size.x > 0 && self.size.y > 0 && self.size.z > 0
+    }
+
+    #[inline]
+    pub fn get_rotation(&self) -> StructureRotation {
+        self.rotation
+    }
+
+    #[inline]
+    pub fn set_rotation(&mut self, rotation: StructureRotation) {
+        self.rotation = rotation;
+    }
+
+    #[inline]
+    pub fn get_mirror(&self) -> StructureMirror {
+        self.mirror
+    }
+
+    #[inline]
+    pub fn set_mirror(&mut self, mirror: StructureMirror) {
+        self.mirror = mirror;
+    }
+
+    #[inline]
+    pub fn get_mode(&self) -> StructureMode {
+        self.mode
+    }
+
+    #[inline]
+    pub fn set_mode(&mut self, mode: StructureMode) {
+        self.mode = mode;
+    }
+
+    #[inline]
+    pub fn is_ignoring_entities(&self) -> bool {
+        self.ignore_entities
+    }
+
+    #[inline]
+    pub fn set_ignore_entities(&mut self, ignore_entities: bool) {
+        self.ignore_entities = ignore_entities;
+    }
+
+    #[inline]
+    pub fn is_powered(&self) -> bool {
+        self.powered
+    }
+
+    /// Set if the structure block is receiving redstone power, the structure block is
+    /// triggered when it starts receiving power.
+    pub fn set_powered(&mut self, powered: bool) {
+        if powered && !self.powered {
+            self.triggered = true;
+        }
+        self.powered = powered;
+    }
+
+    /// Return true if the structure block has been triggered since the last call, and reset
+    /// the trigger.
+    pub fn take_triggered(&mut self) -> bool {
+        std::mem::take(&mut self.triggered)
+    }
+
+    #[inline]
+    pub fn is_showing_air(&self) -> bool {
+        self.show_air
+    }
+
+    #[inline]
+    pub fn set_show_air(&mut self, show_air: bool) {
+        self.show_air = show_air;
+    }
+
+    #[inline]
+    pub fn is_showing_bounding_box(&self) -> bool {
+        self.show_bounding_box
+    }
+
+    #[inline]
+    pub fn set_show_bounding_box(&mut self, show_bounding_box: bool) {
+        self.show_bounding_box = show_bounding_box;
+    }
+
+    #[inline]
+    pub fn get_integrity(&self) -> f32 {
+        self.integrity
+    }
+
+    /// Set the integrity of the structure, clamped between 0 and 1.
+    pub fn set_integrity(&mut self, integrity: f32) {
+        self.integrity = integrity.clamp(0.0, 1.0);
+    }
+
+    #[inline]
+    pub fn get_seed(&self) -> i64 {
+        self.seed
+    }
+
+    #[inline]
+    pub fn set_seed(&mut self, seed: i64) {
+        self.seed = seed;
+    }
+
+}
+
+fn clamp_pos(pos: BlockPos, min: i32) -> BlockPos {
+    BlockPos::new(
+        pos.x.clamp(min, MAX_STRUCTURE_SIZE),
+        pos.y.clamp(min, MAX_STRUCTURE_SIZE),
+        pos.z.clamp(min, MAX_STRUCTURE_SIZE)
+    )
+}
+
+entity_component!(StructureBlockEntity: StructureBlockEntityCodec);
+
+pub struct StructureBlockEntityCodec;
+impl SingleEntityCodec for StructureBlockEntityCodec {
+
+    type Comp = StructureBlockEntity;
+
+    fn encode(&self, src: &Self::Comp, dst: &mut CompoundTag) {
+        dst.insert_str("name", &src.name);
+        dst.insert_str("author", &src.author);
+        dst.insert_str("metadata", &src.metadata);
+        dst.insert_split_block_pos("posX", "posY", "posZ", &src.offset);
+        dst.insert_split_block_pos("sizeX", "sizeY", "sizeZ", &src.size);
+        dst.insert_str("rotation", src.rotation.get_name());
+        dst.insert_str("mirror", src.mirror.get_name());
+        dst.insert_str("mode", src.mode.prop_to_string().to_uppercase());
+        dst.insert_bool("ignoreEntities", src.ignore_entities);
+        dst.insert_bool("powered", src.powered);
+        dst.insert_bool("showair", src.show_air);
+        dst.insert_bool("showboundingbox", src.show_bounding_box);
+        dst.insert_f32("integrity", src.integrity);
+        dst.insert_i64("seed", src.seed);
+    }
+
+    fn decode(&self, src: &CompoundTag) -> Self::Comp {
+        let default = StructureBlockEntity::default();
+        StructureBlockEntity {
+            name: src.get_str("name").unwrap_or_default().to_string(),
+            author: src.get_str("author").unwrap_or_default().to_string(),
+            metadata: src.get_str("metadata").unwrap_or_default().to_string(),
+            offset: src.get_split_block_pos("posX", "posY", "posZ")
+                .map(|offset| clamp_pos(offset, -MAX_STRUCTURE_SIZE))
+                .unwrap_or(default.offset),
+            size: src.get_split_block_pos("sizeX", "sizeY", "sizeZ")
+                .map(|size| clamp_pos(size, 0))
+                .unwrap_or(default.size),
+            rotation: src.get_str("rotation").ok()
+                .and_then(StructureRotation::from_name)
+                .unwrap_or(default.rotation),
+            mirror: src.get_str("mirror").ok()
+                .and_then(StructureMirror::from_name)
+                .unwrap_or(default.mirror),
+            mode: src.get_str("mode").ok()
+                .and_then(|mode| StructureMode::prop_from_string(&mode.to_lowercase()))
+                .unwrap_or(default.mode),
+            ignore_entities: src.get_bool_or("ignoreEntities", true),
+            powered: src.get_bool_or("powered", false),
+            show_air: src.get_bool_or("showair", false),
+            show_bounding_box: src.get_bool_or("showboundingbox", true),
+            integrity: src.get_f32_or("integrity", 1.0).clamp(0.0, 1.0),
+            seed: src.get_i64_or("seed", 0),
+            triggered: false
+        }
+    }
+
+}
@@ -0,0 +1,304 @@
+//! Command blocks behaviour. Impulse command blocks execute their command once when they are
+//! activated, repeating command blocks execute it every tick while they are active and chain
+//! command blocks execute it after the command block pointing to them. Conditional command
+//! blocks only execute their command if the command block behind them has succeeded.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use mc_core::world::level::{Level, BaseBlockEntity};
+use mc_core::block::{Block, BlockState};
+use mc_core::pos::{BlockPos, Direction, EntityPos};
+use hecs::Entity;
+
+use mc_runtime::command::{CommandSource, execute_command};
+use mc_runtime::world::World;
+
+use crate::block::*;
+use crate::block_entity::CommandBlockEntity;
+
+
+/// Maximum number of chain command blocks executed after a command block, this is the
+/// default value of the `maxCommandChainLength` game rule.
+pub const MAX_COMMAND_CHAIN_LENGTH: usize = 65536;
+
+
+/// The mode of a command block, given by its block.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CommandBlockMode {
+    Impulse,
+    Chain,
+    Repeating
+}
+
+impl CommandBlockMode {
+
+    pub fn from_block(block: &'static Block) -> Option<Self> {
+        if block == &COMMAND_BLOCK {
+            Some(Self::Impulse)
+        } else if block == &CHAIN_COMMAND_BLOCK {
+            Some(Self::Chain)
+        } else if block == &REPEATING_COMMAND_BLOCK {
+            Some(Self::Repeating)
+        } else {
+            None
+        }
+    }
+
+}
+
+
+/// Return the command block entity at the given position, if any.
+pub fn find_command_block(level: &Level, pos: &BlockPos) -> Option<Entity> {
+    let chunk = level.chunks.get_chunk_at(pos.x, pos.z)?;
+    let ecs = &level.entities.ecs;
+    let found = chunk.iter_entities().find(|&entity| {
+        matches!(ecs.get::<BaseBlockEntity>(entity), Ok(base) if base.pos == *pos)
+            && ecs.get::<CommandBlockEntity>(entity).is_ok()
+    });
+    found
+}
+
+/// Check and store the condition of a command block, non-conditional command blocks always
+/// meet their condition. Conditional command blocks meet their condition if the command
+/// block behind them has a success count greater than 0.
+fn update_condition(level: &Level, entity: Entity, pos: &BlockPos, state: &'static BlockState) -> bool {
+
+    let facing: Direction = state.expect(&PROP_FACING);
+    let condition_met = if state.expect(&PROP_CONDITIONAL) {
+        find_command_block(level, &pos.relative(facing.opposite(), 1))
+            .and_then(|behind| level.entities.ecs.get::<CommandBlockEntity>(behind).ok().map(|behind| behind.get_success_count() > 0))
+            .unwrap_or(false)
+    } else {
+        true
+    };
+
+    let mut command_block = level.entities.ecs.get_mut::<CommandBlockEntity>(entity).unwrap();
+    command_block.set_condition_met(condition_met);
+    condition_met
+
+}
+
+/// Execute the command of a command block through the world's command dispatcher, and store
+/// its success count and output. Command blocks without command have a success count of 0.
+fn perform_command(world: &mut World, level_rc: &Rc<RefCell<Level>>, entity: Entity, pos: &BlockPos) {
+
+    let command = {
+        let level = level_rc.borrow();
+        let mut command_block = level.entities.ecs.get_mut::<CommandBlockEntity>(entity).unwrap();
+        if command_block.get_command().is_empty() {
+            command_block.set_success_count(0);
+            return;
+        }
+        command_block.get_command().to_string()
+    };
+
+    let pos = EntityPos::new(pos.x as f64 + 0.5, pos.y as f64 + 0.5, pos.z as f64 + 0.5);
+    let source = CommandSource::new("@".to_string(), Rc::clone(level_rc), pos);
+    let result = execute_command(world, &source, &command);
+
+    let level = level_rc.borrow();
+    if let Ok(mut command_block) = level.entities.ecs.get_mut::<CommandBlockEntity>(entity) {
+        match result {
+            Ok(success_count) => {
+                command_block.set_success_count(success_count);
+                command_block.set_last_output(String::new());
+            }
+            Err(err) => {
+                command_block.set_success_count(0);
+                command_block.set_last_output(err.to_string());
+            }
+        }
+    };
+
+}
+
+/// Execute the chain command blocks following the command block at the given position,
+/// chain command blocks are followed in the direction they are facing.
+fn execute_chain(world: &mut World, level_rc: &Rc<RefCell<Level>>, mut pos: BlockPos, mut facing: Direction) {
+
+    for _ in 0..MAX_COMMAND_CHAIN_LENGTH {
+
+        pos = pos.relative(facing, 1);
+
+        let (state, entity, condition_met) = {
+            let level = level_rc.borrow();
+            let state = match level.chunks.get_block_at(pos.x, pos.y, pos.z) {
+                Ok(state) if state.is_block(&CHAIN_COMMAND_BLOCK) => state,
+                _ => break
+            };
+            let entity = match find_command_block(&level, &pos) {
+                Some(entity) => entity,
+                None => break
+            };
+            if !level.entities.ecs.get::<CommandBlockEntity>(entity).unwrap().is_active() {
+                facing = state.expect(&PROP_FACING);
+                continue;
+            }
+            (state, entity, update_condition(&level, entity, &pos, state))
+        };
+
+        if condition_met {
+            perform_command(world, level_rc, entity, &pos);
+        } else if state.expect(&PROP_CONDITIONAL) {
+            let level = level_rc.borrow();
+            if let Ok(mut command_block) = level.entities.ecs.get_mut::<CommandBlockEntity>(entity) {
+                command_block.set_success_count(0);
+            };
+        }
+
+        facing = state.expect(&PROP_FACING);
+
+    }
+
+}
+
+
+/// A system executing activated impulse command blocks and active repeating command blocks
+/// of all levels, followed by their chains of command blocks.
+pub fn system_command_blocks(world: &mut World) {
+
+    let levels = world.levels.clone();
+
+    for level_rc in &levels {
+
+        let mut starts = Vec::new();
+
+        {
+            let mut level = level_rc.borrow_mut();
+            let level = &mut *level;
+            for (entity, (base, command_block)) in level.entities.ecs.query_mut::<(&BaseBlockEntity, &mut CommandBlockEntity)>() {
+                let state = match level.chunks.get_block_at(base.pos.x, base.pos.y, base.pos.z) {
+                    Ok(state) => state,
+                    Err(_) => continue
+                };
+                let execute = match CommandBlockMode::from_block(state.get_block()) {
+                    Some(CommandBlockMode::Impulse) => std::mem::take(&mut command_block.pending),
+                    Some(CommandBlockMode::Repeating) => command_block.is_active(),
+                    _ => false
+                };
+                if execute {
+                    starts.push((base.pos.clone(), entity, state));
+                }
+            }
+        }
+
+        // Command blocks are executed in a deterministic order.
+        starts.sort_unstable_by_key(|(pos, _, _)| (pos.x, pos.y, pos.z));
+
+        for (pos, entity, state) in starts {
+            let condition_met = update_condition(&level_rc.borrow(), entity, &pos, state);
+            if condition_met {
+                perform_command(world, level_rc, entity, &pos);
+                execute_chain(world, level_rc, pos, state.expect(&PROP_FACING));
+            } else if state.expect(&PROP_CONDITIONAL) {
+                let level = level_rc.borrow();
+                level.entities.ecs.get_mut::<CommandBlockEntity>(entity).unwrap().set_success_count(0);
+            }
+        }
+
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_core::world::level::LevelEnv;
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::source::{ChunkLoadRequest, NullLevelSource};
+    use mc_runtime::command::{CommandDispatcher, CommandResult, CommandError};
+
+    use crate::ext::VanillaLevelEnv;
+    use crate::block_entity::COMMAND_BLOCK as COMMAND_BLOCK_ENTITY;
+
+    use super::*;
+
+    /// Commands executed by the test, in order.
+    struct Executed(Vec<String>);
+
+    fn command_log(world: &mut World, _source: &CommandSource, args: &str) -> CommandResult {
+        world.get_component_mut::<Executed>().unwrap().0.push(args.to_string());
+        Ok(1)
+    }
+
+    fn command_fail(_world: &mut World, _source: &CommandSource, _args: &str) -> CommandResult {
+        Err(CommandError::Failed("Failed.".to_string()))
+    }
+
+    fn place(level: &mut Level, x: i32, block: &'static Block, facing: Direction, conditional: bool, command: &str) -> Entity {
+        let state = block.get_default_state()
+            .with(&PROP_FACING, facing).unwrap()
+            .with(&PROP_CONDITIONAL, conditional).unwrap();
+        level.chunks.set_block_at(x, 0, 0, state).unwrap();
+        let entity = level.spawn_block_entity(&COMMAND_BLOCK_ENTITY, BlockPos::new(x, 0, 0)).unwrap();
+        level.entities.ecs.get_mut::<CommandBlockEntity>(entity).unwrap().set_command(command.to_string());
+        entity
+    }
+
+    fn take_executed(world: &mut World) -> Vec<String> {
+        std::mem::take(&mut world.get_component_mut::<Executed>().unwrap().0)
+    }
+
+    #[test]
+    fn command_blocks() {
+
+        let env = LevelEnv::vanilla();
+        let height = ChunkHeight::new(0, 0);
+        let mut level = Level::new("overworld".to_string(), env.clone(), height, NullLevelSource);
+        level.chunks.insert_chunk(ChunkLoadRequest { env, height, cx: 0, cz: 0 }.build_chunk());
+
+        // An impulse command block followed by a chain, the conditional block after the
+        // failing command is not executed, the inactive one stops nothing.
+        let impulse = place(&mut level, 0, &COMMAND_BLOCK, Direction::East, false, "log first");
+        let chain = [
+            place(&mut level, 1, &CHAIN_COMMAND_BLOCK, Direction::East, false, "fail"),
+            place(&mut level, 2, &CHAIN_COMMAND_BLOCK, Direction::East, true, "log not executed"),
+            place(&mut level, 3, &CHAIN_COMMAND_BLOCK, Direction::East, false, "log inactive"),
+            place(&mut level, 4, &CHAIN_COMMAND_BLOCK, Direction::East, false, "log last"),
+        ];
+        for &entity in &[chain[0], chain[1], chain[3]] {
+            level.entities.ecs.get_mut::<CommandBlockEntity>(entity).unwrap().set_auto(true);
+        }
+
+        let repeating = place(&mut level, 8, &REPEATING_COMMAND_BLOCK, Direction::Up, false, "log repeat");
+
+        let level = Rc::new(RefCell::new(level));
+        let mut world = World::new();
+        world.levels.push(Rc::clone(&level));
+        world.insert_component(Executed(Vec::new()));
+        world.insert_component(CommandDispatcher::new()
+            .with_command("log", command_log)
+            .with_command("fail", command_fail));
+
+        // Nothing is executed until powered.
+        system_command_blocks(&mut world);
+        assert!(take_executed(&mut world).is_empty());
+
+        level.borrow().entities.ecs.get_mut::<CommandBlockEntity>(impulse).unwrap().set_powered(true);
+        system_command_blocks(&mut world);
+        assert_eq!(take_executed(&mut world), vec!["first", "last"]);
+
+        {
+            let level = level.borrow();
+            let ecs = &level.entities.ecs;
+            assert_eq!(ecs.get::<CommandBlockEntity>(impulse).unwrap().get_success_count(), 1);
+            assert_eq!(ecs.get::<CommandBlockEntity>(chain[0]).unwrap().get_success_count(), 0);
+            assert_eq!(ecs.get::<CommandBlockEntity>(chain[0]).unwrap().get_last_output(), "Failed.");
+            assert!(!ecs.get::<CommandBlockEntity>(chain[1]).unwrap().is_condition_met());
+        }
+
+        // Impulse command blocks are only executed once while powered.
+        system_command_blocks(&mut world);
+        assert!(take_executed(&mut world).is_empty());
+
+        // Repeating command blocks are executed every tick while active.
+        level.borrow().entities.ecs.get_mut::<CommandBlockEntity>(repeating).unwrap().set_auto(true);
+        system_command_blocks(&mut world);
+        system_command_blocks(&mut world);
+        assert_eq!(take_executed(&mut world), vec!["repeat", "repeat"]);
+
+    }
+
+}
@@ -0,0 +1,257 @@
+//! Functions of datapacks, a function is a list of commands loaded from a file
+//! `data/<namespace>/functions/<path>.mcfunction` of a datapack, and named `<namespace>:<path>`.
+//! Functions are executed with the `/function` command.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::rc::Rc;
+use std::{fs, io};
+
+use mc_runtime::command::{CommandSource, CommandResult, CommandError, SuggestionProvider, execute_command};
+use mc_runtime::world::World;
+
+use super::block::MAX_COMMAND_CHAIN_LENGTH;
+
+
+/// A world component storing the functions loaded from datapacks.
+#[derive(Default)]
+pub struct Functions {
+    functions: HashMap<String, Rc<[String]>>,
+    /// True while a function is being executed.
+    executing: bool,
+    /// Functions called by the command being executed, they are executed after it.
+    nested: Vec<Rc<[String]>>
+}
+
+impl Functions {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load all functions of the datapack at the given path, functions with the same name as
+    /// already loaded functions replace them. Return the number of functions loaded.
+    pub fn load_datapack<P: AsRef<Path>>(&mut self, path: P) -> io::Result<usize> {
+
+        let mut count = 0;
+
+        for namespace in fs::read_dir(path.as_ref().join("data"))? {
+            let namespace = namespace?;
+            let functions_dir = namespace.path().join("functions");
+            if let (Some(namespace), true) = (namespace.file_name().to_str(), functions_dir.is_dir()) {
+                count += self.load_functions_dir(&functions_dir, &format!("{}:", namespace))?;
+            }
+        }
+
+        Ok(count)
+
+    }
+
+    fn load_functions_dir(&mut self, dir: &Path, prefix: &str) -> io::Result<usize> {
+
+        let mut count = 0;
+
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            let file_name = match entry.file_name().to_str() {
+                Some(file_name) => file_name.to_string(),
+                None => continue
+            };
+            if entry.file_type()?.is_dir() {
+                count += self.load_functions_dir(&path, &format!("{}{}/", prefix, file_name))?;
+            } else if let Some(name) = file_name.strip_suffix(".mcfunction") {
+                let name = format!("{}{}", prefix, name);
+                self.add_function(name, &fs::read_to_string(&path)?)
+                    .map_err(|message| io::Error::new(io::ErrorKind::InvalidData, message))?;
+                count += 1;
+            }
+        }
+
+        Ok(count)
+
+    }
+
+    /// Parse and add a function from its source, empty lines and comments starting with `#`
+    /// are ignored. Like vanilla, commands must not start with a slash, an error message is
+    /// returned if it's the case.
+    pub fn add_function(&mut self, name: String, source: &str) -> Result<(), String> {
+
+        let mut commands = Vec::new();
+
+        for (index, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            } else if line.starts_with('/') {
+                return Err(format!("Command '{}' on line {} of function '{}' must not start with a slash.", line, index + 1, name));
+            }
+            commands.push(line.to_string());
+        }
+
+        self.functions.insert(name, commands.into());
+        Ok(())
+
+    }
+
+    pub fn get_function(&self, name: &str) -> Option<&[String]> {
+        self.functions.get(name).map(|commands| &commands[..])
+    }
+
+    pub fn get_functions_count(&self) -> usize {
+        self.functions.len()
+    }
+
+}
+
+
+/// Execute a function with the given source, each command is executed even if previous ones
+/// have failed. Functions called while another function is being executed are not executed
+/// immediately, but after the calling command, this avoids deep recursions. The number of
+/// commands executed is returned, or the number of commands of the function if it's called
+/// from another function. At most `MAX_COMMAND_CHAIN_LENGTH` commands are executed.
+pub fn execute_function(world: &mut World, source: &CommandSource, name: &str) -> CommandResult {
+
+    let commands = {
+        let mut functions = world.components.get_mut::<Functions>()
+            .map_err(|_| CommandError::Failed("No function is loaded.".to_string()))?;
+        let commands = functions.functions.get(name)
+            .map(Rc::clone)
+            .ok_or_else(|| CommandError::InvalidArguments(format!("Unknown function '{}'.", name)))?;
+        if functions.executing {
+            let len = commands.len() as i32;
+            functions.nested.push(commands);
+            return Ok(len);
+        }
+        functions.executing = true;
+        commands
+    };
+
+    // Stack of functions being executed with the index of their next command.
+    let mut stack = vec![(commands, 0)];
+    let mut count = 0;
+
+    while count < MAX_COMMAND_CHAIN_LENGTH {
+
+        let command = match stack.last_mut() {
+            Some((commands, index)) if *index < commands.len() => {
+                *index += 1;
+                commands[*index - 1].clone()
+            }
+            Some(_) => {
+                stack.pop();
+                continue;
+            }
+            None => break
+        };
+
+        let _ = execute_command(world, source, &command);
+        count += 1;
+
+        let nested = std::mem::take(&mut world.components.get_mut::<Functions>().unwrap().nested);
+        stack.extend(nested.into_iter().rev().map(|commands| (commands, 0)));
+
+    }
+
+    let mut functions = world.components.get_mut::<Functions>().unwrap();
+    functions.executing = false;
+    functions.nested.clear();
+    Ok(count as i32)
+
+}
+
+
+/// Suggest the names of all loaded functions, for the `/function` command.
+pub struct FunctionSuggestions;
+
+impl SuggestionProvider for FunctionSuggestions {
+    fn suggest(&self, world: &World, _source: &CommandSource, _arg: &str, suggestions: &mut Vec<String>) {
+        if let Ok(functions) = world.get_component::<Functions>() {
+            suggestions.extend(functions.functions.keys().cloned());
+        }
+    }
+}
+
+
+/// Handler of the `/function <name>` command, the namespace of the function defaults to
+/// `minecraft`.
+pub fn command_function(world: &mut World, source: &CommandSource, args: &str) -> CommandResult {
+    let name = args.trim();
+    if name.is_empty() {
+        return Err(CommandError::InvalidArguments("Expected a function name.".to_string()));
+    }
+    if name.contains(':') {
+        execute_function(world, source, name)
+    } else {
+        execute_function(world, source, &format!("minecraft:{}", name))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use std::cell::RefCell;
+
+    use mc_core::world::level::{Level, LevelEnv};
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::source::NullLevelSource;
+    use mc_core::pos::EntityPos;
+    use mc_runtime::command::{CommandDispatcher, complete_command};
+
+    use crate::ext::VanillaLevelEnv;
+
+    use super::*;
+
+    struct Counter(u32);
+
+    fn command_count(world: &mut World, _source: &CommandSource, _args: &str) -> CommandResult {
+        world.get_component_mut::<Counter>().unwrap().0 += 1;
+        Ok(1)
+    }
+
+    #[test]
+    fn functions() {
+
+        let dir = std::env::temp_dir().join(format!("mc-vanilla-functions-{}", std::process::id()));
+        let functions_dir = dir.join("data/test/functions/nested");
+        fs::create_dir_all(&functions_dir).unwrap();
+        fs::write(dir.join("data/test/functions/main.mcfunction"), "# Comment\ncount\n\nfunction test:nested/twice\n").unwrap();
+        fs::write(functions_dir.join("twice.mcfunction"), "count\ncount\n").unwrap();
+        fs::write(functions_dir.join("loop.mcfunction"), "count\nfunction test:nested/loop\n").unwrap();
+        fs::write(functions_dir.join("ignored.txt"), "count").unwrap();
+
+        let mut functions = Functions::new();
+        assert_eq!(functions.load_datapack(&dir).unwrap(), 3);
+        assert_eq!(functions.get_function("test:main").unwrap(), &["count", "function test:nested/twice"]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(functions.add_function("test:slash".to_string(), "/count").is_err());
+
+        let level = Level::new("overworld".to_string(), LevelEnv::vanilla(), ChunkHeight::new(0, 0), NullLevelSource);
+        let source = CommandSource::new("@".to_string(), Rc::new(RefCell::new(level)), EntityPos::new(0.0, 0.0, 0.0));
+
+        let mut world = World::new();
+        world.insert_component(functions);
+        world.insert_component(Counter(0));
+        world.insert_component(CommandDispatcher::new()
+            .with_command("count", command_count)
+            .with_command("function", command_function)
+            .with_suggestions("function", 0, FunctionSuggestions));
+
+        let suggestions = complete_command(&world, &source, "/function test:n");
+        assert_eq!(suggestions.start, 10);
+        assert_eq!(suggestions.matches, vec!["test:nested/loop", "test:nested/twice"]);
+
+        assert_eq!(execute_command(&mut world, &source, "function test:main"), Ok(4));
+        assert_eq!(world.get_component::<Counter>().unwrap().0, 3);
+        assert!(execute_command(&mut world, &source, "function test:unknown").is_err());
+
+        // Recursive functions are stopped after the maximum number of commands.
+        world.get_component_mut::<Counter>().unwrap().0 = 0;
+        assert_eq!(execute_command(&mut world, &source, "function test:nested/loop"), Ok(MAX_COMMAND_CHAIN_LENGTH as i32));
+        assert_eq!(world.get_component::<Counter>().unwrap().0 as usize, MAX_COMMAND_CHAIN_LENGTH / 2);
+
+    }
+
+}
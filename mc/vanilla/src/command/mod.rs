@@ -0,0 +1,14 @@
+//! Vanilla commands, command blocks and datapack functions. Commands are executed through
+//! the `CommandDispatcher` component of the runtime.
+
+use mc_runtime::command::CommandDispatcher;
+
+pub mod block;
+pub mod function;
+
+
+/// Register the vanilla commands implemented by this crate to the given dispatcher.
+pub fn register_vanilla_commands(dispatcher: &mut CommandDispatcher) {
+    dispatcher.register("function", function::command_function);
+    dispatcher.register_suggestions("function", 0, function::FunctionSuggestions);
+}
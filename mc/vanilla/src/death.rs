@@ -0,0 +1,468 @@
+//! Death and respawn of players, the `system_deaths` system marks players without health as
+//! `Dead`, drops their inventory and pushes a `PlayerDeathEvent`. Dead players are then
+//! respawned with `respawn_player`, at their bed or respawn anchor if it's still usable, or
+//! at the world spawn otherwise.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use mc_core::world::level::{Level, BaseEntity};
+use mc_core::item::{ItemStack, Inventory};
+use mc_core::pos::{EntityPos, BlockPos};
+use hecs::Entity;
+
+use mc_runtime::world::World;
+
+use crate::block::material::TAG_NON_BLOCKING;
+use crate::block::*;
+use crate::entity::{LivingEntity, PlayerEntity, ITEM};
+use crate::entity::item::ItemEntity;
+use crate::sleep::{Sleeper, find_bed_head, wake_up};
+
+
+/// Health of players when they join or respawn.
+pub const PLAYER_MAX_HEALTH: f32 = 20.0;
+
+/// Pickup delay in ticks of items dropped by dead players.
+const DEATH_DROP_PICKUP_DELAY: u32 = 40;
+/// Height above the position of the dead player where its items are dropped.
+const DEATH_DROP_HEIGHT: f64 = 1.32;
+
+
+/// World component configuring deaths and respawns of players.
+pub struct Deaths {
+    keep_inventory: bool,
+    show_death_messages: bool,
+    /// The level ID and the position where players without spawn point respawn.
+    world_spawn: Option<(String, BlockPos)>
+}
+
+impl Deaths {
+
+    pub fn new() -> Self {
+        Self {
+            keep_inventory: false,
+            show_death_messages: true,
+            world_spawn: None
+        }
+    }
+
+    /// Keep the inventory of dead players instead of dropping it, like the `keepInventory`
+    /// game rule.
+    pub fn with_keep_inventory(mut self, keep_inventory: bool) -> Self {
+        self.keep_inventory = keep_inventory;
+        self
+    }
+
+    /// Like the `showDeathMessages` game rule, this is only forwarded to death events.
+    pub fn with_death_messages(mut self, show_death_messages: bool) -> Self {
+        self.show_death_messages = show_death_messages;
+        self
+    }
+
+    /// Set the world spawn, the origin of the first level is used by default.
+    pub fn with_world_spawn(mut self, level_id: String, pos: BlockPos) -> Self {
+        self.world_spawn = Some((level_id, pos));
+        self
+    }
+
+    #[inline]
+    pub fn is_keep_inventory(&self) -> bool {
+        self.keep_inventory
+    }
+
+    #[inline]
+    pub fn is_showing_death_messages(&self) -> bool {
+        self.show_death_messages
+    }
+
+    pub fn get_world_spawn(&self) -> Option<(&String, &BlockPos)> {
+        self.world_spawn.as_ref().map(|(level_id, pos)| (level_id, pos))
+    }
+
+}
+
+impl Default for Deaths {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// An entity component marking dead players until they respawn.
+#[derive(Debug, Clone, Copy)]
+pub struct Dead;
+
+
+/// Event pushed by `system_deaths` when a player dies.
+pub struct PlayerDeathEvent {
+    pub level: Rc<RefCell<Level>>,
+    pub entity: Entity,
+    /// The position where the player died.
+    pub pos: EntityPos,
+    /// True if a death message should be shown to other players.
+    pub show_message: bool
+}
+
+
+/// Where a dead player respawns, computed by `find_respawn_point`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RespawnPoint {
+    /// Index of the level in the world.
+    pub level_idx: usize,
+    pub pos: EntityPos,
+    /// The yaw of the player, looking at its bed or respawn anchor.
+    pub yaw: f32,
+    /// True if the player had a spawn point that is no longer usable, it has been removed
+    /// and the player respawns at the world spawn.
+    pub spawn_point_missing: bool
+}
+
+
+/// Call this function with a mutable reference to a World to register the `Deaths`
+/// component which is required to run the system `system_deaths` and to respawn players.
+pub fn register_deaths(world: &mut World, deaths: Deaths) {
+    world.insert_component(deaths);
+}
+
+/// A system marking players without health as `Dead`, they are woken up if sleeping and
+/// their inventory is dropped unless the inventory is kept. A `PlayerDeathEvent` is pushed
+/// for each new death.
+pub fn system_deaths(world: &mut World) {
+
+    let deaths = match world.components.get::<Deaths>() {
+        Ok(deaths) => deaths,
+        Err(_) => return
+    };
+
+    for level_rc in &world.levels {
+
+        let mut level = level_rc.borrow_mut();
+
+        let dead: Vec<_> = level.entities.ecs.query::<(&BaseEntity, &LivingEntity)>()
+            .with::<PlayerEntity>()
+            .without::<Dead>()
+            .iter()
+            .filter(|(_, (_, living))| living.get_health() <= 0.0)
+            .map(|(entity, (base, _))| (entity, base.pos.clone()))
+            .collect();
+
+        for (entity, pos) in dead {
+
+            level.entities.ecs.insert_one(entity, Dead).unwrap();
+            wake_up(&mut level, entity);
+
+            if !deaths.keep_inventory {
+                let stacks = match level.entities.ecs.get_mut::<Inventory>(entity) {
+                    Ok(mut inventory) => (0..inventory.get_size())
+                        .filter_map(|index| inventory.take_stack(index))
+                        .collect(),
+                    Err(_) => Vec::new()
+                };
+                drop_stacks(&mut level, &pos, stacks);
+            }
+
+            world.event_tracker.push_event(PlayerDeathEvent {
+                level: Rc::clone(level_rc),
+                entity,
+                pos,
+                show_message: deaths.show_death_messages
+            });
+
+        }
+
+    }
+
+}
+
+
+/// Spawn item entities for the stacks dropped by a dead player.
+fn drop_stacks(level: &mut Level, pos: &EntityPos, stacks: Vec<ItemStack>) {
+    for stack in stacks {
+        let pos = EntityPos::new(pos.x, pos.y + DEATH_DROP_HEIGHT, pos.z);
+        if let Some(entity) = level.spawn_entity(&ITEM, pos) {
+            let item = ItemEntity::new(stack).with_pickup_delay(DEATH_DROP_PICKUP_DELAY);
+            level.entities.ecs.insert_one(entity, item).unwrap();
+        }
+    }
+}
+
+
+/// Find the position where an entity can stand up around a bed or a respawn anchor, the
+/// feet and head blocks must not block movements and the block below must. Positions at
+/// the height of the block are tried first, then above and below it.
+pub fn find_stand_up_pos(level: &Level, pos: &BlockPos) -> Option<EntityPos> {
+
+    let blocks = &level.get_env().blocks;
+    let is_blocking = |pos: &BlockPos| match level.chunks.get_block_at(pos.x, pos.y, pos.z) {
+        Ok(state) => !blocks.has_block_tag(state.get_block(), &TAG_NON_BLOCKING),
+        Err(_) => true
+    };
+
+    for dy in [0, 1, -1] {
+        for dx in -1..=1 {
+            for dz in -1..=1 {
+                let feet = pos.add(dx, dy, dz);
+                if !is_blocking(&feet) && !is_blocking(&feet.above(1)) && is_blocking(&feet.below(1)) {
+                    return Some(EntityPos::new(feet.x as f64 + 0.5, feet.y as f64, feet.z as f64 + 0.5));
+                }
+            }
+        }
+    }
+
+    None
+
+}
+
+
+/// Internal function to check the spawn point of an entity in its level and to return the
+/// position where it can respawn. A charge of respawn anchors is consumed.
+fn use_spawn_point(level: &Level, pos: &BlockPos) -> Option<EntityPos> {
+
+    let dimension_type = level.get_dimension_type();
+
+    if let Some((head_pos, _)) = find_bed_head(level, pos) {
+        if dimension_type.bed_works {
+            return find_stand_up_pos(level, &head_pos);
+        }
+    } else if let Ok(state) = level.chunks.get_block_at(pos.x, pos.y, pos.z) {
+        if state.is_block(&RESPAWN_ANCHOR) && dimension_type.respawn_anchor_works {
+            let charges: u8 = state.get(&PROP_CHARGES)?;
+            if charges > 0 {
+                let stand_up_pos = find_stand_up_pos(level, pos)?;
+                let state = state.with(&PROP_CHARGES, charges - 1)?;
+                let _ = level.chunks.set_block_at(pos.x, pos.y, pos.z, state);
+                return Some(stand_up_pos);
+            }
+        }
+    }
+
+    None
+
+}
+
+
+/// Return the yaw of an entity at the given position looking at the center of a block.
+fn look_at_yaw(from: &EntityPos, to: &BlockPos) -> f32 {
+    let dx = to.x as f64 + 0.5 - from.x;
+    let dz = to.z as f64 + 0.5 - from.z;
+    (-dx.atan2(dz).to_degrees()) as f32
+}
+
+
+/// Find where a player must respawn, its spawn point is used if its bed or its charged
+/// respawn anchor is still present, works in its dimension and isn't obstructed. Otherwise
+/// the spawn point is removed and the world spawn is used.
+///
+/// Return `None` if the entity has no `Sleeper` component or if the world spawn level
+/// doesn't exist.
+pub fn find_respawn_point(world: &World, level_idx: usize, entity: Entity) -> Option<RespawnPoint> {
+
+    let spawn_point = {
+        let level = world.levels.get(level_idx)?.borrow();
+        let sleeper = level.entities.ecs.get::<Sleeper>(entity).ok()?;
+        sleeper.get_spawn_point().map(|(level_id, pos)| (level_id.clone(), pos.clone()))
+    };
+
+    let spawn_point_missing = spawn_point.is_some();
+
+    if let Some((level_id, pos)) = spawn_point {
+        let spawn_level_idx = world.levels.iter().position(|level| level.borrow().get_id() == &level_id);
+        if let Some(spawn_level_idx) = spawn_level_idx {
+            if let Some(stand_up_pos) = use_spawn_point(&world.levels[spawn_level_idx].borrow(), &pos) {
+                return Some(RespawnPoint {
+                    level_idx: spawn_level_idx,
+                    yaw: look_at_yaw(&stand_up_pos, &pos),
+                    pos: stand_up_pos,
+                    spawn_point_missing: false
+                });
+            }
+        }
+        let level = world.levels[level_idx].borrow();
+        level.entities.ecs.get_mut::<Sleeper>(entity).unwrap().clear_spawn_point();
+    }
+
+    let deaths = world.components.get::<Deaths>().ok();
+    let (level_idx, pos) = match deaths.as_ref().and_then(|deaths| deaths.get_world_spawn()) {
+        Some((level_id, pos)) => {
+            let level_idx = world.levels.iter().position(|level| level.borrow().get_id() == level_id)?;
+            (level_idx, pos.clone())
+        }
+        None => (0, BlockPos::nil())
+    };
+
+    Some(RespawnPoint {
+        level_idx,
+        pos: EntityPos::from(&pos),
+        yaw: 0.0,
+        spawn_point_missing
+    })
+
+}
+
+
+/// Respawn a dead player at the given respawn point, its health is restored and it's moved
+/// to the respawn point. If the respawn point is in another level, the entity is transferred
+/// with its `Sleeper` and `Inventory`, other components without codec must be moved by the
+/// caller.
+///
+/// Return the entity of the player in the level of the respawn point, `None` if the entity
+/// doesn't exist or can't be transferred.
+pub fn respawn_player(world: &World, level_idx: usize, entity: Entity, point: &RespawnPoint) -> Option<Entity> {
+
+    let entity = if point.level_idx == level_idx {
+        let mut level = world.levels[level_idx].borrow_mut();
+        if !level.move_entity(entity, point.pos.clone()) {
+            return None;
+        }
+        entity
+    } else {
+        let mut level = world.levels[level_idx].borrow_mut();
+        let mut dst = world.levels.get(point.level_idx)?.borrow_mut();
+        let sleeper = level.entities.ecs.remove_one::<Sleeper>(entity).ok();
+        let inventory = level.entities.ecs.remove_one::<Inventory>(entity).ok();
+        // Components are put back on the entity if it can't be transferred.
+        let new_entity = level.transfer_entity(&mut dst, entity, point.pos.clone());
+        let (target, target_entity) = match new_entity {
+            Some(new_entity) => (&mut dst, new_entity),
+            None => (&mut level, entity)
+        };
+        if let Some(sleeper) = sleeper {
+            target.entities.ecs.insert_one(target_entity, sleeper).unwrap();
+        }
+        if let Some(inventory) = inventory {
+            target.entities.ecs.insert_one(target_entity, inventory).unwrap();
+        }
+        new_entity?
+    };
+
+    let mut level = world.levels[point.level_idx].borrow_mut();
+    let _ = level.entities.ecs.remove_one::<Dead>(entity);
+    if let Ok(mut living) = level.entities.ecs.get_mut::<LivingEntity>(entity) {
+        living.set_health(PLAYER_MAX_HEALTH);
+    }
+
+    Some(entity)
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use mc_core::world::level::LevelEnv;
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::dimension::DimensionType;
+    use mc_core::world::source::{ChunkLoadRequest, NullLevelSource};
+    use mc_core::pos::{Direction, BoundingBox};
+
+    use crate::ext::VanillaLevelEnv;
+    use crate::entity::PLAYER;
+
+    use super::*;
+
+    mc_core::items!(TEST_ITEMS "test" [
+        PEBBLE "pebble"
+    ]);
+
+    fn new_level(id: &str, respawn_anchor_works: bool) -> Level {
+
+        let env = LevelEnv::vanilla();
+        let height = ChunkHeight::new(0, 0);
+        let dimension_type = DimensionType {
+            bed_works: !respawn_anchor_works,
+            respawn_anchor_works,
+            ..DimensionType::new("test:dimension", height)
+        };
+
+        let level = Level::new(id.to_string(), env.clone(), height, NullLevelSource)
+            .with_dimension_type(Arc::new(dimension_type));
+        let chunk = ChunkLoadRequest { env, height, cx: 0, cz: 0 }.build_chunk();
+        level.chunks.insert_chunk(chunk);
+        level.chunks.fill_region(&BoundingBox::new(0, 0, 0, 15, 0, 15), STONE.get_default_state()).unwrap();
+        level
+
+    }
+
+    #[test]
+    fn death_and_respawn() {
+
+        let mut overworld = new_level("overworld", false);
+        let nether = new_level("nether", true);
+
+        // A bed with its head at (4, 1, 4) and its foot to the south.
+        let bed = RED_BED.get_default_state().with(&PROP_HORIZONTAL_FACING, Direction::North).unwrap();
+        overworld.chunks.set_block_at(4, 1, 4, bed.with(&PROP_BED_PART, BedPart::Head).unwrap()).unwrap();
+        overworld.chunks.set_block_at(4, 1, 5, bed.with(&PROP_BED_PART, BedPart::Foot).unwrap()).unwrap();
+
+        let anchor = RESPAWN_ANCHOR.get_default_state().with(&PROP_CHARGES, 1u8).unwrap();
+        nether.chunks.set_block_at(8, 1, 8, anchor).unwrap();
+
+        let player = overworld.spawn_entity(&PLAYER, EntityPos::new(2.5, 1.0, 2.5)).unwrap();
+        let mut sleeper = Sleeper::new();
+        sleeper.set_spawn_point("overworld".to_string(), BlockPos::new(4, 1, 4));
+        let mut inventory = Inventory::new(4);
+        inventory.set_stack(1, Some(ItemStack::with_item_count(&PEBBLE, 5)));
+        overworld.entities.ecs.insert(player, (sleeper, inventory)).unwrap();
+
+        let mut world = World::new();
+        world.add_level(overworld);
+        world.add_level(nether);
+        register_deaths(&mut world, Deaths::new());
+
+        system_deaths(&mut world);
+        assert_eq!(world.event_tracker.poll_events::<PlayerDeathEvent>().count(), 1);
+
+        {
+            let level = world.levels[0].borrow();
+            assert!(level.entities.ecs.get::<Dead>(player).is_ok());
+            assert!(level.entities.ecs.get::<Inventory>(player).unwrap().get_stack(1).is_none());
+            assert_eq!(level.entities.ecs.query::<&ItemEntity>().iter().count(), 1);
+        }
+
+        // Already dead players don't die again.
+        system_deaths(&mut world);
+        assert_eq!(world.event_tracker.poll_events::<PlayerDeathEvent>().count(), 1);
+
+        let point = find_respawn_point(&world, 0, player).unwrap();
+        assert_eq!(point.level_idx, 0);
+        assert!(!point.spawn_point_missing);
+        assert_eq!(BlockPos::from(&point.pos).y, 1);
+        assert!(find_stand_up_pos(&world.levels[0].borrow(), &BlockPos::new(4, 1, 4)).is_some());
+
+        let player = respawn_player(&world, 0, player, &point).unwrap();
+        {
+            let level = world.levels[0].borrow();
+            assert!(level.entities.ecs.get::<Dead>(player).is_err());
+            assert_eq!(level.entities.ecs.get::<LivingEntity>(player).unwrap().get_health(), PLAYER_MAX_HEALTH);
+            // Set the spawn point to the anchor of the nether.
+            level.entities.ecs.get_mut::<Sleeper>(player).unwrap().set_spawn_point("nether".to_string(), BlockPos::new(8, 1, 8));
+        }
+
+        // The anchor is used and discharged, the player is transferred to the nether.
+        let point = find_respawn_point(&world, 0, player).unwrap();
+        assert_eq!(point.level_idx, 1);
+        assert_eq!(world.levels[1].borrow().chunks.get_block_at(8, 1, 8).unwrap().get(&PROP_CHARGES), Some(0u8));
+        let player = respawn_player(&world, 0, player, &point).unwrap();
+        {
+            let level = world.levels[1].borrow();
+            assert!(level.entities.ecs.get::<Sleeper>(player).is_ok());
+            assert!(level.entities.ecs.get::<PlayerEntity>(player).is_ok());
+            assert_eq!(world.levels[0].borrow().entities.ecs.query::<&PlayerEntity>().iter().count(), 0);
+        }
+
+        // The anchor is no longer charged, the player respawns at the world spawn.
+        let point = find_respawn_point(&world, 1, player).unwrap();
+        assert_eq!(point, RespawnPoint {
+            level_idx: 0,
+            pos: EntityPos::new(0.0, 0.0, 0.0),
+            yaw: 0.0,
+            spawn_point_missing: true
+        });
+        assert!(world.levels[1].borrow().entities.ecs.get::<Sleeper>(player).unwrap().get_spawn_point().is_none());
+
+    }
+
+}
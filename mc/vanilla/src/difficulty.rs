@@ -0,0 +1,113 @@
+//! Regional difficulty, the effective difficulty at a position increases with the time
+//! spent in the level, the time spent by players in the chunk and the moon phase.
+
+use mc_core::world::level::Level;
+use mc_core::pos::BlockPos;
+
+use crate::time::LevelTime;
+use crate::util::Difficulty;
+
+
+/// The regional difficulty at a position, computed like vanilla.
+#[derive(Debug, Copy, Clone)]
+pub struct RegionalDifficulty {
+    difficulty: Difficulty,
+    effective: f32
+}
+
+impl RegionalDifficulty {
+
+    /// Compute the regional difficulty from the level difficulty and time, and from the
+    /// inhabited time of the chunk.
+    pub fn new(difficulty: Difficulty, time: &LevelTime, inhabited_time: u64) -> Self {
+        Self {
+            difficulty,
+            effective: calc_effective_difficulty(difficulty, time.get_day_time(), inhabited_time, time.get_moon_brightness())
+        }
+    }
+
+    /// Compute the regional difficulty at the given position in a level, the inhabited time
+    /// is 0 if the chunk at this position is not loaded.
+    pub fn at(level: &Level, pos: &BlockPos, difficulty: Difficulty, time: &LevelTime) -> Self {
+        let inhabited_time = level.chunks.get_chunk_at(pos.x, pos.z)
+            .map_or(0, |chunk| chunk.get_inhabited_time());
+        Self::new(difficulty, time, inhabited_time)
+    }
+
+    #[inline]
+    pub fn get_difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    /// Return the effective difficulty, between 0 and 6.75.
+    #[inline]
+    pub fn get_effective(&self) -> f32 {
+        self.effective
+    }
+
+    #[inline]
+    pub fn is_harder_than(&self, value: f32) -> bool {
+        self.effective > value
+    }
+
+    /// Return the clamped regional difficulty, between 0 and 1, it's 0 below an effective
+    /// difficulty of 2 and 1 above 4. This is used for example to choose mobs equipment.
+    pub fn get_clamped(&self) -> f32 {
+        if self.effective < 2.0 {
+            0.0
+        } else if self.effective > 4.0 {
+            1.0
+        } else {
+            (self.effective - 2.0) / 2.0
+        }
+    }
+
+}
+
+
+fn calc_effective_difficulty(difficulty: Difficulty, day_time: i64, inhabited_time: u64, moon_brightness: f32) -> f32 {
+
+    if difficulty == Difficulty::Peaceful {
+        return 0.0;
+    }
+
+    let level_factor = ((day_time as f32 - 72000.0) / 1440000.0).clamp(0.0, 1.0) * 0.25;
+
+    let mut chunk_factor = (inhabited_time as f32 / 3600000.0).clamp(0.0, 1.0);
+    chunk_factor *= if difficulty == Difficulty::Hard { 1.0 } else { 0.75 };
+    chunk_factor += (moon_brightness * 0.25).clamp(0.0, level_factor);
+
+    if difficulty == Difficulty::Easy {
+        chunk_factor *= 0.5;
+    }
+
+    difficulty.get_id() as f32 * (0.75 + level_factor + chunk_factor)
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn regional_difficulty() {
+
+        let new_level = LevelTime::new();
+        assert_eq!(RegionalDifficulty::new(Difficulty::Peaceful, &new_level, 0).get_effective(), 0.0);
+        assert_eq!(RegionalDifficulty::new(Difficulty::Easy, &new_level, 0).get_effective(), 0.75);
+        assert_eq!(RegionalDifficulty::new(Difficulty::Normal, &new_level, 0).get_effective(), 1.5);
+        assert_eq!(RegionalDifficulty::new(Difficulty::Hard, &new_level, 0).get_clamped(), 0.125);
+
+        // Maximum difficulty with a full moon after 64 days and 50 hours in the chunk.
+        let old_level = LevelTime::new().with_day_time(1536000);
+        let hard = RegionalDifficulty::new(Difficulty::Hard, &old_level, 3600000);
+        assert_eq!(hard.get_effective(), 6.75);
+        assert_eq!(hard.get_clamped(), 1.0);
+        let normal = RegionalDifficulty::new(Difficulty::Normal, &old_level, 3600000);
+        assert_eq!(normal.get_effective(), 4.0);
+
+    }
+
+}
@@ -0,0 +1,46 @@
+//! Vanilla dimension types of the overworld, the nether and the end.
+
+use mc_core::world::dimension::DimensionType;
+use mc_core::world::chunk::ChunkHeight;
+
+
+/// The vanilla overworld dimension type, from Y 0 to 255.
+pub fn overworld() -> DimensionType {
+    DimensionType::new("minecraft:overworld", ChunkHeight::new(0, 15))
+}
+
+/// The vanilla nether dimension type, with a ceiling and a fixed night time.
+pub fn the_nether() -> DimensionType {
+    DimensionType {
+        name: "minecraft:the_nether".to_string(),
+        logical_height: 128,
+        ambient_light: 0.1,
+        has_skylight: false,
+        has_ceiling: true,
+        coordinate_scale: 8.0,
+        effects: "minecraft:the_nether".to_string(),
+        infiniburn: "minecraft:infiniburn_nether".to_string(),
+        fixed_time: Some(18000),
+        natural: false,
+        ultrawarm: true,
+        piglin_safe: true,
+        bed_works: false,
+        respawn_anchor_works: true,
+        has_raids: false,
+        ..overworld()
+    }
+}
+
+/// The vanilla end dimension type, without skylight and with a fixed time.
+pub fn the_end() -> DimensionType {
+    DimensionType {
+        name: "minecraft:the_end".to_string(),
+        has_skylight: false,
+        effects: "minecraft:the_end".to_string(),
+        infiniburn: "minecraft:infiniburn_end".to_string(),
+        fixed_time: Some(6000),
+        natural: false,
+        bed_works: false,
+        ..overworld()
+    }
+}
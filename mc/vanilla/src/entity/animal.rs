@@ -11,6 +11,14 @@ pub struct PigEntity {
     saddle: bool
 }
 
+impl PigEntity {
+
+    pub fn has_saddle(&self) -> bool {
+        self.saddle
+    }
+
+}
+
 entity_component!(PigEntity: PigEntityCodec);
 
 pub struct PigEntityCodec;
@@ -39,6 +47,18 @@ pub struct SheepEntity {
     sheared: bool
 }
 
+impl SheepEntity {
+
+    pub fn get_color(&self) -> DyeColor {
+        self.color
+    }
+
+    pub fn is_sheared(&self) -> bool {
+        self.sheared
+    }
+
+}
+
 entity_component!(SheepEntity: SheepEntityCodec);
 
 pub struct SheepEntityCodec;
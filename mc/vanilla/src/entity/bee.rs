@@ -25,6 +25,35 @@ pub struct BeeEntity {
     hive_pos: BlockPos
 }
 
+impl BeeEntity {
+
+    pub fn has_nectar(&self) -> bool {
+        self.has_nectar
+    }
+
+    pub fn set_has_nectar(&mut self, has_nectar: bool) {
+        self.has_nectar = has_nectar;
+    }
+
+    pub fn get_hive_pos(&self) -> &BlockPos {
+        &self.hive_pos
+    }
+
+    pub fn set_hive_pos(&mut self, hive_pos: BlockPos) {
+        self.hive_pos = hive_pos;
+    }
+
+    /// Return true if the bee can enter a hive, false while its cooldown is running.
+    pub fn can_enter_hive(&self) -> bool {
+        self.enter_hive_cooldown <= 0
+    }
+
+    pub fn set_enter_hive_cooldown(&mut self, ticks: i32) {
+        self.enter_hive_cooldown = ticks;
+    }
+
+}
+
 entity_component!(BeeEntity: BeeEntityCodec);
 
 pub struct BeeEntityCodec;
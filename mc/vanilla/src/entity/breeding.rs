@@ -0,0 +1,405 @@
+//! Breeding of animals with a `BreedableEntity` component. Adult animals fed with one of their
+//! foods fall in love, two animals of the same type in love near each other breed and spawn
+//! a baby, and both parents must then wait for a cooldown before breeding again. Feeding a
+//! baby makes it grow faster.
+//!
+//! Animals don't move toward their partner because there is no pathfinding yet, partners
+//! breed in place once they stayed close enough for `BREED_DELAY` ticks. Partners are looked
+//! up through the entity lists of chunks.
+
+use std::collections::{HashMap, HashSet};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use mc_core::world::level::{Level, BaseEntity};
+use mc_core::entity::EntityType;
+use mc_core::pos::EntityPos;
+use mc_core::item::Item;
+use mc_core::rand::JavaRandom;
+use mc_core::util::OpaquePtr;
+use hecs::Entity;
+use uuid::Uuid;
+
+use mc_runtime::world::World;
+
+use super::*;
+
+
+/// Number of ticks an animal stays in love after being fed.
+pub const IN_LOVE_TICKS: u32 = 600;
+/// Number of ticks before parents can breed again.
+pub const BREED_COOLDOWN: u32 = 6000;
+/// Number of ticks for a baby to grow up.
+pub const BABY_GROW_TICKS: u32 = 24000;
+/// Maximum distance between two partners.
+pub const PARTNER_DISTANCE: f64 = 8.0;
+/// Number of ticks partners must stay close to each other before breeding.
+pub const BREED_DELAY: u32 = 60;
+
+
+/// World component required by `system_breeding`, with the foods of each breedable entity
+/// type. Foods are given by item names, because vanilla items are not defined yet.
+pub struct Breeding {
+    foods: HashMap<OpaquePtr<EntityType>, &'static [&'static str]>,
+    rand: JavaRandom
+}
+
+impl Breeding {
+
+    pub fn new() -> Self {
+        Self {
+            foods: HashMap::new(),
+            rand: JavaRandom::new_seeded()
+        }
+    }
+
+    pub fn with_seed(mut self, seed: i64) -> Self {
+        self.rand = JavaRandom::new(seed);
+        self
+    }
+
+    /// Register the names of the items an entity type can be fed with, replacing any
+    /// previous foods.
+    pub fn with_entity_type(mut self, entity_type: &'static EntityType, foods: &'static [&'static str]) -> Self {
+        self.register(entity_type, foods);
+        self
+    }
+
+    pub fn register(&mut self, entity_type: &'static EntityType, foods: &'static [&'static str]) {
+        self.foods.insert(OpaquePtr::new(entity_type), foods);
+    }
+
+    /// Return true if the given item is a food of the entity type.
+    pub fn is_food(&self, entity_type: &'static EntityType, item: &'static Item) -> bool {
+        self.foods.get(&OpaquePtr::new(entity_type))
+            .is_some_and(|foods| foods.contains(&item.get_name()))
+    }
+
+}
+
+impl Default for Breeding {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// Event pushed by `system_breeding` when two animals have bred, vanilla drops experience
+/// orbs at the parent for this event.
+pub struct AnimalBreedEvent {
+    pub level: Rc<RefCell<Level>>,
+    pub parent: Entity,
+    pub partner: Entity,
+    pub baby: Entity,
+    /// The UUID of the player who fed one of the parents.
+    pub cause: Option<Uuid>,
+    /// The amount of experience to drop, between 1 and 7.
+    pub experience: u16
+}
+
+
+/// Event pushed by `system_breeding` when a baby has grown up.
+pub struct AnimalGrowUpEvent {
+    pub level: Rc<RefCell<Level>>,
+    pub entity: Entity
+}
+
+
+/// Feed an animal with the given item, adults without breeding cooldown fall in love and
+/// babies grow faster. The cause is the UUID of the feeding player. Return true if the item
+/// has been eaten and must be consumed.
+pub fn feed_animal(breeding: &Breeding, level: &mut Level, entity: Entity, item: &'static Item, cause: Option<Uuid>) -> bool {
+
+    let entity_type = match level.entities.ecs.get::<BaseEntity>(entity) {
+        Ok(base) => base.entity_type,
+        Err(_) => return false
+    };
+
+    if !breeding.is_food(entity_type, item) {
+        return false;
+    }
+
+    let mut breedable = match level.entities.ecs.get_mut::<BreedableEntity>(entity) {
+        Ok(breedable) => breedable,
+        Err(_) => return false
+    };
+
+    match *breedable.get_age() {
+        Age::Adult { breed_cooldown: 0 } if !breedable.is_in_love() => {
+            breedable.set_in_love(IN_LOVE_TICKS, cause);
+            true
+        }
+        Age::Baby { ticks_remaining, breed_cooldown_once_adult } => {
+            // Like vanilla, 10% of the remaining time is skipped, rounded down to seconds.
+            let skipped = (ticks_remaining as f32 / 20.0 * 0.1) as u32 * 20;
+            breedable.set_age(Age::Baby {
+                ticks_remaining: ticks_remaining.saturating_sub(skipped),
+                breed_cooldown_once_adult
+            });
+            true
+        }
+        _ => false
+    }
+
+}
+
+
+/// Find the nearest animal of the given type in love, other than the given entity.
+fn find_partner(level: &Level, entity: Entity, entity_type: &'static EntityType, pos: &EntityPos) -> Option<Entity> {
+
+    let ecs = &level.entities.ecs;
+    let min_cx = (pos.x - PARTNER_DISTANCE).floor() as i32 >> 4;
+    let max_cx = (pos.x + PARTNER_DISTANCE).floor() as i32 >> 4;
+    let min_cz = (pos.z - PARTNER_DISTANCE).floor() as i32 >> 4;
+    let max_cz = (pos.z + PARTNER_DISTANCE).floor() as i32 >> 4;
+
+    let mut partner = None;
+    let mut partner_distance = PARTNER_DISTANCE * PARTNER_DISTANCE;
+
+    for cx in min_cx..=max_cx {
+        for cz in min_cz..=max_cz {
+
+            let chunk = match level.chunks.get_chunk(cx, cz) {
+                Some(chunk) => chunk,
+                None => continue
+            };
+
+            for other in chunk.iter_entities().filter(|&other| other != entity) {
+
+                let mut query = match ecs.query_one::<(&BaseEntity, &BreedableEntity)>(other) {
+                    Ok(query) => query,
+                    Err(_) => continue
+                };
+
+                if let Some((base, breedable)) = query.get() {
+                    if std::ptr::eq(base.entity_type, entity_type) && breedable.is_in_love() {
+                        let (dx, dy, dz) = (base.pos.x - pos.x, base.pos.y - pos.y, base.pos.z - pos.z);
+                        let distance = dx * dx + dy * dy + dz * dz;
+                        if distance <= partner_distance {
+                            partner = Some(other);
+                            partner_distance = distance;
+                        }
+                    }
+                }
+
+            }
+
+        }
+    }
+
+    partner
+
+}
+
+
+/// Call this function with a mutable reference to a World to register the `Breeding`
+/// component which is required to run the system `system_breeding`.
+pub fn register_breeding(world: &mut World, breeding: Breeding) {
+    world.insert_component(breeding);
+}
+
+/// A system that makes babies grow up, decrements the breeding cooldown and love of adults,
+/// and breeds animals in love staying close to a partner. An `AnimalBreedEvent` is pushed
+/// for each new baby and an `AnimalGrowUpEvent` for each baby that has grown up.
+pub fn system_breeding(world: &mut World) {
+
+    let mut breeding = match world.components.get_mut::<Breeding>() {
+        Ok(breeding) => breeding,
+        Err(_) => return
+    };
+
+    for level_rc in &world.levels {
+
+        let mut level = level_rc.borrow_mut();
+        let mut in_love = Vec::new();
+
+        for (entity, (base, breedable)) in level.entities.ecs.query_mut::<(&BaseEntity, &mut BreedableEntity)>() {
+
+            let mut age = *breedable.get_age();
+            age.tick();
+
+            if age.should_grow() {
+                if let Age::Baby { breed_cooldown_once_adult, .. } = age {
+                    age = Age::Adult { breed_cooldown: breed_cooldown_once_adult.unwrap_or(0) };
+                    world.event_tracker.push_event(AnimalGrowUpEvent {
+                        level: Rc::clone(level_rc),
+                        entity
+                    });
+                }
+            }
+
+            breedable.set_age(age);
+
+            // Like vanilla, only adults without cooldown can stay in love.
+            if age.can_breed() && breedable.is_in_love() {
+                let cause = breedable.get_love_cause().copied();
+                breedable.set_in_love(breedable.get_love_ticks() - 1, cause);
+            } else if breedable.is_in_love() {
+                breedable.set_in_love(0, None);
+            }
+
+            if breedable.is_in_love() {
+                in_love.push((entity, base.entity_type, base.pos.clone()));
+            } else {
+                breedable.set_breed_time(0);
+            }
+
+        }
+
+        let mut bred = HashSet::new();
+
+        for (entity, entity_type, pos) in in_love {
+
+            if bred.contains(&entity) {
+                continue;
+            }
+
+            let partner = match find_partner(&level, entity, entity_type, &pos) {
+                Some(partner) if !bred.contains(&partner) => partner,
+                _ => {
+                    level.entities.ecs.get_mut::<BreedableEntity>(entity).unwrap().set_breed_time(0);
+                    continue;
+                }
+            };
+
+            let cause = {
+                let mut breedable = level.entities.ecs.get_mut::<BreedableEntity>(entity).unwrap();
+                let breed_time = breedable.get_breed_time() + 1;
+                breedable.set_breed_time(breed_time);
+                if breed_time < BREED_DELAY {
+                    continue;
+                }
+                breedable.get_love_cause().copied()
+            };
+
+            let cause = cause.or_else(|| {
+                level.entities.ecs.get::<BreedableEntity>(partner).unwrap().get_love_cause().copied()
+            });
+
+            for parent in [entity, partner] {
+                let mut breedable = level.entities.ecs.get_mut::<BreedableEntity>(parent).unwrap();
+                breedable.set_age(Age::Adult { breed_cooldown: BREED_COOLDOWN });
+                breedable.set_in_love(0, None);
+                breedable.set_breed_time(0);
+            }
+
+            bred.insert(entity);
+            bred.insert(partner);
+
+            let baby = match level.spawn_entity(entity_type, pos) {
+                Some(baby) => baby,
+                None => continue
+            };
+
+            if let Ok(mut breedable) = level.entities.ecs.get_mut::<BreedableEntity>(baby) {
+                breedable.set_age(Age::Baby { ticks_remaining: BABY_GROW_TICKS, breed_cooldown_once_adult: None });
+            }
+
+            world.event_tracker.push_event(AnimalBreedEvent {
+                level: Rc::clone(level_rc),
+                parent: entity,
+                partner,
+                baby,
+                cause,
+                experience: breeding.rand.next_int_bounded(7) as u16 + 1
+            });
+
+        }
+
+    }
+
+}
+
+
+pub(crate) fn register_vanilla_foods(breeding: &mut Breeding) {
+    breeding.register(&COW, &["minecraft:wheat"]);
+    breeding.register(&SHEEP, &["minecraft:wheat"]);
+    breeding.register(&PIG, &["minecraft:carrot", "minecraft:potato", "minecraft:beetroot"]);
+    breeding.register(&CHICKEN, &["minecraft:wheat_seeds", "minecraft:melon_seeds", "minecraft:pumpkin_seeds", "minecraft:beetroot_seeds"]);
+    breeding.register(&RABBIT, &["minecraft:dandelion", "minecraft:carrot", "minecraft:golden_carrot"]);
+    breeding.register(&TURTLE, &["minecraft:seagrass"]);
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_core::world::level::LevelEnv;
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::source::{ChunkLoadRequest, NullLevelSource};
+
+    use crate::ext::{VanillaLevelEnv, WithVanilla};
+
+    use super::*;
+
+    static WHEAT: Item = Item::new("minecraft:wheat", 64);
+    static CARROT: Item = Item::new("minecraft:carrot", 64);
+
+    #[test]
+    fn breeding() {
+
+        let env = LevelEnv::vanilla();
+        let height = ChunkHeight::new(0, 0);
+        let mut level = Level::new("overworld".to_string(), env.clone(), height, NullLevelSource);
+        level.chunks.insert_chunk(ChunkLoadRequest { env: env.clone(), height, cx: 0, cz: 0 }.build_chunk());
+        level.chunks.insert_chunk(ChunkLoadRequest { env, height, cx: 1, cz: 0 }.build_chunk());
+
+        let breeding = Breeding::with_vanilla().with_seed(0);
+        let player = Uuid::from_u128(1);
+
+        // The partner is in the neighbour chunk.
+        let cow = level.spawn_entity(&COW, EntityPos::new(14.5, 1.0, 8.5)).unwrap();
+        let partner = level.spawn_entity(&COW, EntityPos::new(17.5, 1.0, 8.5)).unwrap();
+        let pig = level.spawn_entity(&PIG, EntityPos::new(15.5, 1.0, 8.5)).unwrap();
+
+        assert!(!feed_animal(&breeding, &mut level, cow, &CARROT, Some(player)));
+        assert!(feed_animal(&breeding, &mut level, cow, &WHEAT, Some(player)));
+        assert!(!feed_animal(&breeding, &mut level, cow, &WHEAT, Some(player)));
+        assert!(feed_animal(&breeding, &mut level, partner, &WHEAT, None));
+        assert!(feed_animal(&breeding, &mut level, pig, &CARROT, None));
+
+        let mut world = World::new();
+        world.add_level(level);
+        register_breeding(&mut world, breeding);
+
+        for _ in 0..BREED_DELAY {
+            system_breeding(&mut world);
+        }
+
+        let events: Vec<_> = world.event_tracker.poll_events::<AnimalBreedEvent>()
+            .map(|event| (event.parent, event.partner, event.baby, event.cause, event.experience))
+            .collect();
+        assert_eq!(events.len(), 1);
+        let (parent, event_partner, baby, cause, experience) = events[0];
+        assert_eq!((parent, event_partner, cause), (cow, partner, Some(player)));
+        assert!((1..=7).contains(&experience));
+
+        {
+            let level = world.levels[0].borrow();
+            let ecs = &level.entities.ecs;
+            assert!(ecs.get::<BreedableEntity>(baby).unwrap().is_baby());
+            assert!(!ecs.get::<BreedableEntity>(cow).unwrap().get_age().can_breed());
+            assert!(ecs.get::<BreedableEntity>(pig).unwrap().is_in_love());
+        }
+
+        // Feeding the baby skips 10% of its growth.
+        {
+            let breeding = world.components.get::<Breeding>().unwrap();
+            let mut level = world.levels[0].borrow_mut();
+            assert!(feed_animal(&breeding, &mut level, baby, &WHEAT, None));
+            let age = *level.entities.ecs.get::<BreedableEntity>(baby).unwrap().get_age();
+            match age {
+                Age::Baby { ticks_remaining, .. } => assert_eq!(ticks_remaining, BABY_GROW_TICKS - 2400),
+                _ => panic!("baby has grown up")
+            }
+        }
+
+        for _ in 0..BABY_GROW_TICKS {
+            system_breeding(&mut world);
+        }
+
+        assert!(!world.levels[0].borrow().entities.ecs.get::<BreedableEntity>(baby).unwrap().is_baby());
+
+    }
+
+}
@@ -3,6 +3,7 @@ use std::num::NonZeroU32;
 
 use mc_core::entity::SingleEntityCodec;
 use mc_core::pos::{EntityPos, BlockPos};
+use mc_core::item::ItemStack;
 use mc_core::util::NbtExt;
 use mc_core::entity_component;
 
@@ -52,10 +53,55 @@ pub struct VanillaEntity {
 
 impl VanillaEntity {
 
+    /// Get the velocity of the entity in blocks per tick.
+    pub fn get_motion(&self) -> &EntityPos {
+        &self.motion
+    }
+
+    pub fn get_rotation_yaw(&self) -> f32 {
+        self.rotation_yaw
+    }
+
+    pub fn get_rotation_pitch(&self) -> f32 {
+        self.rotation_pitch
+    }
+
     pub fn is_on_fire(&self) -> bool {
         self.remaining_fire_ticks > 0
     }
 
+    pub fn get_remaining_fire_ticks(&self) -> i16 {
+        self.remaining_fire_ticks
+    }
+
+    pub fn set_remaining_fire_ticks(&mut self, remaining_fire_ticks: i16) {
+        self.remaining_fire_ticks = remaining_fire_ticks;
+    }
+
+    pub fn has_visual_fire(&self) -> bool {
+        self.has_visual_fire
+    }
+
+    pub fn get_air(&self) -> i16 {
+        self.air
+    }
+
+    pub fn is_glowing(&self) -> bool {
+        self.glowing
+    }
+
+    pub fn is_silent(&self) -> bool {
+        self.silent
+    }
+
+    pub fn has_no_gravity(&self) -> bool {
+        self.no_gravity
+    }
+
+    pub fn get_ticks_frozen(&self) -> u32 {
+        self.ticks_frozen
+    }
+
 }
 
 entity_component!(VanillaEntity: VanillaEntityCodec);
@@ -135,6 +181,18 @@ pub struct NamedEntity {
     custom_name: Option<(String, bool)>, // TODO: Replace with a struct like "TextComponent".
 }
 
+impl NamedEntity {
+
+    pub fn get_custom_name(&self) -> Option<&str> {
+        self.custom_name.as_ref().map(|(custom_name, _)| custom_name.as_str())
+    }
+
+    pub fn is_custom_name_visible(&self) -> bool {
+        matches!(self.custom_name, Some((_, true)))
+    }
+
+}
+
 entity_component!(NamedEntity: NamedEntityCodec);
 
 pub struct NamedEntityCodec;
@@ -187,6 +245,30 @@ pub struct LivingEntity {
     sleeping_pos: Option<BlockPos>,
 }
 
+impl LivingEntity {
+
+    pub fn get_health(&self) -> f32 {
+        self.health
+    }
+
+    pub fn set_health(&mut self, health: f32) {
+        self.health = health;
+    }
+
+    pub fn get_absorption_amount(&self) -> f32 {
+        self.absorption_amount
+    }
+
+    pub fn is_fall_flying(&self) -> bool {
+        self.fall_flying
+    }
+
+    pub fn get_sleeping_pos(&self) -> Option<&BlockPos> {
+        self.sleeping_pos.as_ref()
+    }
+
+}
+
 entity_component!(LivingEntity: LivingEntityCodec);
 
 pub struct LivingEntityCodec;
@@ -240,6 +322,62 @@ pub struct MobEntity {
     persistent: bool,
     /// Optional leash configuration for this entity.
     leash: Option<LeashConfig>,
+    /// The item worn on the head, not saved because stacks can't be decoded without an
+    /// item registry.
+    head_item: Option<ItemStack>,
+    /// Number of ticks since a player was last near the mob, not saved like vanilla.
+    no_action_time: u32
+}
+
+impl MobEntity {
+
+    pub fn is_left_handed(&self) -> bool {
+        self.left_handed
+    }
+
+    pub fn has_no_ai(&self) -> bool {
+        self.no_ai
+    }
+
+    /// Return true if the mob must not despawn naturally.
+    pub fn is_persistent(&self) -> bool {
+        self.persistent
+    }
+
+    pub fn set_persistent(&mut self, persistent: bool) {
+        self.persistent = persistent;
+    }
+
+    pub fn get_head_item(&self) -> Option<&ItemStack> {
+        self.head_item.as_ref()
+    }
+
+    pub fn get_head_item_mut(&mut self) -> Option<&mut ItemStack> {
+        self.head_item.as_mut()
+    }
+
+    pub fn set_head_item(&mut self, head_item: Option<ItemStack>) -> Option<ItemStack> {
+        std::mem::replace(&mut self.head_item, head_item)
+    }
+
+    /// Return the leash of the mob, the entity holding it is given by the `Leashed`
+    /// component once resolved.
+    pub fn get_leash(&self) -> Option<&LeashConfig> {
+        self.leash.as_ref()
+    }
+
+    pub fn set_leash(&mut self, leash: Option<LeashConfig>) -> Option<LeashConfig> {
+        std::mem::replace(&mut self.leash, leash)
+    }
+
+    pub fn get_no_action_time(&self) -> u32 {
+        self.no_action_time
+    }
+
+    pub fn set_no_action_time(&mut self, no_action_time: u32) {
+        self.no_action_time = no_action_time;
+    }
+
 }
 
 entity_component!(MobEntity: MobEntityCodec);
@@ -264,6 +402,7 @@ impl SingleEntityCodec for MobEntityCodec {
                     tag_leash.insert_split_block_pos("X", "Y", "Z", pos);
                 }
             }
+            dst.insert_compound_tag("Leash", tag_leash);
         }
     }
 
@@ -285,7 +424,8 @@ impl SingleEntityCodec for MobEntityCodec {
                 } else {
                     None
                 }
-            }
+            },
+            ..MobEntity::default()
         }
     }
 
@@ -296,7 +436,56 @@ impl SingleEntityCodec for MobEntityCodec {
 pub struct BreedableEntity {
     age: Age,
     love: Love,
-    love_cause: Option<Uuid>
+    love_cause: Option<Uuid>,
+    /// Number of ticks spent next to a partner in love, not saved like vanilla.
+    breed_time: u32
+}
+
+impl BreedableEntity {
+
+    pub fn is_baby(&self) -> bool {
+        matches!(self.age, Age::Baby { .. })
+    }
+
+    pub fn get_age(&self) -> &Age {
+        &self.age
+    }
+
+    pub fn set_age(&mut self, age: Age) {
+        self.age = age;
+    }
+
+    pub fn is_in_love(&self) -> bool {
+        matches!(self.love, Love::InLove(_))
+    }
+
+    /// Return the number of ticks remaining in love, 0 if not in love.
+    pub fn get_love_ticks(&self) -> u32 {
+        match self.love {
+            Love::NotInLove => 0,
+            Love::InLove(ticks) => ticks.get()
+        }
+    }
+
+    /// Set the number of ticks remaining in love and the UUID of the player who caused it,
+    /// the entity is no longer in love if the ticks are 0.
+    pub fn set_in_love(&mut self, ticks: u32, cause: Option<Uuid>) {
+        self.love = NonZeroU32::new(ticks).map_or(Love::NotInLove, Love::InLove);
+        self.love_cause = cause;
+    }
+
+    pub fn get_love_cause(&self) -> Option<&Uuid> {
+        self.love_cause.as_ref()
+    }
+
+    pub fn get_breed_time(&self) -> u32 {
+        self.breed_time
+    }
+
+    pub fn set_breed_time(&mut self, breed_time: u32) {
+        self.breed_time = breed_time;
+    }
+
 }
 
 entity_component!(BreedableEntity: BreedableEntityCodec);
@@ -362,7 +551,8 @@ impl SingleEntityCodec for BreedableEntityCodec {
                     Love::NotInLove
                 }
             },
-            love_cause: src.get_uuid("LoveCause").ok()
+            love_cause: src.get_uuid("LoveCause").ok(),
+            breed_time: 0
         }
     }
     
@@ -437,6 +627,14 @@ pub struct FromBucketEntity {
     from_bucket: bool
 }
 
+impl FromBucketEntity {
+
+    pub fn is_from_bucket(&self) -> bool {
+        self.from_bucket
+    }
+
+}
+
 entity_component!(FromBucketEntity: FromBucketEntityCodec);
 
 pub struct FromBucketEntityCodec;
@@ -459,7 +657,7 @@ impl SingleEntityCodec for FromBucketEntityCodec {
 
 // Utilities for common entities //
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum LeashConfig {
     Entity(Uuid),
     Fence(BlockPos)
@@ -17,6 +17,26 @@ pub struct CreeperEntity {
     powered: bool
 }
 
+impl CreeperEntity {
+
+    pub fn get_explosion_radius(&self) -> u8 {
+        self.explosion_radius
+    }
+
+    pub fn get_fuse(&self) -> u16 {
+        self.fuse
+    }
+
+    pub fn is_ignited(&self) -> bool {
+        self.ignited
+    }
+
+    pub fn is_powered(&self) -> bool {
+        self.powered
+    }
+
+}
+
 entity_component!(CreeperEntity: CreeperEntityCodec);
 
 pub struct CreeperEntityCodec;
@@ -0,0 +1,1057 @@
+//! Decoration entities, item frames, paintings and leash knots hang on a block while armor
+//! stands display a pose. Hanging entities are dropped by `system_decorations` when their
+//! support is no longer valid, the same system pulls leashed mobs toward the entity holding
+//! their leash and breaks leashes that get too long.
+
+use std::rc::Rc;
+
+use mc_core::world::level::{Level, BaseEntity};
+use mc_core::entity::{SingleEntityCodec, EntityType};
+use mc_core::block::{Block, BlockState};
+use mc_core::pos::{EntityPos, BlockPos, Direction};
+use mc_core::item::ItemStack;
+use mc_core::util::NbtExt;
+use mc_core::rand::JavaRandom;
+use mc_core::entity_component;
+use hecs::Entity;
+use nbt::CompoundTag;
+
+use mc_runtime::event::EntityMovedEvent;
+use mc_runtime::world::World;
+
+use crate::block::*;
+use crate::item::{LEAD, PAINTING as PAINTING_ITEM, ITEM_FRAME as ITEM_FRAME_ITEM, GLOW_ITEM_FRAME as GLOW_ITEM_FRAME_ITEM};
+use crate::death::Dead;
+
+use super::{MobEntity, LeashConfig, BreedableEntity, TamableEntity};
+use super::{ITEM, ITEM_FRAME, GLOW_ITEM_FRAME, PAINTING, LEASH_KNOT, IRON_GOLEM, SNOW_GOLEM};
+use super::item::ItemEntity;
+use super::physics::{get_block, is_colliding, move_with_collisions};
+
+
+/// Distance from which leashed mobs are pulled toward the holder of their leash.
+pub const LEASH_PULL_DISTANCE: f64 = 6.0;
+/// Distance from which leashes break, the lead is dropped.
+pub const LEASH_BREAK_DISTANCE: f64 = 10.0;
+/// Distance under which the mobs held by an entity are tied to a fence.
+pub const LEASH_TIE_DISTANCE: f64 = 7.0;
+/// Maximum distance moved by a pulled mob in a tick.
+const LEASH_PULL_SPEED: f64 = 0.4;
+/// Distance between the wall and the position of item frames and paintings.
+const HANGING_WALL_OFFSET: f64 = 0.46875;
+
+/// Fences holding leash knots.
+pub static LEASH_FENCES: [&Block; 9] = [
+    &OAK_FENCE, &SPRUCE_FENCE, &BIRCH_FENCE, &JUNGLE_FENCE, &ACACIA_FENCE, &DARK_OAK_FENCE,
+    &CRIMSON_FENCE, &WARPED_FENCE, &NETHER_BRICK_FENCE
+];
+
+
+/// Return true if the block state is a fence that can hold leash knots.
+pub fn is_leash_fence(state: &'static BlockState) -> bool {
+    LEASH_FENCES.iter().any(|&fence| state.is_block(fence))
+}
+
+
+/// An entity component for entities hanging on a block. Item frames and paintings hang in
+/// front of the wall behind their tile, leash knots are tied to the fence of their tile.
+#[derive(Debug, Clone)]
+pub struct HangingEntity {
+    tile: BlockPos
+}
+
+impl HangingEntity {
+
+    pub fn new(tile: BlockPos) -> Self {
+        Self { tile }
+    }
+
+    pub fn get_tile(&self) -> &BlockPos {
+        &self.tile
+    }
+
+}
+
+impl Default for HangingEntity {
+    fn default() -> Self {
+        Self::new(BlockPos::new(0, 0, 0))
+    }
+}
+
+entity_component!(HangingEntity: HangingEntityCodec);
+
+pub struct HangingEntityCodec;
+impl SingleEntityCodec for HangingEntityCodec {
+
+    type Comp = HangingEntity;
+
+    fn encode(&self, src: &Self::Comp, dst: &mut CompoundTag) {
+        dst.insert_split_block_pos("TileX", "TileY", "TileZ", &src.tile);
+    }
+
+    fn decode(&self, src: &CompoundTag) -> Self::Comp {
+        HangingEntity {
+            tile: src.get_split_block_pos("TileX", "TileY", "TileZ").unwrap_or_else(|_| BlockPos::new(0, 0, 0))
+        }
+    }
+
+}
+
+
+/// An entity component for item frames and glow item frames.
+#[derive(Debug)]
+pub struct ItemFrameEntity {
+    facing: Direction,
+    /// The displayed item, not saved because stacks can't be decoded without an item
+    /// registry.
+    item: Option<ItemStack>,
+    /// Rotation of the item, in eighth of a turn.
+    rotation: u8,
+    /// Chance for the item to drop when the frame breaks.
+    drop_chance: f32,
+    /// True if the frame can't be broken and its item can't be changed.
+    fixed: bool,
+    invisible: bool
+}
+
+impl ItemFrameEntity {
+
+    pub fn new(facing: Direction) -> Self {
+        Self {
+            facing,
+            item: None,
+            rotation: 0,
+            drop_chance: 1.0,
+            fixed: false,
+            invisible: false
+        }
+    }
+
+    pub fn get_facing(&self) -> Direction {
+        self.facing
+    }
+
+    pub fn get_item(&self) -> Option<&ItemStack> {
+        self.item.as_ref()
+    }
+
+    /// Replace the displayed item and reset its rotation, the previous item is returned.
+    pub fn set_item(&mut self, item: Option<ItemStack>) -> Option<ItemStack> {
+        self.rotation = 0;
+        std::mem::replace(&mut self.item, item)
+    }
+
+    pub fn get_rotation(&self) -> u8 {
+        self.rotation
+    }
+
+    /// Rotate the displayed item by an eighth of a turn, return false if the frame is empty.
+    pub fn rotate(&mut self) -> bool {
+        if self.item.is_some() {
+            self.rotation = (self.rotation + 1) % 8;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn get_drop_chance(&self) -> f32 {
+        self.drop_chance
+    }
+
+    pub fn is_fixed(&self) -> bool {
+        self.fixed
+    }
+
+    pub fn is_invisible(&self) -> bool {
+        self.invisible
+    }
+
+    /// Return the signal of a comparator reading this frame, the rotation of the item plus
+    /// one, or zero if the frame is empty.
+    pub fn get_comparator_output(&self) -> u8 {
+        if self.item.is_some() { self.rotation + 1 } else { 0 }
+    }
+
+}
+
+impl Default for ItemFrameEntity {
+    fn default() -> Self {
+        Self::new(Direction::South)
+    }
+}
+
+entity_component!(ItemFrameEntity: ItemFrameEntityCodec);
+
+pub struct ItemFrameEntityCodec;
+impl SingleEntityCodec for ItemFrameEntityCodec {
+
+    type Comp = ItemFrameEntity;
+
+    fn encode(&self, src: &Self::Comp, dst: &mut CompoundTag) {
+        dst.insert_i8("Facing", get_direction_id(src.facing) as i8);
+        dst.insert_i8("ItemRotation", src.rotation as i8);
+        dst.insert_f32("ItemDropChance", src.drop_chance);
+        dst.insert_bool("Fixed", src.fixed);
+        dst.insert_bool("Invisible", src.invisible);
+    }
+
+    fn decode(&self, src: &CompoundTag) -> Self::Comp {
+        ItemFrameEntity {
+            facing: src.get_i8("Facing").ok()
+                .and_then(|id| get_direction_from_id(id as u8))
+                .unwrap_or(Direction::South),
+            item: None,
+            rotation: src.get_i8("ItemRotation").unwrap_or_default().rem_euclid(8) as u8,
+            drop_chance: src.get_f32("ItemDropChance").unwrap_or(1.0),
+            fixed: src.get_bool_or("Fixed", false),
+            invisible: src.get_bool_or("Invisible", false)
+        }
+    }
+
+}
+
+
+/// A painting motive, its size is in blocks.
+#[derive(Debug)]
+pub struct PaintingMotive {
+    name: &'static str,
+    width: u8,
+    height: u8
+}
+
+impl PaintingMotive {
+
+    const fn new(name: &'static str, width: u8, height: u8) -> Self {
+        Self { name, width, height }
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn get_width(&self) -> u8 {
+        self.width
+    }
+
+    pub fn get_height(&self) -> u8 {
+        self.height
+    }
+
+    /// Return the network ID of the motive, its index in `PAINTING_MOTIVES`.
+    pub fn get_id(&'static self) -> i32 {
+        PAINTING_MOTIVES.iter().position(|&motive| std::ptr::eq(motive, self)).unwrap() as i32
+    }
+
+    pub fn from_name(name: &str) -> Option<&'static Self> {
+        PAINTING_MOTIVES.iter().copied().find(|motive| motive.name == name)
+    }
+
+}
+
+macro_rules! painting_motives {
+    ($($id:ident $name:literal $width:literal $height:literal),* $(,)?) => {
+        $(pub static $id: PaintingMotive = PaintingMotive::new(concat!("minecraft:", $name), $width, $height);)*
+        /// All painting motives, in order of their network IDs.
+        pub static PAINTING_MOTIVES: [&PaintingMotive; mc_core::count!($($id)*)] = [$(&$id),*];
+    };
+}
+
+painting_motives! {
+    MOTIVE_KEBAB "kebab" 1 1,
+    MOTIVE_AZTEC "aztec" 1 1,
+    MOTIVE_ALBAN "alban" 1 1,
+    MOTIVE_AZTEC2 "aztec2" 1 1,
+    MOTIVE_BOMB "bomb" 1 1,
+    MOTIVE_PLANT "plant" 1 1,
+    MOTIVE_WASTELAND "wasteland" 1 1,
+    MOTIVE_POOL "pool" 2 1,
+    MOTIVE_COURBET "courbet" 2 1,
+    MOTIVE_SEA "sea" 2 1,
+    MOTIVE_SUNSET "sunset" 2 1,
+    MOTIVE_CREEBET "creebet" 2 1,
+    MOTIVE_WANDERER "wanderer" 1 2,
+    MOTIVE_GRAHAM "graham" 1 2,
+    MOTIVE_MATCH "match" 2 2,
+    MOTIVE_BUST "bust" 2 2,
+    MOTIVE_STAGE "stage" 2 2,
+    MOTIVE_VOID "void" 2 2,
+    MOTIVE_SKULL_AND_ROSES "skull_and_roses" 2 2,
+    MOTIVE_WITHER "wither" 2 2,
+    MOTIVE_FIGHTERS "fighters" 4 2,
+    MOTIVE_POINTER "pointer" 4 4,
+    MOTIVE_PIGSCENE "pigscene" 4 4,
+    MOTIVE_BURNING_SKULL "burning_skull" 4 4,
+    MOTIVE_SKELETON "skeleton" 4 3,
+    MOTIVE_DONKEY_KONG "donkey_kong" 4 3,
+}
+
+
+/// An entity component for paintings, they can only face horizontal directions.
+#[derive(Debug)]
+pub struct PaintingEntity {
+    facing: Direction,
+    motive: &'static PaintingMotive
+}
+
+impl PaintingEntity {
+
+    pub fn new(facing: Direction, motive: &'static PaintingMotive) -> Self {
+        Self { facing, motive }
+    }
+
+    pub fn get_facing(&self) -> Direction {
+        self.facing
+    }
+
+    pub fn get_motive(&self) -> &'static PaintingMotive {
+        self.motive
+    }
+
+}
+
+impl Default for PaintingEntity {
+    fn default() -> Self {
+        Self::new(Direction::South, &MOTIVE_KEBAB)
+    }
+}
+
+entity_component!(PaintingEntity: PaintingEntityCodec);
+
+pub struct PaintingEntityCodec;
+impl SingleEntityCodec for PaintingEntityCodec {
+
+    type Comp = PaintingEntity;
+
+    fn encode(&self, src: &Self::Comp, dst: &mut CompoundTag) {
+        dst.insert_str("Motive", src.motive.name);
+        dst.insert_i8("Facing", get_horizontal_direction_id(src.facing) as i8);
+    }
+
+    fn decode(&self, src: &CompoundTag) -> Self::Comp {
+        PaintingEntity {
+            facing: src.get_i8("Facing").ok()
+                .and_then(|id| get_horizontal_direction_from_id(id as u8))
+                .unwrap_or(Direction::South),
+            motive: src.get_str("Motive").ok()
+                .and_then(PaintingMotive::from_name)
+                .unwrap_or(&MOTIVE_KEBAB)
+        }
+    }
+
+}
+
+
+/// Rotations in degrees around the X, Y and Z axes of a part of an armor stand.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Rotations {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32
+}
+
+impl Rotations {
+
+    pub const fn new(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z }
+    }
+
+}
+
+/// The pose of an armor stand, the rotations of each of its parts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArmorStandPose {
+    pub head: Rotations,
+    pub body: Rotations,
+    pub left_arm: Rotations,
+    pub right_arm: Rotations,
+    pub left_leg: Rotations,
+    pub right_leg: Rotations
+}
+
+impl ArmorStandPose {
+
+    pub const DEFAULT_HEAD: Rotations = Rotations::new(0.0, 0.0, 0.0);
+    pub const DEFAULT_BODY: Rotations = Rotations::new(0.0, 0.0, 0.0);
+    pub const DEFAULT_LEFT_ARM: Rotations = Rotations::new(-10.0, 0.0, -10.0);
+    pub const DEFAULT_RIGHT_ARM: Rotations = Rotations::new(-15.0, 0.0, 10.0);
+    pub const DEFAULT_LEFT_LEG: Rotations = Rotations::new(-1.0, 0.0, -1.0);
+    pub const DEFAULT_RIGHT_LEG: Rotations = Rotations::new(1.0, 0.0, 1.0);
+
+    /// Internal function to iterate over the name, the part and the default rotations of
+    /// each part of the pose.
+    fn parts_mut(&mut self) -> [(&'static str, &mut Rotations, Rotations); 6] {
+        [
+            ("Head", &mut self.head, Self::DEFAULT_HEAD),
+            ("Body", &mut self.body, Self::DEFAULT_BODY),
+            ("LeftArm", &mut self.left_arm, Self::DEFAULT_LEFT_ARM),
+            ("RightArm", &mut self.right_arm, Self::DEFAULT_RIGHT_ARM),
+            ("LeftLeg", &mut self.left_leg, Self::DEFAULT_LEFT_LEG),
+            ("RightLeg", &mut self.right_leg, Self::DEFAULT_RIGHT_LEG)
+        ]
+    }
+
+    /// Encode the parts that are not in their default rotations.
+    fn encode(&self) -> CompoundTag {
+        let mut tag = CompoundTag::new();
+        for (name, part, default) in self.clone().parts_mut() {
+            if *part != default {
+                tag.insert_f32_vec(name, [part.x, part.y, part.z]);
+            }
+        }
+        tag
+    }
+
+    /// Decode a pose, missing or invalid parts are in their default rotations.
+    fn decode(tag: &CompoundTag) -> Self {
+        let mut pose = Self::default();
+        for (name, part, _) in pose.parts_mut() {
+            if let Ok(rotations) = tag.get_f32_vec(name) {
+                if let [x, y, z] = rotations[..] {
+                    *part = Rotations::new(x, y, z);
+                }
+            }
+        }
+        pose
+    }
+
+}
+
+impl Default for ArmorStandPose {
+    fn default() -> Self {
+        Self {
+            head: Self::DEFAULT_HEAD,
+            body: Self::DEFAULT_BODY,
+            left_arm: Self::DEFAULT_LEFT_ARM,
+            right_arm: Self::DEFAULT_RIGHT_ARM,
+            left_leg: Self::DEFAULT_LEFT_LEG,
+            right_leg: Self::DEFAULT_RIGHT_LEG
+        }
+    }
+}
+
+
+/// An entity component for armor stands.
+#[derive(Debug, Default)]
+pub struct ArmorStandEntity {
+    small: bool,
+    show_arms: bool,
+    no_base_plate: bool,
+    /// True if the armor stand has no hitbox.
+    marker: bool,
+    invisible: bool,
+    /// Bit field of the equipment slots that can't be changed by players.
+    disabled_slots: i32,
+    pose: ArmorStandPose
+}
+
+impl ArmorStandEntity {
+
+    pub fn is_small(&self) -> bool {
+        self.small
+    }
+
+    pub fn has_arms(&self) -> bool {
+        self.show_arms
+    }
+
+    pub fn set_show_arms(&mut self, show_arms: bool) {
+        self.show_arms = show_arms;
+    }
+
+    pub fn has_base_plate(&self) -> bool {
+        !self.no_base_plate
+    }
+
+    pub fn is_marker(&self) -> bool {
+        self.marker
+    }
+
+    pub fn is_invisible(&self) -> bool {
+        self.invisible
+    }
+
+    pub fn get_disabled_slots(&self) -> i32 {
+        self.disabled_slots
+    }
+
+    pub fn get_pose(&self) -> &ArmorStandPose {
+        &self.pose
+    }
+
+    pub fn get_pose_mut(&mut self) -> &mut ArmorStandPose {
+        &mut self.pose
+    }
+
+}
+
+entity_component!(ArmorStandEntity: ArmorStandEntityCodec);
+
+pub struct ArmorStandEntityCodec;
+impl SingleEntityCodec for ArmorStandEntityCodec {
+
+    type Comp = ArmorStandEntity;
+
+    fn encode(&self, src: &Self::Comp, dst: &mut CompoundTag) {
+        dst.insert_bool("Small", src.small);
+        dst.insert_bool("ShowArms", src.show_arms);
+        dst.insert_bool("NoBasePlate", src.no_base_plate);
+        dst.insert_bool("Marker", src.marker);
+        dst.insert_bool("Invisible", src.invisible);
+        dst.insert_i32("DisabledSlots", src.disabled_slots);
+        dst.insert_compound_tag("Pose", src.pose.encode());
+    }
+
+    fn decode(&self, src: &CompoundTag) -> Self::Comp {
+        ArmorStandEntity {
+            small: src.get_bool_or("Small", false),
+            show_arms: src.get_bool_or("ShowArms", false),
+            no_base_plate: src.get_bool_or("NoBasePlate", false),
+            marker: src.get_bool_or("Marker", false),
+            invisible: src.get_bool_or("Invisible", false),
+            disabled_slots: src.get_i32("DisabledSlots").unwrap_or_default(),
+            pose: src.get_compound_tag("Pose").map(ArmorStandPose::decode).unwrap_or_default()
+        }
+    }
+
+}
+
+
+/// An entity component for leashed mobs, the entity holding the leash. It is resolved from
+/// the leash of the `MobEntity` component by `system_decorations`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Leashed {
+    holder: Entity
+}
+
+impl Leashed {
+
+    pub fn get_holder(&self) -> Entity {
+        self.holder
+    }
+
+}
+
+
+/// Internal function to get the ID of a direction, as saved for item frames.
+fn get_direction_id(direction: Direction) -> u8 {
+    match direction {
+        Direction::Down => 0,
+        Direction::Up => 1,
+        Direction::North => 2,
+        Direction::South => 3,
+        Direction::West => 4,
+        Direction::East => 5
+    }
+}
+
+fn get_direction_from_id(id: u8) -> Option<Direction> {
+    [Direction::Down, Direction::Up, Direction::North, Direction::South, Direction::West, Direction::East]
+        .get(id as usize)
+        .copied()
+}
+
+/// Internal function to get the ID of a horizontal direction, as saved for paintings.
+fn get_horizontal_direction_id(direction: Direction) -> u8 {
+    match direction {
+        Direction::South => 0,
+        Direction::West => 1,
+        Direction::North => 2,
+        Direction::East => 3,
+        _ => 0
+    }
+}
+
+fn get_horizontal_direction_from_id(id: u8) -> Option<Direction> {
+    [Direction::South, Direction::West, Direction::North, Direction::East]
+        .get(id as usize)
+        .copied()
+}
+
+/// Internal function to rotate a horizontal direction by a quarter counter clockwise.
+fn get_counter_clockwise(direction: Direction) -> Direction {
+    match direction {
+        Direction::North => Direction::West,
+        Direction::West => Direction::South,
+        Direction::South => Direction::East,
+        Direction::East => Direction::North,
+        other => other
+    }
+}
+
+
+/// Return the blocks covered by a painting hanging at the given tile, the painting is
+/// centered on its tile and extends to the left and above for even sizes.
+pub fn get_painting_blocks(tile: &BlockPos, facing: Direction, motive: &PaintingMotive) -> Vec<BlockPos> {
+    let (width, height) = (motive.width as i32, motive.height as i32);
+    let (offset_x, offset_y) = ((width - 1) / -2, (height - 1) / -2);
+    let left = get_counter_clockwise(facing);
+    (0..width)
+        .flat_map(|x| (0..height).map(move |y| (x, y)))
+        .map(|(x, y)| tile.relative(left, x + offset_x).above(y + offset_y))
+        .collect()
+}
+
+/// Internal function to get the blocks covered by a hanging entity in front of its wall,
+/// leash knots don't cover any block.
+fn get_hanging_blocks(level: &Level, entity: Entity) -> Option<(Direction, Vec<BlockPos>)> {
+    let ecs = &level.entities.ecs;
+    let tile = ecs.get::<HangingEntity>(entity).ok()?.tile.clone();
+    if let Ok(frame) = ecs.get::<ItemFrameEntity>(entity) {
+        Some((frame.facing, vec![tile]))
+    } else if let Ok(painting) = ecs.get::<PaintingEntity>(entity) {
+        Some((painting.facing, get_painting_blocks(&tile, painting.facing, painting.motive)))
+    } else {
+        None
+    }
+}
+
+/// Internal function to check that the given blocks can be covered by a new hanging entity
+/// facing the given direction, the wall behind must collide, the blocks in front must not
+/// and no other hanging entity may cover them in the same direction.
+fn can_hang(level: &Level, facing: Direction, blocks: &[BlockPos], exclude: Option<Entity>) -> bool {
+
+    let wall_ok = blocks.iter().all(|pos| {
+        is_colliding(level, &pos.relative(facing.opposite(), 1)) && !is_colliding(level, pos)
+    });
+
+    wall_ok && level.entities.ecs.query::<&HangingEntity>()
+        .iter()
+        .filter(|&(other, _)| Some(other) != exclude)
+        .filter_map(|(other, _)| get_hanging_blocks(level, other))
+        .all(|(other_facing, other_blocks)| {
+            other_facing != facing || !other_blocks.iter().any(|pos| blocks.contains(pos))
+        })
+
+}
+
+/// Return true if a painting of the given motive can be placed at this tile, the blocks
+/// behind the painting must all collide and no other painting or item frame may overlap it.
+pub fn can_place_painting(level: &Level, tile: &BlockPos, facing: Direction, motive: &PaintingMotive) -> bool {
+    facing.axis() != mc_core::pos::Axis::Y && can_hang(level, facing, &get_painting_blocks(tile, facing, motive), None)
+}
+
+/// Return true if an item frame can be placed at this tile, against the block behind it.
+pub fn can_place_item_frame(level: &Level, tile: &BlockPos, facing: Direction) -> bool {
+    can_hang(level, facing, std::slice::from_ref(tile), None)
+}
+
+/// Choose the motive of a painting placed at the given tile like vanilla, a random motive
+/// is picked among the largest motives that can be placed.
+pub fn choose_painting_motive(level: &Level, tile: &BlockPos, facing: Direction, rand: &mut JavaRandom) -> Option<&'static PaintingMotive> {
+    let valid: Vec<&'static PaintingMotive> = PAINTING_MOTIVES.iter()
+        .copied()
+        .filter(|motive| can_place_painting(level, tile, facing, motive))
+        .collect();
+    let max_area = valid.iter().map(|motive| motive.width as u32 * motive.height as u32).max()?;
+    let largest: Vec<_> = valid.into_iter()
+        .filter(|motive| motive.width as u32 * motive.height as u32 == max_area)
+        .collect();
+    Some(largest[rand.next_int_bounded(largest.len() as i32) as usize])
+}
+
+/// Internal function to get the position of a hanging entity in front of its wall, even
+/// sizes are shifted to the middle of their blocks.
+fn get_hanging_pos(tile: &BlockPos, facing: Direction, width: u8, height: u8) -> EntityPos {
+    let (nx, ny, nz) = facing.normal(1);
+    let (lx, _, lz) = get_counter_clockwise(facing).normal(1);
+    let shift = |size: u8| if size.is_multiple_of(2) { 0.5 } else { 0.0 };
+    EntityPos::new(
+        tile.x as f64 + 0.5 - nx as f64 * HANGING_WALL_OFFSET + lx as f64 * shift(width),
+        tile.y as f64 + 0.5 - ny as f64 * HANGING_WALL_OFFSET + shift(height),
+        tile.z as f64 + 0.5 - nz as f64 * HANGING_WALL_OFFSET + lz as f64 * shift(width)
+    )
+}
+
+/// Place a painting at the given tile, its motive is chosen with `choose_painting_motive`.
+/// None is returned if no painting can be placed there.
+pub fn place_painting(level: &mut Level, tile: &BlockPos, facing: Direction, rand: &mut JavaRandom) -> Option<Entity> {
+    let motive = choose_painting_motive(level, tile, facing, rand)?;
+    let entity = level.spawn_entity(&PAINTING, get_hanging_pos(tile, facing, motive.width, motive.height))?;
+    level.entities.ecs.insert(entity, (HangingEntity::new(tile.clone()), PaintingEntity::new(facing, motive))).unwrap();
+    Some(entity)
+}
+
+/// Place an empty item frame at the given tile, glowing if requested. None is returned if
+/// the frame can't be placed there.
+pub fn place_item_frame(level: &mut Level, tile: &BlockPos, facing: Direction, glow: bool) -> Option<Entity> {
+    if !can_place_item_frame(level, tile, facing) {
+        return None;
+    }
+    let entity_type = if glow { &GLOW_ITEM_FRAME } else { &ITEM_FRAME };
+    let entity = level.spawn_entity(entity_type, get_hanging_pos(tile, facing, 1, 1))?;
+    level.entities.ecs.insert(entity, (HangingEntity::new(tile.clone()), ItemFrameEntity::new(facing))).unwrap();
+    Some(entity)
+}
+
+/// Internal function to drop stacks at the given position.
+fn drop_stacks(level: &mut Level, pos: &EntityPos, stacks: Vec<ItemStack>) {
+    for stack in stacks {
+        if let Some(dropped) = level.spawn_entity(&ITEM, pos.clone()) {
+            level.entities.ecs.insert_one(dropped, ItemEntity::new(stack)).unwrap();
+        }
+    }
+}
+
+/// Break a hanging entity, it is removed and its item is dropped, along with the item of
+/// item frames. The mobs tied to a leash knot drop their lead.
+pub fn break_hanging_entity(level: &mut Level, entity: Entity) -> bool {
+
+    let (pos, entity_type) = match level.entities.ecs.query_one_mut::<(&BaseEntity, &HangingEntity)>(entity) {
+        Ok((base, _)) => (base.pos.clone(), base.entity_type),
+        Err(_) => return false
+    };
+
+    let mut stacks = Vec::new();
+    if let Ok(mut frame) = level.entities.ecs.get_mut::<ItemFrameEntity>(entity) {
+        let drop_chance = frame.drop_chance;
+        stacks.extend(frame.set_item(None).filter(|_| drop_chance > 0.0));
+    }
+    stacks.extend(get_hanging_item(entity_type).map(ItemStack::with_item));
+
+    for mob in get_leashed_mobs(level, entity) {
+        unleash_mob(level, mob, true);
+    }
+
+    level.remove_entity(entity);
+    drop_stacks(level, &pos, stacks);
+    true
+
+}
+
+/// Internal function to get the item dropped by a broken hanging entity.
+fn get_hanging_item(entity_type: &'static EntityType) -> Option<&'static mc_core::item::Item> {
+    if std::ptr::eq(entity_type, &PAINTING) {
+        Some(&PAINTING_ITEM)
+    } else if std::ptr::eq(entity_type, &ITEM_FRAME) {
+        Some(&ITEM_FRAME_ITEM)
+    } else if std::ptr::eq(entity_type, &GLOW_ITEM_FRAME) {
+        Some(&GLOW_ITEM_FRAME_ITEM)
+    } else {
+        None
+    }
+}
+
+
+/// Return true if the mob can be leashed, only animals, golems and tamable mobs that are
+/// not already leashed can be leashed.
+pub fn can_be_leashed(level: &Level, mob: Entity) -> bool {
+    let ecs = &level.entities.ecs;
+    let leashable = ecs.get::<BreedableEntity>(mob).is_ok()
+        || ecs.get::<TamableEntity>(mob).is_ok()
+        || ecs.get::<BaseEntity>(mob).is_ok_and(|base| {
+            std::ptr::eq(base.entity_type, &IRON_GOLEM) || std::ptr::eq(base.entity_type, &SNOW_GOLEM)
+        });
+    leashable && ecs.get::<Dead>(mob).is_err() && ecs.get::<MobEntity>(mob).is_ok_and(|mob| mob.get_leash().is_none())
+}
+
+/// Leash a mob to a holder entity, the leash of mobs tied to a leash knot is saved with the
+/// position of the fence. Return false if the mob can't be leashed or the holder doesn't
+/// exist.
+pub fn leash_mob(level: &mut Level, mob: Entity, holder: Entity) -> bool {
+
+    if mob == holder || !can_be_leashed(level, mob) {
+        return false;
+    }
+
+    let leash = {
+        let ecs = &level.entities.ecs;
+        let holder_base = match ecs.get::<BaseEntity>(holder) {
+            Ok(base) => base,
+            Err(_) => return false
+        };
+        if std::ptr::eq(holder_base.entity_type, &LEASH_KNOT) {
+            LeashConfig::Fence(ecs.get::<HangingEntity>(holder).unwrap().tile.clone())
+        } else {
+            LeashConfig::Entity(holder_base.uuid)
+        }
+    };
+
+    level.entities.ecs.get_mut::<MobEntity>(mob).unwrap().set_leash(Some(leash));
+    level.entities.ecs.insert_one(mob, Leashed { holder }).unwrap();
+    true
+
+}
+
+/// Remove the leash of a mob, the lead item is dropped if requested. Return false if the
+/// mob was not leashed.
+pub fn unleash_mob(level: &mut Level, mob: Entity, drop_lead: bool) -> bool {
+
+    let _ = level.entities.ecs.remove_one::<Leashed>(mob);
+    let leash = match level.entities.ecs.get_mut::<MobEntity>(mob) {
+        Ok(mut mob) => mob.set_leash(None),
+        Err(_) => None
+    };
+
+    if leash.is_some() && drop_lead {
+        let pos = level.entities.ecs.get::<BaseEntity>(mob).unwrap().pos.clone();
+        drop_stacks(level, &pos, vec![ItemStack::with_item(&LEAD)]);
+    }
+
+    leash.is_some()
+
+}
+
+/// Return the mobs whose leash is held by the given entity.
+pub fn get_leashed_mobs(level: &Level, holder: Entity) -> Vec<Entity> {
+    level.entities.ecs.query::<&Leashed>()
+        .iter()
+        .filter(|(_, leashed)| leashed.holder == holder)
+        .map(|(mob, _)| mob)
+        .collect()
+}
+
+/// Return the leash knot tied to the fence at the given position.
+pub fn find_leash_knot(level: &Level, pos: &BlockPos) -> Option<Entity> {
+    level.entities.ecs.query::<(&BaseEntity, &HangingEntity)>()
+        .iter()
+        .find(|(_, (base, hanging))| std::ptr::eq(base.entity_type, &LEASH_KNOT) && hanging.tile == *pos)
+        .map(|(entity, _)| entity)
+}
+
+/// Return the leash knot tied to the fence at the given position, a knot is spawned if the
+/// fence has none. None is returned if the block is not a fence.
+pub fn get_or_spawn_leash_knot(level: &mut Level, pos: &BlockPos) -> Option<Entity> {
+    if !get_block(level, pos).is_some_and(is_leash_fence) {
+        return None;
+    }
+    if let Some(knot) = find_leash_knot(level, pos) {
+        return Some(knot);
+    }
+    let knot = level.spawn_entity(&LEASH_KNOT, EntityPos::new(pos.x as f64 + 0.5, pos.y as f64 + 0.375, pos.z as f64 + 0.5))?;
+    level.entities.ecs.insert_one(knot, HangingEntity::new(pos.clone())).unwrap();
+    Some(knot)
+}
+
+/// Tie the mobs held by an entity to the fence at the given position, only the mobs closer
+/// than `LEASH_TIE_DISTANCE` to the fence are tied. Return the number of tied mobs.
+pub fn tie_leashed_mobs(level: &mut Level, holder: Entity, pos: &BlockPos) -> usize {
+
+    let center = EntityPos::new(pos.x as f64 + 0.5, pos.y as f64 + 0.5, pos.z as f64 + 0.5);
+    let mobs: Vec<Entity> = get_leashed_mobs(level, holder).into_iter()
+        .filter(|&mob| level.entities.ecs.get::<BaseEntity>(mob).is_ok_and(|base| {
+            let (dx, dy, dz) = (base.pos.x - center.x, base.pos.y - center.y, base.pos.z - center.z);
+            dx.abs() <= LEASH_TIE_DISTANCE && dy.abs() <= LEASH_TIE_DISTANCE && dz.abs() <= LEASH_TIE_DISTANCE
+        }))
+        .collect();
+
+    if mobs.is_empty() {
+        return 0;
+    }
+
+    let knot = match get_or_spawn_leash_knot(level, pos) {
+        Some(knot) => knot,
+        None => return 0
+    };
+
+    for &mob in &mobs {
+        unleash_mob(level, mob, false);
+        leash_mob(level, mob, knot);
+    }
+
+    mobs.len()
+
+}
+
+
+/// Internal function to find the holder of a leash loaded from NBT, the knot of a fence is
+/// spawned if missing.
+fn resolve_leash(level: &mut Level, leash: &LeashConfig) -> Option<Entity> {
+    match leash {
+        LeashConfig::Entity(uuid) => level.entities.ecs.query::<&BaseEntity>()
+            .iter()
+            .find(|(_, base)| base.uuid == *uuid)
+            .map(|(entity, _)| entity),
+        LeashConfig::Fence(pos) => get_or_spawn_leash_knot(level, pos)
+    }
+}
+
+/// System updating decoration entities, hanging entities whose support is no longer valid
+/// are broken. Leashes loaded from NBT are resolved once their holder is loaded, leashed
+/// mobs are pulled toward their holder and leashes break when the holder is too far, dead
+/// or removed. An `EntityMovedEvent` is pushed for each pulled mob.
+pub fn system_decorations(world: &mut World) {
+
+    for level_rc in &world.levels {
+
+        let mut level = level_rc.borrow_mut();
+
+        let broken: Vec<Entity> = level.entities.ecs.query::<(&BaseEntity, &HangingEntity)>()
+            .iter()
+            .filter(|&(entity, (base, hanging))| {
+                if std::ptr::eq(base.entity_type, &LEASH_KNOT) {
+                    get_block(&level, &hanging.tile).is_some_and(|state| !is_leash_fence(state))
+                } else if let Some((facing, blocks)) = get_hanging_blocks(&level, entity) {
+                    // Unloaded walls are colliding, so hanging entities are kept there.
+                    !blocks.iter().all(|pos| is_colliding(&level, &pos.relative(facing.opposite(), 1)))
+                } else {
+                    false
+                }
+            })
+            .map(|(entity, _)| entity)
+            .collect();
+
+        for entity in broken {
+            break_hanging_entity(&mut level, entity);
+        }
+
+        let unresolved: Vec<(Entity, LeashConfig)> = level.entities.ecs.query::<&MobEntity>()
+            .without::<Leashed>()
+            .iter()
+            .filter_map(|(entity, mob)| mob.get_leash().map(|leash| (entity, leash.clone())))
+            .collect();
+
+        for (mob, leash) in unresolved {
+            match resolve_leash(&mut level, &leash) {
+                Some(holder) => {
+                    level.entities.ecs.insert_one(mob, Leashed { holder }).unwrap();
+                }
+                // The fence has been removed while the mob was unloaded.
+                None if matches!(leash, LeashConfig::Fence(_)) => {
+                    unleash_mob(&mut level, mob, true);
+                }
+                None => {}
+            }
+        }
+
+        let leashed: Vec<(Entity, Option<EntityPos>, EntityPos)> = level.entities.ecs.query::<(&BaseEntity, &Leashed)>()
+            .iter()
+            .map(|(mob, (base, leashed))| {
+                let ecs = &level.entities.ecs;
+                let holder_pos = ecs.get::<BaseEntity>(leashed.holder).ok()
+                    .filter(|_| ecs.get::<Dead>(leashed.holder).is_err())
+                    .map(|holder| holder.pos.clone());
+                (mob, holder_pos, base.pos.clone())
+            })
+            .collect();
+
+        for (mob, holder_pos, from) in leashed {
+
+            let holder_pos = match holder_pos {
+                Some(pos) => pos,
+                None => {
+                    unleash_mob(&mut level, mob, true);
+                    continue;
+                }
+            };
+
+            let (dx, dy, dz) = (holder_pos.x - from.x, holder_pos.y - from.y, holder_pos.z - from.z);
+            let distance = (dx * dx + dy * dy + dz * dz).sqrt();
+
+            if distance > LEASH_BREAK_DISTANCE {
+                unleash_mob(&mut level, mob, true);
+            } else if distance > LEASH_PULL_DISTANCE {
+                let speed = (distance - LEASH_PULL_DISTANCE).min(LEASH_PULL_SPEED) / distance;
+                let mut velocity = EntityPos::new(dx * speed, dy * speed, dz * speed);
+                let mut to = from.clone();
+                let on_ground = move_with_collisions(&level, &mut to, &mut velocity);
+                if to != from {
+                    level.move_entity(mob, to.clone());
+                    world.event_tracker.push_event(EntityMovedEvent {
+                        level: Rc::clone(level_rc),
+                        entity: mob,
+                        from,
+                        to,
+                        look: None,
+                        on_ground
+                    });
+                }
+            }
+
+        }
+
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_core::world::level::LevelEnv;
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::source::{ChunkLoadRequest, NullLevelSource};
+
+    use crate::ext::VanillaLevelEnv;
+    use crate::entity::{PIG, PLAYER};
+
+    use super::*;
+
+    #[test]
+    fn decorations() {
+
+        let env = LevelEnv::vanilla();
+        let height = ChunkHeight::new(0, 0);
+        let mut level = Level::new("overworld".to_string(), env.clone(), height, NullLevelSource);
+        level.chunks.insert_chunk(ChunkLoadRequest { env, height, cx: 0, cz: 0 }.build_chunk());
+
+        // A wall of 2 by 2 blocks facing south, with a fence next to it.
+        for x in 0..2 {
+            for y in 1..3 {
+                level.chunks.set_block_at(x, y, 0, STONE.get_default_state()).unwrap();
+            }
+        }
+        level.chunks.set_block_at(8, 1, 8, OAK_FENCE.get_default_state()).unwrap();
+
+        // The largest motives fitting the wall are chosen, extending to the left and above.
+        let tile = BlockPos::new(0, 1, 1);
+        let mut rand = JavaRandom::new(0);
+        assert!(!can_place_painting(&level, &tile, Direction::North, &MOTIVE_KEBAB));
+        let motive = choose_painting_motive(&level, &tile, Direction::South, &mut rand).unwrap();
+        assert_eq!((motive.get_width(), motive.get_height()), (2, 2));
+        assert!(!can_place_painting(&level, &tile, Direction::South, &MOTIVE_FIGHTERS));
+        let painting = place_painting(&mut level, &tile, Direction::South, &mut rand).unwrap();
+        assert!(!can_place_item_frame(&level, &BlockPos::new(1, 2, 1), Direction::South));
+
+        let mut tag_painting = CompoundTag::new();
+        let motive = level.entities.ecs.get::<PaintingEntity>(painting).unwrap().get_motive();
+        assert_eq!(motive.get_width() * motive.get_height(), 4);
+        PaintingEntityCodec.encode(&level.entities.ecs.get::<PaintingEntity>(painting).unwrap(), &mut tag_painting);
+        assert!(std::ptr::eq(PaintingEntityCodec.decode(&tag_painting).get_motive(), motive));
+
+        // Item frames only rotate their item.
+        let mut frame = ItemFrameEntity::new(Direction::Up);
+        assert!(!frame.rotate());
+        frame.set_item(Some(ItemStack::with_item(&LEAD)));
+        for _ in 0..9 {
+            frame.rotate();
+        }
+        assert_eq!(frame.get_comparator_output(), 2);
+
+        // Armor stands only save the parts that are not in their default pose.
+        let mut stand = ArmorStandEntity::default();
+        stand.get_pose_mut().head = Rotations::new(10.0, 0.0, 0.0);
+        let mut tag_stand = CompoundTag::new();
+        ArmorStandEntityCodec.encode(&stand, &mut tag_stand);
+        assert_eq!(tag_stand.get_compound_tag("Pose").unwrap().iter().count(), 1);
+        assert_eq!(ArmorStandEntityCodec.decode(&tag_stand).get_pose(), stand.get_pose());
+
+        // A pig is pulled toward the player holding its leash, and tied to the fence.
+        let player = level.spawn_entity(&PLAYER, EntityPos::new(8.5, 1.0, 4.0)).unwrap();
+        let pig = level.spawn_entity(&PIG, EntityPos::new(8.5, 1.0, 11.0)).unwrap();
+        assert!(leash_mob(&mut level, pig, player));
+        assert!(!can_be_leashed(&level, pig));
+
+        let mut world = World::new();
+        world.add_level(level);
+        system_decorations(&mut world);
+        let mut level = world.levels[0].borrow_mut();
+        assert!((level.entities.ecs.get::<BaseEntity>(pig).unwrap().pos.z - 10.6).abs() < 1e-9);
+
+        assert_eq!(tie_leashed_mobs(&mut level, player, &BlockPos::new(8, 1, 8)), 1);
+        let knot = find_leash_knot(&level, &BlockPos::new(8, 1, 8)).unwrap();
+        assert_eq!(get_leashed_mobs(&level, knot), vec![pig]);
+        assert!(matches!(level.entities.ecs.get::<MobEntity>(pig).unwrap().get_leash(), Some(LeashConfig::Fence(_))));
+
+        // Breaking the fence breaks the knot, and the lead is dropped.
+        level.chunks.set_block_at(8, 1, 8, AIR.get_default_state()).unwrap();
+        drop(level);
+        system_decorations(&mut world);
+        let level = world.levels[0].borrow();
+        assert!(level.entities.ecs.get::<BaseEntity>(knot).is_err());
+        assert!(level.entities.ecs.get::<MobEntity>(pig).unwrap().get_leash().is_none());
+        assert_eq!(level.entities.ecs.query::<&ItemEntity>().iter().count(), 1);
+
+    }
+
+}
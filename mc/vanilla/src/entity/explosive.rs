@@ -0,0 +1,448 @@
+//! Explosive entities, primed TNT explodes when its fuse expires and end crystals explode
+//! when damaged. TNT blocks can be ignited by flint and steel or dispensers, and TNT blocks
+//! or end crystals hit by an explosion explode in turn.
+
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use mc_core::world::level::{Level, BaseEntity};
+use mc_core::pos::{EntityPos, BlockPos};
+use mc_core::rand::JavaRandom;
+use hecs::Entity;
+
+use mc_runtime::event::EntityMovedEvent;
+use mc_runtime::world::World;
+
+use crate::block::material::BlockMaterials;
+use crate::block::*;
+use crate::explosion::{Explosion, ExplosionEvent, TNT_EXPLOSION_POWER, END_CRYSTAL_EXPLOSION_POWER};
+
+use super::physics::move_with_collisions;
+use super::{TNT as TNT_ENTITY, END_CRYSTAL};
+
+
+/// Default fuse of primed TNT, in ticks.
+pub const TNT_FUSE: u16 = 80;
+
+/// Velocity removed from the Y axis every tick for falling TNT.
+const GRAVITY: f64 = 0.04;
+/// Drag of primed TNT in the air.
+const AIR_DRAG: f64 = 0.98;
+/// Horizontal drag of primed TNT on the ground, applied in addition to the air drag.
+const GROUND_DRAG: f64 = 0.7;
+/// Height above primed TNT where it explodes.
+const TNT_EXPLOSION_HEIGHT: f64 = 0.0625;
+
+
+/// An entity component for primed TNT, ticked by `system_explosives`.
+#[derive(Debug)]
+pub struct PrimedTnt {
+    /// Velocity in blocks per tick.
+    pub velocity: EntityPos,
+    fuse: u16,
+    /// The entity that ignited the TNT.
+    igniter: Option<Entity>
+}
+
+impl PrimedTnt {
+
+    pub fn new(fuse: u16) -> Self {
+        Self {
+            velocity: EntityPos::default(),
+            fuse,
+            igniter: None
+        }
+    }
+
+    pub fn with_velocity(mut self, velocity: EntityPos) -> Self {
+        self.velocity = velocity;
+        self
+    }
+
+    pub fn with_igniter(mut self, igniter: Entity) -> Self {
+        self.igniter = Some(igniter);
+        self
+    }
+
+    /// Return the remaining ticks before the explosion.
+    #[inline]
+    pub fn get_fuse(&self) -> u16 {
+        self.fuse
+    }
+
+    #[inline]
+    pub fn get_igniter(&self) -> Option<Entity> {
+        self.igniter
+    }
+
+}
+
+
+/// An entity component for end crystals, damaged crystals explode on the next run of
+/// `system_explosives`.
+#[derive(Debug)]
+pub struct EndCrystal {
+    /// The block targeted by the crystal's beam, used during the dragon fight.
+    beam_target: Option<BlockPos>,
+    show_bottom: bool,
+    damaged: bool
+}
+
+impl EndCrystal {
+
+    pub fn new() -> Self {
+        Self {
+            beam_target: None,
+            show_bottom: true,
+            damaged: false
+        }
+    }
+
+    /// Set if the bedrock base of the crystal is visible.
+    pub fn with_show_bottom(mut self, show_bottom: bool) -> Self {
+        self.show_bottom = show_bottom;
+        self
+    }
+
+    #[inline]
+    pub fn get_beam_target(&self) -> Option<&BlockPos> {
+        self.beam_target.as_ref()
+    }
+
+    #[inline]
+    pub fn set_beam_target(&mut self, beam_target: Option<BlockPos>) {
+        self.beam_target = beam_target;
+    }
+
+    #[inline]
+    pub fn is_showing_bottom(&self) -> bool {
+        self.show_bottom
+    }
+
+    /// Damage the crystal, it explodes on the next run of `system_explosives`.
+    #[inline]
+    pub fn damage(&mut self) {
+        self.damaged = true;
+    }
+
+    #[inline]
+    pub fn is_damaged(&self) -> bool {
+        self.damaged
+    }
+
+}
+
+impl Default for EndCrystal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// Spawn a primed TNT at the bottom center of the given block, with the small random
+/// horizontal velocity of vanilla TNT.
+pub fn spawn_primed_tnt(level: &mut Level, pos: &BlockPos, fuse: u16, rand: &mut JavaRandom, igniter: Option<Entity>) -> Option<Entity> {
+
+    let angle = rand.next_double() * std::f64::consts::PI * 2.0;
+    let velocity = EntityPos::new(-angle.sin() * 0.02, 0.2, -angle.cos() * 0.02);
+    let mut tnt = PrimedTnt::new(fuse).with_velocity(velocity);
+    if let Some(igniter) = igniter {
+        tnt = tnt.with_igniter(igniter);
+    }
+
+    let entity = level.spawn_entity(&TNT_ENTITY, EntityPos::new(pos.x as f64 + 0.5, pos.y as f64, pos.z as f64 + 0.5))?;
+    level.entities.ecs.insert_one(entity, tnt).unwrap();
+    Some(entity)
+
+}
+
+/// Replace the TNT block at the given position by a primed TNT, `None` if there is no TNT
+/// block at this position.
+pub fn ignite_tnt(level: &mut Level, pos: &BlockPos, rand: &mut JavaRandom, igniter: Option<Entity>) -> Option<Entity> {
+    if !level.chunks.get_block_at(pos.x, pos.y, pos.z).ok()?.is_block(&TNT) {
+        return None;
+    }
+    level.chunks.set_block_at(pos.x, pos.y, pos.z, AIR.get_default_state()).ok()?;
+    spawn_primed_tnt(level, pos, TNT_FUSE, rand, igniter)
+}
+
+/// Use flint and steel on a block, TNT blocks are ignited. Placing fire on other blocks is
+/// not handled here.
+pub fn use_flint_and_steel(level: &mut Level, pos: &BlockPos, rand: &mut JavaRandom, user: Entity) -> Option<Entity> {
+    ignite_tnt(level, pos, rand, Some(user))
+}
+
+/// Return the position of the block in front of a dispenser, `None` if the block at the
+/// given position is not a dispenser.
+fn get_dispenser_front(level: &Level, pos: &BlockPos) -> Option<BlockPos> {
+    let state = level.chunks.get_block_at(pos.x, pos.y, pos.z).ok()?;
+    if state.is_block(&DISPENSER) {
+        Some(pos.relative(state.get(&PROP_FACING)?, 1))
+    } else {
+        None
+    }
+}
+
+/// Dispense a TNT item from the dispenser at the given position, a primed TNT is spawned in
+/// front of the dispenser.
+pub fn dispense_tnt(level: &mut Level, pos: &BlockPos, rand: &mut JavaRandom) -> Option<Entity> {
+    let front = get_dispenser_front(level, pos)?;
+    spawn_primed_tnt(level, &front, TNT_FUSE, rand, None)
+}
+
+/// Dispense flint and steel from the dispenser at the given position, a TNT block in front
+/// of the dispenser is ignited.
+pub fn dispense_flint_and_steel(level: &mut Level, pos: &BlockPos, rand: &mut JavaRandom) -> Option<Entity> {
+    let front = get_dispenser_front(level, pos)?;
+    ignite_tnt(level, &front, rand, None)
+}
+
+/// Spawn an end crystal at the bottom center of the given block.
+pub fn spawn_end_crystal(level: &mut Level, pos: &BlockPos, crystal: EndCrystal) -> Option<Entity> {
+    let entity = level.spawn_entity(&END_CRYSTAL, EntityPos::new(pos.x as f64 + 0.5, pos.y as f64, pos.z as f64 + 0.5))?;
+    level.entities.ecs.insert_one(entity, crystal).unwrap();
+    Some(entity)
+}
+
+
+/// World component required by `system_explosives`, with the materials giving the explosion
+/// resistance of blocks.
+pub struct Explosives {
+    materials: &'static BlockMaterials,
+    rand: JavaRandom,
+    /// Explosions queued with their level index, applied on the next run of the system.
+    queued: Vec<(usize, Explosion)>
+}
+
+impl Explosives {
+
+    pub fn new(materials: &'static BlockMaterials) -> Self {
+        Self {
+            materials,
+            rand: JavaRandom::new_seeded(),
+            queued: Vec::new()
+        }
+    }
+
+    pub fn with_seed(mut self, seed: i64) -> Self {
+        self.rand = JavaRandom::new(seed);
+        self
+    }
+
+    /// Queue an explosion in the level at the given index, it is applied by the next run of
+    /// `system_explosives` like explosions of TNT.
+    pub fn queue_explosion(&mut self, level_idx: usize, explosion: Explosion) {
+        self.queued.push((level_idx, explosion));
+    }
+
+}
+
+
+/// Call this function with a mutable reference to a World to register the `Explosives`
+/// component which is required to run the system `system_explosives`.
+pub fn register_explosives(world: &mut World, explosives: Explosives) {
+    world.insert_component(explosives);
+}
+
+/// A system that moves primed TNT and makes it explode when its fuse expires, damaged end
+/// crystals also explode. TNT blocks destroyed by an explosion are primed with a short fuse
+/// and end crystals hit by an explosion explode immediately. Queued explosions are applied
+/// before the explosions of this tick. An `ExplosionEvent` is pushed
+/// for each explosion and an `EntityMovedEvent` for each moved TNT.
+pub fn system_explosives(world: &mut World) {
+
+    let mut explosives = match world.components.get_mut::<Explosives>() {
+        Ok(explosives) => explosives,
+        Err(_) => return
+    };
+
+    let explosives = &mut *explosives;
+    let queued = std::mem::take(&mut explosives.queued);
+
+    for (level_idx, level_rc) in world.levels.iter().enumerate() {
+
+        let mut level = level_rc.borrow_mut();
+        let mut explosions: VecDeque<_> = queued.iter()
+            .filter(|(idx, _)| *idx == level_idx)
+            .map(|(_, explosion)| explosion.clone())
+            .collect();
+
+        let tnts: Vec<_> = level.entities.ecs.query::<(&BaseEntity, &mut PrimedTnt)>()
+            .iter()
+            .map(|(entity, (base_entity, tnt))| {
+                tnt.fuse = tnt.fuse.saturating_sub(1);
+                (entity, base_entity.pos.clone(), tnt.velocity.clone(), tnt.fuse)
+            })
+            .collect();
+
+        for (entity, from, mut velocity, fuse) in tnts {
+
+            if fuse == 0 {
+                level.remove_entity(entity);
+                let pos = EntityPos::new(from.x, from.y + TNT_EXPLOSION_HEIGHT, from.z);
+                explosions.push_back(Explosion::new(pos, TNT_EXPLOSION_POWER));
+                continue;
+            }
+
+            let mut to = from.clone();
+            velocity.y -= GRAVITY;
+            let on_ground = move_with_collisions(&level, &mut to, &mut velocity);
+            let drag = if on_ground { AIR_DRAG * GROUND_DRAG } else { AIR_DRAG };
+            velocity.x *= drag;
+            velocity.y *= AIR_DRAG;
+            velocity.z *= drag;
+            level.entities.ecs.get_mut::<PrimedTnt>(entity).unwrap().velocity = velocity;
+
+            if to != from {
+                level.move_entity(entity, to.clone());
+                world.event_tracker.push_event(EntityMovedEvent {
+                    level: Rc::clone(level_rc),
+                    entity,
+                    from,
+                    to,
+                    look: None,
+                    on_ground
+                });
+            }
+
+        }
+
+        let crystals: Vec<_> = level.entities.ecs.query::<(&BaseEntity, &EndCrystal)>()
+            .iter()
+            .filter(|(_, (_, crystal))| crystal.is_damaged())
+            .map(|(entity, (base_entity, _))| (entity, base_entity.pos.clone()))
+            .collect();
+
+        for (entity, pos) in crystals {
+            level.remove_entity(entity);
+            explosions.push_back(Explosion::new(pos, END_CRYSTAL_EXPLOSION_POWER));
+        }
+
+        while let Some(explosion) = explosions.pop_front() {
+
+            let mut result = explosion.explode(&mut level, explosives.materials, &mut explosives.rand);
+
+            // Exploded TNT blocks are primed instead of dropping.
+            result.drops.retain(|(_, state)| !state.is_block(&TNT));
+            for (pos, state) in &result.destroyed {
+                if state.is_block(&TNT) {
+                    let fuse = explosives.rand.next_int_bounded(TNT_FUSE as i32 / 4) as u16 + TNT_FUSE / 8;
+                    spawn_primed_tnt(&mut level, pos, fuse, &mut explosives.rand, None);
+                }
+            }
+
+            for hit in &result.hits {
+                let pos = match level.entities.ecs.query_one_mut::<(&BaseEntity, &EndCrystal)>(hit.entity) {
+                    Ok((base_entity, _)) => base_entity.pos.clone(),
+                    Err(_) => continue
+                };
+                level.remove_entity(hit.entity);
+                explosions.push_back(Explosion::new(pos, END_CRYSTAL_EXPLOSION_POWER));
+            }
+
+            world.event_tracker.push_event(ExplosionEvent {
+                level: Rc::clone(level_rc),
+                explosion,
+                result
+            });
+
+        }
+
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_core::world::level::LevelEnv;
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::source::{ChunkLoadRequest, NullLevelSource};
+    use mc_core::pos::Direction;
+
+    use crate::block::material::VANILLA_BLOCK_MATERIALS;
+    use crate::ext::VanillaLevelEnv;
+    use crate::entity::PLAYER;
+
+    use super::*;
+
+    #[test]
+    fn explosives() {
+
+        let env = LevelEnv::vanilla();
+        let height = ChunkHeight::new(0, 0);
+        let mut level = Level::new("overworld".to_string(), env.clone(), height, NullLevelSource);
+
+        let mut chunk = ChunkLoadRequest { env, height, cx: 0, cz: 0 }.build_chunk();
+        for x in 0..16 {
+            for z in 0..16 {
+                chunk.set_block(x, 0, z, BEDROCK.get_default_state()).unwrap();
+                chunk.set_block(x, 1, z, STONE.get_default_state()).unwrap();
+            }
+        }
+        level.chunks.insert_chunk(chunk);
+
+        let mut rand = JavaRandom::new(0);
+        let player = level.spawn_entity(&PLAYER, EntityPos::new(14.5, 2.0, 14.5)).unwrap();
+
+        // A TNT block ignited by a player, another one next to it and an end crystal.
+        level.chunks.set_block_at(8, 2, 8, TNT.get_default_state()).unwrap();
+        level.chunks.set_block_at(6, 2, 8, TNT.get_default_state()).unwrap();
+        let crystal = spawn_end_crystal(&mut level, &BlockPos::new(8, 2, 11), EndCrystal::new()).unwrap();
+
+        assert!(use_flint_and_steel(&mut level, &BlockPos::new(8, 1, 8), &mut rand, player).is_none());
+        let tnt = use_flint_and_steel(&mut level, &BlockPos::new(8, 2, 8), &mut rand, player).unwrap();
+        assert!(level.chunks.get_block_at(8, 2, 8).unwrap().is_block(&AIR));
+        assert_eq!(level.entities.ecs.get::<PrimedTnt>(tnt).unwrap().get_igniter(), Some(player));
+
+        // A dispenser facing a TNT block.
+        level.chunks.set_block_at(2, 2, 2, DISPENSER.get_default_state().with(&PROP_FACING, Direction::East).unwrap()).unwrap();
+        level.chunks.set_block_at(3, 2, 2, TNT.get_default_state()).unwrap();
+        assert!(dispense_tnt(&mut level, &BlockPos::new(3, 2, 2), &mut rand).is_none());
+        let dispensed = dispense_flint_and_steel(&mut level, &BlockPos::new(2, 2, 2), &mut rand).unwrap();
+        assert_eq!(level.entities.ecs.get::<BaseEntity>(dispensed).unwrap().pos, EntityPos::new(3.5, 2.0, 2.5));
+        level.remove_entity(dispensed);
+
+        let mut world = World::new();
+        world.add_level(level);
+        register_explosives(&mut world, Explosives::new(&VANILLA_BLOCK_MATERIALS).with_seed(0));
+
+        for _ in 1..TNT_FUSE {
+            system_explosives(&mut world);
+        }
+
+        // The TNT jumped and fell back on the ground.
+        {
+            let level = world.levels[0].borrow();
+            assert_eq!(level.entities.ecs.get::<BaseEntity>(tnt).unwrap().pos.y, 2.0);
+            assert_eq!(level.entities.ecs.get::<PrimedTnt>(tnt).unwrap().get_fuse(), 1);
+            assert_eq!(world.event_tracker.poll_events::<ExplosionEvent>().count(), 0);
+        }
+
+        // The TNT explodes, the crystal explodes in chain.
+        system_explosives(&mut world);
+        let explosions: Vec<_> = world.event_tracker.poll_events::<ExplosionEvent>()
+            .map(|event| (event.explosion.get_power(), event.result.destroyed.len()))
+            .collect();
+        assert_eq!(explosions.len(), 2);
+        assert_eq!(explosions[0].0, TNT_EXPLOSION_POWER);
+        assert_eq!(explosions[1].0, END_CRYSTAL_EXPLOSION_POWER);
+        assert!(explosions.iter().all(|&(_, destroyed)| destroyed > 0));
+
+        let level = world.levels[0].borrow();
+        assert!(level.entities.ecs.get::<PrimedTnt>(tnt).is_err());
+        assert!(level.entities.ecs.get::<EndCrystal>(crystal).is_err());
+
+        // The second TNT block has been primed with a short fuse.
+        assert!(level.chunks.get_block_at(6, 2, 8).unwrap().is_block(&AIR));
+        let primed: Vec<_> = level.entities.ecs.query::<&PrimedTnt>()
+            .iter()
+            .map(|(_, tnt)| tnt.get_fuse())
+            .collect();
+        assert_eq!(primed.len(), 1);
+        assert!(primed[0] >= TNT_FUSE / 8 && primed[0] < TNT_FUSE / 8 + TNT_FUSE / 4);
+
+    }
+
+}
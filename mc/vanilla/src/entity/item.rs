@@ -0,0 +1,508 @@
+//! Dropped item entities, items fall and float on water, merge with nearby identical stacks,
+//! burn in lava and fire and despawn after 5 minutes. Once their pickup delay is over, items
+//! are pulled toward and collected by nearby entities having an `Inventory` component.
+
+use std::collections::HashSet;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use mc_core::world::level::{Level, BaseEntity};
+use mc_core::item::{ItemStack, Inventory};
+use mc_core::pos::EntityPos;
+use hecs::Entity;
+
+use mc_runtime::event::EntityMovedEvent;
+use mc_runtime::world::World;
+
+use crate::block::*;
+use crate::block::fluid::get_fluid_state;
+use crate::death::Dead;
+
+use super::physics::{get_block_pos, get_block, move_with_collisions};
+
+
+/// Age in ticks at which items despawn, 5 minutes.
+pub const ITEM_DESPAWN_AGE: u32 = 6000;
+/// Default pickup delay in ticks of dropped items.
+pub const ITEM_PICKUP_DELAY: u32 = 10;
+
+/// Velocity removed from the Y axis every tick for falling items.
+const GRAVITY: f64 = 0.04;
+/// Drag of items in the air.
+const AIR_DRAG: f64 = 0.98;
+/// Horizontal drag of items on the ground, applied in addition to the air drag.
+const GROUND_DRAG: f64 = 0.6;
+/// Velocity added to the Y axis of items in water, up to `WATER_MAX_RISE_SPEED`.
+const WATER_BUOYANCY: f64 = 0.0005;
+const WATER_MAX_RISE_SPEED: f64 = 0.06;
+/// Horizontal drag of items in water.
+const WATER_DRAG: f64 = 0.99;
+
+/// Horizontal and vertical distances under which identical stacks merge.
+const MERGE_RANGE_XZ: f64 = 0.75;
+const MERGE_RANGE_Y: f64 = 0.25;
+/// Horizontal distance and vertical bounds, relative to a collector, where items are picked.
+const PICKUP_RANGE_XZ: f64 = 1.425;
+const PICKUP_RANGE_MIN_Y: f64 = -0.75;
+const PICKUP_RANGE_MAX_Y: f64 = 2.3;
+/// Distance under which items are pulled toward collectors, and their acceleration.
+const MAGNET_RADIUS: f64 = 3.0;
+const MAGNET_ACCELERATION: f64 = 0.08;
+
+
+/// An entity component for dropped items, ticked by `system_item_entities`.
+#[derive(Debug)]
+pub struct ItemEntity {
+    stack: ItemStack,
+    /// Velocity in blocks per tick.
+    pub velocity: EntityPos,
+    age: u32,
+    pickup_delay: u32
+}
+
+impl ItemEntity {
+
+    pub fn new(stack: ItemStack) -> Self {
+        Self {
+            stack,
+            velocity: EntityPos::default(),
+            age: 0,
+            pickup_delay: ITEM_PICKUP_DELAY
+        }
+    }
+
+    pub fn with_velocity(mut self, velocity: EntityPos) -> Self {
+        self.velocity = velocity;
+        self
+    }
+
+    pub fn with_pickup_delay(mut self, pickup_delay: u32) -> Self {
+        self.pickup_delay = pickup_delay;
+        self
+    }
+
+    /// Set the initial age of the item, items despawn when reaching `ITEM_DESPAWN_AGE`.
+    pub fn with_age(mut self, age: u32) -> Self {
+        self.age = age;
+        self
+    }
+
+    #[inline]
+    pub fn get_stack(&self) -> &ItemStack {
+        &self.stack
+    }
+
+    #[inline]
+    pub fn get_age(&self) -> u32 {
+        self.age
+    }
+
+    #[inline]
+    pub fn get_pickup_delay(&self) -> u32 {
+        self.pickup_delay
+    }
+
+    #[inline]
+    pub fn can_pickup(&self) -> bool {
+        self.pickup_delay == 0
+    }
+
+}
+
+
+/// Event pushed by `system_item_entities` when items are collected by an entity, the item
+/// entity is removed if its whole stack was collected.
+pub struct ItemPickupEvent {
+    pub level: Rc<RefCell<Level>>,
+    /// The item entity.
+    pub entity: Entity,
+    /// The entity that collected the items.
+    pub collector: Entity,
+    /// The collected items, the rest of the stack remains in the item entity.
+    pub stack: ItemStack
+}
+
+
+/// Event pushed by `system_item_entities` when an item entity is removed from its level.
+pub struct ItemRemovedEvent {
+    pub level: Rc<RefCell<Level>>,
+    pub entity: Entity,
+    pub reason: ItemRemoval
+}
+
+
+/// The reason why an item entity was removed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ItemRemoval {
+    /// The item reached `ITEM_DESPAWN_AGE`.
+    Despawned,
+    /// The item burnt in lava or fire.
+    Burnt,
+    /// The whole stack was merged into another item entity.
+    Merged,
+    /// The whole stack was collected.
+    PickedUp
+}
+
+
+/// Return true if items burn in the given block.
+fn is_burning_block(level: &Level, pos: &EntityPos) -> bool {
+    get_block(level, &get_block_pos(pos)).is_some_and(|state| {
+        state.is_block(&LAVA) || state.is_block(&FIRE) || state.is_block(&SOUL_FIRE)
+    })
+}
+
+/// Apply gravity, buoyancy and drag to an item and move it.
+fn tick_item(level: &Level, pos: &mut EntityPos, velocity: &mut EntityPos) {
+
+    let in_water = get_block(level, &get_block_pos(pos)).is_some_and(|state| get_fluid_state(state).is_water());
+
+    if in_water {
+        if velocity.y < WATER_MAX_RISE_SPEED {
+            velocity.y += WATER_BUOYANCY;
+        }
+        velocity.x *= WATER_DRAG;
+        velocity.z *= WATER_DRAG;
+    } else {
+        velocity.y -= GRAVITY;
+    }
+
+    let on_ground = move_with_collisions(level, pos, velocity);
+    let drag = if on_ground { AIR_DRAG * GROUND_DRAG } else { AIR_DRAG };
+    velocity.x *= drag;
+    velocity.y *= AIR_DRAG;
+    velocity.z *= drag;
+
+}
+
+/// Return true if an item at the given position is in range of the other position.
+#[inline]
+fn is_in_range(pos: &EntityPos, other: &EntityPos, range_xz: f64, min_y: f64, max_y: f64) -> bool {
+    let dy = pos.y - other.y;
+    (pos.x - other.x).abs() <= range_xz && (pos.z - other.z).abs() <= range_xz && dy >= min_y && dy <= max_y
+}
+
+
+/// A system that ticks all item entities of every level, the following steps are run every
+/// tick for each item entity:
+/// - its age is increased and it despawns at `ITEM_DESPAWN_AGE`;
+/// - it's removed if in lava or fire;
+/// - it's moved and pulled toward the nearest collector if its pickup delay is over;
+/// - it's merged with nearby identical stacks, the larger stack absorbing the smaller;
+/// - it's inserted in the inventory of a collector in range.
+///
+/// Collectors are entities with an `Inventory` component that are not `Dead`, usually
+/// players. An `EntityMovedEvent` is pushed for each moved item.
+pub fn system_item_entities(world: &mut World) {
+
+    for level_rc in &world.levels {
+
+        let mut level = level_rc.borrow_mut();
+        let mut removed = Vec::new();
+
+        let collectors: Vec<_> = level.entities.ecs.query::<(&BaseEntity, &Inventory)>()
+            .without::<Dead>()
+            .iter()
+            .map(|(entity, (base_entity, _))| (entity, base_entity.pos.clone()))
+            .collect();
+
+        let items: Vec<_> = level.entities.ecs.query::<(&BaseEntity, &mut ItemEntity)>()
+            .iter()
+            .map(|(entity, (base_entity, item))| {
+                item.age += 1;
+                item.pickup_delay = item.pickup_delay.saturating_sub(1);
+                (entity, base_entity.pos.clone(), item.velocity.clone(), item.age, item.can_pickup())
+            })
+            .collect();
+
+        // Timers and movements.
+        let mut positions = Vec::with_capacity(items.len());
+        for (entity, from, mut velocity, age, can_pickup) in items {
+
+            if age >= ITEM_DESPAWN_AGE {
+                removed.push((entity, ItemRemoval::Despawned));
+                continue;
+            } else if is_burning_block(&level, &from) {
+                removed.push((entity, ItemRemoval::Burnt));
+                continue;
+            }
+
+            if can_pickup {
+                let nearest = collectors.iter()
+                    .filter(|(collector, _)| {
+                        let stack = &level.entities.ecs.get::<ItemEntity>(entity).unwrap().stack;
+                        level.entities.ecs.get::<Inventory>(*collector).unwrap().can_insert(stack)
+                    })
+                    .map(|(_, pos)| (pos.x - from.x, pos.y - from.y, pos.z - from.z))
+                    .map(|(dx, dy, dz)| (dx, dy, dz, (dx * dx + dy * dy + dz * dz).sqrt()))
+                    .filter(|&(_, _, _, distance)| distance > 0.0 && distance < MAGNET_RADIUS)
+                    .min_by(|a, b| a.3.total_cmp(&b.3));
+                if let Some((dx, dy, dz, distance)) = nearest {
+                    let factor = MAGNET_ACCELERATION / distance;
+                    velocity.x += dx * factor;
+                    velocity.y += dy * factor;
+                    velocity.z += dz * factor;
+                }
+            }
+
+            let mut to = from.clone();
+            tick_item(&level, &mut to, &mut velocity);
+            level.entities.ecs.get_mut::<ItemEntity>(entity).unwrap().velocity = velocity;
+
+            if to != from {
+                level.move_entity(entity, to.clone());
+                world.event_tracker.push_event(EntityMovedEvent {
+                    level: Rc::clone(level_rc),
+                    entity,
+                    from,
+                    to: to.clone(),
+                    look: None,
+                    on_ground: false
+                });
+            }
+
+            positions.push((entity, to, can_pickup));
+
+        }
+
+        for &(entity, _) in &removed {
+            level.remove_entity(entity);
+        }
+
+        // Merging, stacks are removed once fully merged into another one.
+        let mut merged = HashSet::new();
+        for (entity, pos, _) in &positions {
+
+            let (cx, cz) = ((pos.x.floor() as i32) >> 4, (pos.z.floor() as i32) >> 4);
+            let mut others = Vec::new();
+            for ncx in (cx - 1)..=(cx + 1) {
+                for ncz in (cz - 1)..=(cz + 1) {
+                    if let Some(chunk) = level.chunks.get_chunk(ncx, ncz) {
+                        others.extend(chunk.iter_entities());
+                    }
+                }
+            }
+
+            for other in others {
+
+                if merged.contains(entity) {
+                    break;
+                } else if other == *entity || merged.contains(&other) {
+                    continue;
+                }
+
+                let ecs = &level.entities.ecs;
+                let other_pos = match ecs.get::<BaseEntity>(other) {
+                    Ok(base_entity) => base_entity.pos.clone(),
+                    Err(_) => continue
+                };
+
+                if !is_in_range(&other_pos, pos, MERGE_RANGE_XZ, -MERGE_RANGE_Y, MERGE_RANGE_Y) {
+                    continue;
+                }
+
+                let (other_stack, other_age, other_pickup_delay) = match ecs.get::<ItemEntity>(other) {
+                    Ok(item) => (item.stack.clone(), item.age, item.pickup_delay),
+                    Err(_) => continue
+                };
+
+                let item_stack = ecs.get::<ItemEntity>(*entity).unwrap().stack.clone();
+                if item_stack.is_full() || other_stack.is_full() || !item_stack.is_stackable_with(&other_stack) {
+                    continue;
+                }
+
+                // The larger stack absorbs the smaller one.
+                let (absorber, absorbed, absorbed_stack) = if other_stack.get_count() > item_stack.get_count() {
+                    (other, *entity, item_stack)
+                } else {
+                    (*entity, other, other_stack)
+                };
+
+                let mut absorber_item = ecs.get_mut::<ItemEntity>(absorber).unwrap();
+                let remaining = absorber_item.stack.merge(absorbed_stack);
+                absorber_item.age = absorber_item.age.min(other_age);
+                absorber_item.pickup_delay = absorber_item.pickup_delay.max(other_pickup_delay);
+                drop(absorber_item);
+
+                match remaining {
+                    Some(remaining) => ecs.get_mut::<ItemEntity>(absorbed).unwrap().stack = remaining,
+                    None => {
+                        merged.insert(absorbed);
+                    }
+                }
+
+            }
+
+        }
+
+        for &entity in &merged {
+            level.remove_entity(entity);
+            removed.push((entity, ItemRemoval::Merged));
+        }
+
+        // Pickup, items are inserted in the inventory of the first collector in range.
+        for (entity, pos, can_pickup) in positions {
+
+            if !can_pickup || merged.contains(&entity) {
+                continue;
+            }
+
+            for (collector, collector_pos) in &collectors {
+
+                if !is_in_range(&pos, collector_pos, PICKUP_RANGE_XZ, PICKUP_RANGE_MIN_Y, PICKUP_RANGE_MAX_Y) {
+                    continue;
+                }
+
+                let ecs = &level.entities.ecs;
+                let mut item = ecs.get_mut::<ItemEntity>(entity).unwrap();
+                let mut inventory = ecs.get_mut::<Inventory>(*collector).unwrap();
+                let count = item.stack.get_count();
+                let remaining = inventory.insert(item.stack.clone());
+                let picked = count - remaining.as_ref().map(ItemStack::get_count).unwrap_or(0);
+
+                if picked == 0 {
+                    continue;
+                }
+
+                let mut stack = item.stack.clone();
+                stack.set_count(picked);
+                world.event_tracker.push_event(ItemPickupEvent {
+                    level: Rc::clone(level_rc),
+                    entity,
+                    collector: *collector,
+                    stack
+                });
+
+                match remaining {
+                    Some(remaining) => item.stack = remaining,
+                    None => {
+                        drop(item);
+                        drop(inventory);
+                        level.remove_entity(entity);
+                        removed.push((entity, ItemRemoval::PickedUp));
+                        break;
+                    }
+                }
+
+            }
+
+        }
+
+        for (entity, reason) in removed {
+            world.event_tracker.push_event(ItemRemovedEvent {
+                level: Rc::clone(level_rc),
+                entity,
+                reason
+            });
+        }
+
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_core::world::level::LevelEnv;
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::source::{ChunkLoadRequest, NullLevelSource};
+
+    use crate::ext::VanillaLevelEnv;
+    use crate::entity::{ITEM, PLAYER};
+
+    use super::*;
+
+    mc_core::items!(TEST_ITEMS "test" [
+        PEBBLE "pebble",
+        PEARL "pearl" 16
+    ]);
+
+    fn spawn_item(level: &mut Level, pos: EntityPos, item: ItemEntity) -> Entity {
+        let entity = level.spawn_entity(&ITEM, pos).unwrap();
+        level.entities.ecs.insert_one(entity, item).unwrap();
+        entity
+    }
+
+    #[test]
+    fn item_entities() {
+
+        let env = LevelEnv::vanilla();
+        let height = ChunkHeight::new(0, 0);
+        let mut level = Level::new("overworld".to_string(), env.clone(), height, NullLevelSource);
+
+        let mut chunk = ChunkLoadRequest { env, height, cx: 0, cz: 0 }.build_chunk();
+        for x in 0..16 {
+            for z in 0..16 {
+                chunk.set_block(x, 0, z, STONE.get_default_state()).unwrap();
+            }
+        }
+        chunk.set_block(2, 1, 2, LAVA.get_default_state()).unwrap();
+        level.chunks.insert_chunk(chunk);
+
+        // Two stacks merging, the larger one absorbs the smaller one.
+        let pebble0 = spawn_item(&mut level, EntityPos::new(8.0, 1.0, 8.0), ItemEntity::new(ItemStack::with_item_count(&PEBBLE, 20)));
+        let pebble1 = spawn_item(&mut level, EntityPos::new(8.5, 1.0, 8.0), ItemEntity::new(ItemStack::with_item_count(&PEBBLE, 5)).with_age(100));
+        // A stack too far to be merged.
+        let pebble2 = spawn_item(&mut level, EntityPos::new(12.0, 1.0, 8.0), ItemEntity::new(ItemStack::with_item(&PEBBLE)));
+        let burnt = spawn_item(&mut level, EntityPos::new(2.5, 1.0, 2.5), ItemEntity::new(ItemStack::with_item(&PEBBLE)));
+        let despawned = spawn_item(&mut level, EntityPos::new(12.0, 1.0, 12.0), ItemEntity::new(ItemStack::with_item(&PEBBLE)).with_age(ITEM_DESPAWN_AGE - 1));
+
+        let mut world = World::new();
+        world.add_level(level);
+
+        system_item_entities(&mut world);
+
+        let removed: Vec<_> = world.event_tracker.poll_events::<ItemRemovedEvent>()
+            .map(|event| (event.entity, event.reason))
+            .collect();
+        assert_eq!(removed.len(), 3);
+        assert!(removed.contains(&(despawned, ItemRemoval::Despawned)));
+        assert!(removed.contains(&(burnt, ItemRemoval::Burnt)));
+        assert!(removed.contains(&(pebble1, ItemRemoval::Merged)));
+
+        {
+            let level = world.levels[0].borrow();
+            let item = level.entities.ecs.get::<ItemEntity>(pebble0).unwrap();
+            assert_eq!(item.get_stack().get_count(), 25);
+            assert_eq!(item.get_age(), 1);
+            assert!(level.entities.ecs.get::<ItemEntity>(pebble2).is_ok());
+        }
+
+        // A player with a small inventory collects pearls once the pickup delay is over.
+        let player = {
+            let mut level = world.levels[0].borrow_mut();
+            let player = level.spawn_entity(&PLAYER, EntityPos::new(8.0, 1.0, 12.0)).unwrap();
+            let mut inventory = Inventory::new(1);
+            inventory.insert(ItemStack::with_item_count(&PEARL, 10));
+            level.entities.ecs.insert_one(player, inventory).unwrap();
+            player
+        };
+
+        let pearl = {
+            let mut level = world.levels[0].borrow_mut();
+            spawn_item(&mut level, EntityPos::new(8.0, 1.0, 11.0), ItemEntity::new(ItemStack::with_item_count(&PEARL, 8)))
+        };
+
+        for _ in 0..ITEM_PICKUP_DELAY - 1 {
+            system_item_entities(&mut world);
+        }
+        assert_eq!(world.event_tracker.poll_events::<ItemPickupEvent>().count(), 0);
+
+        // The pearl is pulled toward the player and partially collected.
+        system_item_entities(&mut world);
+        let pickups: Vec<_> = world.event_tracker.poll_events::<ItemPickupEvent>()
+            .map(|event| (event.entity, event.collector, event.stack.get_count()))
+            .collect();
+        assert_eq!(pickups, vec![(pearl, player, 6)]);
+
+        let level = world.levels[0].borrow();
+        let inventory = level.entities.ecs.get::<Inventory>(player).unwrap();
+        assert_eq!(inventory.count_item(TEST_ITEMS[1]), PEARL.get_stack_size() as usize);
+        let item = level.entities.ecs.get::<ItemEntity>(pearl).unwrap();
+        assert_eq!(item.get_stack().get_count(), 2);
+        assert!(item.velocity.z > 0.0);
+
+    }
+
+}
@@ -0,0 +1,498 @@
+//! Natural lifecycle of mobs: hostile mobs are removed in peaceful difficulty, mobs far from
+//! players despawn unless they are persistent, and undead mobs burn in sunlight unless their
+//! helmet protects them. Only mobs whose entity type has registered `MobRules` are handled.
+//!
+//! The time and weather of levels are taken from the `SleepTracker` component, mobs don't
+//! burn in levels that are not tracked. Burning mobs are only set on fire, the damage of
+//! fire is not applied yet.
+
+use std::collections::HashMap;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use mc_core::world::level::{Level, BaseEntity};
+use mc_core::world::chunk::Light;
+use mc_core::entity::EntityType;
+use mc_core::pos::EntityPos;
+use mc_core::item::Item;
+use mc_core::rand::JavaRandom;
+use mc_core::util::{OpaquePtr, NbtExt};
+use hecs::Entity;
+
+use mc_runtime::world::World;
+
+use crate::block::fluid::VanillaFluids;
+use crate::util::{Difficulty, GameMode};
+use crate::weather::LevelWeather;
+use crate::weather::effects::is_raining_at;
+use crate::sleep::SleepTracker;
+use super::physics::get_block_pos;
+use super::*;
+
+
+/// Number of ticks mobs burning in sunlight are set on fire for.
+pub const SUN_BURN_TICKS: i16 = 160;
+/// Distance to the nearest player below which mobs never despawn.
+pub const NO_DESPAWN_DISTANCE: f64 = 32.0;
+/// Number of ticks without a player nearby before mobs can randomly despawn.
+const RANDOM_DESPAWN_DELAY: u32 = 600;
+/// Chance of random despawn for each tick, one in this value.
+const RANDOM_DESPAWN_CHANCE: i32 = 800;
+
+
+/// The category of a mob, defining if it's hostile and how far from players it despawns.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum MobCategory {
+    Monster,
+    Creature,
+    Ambient,
+    WaterCreature,
+    WaterAmbient,
+    Misc
+}
+
+impl MobCategory {
+
+    /// Return true if mobs of this category are removed in peaceful difficulty.
+    pub fn is_hostile(self) -> bool {
+        self == Self::Monster
+    }
+
+    /// Return the distance to the nearest player above which mobs of this category despawn
+    /// immediately, `None` if they never despawn.
+    pub fn get_despawn_distance(self) -> Option<f64> {
+        match self {
+            Self::Monster | Self::Ambient | Self::WaterCreature => Some(128.0),
+            Self::WaterAmbient => Some(64.0),
+            Self::Creature | Self::Misc => None
+        }
+    }
+
+}
+
+
+/// Lifecycle rules of an entity type.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MobRules {
+    category: MobCategory,
+    /// The height of the eyes of mobs burning in sunlight, the light is checked there.
+    sun_burn_eye_height: Option<f64>
+}
+
+impl MobRules {
+
+    pub const fn new(category: MobCategory) -> Self {
+        Self {
+            category,
+            sun_burn_eye_height: None
+        }
+    }
+
+    /// Make mobs burn in sunlight, the sunlight is checked at the given eyes height.
+    pub const fn with_sun_burn(mut self, eye_height: f64) -> Self {
+        self.sun_burn_eye_height = Some(eye_height);
+        self
+    }
+
+    #[inline]
+    pub fn get_category(&self) -> MobCategory {
+        self.category
+    }
+
+    #[inline]
+    pub fn get_sun_burn_eye_height(&self) -> Option<f64> {
+        self.sun_burn_eye_height
+    }
+
+}
+
+
+/// World component required by `system_mob_lifecycle`, with the rules of entity types and
+/// the maximum damage of the helmets that can be worn by mobs.
+pub struct MobLifecycle {
+    rules: HashMap<OpaquePtr<EntityType>, MobRules>,
+    max_damages: HashMap<OpaquePtr<Item>, u16>,
+    difficulty: Difficulty,
+    rand: JavaRandom
+}
+
+impl MobLifecycle {
+
+    pub fn new() -> Self {
+        Self {
+            rules: HashMap::new(),
+            max_damages: HashMap::new(),
+            difficulty: Difficulty::default(),
+            rand: JavaRandom::new_seeded()
+        }
+    }
+
+    pub fn with_difficulty(mut self, difficulty: Difficulty) -> Self {
+        self.difficulty = difficulty;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: i64) -> Self {
+        self.rand = JavaRandom::new(seed);
+        self
+    }
+
+    /// Register the lifecycle rules of an entity type, replacing any previous rules.
+    pub fn with_entity_type(mut self, entity_type: &'static EntityType, rules: MobRules) -> Self {
+        self.register(entity_type, rules);
+        self
+    }
+
+    /// Register the maximum damage of an item worn as helmet, overriding the durability of
+    /// the item. Helmets of items that are neither registered nor damageable protect mobs
+    /// from sunlight without being damaged.
+    pub fn with_damageable_item(mut self, item: &'static Item, max_damage: u16) -> Self {
+        self.max_damages.insert(OpaquePtr::new(item), max_damage);
+        self
+    }
+
+    pub fn register(&mut self, entity_type: &'static EntityType, rules: MobRules) {
+        self.rules.insert(OpaquePtr::new(entity_type), rules);
+    }
+
+    pub fn get_rules(&self, entity_type: &'static EntityType) -> Option<&MobRules> {
+        self.rules.get(&OpaquePtr::new(entity_type))
+    }
+
+    #[inline]
+    pub fn get_difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.difficulty = difficulty;
+    }
+
+    /// Damage the helmet of a mob burning in sunlight, the helmet breaks when its damage
+    /// reaches the maximum damage of its item. Return true if the mob had a helmet, in this
+    /// case it doesn't burn, even if its helmet broke.
+    fn damage_helmet(&mut self, mob: &mut MobEntity) -> bool {
+
+        let helmet = match mob.get_head_item_mut() {
+            Some(helmet) => helmet,
+            None => return false
+        };
+
+        let item = helmet.get_item();
+        let max_damage = self.max_damages.get(&OpaquePtr::new(item)).copied()
+            .or_else(|| item.is_damageable().then(|| item.get_max_damage()));
+
+        if let Some(max_damage) = max_damage {
+            let damage = helmet.get_tag().map_or(0, |tag| tag.get_i32_or("Damage", 0)) + self.rand.next_int_bounded(2);
+            if damage >= max_damage as i32 {
+                mob.set_head_item(None);
+            } else {
+                helmet.get_tag_mut().insert_i32("Damage", damage);
+            }
+        }
+
+        true
+
+    }
+
+}
+
+impl Default for MobLifecycle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// Event pushed by `system_mob_lifecycle` when a mob is removed from its level.
+pub struct MobDespawnEvent {
+    pub level: Rc<RefCell<Level>>,
+    pub entity: Entity,
+    pub reason: MobDespawn
+}
+
+
+/// The reason why a mob was removed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum MobDespawn {
+    /// The mob is hostile and the difficulty is peaceful.
+    Peaceful,
+    /// The nearest player is further than the despawn distance of the mob.
+    FarAway,
+    /// The mob has randomly despawned after a long time without players nearby.
+    NoAction
+}
+
+
+/// Convert a light level to a brightness between 0 and 1, like the overworld.
+fn get_brightness(light: u8) -> f32 {
+    let light = light as f32 / 15.0;
+    light / (4.0 - 3.0 * light)
+}
+
+/// Return true if a mob at the given position should burn in sunlight this tick, like
+/// vanilla this is random, more likely with a higher brightness. The sky darkening must be
+/// computed from the level time and weather. Mobs in water or rain don't burn.
+pub fn is_sun_burn_tick(level: &Level, pos: &EntityPos, eye_height: f64, sky_darken: u8, weather: &LevelWeather, rand: &mut JavaRandom) -> bool {
+
+    // Not the day.
+    if sky_darken >= 4 {
+        return false;
+    }
+
+    let eye_pos = get_block_pos(&EntityPos::new(pos.x, pos.y + eye_height, pos.z));
+    let (sky_light, block_light) = match level.chunks.get_chunk_at(eye_pos.x, eye_pos.z) {
+        Some(chunk) => (
+            chunk.get_light_at(eye_pos.x, eye_pos.y, eye_pos.z, Light::Sky).unwrap_or(0),
+            chunk.get_light_at(eye_pos.x, eye_pos.y, eye_pos.z, Light::Block).unwrap_or(0)
+        ),
+        None => return false
+    };
+
+    let brightness = get_brightness(sky_light.saturating_sub(sky_darken).max(block_light));
+    if brightness <= 0.5 || rand.next_float() * 30.0 >= (brightness - 0.4) * 2.0 || sky_light < 15 {
+        return false;
+    }
+
+    let feet_pos = get_block_pos(pos);
+    let in_water = level.get_fluid_at(feet_pos.x, feet_pos.y, feet_pos.z)
+        .is_ok_and(|fluid| fluid.is_water());
+
+    !in_water && !is_raining_at(level, weather, &feet_pos)
+
+}
+
+
+/// Call this function with a mutable reference to a World to register the `MobLifecycle`
+/// component which is required to run the system `system_mob_lifecycle`.
+pub fn register_mob_lifecycle(world: &mut World, lifecycle: MobLifecycle) {
+    world.insert_component(lifecycle);
+}
+
+/// A system removing hostile mobs in peaceful difficulty and despawning mobs far from the
+/// nearest player, players in spectator mode are ignored. Persistent mobs and mobs from
+/// buckets never despawn. Mobs burning in sunlight are set on fire or their helmet is
+/// damaged. A `MobDespawnEvent` is pushed for each removed mob.
+pub fn system_mob_lifecycle(world: &mut World) {
+
+    let mut lifecycle = match world.components.get_mut::<MobLifecycle>() {
+        Ok(lifecycle) => lifecycle,
+        Err(_) => return
+    };
+
+    let lifecycle = &mut *lifecycle;
+    let tracker = world.components.get::<SleepTracker>().ok();
+
+    for level_rc in &world.levels {
+
+        let mut level = level_rc.borrow_mut();
+
+        let level_sleep = tracker.as_ref().and_then(|tracker| tracker.get_level(level.get_id()));
+        let sky = level_sleep.map(|level_sleep| {
+            let weather = &level_sleep.weather;
+            (level_sleep.time.get_sky_darken(weather.get_rain_level(), weather.get_thunder_level()), weather.clone())
+        });
+
+        let players: Vec<EntityPos> = level.entities.ecs.query::<(&BaseEntity, &PlayerEntity)>()
+            .iter()
+            .filter(|(_, (_, player))| player.get_game_mode() != GameMode::Spectator)
+            .map(|(_, (base, _))| base.pos.clone())
+            .collect();
+
+        let mobs: Vec<_> = level.entities.ecs.query::<(&BaseEntity, &MobEntity, Option<&FromBucketEntity>)>()
+            .iter()
+            .filter_map(|(entity, (base, mob, from_bucket))| {
+                let rules = *lifecycle.get_rules(base.entity_type)?;
+                let persistent = mob.is_persistent() || from_bucket.is_some_and(FromBucketEntity::is_from_bucket);
+                Some((entity, base.pos.clone(), rules, persistent))
+            })
+            .collect();
+
+        for (entity, pos, rules, persistent) in mobs {
+
+            let category = rules.get_category();
+
+            let despawn = if lifecycle.difficulty == Difficulty::Peaceful && category.is_hostile() {
+                Some(MobDespawn::Peaceful)
+            } else {
+
+                let nearest_distance = players.iter()
+                    .map(|player| {
+                        let (dx, dy, dz) = (player.x - pos.x, player.y - pos.y, player.z - pos.z);
+                        dx * dx + dy * dy + dz * dz
+                    })
+                    .min_by(|a, b| a.total_cmp(b));
+
+                let mut mob = level.entities.ecs.get_mut::<MobEntity>(entity).unwrap();
+                let no_action_time = mob.get_no_action_time();
+                mob.set_no_action_time(no_action_time.saturating_add(1));
+
+                match (category.get_despawn_distance(), nearest_distance) {
+                    _ if persistent => {
+                        mob.set_no_action_time(0);
+                        None
+                    }
+                    (Some(despawn_distance), Some(distance)) => {
+                        let no_despawn_distance = NO_DESPAWN_DISTANCE * NO_DESPAWN_DISTANCE;
+                        if distance > despawn_distance * despawn_distance {
+                            Some(MobDespawn::FarAway)
+                        } else if no_action_time > RANDOM_DESPAWN_DELAY
+                            && lifecycle.rand.next_int_bounded(RANDOM_DESPAWN_CHANCE) == 0
+                            && distance > no_despawn_distance {
+                            Some(MobDespawn::NoAction)
+                        } else {
+                            if distance < no_despawn_distance {
+                                mob.set_no_action_time(0);
+                            }
+                            None
+                        }
+                    }
+                    _ => None
+                }
+
+            };
+
+            if let Some(reason) = despawn {
+                level.remove_entity(entity);
+                world.event_tracker.push_event(MobDespawnEvent {
+                    level: Rc::clone(level_rc),
+                    entity,
+                    reason
+                });
+                continue;
+            }
+
+            if let (Some(eye_height), Some((sky_darken, weather))) = (rules.get_sun_burn_eye_height(), &sky) {
+                if is_sun_burn_tick(&level, &pos, eye_height, *sky_darken, weather, &mut lifecycle.rand) {
+                    let protected = {
+                        let mut mob = level.entities.ecs.get_mut::<MobEntity>(entity).unwrap();
+                        lifecycle.damage_helmet(&mut mob)
+                    };
+                    if !protected {
+                        if let Ok(mut vanilla) = level.entities.ecs.get_mut::<VanillaEntity>(entity) {
+                            let fire_ticks = vanilla.get_remaining_fire_ticks().max(SUN_BURN_TICKS);
+                            vanilla.set_remaining_fire_ticks(fire_ticks);
+                        }
+                    }
+                }
+            }
+
+        }
+
+    }
+
+}
+
+
+pub(crate) fn register_vanilla_mobs(lifecycle: &mut MobLifecycle) {
+
+    const MONSTER: MobRules = MobRules::new(MobCategory::Monster);
+    const UNDEAD: MobRules = MONSTER.with_sun_burn(1.74);
+
+    for entity_type in [&ZOMBIE, &ZOMBIE_VILLAGER, &DROWNED, &SKELETON, &STRAY] {
+        lifecycle.register(entity_type, UNDEAD);
+    }
+
+    lifecycle.register(&PHANTOM, MONSTER.with_sun_burn(0.175));
+
+    for entity_type in [&HUSK, &WITHER_SKELETON, &CREEPER, &SPIDER, &CAVE_SPIDER, &ENDERMAN,
+                        &ENDERMITE, &SILVERFISH, &WITCH, &SLIME, &MAGMA_CUBE, &GHAST, &BLAZE] {
+        lifecycle.register(entity_type, MONSTER);
+    }
+
+    lifecycle.register(&BAT, MobRules::new(MobCategory::Ambient));
+
+    for entity_type in [&SQUID, &GLOW_SQUID, &AXOLOTL] {
+        lifecycle.register(entity_type, MobRules::new(MobCategory::WaterCreature));
+    }
+
+    for entity_type in [&COD, &SALMON, &PUFFERFISH, &TROPICAL_FISH] {
+        lifecycle.register(entity_type, MobRules::new(MobCategory::WaterAmbient));
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_core::world::level::LevelEnv;
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::source::{ChunkLoadRequest, NullLevelSource};
+    use mc_core::item::ItemStack;
+
+    use crate::ext::{VanillaLevelEnv, WithVanilla};
+    use crate::time::LevelTime;
+    use crate::block::STONE;
+
+    use super::*;
+
+    static TEST_HELMET: Item = Item::new("test:helmet", 1);
+
+    fn is_on_fire(world: &World, entity: Entity) -> bool {
+        world.levels[0].borrow().entities.ecs.get::<VanillaEntity>(entity).unwrap().is_on_fire()
+    }
+
+    #[test]
+    fn mob_lifecycle() {
+
+        let env = LevelEnv::vanilla();
+        let height = ChunkHeight::new(0, 0);
+        let mut level = Level::new("overworld".to_string(), env.clone(), height, NullLevelSource);
+        level.chunks.insert_chunk(ChunkLoadRequest { env: env.clone(), height, cx: 0, cz: 0 }.build_chunk());
+        level.chunks.insert_chunk(ChunkLoadRequest { env, height, cx: 10, cz: 0 }.build_chunk());
+
+        // Only the columns of the burning zombie and of the skeleton see the sky.
+        level.chunks.set_block_at(1, 0, 1, STONE.get_default_state()).unwrap();
+        {
+            let mut chunk = level.chunks.get_chunk_mut(0, 0).unwrap();
+            let sub_chunk = chunk.get_sub_chunk_mut(0).unwrap();
+            sub_chunk.set_light(1, 2, 1, Light::Sky, 15);
+            sub_chunk.set_light(1, 2, 3, Light::Sky, 15);
+        }
+
+        level.spawn_entity(&PLAYER, EntityPos::new(8.5, 1.0, 8.5)).unwrap();
+        let burning = level.spawn_entity(&ZOMBIE, EntityPos::new(1.5, 1.0, 1.5)).unwrap();
+        let shaded = level.spawn_entity(&ZOMBIE, EntityPos::new(5.5, 1.0, 5.5)).unwrap();
+        let husk = level.spawn_entity(&HUSK, EntityPos::new(1.5, 1.0, 1.5)).unwrap();
+        let helmeted = level.spawn_entity(&SKELETON, EntityPos::new(1.5, 1.0, 3.5)).unwrap();
+        level.entities.ecs.get_mut::<MobEntity>(helmeted).unwrap().set_head_item(Some(ItemStack::with_item(&TEST_HELMET)));
+        let far_away = level.spawn_entity(&CREEPER, EntityPos::new(168.5, 1.0, 8.5)).unwrap();
+        let persistent = level.spawn_entity(&CREEPER, EntityPos::new(168.5, 1.0, 8.5)).unwrap();
+        level.entities.ecs.get_mut::<MobEntity>(persistent).unwrap().set_persistent(true);
+        let cow = level.spawn_entity(&COW, EntityPos::new(168.5, 1.0, 8.5)).unwrap();
+
+        let mut world = World::new();
+        world.add_level(level);
+        world.insert_component(SleepTracker::new()
+            .with_level("overworld".to_string(), LevelTime::new().with_day_time(6000), LevelWeather::new()));
+        register_mob_lifecycle(&mut world, MobLifecycle::with_vanilla()
+            .with_seed(0)
+            .with_damageable_item(&TEST_HELMET, 1));
+
+        system_mob_lifecycle(&mut world);
+        {
+            let level = world.levels[0].borrow();
+            assert!(level.entities.ecs.contains(persistent) && level.entities.ecs.contains(cow));
+            assert!(!level.entities.ecs.contains(far_away));
+        }
+
+        for _ in 0..200 {
+            system_mob_lifecycle(&mut world);
+        }
+
+        assert!(is_on_fire(&world, burning));
+        assert!(!is_on_fire(&world, shaded));
+        assert!(!is_on_fire(&world, husk));
+        // The helmet protected the skeleton until it broke.
+        assert!(world.levels[0].borrow().entities.ecs.get::<MobEntity>(helmeted).unwrap().get_head_item().is_none());
+
+        world.components.get_mut::<MobLifecycle>().unwrap().set_difficulty(Difficulty::Peaceful);
+        system_mob_lifecycle(&mut world);
+        let level = world.levels[0].borrow();
+        assert!(!level.entities.ecs.contains(burning) && !level.entities.ecs.contains(persistent));
+        assert!(level.entities.ecs.contains(cow));
+
+    }
+
+}
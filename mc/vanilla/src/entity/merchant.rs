@@ -0,0 +1,599 @@
+//! Merchants and their trades, villagers and wandering traders have a `MerchantEntity` with
+//! the offers they sell. Players trade through a `MerchantTrade`, tracking the offer selected
+//! by the player and the 3 slots of the trading window: two payment slots and the result.
+//! Offers are not generated from the professions of villagers, they must be added to
+//! merchants.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use mc_core::world::level::Level;
+use mc_core::entity::SingleEntityCodec;
+use mc_core::entity_component;
+use mc_core::item::{ItemStack, Inventory};
+use mc_core::nbt::compound_tag_eq;
+use mc_core::util::NbtExt;
+use hecs::Entity;
+
+use nbt::CompoundTag;
+
+
+/// Slot of the first payment in the container of a trading window.
+pub const MERCHANT_PAYMENT_A_SLOT: usize = 0;
+/// Slot of the second payment in the container of a trading window.
+pub const MERCHANT_PAYMENT_B_SLOT: usize = 1;
+/// Slot of the result in the container of a trading window.
+pub const MERCHANT_RESULT_SLOT: usize = 2;
+/// Number of slots of the container of a trading window.
+pub const MERCHANT_CONTAINER_SIZE: usize = 3;
+
+/// Experience needed by villagers to reach levels 2 to 5, villagers start at level 1.
+pub const VILLAGER_LEVEL_XP: [u32; 4] = [10, 70, 150, 250];
+
+
+/// An offer sold by a merchant, one or two stacks are bought to get the sold stack. The
+/// price of the first stack increases with the demand and decreases with the special price,
+/// given for example to players who cured the villager.
+#[derive(Debug, Clone)]
+pub struct MerchantOffer {
+    buy: ItemStack,
+    buy_b: Option<ItemStack>,
+    sell: ItemStack,
+    uses: u32,
+    max_uses: u32,
+    /// True if trading this offer drops experience orbs for the player.
+    reward_xp: bool,
+    /// Experience given to the merchant when trading this offer.
+    xp: u32,
+    price_multiplier: f32,
+    special_price: i32,
+    demand: i32
+}
+
+impl MerchantOffer {
+
+    pub fn new(buy: ItemStack, sell: ItemStack, max_uses: u32, xp: u32, price_multiplier: f32) -> Self {
+        Self {
+            buy,
+            buy_b: None,
+            sell,
+            uses: 0,
+            max_uses,
+            reward_xp: true,
+            xp,
+            price_multiplier,
+            special_price: 0,
+            demand: 0
+        }
+    }
+
+    /// Require a second stack to buy this offer.
+    pub fn with_buy_b(mut self, buy_b: ItemStack) -> Self {
+        self.buy_b = Some(buy_b);
+        self
+    }
+
+    pub fn with_reward_xp(mut self, reward_xp: bool) -> Self {
+        self.reward_xp = reward_xp;
+        self
+    }
+
+    /// The first stack to buy, before adjusting its price.
+    #[inline]
+    pub fn get_base_cost_a(&self) -> &ItemStack {
+        &self.buy
+    }
+
+    /// The first stack to buy, its count is adjusted with the demand and the special price
+    /// and clamped between 1 and the stack size of the item.
+    pub fn get_cost_a(&self) -> ItemStack {
+        let count = self.buy.get_count() as i32;
+        let demand_bonus = ((count * self.demand) as f32 * self.price_multiplier).floor().max(0.0) as i32;
+        let max_count = self.buy.get_item().get_stack_size() as i32;
+        let mut cost = self.buy.clone();
+        cost.set_count((count + demand_bonus + self.special_price).clamp(1, max_count) as u16);
+        cost
+    }
+
+    #[inline]
+    pub fn get_cost_b(&self) -> Option<&ItemStack> {
+        self.buy_b.as_ref()
+    }
+
+    #[inline]
+    pub fn get_result(&self) -> &ItemStack {
+        &self.sell
+    }
+
+    #[inline]
+    pub fn get_uses(&self) -> u32 {
+        self.uses
+    }
+
+    #[inline]
+    pub fn get_max_uses(&self) -> u32 {
+        self.max_uses
+    }
+
+    #[inline]
+    pub fn is_out_of_stock(&self) -> bool {
+        self.uses >= self.max_uses
+    }
+
+    pub fn increase_uses(&mut self) {
+        self.uses += 1;
+    }
+
+    pub fn reset_uses(&mut self) {
+        self.uses = 0;
+    }
+
+    pub fn set_out_of_stock(&mut self) {
+        self.uses = self.max_uses;
+    }
+
+    #[inline]
+    pub fn is_reward_xp(&self) -> bool {
+        self.reward_xp
+    }
+
+    #[inline]
+    pub fn get_xp(&self) -> u32 {
+        self.xp
+    }
+
+    #[inline]
+    pub fn get_price_multiplier(&self) -> f32 {
+        self.price_multiplier
+    }
+
+    #[inline]
+    pub fn get_special_price(&self) -> i32 {
+        self.special_price
+    }
+
+    pub fn set_special_price(&mut self, special_price: i32) {
+        self.special_price = special_price;
+    }
+
+    #[inline]
+    pub fn get_demand(&self) -> i32 {
+        self.demand
+    }
+
+    /// Update the demand when restocking, the demand increases if the offer has been used
+    /// more than half of its maximum uses, and decreases otherwise.
+    pub fn update_demand(&mut self) {
+        self.demand += self.uses as i32 - (self.max_uses as i32 - self.uses as i32);
+    }
+
+    /// Return true if the given payment stacks are enough to buy this offer.
+    pub fn satisfied_by(&self, a: Option<&ItemStack>, b: Option<&ItemStack>) -> bool {
+        is_required_stack(a, Some(&self.get_cost_a())) && is_required_stack(b, self.buy_b.as_ref())
+    }
+
+    /// Remove the cost of this offer from the given payment stacks, return false if the
+    /// payment is not enough, in such case the stacks are left untouched.
+    pub fn take(&self, a: &mut Option<ItemStack>, b: &mut Option<ItemStack>) -> bool {
+        if !self.satisfied_by(a.as_ref(), b.as_ref()) {
+            return false;
+        }
+        shrink_stack(a, self.get_cost_a().get_count());
+        if let Some(cost_b) = &self.buy_b {
+            shrink_stack(b, cost_b.get_count());
+        }
+        true
+    }
+
+}
+
+
+/// Return true if the given stack has at least the count of the cost stack, with the same
+/// item. If the cost has a tag, the given stack must have the same tag.
+fn is_required_stack(given: Option<&ItemStack>, cost: Option<&ItemStack>) -> bool {
+    match (given, cost) {
+        (_, None) => true,
+        (None, Some(_)) => false,
+        (Some(given), Some(cost)) => {
+            std::ptr::eq(given.get_item(), cost.get_item())
+                && given.get_count() >= cost.get_count()
+                && match (cost.get_tag(), given.get_tag()) {
+                    (None, _) => true,
+                    (Some(cost_tag), Some(given_tag)) => compound_tag_eq(cost_tag, given_tag),
+                    (Some(_), None) => false
+                }
+        }
+    }
+}
+
+/// Remove the given count from a stack, the stack is emptied if not enough.
+fn shrink_stack(stack: &mut Option<ItemStack>, count: u16) {
+    if let Some(inner) = stack {
+        if inner.get_count() > count {
+            inner.set_count(inner.get_count() - count);
+        } else {
+            *stack = None;
+        }
+    }
+}
+
+
+/// An entity component for merchants, their offers and the player trading with them, a
+/// merchant trades with one player at a time.
+#[derive(Debug, Default)]
+pub struct MerchantEntity {
+    offers: Vec<MerchantOffer>,
+    /// Experience of villagers, gained by trading.
+    xp: u32,
+    trader: Option<Entity>
+}
+
+impl MerchantEntity {
+
+    pub fn get_offers(&self) -> &[MerchantOffer] {
+        &self.offers
+    }
+
+    pub fn get_offers_mut(&mut self) -> &mut Vec<MerchantOffer> {
+        &mut self.offers
+    }
+
+    pub fn add_offer(&mut self, offer: MerchantOffer) {
+        self.offers.push(offer);
+    }
+
+    #[inline]
+    pub fn get_xp(&self) -> u32 {
+        self.xp
+    }
+
+    pub fn set_xp(&mut self, xp: u32) {
+        self.xp = xp;
+    }
+
+    /// Level of villagers from 1 to 5, depending on their experience.
+    pub fn get_level(&self) -> u8 {
+        1 + VILLAGER_LEVEL_XP.iter().filter(|&&level_xp| self.xp >= level_xp).count() as u8
+    }
+
+    /// The player currently trading with this merchant.
+    #[inline]
+    pub fn get_trader(&self) -> Option<Entity> {
+        self.trader
+    }
+
+    pub fn set_trader(&mut self, trader: Option<Entity>) {
+        self.trader = trader;
+    }
+
+    /// Return true if the given player can start trading, the merchant must have offers and
+    /// must not trade with another player.
+    pub fn can_trade_with(&self, player: Entity) -> bool {
+        !self.offers.is_empty() && self.trader.is_none_or(|trader| trader == player)
+    }
+
+    /// Start a trade with the current offers of this merchant, `regular` is true for
+    /// villagers, showing their level and experience.
+    pub fn start_trade(&self, regular: bool) -> MerchantTrade {
+        let trade = MerchantTrade::new(self.offers.clone());
+        if regular {
+            trade.with_villager(self.get_level(), self.xp)
+        } else {
+            trade
+        }
+    }
+
+    /// Record a trade of the offer at the given index, its uses are increased and its
+    /// experience is given to the merchant. The traded offer is returned.
+    pub fn complete_trade(&mut self, index: usize) -> Option<&MerchantOffer> {
+        let offer = self.offers.get_mut(index)?;
+        offer.increase_uses();
+        self.xp += offer.xp;
+        Some(offer)
+    }
+
+    /// Restock all offers, their demand is updated before resetting their uses.
+    pub fn restock(&mut self) {
+        for offer in &mut self.offers {
+            offer.update_demand();
+            offer.reset_uses();
+        }
+    }
+
+}
+
+entity_component!(MerchantEntity: MerchantEntityCodec);
+
+pub struct MerchantEntityCodec;
+impl SingleEntityCodec for MerchantEntityCodec {
+
+    type Comp = MerchantEntity;
+
+    // Offers are not saved because stacks can't be decoded without an item registry.
+
+    fn encode(&self, src: &Self::Comp, dst: &mut CompoundTag) {
+        dst.insert_i32("Xp", src.xp as i32);
+    }
+
+    fn decode(&self, src: &CompoundTag) -> Self::Comp {
+        MerchantEntity {
+            xp: src.get_i32_or("Xp", 0).max(0) as u32,
+            ..MerchantEntity::default()
+        }
+    }
+
+}
+
+
+/// The trading state of a player with a merchant, the offers are copied from the merchant
+/// when the trade starts, and offers traded must be recorded with `complete_trade` on the
+/// merchant. The container is the inventory of `MERCHANT_CONTAINER_SIZE` slots of the
+/// trading window.
+#[derive(Debug, Clone)]
+pub struct MerchantTrade {
+    offers: Vec<MerchantOffer>,
+    /// The offer selected by the player, the first offer matching the payment is used if 0.
+    selected: usize,
+    /// The offer matching the payment, its result is in the result slot.
+    active: Option<usize>,
+    /// The level and experience of the villager, none for other merchants.
+    villager: Option<(u8, u32)>
+}
+
+impl MerchantTrade {
+
+    pub fn new(offers: Vec<MerchantOffer>) -> Self {
+        Self {
+            offers,
+            selected: 0,
+            active: None,
+            villager: None
+        }
+    }
+
+    /// Show the level and the experience of the villager in the trading window.
+    pub fn with_villager(mut self, level: u8, xp: u32) -> Self {
+        self.villager = Some((level, xp));
+        self
+    }
+
+    pub fn get_offers(&self) -> &[MerchantOffer] {
+        &self.offers
+    }
+
+    #[inline]
+    pub fn get_selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Index of the offer whose result is in the result slot.
+    #[inline]
+    pub fn get_active(&self) -> Option<usize> {
+        self.active
+    }
+
+    #[inline]
+    pub fn get_villager(&self) -> Option<(u8, u32)> {
+        self.villager
+    }
+
+    /// Return the offer bought with the given payment, only the selected offer is checked
+    /// if not the first one.
+    fn find_offer(&self, a: Option<&ItemStack>, b: Option<&ItemStack>) -> Option<usize> {
+        if self.selected > 0 && self.selected < self.offers.len() {
+            Some(self.selected).filter(|&index| self.offers[index].satisfied_by(a, b))
+        } else {
+            self.offers.iter().position(|offer| offer.satisfied_by(a, b))
+        }
+    }
+
+    /// Update the result slot from the payment slots, this must be called each time the
+    /// payment changes. Payment stacks can be given in any slot and in any order.
+    pub fn update_result(&mut self, container: &mut Inventory) {
+
+        let (a, b) = match container.get_stack(MERCHANT_PAYMENT_A_SLOT) {
+            Some(a) => (Some(a), container.get_stack(MERCHANT_PAYMENT_B_SLOT)),
+            None => (container.get_stack(MERCHANT_PAYMENT_B_SLOT), None)
+        };
+
+        self.active = if a.is_none() {
+            None
+        } else {
+            [(a, b), (b, a)].iter()
+                .filter_map(|&(a, b)| self.find_offer(a, b))
+                .find(|&index| !self.offers[index].is_out_of_stock())
+        };
+
+        let result = self.active.map(|index| self.offers[index].get_result().clone());
+        container.set_stack(MERCHANT_RESULT_SLOT, result);
+
+    }
+
+    /// Select an offer, the current payment is moved back to the player inventory and the
+    /// cost of the selected offer is moved from the player inventory to the payment slots.
+    /// The payment is kept if it doesn't fit in the player inventory.
+    pub fn select(&mut self, container: &mut Inventory, inventory: &mut Inventory, index: usize) {
+
+        if index >= self.offers.len() {
+            return;
+        }
+
+        self.selected = index;
+
+        for slot in [MERCHANT_PAYMENT_A_SLOT, MERCHANT_PAYMENT_B_SLOT] {
+            if let Some(stack) = container.take_stack(slot) {
+                if let Some(remaining) = inventory.insert(stack) {
+                    container.set_stack(slot, Some(remaining));
+                    self.update_result(container);
+                    return;
+                }
+            }
+        }
+
+        let offer = &self.offers[index];
+        move_payment(container, inventory, MERCHANT_PAYMENT_A_SLOT, Some(&offer.get_cost_a()));
+        move_payment(container, inventory, MERCHANT_PAYMENT_B_SLOT, offer.get_cost_b());
+        self.update_result(container);
+
+    }
+
+    /// Complete the trade of the active offer once its result has been taken by the player,
+    /// the cost is removed from the payment slots and the uses of the offer are increased.
+    /// The result slot is then updated and the index of the traded offer is returned.
+    pub fn complete(&mut self, container: &mut Inventory) -> Option<usize> {
+
+        let index = self.active?;
+        let offer = &mut self.offers[index];
+        let mut a = container.take_stack(MERCHANT_PAYMENT_A_SLOT);
+        let mut b = container.take_stack(MERCHANT_PAYMENT_B_SLOT);
+
+        let traded = offer.take(&mut a, &mut b) || offer.take(&mut b, &mut a);
+        if traded {
+            offer.increase_uses();
+            if let Some((_, xp)) = &mut self.villager {
+                *xp += offer.xp;
+            }
+        }
+
+        container.set_stack(MERCHANT_PAYMENT_A_SLOT, a);
+        container.set_stack(MERCHANT_PAYMENT_B_SLOT, b);
+        self.update_result(container);
+        traded.then_some(index)
+
+    }
+
+}
+
+
+/// Move stacks of the cost item from the player inventory to a payment slot, the main
+/// inventory is used before the hotbar, as shown in the window.
+fn move_payment(container: &mut Inventory, inventory: &mut Inventory, slot: usize, cost: Option<&ItemStack>) {
+
+    let cost = match cost {
+        Some(cost) => cost,
+        None => return
+    };
+
+    let stack_size = cost.get_item().get_stack_size();
+    let hotbar_end = inventory.get_size().min(9);
+
+    for index in (hotbar_end..inventory.get_size()).chain(0..hotbar_end) {
+        let count = container.get_stack(slot).map_or(0, ItemStack::get_count);
+        if count >= stack_size {
+            break;
+        }
+        match (inventory.get_stack(index), container.get_stack(slot)) {
+            (Some(stack), None) if std::ptr::eq(stack.get_item(), cost.get_item()) => {}
+            // Stacks with different tags are not merged.
+            (Some(stack), Some(payment)) if stack.is_stackable_with(payment) => {}
+            _ => continue
+        }
+        let mut stack = inventory.take_stack(index).unwrap();
+        let moved = (stack_size - count).min(stack.get_count());
+        let mut payment = stack.clone();
+        payment.set_count(count + moved);
+        container.set_stack(slot, Some(payment));
+        if stack.get_count() > moved {
+            stack.set_count(stack.get_count() - moved);
+            inventory.set_stack(index, Some(stack));
+        }
+    }
+
+}
+
+
+/// Event pushed when a player has traded an offer with a merchant.
+pub struct MerchantTradeEvent {
+    pub level: Rc<RefCell<Level>>,
+    pub merchant: Entity,
+    pub player: Entity,
+    pub offer: MerchantOffer
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::item::{EMERALD, WHEAT, BREAD, DIAMOND_SWORD, BOOK};
+
+    fn container_count(container: &Inventory, slot: usize) -> u16 {
+        container.get_stack(slot).map_or(0, ItemStack::get_count)
+    }
+
+    #[test]
+    fn merchant_offers() {
+
+        let mut offer = MerchantOffer::new(ItemStack::with_item_count(&WHEAT, 20), ItemStack::with_item(&EMERALD), 2, 2, 0.05);
+        assert_eq!(offer.get_cost_a().get_count(), 20);
+
+        // A high demand increases the price, the special price decreases it.
+        offer.increase_uses();
+        offer.increase_uses();
+        assert!(offer.is_out_of_stock());
+        offer.update_demand();
+        offer.reset_uses();
+        offer.update_demand();
+        assert_eq!(offer.get_demand(), 0);
+        offer.increase_uses();
+        offer.increase_uses();
+        offer.update_demand();
+        offer.update_demand();
+        assert_eq!(offer.get_demand(), 4);
+        assert_eq!(offer.get_cost_a().get_count(), 24);
+        offer.set_special_price(-30);
+        assert_eq!(offer.get_cost_a().get_count(), 1);
+
+        let mut merchant = MerchantEntity::default();
+        assert_eq!(merchant.get_level(), 1);
+        merchant.add_offer(MerchantOffer::new(ItemStack::with_item_count(&WHEAT, 20), ItemStack::with_item(&EMERALD), 12, 2, 0.05));
+        merchant.add_offer(MerchantOffer::new(ItemStack::with_item_count(&EMERALD, 1), ItemStack::with_item_count(&BREAD, 6), 16, 1, 0.05));
+        merchant.add_offer(MerchantOffer::new(ItemStack::with_item_count(&EMERALD, 5), ItemStack::with_item(&DIAMOND_SWORD), 3, 10, 0.2)
+            .with_buy_b(ItemStack::with_item(&BOOK)));
+
+        let mut inventory = Inventory::new(36);
+        inventory.set_stack(0, Some(ItemStack::with_item_count(&WHEAT, 30)));
+        inventory.set_stack(20, Some(ItemStack::with_item_count(&WHEAT, 20)));
+        inventory.set_stack(1, Some(ItemStack::with_item_count(&EMERALD, 6)));
+
+        let mut trade = merchant.start_trade(true);
+        let mut container = Inventory::new(MERCHANT_CONTAINER_SIZE);
+        assert_eq!(trade.get_villager(), Some((1, 0)));
+
+        // The payment is taken from the main inventory first.
+        trade.select(&mut container, &mut inventory, 0);
+        assert_eq!(container_count(&container, MERCHANT_PAYMENT_A_SLOT), 50);
+        assert!(inventory.get_stack(0).is_none() && inventory.get_stack(20).is_none());
+        assert_eq!(trade.get_active(), Some(0));
+        assert!(std::ptr::eq(container.get_stack(MERCHANT_RESULT_SLOT).unwrap().get_item(), &EMERALD));
+
+        assert_eq!(trade.complete(&mut container), Some(0));
+        assert_eq!(container_count(&container, MERCHANT_PAYMENT_A_SLOT), 30);
+        assert_eq!(trade.get_offers()[0].get_uses(), 1);
+        merchant.complete_trade(0);
+        assert_eq!(merchant.get_xp(), 2);
+
+        // Selecting another offer gives back the payment.
+        trade.select(&mut container, &mut inventory, 2);
+        assert_eq!(inventory.count_item(&WHEAT), 30);
+        assert_eq!(container_count(&container, MERCHANT_PAYMENT_A_SLOT), 6);
+        assert!(container.get_stack(MERCHANT_PAYMENT_B_SLOT).is_none());
+        assert!(container.get_stack(MERCHANT_RESULT_SLOT).is_none());
+
+        // Payment stacks can be given in any order.
+        container.set_stack(MERCHANT_PAYMENT_A_SLOT, Some(ItemStack::with_item(&BOOK)));
+        container.set_stack(MERCHANT_PAYMENT_B_SLOT, Some(ItemStack::with_item_count(&EMERALD, 6)));
+        trade.update_result(&mut container);
+        assert_eq!(trade.get_active(), Some(2));
+        assert_eq!(trade.complete(&mut container), Some(2));
+        assert!(container.get_stack(MERCHANT_PAYMENT_A_SLOT).is_none());
+        assert_eq!(container_count(&container, MERCHANT_PAYMENT_B_SLOT), 1);
+        assert!(container.get_stack(MERCHANT_RESULT_SLOT).is_none());
+        assert_eq!(trade.get_villager(), Some((1, 12)));
+
+        merchant.set_xp(70);
+        assert_eq!(merchant.get_level(), 3);
+
+    }
+
+}
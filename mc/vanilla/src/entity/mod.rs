@@ -3,6 +3,15 @@
 //! - https://minecraft.fandom.com/wiki/Entity_format
 
 pub mod ai;
+pub mod vehicle;
+pub mod item;
+pub mod explosive;
+pub mod lifecycle;
+pub mod breeding;
+pub mod merchant;
+pub mod decoration;
+
+pub(crate) mod physics;
 
 mod common;
 mod snow_golem;
@@ -43,6 +52,10 @@ pub use bee::*;
 mod player;
 pub use player::*;
 
+use crate::hunger::Hunger;
+use merchant::MerchantEntity;
+use decoration::{HangingEntity, ItemFrameEntity, PaintingEntity, ArmorStandEntity};
+
 
 macro_rules! vanilla_entities {
     (
@@ -50,7 +63,7 @@ macro_rules! vanilla_entities {
         $(,)?
     ) => {
         mc_core::entities!(pub VANILLA_ENTITIES "minecraft" [
-            PLAYER "player" [VanillaEntity, LivingEntity, PlayerEntity],
+            PLAYER "player" [VanillaEntity, LivingEntity, PlayerEntity, Hunger],
             $($entity_id $entity_name [VanillaEntity, NamedEntity $(,$comp_id)*]),*
         ]);
     }
@@ -70,31 +83,31 @@ vanilla_entities! {
     CREEPER "creeper" [MobEntity, LivingEntity, CreeperEntity],
     DOLPHIN "dolphin" [],
     DONKEY "donkey" [],
-    DROWNED "drowned" [],
+    DROWNED "drowned" [MobEntity, LivingEntity],
     ELDER_GUARDIAN "elder_guardian" [],
     ENDER_DRAGON "ender_dragon" [],
-    ENDERMAN "enderman" [],
-    ENDERMITE "endermite" [],
+    ENDERMAN "enderman" [MobEntity, LivingEntity],
+    ENDERMITE "endermite" [MobEntity, LivingEntity],
     EVOKER "evoker" [],
     FOX "fox" [],
-    GHAST "ghast" [],
+    GHAST "ghast" [MobEntity, LivingEntity],
     GIANT "giant" [],
     GLOW_SQUID "glow_squid" [MobEntity, LivingEntity, GlowSquidEntity],
     GOAT "goat" [],
     GUARDIAN "guardian" [],
     HOGLIN "hoglin" [],
     HORSE "horse" [],
-    HUSK "husk" [],
+    HUSK "husk" [MobEntity, LivingEntity],
     ILLUSIONER "illusioner" [],
     IRON_GOLEM "iron_golem" [MobEntity, LivingEntity, AngryEntity, IronGolemEntity],
     LLAMA "llama" [],
-    MAGMA_CUBE "magma_cube" [],
+    MAGMA_CUBE "magma_cube" [MobEntity, LivingEntity],
     MOOSHROOM "mooshroom" [],
     MULE "mule" [],
     OCELOT "ocelot" [],
     PANDA "panda" [],
     PARROT "parrot" [MobEntity, LivingEntity, TamableEntity, ParrotEntity],
-    PHANTOM "phantom" [],
+    PHANTOM "phantom" [MobEntity, LivingEntity],
     PIG "pig" [MobEntity, LivingEntity, BreedableEntity, PigEntity],
     PIGLIN "piglin" [],
     PIGLIN_BRUTE "piglin_brute" [],
@@ -106,30 +119,30 @@ vanilla_entities! {
     SALMON "salmon" [MobEntity, LivingEntity, FromBucketEntity, SalmonEntity],
     SHEEP "sheep" [MobEntity, LivingEntity, BreedableEntity, SheepEntity],
     SHULKER "shulker" [],
-    SILVERFISH "silverfish" [],
-    SKELETON "skeleton" [],
+    SILVERFISH "silverfish" [MobEntity, LivingEntity],
+    SKELETON "skeleton" [MobEntity, LivingEntity],
     SKELETON_HORSE "skeleton_horse" [],
     SLIME "slime" [MobEntity, LivingEntity, SlimeEntity],
     SNOW_GOLEM "snow_golem" [MobEntity, LivingEntity, SnowGolemEntity],
-    SPIDER "spider" [],
+    SPIDER "spider" [MobEntity, LivingEntity],
     STRIDER "strider" [],
     SQUID "squid" [MobEntity, LivingEntity, SquidEntity],
-    STRAY "stray" [],
+    STRAY "stray" [MobEntity, LivingEntity],
     TRADER_LLAMA "trader_llama" [],
     TROPICAL_FISH "tropical_fish" [MobEntity, LivingEntity, FromBucketEntity, TropicalFishEntity],
     TURTLE "turtle" [MobEntity, LivingEntity, BreedableEntity, TurtleEntity],
     VEX "vex" [],
-    VILLAGER "villager" [],
+    VILLAGER "villager" [MerchantEntity],
     VINDICATOR "vindicator" [],
-    WANDERING_TRADER "wandering_trader" [],
-    WITCH "witch" [],
+    WANDERING_TRADER "wandering_trader" [MerchantEntity],
+    WITCH "witch" [MobEntity, LivingEntity],
     WITHER "wither" [],
-    WITHER_SKELETON "wither_skeleton" [],
+    WITHER_SKELETON "wither_skeleton" [MobEntity, LivingEntity],
     WOLF "wolf" [MobEntity, LivingEntity, TamableEntity, AngryEntity, BreedableEntity, PetEntity, WolfEntity],
     ZOGLIN "zoglin" [],
-    ZOMBIE "zombie" [],
+    ZOMBIE "zombie" [MobEntity, LivingEntity],
     ZOMBIE_HORSE "zombie_horse" [],
-    ZOMBIE_VILLAGER "zombie_villager" [],
+    ZOMBIE_VILLAGER "zombie_villager" [MobEntity, LivingEntity],
     ZOMBIFIED_PIGLIN "zombified_piglin" [],
     // Projectiles entities //
     ARROW "arrow" [],
@@ -164,13 +177,15 @@ vanilla_entities! {
     TNT "tnt" [],
     // Other entities //
     AREA_EFFECT_CLOUD "area_effect_cloud" [],
-    ARMOR_STAND "armor_stand" [],
+    ARMOR_STAND "armor_stand" [LivingEntity, ArmorStandEntity],
     END_CRYSTAL "end_crystal" [],
     EVOKER_FANGS "evoker_fangs" [],
     EYE_OF_ENDER "eye_of_ender" [],
     FISHING_BOBBER "fishing_bobber" [],
-    ITEM_FRAME "item_frame" [],
+    GLOW_ITEM_FRAME "glow_item_frame" [HangingEntity, ItemFrameEntity],
+    ITEM_FRAME "item_frame" [HangingEntity, ItemFrameEntity],
+    LEASH_KNOT "leash_knot" [HangingEntity],
     LIGHTNING_BOLT "lightning_bolt" [],
     MARKER "marker" [],
-    PAINTING "painting" [],
+    PAINTING "painting" [HangingEntity, PaintingEntity],
 }
@@ -0,0 +1,64 @@
+//! Simple physics shared by vanilla entities moved by runtime systems, entities are handled
+//! as points colliding with blocks that are not tagged as non blocking.
+
+use mc_core::world::level::Level;
+use mc_core::block::BlockState;
+use mc_core::pos::{EntityPos, BlockPos};
+
+use crate::block::material::TAG_NON_BLOCKING;
+
+
+#[inline]
+pub(crate) fn get_block_pos(pos: &EntityPos) -> BlockPos {
+    BlockPos::new(pos.x.floor() as i32, pos.y.floor() as i32, pos.z.floor() as i32)
+}
+
+/// Get a block state, `None` if the chunk is not loaded.
+#[inline]
+pub(crate) fn get_block(level: &Level, pos: &BlockPos) -> Option<&'static BlockState> {
+    level.chunks.get_chunk_at_block(pos.clone())?.get_block_at(pos.x, pos.y, pos.z).ok()
+}
+
+/// Return true if the block stops entities, unloaded blocks are colliding.
+pub(crate) fn is_colliding(level: &Level, pos: &BlockPos) -> bool {
+    match get_block(level, pos) {
+        Some(state) => !level.get_env().blocks.has_block_tag(state.get_block(), &TAG_NON_BLOCKING),
+        None => true
+    }
+}
+
+/// Move a position by the given velocity, axes blocked by a colliding block are cancelled.
+/// Return true if the position landed on a block.
+pub(crate) fn move_with_collisions(level: &Level, pos: &mut EntityPos, velocity: &mut EntityPos) -> bool {
+
+    let mut on_ground = false;
+
+    let y = pos.y + velocity.y;
+    let block = BlockPos::new(pos.x.floor() as i32, y.floor() as i32, pos.z.floor() as i32);
+    if velocity.y <= 0.0 && is_colliding(level, &block) {
+        pos.y = (block.y + 1) as f64;
+        velocity.y = 0.0;
+        on_ground = true;
+    } else if velocity.y > 0.0 && is_colliding(level, &block) {
+        velocity.y = 0.0;
+    } else {
+        pos.y = y;
+    }
+
+    let x = pos.x + velocity.x;
+    if is_colliding(level, &BlockPos::new(x.floor() as i32, pos.y.floor() as i32, pos.z.floor() as i32)) {
+        velocity.x = 0.0;
+    } else {
+        pos.x = x;
+    }
+
+    let z = pos.z + velocity.z;
+    if is_colliding(level, &BlockPos::new(pos.x.floor() as i32, pos.y.floor() as i32, z.floor() as i32)) {
+        velocity.z = 0.0;
+    } else {
+        pos.z = z;
+    }
+
+    on_ground
+
+}
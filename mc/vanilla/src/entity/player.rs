@@ -1,21 +1,191 @@
 use mc_core::entity::SingleEntityCodec;
 use mc_core::entity_component;
+use mc_core::block::{Block, GlobalBlocks};
+use mc_core::item::{ItemStack, Inventory};
 
+use crate::item::adventure;
 use crate::util::GameMode;
 
 use nbt::CompoundTag;
 
 
+/// Abilities of a player, mostly given by its game mode.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerAbilities {
+    /// The player doesn't take damage.
+    pub invulnerable: bool,
+    pub flying: bool,
+    /// The player is allowed to fly.
+    pub may_fly: bool,
+    /// Blocks are broken instantly and items are not consumed.
+    pub instant_build: bool,
+    /// The player can break and place blocks.
+    pub may_build: bool,
+    pub fly_speed: f32,
+    pub walk_speed: f32
+}
+
+impl PlayerAbilities {
+
+    pub const DEFAULT_FLY_SPEED: f32 = 0.05;
+    pub const DEFAULT_WALK_SPEED: f32 = 0.1;
+
+    /// Create the abilities of a player in survival mode.
+    pub fn new() -> Self {
+        Self {
+            invulnerable: false,
+            flying: false,
+            may_fly: false,
+            instant_build: false,
+            may_build: true,
+            fly_speed: Self::DEFAULT_FLY_SPEED,
+            walk_speed: Self::DEFAULT_WALK_SPEED
+        }
+    }
+
+    /// Update the abilities for the given game mode, speeds are kept.
+    pub fn update_for_game_mode(&mut self, game_mode: GameMode) {
+        match game_mode {
+            GameMode::Creative => {
+                self.may_fly = true;
+                self.instant_build = true;
+                self.invulnerable = true;
+            }
+            GameMode::Spectator => {
+                self.may_fly = true;
+                self.instant_build = false;
+                self.invulnerable = true;
+                self.flying = true;
+            }
+            GameMode::Survival | GameMode::Adventure => {
+                self.may_fly = false;
+                self.instant_build = false;
+                self.invulnerable = false;
+                self.flying = false;
+            }
+        }
+        self.may_build = !game_mode.is_block_placing_restricted();
+    }
+
+}
+
+impl Default for PlayerAbilities {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
 #[derive(Debug, Default)]
 pub struct PlayerEntity {
     /// The game mode of the player.
     game_mode: GameMode,
     /// The previous game mode of the player.
     previous_game_mode: Option<GameMode>,
+    /// The abilities of the player, updated when the game mode changes.
+    abilities: PlayerAbilities,
+    /// The selected hotbar slot, from 0 to 8.
+    selected_slot: u8,
     /// The Score displayed upon death.
     score: u32,
 }
 
+impl PlayerEntity {
+
+    pub fn get_game_mode(&self) -> GameMode {
+        self.game_mode
+    }
+
+    pub fn get_previous_game_mode(&self) -> Option<GameMode> {
+        self.previous_game_mode
+    }
+
+    /// Change the game mode of the player and update its abilities, nothing is done if the
+    /// player is already in this game mode.
+    pub fn set_game_mode(&mut self, game_mode: GameMode) {
+        if game_mode != self.game_mode {
+            self.previous_game_mode = Some(self.game_mode);
+            self.game_mode = game_mode;
+            self.abilities.update_for_game_mode(game_mode);
+        }
+    }
+
+    pub fn get_abilities(&self) -> &PlayerAbilities {
+        &self.abilities
+    }
+
+    pub fn get_abilities_mut(&mut self) -> &mut PlayerAbilities {
+        &mut self.abilities
+    }
+
+    pub fn get_selected_slot(&self) -> u8 {
+        self.selected_slot
+    }
+
+    /// Set the selected hotbar slot, nothing is done if the slot is not in the hotbar.
+    pub fn set_selected_slot(&mut self, slot: u8) {
+        if slot < 9 {
+            self.selected_slot = slot;
+        }
+    }
+
+    /// Return true if the player can break blocks.
+    pub fn can_break_blocks(&self) -> bool {
+        self.abilities.may_build
+    }
+
+    /// Return true if the player can break the given block while holding the given stack,
+    /// players in adventure mode need a stack with the block in its `CanDestroy` tag.
+    pub fn can_break_block(&self, held: Option<&ItemStack>, blocks: &GlobalBlocks, block: &'static Block) -> bool {
+        match self.game_mode {
+            GameMode::Spectator => false,
+            GameMode::Adventure => held.is_some_and(|stack| adventure::can_destroy(stack, blocks, block)),
+            _ => self.abilities.may_build
+        }
+    }
+
+    /// Return true if the player can place the given stack against the given block, players
+    /// in adventure mode need the block to be in the `CanPlaceOn` tag of the stack.
+    pub fn can_place_block_on(&self, stack: &ItemStack, blocks: &GlobalBlocks, against: &'static Block) -> bool {
+        match self.game_mode {
+            GameMode::Spectator => false,
+            GameMode::Adventure => adventure::can_place_on(stack, blocks, against),
+            _ => self.abilities.may_build
+        }
+    }
+
+    /// Return true if the player can interact with blocks, entities and windows, spectators
+    /// can only look at them.
+    pub fn can_interact(&self) -> bool {
+        self.game_mode != GameMode::Spectator
+    }
+
+    /// Consume items of a stack used by the player, items are infinite for players that
+    /// build instantly. Return false if the slot has less items than required, in which case
+    /// nothing is consumed.
+    pub fn consume_item(&self, inventory: &mut Inventory, index: usize, count: u16) -> bool {
+        let available = inventory.get_stack(index).map_or(0, ItemStack::get_count);
+        if available < count {
+            false
+        } else if self.abilities.instant_build {
+            true
+        } else {
+            if available == count {
+                inventory.set_stack(index, None);
+            } else if let Some(mut stack) = inventory.take_stack(index) {
+                stack.set_count(available - count);
+                inventory.set_stack(index, Some(stack));
+            }
+            true
+        }
+    }
+
+    pub fn get_score(&self) -> u32 {
+        self.score
+    }
+
+}
+
 entity_component!(PlayerEntity: PlayerEntityCodec);
 
 pub struct PlayerEntityCodec;
@@ -28,15 +198,97 @@ impl SingleEntityCodec for PlayerEntityCodec {
         if let Some(previous_game_mode) = src.previous_game_mode {
             dst.insert_i8("previousPlayerGameType", previous_game_mode.get_id() as i8);
         }
+        let mut abilities = CompoundTag::new();
+        abilities.insert_bool("invulnerable", src.abilities.invulnerable);
+        abilities.insert_bool("flying", src.abilities.flying);
+        abilities.insert_bool("mayfly", src.abilities.may_fly);
+        abilities.insert_bool("instabuild", src.abilities.instant_build);
+        abilities.insert_bool("mayBuild", src.abilities.may_build);
+        abilities.insert_f32("flySpeed", src.abilities.fly_speed);
+        abilities.insert_f32("walkSpeed", src.abilities.walk_speed);
+        dst.insert_compound_tag("abilities", abilities);
+        dst.insert_i32("SelectedItemSlot", src.selected_slot as i32);
         dst.insert_i32("Score", src.score as i32);
     }
 
     fn decode(&self, src: &CompoundTag) -> Self::Comp {
+        let game_mode = GameMode::from_id(src.get_i8("playerGameType").unwrap_or(0) as u8);
+        let abilities = match src.get_compound_tag("abilities") {
+            Ok(tag) => PlayerAbilities {
+                invulnerable: tag.get_bool("invulnerable").unwrap_or(false),
+                flying: tag.get_bool("flying").unwrap_or(false),
+                may_fly: tag.get_bool("mayfly").unwrap_or(false),
+                instant_build: tag.get_bool("instabuild").unwrap_or(false),
+                may_build: tag.get_bool("mayBuild").unwrap_or(true),
+                fly_speed: tag.get_f32("flySpeed").unwrap_or(PlayerAbilities::DEFAULT_FLY_SPEED),
+                walk_speed: tag.get_f32("walkSpeed").unwrap_or(PlayerAbilities::DEFAULT_WALK_SPEED)
+            },
+            Err(_) => {
+                let mut abilities = PlayerAbilities::new();
+                abilities.update_for_game_mode(game_mode);
+                abilities
+            }
+        };
         PlayerEntity {
-            game_mode: GameMode::from_id(src.get_i8("playerGameType").unwrap_or(0) as u8),
+            game_mode,
             previous_game_mode: src.get_i8("previousPlayerGameType").ok().map(|id| GameMode::from_id(id as u8)),
+            abilities,
+            selected_slot: src.get_i32("SelectedItemSlot").ok().filter(|slot| (0..9).contains(slot)).unwrap_or(0) as u8,
             score: src.get_i32("Score").unwrap_or(0) as u32
         }
     }
 
-}
\ No newline at end of file
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    mc_core::items!(TEST_ITEMS "test" [
+        APPLE "apple"
+    ]);
+
+    #[test]
+    fn game_mode_abilities() {
+
+        let mut player = PlayerEntity::default();
+        assert!(player.can_break_blocks());
+        assert!(!player.get_abilities().may_fly);
+
+        player.set_game_mode(GameMode::Spectator);
+        assert_eq!(player.get_previous_game_mode(), Some(GameMode::Survival));
+        assert!(player.get_abilities().flying);
+        assert!(!player.can_break_blocks());
+        assert!(!player.can_interact());
+
+        player.set_game_mode(GameMode::Creative);
+        assert!(player.get_abilities().instant_build && player.get_abilities().flying);
+
+        let mut tag = CompoundTag::new();
+        PlayerEntityCodec.encode(&player, &mut tag);
+        let decoded = PlayerEntityCodec.decode(&tag);
+        assert_eq!(decoded.get_game_mode(), GameMode::Creative);
+        assert_eq!(decoded.get_previous_game_mode(), Some(GameMode::Spectator));
+        assert_eq!(decoded.get_abilities(), player.get_abilities());
+
+        // Players saved without abilities get the abilities of their game mode.
+        let mut tag = CompoundTag::new();
+        tag.insert_i8("playerGameType", GameMode::Creative.get_id() as i8);
+        assert!(PlayerEntityCodec.decode(&tag).get_abilities().may_fly);
+
+        // Items are infinite in creative mode.
+        let mut inventory = Inventory::new(9);
+        inventory.set_stack(0, Some(ItemStack::new(&APPLE, 3, None)));
+        assert!(player.consume_item(&mut inventory, 0, 2));
+        assert_eq!(inventory.get_stack(0).unwrap().get_count(), 3);
+        player.set_game_mode(GameMode::Survival);
+        assert!(player.consume_item(&mut inventory, 0, 2));
+        assert!(!player.consume_item(&mut inventory, 0, 2));
+        assert!(player.consume_item(&mut inventory, 0, 1));
+        assert!(inventory.get_stack(0).is_none());
+
+    }
+
+}
@@ -7,6 +7,14 @@ pub struct SlimeEntity {
     size: u8
 }
 
+impl SlimeEntity {
+
+    pub fn get_size(&self) -> u8 {
+        self.size
+    }
+
+}
+
 entity_component!(SlimeEntity: SlimeEntityCodec);
 
 pub struct SlimeEntityCodec;
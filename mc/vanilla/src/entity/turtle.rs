@@ -14,6 +14,23 @@ pub struct TurtleEntity {
     travel_pos: BlockPos
 }
 
+impl TurtleEntity {
+
+    pub fn has_egg(&self) -> bool {
+        self.has_egg
+    }
+
+    /// The beach where the turtle hatched, where it comes back to lay its eggs.
+    pub fn get_home_pos(&self) -> &BlockPos {
+        &self.home_pos
+    }
+
+    pub fn set_home_pos(&mut self, home_pos: BlockPos) {
+        self.home_pos = home_pos;
+    }
+
+}
+
 entity_component!(TurtleEntity: TurtleEntityCodec);
 
 pub struct TurtleEntityCodec;
@@ -0,0 +1,401 @@
+//! Vehicles movement, minecarts follow rails and are accelerated by powered rails, boats
+//! float on water. Vehicles are moved by their velocity every tick by `system_vehicles`,
+//! other systems can change the velocity, for example when a player pushes a vehicle.
+
+use std::rc::Rc;
+
+use mc_core::world::level::{Level, BaseEntity};
+use mc_core::pos::{EntityPos, BlockPos};
+
+use mc_runtime::event::EntityMovedEvent;
+use mc_runtime::world::World;
+
+use crate::block::*;
+use crate::block::fluid::get_fluid_state;
+
+use super::physics::{get_block_pos, get_block, is_colliding, move_with_collisions};
+
+
+/// Velocity removed from the Y axis every tick for falling vehicles.
+const GRAVITY: f64 = 0.04;
+/// Horizontal drag of vehicles in the air.
+const AIR_DRAG: f64 = 0.95;
+/// Horizontal drag of vehicles on the ground.
+const GROUND_DRAG: f64 = 0.5;
+
+/// Drag of minecarts on rails, vanilla value for empty minecarts.
+const MINECART_RAIL_DRAG: f64 = 0.96;
+/// Maximum speed of minecarts on rails, in blocks per tick.
+const MINECART_MAX_SPEED: f64 = 0.4;
+/// Speed removed from minecarts going up an ascending rail.
+const MINECART_SLOPE_ACCELERATION: f64 = 0.0078125;
+/// Speed added to moving minecarts on powered rails.
+const POWERED_RAIL_ACCELERATION: f64 = 0.06;
+
+/// Velocity added to the Y axis of boats under water.
+const BOAT_BUOYANCY: f64 = 0.04;
+/// Horizontal drag of boats on water.
+const BOAT_WATER_DRAG: f64 = 0.9;
+
+
+/// An entity component for minecarts moved by `system_vehicles`.
+#[derive(Debug, Default)]
+pub struct Minecart {
+    /// Velocity in blocks per tick.
+    pub velocity: EntityPos
+}
+
+impl Minecart {
+    pub fn new(velocity: EntityPos) -> Self {
+        Self { velocity }
+    }
+}
+
+
+/// An entity component for boats moved by `system_vehicles`.
+#[derive(Debug, Default)]
+pub struct Boat {
+    /// Velocity in blocks per tick.
+    pub velocity: EntityPos
+}
+
+impl Boat {
+    pub fn new(velocity: EntityPos) -> Self {
+        Self { velocity }
+    }
+}
+
+
+/// A rail block under a minecart.
+struct Rail {
+    pos: BlockPos,
+    shape: RailShape,
+    /// Some if the rail is a powered rail, with its powered state.
+    powered: Option<bool>
+}
+
+impl Rail {
+
+    /// Find the rail a minecart at the given position is running on, the rail can be in the
+    /// block of the position or the block below, for descending rails.
+    fn find(level: &Level, pos: &EntityPos) -> Option<Self> {
+        let pos = get_block_pos(pos);
+        Self::from_block(level, pos.clone()).or_else(|| Self::from_block(level, pos.below(1)))
+    }
+
+    /// Find the next rail of a minecart leaving this rail at the given position, the next
+    /// rail can be at the same height, above or below this rail.
+    fn find_next(&self, level: &Level, pos: &EntityPos) -> Option<Self> {
+        let pos = BlockPos::new(pos.x.floor() as i32, self.pos.y, pos.z.floor() as i32);
+        Self::from_block(level, pos.clone())
+            .or_else(|| Self::from_block(level, pos.above(1)))
+            .or_else(|| Self::from_block(level, pos.below(1)))
+    }
+
+    fn from_block(level: &Level, pos: BlockPos) -> Option<Self> {
+        let state = get_block(level, &pos)?;
+        if state.is_block(&RAIL) {
+            Some(Self { pos, shape: state.get(&PROP_RAIL_SHAPE)?, powered: None })
+        } else if state.is_block(&POWERED_RAIL) {
+            Some(Self { pos, shape: state.get(&PROP_RAIL_SHAPE_SPECIAL)?, powered: state.get(&PROP_POWERED) })
+        } else if state.is_block(&DETECTOR_RAIL) || state.is_block(&ACTIVATOR_RAIL) {
+            Some(Self { pos, shape: state.get(&PROP_RAIL_SHAPE_SPECIAL)?, powered: None })
+        } else {
+            None
+        }
+    }
+
+    /// Return the horizontal offsets of both exits of this rail.
+    fn get_exits(&self) -> ((i32, i32), (i32, i32)) {
+        match self.shape {
+            RailShape::EastWest | RailShape::AscendingEast | RailShape::AscendingWest => ((-1, 0), (1, 0)),
+            RailShape::NorthSouth | RailShape::AscendingNorth | RailShape::AscendingSouth => ((0, -1), (0, 1)),
+            RailShape::NorthEast => ((0, -1), (1, 0)),
+            RailShape::NorthWest => ((0, -1), (-1, 0)),
+            RailShape::SouthEast => ((0, 1), (1, 0)),
+            RailShape::SouthWest => ((0, 1), (-1, 0))
+        }
+    }
+
+    /// Return the horizontal direction going up for ascending rails.
+    fn get_ascending(&self) -> Option<(i32, i32)> {
+        match self.shape {
+            RailShape::AscendingEast => Some((1, 0)),
+            RailShape::AscendingWest => Some((-1, 0)),
+            RailShape::AscendingNorth => Some((0, -1)),
+            RailShape::AscendingSouth => Some((0, 1)),
+            _ => None
+        }
+    }
+
+    /// Return the normalized horizontal direction from the first exit to the second one.
+    fn get_direction(&self) -> (f64, f64) {
+        let ((x0, z0), (x1, z1)) = self.get_exits();
+        let (dx, dz) = ((x1 - x0) as f64, (z1 - z0) as f64);
+        let len = (dx * dx + dz * dz).sqrt();
+        (dx / len, dz / len)
+    }
+
+    /// Move the given position on the line between both exits of this rail, its height
+    /// follows the slope of ascending rails.
+    fn snap(&self, pos: &mut EntityPos) {
+
+        let ((x0, z0), (x1, z1)) = self.get_exits();
+        let (cx, cz) = (self.pos.x as f64 + 0.5, self.pos.z as f64 + 0.5);
+        let (p0x, p0z) = (cx + x0 as f64 * 0.5, cz + z0 as f64 * 0.5);
+        let (sx, sz) = ((x1 - x0) as f64 * 0.5, (z1 - z0) as f64 * 0.5);
+
+        let t = ((pos.x - p0x) * sx + (pos.z - p0z) * sz) / (sx * sx + sz * sz);
+        let t = t.clamp(0.0, 1.0);
+        pos.x = p0x + sx * t;
+        pos.z = p0z + sz * t;
+
+        pos.y = self.pos.y as f64 + match self.get_ascending() {
+            Some((ux, uz)) => (pos.x - cx) * ux as f64 + (pos.z - cz) * uz as f64 + 0.5,
+            None => 0.0
+        };
+
+    }
+
+}
+
+
+/// Tick the movement of a minecart, following the rail under it if any.
+fn tick_minecart(level: &Level, pos: &mut EntityPos, velocity: &mut EntityPos) {
+
+    let rail = match Rail::find(level, pos) {
+        Some(rail) => rail,
+        None => {
+            velocity.y -= GRAVITY;
+            let drag = if move_with_collisions(level, pos, velocity) { GROUND_DRAG } else { AIR_DRAG };
+            velocity.x *= drag;
+            velocity.z *= drag;
+            return;
+        }
+    };
+
+    // The horizontal speed is kept and redirected along the rail, like vanilla on curves.
+    let (dx, dz) = rail.get_direction();
+    let mut speed = (velocity.x * velocity.x + velocity.z * velocity.z).sqrt();
+    if velocity.x * dx + velocity.z * dz < 0.0 {
+        speed = -speed;
+    }
+
+    if let Some((ux, uz)) = rail.get_ascending() {
+        speed -= MINECART_SLOPE_ACCELERATION * (ux as f64 * dx + uz as f64 * dz);
+    }
+
+    match rail.powered {
+        Some(true) if speed.abs() > 0.01 => speed += POWERED_RAIL_ACCELERATION * speed.signum(),
+        Some(false) if speed.abs() < 0.03 => speed = 0.0,
+        Some(false) => speed *= 0.5,
+        _ => {}
+    }
+
+    speed = (speed * MINECART_RAIL_DRAG).clamp(-MINECART_MAX_SPEED, MINECART_MAX_SPEED);
+    velocity.x = dx * speed;
+    velocity.y = 0.0;
+    velocity.z = dz * speed;
+
+    rail.snap(pos);
+    let mut next = EntityPos::new(pos.x + velocity.x, pos.y, pos.z + velocity.z);
+
+    match rail.find_next(level, &next) {
+        Some(next_rail) => {
+            next_rail.snap(&mut next);
+            *pos = next;
+        }
+        None => {
+            // Leaving the rails, the minecart falls on the next tick if there is no block.
+            if is_colliding(level, &get_block_pos(&next)) {
+                velocity.x = 0.0;
+                velocity.z = 0.0;
+            } else {
+                *pos = next;
+            }
+        }
+    }
+
+}
+
+
+/// Tick the movement of a boat, boats under water go up to the surface and then float.
+fn tick_boat(level: &Level, pos: &mut EntityPos, velocity: &mut EntityPos) {
+
+    let is_water = |pos: &BlockPos| get_block(level, pos).is_some_and(|state| get_fluid_state(state).is_water());
+
+    let mut block = get_block_pos(pos);
+
+    if is_water(&block) {
+
+        while is_water(&block.above(1)) {
+            block = block.above(1);
+        }
+
+        let surface = (block.y + 1) as f64;
+        velocity.y += BOAT_BUOYANCY;
+        velocity.x *= BOAT_WATER_DRAG;
+        velocity.z *= BOAT_WATER_DRAG;
+        move_with_collisions(level, pos, velocity);
+
+        if pos.y >= surface {
+            pos.y = surface;
+            velocity.y = 0.0;
+        }
+
+    } else if pos.y == block.y as f64 && is_water(&block.below(1)) {
+        velocity.y = 0.0;
+        velocity.x *= BOAT_WATER_DRAG;
+        velocity.z *= BOAT_WATER_DRAG;
+        move_with_collisions(level, pos, velocity);
+    } else {
+        velocity.y -= GRAVITY;
+        let drag = if move_with_collisions(level, pos, velocity) { GROUND_DRAG } else { AIR_DRAG };
+        velocity.x *= drag;
+        velocity.z *= drag;
+    }
+
+}
+
+
+/// A system that moves all minecarts and boats, an `EntityMovedEvent` is pushed for each
+/// moved vehicle.
+pub fn system_vehicles(world: &mut World) {
+
+    for level_rc in &world.levels {
+
+        let mut level = level_rc.borrow_mut();
+
+        let minecarts: Vec<_> = level.entities.ecs.query::<(&BaseEntity, &Minecart)>()
+            .iter()
+            .map(|(entity, (base_entity, minecart))| (entity, base_entity.pos.clone(), minecart.velocity.clone(), true))
+            .collect();
+
+        let boats: Vec<_> = level.entities.ecs.query::<(&BaseEntity, &Boat)>()
+            .iter()
+            .map(|(entity, (base_entity, boat))| (entity, base_entity.pos.clone(), boat.velocity.clone(), false))
+            .collect();
+
+        for (entity, from, mut velocity, minecart) in minecarts.into_iter().chain(boats) {
+
+            let mut to = from.clone();
+            if minecart {
+                tick_minecart(&level, &mut to, &mut velocity);
+                level.entities.ecs.get_mut::<Minecart>(entity).unwrap().velocity = velocity;
+            } else {
+                tick_boat(&level, &mut to, &mut velocity);
+                level.entities.ecs.get_mut::<Boat>(entity).unwrap().velocity = velocity;
+            }
+
+            if to != from {
+                level.move_entity(entity, to.clone());
+                world.event_tracker.push_event(EntityMovedEvent {
+                    level: Rc::clone(level_rc),
+                    entity,
+                    from,
+                    to,
+                    look: None,
+                    on_ground: false
+                });
+            }
+
+        }
+
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_core::world::level::LevelEnv;
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::source::{ChunkLoadRequest, NullLevelSource};
+
+    use crate::ext::VanillaLevelEnv;
+    use crate::entity::{MINECART, BOAT};
+
+    use super::*;
+
+    #[test]
+    fn vehicles() {
+
+        let env = LevelEnv::vanilla();
+        let height = ChunkHeight::new(0, 3);
+        let mut level = Level::new("overworld".to_string(), env.clone(), height, NullLevelSource);
+
+        let mut chunk = ChunkLoadRequest { env, height, cx: 0, cz: 0 }.build_chunk();
+        for x in 0..16 {
+            for z in 0..16 {
+                chunk.set_block(x, 0, z, STONE.get_default_state()).unwrap();
+            }
+        }
+
+        // A rail line going east with powered rails and a slope up at its end.
+        let rail = RAIL.get_default_state().with(&PROP_RAIL_SHAPE, RailShape::EastWest).unwrap();
+        let powered_rail = POWERED_RAIL.get_default_state()
+            .with(&PROP_RAIL_SHAPE_SPECIAL, RailShape::EastWest).unwrap()
+            .with(&PROP_POWERED, true).unwrap();
+        for x in 0..11 {
+            chunk.set_block(x, 1, 8, if x >= 6 { powered_rail } else { rail }).unwrap();
+        }
+        chunk.set_block(11, 1, 8, RAIL.get_default_state().with(&PROP_RAIL_SHAPE, RailShape::AscendingEast).unwrap()).unwrap();
+        chunk.set_block(12, 1, 8, STONE.get_default_state()).unwrap();
+        chunk.set_block(12, 2, 8, rail).unwrap();
+        chunk.set_block(13, 1, 8, STONE.get_default_state()).unwrap();
+        chunk.set_block(13, 2, 8, rail).unwrap();
+        chunk.set_block(14, 2, 8, STONE.get_default_state()).unwrap();
+
+        // A pool of water three blocks deep.
+        for x in 0..8 {
+            for z in 0..4 {
+                for y in 1..4 {
+                    chunk.set_block(x, y, z, WATER.get_default_state()).unwrap();
+                }
+            }
+        }
+
+        level.chunks.insert_chunk(chunk);
+
+        let minecart = level.spawn_entity(&MINECART, EntityPos::new(0.5, 1.0, 8.5)).unwrap();
+        let boat = level.spawn_entity(&BOAT, EntityPos::new(2.5, 6.0, 2.5)).unwrap();
+        level.entities.ecs.insert_one(minecart, Minecart::new(EntityPos::new(0.4, 0.0, 0.0))).unwrap();
+        level.entities.ecs.insert_one(boat, Boat::new(EntityPos::new(0.2, 0.0, 0.0))).unwrap();
+
+        let mut world = World::new();
+        world.add_level(level);
+
+        let get_pos = |world: &World, entity| {
+            world.levels[0].borrow().entities.ecs.get::<BaseEntity>(entity).unwrap().pos.clone()
+        };
+
+        let mut last_speed = 0.4;
+        let mut accelerated = false;
+        for _ in 0..60 {
+            system_vehicles(&mut world);
+            let pos = get_pos(&world, minecart);
+            assert_eq!(pos.z, 8.5);
+            if pos.x < 11.0 {
+                assert_eq!(pos.y, 1.0);
+            }
+            let speed = world.levels[0].borrow().entities.ecs.get::<Minecart>(minecart).unwrap().velocity.x;
+            accelerated |= speed > last_speed;
+            last_speed = speed;
+        }
+
+        // The minecart has been accelerated by powered rails and climbed the slope.
+        assert!(accelerated);
+        let pos = get_pos(&world, minecart);
+        assert!(pos.x > 12.0, "{:?}", pos);
+        assert_eq!(pos.y, 2.0);
+
+        // The boat fell in water and floats at its surface.
+        let pos = get_pos(&world, boat);
+        assert_eq!(pos.y, 4.0);
+        assert!(pos.x > 2.5 && pos.x < 8.0);
+        assert!(world.levels[0].borrow().entities.ecs.get::<Boat>(boat).unwrap().velocity.x < 0.01);
+
+    }
+
+}
@@ -0,0 +1,511 @@
+//! Vanilla explosions, blocks are destroyed by rays cast from the center of the explosion and
+//! weakened by the explosion resistance of the blocks they cross. Entities near the explosion
+//! are damaged and knocked back depending on their distance and on their exposure.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use mc_core::world::level::{Level, BaseEntity};
+use mc_core::block::BlockState;
+use mc_core::pos::{EntityPos, BlockPos};
+use mc_core::rand::JavaRandom;
+use hecs::Entity;
+
+use crate::block::material::BlockMaterials;
+use crate::block::*;
+use crate::entity::{LivingEntity, CreeperEntity};
+use crate::entity::item::ItemEntity;
+use crate::entity::vehicle::{Minecart, Boat};
+use crate::entity::explosive::PrimedTnt;
+use crate::entity::physics::{get_block_pos, is_colliding};
+
+
+/// Power of primed TNT explosions.
+pub const TNT_EXPLOSION_POWER: f32 = 4.0;
+/// Power of beds explosions in dimensions where beds don't work.
+pub const BED_EXPLOSION_POWER: f32 = 5.0;
+/// Power of end crystals explosions.
+pub const END_CRYSTAL_EXPLOSION_POWER: f32 = 6.0;
+
+/// Number of rays along each edge of the cube of rays cast from the explosion.
+const RAY_GRID_SIZE: i32 = 16;
+/// Distance between two points of a ray.
+const RAY_STEP: f64 = 0.3;
+/// Intensity lost by rays at each step, in addition to blocks resistance.
+const RAY_STEP_ATTENUATION: f32 = 0.22500001;
+/// Distance between two points of the rays used to compute the exposure of entities.
+const EXPOSURE_STEP: f64 = 0.1;
+/// Size of the box sampled to compute the exposure of entities, like players and most mobs.
+const EXPOSURE_WIDTH: f64 = 0.6;
+const EXPOSURE_HEIGHT: f64 = 1.8;
+
+
+/// What an explosion does to the blocks it reaches.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ExplosionInteraction {
+    /// Blocks are not changed, like explosions when mob griefing is disabled.
+    None,
+    /// Blocks are destroyed and all of them drop their items.
+    Break,
+    /// Blocks are destroyed and each of them drops its items with a probability inversely
+    /// proportional to the power of the explosion.
+    Destroy
+}
+
+
+/// An explosion at a position, with a power and how it interacts with blocks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explosion {
+    pos: EntityPos,
+    power: f32,
+    fire: bool,
+    interaction: ExplosionInteraction,
+    /// The entity causing the explosion, it's not damaged by the explosion.
+    source: Option<Entity>
+}
+
+impl Explosion {
+
+    pub fn new(pos: EntityPos, power: f32) -> Self {
+        Self {
+            pos,
+            power,
+            fire: false,
+            interaction: ExplosionInteraction::Destroy,
+            source: None
+        }
+    }
+
+    /// Construct the explosion of a creeper, charged creepers have a doubled power.
+    pub fn from_creeper(pos: EntityPos, creeper: &CreeperEntity) -> Self {
+        let power = creeper.get_explosion_radius() as f32 * if creeper.is_powered() { 2.0 } else { 1.0 };
+        Self::new(pos, power)
+    }
+
+    /// Set if the explosion randomly places fire on blocks it destroys.
+    pub fn with_fire(mut self, fire: bool) -> Self {
+        self.fire = fire;
+        self
+    }
+
+    pub fn with_interaction(mut self, interaction: ExplosionInteraction) -> Self {
+        self.interaction = interaction;
+        self
+    }
+
+    pub fn with_source(mut self, source: Entity) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    #[inline]
+    pub fn get_pos(&self) -> &EntityPos {
+        &self.pos
+    }
+
+    #[inline]
+    pub fn get_power(&self) -> f32 {
+        self.power
+    }
+
+    #[inline]
+    pub fn has_fire(&self) -> bool {
+        self.fire
+    }
+
+    #[inline]
+    pub fn get_interaction(&self) -> ExplosionInteraction {
+        self.interaction
+    }
+
+    #[inline]
+    pub fn get_source(&self) -> Option<Entity> {
+        self.source
+    }
+
+    /// Compute the positions of all blocks reached by the rays of this explosion, including
+    /// air blocks, sorted by coordinates. Rays are stopped by unloaded chunks.
+    pub fn compute_blocks(&self, level: &Level, materials: &BlockMaterials, rand: &mut JavaRandom) -> Vec<BlockPos> {
+
+        let mut blocks = Vec::new();
+        let max = RAY_GRID_SIZE - 1;
+
+        for i in 0..RAY_GRID_SIZE {
+            for j in 0..RAY_GRID_SIZE {
+                for k in 0..RAY_GRID_SIZE {
+
+                    if i != 0 && i != max && j != 0 && j != max && k != 0 && k != max {
+                        continue;
+                    }
+
+                    let mut dx = i as f64 / max as f64 * 2.0 - 1.0;
+                    let mut dy = j as f64 / max as f64 * 2.0 - 1.0;
+                    let mut dz = k as f64 / max as f64 * 2.0 - 1.0;
+                    let norm = (dx * dx + dy * dy + dz * dz).sqrt();
+                    dx /= norm;
+                    dy /= norm;
+                    dz /= norm;
+
+                    let mut intensity = self.power * (0.7 + rand.next_float() * 0.6);
+                    let mut pos = self.pos.clone();
+
+                    while intensity > 0.0 {
+
+                        let block_pos = get_block_pos(&pos);
+                        let state = match level.chunks.get_block_at(block_pos.x, block_pos.y, block_pos.z) {
+                            Ok(state) => state,
+                            Err(_) => break
+                        };
+
+                        if !is_air(state) {
+                            let resistance = materials.get_explosion_resistance(state.get_block());
+                            intensity -= (resistance + 0.3) * 0.3;
+                        }
+
+                        if intensity > 0.0 {
+                            blocks.push(block_pos);
+                        }
+
+                        pos.x += dx * RAY_STEP;
+                        pos.y += dy * RAY_STEP;
+                        pos.z += dz * RAY_STEP;
+                        intensity -= RAY_STEP_ATTENUATION;
+
+                    }
+
+                }
+            }
+        }
+
+        blocks.sort_unstable_by_key(|pos| (pos.x, pos.y, pos.z));
+        blocks.dedup();
+        blocks
+
+    }
+
+    /// Compute the damage and knockback of every entity in the radius of this explosion,
+    /// which is twice its power. The source of the explosion is not hit.
+    pub fn compute_hits(&self, level: &Level) -> Vec<ExplosionHit> {
+
+        let radius = self.power as f64 * 2.0;
+        let chunk_radius = (radius as i32 >> 4) + 1;
+        let (cx, cz) = ((self.pos.x.floor() as i32) >> 4, (self.pos.z.floor() as i32) >> 4);
+        let mut hits = Vec::new();
+
+        for ncx in (cx - chunk_radius)..=(cx + chunk_radius) {
+            for ncz in (cz - chunk_radius)..=(cz + chunk_radius) {
+
+                let chunk = match level.chunks.get_chunk(ncx, ncz) {
+                    Some(chunk) => chunk,
+                    None => continue
+                };
+
+                for entity in chunk.iter_entities() {
+
+                    if Some(entity) == self.source {
+                        continue;
+                    }
+
+                    let pos = match level.entities.ecs.get::<BaseEntity>(entity) {
+                        Ok(base_entity) => base_entity.pos.clone(),
+                        Err(_) => continue
+                    };
+
+                    let (dx, dy, dz) = (pos.x - self.pos.x, pos.y - self.pos.y, pos.z - self.pos.z);
+                    let distance = (dx * dx + dy * dy + dz * dz).sqrt();
+                    if distance == 0.0 || distance > radius {
+                        continue;
+                    }
+
+                    let impact = (1.0 - distance / radius) * self.compute_exposure(level, &pos);
+                    hits.push(ExplosionHit {
+                        entity,
+                        damage: ((impact * impact + impact) / 2.0 * 7.0 * radius + 1.0).floor() as f32,
+                        knockback: EntityPos::new(dx / distance * impact, dy / distance * impact, dz / distance * impact)
+                    });
+
+                }
+
+            }
+        }
+
+        hits
+
+    }
+
+    /// Compute the proportion of points of an entity box visible from the center of the
+    /// explosion, between 0 and 1, points are blocked by colliding blocks.
+    fn compute_exposure(&self, level: &Level, pos: &EntityPos) -> f64 {
+
+        let step_xz = 1.0 / (EXPOSURE_WIDTH * 2.0 + 1.0);
+        let step_y = 1.0 / (EXPOSURE_HEIGHT * 2.0 + 1.0);
+        // Offset centering the sampled points in the box.
+        let offset = (1.0 - (1.0 / step_xz).floor() * step_xz) / 2.0;
+
+        let min = EntityPos::new(pos.x - EXPOSURE_WIDTH / 2.0 + offset, pos.y, pos.z - EXPOSURE_WIDTH / 2.0 + offset);
+        let (mut visible, mut total) = (0, 0);
+
+        let mut fx = 0.0;
+        while fx <= 1.0 {
+            let mut fy = 0.0;
+            while fy <= 1.0 {
+                let mut fz = 0.0;
+                while fz <= 1.0 {
+                    let point = EntityPos::new(
+                        min.x + fx * EXPOSURE_WIDTH,
+                        min.y + fy * EXPOSURE_HEIGHT,
+                        min.z + fz * EXPOSURE_WIDTH
+                    );
+                    if !self.is_ray_blocked(level, &point) {
+                        visible += 1;
+                    }
+                    total += 1;
+                    fz += step_xz;
+                }
+                fy += step_y;
+            }
+            fx += step_xz;
+        }
+
+        visible as f64 / total as f64
+
+    }
+
+    /// Return true if a colliding block is between the given point and the explosion center.
+    fn is_ray_blocked(&self, level: &Level, point: &EntityPos) -> bool {
+
+        let (dx, dy, dz) = (self.pos.x - point.x, self.pos.y - point.y, self.pos.z - point.z);
+        let distance = (dx * dx + dy * dy + dz * dz).sqrt();
+        let steps = (distance / EXPOSURE_STEP).ceil() as i32;
+        let mut last_block = None;
+
+        for step in 0..=steps {
+            let factor = if steps == 0 { 0.0 } else { step as f64 / steps as f64 };
+            let block = get_block_pos(&EntityPos::new(point.x + dx * factor, point.y + dy * factor, point.z + dz * factor));
+            if last_block.as_ref() != Some(&block) {
+                if is_colliding(level, &block) {
+                    return true;
+                }
+                last_block = Some(block);
+            }
+        }
+
+        false
+
+    }
+
+    /// Compute and apply this explosion to a level, blocks are destroyed and fire is placed,
+    /// living entities are damaged and item entities and vehicles are knocked back. Other
+    /// entities are only present in the hits of the result.
+    pub fn explode(&self, level: &mut Level, materials: &BlockMaterials, rand: &mut JavaRandom) -> ExplosionResult {
+
+        let blocks = self.compute_blocks(level, materials, rand);
+        let mut result = ExplosionResult {
+            hits: self.compute_hits(level),
+            ..ExplosionResult::default()
+        };
+
+        for hit in &result.hits {
+            let ecs = &level.entities.ecs;
+            if let Ok(mut living) = ecs.get_mut::<LivingEntity>(hit.entity) {
+                let health = living.get_health();
+                living.set_health((health - hit.damage).max(0.0));
+            }
+            if let Ok(mut item) = ecs.get_mut::<ItemEntity>(hit.entity) {
+                add_knockback(&mut item.velocity, &hit.knockback);
+            } else if let Ok(mut minecart) = ecs.get_mut::<Minecart>(hit.entity) {
+                add_knockback(&mut minecart.velocity, &hit.knockback);
+            } else if let Ok(mut boat) = ecs.get_mut::<Boat>(hit.entity) {
+                add_knockback(&mut boat.velocity, &hit.knockback);
+            } else if let Ok(mut tnt) = ecs.get_mut::<PrimedTnt>(hit.entity) {
+                add_knockback(&mut tnt.velocity, &hit.knockback);
+            }
+        }
+
+        if self.interaction != ExplosionInteraction::None {
+            for pos in &blocks {
+                let state = level.chunks.get_block_at(pos.x, pos.y, pos.z).unwrap();
+                if is_air(state) {
+                    continue;
+                }
+                let drops = match self.interaction {
+                    ExplosionInteraction::Destroy => rand.next_float() <= 1.0 / self.power,
+                    _ => true
+                };
+                level.chunks.set_block_at(pos.x, pos.y, pos.z, AIR.get_default_state()).unwrap();
+                if drops {
+                    result.drops.push((pos.clone(), state));
+                }
+                result.destroyed.push((pos.clone(), state));
+            }
+        }
+
+        if self.fire {
+            for pos in &blocks {
+                if rand.next_int_bounded(3) != 0 {
+                    continue;
+                }
+                let below = pos.below(1);
+                let can_burn = matches!(level.chunks.get_block_at(pos.x, pos.y, pos.z), Ok(state) if is_air(state))
+                    && matches!(level.chunks.get_block_at(below.x, below.y, below.z), Ok(state) if materials.get_opacity(state.get_block()) == 15);
+                if can_burn {
+                    level.chunks.set_block_at(pos.x, pos.y, pos.z, FIRE.get_default_state()).unwrap();
+                    result.fires.push(pos.clone());
+                }
+            }
+        }
+
+        result
+
+    }
+
+}
+
+
+#[inline]
+fn add_knockback(velocity: &mut EntityPos, knockback: &EntityPos) {
+    velocity.x += knockback.x;
+    velocity.y += knockback.y;
+    velocity.z += knockback.z;
+}
+
+#[inline]
+fn is_air(state: &BlockState) -> bool {
+    state.is_block(&AIR) || state.is_block(&CAVE_AIR) || state.is_block(&VOID_AIR)
+}
+
+
+/// An entity hit by an explosion.
+#[derive(Debug, Clone)]
+pub struct ExplosionHit {
+    pub entity: Entity,
+    pub damage: f32,
+    /// Velocity added to the entity.
+    pub knockback: EntityPos
+}
+
+
+/// The changes made by an explosion to a level.
+#[derive(Debug, Default)]
+pub struct ExplosionResult {
+    /// Destroyed blocks with their previous state.
+    pub destroyed: Vec<(BlockPos, &'static BlockState)>,
+    /// Destroyed blocks that must drop their items.
+    pub drops: Vec<(BlockPos, &'static BlockState)>,
+    /// Positions where fire has been placed.
+    pub fires: Vec<BlockPos>,
+    pub hits: Vec<ExplosionHit>
+}
+
+
+/// Event pushed by systems creating explosions once the explosion has been applied.
+pub struct ExplosionEvent {
+    pub level: Rc<RefCell<Level>>,
+    pub explosion: Explosion,
+    pub result: ExplosionResult
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_core::world::level::LevelEnv;
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::source::{ChunkLoadRequest, NullLevelSource};
+
+    use crate::block::material::VANILLA_BLOCK_MATERIALS;
+    use crate::ext::VanillaLevelEnv;
+    use crate::entity::{PIG, ITEM};
+
+    use super::*;
+
+    mc_core::items!(TEST_ITEMS "test" [
+        PEBBLE "pebble"
+    ]);
+
+    fn new_level() -> Level {
+
+        let env = LevelEnv::vanilla();
+        let height = ChunkHeight::new(0, 0);
+        let level = Level::new("overworld".to_string(), env.clone(), height, NullLevelSource);
+
+        for cx in -1..=1 {
+            for cz in -1..=1 {
+                let mut chunk = ChunkLoadRequest { env: env.clone(), height, cx, cz }.build_chunk();
+                for x in 0..16 {
+                    for z in 0..16 {
+                        chunk.set_block(x, 0, z, BEDROCK.get_default_state()).unwrap();
+                        for y in 1..4 {
+                            chunk.set_block(x, y, z, STONE.get_default_state()).unwrap();
+                        }
+                    }
+                }
+                level.chunks.insert_chunk(chunk);
+            }
+        }
+
+        // An obsidian wall next to the explosion.
+        for y in 4..8 {
+            for z in 4..13 {
+                level.chunks.set_block_at(11, y, z, OBSIDIAN.get_default_state()).unwrap();
+            }
+        }
+
+        level
+
+    }
+
+    #[test]
+    fn explosion() {
+
+        let materials = &*VANILLA_BLOCK_MATERIALS;
+        let mut rand = JavaRandom::new(0);
+        let mut level = new_level();
+
+        let pig = level.spawn_entity(&PIG, EntityPos::new(6.5, 4.0, 8.5)).unwrap();
+        level.entities.ecs.get_mut::<LivingEntity>(pig).unwrap().set_health(10.0);
+        let item = level.spawn_entity(&ITEM, EntityPos::new(10.0, 4.0, 8.5)).unwrap();
+        level.entities.ecs.insert_one(item, ItemEntity::new(mc_core::item::ItemStack::with_item(TEST_ITEMS[0]))).unwrap();
+        // Behind the obsidian wall.
+        let hidden_pig = level.spawn_entity(&PIG, EntityPos::new(12.5, 4.0, 8.5)).unwrap();
+        let far_pig = level.spawn_entity(&PIG, EntityPos::new(20.5, 4.0, 8.5)).unwrap();
+
+        let explosion = Explosion::new(EntityPos::new(8.5, 4.5, 8.5), TNT_EXPLOSION_POWER)
+            .with_interaction(ExplosionInteraction::Break)
+            .with_fire(true);
+        let result = explosion.explode(&mut level, materials, &mut rand);
+
+        // Blocks below the explosion are destroyed, but not bedrock and obsidian.
+        assert!(result.destroyed.iter().any(|(pos, _)| *pos == BlockPos::new(8, 3, 8)));
+        assert!(result.destroyed.iter().all(|(_, state)| state.is_block(&STONE)));
+        assert_eq!(result.drops.len(), result.destroyed.len());
+        assert!(!level.chunks.get_block_at(8, 3, 8).unwrap().is_block(&STONE));
+        assert!(level.chunks.get_block_at(8, 0, 8).unwrap().is_block(&BEDROCK));
+        assert!(level.chunks.get_block_at(11, 4, 8).unwrap().is_block(&OBSIDIAN));
+        assert!(result.fires.iter().all(|pos| level.chunks.get_block_at(pos.x, pos.y, pos.z).unwrap().is_block(&FIRE)));
+
+        let get_hit = |entity| result.hits.iter().find(|hit| hit.entity == entity);
+        let pig_hit = get_hit(pig).unwrap();
+        assert!(pig_hit.damage > 1.0 && pig_hit.knockback.x < 0.0);
+        assert_eq!(level.entities.ecs.get::<LivingEntity>(pig).unwrap().get_health(), (10.0 - pig_hit.damage).max(0.0));
+        assert!(level.entities.ecs.get::<ItemEntity>(item).unwrap().velocity.x > 0.0);
+        assert_eq!(get_hit(hidden_pig).unwrap().damage, 1.0);
+        assert!(get_hit(far_pig).is_none());
+
+        // Explosions without block interaction still hit entities.
+        let mut level = new_level();
+        let pig = level.spawn_entity(&PIG, EntityPos::new(6.5, 4.0, 8.5)).unwrap();
+        let explosion = Explosion::new(EntityPos::new(8.5, 4.5, 8.5), TNT_EXPLOSION_POWER)
+            .with_interaction(ExplosionInteraction::None);
+        let result = explosion.explode(&mut level, materials, &mut rand);
+        assert!(result.destroyed.is_empty());
+        assert_eq!(result.hits.len(), 1);
+        assert_eq!(result.hits[0].entity, pig);
+
+        // The source is never hit.
+        let explosion = Explosion::new(EntityPos::new(8.5, 4.5, 8.5), TNT_EXPLOSION_POWER).with_source(pig);
+        assert!(explosion.compute_hits(&level).is_empty());
+
+    }
+
+}
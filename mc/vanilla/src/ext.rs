@@ -1,10 +1,26 @@
-use mc_core::world::level::LevelEnv;
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+
+use mc_core::world::level::{LevelEnv, LevelEnvBuilder};
 use mc_core::entity::GlobalEntities;
 use mc_core::block::GlobalBlocks;
 use mc_core::biome::GlobalBiomes;
 use mc_core::heightmap::GlobalHeightmaps;
+use mc_core::world::dimension::DimensionTypes;
 use crate::entity::VANILLA_ENTITIES;
+use crate::block_entity::VANILLA_BLOCK_ENTITIES;
 use crate::block::VANILLA_BLOCKS;
+use crate::block::material::{BlockMaterials, MAT_STONE};
+use crate::block::interaction::UseBlockBehaviours;
+use crate::block::contact::ContactBlocks;
+use crate::block::tick::RandomTicks;
+use crate::block::color::BlockColors;
+use crate::item::food::ItemFoods;
+use crate::item::compost::ItemCompostables;
+use crate::item::tab::CreativeTabs;
+use crate::entity::lifecycle::MobLifecycle;
+use crate::entity::breeding::Breeding;
 use crate::biome::VANILLA_BIOMES;
 use crate::heightmap::VANILLA_HEIGHTMAPS;
 
@@ -27,6 +43,96 @@ impl WithVanilla for GlobalBlocks {
 }
 
 
+impl WithVanilla for BlockMaterials {
+    fn with_vanilla() -> Self {
+        let mut materials = Self::new(&MAT_STONE);
+        crate::block::material::register_vanilla_materials(&mut materials);
+        materials
+    }
+}
+
+
+impl WithVanilla for UseBlockBehaviours {
+    fn with_vanilla() -> Self {
+        let mut behaviours = Self::new();
+        crate::block::interaction::register_vanilla_behaviours(&mut behaviours);
+        behaviours
+    }
+}
+
+
+impl WithVanilla for ContactBlocks {
+    fn with_vanilla() -> Self {
+        let mut contacts = Self::new();
+        crate::block::contact::register_vanilla_contacts(&mut contacts);
+        contacts
+    }
+}
+
+
+impl WithVanilla for RandomTicks {
+    fn with_vanilla() -> Self {
+        let mut ticks = Self::new();
+        crate::block::tick::register_vanilla_ticks(&mut ticks);
+        ticks
+    }
+}
+
+
+impl WithVanilla for BlockColors {
+    fn with_vanilla() -> Self {
+        let mut colors = Self::new();
+        crate::block::color::register_vanilla_colors(&mut colors);
+        colors
+    }
+}
+
+
+impl WithVanilla for ItemFoods {
+    fn with_vanilla() -> Self {
+        let mut foods = Self::new();
+        crate::item::food::register_vanilla_foods(&mut foods);
+        foods
+    }
+}
+
+
+impl WithVanilla for ItemCompostables {
+    fn with_vanilla() -> Self {
+        let mut compostables = Self::new();
+        crate::item::compost::register_vanilla_compostables(&mut compostables);
+        compostables
+    }
+}
+
+
+impl WithVanilla for CreativeTabs {
+    fn with_vanilla() -> Self {
+        let mut tabs = Self::new();
+        crate::item::tab::register_vanilla_tabs(&mut tabs);
+        tabs
+    }
+}
+
+
+impl WithVanilla for MobLifecycle {
+    fn with_vanilla() -> Self {
+        let mut lifecycle = Self::new();
+        crate::entity::lifecycle::register_vanilla_mobs(&mut lifecycle);
+        lifecycle
+    }
+}
+
+
+impl WithVanilla for Breeding {
+    fn with_vanilla() -> Self {
+        let mut breeding = Self::new();
+        crate::entity::breeding::register_vanilla_foods(&mut breeding);
+        breeding
+    }
+}
+
+
 impl WithVanilla for GlobalBiomes {
     fn with_vanilla() -> Self {
         // SAFETY: Check safety comment for vanilla blocks.
@@ -37,7 +143,9 @@ impl WithVanilla for GlobalBiomes {
 
 impl WithVanilla for GlobalEntities {
     fn with_vanilla() -> Self {
-        Self::with_all(&VANILLA_ENTITIES)
+        let mut entities = Self::with_all(&VANILLA_ENTITIES);
+        entities.register_all(&VANILLA_BLOCK_ENTITIES);
+        entities
     }
 }
 
@@ -49,13 +157,47 @@ impl WithVanilla for GlobalHeightmaps {
 }
 
 
+impl WithVanilla for DimensionTypes {
+    fn with_vanilla() -> Self {
+        let mut dimension_types = Self::new();
+        dimension_types.register(Arc::new(crate::dimension::overworld()));
+        dimension_types.register(Arc::new(crate::dimension::the_nether()));
+        dimension_types.register(Arc::new(crate::dimension::the_end()));
+        dimension_types
+    }
+}
+
+
+impl WithVanilla for LevelEnvBuilder {
+    fn with_vanilla() -> Self {
+        Self::new()
+            .with_blocks(GlobalBlocks::with_vanilla())
+            .with_biomes(GlobalBiomes::with_vanilla())
+            .with_entities(GlobalEntities::with_vanilla())
+            .with_heightmaps(GlobalHeightmaps::with_vanilla())
+            .with_legacy_blocks(crate::block::legacy::get_flattened_state)
+    }
+}
+
+
 impl WithVanilla for LevelEnv {
     fn with_vanilla() -> Self {
-        Self::new(
-            GlobalBlocks::with_vanilla(),
-            GlobalBiomes::with_vanilla(),
-            GlobalEntities::with_vanilla(),
-            GlobalHeightmaps::with_vanilla()
-        )
+        // SAFETY: Vanilla registries are already built, the builder can't fail.
+        LevelEnvBuilder::with_vanilla().build().unwrap()
+    }
+}
+
+
+/// A trait implemented on `LevelEnv` to get a vanilla environment shared by all callers,
+/// this avoids rebuilding the vanilla registries (around 20k block states) for each level.
+pub trait VanillaLevelEnv {
+    fn vanilla() -> Arc<LevelEnv>;
+}
+
+static VANILLA_ENV: Lazy<Arc<LevelEnv>> = Lazy::new(|| Arc::new(LevelEnv::with_vanilla()));
+
+impl VanillaLevelEnv for LevelEnv {
+    fn vanilla() -> Arc<LevelEnv> {
+        Arc::clone(&VANILLA_ENV)
     }
 }
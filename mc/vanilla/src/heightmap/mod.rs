@@ -1,29 +1,37 @@
-use mc_core::block::{BlockState, GlobalBlocks};
+use mc_core::block::{Block, BlockState, GlobalBlocks};
 use mc_core::heightmaps;
 
-use crate::block::material::{TAG_LIQUID, TAG_NON_BLOCKING, TAG_LEAVES};
+use crate::block::material::{VANILLA_BLOCK_MATERIALS, MAT_LEAVES};
 use crate::block::AIR;
 
 
+fn blocks_motion(block: &'static Block) -> bool {
+    VANILLA_BLOCK_MATERIALS.blocks_motion(block)
+}
+
+fn is_leaves(block: &'static Block) -> bool {
+    std::ptr::eq(VANILLA_BLOCK_MATERIALS.get_material(block), &MAT_LEAVES)
+}
+
 fn heightmap_world_surface(state: &'static BlockState, _blocks: &GlobalBlocks) -> bool {
     state != AIR.get_default_state()
 }
 
-fn heightmap_ocean_floor(state: &'static BlockState, blocks: &GlobalBlocks) -> bool {
-    !blocks.has_block_tag(state.get_block(), &TAG_NON_BLOCKING)
+fn heightmap_ocean_floor(state: &'static BlockState, _blocks: &GlobalBlocks) -> bool {
+    blocks_motion(state.get_block())
 }
 
 fn heightmap_ocean_floor_wg(state: &'static BlockState, blocks: &GlobalBlocks) -> bool {
-    heightmap_ocean_floor(state, blocks) && !blocks.has_block_tag(state.get_block(), &TAG_LEAVES)
+    heightmap_ocean_floor(state, blocks) && !is_leaves(state.get_block())
 }
 
-fn heightmap_motion_blocking(state: &'static BlockState, blocks: &GlobalBlocks) -> bool {
+fn heightmap_motion_blocking(state: &'static BlockState, _blocks: &GlobalBlocks) -> bool {
     let block = state.get_block();
-    !blocks.has_block_tag(block, &TAG_NON_BLOCKING) || blocks.has_block_tag(block, &TAG_LIQUID)
+    blocks_motion(block) || VANILLA_BLOCK_MATERIALS.is_liquid(block)
 }
 
 fn heightmap_motion_blocking_no_leaves(state: &'static BlockState, blocks: &GlobalBlocks) -> bool {
-    heightmap_motion_blocking(state, blocks) && !blocks.has_block_tag(state.get_block(), &TAG_LEAVES)
+    heightmap_motion_blocking(state, blocks) && !is_leaves(state.get_block())
 }
 
 heightmaps!(pub VANILLA_HEIGHTMAPS [
@@ -0,0 +1,628 @@
+//! Hunger of players, players have a `Hunger` component with their food level, saturation
+//! and exhaustion. Actions exhaust players, exhaustion consumes saturation and then food,
+//! the `system_hunger` system regenerates the health of well fed players and damages starving
+//! players depending on the difficulty. Players eat the food they hold with `start_eating`,
+//! the food is consumed by the system once eaten long enough.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use mc_core::world::level::{Level, BaseEntity};
+use mc_core::entity::SingleEntityCodec;
+use mc_core::entity_component;
+use mc_core::item::{Item, ItemStack, Inventory};
+use mc_core::pos::EntityPos;
+use mc_core::util::NbtExt;
+use hecs::Entity;
+
+use nbt::CompoundTag;
+
+use mc_runtime::world::World;
+
+use crate::entity::{LivingEntity, PlayerEntity, ITEM};
+use crate::entity::item::ItemEntity;
+use crate::item::food::{FoodProperties, VANILLA_FOODS};
+use crate::item::{BOWL, GLASS_BOTTLE};
+use crate::death::{Dead, PLAYER_MAX_HEALTH};
+use crate::util::Difficulty;
+
+
+/// Maximum food level of players, players with less food can eat.
+pub const MAX_FOOD_LEVEL: u8 = 20;
+/// Saturation of players when they join.
+pub const DEFAULT_SATURATION: f32 = 5.0;
+/// Maximum accumulated exhaustion.
+pub const MAX_EXHAUSTION: f32 = 40.0;
+
+/// Exhaustion consuming one point of saturation or food.
+const EXHAUSTION_PER_FOOD: f32 = 4.0;
+/// Exhaustion of healing one point of health with food.
+const EXHAUSTION_HEAL: f32 = 6.0;
+/// Minimum food level to regenerate health.
+const REGENERATION_FOOD_LEVEL: u8 = 18;
+/// Ticks between regenerations or starvation damages.
+const SLOW_TICK_INTERVAL: u32 = 80;
+/// Ticks between regenerations with saturation and a full food level.
+const FAST_TICK_INTERVAL: u32 = 10;
+
+/// Height above the position of the player where returned containers are dropped.
+const CONTAINER_DROP_HEIGHT: f64 = 1.32;
+
+
+/// Actions exhausting players.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ExhaustionAction {
+    Jump,
+    SprintJump,
+    /// Attacking an entity.
+    Attack,
+    /// Taking damage.
+    Damage,
+    /// Breaking a block.
+    Mine,
+    /// Swimming the given distance, in blocks.
+    Swim(f32),
+    /// Walking the given distance underwater, in blocks.
+    WalkUnderwater(f32),
+    /// Sprinting the given distance, in blocks.
+    Sprint(f32)
+}
+
+impl ExhaustionAction {
+
+    pub fn get_exhaustion(self) -> f32 {
+        match self {
+            ExhaustionAction::Jump => 0.05,
+            ExhaustionAction::SprintJump => 0.2,
+            ExhaustionAction::Attack | ExhaustionAction::Damage => 0.1,
+            ExhaustionAction::Mine => 0.005,
+            ExhaustionAction::Swim(distance) | ExhaustionAction::WalkUnderwater(distance) => 0.01 * distance,
+            ExhaustionAction::Sprint(distance) => 0.1 * distance
+        }
+    }
+
+}
+
+
+/// Entity component of players with their food, saturation and exhaustion.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hunger {
+    food_level: u8,
+    saturation: f32,
+    exhaustion: f32,
+    /// Ticks since the last regeneration or starvation damage.
+    tick_timer: u32
+}
+
+impl Hunger {
+
+    pub fn new() -> Self {
+        Self {
+            food_level: MAX_FOOD_LEVEL,
+            saturation: DEFAULT_SATURATION,
+            exhaustion: 0.0,
+            tick_timer: 0
+        }
+    }
+
+    #[inline]
+    pub fn get_food_level(&self) -> u8 {
+        self.food_level
+    }
+
+    pub fn set_food_level(&mut self, food_level: u8) {
+        self.food_level = food_level.min(MAX_FOOD_LEVEL);
+    }
+
+    #[inline]
+    pub fn get_saturation(&self) -> f32 {
+        self.saturation
+    }
+
+    /// Set the saturation, it can't exceed the food level.
+    pub fn set_saturation(&mut self, saturation: f32) {
+        self.saturation = saturation.clamp(0.0, self.food_level as f32);
+    }
+
+    #[inline]
+    pub fn get_exhaustion(&self) -> f32 {
+        self.exhaustion
+    }
+
+    pub fn add_exhaustion(&mut self, exhaustion: f32) {
+        self.exhaustion = (self.exhaustion + exhaustion).min(MAX_EXHAUSTION);
+    }
+
+    pub fn exhaust(&mut self, action: ExhaustionAction) {
+        self.add_exhaustion(action.get_exhaustion());
+    }
+
+    #[inline]
+    pub fn needs_food(&self) -> bool {
+        self.food_level < MAX_FOOD_LEVEL
+    }
+
+    /// Return true if food can be eaten, foods that are always edible can be eaten with a
+    /// full food level.
+    pub fn can_eat(&self, always_edible: bool) -> bool {
+        always_edible || self.needs_food()
+    }
+
+    /// Restore food and saturation, the saturation can't exceed the new food level.
+    pub fn eat(&mut self, nutrition: u8, saturation_modifier: f32) {
+        self.food_level = self.food_level.saturating_add(nutrition).min(MAX_FOOD_LEVEL);
+        let saturation = self.saturation + nutrition as f32 * saturation_modifier * 2.0;
+        self.saturation = saturation.min(self.food_level as f32);
+    }
+
+    pub fn eat_food(&mut self, food: &FoodProperties) {
+        self.eat(food.get_nutrition(), food.get_saturation_modifier());
+    }
+
+    /// Tick the hunger of a player with the given health, exhaustion is consumed and the
+    /// health change is returned, positive when healing and negative when starving. Health
+    /// regenerates only with natural regeneration and starvation damages players down to
+    /// 10 health in easy, 1 health in normal and kill them in hard.
+    pub fn tick(&mut self, difficulty: Difficulty, natural_regeneration: bool, health: f32, max_health: f32) -> f32 {
+
+        if self.exhaustion > EXHAUSTION_PER_FOOD {
+            self.exhaustion -= EXHAUSTION_PER_FOOD;
+            if self.saturation > 0.0 {
+                self.saturation = (self.saturation - 1.0).max(0.0);
+            } else if difficulty != Difficulty::Peaceful {
+                self.food_level = self.food_level.saturating_sub(1);
+            }
+        }
+
+        let hurt = health > 0.0 && health < max_health;
+
+        if natural_regeneration && hurt && self.saturation > 0.0 && self.food_level >= MAX_FOOD_LEVEL {
+            self.tick_timer += 1;
+            if self.tick_timer >= FAST_TICK_INTERVAL {
+                let saturation = self.saturation.min(EXHAUSTION_HEAL);
+                self.add_exhaustion(saturation);
+                self.tick_timer = 0;
+                return saturation / EXHAUSTION_HEAL;
+            }
+        } else if natural_regeneration && hurt && self.food_level >= REGENERATION_FOOD_LEVEL {
+            self.tick_timer += 1;
+            if self.tick_timer >= SLOW_TICK_INTERVAL {
+                self.add_exhaustion(EXHAUSTION_HEAL);
+                self.tick_timer = 0;
+                return 1.0;
+            }
+        } else if self.food_level == 0 {
+            self.tick_timer += 1;
+            if self.tick_timer >= SLOW_TICK_INTERVAL {
+                self.tick_timer = 0;
+                let starve = match difficulty {
+                    Difficulty::Hard => true,
+                    Difficulty::Normal => health > 1.0,
+                    _ => health > 10.0
+                };
+                if starve {
+                    return -1.0;
+                }
+            }
+        } else {
+            self.tick_timer = 0;
+        }
+
+        0.0
+
+    }
+
+}
+
+impl Default for Hunger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+entity_component!(Hunger: HungerCodec);
+
+pub struct HungerCodec;
+impl SingleEntityCodec for HungerCodec {
+
+    type Comp = Hunger;
+
+    fn encode(&self, src: &Self::Comp, dst: &mut CompoundTag) {
+        dst.insert_i32("foodLevel", src.food_level as i32);
+        dst.insert_f32("foodSaturationLevel", src.saturation);
+        dst.insert_f32("foodExhaustionLevel", src.exhaustion);
+        dst.insert_i32("foodTickTimer", src.tick_timer as i32);
+    }
+
+    fn decode(&self, src: &CompoundTag) -> Self::Comp {
+        Hunger {
+            food_level: src.get_i32_or("foodLevel", MAX_FOOD_LEVEL as i32).clamp(0, MAX_FOOD_LEVEL as i32) as u8,
+            saturation: src.get_f32_or("foodSaturationLevel", DEFAULT_SATURATION),
+            exhaustion: src.get_f32_or("foodExhaustionLevel", 0.0),
+            tick_timer: src.get_i32_or("foodTickTimer", 0).max(0) as u32
+        }
+    }
+
+}
+
+
+/// An entity component added to players eating the item held in their selected slot.
+#[derive(Debug, Clone)]
+pub struct Eating {
+    slot: u8,
+    item: &'static Item,
+    remaining_ticks: u16
+}
+
+impl Eating {
+
+    #[inline]
+    pub fn get_item(&self) -> &'static Item {
+        self.item
+    }
+
+    #[inline]
+    pub fn get_remaining_ticks(&self) -> u16 {
+        self.remaining_ticks
+    }
+
+}
+
+
+/// World component configuring the hunger of players.
+pub struct HungerRules {
+    difficulty: Difficulty,
+    natural_regeneration: bool,
+    /// Ticks since the registration, used to regenerate players in peaceful.
+    ticks: u32
+}
+
+impl HungerRules {
+
+    pub fn new() -> Self {
+        Self {
+            difficulty: Difficulty::default(),
+            natural_regeneration: true,
+            ticks: 0
+        }
+    }
+
+    pub fn with_difficulty(mut self, difficulty: Difficulty) -> Self {
+        self.difficulty = difficulty;
+        self
+    }
+
+    /// Like the `naturalRegeneration` game rule, enabled by default.
+    pub fn with_natural_regeneration(mut self, natural_regeneration: bool) -> Self {
+        self.natural_regeneration = natural_regeneration;
+        self
+    }
+
+    #[inline]
+    pub fn get_difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.difficulty = difficulty;
+    }
+
+    #[inline]
+    pub fn has_natural_regeneration(&self) -> bool {
+        self.natural_regeneration
+    }
+
+}
+
+impl Default for HungerRules {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// Event pushed by `system_hunger` when a player has eaten a food item.
+pub struct PlayerEatEvent {
+    pub level: Rc<RefCell<Level>>,
+    pub entity: Entity,
+    pub item: &'static Item
+}
+
+
+/// Exhaust a player doing the given action, players that are invulnerable, like players in
+/// creative mode, are not exhausted. Return false if the entity has no `Hunger`.
+pub fn exhaust_player(level: &Level, entity: Entity, action: ExhaustionAction) -> bool {
+    let invulnerable = level.entities.ecs.get::<PlayerEntity>(entity)
+        .is_ok_and(|player| player.get_abilities().invulnerable);
+    match level.entities.ecs.get_mut::<Hunger>(entity) {
+        Ok(mut hunger) => {
+            if !invulnerable {
+                hunger.exhaust(action);
+            }
+            true
+        }
+        Err(_) => false
+    }
+}
+
+
+/// Start eating the food held by a player, the food is consumed by `system_hunger` after its
+/// eating duration. Return false if the player can't eat the held item, because it's not
+/// edible or because the player is not hungry. Invulnerable players can always eat.
+pub fn start_eating(level: &mut Level, entity: Entity) -> bool {
+
+    let eating = {
+        let ecs = &level.entities.ecs;
+        let (player, hunger, inventory) = match (ecs.get::<PlayerEntity>(entity), ecs.get::<Hunger>(entity), ecs.get::<Inventory>(entity)) {
+            (Ok(player), Ok(hunger), Ok(inventory)) => (player, hunger, inventory),
+            _ => return false
+        };
+        let slot = player.get_selected_slot();
+        let item = match inventory.get_stack(slot as usize) {
+            Some(stack) if player.can_interact() => stack.get_item(),
+            _ => return false
+        };
+        let food = match VANILLA_FOODS.get_food(item) {
+            Some(food) => food,
+            None => return false
+        };
+        if !player.get_abilities().invulnerable && !hunger.can_eat(food.is_always_edible()) {
+            return false;
+        }
+        Eating { slot, item, remaining_ticks: food.get_eat_duration() }
+    };
+
+    level.entities.ecs.insert_one(entity, eating).unwrap();
+    true
+
+}
+
+/// Stop a player eating, for example when the use item is released. Return true if the
+/// player was eating.
+pub fn stop_eating(level: &mut Level, entity: Entity) -> bool {
+    level.entities.ecs.remove_one::<Eating>(entity).is_ok()
+}
+
+
+/// Call this function with a mutable reference to a World to register the `HungerRules`
+/// component which is required to run the system `system_hunger`.
+pub fn register_hunger(world: &mut World, rules: HungerRules) {
+    world.insert_component(rules);
+}
+
+/// A system ticking the hunger of players that are not dead, regenerating or starving them,
+/// and finishing the food eaten by players. A `PlayerEatEvent` is pushed for each eaten
+/// food. In peaceful, players with natural regeneration also regain health and food over
+/// time.
+pub fn system_hunger(world: &mut World) {
+
+    let mut rules = match world.components.get_mut::<HungerRules>() {
+        Ok(rules) => rules,
+        Err(_) => return
+    };
+
+    rules.ticks = rules.ticks.wrapping_add(1);
+    let peaceful_regeneration = rules.natural_regeneration && rules.difficulty == Difficulty::Peaceful;
+
+    for level_rc in &world.levels {
+
+        let mut level = level_rc.borrow_mut();
+
+        for (_, (hunger, living, player)) in level.entities.ecs.query::<(&mut Hunger, &mut LivingEntity, &PlayerEntity)>().without::<Dead>().iter() {
+
+            let health = living.get_health();
+
+            if peaceful_regeneration {
+                if health < PLAYER_MAX_HEALTH && rules.ticks % 20 == 0 {
+                    living.set_health((health + 1.0).min(PLAYER_MAX_HEALTH));
+                }
+                if hunger.needs_food() && rules.ticks % 10 == 0 {
+                    hunger.set_food_level(hunger.get_food_level() + 1);
+                }
+            }
+
+            let health = living.get_health();
+            let change = hunger.tick(rules.difficulty, rules.natural_regeneration, health, PLAYER_MAX_HEALTH);
+            if change > 0.0 {
+                living.set_health((health + change).min(PLAYER_MAX_HEALTH));
+            } else if change < 0.0 && !player.get_abilities().invulnerable {
+                living.set_health((health + change).max(0.0));
+            }
+
+        }
+
+        let eaten: Vec<_> = level.entities.ecs.query_mut::<&mut Eating>()
+            .into_iter()
+            .filter_map(|(entity, eating)| {
+                eating.remaining_ticks = eating.remaining_ticks.saturating_sub(1);
+                (eating.remaining_ticks == 0).then_some((entity, eating.slot, eating.item))
+            })
+            .collect();
+
+        for (entity, slot, item) in eaten {
+            level.entities.ecs.remove_one::<Eating>(entity).unwrap();
+            if finish_eating(&mut level, entity, slot, item) {
+                world.event_tracker.push_event(PlayerEatEvent {
+                    level: Rc::clone(level_rc),
+                    entity,
+                    item
+                });
+            }
+        }
+
+    }
+
+}
+
+
+/// Internal function to consume the food eaten by a player, nothing is done if the player
+/// no longer holds the food. Containers of stews and bottles are given back to the player.
+fn finish_eating(level: &mut Level, entity: Entity, slot: u8, item: &'static Item) -> bool {
+
+    let container = {
+
+        let ecs = &level.entities.ecs;
+        let mut query = match ecs.query_one::<(&PlayerEntity, &mut Hunger, &mut Inventory)>(entity) {
+            Ok(query) => query,
+            Err(_) => return false
+        };
+        let (player, hunger, inventory) = match query.get() {
+            Some(components) => components,
+            None => return false
+        };
+
+        let food = match VANILLA_FOODS.get_food(item) {
+            Some(food) => food,
+            None => return false
+        };
+
+        let holding = player.get_selected_slot() == slot && inventory.get_stack(slot as usize)
+            .is_some_and(|stack| std::ptr::eq(stack.get_item(), item));
+
+        if !holding || !player.consume_item(inventory, slot as usize, 1) {
+            return false;
+        }
+
+        hunger.eat_food(food);
+
+        match get_food_container(item) {
+            Some(container) if !player.get_abilities().instant_build => {
+                let container = ItemStack::with_item(container);
+                if inventory.get_stack(slot as usize).is_none() {
+                    inventory.set_stack(slot as usize, Some(container));
+                    None
+                } else {
+                    inventory.insert(container)
+                }
+            }
+            _ => None
+        }
+
+    };
+
+    // The container is dropped if it doesn't fit in the inventory.
+    if let Some(container) = container {
+        let pos = level.entities.ecs.get::<BaseEntity>(entity).unwrap().pos.clone();
+        let pos = EntityPos::new(pos.x, pos.y + CONTAINER_DROP_HEIGHT, pos.z);
+        if let Some(dropped) = level.spawn_entity(&ITEM, pos) {
+            level.entities.ecs.insert_one(dropped, ItemEntity::new(container)).unwrap();
+        }
+    }
+
+    true
+
+}
+
+
+/// Return the container item given back after eating the given food.
+fn get_food_container(item: &'static Item) -> Option<&'static Item> {
+    match item.get_name() {
+        "minecraft:mushroom_stew" | "minecraft:rabbit_stew" | "minecraft:beetroot_soup" |
+        "minecraft:suspicious_stew" => Some(&BOWL),
+        "minecraft:honey_bottle" => Some(&GLASS_BOTTLE),
+        _ => None
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_core::world::level::LevelEnv;
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::source::NullLevelSource;
+
+    use crate::ext::VanillaLevelEnv;
+    use crate::entity::PLAYER;
+    use crate::item::{COOKED_BEEF, MUSHROOM_STEW, STONE};
+
+    use super::*;
+
+    #[test]
+    fn hunger_tick() {
+
+        let mut hunger = Hunger::new();
+        hunger.add_exhaustion(4.5);
+        assert_eq!(hunger.tick(Difficulty::Normal, true, 20.0, 20.0), 0.0);
+        assert_eq!(hunger.get_saturation(), 4.0);
+        assert_eq!(hunger.get_food_level(), 20);
+
+        // Fast regeneration with saturation and a full food level.
+        let mut healed = 0.0;
+        for _ in 0..10 {
+            healed += hunger.tick(Difficulty::Normal, true, 10.0, 20.0);
+        }
+        assert_eq!(healed, 4.0 / 6.0);
+        assert_eq!(hunger.get_exhaustion(), 4.5);
+
+        // Starvation stops at 1 health in normal.
+        hunger.set_saturation(0.0);
+        hunger.set_food_level(0);
+        let starve = |hunger: &mut Hunger, difficulty, health| (0..80).map(|_| hunger.tick(difficulty, true, health, 20.0)).sum::<f32>();
+        assert_eq!(starve(&mut hunger, Difficulty::Normal, 5.0), -1.0);
+        assert_eq!(starve(&mut hunger, Difficulty::Normal, 1.0), 0.0);
+        assert_eq!(starve(&mut hunger, Difficulty::Easy, 5.0), 0.0);
+        assert_eq!(starve(&mut hunger, Difficulty::Hard, 1.0), -1.0);
+
+        hunger.eat(8, 0.8);
+        assert_eq!(hunger.get_food_level(), 8);
+        assert_eq!(hunger.get_saturation(), 8.0);
+
+        let mut tag = CompoundTag::new();
+        HungerCodec.encode(&hunger, &mut tag);
+        assert_eq!(HungerCodec.decode(&tag), hunger);
+
+    }
+
+    #[test]
+    fn eating() {
+
+        let env = LevelEnv::vanilla();
+        let height = ChunkHeight::new(0, 0);
+        let mut level = Level::new("overworld".to_string(), env, height, NullLevelSource);
+
+        let player = level.spawn_entity(&PLAYER, EntityPos::new(0.5, 1.0, 0.5)).unwrap();
+        let mut inventory = Inventory::new(9);
+        inventory.set_stack(0, Some(ItemStack::with_item_count(&COOKED_BEEF, 2)));
+        inventory.set_stack(1, Some(ItemStack::with_item(&MUSHROOM_STEW)));
+        inventory.set_stack(2, Some(ItemStack::with_item(&STONE)));
+        level.entities.ecs.insert_one(player, inventory).unwrap();
+        level.entities.ecs.get_mut::<LivingEntity>(player).unwrap().set_health(PLAYER_MAX_HEALTH);
+
+        // Players can't eat with a full food level.
+        assert!(!start_eating(&mut level, player));
+        level.entities.ecs.get_mut::<Hunger>(player).unwrap().set_food_level(4);
+        assert!(start_eating(&mut level, player));
+
+        let mut world = World::new();
+        world.add_level(level);
+        register_hunger(&mut world, HungerRules::new());
+
+        for _ in 0..32 {
+            system_hunger(&mut world);
+        }
+
+        assert_eq!(world.event_tracker.poll_events::<PlayerEatEvent>().count(), 1);
+
+        let mut level = world.levels[0].borrow_mut();
+        assert!(level.entities.ecs.get::<Eating>(player).is_err());
+        assert_eq!(level.entities.ecs.get::<Hunger>(player).unwrap().get_food_level(), 12);
+        assert_eq!(level.entities.ecs.get::<Inventory>(player).unwrap().get_stack(0).unwrap().get_count(), 1);
+
+        // Stews give their bowl back.
+        level.entities.ecs.get_mut::<PlayerEntity>(player).unwrap().set_selected_slot(1);
+        assert!(start_eating(&mut level, player));
+        let slot = level.entities.ecs.get::<Eating>(player).unwrap().slot;
+        assert!(finish_eating(&mut level, player, slot, &MUSHROOM_STEW));
+        assert!(std::ptr::eq(level.entities.ecs.get::<Inventory>(player).unwrap().get_stack(1).unwrap().get_item(), &BOWL));
+
+        level.entities.ecs.get_mut::<PlayerEntity>(player).unwrap().set_selected_slot(2);
+        assert!(!start_eating(&mut level, player));
+        assert!(stop_eating(&mut level, player));
+
+        assert!(exhaust_player(&level, player, ExhaustionAction::Sprint(10.0)));
+        assert_eq!(level.entities.ecs.get::<Hunger>(player).unwrap().get_exhaustion(), 1.0);
+
+    }
+
+}
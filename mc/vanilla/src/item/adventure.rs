@@ -0,0 +1,99 @@
+//! Adventure mode restrictions, players in adventure mode can only break the blocks listed in
+//! the `CanDestroy` tag of their held item and only place blocks against the blocks listed in
+//! the `CanPlaceOn` tag of the placed item.
+
+use mc_core::block::{Block, GlobalBlocks};
+use mc_core::item::ItemStack;
+
+
+/// Return true if the given stack allows breaking the given block in adventure mode.
+pub fn can_destroy(stack: &ItemStack, blocks: &GlobalBlocks, block: &'static Block) -> bool {
+    matches_predicates(stack, "CanDestroy", blocks, block)
+}
+
+/// Return true if the given stack can be placed against the given block in adventure mode.
+pub fn can_place_on(stack: &ItemStack, blocks: &GlobalBlocks, block: &'static Block) -> bool {
+    matches_predicates(stack, "CanPlaceOn", blocks, block)
+}
+
+
+/// Internal function to check if any block predicate of a stack's tag matches a block.
+fn matches_predicates(stack: &ItemStack, key: &str, blocks: &GlobalBlocks, block: &'static Block) -> bool {
+    stack.get_tag()
+        .and_then(|tag| tag.get_str_vec(key).ok())
+        .is_some_and(|predicates| predicates.iter().any(|predicate| matches_block(predicate, blocks, block)))
+}
+
+
+/// Check a block predicate, either a block name or a block tag name prefixed by `#`. The
+/// namespace defaults to `minecraft`. Predicates with block states or NBT are not supported
+/// and never match.
+fn matches_block(predicate: &str, blocks: &GlobalBlocks, block: &'static Block) -> bool {
+
+    if predicate.contains(['[', '{']) {
+        return false;
+    }
+
+    let (is_tag, name) = match predicate.strip_prefix('#') {
+        Some(name) => (true, name),
+        None => (false, predicate)
+    };
+
+    let name = if name.contains(':') {
+        name.to_string()
+    } else {
+        format!("minecraft:{}", name)
+    };
+
+    if is_tag {
+        blocks.get_tag_type_from_name(&name)
+            .is_some_and(|tag_type| blocks.has_block_tag(block, tag_type))
+    } else {
+        block.get_name() == name
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_core::tag::TagType;
+    use nbt::CompoundTag;
+
+    use super::*;
+
+    mc_core::blocks!(TEST_BLOCKS "minecraft" [
+        STONE "stone",
+        OAK_LOG "oak_log",
+        DIRT "dirt"
+    ]);
+
+    mc_core::items!(TEST_ITEMS "minecraft" [
+        PICKAXE "pickaxe"
+    ]);
+
+    static TAG_LOGS: TagType = TagType::new("minecraft:logs");
+
+    #[test]
+    fn adventure_predicates() {
+
+        let mut blocks = GlobalBlocks::with_all(&TEST_BLOCKS).unwrap();
+        blocks.register_tag_type(&TAG_LOGS);
+        blocks.set_blocks_tag(&TAG_LOGS, true, [&OAK_LOG]).unwrap();
+
+        let mut tag = CompoundTag::new();
+        tag.insert_str_vec("CanDestroy", vec!["stone", "#minecraft:logs", "dirt[snowy=true]"]);
+        tag.insert_str_vec("CanPlaceOn", vec!["minecraft:dirt"]);
+        let stack = ItemStack::new(&PICKAXE, 1, Some(tag));
+
+        assert!(can_destroy(&stack, &blocks, &STONE));
+        assert!(can_destroy(&stack, &blocks, &OAK_LOG));
+        assert!(!can_destroy(&stack, &blocks, &DIRT));
+        assert!(can_place_on(&stack, &blocks, &DIRT));
+        assert!(!can_place_on(&stack, &blocks, &STONE));
+        assert!(!can_destroy(&ItemStack::new(&PICKAXE, 1, None), &blocks, &STONE));
+
+    }
+
+}
@@ -0,0 +1,113 @@
+//! Compostability of vanilla items, the chance for an item put in a composter to raise its
+//! level.
+
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+
+use mc_core::item::Item;
+use mc_core::util::OpaquePtr;
+
+use crate::ext::WithVanilla;
+use super::*;
+
+
+/// A registry of compostable items and their chance to raise the level of a composter.
+pub struct ItemCompostables {
+    items: HashMap<OpaquePtr<Item>, f32>
+}
+
+impl ItemCompostables {
+
+    pub fn new() -> Self {
+        Self {
+            items: HashMap::new()
+        }
+    }
+
+    /// Register the compost chance of an item, between 0 and 1, replacing any previous one.
+    pub fn register(&mut self, item: &'static Item, chance: f32) {
+        self.items.insert(OpaquePtr::new(item), chance);
+    }
+
+    pub fn register_all(&mut self, items: &[&'static Item], chance: f32) {
+        for &item in items {
+            self.register(item, chance);
+        }
+    }
+
+    /// Return the compost chance of an item, `None` if the item is not compostable.
+    pub fn get_chance(&self, item: &'static Item) -> Option<f32> {
+        self.items.get(&OpaquePtr::new(item)).copied()
+    }
+
+    #[inline]
+    pub fn is_compostable(&self, item: &'static Item) -> bool {
+        self.items.contains_key(&OpaquePtr::new(item))
+    }
+
+}
+
+impl Default for ItemCompostables {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// Vanilla compostable items, this is lazily built on first use with `WithVanilla`.
+pub static VANILLA_COMPOSTABLES: Lazy<ItemCompostables> = Lazy::new(ItemCompostables::with_vanilla);
+
+
+pub(crate) fn register_vanilla_compostables(compostables: &mut ItemCompostables) {
+
+    compostables.register_all(&[
+        &OAK_LEAVES, &SPRUCE_LEAVES, &BIRCH_LEAVES, &JUNGLE_LEAVES, &ACACIA_LEAVES,
+        &DARK_OAK_LEAVES, &AZALEA_LEAVES, &OAK_SAPLING, &SPRUCE_SAPLING, &BIRCH_SAPLING,
+        &JUNGLE_SAPLING, &ACACIA_SAPLING, &DARK_OAK_SAPLING, &BEETROOT_SEEDS, &DRIED_KELP,
+        &GRASS, &KELP, &MELON_SEEDS, &PUMPKIN_SEEDS, &SEAGRASS, &SWEET_BERRIES, &GLOW_BERRIES,
+        &WHEAT_SEEDS, &MOSS_CARPET, &SMALL_DRIPLEAF, &HANGING_ROOTS
+    ], 0.3);
+
+    compostables.register_all(&[
+        &DRIED_KELP_BLOCK, &TALL_GRASS, &FLOWERING_AZALEA_LEAVES, &CACTUS, &SUGAR_CANE, &VINE,
+        &NETHER_SPROUTS, &WEEPING_VINES, &TWISTING_VINES, &MELON_SLICE, &GLOW_LICHEN
+    ], 0.5);
+
+    compostables.register_all(&[
+        &SEA_PICKLE, &LILY_PAD, &PUMPKIN, &CARVED_PUMPKIN, &MELON, &APPLE, &BEETROOT, &CARROT,
+        &COCOA_BEANS, &POTATO, &WHEAT, &BROWN_MUSHROOM, &RED_MUSHROOM, &MUSHROOM_STEM,
+        &CRIMSON_FUNGUS, &WARPED_FUNGUS, &NETHER_WART, &CRIMSON_ROOTS, &WARPED_ROOTS,
+        &SHROOMLIGHT, &DANDELION, &POPPY, &BLUE_ORCHID, &ALLIUM, &AZURE_BLUET, &RED_TULIP,
+        &ORANGE_TULIP, &WHITE_TULIP, &PINK_TULIP, &OXEYE_DAISY, &CORNFLOWER,
+        &LILY_OF_THE_VALLEY, &WITHER_ROSE, &FERN, &SUNFLOWER, &LILAC, &ROSE_BUSH, &PEONY,
+        &LARGE_FERN, &SPORE_BLOSSOM, &AZALEA, &MOSS_BLOCK, &BIG_DRIPLEAF
+    ], 0.65);
+
+    compostables.register_all(&[
+        &HAY_BLOCK, &BROWN_MUSHROOM_BLOCK, &RED_MUSHROOM_BLOCK, &NETHER_WART_BLOCK,
+        &WARPED_WART_BLOCK, &FLOWERING_AZALEA, &BREAD, &BAKED_POTATO, &COOKIE
+    ], 0.85);
+
+    compostables.register_all(&[&CAKE, &PUMPKIN_PIE], 1.0);
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn vanilla_compostables() {
+        let compostables = &*VANILLA_COMPOSTABLES;
+        assert_eq!(compostables.get_chance(&WHEAT_SEEDS), Some(0.3));
+        assert_eq!(compostables.get_chance(&MELON_SLICE), Some(0.5));
+        assert_eq!(compostables.get_chance(&POPPY), Some(0.65));
+        assert_eq!(compostables.get_chance(&BREAD), Some(0.85));
+        assert_eq!(compostables.get_chance(&PUMPKIN_PIE), Some(1.0));
+        assert!(!compostables.is_compostable(&STONE));
+    }
+
+}
@@ -0,0 +1,205 @@
+//! Food properties of vanilla items, the nutrition and saturation restored when eating them.
+//! Status effects given by some foods (like rotten flesh or golden apples) are not included.
+
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+
+use mc_core::item::Item;
+use mc_core::util::OpaquePtr;
+
+use crate::ext::WithVanilla;
+use super::*;
+
+
+/// Properties of an edible item.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FoodProperties {
+    nutrition: u8,
+    saturation_modifier: f32,
+    meat: bool,
+    always_edible: bool,
+    fast: bool
+}
+
+impl FoodProperties {
+
+    pub const fn new(nutrition: u8, saturation_modifier: f32) -> Self {
+        Self {
+            nutrition,
+            saturation_modifier,
+            meat: false,
+            always_edible: false,
+            fast: false
+        }
+    }
+
+    /// Meat can be fed to wolves.
+    pub const fn with_meat(mut self) -> Self {
+        self.meat = true;
+        self
+    }
+
+    /// The food can be eaten even if the food level is full.
+    pub const fn with_always_edible(mut self) -> Self {
+        self.always_edible = true;
+        self
+    }
+
+    /// The food is eaten in 16 ticks instead of 32.
+    pub const fn with_fast(mut self) -> Self {
+        self.fast = true;
+        self
+    }
+
+    /// Food level restored when eating.
+    #[inline]
+    pub fn get_nutrition(&self) -> u8 {
+        self.nutrition
+    }
+
+    #[inline]
+    pub fn get_saturation_modifier(&self) -> f32 {
+        self.saturation_modifier
+    }
+
+    /// Saturation restored when eating, before being capped by the food level.
+    pub fn get_saturation(&self) -> f32 {
+        self.nutrition as f32 * self.saturation_modifier * 2.0
+    }
+
+    #[inline]
+    pub fn is_meat(&self) -> bool {
+        self.meat
+    }
+
+    #[inline]
+    pub fn is_always_edible(&self) -> bool {
+        self.always_edible
+    }
+
+    /// Duration of eating this food, in ticks.
+    pub fn get_eat_duration(&self) -> u16 {
+        if self.fast { 16 } else { 32 }
+    }
+
+}
+
+
+/// A registry of edible items and their food properties.
+pub struct ItemFoods {
+    items: HashMap<OpaquePtr<Item>, FoodProperties>
+}
+
+impl ItemFoods {
+
+    pub fn new() -> Self {
+        Self {
+            items: HashMap::new()
+        }
+    }
+
+    /// Register the food properties of an item, replacing any previous ones.
+    pub fn register(&mut self, item: &'static Item, food: FoodProperties) {
+        self.items.insert(OpaquePtr::new(item), food);
+    }
+
+    /// Return the food properties of an item, `None` if the item is not edible.
+    pub fn get_food(&self, item: &'static Item) -> Option<&FoodProperties> {
+        self.items.get(&OpaquePtr::new(item))
+    }
+
+    #[inline]
+    pub fn is_edible(&self, item: &'static Item) -> bool {
+        self.items.contains_key(&OpaquePtr::new(item))
+    }
+
+    pub fn foods_count(&self) -> usize {
+        self.items.len()
+    }
+
+}
+
+impl Default for ItemFoods {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// Vanilla food properties, this is lazily built on first use with `WithVanilla`.
+pub static VANILLA_FOODS: Lazy<ItemFoods> = Lazy::new(ItemFoods::with_vanilla);
+
+
+pub(crate) fn register_vanilla_foods(foods: &mut ItemFoods) {
+
+    let food = FoodProperties::new;
+
+    foods.register(&APPLE, food(4, 0.3));
+    foods.register(&BAKED_POTATO, food(5, 0.6));
+    foods.register(&BEEF, food(3, 0.3).with_meat());
+    foods.register(&BEETROOT, food(1, 0.6));
+    foods.register(&BEETROOT_SOUP, food(6, 0.6));
+    foods.register(&BREAD, food(5, 0.6));
+    foods.register(&CARROT, food(3, 0.6));
+    foods.register(&CHICKEN, food(2, 0.3).with_meat());
+    foods.register(&CHORUS_FRUIT, food(4, 0.3).with_always_edible());
+    foods.register(&COD, food(2, 0.1));
+    foods.register(&COOKED_BEEF, food(8, 0.8).with_meat());
+    foods.register(&COOKED_CHICKEN, food(6, 0.6).with_meat());
+    foods.register(&COOKED_COD, food(5, 0.6));
+    foods.register(&COOKED_MUTTON, food(6, 0.8).with_meat());
+    foods.register(&COOKED_PORKCHOP, food(8, 0.8).with_meat());
+    foods.register(&COOKED_RABBIT, food(5, 0.6).with_meat());
+    foods.register(&COOKED_SALMON, food(6, 0.8));
+    foods.register(&COOKIE, food(2, 0.1));
+    foods.register(&DRIED_KELP, food(1, 0.3).with_fast());
+    foods.register(&ENCHANTED_GOLDEN_APPLE, food(4, 1.2).with_always_edible());
+    foods.register(&GOLDEN_APPLE, food(4, 1.2).with_always_edible());
+    foods.register(&GLOW_BERRIES, food(2, 0.1));
+    foods.register(&GOLDEN_CARROT, food(6, 1.2));
+    foods.register(&HONEY_BOTTLE, food(6, 0.1));
+    foods.register(&MELON_SLICE, food(2, 0.3));
+    foods.register(&MUSHROOM_STEW, food(6, 0.6));
+    foods.register(&MUTTON, food(2, 0.3).with_meat());
+    foods.register(&POISONOUS_POTATO, food(2, 0.3));
+    foods.register(&PORKCHOP, food(3, 0.3).with_meat());
+    foods.register(&POTATO, food(1, 0.3));
+    foods.register(&PUFFERFISH, food(1, 0.1));
+    foods.register(&PUMPKIN_PIE, food(8, 0.3));
+    foods.register(&RABBIT, food(3, 0.3).with_meat());
+    foods.register(&RABBIT_STEW, food(10, 0.6));
+    foods.register(&ROTTEN_FLESH, food(4, 0.1).with_meat());
+    foods.register(&SALMON, food(2, 0.1));
+    foods.register(&SPIDER_EYE, food(2, 0.8));
+    foods.register(&SUSPICIOUS_STEW, food(6, 0.6).with_always_edible());
+    foods.register(&SWEET_BERRIES, food(2, 0.1));
+    foods.register(&TROPICAL_FISH, food(1, 0.1));
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn vanilla_foods() {
+
+        let foods = &*VANILLA_FOODS;
+
+        let beef = foods.get_food(&COOKED_BEEF).unwrap();
+        assert_eq!(beef.get_nutrition(), 8);
+        assert!((beef.get_saturation() - 12.8).abs() < 1e-5);
+        assert!(beef.is_meat());
+        assert_eq!(beef.get_eat_duration(), 32);
+
+        assert_eq!(foods.get_food(&DRIED_KELP).unwrap().get_eat_duration(), 16);
+        assert!(foods.get_food(&GOLDEN_APPLE).unwrap().is_always_edible());
+        assert!(foods.is_edible(&SWEET_BERRIES));
+        assert!(!foods.is_edible(&STONE));
+
+    }
+
+}
@@ -1 +1,1145 @@
+use mc_core::items;
 
+use crate::block;
+
+pub mod adventure;
+pub mod food;
+pub mod compost;
+pub mod tab;
+
+
+// Block items are defined in the order of their blocks, followed by other items, this is
+// not the order of the vanilla registry so network IDs of items must not be derived from
+// this order.
+items!(pub VANILLA_ITEMS "minecraft" [
+    AIR "air" block block::AIR,
+    STONE "stone" block block::STONE,
+    GRANITE "granite" block block::GRANITE,
+    POLISHED_GRANITE "polished_granite" block block::POLISHED_GRANITE,
+    DIORITE "diorite" block block::DIORITE,
+    POLISHED_DIORITE "polished_diorite" block block::POLISHED_DIORITE,
+    ANDESITE "andesite" block block::ANDESITE,
+    POLISHED_ANDESITE "polished_andesite" block block::POLISHED_ANDESITE,
+    GRASS_BLOCK "grass_block" block block::GRASS_BLOCK,
+    PODZOL "podzol" block block::PODZOL,
+    DIRT "dirt" block block::DIRT,
+    COARSE_DIRT "coarse_dirt" block block::COARSE_DIRT,
+    COBBLESTONE "cobblestone" block block::COBBLESTONE,
+    OAK_PLANKS "oak_planks" block block::OAK_PLANKS,
+    SPRUCE_PLANKS "spruce_planks" block block::SPRUCE_PLANKS,
+    BIRCH_PLANKS "birch_planks" block block::BIRCH_PLANKS,
+    JUNGLE_PLANKS "jungle_planks" block block::JUNGLE_PLANKS,
+    ACACIA_PLANKS "acacia_planks" block block::ACACIA_PLANKS,
+    DARK_OAK_PLANKS "dark_oak_planks" block block::DARK_OAK_PLANKS,
+    OAK_SAPLING "oak_sapling" block block::OAK_SAPLING,
+    SPRUCE_SAPLING "spruce_sapling" block block::SPRUCE_SAPLING,
+    BIRCH_SAPLING "birch_sapling" block block::BIRCH_SAPLING,
+    JUNGLE_SAPLING "jungle_sapling" block block::JUNGLE_SAPLING,
+    ACACIA_SAPLING "acacia_sapling" block block::ACACIA_SAPLING,
+    DARK_OAK_SAPLING "dark_oak_sapling" block block::DARK_OAK_SAPLING,
+    BEDROCK "bedrock" block block::BEDROCK,
+    SAND "sand" block block::SAND,
+    RED_SAND "red_sand" block block::RED_SAND,
+    GRAVEL "gravel" block block::GRAVEL,
+    GOLD_ORE "gold_ore" block block::GOLD_ORE,
+    DEEPSLATE_GOLD_ORE "deepslate_gold_ore" block block::DEEPSLATE_GOLD_ORE,
+    IRON_ORE "iron_ore" block block::IRON_ORE,
+    DEEPSLATE_IRON_ORE "deepslate_iron_ore" block block::DEEPSLATE_IRON_ORE,
+    COAL_ORE "coal_ore" block block::COAL_ORE,
+    DEEPSLATE_COAL_ORE "deepslate_coal_ore" block block::DEEPSLATE_COAL_ORE,
+    NETHER_GOLD_ORE "nether_gold_ore" block block::NETHER_GOLD_ORE,
+    OAK_LOG "oak_log" block block::OAK_LOG,
+    SPRUCE_LOG "spruce_log" block block::SPRUCE_LOG,
+    BIRCH_LOG "birch_log" block block::BIRCH_LOG,
+    JUNGLE_LOG "jungle_log" block block::JUNGLE_LOG,
+    ACACIA_LOG "acacia_log" block block::ACACIA_LOG,
+    DARK_OAK_LOG "dark_oak_log" block block::DARK_OAK_LOG,
+    STRIPPED_OAK_LOG "stripped_oak_log" block block::STRIPPED_OAK_LOG,
+    STRIPPED_SPRUCE_LOG "stripped_spruce_log" block block::STRIPPED_SPRUCE_LOG,
+    STRIPPED_BIRCH_LOG "stripped_birch_log" block block::STRIPPED_BIRCH_LOG,
+    STRIPPED_JUNGLE_LOG "stripped_jungle_log" block block::STRIPPED_JUNGLE_LOG,
+    STRIPPED_ACACIA_LOG "stripped_acacia_log" block block::STRIPPED_ACACIA_LOG,
+    STRIPPED_DARK_OAK_LOG "stripped_dark_oak_log" block block::STRIPPED_DARK_OAK_LOG,
+    OAK_WOOD "oak_wood" block block::OAK_WOOD,
+    SPRUCE_WOOD "spruce_wood" block block::SPRUCE_WOOD,
+    BIRCH_WOOD "birch_wood" block block::BIRCH_WOOD,
+    JUNGLE_WOOD "jungle_wood" block block::JUNGLE_WOOD,
+    ACACIA_WOOD "acacia_wood" block block::ACACIA_WOOD,
+    DARK_OAK_WOOD "dark_oak_wood" block block::DARK_OAK_WOOD,
+    STRIPPED_OAK_WOOD "stripped_oak_wood" block block::STRIPPED_OAK_WOOD,
+    STRIPPED_SPRUCE_WOOD "stripped_spruce_wood" block block::STRIPPED_SPRUCE_WOOD,
+    STRIPPED_BIRCH_WOOD "stripped_birch_wood" block block::STRIPPED_BIRCH_WOOD,
+    STRIPPED_JUNGLE_WOOD "stripped_jungle_wood" block block::STRIPPED_JUNGLE_WOOD,
+    STRIPPED_ACACIA_WOOD "stripped_acacia_wood" block block::STRIPPED_ACACIA_WOOD,
+    STRIPPED_DARK_OAK_WOOD "stripped_dark_oak_wood" block block::STRIPPED_DARK_OAK_WOOD,
+    OAK_LEAVES "oak_leaves" block block::OAK_LEAVES,
+    SPRUCE_LEAVES "spruce_leaves" block block::SPRUCE_LEAVES,
+    BIRCH_LEAVES "birch_leaves" block block::BIRCH_LEAVES,
+    JUNGLE_LEAVES "jungle_leaves" block block::JUNGLE_LEAVES,
+    ACACIA_LEAVES "acacia_leaves" block block::ACACIA_LEAVES,
+    DARK_OAK_LEAVES "dark_oak_leaves" block block::DARK_OAK_LEAVES,
+    AZALEA_LEAVES "azalea_leaves" block block::AZALEA_LEAVES,
+    FLOWERING_AZALEA_LEAVES "flowering_azalea_leaves" block block::FLOWERING_AZALEA_LEAVES,
+    SPONGE "sponge" block block::SPONGE,
+    WET_SPONGE "wet_sponge" block block::WET_SPONGE,
+    GLASS "glass" block block::GLASS,
+    LAPIS_ORE "lapis_ore" block block::LAPIS_ORE,
+    DEEPSLATE_LAPIS_ORE "deepslate_lapis_ore" block block::DEEPSLATE_LAPIS_ORE,
+    LAPIS_BLOCK "lapis_block" block block::LAPIS_BLOCK,
+    DISPENSER "dispenser" block block::DISPENSER,
+    SANDSTONE "sandstone" block block::SANDSTONE,
+    CHISELED_SANDSTONE "chiseled_sandstone" block block::CHISELED_SANDSTONE,
+    CUT_SANDSTONE "cut_sandstone" block block::CUT_SANDSTONE,
+    NOTE_BLOCK "note_block" block block::NOTE_BLOCK,
+    WHITE_BED "white_bed" 1 block block::WHITE_BED,
+    ORANGE_BED "orange_bed" 1 block block::ORANGE_BED,
+    MAGENTA_BED "magenta_bed" 1 block block::MAGENTA_BED,
+    LIGHT_BLUE_BED "light_blue_bed" 1 block block::LIGHT_BLUE_BED,
+    YELLOW_BED "yellow_bed" 1 block block::YELLOW_BED,
+    LIME_BED "lime_bed" 1 block block::LIME_BED,
+    PINK_BED "pink_bed" 1 block block::PINK_BED,
+    GRAY_BED "gray_bed" 1 block block::GRAY_BED,
+    LIGHT_GRAY_BED "light_gray_bed" 1 block block::LIGHT_GRAY_BED,
+    CYAN_BED "cyan_bed" 1 block block::CYAN_BED,
+    PURPLE_BED "purple_bed" 1 block block::PURPLE_BED,
+    BLUE_BED "blue_bed" 1 block block::BLUE_BED,
+    BROWN_BED "brown_bed" 1 block block::BROWN_BED,
+    GREEN_BED "green_bed" 1 block block::GREEN_BED,
+    RED_BED "red_bed" 1 block block::RED_BED,
+    BLACK_BED "black_bed" 1 block block::BLACK_BED,
+    POWERED_RAIL "powered_rail" block block::POWERED_RAIL,
+    DETECTOR_RAIL "detector_rail" block block::DETECTOR_RAIL,
+    PISTON "piston" block block::PISTON,
+    STICKY_PISTON "sticky_piston" block block::STICKY_PISTON,
+    COBWEB "cobweb" block block::COBWEB,
+    GRASS "grass" block block::GRASS,
+    FERN "fern" block block::FERN,
+    DEAD_BUSH "dead_bush" block block::DEAD_BUSH,
+    SEAGRASS "seagrass" block block::SEAGRASS,
+    WHITE_WOOL "white_wool" block block::WHITE_WOOL,
+    ORANGE_WOOL "orange_wool" block block::ORANGE_WOOL,
+    MAGENTA_WOOL "magenta_wool" block block::MAGENTA_WOOL,
+    LIGHT_BLUE_WOOL "light_blue_wool" block block::LIGHT_BLUE_WOOL,
+    YELLOW_WOOL "yellow_wool" block block::YELLOW_WOOL,
+    LIME_WOOL "lime_wool" block block::LIME_WOOL,
+    PINK_WOOL "pink_wool" block block::PINK_WOOL,
+    GRAY_WOOL "gray_wool" block block::GRAY_WOOL,
+    LIGHT_GRAY_WOOL "light_gray_wool" block block::LIGHT_GRAY_WOOL,
+    CYAN_WOOL "cyan_wool" block block::CYAN_WOOL,
+    PURPLE_WOOL "purple_wool" block block::PURPLE_WOOL,
+    BLUE_WOOL "blue_wool" block block::BLUE_WOOL,
+    BROWN_WOOL "brown_wool" block block::BROWN_WOOL,
+    GREEN_WOOL "green_wool" block block::GREEN_WOOL,
+    RED_WOOL "red_wool" block block::RED_WOOL,
+    BLACK_WOOL "black_wool" block block::BLACK_WOOL,
+    DANDELION "dandelion" block block::DANDELION,
+    POPPY "poppy" block block::POPPY,
+    BLUE_ORCHID "blue_orchid" block block::BLUE_ORCHID,
+    ALLIUM "allium" block block::ALLIUM,
+    AZURE_BLUET "azure_bluet" block block::AZURE_BLUET,
+    RED_TULIP "red_tulip" block block::RED_TULIP,
+    ORANGE_TULIP "orange_tulip" block block::ORANGE_TULIP,
+    WHITE_TULIP "white_tulip" block block::WHITE_TULIP,
+    PINK_TULIP "pink_tulip" block block::PINK_TULIP,
+    OXEYE_DAISY "oxeye_daisy" block block::OXEYE_DAISY,
+    CORNFLOWER "cornflower" block block::CORNFLOWER,
+    WITHER_ROSE "wither_rose" block block::WITHER_ROSE,
+    LILY_OF_THE_VALLEY "lily_of_the_valley" block block::LILY_OF_THE_VALLEY,
+    BROWN_MUSHROOM "brown_mushroom" block block::BROWN_MUSHROOM,
+    RED_MUSHROOM "red_mushroom" block block::RED_MUSHROOM,
+    GOLD_BLOCK "gold_block" block block::GOLD_BLOCK,
+    IRON_BLOCK "iron_block" block block::IRON_BLOCK,
+    BRICKS "bricks" block block::BRICKS,
+    TNT "tnt" block block::TNT,
+    BOOKSHELF "bookshelf" block block::BOOKSHELF,
+    MOSSY_COBBLESTONE "mossy_cobblestone" block block::MOSSY_COBBLESTONE,
+    OBSIDIAN "obsidian" block block::OBSIDIAN,
+    TORCH "torch" block block::TORCH,
+    SPAWNER "spawner" block block::SPAWNER,
+    OAK_STAIRS "oak_stairs" block block::OAK_STAIRS,
+    SPRUCE_STAIRS "spruce_stairs" block block::SPRUCE_STAIRS,
+    BIRCH_STAIRS "birch_stairs" block block::BIRCH_STAIRS,
+    JUNGLE_STAIRS "jungle_stairs" block block::JUNGLE_STAIRS,
+    ACACIA_STAIRS "acacia_stairs" block block::ACACIA_STAIRS,
+    DARK_OAK_STAIRS "dark_oak_stairs" block block::DARK_OAK_STAIRS,
+    CHEST "chest" block block::CHEST,
+    REDSTONE "redstone" block block::REDSTONE_WIRE,
+    DIAMOND_ORE "diamond_ore" block block::DIAMOND_ORE,
+    DEEPSLATE_DIAMOND_ORE "deepslate_diamond_ore" block block::DEEPSLATE_DIAMOND_ORE,
+    DIAMOND_BLOCK "diamond_block" block block::DIAMOND_BLOCK,
+    CRAFTING_TABLE "crafting_table" block block::CRAFTING_TABLE,
+    WHEAT_SEEDS "wheat_seeds" block block::WHEAT,
+    FARMLAND "farmland" block block::FARMLAND,
+    FURNACE "furnace" block block::FURNACE,
+    OAK_SIGN "oak_sign" 16 block block::OAK_SIGN,
+    SPRUCE_SIGN "spruce_sign" 16 block block::SPRUCE_SIGN,
+    BIRCH_SIGN "birch_sign" 16 block block::BIRCH_SIGN,
+    JUNGLE_SIGN "jungle_sign" 16 block block::JUNGLE_SIGN,
+    ACACIA_SIGN "acacia_sign" 16 block block::ACACIA_SIGN,
+    DARK_OAK_SIGN "dark_oak_sign" 16 block block::DARK_OAK_SIGN,
+    LADDER "ladder" block block::LADDER,
+    RAIL "rail" block block::RAIL,
+    COBBLESTONE_STAIRS "cobblestone_stairs" block block::COBBLESTONE_STAIRS,
+    LEVER "lever" block block::LEVER,
+    STONE_PRESSURE_PLATE "stone_pressure_plate" block block::STONE_PRESSURE_PLATE,
+    IRON_DOOR "iron_door" block block::IRON_DOOR,
+    OAK_PRESSURE_PLATE "oak_pressure_plate" block block::OAK_PRESSURE_PLATE,
+    SPRUCE_PRESSURE_PLATE "spruce_pressure_plate" block block::SPRUCE_PRESSURE_PLATE,
+    BIRCH_PRESSURE_PLATE "birch_pressure_plate" block block::BIRCH_PRESSURE_PLATE,
+    JUNGLE_PRESSURE_PLATE "jungle_pressure_plate" block block::JUNGLE_PRESSURE_PLATE,
+    ACACIA_PRESSURE_PLATE "acacia_pressure_plate" block block::ACACIA_PRESSURE_PLATE,
+    DARK_OAK_PRESSURE_PLATE "dark_oak_pressure_plate" block block::DARK_OAK_PRESSURE_PLATE,
+    REDSTONE_ORE "redstone_ore" block block::REDSTONE_ORE,
+    DEEPSLATE_REDSTONE_ORE "deepslate_redstone_ore" block block::DEEPSLATE_REDSTONE_ORE,
+    REDSTONE_TORCH "redstone_torch" block block::REDSTONE_TORCH,
+    STONE_BUTTON "stone_button" block block::STONE_BUTTON,
+    SNOW "snow" block block::SNOW,
+    ICE "ice" block block::ICE,
+    SNOW_BLOCK "snow_block" block block::SNOW_BLOCK,
+    CACTUS "cactus" block block::CACTUS,
+    CLAY "clay" block block::CLAY,
+    SUGAR_CANE "sugar_cane" block block::SUGAR_CANE,
+    JUKEBOX "jukebox" block block::JUKEBOX,
+    PUMPKIN "pumpkin" block block::PUMPKIN,
+    NETHERRACK "netherrack" block block::NETHERRACK,
+    SOUL_SAND "soul_sand" block block::SOUL_SAND,
+    SOUL_SOIL "soul_soil" block block::SOUL_SOIL,
+    BASALT "basalt" block block::BASALT,
+    POLISHED_BASALT "polished_basalt" block block::POLISHED_BASALT,
+    SOUL_TORCH "soul_torch" block block::SOUL_TORCH,
+    GLOWSTONE "glowstone" block block::GLOWSTONE,
+    CARVED_PUMPKIN "carved_pumpkin" block block::CARVED_PUMPKIN,
+    JACK_O_LANTERN "jack_o_lantern" block block::JACK_O_LANTERN,
+    CAKE "cake" 1 block block::CAKE,
+    REPEATER "repeater" block block::REPEATER,
+    WHITE_STAINED_GLASS "white_stained_glass" block block::WHITE_STAINED_GLASS,
+    ORANGE_STAINED_GLASS "orange_stained_glass" block block::ORANGE_STAINED_GLASS,
+    MAGENTA_STAINED_GLASS "magenta_stained_glass" block block::MAGENTA_STAINED_GLASS,
+    LIGHT_BLUE_STAINED_GLASS "light_blue_stained_glass" block block::LIGHT_BLUE_STAINED_GLASS,
+    YELLOW_STAINED_GLASS "yellow_stained_glass" block block::YELLOW_STAINED_GLASS,
+    LIME_STAINED_GLASS "lime_stained_glass" block block::LIME_STAINED_GLASS,
+    PINK_STAINED_GLASS "pink_stained_glass" block block::PINK_STAINED_GLASS,
+    GRAY_STAINED_GLASS "gray_stained_glass" block block::GRAY_STAINED_GLASS,
+    LIGHT_GRAY_STAINED_GLASS "light_gray_stained_glass" block block::LIGHT_GRAY_STAINED_GLASS,
+    CYAN_STAINED_GLASS "cyan_stained_glass" block block::CYAN_STAINED_GLASS,
+    PURPLE_STAINED_GLASS "purple_stained_glass" block block::PURPLE_STAINED_GLASS,
+    BLUE_STAINED_GLASS "blue_stained_glass" block block::BLUE_STAINED_GLASS,
+    BROWN_STAINED_GLASS "brown_stained_glass" block block::BROWN_STAINED_GLASS,
+    GREEN_STAINED_GLASS "green_stained_glass" block block::GREEN_STAINED_GLASS,
+    RED_STAINED_GLASS "red_stained_glass" block block::RED_STAINED_GLASS,
+    BLACK_STAINED_GLASS "black_stained_glass" block block::BLACK_STAINED_GLASS,
+    OAK_TRAPDOOR "oak_trapdoor" block block::OAK_TRAPDOOR,
+    SPRUCE_TRAPDOOR "spruce_trapdoor" block block::SPRUCE_TRAPDOOR,
+    BIRCH_TRAPDOOR "birch_trapdoor" block block::BIRCH_TRAPDOOR,
+    JUNGLE_TRAPDOOR "jungle_trapdoor" block block::JUNGLE_TRAPDOOR,
+    ACACIA_TRAPDOOR "acacia_trapdoor" block block::ACACIA_TRAPDOOR,
+    DARK_OAK_TRAPDOOR "dark_oak_trapdoor" block block::DARK_OAK_TRAPDOOR,
+    STONE_BRICKS "stone_bricks" block block::STONE_BRICKS,
+    MOSSY_STONE_BRICKS "mossy_stone_bricks" block block::MOSSY_STONE_BRICKS,
+    CRACKED_STONE_BRICKS "cracked_stone_bricks" block block::CRACKED_STONE_BRICKS,
+    CHISELED_STONE_BRICKS "chiseled_stone_bricks" block block::CHISELED_STONE_BRICKS,
+    INFESTED_STONE "infested_stone" block block::INFESTED_STONE,
+    INFESTED_COBBLESTONE "infested_cobblestone" block block::INFESTED_COBBLESTONE,
+    INFESTED_STONE_BRICKS "infested_stone_bricks" block block::INFESTED_STONE_BRICKS,
+    INFESTED_MOSSY_STONE_BRICKS "infested_mossy_stone_bricks" block block::INFESTED_MOSSY_STONE_BRICKS,
+    INFESTED_CRACKED_STONE_BRICKS "infested_cracked_stone_bricks" block block::INFESTED_CRACKED_STONE_BRICKS,
+    INFESTED_CHISELED_STONE_BRICKS "infested_chiseled_stone_bricks" block block::INFESTED_CHISELED_STONE_BRICKS,
+    BROWN_MUSHROOM_BLOCK "brown_mushroom_block" block block::BROWN_MUSHROOM_BLOCK,
+    RED_MUSHROOM_BLOCK "red_mushroom_block" block block::RED_MUSHROOM_BLOCK,
+    MUSHROOM_STEM "mushroom_stem" block block::MUSHROOM_STEM,
+    IRON_BARS "iron_bars" block block::IRON_BARS,
+    CHAIN "chain" block block::CHAIN,
+    GLASS_PANE "glass_pane" block block::GLASS_PANE,
+    MELON "melon" block block::MELON,
+    PUMPKIN_SEEDS "pumpkin_seeds" block block::PUMPKIN_STEM,
+    MELON_SEEDS "melon_seeds" block block::MELON_STEM,
+    VINE "vine" block block::VINE,
+    GLOW_LICHEN "glow_lichen" block block::GLOW_LICHEN,
+    BRICK_STAIRS "brick_stairs" block block::BRICK_STAIRS,
+    STONE_BRICK_STAIRS "stone_brick_stairs" block block::STONE_BRICK_STAIRS,
+    MYCELIUM "mycelium" block block::MYCELIUM,
+    LILY_PAD "lily_pad" block block::LILY_PAD,
+    NETHER_BRICKS "nether_bricks" block block::NETHER_BRICKS,
+    NETHER_BRICK_FENCE "nether_brick_fence" block block::NETHER_BRICK_FENCE,
+    NETHER_BRICK_STAIRS "nether_brick_stairs" block block::NETHER_BRICK_STAIRS,
+    NETHER_WART "nether_wart" block block::NETHER_WART,
+    ENCHANTING_TABLE "enchanting_table" block block::ENCHANTING_TABLE,
+    BREWING_STAND "brewing_stand" block block::BREWING_STAND,
+    CAULDRON "cauldron" block block::CAULDRON,
+    END_PORTAL_FRAME "end_portal_frame" block block::END_PORTAL_FRAME,
+    END_STONE "end_stone" block block::END_STONE,
+    DRAGON_EGG "dragon_egg" block block::DRAGON_EGG,
+    REDSTONE_LAMP "redstone_lamp" block block::REDSTONE_LAMP,
+    COCOA_BEANS "cocoa_beans" block block::COCOA,
+    SANDSTONE_STAIRS "sandstone_stairs" block block::SANDSTONE_STAIRS,
+    EMERALD_ORE "emerald_ore" block block::EMERALD_ORE,
+    DEEPSLATE_EMERALD_ORE "deepslate_emerald_ore" block block::DEEPSLATE_EMERALD_ORE,
+    ENDER_CHEST "ender_chest" block block::ENDER_CHEST,
+    TRIPWIRE_HOOK "tripwire_hook" block block::TRIPWIRE_HOOK,
+    STRING "string" block block::TRIPWIRE,
+    EMERALD_BLOCK "emerald_block" block block::EMERALD_BLOCK,
+    COMMAND_BLOCK "command_block" block block::COMMAND_BLOCK,
+    CHAIN_COMMAND_BLOCK "chain_command_block" block block::CHAIN_COMMAND_BLOCK,
+    REPEATING_COMMAND_BLOCK "repeating_command_block" block block::REPEATING_COMMAND_BLOCK,
+    BEACON "beacon" block block::BEACON,
+    COBBLESTONE_WALL "cobblestone_wall" block block::COBBLESTONE_WALL,
+    MOSSY_COBBLESTONE_WALL "mossy_cobblestone_wall" block block::MOSSY_COBBLESTONE_WALL,
+    FLOWER_POT "flower_pot" block block::FLOWER_POT,
+    CARROT "carrot" block block::CARROTS,
+    POTATO "potato" block block::POTATOES,
+    OAK_BUTTON "oak_button" block block::OAK_BUTTON,
+    SPRUCE_BUTTON "spruce_button" block block::SPRUCE_BUTTON,
+    BIRCH_BUTTON "birch_button" block block::BIRCH_BUTTON,
+    JUNGLE_BUTTON "jungle_button" block block::JUNGLE_BUTTON,
+    ACACIA_BUTTON "acacia_button" block block::ACACIA_BUTTON,
+    DARK_OAK_BUTTON "dark_oak_button" block block::DARK_OAK_BUTTON,
+    SKELETON_SKULL "skeleton_skull" block block::SKELETON_SKULL,
+    WITHER_SKELETON_SKULL "wither_skeleton_skull" block block::WITHER_SKELETON_SKULL,
+    ZOMBIE_HEAD "zombie_head" block block::ZOMBIE_HEAD,
+    PLAYER_HEAD "player_head" block block::PLAYER_HEAD,
+    CREEPER_HEAD "creeper_head" block block::CREEPER_HEAD,
+    DRAGON_HEAD "dragon_head" block block::DRAGON_HEAD,
+    ANVIL "anvil" block block::ANVIL,
+    CHIPPED_ANVIL "chipped_anvil" block block::CHIPPED_ANVIL,
+    DAMAGED_ANVIL "damaged_anvil" block block::DAMAGED_ANVIL,
+    TRAPPED_CHEST "trapped_chest" block block::TRAPPED_CHEST,
+    LIGHT_WEIGHTED_PRESSURE_PLATE "light_weighted_pressure_plate" block block::LIGHT_WEIGHTED_PRESSURE_PLATE,
+    HEAVY_WEIGHTED_PRESSURE_PLATE "heavy_weighted_pressure_plate" block block::HEAVY_WEIGHTED_PRESSURE_PLATE,
+    COMPARATOR "comparator" block block::COMPARATOR,
+    DAYLIGHT_DETECTOR "daylight_detector" block block::DAYLIGHT_DETECTOR,
+    REDSTONE_BLOCK "redstone_block" block block::REDSTONE_BLOCK,
+    NETHER_QUARTZ_ORE "nether_quartz_ore" block block::NETHER_QUARTZ_ORE,
+    HOPPER "hopper" block block::HOPPER,
+    QUARTZ_BLOCK "quartz_block" block block::QUARTZ_BLOCK,
+    CHISELED_QUARTZ_BLOCK "chiseled_quartz_block" block block::CHISELED_QUARTZ_BLOCK,
+    QUARTZ_PILLAR "quartz_pillar" block block::QUARTZ_PILLAR,
+    QUARTZ_STAIRS "quartz_stairs" block block::QUARTZ_STAIRS,
+    ACTIVATOR_RAIL "activator_rail" block block::ACTIVATOR_RAIL,
+    DROPPER "dropper" block block::DROPPER,
+    TERRACOTTA "terracotta" block block::TERRACOTTA,
+    WHITE_TERRACOTTA "white_terracotta" block block::WHITE_TERRACOTTA,
+    ORANGE_TERRACOTTA "orange_terracotta" block block::ORANGE_TERRACOTTA,
+    MAGENTA_TERRACOTTA "magenta_terracotta" block block::MAGENTA_TERRACOTTA,
+    LIGHT_BLUE_TERRACOTTA "light_blue_terracotta" block block::LIGTH_BLUE_TERRACOTTA,
+    YELLOW_TERRACOTTA "yellow_terracotta" block block::YELLOW_TERRACOTTA,
+    LIME_TERRACOTTA "lime_terracotta" block block::LIME_TERRACOTTA,
+    PINK_TERRACOTTA "pink_terracotta" block block::PINK_TERRACOTTA,
+    GRAY_TERRACOTTA "gray_terracotta" block block::GRAY_TERRACOTTA,
+    LIGHT_GRAY_TERRACOTTA "light_gray_terracotta" block block::LIGHT_GRAY_TERRACOTTA,
+    CYAN_TERRACOTTA "cyan_terracotta" block block::CYAN_TERRACOTTA,
+    PURPLE_TERRACOTTA "purple_terracotta" block block::PURPLE_TERRACOTTA,
+    BLUE_TERRACOTTA "blue_terracotta" block block::BLUE_TERRACOTTA,
+    BROWN_TERRACOTTA "brown_terracotta" block block::BROWN_TERRACOTTA,
+    GREEN_TERRACOTTA "green_terracotta" block block::GREEN_TERRACOTTA,
+    RED_TERRACOTTA "red_terracotta" block block::RED_TERRACOTTA,
+    BLACK_TERRACOTTA "black_terracotta" block block::BLACK_TERRACOTTA,
+    WHITE_STAINED_GLASS_PANE "white_stained_glass_pane" block block::WHITE_STAINED_GLASS_PANE,
+    ORANGE_STAINED_GLASS_PANE "orange_stained_glass_pane" block block::ORANGE_STAINED_GLASS_PANE,
+    MAGENTA_STAINED_GLASS_PANE "magenta_stained_glass_pane" block block::MAGENTA_STAINED_GLASS_PANE,
+    LIGHT_BLUE_STAINED_GLASS_PANE "light_blue_stained_glass_pane" block block::LIGHT_BLUE_STAINED_GLASS_PANE,
+    YELLOW_STAINED_GLASS_PANE "yellow_stained_glass_pane" block block::YELLOW_STAINED_GLASS_PANE,
+    LIME_STAINED_GLASS_PANE "lime_stained_glass_pane" block block::LIME_STAINED_GLASS_PANE,
+    PINK_STAINED_GLASS_PANE "pink_stained_glass_pane" block block::PINK_STAINED_GLASS_PANE,
+    GRAY_STAINED_GLASS_PANE "gray_stained_glass_pane" block block::GRAY_STAINED_GLASS_PANE,
+    LIGHT_GRAY_STAINED_GLASS_PANE "light_gray_stained_glass_pane" block block::LIGHT_GRAY_STAINED_GLASS_PANE,
+    CYAN_STAINED_GLASS_PANE "cyan_stained_glass_pane" block block::CYAN_STAINED_GLASS_PANE,
+    PURPLE_STAINED_GLASS_PANE "purple_stained_glass_pane" block block::PURPLE_STAINED_GLASS_PANE,
+    BLUE_STAINED_GLASS_PANE "blue_stained_glass_pane" block block::BLUE_STAINED_GLASS_PANE,
+    BROWN_STAINED_GLASS_PANE "brown_stained_glass_pane" block block::BROWN_STAINED_GLASS_PANE,
+    GREEN_STAINED_GLASS_PANE "green_stained_glass_pane" block block::GREEN_STAINED_GLASS_PANE,
+    RED_STAINED_GLASS_PANE "red_stained_glass_pane" block block::RED_STAINED_GLASS_PANE,
+    BLACK_STAINED_GLASS_PANE "black_stained_glass_pane" block block::BLACK_STAINED_GLASS_PANE,
+    SLIME_BLOCK "slime_block" block block::SLIME_BLOCK,
+    BARRIER "barrier" block block::BARRIER,
+    LIGHT "light" block block::LIGHT,
+    IRON_TRAPDOOR "iron_trapdoor" block block::IRON_TRAPDOOR,
+    PRISMARINE "prismarine" block block::PRISMARINE,
+    PRISMARINE_BRICKS "prismarine_bricks" block block::PRISMARINE_BRICKS,
+    DARK_PRISMARINE "dark_prismarine" block block::DARK_PRISMARINE,
+    PRISMARINE_STAIRS "prismarine_stairs" block block::PRISMARINE_STAIRS,
+    PRISMARINE_BRICK_STAIRS "prismarine_brick_stairs" block block::PRISMARINE_BRICK_STAIRS,
+    DARK_PRISMARINE_STAIRS "dark_prismarine_stairs" block block::DARK_PRISMARINE_STAIRS,
+    PRISMARINE_SLAB "prismarine_slab" block block::PRISMARINE_SLAB,
+    PRISMARINE_BRICK_SLAB "prismarine_brick_slab" block block::PRISMARINE_BRICK_SLAB,
+    DARK_PRISMARINE_SLAB "dark_prismarine_slab" block block::DARK_PRISMARINE_SLAB,
+    SEA_LANTERN "sea_lantern" block block::SEA_LANTERN,
+    HAY_BLOCK "hay_block" block block::HAY_BLOCK,
+    WHITE_CARPET "white_carpet" block block::WHITE_CARPET,
+    ORANGE_CARPET "orange_carpet" block block::ORANGE_CARPET,
+    MAGENTA_CARPET "magenta_carpet" block block::MAGENTA_CARPET,
+    LIGHT_BLUE_CARPET "light_blue_carpet" block block::LIGHT_BLUE_CARPET,
+    YELLOW_CARPET "yellow_carpet" block block::YELLOW_CARPET,
+    LIME_CARPET "lime_carpet" block block::LIME_CARPET,
+    PINK_CARPET "pink_carpet" block block::PINK_CARPET,
+    GRAY_CARPET "gray_carpet" block block::GRAY_CARPET,
+    LIGHT_GRAY_CARPET "light_gray_carpet" block block::LIGHT_GRAY_CARPET,
+    CYAN_CARPET "cyan_carpet" block block::CYAN_CARPET,
+    PURPLE_CARPET "purple_carpet" block block::PURPLE_CARPET,
+    BLUE_CARPET "blue_carpet" block block::BLUE_CARPET,
+    BROWN_CARPET "brown_carpet" block block::BROWN_CARPET,
+    GREEN_CARPET "green_carpet" block block::GREEN_CARPET,
+    RED_CARPET "red_carpet" block block::RED_CARPET,
+    BLACK_CARPET "black_carpet" block block::BLACK_CARPET,
+    COAL_BLOCK "coal_block" block block::COAL_BLOCK,
+    PACKED_ICE "packed_ice" block block::PACKED_ICE,
+    SUNFLOWER "sunflower" block block::SUNFLOWER,
+    LILAC "lilac" block block::LILAC,
+    ROSE_BUSH "rose_bush" block block::ROSE_BUSH,
+    PEONY "peony" block block::PEONY,
+    TALL_GRASS "tall_grass" block block::TALL_GRASS,
+    LARGE_FERN "large_fern" block block::LARGE_FERN,
+    WHITE_BANNER "white_banner" 16 block block::WHITE_BANNER,
+    ORANGE_BANNER "orange_banner" 16 block block::ORANGE_BANNER,
+    MAGENTA_BANNER "magenta_banner" 16 block block::MAGENTA_BANNER,
+    LIGHT_BLUE_BANNER "light_blue_banner" 16 block block::LIGHT_BLUE_BANNER,
+    YELLOW_BANNER "yellow_banner" 16 block block::YELLOW_BANNER,
+    LIME_BANNER "lime_banner" 16 block block::LIME_BANNER,
+    PINK_BANNER "pink_banner" 16 block block::PINK_BANNER,
+    GRAY_BANNER "gray_banner" 16 block block::GRAY_BANNER,
+    LIGHT_GRAY_BANNER "light_gray_banner" 16 block block::LIGHT_GRAY_BANNER,
+    CYAN_BANNER "cyan_banner" 16 block block::CYAN_BANNER,
+    PURPLE_BANNER "purple_banner" 16 block block::PURPLE_BANNER,
+    BLUE_BANNER "blue_banner" 16 block block::BLUE_BANNER,
+    BROWN_BANNER "brown_banner" 16 block block::BROWN_BANNER,
+    GREEN_BANNER "green_banner" 16 block block::GREEN_BANNER,
+    RED_BANNER "red_banner" 16 block block::RED_BANNER,
+    BLACK_BANNER "black_banner" 16 block block::BLACK_BANNER,
+    RED_SANDSTONE "red_sandstone" block block::RED_SANDSTONE,
+    CHISELED_RED_SANDSTONE "chiseled_red_sandstone" block block::CHISELED_RED_SANDSTONE,
+    CUT_RED_SANDSTONE "cut_red_sandstone" block block::CUT_RED_SANDSTONE,
+    RED_SANDSTONE_STAIRS "red_sandstone_stairs" block block::RED_SANDSTONE_STAIRS,
+    OAK_SLAB "oak_slab" block block::OAK_SLAB,
+    SPRUCE_SLAB "spruce_slab" block block::SPRUCE_SLAB,
+    BIRCH_SLAB "birch_slab" block block::BIRCH_SLAB,
+    JUNGLE_SLAB "jungle_slab" block block::JUNGLE_SLAB,
+    ACACIA_SLAB "acacia_slab" block block::ACACIA_SLAB,
+    DARK_OAK_SLAB "dark_oak_slab" block block::DARK_OAK_SLAB,
+    STONE_SLAB "stone_slab" block block::STONE_SLAB,
+    SMOOTH_STONE_SLAB "smooth_stone_slab" block block::SMOOTH_STONE_SLAB,
+    SANDSTONE_SLAB "sandstone_slab" block block::SANDSTONE_SLAB,
+    CUT_SANDSTONE_SLAB "cut_sandstone_slab" block block::CUT_SANDSTONE_SLAB,
+    PETRIFIED_OAK_SLAB "petrified_oak_slab" block block::PETRIFIED_OAK_SLAB,
+    COBBLESTONE_SLAB "cobblestone_slab" block block::COBBLESTONE_SLAB,
+    BRICK_SLAB "brick_slab" block block::BRICK_SLAB,
+    STONE_BRICK_SLAB "stone_brick_slab" block block::STONE_BRICK_SLAB,
+    NETHER_BRICK_SLAB "nether_brick_slab" block block::NETHER_BRICK_SLAB,
+    QUARTZ_SLAB "quartz_slab" block block::QUARTZ_SLAB,
+    RED_SANDSTONE_SLAB "red_sandstone_slab" block block::RED_SANDSTONE_SLAB,
+    CUT_RED_SANDSTONE_SLAB "cut_red_sandstone_slab" block block::CUT_RED_SANDSTONE_SLAB,
+    PURPUR_SLAB "purpur_slab" block block::PURPUR_SLAB,
+    SMOOTH_STONE "smooth_stone" block block::SMOOTH_STONE,
+    SMOOTH_SANDSTONE "smooth_sandstone" block block::SMOOTH_SANDSTONE,
+    SMOOTH_QUARTZ "smooth_quartz" block block::SMOOTH_QUARTZ,
+    SMOOTH_RED_SANDSTONE "smooth_red_sandstone" block block::SMOOTH_RED_SANDSTONE,
+    OAK_FENCE_GATE "oak_fence_gate" block block::OAK_FENCE_GATE,
+    SPRUCE_FENCE_GATE "spruce_fence_gate" block block::SPRUCE_FENCE_GATE,
+    BIRCH_FENCE_GATE "birch_fence_gate" block block::BIRCH_FENCE_GATE,
+    JUNGLE_FENCE_GATE "jungle_fence_gate" block block::JUNGLE_FENCE_GATE,
+    ACACIA_FENCE_GATE "acacia_fence_gate" block block::ACACIA_FENCE_GATE,
+    DARK_OAK_FENCE_GATE "dark_oak_fence_gate" block block::DARK_OAK_FENCE_GATE,
+    OAK_FENCE "oak_fence" block block::OAK_FENCE,
+    SPRUCE_FENCE "spruce_fence" block block::SPRUCE_FENCE,
+    BIRCH_FENCE "birch_fence" block block::BIRCH_FENCE,
+    JUNGLE_FENCE "jungle_fence" block block::JUNGLE_FENCE,
+    ACACIA_FENCE "acacia_fence" block block::ACACIA_FENCE,
+    DARK_OAK_FENCE "dark_oak_fence" block block::DARK_OAK_FENCE,
+    OAK_DOOR "oak_door" block block::OAK_DOOR,
+    SPRUCE_DOOR "spruce_door" block block::SPRUCE_DOOR,
+    BIRCH_DOOR "birch_door" block block::BIRCH_DOOR,
+    JUNGLE_DOOR "jungle_door" block block::JUNGLE_DOOR,
+    ACACIA_DOOR "acacia_door" block block::ACACIA_DOOR,
+    DARK_OAK_DOOR "dark_oak_door" block block::DARK_OAK_DOOR,
+    END_ROD "end_rod" block block::END_ROD,
+    CHORUS_PLANT "chorus_plant" block block::CHORUS_PLANT,
+    CHORUS_FLOWER "chorus_flower" block block::CHORUS_FLOWER,
+    PURPUR_BLOCK "purpur_block" block block::PURPUR_BLOCK,
+    PURPUR_PILLAR "purpur_pillar" block block::PURPUR_PILLAR,
+    PURPUR_STAIRS "purpur_stairs" block block::PURPUR_STAIRS,
+    END_STONE_BRICKS "end_stone_bricks" block block::END_STONE_BRICKS,
+    BEETROOT_SEEDS "beetroot_seeds" block block::BEETROOTS,
+    DIRT_PATH "dirt_path" block block::DIRT_PATH,
+    MAGMA_BLOCK "magma_block" block block::MAGMA_BLOCK,
+    NETHER_WART_BLOCK "nether_wart_block" block block::NETHER_WART_BLOCK,
+    RED_NETHER_BRICKS "red_nether_bricks" block block::RED_NETHER_BRICKS,
+    BONE_BLOCK "bone_block" block block::BONE_BLOCK,
+    STRUCTURE_VOID "structure_void" block block::STRUCTURE_VOID,
+    OBSERVER "observer" block block::OBSERVER,
+    SHULKER_BOX "shulker_box" 1 block block::SHULKER_BOX,
+    WHITE_SHULKER_BOX "white_shulker_box" 1 block block::WHITE_SHULKER_BOX,
+    ORANGE_SHULKER_BOX "orange_shulker_box" 1 block block::ORANGE_SHULKER_BOX,
+    MAGENTA_SHULKER_BOX "magenta_shulker_box" 1 block block::MAGENTA_SHULKER_BOX,
+    LIGHT_BLUE_SHULKER_BOX "light_blue_shulker_box" 1 block block::LIGHT_BLUE_SHULKER_BOX,
+    YELLOW_SHULKER_BOX "yellow_shulker_box" 1 block block::YELLOW_SHULKER_BOX,
+    LIME_SHULKER_BOX "lime_shulker_box" 1 block block::LIME_SHULKER_BOX,
+    PINK_SHULKER_BOX "pink_shulker_box" 1 block block::PINK_SHULKER_BOX,
+    GRAY_SHULKER_BOX "gray_shulker_box" 1 block block::GRAY_SHULKER_BOX,
+    LIGHT_GRAY_SHULKER_BOX "light_gray_shulker_box" 1 block block::LIGHT_GRAY_SHULKER_BOX,
+    CYAN_SHULKER_BOX "cyan_shulker_box" 1 block block::CYAN_SHULKER_BOX,
+    PURPLE_SHULKER_BOX "purple_shulker_box" 1 block block::PURPLE_SHULKER_BOX,
+    BLUE_SHULKER_BOX "blue_shulker_box" 1 block block::BLUE_SHULKER_BOX,
+    BROWN_SHULKER_BOX "brown_shulker_box" 1 block block::BROWN_SHULKER_BOX,
+    GREEN_SHULKER_BOX "green_shulker_box" 1 block block::GREEN_SHULKER_BOX,
+    RED_SHULKER_BOX "red_shulker_box" 1 block block::RED_SHULKER_BOX,
+    BLACK_SHULKER_BOX "black_shulker_box" 1 block block::BLACK_SHULKER_BOX,
+    WHITE_GLAZED_TERRACOTTA "white_glazed_terracotta" block block::WHITE_GLAZED_TERRACOTTA,
+    ORANGE_GLAZED_TERRACOTTA "orange_glazed_terracotta" block block::ORANGE_GLAZED_TERRACOTTA,
+    MAGENTA_GLAZED_TERRACOTTA "magenta_glazed_terracotta" block block::MAGENTA_GLAZED_TERRACOTTA,
+    LIGHT_BLUE_GLAZED_TERRACOTTA "light_blue_glazed_terracotta" block block::LIGHT_BLUE_GLAZED_TERRACOTTA,
+    YELLOW_GLAZED_TERRACOTTA "yellow_glazed_terracotta" block block::YELLOW_GLAZED_TERRACOTTA,
+    LIME_GLAZED_TERRACOTTA "lime_glazed_terracotta" block block::LIME_GLAZED_TERRACOTTA,
+    PINK_GLAZED_TERRACOTTA "pink_glazed_terracotta" block block::PINK_GLAZED_TERRACOTTA,
+    GRAY_GLAZED_TERRACOTTA "gray_glazed_terracotta" block block::GRAY_GLAZED_TERRACOTTA,
+    LIGHT_GRAY_GLAZED_TERRACOTTA "light_gray_glazed_terracotta" block block::LIGHT_GRAY_GLAZED_TERRACOTTA,
+    CYAN_GLAZED_TERRACOTTA "cyan_glazed_terracotta" block block::CYAN_GLAZED_TERRACOTTA,
+    PURPLE_GLAZED_TERRACOTTA "purple_glazed_terracotta" block block::PURPLE_GLAZED_TERRACOTTA,
+    BLUE_GLAZED_TERRACOTTA "blue_glazed_terracotta" block block::BLUE_GLAZED_TERRACOTTA,
+    BROWN_GLAZED_TERRACOTTA "brown_glazed_terracotta" block block::BROWN_GLAZED_TERRACOTTA,
+    GREEN_GLAZED_TERRACOTTA "green_glazed_terracotta" block block::GREEN_GLAZED_TERRACOTTA,
+    RED_GLAZED_TERRACOTTA "red_glazed_terracotta" block block::RED_GLAZED_TERRACOTTA,
+    BLACK_GLAZED_TERRACOTTA "black_glazed_terracotta" block block::BLACK_GLAZED_TERRACOTTA,
+    WHITE_CONCRETE "white_concrete" block block::WHITE_CONCRETE,
+    ORANGE_CONCRETE "orange_concrete" block block::ORANGE_CONCRETE,
+    MAGENTA_CONCRETE "magenta_concrete" block block::MAGENTA_CONCRETE,
+    LIGHT_BLUE_CONCRETE "light_blue_concrete" block block::LIGHT_BLUE_CONCRETE,
+    YELLOW_CONCRETE "yellow_concrete" block block::YELLOW_CONCRETE,
+    LIME_CONCRETE "lime_concrete" block block::LIME_CONCRETE,
+    PINK_CONCRETE "pink_concrete" block block::PINK_CONCRETE,
+    GRAY_CONCRETE "gray_concrete" block block::GRAY_CONCRETE,
+    LIGHT_GRAY_CONCRETE "light_gray_concrete" block block::LIGHT_GRAY_CONCRETE,
+    CYAN_CONCRETE "cyan_concrete" block block::CYAN_CONCRETE,
+    PURPLE_CONCRETE "purple_concrete" block block::PURPLE_CONCRETE,
+    BLUE_CONCRETE "blue_concrete" block block::BLUE_CONCRETE,
+    BROWN_CONCRETE "brown_concrete" block block::BROWN_CONCRETE,
+    GREEN_CONCRETE "green_concrete" block block::GREEN_CONCRETE,
+    RED_CONCRETE "red_concrete" block block::RED_CONCRETE,
+    BLACK_CONCRETE "black_concrete" block block::BLACK_CONCRETE,
+    WHITE_CONCRETE_POWDER "white_concrete_powder" block block::WHITE_CONCRETE_POWDER,
+    ORANGE_CONCRETE_POWDER "orange_concrete_powder" block block::ORANGE_CONCRETE_POWDER,
+    MAGENTA_CONCRETE_POWDER "magenta_concrete_powder" block block::MAGENTA_CONCRETE_POWDER,
+    LIGHT_BLUE_CONCRETE_POWDER "light_blue_concrete_powder" block block::LIGHT_BLUE_CONCRETE_POWDER,
+    YELLOW_CONCRETE_POWDER "yellow_concrete_powder" block block::YELLOW_CONCRETE_POWDER,
+    LIME_CONCRETE_POWDER "lime_concrete_powder" block block::LIME_CONCRETE_POWDER,
+    PINK_CONCRETE_POWDER "pink_concrete_powder" block block::PINK_CONCRETE_POWDER,
+    GRAY_CONCRETE_POWDER "gray_concrete_powder" block block::GRAY_CONCRETE_POWDER,
+    LIGHT_GRAY_CONCRETE_POWDER "light_gray_concrete_powder" block block::LIGHT_GRAY_CONCRETE_POWDER,
+    CYAN_CONCRETE_POWDER "cyan_concrete_powder" block block::CYAN_CONCRETE_POWDER,
+    PURPLE_CONCRETE_POWDER "purple_concrete_powder" block block::PURPLE_CONCRETE_POWDER,
+    BLUE_CONCRETE_POWDER "blue_concrete_powder" block block::BLUE_CONCRETE_POWDER,
+    BROWN_CONCRETE_POWDER "brown_concrete_powder" block block::BROWN_CONCRETE_POWDER,
+    GREEN_CONCRETE_POWDER "green_concrete_powder" block block::GREEN_CONCRETE_POWDER,
+    RED_CONCRETE_POWDER "red_concrete_powder" block block::RED_CONCRETE_POWDER,
+    BLACK_CONCRETE_POWDER "black_concrete_powder" block block::BLACK_CONCRETE_POWDER,
+    KELP "kelp" block block::KELP,
+    DRIED_KELP_BLOCK "dried_kelp_block" block block::DRIED_KELP_BLOCK,
+    TURTLE_EGG "turtle_egg" block block::TURTLE_EGG,
+    TUBE_CORAL_BLOCK "tube_coral_block" block block::TUBE_CORAL_BLOCK,
+    BRAIN_CORAL_BLOCK "brain_coral_block" block block::BRAIN_CORAL_BLOCK,
+    BUBBLE_CORAL_BLOCK "bubble_coral_block" block block::BUBBLE_CORAL_BLOCK,
+    FIRE_CORAL_BLOCK "fire_coral_block" block block::FIRE_CORAL_BLOCK,
+    HORN_CORAL_BLOCK "horn_coral_block" block block::HORN_CORAL_BLOCK,
+    DEAD_TUBE_CORAL_BLOCK "dead_tube_coral_block" block block::DEAD_TUBE_CORAL_BLOCK,
+    DEAD_BRAIN_CORAL_BLOCK "dead_brain_coral_block" block block::DEAD_BRAIN_CORAL_BLOCK,
+    DEAD_BUBBLE_CORAL_BLOCK "dead_bubble_coral_block" block block::DEAD_BUBBLE_CORAL_BLOCK,
+    DEAD_FIRE_CORAL_BLOCK "dead_fire_coral_block" block block::DEAD_FIRE_CORAL_BLOCK,
+    DEAD_HORN_CORAL_BLOCK "dead_horn_coral_block" block block::DEAD_HORN_CORAL_BLOCK,
+    TUBE_CORAL "tube_coral" block block::TUBE_CORAL,
+    BRAIN_CORAL "brain_coral" block block::BRAIN_CORAL,
+    BUBBLE_CORAL "bubble_coral" block block::BUBBLE_CORAL,
+    FIRE_CORAL "fire_coral" block block::FIRE_CORAL,
+    HORN_CORAL "horn_coral" block block::HORN_CORAL,
+    DEAD_TUBE_CORAL "dead_tube_coral" block block::DEAD_TUBE_CORAL,
+    DEAD_BRAIN_CORAL "dead_brain_coral" block block::DEAD_BRAIN_CORAL,
+    DEAD_BUBBLE_CORAL "dead_bubble_coral" block block::DEAD_BUBBLE_CORAL,
+    DEAD_FIRE_CORAL "dead_fire_coral" block block::DEAD_FIRE_CORAL,
+    DEAD_HORN_CORAL "dead_horn_coral" block block::DEAD_HORN_CORAL,
+    TUBE_CORAL_FAN "tube_coral_fan" block block::TUBE_CORAL_FAN,
+    BRAIN_CORAL_FAN "brain_coral_fan" block block::BRAIN_CORAL_FAN,
+    BUBBLE_CORAL_FAN "bubble_coral_fan" block block::BUBBLE_CORAL_FAN,
+    FIRE_CORAL_FAN "fire_coral_fan" block block::FIRE_CORAL_FAN,
+    HORN_CORAL_FAN "horn_coral_fan" block block::HORN_CORAL_FAN,
+    DEAD_TUBE_CORAL_FAN "dead_tube_coral_fan" block block::DEAD_TUBE_CORAL_FAN,
+    DEAD_BRAIN_CORAL_FAN "dead_brain_coral_fan" block block::DEAD_BRAIN_CORAL_FAN,
+    DEAD_BUBBLE_CORAL_FAN "dead_bubble_coral_fan" block block::DEAD_BUBBLE_CORAL_FAN,
+    DEAD_FIRE_CORAL_FAN "dead_fire_coral_fan" block block::DEAD_FIRE_CORAL_FAN,
+    DEAD_HORN_CORAL_FAN "dead_horn_coral_fan" block block::DEAD_HORN_CORAL_FAN,
+    SEA_PICKLE "sea_pickle" block block::SEA_PICKLE,
+    BLUE_ICE "blue_ice" block block::BLUE_ICE,
+    CONDUIT "conduit" block block::CONDUIT,
+    BAMBOO "bamboo" block block::BAMBOO,
+    POLISHED_GRANITE_STAIRS "polished_granite_stairs" block block::POLISHED_GRANITE_STAIRS,
+    SMOOTH_RED_SANDSTONE_STAIRS "smooth_red_sandstone_stairs" block block::SMOOTH_RED_SANDSTONE_STAIRS,
+    MOSSY_STONE_BRICK_STAIRS "mossy_stone_brick_stairs" block block::MOSSY_STONE_BRICK_STAIRS,
+    POLISHED_DIORITE_STAIRS "polished_diorite_stairs" block block::POLISHED_DIORITE_STAIRS,
+    MOSSY_COBBLESTONE_STAIRS "mossy_cobblestone_stairs" block block::MOSSY_COBBLESTONE_STAIRS,
+    END_STONE_BRICK_STAIRS "end_stone_brick_stairs" block block::END_STONE_BRICK_STAIRS,
+    STONE_STAIRS "stone_stairs" block block::STONE_STAIRS,
+    SMOOTH_SANDSTONE_STAIRS "smooth_sandstone_stairs" block block::SMOOTH_SANDSTONE_STAIRS,
+    SMOOTH_QUARTZ_STAIRS "smooth_quartz_stairs" block block::SMOOTH_QUARTZ_STAIRS,
+    GRANITE_STAIRS "granite_stairs" block block::GRANITE_STAIRS,
+    ANDESITE_STAIRS "andesite_stairs" block block::ANDESITE_STAIRS,
+    RED_NETHER_BRICK_STAIRS "red_nether_brick_stairs" block block::RED_NETHER_BRICK_STAIRS,
+    POLISHED_ANDESITE_STAIRS "polished_andesite_stairs" block block::POLISHED_ANDESITE_STAIRS,
+    DIORITE_STAIRS "diorite_stairs" block block::DIORITE_STAIRS,
+    POLISHED_GRANITE_SLAB "polished_granite_slab" block block::POLISHED_GRANITE_SLAB,
+    SMOOTH_RED_SANDSTONE_SLAB "smooth_red_sandstone_slab" block block::SMOOTH_RED_SANDSTONE_SLAB,
+    MOSSY_STONE_BRICK_SLAB "mossy_stone_brick_slab" block block::MOSSY_STONE_BRICK_SLAB,
+    POLISHED_DIORITE_SLAB "polished_diorite_slab" block block::POLISHED_DIORITE_SLAB,
+    MOSSY_COBBLESTONE_SLAB "mossy_cobblestone_slab" block block::MOSSY_COBBLESTONE_SLAB,
+    END_STONE_BRICK_SLAB "end_stone_brick_slab" block block::END_STONE_BRICK_SLAB,
+    SMOOTH_SANDSTONE_SLAB "smooth_sandstone_slab" block block::SMOOTH_SANDSTONE_SLAB,
+    SMOOTH_QUARTZ_SLAB "smooth_quartz_slab" block block::SMOOTH_QUARTZ_SLAB,
+    GRANITE_SLAB "granite_slab" block block::GRANITE_SLAB,
+    ANDESITE_SLAB "andesite_slab" block block::ANDESITE_SLAB,
+    RED_NETHER_BRICK_SLAB "red_nether_brick_slab" block block::RED_NETHER_BRICK_SLAB,
+    POLISHED_ANDESITE_SLAB "polished_andesite_slab" block block::POLISHED_ANDESITE_SLAB,
+    DIORITE_SLAB "diorite_slab" block block::DIORITE_SLAB,
+    BRICK_WALL "brick_wall" block block::BRICK_WALL,
+    PRISMARINE_WALL "prismarine_wall" block block::PRISMARINE_WALL,
+    RED_SANDSTONE_WALL "red_sandstone_wall" block block::RED_SANDSTONE_WALL,
+    MOSSY_STONE_BRICK_WALL "mossy_stone_brick_wall" block block::MOSSY_STONE_BRICK_WALL,
+    GRANITE_WALL "granite_wall" block block::GRANITE_WALL,
+    STONE_BRICK_WALL "stone_brick_wall" block block::STONE_BRICK_WALL,
+    NETHER_BRICK_WALL "nether_brick_wall" block block::NETHER_BRICK_WALL,
+    ANDESITE_WALL "andesite_wall" block block::ANDESITE_WALL,
+    RED_NETHER_BRICK_WALL "red_nether_brick_wall" block block::RED_NETHER_BRICK_WALL,
+    SANDSTONE_WALL "sandstone_wall" block block::SANDSTONE_WALL,
+    END_STONE_BRICK_WALL "end_stone_brick_wall" block block::END_STONE_BRICK_WALL,
+    DIORITE_WALL "diorite_wall" block block::DIORITE_WALL,
+    SCAFFOLDING "scaffolding" block block::SCAFFOLDING,
+    LOOM "loom" block block::LOOM,
+    BARREL "barrel" block block::BARREL,
+    SMOKER "smoker" block block::SMOKER,
+    BLAST_FURNACE "blast_furnace" block block::BLAST_FURNACE,
+    CARTOGRAPHY_TABLE "cartography_table" block block::CARTOGRAPHY_TABLE,
+    FLETCHING_TABLE "fletching_table" block block::FLETCHING_TABLE,
+    GRINDSTONE "grindstone" block block::GRINDSTONE,
+    LECTERN "lectern" block block::LECTERN,
+    SMITHING_TABLE "smithing_table" block block::SMITHING_TABLE,
+    STONECUTTER "stonecutter" block block::STONECUTTER,
+    BELL "bell" block block::BELL,
+    LANTERN "lantern" block block::LANTERN,
+    SOUL_LANTERN "soul_lantern" block block::SOUL_LANTERN,
+    CAMPFIRE "campfire" block block::CAMPFIRE,
+    SOUL_CAMPFIRE "soul_campfire" block block::SOUL_CAMPFIRE,
+    SWEET_BERRIES "sweet_berries" block block::SWEET_BERRY_BUSH,
+    WARPED_STEM "warped_stem" block block::WARPED_STEM,
+    STRIPPED_WARPED_STEM "stripped_warped_stem" block block::STRIPPED_WARPED_STEM,
+    WARPED_HYPHAE "warped_hyphae" block block::WARPED_HYPHAE,
+    STRIPPED_WARPED_HYPHAE "stripped_warped_hyphae" block block::STRIPPED_WARPED_HYPHAE,
+    WARPED_NYLIUM "warped_nylium" block block::WARPED_NYLIUM,
+    WARPED_FUNGUS "warped_fungus" block block::WARPED_FUNGUS,
+    WARPED_WART_BLOCK "warped_wart_block" block block::WARPED_WART_BLOCK,
+    WARPED_ROOTS "warped_roots" block block::WARPED_ROOTS,
+    CRIMSON_STEM "crimson_stem" block block::CRIMSON_STEM,
+    STRIPPED_CRIMSON_STEM "stripped_crimson_stem" block block::STRIPPED_CRIMSON_STEM,
+    CRIMSON_HYPHAE "crimson_hyphae" block block::CRIMSON_HYPHAE,
+    STRIPPED_CRIMSON_HYPHAE "stripped_crimson_hyphae" block block::STRIPPED_CRIMSON_HYPHAE,
+    CRIMSON_NYLIUM "crimson_nylium" block block::CRIMSON_NYLIUM,
+    CRIMSON_FUNGUS "crimson_fungus" block block::CRIMSON_FUNGUS,
+    CRIMSON_ROOTS "crimson_roots" block block::CRIMSON_ROOTS,
+    NETHER_SPROUTS "nether_sprouts" block block::NETHER_SPROUTS,
+    SHROOMLIGHT "shroomlight" block block::SHROOMLIGHT,
+    WEEPING_VINES "weeping_vines" block block::WEEPING_VINES,
+    TWISTING_VINES "twisting_vines" block block::TWISTING_VINES,
+    CRIMSON_PLANKS "crimson_planks" block block::CRIMSON_PLANKS,
+    WARPED_PLANKS "warped_planks" block block::WARPED_PLANKS,
+    CRIMSON_SLAB "crimson_slab" block block::CRIMSON_SLAB,
+    WARPED_SLAB "warped_slab" block block::WARPED_SLAB,
+    CRIMSON_PRESSURE_PLATE "crimson_pressure_plate" block block::CRIMSON_PRESSURE_PLATE,
+    WARPED_PRESSURE_PLATE "warped_pressure_plate" block block::WARPED_PRESSURE_PLATE,
+    CRIMSON_FENCE "crimson_fence" block block::CRIMSON_FENCE,
+    WARPED_FENCE "warped_fence" block block::WARPED_FENCE,
+    CRIMSON_TRAPDOOR "crimson_trapdoor" block block::CRIMSON_TRAPDOOR,
+    WARPED_TRAPDOOR "warped_trapdoor" block block::WARPED_TRAPDOOR,
+    CRIMSON_FENCE_GATE "crimson_fence_gate" block block::CRIMSON_FENCE_GATE,
+    WARPED_FENCE_GATE "warped_fence_gate" block block::WARPED_FENCE_GATE,
+    CRIMSON_STAIRS "crimson_stairs" block block::CRIMSON_STAIRS,
+    WARPED_STAIRS "warped_stairs" block block::WARPED_STAIRS,
+    CRIMSON_BUTTON "crimson_button" block block::CRIMSON_BUTTON,
+    WARPED_BUTTON "warped_button" block block::WARPED_BUTTON,
+    CRIMSON_DOOR "crimson_door" block block::CRIMSON_DOOR,
+    WARPED_DOOR "warped_door" block block::WARPED_DOOR,
+    CRIMSON_SIGN "crimson_sign" 16 block block::CRIMSON_SIGN,
+    WARPED_SIGN "warped_sign" 16 block block::WARPED_SIGN,
+    STRUCTURE_BLOCK "structure_block" block block::STRUCTURE_BLOCK,
+    JIGSAW "jigsaw" block block::JIGSAW,
+    COMPOSTER "composter" block block::COMPOSTER,
+    TARGET "target" block block::TARGET,
+    BEE_NEST "bee_nest" block block::BEE_NEST,
+    BEEHIVE "beehive" block block::BEEHIVE,
+    HONEY_BLOCK "honey_block" block block::HONEY_BLOCK,
+    HONEYCOMB_BLOCK "honeycomb_block" block block::HONEYCOMB_BLOCK,
+    NETHERITE_BLOCK "netherite_block" block block::NETHERITE_BLOCK,
+    ANCIENT_DEBRIS "ancient_debris" block block::ANCIENT_DEBRIS,
+    CRYING_OBSIDIAN "crying_obsidian" block block::CRYING_OBSIDIAN,
+    RESPAWN_ANCHOR "respawn_anchor" block block::RESPAWN_ANCHOR,
+    LODESTONE "lodestone" block block::LODESTONE,
+    BLACKSTONE "blackstone" block block::BLACKSTONE,
+    BLACKSTONE_STAIRS "blackstone_stairs" block block::BLACKSTONE_STAIRS,
+    BLACKSTONE_WALL "blackstone_wall" block block::BLACKSTONE_WALL,
+    BLACKSTONE_SLAB "blackstone_slab" block block::BLACKSTONE_SLAB,
+    POLISHED_BLACKSTONE "polished_blackstone" block block::POLISHED_BLACKSTONE,
+    POLISHED_BLACKSTONE_BRICKS "polished_blackstone_bricks" block block::POLISHED_BLACKSTONE_BRICKS,
+    CRACKED_POLISHED_BLACKSTONE_BRICKS "cracked_polished_blackstone_bricks" block block::CRACKED_POLISHED_BLACKSTONE_BRICKS,
+    CHISELED_POLISHED_BLACKSTONE "chiseled_polished_blackstone" block block::CHISELED_POLISHED_BLACKSTONE,
+    POLISHED_BLACKSTONE_BRICK_SLAB "polished_blackstone_brick_slab" block block::POLISHED_BLACKSTONE_BRICK_SLAB,
+    POLISHED_BLACKSTONE_BRICK_STAIRS "polished_blackstone_brick_stairs" block block::POLISHED_BLACKSTONE_BRICK_STAIRS,
+    POLISHED_BLACKSTONE_BRICK_WALL "polished_blackstone_brick_wall" block block::POLISHED_BLACKSTONE_BRICK_WALL,
+    GILDED_BLACKSTONE "gilded_blackstone" block block::GILDED_BLACKSTONE,
+    POLISHED_BLACKSTONE_STAIRS "polished_blackstone_stairs" block block::POLISHED_BLACKSTONE_STAIRS,
+    POLISHED_BLACKSTONE_SLAB "polished_blackstone_slab" block block::POLISHED_BLACKSTONE_SLAB,
+    POLISHED_BLACKSTONE_PRESSURE_PLATE "polished_blackstone_pressure_plate" block block::POLISHED_BLACKSTONE_PRESSURE_PLATE,
+    POLISHED_BLACKSTONE_BUTTON "polished_blackstone_button" block block::POLISHED_BLACKSTONE_BUTTON,
+    POLISHED_BLACKSTONE_WALL "polished_blackstone_wall" block block::POLISHED_BLACKSTONE_WALL,
+    CHISELED_NETHER_BRICKS "chiseled_nether_bricks" block block::CHISELED_NETHER_BRICKS,
+    CRACKED_NETHER_BRICKS "cracked_nether_bricks" block block::CRACKED_NETHER_BRICKS,
+    QUARTZ_BRICKS "quartz_bricks" block block::QUARTZ_BRICKS,
+    CANDLE "candle" block block::CANDLE,
+    WHITE_CANDLE "white_candle" block block::WHITE_CANDLE,
+    ORANGE_CANDLE "orange_candle" block block::ORANGE_CANDLE,
+    MAGENTA_CANDLE "magenta_candle" block block::MAGENTA_CANDLE,
+    LIGHT_BLUE_CANDLE "light_blue_candle" block block::LIGHT_BLUE_CANDLE,
+    YELLOW_CANDLE "yellow_candle" block block::YELLOW_CANDLE,
+    LIME_CANDLE "lime_candle" block block::LIME_CANDLE,
+    PINK_CANDLE "pink_candle" block block::PINK_CANDLE,
+    GRAY_CANDLE "gray_candle" block block::GRAY_CANDLE,
+    LIGHT_GRAY_CANDLE "light_gray_candle" block block::LIGHT_GRAY_CANDLE,
+    CYAN_CANDLE "cyan_candle" block block::CYAN_CANDLE,
+    PURPLE_CANDLE "purple_candle" block block::PURPLE_CANDLE,
+    BLUE_CANDLE "blue_candle" block block::BLUE_CANDLE,
+    BROWN_CANDLE "brown_candle" block block::BROWN_CANDLE,
+    GREEN_CANDLE "green_candle" block block::GREEN_CANDLE,
+    RED_CANDLE "red_candle" block block::RED_CANDLE,
+    BLACK_CANDLE "black_candle" block block::BLACK_CANDLE,
+    AMETHYST_BLOCK "amethyst_block" block block::AMETHYST_BLOCK,
+    BUDDING_AMETHYST "budding_amethyst" block block::BUDDING_AMETHYST,
+    AMETHYST_CLUSTER "amethyst_cluster" block block::AMETHYST_CLUSTER,
+    LARGE_AMETHYST_BUD "large_amethyst_bud" block block::LARGE_AMETHYST_BUD,
+    MEDIUM_AMETHYST_BUD "medium_amethyst_bud" block block::MEDIUM_AMETHYST_BUD,
+    SMALL_AMETHYST_BUD "small_amethyst_bud" block block::SMALL_AMETHYST_BUD,
+    TUFF "tuff" block block::TUFF,
+    CALCITE "calcite" block block::CALCITE,
+    TINTED_GLASS "tinted_glass" block block::TINTED_GLASS,
+    POWDER_SNOW_BUCKET "powder_snow_bucket" 1 block block::POWDER_SNOW,
+    SCULK_SENSOR "sculk_sensor" block block::SCULK_SENSOR,
+    COPPER_ORE "copper_ore" block block::COPPER_ORE,
+    DEEPSLATE_COPPER_ORE "deepslate_copper_ore" block block::DEEPSLATE_COPPER_ORE,
+    COPPER_BLOCK "copper_block" block block::COPPER_BLOCK,
+    EXPOSED_COPPER "exposed_copper" block block::EXPOSED_COPPER_BLOCK,
+    WEATHERED_COPPER "weathered_copper" block block::WEATHERED_COPPER_BLOCK,
+    OXIDIZED_COPPER "oxidized_copper" block block::OXIDIZED_COPPER_BLOCK,
+    CUT_COPPER "cut_copper" block block::CUT_COPPER,
+    EXPOSED_CUT_COPPER "exposed_cut_copper" block block::EXPOSED_CUT_COPPER,
+    WEATHERED_CUT_COPPER "weathered_cut_copper" block block::WEATHERED_CUT_COPPER,
+    OXIDIZED_CUT_COPPER "oxidized_cut_copper" block block::OXIDIZED_CUT_COPPER,
+    CUT_COPPER_STAIRS "cut_copper_stairs" block block::CUT_COPPER_STAIRS,
+    EXPOSED_CUT_COPPER_STAIRS "exposed_cut_copper_stairs" block block::EXPOSED_CUT_COPPER_STAIRS,
+    WEATHERED_CUT_COPPER_STAIRS "weathered_cut_copper_stairs" block block::WEATHERED_CUT_COPPER_STAIRS,
+    OXIDIZED_CUT_COPPER_STAIRS "oxidized_cut_copper_stairs" block block::OXIDIZED_CUT_COPPER_STAIRS,
+    CUT_COPPER_SLAB "cut_copper_slab" block block::CUT_COPPER_SLAB,
+    EXPOSED_CUT_COPPER_SLAB "exposed_cut_copper_slab" block block::EXPOSED_CUT_COPPER_SLAB,
+    WEATHERED_CUT_COPPER_SLAB "weathered_cut_copper_slab" block block::WEATHERED_CUT_COPPER_SLAB,
+    OXIDIZED_CUT_COPPER_SLAB "oxidized_cut_copper_slab" block block::OXIDIZED_CUT_COPPER_SLAB,
+    WAXED_COPPER_BLOCK "waxed_copper_block" block block::WAXED_COPPER_BLOCK,
+    WAXED_EXPOSED_COPPER "waxed_exposed_copper" block block::WAXED_EXPOSED_COPPER_BLOCK,
+    WAXED_WEATHERED_COPPER "waxed_weathered_copper" block block::WAXED_WEATHERED_COPPER_BLOCK,
+    WAXED_OXIDIZED_COPPER "waxed_oxidized_copper" block block::WAXED_OXIDIZED_COPPER_BLOCK,
+    WAXED_CUT_COPPER "waxed_cut_copper" block block::WAXED_CUT_COPPER,
+    WAXED_EXPOSED_CUT_COPPER "waxed_exposed_cut_copper" block block::WAXED_EXPOSED_CUT_COPPER,
+    WAXED_WEATHERED_CUT_COPPER "waxed_weathered_cut_copper" block block::WAXED_WEATHERED_CUT_COPPER,
+    WAXED_OXIDIZED_CUT_COPPER "waxed_oxidized_cut_copper" block block::WAXED_OXIDIZED_CUT_COPPER,
+    WAXED_CUT_COPPER_STAIRS "waxed_cut_copper_stairs" block block::WAXED_CUT_COPPER_STAIRS,
+    WAXED_EXPOSED_CUT_COPPER_STAIRS "waxed_exposed_cut_copper_stairs" block block::WAXED_EXPOSED_CUT_COPPER_STAIRS,
+    WAXED_WEATHERED_CUT_COPPER_STAIRS "waxed_weathered_cut_copper_stairs" block block::WAXED_WEATHERED_CUT_COPPER_STAIRS,
+    WAXED_OXIDIZED_CUT_COPPER_STAIRS "waxed_oxidized_cut_copper_stairs" block block::WAXED_OXIDIZED_CUT_COPPER_STAIRS,
+    WAXED_CUT_COPPER_SLAB "waxed_cut_copper_slab" block block::WAXED_CUT_COPPER_SLAB,
+    WAXED_EXPOSED_CUT_COPPER_SLAB "waxed_exposed_cut_copper_slab" block block::WAXED_EXPOSED_CUT_COPPER_SLAB,
+    WAXED_WEATHERED_CUT_COPPER_SLAB "waxed_weathered_cut_copper_slab" block block::WAXED_WEATHERED_CUT_COPPER_SLAB,
+    WAXED_OXIDIZED_CUT_COPPER_SLAB "waxed_oxidized_cut_copper_slab" block block::WAXED_OXIDIZED_CUT_COPPER_SLAB,
+    LIGHTNING_ROD "lightning_rod" block block::LIGHTNING_ROD,
+    POINTED_DRIPSTONE "pointed_dripstone" block block::POINTED_DRIPSTONE,
+    DRIPSTONE_BLOCK "dripstone_block" block block::DRIPSTONE_BLOCK,
+    GLOW_BERRIES "glow_berries" block block::CAVE_VINES,
+    SPORE_BLOSSOM "spore_blossom" block block::SPORE_BLOSSOM,
+    AZALEA "azalea" block block::AZALEA,
+    FLOWERING_AZALEA "flowering_azalea" block block::FLOWERING_AZALEA,
+    MOSS_CARPET "moss_carpet" block block::MOSS_CARPET,
+    MOSS_BLOCK "moss_block" block block::MOSS_BLOCK,
+    BIG_DRIPLEAF "big_dripleaf" block block::BIG_DRIPLEAF,
+    SMALL_DRIPLEAF "small_dripleaf" block block::SMALL_DRIPLEAF,
+    HANGING_ROOTS "hanging_roots" block block::HANGING_ROOTS,
+    ROOTED_DIRT "rooted_dirt" block block::ROOTED_DIRT,
+    DEEPSLATE "deepslate" block block::DEEPSLATE,
+    COBBLED_DEEPSLATE "cobbled_deepslate" block block::COBBLED_DEEPSLATE,
+    COBBLED_DEEPSLATE_STAIRS "cobbled_deepslate_stairs" block block::COBBLED_DEEPSLATE_STAIRS,
+    COBBLED_DEEPSLATE_SLAB "cobbled_deepslate_slab" block block::COBBLED_DEEPSLATE_SLAB,
+    COBBLED_DEEPSLATE_WALL "cobbled_deepslate_wall" block block::COBBLED_DEEPSLATE_WALL,
+    POLISHED_DEEPSLATE "polished_deepslate" block block::POLISHED_DEEPSLATE,
+    POLISHED_DEEPSLATE_STAIRS "polished_deepslate_stairs" block block::POLISHED_DEEPSLATE_STAIRS,
+    POLISHED_DEEPSLATE_SLAB "polished_deepslate_slab" block block::POLISHED_DEEPSLATE_SLAB,
+    POLISHED_DEEPSLATE_WALL "polished_deepslate_wall" block block::POLISHED_DEEPSLATE_WALL,
+    DEEPSLATE_TILES "deepslate_tiles" block block::DEEPSLATE_TILES,
+    DEEPSLATE_TILE_STAIRS "deepslate_tile_stairs" block block::DEEPSLATE_TILE_STAIRS,
+    DEEPSLATE_TILE_SLAB "deepslate_tile_slab" block block::DEEPSLATE_TILE_SLAB,
+    DEEPSLATE_TILE_WALL "deepslate_tile_wall" block block::DEEPSLATE_TILE_WALL,
+    DEEPSLATE_BRICKS "deepslate_bricks" block block::DEEPSLATE_BRICKS,
+    DEEPSLATE_BRICK_STAIRS "deepslate_brick_stairs" block block::DEEPSLATE_BRICK_STAIRS,
+    DEEPSLATE_BRICK_SLAB "deepslate_brick_slab" block block::DEEPSLATE_BRICK_SLAB,
+    DEEPSLATE_BRICK_WALL "deepslate_brick_wall" block block::DEEPSLATE_BRICK_WALL,
+    CHISELED_DEEPSLATE "chiseled_deepslate" block block::CHISELED_DEEPSLATE,
+    CRACKED_DEEPSLATE_BRICKS "cracked_deepslate_bricks" block block::CRACKED_DEEPSLATE_BRICKS,
+    CRACKED_DEEPSLATE_TILES "cracked_deepslate_tiles" block block::CRACKED_DEEPSLATE_TILES,
+    INFESTED_DEEPSLATE "infested_deepslate" block block::INFESTED_DEEPSLATE,
+    SMOOTH_BASALT "smooth_basalt" block block::SMOOTH_BASALT,
+    RAW_IRON_BLOCK "raw_iron_block" block block::RAW_IRON_BLOCK,
+    RAW_COPPER_BLOCK "raw_copper_block" block block::RAW_COPPER_BLOCK,
+    RAW_GOLD_BLOCK "raw_gold_block" block block::RAW_GOLD_BLOCK,
+    SCUTE "scute",
+    FLINT_AND_STEEL "flint_and_steel" 1 damage 64,
+    APPLE "apple",
+    BOW "bow" 1 damage 384,
+    ARROW "arrow",
+    COAL "coal",
+    CHARCOAL "charcoal",
+    DIAMOND "diamond",
+    EMERALD "emerald",
+    LAPIS_LAZULI "lapis_lazuli",
+    QUARTZ "quartz",
+    AMETHYST_SHARD "amethyst_shard",
+    RAW_IRON "raw_iron",
+    IRON_INGOT "iron_ingot",
+    RAW_COPPER "raw_copper",
+    COPPER_INGOT "copper_ingot",
+    RAW_GOLD "raw_gold",
+    GOLD_INGOT "gold_ingot",
+    NETHERITE_INGOT "netherite_ingot",
+    NETHERITE_SCRAP "netherite_scrap",
+    WOODEN_SWORD "wooden_sword" 1 damage 59,
+    WOODEN_SHOVEL "wooden_shovel" 1 damage 59,
+    WOODEN_PICKAXE "wooden_pickaxe" 1 damage 59,
+    WOODEN_AXE "wooden_axe" 1 damage 59,
+    WOODEN_HOE "wooden_hoe" 1 damage 59,
+    STONE_SWORD "stone_sword" 1 damage 131,
+    STONE_SHOVEL "stone_shovel" 1 damage 131,
+    STONE_PICKAXE "stone_pickaxe" 1 damage 131,
+    STONE_AXE "stone_axe" 1 damage 131,
+    STONE_HOE "stone_hoe" 1 damage 131,
+    GOLDEN_SWORD "golden_sword" 1 damage 32,
+    GOLDEN_SHOVEL "golden_shovel" 1 damage 32,
+    GOLDEN_PICKAXE "golden_pickaxe" 1 damage 32,
+    GOLDEN_AXE "golden_axe" 1 damage 32,
+    GOLDEN_HOE "golden_hoe" 1 damage 32,
+    IRON_SWORD "iron_sword" 1 damage 250,
+    IRON_SHOVEL "iron_shovel" 1 damage 250,
+    IRON_PICKAXE "iron_pickaxe" 1 damage 250,
+    IRON_AXE "iron_axe" 1 damage 250,
+    IRON_HOE "iron_hoe" 1 damage 250,
+    DIAMOND_SWORD "diamond_sword" 1 damage 1561,
+    DIAMOND_SHOVEL "diamond_shovel" 1 damage 1561,
+    DIAMOND_PICKAXE "diamond_pickaxe" 1 damage 1561,
+    DIAMOND_AXE "diamond_axe" 1 damage 1561,
+    DIAMOND_HOE "diamond_hoe" 1 damage 1561,
+    NETHERITE_SWORD "netherite_sword" 1 damage 2031,
+    NETHERITE_SHOVEL "netherite_shovel" 1 damage 2031,
+    NETHERITE_PICKAXE "netherite_pickaxe" 1 damage 2031,
+    NETHERITE_AXE "netherite_axe" 1 damage 2031,
+    NETHERITE_HOE "netherite_hoe" 1 damage 2031,
+    STICK "stick",
+    BOWL "bowl",
+    MUSHROOM_STEW "mushroom_stew" 1,
+    FEATHER "feather",
+    GUNPOWDER "gunpowder",
+    WHEAT "wheat",
+    BREAD "bread",
+    LEATHER_HELMET "leather_helmet" 1 damage 55,
+    LEATHER_CHESTPLATE "leather_chestplate" 1 damage 80,
+    LEATHER_LEGGINGS "leather_leggings" 1 damage 75,
+    LEATHER_BOOTS "leather_boots" 1 damage 65,
+    CHAINMAIL_HELMET "chainmail_helmet" 1 damage 165,
+    CHAINMAIL_CHESTPLATE "chainmail_chestplate" 1 damage 240,
+    CHAINMAIL_LEGGINGS "chainmail_leggings" 1 damage 225,
+    CHAINMAIL_BOOTS "chainmail_boots" 1 damage 195,
+    IRON_HELMET "iron_helmet" 1 damage 165,
+    IRON_CHESTPLATE "iron_chestplate" 1 damage 240,
+    IRON_LEGGINGS "iron_leggings" 1 damage 225,
+    IRON_BOOTS "iron_boots" 1 damage 195,
+    DIAMOND_HELMET "diamond_helmet" 1 damage 363,
+    DIAMOND_CHESTPLATE "diamond_chestplate" 1 damage 528,
+    DIAMOND_LEGGINGS "diamond_leggings" 1 damage 495,
+    DIAMOND_BOOTS "diamond_boots" 1 damage 429,
+    GOLDEN_HELMET "golden_helmet" 1 damage 77,
+    GOLDEN_CHESTPLATE "golden_chestplate" 1 damage 112,
+    GOLDEN_LEGGINGS "golden_leggings" 1 damage 105,
+    GOLDEN_BOOTS "golden_boots" 1 damage 91,
+    NETHERITE_HELMET "netherite_helmet" 1 damage 407,
+    NETHERITE_CHESTPLATE "netherite_chestplate" 1 damage 592,
+    NETHERITE_LEGGINGS "netherite_leggings" 1 damage 555,
+    NETHERITE_BOOTS "netherite_boots" 1 damage 481,
+    TURTLE_HELMET "turtle_helmet" 1 damage 275,
+    FLINT "flint",
+    PORKCHOP "porkchop",
+    COOKED_PORKCHOP "cooked_porkchop",
+    PAINTING "painting",
+    GOLDEN_APPLE "golden_apple",
+    ENCHANTED_GOLDEN_APPLE "enchanted_golden_apple",
+    BUCKET "bucket" 16,
+    WATER_BUCKET "water_bucket" 1,
+    LAVA_BUCKET "lava_bucket" 1,
+    MINECART "minecart" 1,
+    SADDLE "saddle" 1,
+    SNOWBALL "snowball" 16,
+    OAK_BOAT "oak_boat" 1,
+    SPRUCE_BOAT "spruce_boat" 1,
+    BIRCH_BOAT "birch_boat" 1,
+    JUNGLE_BOAT "jungle_boat" 1,
+    ACACIA_BOAT "acacia_boat" 1,
+    DARK_OAK_BOAT "dark_oak_boat" 1,
+    LEATHER "leather",
+    MILK_BUCKET "milk_bucket" 1,
+    PUFFERFISH_BUCKET "pufferfish_bucket" 1,
+    SALMON_BUCKET "salmon_bucket" 1,
+    COD_BUCKET "cod_bucket" 1,
+    TROPICAL_FISH_BUCKET "tropical_fish_bucket" 1,
+    AXOLOTL_BUCKET "axolotl_bucket" 1,
+    BRICK "brick",
+    CLAY_BALL "clay_ball",
+    DRIED_KELP "dried_kelp",
+    PAPER "paper",
+    BOOK "book",
+    SLIME_BALL "slime_ball",
+    CHEST_MINECART "chest_minecart" 1,
+    FURNACE_MINECART "furnace_minecart" 1,
+    EGG "egg" 16,
+    COMPASS "compass",
+    BUNDLE "bundle" 1,
+    FISHING_ROD "fishing_rod" 1 damage 64,
+    CLOCK "clock",
+    SPYGLASS "spyglass" 1,
+    GLOWSTONE_DUST "glowstone_dust",
+    COD "cod",
+    SALMON "salmon",
+    TROPICAL_FISH "tropical_fish",
+    PUFFERFISH "pufferfish",
+    COOKED_COD "cooked_cod",
+    COOKED_SALMON "cooked_salmon",
+    INK_SAC "ink_sac",
+    GLOW_INK_SAC "glow_ink_sac",
+    WHITE_DYE "white_dye",
+    ORANGE_DYE "orange_dye",
+    MAGENTA_DYE "magenta_dye",
+    LIGHT_BLUE_DYE "light_blue_dye",
+    YELLOW_DYE "yellow_dye",
+    LIME_DYE "lime_dye",
+    PINK_DYE "pink_dye",
+    GRAY_DYE "gray_dye",
+    LIGHT_GRAY_DYE "light_gray_dye",
+    CYAN_DYE "cyan_dye",
+    PURPLE_DYE "purple_dye",
+    BLUE_DYE "blue_dye",
+    BROWN_DYE "brown_dye",
+    GREEN_DYE "green_dye",
+    RED_DYE "red_dye",
+    BLACK_DYE "black_dye",
+    BONE_MEAL "bone_meal",
+    BONE "bone",
+    SUGAR "sugar",
+    COOKIE "cookie",
+    FILLED_MAP "filled_map",
+    SHEARS "shears" 1 damage 238,
+    MELON_SLICE "melon_slice",
+    BEEF "beef",
+    COOKED_BEEF "cooked_beef",
+    CHICKEN "chicken",
+    COOKED_CHICKEN "cooked_chicken",
+    ROTTEN_FLESH "rotten_flesh",
+    ENDER_PEARL "ender_pearl" 16,
+    BLAZE_ROD "blaze_rod",
+    GHAST_TEAR "ghast_tear",
+    GOLD_NUGGET "gold_nugget",
+    POTION "potion" 1,
+    GLASS_BOTTLE "glass_bottle",
+    SPIDER_EYE "spider_eye",
+    FERMENTED_SPIDER_EYE "fermented_spider_eye",
+    BLAZE_POWDER "blaze_powder",
+    MAGMA_CREAM "magma_cream",
+    ENDER_EYE "ender_eye",
+    GLISTERING_MELON_SLICE "glistering_melon_slice",
+    AXOLOTL_SPAWN_EGG "axolotl_spawn_egg",
+    BAT_SPAWN_EGG "bat_spawn_egg",
+    BEE_SPAWN_EGG "bee_spawn_egg",
+    BLAZE_SPAWN_EGG "blaze_spawn_egg",
+    CAT_SPAWN_EGG "cat_spawn_egg",
+    CAVE_SPIDER_SPAWN_EGG "cave_spider_spawn_egg",
+    CHICKEN_SPAWN_EGG "chicken_spawn_egg",
+    COD_SPAWN_EGG "cod_spawn_egg",
+    COW_SPAWN_EGG "cow_spawn_egg",
+    CREEPER_SPAWN_EGG "creeper_spawn_egg",
+    DOLPHIN_SPAWN_EGG "dolphin_spawn_egg",
+    DONKEY_SPAWN_EGG "donkey_spawn_egg",
+    DROWNED_SPAWN_EGG "drowned_spawn_egg",
+    ELDER_GUARDIAN_SPAWN_EGG "elder_guardian_spawn_egg",
+    ENDERMAN_SPAWN_EGG "enderman_spawn_egg",
+    ENDERMITE_SPAWN_EGG "endermite_spawn_egg",
+    EVOKER_SPAWN_EGG "evoker_spawn_egg",
+    FOX_SPAWN_EGG "fox_spawn_egg",
+    GHAST_SPAWN_EGG "ghast_spawn_egg",
+    GLOW_SQUID_SPAWN_EGG "glow_squid_spawn_egg",
+    GOAT_SPAWN_EGG "goat_spawn_egg",
+    GUARDIAN_SPAWN_EGG "guardian_spawn_egg",
+    HOGLIN_SPAWN_EGG "hoglin_spawn_egg",
+    HORSE_SPAWN_EGG "horse_spawn_egg",
+    HUSK_SPAWN_EGG "husk_spawn_egg",
+    LLAMA_SPAWN_EGG "llama_spawn_egg",
+    MAGMA_CUBE_SPAWN_EGG "magma_cube_spawn_egg",
+    MOOSHROOM_SPAWN_EGG "mooshroom_spawn_egg",
+    MULE_SPAWN_EGG "mule_spawn_egg",
+    OCELOT_SPAWN_EGG "ocelot_spawn_egg",
+    PANDA_SPAWN_EGG "panda_spawn_egg",
+    PARROT_SPAWN_EGG "parrot_spawn_egg",
+    PHANTOM_SPAWN_EGG "phantom_spawn_egg",
+    PIG_SPAWN_EGG "pig_spawn_egg",
+    PIGLIN_SPAWN_EGG "piglin_spawn_egg",
+    PIGLIN_BRUTE_SPAWN_EGG "piglin_brute_spawn_egg",
+    PILLAGER_SPAWN_EGG "pillager_spawn_egg",
+    POLAR_BEAR_SPAWN_EGG "polar_bear_spawn_egg",
+    PUFFERFISH_SPAWN_EGG "pufferfish_spawn_egg",
+    RABBIT_SPAWN_EGG "rabbit_spawn_egg",
+    RAVAGER_SPAWN_EGG "ravager_spawn_egg",
+    SALMON_SPAWN_EGG "salmon_spawn_egg",
+    SHEEP_SPAWN_EGG "sheep_spawn_egg",
+    SHULKER_SPAWN_EGG "shulker_spawn_egg",
+    SILVERFISH_SPAWN_EGG "silverfish_spawn_egg",
+    SKELETON_SPAWN_EGG "skeleton_spawn_egg",
+    SKELETON_HORSE_SPAWN_EGG "skeleton_horse_spawn_egg",
+    SLIME_SPAWN_EGG "slime_spawn_egg",
+    SPIDER_SPAWN_EGG "spider_spawn_egg",
+    SQUID_SPAWN_EGG "squid_spawn_egg",
+    STRAY_SPAWN_EGG "stray_spawn_egg",
+    STRIDER_SPAWN_EGG "strider_spawn_egg",
+    TRADER_LLAMA_SPAWN_EGG "trader_llama_spawn_egg",
+    TROPICAL_FISH_SPAWN_EGG "tropical_fish_spawn_egg",
+    TURTLE_SPAWN_EGG "turtle_spawn_egg",
+    VEX_SPAWN_EGG "vex_spawn_egg",
+    VILLAGER_SPAWN_EGG "villager_spawn_egg",
+    VINDICATOR_SPAWN_EGG "vindicator_spawn_egg",
+    WANDERING_TRADER_SPAWN_EGG "wandering_trader_spawn_egg",
+    WITCH_SPAWN_EGG "witch_spawn_egg",
+    WITHER_SKELETON_SPAWN_EGG "wither_skeleton_spawn_egg",
+    WOLF_SPAWN_EGG "wolf_spawn_egg",
+    ZOGLIN_SPAWN_EGG "zoglin_spawn_egg",
+    ZOMBIE_SPAWN_EGG "zombie_spawn_egg",
+    ZOMBIE_HORSE_SPAWN_EGG "zombie_horse_spawn_egg",
+    ZOMBIE_VILLAGER_SPAWN_EGG "zombie_villager_spawn_egg",
+    ZOMBIFIED_PIGLIN_SPAWN_EGG "zombified_piglin_spawn_egg",
+    EXPERIENCE_BOTTLE "experience_bottle",
+    FIRE_CHARGE "fire_charge",
+    WRITABLE_BOOK "writable_book" 1,
+    WRITTEN_BOOK "written_book" 16,
+    ITEM_FRAME "item_frame",
+    GLOW_ITEM_FRAME "glow_item_frame",
+    BAKED_POTATO "baked_potato",
+    POISONOUS_POTATO "poisonous_potato",
+    MAP "map",
+    GOLDEN_CARROT "golden_carrot",
+    NETHER_STAR "nether_star",
+    PUMPKIN_PIE "pumpkin_pie",
+    FIREWORK_ROCKET "firework_rocket",
+    FIREWORK_STAR "firework_star",
+    ENCHANTED_BOOK "enchanted_book" 1,
+    NETHER_BRICK "nether_brick",
+    PRISMARINE_SHARD "prismarine_shard",
+    PRISMARINE_CRYSTALS "prismarine_crystals",
+    RABBIT "rabbit",
+    COOKED_RABBIT "cooked_rabbit",
+    RABBIT_STEW "rabbit_stew" 1,
+    RABBIT_FOOT "rabbit_foot",
+    RABBIT_HIDE "rabbit_hide",
+    ARMOR_STAND "armor_stand" 16,
+    IRON_HORSE_ARMOR "iron_horse_armor" 1,
+    GOLDEN_HORSE_ARMOR "golden_horse_armor" 1,
+    DIAMOND_HORSE_ARMOR "diamond_horse_armor" 1,
+    LEATHER_HORSE_ARMOR "leather_horse_armor" 1,
+    LEAD "lead",
+    NAME_TAG "name_tag",
+    COMMAND_BLOCK_MINECART "command_block_minecart" 1,
+    MUTTON "mutton",
+    COOKED_MUTTON "cooked_mutton",
+    END_CRYSTAL "end_crystal",
+    CHORUS_FRUIT "chorus_fruit",
+    POPPED_CHORUS_FRUIT "popped_chorus_fruit",
+    BEETROOT "beetroot",
+    BEETROOT_SOUP "beetroot_soup" 1,
+    DRAGON_BREATH "dragon_breath",
+    SPLASH_POTION "splash_potion" 1,
+    SPECTRAL_ARROW "spectral_arrow",
+    TIPPED_ARROW "tipped_arrow",
+    LINGERING_POTION "lingering_potion" 1,
+    SHIELD "shield" 1 damage 336,
+    ELYTRA "elytra" 1 damage 432,
+    TOTEM_OF_UNDYING "totem_of_undying" 1,
+    SHULKER_SHELL "shulker_shell",
+    IRON_NUGGET "iron_nugget",
+    KNOWLEDGE_BOOK "knowledge_book" 1,
+    DEBUG_STICK "debug_stick" 1,
+    MUSIC_DISC_13 "music_disc_13" 1,
+    MUSIC_DISC_CAT "music_disc_cat" 1,
+    MUSIC_DISC_BLOCKS "music_disc_blocks" 1,
+    MUSIC_DISC_CHIRP "music_disc_chirp" 1,
+    MUSIC_DISC_FAR "music_disc_far" 1,
+    MUSIC_DISC_MALL "music_disc_mall" 1,
+    MUSIC_DISC_MELLOHI "music_disc_mellohi" 1,
+    MUSIC_DISC_STAL "music_disc_stal" 1,
+    MUSIC_DISC_STRAD "music_disc_strad" 1,
+    MUSIC_DISC_WARD "music_disc_ward" 1,
+    MUSIC_DISC_11 "music_disc_11" 1,
+    MUSIC_DISC_WAIT "music_disc_wait" 1,
+    MUSIC_DISC_PIGSTEP "music_disc_pigstep" 1,
+    TRIDENT "trident" 1 damage 250,
+    PHANTOM_MEMBRANE "phantom_membrane",
+    NAUTILUS_SHELL "nautilus_shell",
+    HEART_OF_THE_SEA "heart_of_the_sea",
+    CROSSBOW "crossbow" 1 damage 465,
+    SUSPICIOUS_STEW "suspicious_stew" 1,
+    FLOWER_BANNER_PATTERN "flower_banner_pattern" 1,
+    CREEPER_BANNER_PATTERN "creeper_banner_pattern" 1,
+    SKULL_BANNER_PATTERN "skull_banner_pattern" 1,
+    MOJANG_BANNER_PATTERN "mojang_banner_pattern" 1,
+    GLOBE_BANNER_PATTERN "globe_banner_pattern" 1,
+    PIGLIN_BANNER_PATTERN "piglin_banner_pattern" 1,
+    HONEYCOMB "honeycomb",
+    HONEY_BOTTLE "honey_bottle" 16,
+    CARROT_ON_A_STICK "carrot_on_a_stick" 1 damage 25,
+    WARPED_FUNGUS_ON_A_STICK "warped_fungus_on_a_stick" 1 damage 100,
+    TNT_MINECART "tnt_minecart" 1,
+    HOPPER_MINECART "hopper_minecart" 1
+]);
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn vanilla_items() {
+
+        assert_eq!(STONE.get_name(), "minecraft:stone");
+        assert_eq!(STONE.get_block(), Some(&block::STONE));
+        assert_eq!(REDSTONE.get_block(), Some(&block::REDSTONE_WIRE));
+        assert_eq!(LIGHT_BLUE_TERRACOTTA.get_block(), Some(&block::LIGTH_BLUE_TERRACOTTA));
+        assert_eq!(DIAMOND.get_block(), None);
+
+        assert_eq!(ENDER_PEARL.get_stack_size(), 16);
+        assert_eq!(WHITE_BED.get_stack_size(), 1);
+        assert_eq!(OAK_SIGN.get_stack_size(), 16);
+        assert!(!STICK.is_damageable());
+        assert_eq!(DIAMOND_PICKAXE.get_max_damage(), 1561);
+        assert_eq!(ELYTRA.get_max_damage(), 432);
+
+        // Blocks without items.
+        assert!(VANILLA_ITEMS.iter().all(|item| item.get_block() != Some(&block::WALL_TORCH)));
+        assert!(VANILLA_ITEMS.iter().all(|item| !item.get_name().starts_with("minecraft:potted_")));
+
+    }
+
+}
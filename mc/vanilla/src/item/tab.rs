@@ -0,0 +1,209 @@
+//! Creative tabs of vanilla items, the categories of the creative inventory. Some items are
+//! not shown in any tab, like command blocks, or are shown in several tabs depending on their
+//! tag, like enchanted books, these items have no tab.
+
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+
+use mc_core::item::Item;
+use mc_core::util::OpaquePtr;
+
+use crate::ext::WithVanilla;
+use super::VANILLA_ITEMS;
+
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum CreativeTab {
+    BuildingBlocks,
+    Decorations,
+    Redstone,
+    Transportation,
+    Misc,
+    Food,
+    Tools,
+    Combat,
+    Brewing
+}
+
+impl CreativeTab {
+
+    /// Name of the tab, as used in the translation key `itemGroup.<name>`.
+    pub fn get_name(self) -> &'static str {
+        match self {
+            CreativeTab::BuildingBlocks => "buildingBlocks",
+            CreativeTab::Decorations => "decorations",
+            CreativeTab::Redstone => "redstone",
+            CreativeTab::Transportation => "transportation",
+            CreativeTab::Misc => "misc",
+            CreativeTab::Food => "food",
+            CreativeTab::Tools => "tools",
+            CreativeTab::Combat => "combat",
+            CreativeTab::Brewing => "brewing"
+        }
+    }
+
+}
+
+
+/// A registry associating items to their creative tab, items of each tab are kept in their
+/// registration order.
+pub struct CreativeTabs {
+    items: HashMap<OpaquePtr<Item>, CreativeTab>,
+    tabs: HashMap<CreativeTab, Vec<&'static Item>>
+}
+
+impl CreativeTabs {
+
+    pub fn new() -> Self {
+        Self {
+            items: HashMap::new(),
+            tabs: HashMap::new()
+        }
+    }
+
+    /// Register the creative tab of an item, an item registered twice is only kept in its
+    /// last tab.
+    pub fn register(&mut self, item: &'static Item, tab: CreativeTab) {
+        if let Some(old_tab) = self.items.insert(OpaquePtr::new(item), tab) {
+            if let Some(items) = self.tabs.get_mut(&old_tab) {
+                items.retain(|&old_item| !std::ptr::eq(old_item, item));
+            }
+        }
+        self.tabs.entry(tab).or_default().push(item);
+    }
+
+    pub fn register_all(&mut self, items: &[&'static Item], tab: CreativeTab) {
+        for &item in items {
+            self.register(item, tab);
+        }
+    }
+
+    pub fn get_tab(&self, item: &'static Item) -> Option<CreativeTab> {
+        self.items.get(&OpaquePtr::new(item)).copied()
+    }
+
+    /// Get the items of a tab, in their registration order.
+    pub fn get_items(&self, tab: CreativeTab) -> &[&'static Item] {
+        self.tabs.get(&tab).map_or(&[], |items| &items[..])
+    }
+
+}
+
+impl Default for CreativeTabs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// Vanilla creative tabs, this is lazily built on first use with `WithVanilla`.
+pub static VANILLA_CREATIVE_TABS: Lazy<CreativeTabs> = Lazy::new(CreativeTabs::with_vanilla);
+
+
+pub(crate) fn register_vanilla_tabs(tabs: &mut CreativeTabs) {
+    for &item in &VANILLA_ITEMS {
+        if let Some(tab) = get_vanilla_tab(item) {
+            tabs.register(item, tab);
+        }
+    }
+}
+
+
+fn get_vanilla_tab(item: &'static Item) -> Option<CreativeTab> {
+
+    use CreativeTab::*;
+
+    let name = item.get_name().strip_prefix("minecraft:").unwrap_or(item.get_name());
+    let ends_with_any = |suffixes: &[&str]| suffixes.iter().any(|&suffix| name.ends_with(suffix));
+
+    Some(match name {
+        "air" | "spawner" | "barrier" | "light" | "structure_void" | "structure_block" | "jigsaw" |
+        "command_block" | "chain_command_block" | "repeating_command_block" | "command_block_minecart" |
+        "farmland" | "dirt_path" | "petrified_oak_slab" | "budding_amethyst" | "dragon_egg" |
+        "debug_stick" | "knowledge_book" | "filled_map" | "written_book" | "enchanted_book" |
+        "suspicious_stew" | "bundle" => return None,
+        "potion" | "splash_potion" | "lingering_potion" | "glass_bottle" | "fermented_spider_eye" |
+        "blaze_powder" | "magma_cream" | "brewing_stand" | "cauldron" | "glistering_melon_slice" |
+        "golden_carrot" | "rabbit_foot" | "dragon_breath" | "phantom_membrane" | "ghast_tear" => Brewing,
+        "apple" | "mushroom_stew" | "bread" | "porkchop" | "cooked_porkchop" | "golden_apple" |
+        "enchanted_golden_apple" | "cod" | "salmon" | "tropical_fish" | "pufferfish" | "cooked_cod" |
+        "cooked_salmon" | "cake" | "cookie" | "melon_slice" | "dried_kelp" | "beef" | "cooked_beef" |
+        "chicken" | "cooked_chicken" | "rotten_flesh" | "spider_eye" | "carrot" | "potato" |
+        "baked_potato" | "poisonous_potato" | "pumpkin_pie" | "rabbit" | "cooked_rabbit" |
+        "rabbit_stew" | "mutton" | "cooked_mutton" | "beetroot" | "beetroot_soup" | "sweet_berries" |
+        "glow_berries" | "honey_bottle" => Food,
+        "rail" | "minecart" | "saddle" | "carrot_on_a_stick" | "warped_fungus_on_a_stick" |
+        "elytra" => Transportation,
+        "redstone" | "dispenser" | "note_block" | "piston" | "sticky_piston" | "tnt" | "lever" |
+        "redstone_torch" | "repeater" | "comparator" | "redstone_block" | "hopper" | "dropper" |
+        "daylight_detector" | "observer" | "redstone_lamp" | "tripwire_hook" | "trapped_chest" |
+        "target" | "lectern" | "lightning_rod" | "sculk_sensor" => Redstone,
+        "bow" | "arrow" | "shield" | "trident" | "crossbow" | "totem_of_undying" => Combat,
+        "flint_and_steel" | "compass" | "clock" | "fishing_rod" | "shears" | "lead" | "name_tag" |
+        "spyglass" => Tools,
+        "beacon" | "conduit" | "turtle_egg" | "composter" | "powder_snow_bucket" | "string" |
+        "wheat_seeds" | "pumpkin_seeds" | "melon_seeds" | "beetroot_seeds" | "cocoa_beans" |
+        "nether_wart" | "sugar_cane" | "kelp" | "bamboo" => Misc,
+        "cobweb" | "grass" | "fern" | "dead_bush" | "seagrass" | "sea_pickle" | "dandelion" | "poppy" |
+        "blue_orchid" | "allium" | "azure_bluet" | "oxeye_daisy" | "cornflower" | "lily_of_the_valley" |
+        "wither_rose" | "sunflower" | "lilac" | "rose_bush" | "peony" | "tall_grass" | "large_fern" |
+        "brown_mushroom" | "red_mushroom" | "mushroom_stem" | "torch" | "soul_torch" | "end_rod" |
+        "chorus_plant" | "chorus_flower" | "chest" | "crafting_table" | "furnace" | "ladder" | "snow" |
+        "cactus" | "jukebox" | "iron_bars" | "chain" | "glass_pane" | "vine" | "glow_lichen" |
+        "lily_pad" | "enchanting_table" | "end_portal_frame" | "ender_chest" | "flower_pot" |
+        "scaffolding" | "loom" | "barrel" | "smoker" | "blast_furnace" | "cartography_table" |
+        "fletching_table" | "grindstone" | "smithing_table" | "stonecutter" | "bell" | "lantern" |
+        "soul_lantern" | "campfire" | "soul_campfire" | "shroomlight" | "bee_nest" | "beehive" |
+        "honey_block" | "honeycomb_block" | "lodestone" | "respawn_anchor" | "amethyst_cluster" |
+        "pointed_dripstone" | "azalea" | "flowering_azalea" | "moss_block" | "spore_blossom" |
+        "big_dripleaf" | "small_dripleaf" | "nether_sprouts" | "slime_block" | "painting" |
+        "item_frame" | "glow_item_frame" | "armor_stand" | "end_crystal" => Decorations,
+        _ if ends_with_any(&["_rail", "_minecart", "_boat"]) => Transportation,
+        _ if ends_with_any(&["_pressure_plate", "_button", "_door", "_trapdoor", "_fence_gate"]) => Redstone,
+        _ if ends_with_any(&["_sword", "_helmet", "_chestplate", "_leggings", "_boots", "_arrow"]) => Combat,
+        _ if ends_with_any(&["_shovel", "_pickaxe", "_axe", "_hoe"]) => Tools,
+        _ if name.starts_with("infested_") || ends_with_any(&[
+            "_sapling", "_leaves", "_tulip", "_fence", "_wall", "_glass_pane", "_carpet", "_banner",
+            "_bed", "shulker_box", "_glazed_terracotta", "_coral", "_coral_fan", "_head", "_skull",
+            "anvil", "candle", "_bud", "_fungus", "_roots", "_vines", "_mushroom_block"
+        ]) => Decorations,
+        _ if item.get_block().is_some() => BuildingBlocks,
+        _ => Misc
+    })
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::item::*;
+
+    #[test]
+    fn vanilla_tabs() {
+
+        let tabs = &*VANILLA_CREATIVE_TABS;
+
+        assert_eq!(tabs.get_tab(&STONE), Some(CreativeTab::BuildingBlocks));
+        assert_eq!(tabs.get_tab(&OAK_SLAB), Some(CreativeTab::BuildingBlocks));
+        assert_eq!(tabs.get_tab(&JACK_O_LANTERN), Some(CreativeTab::BuildingBlocks));
+        assert_eq!(tabs.get_tab(&OAK_FENCE), Some(CreativeTab::Decorations));
+        assert_eq!(tabs.get_tab(&OAK_FENCE_GATE), Some(CreativeTab::Redstone));
+        assert_eq!(tabs.get_tab(&REDSTONE), Some(CreativeTab::Redstone));
+        assert_eq!(tabs.get_tab(&WOODEN_PICKAXE), Some(CreativeTab::Tools));
+        assert_eq!(tabs.get_tab(&NETHERITE_AXE), Some(CreativeTab::Tools));
+        assert_eq!(tabs.get_tab(&TURTLE_HELMET), Some(CreativeTab::Combat));
+        assert_eq!(tabs.get_tab(&COOKED_BEEF), Some(CreativeTab::Food));
+        assert_eq!(tabs.get_tab(&WARPED_FUNGUS_ON_A_STICK), Some(CreativeTab::Transportation));
+        assert_eq!(tabs.get_tab(&CREEPER_SPAWN_EGG), Some(CreativeTab::Misc));
+        assert_eq!(tabs.get_tab(&COMMAND_BLOCK), None);
+
+        let brewing = tabs.get_items(CreativeTab::Brewing);
+        assert!(brewing.iter().any(|&item| std::ptr::eq(item, &POTION)));
+        assert_eq!(CreativeTab::BuildingBlocks.get_name(), "buildingBlocks");
+
+    }
+
+}
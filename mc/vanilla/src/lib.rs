@@ -2,7 +2,21 @@ pub mod block;
 pub mod biome;
 pub mod item;
 pub mod entity;
+pub mod block_entity;
 pub mod heightmap;
+pub mod sound;
+pub mod particle;
+pub mod time;
+pub mod difficulty;
+pub mod weather;
+pub mod sleep;
+pub mod death;
+pub mod hunger;
+pub mod advancement;
+pub mod explosion;
+pub mod command;
+pub mod dimension;
+pub mod map;
 
 pub mod util;
 pub mod ext;
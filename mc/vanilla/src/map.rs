@@ -0,0 +1,798 @@
+//! Filled maps, their data is stored in the `data/map_<id>.dat` files of the world directory
+//! and the ID of a filled map is stored in the `map` tag of its stack. The colors of a map
+//! are updated from the blocks around the players holding it.
+//!
+//! Banner and item frame markers are not supported yet, they are kept when saving maps.
+//! Maps can't be created from empty maps yet.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::io;
+
+use nbt::decode::read_gzip_compound_tag;
+use nbt::encode::write_gzip_compound_tag;
+use nbt::CompoundTag;
+
+use mc_core::world::anvil::encode::DATA_VERSION;
+use mc_core::world::level::{Level, BaseEntity};
+use mc_core::item::{Inventory, ItemStack};
+use mc_runtime::world::World;
+
+use crate::block::material::{MapColor, VANILLA_BLOCK_MATERIALS};
+use crate::block::fluid::get_fluid_state;
+use crate::block::BEDROCK;
+use crate::entity::PlayerEntity;
+use crate::heightmap::WORLD_SURFACE;
+use crate::item::FILLED_MAP;
+
+
+/// Width and height of maps, in pixels.
+pub const MAP_SIZE: usize = 128;
+/// The maximum scale of maps, a pixel of a map of scale `s` covers `2^s` blocks.
+pub const MAX_MAP_SCALE: u8 = 4;
+
+
+/// Type of a decoration displayed on a map.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum MapDecorationType {
+    Player,
+    Frame,
+    RedMarker,
+    BlueMarker,
+    TargetX,
+    TargetPoint,
+    /// A player out of the map but close to it.
+    PlayerOffMap,
+    /// A player far away from the map, only displayed on maps with unlimited tracking.
+    PlayerOffLimits,
+    Mansion,
+    Monument,
+    RedX
+}
+
+impl MapDecorationType {
+
+    pub fn get_id(self) -> u8 {
+        match self {
+            MapDecorationType::Player => 0,
+            MapDecorationType::Frame => 1,
+            MapDecorationType::RedMarker => 2,
+            MapDecorationType::BlueMarker => 3,
+            MapDecorationType::TargetX => 4,
+            MapDecorationType::TargetPoint => 5,
+            MapDecorationType::PlayerOffMap => 6,
+            MapDecorationType::PlayerOffLimits => 7,
+            MapDecorationType::Mansion => 8,
+            MapDecorationType::Monument => 9,
+            MapDecorationType::RedX => 26
+        }
+    }
+
+}
+
+
+/// A decoration displayed on a map, its coordinates are in half pixels relative to the
+/// center of the map.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct MapDecoration {
+    pub kind: MapDecorationType,
+    pub x: i8,
+    pub z: i8,
+    /// Rotation of the decoration, from 0 to 15, 0 is facing south.
+    pub rotation: u8
+}
+
+
+/// A rectangle of colors of a map, used to send updated colors to clients.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MapPatch {
+    pub x: u8,
+    pub z: u8,
+    pub width: u8,
+    pub height: u8,
+    /// Colors of the rectangle, row by row.
+    pub colors: Vec<u8>
+}
+
+
+/// The data of a filled map. Each color is the ID of a `MapColor` multiplied by 4 plus
+/// its brightness, from 0 (darker) to 3.
+#[derive(Debug, Clone)]
+pub struct MapData {
+    x_center: i32,
+    z_center: i32,
+    dimension: String,
+    scale: u8,
+    tracking_position: bool,
+    unlimited_tracking: bool,
+    locked: bool,
+    colors: Vec<u8>,
+    /// Raw banner markers, kept for saving.
+    banners: Vec<CompoundTag>,
+    /// Raw item frame markers, kept for saving.
+    frames: Vec<CompoundTag>,
+    /// The rectangle of colors modified since the last patch, (min x, min z, max x, max z).
+    dirty: Option<(u8, u8, u8, u8)>,
+    /// True if the map has been modified since it was last saved.
+    modified: bool
+}
+
+impl MapData {
+
+    /// Create a new map of the given scale in a dimension, the center of the map is aligned
+    /// on the grid of maps of this scale containing the given position.
+    pub fn new(x: i32, z: i32, scale: u8, dimension: impl Into<String>) -> Self {
+        let scale = scale.min(MAX_MAP_SCALE);
+        let size = (MAP_SIZE as i32) << scale;
+        let center = |pos: i32| (pos + 64).div_euclid(size) * size + size / 2 - 64;
+        Self {
+            x_center: center(x),
+            z_center: center(z),
+            dimension: dimension.into(),
+            scale,
+            tracking_position: true,
+            unlimited_tracking: false,
+            locked: false,
+            colors: vec![0; MAP_SIZE * MAP_SIZE],
+            banners: Vec::new(),
+            frames: Vec::new(),
+            dirty: None,
+            modified: true
+        }
+    }
+
+    pub fn with_tracking_position(mut self, tracking_position: bool) -> Self {
+        self.tracking_position = tracking_position;
+        self
+    }
+
+    /// Players far away from the map are still displayed on its border.
+    pub fn with_unlimited_tracking(mut self, unlimited_tracking: bool) -> Self {
+        self.unlimited_tracking = unlimited_tracking;
+        self
+    }
+
+    #[inline]
+    pub fn get_center(&self) -> (i32, i32) {
+        (self.x_center, self.z_center)
+    }
+
+    /// The ID of the level this map is drawn from.
+    #[inline]
+    pub fn get_dimension(&self) -> &str {
+        &self.dimension
+    }
+
+    #[inline]
+    pub fn get_scale(&self) -> u8 {
+        self.scale
+    }
+
+    /// Number of blocks covered by a pixel, on each axis.
+    #[inline]
+    pub fn get_block_scale(&self) -> i32 {
+        1 << self.scale
+    }
+
+    #[inline]
+    pub fn is_tracking_position(&self) -> bool {
+        self.tracking_position
+    }
+
+    #[inline]
+    pub fn is_unlimited_tracking(&self) -> bool {
+        self.unlimited_tracking
+    }
+
+    /// Locked maps are no longer updated.
+    #[inline]
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    pub fn set_locked(&mut self, locked: bool) {
+        self.locked = locked;
+        self.modified = true;
+    }
+
+    #[inline]
+    pub fn get_colors(&self) -> &[u8] {
+        &self.colors
+    }
+
+    /// Get the color of a pixel, the coordinates must be lower than `MAP_SIZE`.
+    pub fn get_color(&self, x: u8, z: u8) -> u8 {
+        self.colors[x as usize + z as usize * MAP_SIZE]
+    }
+
+    /// Set the color of a pixel, the coordinates must be lower than `MAP_SIZE`. Return true
+    /// if the color was changed, the pixel is then included in the next patch.
+    pub fn set_color(&mut self, x: u8, z: u8, color: u8) -> bool {
+        let index = x as usize + z as usize * MAP_SIZE;
+        if self.colors[index] == color {
+            return false;
+        }
+        self.colors[index] = color;
+        self.dirty = Some(match self.dirty {
+            Some((min_x, min_z, max_x, max_z)) => (min_x.min(x), min_z.min(z), max_x.max(x), max_z.max(z)),
+            None => (x, z, x, z)
+        });
+        self.modified = true;
+        true
+    }
+
+    /// Return the patch of all colors of the map.
+    pub fn get_full_patch(&self) -> MapPatch {
+        MapPatch {
+            x: 0,
+            z: 0,
+            width: MAP_SIZE as u8,
+            height: MAP_SIZE as u8,
+            colors: self.colors.clone()
+        }
+    }
+
+    /// Take the patch of colors changed since the last call, `None` if no color changed.
+    pub fn take_dirty_patch(&mut self) -> Option<MapPatch> {
+        let (min_x, min_z, max_x, max_z) = self.dirty.take()?;
+        let mut colors = Vec::with_capacity((max_x - min_x + 1) as usize * (max_z - min_z + 1) as usize);
+        for z in min_z..=max_z {
+            let start = min_x as usize + z as usize * MAP_SIZE;
+            colors.extend_from_slice(&self.colors[start..=start + (max_x - min_x) as usize]);
+        }
+        Some(MapPatch {
+            x: min_x,
+            z: min_z,
+            width: max_x - min_x + 1,
+            height: max_z - min_z + 1,
+            colors
+        })
+    }
+
+    /// Return the decoration of a player at the given position on this map, `None` if the
+    /// map doesn't track positions or if the player is too far to be displayed.
+    pub fn get_player_decoration(&self, x: f64, z: f64, yaw: f32) -> Option<MapDecoration> {
+
+        if !self.tracking_position {
+            return None;
+        }
+
+        let scale = self.get_block_scale() as f32;
+        let map_x = (x - self.x_center as f64) as f32 / scale;
+        let map_z = (z - self.z_center as f64) as f32 / scale;
+        let to_half_pixel = |pos: f32| ((pos * 2.0) as f64 + 0.5) as i32 as i8;
+
+        if (-63.0..=63.0).contains(&map_x) && (-63.0..=63.0).contains(&map_z) {
+            let yaw = yaw as f64 + if yaw < 0.0 { -8.0 } else { 8.0 };
+            return Some(MapDecoration {
+                kind: MapDecorationType::Player,
+                x: to_half_pixel(map_x),
+                z: to_half_pixel(map_z),
+                rotation: ((yaw * 16.0 / 360.0) as i32 & 15) as u8
+            });
+        }
+
+        let kind = if map_x.abs() < 320.0 && map_z.abs() < 320.0 {
+            MapDecorationType::PlayerOffMap
+        } else if self.unlimited_tracking {
+            MapDecorationType::PlayerOffLimits
+        } else {
+            return None;
+        };
+
+        let clamp = |pos: f32| if pos <= -63.0 { -128 } else if pos >= 63.0 { 127 } else { to_half_pixel(pos) };
+        Some(MapDecoration {
+            kind,
+            x: clamp(map_x),
+            z: clamp(map_z),
+            rotation: 0
+        })
+
+    }
+
+    pub fn encode(&self, dst: &mut CompoundTag) {
+        dst.insert_i8("scale", self.scale as i8);
+        dst.insert_str("dimension", &self.dimension);
+        dst.insert_i32("xCenter", self.x_center);
+        dst.insert_i32("zCenter", self.z_center);
+        dst.insert_bool("trackingPosition", self.tracking_position);
+        dst.insert_bool("unlimitedTracking", self.unlimited_tracking);
+        dst.insert_bool("locked", self.locked);
+        dst.insert_i8_vec("colors", self.colors.iter().map(|&color| color as i8).collect());
+        dst.insert_compound_tag_vec("banners", self.banners.iter().cloned());
+        dst.insert_compound_tag_vec("frames", self.frames.iter().cloned());
+    }
+
+    pub fn decode(src: &CompoundTag) -> Result<Self, String> {
+
+        let dimension = src.get_str("dimension").map_err(|err| format!("{}", err))?;
+        let mut map = Self::new(0, 0, src.get_i8("scale").unwrap_or(0).max(0) as u8, dimension);
+
+        map.x_center = src.get_i32("xCenter").map_err(|err| format!("{}", err))?;
+        map.z_center = src.get_i32("zCenter").map_err(|err| format!("{}", err))?;
+        map.tracking_position = src.get_bool("trackingPosition").unwrap_or(true);
+        map.unlimited_tracking = src.get_bool("unlimitedTracking").unwrap_or(false);
+        map.locked = src.get_bool("locked").unwrap_or(false);
+        map.modified = false;
+
+        if let Ok(colors) = src.get_i8_vec("colors") {
+            if colors.len() != MAP_SIZE * MAP_SIZE {
+                return Err(format!("invalid colors length {}", colors.len()));
+            }
+            map.colors = colors.iter().map(|&color| color as u8).collect();
+        }
+
+        map.banners = src.get_compound_tag_vec("banners").map_or(Vec::new(), |tags| tags.into_iter().cloned().collect());
+        map.frames = src.get_compound_tag_vec("frames").map_or(Vec::new(), |tags| tags.into_iter().cloned().collect());
+        Ok(map)
+
+    }
+
+}
+
+
+/// Return the ID of the map of a filled map stack, `None` for other stacks.
+pub fn get_map_id(stack: &ItemStack) -> Option<i32> {
+    if std::ptr::eq(stack.get_item(), &FILLED_MAP) {
+        stack.get_tag()?.get_i32("map").ok()
+    } else {
+        None
+    }
+}
+
+/// Create a filled map stack for the map with the given ID.
+pub fn new_filled_map(id: i32) -> ItemStack {
+    let mut stack = ItemStack::with_item(&FILLED_MAP);
+    stack.get_tag_mut().insert_i32("map", id);
+    stack
+}
+
+
+fn map_path(data_dir: &Path, id: i32) -> PathBuf {
+    data_dir.join(format!("map_{}.dat", id))
+}
+
+/// Load the map with the given ID from the world in the given directory, `None` if the map
+/// file doesn't exist.
+pub fn load_map<P: AsRef<Path>>(dir: P, id: i32) -> io::Result<Option<MapData>> {
+
+    let mut file = match File::open(map_path(&dir.as_ref().join("data"), id)) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err)
+    };
+
+    let tag_root = read_gzip_compound_tag(&mut file)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, format!("map_{}: {}", id, err)))?;
+    tag_root.get_compound_tag("data")
+        .map_err(|err| format!("{}", err))
+        .and_then(MapData::decode)
+        .map(Some)
+        .map_err(|message| io::Error::new(io::ErrorKind::InvalidData, format!("map_{}: {}", id, message)))
+
+}
+
+/// Save a map with the given ID to the world in the given directory.
+pub fn save_map<P: AsRef<Path>>(dir: P, id: i32, map: &MapData) -> io::Result<()> {
+
+    let data_dir = dir.as_ref().join("data");
+    fs::create_dir_all(&data_dir)?;
+
+    let mut tag_data = CompoundTag::new();
+    map.encode(&mut tag_data);
+
+    let mut tag_root = CompoundTag::new();
+    tag_root.insert_i32("DataVersion", DATA_VERSION);
+    tag_root.insert_compound_tag("data", tag_data);
+
+    let mut file = File::create(map_path(&data_dir, id))?;
+    write_gzip_compound_tag(&mut file, &tag_root)
+        .map_err(io::Error::other)
+
+}
+
+
+/// A world component storing the maps of the world, with the ID of the last created map.
+pub struct Maps {
+    maps: HashMap<i32, MapData>,
+    /// The directory of the world, maps are not saved if `None`.
+    dir: Option<PathBuf>,
+    /// The last created map ID, -1 if no map has been created.
+    last_id: i32,
+    /// Ticks counter, each column of held maps is updated every 16 ticks.
+    ticks: u32
+}
+
+impl Maps {
+
+    pub fn new() -> Self {
+        Self {
+            maps: HashMap::new(),
+            dir: None,
+            last_id: -1,
+            ticks: 0
+        }
+    }
+
+    /// Load all maps of the world in the given directory, the maps are then saved to this
+    /// directory with `save`.
+    pub fn load<P: AsRef<Path>>(dir: P) -> io::Result<Self> {
+
+        let dir = dir.as_ref();
+        let data_dir = dir.join("data");
+        let mut maps = Self::new();
+        maps.dir = Some(dir.to_path_buf());
+
+        let entries = match fs::read_dir(&data_dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(maps),
+            Err(err) => return Err(err)
+        };
+
+        for entry in entries {
+            let file_name = entry?.file_name();
+            let id = file_name.to_str()
+                .and_then(|name| name.strip_prefix("map_"))
+                .and_then(|name| name.strip_suffix(".dat"))
+                .and_then(|id| id.parse::<i32>().ok());
+            if let Some(id) = id {
+                if let Some(map) = load_map(dir, id)? {
+                    maps.maps.insert(id, map);
+                }
+            }
+        }
+
+        maps.last_id = match File::open(data_dir.join("idcounts.dat")) {
+            Ok(mut file) => read_gzip_compound_tag(&mut file)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, format!("idcounts: {}", err)))?
+                .get_compound_tag("data")
+                .and_then(|tag_data| tag_data.get_i32("map"))
+                .unwrap_or(-1),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => -1,
+            Err(err) => return Err(err)
+        };
+
+        // The counter may be missing or outdated if the world was not saved properly.
+        maps.last_id = maps.maps.keys().copied().fold(maps.last_id, i32::max);
+        Ok(maps)
+
+    }
+
+    /// Save the maps modified since the last save and the last map ID, nothing is done if
+    /// the maps were not loaded from a directory.
+    pub fn save(&mut self) -> io::Result<()> {
+
+        let dir = match &self.dir {
+            Some(dir) => dir,
+            None => return Ok(())
+        };
+
+        for (&id, map) in &mut self.maps {
+            if map.modified {
+                save_map(dir, id, map)?;
+                map.modified = false;
+            }
+        }
+
+        let mut tag_data = CompoundTag::new();
+        tag_data.insert_i32("map", self.last_id);
+        let mut tag_root = CompoundTag::new();
+        tag_root.insert_i32("DataVersion", DATA_VERSION);
+        tag_root.insert_compound_tag("data", tag_data);
+
+        let data_dir = dir.join("data");
+        fs::create_dir_all(&data_dir)?;
+        let mut file = File::create(data_dir.join("idcounts.dat"))?;
+        write_gzip_compound_tag(&mut file, &tag_root)
+            .map_err(io::Error::other)
+
+    }
+
+    /// Add a new map and return its ID.
+    pub fn create_map(&mut self, map: MapData) -> i32 {
+        self.last_id += 1;
+        self.maps.insert(self.last_id, map);
+        self.last_id
+    }
+
+    pub fn get_map(&self, id: i32) -> Option<&MapData> {
+        self.maps.get(&id)
+    }
+
+    pub fn get_map_mut(&mut self, id: i32) -> Option<&mut MapData> {
+        self.maps.get_mut(&id)
+    }
+
+    pub fn iter_maps_mut(&mut self) -> impl Iterator<Item = (i32, &mut MapData)> {
+        self.maps.iter_mut().map(|(&id, map)| (id, map))
+    }
+
+    #[inline]
+    pub fn get_ticks(&self) -> u32 {
+        self.ticks
+    }
+
+}
+
+impl Default for Maps {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// Update the colors of a map around a player at the given position, only the columns of
+/// pixels matching the step (modulo 16) are updated, as well as the columns following
+/// changed ones. Nothing is done if the map is locked or drawn from another level.
+pub fn update_map_colors(level: &Level, map: &mut MapData, x: f64, z: f64, step: u32) {
+
+    if map.locked || map.dimension != *level.get_id() {
+        return;
+    }
+
+    let scale = map.get_block_scale();
+    let ceiling = level.get_dimension_type().has_ceiling;
+    let player_x = (x - map.x_center as f64).floor() as i32 / scale + 64;
+    let player_z = (z - map.z_center as f64).floor() as i32 / scale + 64;
+    let radius = if ceiling { 64 / scale } else { 128 / scale };
+
+    let mut force_column = false;
+
+    for map_x in (player_x - radius + 1)..(player_x + radius) {
+
+        if (map_x & 15) as u32 != step & 15 && !force_column {
+            continue;
+        }
+
+        force_column = false;
+        let mut prev_height = 0.0;
+
+        for map_z in (player_z - radius - 1)..(player_z + radius) {
+
+            if map_x < 0 || map_z < -1 || map_x >= MAP_SIZE as i32 || map_z >= MAP_SIZE as i32 {
+                continue;
+            }
+
+            let (dx, dz) = (map_x - player_x, map_z - player_z);
+            let outer = dx * dx + dz * dz > (radius - 2) * (radius - 2);
+            let block_x = (map.x_center / scale + map_x - 64) * scale;
+            let block_z = (map.z_center / scale + map_z - 64) * scale;
+
+            let (color, height, depth) = match sample_pixel(level, block_x, block_z, scale, ceiling) {
+                Some(sample) => sample,
+                None => continue
+            };
+
+            let parity = (map_x + map_z) & 1;
+            let mut brightness = 1;
+
+            if color == MapColor::WATER {
+                let shade = depth as f64 * 0.1 + parity as f64 * 0.2;
+                if shade < 0.5 {
+                    brightness = 2;
+                } else if shade > 0.9 {
+                    brightness = 0;
+                }
+            } else {
+                let shade = (height - prev_height) * 4.0 / (scale + 4) as f64 + (parity as f64 - 0.5) * 0.4;
+                if shade > 0.6 {
+                    brightness = 2;
+                } else if shade < -0.6 {
+                    brightness = 0;
+                }
+            }
+
+            prev_height = height;
+
+            // The border of the updated disc is dithered.
+            if map_z >= 0 && dx * dx + dz * dz < radius * radius && (!outer || parity != 0) {
+                force_column |= map.set_color(map_x as u8, map_z as u8, color.get_id() * 4 + brightness);
+            }
+
+        }
+
+    }
+
+}
+
+/// Sample the blocks covered by a pixel, returning the most common map color, the average
+/// height and the average liquid depth. `None` if the chunk is not loaded.
+fn sample_pixel(level: &Level, block_x: i32, block_z: i32, scale: i32, ceiling: bool) -> Option<(MapColor, f64, i32)> {
+
+    let chunk = level.chunks.get_chunk_at(block_x, block_z)?;
+
+    if ceiling {
+        let mut n = block_x.wrapping_add(block_z.wrapping_mul(231871));
+        n = n.wrapping_mul(n).wrapping_mul(31287121).wrapping_add(n.wrapping_mul(11));
+        let color = if (n >> 20) & 1 == 0 { MapColor::DIRT } else { MapColor::STONE };
+        return Some((color, 100.0, 0));
+    }
+
+    let materials = &*VANILLA_BLOCK_MATERIALS;
+    let chunk_height = chunk.get_height();
+    let (min_y, max_y) = (chunk_height.get_min_block(), chunk_height.get_max_block());
+
+    // Colors are counted in their insertion order to keep the first color on equality.
+    let mut colors: Vec<(MapColor, u32)> = Vec::new();
+    let mut total_height = 0.0;
+    let mut total_depth = 0;
+
+    for bx in block_x..block_x + scale {
+        for bz in block_z..block_z + scale {
+
+            let (x, z) = ((bx & 15) as u8, (bz & 15) as u8);
+            let mut y = chunk.get_heightmap_column(&WORLD_SURFACE, x, z).unwrap_or(max_y + 1);
+            let color;
+
+            if y <= min_y + 1 {
+                color = materials.get_map_color(&BEDROCK);
+            } else {
+                let mut state;
+                loop {
+                    y -= 1;
+                    state = chunk.get_block(x, y, z).ok()?;
+                    if materials.get_map_color(state.get_block()) != MapColor::NONE || y <= min_y {
+                        break;
+                    }
+                }
+                let fluid = get_fluid_state(state);
+                if y > min_y && !fluid.is_empty() {
+                    let mut fluid_y = y - 1;
+                    loop {
+                        total_depth += 1;
+                        let below = chunk.get_block(x, fluid_y, z).ok()?;
+                        fluid_y -= 1;
+                        if fluid_y <= min_y || get_fluid_state(below).is_empty() {
+                            break;
+                        }
+                    }
+                    // Waterlogged blocks that are not solid are drawn as their liquid.
+                    if !materials.is_solid(state.get_block()) {
+                        state = fluid.get_block_state().unwrap_or(state);
+                    }
+                }
+                color = materials.get_map_color(state.get_block());
+            }
+
+            total_height += y as f64 / (scale * scale) as f64;
+            match colors.iter_mut().find(|(other, _)| *other == color) {
+                Some((_, count)) => *count += 1,
+                None => colors.push((color, 1))
+            }
+
+        }
+    }
+
+    let mut best = (MapColor::NONE, 0);
+    for &(color, count) in &colors {
+        if count > best.1 {
+            best = (color, count);
+        }
+    }
+
+    Some((best.0, total_height, total_depth / (scale * scale)))
+
+}
+
+
+/// Register the maps component in the world, this is required by `system_maps`.
+pub fn register_maps(world: &mut World, maps: Maps) {
+    world.insert_component(maps);
+}
+
+/// System updating the colors of the maps held by players in their selected slot.
+pub fn system_maps(world: &mut World) {
+
+    let mut maps = match world.get_component_mut::<Maps>() {
+        Ok(maps) => maps,
+        Err(_) => return
+    };
+
+    let step = maps.ticks;
+    maps.ticks = maps.ticks.wrapping_add(1);
+
+    for level in &world.levels {
+        let level = level.borrow();
+        for (_, (base, player, inventory)) in level.entities.ecs.query::<(&BaseEntity, &PlayerEntity, &Inventory)>().iter() {
+            let id = inventory.get_stack(player.get_selected_slot() as usize).and_then(get_map_id);
+            if let Some(map) = id.and_then(|id| maps.get_map_mut(id)) {
+                update_map_colors(&level, map, base.pos.x, base.pos.z, step);
+            }
+        }
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_core::world::level::LevelEnv;
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::source::{ChunkLoadRequest, NullLevelSource};
+
+    use crate::ext::VanillaLevelEnv;
+    use crate::block::{STONE, WATER};
+
+    use super::*;
+
+    #[test]
+    fn map_data() {
+
+        let mut map = MapData::new(100, -100, 1, "minecraft:overworld");
+        assert_eq!(map.get_center(), (64, -192));
+        assert_eq!(MapData::new(-65, 63, 0, "minecraft:overworld").get_center(), (-128, 0));
+
+        assert!(map.set_color(3, 5, 4 * 12 + 1));
+        assert!(!map.set_color(3, 5, 4 * 12 + 1));
+        assert!(map.set_color(6, 4, 9));
+        let patch = map.take_dirty_patch().unwrap();
+        assert_eq!((patch.x, patch.z, patch.width, patch.height), (3, 4, 4, 2));
+        assert_eq!(patch.colors[3], 9);
+        assert_eq!(patch.colors[4], 4 * 12 + 1);
+        assert!(map.take_dirty_patch().is_none());
+
+        let decoration = map.get_player_decoration(64.0, -192.0 + 10.0, 90.0).unwrap();
+        assert_eq!((decoration.kind, decoration.x, decoration.z, decoration.rotation), (MapDecorationType::Player, 0, 10, 4));
+        let decoration = map.get_player_decoration(64.0 + 200.0, -192.0, 0.0).unwrap();
+        assert_eq!((decoration.kind, decoration.x), (MapDecorationType::PlayerOffMap, 127));
+        assert!(map.get_player_decoration(64.0 + 1000.0, -192.0, 0.0).is_none());
+
+        let dir = std::env::temp_dir().join(format!("mc-vanilla-maps-{}", std::process::id()));
+
+        let mut maps = Maps::load(&dir).unwrap();
+        assert_eq!(maps.create_map(map), 0);
+        maps.save().unwrap();
+
+        let loaded = Maps::load(&dir).unwrap();
+        let map = loaded.get_map(0).unwrap();
+        assert_eq!(map.get_center(), (64, -192));
+        assert_eq!(map.get_scale(), 1);
+        assert_eq!(map.get_dimension(), "minecraft:overworld");
+        assert_eq!(map.get_color(3, 5), 4 * 12 + 1);
+        assert_eq!(map.get_color(6, 4), 9);
+        assert_eq!(loaded.last_id, 0);
+
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(get_map_id(&new_filled_map(7)), Some(7));
+        assert_eq!(get_map_id(&ItemStack::with_item(&crate::item::MAP)), None);
+
+    }
+
+    #[test]
+    fn update_colors() {
+
+        let env = LevelEnv::vanilla();
+        let height = ChunkHeight::new(0, 0);
+        let level = Level::new("overworld".to_string(), env.clone(), height, NullLevelSource);
+        level.chunks.insert_chunk(ChunkLoadRequest { env, height, cx: 0, cz: 0 }.build_chunk());
+
+        for x in 0..16 {
+            for z in 0..16 {
+                level.chunks.set_block_at(x, 0, z, STONE.get_default_state()).unwrap();
+                level.chunks.set_block_at(x, 1, z, STONE.get_default_state()).unwrap();
+            }
+        }
+        level.chunks.set_block_at(3, 1, 3, WATER.get_default_state()).unwrap();
+
+        let mut map = MapData::new(0, 0, 0, "overworld");
+        for step in 0..16 {
+            update_map_colors(&level, &mut map, 8.0, 8.0, step);
+        }
+
+        // The first row of the chunk is brighter because the chunk in the north is missing.
+        assert_eq!(map.get_color(64 + 5, 64 + 5), MapColor::STONE.get_id() * 4 + 1);
+        assert_eq!(map.get_color(64 + 5, 64), MapColor::STONE.get_id() * 4 + 2);
+        assert_eq!(map.get_color(64 + 3, 64 + 3), MapColor::WATER.get_id() * 4 + 2);
+        assert_eq!(map.get_color(64 + 16, 64 + 5), 0);
+        assert!(map.take_dirty_patch().is_some());
+
+        let mut other = MapData::new(0, 0, 0, "the_nether");
+        update_map_colors(&level, &mut other, 8.0, 8.0, 0);
+        assert!(other.take_dirty_patch().is_none());
+
+    }
+
+}
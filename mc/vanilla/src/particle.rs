@@ -0,0 +1,112 @@
+//! Vanilla particle types, with their protocol IDs for 1.17.1.
+
+use mc_core::particles;
+
+
+particles!(pub VANILLA_PARTICLES "minecraft" [
+    AMBIENT_ENTITY_EFFECT "ambient_entity_effect" 0,
+    ANGRY_VILLAGER "angry_villager" 1,
+    BARRIER "barrier" 2,
+    LIGHT "light" 3, // 1.17
+    BLOCK "block" 4,
+    BUBBLE "bubble" 5,
+    CLOUD "cloud" 6,
+    CRIT "crit" 7,
+    DAMAGE_INDICATOR "damage_indicator" 8,
+    DRAGON_BREATH "dragon_breath" 9,
+    DRIPPING_LAVA "dripping_lava" 10,
+    FALLING_LAVA "falling_lava" 11,
+    LANDING_LAVA "landing_lava" 12,
+    DRIPPING_WATER "dripping_water" 13,
+    FALLING_WATER "falling_water" 14,
+    DUST "dust" 15,
+    DUST_COLOR_TRANSITION "dust_color_transition" 16, // 1.17
+    EFFECT "effect" 17,
+    ELDER_GUARDIAN "elder_guardian" 18,
+    ENCHANTED_HIT "enchanted_hit" 19,
+    ENCHANT "enchant" 20,
+    END_ROD "end_rod" 21,
+    ENTITY_EFFECT "entity_effect" 22,
+    EXPLOSION_EMITTER "explosion_emitter" 23,
+    EXPLOSION "explosion" 24,
+    FALLING_DUST "falling_dust" 25,
+    FIREWORK "firework" 26,
+    FISHING "fishing" 27,
+    FLAME "flame" 28,
+    SOUL_FIRE_FLAME "soul_fire_flame" 29,
+    SOUL "soul" 30,
+    FLASH "flash" 31,
+    HAPPY_VILLAGER "happy_villager" 32,
+    COMPOSTER "composter" 33,
+    HEART "heart" 34,
+    INSTANT_EFFECT "instant_effect" 35,
+    ITEM "item" 36,
+    VIBRATION "vibration" 37, // 1.17
+    ITEM_SLIME "item_slime" 38,
+    ITEM_SNOWBALL "item_snowball" 39,
+    LARGE_SMOKE "large_smoke" 40,
+    LAVA "lava" 41,
+    MYCELIUM "mycelium" 42,
+    NOTE "note" 43,
+    POOF "poof" 44,
+    PORTAL "portal" 45,
+    RAIN "rain" 46,
+    SMOKE "smoke" 47,
+    SNEEZE "sneeze" 48,
+    SPIT "spit" 49,
+    SQUID_INK "squid_ink" 50,
+    SWEEP_ATTACK "sweep_attack" 51,
+    TOTEM_OF_UNDYING "totem_of_undying" 52,
+    UNDERWATER "underwater" 53,
+    SPLASH "splash" 54,
+    WITCH "witch" 55,
+    BUBBLE_POP "bubble_pop" 56,
+    CURRENT_DOWN "current_down" 57,
+    BUBBLE_COLUMN_UP "bubble_column_up" 58,
+    NAUTILUS "nautilus" 59,
+    DOLPHIN "dolphin" 60,
+    CAMPFIRE_COSY_SMOKE "campfire_cosy_smoke" 61,
+    CAMPFIRE_SIGNAL_SMOKE "campfire_signal_smoke" 62,
+    DRIPPING_HONEY "dripping_honey" 63,
+    FALLING_HONEY "falling_honey" 64,
+    LANDING_HONEY "landing_honey" 65,
+    FALLING_NECTAR "falling_nectar" 66,
+    FALLING_SPORE_BLOSSOM "falling_spore_blossom" 67, // 1.17
+    ASH "ash" 68,
+    CRIMSON_SPORE "crimson_spore" 69,
+    WARPED_SPORE "warped_spore" 70,
+    SPORE_BLOSSOM_AIR "spore_blossom_air" 71, // 1.17
+    DRIPPING_OBSIDIAN_TEAR "dripping_obsidian_tear" 72,
+    FALLING_OBSIDIAN_TEAR "falling_obsidian_tear" 73,
+    LANDING_OBSIDIAN_TEAR "landing_obsidian_tear" 74,
+    REVERSE_PORTAL "reverse_portal" 75,
+    WHITE_ASH "white_ash" 76,
+    SMALL_FLAME "small_flame" 77, // 1.17
+    SNOWFLAKE "snowflake" 78, // 1.17
+    DRIPPING_DRIPSTONE_LAVA "dripping_dripstone_lava" 79, // 1.17
+    FALLING_DRIPSTONE_LAVA "falling_dripstone_lava" 80, // 1.17
+    DRIPPING_DRIPSTONE_WATER "dripping_dripstone_water" 81, // 1.17
+    FALLING_DRIPSTONE_WATER "falling_dripstone_water" 82, // 1.17
+    GLOW_SQUID_INK "glow_squid_ink" 83, // 1.17
+    GLOW "glow" 84, // 1.17
+    WAX_ON "wax_on" 85, // 1.17
+    WAX_OFF "wax_off" 86, // 1.17
+    ELECTRIC_SPARK "electric_spark" 87, // 1.17
+    SCRAPE "scrape" 88 // 1.17
+]);
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn particle_ids() {
+        for (id, particle) in VANILLA_PARTICLES.iter().enumerate() {
+            assert_eq!(particle.get_id(), id as i32, "wrong id for {}", particle.get_name());
+        }
+        assert_eq!(FLAME.get_name(), "minecraft:flame");
+    }
+
+}
@@ -0,0 +1,532 @@
+//! Beds and sleeping, entities with a `Sleeper` component (usually players) can use beds to
+//! set their spawn point and sleep at night. The `system_sleep` system tracks sleeping players
+//! of each level and skips to the next day when enough of them are sleeping.
+
+use std::collections::HashMap;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use mc_core::world::level::{Level, BaseEntity};
+use mc_core::block::BlockState;
+use mc_core::pos::{EntityPos, BlockPos};
+use hecs::Entity;
+
+use mc_runtime::world::World;
+
+use crate::block::material::{TAG_BED, TAG_NON_BLOCKING};
+use crate::block::*;
+use crate::time::{LevelTime, DAY_LENGTH};
+use crate::weather::LevelWeather;
+use crate::explosion::{Explosion, BED_EXPLOSION_POWER};
+
+
+/// Number of ticks a player must have slept before the night can be skipped.
+pub const SLEEP_DURATION: u16 = 100;
+
+/// Maximum horizontal and vertical distances between a sleeper and the bed it uses.
+const BED_REACH_XZ: f64 = 3.0;
+const BED_REACH_Y: f64 = 2.0;
+/// Height of sleeping entities above the bed block.
+const BED_SLEEP_HEIGHT: f64 = 0.5625;
+
+
+/// An entity component for entities able to sleep in beds and respawn at them, or at
+/// respawn anchors.
+#[derive(Debug, Default, Clone)]
+pub struct Sleeper {
+    /// The level ID and the position of the bed head or the respawn anchor where the
+    /// entity respawns, see `death::find_respawn_point`.
+    spawn_point: Option<(String, BlockPos)>,
+    /// The position of the head of the bed the entity is sleeping in.
+    bed: Option<BlockPos>,
+    sleep_timer: u16
+}
+
+impl Sleeper {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get_spawn_point(&self) -> Option<(&String, &BlockPos)> {
+        self.spawn_point.as_ref().map(|(level_id, pos)| (level_id, pos))
+    }
+
+    pub fn set_spawn_point(&mut self, level_id: String, pos: BlockPos) {
+        self.spawn_point = Some((level_id, pos));
+    }
+
+    pub fn clear_spawn_point(&mut self) {
+        self.spawn_point = None;
+    }
+
+    #[inline]
+    pub fn get_bed(&self) -> Option<&BlockPos> {
+        self.bed.as_ref()
+    }
+
+    #[inline]
+    pub fn is_sleeping(&self) -> bool {
+        self.bed.is_some()
+    }
+
+    /// Return the number of ticks this entity has been sleeping, up to `SLEEP_DURATION`.
+    #[inline]
+    pub fn get_sleep_timer(&self) -> u16 {
+        self.sleep_timer
+    }
+
+    /// Return true if this entity has been sleeping long enough to skip the night.
+    #[inline]
+    pub fn is_sleeping_long_enough(&self) -> bool {
+        self.bed.is_some() && self.sleep_timer >= SLEEP_DURATION
+    }
+
+}
+
+
+/// The result of an entity using a bed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BedInteraction {
+    /// The entity is now sleeping in the bed and its spawn point has been set.
+    Sleeping,
+    /// The spawn point has been set but the entity can't sleep during the day.
+    NotPossibleNow,
+    /// Another entity is already sleeping in the bed.
+    Occupied,
+    /// The entity is too far away from the bed.
+    TooFarAway,
+    /// The blocks above the bed are obstructing it.
+    Obstructed,
+    /// Beds don't work in this dimension, the bed has been removed and the given explosion
+    /// must be applied to the level.
+    Explode(Explosion)
+}
+
+
+/// Return the position and the state of the head of the bed at the given position, `None`
+/// if there is no complete bed there.
+pub fn find_bed_head(level: &Level, pos: &BlockPos) -> Option<(BlockPos, &'static BlockState)> {
+
+    let blocks = &level.get_env().blocks;
+    let state = level.chunks.get_block_at(pos.x, pos.y, pos.z).ok()?;
+    if !blocks.has_block_tag(state.get_block(), &TAG_BED) {
+        return None;
+    }
+
+    let facing = state.get(&PROP_HORIZONTAL_FACING)?;
+    let (head_pos, other_pos) = match state.get(&PROP_BED_PART)? {
+        BedPart::Head => (pos.clone(), pos.relative(facing.opposite(), 1)),
+        BedPart::Foot => (pos.relative(facing, 1), pos.relative(facing, 1))
+    };
+
+    // The other part of the bed must be present.
+    let other = level.chunks.get_block_at(other_pos.x, other_pos.y, other_pos.z).ok()?;
+    if !other.is_block(state.get_block()) {
+        return None;
+    }
+
+    let head = level.chunks.get_block_at(head_pos.x, head_pos.y, head_pos.z).ok()?;
+    Some((head_pos, head))
+
+}
+
+/// Set the occupied property of both parts of a bed, the head position must be valid.
+fn set_bed_occupied(level: &Level, head_pos: &BlockPos, occupied: bool) {
+    let head = level.chunks.get_block_at(head_pos.x, head_pos.y, head_pos.z).unwrap();
+    let foot_pos = head_pos.relative(head.expect(&PROP_HORIZONTAL_FACING).opposite(), 1);
+    for pos in [head_pos, &foot_pos] {
+        if let Ok(state) = level.chunks.get_block_at(pos.x, pos.y, pos.z) {
+            if let Some(state) = state.with(&PROP_OCCUPIED, occupied) {
+                let _ = level.chunks.set_block_at(pos.x, pos.y, pos.z, state);
+            }
+        }
+    }
+}
+
+/// Return true if entities can sleep at the current time, at night or during thunderstorms.
+/// Levels with a fixed time are always considered as night.
+pub fn can_sleep_now(level: &Level, time: &LevelTime, weather: &LevelWeather) -> bool {
+    level.get_dimension_type().fixed_time.is_some()
+        || time.get_sky_darken(weather.get_rain_level(), weather.get_thunder_level()) >= 4
+}
+
+/// Use the bed at the given position, like vanilla when a player right clicks a bed. If the
+/// entity can sleep, its spawn point is set, it's moved into the bed and the bed becomes
+/// occupied. Nearby monsters are not checked.
+///
+/// `None` is returned if there is no bed at this position or if the entity has no `Sleeper`
+/// component.
+pub fn use_bed(level: &mut Level, time: &LevelTime, weather: &LevelWeather, entity: Entity, pos: &BlockPos) -> Option<BedInteraction> {
+
+    let (head_pos, head) = find_bed_head(level, pos)?;
+    let facing = head.expect(&PROP_HORIZONTAL_FACING);
+    let foot_pos = head_pos.relative(facing.opposite(), 1);
+
+    let entity_pos = level.entities.ecs.get::<BaseEntity>(entity).ok()?.pos.clone();
+    level.entities.ecs.get::<Sleeper>(entity).ok()?;
+
+    if !level.get_dimension_type().bed_works {
+        for pos in [&head_pos, &foot_pos] {
+            let _ = level.chunks.set_block_at(pos.x, pos.y, pos.z, AIR.get_default_state());
+        }
+        let center = EntityPos::new(head_pos.x as f64 + 0.5, head_pos.y as f64 + 0.5, head_pos.z as f64 + 0.5);
+        return Some(BedInteraction::Explode(Explosion::new(center, BED_EXPLOSION_POWER).with_fire(true)));
+    }
+
+    if head.expect(&PROP_OCCUPIED) {
+        return Some(BedInteraction::Occupied);
+    }
+
+    let reachable = [&head_pos, &foot_pos].iter().any(|pos| {
+        (entity_pos.x - (pos.x as f64 + 0.5)).abs() <= BED_REACH_XZ
+            && (entity_pos.y - pos.y as f64).abs() <= BED_REACH_Y
+            && (entity_pos.z - (pos.z as f64 + 0.5)).abs() <= BED_REACH_XZ
+    });
+
+    if !reachable {
+        return Some(BedInteraction::TooFarAway);
+    }
+
+    let blocks = &level.get_env().blocks;
+    let obstructed = [head_pos.above(1), foot_pos.above(1)].iter().any(|pos| {
+        match level.chunks.get_block_at(pos.x, pos.y, pos.z) {
+            Ok(state) => !blocks.has_block_tag(state.get_block(), &TAG_NON_BLOCKING),
+            Err(_) => false
+        }
+    });
+
+    if obstructed {
+        return Some(BedInteraction::Obstructed);
+    }
+
+    let level_id = level.get_id().clone();
+    level.entities.ecs.get_mut::<Sleeper>(entity).unwrap().set_spawn_point(level_id, head_pos.clone());
+
+    if !can_sleep_now(level, time, weather) {
+        return Some(BedInteraction::NotPossibleNow);
+    }
+
+    set_bed_occupied(level, &head_pos, true);
+
+    let mut sleeper = level.entities.ecs.get_mut::<Sleeper>(entity).unwrap();
+    sleeper.bed = Some(head_pos.clone());
+    sleeper.sleep_timer = 0;
+    drop(sleeper);
+
+    let sleep_pos = EntityPos::new(head_pos.x as f64 + 0.5, head_pos.y as f64 + BED_SLEEP_HEIGHT, head_pos.z as f64 + 0.5);
+    level.move_entity(entity, sleep_pos);
+
+    Some(BedInteraction::Sleeping)
+
+}
+
+/// Wake up a sleeping entity and free its bed, return false if the entity was not sleeping.
+pub fn wake_up(level: &mut Level, entity: Entity) -> bool {
+
+    let bed = match level.entities.ecs.get_mut::<Sleeper>(entity) {
+        Ok(mut sleeper) => {
+            sleeper.sleep_timer = 0;
+            sleeper.bed.take()
+        }
+        Err(_) => return false
+    };
+
+    match bed {
+        Some(head_pos) => {
+            if find_bed_head(level, &head_pos).is_some() {
+                set_bed_occupied(level, &head_pos, false);
+            }
+            true
+        }
+        None => false
+    }
+
+}
+
+
+/// Sleep status of a level tracked by a `SleepTracker`.
+#[derive(Debug, Clone)]
+pub struct LevelSleep {
+    /// Time of the level, changed when the night is skipped.
+    pub time: LevelTime,
+    /// Weather of the level, the rain and thunder stop when the night is skipped.
+    pub weather: LevelWeather,
+    sleeping: usize,
+    players: usize
+}
+
+impl LevelSleep {
+
+    #[inline]
+    pub fn get_sleeping(&self) -> usize {
+        self.sleeping
+    }
+
+    #[inline]
+    pub fn get_players(&self) -> usize {
+        self.players
+    }
+
+}
+
+
+/// World component tracking sleeping players of levels, levels must be added to this tracker
+/// with their time and weather to allow skipping the night.
+pub struct SleepTracker {
+    levels: HashMap<String, LevelSleep>,
+    sleeping_percentage: u8
+}
+
+impl SleepTracker {
+
+    pub fn new() -> Self {
+        Self {
+            levels: HashMap::new(),
+            sleeping_percentage: 100
+        }
+    }
+
+    /// Set the percentage of players that must sleep to skip the night, like the
+    /// `playersSleepingPercentage` game rule. The night is never skipped above 100.
+    pub fn with_sleeping_percentage(mut self, sleeping_percentage: u8) -> Self {
+        self.sleeping_percentage = sleeping_percentage;
+        self
+    }
+
+    /// Track the level with the given ID, with its current time and weather.
+    pub fn with_level(mut self, level_id: String, time: LevelTime, weather: LevelWeather) -> Self {
+        self.levels.insert(level_id, LevelSleep { time, weather, sleeping: 0, players: 0 });
+        self
+    }
+
+    #[inline]
+    pub fn get_sleeping_percentage(&self) -> u8 {
+        self.sleeping_percentage
+    }
+
+    pub fn get_level(&self, level_id: &str) -> Option<&LevelSleep> {
+        self.levels.get(level_id)
+    }
+
+    pub fn get_level_mut(&mut self, level_id: &str) -> Option<&mut LevelSleep> {
+        self.levels.get_mut(level_id)
+    }
+
+    /// Return the number of sleeping players required to skip the night, at least one.
+    pub fn get_required_sleeping(&self, players: usize) -> usize {
+        (players * self.sleeping_percentage as usize).div_ceil(100).max(1)
+    }
+
+}
+
+impl Default for SleepTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// Event pushed by `system_sleep` when the number of sleeping players or the number of
+/// players in a level has changed.
+pub struct SleepStatusEvent {
+    pub level: Rc<RefCell<Level>>,
+    pub sleeping: usize,
+    pub players: usize,
+    /// Number of sleeping players required to skip the night.
+    pub required: usize
+}
+
+
+/// Event pushed by `system_sleep` when the night has been skipped, all sleeping players have
+/// been woken up.
+pub struct NightSkippedEvent {
+    pub level: Rc<RefCell<Level>>,
+    /// The new day time of the level.
+    pub day_time: i64
+}
+
+
+/// Call this function with a mutable reference to a World to register the `SleepTracker`
+/// component which is required to run the system `system_sleep`.
+pub fn register_sleep(world: &mut World, tracker: SleepTracker) {
+    world.insert_component(tracker);
+}
+
+/// A system that increments the sleep timer of sleeping entities and skips to the next day
+/// when enough players of a tracked level have slept long enough. Entities with a `Sleeper`
+/// component are counted as players.
+pub fn system_sleep(world: &mut World) {
+
+    let mut tracker = match world.components.get_mut::<SleepTracker>() {
+        Ok(tracker) => tracker,
+        Err(_) => return
+    };
+
+    for level_rc in &world.levels {
+
+        let mut level = level_rc.borrow_mut();
+        let mut players = 0;
+        let mut sleeping = Vec::new();
+        let mut sleeping_long_enough = 0;
+
+        for (entity, sleeper) in level.entities.ecs.query_mut::<&mut Sleeper>() {
+            players += 1;
+            if sleeper.is_sleeping() {
+                sleeper.sleep_timer = (sleeper.sleep_timer + 1).min(SLEEP_DURATION);
+                sleeping.push(entity);
+                if sleeper.is_sleeping_long_enough() {
+                    sleeping_long_enough += 1;
+                }
+            }
+        }
+
+        let required = tracker.get_required_sleeping(players);
+        let level_sleep = match tracker.get_level_mut(level.get_id()) {
+            Some(level_sleep) => level_sleep,
+            None => continue
+        };
+
+        if (level_sleep.sleeping, level_sleep.players) != (sleeping.len(), players) {
+            level_sleep.sleeping = sleeping.len();
+            level_sleep.players = players;
+            world.event_tracker.push_event(SleepStatusEvent {
+                level: Rc::clone(level_rc),
+                sleeping: sleeping.len(),
+                players,
+                required
+            });
+        }
+
+        if !sleeping.is_empty() && sleeping_long_enough >= required {
+
+            let next_day = level_sleep.time.get_day_time() + DAY_LENGTH;
+            let day_time = next_day - next_day.rem_euclid(DAY_LENGTH);
+            level_sleep.time.set_day_time(day_time);
+            level_sleep.weather.reset_cycle();
+
+            for entity in sleeping {
+                wake_up(&mut level, entity);
+            }
+
+            world.event_tracker.push_event(NightSkippedEvent {
+                level: Rc::clone(level_rc),
+                day_time
+            });
+
+        }
+
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use mc_core::world::level::LevelEnv;
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::dimension::DimensionType;
+    use mc_core::world::source::{ChunkLoadRequest, NullLevelSource};
+    use mc_core::pos::Direction;
+
+    use crate::ext::VanillaLevelEnv;
+    use crate::entity::PLAYER;
+
+    use super::*;
+
+    fn new_level(id: &str, bed_works: bool) -> Level {
+
+        let env = LevelEnv::vanilla();
+        let height = ChunkHeight::new(0, 0);
+        let dimension_type = DimensionType {
+            bed_works,
+            ..DimensionType::new("test:dimension", height)
+        };
+
+        let level = Level::new(id.to_string(), env.clone(), height, NullLevelSource)
+            .with_dimension_type(Arc::new(dimension_type));
+        let chunk = ChunkLoadRequest { env, height, cx: 0, cz: 0 }.build_chunk();
+        level.chunks.insert_chunk(chunk);
+
+        // A bed with its head at (4, 1, 4) and its foot to the south.
+        let bed = RED_BED.get_default_state().with(&PROP_HORIZONTAL_FACING, Direction::North).unwrap();
+        level.chunks.set_block_at(4, 1, 4, bed.with(&PROP_BED_PART, BedPart::Head).unwrap()).unwrap();
+        level.chunks.set_block_at(4, 1, 5, bed.with(&PROP_BED_PART, BedPart::Foot).unwrap()).unwrap();
+        level
+
+    }
+
+    fn spawn_player(level: &mut Level, pos: EntityPos) -> Entity {
+        let player = level.spawn_entity(&PLAYER, pos).unwrap();
+        level.entities.ecs.insert_one(player, Sleeper::new()).unwrap();
+        player
+    }
+
+    #[test]
+    fn sleep() {
+
+        let night = LevelTime::new().with_day_time(DAY_LENGTH * 2 + 18000);
+        let weather = LevelWeather::new();
+        let bed_pos = BlockPos::new(4, 1, 5);
+
+        // Beds explode where they don't work.
+        let mut nether = new_level("nether", false);
+        let player = spawn_player(&mut nether, EntityPos::new(4.5, 1.0, 7.0));
+        assert_eq!(use_bed(&mut nether, &night, &weather, player, &bed_pos), Some(BedInteraction::Explode(
+            Explosion::new(EntityPos::new(4.5, 1.5, 4.5), BED_EXPLOSION_POWER).with_fire(true))));
+        assert!(nether.chunks.get_block_at(4, 1, 4).unwrap().is_block(&AIR));
+
+        let mut level = new_level("overworld", true);
+        let player0 = spawn_player(&mut level, EntityPos::new(4.5, 1.0, 7.0));
+        let player1 = spawn_player(&mut level, EntityPos::new(4.5, 1.0, 7.0));
+        let far_player = spawn_player(&mut level, EntityPos::new(12.0, 1.0, 12.0));
+
+        assert_eq!(use_bed(&mut level, &night, &weather, player0, &BlockPos::new(0, 0, 0)), None);
+        assert_eq!(use_bed(&mut level, &night, &weather, far_player, &bed_pos), Some(BedInteraction::TooFarAway));
+
+        // The spawn point is set even during the day.
+        let day = LevelTime::new().with_day_time(6000);
+        assert_eq!(use_bed(&mut level, &day, &weather, player0, &bed_pos), Some(BedInteraction::NotPossibleNow));
+        let spawn_point = level.entities.ecs.get::<Sleeper>(player0).unwrap().get_spawn_point().map(|(id, pos)| (id.clone(), pos.clone()));
+        assert_eq!(spawn_point, Some(("overworld".to_string(), BlockPos::new(4, 1, 4))));
+
+        assert_eq!(use_bed(&mut level, &night, &weather, player0, &bed_pos), Some(BedInteraction::Sleeping));
+        assert_eq!(use_bed(&mut level, &night, &weather, player1, &bed_pos), Some(BedInteraction::Occupied));
+        assert!(level.chunks.get_block_at(4, 1, 5).unwrap().expect(&PROP_OCCUPIED));
+        assert_eq!(level.entities.ecs.get::<BaseEntity>(player0).unwrap().pos, EntityPos::new(4.5, 1.5625, 4.5));
+
+        let mut world = World::new();
+        world.add_level(level);
+        register_sleep(&mut world, SleepTracker::new()
+            .with_sleeping_percentage(30)
+            .with_level("overworld".to_string(), night, weather));
+
+        // One player out of three is enough with 30%, once slept long enough.
+        system_sleep(&mut world);
+        let status: Vec<_> = world.event_tracker.poll_events::<SleepStatusEvent>()
+            .map(|event| (event.sleeping, event.players, event.required))
+            .collect();
+        assert_eq!(status, vec![(1, 3, 1)]);
+
+        for _ in 2..SLEEP_DURATION {
+            system_sleep(&mut world);
+        }
+        assert_eq!(world.event_tracker.poll_events::<NightSkippedEvent>().count(), 0);
+
+        system_sleep(&mut world);
+        let skipped: Vec<_> = world.event_tracker.poll_events::<NightSkippedEvent>().map(|event| event.day_time).collect();
+        assert_eq!(skipped, vec![DAY_LENGTH * 3]);
+
+        let tracker = world.components.get::<SleepTracker>().unwrap();
+        assert_eq!(tracker.get_level("overworld").unwrap().time.get_day_time(), DAY_LENGTH * 3);
+
+        let level = world.levels[0].borrow();
+        assert!(!level.entities.ecs.get::<Sleeper>(player0).unwrap().is_sleeping());
+        assert!(!level.chunks.get_block_at(4, 1, 4).unwrap().expect(&PROP_OCCUPIED));
+
+    }
+
+}
@@ -0,0 +1,218 @@
+//! Vanilla sound events. Sound events are sent by name to clients, so this list doesn't need
+//! to be complete and only defines commonly used sounds.
+
+use mc_core::sounds;
+
+
+sounds!(pub VANILLA_SOUNDS "minecraft" [
+    AMBIENT_CAVE "ambient.cave",
+
+    BLOCK_ANVIL_LAND "block.anvil.land",
+    BLOCK_ANVIL_USE "block.anvil.use",
+    BLOCK_ANVIL_DESTROY "block.anvil.destroy",
+
+    BLOCK_BARREL_OPEN "block.barrel.open",
+    BLOCK_BARREL_CLOSE "block.barrel.close",
+    BLOCK_CHEST_OPEN "block.chest.open",
+    BLOCK_CHEST_CLOSE "block.chest.close",
+    BLOCK_CHEST_LOCKED "block.chest.locked",
+    BLOCK_ENDER_CHEST_OPEN "block.ender_chest.open",
+    BLOCK_ENDER_CHEST_CLOSE "block.ender_chest.close",
+    BLOCK_SHULKER_BOX_OPEN "block.shulker_box.open",
+    BLOCK_SHULKER_BOX_CLOSE "block.shulker_box.close",
+
+    BLOCK_WOODEN_DOOR_OPEN "block.wooden_door.open",
+    BLOCK_WOODEN_DOOR_CLOSE "block.wooden_door.close",
+    BLOCK_IRON_DOOR_OPEN "block.iron_door.open",
+    BLOCK_IRON_DOOR_CLOSE "block.iron_door.close",
+    BLOCK_WOODEN_TRAPDOOR_OPEN "block.wooden_trapdoor.open",
+    BLOCK_WOODEN_TRAPDOOR_CLOSE "block.wooden_trapdoor.close",
+    BLOCK_IRON_TRAPDOOR_OPEN "block.iron_trapdoor.open",
+    BLOCK_IRON_TRAPDOOR_CLOSE "block.iron_trapdoor.close",
+    BLOCK_FENCE_GATE_OPEN "block.fence_gate.open",
+    BLOCK_FENCE_GATE_CLOSE "block.fence_gate.close",
+
+    BLOCK_LEVER_CLICK "block.lever.click",
+    BLOCK_STONE_BUTTON_CLICK_ON "block.stone_button.click_on",
+    BLOCK_STONE_BUTTON_CLICK_OFF "block.stone_button.click_off",
+    BLOCK_WOODEN_BUTTON_CLICK_ON "block.wooden_button.click_on",
+    BLOCK_WOODEN_BUTTON_CLICK_OFF "block.wooden_button.click_off",
+    BLOCK_STONE_PRESSURE_PLATE_CLICK_ON "block.stone_pressure_plate.click_on",
+    BLOCK_STONE_PRESSURE_PLATE_CLICK_OFF "block.stone_pressure_plate.click_off",
+    BLOCK_WOODEN_PRESSURE_PLATE_CLICK_ON "block.wooden_pressure_plate.click_on",
+    BLOCK_WOODEN_PRESSURE_PLATE_CLICK_OFF "block.wooden_pressure_plate.click_off",
+
+    BLOCK_TRIPWIRE_ATTACH "block.tripwire.attach",
+    BLOCK_TRIPWIRE_DETACH "block.tripwire.detach",
+    BLOCK_TRIPWIRE_CLICK_ON "block.tripwire.click_on",
+    BLOCK_TRIPWIRE_CLICK_OFF "block.tripwire.click_off",
+    BLOCK_COMPARATOR_CLICK "block.comparator.click",
+    BLOCK_REDSTONE_TORCH_BURNOUT "block.redstone_torch.burnout",
+    BLOCK_PISTON_EXTEND "block.piston.extend",
+    BLOCK_PISTON_CONTRACT "block.piston.contract",
+
+    BLOCK_DISPENSER_DISPENSE "block.dispenser.dispense",
+    BLOCK_DISPENSER_FAIL "block.dispenser.fail",
+    BLOCK_DISPENSER_LAUNCH "block.dispenser.launch",
+
+    BLOCK_NOTE_BLOCK_HARP "block.note_block.harp",
+    BLOCK_NOTE_BLOCK_BASEDRUM "block.note_block.basedrum",
+    BLOCK_NOTE_BLOCK_SNARE "block.note_block.snare",
+    BLOCK_NOTE_BLOCK_HAT "block.note_block.hat",
+    BLOCK_NOTE_BLOCK_BASS "block.note_block.bass",
+    BLOCK_NOTE_BLOCK_FLUTE "block.note_block.flute",
+    BLOCK_NOTE_BLOCK_BELL "block.note_block.bell",
+    BLOCK_NOTE_BLOCK_GUITAR "block.note_block.guitar",
+    BLOCK_NOTE_BLOCK_CHIME "block.note_block.chime",
+    BLOCK_NOTE_BLOCK_XYLOPHONE "block.note_block.xylophone",
+    BLOCK_NOTE_BLOCK_IRON_XYLOPHONE "block.note_block.iron_xylophone",
+    BLOCK_NOTE_BLOCK_COW_BELL "block.note_block.cow_bell",
+    BLOCK_NOTE_BLOCK_DIDGERIDOO "block.note_block.didgeridoo",
+    BLOCK_NOTE_BLOCK_BIT "block.note_block.bit",
+    BLOCK_NOTE_BLOCK_BANJO "block.note_block.banjo",
+    BLOCK_NOTE_BLOCK_PLING "block.note_block.pling",
+
+    BLOCK_FIRE_AMBIENT "block.fire.ambient",
+    BLOCK_FIRE_EXTINGUISH "block.fire.extinguish",
+    BLOCK_LAVA_AMBIENT "block.lava.ambient",
+    BLOCK_LAVA_EXTINGUISH "block.lava.extinguish",
+    BLOCK_LAVA_POP "block.lava.pop",
+    BLOCK_WATER_AMBIENT "block.water.ambient",
+
+    BLOCK_PORTAL_AMBIENT "block.portal.ambient",
+    BLOCK_PORTAL_TRAVEL "block.portal.travel",
+    BLOCK_PORTAL_TRIGGER "block.portal.trigger",
+    BLOCK_END_PORTAL_SPAWN "block.end_portal.spawn",
+    BLOCK_END_PORTAL_FRAME_FILL "block.end_portal_frame.fill",
+
+    BLOCK_STONE_BREAK "block.stone.break",
+    BLOCK_STONE_PLACE "block.stone.place",
+    BLOCK_STONE_STEP "block.stone.step",
+    BLOCK_WOOD_BREAK "block.wood.break",
+    BLOCK_WOOD_PLACE "block.wood.place",
+    BLOCK_WOOD_STEP "block.wood.step",
+    BLOCK_GRASS_BREAK "block.grass.break",
+    BLOCK_GRASS_PLACE "block.grass.place",
+    BLOCK_GRASS_STEP "block.grass.step",
+    BLOCK_GRAVEL_BREAK "block.gravel.break",
+    BLOCK_GRAVEL_PLACE "block.gravel.place",
+    BLOCK_GRAVEL_STEP "block.gravel.step",
+    BLOCK_SAND_BREAK "block.sand.break",
+    BLOCK_SAND_PLACE "block.sand.place",
+    BLOCK_SAND_STEP "block.sand.step",
+    BLOCK_GLASS_BREAK "block.glass.break",
+    BLOCK_GLASS_PLACE "block.glass.place",
+    BLOCK_GLASS_STEP "block.glass.step",
+    BLOCK_WOOL_BREAK "block.wool.break",
+    BLOCK_WOOL_PLACE "block.wool.place",
+    BLOCK_WOOL_STEP "block.wool.step",
+
+    BLOCK_BELL_USE "block.bell.use",
+    BLOCK_BEACON_ACTIVATE "block.beacon.activate",
+    BLOCK_BEACON_AMBIENT "block.beacon.ambient",
+    BLOCK_BEACON_DEACTIVATE "block.beacon.deactivate",
+    BLOCK_BEACON_POWER_SELECT "block.beacon.power_select",
+    BLOCK_BREWING_STAND_BREW "block.brewing_stand.brew",
+    BLOCK_ENCHANTMENT_TABLE_USE "block.enchantment_table.use",
+    BLOCK_FURNACE_FIRE_CRACKLE "block.furnace.fire_crackle",
+    BLOCK_RESPAWN_ANCHOR_CHARGE "block.respawn_anchor.charge",
+    BLOCK_RESPAWN_ANCHOR_DEPLETE "block.respawn_anchor.deplete",
+    BLOCK_RESPAWN_ANCHOR_SET_SPAWN "block.respawn_anchor.set_spawn",
+
+    ENTITY_GENERIC_BIG_FALL "entity.generic.big_fall",
+    ENTITY_GENERIC_BURN "entity.generic.burn",
+    ENTITY_GENERIC_DEATH "entity.generic.death",
+    ENTITY_GENERIC_DRINK "entity.generic.drink",
+    ENTITY_GENERIC_EAT "entity.generic.eat",
+    ENTITY_GENERIC_EXPLODE "entity.generic.explode",
+    ENTITY_GENERIC_EXTINGUISH_FIRE "entity.generic.extinguish_fire",
+    ENTITY_GENERIC_HURT "entity.generic.hurt",
+    ENTITY_GENERIC_SMALL_FALL "entity.generic.small_fall",
+    ENTITY_GENERIC_SPLASH "entity.generic.splash",
+    ENTITY_GENERIC_SWIM "entity.generic.swim",
+
+    ENTITY_ITEM_BREAK "entity.item.break",
+    ENTITY_ITEM_PICKUP "entity.item.pickup",
+    ENTITY_EXPERIENCE_ORB_PICKUP "entity.experience_orb.pickup",
+    ENTITY_TNT_PRIMED "entity.tnt.primed",
+    ENTITY_ARROW_HIT "entity.arrow.hit",
+    ENTITY_ARROW_HIT_PLAYER "entity.arrow.hit_player",
+    ENTITY_ARROW_SHOOT "entity.arrow.shoot",
+    ENTITY_ENDER_PEARL_THROW "entity.ender_pearl.throw",
+    ENTITY_FIREWORK_ROCKET_LAUNCH "entity.firework_rocket.launch",
+    ENTITY_FIREWORK_ROCKET_BLAST "entity.firework_rocket.blast",
+
+    ENTITY_PLAYER_ATTACK_CRIT "entity.player.attack.crit",
+    ENTITY_PLAYER_ATTACK_KNOCKBACK "entity.player.attack.knockback",
+    ENTITY_PLAYER_ATTACK_NODAMAGE "entity.player.attack.nodamage",
+    ENTITY_PLAYER_ATTACK_STRONG "entity.player.attack.strong",
+    ENTITY_PLAYER_ATTACK_SWEEP "entity.player.attack.sweep",
+    ENTITY_PLAYER_ATTACK_WEAK "entity.player.attack.weak",
+    ENTITY_PLAYER_BURP "entity.player.burp",
+    ENTITY_PLAYER_DEATH "entity.player.death",
+    ENTITY_PLAYER_HURT "entity.player.hurt",
+    ENTITY_PLAYER_LEVELUP "entity.player.levelup",
+
+    ENTITY_BOAT_PADDLE_WATER "entity.boat.paddle_water",
+    ENTITY_MINECART_RIDING "entity.minecart.riding",
+    ENTITY_MINECART_INSIDE "entity.minecart.inside",
+    ENTITY_ITEM_FRAME_ADD_ITEM "entity.item_frame.add_item",
+    ENTITY_ITEM_FRAME_BREAK "entity.item_frame.break",
+    ENTITY_ITEM_FRAME_PLACE "entity.item_frame.place",
+    ENTITY_ITEM_FRAME_REMOVE_ITEM "entity.item_frame.remove_item",
+    ENTITY_ITEM_FRAME_ROTATE_ITEM "entity.item_frame.rotate_item",
+    ENTITY_PAINTING_BREAK "entity.painting.break",
+    ENTITY_PAINTING_PLACE "entity.painting.place",
+
+    ENTITY_CHICKEN_AMBIENT "entity.chicken.ambient",
+    ENTITY_CHICKEN_DEATH "entity.chicken.death",
+    ENTITY_CHICKEN_EGG "entity.chicken.egg",
+    ENTITY_CHICKEN_HURT "entity.chicken.hurt",
+    ENTITY_COW_AMBIENT "entity.cow.ambient",
+    ENTITY_COW_DEATH "entity.cow.death",
+    ENTITY_COW_HURT "entity.cow.hurt",
+    ENTITY_COW_MILK "entity.cow.milk",
+    ENTITY_PIG_AMBIENT "entity.pig.ambient",
+    ENTITY_PIG_DEATH "entity.pig.death",
+    ENTITY_PIG_HURT "entity.pig.hurt",
+    ENTITY_PIG_SADDLE "entity.pig.saddle",
+    ENTITY_SHEEP_AMBIENT "entity.sheep.ambient",
+    ENTITY_SHEEP_DEATH "entity.sheep.death",
+    ENTITY_SHEEP_HURT "entity.sheep.hurt",
+    ENTITY_SHEEP_SHEAR "entity.sheep.shear",
+
+    ENTITY_VILLAGER_AMBIENT "entity.villager.ambient",
+    ENTITY_VILLAGER_DEATH "entity.villager.death",
+    ENTITY_VILLAGER_HURT "entity.villager.hurt",
+    ENTITY_VILLAGER_NO "entity.villager.no",
+    ENTITY_VILLAGER_TRADE "entity.villager.trade",
+    ENTITY_VILLAGER_YES "entity.villager.yes",
+    ENTITY_ZOMBIE_AMBIENT "entity.zombie.ambient",
+    ENTITY_ZOMBIE_DEATH "entity.zombie.death",
+    ENTITY_ZOMBIE_HURT "entity.zombie.hurt",
+
+    ENTITY_LIGHTNING_BOLT_IMPACT "entity.lightning_bolt.impact",
+    ENTITY_LIGHTNING_BOLT_THUNDER "entity.lightning_bolt.thunder",
+    WEATHER_RAIN "weather.rain",
+    WEATHER_RAIN_ABOVE "weather.rain.above",
+
+    ITEM_ARMOR_EQUIP_GENERIC "item.armor.equip_generic",
+    ITEM_BUCKET_EMPTY "item.bucket.empty",
+    ITEM_BUCKET_EMPTY_LAVA "item.bucket.empty_lava",
+    ITEM_BUCKET_FILL "item.bucket.fill",
+    ITEM_BUCKET_FILL_LAVA "item.bucket.fill_lava",
+    ITEM_FIRECHARGE_USE "item.firecharge.use",
+    ITEM_FLINTANDSTEEL_USE "item.flintandsteel.use",
+    ITEM_TOTEM_USE "item.totem.use",
+
+    MUSIC_CREATIVE "music.creative",
+    MUSIC_CREDITS "music.credits",
+    MUSIC_END "music.end",
+    MUSIC_GAME "music.game",
+    MUSIC_MENU "music.menu",
+
+    UI_BUTTON_CLICK "ui.button.click",
+    UI_TOAST_CHALLENGE_COMPLETE "ui.toast.challenge_complete",
+    UI_TOAST_IN "ui.toast.in",
+    UI_TOAST_OUT "ui.toast.out"
+]);
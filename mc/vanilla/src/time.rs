@@ -0,0 +1,144 @@
+//! Vanilla level time, used to compute the time of day, the sun angle and the darkening of
+//! the sky light.
+
+use std::f64::consts::PI;
+
+
+/// Number of ticks in a day.
+pub const DAY_LENGTH: i64 = 24000;
+
+
+/// Time of a level, the game time is the total number of ticks run by the level while the
+/// day time is the time used for the day/night cycle, it can be changed by commands.
+#[derive(Debug, Clone)]
+pub struct LevelTime {
+    game_time: i64,
+    day_time: i64,
+    daylight_cycle: bool
+}
+
+impl LevelTime {
+
+    pub fn new() -> Self {
+        Self {
+            game_time: 0,
+            day_time: 0,
+            daylight_cycle: true
+        }
+    }
+
+    pub fn with_game_time(mut self, game_time: i64) -> Self {
+        self.game_time = game_time;
+        self
+    }
+
+    pub fn with_day_time(mut self, day_time: i64) -> Self {
+        self.day_time = day_time;
+        self
+    }
+
+    /// Enable or disable the day/night cycle, like the `doDaylightCycle` game rule.
+    pub fn with_daylight_cycle(mut self, daylight_cycle: bool) -> Self {
+        self.daylight_cycle = daylight_cycle;
+        self
+    }
+
+    /// Advance the time by one tick, the day time is frozen if the cycle is disabled.
+    pub fn tick(&mut self) {
+        self.game_time += 1;
+        if self.daylight_cycle {
+            self.day_time += 1;
+        }
+    }
+
+    #[inline]
+    pub fn get_game_time(&self) -> i64 {
+        self.game_time
+    }
+
+    #[inline]
+    pub fn get_day_time(&self) -> i64 {
+        self.day_time
+    }
+
+    #[inline]
+    pub fn set_day_time(&mut self, day_time: i64) {
+        self.day_time = day_time;
+    }
+
+    /// Return the time of day between 0 and 1, 0 is noon and 0.5 is midnight.
+    pub fn get_time_of_day(&self) -> f32 {
+        let day = self.day_time as f64 / DAY_LENGTH as f64 - 0.25;
+        let day = day - day.floor();
+        let smooth = 0.5 - (day * PI).cos() / 2.0;
+        (day * 2.0 + smooth) as f32 / 3.0
+    }
+
+    /// Return the angle of the sun in radians, 0 is noon.
+    pub fn get_sun_angle(&self) -> f32 {
+        self.get_time_of_day() * std::f32::consts::PI * 2.0
+    }
+
+    /// Return the current moon phase, between 0 (full moon) and 7.
+    pub fn get_moon_phase(&self) -> u8 {
+        (self.day_time / DAY_LENGTH).rem_euclid(8) as u8
+    }
+
+    /// Return the brightness of the moon for the current moon phase, between 0 (new moon)
+    /// and 1 (full moon).
+    pub fn get_moon_brightness(&self) -> f32 {
+        const MOON_BRIGHTNESS: [f32; 8] = [1.0, 0.75, 0.5, 0.25, 0.0, 0.25, 0.5, 0.75];
+        MOON_BRIGHTNESS[self.get_moon_phase() as usize]
+    }
+
+    /// Return the number of sky light levels to subtract from the sky light of blocks, this
+    /// is 0 at noon and 11 at midnight. Rain and thunder levels are between 0 and 1 and
+    /// darken the sky further.
+    pub fn get_sky_darken(&self, rain_level: f32, thunder_level: f32) -> u8 {
+        let rain = 1.0 - (rain_level * 5.0) as f64 / 16.0;
+        let thunder = 1.0 - (thunder_level * 5.0) as f64 / 16.0;
+        let sun = (self.get_sun_angle() as f64).cos().clamp(-0.25, 0.25);
+        let light = 0.5 + 2.0 * sun;
+        ((1.0 - light * rain * thunder) * 11.0) as u8
+    }
+
+}
+
+impl Default for LevelTime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn sky_darken() {
+
+        let noon = LevelTime::new().with_day_time(6000);
+        assert_eq!(noon.get_time_of_day(), 0.0);
+        assert_eq!(noon.get_sky_darken(0.0, 0.0), 0);
+        assert_eq!(noon.get_sky_darken(1.0, 0.0), 3);
+        assert_eq!(noon.get_sky_darken(1.0, 1.0), 5);
+
+        let midnight = LevelTime::new().with_day_time(18000);
+        assert_eq!(midnight.get_time_of_day(), 0.5);
+        assert_eq!(midnight.get_sky_darken(0.0, 0.0), 11);
+
+        // The time of day is wrapping every day.
+        let next_noon = LevelTime::new().with_day_time(6000 + DAY_LENGTH * 3);
+        assert_eq!(next_noon.get_time_of_day(), 0.0);
+        assert_eq!(next_noon.get_moon_phase(), 3);
+
+        let mut frozen = LevelTime::new().with_daylight_cycle(false);
+        frozen.tick();
+        assert_eq!(frozen.get_game_time(), 1);
+        assert_eq!(frozen.get_day_time(), 0);
+
+    }
+
+}
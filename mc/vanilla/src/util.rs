@@ -41,6 +41,33 @@ impl DyeColor {
         }
     }
 
+    /// Return the vanilla name of this color, used in NBT data.
+    pub fn get_name(self) -> &'static str {
+        use DyeColor::*;
+        match self {
+            White => "white",
+            Orange => "orange",
+            Magenta => "magenta",
+            LightBlue => "light_blue",
+            Yellow => "yellow",
+            Lime => "lime",
+            Pink => "pink",
+            Gray => "gray",
+            LightGray => "light_gray",
+            Cyan => "cyan",
+            Purple => "purple",
+            Blue => "blue",
+            Brown => "brown",
+            Green => "green",
+            Red => "red",
+            Black => "black"
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        (0..16).map(Self::from_id).find(|color| color.get_name() == name)
+    }
+
     pub fn get_diffuse_color(self) -> u32 {
         use DyeColor::*;
         match self {
@@ -95,4 +122,74 @@ impl GameMode {
         }
     }
 
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Self::Survival => "survival",
+            Self::Creative => "creative",
+            Self::Adventure => "adventure",
+            Self::Spectator => "spectator"
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "survival" => Some(Self::Survival),
+            "creative" => Some(Self::Creative),
+            "adventure" => Some(Self::Adventure),
+            "spectator" => Some(Self::Spectator),
+            _ => None
+        }
+    }
+
+    /// Return true if players in this game mode can't break or place blocks.
+    pub fn is_block_placing_restricted(self) -> bool {
+        matches!(self, Self::Adventure | Self::Spectator)
+    }
+
+}
+
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[repr(u8)]
+pub enum Difficulty {
+    Peaceful = 0,
+    Easy = 1,
+    #[default]
+    Normal = 2,
+    Hard = 3
+}
+
+impl Difficulty {
+
+    pub fn get_id(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_id(id: u8) -> Self {
+        if id <= 3 {
+            unsafe { std::mem::transmute::<u8, Difficulty>(id) }
+        } else {
+            Self::default()
+        }
+    }
+
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Self::Peaceful => "peaceful",
+            Self::Easy => "easy",
+            Self::Normal => "normal",
+            Self::Hard => "hard"
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "peaceful" => Some(Self::Peaceful),
+            "easy" => Some(Self::Easy),
+            "normal" => Some(Self::Normal),
+            "hard" => Some(Self::Hard),
+            _ => None
+        }
+    }
+
 }
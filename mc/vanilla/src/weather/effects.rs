@@ -0,0 +1,488 @@
+//! Effects of the weather on a level, these functions should be called for each loaded chunk
+//! every tick through `tick_chunk_weather`, lightning bolts are then struck with
+//! `strike_lightning` when the tick returns a position.
+
+use mc_core::world::chunk::{Chunk, Light};
+use mc_core::world::level::{Level, BaseEntity};
+use mc_core::block::Block;
+use mc_core::rand::JavaRandom;
+use mc_core::pos::BlockPos;
+
+use crate::biome::climate::{get_biome_climate, Precipitation};
+use crate::block::material::VANILLA_BLOCK_MATERIALS;
+use crate::block::*;
+use crate::entity::LivingEntity;
+use crate::heightmap::{MOTION_BLOCKING, WORLD_SURFACE};
+
+use super::LevelWeather;
+
+
+/// Lightning rods redirect lightning bolts in this radius.
+const LIGHTNING_ROD_RADIUS: i32 = 128;
+/// Number of additional fires spread around a lightning bolt.
+const LIGHTNING_EXTRA_FIRES: u32 = 4;
+
+
+/// Tick the weather effects of a chunk, snow is accumulated and cauldrons are filled while
+/// raining. The snow can accumulate up to the given number of layers, vanilla only places
+/// a single layer. If a lightning bolt should be struck, its position is returned.
+pub fn tick_chunk_weather(level: &Level, cx: i32, cz: i32, weather: &LevelWeather, rand: &mut JavaRandom, snow_accumulation: u8) -> Option<BlockPos> {
+
+    let raining = weather.is_raining();
+    let mut lightning = None;
+
+    if raining && weather.is_thundering() && rand.next_int_bounded(100000) == 0 {
+        let x = cx * 16 + rand.next_int_bounded(16);
+        let z = cz * 16 + rand.next_int_bounded(16);
+        let target = find_lightning_target(level, x, z, rand);
+        if is_raining_at(level, weather, &target) {
+            lightning = Some(target);
+        }
+    }
+
+    if raining && rand.next_int_bounded(16) == 0 {
+        let x = cx * 16 + rand.next_int_bounded(16);
+        let z = cz * 16 + rand.next_int_bounded(16);
+        if let Some(mut chunk) = level.chunks.get_chunk_at_mut(x, z) {
+            if let Ok(y) = chunk.get_heightmap_column_at(&MOTION_BLOCKING, x, z) {
+                accumulate_snow(&mut chunk, x, y, z, snow_accumulation);
+                if let Ok(biome) = chunk.get_biome_at(x, y, z) {
+                    let precipitation = get_biome_climate(biome).get_precipitation_at(&BlockPos::new(x, y - 1, z));
+                    fill_cauldron(&mut chunk, x, y - 1, z, precipitation, rand);
+                }
+            }
+        }
+    }
+
+    lightning
+
+}
+
+
+/// Return true if it's raining at the given position, rain needs an access to the sky and a
+/// biome where the rain is not snow.
+pub fn is_raining_at(level: &Level, weather: &LevelWeather, pos: &BlockPos) -> bool {
+
+    if !weather.is_raining() {
+        return false;
+    }
+
+    let chunk = match level.chunks.get_chunk_at(pos.x, pos.z) {
+        Some(chunk) => chunk,
+        None => return false
+    };
+
+    match chunk.get_heightmap_column_at(&MOTION_BLOCKING, pos.x, pos.z) {
+        Ok(height) if height <= pos.y => {}
+        _ => return false
+    }
+
+    match chunk.get_biome_at(pos.x, pos.y, pos.z) {
+        Ok(biome) => get_biome_climate(biome).get_precipitation_at(pos) == Precipitation::Rain,
+        Err(_) => false
+    }
+
+}
+
+
+/// Place or grow a snow layer at the given position if it's cold enough, the position must
+/// be the top of the column. Return true if the snow has changed.
+pub fn accumulate_snow(chunk: &mut Chunk, x: i32, y: i32, z: i32, max_layers: u8) -> bool {
+
+    let climate = match chunk.get_biome_at(x, y, z) {
+        Ok(biome) => get_biome_climate(biome),
+        Err(_) => return false
+    };
+
+    if !climate.is_cold_enough_to_snow(&BlockPos::new(x, y, z)) {
+        return false;
+    }
+
+    // Snow melts under block light.
+    if chunk.get_light_at(x, y, z, Light::Block).unwrap_or(0) >= 10 {
+        return false;
+    }
+
+    let (state, below) = match (chunk.get_block_at(x, y, z), chunk.get_block_at(x, y - 1, z)) {
+        (Ok(state), Ok(below)) => (state, below),
+        _ => return false
+    };
+
+    let new_state = if is_air(state.get_block()) {
+        if !can_snow_survive_on(below.get_block()) {
+            return false;
+        }
+        SNOW.get_default_state()
+    } else if state.get_block() == &SNOW {
+        let layers: u8 = state.expect(&PROP_SNOW_LAYERS);
+        if layers >= max_layers {
+            return false;
+        }
+        state.with(&PROP_SNOW_LAYERS, layers + 1).unwrap()
+    } else {
+        return false;
+    };
+
+    chunk.set_block_at(x, y, z, new_state).is_ok()
+
+}
+
+
+/// Fill the cauldron at the given position depending on the precipitation, empty cauldrons
+/// are filled by water with rain or powder snow with snow. Return true if the cauldron has
+/// been filled.
+pub fn fill_cauldron(chunk: &mut Chunk, x: i32, y: i32, z: i32, precipitation: Precipitation, rand: &mut JavaRandom) -> bool {
+
+    let state = match chunk.get_block_at(x, y, z) {
+        Ok(state) => state,
+        Err(_) => return false
+    };
+
+    let block = state.get_block();
+    let chance = match precipitation {
+        Precipitation::None => return false,
+        Precipitation::Rain => 0.05,
+        Precipitation::Snow => 0.1
+    };
+
+    let new_state = if block == &CAULDRON {
+        match precipitation {
+            Precipitation::Rain => WATER_CAULDRON.get_default_state(),
+            _ => POWDER_SNOW_CAULDRON.get_default_state()
+        }
+    } else if (block == &WATER_CAULDRON && precipitation == Precipitation::Rain)
+        || (block == &POWDER_SNOW_CAULDRON && precipitation == Precipitation::Snow)
+    {
+        let level: u8 = state.expect(&PROP_CAULDRON_LEVEL);
+        if level >= 3 {
+            return false;
+        }
+        state.with(&PROP_CAULDRON_LEVEL, level + 1).unwrap()
+    } else {
+        return false;
+    };
+
+    if rand.next_float() >= chance {
+        return false;
+    }
+
+    chunk.set_block_at(x, y, z, new_state).is_ok()
+
+}
+
+
+/// Find the position where a lightning bolt falls around the given column, the bolt is
+/// redirected to the nearest lightning rod exposed to the sky, or else to a random living
+/// entity under the sky near the column.
+pub fn find_lightning_target(level: &Level, x: i32, z: i32, rand: &mut JavaRandom) -> BlockPos {
+
+    let (mut y, min_y, max_y) = match level.chunks.get_chunk_at(x, z) {
+        Some(chunk) => {
+            let height = chunk.get_height();
+            (chunk.get_heightmap_column_at(&MOTION_BLOCKING, x, z).unwrap_or(0), height.get_min_block(), height.get_max_block())
+        }
+        None => return BlockPos::new(x, 0, z)
+    };
+
+    let pos = BlockPos::new(x, y, z);
+    if let Some(rod) = find_lightning_rod(level, &pos) {
+        return rod.add(0, 1, 0);
+    }
+
+    let targets: Vec<BlockPos> = level.entities.ecs.query::<(&BaseEntity, &LivingEntity)>()
+        .iter()
+        .map(|(_, (base_entity, living_entity))| (BlockPos::from(&base_entity.pos), living_entity))
+        .filter(|(entity_pos, living_entity)| {
+            living_entity.get_health() > 0.0
+                && (entity_pos.x - x).abs() <= 3
+                && (entity_pos.z - z).abs() <= 3
+                && entity_pos.y >= y - 3 && entity_pos.y <= max_y + 3
+                && can_see_sky(level, entity_pos)
+        })
+        .map(|(entity_pos, _)| entity_pos)
+        .collect();
+
+    if !targets.is_empty() {
+        return targets[rand.next_int_bounded(targets.len() as i32) as usize].clone();
+    }
+
+    if y == min_y {
+        // Nothing in the column, lightning is struck above the void.
+        y += 2;
+    }
+
+    BlockPos::new(x, y, z)
+
+}
+
+
+/// Find the nearest lightning rod exposed to the sky around the given position.
+fn find_lightning_rod(level: &Level, pos: &BlockPos) -> Option<BlockPos> {
+
+    let mut nearest: Option<(i32, BlockPos)> = None;
+
+    let (pcx, pcz) = (pos.x >> 4, pos.z >> 4);
+    let chunk_radius = (LIGHTNING_ROD_RADIUS >> 4) + 1;
+
+    for chunk in level.chunks.iter_chunks() {
+
+        let chunk = chunk.read().unwrap();
+        let (cx, cz) = chunk.get_position();
+        if (cx - pcx).abs() > chunk_radius || (cz - pcz).abs() > chunk_radius {
+            continue;
+        }
+
+        for bx in 0..16 {
+            for bz in 0..16 {
+                let y = match chunk.get_heightmap_column(&WORLD_SURFACE, bx, bz) {
+                    Ok(height) => height - 1,
+                    Err(_) => continue
+                };
+                if !matches!(chunk.get_block(bx, y, bz), Ok(state) if state.get_block() == &LIGHTNING_ROD) {
+                    continue;
+                }
+                let rod = BlockPos::new(cx * 16 + bx as i32, y, cz * 16 + bz as i32);
+                let (dx, dy, dz) = (rod.x - pos.x, rod.y - pos.y, rod.z - pos.z);
+                let dist = dx * dx + dy * dy + dz * dz;
+                if dist <= LIGHTNING_ROD_RADIUS * LIGHTNING_ROD_RADIUS && nearest.as_ref().is_none_or(|&(nearest_dist, _)| dist < nearest_dist) {
+                    nearest = Some((dist, rod));
+                }
+            }
+        }
+
+    }
+
+    nearest.map(|(_, rod)| rod)
+
+}
+
+
+/// The result of a lightning strike.
+#[derive(Debug)]
+pub struct LightningStrike {
+    pub pos: BlockPos,
+    /// The lightning rod powered by this strike, vanilla unpowers it after 8 ticks.
+    pub powered_rod: Option<BlockPos>,
+    /// Positions where fire has been placed.
+    pub fires: Vec<BlockPos>
+}
+
+
+/// Strike a lightning bolt at the given position, the lightning rod under the position is
+/// powered and fire is spread around if enabled, vanilla disables fire in peaceful difficulty
+/// or if the `doFireTick` game rule is false.
+pub fn strike_lightning(level: &Level, pos: BlockPos, rand: &mut JavaRandom, spawn_fire: bool) -> LightningStrike {
+
+    let mut strike = LightningStrike {
+        powered_rod: None,
+        fires: Vec::new(),
+        pos
+    };
+
+    let below = strike.pos.add(0, -1, 0);
+    if let Some(mut chunk) = level.chunks.get_chunk_at_block_mut(below.clone()) {
+        if let Ok(state) = chunk.get_block_at(below.x, below.y, below.z) {
+            if state.get_block() == &LIGHTNING_ROD {
+                let powered = state.with(&PROP_POWERED, true).unwrap();
+                if chunk.set_block_at(below.x, below.y, below.z, powered).is_ok() {
+                    strike.powered_rod = Some(below);
+                }
+            }
+        }
+    }
+
+    if spawn_fire {
+        if place_fire(level, &strike.pos) {
+            strike.fires.push(strike.pos.clone());
+        }
+        for _ in 0..LIGHTNING_EXTRA_FIRES {
+            let dx = rand.next_int_bounded(3) - 1;
+            let dy = rand.next_int_bounded(3) - 1;
+            let dz = rand.next_int_bounded(3) - 1;
+            let fire_pos = strike.pos.add(dx, dy, dz);
+            if place_fire(level, &fire_pos) {
+                strike.fires.push(fire_pos);
+            }
+        }
+    }
+
+    strike
+
+}
+
+
+/// Place fire at the given position if it's air and fire can survive there.
+fn place_fire(level: &Level, pos: &BlockPos) -> bool {
+
+    let get_block = |x: i32, y: i32, z: i32| -> Option<&'static Block> {
+        level.chunks.get_chunk_at(x, z)?.get_block_at(x, y, z).ok().map(|state| state.get_block())
+    };
+
+    if !get_block(pos.x, pos.y, pos.z).is_some_and(is_air) {
+        return false;
+    }
+
+    let below = get_block(pos.x, pos.y - 1, pos.z);
+    let materials = &*VANILLA_BLOCK_MATERIALS;
+
+    let fire = match below {
+        Some(block) if block == &SOUL_SAND || block == &SOUL_SOIL => &SOUL_FIRE,
+        _ => &FIRE
+    };
+
+    // Fire needs a solid block below or a flammable neighbor.
+    let can_survive = below.is_some_and(|block| materials.blocks_motion(block)) || [
+        (1, 0, 0), (-1, 0, 0), (0, 1, 0), (0, -1, 0), (0, 0, 1), (0, 0, -1)
+    ].iter().any(|&(dx, dy, dz)| {
+        get_block(pos.x + dx, pos.y + dy, pos.z + dz).is_some_and(|block| materials.is_flammable(block))
+    });
+
+    if !can_survive {
+        return false;
+    }
+
+    match level.chunks.get_chunk_at_mut(pos.x, pos.z) {
+        Some(mut chunk) => chunk.set_block_at(pos.x, pos.y, pos.z, fire.get_default_state()).is_ok(),
+        None => false
+    }
+
+}
+
+
+fn can_see_sky(level: &Level, pos: &BlockPos) -> bool {
+    match level.chunks.get_chunk_at(pos.x, pos.z) {
+        Some(chunk) => chunk.get_heightmap_column_at(&WORLD_SURFACE, pos.x, pos.z).is_ok_and(|height| pos.y >= height),
+        None => false
+    }
+}
+
+
+#[inline]
+fn is_air(block: &'static Block) -> bool {
+    block == &AIR || block == &CAVE_AIR || block == &VOID_AIR
+}
+
+
+fn can_snow_survive_on(block: &'static Block) -> bool {
+    if block == &ICE || block == &PACKED_ICE || block == &BARRIER {
+        false
+    } else if block == &HONEY_BLOCK || block == &SOUL_SAND {
+        true
+    } else {
+        VANILLA_BLOCK_MATERIALS.blocks_motion(block)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::source::{ChunkLoadRequest, NullLevelSource};
+    use mc_core::world::level::LevelEnv;
+    use mc_core::pos::EntityPos;
+
+    use crate::biome::{PLAINS, SNOWY_PLAINS};
+    use crate::entity::PIG;
+    use crate::ext::VanillaLevelEnv;
+    use super::*;
+
+    fn build_level() -> Level {
+        let env = LevelEnv::vanilla();
+        let height = ChunkHeight::new(0, 7);
+        let level = Level::new("overworld".to_string(), env.clone(), height, NullLevelSource);
+        for cx in -1..=1 {
+            for cz in -1..=1 {
+                let mut chunk = ChunkLoadRequest { env: env.clone(), height, cx, cz }.build_chunk();
+                for x in 0..16 {
+                    for z in 0..16 {
+                        for y in 0..64 {
+                            chunk.set_block(x, y, z, STONE.get_default_state()).unwrap();
+                        }
+                    }
+                }
+                let biome = if cx < 0 { &SNOWY_PLAINS } else { &PLAINS };
+                for x in 0..4 {
+                    for z in 0..4 {
+                        for y in 0..32 {
+                            chunk.set_biome(x, y, z, biome).unwrap();
+                        }
+                    }
+                }
+                level.chunks.insert_chunk(chunk);
+            }
+        }
+        level
+    }
+
+    #[test]
+    fn snow_and_cauldrons() {
+
+        let level = build_level();
+        let mut rand = JavaRandom::new(0);
+
+        {
+            let mut chunk = level.chunks.get_chunk_at_mut(-8, 8).unwrap();
+            assert!(accumulate_snow(&mut chunk, -8, 64, 8, 2));
+            assert!(accumulate_snow(&mut chunk, -8, 64, 8, 2));
+            assert!(!accumulate_snow(&mut chunk, -8, 64, 8, 2));
+            assert_eq!(chunk.get_block_at(-8, 64, 8).unwrap().expect::<u8, _>(&PROP_SNOW_LAYERS), 2);
+        }
+
+        {
+            let mut chunk = level.chunks.get_chunk_at_mut(8, 8).unwrap();
+            assert!(!accumulate_snow(&mut chunk, 8, 64, 8, 2));
+            chunk.set_block_at(8, 64, 8, CAULDRON.get_default_state()).unwrap();
+            while !fill_cauldron(&mut chunk, 8, 64, 8, Precipitation::Rain, &mut rand) {}
+            assert_eq!(chunk.get_block_at(8, 64, 8).unwrap().get_block(), &WATER_CAULDRON);
+            for _ in 0..1000 {
+                fill_cauldron(&mut chunk, 8, 64, 8, Precipitation::Rain, &mut rand);
+            }
+            assert_eq!(chunk.get_block_at(8, 64, 8).unwrap().expect::<u8, _>(&PROP_CAULDRON_LEVEL), 3);
+            assert!(!fill_cauldron(&mut chunk, 8, 64, 8, Precipitation::Snow, &mut rand));
+        }
+
+        let mut weather = LevelWeather::new();
+        weather.set_rain(1000, false);
+        for _ in 0..100 {
+            weather.tick(&mut rand);
+        }
+
+        assert!(!is_raining_at(&level, &weather, &BlockPos::new(8, 64, 8)));
+        assert!(is_raining_at(&level, &weather, &BlockPos::new(9, 64, 8)));
+        assert!(!is_raining_at(&level, &weather, &BlockPos::new(-9, 64, 8)));
+
+    }
+
+    #[test]
+    fn lightning() {
+
+        let mut level = build_level();
+        let mut rand = JavaRandom::new(0);
+
+        // Without rod or entity, the lightning strikes the column.
+        assert_eq!(find_lightning_target(&level, 4, 4, &mut rand), BlockPos::new(4, 64, 4));
+
+        let pig = level.spawn_entity(&PIG, EntityPos::new(6.5, 64.0, 4.5)).unwrap();
+        level.entities.ecs.get_mut::<LivingEntity>(pig).unwrap().set_health(10.0);
+        assert_eq!(find_lightning_target(&level, 4, 4, &mut rand), BlockPos::new(6, 64, 4));
+        level.entities.remove_entity(pig);
+
+        level.chunks.get_chunk_at_mut(20, 20).unwrap()
+            .set_block_at(20, 64, 20, LIGHTNING_ROD.get_default_state()).unwrap();
+        let target = find_lightning_target(&level, 4, 4, &mut rand);
+        assert_eq!(target, BlockPos::new(20, 65, 20));
+
+        let strike = strike_lightning(&level, target, &mut rand, true);
+        assert_eq!(strike.powered_rod, Some(BlockPos::new(20, 64, 20)));
+        let rod = level.chunks.get_block_at(20, 64, 20).unwrap();
+        assert!(rod.expect::<bool, _>(&PROP_POWERED));
+
+        let strike = strike_lightning(&level, BlockPos::new(4, 64, 4), &mut rand, true);
+        assert!(strike.powered_rod.is_none());
+        assert!(strike.fires.contains(&BlockPos::new(4, 64, 4)));
+        assert_eq!(level.chunks.get_block_at(4, 64, 4).unwrap().get_block(), &FIRE);
+
+    }
+
+}
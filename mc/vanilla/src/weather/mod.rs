@@ -0,0 +1,193 @@
+//! Vanilla weather cycle of a level, with the effects of the weather on the world in the
+//! `effects` module.
+
+use mc_core::rand::JavaRandom;
+
+pub mod effects;
+
+
+/// Weather state of a level, rain and thunder are alternating with clear weather following
+/// vanilla random durations. Rain and thunder levels are smoothly increasing or decreasing
+/// between 0 and 1 when the weather changes.
+#[derive(Debug, Clone)]
+pub struct LevelWeather {
+    raining: bool,
+    rain_time: i32,
+    thundering: bool,
+    thunder_time: i32,
+    /// Remaining ticks of forced clear weather.
+    clear_time: i32,
+    rain_level: f32,
+    thunder_level: f32,
+    weather_cycle: bool
+}
+
+impl LevelWeather {
+
+    pub fn new() -> Self {
+        Self {
+            raining: false,
+            rain_time: 0,
+            thundering: false,
+            thunder_time: 0,
+            clear_time: 0,
+            rain_level: 0.0,
+            thunder_level: 0.0,
+            weather_cycle: true
+        }
+    }
+
+    /// Enable or disable the weather cycle, like the `doWeatherCycle` game rule.
+    pub fn with_weather_cycle(mut self, weather_cycle: bool) -> Self {
+        self.weather_cycle = weather_cycle;
+        self
+    }
+
+    /// Force a clear weather for the given number of ticks, like the `/weather clear` command.
+    pub fn set_clear(&mut self, duration: i32) {
+        self.clear_time = duration;
+        self.rain_time = 0;
+        self.thunder_time = 0;
+        self.raining = false;
+        self.thundering = false;
+    }
+
+    /// Force rain for the given number of ticks, optionally with thunder, like the
+    /// `/weather rain` and `/weather thunder` commands.
+    pub fn set_rain(&mut self, duration: i32, thundering: bool) {
+        self.clear_time = 0;
+        self.rain_time = duration;
+        self.thunder_time = duration;
+        self.raining = true;
+        self.thundering = thundering;
+    }
+
+    /// Stop the rain and thunder and let the cycle choose their next durations, like vanilla
+    /// when players wake up in the morning. Nothing is done if the weather cycle is disabled.
+    pub fn reset_cycle(&mut self) {
+        if self.weather_cycle {
+            self.rain_time = 0;
+            self.thunder_time = 0;
+            self.raining = false;
+            self.thundering = false;
+        }
+    }
+
+    /// Advance the weather by one tick, the random is used to choose the duration of the
+    /// next weather, like vanilla.
+    pub fn tick(&mut self, rand: &mut JavaRandom) {
+
+        if self.weather_cycle {
+            if self.clear_time > 0 {
+                self.clear_time -= 1;
+                self.thunder_time = if self.thundering { 0 } else { 1 };
+                self.rain_time = if self.raining { 0 } else { 1 };
+                self.thundering = false;
+                self.raining = false;
+            } else {
+
+                if self.thunder_time > 0 {
+                    self.thunder_time -= 1;
+                    if self.thunder_time == 0 {
+                        self.thundering = !self.thundering;
+                    }
+                } else if self.thundering {
+                    self.thunder_time = rand.next_int_bounded(12000) + 3600;
+                } else {
+                    self.thunder_time = rand.next_int_bounded(168000) + 12000;
+                }
+
+                if self.rain_time > 0 {
+                    self.rain_time -= 1;
+                    if self.rain_time == 0 {
+                        self.raining = !self.raining;
+                    }
+                } else if self.raining {
+                    self.rain_time = rand.next_int_bounded(12000) + 12000;
+                } else {
+                    self.rain_time = rand.next_int_bounded(168000) + 12000;
+                }
+
+            }
+        }
+
+        let thunder_delta = if self.thundering { 0.01 } else { -0.01 };
+        self.thunder_level = (self.thunder_level + thunder_delta).clamp(0.0, 1.0);
+        let rain_delta = if self.raining { 0.01 } else { -0.01 };
+        self.rain_level = (self.rain_level + rain_delta).clamp(0.0, 1.0);
+
+    }
+
+    /// Return the rain level, between 0 and 1.
+    #[inline]
+    pub fn get_rain_level(&self) -> f32 {
+        self.rain_level
+    }
+
+    /// Return the thunder level, between 0 and 1, thunder is only effective with rain.
+    #[inline]
+    pub fn get_thunder_level(&self) -> f32 {
+        self.thunder_level * self.rain_level
+    }
+
+    /// Return true if the rain is strong enough to have effects on the world.
+    #[inline]
+    pub fn is_raining(&self) -> bool {
+        self.get_rain_level() > 0.2
+    }
+
+    /// Return true if the thunder is strong enough to strike lightning bolts.
+    #[inline]
+    pub fn is_thundering(&self) -> bool {
+        self.get_thunder_level() > 0.9
+    }
+
+}
+
+impl Default for LevelWeather {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn weather_cycle() {
+
+        let mut rand = JavaRandom::new(0);
+
+        let mut weather = LevelWeather::new();
+        weather.set_rain(400, true);
+        for _ in 0..150 {
+            weather.tick(&mut rand);
+        }
+        assert_eq!(weather.get_rain_level(), 1.0);
+        assert!(weather.is_raining());
+        assert!(weather.is_thundering());
+
+        weather.set_clear(1000);
+        for _ in 0..50 {
+            weather.tick(&mut rand);
+        }
+        assert!((weather.get_rain_level() - 0.5).abs() < 0.001);
+        assert!(!weather.is_thundering());
+        for _ in 0..50 {
+            weather.tick(&mut rand);
+        }
+        assert!(!weather.is_raining());
+
+        // Without a weather cycle, the weather is never changing.
+        let mut weather = LevelWeather::new().with_weather_cycle(false);
+        for _ in 0..200000 {
+            weather.tick(&mut rand);
+        }
+        assert!(!weather.is_raining());
+
+    }
+
+}
@@ -0,0 +1,47 @@
+//! Benchmarks of biome sources behind the `BiomeSource` trait, each source is queried for the
+//! biomes of chunks with static dispatch (generic fast path) and with dynamic dispatch through
+//! `dyn BiomeSource`, to compare the overhead of trait objects with the cost of the sources.
+
+use criterion::{criterion_group, criterion_main, Criterion, black_box};
+
+use mc_worldgen::gen::legacy::{BiomeSource, ChunkBiomeCache, BiomeResolution, ChunkGenerator};
+use mc_worldgen::gen::biome_source::{FixedBiomeSource, MultiNoiseBiomeSource};
+use mc_worldgen::gen::r102::R102Generator;
+
+use mc_vanilla::biome::PLAINS;
+
+
+const SEED: i64 = 3048926232851431861;
+
+
+/// Query the biomes of a square of 4x4 chunks, at block and noise resolutions.
+fn query_chunks<B: BiomeSource + ?Sized>(source: &mut B) {
+    for cz in 0..4 {
+        for cx in 0..4 {
+            black_box(ChunkBiomeCache::new(source, cx, cz, BiomeResolution::Block));
+            black_box(ChunkBiomeCache::new(source, cx, cz, BiomeResolution::Noise));
+        }
+    }
+}
+
+fn bench_source<B: BiomeSource + 'static>(c: &mut Criterion, name: &str, mut source: B) {
+
+    c.bench_function(&format!("biome_source_{}_static", name), |b| b.iter(|| {
+        query_chunks(&mut source);
+    }));
+
+    let mut source: Box<dyn BiomeSource> = Box::new(source);
+    c.bench_function(&format!("biome_source_{}_dyn", name), |b| b.iter(|| {
+        query_chunks(black_box(&mut *source));
+    }));
+
+}
+
+fn bench_sources(c: &mut Criterion) {
+    bench_source(c, "layers", R102Generator::new(SEED).build_biome_source());
+    bench_source(c, "multi_noise", MultiNoiseBiomeSource::approximate_nether(SEED));
+    bench_source(c, "fixed", FixedBiomeSource::new(&PLAINS));
+}
+
+criterion_group!(benches, bench_sources);
+criterion_main!(benches);
@@ -1,5 +1,4 @@
 use std::time::Duration;
-use std::sync::Arc;
 
 use mc_core::world::source::LoadOrGenLevelSource;
 use mc_core::world::anvil::source::AnvilLevelSource;
@@ -7,9 +6,9 @@ use mc_core::world::level::{Level, LevelEnv};
 use mc_core::world::chunk::ChunkHeight;
 
 use mc_worldgen::gen::legacy::LegacyGenLevelSource;
-use mc_worldgen::gen::r102::R102Provider;
+use mc_worldgen::gen::r102::R102Generator;
 
-use mc_vanilla::ext::WithVanilla;
+use mc_vanilla::ext::VanillaLevelEnv;
 
 fn main() {
 
@@ -20,8 +19,8 @@ fn main() {
     let region_dir = std::env::var("REGION_DIR").expect("Missing region dir");
     let anvil_source = AnvilLevelSource::new(region_dir);
 
-    let gen_provider = R102Provider::new(3048926232851431861);
-    let gen_source = LegacyGenLevelSource::new(gen_provider, 4);
+    let generator = R102Generator::new(3048926232851431861);
+    let gen_source = LegacyGenLevelSource::new(generator, 4);
 
     let load_or_gen_source = LoadOrGenLevelSource::new(
         anvil_source,
@@ -30,7 +29,7 @@ fn main() {
 
     let mut level = Level::new(
         "overworld".to_string(),
-        Arc::new(LevelEnv::with_vanilla()),
+        LevelEnv::vanilla(),
         ChunkHeight::new(0, 15),
         load_or_gen_source
     );
@@ -0,0 +1,56 @@
+use mc_core::rand::JavaRandom;
+use mc_core::pos::Axis;
+
+use mc_worldgen::debug::{DebugExporter, BiomeColorMap, GradientColorMap, BoolColorMap};
+use mc_worldgen::noise::{PerlinNoiseOctaves, NoiseCube};
+use mc_worldgen::layer::{LayerBuilder, Layer};
+
+
+const SEED: i64 = 3048926232851431861;
+
+
+fn main() {
+
+    let dir = std::env::args().nth(1).unwrap_or_else(|| "debug_export".to_string());
+    let exporter = DebugExporter::new(dir).unwrap();
+
+    let mut biome = LayerBuilder::with_island(1)
+        .then_zoom_fuzzy(2000)
+        .then_add_island(1)
+        .then_zoom_smart(2001)
+        .then_add_island(2)
+        .then_add_snow(2)
+        .then_zoom_smart(2002)
+        .then_add_island(3)
+        .then_zoom_smart(2003)
+        .then_add_island(4)
+        .then_add_mushroom_island(5)
+        .build();
+
+    biome.seed(SEED);
+    exporter.export_layer_png("islands", &mut biome, -64, -64, (128, 128), &BiomeColorMap::with_vanilla()).unwrap();
+
+    let mut river = LayerBuilder::with_island(1)
+        .then_zoom_fuzzy(2000)
+        .then_init_river(100)
+        .then_zoom_smart(1000)
+        .then_zoom_smart(1001)
+        .then_add_river()
+        .build();
+
+    river.seed(SEED);
+    exporter.export_layer_png("rivers", &mut river, -128, -128, (256, 256), &BoolColorMap::new([0, 0, 255], [255; 3])).unwrap();
+
+    let mut rand = JavaRandom::new(SEED);
+    let noise = PerlinNoiseOctaves::<16>::new(&mut rand);
+    let mut cube = NoiseCube::new_default(64, 32, 64);
+    noise.generate_3d(&mut cube, 0, 0, 0, 684.412 / 16.0, 684.412 / 16.0, 684.412 / 16.0);
+
+    let map = GradientColorMap::new_grayscale_fit(&cube.data);
+    exporter.export_cube_slice_png("noise_y16", &cube, Axis::Y, 16, &map).unwrap();
+    exporter.export_cube_slice_png("noise_x0", &cube, Axis::X, 0, &map).unwrap();
+    exporter.export_cube_slice_csv("noise_x0", &cube, Axis::X, 0).unwrap();
+
+    println!("Exported to {}", exporter.get_dir().display());
+
+}
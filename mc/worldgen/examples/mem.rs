@@ -1,7 +1,9 @@
-use mc_worldgen::gen::release102::LevelGenRelease102;
+use mc_worldgen::gen::r102::{R102BiomeSource, R102NoiseGenerator, R102FeatureGenerator};
 
 fn main() {
 
-    println!("Size of generator: {}o", std::mem::size_of::<LevelGenRelease102>());
+    println!("Size of biome source: {}o", std::mem::size_of::<R102BiomeSource>());
+    println!("Size of noise generator: {}o", std::mem::size_of::<R102NoiseGenerator>());
+    println!("Size of feature generator: {}o", std::mem::size_of::<R102FeatureGenerator>());
 
 }
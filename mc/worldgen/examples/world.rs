@@ -1,36 +1,51 @@
-use minecraftrs::version::Version;
-use minecraftrs::world::World;
-use minecraftrs::world::chunk::Chunk;
-
 use std::fs::File;
 use std::io::{Result as IoResult, prelude::*};
 use std::time::Instant;
 
+use mc_core::world::level::{Level, LevelEnv};
+use mc_core::world::chunk::{Chunk, ChunkHeight};
+
+use mc_worldgen::gen::legacy::LegacyGenLevelSource;
+use mc_worldgen::gen::r102::R102Generator;
+
+use mc_vanilla::ext::VanillaLevelEnv;
+
+
+const SEED: i64 = 3048926232851431861;
+
 
 fn main() {
 
-    // 3048926232851431861
-    let mut world: World = World::new(3048926232851431861, Version::RELEASE_1_2_5);
+    let source = LegacyGenLevelSource::new(R102Generator::new(SEED), 4);
 
-    println!("World seed: {}", world.get_info().seed);
+    let mut level = Level::new(
+        "overworld".to_string(),
+        LevelEnv::vanilla(),
+        ChunkHeight::new(0, 15),
+        source
+    );
+
+    println!("World seed: {}", SEED);
 
     let center_x = -24;
     let center_z = 37;
 
-    //world.provide_chunk(center_x, center_z).unwrap();
-
     let start = Instant::now();
 
     for x in (center_x - 3)..=(center_x + 3) {
         for z in (center_z - 3)..=(center_z + 3) {
-            world.provide_chunk(x, z).unwrap();
+            level.request_chunk_load(x, z);
         }
     }
 
-    println!("Generated {} chunks in {}s", world.get_chunks().len(), start.elapsed().as_secs_f32());
+    level.load_chunks_blocking();
 
-    /*let file = File::create("world.obj").unwrap();
-    render_world_to_obj(file, &world).unwrap();*/
+    println!("Generated {} chunks in {}s", level.chunks.get_chunks_count(), start.elapsed().as_secs_f32());
+
+    if let Some(path) = std::env::args().nth(1) {
+        let file = File::create(path).unwrap();
+        render_world_to_obj(file, &level).unwrap();
+    }
 
 }
 
@@ -43,25 +58,26 @@ const MASK_TOP: u8 = 0x10;
 const MASK_BOTTOM: u8 = 0x20;
 
 
-fn render_world_to_obj(mut file: File, world: &World) -> IoResult<()> {
+fn render_world_to_obj(mut file: File, level: &Level) -> IoResult<()> {
 
-    file.write_fmt(format_args!("# World export: {}\n\n", world.get_info().seed))?;
+    file.write_fmt(format_args!("# World export: {}\n\n", SEED))?;
     file.write_fmt(format_args!("o world\n\n"))?;
 
     let mut index = 1;
     let mut objfaces: Vec<(usize, usize, usize)> = Vec::new();
 
-    for chunk in world.get_chunks().values() {
+    for chunk in level.chunks.iter_chunks() {
 
+        let chunk = chunk.read().unwrap();
         let (cx, cz) = chunk.get_position();
 
         println!("Rendering chunk at {}/{}", cx, cz);
 
-        for y in 0..chunk.get_max_height() {
+        for y in chunk.get_height().get_min_block()..=chunk.get_height().get_max_block() {
             for x in 0..16 {
                 for z in 0..16 {
 
-                    let (faces, bx, bz) = get_block_render(world, chunk, x, y, z);
+                    let (faces, bx, bz) = get_block_render(level, &*chunk, x, y, z);
 
                     if faces & MASK_NORTH == MASK_NORTH {
                         file.write_fmt(format_args!("v {} {} {}\n", bx + 1, y + 0, bz + 0))?;
@@ -140,65 +156,77 @@ fn render_world_to_obj(mut file: File, world: &World) -> IoResult<()> {
 }
 
 
-fn get_block_render(world: &World, chunk: &Chunk, x: usize, y: usize, z: usize) -> (u8, i32, i32) {
+fn get_block_render(level: &Level, chunk: &Chunk, x: u8, y: i32, z: u8) -> (u8, i32, i32) {
+
+    let blocks = &level.get_env().blocks;
+    let height = chunk.get_height();
+
+    // Return the block save ID at the given position, u32::MAX if not loaded.
+    let get_level_block = |bx: i32, by: i32, bz: i32| {
+        level.chunks.get_block_at(bx, by, bz).ok()
+            .and_then(|state| blocks.get_sid_from(state))
+            .unwrap_or(u32::MAX)
+    };
 
-    let block = chunk.get_block_id(x, y, z);
+    let get_chunk_block = |x: u8, y: i32, z: u8| {
+        chunk.get_block(x, y, z).ok()
+            .and_then(|state| blocks.get_sid_from(state))
+            .unwrap_or(u32::MAX)
+    };
+
+    let block = get_chunk_block(x, y, z);
     let (cx, cz) = chunk.get_position();
     let (cbx, cbz) = (cx << 4, cz << 4);
 
     let mut faces = 0;
 
-    /*if block == 7 {
-        println!("Bedrock at {}/{}/{}", x, y, z);
-    }*/
-
     if block != 0 {
 
         let north_block = if x == 15 {
-            world.get_block_id(cbx + 16, y as i32, cbz + z as i32).unwrap_or(u16::MAX)
+            get_level_block(cbx + 16, y, cbz + z as i32)
         } else {
-            chunk.get_block_id(x + 1, y, z)
+            get_chunk_block(x + 1, y, z)
         };
 
         let south_block = if x == 0 {
-            world.get_block_id(cbx - 1, y as i32, cbz + z as i32).unwrap_or(u16::MAX)
+            get_level_block(cbx - 1, y, cbz + z as i32)
         } else {
-            chunk.get_block_id(x - 1, y, z)
+            get_chunk_block(x - 1, y, z)
         };
 
         let east_block = if z == 15 {
-            world.get_block_id(cbx + x as i32, y as i32, cbz + 16).unwrap_or(u16::MAX)
+            get_level_block(cbx + x as i32, y, cbz + 16)
         } else {
-            chunk.get_block_id(x, y, z + 1)
+            get_chunk_block(x, y, z + 1)
         };
 
         let west_block = if z == 0 {
-            world.get_block_id(cbx + x as i32, y as i32, cbz - 1).unwrap_or(u16::MAX)
+            get_level_block(cbx + x as i32, y, cbz - 1)
         } else {
-            chunk.get_block_id(x, y, z - 1)
+            get_chunk_block(x, y, z - 1)
         };
 
-        let top_block = if y == chunk.get_max_height() - 1 {
+        let top_block = if y == height.get_max_block() {
             0
         } else {
-            chunk.get_block_id(x, y + 1, z)
+            get_chunk_block(x, y + 1, z)
         };
 
-        let bottom_block = if y == 0 {
-            u16::MAX
+        let bottom_block = if y == height.get_min_block() {
+            u32::MAX
         } else {
-            chunk.get_block_id(x, y - 1, z)
+            get_chunk_block(x, y - 1, z)
         };
 
-        if block != north_block && north_block != u16::MAX { faces |= MASK_NORTH; }
-        if block != south_block && south_block != u16::MAX { faces |= MASK_SOUTH; }
-        if block != east_block && east_block != u16::MAX { faces |= MASK_EAST; }
-        if block != west_block && west_block != u16::MAX { faces |= MASK_WEST; }
-        if block != top_block && top_block != u16::MAX { faces |= MASK_TOP; }
-        if block != bottom_block && bottom_block != u16::MAX { faces |= MASK_BOTTOM; }
+        if block != north_block && north_block != u32::MAX { faces |= MASK_NORTH; }
+        if block != south_block && south_block != u32::MAX { faces |= MASK_SOUTH; }
+        if block != east_block && east_block != u32::MAX { faces |= MASK_EAST; }
+        if block != west_block && west_block != u32::MAX { faces |= MASK_WEST; }
+        if block != top_block && top_block != u32::MAX { faces |= MASK_TOP; }
+        if block != bottom_block && bottom_block != u32::MAX { faces |= MASK_BOTTOM; }
 
     }
 
     (faces, cbx + x as i32, cbz + z as i32)
 
-}
\ No newline at end of file
+}
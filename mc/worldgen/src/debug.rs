@@ -0,0 +1,363 @@
+//! Debug exporter for world generation, this module allows worldgen developers to export
+//! layer grids, noise fields and density slices to PNG images or CSV files in order to
+//! visually compare the output of layer stacks or noise generators between changes.
+
+use std::collections::HashMap;
+use std::io::{self, Write, BufWriter};
+use std::path::{Path, PathBuf};
+use std::fmt::Display;
+use std::fs::File;
+
+use mc_core::biome::{Biome, BiomeKey};
+use mc_core::util::{Rect, Cube};
+use mc_core::pos::Axis;
+
+use crate::layer::Layer;
+
+
+/// A trait to implement on structures that map values to RGB colors.
+pub trait ColorMap<T: ?Sized> {
+    fn get_color(&self, value: &T) -> [u8; 3];
+}
+
+impl<T, F> ColorMap<T> for F
+where
+    F: Fn(&T) -> [u8; 3]
+{
+    fn get_color(&self, value: &T) -> [u8; 3] {
+        (self)(value)
+    }
+}
+
+
+/// A color map that linearly interpolates between two colors, values outside of the
+/// range are clamped.
+#[derive(Debug, Clone)]
+pub struct GradientColorMap {
+    min: f64,
+    max: f64,
+    from: [u8; 3],
+    to: [u8; 3]
+}
+
+impl GradientColorMap {
+
+    pub fn new(min: f64, max: f64, from: [u8; 3], to: [u8; 3]) -> Self {
+        Self { min, max, from, to }
+    }
+
+    /// A gradient from black (minimum) to white (maximum).
+    pub fn new_grayscale(min: f64, max: f64) -> Self {
+        Self::new(min, max, [0; 3], [255; 3])
+    }
+
+    /// Construct a grayscale gradient with the minimum and maximum values of the given
+    /// values, this is useful when the range of a noise field is not known.
+    pub fn new_grayscale_fit<'a>(values: impl IntoIterator<Item = &'a f64>) -> Self {
+        let (min, max) = values.into_iter()
+            .fold((f64::MAX, f64::MIN), |(min, max), &v| (min.min(v), max.max(v)));
+        Self::new_grayscale(min, max)
+    }
+
+}
+
+impl ColorMap<f64> for GradientColorMap {
+    fn get_color(&self, value: &f64) -> [u8; 3] {
+        let delta = self.max - self.min;
+        let t = if delta > 0.0 { ((*value - self.min) / delta).clamp(0.0, 1.0) } else { 0.0 };
+        let mut color = [0; 3];
+        for (i, comp) in color.iter_mut().enumerate() {
+            let (from, to) = (self.from[i] as f64, self.to[i] as f64);
+            *comp = (from + (to - from) * t).round() as u8;
+        }
+        color
+    }
+}
+
+
+/// A color map for density fields, negative values (air) are black and positive values
+/// (solid) are white.
+#[derive(Debug, Clone, Copy)]
+pub struct DensityColorMap;
+
+impl ColorMap<f64> for DensityColorMap {
+    fn get_color(&self, value: &f64) -> [u8; 3] {
+        if *value > 0.0 { [255; 3] } else { [0; 3] }
+    }
+}
+
+
+/// A color map for boolean layers such as rivers layers.
+#[derive(Debug, Clone)]
+pub struct BoolColorMap {
+    pub true_color: [u8; 3],
+    pub false_color: [u8; 3]
+}
+
+impl BoolColorMap {
+    pub fn new(true_color: [u8; 3], false_color: [u8; 3]) -> Self {
+        Self { true_color, false_color }
+    }
+}
+
+impl ColorMap<bool> for BoolColorMap {
+    fn get_color(&self, value: &bool) -> [u8; 3] {
+        if *value { self.true_color } else { self.false_color }
+    }
+}
+
+
+/// A color map for biomes layers, colors can be manually set for each biome, biomes without
+/// color get a color derived from their numeric ID.
+pub struct BiomeColorMap {
+    colors: HashMap<BiomeKey, [u8; 3]>
+}
+
+impl BiomeColorMap {
+
+    pub fn new() -> Self {
+        Self {
+            colors: HashMap::new()
+        }
+    }
+
+    /// Construct a color map with the common colors used by biomes viewers for the legacy
+    /// vanilla biomes.
+    pub fn with_vanilla() -> Self {
+        use mc_vanilla::biome::*;
+        let mut map = Self::new();
+        map.set(&OCEAN, [0x00, 0x00, 0x70]);
+        map.set(&PLAINS, [0x8D, 0xB3, 0x60]);
+        map.set(&DESERT, [0xFA, 0x94, 0x18]);
+        map.set(&MOUNTAINS, [0x60, 0x60, 0x60]);
+        map.set(&FOREST, [0x05, 0x66, 0x21]);
+        map.set(&TAIGA, [0x0B, 0x66, 0x59]);
+        map.set(&SWAMP, [0x07, 0xF9, 0xB2]);
+        map.set(&RIVER, [0x00, 0x00, 0xFF]);
+        map.set(&FROZEN_OCEAN, [0x90, 0x90, 0xA0]);
+        map.set(&FROZEN_RIVER, [0xA0, 0xA0, 0xFF]);
+        map.set(&SNOWY_PLAINS, [0xFF, 0xFF, 0xFF]);
+        map.set(&SNOWY_MOUNTAINS, [0xA0, 0xA0, 0xA0]);
+        map.set(&MUSHROOM_FIELDS, [0xFF, 0x00, 0xFF]);
+        map.set(&MUSHROOM_FIELD_SHORE, [0xA0, 0x00, 0xFF]);
+        map.set(&BEACH, [0xFA, 0xDE, 0x55]);
+        map.set(&DESERT_HILLS, [0xD2, 0x5F, 0x12]);
+        map.set(&WOODED_HILLS, [0x22, 0x55, 0x1C]);
+        map.set(&TAIGA_HILLS, [0x16, 0x39, 0x33]);
+        map.set(&MOUNTAIN_EDGE, [0x72, 0x78, 0x9A]);
+        map.set(&JUNGLE, [0x53, 0x7B, 0x09]);
+        map.set(&JUNGLE_HILLS, [0x2C, 0x42, 0x05]);
+        map
+    }
+
+    pub fn set(&mut self, biome: &'static Biome, color: [u8; 3]) {
+        self.colors.insert(biome.get_key(), color);
+    }
+
+}
+
+impl Default for BiomeColorMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ColorMap<&'static Biome> for BiomeColorMap {
+    fn get_color(&self, value: &&'static Biome) -> [u8; 3] {
+        match self.colors.get(&value.get_key()) {
+            Some(&color) => color,
+            None => {
+                // Simple integer hash to get distinct colors for close IDs.
+                let hash = (value.get_id() as u32).wrapping_mul(0x9E3779B1);
+                [(hash >> 24) as u8, (hash >> 16) as u8, (hash >> 8) as u8]
+            }
+        }
+    }
+}
+
+
+/// Extract a 2D slice of the given cube, perpendicular to the given axis and at the given
+/// index on this axis. The returned rectangle's axes are (Z, Y) for X axis, (X, Z) for Y axis
+/// and (X, Y) for Z axis.
+pub fn slice_cube<T: Clone>(cube: &Cube<T>, axis: Axis, index: usize) -> Rect<T> {
+    match axis {
+        Axis::X => build_rect(cube.z_size, cube.y_size, |z, y| cube.get(index, y, z).clone()),
+        Axis::Y => build_rect(cube.x_size, cube.z_size, |x, z| cube.get(x, index, z).clone()),
+        Axis::Z => build_rect(cube.x_size, cube.y_size, |x, y| cube.get(x, y, index).clone()),
+    }
+}
+
+fn build_rect<T>(x_size: usize, z_size: usize, mut func: impl FnMut(usize, usize) -> T) -> Rect<T> {
+    let mut data = Vec::with_capacity(x_size * z_size);
+    for z in 0..z_size {
+        for x in 0..x_size {
+            data.push(func(x, z));
+        }
+    }
+    Rect::from_raw(data, x_size, z_size)
+}
+
+
+/// Write the given rectangle as a RGB PNG image, the X axis is horizontal and the Z axis
+/// is vertical, each cell is a pixel.
+pub fn write_rect_png<T, M, W>(writer: W, rect: &Rect<T>, map: &M) -> io::Result<()>
+where
+    M: ColorMap<T> + ?Sized,
+    W: Write
+{
+
+    let mut encoder = png::Encoder::new(writer, rect.x_size as u32, rect.z_size as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let data: Vec<u8> = rect.data.iter()
+        .flat_map(|value| map.get_color(value))
+        .collect();
+
+    let mut writer = encoder.write_header().map_err(png_to_io_error)?;
+    writer.write_image_data(&data[..]).map_err(png_to_io_error)
+
+}
+
+/// Write the given rectangle as CSV, each line is a Z coordinate and each column is a
+/// X coordinate. The formatter is used to convert values to displayable types.
+pub fn write_rect_csv<T, F, D, W>(mut writer: W, rect: &Rect<T>, mut formatter: F) -> io::Result<()>
+where
+    F: FnMut(&T) -> D,
+    D: Display,
+    W: Write
+{
+    for z in 0..rect.z_size {
+        for x in 0..rect.x_size {
+            if x != 0 {
+                writer.write_all(b",")?;
+            }
+            write!(writer, "{}", formatter(rect.get(x, z)))?;
+        }
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+fn png_to_io_error(err: png::EncodingError) -> io::Error {
+    match err {
+        png::EncodingError::IoError(err) => err,
+        err => io::Error::other(err)
+    }
+}
+
+
+/// A debug exporter that writes files to a given output directory, each export is given
+/// a name that is used as the file name, with an extension depending on the format.
+pub struct DebugExporter {
+    dir: PathBuf
+}
+
+impl DebugExporter {
+
+    /// Create a new exporter that writes to the given directory, the directory is created
+    /// if it doesn't exist yet.
+    pub fn new<P: AsRef<Path>>(dir: P) -> io::Result<Self> {
+        std::fs::create_dir_all(dir.as_ref())?;
+        Ok(Self {
+            dir: dir.as_ref().to_path_buf()
+        })
+    }
+
+    #[inline]
+    pub fn get_dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn create_file(&self, name: &str, ext: &str) -> io::Result<BufWriter<File>> {
+        let path = self.dir.join(format!("{}.{}", name, ext));
+        File::create(path).map(BufWriter::new)
+    }
+
+    /// Export a rectangle to `<name>.png` using the given color map.
+    pub fn export_rect_png<T, M>(&self, name: &str, rect: &Rect<T>, map: &M) -> io::Result<()>
+    where
+        M: ColorMap<T> + ?Sized
+    {
+        write_rect_png(self.create_file(name, "png")?, rect, map)
+    }
+
+    /// Export a rectangle to `<name>.csv` using the given formatter.
+    pub fn export_rect_csv<T, F, D>(&self, name: &str, rect: &Rect<T>, formatter: F) -> io::Result<()>
+    where
+        F: FnMut(&T) -> D,
+        D: Display
+    {
+        let mut writer = self.create_file(name, "csv")?;
+        write_rect_csv(&mut writer, rect, formatter)?;
+        writer.flush()
+    }
+
+    /// Generate a grid of the given size (X, Z) from the given layer and export it to
+    /// `<name>.png`.
+    pub fn export_layer_png<L, M>(&self, name: &str, layer: &mut L, x: i32, z: i32, size: (usize, usize), map: &M) -> io::Result<()>
+    where
+        L: Layer,
+        M: ColorMap<L::Item> + ?Sized
+    {
+        self.export_rect_png(name, &layer.next_grid(x, z, size.0, size.1), map)
+    }
+
+    /// Export a slice of a noise or density cube to `<name>.png`, check `slice_cube` for the
+    /// orientation of the image.
+    pub fn export_cube_slice_png<M>(&self, name: &str, cube: &Cube<f64>, axis: Axis, index: usize, map: &M) -> io::Result<()>
+    where
+        M: ColorMap<f64> + ?Sized
+    {
+        self.export_rect_png(name, &slice_cube(cube, axis, index), map)
+    }
+
+    /// Export a slice of a noise or density cube to `<name>.csv`.
+    pub fn export_cube_slice_csv(&self, name: &str, cube: &Cube<f64>, axis: Axis, index: usize) -> io::Result<()> {
+        self.export_rect_csv(name, &slice_cube(cube, axis, index), |v| *v)
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn gradient_color_map() {
+        let map = GradientColorMap::new_grayscale(-1.0, 1.0);
+        assert_eq!(map.get_color(&-2.0), [0; 3]);
+        assert_eq!(map.get_color(&0.0), [128; 3]);
+        assert_eq!(map.get_color(&1.0), [255; 3]);
+    }
+
+    #[test]
+    fn cube_slice() {
+        let mut cube = Cube::new(2, 3, 4, 0);
+        cube.set(1, 2, 3, 7);
+        let rect = slice_cube(&cube, Axis::Y, 2);
+        assert_eq!((rect.x_size, rect.z_size), (2, 4));
+        assert_eq!(*rect.get(1, 3), 7);
+        let rect = slice_cube(&cube, Axis::X, 1);
+        assert_eq!((rect.x_size, rect.z_size), (4, 3));
+        assert_eq!(*rect.get(3, 2), 7);
+    }
+
+    #[test]
+    fn rect_csv_png() {
+
+        let rect = Rect::from_raw(vec![1, 2, 3, 4, 5, 6], 3, 2);
+
+        let mut csv = Vec::new();
+        write_rect_csv(&mut csv, &rect, |v| *v).unwrap();
+        assert_eq!(String::from_utf8(csv).unwrap(), "1,2,3\n4,5,6\n");
+
+        let mut png = Vec::new();
+        write_rect_png(&mut png, &rect, &|v: &i32| [*v as u8; 3]).unwrap();
+        assert_eq!(&png[1..4], b"PNG");
+
+    }
+
+}
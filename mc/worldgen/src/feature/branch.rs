@@ -1,5 +1,6 @@
 use mc_core::rand::JavaRandom;
 
+use crate::noise::BIOME_INFO_NOISE;
 use crate::view::LevelView;
 use super::Feature;
 
@@ -15,6 +16,25 @@ impl RepeatCount for u16 {
     }
 }
 
+/// A count picked uniformly between 0 and the given maximum (inclusive).
+pub struct RandomCount(pub u16);
+
+impl RepeatCount for RandomCount {
+    fn get_count(&self, rand: &mut JavaRandom) -> u16 {
+        rand.next_int_bounded(self.0 as i32 + 1) as u16
+    }
+}
+
+/// A count picked uniformly between a minimum and a maximum (inclusive), this is the `count`
+/// placement modifier with a uniform integer provider.
+pub struct UniformCount(pub u16, pub u16);
+
+impl RepeatCount for UniformCount {
+    fn get_count(&self, rand: &mut JavaRandom) -> u16 {
+        rand.next_int_bounded((self.1 - self.0) as i32 + 1) as u16 + self.0
+    }
+}
+
 
 /// A feature that repeat a give number of time the given feature.
 pub struct RepeatedFeature<F: Feature, C: RepeatCount> {
@@ -42,6 +62,40 @@ impl<F: Feature, C: RepeatCount> Feature for RepeatedFeature<F, C> {
 }
 
 
+/// A feature repeated a number of times biased by the biome info noise at the given position,
+/// the count is `ceil((noise(x / factor, z / factor) + offset) * ratio)` and can be zero.
+///
+/// Valid for: 1.13 to 1.16.5
+pub struct NoiseCountFeature<F: Feature> {
+    feature: F,
+    noise_to_count_ratio: i32,
+    noise_factor: f64,
+    noise_offset: f64
+}
+
+impl<F: Feature> NoiseCountFeature<F> {
+    pub fn new(feature: F, noise_to_count_ratio: i32, noise_factor: f64, noise_offset: f64) -> Self {
+        Self {
+            feature,
+            noise_to_count_ratio,
+            noise_factor,
+            noise_offset
+        }
+    }
+}
+
+impl<F: Feature> Feature for NoiseCountFeature<F> {
+    fn generate(&self, level: &mut dyn LevelView, rand: &mut JavaRandom, x: i32, y: i32, z: i32) -> bool {
+        let noise = BIOME_INFO_NOISE.get_value_2d(x as f64 / self.noise_factor, z as f64 / self.noise_factor);
+        let count = ((noise + self.noise_offset) * self.noise_to_count_ratio as f64).ceil() as i32;
+        for _ in 0..count {
+            self.feature.generate(level, rand, x, y, z);
+        }
+        true
+    }
+}
+
+
 pub struct OptionalFeature<F: Feature, E: Feature> {
     if_feature: F,
     else_feature: E,
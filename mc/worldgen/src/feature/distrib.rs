@@ -1,11 +1,16 @@
 use mc_core::block::{Block, GlobalBlocks};
+use mc_core::world::chunk::ChunkHeight;
 use mc_core::heightmap::HeightmapType;
 use mc_core::rand::JavaRandom;
+use mc_core::biome::Biome;
+use mc_core::pos::Direction;
 
 use mc_vanilla::block::material::TAG_LEAVES;
 use mc_vanilla::block::AIR;
 
+use crate::structure::carver::HeightProvider;
 use crate::view::LevelView;
+use super::predicate::BlockPredicate;
 use super::Feature;
 
 
@@ -122,14 +127,14 @@ impl Distrib for HeightmapDistrib {
 pub struct LavaLakeDistrib;
 
 impl Distrib for LavaLakeDistrib {
-    fn pick_pos(&self, _level: &mut dyn LevelView, rand: &mut JavaRandom, x: i32, _y: i32, z: i32) -> Option<(i32, i32, i32)> {
+    fn pick_pos(&self, level: &mut dyn LevelView, rand: &mut JavaRandom, x: i32, _y: i32, z: i32) -> Option<(i32, i32, i32)> {
         let rx = x + rand.next_int_bounded(16);
         let ry = {
             let ry = rand.next_int_bounded(120) + 8;
             rand.next_int_bounded(ry)
         };
         let rz = z + rand.next_int_bounded(16);
-        if ry < 63 || rand.next_int_bounded(10) == 0 {
+        if ry < level.get_settings().sea_level || rand.next_int_bounded(10) == 0 {
             Some((rx, ry, rz))
         } else {
             None
@@ -138,6 +143,19 @@ impl Distrib for LavaLakeDistrib {
 }
 
 
+/// A distribution specific to icebergs, picking X and Z in the center of the chunk (from 4
+/// to 11 included) and keeping Y.
+pub struct IcebergDistrib;
+
+impl Distrib for IcebergDistrib {
+    fn pick_pos(&self, _level: &mut dyn LevelView, rand: &mut JavaRandom, x: i32, y: i32, z: i32) -> Option<(i32, i32, i32)> {
+        let rx = x + rand.next_int_bounded(8) + 4;
+        let rz = z + rand.next_int_bounded(8) + 4;
+        Some((rx, y, rz))
+    }
+}
+
+
 /// A distribution that modifies the Y coordinate by lowering it until the given predicate
 /// returns false for the block a `Y + offset`.
 pub struct OffsetWhileDistrib<P> {
@@ -193,3 +211,267 @@ where
         Some((x, y, z))
     }
 }
+
+
+/// A distribution that picks random X and Z in the chunk and keeps Y, this is the `in_square`
+/// placement modifier.
+pub struct InSquareDistrib;
+
+impl Distrib for InSquareDistrib {
+    fn pick_pos(&self, _level: &mut dyn LevelView, rand: &mut JavaRandom, x: i32, y: i32, z: i32) -> Option<(i32, i32, i32)> {
+        let rx = x + rand.next_int_bounded(16);
+        let rz = z + rand.next_int_bounded(16);
+        Some((rx, y, rz))
+    }
+}
+
+
+/// A distribution keeping the position 1 in `chance` times, this is the `rarity_filter`
+/// placement modifier. Unlike `OptionalFeature`, the chance is checked with a random float.
+///
+/// Valid for: 1.18 and later
+pub struct RarityFilterDistrib {
+    chance: u32
+}
+
+impl RarityFilterDistrib {
+    pub fn new(chance: u32) -> Self {
+        Self { chance }
+    }
+}
+
+impl Distrib for RarityFilterDistrib {
+    fn pick_pos(&self, _level: &mut dyn LevelView, rand: &mut JavaRandom, x: i32, y: i32, z: i32) -> Option<(i32, i32, i32)> {
+        if rand.next_float() < 1.0 / self.chance as f32 {
+            Some((x, y, z))
+        } else {
+            None
+        }
+    }
+}
+
+
+/// A distribution keeping X and Z and assigning the height (of the given type) to the Y
+/// coordinate, the position is discarded if the column is empty. This is the `heightmap`
+/// placement modifier, unlike `HeightmapDistrib` it doesn't pick X and Z.
+pub struct HeightmapColumnDistrib {
+    heightmap_type: &'static HeightmapType
+}
+
+impl HeightmapColumnDistrib {
+    pub fn new(heightmap_type: &'static HeightmapType) -> Self {
+        Self { heightmap_type }
+    }
+}
+
+impl Distrib for HeightmapColumnDistrib {
+    fn pick_pos(&self, level: &mut dyn LevelView, _rand: &mut JavaRandom, x: i32, _y: i32, z: i32) -> Option<(i32, i32, i32)> {
+        let ry = level.get_heightmap_column_at(self.heightmap_type, x, z).ok()?;
+        if ry > get_level_height(level, x, z).get_min_block() {
+            Some((x, ry, z))
+        } else {
+            None
+        }
+    }
+}
+
+
+/// A distribution keeping X and Z and picking Y from a height provider, this is the
+/// `height_range` placement modifier.
+pub struct HeightRangeDistrib {
+    height: HeightProvider
+}
+
+impl HeightRangeDistrib {
+    pub fn new(height: HeightProvider) -> Self {
+        Self { height }
+    }
+}
+
+impl Distrib for HeightRangeDistrib {
+    fn pick_pos(&self, level: &mut dyn LevelView, rand: &mut JavaRandom, x: i32, _y: i32, z: i32) -> Option<(i32, i32, i32)> {
+        let height = get_level_height(level, x, z);
+        Some((x, self.height.sample(rand, height), z))
+    }
+}
+
+
+/// A distribution keeping the position only if the given predicate returns true for the
+/// biome at this position. This is the `biome` placement modifier, where the predicate
+/// should check that the biome has the placed feature.
+pub struct BiomeFilterDistrib<P> {
+    predicate: P
+}
+
+impl<P> BiomeFilterDistrib<P>
+where
+    P: Fn(&'static Biome) -> bool
+{
+    pub fn new(predicate: P) -> Self {
+        Self { predicate }
+    }
+}
+
+impl<P> Distrib for BiomeFilterDistrib<P>
+where
+    P: Fn(&'static Biome) -> bool
+{
+    fn pick_pos(&self, level: &mut dyn LevelView, _rand: &mut JavaRandom, x: i32, y: i32, z: i32) -> Option<(i32, i32, i32)> {
+        match level.get_biome_at(x, y, z) {
+            Ok(biome) if (self.predicate)(biome) => Some((x, y, z)),
+            _ => None
+        }
+    }
+}
+
+
+/// A distribution keeping the position only if the given block predicate is true, this
+/// is the `block_predicate_filter` placement modifier.
+pub struct BlockFilterDistrib {
+    predicate: BlockPredicate
+}
+
+impl BlockFilterDistrib {
+    pub fn new(predicate: BlockPredicate) -> Self {
+        Self { predicate }
+    }
+}
+
+impl Distrib for BlockFilterDistrib {
+    fn pick_pos(&self, level: &mut dyn LevelView, _rand: &mut JavaRandom, x: i32, y: i32, z: i32) -> Option<(i32, i32, i32)> {
+        if self.predicate.test(level, x, y, z) {
+            Some((x, y, z))
+        } else {
+            None
+        }
+    }
+}
+
+
+/// A distribution moving the position in the given direction, at most `max_steps` times,
+/// until the target predicate is true. The scan stops if the allowed predicate is false or
+/// if the position leaves the level. This is the `environment_scan` placement modifier.
+pub struct EnvironmentScanDistrib {
+    direction: Direction,
+    max_steps: u32,
+    target: BlockPredicate,
+    allowed: BlockPredicate
+}
+
+impl EnvironmentScanDistrib {
+
+    pub fn new(direction: Direction, max_steps: u32, target: BlockPredicate) -> Self {
+        Self::with_allowed(direction, max_steps, target, BlockPredicate::True)
+    }
+
+    pub fn with_allowed(direction: Direction, max_steps: u32, target: BlockPredicate, allowed: BlockPredicate) -> Self {
+        Self { direction, max_steps, target, allowed }
+    }
+
+}
+
+impl Distrib for EnvironmentScanDistrib {
+    fn pick_pos(&self, level: &mut dyn LevelView, _rand: &mut JavaRandom, x: i32, y: i32, z: i32) -> Option<(i32, i32, i32)> {
+
+        if !self.allowed.test(level, x, y, z) {
+            return None;
+        }
+
+        let height = get_level_height(level, x, z);
+        let (_, dy, _) = self.direction.normal(1);
+
+        let mut y = y;
+        for _ in 0..self.max_steps {
+            if self.target.test(level, x, y, z) {
+                return Some((x, y, z));
+            }
+            y += dy;
+            if y < height.get_min_block() || y > height.get_max_block() {
+                return None;
+            }
+            if !self.allowed.test(level, x, y, z) {
+                break;
+            }
+        }
+
+        if self.target.test(level, x, y, z) {
+            Some((x, y, z))
+        } else {
+            None
+        }
+
+    }
+}
+
+
+/// Return the height of the level at the given column, levels views without chunks are
+/// assumed to have the legacy height.
+fn get_level_height(level: &dyn LevelView, x: i32, z: i32) -> ChunkHeight {
+    level.get_chunk_at(x, z)
+        .map(|chunk| chunk.get_height())
+        .unwrap_or_else(|| ChunkHeight::new(0, 15))
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_vanilla::heightmap::WORLD_SURFACE;
+    use mc_vanilla::biome::{PLAINS, DESERT};
+    use mc_vanilla::block::{STONE, GRASS_BLOCK};
+
+    use crate::structure::carver::VerticalAnchor;
+    use crate::feature::predicate::AIR_PREDICATE;
+    use crate::view::TestLevelView;
+
+    use super::*;
+
+    #[test]
+    fn placement_modifiers() {
+
+        let mut level = TestLevelView::new(STONE.get_default_state(), 63, &PLAINS);
+        let mut rand = JavaRandom::new(0);
+
+        for _ in 0..32 {
+            let (x, y, z) = InSquareDistrib.pick_pos(&mut level, &mut rand, 16, 40, 32).unwrap();
+            assert!((16..32).contains(&x) && (32..48).contains(&z) && y == 40);
+        }
+
+        let rarity = RarityFilterDistrib::new(4);
+        let count = (0..1000).filter(|_| rarity.pick_pos(&mut level, &mut rand, 0, 0, 0).is_some()).count();
+        assert!((150..350).contains(&count));
+
+        let heightmap = HeightmapColumnDistrib::new(&WORLD_SURFACE);
+        assert_eq!(heightmap.pick_pos(&mut level, &mut rand, 3, 0, 5), Some((3, 64, 5)));
+
+        let range = HeightRangeDistrib::new(HeightProvider::Uniform {
+            min: VerticalAnchor::AboveBottom(8),
+            max: VerticalAnchor::Absolute(20)
+        });
+        for _ in 0..32 {
+            let (_, y, _) = range.pick_pos(&mut level, &mut rand, 0, 0, 0).unwrap();
+            assert!((8..=20).contains(&y));
+        }
+
+        let biome = BiomeFilterDistrib::new(|biome| biome == &PLAINS);
+        assert!(biome.pick_pos(&mut level, &mut rand, 0, 0, 0).is_some());
+        let biome = BiomeFilterDistrib::new(|biome| biome == &DESERT);
+        assert!(biome.pick_pos(&mut level, &mut rand, 0, 0, 0).is_none());
+
+        level.set_block_at(0, 50, 0, GRASS_BLOCK.get_default_state()).unwrap();
+        let is_grass = || BlockPredicate::matching_blocks(&[&GRASS_BLOCK]);
+        assert_eq!(EnvironmentScanDistrib::new(Direction::Down, 20, is_grass()).pick_pos(&mut level, &mut rand, 0, 60, 0), Some((0, 50, 0)));
+        assert_eq!(EnvironmentScanDistrib::new(Direction::Down, 5, is_grass()).pick_pos(&mut level, &mut rand, 0, 60, 0), None);
+        assert_eq!(EnvironmentScanDistrib::new(Direction::Up, 20, is_grass()).pick_pos(&mut level, &mut rand, 0, 40, 0), Some((0, 50, 0)));
+
+        let scan = EnvironmentScanDistrib::with_allowed(Direction::Up, 20, is_grass(), AIR_PREDICATE.clone());
+        assert_eq!(scan.pick_pos(&mut level, &mut rand, 0, 40, 0), None);
+        assert_eq!(scan.pick_pos(&mut level, &mut rand, 0, 51, 0), None);
+
+        let filter = BlockFilterDistrib::new(is_grass());
+        assert!(filter.pick_pos(&mut level, &mut rand, 0, 50, 0).is_some());
+        assert!(filter.pick_pos(&mut level, &mut rand, 0, 51, 0).is_none());
+
+    }
+
+}
@@ -1,9 +1,12 @@
 use mc_core::block::{Block, BlockState};
 use mc_core::rand::JavaRandom;
+use mc_core::biome::Biome;
 
+use mc_vanilla::block::material::VANILLA_BLOCK_MATERIALS;
 use mc_vanilla::block::*;
-use mc_vanilla::block::material::TAG_LEAVES;
+use mc_vanilla::biome::{FLOWER_FOREST, PLAINS, SUNFLOWER_PLAINS, SWAMP, SWAMP_HILLS};
 
+use crate::noise::BIOME_INFO_NOISE;
 use crate::view::LevelView;
 
 use super::Feature;
@@ -114,3 +117,312 @@ impl Feature for SugarCaneFeature {
     }
 
 }
+
+
+static FOREST_FLOWERS: [&Block; 11] = [
+    &DANDELION, &POPPY, &ALLIUM, &AZURE_BLUET,
+    &RED_TULIP, &ORANGE_TULIP, &WHITE_TULIP, &PINK_TULIP,
+    &OXEYE_DAISY, &CORNFLOWER, &LILY_OF_THE_VALLEY
+];
+
+
+/// Flower selection rules, each biome uses one of these variants.
+///
+/// Valid for: 1.14 to 1.17.1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowerVariant {
+    /// Dandelion or poppy, used by most biomes.
+    Default,
+    /// Tulips patches following a low frequency noise, or random common flowers.
+    Plains,
+    /// All flowers distributed along a gradient noise.
+    FlowerForest,
+    /// Only blue orchids.
+    Swamp
+}
+
+impl FlowerVariant {
+
+    /// Return the flower variant used by the given biome.
+    pub fn from_biome(biome: &'static Biome) -> Self {
+        if biome == &FLOWER_FOREST {
+            Self::FlowerForest
+        } else if biome == &PLAINS || biome == &SUNFLOWER_PLAINS {
+            Self::Plains
+        } else if biome == &SWAMP || biome == &SWAMP_HILLS {
+            Self::Swamp
+        } else {
+            Self::Default
+        }
+    }
+
+    /// Select a flower to place at the given position.
+    pub fn get_flower(self, rand: &mut JavaRandom, x: i32, z: i32) -> &'static Block {
+        match self {
+            Self::Default => {
+                if rand.next_float() > 0.6666 { &DANDELION } else { &POPPY }
+            }
+            Self::Plains => {
+                let noise = BIOME_INFO_NOISE.get_value_2d(x as f64 / 200.0, z as f64 / 200.0);
+                if noise < -0.8 {
+                    match rand.next_int_bounded(4) {
+                        0 => &ORANGE_TULIP,
+                        1 => &RED_TULIP,
+                        2 => &PINK_TULIP,
+                        _ => &WHITE_TULIP
+                    }
+                } else if rand.next_int_bounded(3) > 0 {
+                    match rand.next_int_bounded(4) {
+                        0 => &POPPY,
+                        1 => &AZURE_BLUET,
+                        2 => &OXEYE_DAISY,
+                        _ => &CORNFLOWER
+                    }
+                } else {
+                    &DANDELION
+                }
+            }
+            Self::FlowerForest => {
+                let noise = BIOME_INFO_NOISE.get_value_2d(x as f64 / 48.0, z as f64 / 48.0);
+                let gradient = ((1.0 + noise) / 2.0).clamp(0.0, 0.9999);
+                FOREST_FLOWERS[(gradient * FOREST_FLOWERS.len() as f64) as usize]
+            }
+            Self::Swamp => &BLUE_ORCHID
+        }
+    }
+
+}
+
+
+/// A flower patch feature, the flower is selected once per patch at its origin, using either
+/// a fixed variant or the variant of the biome at the origin.
+pub struct FlowerFeature {
+    variant: Option<FlowerVariant>,
+    try_count: u32
+}
+
+impl FlowerFeature {
+
+    pub fn new(variant: FlowerVariant) -> Self {
+        Self {
+            variant: Some(variant),
+            try_count: 64
+        }
+    }
+
+    pub fn new_biome_dependent() -> Self {
+        Self {
+            variant: None,
+            try_count: 64
+        }
+    }
+
+    fn get_variant(&self, level: &dyn LevelView, x: i32, y: i32, z: i32) -> FlowerVariant {
+        match self.variant {
+            Some(variant) => variant,
+            None => FlowerVariant::from_biome(level.get_biome_at(x, y, z).unwrap())
+        }
+    }
+
+}
+
+impl Feature for FlowerFeature {
+
+    fn generate(&self, level: &mut dyn LevelView, rand: &mut JavaRandom, x: i32, y: i32, z: i32) -> bool {
+
+        let flower = self.get_variant(level, x, y, z).get_flower(rand, x, z).get_default_state();
+        let mut placed = false;
+
+        for _ in 0..self.try_count {
+
+            let bx = (x + rand.next_int_bounded(8)) - rand.next_int_bounded(8);
+            let by = (y + rand.next_int_bounded(4)) - rand.next_int_bounded(4);
+            let bz = (z + rand.next_int_bounded(8)) - rand.next_int_bounded(8);
+
+            if by > 0 && by < 256 && level.get_block_at(bx, by, bz).unwrap().is_block(&AIR) {
+                let ground_block = level.get_block_at(bx, by - 1, bz).unwrap().get_block();
+                if can_plant_living(ground_block) {
+                    level.set_block_at(bx, by, bz, flower).unwrap();
+                    placed = true;
+                }
+            }
+
+        }
+
+        placed
+
+    }
+
+}
+
+
+/// A patch of two blocks tall plants, such as sunflowers, lilacs or tall grass.
+pub struct DoublePlantFeature {
+    block: &'static Block,
+    try_count: u32
+}
+
+impl DoublePlantFeature {
+
+    pub fn new(block: &'static Block) -> Self {
+        Self {
+            block,
+            try_count: 64
+        }
+    }
+
+}
+
+impl Feature for DoublePlantFeature {
+
+    fn generate(&self, level: &mut dyn LevelView, rand: &mut JavaRandom, x: i32, y: i32, z: i32) -> bool {
+
+        let mut placed = false;
+
+        for _ in 0..self.try_count {
+
+            let bx = (x + rand.next_int_bounded(8)) - rand.next_int_bounded(8);
+            let by = (y + rand.next_int_bounded(4)) - rand.next_int_bounded(4);
+            let bz = (z + rand.next_int_bounded(8)) - rand.next_int_bounded(8);
+
+            if by > 0 && by < 255 && can_place_double_plant(level, bx, by, bz) {
+                place_double_plant(level, self.block, bx, by, bz);
+                placed = true;
+            }
+
+        }
+
+        placed
+
+    }
+
+}
+
+fn can_place_double_plant(level: &dyn LevelView, x: i32, y: i32, z: i32) -> bool {
+    level.get_block_at(x, y, z).unwrap().is_block(&AIR) &&
+        level.get_block_at(x, y + 1, z).unwrap().is_block(&AIR) &&
+        can_plant_living(level.get_block_at(x, y - 1, z).unwrap().get_block())
+}
+
+/// Place both halves of a double plant, the lower half being at the given position.
+pub fn place_double_plant(level: &mut dyn LevelView, block: &'static Block, x: i32, y: i32, z: i32) {
+    let lower = block.get_default_state().with(&PROP_DOUBLE_BLOCK_HALF, DoubleBlockHalf::Lower).unwrap();
+    let upper = block.get_default_state().with(&PROP_DOUBLE_BLOCK_HALF, DoubleBlockHalf::Upper).unwrap();
+    level.set_block_at(x, y, z, lower).unwrap();
+    level.set_block_at(x, y + 1, z, upper).unwrap();
+}
+
+
+// BONE MEAL //
+
+/// Apply bone meal on the grass block at the given position, this spreads grass and
+/// biome-dependent flowers on the surrounding grass blocks, and sometimes grows existing
+/// grass into tall grass. Returns false if the block at the given position is not grass.
+///
+/// Valid for: 1.14 to 1.17.1
+pub fn grow_grass_block(level: &mut dyn LevelView, rand: &mut JavaRandom, x: i32, y: i32, z: i32) -> bool {
+
+    if !level.get_block_at(x, y, z).unwrap().is_block(&GRASS_BLOCK) {
+        return false;
+    }
+
+    'outer: for i in 0..128 {
+
+        let (mut bx, mut by, mut bz) = (x, y + 1, z);
+
+        for _ in 0..(i / 16) {
+            bx += rand.next_int_bounded(3) - 1;
+            by += (rand.next_int_bounded(3) - 1) * rand.next_int_bounded(3) / 2;
+            bz += rand.next_int_bounded(3) - 1;
+            if by <= 0 || by >= 255 {
+                continue 'outer;
+            }
+            let ground_block = level.get_block_at(bx, by - 1, bz).unwrap().get_block();
+            let current_block = level.get_block_at(bx, by, bz).unwrap().get_block();
+            if ground_block != &GRASS_BLOCK || VANILLA_BLOCK_MATERIALS.blocks_motion(current_block) {
+                continue 'outer;
+            }
+        }
+
+        let current_block = level.get_block_at(bx, by, bz).unwrap().get_block();
+
+        if current_block == &GRASS && rand.next_int_bounded(10) == 0 {
+            grow_tall_grass(level, bx, by, bz);
+        }
+
+        if current_block == &AIR {
+            let block = if rand.next_int_bounded(8) == 0 {
+                let biome = level.get_biome_at(bx, by, bz).unwrap();
+                FlowerVariant::from_biome(biome).get_flower(rand, bx, bz)
+            } else {
+                &GRASS
+            };
+            if can_plant_living(level.get_block_at(bx, by - 1, bz).unwrap().get_block()) {
+                level.set_block_at(bx, by, bz, block.get_default_state()).unwrap();
+            }
+        }
+
+    }
+
+    true
+
+}
+
+/// Apply bone meal on a grass or fern at the given position, growing it into its double
+/// plant variant if the block above is free. Returns false if nothing has been grown.
+pub fn grow_tall_grass(level: &mut dyn LevelView, x: i32, y: i32, z: i32) -> bool {
+
+    let double_block = match level.get_block_at(x, y, z).unwrap().get_block() {
+        b if b == &GRASS => &TALL_GRASS,
+        b if b == &FERN => &LARGE_FERN,
+        _ => return false
+    };
+
+    if y + 1 < 256 && level.get_block_at(x, y + 1, z).unwrap().is_block(&AIR) {
+        place_double_plant(level, double_block, x, y, z);
+        true
+    } else {
+        false
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::noise::SimplexNoise;
+
+    use super::*;
+
+    #[test]
+    fn flower_variants() {
+
+        let mut rand = JavaRandom::new(0);
+
+        assert_eq!(FlowerVariant::from_biome(&SWAMP_HILLS), FlowerVariant::Swamp);
+        assert_eq!(FlowerVariant::from_biome(&SUNFLOWER_PLAINS), FlowerVariant::Plains);
+        assert_eq!(FlowerVariant::from_biome(&FLOWER_FOREST), FlowerVariant::FlowerForest);
+        assert_eq!(FlowerVariant::from_biome(&mc_vanilla::biome::FOREST), FlowerVariant::Default);
+
+        for i in 0..64 {
+            assert_eq!(FlowerVariant::Swamp.get_flower(&mut rand, i * 16, 0), &BLUE_ORCHID);
+            let default = FlowerVariant::Default.get_flower(&mut rand, i * 16, 0);
+            assert!(default == &DANDELION || default == &POPPY);
+        }
+
+        // The flower forest gradient is continuous, so close positions use the same or
+        // neighbor flowers in the list.
+        let index_at = |x: i32| {
+            let flower = FlowerVariant::FlowerForest.get_flower(&mut JavaRandom::new(0), x, 0);
+            FOREST_FLOWERS.iter().position(|&f| f == flower).unwrap() as i32
+        };
+        for x in 0..256 {
+            assert!((index_at(x) - index_at(x + 1)).abs() <= 1);
+        }
+
+        assert!(SimplexNoise::new(&mut JavaRandom::new(2345)).get_value_2d(0.0, 0.0).abs() < 1e-9);
+
+    }
+
+}
@@ -0,0 +1,103 @@
+use std::path::Path;
+use std::sync::Arc;
+use std::fs::File;
+
+use mc_core::world::anvil::decode::DecodeError;
+use mc_core::world::level::LevelEnv;
+use mc_core::rand::JavaRandom;
+
+use mc_vanilla::heightmap::OCEAN_FLOOR_WG;
+
+use crate::structure::template::{StructureTemplate, TemplatePlaceSettings, Rotation};
+use crate::structure::piece::BoundingBox;
+use crate::view::LevelView;
+
+use super::Feature;
+
+
+/// Names of the vanilla fossil templates, in their vanilla order. Each fossil also has a
+/// coal overlay template named with a `_coal` suffix.
+pub const VANILLA_FOSSILS: [&str; 8] = [
+    "spine_1", "spine_2", "spine_3", "spine_4",
+    "skull_1", "skull_2", "skull_3", "skull_4"
+];
+
+
+/// Fossil feature, a random fossil template is buried about 15 to 25 blocks under the
+/// ocean floor, with a 90% integrity, and its coal overlay with a 10% integrity. Fossils
+/// are clipped to the chunk of the given position, which should be the minimum corner of
+/// the chunk, like for vanilla's decoration.
+///
+/// Valid for: 1.13 to 1.16.5
+pub struct FossilFeature {
+    fossils: Vec<(Arc<StructureTemplate>, Arc<StructureTemplate>)>
+}
+
+impl FossilFeature {
+
+    /// Construct a fossil feature from a list of fossil templates, each with its overlay.
+    /// The list must not be empty.
+    pub fn new(fossils: Vec<(Arc<StructureTemplate>, Arc<StructureTemplate>)>) -> Self {
+        assert!(!fossils.is_empty(), "at least one fossil is required");
+        Self { fossils }
+    }
+
+    /// Load the vanilla fossils templates from the given directory, typically the
+    /// `data/minecraft/structures/fossil` directory extracted from the game.
+    pub fn load_vanilla(dir: &Path, env: &LevelEnv) -> Result<Self, DecodeError> {
+        let load = |name: String| -> Result<Arc<StructureTemplate>, DecodeError> {
+            let mut file = File::open(dir.join(format!("{}.nbt", name)))
+                .map_err(|e| DecodeError::Malformed(format!("Can't open fossil template '{}': {}", name, e)))?;
+            Ok(Arc::new(StructureTemplate::from_reader(&mut file, env)?))
+        };
+        let mut fossils = Vec::with_capacity(VANILLA_FOSSILS.len());
+        for name in VANILLA_FOSSILS {
+            fossils.push((load(name.to_string())?, load(format!("{}_coal", name))?));
+        }
+        Ok(Self::new(fossils))
+    }
+
+    /// Vanilla fossils placement, 1 in 64 chunks in deserts and swamps.
+    pub fn into_vanilla(self) -> impl Feature {
+        self.optional(64)
+    }
+
+}
+
+impl Feature for FossilFeature {
+
+    fn generate(&self, level: &mut dyn LevelView, rand: &mut JavaRandom, x: i32, _y: i32, z: i32) -> bool {
+
+        let rotation = Rotation::random(rand);
+        let (fossil, overlay) = &self.fossils[rand.next_int_bounded(self.fossils.len() as i32) as usize];
+
+        let (cx, cz) = (x >> 4, z >> 4);
+        let bounding_box = BoundingBox::new(cx * 16, 0, cz * 16, cx * 16 + 15, 256, cz * 16 + 15);
+
+        let (size_x, _, size_z) = fossil.get_rotated_size(rotation);
+        let ox = rand.next_int_bounded(16 - size_x);
+        let oz = rand.next_int_bounded(16 - size_z);
+
+        let mut min_height = 256;
+        for dx in 0..size_x {
+            for dz in 0..size_z {
+                let height = level.get_heightmap_column_at(&OCEAN_FLOOR_WG, x + dx + ox, z + dz + oz).unwrap();
+                min_height = min_height.min(height);
+            }
+        }
+
+        let y = (min_height - 15 - rand.next_int_bounded(10)).max(10);
+        let (px, py, pz) = fossil.get_zero_position(x + ox, y, z + oz, rotation);
+
+        let settings = TemplatePlaceSettings::new()
+            .with_rotation(rotation)
+            .with_bounding_box(bounding_box);
+
+        fossil.place(level, rand, px, py, pz, &settings.clone().with_integrity(0.9));
+        overlay.place(level, rand, px, py, pz, &settings.with_integrity(0.1));
+
+        true
+
+    }
+
+}
@@ -0,0 +1,449 @@
+use std::f64::consts::PI;
+
+use mc_core::block::{Block, BlockState};
+use mc_core::rand::JavaRandom;
+
+use mc_vanilla::block::*;
+
+use crate::view::LevelView;
+
+use super::branch::RandomCount;
+use super::distrib::IcebergDistrib;
+use super::{Feature, get_block, set_block};
+
+
+const SEA_LEVEL: i32 = 63;
+
+
+/// Blue ice feature, a cluster of blue ice growing from packed ice, under the sea level.
+///
+/// Valid for: 1.13 to 1.17.1
+pub struct BlueIceFeature;
+
+impl BlueIceFeature {
+
+    /// Vanilla blue ice placement in frozen oceans, 0 to 19 times per chunk, between Y=30
+    /// and Y=61.
+    pub fn new_vanilla() -> impl Feature {
+        BlueIceFeature
+            .distributed_uniform_with_late_y(30, 62)
+            .repeated(RandomCount(19))
+    }
+
+}
+
+impl Feature for BlueIceFeature {
+
+    fn generate(&self, level: &mut dyn LevelView, rand: &mut JavaRandom, x: i32, y: i32, z: i32) -> bool {
+
+        if y > SEA_LEVEL - 1 {
+            return false;
+        }
+
+        if get_block(level, x, y, z) != Some(&WATER) && get_block(level, x, y - 1, z) != Some(&WATER) {
+            return false;
+        }
+
+        const SIDES: [(i32, i32, i32); 5] = [(0, 1, 0), (0, 0, -1), (0, 0, 1), (-1, 0, 0), (1, 0, 0)];
+        const DIRECTIONS: [(i32, i32, i32); 6] = [(0, -1, 0), (0, 1, 0), (0, 0, -1), (0, 0, 1), (-1, 0, 0), (1, 0, 0)];
+
+        if !SIDES.iter().any(|&(dx, dy, dz)| get_block(level, x + dx, y + dy, z + dz) == Some(&PACKED_ICE)) {
+            return false;
+        }
+
+        let blue_ice = BLUE_ICE.get_default_state();
+        set_block(level, x, y, z, blue_ice);
+
+        for _ in 0..200 {
+
+            let dy = rand.next_int_bounded(5) - rand.next_int_bounded(6);
+            let mut spread = 3;
+            if dy < 2 {
+                spread += -dy / 2;
+            }
+
+            if spread >= 1 {
+
+                let bx = x + rand.next_int_bounded(spread) - rand.next_int_bounded(spread);
+                let by = y + dy;
+                let bz = z + rand.next_int_bounded(spread) - rand.next_int_bounded(spread);
+
+                let block = get_block(level, bx, by, bz);
+                let replaceable = block == Some(&AIR) || block == Some(&WATER) || block == Some(&PACKED_ICE) || block == Some(&ICE);
+
+                if replaceable && DIRECTIONS.iter().any(|&(dx, dy, dz)| get_block(level, bx + dx, by + dy, bz + dz) == Some(&BLUE_ICE)) {
+                    set_block(level, bx, by, bz, blue_ice);
+                }
+
+            }
+
+        }
+
+        true
+
+    }
+
+}
+
+
+/// Iceberg feature, a large floating block of ice (packed ice or blue ice) at the sea level,
+/// either round or elliptic, with snow on top and sometimes a carved cut out.
+///
+/// Valid for: 1.13 to 1.17.1
+pub struct IcebergFeature {
+    block: &'static BlockState
+}
+
+impl IcebergFeature {
+
+    pub fn new(block: &'static Block) -> Self {
+        Self {
+            block: block.get_default_state()
+        }
+    }
+
+    /// Vanilla icebergs placement in frozen oceans, 1 in 16 chunks for packed ice icebergs
+    /// and then 1 in 200 chunks for blue ice icebergs.
+    pub fn new_vanilla_pair() -> impl Feature {
+        Self::new(&PACKED_ICE).distributed(IcebergDistrib).optional(16)
+            .chain(Self::new(&BLUE_ICE).distributed(IcebergDistrib).optional(200))
+    }
+
+}
+
+impl Feature for IcebergFeature {
+
+    fn generate(&self, level: &mut dyn LevelView, rand: &mut JavaRandom, x: i32, _y: i32, z: i32) -> bool {
+
+        let origin = (x, SEA_LEVEL, z);
+        let snow_on_top = rand.next_double() > 0.7;
+        let shape_angle = rand.next_double() * 2.0 * PI;
+        let shape_ellipse_a = 11 - rand.next_int_bounded(5);
+        let shape_ellipse_c = 3 + rand.next_int_bounded(3);
+        let is_ellipse = rand.next_double() > 0.7;
+
+        let mut over_water_height = if is_ellipse {
+            rand.next_int_bounded(6) + 6
+        } else {
+            rand.next_int_bounded(15) + 3
+        };
+
+        if !is_ellipse && rand.next_double() > 0.9 {
+            over_water_height += rand.next_int_bounded(19) + 7;
+        }
+
+        let under_water_height = (over_water_height + rand.next_int_bounded(11)).min(18);
+        let width = (over_water_height + rand.next_int_bounded(7) - rand.next_int_bounded(5)).min(11);
+        let a = if is_ellipse { shape_ellipse_a } else { 11 };
+
+        let shape = IcebergShape {
+            origin,
+            is_ellipse,
+            shape_ellipse_c,
+            shape_angle,
+            snow_on_top,
+            block: self.block
+        };
+
+        for xo in -a..a {
+            for zo in -a..a {
+                for y_off in 0..over_water_height {
+                    let radius = if is_ellipse {
+                        height_dependent_radius_ellipse(y_off, over_water_height, width)
+                    } else {
+                        height_dependent_radius_round(rand, y_off, over_water_height, width)
+                    };
+                    if is_ellipse || xo < radius {
+                        shape.generate_block(level, rand, over_water_height, xo, y_off, zo, radius, a);
+                    }
+                }
+            }
+        }
+
+        smooth(level, origin, width, over_water_height, is_ellipse, shape_ellipse_a);
+
+        for xo in -a..a {
+            for zo in -a..a {
+                for y_off in ((-under_water_height + 1)..=-1).rev() {
+                    let new_a = if is_ellipse {
+                        (a as f32 * (1.0 - (y_off as f32).powi(2) / (under_water_height as f32 * 8.0))).ceil() as i32
+                    } else {
+                        a
+                    };
+                    let radius = height_dependent_radius_steep(rand, -y_off, under_water_height, width);
+                    if xo < radius {
+                        shape.generate_block(level, rand, under_water_height, xo, y_off, zo, radius, new_a);
+                    }
+                }
+            }
+        }
+
+        let do_cut_out = if is_ellipse {
+            rand.next_double() > 0.1
+        } else {
+            rand.next_double() > 0.7
+        };
+
+        if do_cut_out {
+            generate_cut_out(level, rand, width, over_water_height, origin, is_ellipse, shape_ellipse_a, shape_angle, shape_ellipse_c);
+        }
+
+        true
+
+    }
+
+}
+
+
+/// Common parameters used to place the blocks of an iceberg.
+struct IcebergShape {
+    origin: (i32, i32, i32),
+    is_ellipse: bool,
+    shape_ellipse_c: i32,
+    shape_angle: f64,
+    snow_on_top: bool,
+    block: &'static BlockState
+}
+
+impl IcebergShape {
+
+    #[allow(clippy::too_many_arguments)]
+    fn generate_block(&self, level: &mut dyn LevelView, rand: &mut JavaRandom, height: i32, xo: i32, y_off: i32, zo: i32, radius: i32, a: i32) {
+
+        let signed_dist = if self.is_ellipse {
+            let c = get_ellipse_c(y_off, height, self.shape_ellipse_c);
+            signed_distance_ellipse(xo, zo, (0, 0), a, c, self.shape_angle)
+        } else {
+            signed_distance_circle(rand, xo, zo, (0, 0), radius)
+        };
+
+        if signed_dist < 0.0 {
+            let compare = if self.is_ellipse { -0.5 } else { (-6 - rand.next_int_bounded(3)) as f64 };
+            if signed_dist > compare && rand.next_double() > 0.9 {
+                return;
+            }
+            let (ox, oy, oz) = self.origin;
+            self.set_iceberg_block(level, rand, ox + xo, oy + y_off, oz + zo, height - y_off, height);
+        }
+
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn set_iceberg_block(&self, level: &mut dyn LevelView, rand: &mut JavaRandom, x: i32, y: i32, z: i32, h_diff: i32, height: i32) {
+
+        let block = match get_block(level, x, y, z) {
+            Some(block) => block,
+            None => return
+        };
+
+        if block == &AIR || block == &SNOW_BLOCK || block == &ICE || block == &WATER {
+            let randomness = !self.is_ellipse || rand.next_double() > 0.05;
+            let divisor = if self.is_ellipse { 3 } else { 2 };
+            if self.snow_on_top && block != &WATER &&
+                (h_diff as f64) <= rand.next_int_bounded((height / divisor).max(1)) as f64 + height as f64 * 0.6 &&
+                randomness {
+                set_block(level, x, y, z, SNOW_BLOCK.get_default_state());
+            } else {
+                set_block(level, x, y, z, self.block);
+            }
+        }
+
+    }
+
+}
+
+#[inline]
+fn is_iceberg_block(block: &'static Block) -> bool {
+    block == &PACKED_ICE || block == &SNOW_BLOCK || block == &BLUE_ICE
+}
+
+fn get_ellipse_c(y_off: i32, height: i32, value: i32) -> i32 {
+    if y_off > 0 && height - y_off <= 3 {
+        value - (4 - (height - y_off))
+    } else {
+        value
+    }
+}
+
+fn signed_distance_circle(rand: &mut JavaRandom, xo: i32, zo: i32, origin: (i32, i32), radius: i32) -> f64 {
+    let off = 10.0 * rand.next_float().clamp(0.2, 0.8) / radius as f32;
+    off as f64 + ((xo - origin.0) as f64).powi(2) + ((zo - origin.1) as f64).powi(2) - (radius as f64).powi(2)
+}
+
+fn signed_distance_ellipse(xo: i32, zo: i32, origin: (i32, i32), a: i32, c: i32, angle: f64) -> f64 {
+    let dx = (xo - origin.0) as f64;
+    let dz = (zo - origin.1) as f64;
+    ((dx * angle.cos() - dz * angle.sin()) / a as f64).powi(2) +
+        ((dx * angle.sin() + dz * angle.cos()) / c as f64).powi(2) - 1.0
+}
+
+fn height_dependent_radius_round(rand: &mut JavaRandom, y_off: i32, height: i32, width: i32) -> i32 {
+    let k = 3.5 - rand.next_float();
+    let mut scale = (1.0 - (y_off as f32).powi(2) / (height as f32 * k)) * width as f32;
+    if height > 15 + rand.next_int_bounded(5) {
+        let temp_y_off = if y_off < 3 + rand.next_int_bounded(6) { y_off / 2 } else { y_off };
+        scale = (1.0 - temp_y_off as f32 / (height as f32 * k * 0.4)) * width as f32;
+    }
+    (scale / 2.0).ceil() as i32
+}
+
+fn height_dependent_radius_ellipse(y_off: i32, height: i32, width: i32) -> i32 {
+    let scale = (1.0 - (y_off as f32).powi(2) / height as f32) * width as f32;
+    (scale / 2.0).ceil() as i32
+}
+
+fn height_dependent_radius_steep(rand: &mut JavaRandom, y_off: i32, height: i32, width: i32) -> i32 {
+    let k = 1.0 + rand.next_float() / 2.0;
+    let scale = (1.0 - y_off as f32 / (height as f32 * k)) * width as f32;
+    (scale / 2.0).ceil() as i32
+}
+
+/// Remove floating or isolated blocks of the part above water.
+fn smooth(level: &mut dyn LevelView, origin: (i32, i32, i32), width: i32, height: i32, is_ellipse: bool, shape_ellipse_a: i32) {
+
+    let a = if is_ellipse { shape_ellipse_a } else { width / 2 };
+    let (ox, oy, oz) = origin;
+    let air = AIR.get_default_state();
+
+    for x in -a..=a {
+        for z in -a..=a {
+            for y_off in 0..=height {
+
+                let (bx, by, bz) = (ox + x, oy + y_off, oz + z);
+                let block = match get_block(level, bx, by, bz) {
+                    Some(block) => block,
+                    None => continue
+                };
+
+                if is_iceberg_block(block) || block == &SNOW {
+                    if get_block(level, bx, by - 1, bz) == Some(&AIR) {
+                        set_block(level, bx, by, bz, air);
+                        set_block(level, bx, by + 1, bz, air);
+                    } else if is_iceberg_block(block) {
+                        let sides = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+                        let counter = sides.iter()
+                            .filter(|&&(dx, dz)| !get_block(level, bx + dx, by, bz + dz).map(is_iceberg_block).unwrap_or(false))
+                            .count();
+                        if counter >= 3 {
+                            set_block(level, bx, by, bz, air);
+                        }
+                    }
+                }
+
+            }
+        }
+    }
+
+}
+
+#[allow(clippy::too_many_arguments)]
+fn generate_cut_out(level: &mut dyn LevelView, rand: &mut JavaRandom, width: i32, height: i32, origin: (i32, i32, i32), is_ellipse: bool, shape_ellipse_a: i32, shape_angle: f64, shape_ellipse_c: i32) {
+
+    let sign_x = if rand.next_boolean() { -1 } else { 1 };
+    let sign_z = if rand.next_boolean() { -1 } else { 1 };
+
+    let mut x_off = rand.next_int_bounded((width / 2 - 2).max(1));
+    if rand.next_boolean() {
+        x_off = width / 2 + 1 - rand.next_int_bounded((width - width / 2 - 1).max(1));
+    }
+
+    let mut z_off = rand.next_int_bounded((width / 2 - 2).max(1));
+    if rand.next_boolean() {
+        z_off = width / 2 + 1 - rand.next_int_bounded((width - width / 2 - 1).max(1));
+    }
+
+    if is_ellipse {
+        x_off = rand.next_int_bounded((shape_ellipse_a - 5).max(1));
+        z_off = x_off;
+    }
+
+    let local_origin = (sign_x * x_off, sign_z * z_off);
+    let angle = if is_ellipse {
+        shape_angle + PI / 2.0
+    } else {
+        rand.next_double() * 2.0 * PI
+    };
+
+    for y_off in 0..(height - 3) {
+        let radius = height_dependent_radius_round(rand, y_off, height, width);
+        carve(level, radius, y_off, origin, false, angle, local_origin, shape_ellipse_a, shape_ellipse_c);
+    }
+
+    let mut y_off = -1;
+    while y_off > -height + rand.next_int_bounded(5) {
+        let radius = height_dependent_radius_steep(rand, -y_off, height, width);
+        carve(level, radius, y_off, origin, true, angle, local_origin, shape_ellipse_a, shape_ellipse_c);
+        y_off -= 1;
+    }
+
+}
+
+#[allow(clippy::too_many_arguments)]
+fn carve(level: &mut dyn LevelView, radius: i32, y_off: i32, origin: (i32, i32, i32), under_water: bool, angle: f64, local_origin: (i32, i32), shape_ellipse_a: i32, shape_ellipse_c: i32) {
+
+    let a = radius + 1 + shape_ellipse_a / 3;
+    let c = (radius - 3).min(3) + shape_ellipse_c / 2 - 1;
+    let (ox, oy, oz) = origin;
+
+    for xo in -a..a {
+        for zo in -a..a {
+            if signed_distance_ellipse(xo, zo, local_origin, a, c, angle) < 0.0 {
+                let (bx, by, bz) = (ox + xo, oy + y_off, oz + zo);
+                if let Some(block) = get_block(level, bx, by, bz) {
+                    if is_iceberg_block(block) || block == &SNOW_BLOCK {
+                        if under_water {
+                            set_block(level, bx, by, bz, WATER.get_default_state());
+                        } else {
+                            set_block(level, bx, by, bz, AIR.get_default_state());
+                            if get_block(level, bx, by + 1, bz) == Some(&SNOW) {
+                                set_block(level, bx, by + 1, bz, AIR.get_default_state());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_vanilla::biome::FROZEN_OCEAN;
+
+    use crate::view::TestLevelView;
+
+    use super::*;
+
+    #[test]
+    fn iceberg_and_blue_ice() {
+
+        let mut level = TestLevelView::new(WATER.get_default_state(), SEA_LEVEL - 1, &FROZEN_OCEAN);
+        let mut rand = JavaRandom::new(42);
+        assert!(IcebergFeature::new(&PACKED_ICE).generate(&mut level, &mut rand, 0, 0, 0));
+
+        let packed_ice = level.count_blocks(PACKED_ICE.get_default_state());
+        assert!(packed_ice > 0);
+        // The iceberg is mostly at the sea level.
+        assert!(level.get_block_at(0, SEA_LEVEL - 2, 0).unwrap().is_block(&PACKED_ICE) ||
+            level.get_block_at(0, SEA_LEVEL, 0).unwrap().is_block(&PACKED_ICE));
+
+        // Blue ice needs water and packed ice next to it.
+        let mut found = false;
+        for y in (30..SEA_LEVEL).rev() {
+            if level.get_block_at(0, y, 0).unwrap().is_block(&WATER) &&
+                level.get_block_at(0, y + 1, 0).unwrap().is_block(&PACKED_ICE) {
+                assert!(BlueIceFeature.generate(&mut level, &mut rand, 0, y, 0));
+                found = true;
+                break;
+            }
+        }
+        assert!(found);
+        assert!(level.count_blocks(BLUE_ICE.get_default_state()) > 0);
+        assert!(!BlueIceFeature.generate(&mut level, &mut rand, 100, 40, 100));
+
+    }
+
+}
@@ -3,34 +3,72 @@ use mc_core::block::BlockState;
 use mc_core::rand::JavaRandom;
 
 use mc_vanilla::block::material::{TAG_LIQUID, TAG_NON_SOLID};
+use mc_vanilla::heightmap::MOTION_BLOCKING_NO_LEAVES;
 use mc_vanilla::block::*;
 
 use crate::gen::biome::BiomePropertyMap;
 
 use crate::view::LevelView;
+use super::distrib::LavaLakeDistrib;
 use super::Feature;
 
 
+/// Lake feature, a blob of liquid 16x8x16 made of 4 to 7 ellipsoids. The upper half of the
+/// blob is carved with air and the lower half is filled with the liquid.
+///
+/// Valid for: 1.2.5
 pub struct LakeFeature {
     block: &'static BlockState,
     biomes_map: &'static Lazy<BiomePropertyMap>
 }
 
 impl LakeFeature {
+
     pub fn new(block: &'static BlockState, biomes_map: &'static Lazy<BiomePropertyMap>) -> Self {
         Self {
             block,
             biomes_map
         }
     }
+
+    pub fn new_water(biomes_map: &'static Lazy<BiomePropertyMap>) -> Self {
+        Self::new(WATER.get_default_state(), biomes_map)
+    }
+
+    pub fn new_lava(biomes_map: &'static Lazy<BiomePropertyMap>) -> Self {
+        Self::new(LAVA.get_default_state(), biomes_map)
+    }
+
+    /// Build the vanilla lakes pair, a water lake generated 1 in 4 chunks at any height, and
+    /// then a lava lake generated 1 in 8 chunks, mostly below the sea level.
+    pub fn new_vanilla_pair(biomes_map: &'static Lazy<BiomePropertyMap>) -> impl Feature {
+        Self::new_water(biomes_map).distributed_uniform(0, 128).optional(4)
+            .chain(Self::new_lava(biomes_map).distributed(LavaLakeDistrib).optional(8))
+    }
+
+}
+
+#[inline]
+fn flag_index(dx: usize, dz: usize, dy: usize) -> usize {
+    (dx * 16 + dz) * 8 + dy
+}
+
+/// Return true if the given position is not part of the lake, but touches it.
+fn is_lake_border(flags: &[bool; 2048], dx: usize, dz: usize, dy: usize) -> bool {
+    !flags[flag_index(dx, dz, dy)] && (
+        (dx != 15 && flags[flag_index(dx + 1, dz, dy)]) ||
+        (dx != 0 && flags[flag_index(dx - 1, dz, dy)]) ||
+        (dz != 15 && flags[flag_index(dx, dz + 1, dy)]) ||
+        (dz != 0 && flags[flag_index(dx, dz - 1, dy)]) ||
+        (dy != 7 && flags[flag_index(dx, dz, dy + 1)]) ||
+        (dy != 0 && flags[flag_index(dx, dz, dy - 1)])
+    )
 }
 
 impl Feature for LakeFeature {
 
     fn generate(&self, level: &mut dyn LevelView, rand: &mut JavaRandom, x: i32, y: i32, z: i32) -> bool {
 
-        // level.set_block_at(x, y, z, GOLD_BLOCK.get_default_state()).unwrap();
-
         let x = x - 8;
         let mut y = y;
         let z = z - 8;
@@ -39,8 +77,6 @@ impl Feature for LakeFeature {
             y -= 1;
         }
 
-        // level.set_block_at(x, y, z, IRON_BLOCK.get_default_state()).unwrap();
-
         if y <= 4 {
             return false;
         } else {
@@ -62,39 +98,34 @@ impl Feature for LakeFeature {
                         let y_dist = (dy as f64 - bb) / (b / 2.0);
                         let z_dist = (dz as f64 - cc) / (c / 2.0);
                         if x_dist * x_dist + y_dist * y_dist + z_dist * z_dist < 1.0 {
-                            flags[(dx * 16 + dz) * 8 + dy] = true;
+                            flags[flag_index(dx, dz, dy)] = true;
                         }
                     }
                 }
             }
         }
 
+        // The lake is cancelled if its upper border touches a liquid or if its lower border
+        // is not fully enclosed by solid blocks (or the lake's own liquid).
         for dx in 0..16 {
             for dz in 0..16 {
                 for dy in 0..8 {
-
-                    let flag = !flags[(dx * 16 + dz) * 8 + dy] && (
-                        (dx != 15 && flags[((dx + 1) * 16 + dz) * 8 + dy]) ||
-                        (dx != 0 && flags[((dx - 1) * 16 + dz) * 8 + dy]) ||
-                        (dz != 15 && flags[(dx * 16 + (dz + 1)) * 8 + dy]) ||
-                        (dz != 0 && flags[(dx * 16 + (dz - 1)) * 8 + dy]) ||
-                        (dy != 7 && flags[(dx * 16 + dz) * 8 + (dy + 1)]) ||
-                        (dy != 0 && flags[(dx * 16 + dz) * 8 + (dy - 1)])
-                    );
-
-                    if flag {
+                    if is_lake_border(&flags, dx, dz, dy) {
 
                         let block = level.get_block_at(x + dx as i32, y + dy as i32, z + dz as i32).unwrap().get_block();
 
                         let env_blocks = &level.get_env().blocks;
-                        if dy >= 4 && env_blocks.has_block_tag(block, &TAG_LIQUID) {
-                            return false;
-                        } else  if dy < 4 && env_blocks.has_block_tag(block, &TAG_NON_SOLID) && block != self.block.get_block() {
+                        let cancel = if dy >= 4 {
+                            env_blocks.has_block_tag(block, &TAG_LIQUID)
+                        } else {
+                            env_blocks.has_block_tag(block, &TAG_NON_SOLID) && block != self.block.get_block()
+                        };
+
+                        if cancel {
                             return false;
                         }
 
                     }
-
                 }
             }
         }
@@ -104,23 +135,36 @@ impl Feature for LakeFeature {
         for dx in 0..16 {
             for dz in 0..16 {
                 for dy in 0..8 {
+                    if flags[flag_index(dx, dz, dy)] {
+                        level.set_block_at(x + dx as i32, y + dy as i32, z + dz as i32, if dy < 4 {
+                            self.block
+                        } else {
+                            block_air
+                        }).unwrap();
+                    }
+                }
+            }
+        }
 
-                    if flags[(dx * 16 + dz) * 8 + dy] {
+        // Dirt uncovered by the carved air is turned into grass (or mycelium), but only where
+        // the sky is visible, this keeps dirt under overhangs. Sky light is approximated by
+        // the motion blocking heightmap, leaves are ignored because they let sky light pass.
+        for dx in 0..16 {
+            for dz in 0..16 {
+                for dy in 4..8 {
+                    if flags[flag_index(dx, dz, dy)] {
 
                         let bx = x + dx as i32;
                         let by = y + dy as i32;
                         let bz = z + dz as i32;
 
-                        level.set_block_at(bx, by, bz, if dy < 4 {
-                            self.block
-                        } else {
-                            block_air
-                        }).unwrap();
+                        if level.get_block_at(bx, by - 1, bz).unwrap().is_block(&DIRT) {
 
-                        if dy >= 4 {
+                            let sky_visible = level.get_heightmap_column_at(&MOTION_BLOCKING_NO_LEAVES, bx, bz)
+                                .map(|height| by >= height)
+                                .unwrap_or(true);
 
-                            // TODO: Also need to check sky light here.
-                            if level.get_block_at(bx, by - 1, bz).unwrap().is_block(&DIRT) {
+                            if sky_visible {
 
                                 let biome = level.get_biome_at(bx, by, bz).unwrap();
                                 let biome_prop = self.biomes_map.get(biome).unwrap();
@@ -138,46 +182,131 @@ impl Feature for LakeFeature {
                         }
 
                     }
-
                 }
             }
         }
 
         if self.block.is_block(&LAVA) {
 
-            let block_stone = STONE.get_default_state();
+            let block_stone = level.get_settings().default_block;
 
             for dx in 0..16 {
                 for dz in 0..16 {
                     for dy in 0..8 {
-
-                        let flag = !flags[(dx * 16 + dz) * 8 + dy] && (
-                            (dx != 15 && flags[((dx + 1) * 16 + dz) * 8 + dy]) ||
-                            (dx != 0 && flags[((dx - 1) * 16 + dz) * 8 + dy]) ||
-                            (dz != 15 && flags[(dx * 16 + (dz + 1)) * 8 + dy]) ||
-                            (dz != 0 && flags[(dx * 16 + (dz - 1)) * 8 + dy]) ||
-                            (dy != 7 && flags[(dx * 16 + dz) * 8 + (dy + 1)]) ||
-                            (dy != 0 && flags[(dx * 16 + dz) * 8 + (dy - 1)])
-                        );
-
-                        if flag && (dy < 4 || rand.next_int_bounded(2) != 0) {
+                        if is_lake_border(&flags, dx, dz, dy) && (dy < 4 || rand.next_int_bounded(2) != 0) {
                             let env_blocks = &level.get_env().blocks;
                             let block = level.get_block_at(x + dx as i32, y + dy as i32, z + dz as i32).unwrap().get_block();
                             if !env_blocks.has_block_tag(block, &TAG_NON_SOLID) {
                                 level.set_block_at(x + dx as i32, y + dy as i32, z + dz as i32, block_stone).unwrap();
                             }
                         }
-
                     }
                 }
             }
 
-        } else if self.block.is_block(&WATER) {
-            // TODO: Finish water-specific lake features (currently not
         }
 
+        // Water lakes also try to freeze their surface at 'y + 4', but this layer is always
+        // carved with air, so this never happens and is not implemented here. Frozen lakes
+        // actually come from the snow and ice pass of the chunk population.
+
         true
 
     }
 
 }
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_vanilla::biome::PLAINS;
+
+    use crate::gen::biome::BiomeProperty;
+    use crate::feature::FeatureChain;
+    use crate::view::TestLevelView;
+
+    use super::*;
+
+    static TEST_BIOMES: Lazy<BiomePropertyMap> = Lazy::new(|| {
+        let mut map = BiomePropertyMap::new();
+        map.insert(&PLAINS, BiomeProperty {
+            min_height: 0.1,
+            max_height: 0.3,
+            temperature: 0.8,
+            top_block: GRASS_BLOCK.get_default_state(),
+            filler_block: DIRT.get_default_state(),
+            features: FeatureChain::new()
+        });
+        map
+    });
+
+    #[test]
+    fn water_lake() {
+
+        let mut level = TestLevelView::new(DIRT.get_default_state(), 64, &PLAINS);
+        let mut rand = JavaRandom::new(123456789);
+        assert!(LakeFeature::new_water(&TEST_BIOMES).generate(&mut level, &mut rand, 8, 70, 8));
+
+        // The lake lies on the ground, 4 blocks below its origin, and the carved upper
+        // half is only air.
+        let water = WATER.get_default_state();
+        let water_count = level.count_blocks(water);
+        assert!(water_count > 0);
+        for x in 0..16 {
+            for z in 0..16 {
+                for y in 64..68 {
+                    assert!(!level.get_block_at(x, y, z).unwrap().is_block(&WATER));
+                }
+            }
+        }
+
+        // Dirt uncovered with full sky access is turned into grass.
+        assert!(level.count_blocks(GRASS_BLOCK.get_default_state()) > 0);
+
+        // Known seed regression, blocks and counts of vanilla for this seed and ground.
+        assert_eq!(water_count, 77);
+        assert_eq!(level.count_blocks(GRASS_BLOCK.get_default_state()), 21);
+        assert_eq!(level.count_blocks(AIR.get_default_state()), 163);
+        assert_eq!(level.get_block_at(5, 63, 7).unwrap(), water);
+        assert_eq!(level.get_block_at(12, 63, 5).unwrap(), water);
+        assert_eq!(level.get_block_at(10, 61, 10).unwrap(), water);
+        assert_eq!(level.get_block_at(4, 63, 7).unwrap(), GRASS_BLOCK.get_default_state());
+        assert_eq!(level.get_block_at(5, 64, 10).unwrap(), GRASS_BLOCK.get_default_state());
+
+        // Cancelled when the upper half touches a liquid.
+        let mut level = TestLevelView::new(WATER.get_default_state(), 64, &PLAINS);
+        let mut rand = JavaRandom::new(123456789);
+        assert!(!LakeFeature::new_water(&TEST_BIOMES).generate(&mut level, &mut rand, 8, 70, 8));
+        assert_eq!(level.count_blocks(water), 0);
+
+    }
+
+    #[test]
+    fn lava_lake() {
+
+        let mut level = TestLevelView::new(DIRT.get_default_state(), 64, &PLAINS);
+        let mut rand = JavaRandom::new(987654321);
+        assert!(LakeFeature::new_lava(&TEST_BIOMES).generate(&mut level, &mut rand, 8, 70, 8));
+
+        // Known seed regression, blocks and counts of vanilla for this seed and ground.
+        let lava = LAVA.get_default_state();
+        let stone = STONE.get_default_state();
+        assert_eq!(level.count_blocks(lava), 95);
+        assert_eq!(level.count_blocks(AIR.get_default_state()), 141);
+        assert_eq!(level.get_block_at(4, 62, 4).unwrap(), lava);
+        assert_eq!(level.get_block_at(5, 61, 4).unwrap(), lava);
+        assert_eq!(level.get_block_at(13, 63, 12).unwrap(), lava);
+        assert_eq!(level.get_block_at(2, 64, 5).unwrap(), AIR.get_default_state());
+
+        // The lower border of lava lakes is always turned into stone, on a flat ground this
+        // also replaces the grass.
+        assert_eq!(level.count_blocks(stone), 130);
+        assert_eq!(level.get_block_at(5, 60, 4).unwrap(), stone);
+        assert_eq!(level.get_block_at(1, 64, 5).unwrap(), stone);
+        assert_eq!(level.get_block_at(14, 64, 13).unwrap(), stone);
+        assert_eq!(level.count_blocks(GRASS_BLOCK.get_default_state()), 0);
+
+    }
+
+}
@@ -1,8 +1,10 @@
+use mc_core::block::{Block, BlockState};
 use mc_core::rand::JavaRandom;
 
 use crate::view::LevelView;
 
 pub mod distrib;
+pub mod predicate;
 pub mod branch;
 pub mod vein;
 pub mod lake;
@@ -10,6 +12,10 @@ pub mod debug;
 pub mod dungeon;
 pub mod tree;
 pub mod flower;
+pub mod well;
+pub mod fossil;
+pub mod ice;
+pub mod ocean;
 
 use distrib::{Distrib, DistribFeature, TriangularVerticalDistrib, UniformVerticalDistrib};
 use branch::{OptionalFeature, RepeatCount, RepeatedFeature, ChainFeature};
@@ -119,3 +125,18 @@ impl FeatureChain {
     }
 
 }
+
+
+/// Return the block at the given position, or None if the position is not in the level view.
+/// This is used by features that may be larger than the population area, these features are
+/// simply clipped to the view.
+#[inline]
+pub(crate) fn get_block(level: &dyn LevelView, x: i32, y: i32, z: i32) -> Option<&'static Block> {
+    level.get_block_at(x, y, z).ok().map(|state| state.get_block())
+}
+
+/// Set the block at the given position, ignored if the position is not in the level view.
+#[inline]
+pub(crate) fn set_block(level: &mut dyn LevelView, x: i32, y: i32, z: i32, state: &'static BlockState) {
+    let _ = level.set_block_at(x, y, z, state);
+}
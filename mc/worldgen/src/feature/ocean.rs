@@ -0,0 +1,620 @@
+use mc_core::block::{Block, BlockState};
+use mc_core::rand::JavaRandom;
+use mc_core::biome::Biome;
+use mc_core::pos::Direction;
+
+use mc_vanilla::block::material::VANILLA_BLOCK_MATERIALS;
+use mc_vanilla::heightmap::{OCEAN_FLOOR, OCEAN_FLOOR_WG};
+use mc_vanilla::block::*;
+use mc_vanilla::biome::{
+    OCEAN, DEEP_OCEAN, COLD_OCEAN, DEEP_COLD_OCEAN,
+    LUKEWARM_OCEAN, DEEP_LUKEWARM_OCEAN, WARM_OCEAN, DEEP_WARM_OCEAN
+};
+
+use crate::view::LevelView;
+
+use super::branch::NoiseCountFeature;
+use super::distrib::HeightmapDistrib;
+use super::{Feature, FeatureChain, get_block, set_block};
+
+
+/// Return true if the block below the given position can support an underwater plant.
+fn is_sturdy_below(level: &dyn LevelView, x: i32, y: i32, z: i32) -> bool {
+    match get_block(level, x, y - 1, z) {
+        Some(block) => block != &MAGMA_BLOCK && VANILLA_BLOCK_MATERIALS.blocks_motion(block),
+        None => false
+    }
+}
+
+/// Horizontal directions in the vanilla order.
+const HORIZONTAL: [Direction; 4] = [Direction::North, Direction::East, Direction::South, Direction::West];
+
+fn clockwise(direction: Direction) -> Direction {
+    match direction {
+        Direction::North => Direction::East,
+        Direction::East => Direction::South,
+        Direction::South => Direction::West,
+        Direction::West => Direction::North,
+        other => other
+    }
+}
+
+fn counter_clockwise(direction: Direction) -> Direction {
+    clockwise(clockwise(clockwise(direction)))
+}
+
+/// Shuffle the given slice, consuming the RNG like Java's `Collections.shuffle`.
+fn shuffle<T>(values: &mut [T], rand: &mut JavaRandom) {
+    for i in (1..values.len()).rev() {
+        values.swap(i, rand.next_int_bounded(i as i32 + 1) as usize);
+    }
+}
+
+fn random_sea_pickle(rand: &mut JavaRandom) -> &'static BlockState {
+    SEA_PICKLE.get_default_state()
+        .with(&PROP_WATERLOGGED, true).unwrap()
+        .with(&PROP_PICKLES, rand.next_int_bounded(4) as u8 + 1).unwrap()
+}
+
+
+// SEAGRASS //
+
+/// Seagrass feature, a single seagrass (or tall seagrass) is placed on the ocean floor, in a
+/// 15x15 area around the given position.
+///
+/// Valid for: 1.13 to 1.16.5
+pub struct SeagrassFeature {
+    tall_probability: f64
+}
+
+impl SeagrassFeature {
+
+    /// Construct a seagrass feature with the given probability for a seagrass to be tall.
+    pub fn new(tall_probability: f32) -> Self {
+        Self {
+            tall_probability: tall_probability as f64
+        }
+    }
+
+    /// Vanilla seagrass placement, `count` times per chunk on the ocean floor.
+    pub fn new_vanilla(tall_probability: f32, count: u16) -> impl Feature {
+        Self::new(tall_probability)
+            .distributed(HeightmapDistrib::new(&OCEAN_FLOOR_WG))
+            .repeated(count)
+    }
+
+}
+
+impl Feature for SeagrassFeature {
+
+    fn generate(&self, level: &mut dyn LevelView, rand: &mut JavaRandom, x: i32, _y: i32, z: i32) -> bool {
+
+        let x = x + rand.next_int_bounded(8) - rand.next_int_bounded(8);
+        let z = z + rand.next_int_bounded(8) - rand.next_int_bounded(8);
+        let y = match level.get_heightmap_column_at(&OCEAN_FLOOR, x, z) {
+            Ok(y) => y,
+            Err(_) => return false
+        };
+
+        if get_block(level, x, y, z) != Some(&WATER) {
+            return false;
+        }
+
+        let tall = rand.next_double() < self.tall_probability;
+        if !is_sturdy_below(level, x, y, z) {
+            return false;
+        }
+
+        if tall {
+            if get_block(level, x, y + 1, z) == Some(&WATER) {
+                let lower = TALL_SEAGRASS.get_default_state().with(&PROP_DOUBLE_BLOCK_HALF, DoubleBlockHalf::Lower).unwrap();
+                let upper = lower.with(&PROP_DOUBLE_BLOCK_HALF, DoubleBlockHalf::Upper).unwrap();
+                set_block(level, x, y, z, lower);
+                set_block(level, x, y + 1, z, upper);
+            }
+        } else {
+            set_block(level, x, y, z, SEAGRASS.get_default_state());
+        }
+
+        true
+
+    }
+
+}
+
+
+// KELP //
+
+/// Kelp feature, a column of 1 to 10 kelp blocks growing from the ocean floor, topped by a
+/// kelp head with an age between 20 and 23. The column stops early when reaching the surface.
+///
+/// Valid for: 1.13 to 1.16.5
+pub struct KelpFeature;
+
+impl KelpFeature {
+
+    /// Vanilla kelp placement, the number of kelp per chunk depends on the biome info noise
+    /// and on the given noise to count ratio (80 for warm oceans and 120 for colder ones).
+    pub fn new_vanilla(noise_to_count_ratio: i32) -> impl Feature {
+        NoiseCountFeature::new(
+            KelpFeature.distributed(HeightmapDistrib::new(&OCEAN_FLOOR_WG)),
+            noise_to_count_ratio, 80.0, 0.0
+        )
+    }
+
+}
+
+/// Return true if a kelp block can survive at the given position.
+fn can_kelp_survive(level: &dyn LevelView, x: i32, y: i32, z: i32) -> bool {
+    match get_block(level, x, y - 1, z) {
+        Some(block) if block == &KELP || block == &KELP_PLANT => true,
+        _ => is_sturdy_below(level, x, y, z)
+    }
+}
+
+impl Feature for KelpFeature {
+
+    fn generate(&self, level: &mut dyn LevelView, rand: &mut JavaRandom, x: i32, _y: i32, z: i32) -> bool {
+
+        let floor_y = match level.get_heightmap_column_at(&OCEAN_FLOOR, x, z) {
+            Ok(y) => y,
+            Err(_) => return false
+        };
+
+        if get_block(level, x, floor_y, z) != Some(&WATER) {
+            return false;
+        }
+
+        let plant = KELP_PLANT.get_default_state();
+        let mut placed = false;
+
+        let height = 1 + rand.next_int_bounded(10);
+        for i in 0..=height {
+
+            let y = floor_y + i;
+
+            if get_block(level, x, y, z) == Some(&WATER) &&
+                get_block(level, x, y + 1, z) == Some(&WATER) &&
+                can_kelp_survive(level, x, y, z) {
+
+                if i == height {
+                    set_block(level, x, y, z, KELP.get_default_state().with(&PROP_AGE_26, rand.next_int_bounded(4) as u8 + 20).unwrap());
+                    placed = true;
+                } else {
+                    set_block(level, x, y, z, plant);
+                }
+
+            } else if i > 0 {
+
+                // The column can't grow anymore, the last plant is replaced by the head.
+                if can_kelp_survive(level, x, y - 1, z) && get_block(level, x, y - 2, z) != Some(&KELP) {
+                    set_block(level, x, y - 1, z, KELP.get_default_state().with(&PROP_AGE_26, rand.next_int_bounded(4) as u8 + 20).unwrap());
+                    placed = true;
+                }
+
+                break;
+
+            }
+
+        }
+
+        placed
+
+    }
+
+}
+
+
+// SEA PICKLE //
+
+/// Sea pickle feature, tries `count` times to place a sea pickle (with 1 to 4 pickles) on the
+/// ocean floor, in a 15x15 area around the given position.
+///
+/// Valid for: 1.13 to 1.16.5
+pub struct SeaPickleFeature {
+    count: u16
+}
+
+impl SeaPickleFeature {
+
+    pub fn new(count: u16) -> Self {
+        Self { count }
+    }
+
+    /// Vanilla sea pickles placement in warm oceans, 20 tries 1 in 16 chunks.
+    pub fn new_vanilla() -> impl Feature {
+        Self::new(20)
+            .distributed(HeightmapDistrib::new(&OCEAN_FLOOR_WG))
+            .optional(16)
+    }
+
+}
+
+impl Feature for SeaPickleFeature {
+
+    fn generate(&self, level: &mut dyn LevelView, rand: &mut JavaRandom, x: i32, _y: i32, z: i32) -> bool {
+
+        let mut placed = false;
+
+        for _ in 0..self.count {
+
+            let bx = x + rand.next_int_bounded(8) - rand.next_int_bounded(8);
+            let bz = z + rand.next_int_bounded(8) - rand.next_int_bounded(8);
+            let by = match level.get_heightmap_column_at(&OCEAN_FLOOR, bx, bz) {
+                Ok(by) => by,
+                Err(_) => continue
+            };
+
+            let state = random_sea_pickle(rand);
+            if get_block(level, bx, by, bz) == Some(&WATER) && is_sturdy_below(level, bx, by, bz) {
+                set_block(level, bx, by, bz, state);
+                placed = true;
+            }
+
+        }
+
+        placed
+
+    }
+
+}
+
+
+// CORAL //
+
+static CORAL_BLOCKS: [&Block; 5] = [
+    &TUBE_CORAL_BLOCK, &BRAIN_CORAL_BLOCK, &BUBBLE_CORAL_BLOCK, &FIRE_CORAL_BLOCK, &HORN_CORAL_BLOCK
+];
+
+static CORALS: [&Block; 10] = [
+    &TUBE_CORAL, &BRAIN_CORAL, &BUBBLE_CORAL, &FIRE_CORAL, &HORN_CORAL,
+    &TUBE_CORAL_FAN, &BRAIN_CORAL_FAN, &BUBBLE_CORAL_FAN, &FIRE_CORAL_FAN, &HORN_CORAL_FAN
+];
+
+static WALL_CORALS: [&Block; 5] = [
+    &TUBE_CORAL_WALL_FAN, &BRAIN_CORAL_WALL_FAN, &BUBBLE_CORAL_WALL_FAN, &FIRE_CORAL_WALL_FAN, &HORN_CORAL_WALL_FAN
+];
+
+fn random_block(blocks: &[&'static Block], rand: &mut JavaRandom) -> &'static Block {
+    blocks[rand.next_int_bounded(blocks.len() as i32) as usize]
+}
+
+
+/// Shapes of coral reefs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoralShape {
+    /// A short trunk with 2 to 4 branches growing in random horizontal directions.
+    Tree,
+    /// A block with 2 or 3 arms curving toward the same direction.
+    Claw,
+    /// A hollow box of coral blocks, partially buried.
+    Mushroom
+}
+
+impl CoralShape {
+
+    /// Pick a random shape, calling `next_int_bounded(3)` once.
+    pub fn random(rand: &mut JavaRandom) -> Self {
+        match rand.next_int_bounded(3) {
+            0 => Self::Tree,
+            1 => Self::Claw,
+            _ => Self::Mushroom
+        }
+    }
+
+}
+
+
+/// Coral feature, a reef made of a random coral block with a given shape. Each coral block
+/// placed may be decorated with corals or sea pickles on top and coral fans on its sides.
+///
+/// Valid for: 1.13 to 1.16.5
+pub struct CoralFeature {
+    shape: Option<CoralShape>
+}
+
+impl CoralFeature {
+
+    pub fn new(shape: CoralShape) -> Self {
+        Self {
+            shape: Some(shape)
+        }
+    }
+
+    /// Construct a coral feature picking a random shape each time it's generated.
+    pub fn new_random() -> Self {
+        Self {
+            shape: None
+        }
+    }
+
+    /// Vanilla coral reefs placement in warm oceans, the number of reefs per chunk depends on
+    /// the biome info noise.
+    pub fn new_vanilla() -> impl Feature {
+        NoiseCountFeature::new(
+            Self::new_random().distributed(HeightmapDistrib::new(&OCEAN_FLOOR_WG)),
+            20, 400.0, 0.0
+        )
+    }
+
+}
+
+/// Place a coral block at the given position if it is water (or a coral) and if the block
+/// above is water, then decorate it. Return true if the block has been placed.
+fn place_coral_block(level: &mut dyn LevelView, rand: &mut JavaRandom, x: i32, y: i32, z: i32, state: &'static BlockState) -> bool {
+
+    let current = match get_block(level, x, y, z) {
+        Some(block) => block,
+        None => return false
+    };
+
+    if (current != &WATER && !CORALS.contains(&current)) || get_block(level, x, y + 1, z) != Some(&WATER) {
+        return false;
+    }
+
+    set_block(level, x, y, z, state);
+
+    if rand.next_float() < 0.25 {
+        let coral = random_block(&CORALS, rand).get_default_state().with(&PROP_WATERLOGGED, true).unwrap();
+        set_block(level, x, y + 1, z, coral);
+    } else if rand.next_float() < 0.05 {
+        let sea_pickle = random_sea_pickle(rand);
+        set_block(level, x, y + 1, z, sea_pickle);
+    }
+
+    for direction in HORIZONTAL {
+        if rand.next_float() < 0.2 {
+            let (dx, _, dz) = direction.normal(1);
+            if get_block(level, x + dx, y, z + dz) == Some(&WATER) {
+                let fan = random_block(&WALL_CORALS, rand).get_default_state()
+                    .with(&PROP_WATERLOGGED, true).unwrap()
+                    .with(&PROP_FACING, direction).unwrap();
+                set_block(level, x + dx, y, z + dz, fan);
+            }
+        }
+    }
+
+    true
+
+}
+
+impl CoralFeature {
+
+    fn generate_tree(level: &mut dyn LevelView, rand: &mut JavaRandom, x: i32, mut y: i32, z: i32, state: &'static BlockState) {
+
+        for _ in 0..(rand.next_int_bounded(3) + 1) {
+            if !place_coral_block(level, rand, x, y, z, state) {
+                return;
+            }
+            y += 1;
+        }
+
+        let branches = rand.next_int_bounded(3) + 2;
+        let mut directions = HORIZONTAL;
+        shuffle(&mut directions, rand);
+
+        for &direction in &directions[..branches as usize] {
+
+            let (dx, _, dz) = direction.normal(1);
+            let (mut bx, mut by, mut bz) = (x + dx, y, z + dz);
+
+            let length = rand.next_int_bounded(5) + 2;
+            let mut straight = 0;
+
+            for i in 0..length {
+                if !place_coral_block(level, rand, bx, by, bz, state) {
+                    break;
+                }
+                straight += 1;
+                by += 1;
+                if i == 0 || (straight >= 2 && rand.next_float() < 0.25) {
+                    bx += dx;
+                    bz += dz;
+                    straight = 0;
+                }
+            }
+
+        }
+
+    }
+
+    fn generate_claw(level: &mut dyn LevelView, rand: &mut JavaRandom, x: i32, y: i32, z: i32, state: &'static BlockState) {
+
+        if !place_coral_block(level, rand, x, y, z, state) {
+            return;
+        }
+
+        let main_direction = HORIZONTAL[rand.next_int_bounded(4) as usize];
+        let arms = rand.next_int_bounded(2) + 2;
+        let mut directions = [main_direction, clockwise(main_direction), counter_clockwise(main_direction)];
+        shuffle(&mut directions, rand);
+
+        for &direction in &directions[..arms as usize] {
+
+            let (dx, _, dz) = direction.normal(1);
+            let (mut bx, mut by, mut bz) = (x + dx, y, z + dz);
+
+            let base_length = rand.next_int_bounded(2) + 1;
+            let (arm_direction, arm_length) = if direction == main_direction {
+                (main_direction, rand.next_int_bounded(3) + 2)
+            } else {
+                by += 1;
+                let arm_direction = if rand.next_int_bounded(2) == 0 { direction } else { Direction::Up };
+                (arm_direction, rand.next_int_bounded(3) + 3)
+            };
+
+            let (adx, ady, adz) = arm_direction.normal(1);
+            for _ in 0..base_length {
+                if !place_coral_block(level, rand, bx, by, bz, state) {
+                    break;
+                }
+                bx += adx;
+                by += ady;
+                bz += adz;
+            }
+
+            bx -= adx;
+            by -= ady - 1;
+            bz -= adz;
+
+            let (mdx, _, mdz) = main_direction.normal(1);
+            for _ in 0..arm_length {
+                bx += mdx;
+                bz += mdz;
+                if !place_coral_block(level, rand, bx, by, bz, state) {
+                    break;
+                }
+                if rand.next_float() < 0.25 {
+                    by += 1;
+                }
+            }
+
+        }
+
+    }
+
+    fn generate_mushroom(level: &mut dyn LevelView, rand: &mut JavaRandom, x: i32, y: i32, z: i32, state: &'static BlockState) {
+
+        let size_y = rand.next_int_bounded(3) + 3;
+        let size_x = rand.next_int_bounded(3) + 3;
+        let size_z = rand.next_int_bounded(3) + 3;
+        let buried = rand.next_int_bounded(3) + 1;
+
+        for dx in 0..=size_x {
+            for dy in 0..=size_y {
+                for dz in 0..=size_z {
+
+                    let edge_x = dx == 0 || dx == size_x;
+                    let edge_y = dy == 0 || dy == size_y;
+                    let edge_z = dz == 0 || dz == size_z;
+
+                    // Only the faces of the box are placed, without its edges.
+                    let faces = [edge_x, edge_y, edge_z].iter().filter(|&&edge| edge).count();
+
+                    if faces == 1 && rand.next_float() >= 0.1 {
+                        place_coral_block(level, rand, x + dx, y + dy - buried, z + dz, state);
+                    }
+
+                }
+            }
+        }
+
+    }
+
+}
+
+impl Feature for CoralFeature {
+
+    fn generate(&self, level: &mut dyn LevelView, rand: &mut JavaRandom, x: i32, y: i32, z: i32) -> bool {
+
+        let shape = match self.shape {
+            Some(shape) => shape,
+            None => CoralShape::random(rand)
+        };
+
+        let state = random_block(&CORAL_BLOCKS, rand).get_default_state();
+
+        match shape {
+            CoralShape::Tree => Self::generate_tree(level, rand, x, y, z, state),
+            CoralShape::Claw => Self::generate_claw(level, rand, x, y, z, state),
+            CoralShape::Mushroom => Self::generate_mushroom(level, rand, x, y, z, state)
+        }
+
+        true
+
+    }
+
+}
+
+
+// BIOMES //
+
+/// Build the vanilla vegetation of the given ocean biome: coral reefs, kelp, seagrass and
+/// sea pickles. Non-ocean and frozen ocean biomes have no ocean vegetation.
+///
+/// Valid for: 1.13 to 1.16.5
+pub fn new_vanilla_ocean_vegetation(biome: &'static Biome) -> FeatureChain {
+
+    let mut chain = FeatureChain::new();
+
+    if biome == &WARM_OCEAN {
+        chain.push(CoralFeature::new_vanilla());
+        chain.push(SeagrassFeature::new_vanilla(0.3, 80));
+        chain.push(SeaPickleFeature::new_vanilla());
+    } else if biome == &DEEP_WARM_OCEAN {
+        chain.push(SeagrassFeature::new_vanilla(0.8, 80));
+    } else if biome == &LUKEWARM_OCEAN {
+        chain.push(SeagrassFeature::new_vanilla(0.3, 80));
+        chain.push(KelpFeature::new_vanilla(80));
+    } else if biome == &DEEP_LUKEWARM_OCEAN {
+        chain.push(SeagrassFeature::new_vanilla(0.8, 80));
+        chain.push(KelpFeature::new_vanilla(80));
+    } else if biome == &OCEAN {
+        chain.push(SeagrassFeature::new_vanilla(0.3, 48));
+        chain.push(KelpFeature::new_vanilla(120));
+    } else if biome == &DEEP_OCEAN {
+        chain.push(SeagrassFeature::new_vanilla(0.8, 48));
+        chain.push(KelpFeature::new_vanilla(120));
+    } else if biome == &COLD_OCEAN {
+        chain.push(SeagrassFeature::new_vanilla(0.3, 32));
+        chain.push(KelpFeature::new_vanilla(120));
+    } else if biome == &DEEP_COLD_OCEAN {
+        chain.push(SeagrassFeature::new_vanilla(0.8, 40));
+        chain.push(KelpFeature::new_vanilla(120));
+    }
+
+    chain
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use crate::view::TestLevelView;
+
+    use super::*;
+
+    fn new_ocean() -> TestLevelView {
+        TestLevelView::new(SAND.get_default_state(), 40, &WARM_OCEAN)
+            .with_above(WATER.get_default_state())
+    }
+
+    #[test]
+    fn ocean_vegetation() {
+
+        let mut level = new_ocean();
+        let mut rand = JavaRandom::new(0);
+
+        // Kelp grows from the ocean floor, with a head on top.
+        assert!(KelpFeature.generate(&mut level, &mut rand, 0, 0, 0));
+        let mut y = 41;
+        while level.get_block_at(0, y, 0).unwrap().is_block(&KELP_PLANT) {
+            y += 1;
+        }
+        assert!(y <= 51);
+        assert!(level.get_block_at(0, y, 0).unwrap().is_block(&KELP));
+
+        // Seagrass and sea pickles only grow on the floor.
+        for _ in 0..32 {
+            SeagrassFeature::new(0.5).generate(&mut level, &mut rand, 0, 0, 0);
+        }
+        assert!(level.count_blocks(SEAGRASS.get_default_state()) > 0);
+        assert!(SeaPickleFeature::new(20).generate(&mut level, &mut rand, 0, 0, 0));
+
+        // Coral reefs of all shapes are made of a single coral block.
+        for shape in [CoralShape::Tree, CoralShape::Claw, CoralShape::Mushroom] {
+            let mut level = new_ocean();
+            assert!(CoralFeature::new(shape).generate(&mut level, &mut rand, 0, 41, 0));
+            let count = CORAL_BLOCKS.iter().filter(|block| level.count_blocks(block.get_default_state()) > 0).count();
+            assert_eq!(count, 1);
+        }
+
+        // Nothing grows out of the water.
+        let mut level = TestLevelView::new(SAND.get_default_state(), 40, &WARM_OCEAN);
+        assert!(!KelpFeature.generate(&mut level, &mut rand, 0, 0, 0));
+        assert!(!SeaPickleFeature::new(20).generate(&mut level, &mut rand, 0, 0, 0));
+
+    }
+
+}
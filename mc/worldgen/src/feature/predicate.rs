@@ -0,0 +1,253 @@
+//! Block predicates, a small language used by features and placement modifiers to check
+//! blocks around a position. Predicates can be defined statically or decoded from the
+//! `block_predicate` JSON objects of datapacks.
+
+use std::borrow::Cow;
+use std::ops::Not;
+
+use serde_json::Value;
+
+use mc_core::world::anvil::decode::DecodeError;
+use mc_core::world::level::LevelEnv;
+use mc_core::block::{Block, BlockState};
+use mc_core::pos::Direction;
+use mc_core::tag::TagType;
+
+use mc_vanilla::block::material::{VANILLA_BLOCK_MATERIALS, TAG_LEAVES, TAG_LOG, TAG_SAPLING};
+use mc_vanilla::block::AIR;
+
+use crate::view::LevelView;
+
+
+/// Offset from the tested position to the checked block.
+pub type BlockOffset = (i32, i32, i32);
+
+
+/// A predicate on the block at an offset of a position. Blocks outside of the level view
+/// are considered to be air.
+#[derive(Clone)]
+pub enum BlockPredicate {
+    True,
+    MatchingBlocks {
+        blocks: Cow<'static, [&'static Block]>,
+        offset: BlockOffset
+    },
+    MatchingBlockTag {
+        tag: &'static TagType,
+        offset: BlockOffset
+    },
+    /// The block can be replaced when placing another block over it.
+    Replaceable {
+        offset: BlockOffset
+    },
+    /// The block has a solid material.
+    Solid {
+        offset: BlockOffset
+    },
+    /// The face of the block in the given direction can support other blocks. Blocks have
+    /// no shape in this crate, so this is approximated with materials blocking light.
+    HasSturdyFace {
+        offset: BlockOffset,
+        direction: Direction
+    },
+    AllOf(Cow<'static, [BlockPredicate]>),
+    AnyOf(Cow<'static, [BlockPredicate]>),
+    Not(Box<BlockPredicate>)
+}
+
+impl BlockPredicate {
+
+    pub fn matching_blocks(blocks: &[&'static Block]) -> Self {
+        Self::MatchingBlocks { blocks: Cow::Owned(blocks.to_vec()), offset: (0, 0, 0) }
+    }
+
+    pub fn matching_block_tag(tag: &'static TagType) -> Self {
+        Self::MatchingBlockTag { tag, offset: (0, 0, 0) }
+    }
+
+    /// Test this predicate at the given position.
+    pub fn test(&self, level: &dyn LevelView, x: i32, y: i32, z: i32) -> bool {
+
+        let get_block = |(dx, dy, dz): BlockOffset| {
+            level.get_block_at(x + dx, y + dy, z + dz)
+                .map(BlockState::get_block)
+                .unwrap_or(&AIR)
+        };
+
+        match self {
+            Self::True => true,
+            Self::MatchingBlocks { blocks, offset } => blocks.contains(&get_block(*offset)),
+            Self::MatchingBlockTag { tag, offset } => level.get_env().blocks.has_block_tag(get_block(*offset), tag),
+            Self::Replaceable { offset } => VANILLA_BLOCK_MATERIALS.is_replaceable(get_block(*offset)),
+            Self::Solid { offset } => VANILLA_BLOCK_MATERIALS.is_solid(get_block(*offset)),
+            Self::HasSturdyFace { offset, .. } => VANILLA_BLOCK_MATERIALS.get_material(get_block(*offset)).solid_blocking,
+            Self::AllOf(predicates) => predicates.iter().all(|predicate| predicate.test(level, x, y, z)),
+            Self::AnyOf(predicates) => predicates.iter().any(|predicate| predicate.test(level, x, y, z)),
+            Self::Not(predicate) => !predicate.test(level, x, y, z)
+        }
+
+    }
+
+    /// Decode a block predicate from its JSON representation, blocks are searched in the
+    /// given environment.
+    pub fn from_json(json: &Value, env: &LevelEnv) -> Result<Self, DecodeError> {
+
+        let malformed = |msg: String| DecodeError::Malformed(msg);
+
+        let offset = match &json["offset"] {
+            Value::Null => (0, 0, 0),
+            Value::Array(coords) if coords.len() == 3 => {
+                let mut offset = [0; 3];
+                for (dst, coord) in offset.iter_mut().zip(coords) {
+                    *dst = coord.as_i64().ok_or_else(|| malformed("Invalid block predicate offset.".to_string()))? as i32;
+                }
+                (offset[0], offset[1], offset[2])
+            }
+            _ => return Err(malformed("Invalid block predicate offset.".to_string()))
+        };
+
+        let decode_all = |json: &Value| -> Result<Vec<Self>, DecodeError> {
+            json["predicates"].as_array()
+                .ok_or_else(|| malformed("Missing block predicates.".to_string()))?
+                .iter()
+                .map(|json| Self::from_json(json, env))
+                .collect()
+        };
+
+        let predicate_type = json["type"].as_str()
+            .ok_or_else(|| malformed("Block predicate without type.".to_string()))?;
+
+        Ok(match predicate_type {
+            "minecraft:true" => Self::True,
+            "minecraft:matching_blocks" => {
+                let names = match &json["blocks"] {
+                    Value::String(name) => vec![name.as_str()],
+                    Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+                    _ => return Err(malformed("Missing matching blocks.".to_string()))
+                };
+                let blocks = names.into_iter()
+                    .map(|name| env.blocks.get_block_from_name(name)
+                        .ok_or_else(|| malformed(format!("Unknown block '{}'.", name))))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Self::MatchingBlocks { blocks: Cow::Owned(blocks), offset }
+            }
+            "minecraft:matching_block_tag" => {
+                let name = json["tag"].as_str().unwrap_or_default();
+                let tag = get_vanilla_block_tag(name)
+                    .ok_or_else(|| malformed(format!("Unsupported block tag '{}'.", name)))?;
+                Self::MatchingBlockTag { tag, offset }
+            }
+            "minecraft:replaceable" => Self::Replaceable { offset },
+            "minecraft:solid" => Self::Solid { offset },
+            "minecraft:has_sturdy_face" => {
+                let direction = match json["direction"].as_str() {
+                    Some("east") => Direction::East,
+                    Some("west") => Direction::West,
+                    Some("south") => Direction::South,
+                    Some("north") => Direction::North,
+                    Some("up") => Direction::Up,
+                    Some("down") => Direction::Down,
+                    _ => return Err(malformed("Invalid sturdy face direction.".to_string()))
+                };
+                Self::HasSturdyFace { offset, direction }
+            }
+            "minecraft:all_of" => Self::AllOf(Cow::Owned(decode_all(json)?)),
+            "minecraft:any_of" => Self::AnyOf(Cow::Owned(decode_all(json)?)),
+            "minecraft:not" => !Self::from_json(&json["predicate"], env)?,
+            _ => return Err(malformed(format!("Unsupported block predicate type '{}'.", predicate_type)))
+        })
+
+    }
+
+}
+
+
+impl Not for BlockPredicate {
+    type Output = Self;
+    fn not(self) -> Self::Output {
+        Self::Not(Box::new(self))
+    }
+}
+
+
+/// Predicate matching air.
+pub static AIR_PREDICATE: BlockPredicate = BlockPredicate::MatchingBlocks {
+    blocks: Cow::Borrowed(&[&AIR]),
+    offset: (0, 0, 0)
+};
+
+/// Predicate matching air or leaves, these blocks can be replaced by trees.
+pub static AIR_OR_LEAVES_PREDICATE: BlockPredicate = BlockPredicate::AnyOf(Cow::Borrowed(&[
+    BlockPredicate::MatchingBlocks {
+        blocks: Cow::Borrowed(&[&AIR]),
+        offset: (0, 0, 0)
+    },
+    BlockPredicate::MatchingBlockTag {
+        tag: &TAG_LEAVES,
+        offset: (0, 0, 0)
+    }
+]));
+
+
+/// Return the logical block tag of this crate matching the given vanilla block tag, only a
+/// few tags are supported.
+fn get_vanilla_block_tag(name: &str) -> Option<&'static TagType> {
+    match name {
+        "minecraft:leaves" => Some(&TAG_LEAVES),
+        "minecraft:logs" => Some(&TAG_LOG),
+        "minecraft:saplings" => Some(&TAG_SAPLING),
+        _ => None
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_vanilla::ext::VanillaLevelEnv;
+    use mc_vanilla::block::{STONE, OAK_LEAVES, GRASS};
+    use mc_vanilla::biome::PLAINS;
+
+    use crate::view::TestLevelView;
+
+    use super::*;
+
+    #[test]
+    fn block_predicate() {
+
+        let mut level = TestLevelView::new(STONE.get_default_state(), 63, &PLAINS);
+        level.set_block_at(0, 64, 0, OAK_LEAVES.get_default_state()).unwrap();
+        level.set_block_at(1, 64, 0, GRASS.get_default_state()).unwrap();
+
+        assert!(AIR_OR_LEAVES_PREDICATE.test(&level, 0, 64, 0));
+        assert!(AIR_OR_LEAVES_PREDICATE.test(&level, 0, 65, 0));
+        assert!(!AIR_OR_LEAVES_PREDICATE.test(&level, 0, 63, 0));
+        assert!(AIR_PREDICATE.test(&level, 0, 1000, 0));
+
+        let env = LevelEnv::vanilla();
+        let json: Value = serde_json::from_str(r#"{
+            "type": "minecraft:all_of",
+            "predicates": [
+                { "type": "minecraft:replaceable" },
+                { "type": "minecraft:has_sturdy_face", "offset": [0, -1, 0], "direction": "up" },
+                {
+                    "type": "minecraft:not",
+                    "predicate": { "type": "minecraft:matching_blocks", "blocks": "minecraft:grass" }
+                }
+            ]
+        }"#).unwrap();
+
+        let predicate = BlockPredicate::from_json(&json, &env).unwrap();
+        assert!(predicate.test(&level, 2, 64, 0));
+        assert!(!predicate.test(&level, 1, 64, 0));
+        assert!(!predicate.test(&level, 2, 65, 0));
+        assert!(!predicate.test(&level, 2, 63, 0));
+
+        let json: Value = serde_json::from_str(r#"{ "type": "minecraft:matching_block_tag", "tag": "minecraft:leaves" }"#).unwrap();
+        assert!(BlockPredicate::from_json(&json, &env).unwrap().test(&level, 0, 64, 0));
+        let json: Value = serde_json::from_str(r#"{ "type": "minecraft:matching_blocks", "blocks": ["minecraft:unknown"] }"#).unwrap();
+        assert!(BlockPredicate::from_json(&json, &env).is_err());
+
+    }
+
+}
@@ -1,14 +1,11 @@
-use std::sync::Arc;
-
-use mc_core::world::level::LevelEnv;
 use mc_core::block::BlockState;
 use mc_core::rand::JavaRandom;
 use mc_core::math::JAVA_PI;
 
-use mc_vanilla::block::material::TAG_LEAVES;
 use mc_vanilla::block::*;
 
 use super::{TreePalette, TreeHeight, RandomTreeHeight, BlockLineIter};
+use crate::feature::predicate::AIR_OR_LEAVES_PREDICATE;
 use crate::feature::Feature;
 use crate::view::LevelView;
 
@@ -48,7 +45,6 @@ impl<H: TreeHeight> Feature for BigTreeFeature<H> {
         BigTreeBuilder {
             rand: JavaRandom::new(rand.next_long()),
             feature: self,
-            env: Arc::clone(level.get_env()),
             level
         }.generate(x, y, z)
     }
@@ -59,7 +55,6 @@ impl<H: TreeHeight> Feature for BigTreeFeature<H> {
 struct BigTreeBuilder<'a, 'b, H> {
     rand: JavaRandom,
     feature: &'a BigTreeFeature<H>,
-    env: Arc<LevelEnv>,
     level: &'b mut dyn LevelView
 }
 
@@ -201,8 +196,7 @@ impl<'a, 'b, H: TreeHeight> BigTreeBuilder<'a, 'b, H> {
                 if dist <= radius_f64 {
                     let bx = x + dx;
                     let bz = z + dz;
-                    let block = self.level.get_block_at(bx, y, bz).unwrap().get_block();
-                    if block == &AIR || self.env.blocks.has_block_tag(block, &TAG_LEAVES) {
+                    if AIR_OR_LEAVES_PREDICATE.test(self.level, bx, y, bz) {
                         self.level.set_block_at(bx, y, bz, leaves_block).unwrap();
                     }
                 }
@@ -242,8 +236,7 @@ impl<'a, 'b, H: TreeHeight> BigTreeBuilder<'a, 'b, H> {
     /// ray don't hit any block.
     fn count_block_line(&self, from: [i32; 3], to: [i32; 3]) -> i32 {
         for (x, y, z, step) in BlockLineIter::new(from, to, 0.0) {
-            let block = self.level.get_block_at(x, y, z).unwrap().get_block();
-            if block != &AIR && !self.env.blocks.has_block_tag(block, &TAG_LEAVES) {
+            if !AIR_OR_LEAVES_PREDICATE.test(self.level, x, y, z) {
                 return step.abs();
             }
         }
@@ -5,6 +5,7 @@ use mc_core::rand::JavaRandom;
 use mc_vanilla::block::*;
 use mc_vanilla::block::material::{TAG_LOG, TAG_LEAVES};
 
+use crate::feature::predicate::{AIR_PREDICATE, AIR_OR_LEAVES_PREDICATE};
 use crate::feature::Feature;
 use crate::view::LevelView;
 use super::{
@@ -169,26 +170,25 @@ impl<H: TreeHeight> Feature for TreeFeature<H> {
         let block_north_vine = block_vine.with(&PROP_NORTH, true).unwrap();
 
         for dy in y..(y + height) {
-            let block = level.get_block_at(x, dy, z).unwrap().get_block();
-            if block == &AIR || env_blocks.has_block_tag(block, &TAG_LEAVES) || (is_swamp_kind && block == &WATER) {
+            if AIR_OR_LEAVES_PREDICATE.test(level, x, dy, z) || (is_swamp_kind && level.get_block_at(x, dy, z).unwrap().is_block(&WATER)) {
 
                 level.set_block_at(x, dy, z, block_log).unwrap();
 
                 if matches!(self.kind, TreeKind::Jungle) && dy != y {
 
-                    if rand.next_int_bounded(3) != 0 && level.get_block_at(x - 1, dy, z).unwrap().is_block(&AIR) {
+                    if rand.next_int_bounded(3) != 0 && AIR_PREDICATE.test(level, x - 1, dy, z) {
                         level.set_block_at(x - 1, dy, z, block_east_vine).unwrap();
                     }
 
-                    if rand.next_int_bounded(3) != 0 && level.get_block_at(x + 1, dy, z).unwrap().is_block(&AIR) {
+                    if rand.next_int_bounded(3) != 0 && AIR_PREDICATE.test(level, x + 1, dy, z) {
                         level.set_block_at(x + 1, dy, z, block_west_vine).unwrap();
                     }
 
-                    if rand.next_int_bounded(3) != 0 && level.get_block_at(x, dy, z - 1).unwrap().is_block(&AIR) {
+                    if rand.next_int_bounded(3) != 0 && AIR_PREDICATE.test(level, x, dy, z - 1) {
                         level.set_block_at(x, dy, z - 1, block_south_vine).unwrap();
                     }
 
-                    if rand.next_int_bounded(3) != 0 && level.get_block_at(x, dy, z + 1).unwrap().is_block(&AIR) {
+                    if rand.next_int_bounded(3) != 0 && AIR_PREDICATE.test(level, x, dy, z + 1) {
                         level.set_block_at(x, dy, z + 1, block_north_vine).unwrap();
                     }
 
@@ -208,19 +208,19 @@ impl<H: TreeHeight> Feature for TreeFeature<H> {
                     for dz in (z - radius)..=(z + radius) {
                         if env_blocks.has_block_tag(level.get_block_at(dx, dy, dz).unwrap().get_block(), &TAG_LEAVES) {
 
-                            if rand.next_int_bounded(4) == 0 && level.get_block_at(dx - 1, dy, dz).unwrap().is_block(&AIR) {
+                            if rand.next_int_bounded(4) == 0 && AIR_PREDICATE.test(level, dx - 1, dy, dz) {
                                 generate_falling_vines(level, dx - 1, dy, dz, block_east_vine);
                             }
 
-                            if rand.next_int_bounded(4) == 0 && level.get_block_at(dx + 1, dy, dz).unwrap().is_block(&AIR) {
+                            if rand.next_int_bounded(4) == 0 && AIR_PREDICATE.test(level, dx + 1, dy, dz) {
                                 generate_falling_vines(level, dx + 1, dy, dz, block_west_vine);
                             }
 
-                            if rand.next_int_bounded(4) == 0 && level.get_block_at(dx, dy, dz - 1).unwrap().is_block(&AIR) {
+                            if rand.next_int_bounded(4) == 0 && AIR_PREDICATE.test(level, dx, dy, dz - 1) {
                                 generate_falling_vines(level, dx, dy, dz - 1, block_south_vine);
                             }
 
-                            if rand.next_int_bounded(4) == 0 && level.get_block_at(dx, dy, dz + 1).unwrap().is_block(&AIR) {
+                            if rand.next_int_bounded(4) == 0 && AIR_PREDICATE.test(level, dx, dy, dz + 1) {
                                 generate_falling_vines(level, dx, dy, dz + 1, block_north_vine);
                             }
 
@@ -8,6 +8,7 @@ use mc_vanilla::block::material::{TAG_LEAVES, TAG_LOG, TAG_NON_SOLID, TAG_SAPLIN
 use mc_vanilla::block::*;
 
 use super::{TreePalette, TreeHeight, DoubleRandomTreeHeight, generate_leaves_layer};
+use crate::feature::predicate::{AIR_PREDICATE, AIR_OR_LEAVES_PREDICATE};
 use crate::feature::Feature;
 use crate::view::LevelView;
 
@@ -33,12 +34,8 @@ impl Feature for ShrubFeature {
 
     fn generate(&self, level: &mut dyn LevelView, rand: &mut JavaRandom, x: i32, mut y: i32, z: i32) -> bool {
 
-        let env = Arc::clone(level.get_env());
-        let env_blocks = &env.blocks;
-
         loop {
-            let block = level.get_block_at(x, y, z).unwrap().get_block();
-            if (block == &AIR || env_blocks.has_block_tag(block, &TAG_LEAVES)) && y > 0 {
+            if AIR_OR_LEAVES_PREDICATE.test(level, x, y, z) && y > 0 {
                 y -= 1;
             } else {
                 y += 1;
@@ -173,21 +170,21 @@ impl<H: TreeHeight> Feature for HugeJungleTreeFeature<H> {
 
             let dy = by - y;
 
-            if generate_core_log(level, env_blocks, x, by, z, block_log) && dy > 0 {
+            if generate_core_log(level, x, by, z, block_log) && dy > 0 {
                 generate_core_vines(level, rand, x, by, z, -1, -1, block_east_vine, block_south_vine);
             }
 
             if dy < height - 1 {
 
-                if generate_core_log(level, env_blocks, x + 1, by, z, block_log) && dy > 0 {
+                if generate_core_log(level, x + 1, by, z, block_log) && dy > 0 {
                     generate_core_vines(level, rand, x + 1, by, z, 1, -1, block_west_vine, block_south_vine);
                 }
 
-                if generate_core_log(level, env_blocks, x + 1, by, z + 1, block_log) && dy > 0 {
+                if generate_core_log(level, x + 1, by, z + 1, block_log) && dy > 0 {
                     generate_core_vines(level, rand, x + 1, by, z + 1, 1, 1, block_west_vine, block_north_vine);
                 }
 
-                if generate_core_log(level, env_blocks, x, by, z + 1, block_log) && dy > 0 {
+                if generate_core_log(level, x, by, z + 1, block_log) && dy > 0 {
                     generate_core_vines(level, rand, x, by, z + 1, -1, 1, block_east_vine, block_north_vine);
                 }
 
@@ -239,12 +236,10 @@ fn generate_crown(
 #[inline]
 fn generate_core_log(
     level: &mut dyn LevelView,
-    env_blocks: &GlobalBlocks,
     x: i32, y: i32, z: i32,
     block_log: &'static BlockState,
 ) -> bool {
-    let current_block = level.get_block_at(x, y, z).unwrap().get_block();
-    if current_block == &AIR || env_blocks.has_block_tag(current_block, &TAG_LEAVES) {
+    if AIR_OR_LEAVES_PREDICATE.test(level, x, y, z) {
         level.set_block_at(x, y, z, block_log).unwrap();
         true
     } else {
@@ -262,10 +257,10 @@ fn generate_core_vines(
     dx_vine_state: &'static BlockState,
     dz_vine_state: &'static BlockState,
 ) {
-    if rand.next_int_bounded(3) != 0 && level.get_block_at(x + dx_vine, y, z).unwrap().is_block(&AIR) {
+    if rand.next_int_bounded(3) != 0 && AIR_PREDICATE.test(level, x + dx_vine, y, z) {
         level.set_block_at(x + dx_vine, y, z, dx_vine_state).unwrap();
     }
-    if rand.next_int_bounded(3) != 0 && level.get_block_at(x, y, z + dz_vine).unwrap().is_block(&AIR) {
+    if rand.next_int_bounded(3) != 0 && AIR_PREDICATE.test(level, x, y, z + dz_vine) {
         level.set_block_at(x, y, z + dz_vine, dz_vine_state).unwrap();
     }
 }
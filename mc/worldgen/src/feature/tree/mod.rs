@@ -9,6 +9,7 @@ use mc_core::pos::Axis;
 use mc_vanilla::block::material::TAG_NON_SOLID;
 use mc_vanilla::block::*;
 
+use super::predicate::AIR_PREDICATE;
 use super::LevelView;
 
 mod common;
@@ -119,7 +120,7 @@ pub fn generate_falling_vines(level: &mut dyn LevelView, x: i32, mut y: i32, z:
     for _ in 0..5 {
         level.set_block_at(x, y, z, state).unwrap();
         y -= 1;
-        if !level.get_block_at(x, y, z).unwrap().is_block(&AIR) {
+        if !AIR_PREDICATE.test(level, x, y, z) {
             break;
         }
     }
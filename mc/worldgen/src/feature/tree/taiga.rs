@@ -6,6 +6,7 @@ use mc_vanilla::block::material::TAG_LEAVES;
 use mc_vanilla::block::*;
 
 use super::{TreePalette, TreeHeight, RandomTreeHeight, generate_leaves_layer};
+use crate::feature::predicate::AIR_OR_LEAVES_PREDICATE;
 use crate::feature::Feature;
 use crate::view::LevelView;
 
@@ -141,8 +142,7 @@ impl<H: TreeHeight> Feature for TaigaTreeFeature<H> {
         };
 
         for dy in y..(y + height - log_height_offset) {
-            let block = level.get_block_at(x, dy, z).unwrap().get_block();
-            if block == &AIR || env_blocks.has_block_tag(block, &TAG_LEAVES) {
+            if AIR_OR_LEAVES_PREDICATE.test(level, x, dy, z) {
                 level.set_block_at(x, dy, z, block_log).unwrap();
             }
         }
@@ -0,0 +1,135 @@
+use mc_core::rand::JavaRandom;
+
+use mc_vanilla::heightmap::WORLD_SURFACE;
+use mc_vanilla::block::*;
+
+use crate::view::LevelView;
+
+use super::distrib::HeightmapDistrib;
+use super::Feature;
+
+
+/// Desert well feature, a small sandstone well with 5 water blocks. The well is only placed
+/// on sand and if the ground below is not hollow.
+///
+/// Valid for: 1.2.5 to 1.17.1
+pub struct DesertWellFeature;
+
+impl DesertWellFeature {
+
+    /// Vanilla desert wells placement, 1 in 1000 chunks on the surface of desert biomes.
+    pub fn new_vanilla() -> impl Feature {
+        DesertWellFeature
+            .distributed(HeightmapDistrib::new(&WORLD_SURFACE))
+            .optional(1000)
+    }
+
+}
+
+impl Feature for DesertWellFeature {
+
+    fn generate(&self, level: &mut dyn LevelView, _rand: &mut JavaRandom, x: i32, mut y: i32, z: i32) -> bool {
+
+        while y > 2 && level.get_block_at(x, y, z).unwrap().is_block(&AIR) {
+            y -= 1;
+        }
+
+        if !level.get_block_at(x, y, z).unwrap().is_block(&SAND) {
+            return false;
+        }
+
+        for dx in -2..=2 {
+            for dz in -2..=2 {
+                if level.get_block_at(x + dx, y - 1, z + dz).unwrap().is_block(&AIR) &&
+                    level.get_block_at(x + dx, y - 2, z + dz).unwrap().is_block(&AIR) {
+                    return false;
+                }
+            }
+        }
+
+        let sandstone = SANDSTONE.get_default_state();
+        let slab = SANDSTONE_SLAB.get_default_state().with(&PROP_SLAB_TYPE, SlabType::Bottom).unwrap();
+        let water = WATER.get_default_state();
+
+        // Base and water.
+        for dy in -1..=0 {
+            for dx in -2..=2 {
+                for dz in -2..=2 {
+                    level.set_block_at(x + dx, y + dy, z + dz, sandstone).unwrap();
+                }
+            }
+        }
+
+        level.set_block_at(x, y, z, water).unwrap();
+        level.set_block_at(x - 1, y, z, water).unwrap();
+        level.set_block_at(x + 1, y, z, water).unwrap();
+        level.set_block_at(x, y, z - 1, water).unwrap();
+        level.set_block_at(x, y, z + 1, water).unwrap();
+
+        // Rim and its slabs.
+        for dx in -2..=2 {
+            for dz in -2..=2 {
+                if dx == -2 || dx == 2 || dz == -2 || dz == 2 {
+                    level.set_block_at(x + dx, y + 1, z + dz, sandstone).unwrap();
+                }
+            }
+        }
+
+        level.set_block_at(x + 2, y + 1, z, slab).unwrap();
+        level.set_block_at(x - 2, y + 1, z, slab).unwrap();
+        level.set_block_at(x, y + 1, z + 2, slab).unwrap();
+        level.set_block_at(x, y + 1, z - 2, slab).unwrap();
+
+        // Roof.
+        for dx in -1..=1 {
+            for dz in -1..=1 {
+                level.set_block_at(x + dx, y + 4, z + dz, if dx == 0 && dz == 0 {
+                    sandstone
+                } else {
+                    slab
+                }).unwrap();
+            }
+        }
+
+        // Pillars.
+        for dy in 1..=3 {
+            level.set_block_at(x - 1, y + dy, z - 1, sandstone).unwrap();
+            level.set_block_at(x - 1, y + dy, z + 1, sandstone).unwrap();
+            level.set_block_at(x + 1, y + dy, z - 1, sandstone).unwrap();
+            level.set_block_at(x + 1, y + dy, z + 1, sandstone).unwrap();
+        }
+
+        true
+
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_vanilla::biome::DESERT;
+
+    use crate::view::TestLevelView;
+
+    use super::*;
+
+    #[test]
+    fn desert_well() {
+
+        let mut rand = JavaRandom::new(0);
+
+        let mut level = TestLevelView::new(SAND.get_default_state(), 64, &DESERT);
+        assert!(DesertWellFeature.generate(&mut level, &mut rand, 0, 70, 0));
+        assert_eq!(level.count_blocks(WATER.get_default_state()), 5);
+        assert!(level.get_block_at(0, 64, 0).unwrap().is_block(&WATER));
+        assert!(level.get_block_at(0, 68, 0).unwrap().is_block(&SANDSTONE));
+        assert!(level.get_block_at(2, 65, 0).unwrap().is_block(&SANDSTONE_SLAB));
+
+        let mut level = TestLevelView::new(STONE.get_default_state(), 64, &DESERT);
+        assert!(!DesertWellFeature.generate(&mut level, &mut rand, 0, 70, 0));
+
+    }
+
+}
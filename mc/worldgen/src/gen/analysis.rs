@@ -0,0 +1,215 @@
+//! Analysis helpers for tools that need to inspect many seeds or large areas quickly, such
+//! as seed finders and map previews. These helpers only query biome sources and noise
+//! columns, no chunk is actually generated.
+
+use std::collections::HashMap;
+
+use mc_core::biome::{Biome, BiomeKey};
+use mc_core::util::Rect;
+
+use super::legacy::{BiomeSource, BiomeResolution};
+
+
+/// Maximum size of the rectangles queried to biome sources along each axis, larger areas are
+/// split in tiles to bound the memory used by biome layers.
+const SAMPLE_TILE_SIZE: usize = 256;
+
+
+/// Number of occurrences of each biome sampled over an area.
+#[derive(Default)]
+pub struct BiomeStats {
+    counts: HashMap<BiomeKey, (&'static Biome, usize)>,
+    total: usize
+}
+
+impl BiomeStats {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the given number of occurrences of a biome.
+    pub fn add(&mut self, biome: &'static Biome, count: usize) {
+        self.counts.entry(biome.get_key()).or_insert((biome, 0)).1 += count;
+        self.total += count;
+    }
+
+    /// Add all biomes of the given rectangle.
+    pub fn add_rect(&mut self, biomes: &Rect<&'static Biome>) {
+        for &biome in &biomes.data {
+            self.add(biome, 1);
+        }
+    }
+
+    /// Add all occurrences of other statistics to these ones.
+    pub fn merge(&mut self, other: &BiomeStats) {
+        for &(biome, count) in other.counts.values() {
+            self.add(biome, count);
+        }
+    }
+
+    /// Return the total number of samples.
+    #[inline]
+    pub fn get_total(&self) -> usize {
+        self.total
+    }
+
+    /// Return the number of samples of the given biome.
+    pub fn get_count(&self, biome: &'static Biome) -> usize {
+        self.counts.get(&biome.get_key()).map(|&(_, count)| count).unwrap_or(0)
+    }
+
+    /// Return the proportion of samples of the given biome, between 0 and 1.
+    pub fn get_frequency(&self, biome: &'static Biome) -> f32 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.get_count(biome) as f32 / self.total as f32
+        }
+    }
+
+    /// Return the biome with the most samples, `None` if there is no sample.
+    pub fn get_dominant(&self) -> Option<&'static Biome> {
+        self.counts.values()
+            .max_by_key(|&&(biome, count)| (count, -biome.get_id()))
+            .map(|&(biome, _)| biome)
+    }
+
+    /// Iterate over all sampled biomes with their number of samples, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static Biome, usize)> + '_ {
+        self.counts.values().copied()
+    }
+
+    /// Return all sampled biomes with their number of samples, most frequent first.
+    pub fn to_sorted_vec(&self) -> Vec<(&'static Biome, usize)> {
+        let mut vec: Vec<_> = self.iter().collect();
+        vec.sort_by_key(|&(biome, count)| (std::cmp::Reverse(count), biome.get_id()));
+        vec
+    }
+
+}
+
+
+/// Sample the biomes of an area from a biome source at the given resolution, coordinates and
+/// sizes are given in the resolution's unit (blocks or noise cells of 4 blocks). The area is
+/// queried by tiles, so it can be arbitrarily large.
+pub fn sample_biomes<B>(biome_source: &mut B, resolution: BiomeResolution, x: i32, z: i32, x_size: usize, z_size: usize) -> BiomeStats
+where
+    B: BiomeSource + ?Sized
+{
+
+    let mut stats = BiomeStats::new();
+
+    for tile_dz in (0..z_size).step_by(SAMPLE_TILE_SIZE) {
+        for tile_dx in (0..x_size).step_by(SAMPLE_TILE_SIZE) {
+
+            let tile_x = x + tile_dx as i32;
+            let tile_z = z + tile_dz as i32;
+            let tile_x_size = (x_size - tile_dx).min(SAMPLE_TILE_SIZE);
+            let tile_z_size = (z_size - tile_dz).min(SAMPLE_TILE_SIZE);
+
+            let biomes = match resolution {
+                BiomeResolution::Block => biome_source.get_biomes(tile_x, tile_z, tile_x_size, tile_z_size),
+                BiomeResolution::Noise => biome_source.get_noise_biomes(tile_x, tile_z, tile_x_size, tile_z_size)
+            };
+
+            assert!(biomes.x_size == tile_x_size && biomes.z_size == tile_z_size, "The biome source returned invalid biomes.");
+            stats.add_rect(&biomes);
+
+        }
+    }
+
+    stats
+
+}
+
+
+/// Trait for noise generators able to compute the terrain height of a chunk from their noise
+/// columns only, without building blocks, surface or structures. Heights don't include the
+/// sea and may slightly differ from generated chunks once carvers and features are applied.
+pub trait HeightSampler {
+    /// Return the 16x16 heights of the given chunk, indexed by relative block coordinates. The
+    /// height of a column is the Y coordinate just above its highest solid block, or 0 if the
+    /// column is empty.
+    fn sample_heights<B: BiomeSource + ?Sized>(&mut self, cx: i32, cz: i32, biome_source: &mut B) -> Rect<i32>;
+}
+
+
+/// Sample the terrain heights of a rectangle of chunks, the returned rectangle has a height
+/// per block column, starting at the block coordinates of the first chunk.
+pub fn sample_heights_area<S, B>(sampler: &mut S, biome_source: &mut B, cx: i32, cz: i32, cx_size: usize, cz_size: usize) -> Rect<i32>
+where
+    S: HeightSampler,
+    B: BiomeSource + ?Sized
+{
+
+    let mut heights = Rect::new(cx_size * 16, cz_size * 16, 0);
+
+    for dcz in 0..cz_size {
+        for dcx in 0..cx_size {
+            let chunk_heights = sampler.sample_heights(cx + dcx as i32, cz + dcz as i32, biome_source);
+            for z in 0..16 {
+                for x in 0..16 {
+                    heights.set(dcx * 16 + x, dcz * 16 + z, *chunk_heights.get(x, z));
+                }
+            }
+        }
+    }
+
+    heights
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_vanilla::biome::{PLAINS, DESERT};
+
+    use super::*;
+
+    /// A biome source with desert for positive X coordinates and plains elsewhere, checking
+    /// that queried rectangles are not larger than tiles.
+    struct HalfBiomeSource;
+
+    impl BiomeSource for HalfBiomeSource {
+
+        fn get_biomes(&mut self, x: i32, _z: i32, x_size: usize, z_size: usize) -> Rect<&'static Biome> {
+            assert!(x_size <= SAMPLE_TILE_SIZE && z_size <= SAMPLE_TILE_SIZE);
+            let mut rect = Rect::new(x_size, z_size, &PLAINS);
+            for dx in 0..x_size {
+                if x + dx as i32 >= 0 {
+                    for dz in 0..z_size {
+                        rect.set(dx, dz, &DESERT);
+                    }
+                }
+            }
+            rect
+        }
+
+        fn get_noise_biomes(&mut self, x: i32, z: i32, x_size: usize, z_size: usize) -> Rect<&'static Biome> {
+            self.get_biomes(x, z, x_size, z_size)
+        }
+
+    }
+
+    #[test]
+    fn sample_biome_stats() {
+
+        let stats = sample_biomes(&mut HalfBiomeSource, BiomeResolution::Block, -300, 0, 600, 2);
+        assert_eq!(stats.get_total(), 1200);
+        assert_eq!(stats.get_count(&PLAINS), 600);
+        assert_eq!(stats.get_count(&DESERT), 600);
+        assert_eq!(stats.get_frequency(&DESERT), 0.5);
+
+        let mut stats = sample_biomes(&mut HalfBiomeSource, BiomeResolution::Noise, -10, 0, 30, 10);
+        assert_eq!(stats.get_dominant(), Some(&DESERT));
+        assert_eq!(stats.to_sorted_vec(), vec![(&DESERT, 200), (&PLAINS, 100)]);
+
+        stats.merge(&sample_biomes(&mut HalfBiomeSource, BiomeResolution::Noise, -300, 0, 10, 30));
+        assert_eq!(stats.get_dominant(), Some(&PLAINS));
+        assert_eq!(stats.get_total(), 600);
+
+    }
+
+}
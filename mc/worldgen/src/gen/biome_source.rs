@@ -0,0 +1,248 @@
+//! Common biome sources that are not bound to a specific generator version: a fixed source
+//! returning a single biome and a multi noise source choosing biomes from climate noises,
+//! like the nether since 1.16.
+
+use mc_core::biome::Biome;
+use mc_core::rand::JavaRandom;
+use mc_core::util::Rect;
+
+use mc_vanilla::biome::{NETHER_WASTES, SOUL_SAND_VALLEY, CRIMSON_FOREST, WARPED_FOREST, BASALT_DELTAS};
+
+use crate::noise::{PerlinNoiseOctaves, NoiseRect};
+
+use super::legacy::BiomeSource;
+
+
+/// A biome source returning the same biome everywhere.
+pub struct FixedBiomeSource {
+    biome: &'static Biome
+}
+
+impl FixedBiomeSource {
+
+    pub fn new(biome: &'static Biome) -> Self {
+        Self { biome }
+    }
+
+    #[inline]
+    pub fn get_biome(&self) -> &'static Biome {
+        self.biome
+    }
+
+}
+
+impl BiomeSource for FixedBiomeSource {
+
+    fn get_biomes(&mut self, _x: i32, _z: i32, x_size: usize, z_size: usize) -> Rect<&'static Biome> {
+        Rect::new(x_size, z_size, self.biome)
+    }
+
+    fn get_noise_biomes(&mut self, _x: i32, _z: i32, x_size: usize, z_size: usize) -> Rect<&'static Biome> {
+        Rect::new(x_size, z_size, self.biome)
+    }
+
+}
+
+
+/// Scale of climate noises, per noise cell of 4 blocks.
+const CLIMATE_NOISE_SCALE: f64 = 1.0 / 32.0;
+/// Inverse of the sum of the amplitudes of the climate noise octaves, to keep values near
+/// the range -1 to 1.
+const CLIMATE_NOISE_FACTOR: f64 = 1.0 / 3.0;
+
+
+/// The climate parameters of a biome in a `MultiNoiseBiomeSource`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClimatePoint {
+    pub temperature: f32,
+    pub humidity: f32,
+    pub altitude: f32,
+    pub weirdness: f32,
+    /// An offset added to the distance of this point, the higher the offset, the rarer the
+    /// biome.
+    pub offset: f32
+}
+
+impl ClimatePoint {
+
+    pub fn new(temperature: f32, humidity: f32, altitude: f32, weirdness: f32, offset: f32) -> Self {
+        Self {
+            temperature,
+            humidity,
+            altitude,
+            weirdness,
+            offset
+        }
+    }
+
+    /// Return the squared distance of the given climate to this point, including the offset.
+    #[inline]
+    fn distance_sq(&self, temperature: f32, humidity: f32, altitude: f32, weirdness: f32) -> f32 {
+        (self.temperature - temperature).powi(2)
+            + (self.humidity - humidity).powi(2)
+            + (self.altitude - altitude).powi(2)
+            + (self.weirdness - weirdness).powi(2)
+            + self.offset.powi(2)
+    }
+
+}
+
+
+/// A biome source choosing, for each noise cell, the biome whose climate point is the nearest
+/// to the climate sampled from four noises (temperature, humidity, altitude and weirdness).
+///
+/// The climate noises are 2D perlin noises, biomes are therefore not identical to vanilla
+/// ones, that are sampled from 3D noises and smoothed with a fuzzy zoom.
+pub struct MultiNoiseBiomeSource {
+    points: Vec<(ClimatePoint, &'static Biome)>,
+    temperature_noise: PerlinNoiseOctaves<2>,
+    humidity_noise: PerlinNoiseOctaves<2>,
+    altitude_noise: PerlinNoiseOctaves<2>,
+    weirdness_noise: PerlinNoiseOctaves<2>
+}
+
+impl MultiNoiseBiomeSource {
+
+    /// Construct a new multi noise source without biomes, at least one biome must be added
+    /// with `with_biome` before querying biomes.
+    pub fn new(seed: i64) -> Self {
+        Self {
+            points: Vec::new(),
+            temperature_noise: PerlinNoiseOctaves::new(&mut JavaRandom::new(seed)),
+            humidity_noise: PerlinNoiseOctaves::new(&mut JavaRandom::new(seed.wrapping_add(1))),
+            altitude_noise: PerlinNoiseOctaves::new(&mut JavaRandom::new(seed.wrapping_add(2))),
+            weirdness_noise: PerlinNoiseOctaves::new(&mut JavaRandom::new(seed.wrapping_add(3)))
+        }
+    }
+
+    /// Construct a multi noise source with the biomes and climate points of the vanilla
+    /// nether preset. Because climate noises are sampled differently, the biomes are only an
+    /// approximation of the vanilla nether.
+    pub fn approximate_nether(seed: i64) -> Self {
+        Self::new(seed)
+            .with_biome(ClimatePoint::new(0.0, 0.0, 0.0, 0.0, 0.0), &NETHER_WASTES)
+            .with_biome(ClimatePoint::new(0.0, -0.5, 0.0, 0.0, 0.0), &SOUL_SAND_VALLEY)
+            .with_biome(ClimatePoint::new(0.4, 0.0, 0.0, 0.0, 0.0), &CRIMSON_FOREST)
+            .with_biome(ClimatePoint::new(0.0, 0.5, 0.0, 0.0, 0.375), &WARPED_FOREST)
+            .with_biome(ClimatePoint::new(-0.5, 0.0, 0.0, 0.0, 0.175), &BASALT_DELTAS)
+    }
+
+    pub fn with_biome(mut self, point: ClimatePoint, biome: &'static Biome) -> Self {
+        self.points.push((point, biome));
+        self
+    }
+
+    #[inline]
+    pub fn get_points(&self) -> &[(ClimatePoint, &'static Biome)] {
+        &self.points[..]
+    }
+
+}
+
+impl BiomeSource for MultiNoiseBiomeSource {
+
+    fn get_biomes(&mut self, x: i32, z: i32, x_size: usize, z_size: usize) -> Rect<&'static Biome> {
+
+        let noise_x = x >> 2;
+        let noise_z = z >> 2;
+        let noise_x_size = (((x + x_size as i32 - 1) >> 2) - noise_x + 1) as usize;
+        let noise_z_size = (((z + z_size as i32 - 1) >> 2) - noise_z + 1) as usize;
+        let noise_biomes = self.get_noise_biomes(noise_x, noise_z, noise_x_size, noise_z_size);
+
+        let mut biomes = Rect::new(x_size, z_size, *noise_biomes.get(0, 0));
+        for dz in 0..z_size {
+            for dx in 0..x_size {
+                let biome_x = (((x + dx as i32) >> 2) - noise_x) as usize;
+                let biome_z = (((z + dz as i32) >> 2) - noise_z) as usize;
+                biomes.set(dx, dz, *noise_biomes.get(biome_x, biome_z));
+            }
+        }
+
+        biomes
+
+    }
+
+    fn get_noise_biomes(&mut self, x: i32, z: i32, x_size: usize, z_size: usize) -> Rect<&'static Biome> {
+
+        assert!(!self.points.is_empty(), "The multi noise biome source has no biome.");
+
+        let mut temperature = NoiseRect::new_default(x_size, z_size);
+        let mut humidity = NoiseRect::new_default(x_size, z_size);
+        let mut altitude = NoiseRect::new_default(x_size, z_size);
+        let mut weirdness = NoiseRect::new_default(x_size, z_size);
+        self.temperature_noise.generate_2d(&mut temperature, x, z, CLIMATE_NOISE_SCALE, CLIMATE_NOISE_SCALE);
+        self.humidity_noise.generate_2d(&mut humidity, x, z, CLIMATE_NOISE_SCALE, CLIMATE_NOISE_SCALE);
+        self.altitude_noise.generate_2d(&mut altitude, x, z, CLIMATE_NOISE_SCALE, CLIMATE_NOISE_SCALE);
+        self.weirdness_noise.generate_2d(&mut weirdness, x, z, CLIMATE_NOISE_SCALE, CLIMATE_NOISE_SCALE);
+
+        let mut biomes = Rect::new(x_size, z_size, self.points[0].1);
+        for dz in 0..z_size {
+            for dx in 0..x_size {
+
+                let climate = |noise: &NoiseRect| (*noise.get(dx, dz) * CLIMATE_NOISE_FACTOR) as f32;
+                let (t, h, a, w) = (climate(&temperature), climate(&humidity), climate(&altitude), climate(&weirdness));
+
+                let (_, biome) = self.points.iter()
+                    .min_by(|(p0, _), (p1, _)| p0.distance_sq(t, h, a, w).total_cmp(&p1.distance_sq(t, h, a, w)))
+                    .unwrap();
+
+                biomes.set(dx, dz, *biome);
+
+            }
+        }
+
+        biomes
+
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_vanilla::biome::DESERT;
+
+    use crate::gen::legacy::{ChunkBiomeCache, BiomeResolution};
+    use crate::gen::analysis::sample_biomes;
+
+    use super::*;
+
+    #[test]
+    fn fixed_and_multi_noise_sources() {
+
+        let mut fixed = FixedBiomeSource::new(&DESERT);
+        assert_eq!(fixed.get_biomes(-7, 3, 5, 6).data, vec![&DESERT; 30]);
+
+        // Climate noises seeds are derived from the world seed without overflowing.
+        MultiNoiseBiomeSource::approximate_nether(i64::MAX).get_noise_biomes(0, 0, 4, 4);
+
+        let mut nether = MultiNoiseBiomeSource::approximate_nether(1234);
+        let stats = sample_biomes(&mut nether, BiomeResolution::Noise, -256, -256, 512, 512);
+        assert_eq!(stats.get_total(), 512 * 512);
+        assert!(stats.iter().count() >= 3);
+        assert!(stats.iter().all(|(biome, _)| nether.get_points().iter().any(|&(_, b)| b == biome)));
+
+        // Block biomes are the noise biomes of their cell.
+        let biomes = nether.get_biomes(-6, 10, 16, 16);
+        let noise_biomes = nether.get_noise_biomes(-2, 2, 5, 5);
+        for dz in 0..16 {
+            for dx in 0..16 {
+                let noise_x = ((-6 + dx as i32) >> 2) + 2;
+                let noise_z = ((10 + dz as i32) >> 2) - 2;
+                assert_eq!(*biomes.get(dx, dz), *noise_biomes.get(noise_x as usize, noise_z as usize));
+            }
+        }
+
+        // Sources can be used behind trait objects.
+        let mut sources: Vec<Box<dyn BiomeSource>> = vec![Box::new(fixed), Box::new(nether)];
+        for source in &mut sources {
+            let cache = ChunkBiomeCache::new(source, 3, -1, BiomeResolution::Block);
+            assert_eq!(cache.get_position(), (3, -1));
+        }
+        let cache = ChunkBiomeCache::new(&mut *sources[0], 0, 0, BiomeResolution::Noise);
+        assert_eq!(cache.get(5, 5), &DESERT);
+
+    }
+
+}
@@ -0,0 +1,390 @@
+//! Blending of newly generated terrain with chunks generated by older versions. When a world
+//! is upgraded, its existing chunks have been generated with different noises and new chunks
+//! generated next to them would form sheer cliffs at the border. Like the blending data of
+//! 1.18, chunks with old noise are marked when decoded (`Chunk::has_old_noise`) and new chunks
+//! generated around them are reshaped to smoothly interpolate between both terrains.
+//!
+//! The blending is implemented by `BlendingLevelSource`, a load or generate source that keeps
+//! the surface of old chunks returned by its loader and reshapes the columns of generated
+//! chunks near them. Only old chunks loaded before their new neighbours are generated are
+//! taken into account, this is the case when loading chunks around players.
+
+use std::collections::HashMap;
+
+use mc_core::world::source::{LevelSource, LevelSourceError, ChunkLoadRequest, ChunkSaveRequest, ProtoChunk};
+use mc_core::world::chunk::Chunk;
+use mc_core::block::BlockState;
+use mc_core::biome::Biome;
+
+use mc_vanilla::block::AIR;
+use mc_vanilla::block::material::{VANILLA_BLOCK_MATERIALS, TAG_LEAVES, TAG_LOG};
+
+use super::settings::GeneratorSettings;
+
+
+/// The maximum blending radius, only direct neighbours of old chunks are blended.
+pub const MAX_RADIUS: u8 = 16;
+/// Depth below the lowest surface from which columns are reshaped.
+const RESHAPE_DEPTH: i32 = 8;
+
+
+/// Blending settings of a level.
+#[derive(Debug, Clone)]
+pub struct BlendingSettings {
+    /// Distance in blocks from old chunks within which new terrain is blended, blending is
+    /// disabled with a radius of 0.
+    pub radius: u8,
+    /// True to also copy the biomes of old chunks along their border.
+    pub biomes: bool,
+    /// Settings of the generator, used to fill reshaped columns.
+    pub generator_settings: GeneratorSettings
+}
+
+impl BlendingSettings {
+
+    pub fn new() -> Self {
+        Self {
+            radius: 8,
+            biomes: true,
+            generator_settings: GeneratorSettings::new()
+        }
+    }
+
+    /// Set the blending radius, clamped to `MAX_RADIUS`.
+    pub fn with_radius(mut self, radius: u8) -> Self {
+        self.radius = radius.min(MAX_RADIUS);
+        self
+    }
+
+    pub fn with_biomes(mut self, biomes: bool) -> Self {
+        self.biomes = biomes;
+        self
+    }
+
+    pub fn with_generator_settings(mut self, generator_settings: GeneratorSettings) -> Self {
+        self.generator_settings = generator_settings;
+        self
+    }
+
+}
+
+impl Default for BlendingSettings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// Surface of a chunk with old noise, kept to blend its new neighbours.
+struct OldChunkSurface {
+    /// Surface height of each column, indexed by `x + z * 16`.
+    heights: Vec<i32>,
+    /// Surface biome of each column, indexed by `x + z * 16`.
+    biomes: Vec<&'static Biome>
+}
+
+impl OldChunkSurface {
+
+    fn new(chunk: &Chunk) -> Self {
+        let mut heights = Vec::with_capacity(256);
+        let mut biomes = Vec::with_capacity(256);
+        for z in 0..16 {
+            for x in 0..16 {
+                let height = get_surface_height(chunk, x, z);
+                heights.push(height);
+                biomes.push(chunk.get_biome_at(x as i32, height, z as i32).unwrap());
+            }
+        }
+        Self {
+            heights,
+            biomes
+        }
+    }
+
+}
+
+
+/// A load or generate `LevelSource` like `LoadOrGenLevelSource`, that also blends generated
+/// chunks with neighbour chunks with old noise, see the module documentation.
+pub struct BlendingLevelSource<L, G> {
+    loader: L,
+    generator: G,
+    settings: BlendingSettings,
+    /// Surfaces of old chunks returned by the loader, by chunk position.
+    old_surfaces: HashMap<(i32, i32), OldChunkSurface>
+}
+
+impl<L, G> BlendingLevelSource<L, G>
+where
+    L: LevelSource,
+    G: LevelSource,
+{
+
+    /// Construct a new blending source, like `LoadOrGenLevelSource::new` the given sources
+    /// should not return `UnsupportedOperation` for `request_chunk_load`.
+    pub fn new(loader: L, generator: G, settings: BlendingSettings) -> Self {
+        Self {
+            loader,
+            generator,
+            settings,
+            old_surfaces: HashMap::new()
+        }
+    }
+
+    #[inline]
+    pub fn get_settings(&self) -> &BlendingSettings {
+        &self.settings
+    }
+
+    /// Reshape the columns of a generated chunk near old chunks.
+    fn blend_chunk(&self, chunk: &mut Chunk) {
+
+        let radius = self.settings.radius as f32;
+        if radius == 0.0 {
+            return;
+        }
+
+        let (cx, cz) = chunk.get_position();
+        let neighbours: Vec<(i32, i32, &OldChunkSurface)> = (-1..=1)
+            .flat_map(|dcx| (-1..=1).map(move |dcz| (cx + dcx, cz + dcz)))
+            .filter_map(|(ncx, ncz)| self.old_surfaces.get(&(ncx, ncz)).map(|surface| (ncx, ncz, surface)))
+            .collect();
+
+        if neighbours.is_empty() {
+            return;
+        }
+
+        for z in 0..16u8 {
+            for x in 0..16u8 {
+
+                let bx = cx * 16 + x as i32;
+                let bz = cz * 16 + z as i32;
+
+                // Find the nearest column of old chunks.
+                let nearest = neighbours.iter()
+                    .map(|&(ncx, ncz, surface)| {
+                        let ox = bx.clamp(ncx * 16, ncx * 16 + 15);
+                        let oz = bz.clamp(ncz * 16, ncz * 16 + 15);
+                        let dist = (((bx - ox).pow(2) + (bz - oz).pow(2)) as f32).sqrt();
+                        let idx = (ox & 15) as usize + (oz & 15) as usize * 16;
+                        (dist, surface.heights[idx], surface.biomes[idx])
+                    })
+                    .min_by(|a, b| a.0.total_cmp(&b.0));
+
+                let (dist, old_height, old_biome) = match nearest {
+                    Some(nearest) if nearest.0 <= radius => nearest,
+                    _ => continue
+                };
+
+                let new_height = get_surface_height(chunk, x, z);
+                let t = dist / (radius + 1.0);
+                let factor = t * t * (3.0 - 2.0 * t);
+                let target_height = old_height + ((new_height - old_height) as f32 * factor).round() as i32;
+
+                self.reshape_column(chunk, x, z, new_height, target_height);
+
+                if self.settings.biomes && dist <= radius / 2.0 {
+                    let height = chunk.get_height();
+                    for y in (height.get_min_block()..=height.get_max_block()).step_by(4) {
+                        let _ = chunk.set_biome_at(x as i32, y, z as i32, old_biome);
+                    }
+                }
+
+            }
+        }
+
+    }
+
+    /// Move a column of a chunk vertically to bring its surface from a height to another.
+    /// Blocks below the sea level left empty are filled with the default fluid, and the
+    /// default fluid moved above the sea level is removed.
+    fn reshape_column(&self, chunk: &mut Chunk, x: u8, z: u8, from: i32, to: i32) {
+
+        let delta = to - from;
+        if delta == 0 {
+            return;
+        }
+
+        let settings = &self.settings.generator_settings;
+        let height = chunk.get_height();
+        let min_y = height.get_min_block();
+        let max_y = height.get_max_block();
+        let base_y = (from.min(to) - RESHAPE_DEPTH).max(min_y);
+
+        let air = AIR.get_default_state();
+        let column: Vec<&'static BlockState> = (base_y..=max_y)
+            .map(|y| chunk.get_block(x, y, z).unwrap_or(air))
+            .collect();
+
+        for y in (base_y + 1)..=max_y {
+
+            let src_y = y - delta;
+            let mut state = if src_y < base_y {
+                settings.default_block
+            } else if src_y > max_y {
+                air
+            } else {
+                column[(src_y - base_y) as usize]
+            };
+
+            if y < settings.sea_level && y > to && state == air {
+                state = settings.default_fluid;
+            } else if y >= settings.sea_level && state == settings.default_fluid {
+                state = air;
+            }
+
+            if state != column[(y - base_y) as usize] {
+                let _ = chunk.set_block(x, y, z, state);
+            }
+
+        }
+
+    }
+
+}
+
+impl<L, G> LevelSource for BlendingLevelSource<L, G>
+where
+    L: LevelSource,
+    G: LevelSource,
+{
+
+    fn request_chunk_load(&mut self, req: ChunkLoadRequest) -> Result<(), (LevelSourceError, ChunkLoadRequest)> {
+        match self.loader.request_chunk_load(req) {
+            Err((LevelSourceError::UnsupportedChunkPosition, info)) => {
+                self.generator.request_chunk_load(info)
+            }
+            Err(e) => Err(e),
+            _ => Ok(())
+        }
+    }
+
+    fn poll_chunk(&mut self) -> Option<Result<ProtoChunk, (LevelSourceError, ChunkLoadRequest)>> {
+
+        while let Some(res) = self.loader.poll_chunk() {
+            match res {
+                Err((LevelSourceError::UnsupportedChunkPosition, chunk_info)) => {
+                    if let Err(e) = self.generator.request_chunk_load(chunk_info) {
+                        return Some(Err(e));
+                    }
+                },
+                Ok(proto_chunk) => {
+                    if proto_chunk.has_old_noise() {
+                        self.old_surfaces.insert(proto_chunk.get_position(), OldChunkSurface::new(&proto_chunk));
+                    }
+                    return Some(Ok(proto_chunk));
+                }
+                res => return Some(res)
+            }
+        }
+
+        let mut res = self.generator.poll_chunk();
+        if let Some(Ok(ref mut proto_chunk)) = res {
+            self.blend_chunk(proto_chunk);
+            proto_chunk.dirty = true;
+        }
+        res
+
+    }
+
+    fn request_chunk_save(&mut self, req: ChunkSaveRequest) -> Result<(), LevelSourceError> {
+        self.loader.request_chunk_save(req)
+    }
+
+    fn flush(&mut self) -> Result<(), LevelSourceError> {
+        self.loader.flush()
+    }
+
+}
+
+
+/// Return the height of the highest block of a column blocking motion, ignoring trees.
+/// The minimum height of the chunk is returned if the column is empty.
+fn get_surface_height(chunk: &Chunk, x: u8, z: u8) -> i32 {
+    let height = chunk.get_height();
+    let blocks = &chunk.get_env().blocks;
+    (height.get_min_block()..=height.get_max_block()).rev()
+        .find(|&y| {
+            let block = chunk.get_block(x, y, z).unwrap().get_block();
+            VANILLA_BLOCK_MATERIALS.blocks_motion(block)
+                && !blocks.has_block_tag(block, &TAG_LEAVES)
+                && !blocks.has_block_tag(block, &TAG_LOG)
+        })
+        .unwrap_or_else(|| height.get_min_block())
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use mc_core::world::source::{LevelGenerator, MemoryLevelSource};
+    use mc_core::world::level::LevelEnv;
+    use mc_core::world::chunk::ChunkHeight;
+
+    use mc_vanilla::ext::VanillaLevelEnv;
+    use mc_vanilla::block::STONE;
+
+    use super::*;
+
+    /// A generator of flat stone terrain with a surface at the given height.
+    struct StoneGenerator(i32);
+
+    impl LevelGenerator for StoneGenerator {
+        fn generate(&mut self, info: ChunkLoadRequest) -> Result<ProtoChunk, (LevelSourceError, ChunkLoadRequest)> {
+            let mut chunk = info.build_proto_chunk();
+            for x in 0..16 {
+                for z in 0..16 {
+                    for y in 0..=self.0 {
+                        chunk.set_block(x, y, z, STONE.get_default_state()).unwrap();
+                    }
+                }
+            }
+            Ok(chunk)
+        }
+    }
+
+    #[test]
+    fn blend_new_chunks() {
+
+        let env = LevelEnv::vanilla();
+        let height = ChunkHeight::new(0, 7);
+
+        let mut old_chunk = StoneGenerator(40).generate(ChunkLoadRequest { env: Arc::clone(&env), height, cx: 0, cz: 0 }).unwrap();
+        old_chunk.set_old_noise(true);
+
+        let mut loader = MemoryLevelSource::new();
+        loader.insert_chunk(&old_chunk);
+
+        let generator = MemoryLevelSource::with_generator(StoneGenerator(80));
+        let mut source = BlendingLevelSource::new(loader, generator, BlendingSettings::new()
+            .with_generator_settings(GeneratorSettings::new().with_sea_level(0)));
+
+        for cx in 0..3 {
+            source.request_chunk_load(ChunkLoadRequest { env: Arc::clone(&env), height, cx, cz: 0 }).unwrap();
+        }
+
+        let old_chunk = source.poll_chunk().unwrap().unwrap();
+        assert!(old_chunk.has_old_noise());
+        let new_chunk = source.poll_chunk().unwrap().unwrap();
+        assert!(!new_chunk.has_old_noise());
+        let far_chunk = source.poll_chunk().unwrap().unwrap();
+
+        // Heights go up from the old terrain to the new one.
+        let heights: Vec<i32> = (0..16).map(|x| get_surface_height(&new_chunk, x, 8)).collect();
+        assert!(heights[0] > 40 && heights[0] < 50);
+        assert!(heights.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(heights[8], 80);
+        assert_eq!(get_surface_height(&far_chunk, 0, 8), 80);
+        assert_eq!(get_surface_height(&old_chunk, 15, 8), 40);
+
+        // Blending is disabled with a null radius.
+        let mut source = BlendingLevelSource::new(MemoryLevelSource::new(), MemoryLevelSource::with_generator(StoneGenerator(80)),
+            BlendingSettings::new().with_radius(0));
+        source.request_chunk_load(ChunkLoadRequest { env, height, cx: 1, cz: 0 }).unwrap();
+        assert_eq!(get_surface_height(&source.poll_chunk().unwrap().unwrap(), 0, 8), 80);
+
+    }
+
+}
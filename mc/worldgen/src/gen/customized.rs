@@ -0,0 +1,363 @@
+//! Presets of the 1.12 "Customized" world type, stored as JSON in the `generatorOptions`
+//! of `level.dat`. Missing fields use the default values of vanilla.
+
+use serde_json::{Value, Map};
+
+
+/// Configuration of an ore vein distributed uniformly between two heights.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OreConfig {
+    pub size: u32,
+    pub count: u32,
+    pub min_height: i32,
+    pub max_height: i32
+}
+
+impl OreConfig {
+
+    pub fn new(size: u32, count: u32, min_height: i32, max_height: i32) -> Self {
+        Self { size, count, min_height, max_height }
+    }
+
+    fn decode(&mut self, json: &Value, name: &str) {
+        decode_u32(json, &format!("{}Size", name), &mut self.size);
+        decode_u32(json, &format!("{}Count", name), &mut self.count);
+        decode_i32(json, &format!("{}MinHeight", name), &mut self.min_height);
+        decode_i32(json, &format!("{}MaxHeight", name), &mut self.max_height);
+    }
+
+    fn encode(&self, json: &mut Map<String, Value>, name: &str) {
+        json.insert(format!("{}Size", name), self.size.into());
+        json.insert(format!("{}Count", name), self.count.into());
+        json.insert(format!("{}MinHeight", name), self.min_height.into());
+        json.insert(format!("{}MaxHeight", name), self.max_height.into());
+    }
+
+}
+
+
+/// A customized world preset, fields are named like in the JSON preset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomizedPreset {
+    pub coordinate_scale: f32,
+    pub height_scale: f32,
+    pub upper_limit_scale: f32,
+    pub lower_limit_scale: f32,
+    pub depth_noise_scale_x: f32,
+    pub depth_noise_scale_z: f32,
+    pub depth_noise_scale_exponent: f32,
+    pub main_noise_scale_x: f32,
+    pub main_noise_scale_y: f32,
+    pub main_noise_scale_z: f32,
+    pub base_size: f32,
+    pub stretch_y: f32,
+    pub biome_depth_weight: f32,
+    pub biome_depth_offset: f32,
+    pub biome_scale_weight: f32,
+    pub biome_scale_offset: f32,
+    pub sea_level: i32,
+    pub use_caves: bool,
+    pub use_dungeons: bool,
+    pub dungeon_chance: u32,
+    pub use_strongholds: bool,
+    pub use_villages: bool,
+    pub use_mine_shafts: bool,
+    pub use_temples: bool,
+    pub use_monuments: bool,
+    pub use_mansions: bool,
+    pub use_ravines: bool,
+    pub use_water_lakes: bool,
+    pub water_lake_chance: u32,
+    pub use_lava_lakes: bool,
+    pub lava_lake_chance: u32,
+    pub use_lava_oceans: bool,
+    /// The legacy ID of the only biome to generate, or -1 for all biomes.
+    pub fixed_biome: i32,
+    /// Number of zoom layers applied to biomes.
+    pub biome_size: u8,
+    /// Number of zoom layers applied to rivers.
+    pub river_size: u8,
+    pub dirt: OreConfig,
+    pub gravel: OreConfig,
+    pub granite: OreConfig,
+    pub diorite: OreConfig,
+    pub andesite: OreConfig,
+    pub coal: OreConfig,
+    pub iron: OreConfig,
+    pub gold: OreConfig,
+    pub redstone: OreConfig,
+    pub diamond: OreConfig,
+    pub lapis_size: u32,
+    pub lapis_count: u32,
+    pub lapis_center_height: i32,
+    pub lapis_spread: i32
+}
+
+impl Default for CustomizedPreset {
+    fn default() -> Self {
+        Self {
+            coordinate_scale: 684.412,
+            height_scale: 684.412,
+            upper_limit_scale: 512.0,
+            lower_limit_scale: 512.0,
+            depth_noise_scale_x: 200.0,
+            depth_noise_scale_z: 200.0,
+            depth_noise_scale_exponent: 0.5,
+            main_noise_scale_x: 80.0,
+            main_noise_scale_y: 160.0,
+            main_noise_scale_z: 80.0,
+            base_size: 8.5,
+            stretch_y: 12.0,
+            biome_depth_weight: 1.0,
+            biome_depth_offset: 0.0,
+            biome_scale_weight: 1.0,
+            biome_scale_offset: 0.0,
+            sea_level: 63,
+            use_caves: true,
+            use_dungeons: true,
+            dungeon_chance: 8,
+            use_strongholds: true,
+            use_villages: true,
+            use_mine_shafts: true,
+            use_temples: true,
+            use_monuments: true,
+            use_mansions: true,
+            use_ravines: true,
+            use_water_lakes: true,
+            water_lake_chance: 4,
+            use_lava_lakes: true,
+            lava_lake_chance: 80,
+            use_lava_oceans: false,
+            fixed_biome: -1,
+            biome_size: 4,
+            river_size: 4,
+            dirt: OreConfig::new(33, 10, 0, 256),
+            gravel: OreConfig::new(33, 8, 0, 256),
+            granite: OreConfig::new(33, 10, 0, 80),
+            diorite: OreConfig::new(33, 10, 0, 80),
+            andesite: OreConfig::new(33, 10, 0, 80),
+            coal: OreConfig::new(17, 20, 0, 128),
+            iron: OreConfig::new(9, 20, 0, 64),
+            gold: OreConfig::new(9, 2, 0, 32),
+            redstone: OreConfig::new(8, 8, 0, 16),
+            diamond: OreConfig::new(8, 1, 0, 16),
+            lapis_size: 7,
+            lapis_count: 1,
+            lapis_center_height: 16,
+            lapis_spread: 16
+        }
+    }
+}
+
+impl CustomizedPreset {
+
+    /// Decode a preset from its JSON representation, an empty string gives the default
+    /// preset.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+
+        let mut preset = Self::default();
+        if json.trim().is_empty() {
+            return Ok(preset);
+        }
+
+        let json: Value = serde_json::from_str(json)?;
+
+        decode_f32(&json, "coordinateScale", &mut preset.coordinate_scale);
+        decode_f32(&json, "heightScale", &mut preset.height_scale);
+        decode_f32(&json, "upperLimitScale", &mut preset.upper_limit_scale);
+        decode_f32(&json, "lowerLimitScale", &mut preset.lower_limit_scale);
+        decode_f32(&json, "depthNoiseScaleX", &mut preset.depth_noise_scale_x);
+        decode_f32(&json, "depthNoiseScaleZ", &mut preset.depth_noise_scale_z);
+        decode_f32(&json, "depthNoiseScaleExponent", &mut preset.depth_noise_scale_exponent);
+        decode_f32(&json, "mainNoiseScaleX", &mut preset.main_noise_scale_x);
+        decode_f32(&json, "mainNoiseScaleY", &mut preset.main_noise_scale_y);
+        decode_f32(&json, "mainNoiseScaleZ", &mut preset.main_noise_scale_z);
+        decode_f32(&json, "baseSize", &mut preset.base_size);
+        decode_f32(&json, "stretchY", &mut preset.stretch_y);
+        decode_f32(&json, "biomeDepthWeight", &mut preset.biome_depth_weight);
+        decode_f32(&json, "biomeDepthOffset", &mut preset.biome_depth_offset);
+        decode_f32(&json, "biomeScaleWeight", &mut preset.biome_scale_weight);
+        decode_f32(&json, "biomeScaleOffset", &mut preset.biome_scale_offset);
+        decode_i32(&json, "seaLevel", &mut preset.sea_level);
+        decode_bool(&json, "useCaves", &mut preset.use_caves);
+        decode_bool(&json, "useDungeons", &mut preset.use_dungeons);
+        decode_u32(&json, "dungeonChance", &mut preset.dungeon_chance);
+        decode_bool(&json, "useStrongholds", &mut preset.use_strongholds);
+        decode_bool(&json, "useVillages", &mut preset.use_villages);
+        decode_bool(&json, "useMineShafts", &mut preset.use_mine_shafts);
+        decode_bool(&json, "useTemples", &mut preset.use_temples);
+        decode_bool(&json, "useMonuments", &mut preset.use_monuments);
+        decode_bool(&json, "useMansions", &mut preset.use_mansions);
+        decode_bool(&json, "useRavines", &mut preset.use_ravines);
+        decode_bool(&json, "useWaterLakes", &mut preset.use_water_lakes);
+        decode_u32(&json, "waterLakeChance", &mut preset.water_lake_chance);
+        decode_bool(&json, "useLavaLakes", &mut preset.use_lava_lakes);
+        decode_u32(&json, "lavaLakeChance", &mut preset.lava_lake_chance);
+        decode_bool(&json, "useLavaOceans", &mut preset.use_lava_oceans);
+        decode_i32(&json, "fixedBiome", &mut preset.fixed_biome);
+
+        let mut biome_size = preset.biome_size as u32;
+        let mut river_size = preset.river_size as u32;
+        decode_u32(&json, "biomeSize", &mut biome_size);
+        decode_u32(&json, "riverSize", &mut river_size);
+        // Same limits as the customization screen.
+        preset.biome_size = biome_size.clamp(1, 8) as u8;
+        preset.river_size = river_size.clamp(1, 5) as u8;
+
+        preset.dirt.decode(&json, "dirt");
+        preset.gravel.decode(&json, "gravel");
+        preset.granite.decode(&json, "granite");
+        preset.diorite.decode(&json, "diorite");
+        preset.andesite.decode(&json, "andesite");
+        preset.coal.decode(&json, "coal");
+        preset.iron.decode(&json, "iron");
+        preset.gold.decode(&json, "gold");
+        preset.redstone.decode(&json, "redstone");
+        preset.diamond.decode(&json, "diamond");
+        decode_u32(&json, "lapisSize", &mut preset.lapis_size);
+        decode_u32(&json, "lapisCount", &mut preset.lapis_count);
+        decode_i32(&json, "lapisCenterHeight", &mut preset.lapis_center_height);
+        decode_i32(&json, "lapisSpread", &mut preset.lapis_spread);
+
+        Ok(preset)
+
+    }
+
+    /// Encode this preset to its JSON representation, all fields are written like the
+    /// customization screen does.
+    pub fn to_json(&self) -> String {
+
+        let mut json = Map::new();
+
+        encode_f32(&mut json, "coordinateScale", self.coordinate_scale);
+        encode_f32(&mut json, "heightScale", self.height_scale);
+        encode_f32(&mut json, "upperLimitScale", self.upper_limit_scale);
+        encode_f32(&mut json, "lowerLimitScale", self.lower_limit_scale);
+        encode_f32(&mut json, "depthNoiseScaleX", self.depth_noise_scale_x);
+        encode_f32(&mut json, "depthNoiseScaleZ", self.depth_noise_scale_z);
+        encode_f32(&mut json, "depthNoiseScaleExponent", self.depth_noise_scale_exponent);
+        encode_f32(&mut json, "mainNoiseScaleX", self.main_noise_scale_x);
+        encode_f32(&mut json, "mainNoiseScaleY", self.main_noise_scale_y);
+        encode_f32(&mut json, "mainNoiseScaleZ", self.main_noise_scale_z);
+        encode_f32(&mut json, "baseSize", self.base_size);
+        encode_f32(&mut json, "stretchY", self.stretch_y);
+        encode_f32(&mut json, "biomeDepthWeight", self.biome_depth_weight);
+        encode_f32(&mut json, "biomeDepthOffset", self.biome_depth_offset);
+        encode_f32(&mut json, "biomeScaleWeight", self.biome_scale_weight);
+        encode_f32(&mut json, "biomeScaleOffset", self.biome_scale_offset);
+        json.insert("seaLevel".to_string(), self.sea_level.into());
+        json.insert("useCaves".to_string(), self.use_caves.into());
+        json.insert("useDungeons".to_string(), self.use_dungeons.into());
+        json.insert("dungeonChance".to_string(), self.dungeon_chance.into());
+        json.insert("useStrongholds".to_string(), self.use_strongholds.into());
+        json.insert("useVillages".to_string(), self.use_villages.into());
+        json.insert("useMineShafts".to_string(), self.use_mine_shafts.into());
+        json.insert("useTemples".to_string(), self.use_temples.into());
+        json.insert("useMonuments".to_string(), self.use_monuments.into());
+        json.insert("useMansions".to_string(), self.use_mansions.into());
+        json.insert("useRavines".to_string(), self.use_ravines.into());
+        json.insert("useWaterLakes".to_string(), self.use_water_lakes.into());
+        json.insert("waterLakeChance".to_string(), self.water_lake_chance.into());
+        json.insert("useLavaLakes".to_string(), self.use_lava_lakes.into());
+        json.insert("lavaLakeChance".to_string(), self.lava_lake_chance.into());
+        json.insert("useLavaOceans".to_string(), self.use_lava_oceans.into());
+        json.insert("fixedBiome".to_string(), self.fixed_biome.into());
+        json.insert("biomeSize".to_string(), self.biome_size.into());
+        json.insert("riverSize".to_string(), self.river_size.into());
+
+        self.dirt.encode(&mut json, "dirt");
+        self.gravel.encode(&mut json, "gravel");
+        self.granite.encode(&mut json, "granite");
+        self.diorite.encode(&mut json, "diorite");
+        self.andesite.encode(&mut json, "andesite");
+        self.coal.encode(&mut json, "coal");
+        self.iron.encode(&mut json, "iron");
+        self.gold.encode(&mut json, "gold");
+        self.redstone.encode(&mut json, "redstone");
+        self.diamond.encode(&mut json, "diamond");
+        json.insert("lapisSize".to_string(), self.lapis_size.into());
+        json.insert("lapisCount".to_string(), self.lapis_count.into());
+        json.insert("lapisCenterHeight".to_string(), self.lapis_center_height.into());
+        json.insert("lapisSpread".to_string(), self.lapis_spread.into());
+
+        Value::Object(json).to_string()
+
+    }
+
+}
+
+
+fn decode_f32(json: &Value, name: &str, dst: &mut f32) {
+    if let Some(val) = json[name].as_f64() {
+        *dst = val as f32;
+    }
+}
+
+fn decode_i32(json: &Value, name: &str, dst: &mut i32) {
+    if let Some(val) = json[name].as_i64() {
+        *dst = val as i32;
+    }
+}
+
+fn decode_u32(json: &Value, name: &str, dst: &mut u32) {
+    if let Some(val) = json[name].as_u64() {
+        *dst = val as u32;
+    }
+}
+
+fn decode_bool(json: &Value, name: &str, dst: &mut bool) {
+    if let Some(val) = json[name].as_bool() {
+        *dst = val;
+    }
+}
+
+/// Floats are widened through their shortest representation, so that `684.412` isn't
+/// written as `684.4119873046875`.
+fn encode_f32(json: &mut Map<String, Value>, name: &str, val: f32) {
+    let val = val.to_string().parse::<f64>().unwrap_or(val as f64);
+    json.insert(name.to_string(), val.into());
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn customized_preset() {
+
+        assert_eq!(CustomizedPreset::from_json("").unwrap(), CustomizedPreset::default());
+        assert!(CustomizedPreset::from_json("{").is_err());
+
+        let preset = CustomizedPreset::from_json(r#"{
+            "coordinateScale": 1000.0,
+            "seaLevel": 90,
+            "useCaves": false,
+            "dungeonChance": 30,
+            "fixedBiome": 2,
+            "biomeSize": 12,
+            "riverSize": 2,
+            "diamondCount": 5,
+            "diamondMaxHeight": 40
+        }"#).unwrap();
+
+        assert_eq!(preset.coordinate_scale, 1000.0);
+        assert_eq!(preset.height_scale, 684.412);
+        assert_eq!(preset.sea_level, 90);
+        assert!(!preset.use_caves);
+        assert!(preset.use_ravines);
+        assert_eq!(preset.dungeon_chance, 30);
+        assert_eq!(preset.fixed_biome, 2);
+        assert_eq!(preset.biome_size, 8);
+        assert_eq!(preset.river_size, 2);
+        assert_eq!(preset.diamond, OreConfig::new(8, 5, 0, 40));
+
+        let json = preset.to_json();
+        assert!(json.contains(r#""coordinateScale":1000.0"#));
+        assert!(json.contains(r#""heightScale":684.412"#));
+        assert_eq!(CustomizedPreset::from_json(&json).unwrap(), preset);
+
+    }
+
+}
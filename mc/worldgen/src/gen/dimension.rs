@@ -0,0 +1,535 @@
+//! World generation settings of 1.16 and later, stored in the `WorldGenSettings` compound
+//! of `level.dat`. Unlike legacy generator options, each dimension has its own chunk
+//! generator and biome source. Generators and biome sources of unknown types or with
+//! unknown fields are kept as raw compounds, so that they are encoded back untouched.
+
+use nbt::{CompoundTag, Tag};
+
+use mc_core::world::anvil::decode::DecodeError;
+
+use super::options::GeneratorType;
+use super::flat::FlatLayer;
+
+
+pub const OVERWORLD: &str = "minecraft:overworld";
+pub const THE_NETHER: &str = "minecraft:the_nether";
+pub const THE_END: &str = "minecraft:the_end";
+
+
+/// An entry of a registry, either referenced by name or defined inline, like dimension
+/// types and noise settings.
+#[derive(Debug, Clone)]
+pub enum RegistryEntry {
+    Registered(String),
+    Inline(CompoundTag)
+}
+
+impl RegistryEntry {
+
+    pub fn registered(name: impl Into<String>) -> Self {
+        Self::Registered(name.into())
+    }
+
+    /// Return the name of this entry, if registered.
+    pub fn get_name(&self) -> Option<&str> {
+        match self {
+            Self::Registered(name) => Some(name),
+            Self::Inline(_) => None
+        }
+    }
+
+    fn from_nbt(tag: &CompoundTag, name: &str) -> Result<Self, DecodeError> {
+        match tag.get_compound_tag(name) {
+            Ok(tag_inline) => Ok(Self::Inline(tag_inline.clone())),
+            Err(_) => Ok(Self::Registered(tag.get_str(name)?.to_string()))
+        }
+    }
+
+    fn encode_nbt(&self, tag: &mut CompoundTag, name: &str) {
+        match self {
+            Self::Registered(entry_name) => tag.insert_str(name, entry_name),
+            Self::Inline(tag_inline) => tag.insert_compound_tag(name, tag_inline.clone())
+        }
+    }
+
+}
+
+
+/// Biome source of a noise chunk generator.
+#[derive(Debug, Clone)]
+pub enum BiomeSource {
+    /// Biomes of the legacy layer stack, used by the overworld until 1.18.
+    VanillaLayered {
+        seed: i64,
+        large_biomes: bool,
+        legacy_biome_init_layer: bool
+    },
+    /// Multi noise biomes from a preset, like the nether, the seed is no longer stored
+    /// since 1.18.
+    MultiNoise {
+        seed: Option<i64>,
+        preset: String
+    },
+    TheEnd {
+        seed: i64
+    },
+    Fixed {
+        biome: String
+    },
+    Checkerboard {
+        biomes: Vec<String>,
+        scale: i32
+    },
+    Other(CompoundTag)
+}
+
+impl BiomeSource {
+
+    pub fn from_nbt(tag: &CompoundTag) -> Self {
+        Self::decode_typed(tag).unwrap_or_else(|| Self::Other(tag.clone()))
+    }
+
+    fn decode_typed(tag: &CompoundTag) -> Option<Self> {
+        let source = match get_type(tag)? {
+            "vanilla_layered" if has_only_keys(tag, &["type", "seed", "large_biomes", "legacy_biome_init_layer"]) => Self::VanillaLayered {
+                seed: tag.get_i64("seed").ok()?,
+                large_biomes: get_opt_bool(tag, "large_biomes")?,
+                legacy_biome_init_layer: get_opt_bool(tag, "legacy_biome_init_layer")?
+            },
+            "multi_noise" if has_only_keys(tag, &["type", "seed", "preset"]) => Self::MultiNoise {
+                seed: match tag.contains_key("seed") {
+                    true => Some(tag.get_i64("seed").ok()?),
+                    false => None
+                },
+                preset: tag.get_str("preset").ok()?.to_string()
+            },
+            "the_end" if has_only_keys(tag, &["type", "seed"]) => Self::TheEnd {
+                seed: tag.get_i64("seed").ok()?
+            },
+            "fixed" if has_only_keys(tag, &["type", "biome"]) => Self::Fixed {
+                biome: tag.get_str("biome").ok()?.to_string()
+            },
+            "checkerboard" if has_only_keys(tag, &["type", "biomes", "scale"]) => Self::Checkerboard {
+                biomes: tag.get_str_vec("biomes").ok()?.into_iter().map(str::to_string).collect(),
+                scale: match tag.contains_key("scale") {
+                    true => tag.get_i32("scale").ok()?,
+                    false => 2
+                }
+            },
+            _ => return None
+        };
+        Some(source)
+    }
+
+    pub fn encode_nbt(&self) -> CompoundTag {
+        let mut tag = CompoundTag::new();
+        match self {
+            Self::VanillaLayered { seed, large_biomes, legacy_biome_init_layer } => {
+                tag.insert_str("type", "minecraft:vanilla_layered");
+                tag.insert_i64("seed", *seed);
+                tag.insert_bool("large_biomes", *large_biomes);
+                tag.insert_bool("legacy_biome_init_layer", *legacy_biome_init_layer);
+            }
+            Self::MultiNoise { seed, preset } => {
+                tag.insert_str("type", "minecraft:multi_noise");
+                if let Some(seed) = seed {
+                    tag.insert_i64("seed", *seed);
+                }
+                tag.insert_str("preset", preset);
+            }
+            Self::TheEnd { seed } => {
+                tag.insert_str("type", "minecraft:the_end");
+                tag.insert_i64("seed", *seed);
+            }
+            Self::Fixed { biome } => {
+                tag.insert_str("type", "minecraft:fixed");
+                tag.insert_str("biome", biome);
+            }
+            Self::Checkerboard { biomes, scale } => {
+                tag.insert_str("type", "minecraft:checkerboard");
+                tag.insert_str_vec("biomes", biomes);
+                tag.insert_i32("scale", *scale);
+            }
+            Self::Other(tag_other) => return tag_other.clone()
+        }
+        tag
+    }
+
+}
+
+
+/// Settings of the flat chunk generator, structures are kept as a raw compound.
+#[derive(Debug, Clone)]
+pub struct FlatSettings {
+    pub layers: Vec<FlatLayer>,
+    pub biome: String,
+    pub lakes: bool,
+    pub features: bool,
+    pub structures: CompoundTag
+}
+
+impl Default for FlatSettings {
+    fn default() -> Self {
+
+        let mut tag_village = CompoundTag::new();
+        tag_village.insert_i32("spacing", 32);
+        tag_village.insert_i32("separation", 8);
+        tag_village.insert_i32("salt", 10387312);
+
+        let mut tag_configs = CompoundTag::new();
+        tag_configs.insert_compound_tag("minecraft:village", tag_village);
+
+        let mut tag_stronghold = CompoundTag::new();
+        tag_stronghold.insert_i32("distance", 32);
+        tag_stronghold.insert_i32("spread", 3);
+        tag_stronghold.insert_i32("count", 128);
+
+        let mut structures = CompoundTag::new();
+        structures.insert_compound_tag("stronghold", tag_stronghold);
+        structures.insert_compound_tag("structures", tag_configs);
+
+        Self {
+            layers: vec![
+                FlatLayer::new("minecraft:bedrock", 1),
+                FlatLayer::new("minecraft:dirt", 2),
+                FlatLayer::new("minecraft:grass_block", 1)
+            ],
+            biome: "minecraft:plains".to_string(),
+            lakes: false,
+            features: false,
+            structures
+        }
+
+    }
+}
+
+impl FlatSettings {
+
+    fn decode_typed(tag: &CompoundTag) -> Option<Self> {
+        if !has_only_keys(tag, &["layers", "biome", "lakes", "features", "structures"]) {
+            return None;
+        }
+        Some(Self {
+            layers: tag.get_compound_tag_vec("layers").ok()?
+                .into_iter()
+                .map(FlatLayer::from_nbt)
+                .collect::<Result<Vec<_>, _>>().ok()?,
+            biome: tag.get_str("biome").ok()?.to_string(),
+            lakes: get_opt_bool(tag, "lakes")?,
+            features: get_opt_bool(tag, "features")?,
+            structures: tag.get_compound_tag("structures").ok()?.clone()
+        })
+    }
+
+    fn encode_nbt(&self) -> CompoundTag {
+        let mut tag = CompoundTag::new();
+        tag.insert_compound_tag_vec("layers", self.layers.iter().map(FlatLayer::encode_nbt));
+        tag.insert_str("biome", &self.biome);
+        tag.insert_bool("lakes", self.lakes);
+        tag.insert_bool("features", self.features);
+        tag.insert_compound_tag("structures", self.structures.clone());
+        tag
+    }
+
+}
+
+
+/// Chunk generator of a dimension.
+#[derive(Debug, Clone)]
+pub enum ChunkGenerator {
+    Noise {
+        seed: i64,
+        settings: RegistryEntry,
+        biome_source: BiomeSource
+    },
+    Flat(FlatSettings),
+    Debug,
+    Other(CompoundTag)
+}
+
+impl ChunkGenerator {
+
+    pub fn from_nbt(tag: &CompoundTag) -> Self {
+        Self::decode_typed(tag).unwrap_or_else(|| Self::Other(tag.clone()))
+    }
+
+    fn decode_typed(tag: &CompoundTag) -> Option<Self> {
+        let generator = match get_type(tag)? {
+            "noise" if has_only_keys(tag, &["type", "seed", "settings", "biome_source"]) => Self::Noise {
+                seed: tag.get_i64("seed").ok()?,
+                settings: RegistryEntry::from_nbt(tag, "settings").ok()?,
+                biome_source: BiomeSource::from_nbt(tag.get_compound_tag("biome_source").ok()?)
+            },
+            "flat" if has_only_keys(tag, &["type", "settings"]) => {
+                Self::Flat(FlatSettings::decode_typed(tag.get_compound_tag("settings").ok()?)?)
+            }
+            "debug" if has_only_keys(tag, &["type"]) => Self::Debug,
+            _ => return None
+        };
+        Some(generator)
+    }
+
+    pub fn encode_nbt(&self) -> CompoundTag {
+        let mut tag = CompoundTag::new();
+        match self {
+            Self::Noise { seed, settings, biome_source } => {
+                tag.insert_str("type", "minecraft:noise");
+                tag.insert_i64("seed", *seed);
+                settings.encode_nbt(&mut tag, "settings");
+                tag.insert_compound_tag("biome_source", biome_source.encode_nbt());
+            }
+            Self::Flat(settings) => {
+                tag.insert_str("type", "minecraft:flat");
+                tag.insert_compound_tag("settings", settings.encode_nbt());
+            }
+            Self::Debug => tag.insert_str("type", "minecraft:debug"),
+            Self::Other(tag_other) => return tag_other.clone()
+        }
+        tag
+    }
+
+}
+
+
+/// A dimension of the world, with its type and its chunk generator.
+#[derive(Debug, Clone)]
+pub struct Dimension {
+    pub dimension_type: RegistryEntry,
+    pub generator: ChunkGenerator
+}
+
+impl Dimension {
+
+    pub fn new(dimension_type: RegistryEntry, generator: ChunkGenerator) -> Self {
+        Self { dimension_type, generator }
+    }
+
+    pub fn from_nbt(tag: &CompoundTag) -> Result<Self, DecodeError> {
+        Ok(Self {
+            dimension_type: RegistryEntry::from_nbt(tag, "type")?,
+            generator: ChunkGenerator::from_nbt(tag.get_compound_tag("generator")?)
+        })
+    }
+
+    pub fn encode_nbt(&self) -> CompoundTag {
+        let mut tag = CompoundTag::new();
+        self.dimension_type.encode_nbt(&mut tag, "type");
+        tag.insert_compound_tag("generator", self.generator.encode_nbt());
+        tag
+    }
+
+}
+
+
+/// World generation settings, dimensions are kept in their encoding order.
+#[derive(Debug, Clone)]
+pub struct WorldGenSettings {
+    pub seed: i64,
+    pub generate_features: bool,
+    pub bonus_chest: bool,
+    /// The JSON preset of worlds upgraded from the 1.12 customized world type.
+    pub legacy_custom_options: Option<String>,
+    pub dimensions: Vec<(String, Dimension)>
+}
+
+impl WorldGenSettings {
+
+    /// Create the settings of a new vanilla world with the three vanilla dimensions, the
+    /// customized world type can't be created since 1.13 and gives the default settings.
+    pub fn new(seed: i64, generator_type: GeneratorType) -> Self {
+
+        let overworld = match generator_type {
+            GeneratorType::Flat => ChunkGenerator::Flat(FlatSettings::default()),
+            GeneratorType::DebugAllBlockStates => ChunkGenerator::Debug,
+            _ => ChunkGenerator::Noise {
+                seed,
+                settings: RegistryEntry::registered(match generator_type {
+                    GeneratorType::Amplified => "minecraft:amplified",
+                    _ => "minecraft:overworld"
+                }),
+                biome_source: BiomeSource::VanillaLayered {
+                    seed,
+                    large_biomes: generator_type == GeneratorType::LargeBiomes,
+                    legacy_biome_init_layer: false
+                }
+            }
+        };
+
+        let nether = ChunkGenerator::Noise {
+            seed,
+            settings: RegistryEntry::registered("minecraft:nether"),
+            biome_source: BiomeSource::MultiNoise { seed: Some(seed), preset: "minecraft:nether".to_string() }
+        };
+
+        let end = ChunkGenerator::Noise {
+            seed,
+            settings: RegistryEntry::registered("minecraft:end"),
+            biome_source: BiomeSource::TheEnd { seed }
+        };
+
+        Self {
+            seed,
+            generate_features: true,
+            bonus_chest: false,
+            legacy_custom_options: None,
+            dimensions: vec![
+                (OVERWORLD.to_string(), Dimension::new(RegistryEntry::registered(OVERWORLD), overworld)),
+                (THE_NETHER.to_string(), Dimension::new(RegistryEntry::registered(THE_NETHER), nether)),
+                (THE_END.to_string(), Dimension::new(RegistryEntry::registered(THE_END), end))
+            ]
+        }
+
+    }
+
+    pub fn with_generate_features(mut self, generate_features: bool) -> Self {
+        self.generate_features = generate_features;
+        self
+    }
+
+    pub fn with_bonus_chest(mut self, bonus_chest: bool) -> Self {
+        self.bonus_chest = bonus_chest;
+        self
+    }
+
+    pub fn get_dimension(&self, name: &str) -> Option<&Dimension> {
+        self.dimensions.iter().find(|(dim_name, _)| dim_name == name).map(|(_, dim)| dim)
+    }
+
+    pub fn get_dimension_mut(&mut self, name: &str) -> Option<&mut Dimension> {
+        self.dimensions.iter_mut().find(|(dim_name, _)| dim_name == name).map(|(_, dim)| dim)
+    }
+
+    /// Return the legacy world type matching the overworld generator.
+    pub fn get_generator_type(&self) -> GeneratorType {
+
+        if self.legacy_custom_options.is_some() {
+            return GeneratorType::Customized;
+        }
+
+        match self.get_dimension(OVERWORLD).map(|dim| &dim.generator) {
+            Some(ChunkGenerator::Flat(_)) => GeneratorType::Flat,
+            Some(ChunkGenerator::Debug) => GeneratorType::DebugAllBlockStates,
+            Some(ChunkGenerator::Noise { settings, biome_source, .. }) => {
+                if settings.get_name() == Some("minecraft:amplified") {
+                    GeneratorType::Amplified
+                } else if let BiomeSource::VanillaLayered { large_biomes: true, .. } = biome_source {
+                    GeneratorType::LargeBiomes
+                } else {
+                    GeneratorType::Default
+                }
+            }
+            _ => GeneratorType::Default
+        }
+
+    }
+
+    pub fn from_nbt(tag: &CompoundTag) -> Result<Self, DecodeError> {
+
+        let dimensions = tag.get_compound_tag("dimensions")?
+            .iter()
+            .map(|(name, tag_dim)| match tag_dim {
+                Tag::Compound(tag_dim) => Ok((name.clone(), Dimension::from_nbt(tag_dim)?)),
+                _ => Err(DecodeError::Malformed(format!("Invalid dimension '{}'.", name)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            seed: tag.get_i64("seed")?,
+            generate_features: tag.get_bool("generate_features").unwrap_or(true),
+            bonus_chest: tag.get_bool("bonus_chest").unwrap_or(false),
+            legacy_custom_options: tag.get_str("legacy_custom_options").ok().map(str::to_string),
+            dimensions
+        })
+
+    }
+
+    pub fn encode_nbt(&self) -> CompoundTag {
+
+        let mut tag_dimensions = CompoundTag::new();
+        for (name, dim) in &self.dimensions {
+            tag_dimensions.insert_compound_tag(name, dim.encode_nbt());
+        }
+
+        let mut tag = CompoundTag::new();
+        tag.insert_i64("seed", self.seed);
+        tag.insert_bool("generate_features", self.generate_features);
+        tag.insert_bool("bonus_chest", self.bonus_chest);
+        if let Some(options) = &self.legacy_custom_options {
+            tag.insert_str("legacy_custom_options", options);
+        }
+        tag.insert_compound_tag("dimensions", tag_dimensions);
+        tag
+
+    }
+
+}
+
+
+/// Return the type of a generator or biome source, without the default namespace.
+fn get_type(tag: &CompoundTag) -> Option<&str> {
+    let ty = tag.get_str("type").ok()?;
+    Some(ty.strip_prefix("minecraft:").unwrap_or(ty))
+}
+
+fn has_only_keys(tag: &CompoundTag, keys: &[&str]) -> bool {
+    tag.iter().all(|(key, _)| keys.contains(&key.as_str()))
+}
+
+/// Decode an optional boolean, `None` is returned if the tag has the wrong type.
+fn get_opt_bool(tag: &CompoundTag, name: &str) -> Option<bool> {
+    match tag.contains_key(name) {
+        true => tag.get_bool(name).ok(),
+        false => Some(false)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn world_gen_settings() {
+
+        for &ty in &[GeneratorType::Default, GeneratorType::Flat, GeneratorType::LargeBiomes, GeneratorType::Amplified, GeneratorType::DebugAllBlockStates] {
+            let settings = WorldGenSettings::new(-7, ty);
+            let tag = settings.encode_nbt();
+            let decoded = WorldGenSettings::from_nbt(&tag).unwrap();
+            assert_eq!(decoded.get_generator_type(), ty);
+            assert_eq!(decoded.encode_nbt().to_string(), tag.to_string());
+        }
+
+        let mut settings = WorldGenSettings::new(12, GeneratorType::Default).with_bonus_chest(true);
+        settings.legacy_custom_options = Some(r#"{"seaLevel":90}"#.to_string());
+        assert_eq!(settings.get_generator_type(), GeneratorType::Customized);
+
+        // Unknown generators and unknown fields are kept untouched.
+        let mut tag_generator = CompoundTag::new();
+        tag_generator.insert_str("type", "mymod:islands");
+        tag_generator.insert_i32("radius", 16);
+        let mut tag_source = BiomeSource::Fixed { biome: "minecraft:desert".to_string() }.encode_nbt();
+        tag_source.insert_i32("custom", 1);
+
+        settings.dimensions.push(("mymod:islands".to_string(), Dimension::new(RegistryEntry::registered(OVERWORLD), ChunkGenerator::Other(tag_generator))));
+        settings.get_dimension_mut(THE_END).unwrap().generator = ChunkGenerator::Noise {
+            seed: 12,
+            settings: RegistryEntry::Inline(CompoundTag::new()),
+            biome_source: BiomeSource::from_nbt(&tag_source)
+        };
+
+        let tag = settings.encode_nbt();
+        let decoded = WorldGenSettings::from_nbt(&tag).unwrap();
+        assert!(decoded.bonus_chest);
+        assert_eq!(decoded.dimensions.len(), 4);
+        assert!(matches!(decoded.get_dimension("mymod:islands").unwrap().generator, ChunkGenerator::Other(_)));
+        assert!(matches!(decoded.get_dimension(THE_END).unwrap().generator, ChunkGenerator::Noise {
+            settings: RegistryEntry::Inline(_),
+            biome_source: BiomeSource::Other(_),
+            ..
+        }));
+        assert_eq!(decoded.encode_nbt().to_string(), tag.to_string());
+
+    }
+
+}
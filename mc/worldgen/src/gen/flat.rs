@@ -0,0 +1,286 @@
+//! Presets of the "Superflat" world type. Up to 1.12 presets are stored as text in the
+//! `generatorOptions` of `level.dat`, for example `3;minecraft:bedrock,2*minecraft:dirt,
+//! minecraft:grass;1;village`, from 1.13 to 1.15 they are stored as a compound.
+
+use nbt::{CompoundTag, Tag};
+
+use mc_core::world::anvil::decode::DecodeError;
+
+
+/// The latest version of text presets, the version changes how layers are written.
+pub const FLAT_PRESET_VERSION: u8 = 3;
+
+
+/// A layer of blocks, layers are listed from the bottom of the world.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlatLayer {
+    /// The block of the layer, text presets before version 3 use numeric legacy IDs and
+    /// can be followed by the metadata, like `43:2`.
+    pub block: String,
+    pub height: u32
+}
+
+impl FlatLayer {
+
+    pub fn new(block: impl Into<String>, height: u32) -> Self {
+        Self { block: block.into(), height }
+    }
+
+    pub(crate) fn from_nbt(tag: &CompoundTag) -> Result<Self, DecodeError> {
+        Ok(Self {
+            block: tag.get_str("block")?.to_string(),
+            height: tag.get_i32("height")?.max(0) as u32
+        })
+    }
+
+    pub(crate) fn encode_nbt(&self) -> CompoundTag {
+        let mut tag = CompoundTag::new();
+        tag.insert_str("block", &self.block);
+        tag.insert_i32("height", self.height as i32);
+        tag
+    }
+
+}
+
+
+/// A structure enabled by a preset, with its textual options like `distance=32`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlatStructure {
+    pub name: String,
+    pub options: Vec<(String, String)>
+}
+
+impl FlatStructure {
+
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into(), options: Vec::new() }
+    }
+
+    pub fn with_option(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.options.push((key.into(), value.into()));
+        self
+    }
+
+}
+
+
+/// A superflat preset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlatPreset {
+    /// Version of the text preset, `0` for the unversioned presets of 1.4.
+    pub version: u8,
+    pub layers: Vec<FlatLayer>,
+    /// The biome of the whole world, a legacy numeric ID in text presets and a biome name
+    /// in compound presets.
+    pub biome: String,
+    pub structures: Vec<FlatStructure>
+}
+
+impl Default for FlatPreset {
+    fn default() -> Self {
+        Self {
+            version: FLAT_PRESET_VERSION,
+            layers: vec![
+                FlatLayer::new("minecraft:bedrock", 1),
+                FlatLayer::new("minecraft:dirt", 2),
+                FlatLayer::new("minecraft:grass", 1)
+            ],
+            biome: "1".to_string(),
+            structures: vec![FlatStructure::new("village")]
+        }
+    }
+}
+
+impl FlatPreset {
+
+    /// Decode a text preset, an empty string gives the default preset and `None` is
+    /// returned for invalid presets. Like vanilla, the biome defaults to plains and
+    /// villages are enabled if the preset doesn't specify them.
+    pub fn from_text(text: &str) -> Option<Self> {
+
+        if text.trim().is_empty() {
+            return Some(Self::default());
+        }
+
+        let mut parts = text.split(';');
+        let version = if text.contains(';') {
+            parts.next()?.trim().parse::<u8>().ok().filter(|&v| v <= FLAT_PRESET_VERSION)?
+        } else {
+            0
+        };
+
+        let count_sep = if version >= 3 { '*' } else { 'x' };
+        let layers = parts.next()?.split(',')
+            .map(|layer| {
+                let layer = layer.trim();
+                match layer.split_once(count_sep) {
+                    Some((height, block)) => {
+                        let height = height.parse::<u32>().ok().filter(|&h| h > 0)?;
+                        Some(FlatLayer::new(block, height))
+                    }
+                    None => Some(FlatLayer::new(layer, 1))
+                }
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        if layers.iter().any(|layer| layer.block.is_empty()) {
+            return None;
+        }
+
+        let biome = parts.next().map_or("1", str::trim).to_string();
+        let structures = match parts.next() {
+            Some(structures) => structures.split(',')
+                .map(str::trim)
+                .filter(|structure| !structure.is_empty())
+                .map(|structure| match structure.split_once('(') {
+                    Some((name, options)) => FlatStructure {
+                        name: name.to_string(),
+                        options: options.trim_end_matches(')')
+                            .split(' ')
+                            .filter_map(|option| option.split_once('='))
+                            .map(|(key, value)| (key.to_string(), value.to_string()))
+                            .collect()
+                    },
+                    None => FlatStructure::new(structure)
+                })
+                .collect(),
+            None => vec![FlatStructure::new("village")]
+        };
+
+        Some(Self { version, layers, biome, structures })
+
+    }
+
+    /// Encode this preset as text, in the format of its version.
+    pub fn to_text(&self) -> String {
+
+        let count_sep = if self.version >= 3 { "*" } else { "x" };
+        let layers = self.layers.iter()
+            .map(|layer| match layer.height {
+                1 => layer.block.clone(),
+                height => format!("{}{}{}", height, count_sep, layer.block)
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let structures = self.structures.iter()
+            .map(|structure| match structure.options.len() {
+                0 => structure.name.clone(),
+                _ => {
+                    let options = structure.options.iter()
+                        .map(|(key, value)| format!("{}={}", key, value))
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    format!("{}({})", structure.name, options)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+
+        match self.version {
+            0 => format!("{};{};{}", layers, self.biome, structures),
+            version => format!("{};{};{};{}", version, layers, self.biome, structures)
+        }
+
+    }
+
+    /// Decode a compound preset of releases 1.13 to 1.15.
+    pub fn from_nbt(tag: &CompoundTag) -> Result<Self, DecodeError> {
+
+        let layers = tag.get_compound_tag_vec("layers")?
+            .into_iter()
+            .map(FlatLayer::from_nbt)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let structures = match tag.get_compound_tag("structures") {
+            Ok(tag_structures) => tag_structures.iter()
+                .map(|(name, tag_options)| match tag_options {
+                    Tag::Compound(tag_options) => Ok(FlatStructure {
+                        name: name.clone(),
+                        options: tag_options.iter()
+                            .map(|(key, value)| match value {
+                                Tag::String(value) => (key.clone(), value.clone()),
+                                value => (key.clone(), value.to_string())
+                            })
+                            .collect()
+                    }),
+                    _ => Err(DecodeError::Malformed(format!("Invalid flat structure '{}'.", name)))
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            Err(_) => Vec::new()
+        };
+
+        Ok(Self {
+            version: FLAT_PRESET_VERSION,
+            layers,
+            biome: tag.get_str("biome").unwrap_or("minecraft:plains").to_string(),
+            structures
+        })
+
+    }
+
+    /// Encode this preset as a compound of releases 1.13 to 1.15.
+    pub fn encode_nbt(&self) -> CompoundTag {
+
+        let mut tag_structures = CompoundTag::new();
+        for structure in &self.structures {
+            let mut tag_options = CompoundTag::new();
+            for (key, value) in &structure.options {
+                tag_options.insert_str(key, value);
+            }
+            tag_structures.insert_compound_tag(&structure.name, tag_options);
+        }
+
+        let mut tag = CompoundTag::new();
+        tag.insert_compound_tag_vec("layers", self.layers.iter().map(FlatLayer::encode_nbt));
+        tag.insert_str("biome", &self.biome);
+        tag.insert_compound_tag("structures", tag_structures);
+        tag
+
+    }
+
+    /// Return the total height of the layers.
+    pub fn get_height(&self) -> u32 {
+        self.layers.iter().map(|layer| layer.height).sum()
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn flat_preset() {
+
+        assert_eq!(FlatPreset::from_text("").unwrap(), FlatPreset::default());
+        assert_eq!(FlatPreset::default().to_text(), "3;minecraft:bedrock,2*minecraft:dirt,minecraft:grass;1;village");
+        assert_eq!(FlatPreset::default().get_height(), 4);
+
+        let text = "3;minecraft:bedrock,59*minecraft:stone,minecraft:sandstone:2;2;village(size=1 distance=32),mineshaft,biome_1";
+        let preset = FlatPreset::from_text(text).unwrap();
+        assert_eq!(preset.layers[1], FlatLayer::new("minecraft:stone", 59));
+        assert_eq!(preset.layers[2], FlatLayer::new("minecraft:sandstone:2", 1));
+        assert_eq!(preset.biome, "2");
+        assert_eq!(preset.structures[0], FlatStructure::new("village").with_option("size", "1").with_option("distance", "32"));
+        assert_eq!(preset.to_text(), text);
+
+        let preset = FlatPreset::from_text("2;7,2x3,2;1").unwrap();
+        assert_eq!(preset.layers[1], FlatLayer::new("3", 2));
+        assert_eq!(preset.to_text(), "2;7,2x3,2;1;village");
+        assert_eq!(FlatPreset::from_text("7,3x1").unwrap().to_text(), "7,3x1;1;village");
+        assert!(FlatPreset::from_text("3;").is_none());
+
+        assert!(FlatPreset::from_text("4;minecraft:bedrock").is_none());
+        assert!(FlatPreset::from_text("3;0*minecraft:bedrock").is_none());
+        assert!(FlatPreset::from_text("3;2*").is_none());
+
+        let mut preset = FlatPreset { biome: "minecraft:desert".to_string(), ..FlatPreset::default() };
+        preset.structures.push(FlatStructure::new("stronghold").with_option("count", "3"));
+        assert_eq!(FlatPreset::from_nbt(&preset.encode_nbt()).unwrap(), preset);
+
+    }
+
+}
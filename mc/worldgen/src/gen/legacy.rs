@@ -16,11 +16,151 @@ use mc_core::perf;
 
 use crate::view::{LevelView, ProtoChunkView};
 
+use super::settings::{GeneratorSettings, DEFAULT_SETTINGS};
 
-/// Trait for terrain generators.
-pub trait TerrainGenerator {
+
+/// Trait for biome sources, a biome source is queried by noise generators for the biomes of
+/// the chunks they generate and for the biomes used to shape the terrain.
+///
+/// This trait is object safe, generators and utilities are generic over their biome source
+/// (with `?Sized`), so concrete sources are statically dispatched while `dyn BiomeSource`
+/// or `Box<dyn BiomeSource>` can be used when the source is only known at runtime. See the
+/// `biome_source` benchmark for the cost of dynamic dispatch.
+pub trait BiomeSource {
+    /// Return a rectangle of biomes at block resolution, starting at the given block position.
+    fn get_biomes(&mut self, x: i32, z: i32, x_size: usize, z_size: usize) -> Rect<&'static Biome>;
+    /// Return a rectangle of biomes at noise resolution (one biome every 4 blocks), starting
+    /// at the given noise position.
+    fn get_noise_biomes(&mut self, x: i32, z: i32, x_size: usize, z_size: usize) -> Rect<&'static Biome>;
+}
+
+impl<B: BiomeSource + ?Sized> BiomeSource for &mut B {
+
+    #[inline]
+    fn get_biomes(&mut self, x: i32, z: i32, x_size: usize, z_size: usize) -> Rect<&'static Biome> {
+        (**self).get_biomes(x, z, x_size, z_size)
+    }
+
+    #[inline]
+    fn get_noise_biomes(&mut self, x: i32, z: i32, x_size: usize, z_size: usize) -> Rect<&'static Biome> {
+        (**self).get_noise_biomes(x, z, x_size, z_size)
+    }
+
+}
+
+impl<B: BiomeSource + ?Sized> BiomeSource for Box<B> {
+
+    #[inline]
+    fn get_biomes(&mut self, x: i32, z: i32, x_size: usize, z_size: usize) -> Rect<&'static Biome> {
+        (**self).get_biomes(x, z, x_size, z_size)
+    }
+
+    #[inline]
+    fn get_noise_biomes(&mut self, x: i32, z: i32, x_size: usize, z_size: usize) -> Rect<&'static Biome> {
+        (**self).get_noise_biomes(x, z, x_size, z_size)
+    }
+
+}
+
+
+/// Resolution of the biomes stored in a `ChunkBiomeCache`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BiomeResolution {
+    /// One biome per block column, 16x16 biomes per chunk.
+    Block,
+    /// One biome every 4 blocks (noise resolution), 4x4 biomes per chunk.
+    Noise
+}
+
+impl BiomeResolution {
+
+    /// Return the number of bits to shift block coordinates to get cache coordinates.
+    #[inline]
+    fn get_shift(self) -> u32 {
+        match self {
+            Self::Block => 0,
+            Self::Noise => 2
+        }
+    }
+
+    /// Return the number of biomes along each axis of a chunk.
+    #[inline]
+    pub fn get_chunk_size(self) -> usize {
+        16 >> self.get_shift()
+    }
+
+}
+
+/// A grid of biomes for a single chunk, computed once from a biome source. This cache is
+/// kept alongside the proto chunk until its decoration, so features can query biomes at
+/// block resolution without running the biome source again for each block.
+pub struct ChunkBiomeCache {
+    cx: i32,
+    cz: i32,
+    resolution: BiomeResolution,
+    biomes: Rect<&'static Biome>
+}
+
+impl ChunkBiomeCache {
+
+    /// Compute the biomes of the given chunk from the biome source, at the given resolution.
+    pub fn new<B: BiomeSource + ?Sized>(biome_source: &mut B, cx: i32, cz: i32, resolution: BiomeResolution) -> Self {
+        let size = resolution.get_chunk_size();
+        let biomes = match resolution {
+            BiomeResolution::Block => biome_source.get_biomes(cx * 16, cz * 16, size, size),
+            BiomeResolution::Noise => biome_source.get_noise_biomes(cx * 4, cz * 4, size, size)
+        };
+        assert!(biomes.x_size == size && biomes.z_size == size, "The biome source returned invalid biomes.");
+        Self {
+            cx,
+            cz,
+            resolution,
+            biomes
+        }
+    }
+
+    #[inline]
+    pub fn get_position(&self) -> (i32, i32) {
+        (self.cx, self.cz)
+    }
+
+    #[inline]
+    pub fn get_resolution(&self) -> BiomeResolution {
+        self.resolution
+    }
+
+    /// Get the underlying rectangle of biomes, its size depends on the resolution.
+    #[inline]
+    pub fn as_rect(&self) -> &Rect<&'static Biome> {
+        &self.biomes
+    }
+
+    /// Get the biome at the given block coordinates relative to the chunk, coordinates must
+    /// be in range 0..16.
+    #[inline]
+    pub fn get(&self, x: u8, z: u8) -> &'static Biome {
+        let shift = self.resolution.get_shift();
+        self.biomes.get((x >> shift) as usize, (z >> shift) as usize)
+    }
+
+    /// Get the biome at the given absolute block coordinates, returning `None` if these
+    /// coordinates are not in the cached chunk.
+    pub fn get_at(&self, x: i32, z: i32) -> Option<&'static Biome> {
+        if x >> 4 == self.cx && z >> 4 == self.cz {
+            Some(self.get((x & 15) as u8, (z & 15) as u8))
+        } else {
+            None
+        }
+    }
+
+}
+
+
+/// Trait for noise generators, a noise generator generates the biomes and the terrain of
+/// chunks using a biome source, this includes surface and carvers.
+pub trait NoiseGenerator {
     type Chunk: ProtoChunkView;
-    fn generate(&mut self, chunk: ProtoChunk) -> Self::Chunk;
+    fn generate<B: BiomeSource + ?Sized>(&mut self, chunk: ProtoChunk, biome_source: &mut B) -> Self::Chunk;
 }
 
 /// Trait for feature generators.
@@ -29,16 +169,85 @@ pub trait FeatureGenerator {
     fn decorate(&mut self, level: QuadLevelView<Self::Chunk>, cx: i32, cz: i32, x: i32, z: i32);
 }
 
-/// Base trait for a temporary provider of terrain and feature generators. Structures
-/// implementing this trait should also be `Sync` and `Send` because they will be shared
-/// between threads in order to call its methods inside the thread. This allows terrain
-/// and feature generators not to be Send and/or Sync, which is not required because these
-/// generators will not be moved outside the thread.
-pub trait GeneratorProvider {
-    type Terrain: TerrainGenerator;
-    type Feature: FeatureGenerator;
-    fn build_terrain(&self) -> Self::Terrain;
+/// Base trait for chunk generators, a chunk generator is a temporary provider of a biome
+/// source, a noise generator and a feature generator. Structures implementing this trait
+/// should also be `Sync` and `Send` because they will be shared between threads in order
+/// to call its methods inside the thread. This allows the built components not to be Send
+/// and/or Sync, which is not required because they will not be moved outside the thread.
+pub trait ChunkGenerator {
+    type BiomeSource: BiomeSource;
+    type Noise: NoiseGenerator;
+    type Feature: FeatureGenerator<Chunk = <Self::Noise as NoiseGenerator>::Chunk>;
+    fn build_biome_source(&self) -> Self::BiomeSource;
+    fn build_noise(&self) -> Self::Noise;
     fn build_feature(&self) -> Self::Feature;
+    /// Return the settings of this generator, given to features through their level view.
+    fn get_settings(&self) -> &GeneratorSettings {
+        &DEFAULT_SETTINGS
+    }
+}
+
+
+/// A chunk generator built from the components of different generators, each component is
+/// built by a function. This can be used for example to decorate the terrain of a vanilla
+/// generator with custom features.
+pub struct MixedGenerator<B, N, F> {
+    biome_source: B,
+    noise: N,
+    feature: F,
+    settings: GeneratorSettings
+}
+
+impl<B, N, F> MixedGenerator<B, N, F> {
+
+    pub fn new(biome_source: B, noise: N, feature: F) -> Self {
+        Self {
+            biome_source,
+            noise,
+            feature,
+            settings: GeneratorSettings::new()
+        }
+    }
+
+    /// Set the settings given to features, note that these settings are not given to the
+    /// noise generator, which has its own settings.
+    pub fn with_settings(mut self, settings: GeneratorSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+}
+
+impl<B, N, F, BS, NG, FG> ChunkGenerator for MixedGenerator<B, N, F>
+where
+    B: Fn() -> BS,
+    N: Fn() -> NG,
+    F: Fn() -> FG,
+    BS: BiomeSource,
+    NG: NoiseGenerator,
+    FG: FeatureGenerator<Chunk = NG::Chunk>,
+{
+
+    type BiomeSource = BS;
+    type Noise = NG;
+    type Feature = FG;
+
+    fn build_biome_source(&self) -> Self::BiomeSource {
+        (self.biome_source)()
+    }
+
+    fn build_noise(&self) -> Self::Noise {
+        (self.noise)()
+    }
+
+    fn build_feature(&self) -> Self::Feature {
+        (self.feature)()
+    }
+
+    fn get_settings(&self) -> &GeneratorSettings {
+        &self.settings
+    }
+
 }
 
 
@@ -74,12 +283,11 @@ impl LegacyGenLevelSource {
     /// Construct a new legacy generator with the given number of terrain workers (threads).
     /// For now there is only a single worker for features generation, this might change in
     /// the future.
-    pub fn new<P, C>(provider: P, terrain_workers: u16) -> Self
+    pub fn new<G, C>(generator: G, terrain_workers: u16) -> Self
     where
-        P: GeneratorProvider + Send + Sync + 'static,
+        G: ChunkGenerator + Send + Sync + 'static,
         C: ProtoChunkView + Send + 'static,
-        P::Terrain: TerrainGenerator<Chunk = C>,
-        P::Feature: FeatureGenerator<Chunk = C>,
+        G::Noise: NoiseGenerator<Chunk = C>,
     {
 
         let (
@@ -97,19 +305,20 @@ impl LegacyGenLevelSource {
             chunk_receiver
         ) = bounded(256);
 
-        let provider = Arc::new(provider);
+        let generator = Arc::new(generator);
 
         for i in 0..terrain_workers {
             let request_receiver = request_receiver.clone();
             let terrain_sender = terrain_sender.clone();
-            let provider = Arc::clone(&provider);
+            let generator = Arc::clone(&generator);
             std::thread::Builder::new()
                 .name(format!("Legacy Generator Terrain Worker #{}", i))
                 .spawn(move || {
                     TerrainWorker {
                         request_receiver,
                         terrain_sender,
-                        generator: provider.build_terrain()
+                        biome_source: generator.build_biome_source(),
+                        noise: generator.build_noise()
                     }.run()
                 }).unwrap();
         }
@@ -122,7 +331,8 @@ impl LegacyGenLevelSource {
                     chunks_counters: HashMap::new(),
                     terrain_receiver,
                     chunk_sender,
-                    generator: provider.build_feature(),
+                    generator: generator.build_feature(),
+                    settings: generator.get_settings().clone()
                 }.run()
             }).unwrap();
 
@@ -169,13 +379,14 @@ impl LevelSource for LegacyGenLevelSource {
 
 /// Internal thread worker for terrain generation, this is the first step in the generation
 /// process. Another thread is responsible of the features generation.
-struct TerrainWorker<G: TerrainGenerator> {
+struct TerrainWorker<B: BiomeSource, N: NoiseGenerator> {
     request_receiver: Receiver<ChunkLoadRequest>,
-    terrain_sender: Sender<G::Chunk>,
-    generator: G,
+    terrain_sender: Sender<N::Chunk>,
+    biome_source: B,
+    noise: N,
 }
 
-impl<G: TerrainGenerator> TerrainWorker<G> {
+impl<B: BiomeSource, N: NoiseGenerator> TerrainWorker<B, N> {
 
     fn run(mut self) {
         loop {
@@ -183,7 +394,7 @@ impl<G: TerrainGenerator> TerrainWorker<G> {
                 Err(_) => break,
                 Ok(req) => {
                     perf::push("gen_terrain");
-                    let proto_chunk = self.generator.generate(req.build_proto_chunk());
+                    let proto_chunk = self.noise.generate(req.build_proto_chunk(), &mut self.biome_source);
                     self.terrain_sender.send(proto_chunk).unwrap();
                     perf::pop();
                     // perf::debug();
@@ -201,7 +412,8 @@ struct FeatureWorker<G: FeatureGenerator> {
     chunks_counters: HashMap<(i32, i32), u8>,
     terrain_receiver: Receiver<G::Chunk>,
     chunk_sender: Sender<ProtoChunk>,
-    generator: G
+    generator: G,
+    settings: GeneratorSettings
 }
 
 impl<G: FeatureGenerator> FeatureWorker<G> {
@@ -278,7 +490,8 @@ impl<G: FeatureGenerator> FeatureWorker<G> {
                                                 c01.borrow_mut(), c11.borrow_mut()
                                             ],
                                             ocx,
-                                            ocz
+                                            ocz,
+                                            settings: &self.settings
                                         };
 
                                         self.generator.decorate(view, ocx, ocz, block_x, block_z);
@@ -324,7 +537,8 @@ pub struct QuadLevelView<'a, C: ProtoChunkView> {
     /// Ordering is 0/0 1/0 0/1 1/1 (X then Z)
     chunks: [RefMut<'a, C>; 4],
     ocx: i32,
-    ocz: i32
+    ocz: i32,
+    settings: &'a GeneratorSettings
 }
 
 impl<'a, C: ProtoChunkView> QuadLevelView<'a, C> {
@@ -353,6 +567,11 @@ impl<'a, C: ProtoChunkView> LevelView for QuadLevelView<'a, C> {
         self.chunks[0].as_chunk_ref().get_env()
     }
 
+    #[inline]
+    fn get_settings(&self) -> &GeneratorSettings {
+        self.settings
+    }
+
     fn get_chunk(&self, cx: i32, cz: i32) -> Option<&Chunk> {
         let idx = self.get_chunk_index(cx, cz).ok()?;
         self.chunks.get(idx).map(|proto| proto.as_chunk_ref())
@@ -379,23 +598,28 @@ impl<'a, C: ProtoChunkView> LevelView for QuadLevelView<'a, C> {
         self.chunks[self.get_chunk_at_index(x, z)?].get_heightmap_column_at(heightmap_type, x, z)
     }
 
+    fn get_biome_cache(&self, cx: i32, cz: i32) -> Option<&ChunkBiomeCache> {
+        let idx = self.get_chunk_index(cx, cz).ok()?;
+        self.chunks[idx].get_biome_cache()
+    }
+
 }
 
 
 /// This is an implementation of the trait `ProtoChunkView`, this implementation does not
 /// use the chunk's biome methods but uses the `legacy_biomes` field of this structure which
-/// is actually a rectangle of biomes of 16x16 (minimum required size).
+/// is a cache of the biomes computed by the biome source for this chunk.
 pub struct LegacyProtoChunk {
     /// The real (modern) proto chunk.
     pub inner: ProtoChunk,
-    /// The legacy 16x16 biomes rectangle.
-    pub legacy_biomes: Rect<&'static Biome>
+    /// The legacy biomes cache of this chunk.
+    pub legacy_biomes: ChunkBiomeCache
 }
 
 impl LegacyProtoChunk {
 
     pub fn get_legacy_biome(&self, x: u8, z: u8) -> &'static Biome {
-        *self.legacy_biomes.get(x as usize, z as usize)
+        self.legacy_biomes.get(x, z)
     }
 
 }
@@ -430,7 +654,12 @@ impl ProtoChunkView for LegacyProtoChunk {
     }
 
     fn get_biome_at(&self, x: i32, _y: i32, z: i32) -> ChunkResult<&'static Biome> {
-        Ok(*self.legacy_biomes.get((x & 15) as usize, (z & 15) as usize))
+        Ok(self.legacy_biomes.get((x & 15) as u8, (z & 15) as u8))
+    }
+
+    #[inline]
+    fn get_biome_cache(&self) -> Option<&ChunkBiomeCache> {
+        Some(&self.legacy_biomes)
     }
 
     fn get_heightmap_column_at(&self, heightmap_type: &'static HeightmapType, x: i32, z: i32) -> ChunkResult<i32> {
@@ -438,3 +667,65 @@ impl ProtoChunkView for LegacyProtoChunk {
     }
 
 }
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_vanilla::biome::{PLAINS, DESERT};
+
+    use super::*;
+
+    /// A biome source with desert for positive X coordinates and plains elsewhere, this
+    /// source panics if queried more than once.
+    struct HalfBiomeSource(bool);
+
+    impl HalfBiomeSource {
+        fn gen(&mut self, x: i32, z: i32, x_size: usize, z_size: usize) -> Rect<&'static Biome> {
+            assert!(!self.0, "The biome source has already been queried.");
+            self.0 = true;
+            let mut rect = Rect::new(x_size, z_size, &PLAINS);
+            for dx in 0..x_size {
+                for dz in 0..z_size {
+                    if x + dx as i32 >= 0 && z + dz as i32 >= 0 {
+                        rect.set(dx, dz, &DESERT);
+                    }
+                }
+            }
+            rect
+        }
+    }
+
+    impl BiomeSource for HalfBiomeSource {
+
+        fn get_biomes(&mut self, x: i32, z: i32, x_size: usize, z_size: usize) -> Rect<&'static Biome> {
+            self.gen(x, z, x_size, z_size)
+        }
+
+        fn get_noise_biomes(&mut self, x: i32, z: i32, x_size: usize, z_size: usize) -> Rect<&'static Biome> {
+            self.gen(x, z, x_size, z_size)
+        }
+
+    }
+
+    #[test]
+    fn chunk_biome_cache() {
+
+        let cache = ChunkBiomeCache::new(&mut HalfBiomeSource(false), 0, 0, BiomeResolution::Block);
+        assert_eq!(cache.as_rect().x_size, 16);
+        assert_eq!(cache.get(15, 15), &DESERT);
+        assert_eq!(cache.get_at(3, 7), Some(&DESERT));
+        assert_eq!(cache.get_at(-1, 7), None);
+        assert_eq!(cache.get_at(16, 7), None);
+
+        let cache = ChunkBiomeCache::new(&mut HalfBiomeSource(false), -1, 0, BiomeResolution::Noise);
+        assert_eq!(cache.get_resolution(), BiomeResolution::Noise);
+        assert_eq!(cache.as_rect().x_size, 4);
+        assert_eq!(cache.get(0, 0), &PLAINS);
+        assert_eq!(cache.get(15, 15), &PLAINS);
+        assert_eq!(cache.get_at(-16, 0), Some(&PLAINS));
+        assert_eq!(cache.get_at(0, 0), None);
+
+    }
+
+}
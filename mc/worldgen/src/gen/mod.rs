@@ -6,6 +6,15 @@
 
 pub mod legacy;
 pub mod biome;
+pub mod biome_source;
+pub mod options;
+pub mod customized;
+pub mod flat;
+pub mod dimension;
+pub mod settings;
+pub mod noise;
+pub mod analysis;
+pub mod blend;
 
 // pub mod r101;
 #[cfg(feature = "release-1-2")]
@@ -0,0 +1,215 @@
+//! Noise settings of the vanilla noise chunk generators of 1.16, the `noise_settings` of data
+//! packs. These settings define how the terrain density is sampled and faded at the top and
+//! the bottom of the world, and the blocks filling the terrain. Structure settings are not
+//! included.
+
+use mc_core::block::Block;
+
+use mc_vanilla::block::{STONE, WATER, NETHERRACK, LAVA, END_STONE, AIR};
+
+use super::settings::GeneratorSettings;
+
+
+/// Scales of the noises sampled for the terrain density.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct NoiseSampling {
+    pub xz_scale: f64,
+    pub y_scale: f64,
+    pub xz_factor: f64,
+    pub y_factor: f64
+}
+
+
+/// A fade of the terrain density toward a target, at the top or at the bottom of the world.
+/// The size and the offset are in cells of `size_vertical` quarters of blocks.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct NoiseSlide {
+    pub target: i32,
+    pub size: i32,
+    pub offset: i32
+}
+
+
+/// Settings of the terrain density noise.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct NoiseSettings {
+    /// Height of the generated terrain, in blocks.
+    pub height: i32,
+    pub sampling: NoiseSampling,
+    pub top_slide: NoiseSlide,
+    pub bottom_slide: NoiseSlide,
+    /// Horizontal size of noise cells, in quarters of blocks.
+    pub size_horizontal: i32,
+    /// Vertical size of noise cells, in quarters of blocks.
+    pub size_vertical: i32,
+    pub density_factor: f64,
+    pub density_offset: f64,
+    pub simplex_surface_noise: bool,
+    pub random_density_offset: bool,
+    /// Use the island noise of the end instead of the biomes depth and scale.
+    pub island_noise_override: bool,
+    pub amplified: bool
+}
+
+
+/// Noise settings of a noise chunk generator.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct NoiseGeneratorSettings {
+    pub noise: NoiseSettings,
+    /// The block filling the terrain, in its default state.
+    pub default_block: &'static Block,
+    /// The fluid filling the terrain below the sea level, in its default state.
+    pub default_fluid: &'static Block,
+    /// Distance of the bedrock roof from the top of the world, disabled if negative or out
+    /// of the world.
+    pub bedrock_roof_position: i32,
+    /// Height of the bedrock floor, disabled if negative or out of the world.
+    pub bedrock_floor_position: i32,
+    pub sea_level: i32,
+    pub disable_mob_generation: bool
+}
+
+impl NoiseGeneratorSettings {
+
+    /// Return the vanilla noise settings registered with the given name, like
+    /// `minecraft:overworld`.
+    pub fn from_registry_name(name: &str) -> Option<&'static Self> {
+        VANILLA_NOISE_SETTINGS.iter()
+            .find(|&&(settings_name, _)| settings_name == name)
+            .map(|&(_, settings)| settings)
+    }
+
+    /// Return the common generator settings used by the terrain, carvers and features.
+    pub fn get_generator_settings(&self) -> GeneratorSettings {
+        GeneratorSettings::new()
+            .with_sea_level(self.sea_level)
+            .with_default_block(self.default_block.get_default_state())
+            .with_default_fluid(self.default_fluid.get_default_state())
+    }
+
+}
+
+
+/// Vanilla noise settings with their registry names.
+pub static VANILLA_NOISE_SETTINGS: [(&str, &NoiseGeneratorSettings); 6] = [
+    ("minecraft:overworld", &OVERWORLD_NOISE_SETTINGS),
+    ("minecraft:amplified", &AMPLIFIED_NOISE_SETTINGS),
+    ("minecraft:nether", &NETHER_NOISE_SETTINGS),
+    ("minecraft:end", &END_NOISE_SETTINGS),
+    ("minecraft:caves", &CAVES_NOISE_SETTINGS),
+    ("minecraft:floating_islands", &FLOATING_ISLANDS_NOISE_SETTINGS),
+];
+
+
+/// Scale of overworld noises, slightly lower than 1 in vanilla.
+const OVERWORLD_SCALE: f64 = 0.9999999814507745;
+
+pub static OVERWORLD_NOISE_SETTINGS: NoiseGeneratorSettings = overworld(false);
+pub static AMPLIFIED_NOISE_SETTINGS: NoiseGeneratorSettings = overworld(true);
+pub static NETHER_NOISE_SETTINGS: NoiseGeneratorSettings = nether(&NETHERRACK, &LAVA);
+pub static CAVES_NOISE_SETTINGS: NoiseGeneratorSettings = nether(&STONE, &WATER);
+pub static END_NOISE_SETTINGS: NoiseGeneratorSettings = end(&END_STONE, &AIR, true);
+pub static FLOATING_ISLANDS_NOISE_SETTINGS: NoiseGeneratorSettings = end(&STONE, &WATER, false);
+
+
+const fn overworld(amplified: bool) -> NoiseGeneratorSettings {
+    NoiseGeneratorSettings {
+        noise: NoiseSettings {
+            height: 256,
+            sampling: NoiseSampling { xz_scale: OVERWORLD_SCALE, y_scale: OVERWORLD_SCALE, xz_factor: 80.0, y_factor: 160.0 },
+            top_slide: NoiseSlide { target: -10, size: 3, offset: 0 },
+            bottom_slide: NoiseSlide { target: -30, size: 0, offset: 0 },
+            size_horizontal: 1,
+            size_vertical: 2,
+            density_factor: 1.0,
+            density_offset: -0.46875,
+            simplex_surface_noise: true,
+            random_density_offset: true,
+            island_noise_override: false,
+            amplified
+        },
+        default_block: &STONE,
+        default_fluid: &WATER,
+        bedrock_roof_position: -10,
+        bedrock_floor_position: 0,
+        sea_level: 63,
+        disable_mob_generation: false
+    }
+}
+
+const fn nether(default_block: &'static Block, default_fluid: &'static Block) -> NoiseGeneratorSettings {
+    NoiseGeneratorSettings {
+        noise: NoiseSettings {
+            height: 128,
+            sampling: NoiseSampling { xz_scale: 1.0, y_scale: 3.0, xz_factor: 80.0, y_factor: 60.0 },
+            top_slide: NoiseSlide { target: 120, size: 3, offset: 0 },
+            bottom_slide: NoiseSlide { target: 320, size: 4, offset: -1 },
+            size_horizontal: 1,
+            size_vertical: 2,
+            density_factor: 0.0,
+            density_offset: 0.019921875,
+            simplex_surface_noise: false,
+            random_density_offset: false,
+            island_noise_override: false,
+            amplified: false
+        },
+        default_block,
+        default_fluid,
+        bedrock_roof_position: 0,
+        bedrock_floor_position: 0,
+        sea_level: 32,
+        disable_mob_generation: false
+    }
+}
+
+const fn end(default_block: &'static Block, default_fluid: &'static Block, island_noise_override: bool) -> NoiseGeneratorSettings {
+    NoiseGeneratorSettings {
+        noise: NoiseSettings {
+            height: 128,
+            sampling: NoiseSampling { xz_scale: 2.0, y_scale: 1.0, xz_factor: 80.0, y_factor: 160.0 },
+            top_slide: NoiseSlide { target: -3000, size: 64, offset: -46 },
+            bottom_slide: NoiseSlide { target: -30, size: 7, offset: 1 },
+            size_horizontal: 2,
+            size_vertical: 1,
+            density_factor: 0.0,
+            density_offset: 0.0,
+            simplex_surface_noise: true,
+            random_density_offset: false,
+            island_noise_override,
+            amplified: false
+        },
+        default_block,
+        default_fluid,
+        bedrock_roof_position: -10,
+        bedrock_floor_position: -10,
+        sea_level: 0,
+        disable_mob_generation: island_noise_override
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn vanilla_noise_settings() {
+
+        let overworld = NoiseGeneratorSettings::from_registry_name("minecraft:overworld").unwrap();
+        assert_eq!(overworld, &OVERWORLD_NOISE_SETTINGS);
+        assert!(!overworld.noise.amplified);
+        assert!(NoiseGeneratorSettings::from_registry_name("minecraft:amplified").unwrap().noise.amplified);
+        assert!(NoiseGeneratorSettings::from_registry_name("minecraft:unknown").is_none());
+
+        let nether = NETHER_NOISE_SETTINGS.get_generator_settings();
+        assert_eq!(nether.sea_level, 32);
+        assert_eq!(nether.default_block, NETHERRACK.get_default_state());
+        assert_eq!(nether.default_fluid, LAVA.get_default_state());
+
+        assert!(END_NOISE_SETTINGS.disable_mob_generation);
+        assert!(!FLOATING_ISLANDS_NOISE_SETTINGS.noise.island_noise_override);
+
+    }
+
+}
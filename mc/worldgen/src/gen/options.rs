@@ -0,0 +1,273 @@
+//! Generator options of a level, as stored in the `Data` compound of `level.dat` files,
+//! these options select the world type and its variants. Legacy files store them as a
+//! generator name and options, files of 1.16 and later store `WorldGenSettings`.
+
+use std::path::Path;
+use std::fs::File;
+
+use nbt::decode::read_gzip_compound_tag;
+use nbt::CompoundTag;
+
+use mc_core::world::anvil::decode::DecodeError;
+
+use super::customized::CustomizedPreset;
+use super::dimension::WorldGenSettings;
+use super::flat::FlatPreset;
+
+
+/// Vanilla world types, known as generator name in `level.dat`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum GeneratorType {
+    Default,
+    Flat,
+    LargeBiomes,
+    Amplified,
+    Customized,
+    DebugAllBlockStates
+}
+
+impl GeneratorType {
+
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Flat => "flat",
+            Self::LargeBiomes => "largeBiomes",
+            Self::Amplified => "amplified",
+            Self::Customized => "customized",
+            Self::DebugAllBlockStates => "debug_all_block_states"
+        }
+    }
+
+    /// Get a generator type from its name, case-insensitive like vanilla.
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            Self::Default,
+            Self::Flat,
+            Self::LargeBiomes,
+            Self::Amplified,
+            Self::Customized,
+            Self::DebugAllBlockStates
+        ].iter().copied().find(|ty| ty.get_name().eq_ignore_ascii_case(name))
+    }
+
+}
+
+
+/// Raw options specific to a generator type.
+#[derive(Debug, Clone)]
+pub enum GeneratorOptions {
+    /// Options stored as text until 1.12, the text preset of flat worlds and the JSON
+    /// preset of customized worlds.
+    Text(String),
+    /// Options stored as a compound from 1.13 to 1.15, the preset of flat worlds.
+    Compound(CompoundTag)
+}
+
+impl Default for GeneratorOptions {
+    fn default() -> Self {
+        Self::Text(String::new())
+    }
+}
+
+
+/// Options given to level generators.
+#[derive(Debug, Clone)]
+pub struct LevelGeneratorOptions {
+    pub seed: i64,
+    pub generator_type: GeneratorType,
+    /// Options specific to the generator type, empty by default.
+    pub generator_options: GeneratorOptions,
+    /// True if structures should be generated.
+    pub map_features: bool,
+    /// Settings of levels from 1.16, when present they are encoded instead of the legacy
+    /// fields and should be edited directly, other fields are only derived from them.
+    pub world_gen_settings: Option<WorldGenSettings>
+}
+
+impl LevelGeneratorOptions {
+
+    pub fn new(seed: i64) -> Self {
+        Self {
+            seed,
+            generator_type: GeneratorType::Default,
+            generator_options: GeneratorOptions::default(),
+            map_features: true,
+            world_gen_settings: None
+        }
+    }
+
+    pub fn with_generator_type(mut self, generator_type: GeneratorType) -> Self {
+        self.generator_type = generator_type;
+        self
+    }
+
+    pub fn with_generator_options(mut self, generator_options: impl Into<String>) -> Self {
+        self.generator_options = GeneratorOptions::Text(generator_options.into());
+        self
+    }
+
+    pub fn with_flat_preset(mut self, preset: &FlatPreset) -> Self {
+        self.generator_options = GeneratorOptions::Text(preset.to_text());
+        self
+    }
+
+    pub fn with_customized_preset(mut self, preset: &CustomizedPreset) -> Self {
+        self.generator_options = GeneratorOptions::Text(preset.to_json());
+        self
+    }
+
+    pub fn with_world_gen_settings(mut self, settings: WorldGenSettings) -> Self {
+        self.seed = settings.seed;
+        self.generator_type = settings.get_generator_type();
+        self.map_features = settings.generate_features;
+        self.generator_options = GeneratorOptions::Text(settings.legacy_custom_options.clone().unwrap_or_default());
+        self.world_gen_settings = Some(settings);
+        self
+    }
+
+    /// Decode generator options from the `Data` compound of a `level.dat` file, unknown
+    /// generator names fallback to the default generator like vanilla.
+    pub fn from_level_data(tag_data: &CompoundTag) -> Result<Self, DecodeError> {
+
+        if let Ok(tag_settings) = tag_data.get_compound_tag("WorldGenSettings") {
+            let settings = WorldGenSettings::from_nbt(tag_settings)?;
+            return Ok(Self::new(settings.seed).with_world_gen_settings(settings));
+        }
+
+        let generator_options = match tag_data.get_compound_tag("generatorOptions") {
+            Ok(tag_options) => GeneratorOptions::Compound(tag_options.clone()),
+            Err(_) => GeneratorOptions::Text(tag_data.get_str("generatorOptions").unwrap_or_default().to_string())
+        };
+
+        Ok(Self {
+            seed: tag_data.get_i64("RandomSeed")?,
+            generator_type: tag_data.get_str("generatorName").ok()
+                .and_then(GeneratorType::from_name)
+                .unwrap_or(GeneratorType::Default),
+            generator_options,
+            map_features: tag_data.get_bool("MapFeatures").unwrap_or(true),
+            world_gen_settings: None
+        })
+
+    }
+
+    /// Decode generator options from a gzip-compressed `level.dat` file.
+    pub fn from_level_dat(path: &Path) -> Result<Self, DecodeError> {
+        let mut file = File::open(path)
+            .map_err(|e| DecodeError::Malformed(format!("Can't open level data '{}': {}", path.display(), e)))?;
+        let tag_root = read_gzip_compound_tag(&mut file)?;
+        Self::from_level_data(tag_root.get_compound_tag("Data")?)
+    }
+
+    /// Return the customized preset decoded from the generator options, invalid presets
+    /// fallback to the default preset like vanilla.
+    pub fn get_customized_preset(&self) -> CustomizedPreset {
+        match &self.generator_options {
+            GeneratorOptions::Text(json) => CustomizedPreset::from_json(json).unwrap_or_default(),
+            GeneratorOptions::Compound(_) => CustomizedPreset::default()
+        }
+    }
+
+    /// Return the flat preset decoded from the generator options, invalid presets fallback
+    /// to the default preset like vanilla.
+    pub fn get_flat_preset(&self) -> FlatPreset {
+        match &self.generator_options {
+            GeneratorOptions::Text(text) => FlatPreset::from_text(text),
+            GeneratorOptions::Compound(tag) => FlatPreset::from_nbt(tag).ok()
+        }.unwrap_or_default()
+    }
+
+    /// Encode these generator options into the `Data` compound of a `level.dat` file.
+    pub fn encode_level_data(&self, tag_data: &mut CompoundTag) {
+
+        if let Some(settings) = &self.world_gen_settings {
+            tag_data.insert_compound_tag("WorldGenSettings", settings.encode_nbt());
+            return;
+        }
+
+        tag_data.insert_i64("RandomSeed", self.seed);
+        tag_data.insert_str("generatorName", self.generator_type.get_name());
+        match &self.generator_options {
+            GeneratorOptions::Text(text) => tag_data.insert_str("generatorOptions", text),
+            GeneratorOptions::Compound(tag) => tag_data.insert_compound_tag("generatorOptions", tag.clone())
+        }
+        tag_data.insert_bool("MapFeatures", self.map_features);
+
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn level_data() {
+
+        assert_eq!(GeneratorType::from_name("LARGEBIOMES"), Some(GeneratorType::LargeBiomes));
+        assert_eq!(GeneratorType::from_name("unknown"), None);
+
+        let mut tag_data = CompoundTag::new();
+        LevelGeneratorOptions::new(-42)
+            .with_generator_type(GeneratorType::Amplified)
+            .encode_level_data(&mut tag_data);
+
+        let options = LevelGeneratorOptions::from_level_data(&tag_data).unwrap();
+        assert_eq!(options.seed, -42);
+        assert_eq!(options.generator_type, GeneratorType::Amplified);
+        assert!(options.map_features);
+
+        tag_data.insert_str("generatorName", "unknown");
+        let options = LevelGeneratorOptions::from_level_data(&tag_data).unwrap();
+        assert_eq!(options.generator_type, GeneratorType::Default);
+        assert!(LevelGeneratorOptions::from_level_data(&CompoundTag::new()).is_err());
+
+        // Legacy flat and customized presets.
+        let preset = FlatPreset::from_text("3;minecraft:bedrock,3*minecraft:stone;1;village,mineshaft").unwrap();
+        let mut tag_data = CompoundTag::new();
+        LevelGeneratorOptions::new(1)
+            .with_generator_type(GeneratorType::Flat)
+            .with_flat_preset(&preset)
+            .encode_level_data(&mut tag_data);
+        assert_eq!(tag_data.get_str("generatorOptions").unwrap(), "3;minecraft:bedrock,3*minecraft:stone;1;village,mineshaft");
+        assert_eq!(LevelGeneratorOptions::from_level_data(&tag_data).unwrap().get_flat_preset(), preset);
+
+        let preset = CustomizedPreset { sea_level: 90, ..CustomizedPreset::default() };
+        let mut tag_data = CompoundTag::new();
+        LevelGeneratorOptions::new(1)
+            .with_generator_type(GeneratorType::Customized)
+            .with_customized_preset(&preset)
+            .encode_level_data(&mut tag_data);
+        assert_eq!(LevelGeneratorOptions::from_level_data(&tag_data).unwrap().get_customized_preset(), preset);
+
+        // Compound flat presets of 1.13 to 1.15.
+        let preset = FlatPreset { biome: "minecraft:desert".to_string(), ..FlatPreset::default() };
+        tag_data.insert_str("generatorName", "flat");
+        tag_data.insert_compound_tag("generatorOptions", preset.encode_nbt());
+        let options = LevelGeneratorOptions::from_level_data(&tag_data).unwrap();
+        assert!(matches!(options.generator_options, GeneratorOptions::Compound(_)));
+        assert_eq!(options.get_flat_preset(), preset);
+        let mut tag_encoded = CompoundTag::new();
+        options.encode_level_data(&mut tag_encoded);
+        assert_eq!(tag_encoded.get_compound_tag("generatorOptions").unwrap().to_string(), preset.encode_nbt().to_string());
+
+        // World generation settings of 1.16 and later.
+        let mut tag_data = CompoundTag::new();
+        LevelGeneratorOptions::new(0)
+            .with_world_gen_settings(WorldGenSettings::new(5, GeneratorType::LargeBiomes).with_generate_features(false))
+            .encode_level_data(&mut tag_data);
+        assert!(!tag_data.contains_key("generatorName"));
+        let options = LevelGeneratorOptions::from_level_data(&tag_data).unwrap();
+        assert_eq!(options.seed, 5);
+        assert_eq!(options.generator_type, GeneratorType::LargeBiomes);
+        assert!(!options.map_features);
+        let mut tag_encoded = CompoundTag::new();
+        options.encode_level_data(&mut tag_encoded);
+        assert_eq!(tag_encoded.to_string(), tag_data.to_string());
+
+    }
+
+}
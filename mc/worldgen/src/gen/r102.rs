@@ -22,7 +22,6 @@
 //! feature instance for each biome, so each biome have a different big tree instance that has
 //! a different base height depending on the first generate big tree for this biome.
 
-use std::num::Wrapping;
 use std::sync::Arc;
 
 use once_cell::sync::Lazy;
@@ -43,132 +42,150 @@ use crate::noise::{PerlinNoiseOctaves, NoiseCube, NoiseRect};
 use crate::layer::{LayerBuilder, BoxLayer, Layer};
 use crate::layer::zoom::VoronoiLayer;
 
+use crate::structure::carver::ConfiguredCarver;
 use crate::structure::ravine::RavineStructure;
 use crate::structure::cave::CaveStructure;
+use crate::structure::debug::PieceDebugger;
 use crate::structure::Structure;
 
 use crate::feature::tree::{TreeFeature, BigTreeFeature, TaigaTreeFeature, ShrubFeature, HugeJungleTreeFeature};
-use crate::feature::distrib::{Distrib, HeightmapDistrib, LavaLakeDistrib, OffsetWhileDistrib};
+use crate::feature::distrib::{Distrib, HeightmapDistrib, OffsetWhileDistrib};
 use crate::feature::flower::{PlantFeature, SugarCaneFeature};
 use crate::feature::vein::{WaterCircleFeature, VeinFeature};
 use crate::feature::{FeatureChain, Feature};
 use crate::feature::dungeon::DungeonFeature;
 use crate::feature::branch::RepeatCount;
 use crate::feature::lake::LakeFeature;
+use crate::feature::well::DesertWellFeature;
 use crate::view::LevelView;
+use crate::seed;
 
-use super::legacy::{GeneratorProvider, FeatureGenerator, TerrainGenerator, LegacyProtoChunk, QuadLevelView};
+use super::legacy::{ChunkGenerator, BiomeSource, NoiseGenerator, FeatureGenerator, LegacyProtoChunk, QuadLevelView};
+use super::legacy::{ChunkBiomeCache, BiomeResolution};
 use super::biome::{BiomePropertyMap, BiomeProperty};
+use super::options::{LevelGeneratorOptions, GeneratorType};
+use super::customized::CustomizedPreset;
+use super::settings::GeneratorSettings;
+use super::analysis::HeightSampler;
 
 
-/// Base implementation of `GeneratorProvider` for release 1.2 generation.
-pub struct R102Provider {
-    shared: Arc<Shared>
+/// Base implementation of `ChunkGenerator` for release 1.2 generation.
+pub struct R102Generator {
+    shared: Arc<Shared>,
+    carvers: Option<Arc<[ConfiguredCarver]>>
 }
 
-impl R102Provider {
+impl R102Generator {
 
-    /// Construct a new provider with the given seed.
+    /// Construct a new generator with the given seed.
     pub fn new(seed: i64) -> Self {
+        Self::new_internal(&LevelGeneratorOptions::new(seed), None)
+    }
+
+    /// Construct a new generator with the given options, large biomes, amplified and
+    /// customized world types are supported, other world types use the default generation.
+    /// Note that the amplified terrain is still limited to the 128 blocks generated by this
+    /// version and that features of customized presets (ores, dungeons and lakes) are not
+    /// supported because features are common to all generators of this version.
+    pub fn with_options(options: &LevelGeneratorOptions) -> Self {
+        Self::new_internal(options, None)
+    }
+
+    /// Construct a new generator with the given seed, structures' pieces are recorded in the
+    /// given debugger and outlined in generated chunks if the debugger has a marker.
+    pub fn with_piece_debugger(seed: i64, piece_debugger: Arc<PieceDebugger>) -> Self {
+        Self::new_internal(&LevelGeneratorOptions::new(seed), Some(piece_debugger))
+    }
+
+    /// Replace the legacy caves and ravines with the given configured carvers, for example
+    /// loaded from a datapack.
+    pub fn with_carvers(mut self, carvers: Vec<ConfiguredCarver>) -> Self {
+        self.carvers = Some(carvers.into());
+        self
+    }
+
+    /// Replace the generator settings (sea level, lava level, default block and fluid), the
+    /// sea level of customized presets is overwritten. This must be called before building
+    /// any component of this generator.
+    pub fn with_settings(mut self, settings: GeneratorSettings) -> Self {
+        Arc::get_mut(&mut self.shared)
+            .expect("The generator's components are already built.")
+            .settings.generator = settings;
+        self
+    }
+
+    fn new_internal(options: &LevelGeneratorOptions, piece_debugger: Option<Arc<PieceDebugger>>) -> Self {
+        let seed = options.seed;
         let mut rand = JavaRandom::new(seed);
         Self {
             shared: Arc::new(Shared {
                 seed,
+                settings: TerrainSettings::new(options),
                 noise1: PerlinNoiseOctaves::new(&mut rand),
                 noise2: PerlinNoiseOctaves::new(&mut rand),
                 noise3: PerlinNoiseOctaves::new(&mut rand),
                 noise_surface: PerlinNoiseOctaves::new(&mut rand),
                 noise4: PerlinNoiseOctaves::new(&mut rand),
                 noise5: PerlinNoiseOctaves::new(&mut rand),
-            })
+                piece_debugger,
+            }),
+            carvers: None
         }
     }
 
 }
 
-impl GeneratorProvider for R102Provider {
+impl ChunkGenerator for R102Generator {
 
-    type Terrain = R102TerrainGenerator;
+    type BiomeSource = R102BiomeSource;
+    type Noise = R102NoiseGenerator;
     type Feature = R102FeatureGenerator;
 
-    fn build_terrain(&self) -> Self::Terrain {
-        R102TerrainGenerator::new(Arc::clone(&self.shared))
+    fn build_biome_source(&self) -> Self::BiomeSource {
+        R102BiomeSource::new(self.shared.seed, &self.shared.settings)
+    }
+
+    fn build_noise(&self) -> Self::Noise {
+        R102NoiseGenerator::new(Arc::clone(&self.shared), self.carvers.clone())
     }
 
     fn build_feature(&self) -> Self::Feature {
         R102FeatureGenerator::new(Arc::clone(&self.shared))
     }
 
+    fn get_settings(&self) -> &GeneratorSettings {
+        &self.shared.settings.generator
+    }
+
 }
 
-/// Terrain generator for release 1.2
-pub struct R102TerrainGenerator {
-    shared: Arc<Shared>,
-    noise1_cache: NoiseCube,
-    noise2_cache: NoiseCube,
-    noise3_cache: NoiseCube,
-    noise4_cache: NoiseRect,
-    noise5_cache: NoiseRect,
-    noise_surface_cache: NoiseCube,
-    noise_field: NoiseCube,
+/// Biome source for release 1.2, using biome layers.
+pub struct R102BiomeSource {
     layer_voronoi: VoronoiLayer<BoxLayer<&'static Biome>>,
+    fixed_biome: Option<&'static Biome>,
 }
 
-impl TerrainGenerator for R102TerrainGenerator {
-    type Chunk = LegacyProtoChunk;
-    fn generate(&mut self, mut chunk: ProtoChunk) -> Self::Chunk {
-
-        perf::push("r102_gen_terrain");
-
-        const X_MUL: Wrapping<i64> = Wrapping(0x4f9939f508);
-        const Z_MUL: Wrapping<i64> = Wrapping(0x1ef1565bd5);
-
-        let (cx, cz) = chunk.get_position();
-        let mut rand = JavaRandom::new((Wrapping(cx as i64) * X_MUL + Wrapping(cz as i64) * Z_MUL).0);
-
-        perf::push("init_biomes");
-        let biomes = self.initialize_biomes(&mut *chunk);
-        perf::pop_push("terrain");
-        self.generate_terrain(&mut *chunk);
-        perf::pop();
-
-        let mut chunk = LegacyProtoChunk {
-            inner: chunk,
-            legacy_biomes: biomes
-        };
-
-        perf::push("surface");
-        self.generate_surface(&mut chunk, &mut rand/*&mut *chunk, &mut rand, &biomes*/);
-        perf::pop_push("structures");
-        self.generate_structures(&mut chunk/*&mut *chunk, &biomes*/);
-        perf::pop();
-
-        perf::pop();
-
-        chunk
+impl BiomeSource for R102BiomeSource {
 
+    fn get_biomes(&mut self, x: i32, z: i32, x_size: usize, z_size: usize) -> Rect<&'static Biome> {
+        match self.fixed_biome {
+            Some(biome) => Rect::new(x_size, z_size, biome),
+            None => self.layer_voronoi.next_grid(x, z, x_size, z_size)
+        }
     }
-}
 
-impl R102TerrainGenerator {
-
-    fn new(shared: Arc<Shared>) -> Self {
-        const WIDTH: usize = 5;
-        const HEIGHT: usize = 17;
-        Self {
-            noise1_cache: NoiseCube::new_default(WIDTH, HEIGHT, WIDTH),
-            noise2_cache: NoiseCube::new_default(WIDTH, HEIGHT, WIDTH),
-            noise3_cache: NoiseCube::new_default(WIDTH, HEIGHT, WIDTH),
-            noise4_cache: NoiseRect::new_default(WIDTH, WIDTH),
-            noise5_cache: NoiseRect::new_default(WIDTH, WIDTH),
-            noise_surface_cache: NoiseCube::new_default(16, 16, 1),
-            noise_field: NoiseCube::new_default(WIDTH, HEIGHT, WIDTH),
-            layer_voronoi: Self::new_layers(shared.seed),
-            shared,
+    fn get_noise_biomes(&mut self, x: i32, z: i32, x_size: usize, z_size: usize) -> Rect<&'static Biome> {
+        match self.fixed_biome {
+            Some(biome) => Rect::new(x_size, z_size, biome),
+            None => self.layer_voronoi.parent.next_grid(x, z, x_size, z_size)
         }
     }
 
-    fn new_layers(seed: i64) -> VoronoiLayer<BoxLayer<&'static Biome>> {
+}
+
+impl R102BiomeSource {
+
+    fn new(seed: i64, settings: &TerrainSettings) -> Self {
 
         let (
             river,
@@ -187,31 +204,38 @@ impl R102TerrainGenerator {
             .into_box()
             .into_shared_split();
 
-        let river = river
+        let mut river = river
             .then_init_river(100)
-            .then_zoom_smart(1000)
-            .then_zoom_smart(1001)
-            .then_zoom_smart(1002)
-            .then_zoom_smart(1003)
-            .then_zoom_smart(1004)
-            .then_zoom_smart(1005)
+            .into_box();
+
+        // Rivers are zoomed 2 more times than biomes (by default).
+        for i in 0..(settings.river_size as i64 + 2) {
+            river = river.then_zoom_smart(1000 + i).into_box();
+        }
+
+        let river = river
             .then_add_river()
             .then_smooth(1000)
             .into_box()
             .build();
 
-        let biome = biome
+        let mut biome = biome
             .then_biome(200, (1, 2)).unwrap()
             .then_zoom_smart(1000)
             .then_zoom_smart(1001)
             .then_hills(1000)
-            .then_zoom_smart(1000)
-            .then_add_island(3)
-            .then_zoom_smart(1001)
-            .then_shore()
-            .then_biome_river(1000)
-            .then_zoom_smart(1002)
-            .then_zoom_smart(1003)
+            .into_box();
+
+        for i in 0..settings.biome_size as i64 {
+            biome = biome.then_zoom_smart(1000 + i).into_box();
+            if i == 0 {
+                biome = biome.then_add_island(3).into_box();
+            } else if i == 1 {
+                biome = biome.then_shore().then_biome_river(1000).into_box();
+            }
+        }
+
+        let biome = biome
             .then_smooth(1000)
             .into_box()
             .build();
@@ -222,144 +246,151 @@ impl R102TerrainGenerator {
             .build();
 
         voronoi.seed(seed);
-        voronoi
-
-    }
+        Self {
+            layer_voronoi: voronoi,
+            fixed_biome: settings.fixed_biome
+        }
 
-    fn initialize_biomes(&mut self, chunk: &mut Chunk) -> Rect<&'static Biome> {
-        let (cx, cz) = chunk.get_position();
-        let biomes = self.layer_voronoi.next_grid(cx * 16, cz * 16, 16, 16);
-        chunk.set_biomes_2d(&biomes).expect("The biome layer returned invalid biomes.");
-        biomes
     }
 
-    /// Generate base terrain and return the first stage chunk.
-    fn generate_terrain(&mut self, chunk: &mut Chunk) {
-
-        // Generate terrain only generate 8 sub-chunks in height,
-        // the construction limit is 16 chunks in height.
-        //
-        // 17 noises values are used for the whole 8 sub-chunks,
-        // ignoring the last noise layer, there are 2 noise layers
-        // for each sub-chunk.
-        //
-        // Only 4 values are used for each x & z axis, then a single
-        // noise point in the noise field represent 4*4*8 blocks.
-        //
-        // This function just apply linear interpolation between
-        // noise points.
-
-        let (cx, cz) = chunk.get_position();
-        self.initialize_noise_field(cx, cz);
-
-        // dx/dz/dy are the noise field coordinates
-        for dx in 0..4 {
-            for dz in 0..4 {
-                for dy in 0..16 {
-
-                    // These lines of code will be called 2 times for each sub-chunk (8 sub chunk in total).
-
-                    // Pattern for variables: [n|ns]_<x><y><z>
-                    // The prefix "n" stands for "noise [at]"
-                    // The prefix "ns" stands for "noise step"
-                    // Using "x", "y" or "z" as coordinate means that the value will vary according to
-                    // the specified axis.
-                    let mut n_0y0 = *self.noise_field.get(dx + 0, dy + 0, dz + 0);
-                    let mut n_0y1 = *self.noise_field.get(dx + 0, dy + 0, dz + 1);
-                    let mut n_1y0 = *self.noise_field.get(dx + 1, dy + 0, dz + 0);
-                    let mut n_1y1 = *self.noise_field.get(dx + 1, dy + 0, dz + 1);
-
-                    // println!("[{}/{}/{}] n_0y0={}, n_0y1={}, n_1y0={}, n_1y1={}", dx, dy, dz, n_0y0, n_0y1, n_1y0, n_1y1);
+}
 
-                    // Mul by 0.125 because it equals 1/8, 8 is the number of blocks in the half sub chunk.
-                    let ns_010 = (*self.noise_field.get(dx + 0, dy + 1, dz + 0) - n_0y0) * 0.125;
-                    let ns_011 = (*self.noise_field.get(dx + 0, dy + 1, dz + 1) - n_0y1) * 0.125;
-                    let ns_110 = (*self.noise_field.get(dx + 1, dy + 1, dz + 0) - n_1y0) * 0.125;
-                    let ns_111 = (*self.noise_field.get(dx + 1, dy + 1, dz + 1) - n_1y1) * 0.125;
+/// Noise generator for release 1.2
+pub struct R102NoiseGenerator {
+    shared: Arc<Shared>,
+    carvers: Option<Arc<[ConfiguredCarver]>>,
+    noise1_cache: NoiseCube,
+    noise2_cache: NoiseCube,
+    noise3_cache: NoiseCube,
+    noise4_cache: NoiseRect,
+    noise5_cache: NoiseRect,
+    noise_surface_cache: NoiseCube,
+    noise_field: NoiseCube,
+}
 
-                    // Get the current sub-chunk.
-                    let sub_chunk = chunk.ensure_sub_chunk((dy >> 1) as i8/*, None*/).unwrap();
+impl NoiseGenerator for R102NoiseGenerator {
+    type Chunk = LegacyProtoChunk;
+    fn generate<B: BiomeSource + ?Sized>(&mut self, mut chunk: ProtoChunk, biome_source: &mut B) -> Self::Chunk {
 
-                    // Iterating over the 8 blocks in the half sub-chunk.
-                    for half_sub_chunk_dy in 0..8 {
+        perf::push("r102_gen_terrain");
 
-                        let mut n_xy0 = n_0y0;
-                        let mut n_xy1 = n_0y1;
+        let (cx, cz) = chunk.get_position();
+        let mut rand = JavaRandom::new(seed::chunk_seed(cx, cz));
 
-                        // Mul by 0.25 because it equals 1/4, 4 is the number of blocks for a single
-                        // axis of a single noise point.
-                        let ns_1y0 = (n_1y0 - n_0y0) * 0.25;
-                        let ns_1y1 = (n_1y1 - n_0y1) * 0.25;
+        perf::push("init_biomes");
+        let biomes = self.initialize_biomes(&mut *chunk, biome_source);
+        perf::pop_push("terrain");
+        self.generate_terrain(&mut *chunk, biome_source);
+        perf::pop();
 
-                        // Using "& 1" to alternate between 0*8 and 1*8 for each sub-chunk.
-                        let block_real_y = dy * 8 + half_sub_chunk_dy;
-                        let block_y = block_real_y & 15;
+        let mut chunk = LegacyProtoChunk {
+            inner: chunk,
+            legacy_biomes: biomes
+        };
 
-                        // Iterating over the 4*4 for each value of 'half_sub_chunk_dy'
-                        for sub_block_dx in 0..4 {
+        perf::push("surface");
+        self.generate_surface(&mut chunk, &mut rand/*&mut *chunk, &mut rand, &biomes*/);
+        perf::pop_push("structures");
+        self.generate_structures(&mut chunk/*&mut *chunk, &biomes*/);
+        perf::pop();
 
-                            let block_x = dx * 4 + sub_block_dx;
+        perf::pop();
 
-                            let mut n_xyz = n_xy0;
-                            let ns_xy1 = (n_xy1 - n_xy0) * 0.25;
+        chunk
 
-                            for sub_block_dz in 0..4 {
+    }
+}
 
-                                let block_z = dz * 4 + sub_block_dz;
+impl HeightSampler for R102NoiseGenerator {
+    fn sample_heights<B: BiomeSource + ?Sized>(&mut self, cx: i32, cz: i32, biome_source: &mut B) -> Rect<i32> {
+        self.initialize_noise_field(cx, cz, biome_source);
+        let mut heights = Rect::new(16, 16, 0);
+        interpolate_noise_field(&self.noise_field, |block_x, block_y, block_z, n_xyz| {
+            let height = heights.get_mut(block_x, block_z);
+            if n_xyz > 0.0 && *height <= block_y as i32 {
+                *height = block_y as i32 + 1;
+            }
+        });
+        heights
+    }
+}
 
-                                let block_to_set = if n_xyz > 0.0 {
-                                    Some(STONE.get_default_state())
-                                } else if block_real_y < 63 {
-                                    Some(WATER.get_default_state())
-                                } else {
-                                    None
-                                };
+impl R102NoiseGenerator {
 
-                                if let Some(block) = block_to_set {
-                                    sub_chunk.set_block(block_x as u8, block_y as u8, block_z as u8, block).unwrap();
-                                }
+    fn new(shared: Arc<Shared>, carvers: Option<Arc<[ConfiguredCarver]>>) -> Self {
+        const WIDTH: usize = 5;
+        const HEIGHT: usize = 17;
+        Self {
+            noise1_cache: NoiseCube::new_default(WIDTH, HEIGHT, WIDTH),
+            noise2_cache: NoiseCube::new_default(WIDTH, HEIGHT, WIDTH),
+            noise3_cache: NoiseCube::new_default(WIDTH, HEIGHT, WIDTH),
+            noise4_cache: NoiseRect::new_default(WIDTH, WIDTH),
+            noise5_cache: NoiseRect::new_default(WIDTH, WIDTH),
+            noise_surface_cache: NoiseCube::new_default(16, 16, 1),
+            noise_field: NoiseCube::new_default(WIDTH, HEIGHT, WIDTH),
+            shared,
+            carvers,
+        }
+    }
 
-                                // println!("[{:02}/{:02}/{:02}] noise: {}, block: {:?}", block_x, block_real_y, block_z, n_xyz, block_to_set);
+    fn initialize_biomes<B: BiomeSource + ?Sized>(&mut self, chunk: &mut Chunk, biome_source: &mut B) -> ChunkBiomeCache {
+        let (cx, cz) = chunk.get_position();
+        let biomes = ChunkBiomeCache::new(biome_source, cx, cz, BiomeResolution::Block);
+        chunk.set_biomes_2d(biomes.as_rect()).expect("The biome layer returned invalid biomes.");
+        biomes
+    }
 
-                                n_xyz += ns_xy1;
+    /// Generate base terrain and return the first stage chunk.
+    fn generate_terrain<B: BiomeSource + ?Sized>(&mut self, chunk: &mut Chunk, biome_source: &mut B) {
 
-                            }
+        let (cx, cz) = chunk.get_position();
+        self.initialize_noise_field(cx, cz, biome_source);
 
-                            n_xy0 += ns_1y0;
-                            n_xy1 += ns_1y1;
+        let generator_settings = &self.shared.settings.generator;
+        let sea_level = generator_settings.sea_level;
 
-                        }
+        for cy in 0..8 {
+            chunk.ensure_sub_chunk(cy).unwrap();
+        }
 
-                        n_0y0 += ns_010;
-                        n_0y1 += ns_011;
-                        n_1y0 += ns_110;
-                        n_1y1 += ns_111;
+        interpolate_noise_field(&self.noise_field, |block_x, block_y, block_z, n_xyz| {
 
-                    }
+            let block_to_set = if n_xyz > 0.0 {
+                Some(generator_settings.default_block)
+            } else if (block_y as i32) < sea_level {
+                Some(generator_settings.default_fluid)
+            } else {
+                None
+            };
 
-                }
+            if let Some(block) = block_to_set {
+                let sub_chunk = chunk.ensure_sub_chunk((block_y >> 4) as i8).unwrap();
+                sub_chunk.set_block(block_x as u8, (block_y & 15) as u8, block_z as u8, block).unwrap();
             }
-        }
+
+        });
 
     }
 
-    fn initialize_noise_field(&mut self, cx: i32, cz: i32) {
+    fn initialize_noise_field<B: BiomeSource + ?Sized>(&mut self, cx: i32, cz: i32, biome_source: &mut B) {
 
         let x = cx * 4;
         let y = 0;
         let z = cz * 4;
 
-        let gen_biomes = self.layer_voronoi.parent
-            .next_grid(cx * 4 - 2, cz * 4 - 2, 10, 10);
+        let gen_biomes = biome_source.get_noise_biomes(cx * 4 - 2, cz * 4 - 2, 10, 10);
 
-        const WIDTH_SCALE: f64 = 684.41200000000003;
-        const HEIGHT_SCALE: f64 = 684.41200000000003;
+        let settings = &self.shared.settings;
+        let width_scale = settings.coordinate_scale;
+        let height_scale = settings.height_scale;
+        let (main_scale_x, main_scale_y, main_scale_z) = settings.main_noise_scale;
+        let (depth_scale_x, depth_scale_z) = settings.depth_noise_scale;
 
         self.shared.noise4.generate_2d(&mut self.noise4_cache, x, z, 1.121, 1.121);
-        self.shared.noise5.generate_2d(&mut self.noise5_cache, x, z, 200.0, 200.0);
-        self.shared.noise3.generate_3d(&mut self.noise3_cache, x, y, z, WIDTH_SCALE / 80.0, HEIGHT_SCALE / 160.0, WIDTH_SCALE / 80.0);
-        self.shared.noise1.generate_3d(&mut self.noise1_cache, x, y, z, WIDTH_SCALE, HEIGHT_SCALE, WIDTH_SCALE);
-        self.shared.noise2.generate_3d(&mut self.noise2_cache, x, y, z, WIDTH_SCALE, HEIGHT_SCALE, WIDTH_SCALE);
+        self.shared.noise5.generate_2d(&mut self.noise5_cache, x, z, depth_scale_x, depth_scale_z);
+        self.shared.noise3.generate_3d(&mut self.noise3_cache, x, y, z, width_scale / main_scale_x, height_scale / main_scale_y, width_scale / main_scale_z);
+        self.shared.noise1.generate_3d(&mut self.noise1_cache, x, y, z, width_scale, height_scale, width_scale);
+        self.shared.noise2.generate_3d(&mut self.noise2_cache, x, y, z, width_scale, height_scale, width_scale);
 
         // dx/dz/dy are the position in the noise field
 
@@ -390,15 +421,27 @@ impl R102TerrainGenerator {
                             neighbour_max_height
                         ) = BIOMES_PROPERTIES.get_height(neighbour_biome).unwrap();
 
+                        let weighted_min_height = settings.biome_depth_offset + neighbour_min_height * settings.biome_depth_weight;
+                        let weighted_max_height = settings.biome_scale_offset + neighbour_max_height * settings.biome_scale_weight;
+
+                        let (
+                            weighted_min_height,
+                            weighted_max_height
+                        ) = if settings.amplified && weighted_min_height > 0.0 {
+                            (1.0 + weighted_min_height * 2.0, 1.0 + weighted_max_height * 4.0)
+                        } else {
+                            (weighted_min_height, weighted_max_height)
+                        };
+
                         let mut weight = 10.0 / ((neighbour_dx * neighbour_dx + neighbour_dz * neighbour_dz) as f32 + 0.2).sqrt();
-                        weight /= neighbour_min_height + 2.0;
+                        weight /= weighted_min_height + 2.0;
 
                         if neighbour_min_height > min_height {
                             weight /= 2.0;
                         }
 
-                        average_max_height += neighbour_max_height * weight;
-                        average_min_height += neighbour_min_height * weight;
+                        average_max_height += weighted_max_height * weight;
+                        average_min_height += weighted_min_height * weight;
                         total_weight += weight;
 
                     }
@@ -436,9 +479,9 @@ impl R102TerrainGenerator {
                 for dy in 0..self.noise_field.y_size {
 
                     let a = (average_min_height as f64 + val * 0.20000000000000001) * y_size / 16.0;
-                    let b = y_size / 2.0 + a * 4.0;
+                    let b = settings.base_size + a * 4.0;
                     let mut c;
-                    let mut d = ((dy as f64 - b) * 12.0 * 128.0) / 128.0 / average_max_height as f64;
+                    let mut d = ((dy as f64 - b) * settings.stretch_y * 128.0) / 128.0 / average_max_height as f64;
 
                     //println!(" => [{}] a: {}, b: {}, d: {}", dy, a, b, d);
 
@@ -448,8 +491,8 @@ impl R102TerrainGenerator {
 
                     // println!("  y: {}, noise1: {}", dy, self.noise_main1.get_noise(dx, dy, dz));
 
-                    let val1 = *self.noise1_cache.get(dx, dy, dz) / 512.0;
-                    let val2 = *self.noise2_cache.get(dx, dy, dz) / 512.0;
+                    let val1 = *self.noise1_cache.get(dx, dy, dz) / settings.lower_limit_scale;
+                    let val2 = *self.noise2_cache.get(dx, dy, dz) / settings.upper_limit_scale;
                     let val3 = (*self.noise3_cache.get(dx, dy, dz) / 10.0 + 1.0) / 2.0;
 
                     if val3 < 0.0 {
@@ -482,10 +525,12 @@ impl R102TerrainGenerator {
         let (cx, cz) = chunk.inner.get_position();
 
         let block_air = AIR.get_default_state();
-        let block_stone = STONE.get_default_state();
+        let block_stone = self.shared.settings.generator.default_block;
+        let block_fluid = self.shared.settings.generator.default_fluid;
         let block_bedrock = BEDROCK.get_default_state();
         let block_sand = SAND.get_default_state();
         let block_sandstone = SANDSTONE.get_default_state();
+        let sea_level = self.shared.settings.generator.sea_level;
 
         perf::push("surface_noise");
         const SCALE: f64 = 0.03125 * 2.0;
@@ -534,22 +579,22 @@ impl R102TerrainGenerator {
                                             // stone at the layer behind de surface.
                                             top_block = block_air;
                                             filler_block = block_stone;
-                                        } else if y_real >= 59 && y_real <= 64 {
+                                        } else if y_real >= sea_level - 4 && y_real <= sea_level + 1 {
                                             top_block = biome_top_block;
                                             filler_block = biome_filler_block;
                                         }
 
-                                        if y_real < 63 && top_block == block_air {
+                                        if y_real < sea_level && top_block == block_air {
                                             if biome_prop.temperature < 0.15 {
                                                 top_block = ICE.get_default_state();
                                             } else {
-                                                top_block = WATER.get_default_state();
+                                                top_block = block_fluid;
                                             }
                                         }
 
                                         depth = noise_val;
 
-                                        sub_chunk.set_block(x, y, z, if y_real >= 62 {
+                                        sub_chunk.set_block(x, y, z, if y_real >= sea_level - 1 {
                                             top_block
                                         } else {
                                             filler_block
@@ -588,12 +633,133 @@ impl R102TerrainGenerator {
     }
 
     fn generate_structures(&mut self, chunk: &mut LegacyProtoChunk/*, biomes: &Rect<&'static Biome>*/) {
-        CaveStructure::new(&*BIOMES_PROPERTIES).generate_in(self.shared.seed, chunk, 8);
-        RavineStructure::new(&*BIOMES_PROPERTIES).generate_in(self.shared.seed, chunk, 8);
+        let settings = &self.shared.settings;
+        let generator_settings = &settings.generator;
+        if let Some(carvers) = &self.carvers {
+            let debugger = self.shared.piece_debugger.as_deref();
+            for carver in carvers.iter() {
+                carver.generate_in(self.shared.seed, chunk, 8, &BIOMES_PROPERTIES, generator_settings, debugger);
+            }
+            if let Some(debugger) = debugger {
+                debugger.mark_chunk(chunk);
+            }
+        } else if let Some(debugger) = &self.shared.piece_debugger {
+            if settings.use_caves {
+                CaveStructure::new(&*BIOMES_PROPERTIES).with_settings(generator_settings).with_debugger(debugger).generate_in(self.shared.seed, chunk, 8);
+            }
+            if settings.use_ravines {
+                RavineStructure::new(&*BIOMES_PROPERTIES).with_settings(generator_settings).with_debugger(debugger).generate_in(self.shared.seed, chunk, 8);
+            }
+            debugger.mark_chunk(chunk);
+        } else {
+            if settings.use_caves {
+                CaveStructure::new(&*BIOMES_PROPERTIES).with_settings(generator_settings).generate_in(self.shared.seed, chunk, 8);
+            }
+            if settings.use_ravines {
+                RavineStructure::new(&*BIOMES_PROPERTIES).with_settings(generator_settings).generate_in(self.shared.seed, chunk, 8);
+            }
+        }
     }
 
 }
 
+
+/// Interpolate the noise field of a chunk and call the given function with the noise value
+/// of each block, the block is solid if the noise is positive. The function is called with
+/// the X, Y and Z coordinates of the block in the chunk, then the noise value.
+fn interpolate_noise_field<F>(noise_field: &NoiseCube, mut func: F)
+where
+    F: FnMut(usize, usize, usize, f64)
+{
+
+    // Generate terrain only generate 8 sub-chunks in height,
+    // the construction limit is 16 chunks in height.
+    //
+    // 17 noises values are used for the whole 8 sub-chunks,
+    // ignoring the last noise layer, there are 2 noise layers
+    // for each sub-chunk.
+    //
+    // Only 4 values are used for each x & z axis, then a single
+    // noise point in the noise field represent 4*4*8 blocks.
+    //
+    // This function just apply linear interpolation between
+    // noise points.
+
+    // dx/dz/dy are the noise field coordinates
+    for dx in 0..4 {
+        for dz in 0..4 {
+            for dy in 0..16 {
+
+                // These lines of code will be called 2 times for each sub-chunk (8 sub chunk in total).
+
+                // Pattern for variables: [n|ns]_<x><y><z>
+                // The prefix "n" stands for "noise [at]"
+                // The prefix "ns" stands for "noise step"
+                // Using "x", "y" or "z" as coordinate means that the value will vary according to
+                // the specified axis.
+                let mut n_0y0 = *noise_field.get(dx + 0, dy + 0, dz + 0);
+                let mut n_0y1 = *noise_field.get(dx + 0, dy + 0, dz + 1);
+                let mut n_1y0 = *noise_field.get(dx + 1, dy + 0, dz + 0);
+                let mut n_1y1 = *noise_field.get(dx + 1, dy + 0, dz + 1);
+
+                // println!("[{}/{}/{}] n_0y0={}, n_0y1={}, n_1y0={}, n_1y1={}", dx, dy, dz, n_0y0, n_0y1, n_1y0, n_1y1);
+
+                // Mul by 0.125 because it equals 1/8, 8 is the number of blocks in the half sub chunk.
+                let ns_010 = (*noise_field.get(dx + 0, dy + 1, dz + 0) - n_0y0) * 0.125;
+                let ns_011 = (*noise_field.get(dx + 0, dy + 1, dz + 1) - n_0y1) * 0.125;
+                let ns_110 = (*noise_field.get(dx + 1, dy + 1, dz + 0) - n_1y0) * 0.125;
+                let ns_111 = (*noise_field.get(dx + 1, dy + 1, dz + 1) - n_1y1) * 0.125;
+
+                // Iterating over the 8 blocks in the half sub-chunk.
+                for half_sub_chunk_dy in 0..8 {
+
+                    let mut n_xy0 = n_0y0;
+                    let mut n_xy1 = n_0y1;
+
+                    // Mul by 0.25 because it equals 1/4, 4 is the number of blocks for a single
+                    // axis of a single noise point.
+                    let ns_1y0 = (n_1y0 - n_0y0) * 0.25;
+                    let ns_1y1 = (n_1y1 - n_0y1) * 0.25;
+
+                    let block_real_y = dy * 8 + half_sub_chunk_dy;
+
+                    // Iterating over the 4*4 for each value of 'half_sub_chunk_dy'
+                    for sub_block_dx in 0..4 {
+
+                        let block_x = dx * 4 + sub_block_dx;
+
+                        let mut n_xyz = n_xy0;
+                        let ns_xy1 = (n_xy1 - n_xy0) * 0.25;
+
+                        for sub_block_dz in 0..4 {
+
+                            let block_z = dz * 4 + sub_block_dz;
+
+                            func(block_x, block_real_y, block_z, n_xyz);
+
+                            n_xyz += ns_xy1;
+
+                        }
+
+                        n_xy0 += ns_1y0;
+                        n_xy1 += ns_1y1;
+
+                    }
+
+                    n_0y0 += ns_010;
+                    n_0y1 += ns_011;
+                    n_1y0 += ns_110;
+                    n_1y1 += ns_111;
+
+                }
+
+            }
+        }
+    }
+
+}
+
+
 pub struct R102FeatureGenerator {
     shared: Arc<Shared>
 }
@@ -602,10 +768,7 @@ impl FeatureGenerator for R102FeatureGenerator {
     type Chunk = LegacyProtoChunk;
     fn decorate(&mut self, mut level: QuadLevelView<Self::Chunk>, cx: i32, cz: i32, x: i32, z: i32) {
 
-        let mut rand = JavaRandom::new(self.shared.seed);
-        let a = Wrapping(rand.next_long() / 2 * 2 + 1);
-        let b = Wrapping(rand.next_long() / 2 * 2 + 1);
-        rand.set_seed((Wrapping(cx as i64) * a + Wrapping(cz as i64) * b).0 ^ self.shared.seed);
+        let mut rand = JavaRandom::new(seed::legacy_population_seed(self.shared.seed, cx, cz));
 
         /*{  // Debug biomes
             for dx in x..(x + 16) {
@@ -654,12 +817,104 @@ impl R102FeatureGenerator {
 /// Internal shared structure among terrain and feature generators.
 struct Shared {
     seed: i64,
+    settings: TerrainSettings,
     noise1: PerlinNoiseOctaves<16>,
     noise2: PerlinNoiseOctaves<16>,
     noise3: PerlinNoiseOctaves<8>,
     noise4: PerlinNoiseOctaves<10>,
     noise5: PerlinNoiseOctaves<16>,
     noise_surface: PerlinNoiseOctaves<4>,
+    piece_debugger: Option<Arc<PieceDebugger>>,
+}
+
+/// Internal terrain settings, defaults are the constants of release 1.2, these settings
+/// are changed by world types.
+struct TerrainSettings {
+    coordinate_scale: f64,
+    height_scale: f64,
+    main_noise_scale: (f64, f64, f64),
+    depth_noise_scale: (f64, f64),
+    lower_limit_scale: f64,
+    upper_limit_scale: f64,
+    base_size: f64,
+    stretch_y: f64,
+    biome_depth_weight: f32,
+    biome_depth_offset: f32,
+    biome_scale_weight: f32,
+    biome_scale_offset: f32,
+    /// Amplify the height of biomes above the sea level.
+    amplified: bool,
+    /// Common settings, given to carvers and features.
+    generator: GeneratorSettings,
+    use_caves: bool,
+    use_ravines: bool,
+    fixed_biome: Option<&'static Biome>,
+    /// Number of zoom layers applied to biomes, 4 by default and 6 for large biomes.
+    biome_size: u8,
+    /// Number of zoom layers applied to rivers, in addition to the 2 first zoom layers.
+    river_size: u8,
+}
+
+impl TerrainSettings {
+
+    fn new(options: &LevelGeneratorOptions) -> Self {
+
+        let mut settings = Self {
+            coordinate_scale: 684.412,
+            height_scale: 684.412,
+            main_noise_scale: (80.0, 160.0, 80.0),
+            depth_noise_scale: (200.0, 200.0),
+            lower_limit_scale: 512.0,
+            upper_limit_scale: 512.0,
+            base_size: 8.5,
+            stretch_y: 12.0,
+            biome_depth_weight: 1.0,
+            biome_depth_offset: 0.0,
+            biome_scale_weight: 1.0,
+            biome_scale_offset: 0.0,
+            amplified: false,
+            generator: GeneratorSettings::new(),
+            use_caves: true,
+            use_ravines: true,
+            fixed_biome: None,
+            biome_size: 4,
+            river_size: 4,
+        };
+
+        match options.generator_type {
+            GeneratorType::LargeBiomes => settings.biome_size = 6,
+            GeneratorType::Amplified => settings.amplified = true,
+            GeneratorType::Customized => settings.apply_preset(&options.get_customized_preset()),
+            _ => {}
+        }
+
+        settings
+
+    }
+
+    fn apply_preset(&mut self, preset: &CustomizedPreset) {
+        self.coordinate_scale = preset.coordinate_scale as f64;
+        self.height_scale = preset.height_scale as f64;
+        self.main_noise_scale = (preset.main_noise_scale_x as f64, preset.main_noise_scale_y as f64, preset.main_noise_scale_z as f64);
+        self.depth_noise_scale = (preset.depth_noise_scale_x as f64, preset.depth_noise_scale_z as f64);
+        self.lower_limit_scale = preset.lower_limit_scale as f64;
+        self.upper_limit_scale = preset.upper_limit_scale as f64;
+        self.base_size = preset.base_size as f64;
+        self.stretch_y = preset.stretch_y as f64;
+        self.biome_depth_weight = preset.biome_depth_weight;
+        self.biome_depth_offset = preset.biome_depth_offset;
+        self.biome_scale_weight = preset.biome_scale_weight;
+        self.biome_scale_offset = preset.biome_scale_offset;
+        self.generator.sea_level = preset.sea_level;
+        self.use_caves = preset.use_caves;
+        self.use_ravines = preset.use_ravines;
+        self.fixed_biome = VANILLA_BIOMES.iter()
+            .copied()
+            .find(|biome| biome.get_id() == preset.fixed_biome);
+        self.biome_size = preset.biome_size;
+        self.river_size = preset.river_size;
+    }
+
 }
 
 static BIOMES_PROPERTIES: Lazy<BiomePropertyMap> = Lazy::new(|| {
@@ -677,6 +932,7 @@ static BIOMES_PROPERTIES: Lazy<BiomePropertyMap> = Lazy::new(|| {
         mushroom_count: u16,
         sugar_cane_count: u16,
         cactus_count: u16,
+        desert_well: bool,
         tree_feature_type: TreeFeatureType
     }
 
@@ -704,6 +960,7 @@ static BIOMES_PROPERTIES: Lazy<BiomePropertyMap> = Lazy::new(|| {
                 mushroom_count: 0,
                 sugar_cane_count: 0,
                 cactus_count: 0,
+                desert_well: false,
                 tree_feature_type: TreeFeatureType::Default
             }
         }
@@ -724,8 +981,7 @@ static BIOMES_PROPERTIES: Lazy<BiomePropertyMap> = Lazy::new(|| {
                 features: {
 
                     let mut chain = FeatureChain::new();
-                    chain.push(LakeFeature::new(WATER.get_default_state(), &BIOMES_PROPERTIES).distributed_uniform(0, 128).optional(4));
-                    chain.push(LakeFeature::new(LAVA.get_default_state(), &BIOMES_PROPERTIES).distributed(LavaLakeDistrib).optional(8));
+                    chain.push(LakeFeature::new_vanilla_pair(&BIOMES_PROPERTIES));
                     chain.push(DungeonFeature.distributed_uniform(0, 128).repeated(8));
 
                     chain.push(VeinFeature::new(DIRT.get_default_state(), 32).distributed_uniform(0, 128).repeated(20));
@@ -852,6 +1108,10 @@ static BIOMES_PROPERTIES: Lazy<BiomePropertyMap> = Lazy::new(|| {
                             .repeated(self.sugar_cane_count));
                     }
 
+                    if self.desert_well {
+                        chain.push(DesertWellFeature::new_vanilla());
+                    }
+
                     // chain.push(DebugChunkFeature);
 
                     chain
@@ -872,6 +1132,7 @@ static BIOMES_PROPERTIES: Lazy<BiomePropertyMap> = Lazy::new(|| {
         c.tree_count = None;
         c.dead_bush_count = 2;
         c.sugar_cane_count = 50;
+        c.desert_well = true;
     });
     let forest_config = BiomeConfig::with(|c| {
         c.tree_count = Some(10);
@@ -918,7 +1179,7 @@ static BIOMES_PROPERTIES: Lazy<BiomePropertyMap> = Lazy::new(|| {
     map.insert(&RIVER, default_config.build().height(-0.5, 0.0));
     map.insert(&FROZEN_OCEAN, default_config.build().height(-1.0, 0.5).temp(0.0));
     map.insert(&FROZEN_RIVER, default_config.build().height(-0.5, 0.0).temp(0.0));
-    map.insert(&SNOWY_TUNDRA, plains_config.build().temp(0.0));
+    map.insert(&SNOWY_PLAINS, plains_config.build().temp(0.0));
     map.insert(&SNOWY_MOUNTAINS, default_config.build().height(0.2, 1.2).temp(0.0));
     map.insert(&MUSHROOM_FIELDS, mushroom_config.build().height(0.2, 1.0).temp(0.9).blocks(&MYCELIUM, &DIRT));
     map.insert(&MUSHROOM_FIELD_SHORE, mushroom_config.build().height(-1.0, 0.1).temp(0.9).blocks(&MYCELIUM, &DIRT));
@@ -978,3 +1239,150 @@ impl RepeatCount for TreeRepeatCount {
         self.0 + (rand.next_int_bounded(10) == 0) as u16
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+
+    use std::time::{Duration, Instant};
+
+    use mc_core::world::source::{LevelSource, ChunkLoadRequest};
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::level::LevelEnv;
+    use mc_vanilla::ext::VanillaLevelEnv;
+
+    use crate::gen::legacy::{LegacyGenLevelSource, MixedGenerator};
+    use crate::gen::biome_source::FixedBiomeSource;
+
+    use super::*;
+
+    /// A feature generator placing a diamond block in the corner of each feature chunk.
+    struct MarkerFeatureGenerator;
+
+    impl FeatureGenerator for MarkerFeatureGenerator {
+        type Chunk = LegacyProtoChunk;
+        fn decorate(&mut self, mut level: QuadLevelView<Self::Chunk>, _cx: i32, _cz: i32, x: i32, z: i32) {
+            level.set_block_at(x, 200, z, DIAMOND_BLOCK.get_default_state()).unwrap();
+        }
+    }
+
+    #[test]
+    fn mixed_generator() {
+
+        let r102 = Arc::new(R102Generator::new(0));
+        let generator = MixedGenerator::new(
+            || FixedBiomeSource::new(&PLAINS),
+            move || r102.build_noise(),
+            || MarkerFeatureGenerator
+        );
+
+        let mut source = LegacyGenLevelSource::new(generator, 2);
+        source.request_chunk_load(ChunkLoadRequest {
+            env: LevelEnv::vanilla(),
+            height: ChunkHeight::new(0, 15),
+            cx: 0,
+            cz: 0
+        }).unwrap();
+
+        let start = Instant::now();
+        let chunk = loop {
+            match source.poll_chunk() {
+                Some(Ok(chunk)) if chunk.get_position() == (0, 0) => break chunk,
+                Some(Ok(_)) => {}
+                Some(Err(_)) => panic!("Failed to generate chunk."),
+                None => {
+                    assert!(start.elapsed() < Duration::from_secs(30), "Chunk generation timed out.");
+                    std::thread::sleep(Duration::from_millis(1));
+                }
+            }
+        };
+
+        assert!(chunk.iter_biomes().all(|biome| biome == &PLAINS));
+        assert_eq!(chunk.get_block_at(0, 0, 0).unwrap(), BEDROCK.get_default_state());
+        assert_eq!(chunk.get_block_at(8, 200, 8).unwrap(), DIAMOND_BLOCK.get_default_state());
+        assert_ne!(chunk.get_block_at(9, 200, 8).unwrap(), DIAMOND_BLOCK.get_default_state());
+
+    }
+
+    #[test]
+    fn world_types() {
+
+        let generate = |generator: R102Generator, biome: &'static Biome| {
+            let req = ChunkLoadRequest { env: LevelEnv::vanilla(), height: ChunkHeight::new(0, 15), cx: 0, cz: 0 };
+            generator.build_noise().generate(req.build_proto_chunk(), &mut FixedBiomeSource::new(biome))
+        };
+
+        let count_stone = |generator: R102Generator| {
+            let chunk = generate(generator, &MOUNTAINS);
+            (64..128).flat_map(|y| (0..16).flat_map(move |x| (0..16).map(move |z| (x, y, z))))
+                .filter(|&(x, y, z)| chunk.inner.get_block_at(x, y, z).unwrap() == STONE.get_default_state())
+                .count()
+        };
+
+        let default = count_stone(R102Generator::new(0));
+        let amplified = count_stone(R102Generator::with_options(&LevelGeneratorOptions::new(0)
+            .with_generator_type(GeneratorType::Amplified)));
+
+        assert!(amplified > default, "Amplified terrain should be higher ({} <= {}).", amplified, default);
+
+        let customized = R102Generator::with_options(&LevelGeneratorOptions::new(0)
+            .with_generator_type(GeneratorType::Customized)
+            .with_generator_options(r#"{"seaLevel":100,"biomeDepthOffset":-1.5}"#));
+
+        let chunk = generate(customized, &PLAINS);
+        assert_eq!(chunk.inner.get_block_at(8, 99, 8).unwrap(), WATER.get_default_state());
+        assert!(chunk.inner.get_block_at(8, 100, 8).unwrap().is_block(&AIR));
+
+    }
+
+    #[test]
+    fn generator_settings() {
+
+        let generator = R102Generator::with_options(&LevelGeneratorOptions::new(0)
+            .with_generator_type(GeneratorType::Customized)
+            .with_generator_options(r#"{"biomeDepthOffset":-1.5}"#))
+            .with_settings(GeneratorSettings::new()
+                .with_sea_level(100)
+                .with_default_block(NETHERRACK.get_default_state())
+                .with_default_fluid(LAVA.get_default_state()));
+
+        assert_eq!(generator.get_settings().sea_level, 100);
+
+        let req = ChunkLoadRequest { env: LevelEnv::vanilla(), height: ChunkHeight::new(0, 15), cx: 0, cz: 0 };
+        let chunk = generator.build_noise().generate(req.build_proto_chunk(), &mut FixedBiomeSource::new(&PLAINS));
+
+        assert_eq!(chunk.inner.get_block_at(8, 99, 8).unwrap(), LAVA.get_default_state());
+        assert!(chunk.inner.get_block_at(8, 100, 8).unwrap().is_block(&AIR));
+        assert!((0..16).any(|y| chunk.inner.get_block_at(8, y, 8).unwrap() == NETHERRACK.get_default_state()));
+        assert!(chunk.inner.iter_loaded_sub_chunks()
+            .flat_map(|(_, sub_chunk)| sub_chunk.iter_blocks())
+            .all(|state| !state.is_block(&STONE) && !state.is_block(&WATER)));
+
+    }
+
+
+    #[test]
+    fn sample_heights() {
+
+        let generator = R102Generator::new(0);
+        let mut noise = generator.build_noise();
+        let mut biome_source = FixedBiomeSource::new(&PLAINS);
+
+        let req = ChunkLoadRequest { env: LevelEnv::vanilla(), height: ChunkHeight::new(0, 15), cx: 3, cz: -2 };
+        let mut chunk = req.build_proto_chunk();
+        noise.generate_terrain(&mut *chunk, &mut biome_source);
+
+        let heights = noise.sample_heights(3, -2, &mut biome_source);
+        for x in 0..16 {
+            for z in 0..16 {
+                let expected = (0..128).rev()
+                    .find(|&y| chunk.get_block(x, y, z).unwrap() == STONE.get_default_state())
+                    .map(|y| y as i32 + 1)
+                    .unwrap_or(0);
+                assert_eq!(*heights.get(x as usize, z as usize), expected);
+            }
+        }
+
+    }
+
+}
@@ -0,0 +1,65 @@
+//! Generator settings shared by the terrain, carvers and features of a generator, these
+//! settings were implicit in older versions but can be changed by dimensions and datapacks.
+
+use once_cell::sync::Lazy;
+
+use mc_core::block::BlockState;
+
+use mc_vanilla::block::{STONE, WATER};
+
+
+/// Default settings, used by level views that are not bound to a generator.
+pub(crate) static DEFAULT_SETTINGS: Lazy<GeneratorSettings> = Lazy::new(GeneratorSettings::new);
+
+
+/// Common settings of a generator, defaults are the settings of the vanilla overworld.
+#[derive(Debug, Clone)]
+pub struct GeneratorSettings {
+    /// The level below which (exclusive) the terrain is filled with the default fluid.
+    pub sea_level: i32,
+    /// The level below which (inclusive) legacy carvers fill carved blocks with lava.
+    pub lava_level: i32,
+    /// The block used to fill the terrain.
+    pub default_block: &'static BlockState,
+    /// The fluid used to fill the terrain below the sea level.
+    pub default_fluid: &'static BlockState
+}
+
+impl GeneratorSettings {
+
+    pub fn new() -> Self {
+        Self {
+            sea_level: 63,
+            lava_level: 10,
+            default_block: STONE.get_default_state(),
+            default_fluid: WATER.get_default_state()
+        }
+    }
+
+    pub fn with_sea_level(mut self, sea_level: i32) -> Self {
+        self.sea_level = sea_level;
+        self
+    }
+
+    pub fn with_lava_level(mut self, lava_level: i32) -> Self {
+        self.lava_level = lava_level;
+        self
+    }
+
+    pub fn with_default_block(mut self, default_block: &'static BlockState) -> Self {
+        self.default_block = default_block;
+        self
+    }
+
+    pub fn with_default_fluid(mut self, default_fluid: &'static BlockState) -> Self {
+        self.default_fluid = default_fluid;
+        self
+    }
+
+}
+
+impl Default for GeneratorSettings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -3,7 +3,7 @@ use super::{Layer, LayerCache, LayerRand};
 use mc_core::biome::Biome;
 use mc_vanilla::biome::{
     PLAINS, DESERT, FOREST, MOUNTAINS, SWAMP, TAIGA, JUNGLE, DESERT_HILLS, WOODED_HILLS,
-    TAIGA_HILLS, SNOWY_TUNDRA, SNOWY_MOUNTAINS, JUNGLE_HILLS, MUSHROOM_FIELDS, OCEAN,
+    TAIGA_HILLS, SNOWY_PLAINS, SNOWY_MOUNTAINS, JUNGLE_HILLS, MUSHROOM_FIELDS, OCEAN,
     MUSHROOM_FIELD_SHORE, RIVER, BEACH, MOUNTAIN_EDGE, FROZEN_RIVER
 };
 
@@ -116,7 +116,7 @@ where
                 _ if biome == &FOREST => Some(&WOODED_HILLS),  // Forest hills before 1.13
                 _ if biome == &TAIGA => Some(&TAIGA_HILLS),
                 _ if biome == &PLAINS => Some(&FOREST),
-                _ if biome == &SNOWY_TUNDRA => Some(&SNOWY_MOUNTAINS),
+                _ if biome == &SNOWY_PLAINS => Some(&SNOWY_MOUNTAINS),
                 _ if biome == &JUNGLE => Some(&JUNGLE_HILLS),
                 _ => None
             };
@@ -296,7 +296,7 @@ where
 
         if biome != &OCEAN && self.river_parent.next(x, z) {
             biome = match () {
-                _ if biome == &SNOWY_TUNDRA => &FROZEN_RIVER,
+                _ if biome == &SNOWY_PLAINS => &FROZEN_RIVER,
                 _ if biome == &MUSHROOM_FIELDS => &MUSHROOM_FIELD_SHORE,
                 _ if biome == &MUSHROOM_FIELD_SHORE => &MUSHROOM_FIELD_SHORE,
                 _ => &RIVER
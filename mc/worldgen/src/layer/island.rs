@@ -1,6 +1,6 @@
 use super::{Layer, LayerCache, LayerRand};
 
-use mc_vanilla::biome::{PLAINS, OCEAN, SNOWY_TUNDRA, FROZEN_OCEAN, MUSHROOM_FIELDS};
+use mc_vanilla::biome::{PLAINS, OCEAN, SNOWY_PLAINS, FROZEN_OCEAN, MUSHROOM_FIELDS};
 use mc_core::biome::Biome;
 
 
@@ -110,7 +110,7 @@ where
 
                 if rand.next_int(3) == 0 {
                     to_set
-                } else if to_set == &SNOWY_TUNDRA {
+                } else if to_set == &SNOWY_PLAINS {
                     // Snowy Tundra is the modern name of Ice plains
                     &FROZEN_OCEAN
                 } else {
@@ -119,7 +119,7 @@ where
 
             } else if !is_ocean(center) && (is_ocean(sw) || is_ocean(nw) || is_ocean(se) || is_ocean(ne)) {
                 if rand.next_int(5) == 0 {
-                    if center == &SNOWY_TUNDRA {
+                    if center == &SNOWY_PLAINS {
                         &FROZEN_OCEAN
                     } else {
                         &OCEAN
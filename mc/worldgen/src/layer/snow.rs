@@ -1,6 +1,6 @@
 use super::{Layer, LayerRand};
 
-use mc_vanilla::biome::{PLAINS, SNOWY_TUNDRA};
+use mc_vanilla::biome::{PLAINS, SNOWY_PLAINS};
 use mc_core::biome::Biome;
 
 
@@ -35,7 +35,7 @@ where
         if biome == &PLAINS {
             self.rand.init_chunk_seed(x, z);
             if self.rand.next_int(5) == 0 {
-                biome = &SNOWY_TUNDRA;
+                biome = &SNOWY_PLAINS;
             }
         }
         biome
@@ -1,6 +1,9 @@
 
 pub mod noise;
 pub mod view;
+pub mod debug;
+pub mod seed;
+pub mod place;
 
 pub mod structure;
 pub mod feature;
@@ -4,6 +4,8 @@ use mc_core::math::lerp;
 
 use std::mem::MaybeUninit;
 
+pub use mc_core::noise::{SimplexNoise, BIOME_INFO_NOISE};
+
 
 pub type NoiseRect = Rect<f64>;
 pub type NoiseCube = Cube<f64>;
@@ -0,0 +1,187 @@
+//! Common rules for placing blocks from features and structure templates.
+//!
+//! The block placer skips structure voids, waterlogs blocks that are placed into water and
+//! delays the heightmaps updates of the modified columns until all blocks are placed.
+
+use std::collections::HashSet;
+
+use mc_core::block::BlockState;
+
+use mc_vanilla::block::fluid::get_fluid_state;
+use mc_vanilla::block::{STRUCTURE_VOID, PROP_WATERLOGGED};
+
+use crate::view::LevelView;
+
+
+/// A helper to place many blocks in a level view. Heightmaps of the modified columns are
+/// recomputed once when the placer is dropped, or when calling `flush`, blocks placed in
+/// views that don't expose their chunks are placed directly with `set_block_at`.
+pub struct BlockPlacer<'a> {
+    level: &'a mut dyn LevelView,
+    waterlogging: bool,
+    columns: HashSet<(i32, i32)>
+}
+
+impl<'a> BlockPlacer<'a> {
+
+    pub fn new(level: &'a mut dyn LevelView) -> Self {
+        Self {
+            level,
+            waterlogging: true,
+            columns: HashSet::new()
+        }
+    }
+
+    /// Enable or disable waterlogging of blocks placed into water sources, enabled by default.
+    pub fn with_waterlogging(mut self, waterlogging: bool) -> Self {
+        self.waterlogging = waterlogging;
+        self
+    }
+
+    /// Get the underlying level view, heightmaps of the columns modified by this placer are
+    /// not yet updated until the next flush.
+    #[inline]
+    pub fn level(&self) -> &dyn LevelView {
+        &*self.level
+    }
+
+    /// Place a block, returning true if the block has been placed. Structure voids are never
+    /// placed and blocks that can be waterlogged are waterlogged if the replaced block is a
+    /// water source.
+    pub fn place(&mut self, x: i32, y: i32, z: i32, mut state: &'static BlockState) -> bool {
+
+        if state.is_block(&STRUCTURE_VOID) {
+            return false;
+        }
+
+        if self.waterlogging && state.get(&PROP_WATERLOGGED) == Some(false) {
+            if let Ok(old_state) = self.level.get_block_at(x, y, z) {
+                if is_water_source(old_state) {
+                    state = state.with(&PROP_WATERLOGGED, true).unwrap();
+                }
+            }
+        }
+
+        match self.level.get_chunk_at_mut(x, z) {
+            Some(chunk) => {
+                let placed = chunk.ensure_sub_chunk_at(y)
+                    .and_then(|sub_chunk| sub_chunk.set_block((x & 15) as u8, (y & 15) as u8, (z & 15) as u8, state))
+                    .is_ok();
+                if placed {
+                    self.columns.insert((x, z));
+                }
+                placed
+            }
+            None => self.level.set_block_at(x, y, z, state).is_ok()
+        }
+
+    }
+
+    /// Recompute heightmaps of all columns modified since the last flush.
+    pub fn flush(&mut self) {
+        for (x, z) in self.columns.drain() {
+            if let Some(chunk) = self.level.get_chunk_at_mut(x, z) {
+                chunk.recompute_heightmap_column((x & 15) as u8, (z & 15) as u8);
+            }
+        }
+    }
+
+}
+
+impl Drop for BlockPlacer<'_> {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+
+/// Return true if the given state contains a water source, this includes waterlogged blocks
+/// and blocks that are always in water.
+pub fn is_water_source(state: &'static BlockState) -> bool {
+    let fluid = get_fluid_state(state);
+    fluid.is_water() && fluid.is_source()
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use mc_core::world::chunk::{Chunk, ChunkHeight, ChunkResult};
+    use mc_core::world::source::{ChunkLoadRequest, ProtoChunk};
+    use mc_core::world::level::LevelEnv;
+    use mc_core::heightmap::HeightmapType;
+    use mc_core::biome::Biome;
+
+    use mc_vanilla::heightmap::WORLD_SURFACE;
+    use mc_vanilla::ext::VanillaLevelEnv;
+    use mc_vanilla::block::{STONE, CHEST, OAK_STAIRS, WATER, PROP_LIQUID_LEVEL};
+
+    use super::*;
+
+    /// A view of a single chunk at 0/0.
+    struct ChunkView(ProtoChunk);
+
+    impl LevelView for ChunkView {
+
+        fn get_env(&self) -> &Arc<LevelEnv> {
+            self.0.get_env()
+        }
+
+        fn get_chunk(&self, cx: i32, cz: i32) -> Option<&Chunk> {
+            (cx == 0 && cz == 0).then_some(&*self.0)
+        }
+
+        fn get_chunk_mut(&mut self, cx: i32, cz: i32) -> Option<&mut Chunk> {
+            (cx == 0 && cz == 0).then_some(&mut *self.0)
+        }
+
+        fn set_block_at(&mut self, x: i32, y: i32, z: i32, state: &'static BlockState) -> ChunkResult<()> {
+            self.0.set_block_at(x, y, z, state)
+        }
+
+        fn get_block_at(&self, x: i32, y: i32, z: i32) -> ChunkResult<&'static BlockState> {
+            self.0.get_block_at(x, y, z)
+        }
+
+        fn get_biome_at(&self, x: i32, y: i32, z: i32) -> ChunkResult<&'static Biome> {
+            self.0.get_biome_at(x, y, z)
+        }
+
+        fn get_heightmap_column_at(&self, heightmap_type: &'static HeightmapType, x: i32, z: i32) -> ChunkResult<i32> {
+            self.0.get_heightmap_column_at(heightmap_type, x, z)
+        }
+
+    }
+
+    #[test]
+    fn block_placer() {
+
+        let mut view = ChunkView(ChunkLoadRequest {
+            env: LevelEnv::vanilla(),
+            height: ChunkHeight::new(0, 15),
+            cx: 0,
+            cz: 0
+        }.build_proto_chunk());
+
+        view.set_block_at(1, 10, 1, WATER.get_default_state()).unwrap();
+        let flowing_water = WATER.get_default_state().with(&PROP_LIQUID_LEVEL, 3).unwrap();
+        view.set_block_at(2, 10, 1, flowing_water).unwrap();
+
+        {
+            let mut placer = BlockPlacer::new(&mut view);
+            assert!(!placer.place(0, 20, 0, STRUCTURE_VOID.get_default_state()));
+            assert!(placer.place(0, 20, 0, STONE.get_default_state()));
+            assert!(placer.place(1, 10, 1, CHEST.get_default_state()));
+            assert!(placer.place(2, 10, 1, OAK_STAIRS.get_default_state()));
+            assert_eq!(placer.level().get_heightmap_column_at(&WORLD_SURFACE, 0, 0).unwrap(), 0);
+        }
+
+        assert_eq!(view.get_heightmap_column_at(&WORLD_SURFACE, 0, 0).unwrap(), 21);
+        assert_eq!(view.get_block_at(1, 10, 1).unwrap().get(&PROP_WATERLOGGED), Some(true));
+        assert_eq!(view.get_block_at(2, 10, 1).unwrap().get(&PROP_WATERLOGGED), Some(false));
+
+    }
+
+}
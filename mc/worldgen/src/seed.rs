@@ -0,0 +1,100 @@
+//! Seed derivation utilities, these are the exact derivations used by vanilla to compute
+//! the seeds of randoms from the world seed. Randoms should be constructed (or reset) with
+//! these seeds to generate the same world as vanilla.
+
+use std::num::Wrapping;
+
+use mc_core::rand::JavaRandom;
+
+
+/// Return the seed used to generate the terrain and surface of a chunk, this seed doesn't
+/// depend on the world seed.
+///
+/// Valid for: 1.2 to 1.17.1
+pub fn chunk_seed(cx: i32, cz: i32) -> i64 {
+    region_seed(0, cx, cz, 0)
+}
+
+/// Return the seed used to populate (decorate) a chunk before 1.13, the given chunk is the
+/// population chunk.
+///
+/// Valid for: 1.2 to 1.12.2
+pub fn legacy_population_seed(world_seed: i64, cx: i32, cz: i32) -> i64 {
+    let mut rand = JavaRandom::new(world_seed);
+    let a = Wrapping(rand.next_long() / 2 * 2 + 1);
+    let b = Wrapping(rand.next_long() / 2 * 2 + 1);
+    (Wrapping(cx as i64) * a + Wrapping(cz as i64) * b).0 ^ world_seed
+}
+
+/// Return the decoration seed of a chunk since 1.13, the coordinates are the block
+/// coordinates of the minimum corner of the decorated chunk. This seed must then be
+/// salted for each feature using `feature_seed`.
+///
+/// Valid for: 1.13 and later
+pub fn decoration_seed(world_seed: i64, x: i32, z: i32) -> i64 {
+    let mut rand = JavaRandom::new(world_seed);
+    let a = Wrapping(rand.next_long() | 1);
+    let b = Wrapping(rand.next_long() | 1);
+    (Wrapping(x as i64) * a + Wrapping(z as i64) * b).0 ^ world_seed
+}
+
+/// Return the seed of a single feature from the decoration seed of its chunk, the index is
+/// the index of the feature in its decoration step and the step is the index of the step.
+///
+/// Valid for: 1.13 and later
+pub fn feature_seed(decoration_seed: i64, index: i32, step: i32) -> i64 {
+    decoration_seed
+        .wrapping_add(index as i64)
+        .wrapping_add(10000 * step as i64)
+}
+
+/// Return the seed used by large features (carvers and structures starts) for the given
+/// chunk, see `Structure::generate_in`.
+pub fn large_feature_seed(world_seed: i64, cx: i32, cz: i32) -> i64 {
+    let mut rand = JavaRandom::new(world_seed);
+    let a = Wrapping(rand.next_long());
+    let b = Wrapping(rand.next_long());
+    ((Wrapping(cx as i64) * a) ^ (Wrapping(cz as i64) * b) ^ Wrapping(world_seed)).0
+}
+
+/// Return the seed of a region with a salt, this is used to place structures in regions
+/// of chunks (see `RandomSpread`) but also with single chunks.
+pub fn region_seed(world_seed: i64, rx: i32, rz: i32, salt: i32) -> i64 {
+    (rx as i64).wrapping_mul(341873128712)
+        .wrapping_add((rz as i64).wrapping_mul(132897987541))
+        .wrapping_add(world_seed)
+        .wrapping_add(salt as i64)
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn known_seeds() {
+
+        // These values are computed with the Java implementation.
+        assert_eq!(JavaRandom::new(0).next_long(), -4962768465676381896);
+
+        assert_eq!(chunk_seed(1, 0), 0x4f9939f508);
+        assert_eq!(chunk_seed(0, 1), 0x1ef1565bd5);
+
+        assert_eq!(legacy_population_seed(0, 3, -7), -9054613716930535822);
+        assert_eq!(legacy_population_seed(12345, 3, -7), -8218855382820530819);
+        assert_eq!(legacy_population_seed(-4172144997902289642, 3, -7), -673827928780504838);
+
+        assert_eq!(decoration_seed(0, 48, -112), 2700133118787839776);
+        assert_eq!(decoration_seed(12345, 48, -112), -2374477609161563015);
+        assert_eq!(feature_seed(decoration_seed(12345, 48, -112), 2, 9), -2374477609161473013);
+
+        assert_eq!(large_feature_seed(0, 3, -7), 7032215459506559334);
+        assert_eq!(large_feature_seed(-4172144997902289642, 3, -7), 731530307568742548);
+
+        assert_eq!(region_seed(12345, 3, -7, 10387312), 95343873006);
+        assert_eq!(region_seed(-4172144997902289642, 3, -7, 10387312), -4172144902558428981);
+
+    }
+
+}
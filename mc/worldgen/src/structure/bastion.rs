@@ -0,0 +1,71 @@
+//! Bastion remnant structure, assembled from the vanilla bastion template pools.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use mc_core::world::anvil::decode::DecodeError;
+use mc_core::world::level::LevelEnv;
+use mc_core::rand::JavaRandom;
+
+use super::jigsaw::{self, JigsawConfig, TemplatePools};
+use super::piece::{StructurePiece, StructureStart};
+use super::fortress::NETHER_SPREAD;
+use super::{new_start_random, PieceStructure};
+
+
+/// Name of the vanilla pool containing bastion starts.
+pub const BASTION_START_POOL: &str = "minecraft:bastion/starts";
+
+
+/// The bastion remnant structure, generated in regions without nether fortress. Vanilla
+/// bastions don't override mob spawns.
+///
+/// Valid for: 1.16.2 to 1.16.5
+pub struct BastionRemnant {
+    seed: i64,
+    pools: Arc<TemplatePools>
+}
+
+impl BastionRemnant {
+
+    /// Construct a bastion remnant structure using the given pools, which must contain the
+    /// bastion start pool.
+    pub fn new(seed: i64, pools: Arc<TemplatePools>) -> Self {
+        Self { seed, pools }
+    }
+
+    /// Load the vanilla bastion pools from the game's `data` directory, see
+    /// `TemplatePools::load`.
+    pub fn load_vanilla(seed: i64, data_dir: &Path, env: &LevelEnv) -> Result<Self, DecodeError> {
+        Ok(Self::new(seed, Arc::new(TemplatePools::load(data_dir, BASTION_START_POOL, env)?)))
+    }
+
+}
+
+impl PieceStructure for BastionRemnant {
+
+    fn get_range(&self) -> i32 {
+        8
+    }
+
+    fn generate_start(&self, cx: i32, cz: i32) -> Option<StructureStart> {
+
+        let mut rand = JavaRandom::new(0);
+        if NETHER_SPREAD.get_start_chunk(self.seed, cx, cz, &mut rand) != (cx, cz) || rand.next_int_bounded(5) < 2 {
+            return None;
+        }
+
+        let mut rand = new_start_random(self.seed, cx, cz);
+        let config = JigsawConfig::new(BASTION_START_POOL, 6);
+        let pieces = jigsaw::assemble(&self.pools, &config, cx * 16, 33, cz * 16, &mut rand, &|_, _| 33);
+        if pieces.is_empty() {
+            None
+        } else {
+            Some(StructureStart::new(pieces.into_iter()
+                .map(|piece| Box::new(piece) as Box<dyn StructurePiece>)
+                .collect()))
+        }
+
+    }
+
+}
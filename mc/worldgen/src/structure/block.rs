@@ -0,0 +1,361 @@
+//! Structure blocks behaviour. Structure blocks in save mode capture a region of a level into
+//! a template saved in the `generated` directory of the world, structure blocks in load mode
+//! place a saved template with their rotation, mirror and integrity. Structure blocks in
+//! corner mode delimit the region saved by structure blocks in save mode with the same name.
+
+use std::path::{Path, PathBuf};
+use std::io;
+
+use hecs::Entity;
+use thiserror::Error;
+
+use mc_core::world::anvil::decode::DecodeError;
+use mc_core::world::level::{Level, BaseBlockEntity};
+use mc_core::pos::BlockPos;
+use mc_core::rand::JavaRandom;
+
+use mc_vanilla::block::StructureMode;
+use mc_vanilla::block_entity::StructureBlockEntity;
+
+use super::template::{StructureTemplate, TemplatePlaceSettings};
+use super::piece::BoundingBox;
+
+
+/// Maximum distance on each axis between a structure block in save mode and the corner
+/// structure blocks delimiting its structure.
+pub const MAX_CORNER_DISTANCE: i32 = 80;
+
+
+#[derive(Error, Debug)]
+pub enum StructureBlockError {
+    #[error("No structure block at {0:?}.")]
+    NoStructureBlock(BlockPos),
+    #[error("The structure block is not in {0:?} mode.")]
+    InvalidMode(StructureMode),
+    #[error("Invalid structure name '{0}'.")]
+    InvalidName(String),
+    #[error("The structure has no size.")]
+    NoSize,
+    #[error("The structure is in unloaded chunks.")]
+    ChunkUnloaded,
+    #[error("Structure '{0}' not found.")]
+    NotFound(String),
+    #[error("The structure cannot be read or written: {0}")]
+    Io(#[from] io::Error),
+    #[error("The structure cannot be decoded: {0}")]
+    Decode(#[from] DecodeError)
+}
+
+
+/// Templates saved by structure blocks, they are stored in a directory, usually the
+/// `generated` directory of the world, at `<namespace>/structures/<path>.nbt`.
+pub struct GeneratedTemplates {
+    dir: PathBuf
+}
+
+impl GeneratedTemplates {
+
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self {
+            dir: dir.into()
+        }
+    }
+
+    #[inline]
+    pub fn get_dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Return the path of the template with the given name, the namespace defaults to
+    /// `minecraft`. Return `None` if the name is not a valid resource location or if its
+    /// path would escape the directory.
+    pub fn get_template_path(&self, name: &str) -> Option<PathBuf> {
+
+        let (namespace, path) = name.split_once(':').unwrap_or(("minecraft", name));
+
+        let valid_namespace = !namespace.is_empty() && namespace.chars()
+            .all(|c| matches!(c, 'a'..='z' | '0'..='9' | '_' | '-' | '.'));
+        let valid_path = path.split('/').all(|part| {
+            !matches!(part, "" | "." | "..") && part.chars()
+                .all(|c| matches!(c, 'a'..='z' | '0'..='9' | '_' | '-' | '.'))
+        });
+
+        if valid_namespace && valid_path {
+            Some(self.dir.join(namespace).join("structures").join(format!("{}.nbt", path)))
+        } else {
+            None
+        }
+
+    }
+
+    /// Load the template with the given name, using the environment of the given level.
+    pub fn load(&self, name: &str, level: &Level) -> Result<StructureTemplate, StructureBlockError> {
+        let path = self.get_template_path(name)
+            .ok_or_else(|| StructureBlockError::InvalidName(name.to_string()))?;
+        if !path.is_file() {
+            return Err(StructureBlockError::NotFound(name.to_string()));
+        }
+        Ok(StructureTemplate::from_file(&path, level.get_env())?)
+    }
+
+    /// Save the given template with the given name, replacing any previous template.
+    pub fn save(&self, name: &str, template: &StructureTemplate) -> Result<(), StructureBlockError> {
+        let path = self.get_template_path(name)
+            .ok_or_else(|| StructureBlockError::InvalidName(name.to_string()))?;
+        Ok(template.write_to_file(&path)?)
+    }
+
+}
+
+
+/// Return the structure block entity at the given position, if any.
+pub fn find_structure_block(level: &Level, pos: &BlockPos) -> Option<Entity> {
+    let chunk = level.chunks.get_chunk_at(pos.x, pos.z)?;
+    let ecs = &level.entities.ecs;
+    let found = chunk.iter_entities().find(|&entity| {
+        matches!(ecs.get::<BaseBlockEntity>(entity), Ok(base) if base.pos == *pos)
+            && ecs.get::<StructureBlockEntity>(entity).is_ok()
+    });
+    found
+}
+
+fn get_structure_block(level: &Level, pos: &BlockPos, mode: StructureMode) -> Result<Entity, StructureBlockError> {
+    let entity = find_structure_block(level, pos)
+        .ok_or_else(|| StructureBlockError::NoStructureBlock(pos.clone()))?;
+    if level.entities.ecs.get::<StructureBlockEntity>(entity).unwrap().get_mode() != mode {
+        return Err(StructureBlockError::InvalidMode(mode));
+    }
+    Ok(entity)
+}
+
+
+/// Save the structure of the structure block at the given position, the structure block
+/// must be in save mode and have a name and a size. The saved template is returned.
+pub fn save_structure(level: &Level, pos: &BlockPos, templates: &GeneratedTemplates) -> Result<StructureTemplate, StructureBlockError> {
+
+    let entity = get_structure_block(level, pos, StructureMode::Save)?;
+
+    let (name, offset, size) = {
+        let structure_block = level.entities.ecs.get::<StructureBlockEntity>(entity).unwrap();
+        if !structure_block.has_size() {
+            return Err(StructureBlockError::NoSize);
+        }
+        let size = structure_block.get_size();
+        (structure_block.get_name().to_string(), structure_block.get_offset().clone(), (size.x, size.y, size.z))
+    };
+
+    let template = StructureTemplate::capture(level, pos.x + offset.x, pos.y + offset.y, pos.z + offset.z, size)
+        .ok_or(StructureBlockError::ChunkUnloaded)?;
+
+    templates.save(&name, &template)?;
+    Ok(template)
+
+}
+
+
+/// Load the structure of the structure block at the given position, the structure block
+/// must be in load mode. The template is placed at the structure block's offset, with its
+/// rotation, mirror, integrity and seed. The size of the structure block is updated to the
+/// size of the template. The number of blocks placed is returned.
+pub fn load_structure(level: &Level, pos: &BlockPos, templates: &GeneratedTemplates) -> Result<usize, StructureBlockError> {
+
+    let entity = get_structure_block(level, pos, StructureMode::Load)?;
+    let name = level.entities.ecs.get::<StructureBlockEntity>(entity).unwrap().get_name().to_string();
+    let template = templates.load(&name, level)?;
+
+    let (offset, settings, mut rand) = {
+        let mut structure_block = level.entities.ecs.get_mut::<StructureBlockEntity>(entity).unwrap();
+        let (size_x, size_y, size_z) = template.get_size();
+        structure_block.set_size(BlockPos::new(size_x, size_y, size_z));
+        let settings = TemplatePlaceSettings::new()
+            .with_rotation(structure_block.get_rotation().into())
+            .with_mirror(structure_block.get_mirror().into())
+            .with_integrity(structure_block.get_integrity())
+            .with_air(true);
+        let rand = match structure_block.get_seed() {
+            0 => JavaRandom::new_seeded(),
+            seed => JavaRandom::new(seed)
+        };
+        (structure_block.get_offset().clone(), settings, rand)
+    };
+
+    Ok(template.place_in_level(level, &mut rand, pos.x + offset.x, pos.y + offset.y, pos.z + offset.z, &settings))
+
+}
+
+
+/// Detect the structure of the structure block at the given position from the corner
+/// structure blocks with the same name, up to `MAX_CORNER_DISTANCE` blocks away. The
+/// structure block must be in save mode, its offset and size are updated to the region
+/// between the corners, corners excluded. Like vanilla, if a single corner is found, the
+/// structure block itself is used as the other corner. Return false if there is no corner
+/// or if the corners are too close to each other.
+pub fn detect_structure_size(level: &Level, pos: &BlockPos) -> Result<bool, StructureBlockError> {
+
+    let entity = get_structure_block(level, pos, StructureMode::Save)?;
+    let name = level.entities.ecs.get::<StructureBlockEntity>(entity).unwrap().get_name().to_string();
+
+    let range = BoundingBox::new(
+        pos.x - MAX_CORNER_DISTANCE, pos.y - MAX_CORNER_DISTANCE, pos.z - MAX_CORNER_DISTANCE,
+        pos.x + MAX_CORNER_DISTANCE, pos.y + MAX_CORNER_DISTANCE, pos.z + MAX_CORNER_DISTANCE
+    );
+
+    let mut corners = level.entities.ecs.query::<(&BaseBlockEntity, &StructureBlockEntity)>()
+        .iter()
+        .filter(|(_, (base, structure_block))| {
+            structure_block.get_mode() == StructureMode::Corner &&
+                structure_block.get_name() == name &&
+                range.contains(base.pos.x, base.pos.y, base.pos.z)
+        })
+        .map(|(_, (base, _))| BoundingBox::new(base.pos.x, base.pos.y, base.pos.z, base.pos.x, base.pos.y, base.pos.z))
+        .collect::<Vec<_>>();
+
+    let mut bounding_box = match corners.pop() {
+        Some(bounding_box) => bounding_box,
+        None => return Ok(false)
+    };
+
+    if corners.is_empty() {
+        bounding_box.encapsulate(&BoundingBox::new(pos.x, pos.y, pos.z, pos.x, pos.y, pos.z));
+    } else {
+        for corner in &corners {
+            bounding_box.encapsulate(corner);
+        }
+    }
+
+    if bounding_box.get_size_x() <= 2 || bounding_box.get_size_y() <= 2 || bounding_box.get_size_z() <= 2 {
+        return Ok(false);
+    }
+
+    let mut structure_block = level.entities.ecs.get_mut::<StructureBlockEntity>(entity).unwrap();
+    structure_block.set_offset(BlockPos::new(
+        bounding_box.min_x - pos.x + 1,
+        bounding_box.min_y - pos.y + 1,
+        bounding_box.min_z - pos.z + 1
+    ));
+    structure_block.set_size(BlockPos::new(
+        bounding_box.get_size_x() - 2,
+        bounding_box.get_size_y() - 2,
+        bounding_box.get_size_z() - 2
+    ));
+    Ok(true)
+
+}
+
+
+/// Save or load the structures of all structure blocks of the level that have been triggered
+/// since the last call, structure blocks are triggered when they start receiving redstone
+/// power. Like vanilla, errors are ignored. Return the number of structures saved or loaded.
+pub fn update_triggered_structure_blocks(level: &Level, templates: &GeneratedTemplates) -> usize {
+
+    let mut triggered = level.entities.ecs.query::<(&BaseBlockEntity, &mut StructureBlockEntity)>()
+        .iter()
+        .filter_map(|(_, (base, structure_block))| {
+            if structure_block.take_triggered() {
+                Some((base.pos.clone(), structure_block.get_mode()))
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // Structure blocks are updated in a deterministic order.
+    triggered.sort_unstable_by_key(|(pos, _)| (pos.x, pos.y, pos.z));
+
+    triggered.into_iter()
+        .filter(|(pos, mode)| match mode {
+            StructureMode::Save => save_structure(level, pos, templates).is_ok(),
+            StructureMode::Load => load_structure(level, pos, templates).is_ok(),
+            _ => false
+        })
+        .count()
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs;
+
+    use mc_core::world::level::LevelEnv;
+    use mc_core::world::chunk::ChunkHeight;
+    use mc_core::world::source::{ChunkLoadRequest, NullLevelSource};
+    use mc_core::pos::Axis;
+    use mc_vanilla::block::{AIR, STONE, BONE_BLOCK, PROP_AXIS};
+    use mc_vanilla::block_entity::{STRUCTURE_BLOCK, StructureRotation};
+    use mc_vanilla::ext::VanillaLevelEnv;
+
+    use super::*;
+
+    fn spawn(level: &mut Level, pos: BlockPos, mode: StructureMode) -> Entity {
+        let entity = level.spawn_block_entity(&STRUCTURE_BLOCK, pos).unwrap();
+        let mut structure_block = level.entities.ecs.get_mut::<StructureBlockEntity>(entity).unwrap();
+        structure_block.set_name("test:pillar".to_string());
+        structure_block.set_mode(mode);
+        drop(structure_block);
+        entity
+    }
+
+    #[test]
+    fn structure_blocks() {
+
+        let env = LevelEnv::vanilla();
+        let height = ChunkHeight::new(0, 0);
+        let mut level = Level::new("overworld".to_string(), env.clone(), height, NullLevelSource);
+        for cx in 0..2 {
+            level.chunks.insert_chunk(ChunkLoadRequest { env: env.clone(), height, cx, cz: 0 }.build_chunk());
+        }
+
+        let dir = std::env::temp_dir().join(format!("mc-worldgen-structure-blocks-{}", std::process::id()));
+        let templates = GeneratedTemplates::new(&dir);
+        assert_eq!(templates.get_template_path("pillar"), Some(dir.join("minecraft/structures/pillar.nbt")));
+        assert!(templates.get_template_path("test:../pillar").is_none());
+        assert!(templates.get_template_path("Test:pillar").is_none());
+
+        // A horizontal pillar along X, delimited by two corners.
+        let bone_x = BONE_BLOCK.get_default_state().with(&PROP_AXIS, Axis::X).unwrap();
+        level.chunks.fill_region(&BoundingBox::new(1, 1, 1, 3, 1, 1), bone_x).unwrap();
+        level.chunks.set_block_at(1, 2, 1, STONE.get_default_state()).unwrap();
+        spawn(&mut level, BlockPos::new(0, 0, 0), StructureMode::Corner);
+        spawn(&mut level, BlockPos::new(4, 3, 2), StructureMode::Corner);
+
+        let save = spawn(&mut level, BlockPos::new(0, 5, 0), StructureMode::Save);
+        assert!(matches!(save_structure(&level, &BlockPos::new(0, 5, 0), &templates), Err(StructureBlockError::NoSize)));
+        assert!(detect_structure_size(&level, &BlockPos::new(0, 5, 0)).unwrap());
+        {
+            let structure_block = level.entities.ecs.get::<StructureBlockEntity>(save).unwrap();
+            assert_eq!(structure_block.get_offset(), &BlockPos::new(1, -4, 1));
+            assert_eq!(structure_block.get_size(), &BlockPos::new(3, 2, 1));
+        }
+
+        let template = save_structure(&level, &BlockPos::new(0, 5, 0), &templates).unwrap();
+        assert_eq!(template.get_size(), (3, 2, 1));
+        assert_eq!(template.get_blocks().len(), 6);
+        assert!(dir.join("test/structures/pillar.nbt").is_file());
+
+        // Load the pillar rotated, in the second chunk, air is also placed.
+        level.chunks.set_block_at(20, 2, 1, STONE.get_default_state()).unwrap();
+        let load = spawn(&mut level, BlockPos::new(20, 0, 0), StructureMode::Load);
+        {
+            let mut structure_block = level.entities.ecs.get_mut::<StructureBlockEntity>(load).unwrap();
+            structure_block.set_rotation(StructureRotation::Clockwise90);
+            structure_block.set_powered(true);
+        }
+        assert_eq!(update_triggered_structure_blocks(&level, &templates), 1);
+        assert_eq!(update_triggered_structure_blocks(&level, &templates), 0);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(level.entities.ecs.get::<StructureBlockEntity>(load).unwrap().get_size(), &BlockPos::new(3, 2, 1));
+        let bone_z = BONE_BLOCK.get_default_state().with(&PROP_AXIS, Axis::Z).unwrap();
+        for z in 0..3 {
+            assert!(level.chunks.get_block_at(20, 1, z).unwrap() == bone_z);
+        }
+        assert!(level.chunks.get_block_at(20, 2, 0).unwrap().is_block(&STONE));
+        assert!(level.chunks.get_block_at(20, 2, 1).unwrap().is_block(&AIR));
+
+        assert!(matches!(load_structure(&level, &BlockPos::new(0, 5, 0), &templates), Err(StructureBlockError::InvalidMode(StructureMode::Load))));
+
+    }
+
+}
@@ -0,0 +1,444 @@
+//! Configured carvers, loaded from `worldgen/configured_carver` JSON files of datapacks and
+//! mapped to the cave and ravine carvers.
+
+use std::path::Path;
+use std::fs::File;
+
+use nbt::CompoundTag;
+use serde_json::Value;
+
+use mc_core::world::anvil::decode::{decode_block_state, DecodeError};
+use mc_core::world::chunk::ChunkHeight;
+use mc_core::world::level::LevelEnv;
+use mc_core::block::BlockState;
+use mc_core::rand::JavaRandom;
+
+use mc_vanilla::block::*;
+
+use crate::gen::settings::GeneratorSettings;
+use crate::gen::biome::BiomePropertyMap;
+use crate::view::ProtoChunkView;
+use super::debug::PieceDebugger;
+use super::cave::CaveStructure;
+use super::ravine::RavineStructure;
+use super::Structure;
+
+
+/// A vertical position relative to the bottom or the top of a level.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum VerticalAnchor {
+    Absolute(i32),
+    AboveBottom(i32),
+    BelowTop(i32)
+}
+
+impl VerticalAnchor {
+
+    /// Resolve the absolute Y coordinate of this anchor for the given height.
+    pub fn resolve(self, height: ChunkHeight) -> i32 {
+        match self {
+            Self::Absolute(y) => y,
+            Self::AboveBottom(offset) => height.get_min_block() + offset,
+            Self::BelowTop(offset) => height.get_max_block() - offset
+        }
+    }
+
+    fn from_json(json: &Value) -> Option<Self> {
+        if let Some(y) = json["absolute"].as_i64() {
+            Some(Self::Absolute(y as i32))
+        } else if let Some(offset) = json["above_bottom"].as_i64() {
+            Some(Self::AboveBottom(offset as i32))
+        } else {
+            json["below_top"].as_i64().map(|offset| Self::BelowTop(offset as i32))
+        }
+    }
+
+}
+
+
+/// A distribution of Y coordinates.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum HeightProvider {
+    Constant(VerticalAnchor),
+    Uniform {
+        min: VerticalAnchor,
+        max: VerticalAnchor
+    },
+    BiasedToBottom {
+        min: VerticalAnchor,
+        max: VerticalAnchor,
+        inner: i32
+    },
+    VeryBiasedToBottom {
+        min: VerticalAnchor,
+        max: VerticalAnchor,
+        inner: i32
+    },
+    Trapezoid {
+        min: VerticalAnchor,
+        max: VerticalAnchor,
+        plateau: i32
+    }
+}
+
+impl HeightProvider {
+
+    /// Sample a Y coordinate, bounds are inclusive.
+    pub fn sample(&self, rand: &mut JavaRandom, height: ChunkHeight) -> i32 {
+
+        fn next_int_between(rand: &mut JavaRandom, min: i32, max: i32) -> i32 {
+            if min >= max { min } else { min + rand.next_int_bounded(max - min + 1) }
+        }
+
+        match *self {
+            Self::Constant(anchor) => anchor.resolve(height),
+            Self::Uniform { min, max } => {
+                next_int_between(rand, min.resolve(height), max.resolve(height))
+            }
+            Self::BiasedToBottom { min, max, inner } => {
+                let (min, max) = (min.resolve(height), max.resolve(height));
+                let bound = rand.next_int_bounded((max - min - inner + 1).max(1)) + inner;
+                min + rand.next_int_bounded(bound.max(1))
+            }
+            Self::VeryBiasedToBottom { min, max, inner } => {
+                let (min, max) = (min.resolve(height), max.resolve(height));
+                let a = next_int_between(rand, min + inner, max);
+                let b = next_int_between(rand, min, a - 1);
+                next_int_between(rand, min, b - 1 + inner)
+            }
+            Self::Trapezoid { min, max, plateau } => {
+                let (min, max) = (min.resolve(height), max.resolve(height));
+                let range = max - min;
+                if plateau >= range {
+                    next_int_between(rand, min, max)
+                } else {
+                    let a = (range - plateau) / 2;
+                    let b = range - a;
+                    min + next_int_between(rand, 0, b) + next_int_between(rand, 0, a)
+                }
+            }
+        }
+
+    }
+
+    fn from_json(json: &Value) -> Option<Self> {
+
+        if let Some(anchor) = VerticalAnchor::from_json(json) {
+            return Some(Self::Constant(anchor));
+        }
+
+        let min = VerticalAnchor::from_json(&json["min_inclusive"]);
+        let max = VerticalAnchor::from_json(&json["max_inclusive"]);
+
+        match json["type"].as_str()? {
+            "minecraft:constant" => VerticalAnchor::from_json(&json["value"]).map(Self::Constant),
+            "minecraft:uniform" => Some(Self::Uniform { min: min?, max: max? }),
+            "minecraft:biased_to_bottom" => Some(Self::BiasedToBottom {
+                min: min?,
+                max: max?,
+                inner: json["inner"].as_i64().unwrap_or(1) as i32
+            }),
+            "minecraft:very_biased_to_bottom" => Some(Self::VeryBiasedToBottom {
+                min: min?,
+                max: max?,
+                inner: json["inner"].as_i64().unwrap_or(1) as i32
+            }),
+            "minecraft:trapezoid" => Some(Self::Trapezoid {
+                min: VerticalAnchor::from_json(&json["min"])?,
+                max: VerticalAnchor::from_json(&json["max"])?,
+                plateau: json["plateau"].as_i64().unwrap_or(0) as i32
+            }),
+            _ => None
+        }
+
+    }
+
+}
+
+
+/// Debug settings of a carver, in debug mode the carved blocks are replaced by these
+/// states in order to visualize carvers.
+#[derive(Debug, Clone)]
+pub struct CarverDebugSettings {
+    pub debug_mode: bool,
+    pub air_state: &'static BlockState,
+    pub water_state: &'static BlockState,
+    pub lava_state: &'static BlockState,
+    pub barrier_state: &'static BlockState
+}
+
+impl CarverDebugSettings {
+
+    fn from_json(json: &Value, env: &LevelEnv) -> Result<Self, DecodeError> {
+
+        let decode_state = |name: &str, default: &'static BlockState| {
+            match &json[name] {
+                Value::Null => Ok(default),
+                json_state => decode_block_state_json(json_state, env)
+            }
+        };
+
+        Ok(Self {
+            debug_mode: json["debug_mode"].as_bool().unwrap_or(false),
+            air_state: decode_state("air_state", ACACIA_BUTTON.get_default_state())?,
+            water_state: decode_state("water_state", CANDLE.get_default_state().with(&PROP_LIT, true).unwrap())?,
+            lava_state: decode_state("lava_state", ORANGE_STAINED_GLASS.get_default_state())?,
+            barrier_state: decode_state("barrier_state", GLASS.get_default_state())?
+        })
+
+    }
+
+}
+
+
+/// Common configuration of carvers.
+#[derive(Debug, Clone)]
+pub struct CarverConfig {
+    /// Probability for a chunk to be the start of this carver.
+    pub probability: f32,
+    /// Distribution of the starting Y coordinate.
+    pub y: HeightProvider,
+    /// Carved blocks below this level are replaced by lava.
+    pub lava_level: VerticalAnchor,
+    pub debug_settings: Option<CarverDebugSettings>
+}
+
+impl CarverConfig {
+
+    /// Return the debug settings if the debug mode is enabled.
+    pub fn get_debug_settings(&self) -> Option<&CarverDebugSettings> {
+        self.debug_settings.as_ref().filter(|settings| settings.debug_mode)
+    }
+
+}
+
+
+/// Types of carvers.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CarverType {
+    Cave,
+    Ravine
+}
+
+
+/// A carver type with its configuration.
+#[derive(Debug, Clone)]
+pub struct ConfiguredCarver {
+    pub carver_type: CarverType,
+    pub config: CarverConfig
+}
+
+impl ConfiguredCarver {
+
+    /// Decode a configured carver from its JSON representation, block states of debug
+    /// settings are decoded in the given environment.
+    pub fn from_json(json: &Value, env: &LevelEnv) -> Result<Self, DecodeError> {
+
+        let carver_type = match json["type"].as_str() {
+            Some("minecraft:cave") => CarverType::Cave,
+            Some("minecraft:canyon") => CarverType::Ravine,
+            Some(carver_type) => return Err(DecodeError::Malformed(format!("Unsupported carver type '{}'.", carver_type))),
+            None => return Err(DecodeError::Malformed("Carver without type.".to_string()))
+        };
+
+        let json_config = &json["config"];
+        let malformed = |field: &str| DecodeError::Malformed(format!("Missing or invalid carver field '{}'.", field));
+
+        Ok(Self {
+            carver_type,
+            config: CarverConfig {
+                probability: json_config["probability"].as_f64().ok_or_else(|| malformed("probability"))? as f32,
+                y: HeightProvider::from_json(&json_config["y"]).ok_or_else(|| malformed("y"))?,
+                lava_level: VerticalAnchor::from_json(&json_config["lava_level"]).ok_or_else(|| malformed("lava_level"))?,
+                debug_settings: match &json_config["debug_settings"] {
+                    Value::Null => None,
+                    json_debug => Some(CarverDebugSettings::from_json(json_debug, env)?)
+                }
+            }
+        })
+
+    }
+
+    /// Load a configured carver from the given data directory, the carver is stored in
+    /// `<namespace>/worldgen/configured_carver/<path>.json`.
+    pub fn load(data_dir: &Path, name: &str, env: &LevelEnv) -> Result<Self, DecodeError> {
+        let (namespace, path) = name.split_once(':').unwrap_or(("minecraft", name));
+        let path = data_dir.join(namespace).join("worldgen/configured_carver").join(format!("{}.json", path));
+        let file = File::open(&path)
+            .map_err(|e| DecodeError::Malformed(format!("Can't open configured carver '{}': {}", name, e)))?;
+        let json: Value = serde_json::from_reader(file)
+            .map_err(|e| DecodeError::Malformed(format!("Invalid configured carver '{}': {}", name, e)))?;
+        Self::from_json(&json, env)
+    }
+
+    /// Carve the given chunk with this carver, like `Structure::generate_in`.
+    pub fn generate_in(
+        &self,
+        seed: i64,
+        chunk: &mut dyn ProtoChunkView,
+        range: i32,
+        biomes_map: &BiomePropertyMap,
+        settings: &GeneratorSettings,
+        debugger: Option<&PieceDebugger>
+    ) {
+        match self.carver_type {
+            CarverType::Cave => {
+                let mut cave = CaveStructure::new(biomes_map).with_config(&self.config).with_settings(settings);
+                if let Some(debugger) = debugger {
+                    cave = cave.with_debugger(debugger);
+                }
+                cave.generate_in(seed, chunk, range);
+            }
+            CarverType::Ravine => {
+                let mut ravine = RavineStructure::new(biomes_map).with_config(&self.config).with_settings(settings);
+                if let Some(debugger) = debugger {
+                    ravine = ravine.with_debugger(debugger);
+                }
+                ravine.generate_in(seed, chunk, range);
+            }
+        }
+    }
+
+}
+
+
+/// Internal context of carvers, with the blocks and level used to carve blocks, and the
+/// optional debugger recording the carved areas.
+pub(crate) struct CarveContext<'a> {
+    pub biomes_map: &'a BiomePropertyMap,
+    pub debugger: Option<&'a PieceDebugger>,
+    pub air: &'static BlockState,
+    pub lava: &'static BlockState,
+    pub lava_level: i32,
+    /// The default block of the terrain, that can be carved.
+    pub stone: &'static BlockState,
+    /// The default fluid of the terrain, carvers avoid it.
+    pub water: &'static BlockState
+}
+
+impl<'a> CarveContext<'a> {
+
+    /// Blocks of the given configuration, or the legacy ones if no configuration is given,
+    /// the legacy lava level and the terrain blocks are taken from the generator settings.
+    pub fn new(
+        biomes_map: &'a BiomePropertyMap,
+        debugger: Option<&'a PieceDebugger>,
+        config: Option<&CarverConfig>,
+        height: ChunkHeight,
+        settings: &GeneratorSettings
+    ) -> Self {
+        match config {
+            Some(config) => {
+                let debug = config.get_debug_settings();
+                Self {
+                    biomes_map,
+                    debugger,
+                    air: debug.map_or(AIR.get_default_state(), |debug| debug.air_state),
+                    lava: debug.map_or(LAVA.get_default_state(), |debug| debug.lava_state),
+                    lava_level: config.lava_level.resolve(height),
+                    stone: settings.default_block,
+                    water: settings.default_fluid
+                }
+            }
+            None => Self {
+                biomes_map,
+                debugger,
+                air: AIR.get_default_state(),
+                lava: LAVA.get_default_state(),
+                lava_level: settings.lava_level,
+                stone: settings.default_block,
+                water: settings.default_fluid
+            }
+        }
+    }
+
+}
+
+
+/// Decode a block state from its JSON representation, like in datapacks.
+fn decode_block_state_json(json: &Value, env: &LevelEnv) -> Result<&'static BlockState, DecodeError> {
+    let mut tag_block = CompoundTag::new();
+    tag_block.insert_str("Name", json["Name"].as_str()
+        .ok_or_else(|| DecodeError::Malformed("Block state without name.".to_string()))?);
+    if let Some(json_properties) = json["Properties"].as_object() {
+        let mut tag_properties = CompoundTag::new();
+        for (prop_name, prop_value) in json_properties {
+            if let Some(prop_value) = prop_value.as_str() {
+                tag_properties.insert_str(prop_name, prop_value);
+            }
+        }
+        tag_block.insert_compound_tag("Properties", tag_properties);
+    }
+    decode_block_state(&tag_block, env)
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use mc_core::world::source::ChunkLoadRequest;
+    use mc_vanilla::ext::VanillaLevelEnv;
+
+    use super::*;
+
+    #[test]
+    fn configured_carver() {
+
+        let env = LevelEnv::vanilla();
+
+        let json: Value = serde_json::from_str(r#"{
+            "type": "minecraft:cave",
+            "config": {
+                "probability": 1.0,
+                "y": {
+                    "type": "minecraft:uniform",
+                    "min_inclusive": { "absolute": 30 },
+                    "max_inclusive": { "below_top": 200 }
+                },
+                "lava_level": { "above_bottom": 10 },
+                "debug_settings": {
+                    "debug_mode": true,
+                    "air_state": { "Name": "minecraft:glowstone" }
+                }
+            }
+        }"#).unwrap();
+
+        let carver = ConfiguredCarver::from_json(&json, &env).unwrap();
+        assert_eq!(carver.carver_type, CarverType::Cave);
+        assert_eq!(carver.config.lava_level.resolve(ChunkHeight::new(-4, 15)), -54);
+
+        let debug_settings = carver.config.get_debug_settings().unwrap();
+        assert_eq!(debug_settings.air_state, GLOWSTONE.get_default_state());
+        assert_eq!(debug_settings.lava_state, ORANGE_STAINED_GLASS.get_default_state());
+
+        let height = ChunkHeight::new(0, 15);
+        let mut rand = JavaRandom::new(0);
+        for _ in 0..100 {
+            assert!((30..=55).contains(&carver.config.y.sample(&mut rand, height)));
+        }
+
+        let mut chunk = ChunkLoadRequest { env: Arc::clone(&env), height, cx: 0, cz: 0 }.build_proto_chunk();
+        for x in 0..16 {
+            for y in 1..120 {
+                for z in 0..16 {
+                    chunk.set_block_at(x, y, z, STONE.get_default_state()).unwrap();
+                }
+            }
+        }
+
+        carver.generate_in(0, &mut chunk, 8, &BiomePropertyMap::new(), &GeneratorSettings::new(), None);
+        let count_blocks = |state: &'static BlockState| (0..16)
+            .flat_map(|x| (0..128).flat_map(move |y| (0..16).map(move |z| (x, y, z))))
+            .filter(|&(x, y, z)| chunk.get_block_at(x, y, z).unwrap() == state)
+            .count();
+        assert_ne!(count_blocks(GLOWSTONE.get_default_state()), 0);
+        assert_eq!(count_blocks(AIR.get_default_state()), 16 * 16 * 9);
+
+        let json: Value = serde_json::from_str(r#"{ "type": "minecraft:nether_cave", "config": {} }"#).unwrap();
+        assert!(ConfiguredCarver::from_json(&json, &env).is_err());
+
+    }
+
+}
@@ -0,0 +1,188 @@
+//! Debugging utilities for structure pieces placement.
+//!
+//! A `PieceDebugger` can be given to structure generators in order to record the bounding
+//! boxes of every piece they place. These boxes can then be drawn as block outlines in the
+//! generated chunks or exported as a JSON sidecar, this allows debugging piece placement
+//! without any external tool.
+
+use std::io::{self, Write};
+use std::sync::Mutex;
+
+use mc_core::block::BlockState;
+
+use crate::view::ProtoChunkView;
+use super::piece::BoundingBox;
+
+
+/// A piece recorded by a `PieceDebugger`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugPiece {
+    /// Name of the structure that placed this piece.
+    pub structure: &'static str,
+    /// Name of the piece in its structure.
+    pub name: &'static str,
+    /// The chunk being generated when this piece was placed.
+    pub chunk: (i32, i32),
+    /// Bounding box of the piece, in block coordinates.
+    pub bbox: BoundingBox,
+}
+
+
+/// A thread-safe recorder of structure pieces bounding boxes.
+pub struct PieceDebugger {
+    marker: Option<&'static BlockState>,
+    pieces: Mutex<Vec<DebugPiece>>,
+}
+
+impl PieceDebugger {
+
+    /// Construct a new debugger that only records pieces, use `with_marker` if you want
+    /// pieces to be outlined in generated chunks.
+    pub fn new() -> Self {
+        Self {
+            marker: None,
+            pieces: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Set the block state used to outline pieces' bounding boxes when calling `mark_chunk`,
+    /// typically glass or structure void.
+    pub fn with_marker(mut self, marker: &'static BlockState) -> Self {
+        self.marker = Some(marker);
+        self
+    }
+
+    #[inline]
+    pub fn get_marker(&self) -> Option<&'static BlockState> {
+        self.marker
+    }
+
+    /// Record a piece placed by the given structure while generating the given chunk.
+    pub fn record(&self, structure: &'static str, name: &'static str, chunk: (i32, i32), bbox: BoundingBox) {
+        self.pieces.lock().unwrap().push(DebugPiece {
+            structure,
+            name,
+            chunk,
+            bbox
+        });
+    }
+
+    /// Return a copy of all recorded pieces, in recording order.
+    pub fn get_pieces(&self) -> Vec<DebugPiece> {
+        self.pieces.lock().unwrap().clone()
+    }
+
+    pub fn get_pieces_count(&self) -> usize {
+        self.pieces.lock().unwrap().len()
+    }
+
+    /// Remove all recorded pieces.
+    pub fn clear(&self) {
+        self.pieces.lock().unwrap().clear();
+    }
+
+    /// Draw the edges of all recorded bounding boxes intersecting the given chunk with the
+    /// marker block, clipped to the chunk. Nothing is done if no marker has been set.
+    ///
+    /// This should be called after the structures have been generated for this chunk,
+    /// because only already recorded pieces can be drawn.
+    pub fn mark_chunk(&self, chunk: &mut dyn ProtoChunkView) {
+
+        let marker = match self.marker {
+            Some(marker) => marker,
+            None => return
+        };
+
+        let (cx, cz) = chunk.get_position();
+        let height = chunk.as_chunk_ref().get_height();
+        let chunk_bbox = BoundingBox::new_chunk(cx, cz, height.get_min_block(), height.get_max_block());
+
+        let pieces = self.pieces.lock().unwrap();
+        for piece in pieces.iter() {
+            if let Some(clipped) = piece.bbox.intersection(&chunk_bbox) {
+                for x in clipped.min_x..=clipped.max_x {
+                    for z in clipped.min_z..=clipped.max_z {
+                        for y in clipped.min_y..=clipped.max_y {
+                            if piece.bbox.is_on_edge(x, y, z) {
+                                chunk.set_block_at(x, y, z, marker).unwrap();
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+    }
+
+    /// Write all recorded pieces as a JSON array to the given writer. Each piece is an
+    /// object with `structure`, `name`, `chunk` (`[cx, cz]`), `min` and `max` (`[x, y, z]`).
+    pub fn write_json(&self, writer: &mut impl Write) -> io::Result<()> {
+
+        let pieces = self.pieces.lock().unwrap();
+
+        writer.write_all(b"[")?;
+        for (i, piece) in pieces.iter().enumerate() {
+            if i != 0 {
+                writer.write_all(b",")?;
+            }
+            write!(writer,
+                "\n  {{\"structure\": \"{}\", \"name\": \"{}\", \"chunk\": [{}, {}], \"min\": [{}, {}, {}], \"max\": [{}, {}, {}]}}",
+                escape_json(piece.structure), escape_json(piece.name),
+                piece.chunk.0, piece.chunk.1,
+                piece.bbox.min_x, piece.bbox.min_y, piece.bbox.min_z,
+                piece.bbox.max_x, piece.bbox.max_y, piece.bbox.max_z
+            )?;
+        }
+        if !pieces.is_empty() {
+            writer.write_all(b"\n")?;
+        }
+        writer.write_all(b"]\n")
+
+    }
+
+}
+
+impl Default for PieceDebugger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+fn escape_json(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn write_json() {
+
+        let debugger = PieceDebugger::new();
+        let mut buf = Vec::new();
+        debugger.write_json(&mut buf).unwrap();
+        assert_eq!(buf, b"[]\n");
+
+        debugger.record("cave", "tunnel", (0, -1), BoundingBox::new(1, 2, 3, 4, 5, 6));
+        debugger.record("ravine", "ravine", (1, 1), BoundingBox::new(16, 20, 16, 20, 40, 18));
+        assert_eq!(debugger.get_pieces_count(), 2);
+
+        let mut buf = Vec::new();
+        debugger.write_json(&mut buf).unwrap();
+        assert_eq!(String::from_utf8(buf).unwrap(), concat!(
+            "[\n",
+            "  {\"structure\": \"cave\", \"name\": \"tunnel\", \"chunk\": [0, -1], \"min\": [1, 2, 3], \"max\": [4, 5, 6]},\n",
+            "  {\"structure\": \"ravine\", \"name\": \"ravine\", \"chunk\": [1, 1], \"min\": [16, 20, 16], \"max\": [20, 40, 18]}\n",
+            "]\n"
+        ));
+
+        debugger.clear();
+        assert_eq!(debugger.get_pieces_count(), 0);
+
+    }
+
+}
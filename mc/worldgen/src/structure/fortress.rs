@@ -0,0 +1,1029 @@
+//! Nether fortress structure, made of bridges and castle pieces.
+//!
+//! Fortresses are generated piece by piece from a bridge crossing, each piece adds children
+//! pieces at its exits, randomly picked from weighted lists of bridge or castle pieces.
+
+use mc_core::block::BlockState;
+use mc_core::rand::JavaRandom;
+use mc_core::pos::Direction;
+
+use mc_vanilla::block::*;
+use mc_vanilla::entity::{BLAZE, ZOMBIFIED_PIGLIN, WITHER_SKELETON, SKELETON, MAGMA_CUBE};
+
+use crate::view::LevelView;
+
+use super::piece::{BoundingBox, PiecePlacer, StructurePiece, StructureStart};
+use super::{new_start_random, PieceStructure, RandomSpread, SpawnEntry};
+
+
+/// Vanilla mobs spawning inside fortresses.
+pub static FORTRESS_SPAWNS: [SpawnEntry; 5] = [
+    SpawnEntry { entity_type: &BLAZE, weight: 10, min_count: 2, max_count: 3 },
+    SpawnEntry { entity_type: &ZOMBIFIED_PIGLIN, weight: 5, min_count: 4, max_count: 4 },
+    SpawnEntry { entity_type: &WITHER_SKELETON, weight: 8, min_count: 5, max_count: 5 },
+    SpawnEntry { entity_type: &SKELETON, weight: 2, min_count: 5, max_count: 5 },
+    SpawnEntry { entity_type: &MAGMA_CUBE, weight: 3, min_count: 4, max_count: 4 },
+];
+
+/// Placement of fortresses and bastion remnants, they share the same regions and a region
+/// contains either a fortress or a bastion remnant.
+pub const NETHER_SPREAD: RandomSpread = RandomSpread {
+    spacing: 27,
+    separation: 4,
+    salt: 30084232,
+    triangular: false
+};
+
+/// Loot table of the chests in castle corridor turns.
+pub const FORTRESS_LOOT_TABLE: &str = "minecraft:chests/nether_bridge";
+
+const HORIZONTAL: [Direction; 4] = [Direction::North, Direction::East, Direction::South, Direction::West];
+
+
+/// The nether fortress structure.
+///
+/// Valid for: 1.16.2 to 1.17.1
+pub struct NetherFortress {
+    seed: i64
+}
+
+impl NetherFortress {
+
+    pub fn new(seed: i64) -> Self {
+        Self { seed }
+    }
+
+}
+
+impl PieceStructure for NetherFortress {
+
+    fn get_range(&self) -> i32 {
+        8
+    }
+
+    fn generate_start(&self, cx: i32, cz: i32) -> Option<StructureStart> {
+
+        let mut rand = JavaRandom::new(0);
+        if NETHER_SPREAD.get_start_chunk(self.seed, cx, cz, &mut rand) != (cx, cz) || rand.next_int_bounded(5) >= 2 {
+            return None;
+        }
+
+        let mut rand = new_start_random(self.seed, cx, cz);
+        let mut start = StructureStart::new(generate_pieces(&mut rand, cx * 16 + 2, cz * 16 + 2));
+        start.move_inside_heights(&mut rand, 48, 70);
+        Some(start)
+
+    }
+
+    fn get_spawn_overrides(&self) -> Option<&'static [SpawnEntry]> {
+        Some(&FORTRESS_SPAWNS)
+    }
+
+}
+
+
+// PIECES //
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PieceType {
+    BridgeStraight,
+    BridgeEndFiller,
+    BridgeCrossing,
+    RoomCrossing,
+    StairsRoom,
+    MonsterThrone,
+    CastleEntrance,
+    CastleSmallCorridor,
+    CastleSmallCorridorCrossing,
+    CastleSmallCorridorRightTurn,
+    CastleSmallCorridorLeftTurn,
+    CastleCorridorStairs,
+    CastleCorridorTBalcony,
+    CastleStalkRoom
+}
+
+impl PieceType {
+
+    fn get_name(self) -> &'static str {
+        match self {
+            Self::BridgeStraight => "bridge_straight",
+            Self::BridgeEndFiller => "bridge_end_filler",
+            Self::BridgeCrossing => "bridge_crossing",
+            Self::RoomCrossing => "room_crossing",
+            Self::StairsRoom => "stairs_room",
+            Self::MonsterThrone => "monster_throne",
+            Self::CastleEntrance => "castle_entrance",
+            Self::CastleSmallCorridor => "castle_small_corridor",
+            Self::CastleSmallCorridorCrossing => "castle_small_corridor_crossing",
+            Self::CastleSmallCorridorRightTurn => "castle_small_corridor_right_turn",
+            Self::CastleSmallCorridorLeftTurn => "castle_small_corridor_left_turn",
+            Self::CastleCorridorStairs => "castle_corridor_stairs",
+            Self::CastleCorridorTBalcony => "castle_corridor_t_balcony",
+            Self::CastleStalkRoom => "castle_stalk_room"
+        }
+    }
+
+    /// Return the offset and the size of this piece, relative to its orientation.
+    fn get_shape(self) -> ((i32, i32, i32), (i32, i32, i32)) {
+        match self {
+            Self::BridgeStraight => ((-1, -3, 0), (5, 10, 19)),
+            Self::BridgeEndFiller => ((-1, -3, 0), (5, 10, 8)),
+            Self::BridgeCrossing => ((-8, -3, 0), (19, 10, 19)),
+            Self::RoomCrossing => ((-2, 0, 0), (7, 9, 7)),
+            Self::StairsRoom => ((-2, 0, 0), (7, 11, 7)),
+            Self::MonsterThrone => ((-2, 0, 0), (7, 8, 9)),
+            Self::CastleEntrance | Self::CastleStalkRoom => ((-5, -3, 0), (13, 14, 13)),
+            Self::CastleSmallCorridor |
+            Self::CastleSmallCorridorCrossing |
+            Self::CastleSmallCorridorRightTurn |
+            Self::CastleSmallCorridorLeftTurn => ((-1, 0, 0), (5, 7, 5)),
+            Self::CastleCorridorStairs => ((-1, -7, 0), (5, 14, 10)),
+            Self::CastleCorridorTBalcony => ((-3, 0, 0), (9, 7, 9))
+        }
+    }
+
+}
+
+
+struct PieceWeight {
+    ty: PieceType,
+    weight: i32,
+    place_count: i32,
+    max_place_count: i32,
+    allow_in_row: bool
+}
+
+impl PieceWeight {
+
+    const fn new(ty: PieceType, weight: i32, max_place_count: i32, allow_in_row: bool) -> Self {
+        Self {
+            ty,
+            weight,
+            place_count: 0,
+            max_place_count,
+            allow_in_row
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        self.max_place_count == 0 || self.place_count < self.max_place_count
+    }
+
+}
+
+fn new_bridge_weights() -> Vec<PieceWeight> {
+    vec![
+        PieceWeight::new(PieceType::BridgeStraight, 30, 0, true),
+        PieceWeight::new(PieceType::BridgeCrossing, 10, 4, false),
+        PieceWeight::new(PieceType::RoomCrossing, 10, 4, false),
+        PieceWeight::new(PieceType::StairsRoom, 10, 3, false),
+        PieceWeight::new(PieceType::MonsterThrone, 5, 2, false),
+        PieceWeight::new(PieceType::CastleEntrance, 5, 1, false),
+    ]
+}
+
+fn new_castle_weights() -> Vec<PieceWeight> {
+    vec![
+        PieceWeight::new(PieceType::CastleSmallCorridor, 25, 0, true),
+        PieceWeight::new(PieceType::CastleSmallCorridorCrossing, 15, 5, false),
+        PieceWeight::new(PieceType::CastleSmallCorridorRightTurn, 5, 10, false),
+        PieceWeight::new(PieceType::CastleSmallCorridorLeftTurn, 5, 10, false),
+        PieceWeight::new(PieceType::CastleCorridorStairs, 10, 3, true),
+        PieceWeight::new(PieceType::CastleCorridorTBalcony, 7, 2, false),
+        PieceWeight::new(PieceType::CastleStalkRoom, 5, 2, false),
+    ]
+}
+
+
+/// A piece of a nether fortress.
+struct FortressPiece {
+    ty: PieceType,
+    bb: BoundingBox,
+    orientation: Direction,
+    depth: i32,
+    /// Seed of the end filler's own random.
+    filler_seed: i32,
+    /// True while the spawner of the monster throne or the chest of corridor turns is still
+    /// to be placed.
+    pending_block: bool
+}
+
+impl StructurePiece for FortressPiece {
+
+    fn get_name(&self) -> &'static str {
+        self.ty.get_name()
+    }
+
+    fn get_bounding_box(&self) -> &BoundingBox {
+        &self.bb
+    }
+
+    fn offset(&mut self, dx: i32, dy: i32, dz: i32) {
+        self.bb.offset(dx, dy, dz);
+    }
+
+    fn place(&mut self, level: &mut dyn LevelView, rand: &mut JavaRandom, chunk_bb: &BoundingBox) -> bool {
+        let mut placer = PiecePlacer::new(level, chunk_bb, self.bb, self.orientation);
+        match self.ty {
+            PieceType::BridgeStraight => place_bridge_straight(&mut placer),
+            PieceType::BridgeEndFiller => place_bridge_end_filler(&mut placer, self.filler_seed),
+            PieceType::BridgeCrossing => place_bridge_crossing(&mut placer),
+            PieceType::RoomCrossing => place_room_crossing(&mut placer),
+            PieceType::StairsRoom => place_stairs_room(&mut placer),
+            PieceType::MonsterThrone => place_monster_throne(&mut placer, &mut self.pending_block),
+            PieceType::CastleEntrance => place_castle_entrance(&mut placer),
+            PieceType::CastleSmallCorridor => place_castle_small_corridor(&mut placer),
+            PieceType::CastleSmallCorridorCrossing => place_castle_small_corridor_crossing(&mut placer),
+            PieceType::CastleSmallCorridorRightTurn => place_castle_small_corridor_turn(&mut placer, rand, true, &mut self.pending_block),
+            PieceType::CastleSmallCorridorLeftTurn => place_castle_small_corridor_turn(&mut placer, rand, false, &mut self.pending_block),
+            PieceType::CastleCorridorStairs => place_castle_corridor_stairs(&mut placer),
+            PieceType::CastleCorridorTBalcony => place_castle_corridor_t_balcony(&mut placer),
+            PieceType::CastleStalkRoom => place_castle_stalk_room(&mut placer)
+        }
+        true
+    }
+
+}
+
+
+// GENERATION //
+
+/// Internal state of the fortress generation.
+struct FortressBuilder<'a> {
+    rand: &'a mut JavaRandom,
+    pieces: Vec<FortressPiece>,
+    pending: Vec<usize>,
+    bridge_weights: Vec<PieceWeight>,
+    castle_weights: Vec<PieceWeight>,
+    /// The last type of piece picked from weights.
+    previous: Option<PieceType>,
+    start_x: i32,
+    start_z: i32
+}
+
+/// Generate all pieces of a fortress starting at the given block position.
+fn generate_pieces(rand: &mut JavaRandom, x: i32, z: i32) -> Vec<Box<dyn StructurePiece>> {
+
+    let orientation = HORIZONTAL[rand.next_int_bounded(4) as usize];
+    let start = FortressPiece {
+        ty: PieceType::BridgeCrossing,
+        bb: BoundingBox::new(x, 64, z, x + 18, 73, z + 18),
+        orientation,
+        depth: 0,
+        filler_seed: 0,
+        pending_block: false
+    };
+
+    let mut builder = FortressBuilder {
+        rand,
+        pieces: vec![start],
+        pending: Vec::new(),
+        bridge_weights: new_bridge_weights(),
+        castle_weights: new_castle_weights(),
+        previous: None,
+        start_x: x,
+        start_z: z
+    };
+
+    builder.add_children(0);
+    while !builder.pending.is_empty() {
+        let index = builder.rand.next_int_bounded(builder.pending.len() as i32) as usize;
+        let piece_index = builder.pending.remove(index);
+        builder.add_children(piece_index);
+    }
+
+    builder.pieces.into_iter()
+        .map(|piece| Box::new(piece) as Box<dyn StructurePiece>)
+        .collect()
+
+}
+
+impl<'a> FortressBuilder<'a> {
+
+    fn add_children(&mut self, index: usize) {
+        let piece = &self.pieces[index];
+        match piece.ty {
+            PieceType::BridgeStraight => {
+                self.child_forward(index, 1, 3, false);
+            }
+            PieceType::BridgeCrossing => {
+                self.child_forward(index, 8, 3, false);
+                self.child_left(index, 3, 8, false);
+                self.child_right(index, 3, 8, false);
+            }
+            PieceType::RoomCrossing => {
+                self.child_forward(index, 2, 0, false);
+                self.child_left(index, 0, 2, false);
+                self.child_right(index, 0, 2, false);
+            }
+            PieceType::StairsRoom => {
+                self.child_right(index, 6, 2, false);
+            }
+            PieceType::CastleEntrance => {
+                self.child_forward(index, 5, 3, true);
+            }
+            PieceType::CastleStalkRoom => {
+                self.child_forward(index, 5, 3, true);
+                self.child_forward(index, 5, 11, true);
+            }
+            PieceType::CastleSmallCorridor => {
+                self.child_forward(index, 1, 0, true);
+            }
+            PieceType::CastleSmallCorridorCrossing => {
+                self.child_forward(index, 1, 0, true);
+                self.child_left(index, 0, 1, true);
+                self.child_right(index, 0, 1, true);
+            }
+            PieceType::CastleSmallCorridorRightTurn => {
+                self.child_right(index, 0, 1, true);
+            }
+            PieceType::CastleSmallCorridorLeftTurn => {
+                self.child_left(index, 0, 1, true);
+            }
+            PieceType::CastleCorridorStairs => {
+                self.child_forward(index, 1, 0, true);
+            }
+            PieceType::CastleCorridorTBalcony => {
+                let off_z = match piece.orientation {
+                    Direction::West | Direction::North => 5,
+                    _ => 1
+                };
+                let castle = self.rand.next_int_bounded(8) > 0;
+                self.child_left(index, 0, off_z, castle);
+                let castle = self.rand.next_int_bounded(8) > 0;
+                self.child_right(index, 0, off_z, castle);
+            }
+            PieceType::BridgeEndFiller | PieceType::MonsterThrone => {}
+        }
+    }
+
+    fn child_forward(&mut self, index: usize, off_x: i32, off_y: i32, castle: bool) {
+        let FortressPiece { bb, orientation, depth, .. } = self.pieces[index];
+        let (x, y, z) = match orientation {
+            Direction::South => (bb.min_x + off_x, bb.min_y + off_y, bb.max_z + 1),
+            Direction::West => (bb.min_x - 1, bb.min_y + off_y, bb.min_z + off_x),
+            Direction::East => (bb.max_x + 1, bb.min_y + off_y, bb.min_z + off_x),
+            _ => (bb.min_x + off_x, bb.min_y + off_y, bb.min_z - 1)
+        };
+        self.generate_and_add_piece(x, y, z, orientation, depth, castle);
+    }
+
+    fn child_left(&mut self, index: usize, off_y: i32, off_z: i32, castle: bool) {
+        let FortressPiece { bb, orientation, depth, .. } = self.pieces[index];
+        let (x, y, z, direction) = match orientation {
+            Direction::North | Direction::South => (bb.min_x - 1, bb.min_y + off_y, bb.min_z + off_z, Direction::West),
+            _ => (bb.min_x + off_z, bb.min_y + off_y, bb.min_z - 1, Direction::North)
+        };
+        self.generate_and_add_piece(x, y, z, direction, depth, castle);
+    }
+
+    fn child_right(&mut self, index: usize, off_y: i32, off_z: i32, castle: bool) {
+        let FortressPiece { bb, orientation, depth, .. } = self.pieces[index];
+        let (x, y, z, direction) = match orientation {
+            Direction::North | Direction::South => (bb.max_x + 1, bb.min_y + off_y, bb.min_z + off_z, Direction::East),
+            _ => (bb.min_x + off_z, bb.min_y + off_y, bb.max_z + 1, Direction::South)
+        };
+        self.generate_and_add_piece(x, y, z, direction, depth, castle);
+    }
+
+    fn generate_and_add_piece(&mut self, x: i32, y: i32, z: i32, direction: Direction, depth: i32, castle: bool) {
+        if (x - self.start_x).abs() <= 112 && (z - self.start_z).abs() <= 112 {
+            if let Some(piece) = self.generate_piece(x, y, z, direction, depth + 1, castle) {
+                self.pieces.push(piece);
+                self.pending.push(self.pieces.len() - 1);
+            }
+        } else {
+            // Vanilla creates an end filler here, but never adds it to the structure.
+            self.create_piece(PieceType::BridgeEndFiller, x, y, z, direction, depth);
+        }
+    }
+
+    fn generate_piece(&mut self, x: i32, y: i32, z: i32, direction: Direction, depth: i32, castle: bool) -> Option<FortressPiece> {
+
+        let weights = if castle { &self.castle_weights } else { &self.bridge_weights };
+
+        let mut total_weight = 0;
+        let mut limited = false;
+        for weight in weights {
+            if weight.max_place_count > 0 && weight.place_count < weight.max_place_count {
+                limited = true;
+            }
+            total_weight += weight.weight;
+        }
+
+        if limited && total_weight > 0 && depth <= 30 {
+            for _ in 0..5 {
+                let mut remaining = self.rand.next_int_bounded(total_weight);
+                let weights_len = if castle { self.castle_weights.len() } else { self.bridge_weights.len() };
+                for i in 0..weights_len {
+
+                    let weights = if castle { &self.castle_weights } else { &self.bridge_weights };
+                    let weight = &weights[i];
+                    remaining -= weight.weight;
+
+                    if remaining < 0 {
+
+                        if !weight.is_valid() || (Some(weight.ty) == self.previous && !weight.allow_in_row) {
+                            break;
+                        }
+
+                        let ty = weight.ty;
+                        if let Some(piece) = self.create_piece(ty, x, y, z, direction, depth) {
+                            let weights = if castle { &mut self.castle_weights } else { &mut self.bridge_weights };
+                            weights[i].place_count += 1;
+                            self.previous = Some(ty);
+                            if !weights[i].is_valid() {
+                                weights.remove(i);
+                            }
+                            return Some(piece);
+                        }
+
+                    }
+
+                }
+            }
+        }
+
+        self.create_piece(PieceType::BridgeEndFiller, x, y, z, direction, depth)
+
+    }
+
+    /// Create a piece of the given type if it doesn't collide with existing pieces and if
+    /// it's not too low.
+    fn create_piece(&mut self, ty: PieceType, x: i32, y: i32, z: i32, direction: Direction, depth: i32) -> Option<FortressPiece> {
+
+        let ((off_x, off_y, off_z), (size_x, size_y, size_z)) = ty.get_shape();
+        let bb = BoundingBox::new_oriented(x, y, z, off_x, off_y, off_z, size_x, size_y, size_z, direction);
+
+        if bb.min_y <= 10 || self.pieces.iter().any(|piece| piece.bb.intersects(&bb)) {
+            return None;
+        }
+
+        let (filler_seed, pending_block) = match ty {
+            PieceType::BridgeEndFiller => (self.rand.next_int(), false),
+            PieceType::MonsterThrone => (0, true),
+            PieceType::CastleSmallCorridorRightTurn |
+            PieceType::CastleSmallCorridorLeftTurn => (0, self.rand.next_int_bounded(3) == 0),
+            _ => (0, false)
+        };
+
+        Some(FortressPiece {
+            ty,
+            bb,
+            orientation: direction,
+            depth,
+            filler_seed,
+            pending_block
+        })
+
+    }
+
+}
+
+
+// PLACEMENT //
+
+fn bricks() -> &'static BlockState {
+    NETHER_BRICKS.get_default_state()
+}
+
+fn air() -> &'static BlockState {
+    AIR.get_default_state()
+}
+
+fn fence(north: bool, east: bool, south: bool, west: bool) -> &'static BlockState {
+    NETHER_BRICK_FENCE.get_default_state()
+        .with(&PROP_NORTH, north).unwrap()
+        .with(&PROP_EAST, east).unwrap()
+        .with(&PROP_SOUTH, south).unwrap()
+        .with(&PROP_WEST, west).unwrap()
+}
+
+fn stairs(facing: Direction) -> &'static BlockState {
+    NETHER_BRICK_STAIRS.get_default_state()
+        .with(&PROP_HORIZONTAL_FACING, facing).unwrap()
+        .with(&PROP_HALF, Half::Bottom).unwrap()
+}
+
+/// Fill the columns under the given relative area with nether bricks.
+fn fill_foundations(p: &mut PiecePlacer, x0: i32, z0: i32, x1: i32, z1: i32) {
+    for x in x0..=x1 {
+        for z in z0..=z1 {
+            p.fill_column_down(x, -1, z, bricks());
+        }
+    }
+}
+
+fn place_bridge_straight(p: &mut PiecePlacer) {
+
+    p.fill(0, 3, 0, 4, 4, 18, bricks());
+    p.fill(1, 5, 0, 3, 7, 18, air());
+    p.fill(0, 5, 0, 0, 5, 18, bricks());
+    p.fill(4, 5, 0, 4, 5, 18, bricks());
+    p.fill(0, 2, 0, 4, 2, 5, bricks());
+    p.fill(0, 2, 13, 4, 2, 18, bricks());
+    p.fill(0, 0, 0, 4, 1, 3, bricks());
+    p.fill(0, 0, 15, 4, 1, 18, bricks());
+
+    for x in 0..=4 {
+        for z in 0..=2 {
+            p.fill_column_down(x, -1, z, bricks());
+            p.fill_column_down(x, -1, 18 - z, bricks());
+        }
+    }
+
+    let fence_east = fence(true, true, true, false);
+    let fence_west = fence(true, false, true, true);
+    p.fill(0, 1, 1, 0, 4, 1, fence_east);
+    p.fill(0, 3, 4, 0, 4, 4, fence_east);
+    p.fill(0, 3, 14, 0, 4, 14, fence_east);
+    p.fill(0, 1, 17, 0, 4, 17, fence_east);
+    p.fill(4, 1, 1, 4, 4, 1, fence_west);
+    p.fill(4, 3, 4, 4, 4, 4, fence_west);
+    p.fill(4, 3, 14, 4, 4, 14, fence_west);
+    p.fill(4, 1, 17, 4, 4, 17, fence_west);
+
+}
+
+fn place_bridge_end_filler(p: &mut PiecePlacer, seed: i32) {
+
+    let mut rand = JavaRandom::new(seed as i64);
+
+    for x in 0..=4 {
+        for y in 3..=4 {
+            let length = rand.next_int_bounded(8);
+            p.fill(x, y, 0, x, y, length, bricks());
+        }
+    }
+
+    let length = rand.next_int_bounded(8);
+    p.fill(0, 5, 0, 0, 5, length, bricks());
+    let length = rand.next_int_bounded(8);
+    p.fill(4, 5, 0, 4, 5, length, bricks());
+
+    for x in 0..=4 {
+        let length = rand.next_int_bounded(5);
+        p.fill(x, 2, 0, x, 2, length, bricks());
+    }
+
+    for x in 0..=4 {
+        for y in 0..=1 {
+            let length = rand.next_int_bounded(3);
+            p.fill(x, y, 0, x, y, length, bricks());
+        }
+    }
+
+}
+
+fn place_bridge_crossing(p: &mut PiecePlacer) {
+
+    p.fill(7, 3, 0, 11, 4, 18, bricks());
+    p.fill(0, 3, 7, 18, 4, 11, bricks());
+    p.fill(8, 5, 0, 10, 7, 18, air());
+    p.fill(0, 5, 8, 18, 7, 10, air());
+    p.fill(7, 5, 0, 7, 5, 7, bricks());
+    p.fill(7, 5, 11, 7, 5, 18, bricks());
+    p.fill(11, 5, 0, 11, 5, 7, bricks());
+    p.fill(11, 5, 11, 11, 5, 18, bricks());
+    p.fill(0, 5, 7, 7, 5, 7, bricks());
+    p.fill(11, 5, 7, 18, 5, 7, bricks());
+    p.fill(0, 5, 11, 7, 5, 11, bricks());
+    p.fill(11, 5, 11, 18, 5, 11, bricks());
+    p.fill(7, 2, 0, 11, 2, 5, bricks());
+    p.fill(7, 2, 13, 11, 2, 18, bricks());
+    p.fill(7, 0, 0, 11, 1, 3, bricks());
+    p.fill(7, 0, 15, 11, 1, 18, bricks());
+
+    for x in 7..=11 {
+        for z in 0..=2 {
+            p.fill_column_down(x, -1, z, bricks());
+            p.fill_column_down(x, -1, 18 - z, bricks());
+        }
+    }
+
+    p.fill(0, 2, 7, 5, 2, 11, bricks());
+    p.fill(13, 2, 7, 18, 2, 11, bricks());
+    p.fill(0, 0, 7, 3, 1, 11, bricks());
+    p.fill(15, 0, 7, 18, 1, 11, bricks());
+
+    for x in 0..=2 {
+        for z in 7..=11 {
+            p.fill_column_down(x, -1, z, bricks());
+            p.fill_column_down(18 - x, -1, z, bricks());
+        }
+    }
+
+}
+
+fn place_room_crossing(p: &mut PiecePlacer) {
+
+    p.fill(0, 0, 0, 6, 1, 6, bricks());
+    p.fill(0, 2, 0, 6, 7, 6, air());
+    p.fill(0, 2, 0, 1, 6, 0, bricks());
+    p.fill(0, 2, 6, 1, 6, 6, bricks());
+    p.fill(5, 2, 0, 6, 6, 0, bricks());
+    p.fill(5, 2, 6, 6, 6, 6, bricks());
+    p.fill(0, 2, 0, 0, 6, 1, bricks());
+    p.fill(0, 2, 5, 0, 6, 6, bricks());
+    p.fill(6, 2, 0, 6, 6, 1, bricks());
+    p.fill(6, 2, 5, 6, 6, 6, bricks());
+
+    let fence_we = fence(false, true, false, true);
+    let fence_ns = fence(true, false, true, false);
+    p.fill(2, 6, 0, 4, 6, 0, fence_we);
+    p.fill(2, 5, 0, 4, 5, 0, bricks());
+    p.fill(2, 6, 6, 4, 6, 6, fence_we);
+    p.fill(2, 5, 6, 4, 5, 6, bricks());
+    p.fill(0, 6, 2, 0, 6, 4, fence_ns);
+    p.fill(0, 5, 2, 0, 5, 4, bricks());
+    p.fill(6, 6, 2, 6, 6, 4, fence_ns);
+    p.fill(6, 5, 2, 6, 5, 4, bricks());
+
+    fill_foundations(p, 0, 0, 6, 6);
+
+}
+
+fn place_stairs_room(p: &mut PiecePlacer) {
+
+    p.fill(0, 0, 0, 6, 1, 6, bricks());
+    p.fill(0, 2, 0, 6, 10, 6, air());
+    p.fill(0, 2, 0, 1, 8, 0, bricks());
+    p.fill(5, 2, 0, 6, 8, 0, bricks());
+    p.fill(0, 2, 1, 0, 8, 6, bricks());
+    p.fill(6, 2, 1, 6, 8, 6, bricks());
+    p.fill(1, 2, 6, 5, 8, 6, bricks());
+
+    let fence_we = fence(false, true, false, true);
+    let fence_ns = fence(true, false, true, false);
+    p.fill(0, 3, 2, 0, 5, 4, fence_ns);
+    p.fill(6, 3, 2, 6, 5, 2, fence_ns);
+    p.fill(6, 3, 4, 6, 5, 4, fence_ns);
+    p.set_block(5, 2, 5, bricks());
+    p.fill(4, 2, 5, 4, 3, 5, bricks());
+    p.fill(3, 2, 5, 3, 4, 5, bricks());
+    p.fill(2, 2, 5, 2, 5, 5, bricks());
+    p.fill(1, 2, 5, 1, 6, 5, bricks());
+    p.fill(1, 7, 1, 5, 7, 4, bricks());
+    p.fill(6, 8, 2, 6, 8, 4, air());
+    p.fill(2, 6, 0, 4, 8, 0, bricks());
+    p.fill(2, 5, 0, 4, 5, 0, fence_we);
+
+    fill_foundations(p, 0, 0, 6, 6);
+
+}
+
+fn place_monster_throne(p: &mut PiecePlacer, pending_spawner: &mut bool) {
+
+    p.fill(0, 2, 0, 6, 7, 7, air());
+    p.fill(1, 0, 0, 5, 1, 7, bricks());
+    p.fill(1, 2, 1, 5, 2, 7, bricks());
+    p.fill(1, 3, 2, 5, 3, 7, bricks());
+    p.fill(1, 4, 3, 5, 4, 7, bricks());
+    p.fill(1, 2, 0, 1, 4, 2, bricks());
+    p.fill(5, 2, 0, 5, 4, 2, bricks());
+    p.fill(1, 5, 2, 1, 5, 3, bricks());
+    p.fill(5, 5, 2, 5, 5, 3, bricks());
+    p.fill(0, 5, 3, 0, 5, 8, bricks());
+    p.fill(6, 5, 3, 6, 5, 8, bricks());
+    p.fill(1, 5, 8, 5, 5, 8, bricks());
+
+    let fence_we = fence(false, true, false, true);
+    let fence_ns = fence(true, false, true, false);
+    p.set_block(1, 6, 3, fence(false, false, false, true));
+    p.set_block(5, 6, 3, fence(false, true, false, false));
+    p.set_block(0, 6, 3, fence(true, true, false, false));
+    p.set_block(6, 6, 3, fence(true, false, false, true));
+    p.fill(0, 6, 4, 0, 6, 7, fence_ns);
+    p.fill(6, 6, 4, 6, 6, 7, fence_ns);
+    p.set_block(0, 6, 8, fence(false, true, true, false));
+    p.set_block(6, 6, 8, fence(false, false, true, true));
+    p.fill(1, 6, 8, 5, 6, 8, fence_we);
+    p.set_block(1, 7, 8, fence(false, true, false, false));
+    p.fill(2, 7, 8, 4, 7, 8, fence_we);
+    p.set_block(5, 7, 8, fence(false, false, false, true));
+    p.set_block(2, 8, 8, fence(false, true, false, false));
+    p.set_block(3, 8, 8, fence_we);
+    p.set_block(4, 8, 8, fence(false, false, false, true));
+
+    if *pending_spawner && p.is_inside(3, 5, 5) {
+        *pending_spawner = false;
+        // TODO: Set the blaze entity of the spawner's block entity.
+        p.set_block(3, 5, 5, SPAWNER.get_default_state());
+    }
+
+    fill_foundations(p, 0, 0, 6, 6);
+
+}
+
+/// Walls, roof and foundations shared by the castle entrance and the stalk room.
+fn place_castle_hall(p: &mut PiecePlacer) {
+
+    p.fill(0, 3, 0, 12, 4, 12, bricks());
+    p.fill(0, 5, 0, 12, 13, 12, air());
+    p.fill(0, 5, 0, 1, 12, 12, bricks());
+    p.fill(11, 5, 0, 12, 12, 12, bricks());
+    p.fill(2, 5, 11, 4, 12, 12, bricks());
+    p.fill(8, 5, 11, 10, 12, 12, bricks());
+    p.fill(5, 9, 11, 7, 12, 12, bricks());
+    p.fill(2, 5, 0, 4, 12, 1, bricks());
+    p.fill(8, 5, 0, 10, 12, 1, bricks());
+    p.fill(5, 9, 0, 7, 12, 1, bricks());
+    p.fill(2, 11, 2, 10, 12, 10, bricks());
+
+    let fence_we = fence(false, true, false, true);
+    let fence_ns = fence(true, false, true, false);
+
+    for i in (1..=11).step_by(2) {
+        p.fill(i, 10, 0, i, 11, 0, fence_we);
+        p.fill(i, 10, 12, i, 11, 12, fence_we);
+        p.fill(0, 10, i, 0, 11, i, fence_ns);
+        p.fill(12, 10, i, 12, 11, i, fence_ns);
+        p.set_block(i, 13, 0, bricks());
+        p.set_block(i, 13, 12, bricks());
+        p.set_block(0, 13, i, bricks());
+        p.set_block(12, 13, i, bricks());
+        if i != 11 {
+            p.set_block(i + 1, 13, 0, fence_we);
+            p.set_block(i + 1, 13, 12, fence_we);
+            p.set_block(0, 13, i + 1, fence_ns);
+            p.set_block(12, 13, i + 1, fence_ns);
+        }
+    }
+
+    p.set_block(0, 13, 0, fence(true, true, false, false));
+    p.set_block(0, 13, 12, fence(false, true, true, false));
+    p.set_block(12, 13, 12, fence(false, false, true, true));
+    p.set_block(12, 13, 0, fence(true, false, false, true));
+
+    for z in (3..=9).step_by(2) {
+        p.fill(1, 7, z, 1, 8, z, fence(true, false, true, true));
+        p.fill(11, 7, z, 11, 8, z, fence(true, true, true, false));
+    }
+
+}
+
+fn place_castle_foundations(p: &mut PiecePlacer) {
+
+    p.fill(4, 2, 0, 8, 2, 12, bricks());
+    p.fill(0, 2, 4, 12, 2, 8, bricks());
+    p.fill(4, 0, 0, 8, 1, 3, bricks());
+    p.fill(4, 0, 9, 8, 1, 12, bricks());
+    p.fill(0, 0, 4, 3, 1, 8, bricks());
+    p.fill(9, 0, 4, 12, 1, 8, bricks());
+
+    for x in 4..=8 {
+        for z in 0..=2 {
+            p.fill_column_down(x, -1, z, bricks());
+            p.fill_column_down(x, -1, 12 - z, bricks());
+        }
+    }
+
+    for x in 0..=2 {
+        for z in 4..=8 {
+            p.fill_column_down(x, -1, z, bricks());
+            p.fill_column_down(12 - x, -1, z, bricks());
+        }
+    }
+
+}
+
+fn place_castle_entrance(p: &mut PiecePlacer) {
+
+    place_castle_hall(p);
+    place_castle_foundations(p);
+
+    p.fill(5, 5, 5, 7, 5, 7, bricks());
+    p.fill(6, 1, 6, 6, 4, 6, air());
+    p.set_block(6, 0, 6, bricks());
+    p.set_block(6, 5, 6, LAVA.get_default_state());
+
+}
+
+fn place_castle_stalk_room(p: &mut PiecePlacer) {
+
+    place_castle_hall(p);
+
+    let stairs_north = stairs(Direction::North);
+    for k in 0..=6 {
+        let z = k + 4;
+        for x in 5..=7 {
+            p.set_block(x, 5 + k, z, stairs_north);
+        }
+        if (5..=8).contains(&z) {
+            p.fill(5, 5, z, 7, k + 4, z, bricks());
+        } else if (9..=10).contains(&z) {
+            p.fill(5, 8, z, 7, k + 4, z, bricks());
+        }
+        if k >= 1 {
+            p.fill(5, 6 + k, z, 7, 9 + k, z, air());
+        }
+    }
+
+    for x in 5..=7 {
+        p.set_block(x, 12, 11, stairs_north);
+    }
+
+    p.fill(5, 6, 7, 5, 7, 7, fence(true, true, true, false));
+    p.fill(7, 6, 7, 7, 7, 7, fence(true, false, true, true));
+    p.fill(5, 13, 12, 7, 13, 12, air());
+    p.fill(2, 5, 2, 3, 5, 3, bricks());
+    p.fill(2, 5, 9, 3, 5, 10, bricks());
+    p.fill(2, 5, 4, 2, 5, 8, bricks());
+    p.fill(9, 5, 2, 10, 5, 3, bricks());
+    p.fill(9, 5, 9, 10, 5, 10, bricks());
+    p.fill(10, 5, 4, 10, 5, 8, bricks());
+
+    let stairs_east = stairs(Direction::East);
+    let stairs_west = stairs(Direction::West);
+    for z in [2, 3, 9, 10] {
+        p.set_block(4, 5, z, stairs_west);
+        p.set_block(8, 5, z, stairs_east);
+    }
+
+    let soul_sand = SOUL_SAND.get_default_state();
+    let nether_wart = NETHER_WART.get_default_state();
+    p.fill(3, 4, 4, 4, 4, 8, soul_sand);
+    p.fill(8, 4, 4, 9, 4, 8, soul_sand);
+    p.fill(3, 5, 4, 4, 5, 8, nether_wart);
+    p.fill(8, 5, 4, 9, 5, 8, nether_wart);
+
+    place_castle_foundations(p);
+
+}
+
+fn place_castle_small_corridor(p: &mut PiecePlacer) {
+
+    p.fill(0, 0, 0, 4, 1, 4, bricks());
+    p.fill(0, 2, 0, 4, 5, 4, air());
+
+    let fence_ns = fence(true, false, true, false);
+    p.fill(0, 2, 0, 0, 5, 4, bricks());
+    p.fill(4, 2, 0, 4, 5, 4, bricks());
+    p.fill(0, 3, 1, 0, 4, 1, fence_ns);
+    p.fill(0, 3, 3, 0, 4, 3, fence_ns);
+    p.fill(4, 3, 1, 4, 4, 1, fence_ns);
+    p.fill(4, 3, 3, 4, 4, 3, fence_ns);
+    p.fill(0, 6, 0, 4, 6, 4, bricks());
+
+    fill_foundations(p, 0, 0, 4, 4);
+
+}
+
+fn place_castle_small_corridor_crossing(p: &mut PiecePlacer) {
+
+    p.fill(0, 0, 0, 4, 1, 4, bricks());
+    p.fill(0, 2, 0, 4, 5, 4, air());
+    p.fill(0, 2, 0, 0, 5, 0, bricks());
+    p.fill(4, 2, 0, 4, 5, 0, bricks());
+    p.fill(0, 2, 4, 0, 5, 4, bricks());
+    p.fill(4, 2, 4, 4, 5, 4, bricks());
+    p.fill(0, 6, 0, 4, 6, 4, bricks());
+
+    fill_foundations(p, 0, 0, 4, 4);
+
+}
+
+fn place_castle_small_corridor_turn(p: &mut PiecePlacer, rand: &mut JavaRandom, right: bool, pending_chest: &mut bool) {
+
+    p.fill(0, 0, 0, 4, 1, 4, bricks());
+    p.fill(0, 2, 0, 4, 5, 4, air());
+
+    let fence_we = fence(false, true, false, true);
+    let fence_ns = fence(true, false, true, false);
+
+    // The wall is on the side opposite to the turn.
+    let (wall_x, open_x) = if right { (0, 4) } else { (4, 0) };
+    p.fill(wall_x, 2, 0, wall_x, 5, 4, bricks());
+    p.fill(wall_x, 3, 1, wall_x, 4, 1, fence_ns);
+    p.fill(wall_x, 3, 3, wall_x, 4, 3, fence_ns);
+    p.fill(open_x, 2, 0, open_x, 5, 0, bricks());
+    if right {
+        p.fill(1, 2, 4, 4, 5, 4, bricks());
+    } else {
+        p.fill(0, 2, 4, 3, 5, 4, bricks());
+    }
+    p.fill(1, 3, 4, 1, 4, 4, fence_we);
+    p.fill(3, 3, 4, 3, 4, 4, fence_we);
+
+    let chest_x = if right { 1 } else { 3 };
+    if *pending_chest && p.is_inside(chest_x, 2, 3) {
+        *pending_chest = false;
+        p.place_chest(chest_x, 2, 3, rand, FORTRESS_LOOT_TABLE);
+    }
+
+    p.fill(0, 6, 0, 4, 6, 4, bricks());
+
+    fill_foundations(p, 0, 0, 4, 4);
+
+}
+
+fn place_castle_corridor_stairs(p: &mut PiecePlacer) {
+
+    let stairs_south = stairs(Direction::South);
+    let fence_ns = fence(true, false, true, false);
+
+    for z in 0..=9 {
+
+        let floor = (7 - z).max(1);
+        let roof = (floor + 5).max(14 - z).min(13);
+
+        p.fill(0, 0, z, 4, floor, z, bricks());
+        p.fill(1, floor + 1, z, 3, roof - 1, z, air());
+        if z <= 6 {
+            for x in 1..=3 {
+                p.set_block(x, floor + 1, z, stairs_south);
+            }
+        }
+
+        p.fill(0, roof, z, 4, roof, z, bricks());
+        p.fill(0, floor + 1, z, 0, roof - 1, z, bricks());
+        p.fill(4, floor + 1, z, 4, roof - 1, z, bricks());
+        if z & 1 == 0 {
+            p.fill(0, floor + 2, z, 0, floor + 3, z, fence_ns);
+            p.fill(4, floor + 2, z, 4, floor + 3, z, fence_ns);
+        }
+
+        for x in 0..=4 {
+            p.fill_column_down(x, -1, z, bricks());
+        }
+
+    }
+
+}
+
+fn place_castle_corridor_t_balcony(p: &mut PiecePlacer) {
+
+    let fence_ns = fence(true, false, true, false);
+    let fence_we = fence(false, true, false, true);
+
+    p.fill(0, 0, 0, 8, 1, 8, bricks());
+    p.fill(0, 2, 0, 8, 5, 8, air());
+    p.fill(0, 6, 0, 8, 6, 5, bricks());
+    p.fill(0, 2, 0, 2, 5, 0, bricks());
+    p.fill(6, 2, 0, 8, 5, 0, bricks());
+    p.fill(1, 3, 0, 1, 4, 0, fence_we);
+    p.fill(7, 3, 0, 7, 4, 0, fence_we);
+    p.fill(0, 2, 4, 8, 2, 8, bricks());
+    p.fill(1, 1, 4, 2, 2, 4, air());
+    p.fill(6, 1, 4, 7, 2, 4, air());
+    p.fill(1, 3, 8, 7, 3, 8, fence_we);
+    p.set_block(0, 3, 8, fence(false, true, true, false));
+    p.set_block(8, 3, 8, fence(false, false, true, true));
+    p.fill(0, 3, 6, 0, 3, 7, fence_ns);
+    p.fill(8, 3, 6, 8, 3, 7, fence_ns);
+    p.fill(0, 3, 4, 0, 5, 5, bricks());
+    p.fill(8, 3, 4, 8, 5, 5, bricks());
+    p.fill(1, 3, 5, 2, 5, 5, bricks());
+    p.fill(6, 3, 5, 7, 5, 5, bricks());
+    p.fill(1, 4, 5, 1, 5, 5, fence_we);
+    p.fill(7, 4, 5, 7, 5, 5, fence_we);
+
+    fill_foundations(p, 0, 0, 8, 5);
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_vanilla::biome::NETHER_WASTES;
+
+    use crate::view::TestLevelView;
+
+    use super::*;
+
+    #[test]
+    fn fortress() {
+
+        let fortress = NetherFortress::new(0);
+
+        // Find the first fortress around the origin.
+        let start = (-64..64)
+            .flat_map(|cx| (-64..64).map(move |cz| (cx, cz)))
+            .find_map(|(cx, cz)| fortress.generate_start(cx, cz).map(|start| (cx, cz, start)));
+
+        let (cx, cz, mut start) = start.expect("no fortress found");
+        let bb = *start.get_bounding_box();
+        assert!(start.get_pieces().len() > 1);
+        assert!(bb.min_y >= 48 && bb.min_y <= 70);
+        assert_eq!(start.get_pieces()[0].get_name(), "bridge_crossing");
+        assert!(bb.contains(cx * 16 + 2, bb.min_y, cz * 16 + 2));
+
+        // Same seed, same fortress.
+        let other = fortress.generate_start(cx, cz).unwrap();
+        assert_eq!(other.get_pieces().len(), start.get_pieces().len());
+        assert_eq!(*other.get_bounding_box(), bb);
+
+        // The start piece is placed with nether bricks.
+        let mut level = TestLevelView::new(NETHERRACK.get_default_state(), 30, &NETHER_WASTES);
+        start.place_in_chunk(&mut level, &mut JavaRandom::new(0), cx, cz);
+        assert!(level.count_blocks(NETHER_BRICKS.get_default_state()) > 0);
+
+        // Spawn overrides only apply inside pieces.
+        let (x, y, z) = start.get_pieces()[0].get_bounding_box().get_center();
+        assert!(fortress.get_spawn_overrides_at(x, y, z).is_some());
+        assert!(fortress.get_spawn_overrides_at(x, 2, z).is_none());
+
+    }
+
+}
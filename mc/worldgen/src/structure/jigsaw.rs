@@ -0,0 +1,785 @@
+//! Jigsaw structures, made of templates connected together through their jigsaw blocks.
+//!
+//! Templates are picked from weighted template pools, each jigsaw block of a placed template
+//! references a pool from which a template with a matching jigsaw is picked and attached,
+//! recursively up to a maximum depth.
+
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::sync::Arc;
+use std::fs::File;
+
+use serde_json::Value;
+
+use mc_core::world::anvil::decode::DecodeError;
+use mc_core::world::level::LevelEnv;
+use mc_core::rand::JavaRandom;
+
+use mc_vanilla::heightmap::WORLD_SURFACE;
+
+use crate::view::LevelView;
+
+use super::template::{StructureTemplate, TemplatePlaceSettings, TemplateJigsaw, Rotation};
+use super::piece::{BoundingBox, StructurePiece};
+
+
+/// Name of the empty pool, always present and used as the default fallback.
+pub const EMPTY_POOL: &str = "minecraft:empty";
+
+
+/// How an element is placed vertically relative to the element it's attached to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Projection {
+    /// The element is placed at the height of its jigsaw connection.
+    Rigid,
+    /// The element follows the ground height.
+    TerrainMatching
+}
+
+
+/// An element of a template pool.
+pub enum PoolElement {
+    /// An element that stops the generation of the jigsaw it's picked for.
+    Empty,
+    /// A single template.
+    Single {
+        template: Arc<StructureTemplate>,
+        projection: Projection
+    },
+    /// Multiple elements placed at the same position, only the jigsaws of the first element
+    /// are used.
+    List {
+        elements: Vec<PoolElement>,
+        projection: Projection
+    }
+}
+
+impl PoolElement {
+
+    pub fn get_projection(&self) -> Projection {
+        match *self {
+            Self::Empty => Projection::TerrainMatching,
+            Self::Single { projection, .. } | Self::List { projection, .. } => projection
+        }
+    }
+
+    /// Return the bounding box of this element when placed at the given position.
+    pub fn get_bounding_box(&self, x: i32, y: i32, z: i32, rotation: Rotation) -> BoundingBox {
+        match self {
+            Self::Empty => BoundingBox::new(x, y, z, x, y, z),
+            Self::Single { template, .. } => template.get_bounding_box(x, y, z, rotation),
+            Self::List { elements, .. } => {
+                let mut bb = elements[0].get_bounding_box(x, y, z, rotation);
+                for element in &elements[1..] {
+                    bb.encapsulate(&element.get_bounding_box(x, y, z, rotation));
+                }
+                bb
+            }
+        }
+    }
+
+    /// Return the jigsaws of this element when placed at the given position, in a random
+    /// order.
+    pub fn get_shuffled_jigsaws(&self, x: i32, y: i32, z: i32, rotation: Rotation, rand: &mut JavaRandom) -> Vec<TemplateJigsaw> {
+        match self {
+            Self::Empty => Vec::new(),
+            Self::Single { template, .. } => {
+                let mut jigsaws: Vec<TemplateJigsaw> = template.get_jigsaws().iter()
+                    .map(|jigsaw| {
+                        let mut jigsaw = jigsaw.rotated(rotation);
+                        jigsaw.x += x;
+                        jigsaw.y += y;
+                        jigsaw.z += z;
+                        jigsaw
+                    })
+                    .collect();
+                shuffle(rand, &mut jigsaws);
+                jigsaws
+            }
+            Self::List { elements, .. } => elements[0].get_shuffled_jigsaws(x, y, z, rotation, rand)
+        }
+    }
+
+    /// Return the height of the tallest template of this element, empty elements have no
+    /// height.
+    pub fn get_max_height(&self) -> i32 {
+        match self {
+            Self::Empty => 0,
+            Self::Single { template, .. } => template.get_size().1,
+            Self::List { elements, .. } => elements.iter().map(|e| e.get_max_height()).max().unwrap_or(0)
+        }
+    }
+
+    /// Place this element at the given position, clipped to the given bounding box. Terrain
+    /// matching elements are placed block by block on the surface.
+    // TODO: Apply the structure processors of the element.
+    #[allow(clippy::too_many_arguments)]
+    pub fn place(&self, level: &mut dyn LevelView, rand: &mut JavaRandom, x: i32, y: i32, z: i32, rotation: Rotation, bounding_box: BoundingBox) {
+        match self {
+            Self::Empty => {}
+            Self::Single { template, projection } => {
+                let mut settings = TemplatePlaceSettings::new()
+                    .with_rotation(rotation)
+                    .with_bounding_box(bounding_box)
+                    .with_air(true)
+                    .with_jigsaws_replaced(true);
+                if let Projection::TerrainMatching = projection {
+                    settings = settings.with_gravity(&WORLD_SURFACE, -1);
+                }
+                template.place(level, rand, x, y, z, &settings);
+            }
+            Self::List { elements, .. } => {
+                for element in elements {
+                    element.place(level, rand, x, y, z, rotation, bounding_box);
+                }
+            }
+        }
+    }
+
+}
+
+
+/// A weighted pool of elements, with a fallback pool used when no element of this pool can
+/// be attached.
+pub struct TemplatePool {
+    fallback: String,
+    /// Elements, each repeated as many times as its weight, like vanilla.
+    elements: Vec<Arc<PoolElement>>
+}
+
+impl TemplatePool {
+
+    pub fn new(fallback: &str) -> Self {
+        Self {
+            fallback: fallback.to_string(),
+            elements: Vec::new()
+        }
+    }
+
+    /// Add an element to this pool with the given weight.
+    pub fn with_element(mut self, element: PoolElement, weight: u32) -> Self {
+        let element = Arc::new(element);
+        for _ in 0..weight {
+            self.elements.push(Arc::clone(&element));
+        }
+        self
+    }
+
+    #[inline]
+    pub fn get_fallback(&self) -> &str {
+        &self.fallback
+    }
+
+    /// Return the number of elements in this pool, counting each element as many times as
+    /// its weight.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Pick a random element, depending on weights.
+    pub fn get_random_element(&self, rand: &mut JavaRandom) -> &Arc<PoolElement> {
+        &self.elements[rand.next_int_bounded(self.elements.len() as i32) as usize]
+    }
+
+    /// Return the height of the tallest element of this pool.
+    pub fn get_max_height(&self) -> i32 {
+        self.elements.iter().map(|e| e.get_max_height()).max().unwrap_or(0)
+    }
+
+    /// Return all elements in a random order, depending on weights.
+    pub fn get_shuffled_elements(&self, rand: &mut JavaRandom) -> Vec<Arc<PoolElement>> {
+        let mut elements = self.elements.clone();
+        shuffle(rand, &mut elements);
+        elements
+    }
+
+}
+
+
+/// A registry of template pools.
+pub struct TemplatePools {
+    pools: HashMap<String, TemplatePool>
+}
+
+impl TemplatePools {
+
+    /// Construct a registry with only the empty pool.
+    pub fn new() -> Self {
+        let mut pools = HashMap::new();
+        pools.insert(EMPTY_POOL.to_string(), TemplatePool::new(EMPTY_POOL));
+        Self { pools }
+    }
+
+    pub fn insert(&mut self, name: &str, pool: TemplatePool) {
+        self.pools.insert(name.to_string(), pool);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&TemplatePool> {
+        self.pools.get(name)
+    }
+
+    /// Load the given pool and all pools that can be reached from it, from a `data` directory
+    /// extracted from the game. Pools are read from `<namespace>/worldgen/template_pool` and
+    /// templates from `<namespace>/structures`. Feature elements are not supported and are
+    /// loaded as empty elements.
+    pub fn load(data_dir: &Path, start_pool: &str, env: &LevelEnv) -> Result<Self, DecodeError> {
+
+        let mut loader = PoolLoader {
+            data_dir,
+            env,
+            templates: HashMap::new(),
+            pools: Self::new()
+        };
+
+        let mut pending = vec![start_pool.to_string()];
+        while let Some(name) = pending.pop() {
+            if loader.pools.get(&name).is_none() {
+                let pool = loader.load_pool(&name)?;
+                pending.push(pool.fallback.clone());
+                for element in &pool.elements {
+                    collect_pools(element, &mut pending);
+                }
+                loader.pools.insert(&name, pool);
+            }
+        }
+
+        Ok(loader.pools)
+
+    }
+
+}
+
+impl Default for TemplatePools {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// Internal loader of template pools from JSON files.
+struct PoolLoader<'a> {
+    data_dir: &'a Path,
+    env: &'a LevelEnv,
+    templates: HashMap<String, Arc<StructureTemplate>>,
+    pools: TemplatePools
+}
+
+impl<'a> PoolLoader<'a> {
+
+    fn get_path(&self, name: &str, dir: &str, ext: &str) -> std::path::PathBuf {
+        let (namespace, path) = name.split_once(':').unwrap_or(("minecraft", name));
+        self.data_dir.join(namespace).join(dir).join(format!("{}.{}", path, ext))
+    }
+
+    fn load_pool(&mut self, name: &str) -> Result<TemplatePool, DecodeError> {
+
+        let path = self.get_path(name, "worldgen/template_pool", "json");
+        let file = File::open(&path)
+            .map_err(|e| DecodeError::Malformed(format!("Can't open template pool '{}': {}", name, e)))?;
+        let json: Value = serde_json::from_reader(file)
+            .map_err(|e| DecodeError::Malformed(format!("Invalid template pool '{}': {}", name, e)))?;
+
+        let malformed = || DecodeError::Malformed(format!("Malformed template pool '{}'.", name));
+
+        let fallback = json["fallback"].as_str().unwrap_or(EMPTY_POOL);
+        let mut pool = TemplatePool::new(fallback);
+        for json_entry in json["elements"].as_array().ok_or_else(malformed)? {
+            let weight = json_entry["weight"].as_u64().ok_or_else(malformed)?;
+            let element = self.load_element(&json_entry["element"])?;
+            pool = pool.with_element(element, weight as u32);
+        }
+
+        Ok(pool)
+
+    }
+
+    fn load_element(&mut self, json: &Value) -> Result<PoolElement, DecodeError> {
+
+        let projection = match json["projection"].as_str() {
+            Some("terrain_matching") => Projection::TerrainMatching,
+            _ => Projection::Rigid
+        };
+
+        match json["element_type"].as_str() {
+            Some("minecraft:single_pool_element") | Some("minecraft:legacy_single_pool_element") => {
+                let location = json["location"].as_str()
+                    .ok_or_else(|| DecodeError::Malformed("Pool element without location.".to_string()))?;
+                Ok(PoolElement::Single {
+                    template: self.load_template(location)?,
+                    projection
+                })
+            }
+            Some("minecraft:list_pool_element") => {
+                let mut elements = Vec::new();
+                for json_element in json["elements"].as_array().into_iter().flatten() {
+                    elements.push(self.load_element(json_element)?);
+                }
+                if elements.is_empty() {
+                    Ok(PoolElement::Empty)
+                } else {
+                    Ok(PoolElement::List { elements, projection })
+                }
+            }
+            _ => Ok(PoolElement::Empty)
+        }
+
+    }
+
+    fn load_template(&mut self, location: &str) -> Result<Arc<StructureTemplate>, DecodeError> {
+        if let Some(template) = self.templates.get(location) {
+            return Ok(Arc::clone(template));
+        }
+        let path = self.get_path(location, "structures", "nbt");
+        let template = Arc::new(StructureTemplate::from_file(&path, self.env)?);
+        self.templates.insert(location.to_string(), Arc::clone(&template));
+        Ok(template)
+    }
+
+}
+
+/// Collect the names of the pools referenced by the jigsaws of the given element.
+fn collect_pools(element: &PoolElement, pools: &mut Vec<String>) {
+    match element {
+        PoolElement::Empty => {}
+        PoolElement::Single { template, .. } => {
+            pools.extend(template.get_jigsaws().iter().map(|jigsaw| jigsaw.pool.clone()));
+        }
+        PoolElement::List { elements, .. } => {
+            for element in elements {
+                collect_pools(element, pools);
+            }
+        }
+    }
+}
+
+
+/// Shuffle the given slice, like Java's `Collections.shuffle`.
+fn shuffle<T>(rand: &mut JavaRandom, slice: &mut [T]) {
+    for i in (1..slice.len()).rev() {
+        slice.swap(i, rand.next_int_bounded(i as i32 + 1) as usize);
+    }
+}
+
+
+// PIECES //
+
+/// A structure piece made of a pool element.
+pub struct JigsawPiece {
+    element: Arc<PoolElement>,
+    x: i32,
+    y: i32,
+    z: i32,
+    rotation: Rotation,
+    ground_level_delta: i32,
+    bb: BoundingBox
+}
+
+impl JigsawPiece {
+
+    #[inline]
+    pub fn get_element(&self) -> &Arc<PoolElement> {
+        &self.element
+    }
+
+    #[inline]
+    pub fn get_position(&self) -> (i32, i32, i32) {
+        (self.x, self.y, self.z)
+    }
+
+    #[inline]
+    pub fn get_rotation(&self) -> Rotation {
+        self.rotation
+    }
+
+}
+
+impl StructurePiece for JigsawPiece {
+
+    fn get_name(&self) -> &'static str {
+        "jigsaw"
+    }
+
+    fn get_bounding_box(&self) -> &BoundingBox {
+        &self.bb
+    }
+
+    fn offset(&mut self, dx: i32, dy: i32, dz: i32) {
+        self.x += dx;
+        self.y += dy;
+        self.z += dz;
+        self.bb.offset(dx, dy, dz);
+    }
+
+    fn place(&mut self, level: &mut dyn LevelView, rand: &mut JavaRandom, chunk_bb: &BoundingBox) -> bool {
+        self.element.place(level, rand, self.x, self.y, self.z, self.rotation, *chunk_bb);
+        true
+    }
+
+}
+
+
+// ASSEMBLY //
+
+/// Free space where pieces can be placed, made of bounds minus occupied boxes.
+struct FreeSpace {
+    bounds: BoundingBox,
+    occupied: Vec<BoundingBox>
+}
+
+impl FreeSpace {
+
+    fn fits(&self, bb: &BoundingBox) -> bool {
+        bb.min_x >= self.bounds.min_x && bb.max_x <= self.bounds.max_x &&
+            bb.min_y >= self.bounds.min_y && bb.max_y <= self.bounds.max_y &&
+            bb.min_z >= self.bounds.min_z && bb.max_z <= self.bounds.max_z &&
+            !self.occupied.iter().any(|occupied| occupied.intersects(bb))
+    }
+
+}
+
+/// Configuration of a jigsaw assembly.
+#[derive(Debug, Clone, Copy)]
+pub struct JigsawConfig<'a> {
+    /// Name of the pool to pick the start element from.
+    pub start_pool: &'a str,
+    /// Maximum depth of pieces from the start piece.
+    pub max_depth: u32,
+    /// Place the start piece relative to the ground height instead of the given Y.
+    pub project_start: bool,
+    /// Enable the vanilla hack used by pillager outposts and villages, which enlarges the
+    /// space of small pieces upward so that their children can be placed above them.
+    pub expansion_hack: bool
+}
+
+impl<'a> JigsawConfig<'a> {
+
+    pub fn new(start_pool: &'a str, max_depth: u32) -> Self {
+        Self {
+            start_pool,
+            max_depth,
+            project_start: false,
+            expansion_hack: false
+        }
+    }
+
+    pub fn with_start_projected(mut self, project_start: bool) -> Self {
+        self.project_start = project_start;
+        self
+    }
+
+    pub fn with_expansion_hack(mut self, expansion_hack: bool) -> Self {
+        self.expansion_hack = expansion_hack;
+        self
+    }
+
+}
+
+
+/// Internal state of the jigsaw assembly.
+struct Assembler<'a> {
+    pools: &'a TemplatePools,
+    max_depth: u32,
+    expansion_hack: bool,
+    rand: &'a mut JavaRandom,
+    ground_height: &'a dyn Fn(i32, i32) -> i32,
+    pieces: Vec<JigsawPiece>,
+    spaces: Vec<FreeSpace>,
+    /// Pieces to place children for, with their free space and their depth.
+    pending: VecDeque<(usize, usize, u32)>
+}
+
+/// Assemble a jigsaw structure from a random element of the configured start pool, centered
+/// horizontally on the given position. Pieces are connected up to the configured depth and
+/// must stay within 80 blocks of the start. The ground height function is only used for
+/// terrain matching elements and for projecting the start. Return no piece if the start pool
+/// doesn't exist or is empty.
+///
+/// Valid for: 1.16.2 to 1.16.5
+pub fn assemble(
+    pools: &TemplatePools,
+    config: &JigsawConfig,
+    x: i32, y: i32, z: i32,
+    rand: &mut JavaRandom,
+    ground_height: &dyn Fn(i32, i32) -> i32
+) -> Vec<JigsawPiece> {
+
+    let max_depth = config.max_depth;
+    let rotation = Rotation::random(rand);
+    let element = match pools.get(config.start_pool) {
+        Some(pool) if !pool.is_empty() => Arc::clone(pool.get_random_element(rand)),
+        _ => return Vec::new()
+    };
+
+    let bb = element.get_bounding_box(x, y, z, rotation);
+    let mut start = JigsawPiece {
+        element,
+        x,
+        y,
+        z,
+        rotation,
+        ground_level_delta: 1,
+        bb
+    };
+
+    let center_x = (bb.max_x + bb.min_x) / 2;
+    let center_z = (bb.max_z + bb.min_z) / 2;
+    let center_y = if config.project_start {
+        y + ground_height(center_x, center_z)
+    } else {
+        y
+    };
+    start.offset(0, center_y - (bb.min_y + start.ground_level_delta), 0);
+
+    let mut assembler = Assembler {
+        pools,
+        max_depth,
+        expansion_hack: config.expansion_hack,
+        rand,
+        ground_height,
+        pieces: vec![start],
+        spaces: Vec::new(),
+        pending: VecDeque::new()
+    };
+
+    if max_depth > 0 {
+        assembler.spaces.push(FreeSpace {
+            bounds: BoundingBox::new(center_x - 80, center_y - 80, center_z - 80, center_x + 80, center_y + 80, center_z + 80),
+            occupied: vec![assembler.pieces[0].bb]
+        });
+        assembler.pending.push_back((0, 0, 0));
+        while let Some((piece_index, space_index, depth)) = assembler.pending.pop_front() {
+            assembler.place_children(piece_index, space_index, depth);
+        }
+    }
+
+    assembler.pieces
+
+}
+
+impl<'a> Assembler<'a> {
+
+    fn place_children(&mut self, piece_index: usize, space_index: usize, depth: u32) {
+
+        let piece = &self.pieces[piece_index];
+        let element = Arc::clone(&piece.element);
+        let rigid = element.get_projection() == Projection::Rigid;
+        let parent_bb = piece.bb;
+        let parent_ground_delta = piece.ground_level_delta;
+        let min_y = parent_bb.min_y;
+
+        let mut inside_space_index = None;
+
+        let jigsaws = element.get_shuffled_jigsaws(piece.x, piece.y, piece.z, piece.rotation, self.rand);
+        'jigsaws: for jigsaw in jigsaws {
+
+            let (fx, fy, fz) = jigsaw.front.normal(1);
+            let (tx, ty, tz) = (jigsaw.x + fx, jigsaw.y + fy, jigsaw.z + fz);
+            let jigsaw_dy = jigsaw.y - min_y;
+            let mut ground = None;
+
+            let pool = match self.pools.get(&jigsaw.pool) {
+                Some(pool) if !pool.is_empty() || jigsaw.pool == EMPTY_POOL => pool,
+                _ => continue
+            };
+
+            let fallback = match self.pools.get(pool.get_fallback()) {
+                Some(fallback) if !fallback.is_empty() || pool.get_fallback() == EMPTY_POOL => fallback,
+                _ => continue
+            };
+
+            let space_index = if parent_bb.contains(tx, ty, tz) {
+                *inside_space_index.get_or_insert_with(|| {
+                    self.spaces.push(FreeSpace {
+                        bounds: parent_bb,
+                        occupied: Vec::new()
+                    });
+                    self.spaces.len() - 1
+                })
+            } else {
+                space_index
+            };
+
+            let mut candidates = Vec::new();
+            if depth != self.max_depth {
+                candidates.extend(pool.get_shuffled_elements(self.rand));
+            }
+            candidates.extend(fallback.get_shuffled_elements(self.rand));
+
+            for candidate in candidates {
+
+                if let PoolElement::Empty = *candidate {
+                    break;
+                }
+
+                for rotation in Rotation::shuffled(self.rand) {
+
+                    let candidate_jigsaws = candidate.get_shuffled_jigsaws(0, 0, 0, rotation, self.rand);
+
+                    let zero_bb = candidate.get_bounding_box(0, 0, 0, rotation);
+                    let expanded_height = if self.expansion_hack && zero_bb.get_size_y() <= 16 {
+                        candidate_jigsaws.iter()
+                            .map(|candidate_jigsaw| {
+                                let (fx, fy, fz) = candidate_jigsaw.front.normal(1);
+                                if !zero_bb.contains(candidate_jigsaw.x + fx, candidate_jigsaw.y + fy, candidate_jigsaw.z + fz) {
+                                    return 0;
+                                }
+                                match self.pools.get(&candidate_jigsaw.pool) {
+                                    Some(pool) => {
+                                        let fallback_height = self.pools.get(pool.get_fallback())
+                                            .map_or(0, |fallback| fallback.get_max_height());
+                                        pool.get_max_height().max(fallback_height)
+                                    }
+                                    None => 0
+                                }
+                            })
+                            .max()
+                            .unwrap_or(0)
+                    } else {
+                        0
+                    };
+                    for candidate_jigsaw in candidate_jigsaws {
+
+                        if !jigsaw.can_attach(&candidate_jigsaw) {
+                            continue;
+                        }
+
+                        let (ox, oy, oz) = (tx - candidate_jigsaw.x, ty - candidate_jigsaw.y, tz - candidate_jigsaw.z);
+                        let candidate_bb = candidate.get_bounding_box(ox, oy, oz, rotation);
+                        let candidate_rigid = candidate.get_projection() == Projection::Rigid;
+                        let candidate_jigsaw_y = candidate_jigsaw.y;
+                        let dy = jigsaw_dy - candidate_jigsaw_y + fy;
+
+                        let target_y = if rigid && candidate_rigid {
+                            min_y + dy
+                        } else {
+                            let ground = *ground.get_or_insert_with(|| (self.ground_height)(jigsaw.x, jigsaw.z));
+                            ground - candidate_jigsaw_y
+                        };
+
+                        let offset_y = target_y - candidate_bb.min_y;
+                        let mut child_bb = candidate_bb;
+                        child_bb.offset(0, offset_y, 0);
+
+                        if expanded_height > 0 {
+                            let size_y = (expanded_height + 1).max(child_bb.max_y - child_bb.min_y);
+                            child_bb.max_y = child_bb.min_y + size_y;
+                        }
+
+                        let space = &mut self.spaces[space_index];
+                        if space.fits(&child_bb) {
+
+                            space.occupied.push(child_bb);
+
+                            let ground_level_delta = if candidate_rigid {
+                                parent_ground_delta - dy
+                            } else {
+                                1
+                            };
+
+                            self.pieces.push(JigsawPiece {
+                                element: candidate,
+                                x: ox,
+                                y: oy + offset_y,
+                                z: oz,
+                                rotation,
+                                ground_level_delta,
+                                bb: child_bb
+                            });
+
+                            if depth < self.max_depth {
+                                self.pending.push_back((self.pieces.len() - 1, space_index, depth + 1));
+                            }
+
+                            continue 'jigsaws;
+
+                        }
+
+                    }
+
+                }
+
+            }
+
+        }
+
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_core::pos::Direction;
+    use mc_vanilla::block::{STONE, AIR};
+
+    use super::super::template::TemplateBlock;
+    use super::*;
+
+    fn new_jigsaw(x: i32, z: i32, front: Direction, name: &str, target: &str, pool: &str) -> TemplateJigsaw {
+        TemplateJigsaw {
+            x,
+            y: 0,
+            z,
+            front,
+            top: Direction::Up,
+            name: name.to_string(),
+            target: target.to_string(),
+            pool: pool.to_string(),
+            final_state: AIR.get_default_state(),
+            rollable: false
+        }
+    }
+
+    #[test]
+    fn jigsaw_assembly() {
+
+        let stone = STONE.get_default_state();
+        let floor = |size: i32| (0..size)
+            .flat_map(|x| (0..size).map(move |z| TemplateBlock { x, y: 0, z, state: stone }))
+            .collect::<Vec<_>>();
+
+        // A 5x5 room with an exit on each side, and a 3x3 dead end.
+        let room = StructureTemplate::with_jigsaws((5, 1, 5), floor(5), vec![
+            new_jigsaw(2, 0, Direction::North, "room", "room", "test:rooms"),
+            new_jigsaw(2, 4, Direction::South, "room", "room", "test:rooms"),
+            new_jigsaw(0, 2, Direction::West, "room", "room", "test:rooms"),
+            new_jigsaw(4, 2, Direction::East, "room", "room", "test:rooms"),
+        ]);
+        let end = StructureTemplate::with_jigsaws((3, 1, 3), floor(3), vec![
+            new_jigsaw(1, 0, Direction::North, "room", "room", "test:rooms"),
+        ]);
+
+        let single = |template: StructureTemplate| PoolElement::Single {
+            template: Arc::new(template),
+            projection: Projection::Rigid
+        };
+
+        let mut pools = TemplatePools::new();
+        pools.insert("test:rooms", TemplatePool::new("test:ends").with_element(single(room), 1));
+        pools.insert("test:ends", TemplatePool::new(EMPTY_POOL).with_element(single(end), 1));
+
+        let mut rand = JavaRandom::new(0);
+        let pieces = assemble(&pools, &JigsawConfig::new("test:rooms", 3), 0, 64, 0, &mut rand, &|_, _| 64);
+        assert!(pieces.len() > 1);
+
+        // All pieces are on the same level and never overlap.
+        for (i, piece) in pieces.iter().enumerate() {
+            assert_eq!(piece.get_bounding_box().min_y, 63);
+            for other in &pieces[i + 1..] {
+                assert!(!piece.get_bounding_box().intersects(other.get_bounding_box()));
+            }
+        }
+
+        // Missing start pool.
+        assert!(assemble(&pools, &JigsawConfig::new("test:missing", 3), 0, 64, 0, &mut rand, &|_, _| 64).is_empty());
+
+    }
+
+}
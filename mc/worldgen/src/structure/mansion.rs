@@ -0,0 +1,1343 @@
+//! Woodland mansion structure, a three floors building whose layout is generated on a grid
+//! of 8x8 cells, each room is then filled with a random room template.
+//!
+//! The first and second floors share the same base grid, made of corridors recursively
+//! generated from the entrance and surrounded by rooms. The third floor is smaller and is
+//! only reached through a staircase room of the second floor. Rooms without door toward a
+//! corridor are secret rooms, only reachable by breaking walls.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use mc_core::world::anvil::decode::DecodeError;
+use mc_core::world::level::LevelEnv;
+use mc_core::block::BlockState;
+use mc_core::rand::JavaRandom;
+use mc_core::pos::Direction;
+
+use mc_vanilla::block::*;
+
+use crate::view::LevelView;
+
+use super::template::{StructureTemplate, TemplatePlaceSettings, Rotation, Mirror};
+use super::piece::{BoundingBox, PiecePlacer, StructurePiece, StructureStart, set_loot_table};
+use super::{new_start_random, PieceStructure, RandomSpread, SurfaceHeight};
+
+
+/// Loot table of woodland mansion chests.
+pub const MANSION_LOOT_TABLE: &str = "minecraft:chests/woodland_mansion";
+
+pub const MANSION_SPREAD: RandomSpread = RandomSpread {
+    spacing: 80,
+    separation: 20,
+    salt: 10387319,
+    triangular: true
+};
+
+/// Mansions are not generated if the surface is lower than this height.
+const MIN_HEIGHT: i32 = 60;
+
+/// Names of the templates used for the walls, roofs and corridors of the mansion.
+const STRUCTURE_TEMPLATES: [&str; 21] = [
+    "entrance", "wall_flat", "wall_window", "wall_corner", "roof", "roof_front", "roof_corner",
+    "roof_inner_corner", "small_wall", "small_wall_corner", "corridor_floor", "carpet_north",
+    "carpet_east", "carpet_south_1", "carpet_south_2", "carpet_west_1", "carpet_west_2",
+    "indoors_wall_1", "indoors_wall_2", "indoors_door_1", "indoors_door_2"
+];
+
+
+// FLOOR ROOMS //
+
+/// Room templates available for a floor.
+struct FloorRooms {
+    room_1x1: &'static [&'static str],
+    room_1x1_secret: &'static [&'static str],
+    room_1x2_side: &'static [&'static str],
+    room_1x2_side_stairs: Option<&'static str>,
+    room_1x2_front: &'static [&'static str],
+    room_1x2_front_stairs: Option<&'static str>,
+    room_1x2_secret: &'static [&'static str],
+    room_2x2: &'static [&'static str],
+    room_2x2_secret: &'static str
+}
+
+static FIRST_FLOOR_ROOMS: FloorRooms = FloorRooms {
+    room_1x1: &["1x1_a1", "1x1_a2", "1x1_a3", "1x1_a4", "1x1_a5"],
+    room_1x1_secret: &["1x1_as1", "1x1_as2", "1x1_as3", "1x1_as4"],
+    room_1x2_side: &["1x2_a1", "1x2_a2", "1x2_a3", "1x2_a4", "1x2_a5", "1x2_a6", "1x2_a7", "1x2_a8", "1x2_a9"],
+    room_1x2_side_stairs: None,
+    room_1x2_front: &["1x2_b1", "1x2_b2", "1x2_b3", "1x2_b4", "1x2_b5"],
+    room_1x2_front_stairs: None,
+    room_1x2_secret: &["1x2_s1", "1x2_s2"],
+    room_2x2: &["2x2_a1", "2x2_a2", "2x2_a3", "2x2_a4"],
+    room_2x2_secret: "2x2_s1"
+};
+
+/// Rooms of the second floor, also used by the third floor.
+static SECOND_FLOOR_ROOMS: FloorRooms = FloorRooms {
+    room_1x1: &["1x1_b1", "1x1_b2", "1x1_b3", "1x1_b4"],
+    room_1x1_secret: &["1x1_as1", "1x1_as2", "1x1_as3", "1x1_as4"],
+    room_1x2_side: &["1x2_c1", "1x2_c2", "1x2_c3", "1x2_c4"],
+    room_1x2_side_stairs: Some("1x2_c_stairs"),
+    room_1x2_front: &["1x2_d1", "1x2_d2", "1x2_d3", "1x2_d4", "1x2_d5"],
+    room_1x2_front_stairs: Some("1x2_d_stairs"),
+    room_1x2_secret: &["1x2_se1"],
+    room_2x2: &["2x2_b1", "2x2_b2", "2x2_b3", "2x2_b4", "2x2_b5"],
+    room_2x2_secret: "2x2_s1"
+};
+
+impl FloorRooms {
+
+    fn get_floor(floor: usize) -> &'static Self {
+        if floor == 0 { &FIRST_FLOOR_ROOMS } else { &SECOND_FLOOR_ROOMS }
+    }
+
+    fn iter_names(&'static self) -> impl Iterator<Item = &'static str> {
+        self.room_1x1.iter()
+            .chain(self.room_1x1_secret)
+            .chain(self.room_1x2_side)
+            .chain(&self.room_1x2_side_stairs)
+            .chain(self.room_1x2_front)
+            .chain(&self.room_1x2_front_stairs)
+            .chain(self.room_1x2_secret)
+            .chain(self.room_2x2)
+            .chain(std::iter::once(&self.room_2x2_secret))
+            .copied()
+    }
+
+    fn get_1x2_side(&self, rand: &mut JavaRandom, stairs: bool) -> &'static str {
+        match self.room_1x2_side_stairs {
+            Some(name) if stairs => name,
+            _ => pick(rand, self.room_1x2_side)
+        }
+    }
+
+    fn get_1x2_front(&self, rand: &mut JavaRandom, stairs: bool) -> &'static str {
+        match self.room_1x2_front_stairs {
+            Some(name) if stairs => name,
+            _ => pick(rand, self.room_1x2_front)
+        }
+    }
+
+}
+
+fn pick(rand: &mut JavaRandom, names: &[&'static str]) -> &'static str {
+    names[rand.next_int_bounded(names.len() as i32) as usize]
+}
+
+
+/// The woodland mansion structure. This structure should only be decorated in dark forests.
+///
+/// Valid for: 1.16.2 to 1.17.1
+pub struct WoodlandMansion {
+    seed: i64,
+    templates: HashMap<&'static str, Arc<StructureTemplate>>,
+    surface: SurfaceHeight
+}
+
+impl WoodlandMansion {
+
+    /// Return the names of all the vanilla templates used by mansions.
+    pub fn get_template_names() -> Vec<&'static str> {
+        let mut names: Vec<&'static str> = STRUCTURE_TEMPLATES.iter().copied()
+            .chain(FIRST_FLOOR_ROOMS.iter_names())
+            .chain(SECOND_FLOOR_ROOMS.iter_names())
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    /// Construct a woodland mansion structure, the templates are mapped by their vanilla names
+    /// and must contain all mansion templates. The surface height of the chunk generator is
+    /// used to place the mansion.
+    pub fn new(seed: i64, templates: HashMap<&'static str, Arc<StructureTemplate>>, surface: SurfaceHeight) -> Self {
+        for name in Self::get_template_names() {
+            assert!(templates.contains_key(name), "missing woodland mansion template '{}'", name);
+        }
+        Self { seed, templates, surface }
+    }
+
+    /// Load the vanilla mansion templates from the given directory, typically the
+    /// `data/minecraft/structures/woodland_mansion` directory extracted from the game.
+    pub fn load_vanilla(seed: i64, dir: &Path, env: &LevelEnv, surface: SurfaceHeight) -> Result<Self, DecodeError> {
+        let mut templates = HashMap::new();
+        for name in Self::get_template_names() {
+            templates.insert(name, Arc::new(StructureTemplate::from_file(&dir.join(format!("{}.nbt", name)), env)?));
+        }
+        Ok(Self::new(seed, templates, surface))
+    }
+
+}
+
+impl PieceStructure for WoodlandMansion {
+
+    fn get_range(&self) -> i32 {
+        8
+    }
+
+    fn generate_start(&self, cx: i32, cz: i32) -> Option<StructureStart> {
+
+        let mut rand = JavaRandom::new(0);
+        if MANSION_SPREAD.get_start_chunk(self.seed, cx, cz, &mut rand) != (cx, cz) {
+            return None;
+        }
+
+        let mut rand = new_start_random(self.seed, cx, cz);
+        let rotation = Rotation::random(&mut rand);
+
+        // Offsets toward the rotated corner of the mansion's entrance.
+        let (dx, dz) = match rotation {
+            Rotation::None => (5, 5),
+            Rotation::Clockwise90 => (-5, 5),
+            Rotation::Clockwise180 => (-5, -5),
+            Rotation::CounterClockwise90 => (5, -5)
+        };
+
+        let (x, z) = (cx * 16 + 7, cz * 16 + 7);
+        let height = (self.surface)(x, z)
+            .min((self.surface)(x, z + dz))
+            .min((self.surface)(x + dx, z))
+            .min((self.surface)(x + dx, z + dz));
+
+        // The surface function returns the first free block, vanilla uses the first
+        // occupied block, hence the offset.
+        if height - 1 < MIN_HEIGHT {
+            return None;
+        }
+
+        let grid = MansionGrid::new(&mut rand);
+        let mut placer = MansionPiecePlacer {
+            rand: &mut rand,
+            start_x: 0,
+            start_y: 0,
+            pieces: Vec::new()
+        };
+        placer.create_mansion((cx * 16 + 8, height, cz * 16 + 8), rotation, &grid);
+
+        let pieces = placer.pieces.into_iter()
+            .map(|(name, (x, y, z), rotation, mirror)| {
+                let template = Arc::clone(&self.templates[name]);
+                let bb = template.get_mirrored_bounding_box(x, y, z, rotation, mirror);
+                Box::new(MansionPiece { template, x, y, z, rotation, mirror, bb }) as Box<dyn StructurePiece>
+            })
+            .collect();
+
+        Some(StructureStart::new(pieces))
+
+    }
+
+    fn place_start_in_chunk(&self, start: &mut StructureStart, level: &mut dyn LevelView, rand: &mut JavaRandom, cx: i32, cz: i32) {
+
+        start.place_in_chunk(level, rand, cx, cz);
+
+        // Fill the void below the mansion's floor with cobblestone, down to the ground.
+        let y = start.get_bounding_box().min_y;
+        for x in (cx * 16)..(cx * 16 + 16) {
+            for z in (cz * 16)..(cz * 16 + 16) {
+                if !level.get_block_at(x, y, z).is_ok_and(|state| !is_air(state)) || !start.is_inside_piece(x, y, z) {
+                    continue;
+                }
+                for fy in (2..y).rev() {
+                    match level.get_block_at(x, fy, z) {
+                        Ok(state) if is_air(state) || is_liquid(state) => {
+                            let _ = level.set_block_at(x, fy, z, COBBLESTONE.get_default_state());
+                        }
+                        _ => break
+                    }
+                }
+            }
+        }
+
+    }
+
+}
+
+fn is_air(state: &'static BlockState) -> bool {
+    state.is_block(&AIR) || state.is_block(&CAVE_AIR) || state.is_block(&VOID_AIR)
+}
+
+fn is_liquid(state: &'static BlockState) -> bool {
+    state.is_block(&WATER) || state.is_block(&LAVA)
+}
+
+
+// GRID //
+
+/// Cell values of the layout grids.
+const CELL_EMPTY: i32 = 0;
+const CELL_CORRIDOR: i32 = 1;
+const CELL_ROOM: i32 = 2;
+const CELL_STAIRS: i32 = 3;
+const CELL_UNUSED: i32 = 4;
+const CELL_BLOCKED: i32 = 5;
+
+/// Cell values of the room grids, made of a room id, a room size and flags.
+const ROOM_ID_MASK: i32 = 0xFFFF;
+const ROOM_SIZE_MASK: i32 = 0xF0000;
+const ROOM_1X1: i32 = 0x10000;
+const ROOM_1X2: i32 = 0x20000;
+const ROOM_2X2: i32 = 0x40000;
+const ROOM_ORIGIN_FLAG: i32 = 0x100000;
+const ROOM_DOOR_FLAG: i32 = 0x200000;
+const ROOM_STAIRS_FLAG: i32 = 0x400000;
+const ROOM_CORRIDOR_FLAG: i32 = 0x800000;
+
+/// Horizontal directions, in the vanilla iteration order.
+const HORIZONTAL: [Direction; 4] = [Direction::North, Direction::East, Direction::South, Direction::West];
+
+fn step(direction: Direction) -> (i32, i32) {
+    let (dx, _, dz) = direction.normal(1);
+    (dx, dz)
+}
+
+fn clockwise(direction: Direction) -> Direction {
+    Rotation::Clockwise90.rotate_direction(direction)
+}
+
+fn counter_clockwise(direction: Direction) -> Direction {
+    Rotation::CounterClockwise90.rotate_direction(direction)
+}
+
+
+/// A 2D grid of integers, with a value for cells outside of the grid.
+struct SimpleGrid {
+    cells: Vec<i32>,
+    width: i32,
+    height: i32,
+    outside: i32
+}
+
+impl SimpleGrid {
+
+    fn new(width: i32, height: i32, outside: i32) -> Self {
+        Self {
+            cells: vec![0; (width * height) as usize],
+            width,
+            height,
+            outside
+        }
+    }
+
+    fn set(&mut self, x: i32, y: i32, value: i32) {
+        if x >= 0 && x < self.width && y >= 0 && y < self.height {
+            self.cells[(x + y * self.width) as usize] = value;
+        }
+    }
+
+    fn set_area(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, value: i32) {
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                self.set(x, y, value);
+            }
+        }
+    }
+
+    fn get(&self, x: i32, y: i32) -> i32 {
+        if x >= 0 && x < self.width && y >= 0 && y < self.height {
+            self.cells[(x + y * self.width) as usize]
+        } else {
+            self.outside
+        }
+    }
+
+    fn set_if(&mut self, x: i32, y: i32, if_value: i32, value: i32) {
+        if self.get(x, y) == if_value {
+            self.set(x, y, value);
+        }
+    }
+
+    fn edges_to(&self, x: i32, y: i32, value: i32) -> bool {
+        self.get(x - 1, y) == value || self.get(x + 1, y) == value ||
+            self.get(x, y + 1) == value || self.get(x, y - 1) == value
+    }
+
+    fn is_house(&self, x: i32, y: i32) -> bool {
+        matches!(self.get(x, y), CELL_CORRIDOR | CELL_ROOM | CELL_STAIRS | CELL_UNUSED)
+    }
+
+}
+
+
+/// The layout of a mansion, the base grid is shared by the first two floors.
+struct MansionGrid {
+    base: SimpleGrid,
+    third: SimpleGrid,
+    floor_rooms: [SimpleGrid; 3],
+    entrance_x: i32,
+    entrance_y: i32
+}
+
+impl MansionGrid {
+
+    fn new(rand: &mut JavaRandom) -> Self {
+
+        let (ex, ey) = (7, 4);
+
+        let mut base = SimpleGrid::new(11, 11, CELL_BLOCKED);
+        base.set_area(ex, ey, ex + 1, ey + 1, CELL_STAIRS);
+        base.set_area(ex - 1, ey, ex - 1, ey + 1, CELL_ROOM);
+        base.set_area(ex + 2, ey - 2, ex + 3, ey + 3, CELL_BLOCKED);
+        base.set_area(ex + 1, ey - 2, ex + 1, ey - 1, CELL_CORRIDOR);
+        base.set_area(ex + 1, ey + 2, ex + 1, ey + 3, CELL_CORRIDOR);
+        base.set(ex - 1, ey - 1, CELL_CORRIDOR);
+        base.set(ex - 1, ey + 2, CELL_CORRIDOR);
+        base.set_area(0, 0, 11, 1, CELL_BLOCKED);
+        base.set_area(0, 9, 11, 11, CELL_BLOCKED);
+        recursive_corridor(rand, &mut base, ex, ey - 2, Direction::West, 6);
+        recursive_corridor(rand, &mut base, ex, ey + 3, Direction::West, 6);
+        recursive_corridor(rand, &mut base, ex - 2, ey - 1, Direction::West, 3);
+        recursive_corridor(rand, &mut base, ex - 2, ey + 2, Direction::West, 3);
+        while clean_edges(&mut base) {}
+
+        let mut floor_rooms = [
+            SimpleGrid::new(11, 11, CELL_BLOCKED),
+            SimpleGrid::new(11, 11, CELL_BLOCKED),
+            SimpleGrid::new(11, 11, CELL_BLOCKED)
+        ];
+        identify_rooms(rand, &base, &mut floor_rooms[0]);
+        identify_rooms(rand, &base, &mut floor_rooms[1]);
+        floor_rooms[0].set_area(ex + 1, ey, ex + 1, ey + 1, ROOM_CORRIDOR_FLAG);
+        floor_rooms[1].set_area(ex + 1, ey, ex + 1, ey + 1, ROOM_CORRIDOR_FLAG);
+
+        let third = SimpleGrid::new(base.width, base.height, CELL_BLOCKED);
+        let mut grid = Self {
+            base,
+            third,
+            floor_rooms,
+            entrance_x: ex,
+            entrance_y: ey
+        };
+
+        grid.setup_third_floor(rand);
+        identify_rooms(rand, &grid.third, &mut grid.floor_rooms[2]);
+        grid
+
+    }
+
+    fn is_room_id(&self, x: i32, y: i32, floor: usize, room_id: i32) -> bool {
+        (self.floor_rooms[floor].get(x, y) & ROOM_ID_MASK) == room_id
+    }
+
+    /// Return the direction toward the other cell of a 1x2 room.
+    fn get_1x2_room_direction(&self, x: i32, y: i32, floor: usize, room_id: i32) -> Option<Direction> {
+        HORIZONTAL.iter().copied().find(|&direction| {
+            let (dx, dy) = step(direction);
+            self.is_room_id(x + dx, y + dy, floor, room_id)
+        })
+    }
+
+    /// Place the third floor above a random 1x2 room of the second floor, which becomes a
+    /// staircase room.
+    fn setup_third_floor(&mut self, rand: &mut JavaRandom) {
+
+        let mut candidates = Vec::new();
+        let rooms = &self.floor_rooms[1];
+        for y in 0..self.third.height {
+            for x in 0..self.third.width {
+                let room = rooms.get(x, y);
+                if room & ROOM_SIZE_MASK == ROOM_1X2 && room & ROOM_DOOR_FLAG != 0 {
+                    candidates.push((x, y));
+                }
+            }
+        }
+
+        let (width, height) = (self.third.width, self.third.height);
+        if candidates.is_empty() {
+            self.third.set_area(0, 0, width, height, CELL_BLOCKED);
+            return;
+        }
+
+        let (x, y) = candidates[rand.next_int_bounded(candidates.len() as i32) as usize];
+        let room = self.floor_rooms[1].get(x, y);
+        self.floor_rooms[1].set(x, y, room | ROOM_STAIRS_FLAG);
+
+        let direction = self.get_1x2_room_direction(x, y, 1, room & ROOM_ID_MASK)
+            .expect("1x2 rooms have two cells");
+        let (dx, dy) = step(direction);
+        let (other_x, other_y) = (x + dx, y + dy);
+
+        for cy in 0..height {
+            for cx in 0..width {
+                if !self.base.is_house(cx, cy) {
+                    self.third.set(cx, cy, CELL_BLOCKED);
+                } else if cx == x && cy == y {
+                    self.third.set(cx, cy, CELL_STAIRS);
+                } else if cx == other_x && cy == other_y {
+                    self.third.set(cx, cy, CELL_STAIRS);
+                    self.floor_rooms[2].set(cx, cy, ROOM_CORRIDOR_FLAG);
+                }
+            }
+        }
+
+        let directions: Vec<Direction> = HORIZONTAL.iter().copied()
+            .filter(|&direction| {
+                let (dx, dy) = step(direction);
+                self.third.get(other_x + dx, other_y + dy) == CELL_EMPTY
+            })
+            .collect();
+
+        if directions.is_empty() {
+            self.third.set_area(0, 0, width, height, CELL_BLOCKED);
+            self.floor_rooms[1].set(x, y, room);
+        } else {
+            let direction = directions[rand.next_int_bounded(directions.len() as i32) as usize];
+            let (dx, dy) = step(direction);
+            recursive_corridor(rand, &mut self.third, other_x + dx, other_y + dy, direction, 4);
+            while clean_edges(&mut self.third) {}
+        }
+
+    }
+
+}
+
+/// Recursively carve a corridor of the given length, surrounded by rooms.
+fn recursive_corridor(rand: &mut JavaRandom, grid: &mut SimpleGrid, x: i32, y: i32, direction: Direction, length: i32) {
+
+    if length <= 0 {
+        return;
+    }
+
+    let (sx, sy) = step(direction);
+    grid.set(x, y, CELL_CORRIDOR);
+    grid.set_if(x + sx, y + sy, CELL_EMPTY, CELL_CORRIDOR);
+
+    const FROM_2D_DATA: [Direction; 4] = [Direction::South, Direction::West, Direction::North, Direction::East];
+
+    for _ in 0..8 {
+        let next_direction = FROM_2D_DATA[rand.next_int_bounded(4) as usize];
+        if next_direction != direction.opposite() && (next_direction != Direction::East || !rand.next_boolean()) {
+            let (nx, ny) = step(next_direction);
+            let (cx, cy) = (x + sx, y + sy);
+            if grid.get(cx + nx, cy + ny) == CELL_EMPTY && grid.get(cx + nx * 2, cy + ny * 2) == CELL_EMPTY {
+                recursive_corridor(rand, grid, cx + nx, cy + ny, next_direction, length - 1);
+                break;
+            }
+        }
+    }
+
+    let (cwx, cwy) = step(clockwise(direction));
+    let (ccwx, ccwy) = step(counter_clockwise(direction));
+    grid.set_if(x + cwx, y + cwy, CELL_EMPTY, CELL_ROOM);
+    grid.set_if(x + ccwx, y + ccwy, CELL_EMPTY, CELL_ROOM);
+    grid.set_if(x + sx + cwx, y + sy + cwy, CELL_EMPTY, CELL_ROOM);
+    grid.set_if(x + sx + ccwx, y + sy + ccwy, CELL_EMPTY, CELL_ROOM);
+    grid.set_if(x + sx * 2, y + sy * 2, CELL_EMPTY, CELL_ROOM);
+    grid.set_if(x + cwx * 2, y + cwy * 2, CELL_EMPTY, CELL_ROOM);
+    grid.set_if(x + ccwx * 2, y + ccwy * 2, CELL_EMPTY, CELL_ROOM);
+
+}
+
+/// Turn empty cells mostly surrounded by the house into rooms, return true if any cell has
+/// been changed.
+fn clean_edges(grid: &mut SimpleGrid) -> bool {
+
+    let mut changed = false;
+
+    for y in 0..grid.height {
+        for x in 0..grid.width {
+            if grid.get(x, y) != CELL_EMPTY {
+                continue;
+            }
+            let sides = [(1, 0), (-1, 0), (0, 1), (0, -1)].iter()
+                .filter(|&&(dx, dy)| grid.is_house(x + dx, y + dy))
+                .count();
+            if sides >= 3 {
+                grid.set(x, y, CELL_ROOM);
+                changed = true;
+            } else if sides == 2 {
+                let corners = [(1, 1), (-1, 1), (1, -1), (-1, -1)].iter()
+                    .filter(|&&(dx, dy)| grid.is_house(x + dx, y + dy))
+                    .count();
+                if corners <= 1 {
+                    grid.set(x, y, CELL_ROOM);
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    changed
+
+}
+
+/// Group the room cells of the given grid into 2x2, 1x2 and 1x1 rooms. Each room has an
+/// origin cell, which also has a door if it's next to a corridor. Rooms without door are
+/// secret rooms.
+fn identify_rooms(rand: &mut JavaRandom, grid: &SimpleGrid, rooms: &mut SimpleGrid) {
+
+    let mut cells = Vec::new();
+    for y in 0..grid.height {
+        for x in 0..grid.width {
+            if grid.get(x, y) == CELL_ROOM {
+                cells.push((x, y));
+            }
+        }
+    }
+
+    for i in (1..cells.len()).rev() {
+        cells.swap(i, rand.next_int_bounded(i as i32 + 1) as usize);
+    }
+
+    let mut room_id = 10;
+
+    for (x, y) in cells {
+
+        if rooms.get(x, y) != 0 {
+            continue;
+        }
+
+        let is_free = |rooms: &SimpleGrid, dx: i32, dy: i32| {
+            rooms.get(x + dx, y + dy) == 0 && grid.get(x + dx, y + dy) == CELL_ROOM
+        };
+
+        let (mut min_x, mut max_x, mut min_y, mut max_y) = (x, x, y, y);
+        let mut size = ROOM_1X1;
+
+        if is_free(rooms, 1, 0) && is_free(rooms, 0, 1) && is_free(rooms, 1, 1) {
+            max_x = x + 1;
+            max_y = y + 1;
+            size = ROOM_2X2;
+        } else if is_free(rooms, -1, 0) && is_free(rooms, 0, 1) && is_free(rooms, -1, 1) {
+            min_x = x - 1;
+            max_y = y + 1;
+            size = ROOM_2X2;
+        } else if is_free(rooms, -1, 0) && is_free(rooms, 0, -1) && is_free(rooms, -1, -1) {
+            min_x = x - 1;
+            min_y = y - 1;
+            size = ROOM_2X2;
+        } else if is_free(rooms, 1, 0) {
+            max_x = x + 1;
+            size = ROOM_1X2;
+        } else if is_free(rooms, 0, 1) {
+            max_y = y + 1;
+            size = ROOM_1X2;
+        } else if is_free(rooms, -1, 0) {
+            min_x = x - 1;
+            size = ROOM_1X2;
+        } else if is_free(rooms, 0, -1) {
+            min_y = y - 1;
+            size = ROOM_1X2;
+        }
+
+        let mut origin_x = if rand.next_boolean() { min_x } else { max_x };
+        let mut origin_y = if rand.next_boolean() { min_y } else { max_y };
+        let mut door = ROOM_DOOR_FLAG;
+
+        if !grid.edges_to(origin_x, origin_y, CELL_CORRIDOR) {
+            origin_x = if origin_x == min_x { max_x } else { min_x };
+            origin_y = if origin_y == min_y { max_y } else { min_y };
+            if !grid.edges_to(origin_x, origin_y, CELL_CORRIDOR) {
+                origin_y = if origin_y == min_y { max_y } else { min_y };
+                if !grid.edges_to(origin_x, origin_y, CELL_CORRIDOR) {
+                    origin_x = if origin_x == min_x { max_x } else { min_x };
+                    origin_y = if origin_y == min_y { max_y } else { min_y };
+                    if !grid.edges_to(origin_x, origin_y, CELL_CORRIDOR) {
+                        door = 0;
+                        origin_x = min_x;
+                        origin_y = min_y;
+                    }
+                }
+            }
+        }
+
+        for ry in min_y..=max_y {
+            for rx in min_x..=max_x {
+                if rx == origin_x && ry == origin_y {
+                    rooms.set(rx, ry, ROOM_ORIGIN_FLAG | door | size | room_id);
+                } else {
+                    rooms.set(rx, ry, size | room_id);
+                }
+            }
+        }
+
+        room_id += 1;
+
+    }
+
+}
+
+
+// PLACER //
+
+type Pos = (i32, i32, i32);
+
+fn relative((x, y, z): Pos, direction: Direction, n: i32) -> Pos {
+    let (dx, dy, dz) = direction.normal(n);
+    (x + dx, y + dy, z + dz)
+}
+
+fn above((x, y, z): Pos, n: i32) -> Pos {
+    (x, y + n, z)
+}
+
+/// Current position and orientation while walking around the outer walls.
+struct PlacementData {
+    position: Pos,
+    rotation: Rotation,
+    wall_type: &'static str
+}
+
+/// Converts a mansion grid into a list of template pieces, each piece is a template name,
+/// its position, rotation and mirror.
+struct MansionPiecePlacer<'a> {
+    rand: &'a mut JavaRandom,
+    start_x: i32,
+    start_y: i32,
+    pieces: Vec<(&'static str, Pos, Rotation, Mirror)>
+}
+
+impl<'a> MansionPiecePlacer<'a> {
+
+    fn add(&mut self, name: &'static str, pos: Pos, rotation: Rotation) {
+        self.pieces.push((name, pos, rotation, Mirror::None));
+    }
+
+    fn add_mirrored(&mut self, name: &'static str, pos: Pos, rotation: Rotation, mirror: Mirror) {
+        self.pieces.push((name, pos, rotation, mirror));
+    }
+
+    /// Return the position of the given grid cell, relative to the start position.
+    fn get_cell_pos(&self, pos: Pos, rotation: Rotation, x: i32, y: i32) -> Pos {
+        let pos = relative(pos, rotation.rotate_direction(Direction::South), 8 + (y - self.start_y) * 8);
+        relative(pos, rotation.rotate_direction(Direction::East), (x - self.start_x) * 8)
+    }
+
+    fn create_mansion(&mut self, pos: Pos, rotation: Rotation, grid: &MansionGrid) {
+
+        let mut data = PlacementData { position: pos, rotation, wall_type: "wall_flat" };
+        self.entrance(&mut data);
+
+        let mut data1 = PlacementData { position: above(data.position, 8), rotation, wall_type: "wall_window" };
+
+        let base = &grid.base;
+        let third = &grid.third;
+        self.start_x = grid.entrance_x + 1;
+        self.start_y = grid.entrance_y + 1;
+        let (end_x, end_y) = (grid.entrance_x + 1, grid.entrance_y);
+        self.traverse_outer_walls(&mut data, base, Direction::South, self.start_x, self.start_y, end_x, end_y);
+        self.traverse_outer_walls(&mut data1, base, Direction::South, self.start_x, self.start_y, end_x, end_y);
+
+        let mut data2 = PlacementData { position: above(data.position, 19), rotation: data.rotation, wall_type: "wall_window" };
+        'third: for y in 0..third.height {
+            for x in (0..third.width).rev() {
+                if third.is_house(x, y) {
+                    data2.position = self.get_cell_pos(data2.position, rotation, x, y);
+                    self.traverse_wall_piece(&mut data2);
+                    self.traverse_outer_walls(&mut data2, third, Direction::South, x, y, x, y);
+                    break 'third;
+                }
+            }
+        }
+
+        self.create_roof(above(pos, 16), rotation, base, Some(third));
+        self.create_roof(above(pos, 27), rotation, third, None);
+
+        for floor in 0..3 {
+
+            let floor_pos = above(pos, 8 * floor as i32 + if floor == 2 { 3 } else { 0 });
+            let rooms = &grid.floor_rooms[floor];
+            let floor_grid = if floor == 2 { third } else { base };
+            let floor_rooms = FloorRooms::get_floor(floor);
+
+            let carpet_south = if floor == 0 { "carpet_south_1" } else { "carpet_south_2" };
+            let carpet_west = if floor == 0 { "carpet_west_1" } else { "carpet_west_2" };
+
+            let is_corridor = |x: i32, y: i32| {
+                floor_grid.get(x, y) == CELL_CORRIDOR || rooms.get(x, y) & ROOM_CORRIDOR_FLAG != 0
+            };
+
+            for y in 0..floor_grid.height {
+                for x in 0..floor_grid.width {
+                    if floor_grid.get(x, y) == CELL_CORRIDOR {
+                        let cell_pos = self.get_cell_pos(floor_pos, rotation, x, y);
+                        self.add("corridor_floor", cell_pos, rotation);
+                        if is_corridor(x, y - 1) {
+                            let p = relative(cell_pos, rotation.rotate_direction(Direction::East), 1);
+                            self.add("carpet_north", above(p, 1), rotation);
+                        }
+                        if is_corridor(x + 1, y) {
+                            let p = relative(cell_pos, rotation.rotate_direction(Direction::South), 1);
+                            let p = relative(p, rotation.rotate_direction(Direction::East), 5);
+                            self.add("carpet_east", above(p, 1), rotation);
+                        }
+                        if is_corridor(x, y + 1) {
+                            let p = relative(cell_pos, rotation.rotate_direction(Direction::South), 5);
+                            let p = relative(p, rotation.rotate_direction(Direction::West), 1);
+                            self.add(carpet_south, p, rotation);
+                        }
+                        if is_corridor(x - 1, y) {
+                            let p = relative(cell_pos, rotation.rotate_direction(Direction::West), 1);
+                            let p = relative(p, rotation.rotate_direction(Direction::North), 1);
+                            self.add(carpet_west, p, rotation);
+                        }
+                    }
+                }
+            }
+
+            let indoors_wall = if floor == 0 { "indoors_wall_1" } else { "indoors_wall_2" };
+            let indoors_door = if floor == 0 { "indoors_door_1" } else { "indoors_door_2" };
+
+            for y in 0..floor_grid.height {
+                for x in 0..floor_grid.width {
+
+                    let cell = floor_grid.get(x, y);
+                    let third_stairs = floor == 2 && cell == CELL_STAIRS;
+                    if cell != CELL_ROOM && !third_stairs {
+                        continue;
+                    }
+
+                    let room = rooms.get(x, y);
+                    let size = room & ROOM_SIZE_MASK;
+                    let room_id = room & ROOM_ID_MASK;
+                    let third_stairs = third_stairs && room & ROOM_CORRIDOR_FLAG != 0;
+
+                    let mut doors = Vec::new();
+                    if room & ROOM_DOOR_FLAG != 0 {
+                        for direction in HORIZONTAL {
+                            let (dx, dy) = step(direction);
+                            if floor_grid.get(x + dx, y + dy) == CELL_CORRIDOR {
+                                doors.push(direction);
+                            }
+                        }
+                    }
+
+                    let door = if !doors.is_empty() {
+                        Some(doors[self.rand.next_int_bounded(doors.len() as i32) as usize])
+                    } else if room & ROOM_ORIGIN_FLAG != 0 {
+                        Some(Direction::Up)
+                    } else {
+                        None
+                    };
+
+                    let wall_name = |direction: Direction| {
+                        if door == Some(direction) { indoors_door } else { indoors_wall }
+                    };
+
+                    let cell_pos = self.get_cell_pos(floor_pos, rotation, x, y);
+                    let cell_pos = relative(cell_pos, rotation.rotate_direction(Direction::East), -1);
+
+                    if floor_grid.is_house(x - 1, y) && !grid.is_room_id(x - 1, y, floor, room_id) {
+                        self.add(wall_name(Direction::West), cell_pos, rotation);
+                    }
+
+                    if floor_grid.get(x + 1, y) == CELL_CORRIDOR && !third_stairs {
+                        let p = relative(cell_pos, rotation.rotate_direction(Direction::East), 8);
+                        self.add(wall_name(Direction::East), p, rotation);
+                    }
+
+                    if floor_grid.is_house(x, y + 1) && !grid.is_room_id(x, y + 1, floor, room_id) {
+                        let p = relative(cell_pos, rotation.rotate_direction(Direction::South), 7);
+                        let p = relative(p, rotation.rotate_direction(Direction::East), 7);
+                        self.add(wall_name(Direction::South), p, rotation.rotated(Rotation::Clockwise90));
+                    }
+
+                    if floor_grid.get(x, y - 1) == CELL_CORRIDOR && !third_stairs {
+                        let p = relative(cell_pos, rotation.rotate_direction(Direction::North), 1);
+                        let p = relative(p, rotation.rotate_direction(Direction::East), 7);
+                        self.add(wall_name(Direction::North), p, rotation.rotated(Rotation::Clockwise90));
+                    }
+
+                    match (size, door) {
+                        (ROOM_1X1, _) => {
+                            self.add_room_1x1(cell_pos, rotation, door, floor_rooms);
+                        }
+                        (ROOM_1X2, Some(door)) => {
+                            let room_direction = grid.get_1x2_room_direction(x, y, floor, room_id);
+                            let stairs = room & ROOM_STAIRS_FLAG != 0;
+                            self.add_room_1x2(cell_pos, rotation, room_direction, door, floor_rooms, stairs);
+                        }
+                        (ROOM_2X2, Some(Direction::Up)) => {
+                            let p = relative(cell_pos, rotation.rotate_direction(Direction::East), 1);
+                            self.add(floor_rooms.room_2x2_secret, p, rotation);
+                        }
+                        (ROOM_2X2, Some(door)) => {
+                            let mut room_direction = clockwise(door);
+                            let (dx, dy) = step(room_direction);
+                            if !grid.is_room_id(x + dx, y + dy, floor, room_id) {
+                                room_direction = room_direction.opposite();
+                            }
+                            self.add_room_2x2(cell_pos, rotation, room_direction, door, floor_rooms);
+                        }
+                        _ => {}
+                    }
+
+                }
+            }
+
+        }
+
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn traverse_outer_walls(&mut self, data: &mut PlacementData, grid: &SimpleGrid, start_direction: Direction, start_x: i32, start_y: i32, end_x: i32, end_y: i32) {
+
+        let (mut x, mut y) = (start_x, start_y);
+        let mut direction = start_direction;
+
+        loop {
+
+            let (dx, dy) = step(direction);
+            let (ccwx, ccwy) = step(counter_clockwise(direction));
+
+            if !grid.is_house(x + dx, y + dy) {
+                self.traverse_turn(data);
+                direction = clockwise(direction);
+                if x != end_x || y != end_y || direction != start_direction {
+                    self.traverse_wall_piece(data);
+                }
+            } else if grid.is_house(x + dx + ccwx, y + dy + ccwy) {
+                self.traverse_inner_turn(data);
+                x += dx;
+                y += dy;
+                direction = counter_clockwise(direction);
+            } else {
+                x += dx;
+                y += dy;
+                if x != end_x || y != end_y || direction != start_direction {
+                    self.traverse_wall_piece(data);
+                }
+            }
+
+            if x == end_x && y == end_y && direction == start_direction {
+                break;
+            }
+
+        }
+
+    }
+
+    fn traverse_wall_piece(&mut self, data: &mut PlacementData) {
+        let direction = data.rotation.rotate_direction(Direction::South);
+        self.add(data.wall_type, relative(data.position, direction, 7), data.rotation);
+        data.position = relative(data.position, direction, 8);
+    }
+
+    fn traverse_turn(&mut self, data: &mut PlacementData) {
+        data.position = relative(data.position, data.rotation.rotate_direction(Direction::South), -1);
+        self.add("wall_corner", data.position, data.rotation);
+        data.position = relative(data.position, data.rotation.rotate_direction(Direction::South), -7);
+        data.position = relative(data.position, data.rotation.rotate_direction(Direction::West), -6);
+        data.rotation = data.rotation.rotated(Rotation::Clockwise90);
+    }
+
+    fn traverse_inner_turn(&mut self, data: &mut PlacementData) {
+        data.position = relative(data.position, data.rotation.rotate_direction(Direction::South), 6);
+        data.position = relative(data.position, data.rotation.rotate_direction(Direction::East), 8);
+        data.rotation = data.rotation.rotated(Rotation::CounterClockwise90);
+    }
+
+    fn entrance(&mut self, data: &mut PlacementData) {
+        let direction = data.rotation.rotate_direction(Direction::West);
+        self.add("entrance", relative(data.position, direction, 9), data.rotation);
+        data.position = relative(data.position, data.rotation.rotate_direction(Direction::South), 16);
+    }
+
+    fn create_roof(&mut self, pos: Pos, rotation: Rotation, grid: &SimpleGrid, above_grid: Option<&SimpleGrid>) {
+
+        let east = rotation.rotate_direction(Direction::East);
+        let west = rotation.rotate_direction(Direction::West);
+        let south = rotation.rotate_direction(Direction::South);
+        let north = rotation.rotate_direction(Direction::North);
+        let is_above = |x: i32, y: i32| above_grid.is_some_and(|above| above.is_house(x, y));
+
+        for y in 0..grid.height {
+            for x in 0..grid.width {
+                if grid.is_house(x, y) && !is_above(x, y) {
+                    let cell_pos = self.get_cell_pos(pos, rotation, x, y);
+                    self.add("roof", above(cell_pos, 3), rotation);
+                    if !grid.is_house(x + 1, y) {
+                        self.add("roof_front", relative(cell_pos, east, 6), rotation);
+                    }
+                    if !grid.is_house(x - 1, y) {
+                        let p = relative(relative(cell_pos, east, 0), south, 7);
+                        self.add("roof_front", p, rotation.rotated(Rotation::Clockwise180));
+                    }
+                    if !grid.is_house(x, y - 1) {
+                        self.add("roof_front", relative(cell_pos, west, 1), rotation.rotated(Rotation::CounterClockwise90));
+                    }
+                    if !grid.is_house(x, y + 1) {
+                        let p = relative(relative(cell_pos, east, 6), south, 6);
+                        self.add("roof_front", p, rotation.rotated(Rotation::Clockwise90));
+                    }
+                }
+            }
+        }
+
+        if above_grid.is_some() {
+            for y in 0..grid.height {
+                for x in 0..grid.width {
+                    if !grid.is_house(x, y) || !is_above(x, y) {
+                        continue;
+                    }
+                    let cell_pos = self.get_cell_pos(pos, rotation, x, y);
+                    if !grid.is_house(x + 1, y) {
+                        self.add("small_wall", relative(cell_pos, east, 7), rotation);
+                    }
+                    if !grid.is_house(x - 1, y) {
+                        let p = relative(relative(cell_pos, west, 1), south, 6);
+                        self.add("small_wall", p, rotation.rotated(Rotation::Clockwise180));
+                    }
+                    if !grid.is_house(x, y - 1) {
+                        let p = relative(relative(cell_pos, west, 0), north, 1);
+                        self.add("small_wall", p, rotation.rotated(Rotation::CounterClockwise90));
+                    }
+                    if !grid.is_house(x, y + 1) {
+                        let p = relative(relative(cell_pos, east, 6), south, 7);
+                        self.add("small_wall", p, rotation.rotated(Rotation::Clockwise90));
+                    }
+                    if !grid.is_house(x + 1, y) {
+                        if !grid.is_house(x, y - 1) {
+                            let p = relative(relative(cell_pos, east, 7), north, 2);
+                            self.add("small_wall_corner", p, rotation);
+                        }
+                        if !grid.is_house(x, y + 1) {
+                            let p = relative(relative(cell_pos, east, 8), south, 7);
+                            self.add("small_wall_corner", p, rotation.rotated(Rotation::Clockwise90));
+                        }
+                    }
+                    if !grid.is_house(x - 1, y) {
+                        if !grid.is_house(x, y - 1) {
+                            let p = relative(relative(cell_pos, west, 2), north, 1);
+                            self.add("small_wall_corner", p, rotation.rotated(Rotation::CounterClockwise90));
+                        }
+                        if !grid.is_house(x, y + 1) {
+                            let p = relative(relative(cell_pos, west, 1), south, 8);
+                            self.add("small_wall_corner", p, rotation.rotated(Rotation::Clockwise180));
+                        }
+                    }
+                }
+            }
+        }
+
+        for y in 0..grid.height {
+            for x in 0..grid.width {
+                if !grid.is_house(x, y) || is_above(x, y) {
+                    continue;
+                }
+                let cell_pos = self.get_cell_pos(pos, rotation, x, y);
+                if !grid.is_house(x + 1, y) {
+                    let p = relative(cell_pos, east, 6);
+                    if !grid.is_house(x, y + 1) {
+                        self.add("roof_corner", relative(p, south, 6), rotation);
+                    } else if grid.is_house(x + 1, y + 1) {
+                        self.add("roof_inner_corner", relative(p, south, 5), rotation);
+                    }
+                    if !grid.is_house(x, y - 1) {
+                        self.add("roof_corner", p, rotation.rotated(Rotation::CounterClockwise90));
+                    } else if grid.is_house(x + 1, y - 1) {
+                        let p = relative(relative(cell_pos, east, 9), north, 2);
+                        self.add("roof_inner_corner", p, rotation.rotated(Rotation::Clockwise90));
+                    }
+                }
+                if !grid.is_house(x - 1, y) {
+                    let p = cell_pos;
+                    if !grid.is_house(x, y + 1) {
+                        self.add("roof_corner", relative(p, south, 6), rotation.rotated(Rotation::Clockwise90));
+                    } else if grid.is_house(x - 1, y + 1) {
+                        let p = relative(relative(p, south, 8), west, 3);
+                        self.add("roof_inner_corner", p, rotation.rotated(Rotation::CounterClockwise90));
+                    }
+                    if !grid.is_house(x, y - 1) {
+                        self.add("roof_corner", p, rotation.rotated(Rotation::Clockwise180));
+                    } else if grid.is_house(x - 1, y - 1) {
+                        self.add("roof_inner_corner", relative(p, south, 1), rotation.rotated(Rotation::Clockwise180));
+                    }
+                }
+            }
+        }
+
+    }
+
+    fn add_room_1x1(&mut self, pos: Pos, rotation: Rotation, door: Option<Direction>, rooms: &FloorRooms) {
+
+        let mut room_rotation = Rotation::None;
+        let mut name = pick(self.rand, rooms.room_1x1);
+        match door {
+            Some(Direction::East) => {}
+            Some(Direction::North) => room_rotation = Rotation::CounterClockwise90,
+            Some(Direction::West) => room_rotation = Rotation::Clockwise180,
+            Some(Direction::South) => room_rotation = Rotation::Clockwise90,
+            _ => name = pick(self.rand, rooms.room_1x1_secret)
+        }
+
+        // Position of the rotated 7x7 room's corner, offset by one block.
+        let (zx, zz) = match room_rotation {
+            Rotation::None => (1, 0),
+            Rotation::Clockwise90 => (7, 0),
+            Rotation::Clockwise180 => (7, 6),
+            Rotation::CounterClockwise90 => (1, 6)
+        };
+
+        let (dx, dz) = rotation.transform(zx, zz);
+        self.add(name, (pos.0 + dx, pos.1, pos.2 + dz), room_rotation.rotated(rotation));
+
+    }
+
+    fn add_room_1x2(&mut self, pos: Pos, rotation: Rotation, room_direction: Option<Direction>, door: Direction, rooms: &FloorRooms, stairs: bool) {
+
+        use Direction::*;
+
+        let east = rotation.rotate_direction(East);
+        let south = rotation.rotate_direction(South);
+
+        let room_direction = match room_direction {
+            Some(room_direction) => room_direction,
+            None => return
+        };
+
+        match (door, room_direction) {
+            (East, South) => {
+                let name = rooms.get_1x2_side(self.rand, stairs);
+                self.add(name, relative(pos, east, 1), rotation);
+            }
+            (East, North) => {
+                let name = rooms.get_1x2_side(self.rand, stairs);
+                self.add_mirrored(name, relative(relative(pos, east, 1), south, 6), rotation, Mirror::LeftRight);
+            }
+            (West, North) => {
+                let name = rooms.get_1x2_side(self.rand, stairs);
+                self.add(name, relative(relative(pos, east, 7), south, 6), rotation.rotated(Rotation::Clockwise180));
+            }
+            (West, South) => {
+                let name = rooms.get_1x2_side(self.rand, stairs);
+                self.add_mirrored(name, relative(pos, east, 7), rotation, Mirror::FrontBack);
+            }
+            (South, East) => {
+                let name = rooms.get_1x2_side(self.rand, stairs);
+                self.add_mirrored(name, relative(pos, east, 1), rotation.rotated(Rotation::Clockwise90), Mirror::LeftRight);
+            }
+            (South, West) => {
+                let name = rooms.get_1x2_side(self.rand, stairs);
+                self.add(name, relative(pos, east, 7), rotation.rotated(Rotation::Clockwise90));
+            }
+            (North, West) => {
+                let name = rooms.get_1x2_side(self.rand, stairs);
+                self.add_mirrored(name, relative(relative(pos, east, 7), south, 6), rotation.rotated(Rotation::Clockwise90), Mirror::FrontBack);
+            }
+            (North, East) => {
+                let name = rooms.get_1x2_side(self.rand, stairs);
+                self.add(name, relative(relative(pos, east, 1), south, 6), rotation.rotated(Rotation::CounterClockwise90));
+            }
+            (South, North) => {
+                let name = rooms.get_1x2_front(self.rand, stairs);
+                self.add(name, relative(relative(pos, east, 1), south, -8), rotation);
+            }
+            (North, South) => {
+                let name = rooms.get_1x2_front(self.rand, stairs);
+                self.add(name, relative(relative(pos, east, 7), south, 14), rotation.rotated(Rotation::Clockwise180));
+            }
+            (West, East) => {
+                let name = rooms.get_1x2_front(self.rand, stairs);
+                self.add(name, relative(pos, east, 15), rotation.rotated(Rotation::Clockwise90));
+            }
+            (East, West) => {
+                let name = rooms.get_1x2_front(self.rand, stairs);
+                self.add(name, relative(relative(pos, east, -7), south, 6), rotation.rotated(Rotation::CounterClockwise90));
+            }
+            (Up, East) => {
+                let name = pick(self.rand, rooms.room_1x2_secret);
+                self.add(name, relative(pos, east, 15), rotation.rotated(Rotation::Clockwise90));
+            }
+            (Up, South) => {
+                let name = pick(self.rand, rooms.room_1x2_secret);
+                self.add(name, relative(pos, east, 1), rotation);
+            }
+            _ => {}
+        }
+
+    }
+
+    fn add_room_2x2(&mut self, pos: Pos, rotation: Rotation, room_direction: Direction, door: Direction, rooms: &FloorRooms) {
+
+        use Direction::*;
+
+        let (dx, dz, room_rotation, mirror) = match (door, room_direction) {
+            (East, South) => (-7, 0, rotation, Mirror::None),
+            (East, North) => (-7, 6, rotation, Mirror::LeftRight),
+            (North, East) => (1, 14, rotation.rotated(Rotation::CounterClockwise90), Mirror::None),
+            (North, West) => (7, 14, rotation.rotated(Rotation::CounterClockwise90), Mirror::LeftRight),
+            (South, West) => (7, -8, rotation.rotated(Rotation::Clockwise90), Mirror::None),
+            (South, East) => (1, -8, rotation.rotated(Rotation::Clockwise90), Mirror::LeftRight),
+            (West, North) => (15, 6, rotation.rotated(Rotation::Clockwise180), Mirror::None),
+            (West, South) => (15, 0, rotation, Mirror::FrontBack),
+            _ => (0, 0, rotation, Mirror::None)
+        };
+
+        let p = relative(pos, rotation.rotate_direction(East), dx);
+        let p = relative(p, rotation.rotate_direction(South), dz);
+        let name = pick(self.rand, rooms.room_2x2);
+        self.add_mirrored(name, p, room_rotation, mirror);
+
+    }
+
+}
+
+
+// PIECES //
+
+struct MansionPiece {
+    template: Arc<StructureTemplate>,
+    x: i32,
+    y: i32,
+    z: i32,
+    rotation: Rotation,
+    mirror: Mirror,
+    bb: BoundingBox
+}
+
+impl StructurePiece for MansionPiece {
+
+    fn get_name(&self) -> &'static str {
+        "woodland_mansion"
+    }
+
+    fn get_bounding_box(&self) -> &BoundingBox {
+        &self.bb
+    }
+
+    fn offset(&mut self, dx: i32, dy: i32, dz: i32) {
+        self.x += dx;
+        self.y += dy;
+        self.z += dz;
+        self.bb.offset(dx, dy, dz);
+    }
+
+    fn place(&mut self, level: &mut dyn LevelView, rand: &mut JavaRandom, chunk_bb: &BoundingBox) -> bool {
+
+        let settings = TemplatePlaceSettings::new()
+            .with_rotation(self.rotation)
+            .with_mirror(self.mirror)
+            .with_bounding_box(*chunk_bb)
+            .with_air(true);
+
+        self.template.place(level, rand, self.x, self.y, self.z, &settings);
+
+        for marker in self.template.get_markers() {
+
+            let (dx, dz) = settings.transform(marker.x, marker.z);
+            let (mx, my, mz) = (self.x + dx, self.y + marker.y, self.z + dz);
+            if !chunk_bb.contains(mx, my, mz) {
+                continue;
+            }
+
+            let facing = match marker.metadata.as_str() {
+                "ChestWest" => Some(Direction::West),
+                "ChestEast" => Some(Direction::East),
+                "ChestSouth" => Some(Direction::South),
+                "ChestNorth" => Some(Direction::North),
+                "Chest" => None,
+                "Mage" | "Warrior" => {
+                    // TODO: Spawn a persistent evoker or vindicator.
+                    let _ = level.set_block_at(mx, my, mz, AIR.get_default_state());
+                    continue;
+                }
+                _ => continue
+            };
+
+            match facing {
+                Some(facing) => {
+                    if level.get_block_at(mx, my, mz).is_ok_and(|state| !state.is_block(&CHEST)) {
+                        let facing = self.rotation.rotate_direction(facing);
+                        let chest = CHEST.get_default_state().with(&PROP_HORIZONTAL_FACING, facing).unwrap();
+                        let _ = level.set_block_at(mx, my, mz, chest);
+                        set_loot_table(level, rand, mx, my, mz, MANSION_LOOT_TABLE);
+                    }
+                }
+                None => {
+                    // The chest is oriented depending on its surroundings.
+                    let bb = BoundingBox::new(mx, my, mz, mx, my, mz);
+                    PiecePlacer::new(level, chunk_bb, bb, Direction::North)
+                        .place_chest(0, 0, 0, rand, MANSION_LOOT_TABLE);
+                }
+            }
+
+        }
+
+        true
+
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_vanilla::block::DARK_OAK_PLANKS;
+    use mc_vanilla::biome::DARK_FOREST;
+
+    use crate::view::TestLevelView;
+    use super::super::template::{TemplateBlock, TemplateMarker};
+
+    use super::*;
+
+    #[test]
+    fn mansion_grid() {
+
+        for seed in 0..16 {
+
+            let grid = MansionGrid::new(&mut JavaRandom::new(seed));
+
+            // The entrance is always part of the house.
+            assert_eq!(grid.base.get(grid.entrance_x, grid.entrance_y), CELL_STAIRS);
+
+            for floor in 0..2 {
+                for y in 0..grid.base.height {
+                    for x in 0..grid.base.width {
+                        if grid.base.get(x, y) != CELL_ROOM {
+                            continue;
+                        }
+                        // Each room cell belongs to a room, whose origin can be found.
+                        let room = grid.floor_rooms[floor].get(x, y);
+                        let room_id = room & ROOM_ID_MASK;
+                        assert!(room_id >= 10);
+                        assert!(matches!(room & ROOM_SIZE_MASK, ROOM_1X1 | ROOM_1X2 | ROOM_2X2));
+                        let origins = (0..grid.base.height)
+                            .flat_map(|y| (0..grid.base.width).map(move |x| (x, y)))
+                            .filter(|&(x, y)| grid.is_room_id(x, y, floor, room_id))
+                            .filter(|&(x, y)| grid.floor_rooms[floor].get(x, y) & ROOM_ORIGIN_FLAG != 0)
+                            .count();
+                        assert_eq!(origins, 1);
+                    }
+                }
+            }
+
+        }
+
+    }
+
+    #[test]
+    fn mansion() {
+
+        let planks = DARK_OAK_PLANKS.get_default_state();
+        let template = Arc::new(StructureTemplate::new((1, 1, 1), vec![TemplateBlock { x: 0, y: 0, z: 0, state: planks }])
+            .with_markers(vec![TemplateMarker { x: 0, y: 1, z: 0, metadata: "ChestNorth".to_string() }]));
+        let templates = WoodlandMansion::get_template_names().into_iter()
+            .map(|name| (name, Arc::clone(&template)))
+            .collect();
+
+        let mansion = WoodlandMansion::new(0, templates, Arc::new(|_, _| 70));
+        let (cx, cz, mut start) = (-64..64)
+            .flat_map(|cx| (-64..64).map(move |cz| (cx, cz)))
+            .find_map(|(cx, cz)| mansion.generate_start(cx, cz).map(|start| (cx, cz, start)))
+            .expect("no mansion found");
+
+        assert!(start.get_pieces().len() > 100);
+        assert_eq!(start.get_bounding_box().min_y, 70);
+
+        // Too low surfaces prevent mansions.
+        let low_mansion = WoodlandMansion::new(0, mansion.templates.clone(), Arc::new(|_, _| 60));
+        assert!(low_mansion.generate_start(cx, cz).is_none());
+
+        // The floor is supported by cobblestone.
+        let mut level = TestLevelView::new(DARK_OAK_PLANKS.get_default_state(), 50, &DARK_FOREST);
+        mansion.place_start_in_chunk(&mut start, &mut level, &mut JavaRandom::new(0), cx, cz);
+        assert!(level.count_blocks(COBBLESTONE.get_default_state()) > 0);
+        assert!(level.get_loot_table_positions().iter()
+            .all(|&(lx, ly, lz)| level.get_block_at(lx, ly, lz).unwrap().is_block(&CHEST)));
+
+    }
+
+}
@@ -1,11 +1,30 @@
-use std::num::Wrapping;
+use std::sync::Arc;
 
+use mc_core::entity::EntityType;
 use mc_core::rand::JavaRandom;
 
-use crate::view::ProtoChunkView;
+use crate::view::{LevelView, ProtoChunkView};
+use crate::seed;
+use crate::feature::Feature;
 
+use piece::StructureStart;
+
+pub mod piece;
+pub mod debug;
+pub mod template;
+pub mod block;
+pub mod jigsaw;
+pub mod carver;
 pub mod cave;
 pub mod ravine;
+pub mod fortress;
+pub mod bastion;
+pub mod shipwreck;
+pub mod ocean_ruin;
+pub mod treasure;
+pub mod outpost;
+pub mod mansion;
+pub mod spawn;
 
 
 /// Base trait for all structure implementations, including carvers, village or strongholds.
@@ -17,22 +36,195 @@ pub trait Structure {
 
         let mut rand = JavaRandom::new(seed);
 
-        let x_rand = Wrapping(rand.next_long());
-        let z_rand = Wrapping(rand.next_long());
-
         let (cx, cz) = chunk.get_position();
 
         for ccx in (cx - range)..=(cx + range) {
             for ccz in (cz - range)..=(cz + range) {
+                rand.set_seed(seed::large_feature_seed(seed, ccx, ccz));
+                self.generate(ccx, ccz, chunk, range, &mut rand);
 
-                let seed = (Wrapping(ccx as i64) * x_rand) ^ (Wrapping(ccz as i64) * z_rand) ^ Wrapping(seed);
-                rand.set_seed(seed.0);
+            }
+        }
 
-                self.generate(ccx, ccz, chunk, range, &mut rand);
+    }
+
+}
+
+
+/// Return the random used to generate the structure start in the given chunk, this is the
+/// same random as the one used by `Structure::generate_in` for each chunk.
+pub fn new_start_random(seed: i64, cx: i32, cz: i32) -> JavaRandom {
+    JavaRandom::new(seed::large_feature_seed(seed, cx, cz))
+}
+
+
+/// A placement where the world is divided in square regions of `spacing` chunks, each region
+/// has one potential start chunk, placed randomly with at least `separation` chunks between
+/// starts of adjacent regions. Triangular spreads average two random offsets on each axis,
+/// which favors starts near the center of regions.
+///
+/// Valid for: 1.16.2 to 1.17.1
+#[derive(Debug, Clone, Copy)]
+pub struct RandomSpread {
+    pub spacing: i32,
+    pub separation: i32,
+    pub salt: i32,
+    pub triangular: bool
+}
+
+impl RandomSpread {
+
+    /// Return the potential start chunk of the region containing the given chunk. The given
+    /// random is seeded for the region and can be used afterward, like vanilla does for
+    /// some structures.
+    pub fn get_start_chunk(&self, seed: i64, cx: i32, cz: i32, rand: &mut JavaRandom) -> (i32, i32) {
+        let rx = cx.div_euclid(self.spacing);
+        let rz = cz.div_euclid(self.spacing);
+        rand.set_seed(seed::region_seed(seed, rx, rz, self.salt));
+        let bound = self.spacing - self.separation;
+        let (ox, oz) = if self.triangular {
+            let ox = (rand.next_int_bounded(bound) + rand.next_int_bounded(bound)) / 2;
+            let oz = (rand.next_int_bounded(bound) + rand.next_int_bounded(bound)) / 2;
+            (ox, oz)
+        } else {
+            (rand.next_int_bounded(bound), rand.next_int_bounded(bound))
+        };
+        (rx * self.spacing + ox, rz * self.spacing + oz)
+    }
+
+}
+
+
+/// A function returning the surface height of a column, as generated by the chunk generator
+/// before any decoration. Structures depending on the terrain use it to compute their height
+/// when generating their start, before the chunks they're placed in actually exist.
+pub type SurfaceHeight = Arc<dyn Fn(i32, i32) -> i32 + Send + Sync>;
+
+
+/// An entry of a mob spawn list.
+#[derive(Clone, Copy)]
+pub struct SpawnEntry {
+    pub entity_type: &'static EntityType,
+    pub weight: u16,
+    pub min_count: u8,
+    pub max_count: u8
+}
+
+
+/// Base trait for piece-based structures, like nether fortresses. Unlike `Structure`, these
+/// structures are placed at decoration, using a `PieceStructureFeature`.
+pub trait PieceStructure: Send + Sync {
+
+    /// Maximum distance, in chunks, between a start chunk and the chunks its pieces can reach.
+    fn get_range(&self) -> i32;
+
+    /// Generate the structure start of the given chunk, if this chunk is a start chunk.
+    fn generate_start(&self, cx: i32, cz: i32) -> Option<StructureStart>;
+
+    /// Return the monsters spawn list replacing the biome one inside this structure, none if
+    /// this structure doesn't change spawns. An empty list prevents monsters from spawning.
+    fn get_spawn_overrides(&self) -> Option<&'static [SpawnEntry]> {
+        None
+    }
 
+    /// Return true if spawn overrides only apply inside the pieces of this structure, false
+    /// if they apply in the whole bounding box of its start.
+    fn is_spawn_override_in_pieces(&self) -> bool {
+        true
+    }
+
+    /// Place the given start of this structure in the given chunk, structures can override
+    /// this to place blocks that are not part of their pieces.
+    fn place_start_in_chunk(&self, start: &mut StructureStart, level: &mut dyn LevelView, rand: &mut JavaRandom, cx: i32, cz: i32) {
+        start.place_in_chunk(level, rand, cx, cz);
+    }
+
+    /// Generate all structure starts that can reach the given chunk.
+    fn generate_starts_around(&self, cx: i32, cz: i32) -> Vec<StructureStart> {
+        let range = self.get_range();
+        let mut starts = Vec::new();
+        for scx in (cx - range)..=(cx + range) {
+            for scz in (cz - range)..=(cz + range) {
+                if let Some(start) = self.generate_start(scx, scz) {
+                    if start.get_bounding_box().intersects_chunk(cx, cz) {
+                        starts.push(start);
+                    }
+                }
             }
         }
+        starts
+    }
+
+    /// Return the spawn list override at the given block position, if it's inside this
+    /// structure. This is intended to be used by natural spawners, see `StructureSpawns`.
+    fn get_spawn_overrides_at(&self, x: i32, y: i32, z: i32) -> Option<&'static [SpawnEntry]> {
+        let overrides = self.get_spawn_overrides()?;
+        let in_pieces = self.is_spawn_override_in_pieces();
+        self.generate_starts_around(x >> 4, z >> 4).iter()
+            .any(|start| if in_pieces {
+                start.is_inside_piece(x, y, z)
+            } else {
+                start.get_bounding_box().contains(x, y, z)
+            })
+            .then_some(overrides)
+    }
+
+}
+
+/// Shared structures, for example to use the same structure in a `PieceStructureFeature` and
+/// in `StructureSpawns`.
+impl<S: PieceStructure + ?Sized> PieceStructure for Arc<S> {
+
+    fn get_range(&self) -> i32 {
+        (**self).get_range()
+    }
+
+    fn generate_start(&self, cx: i32, cz: i32) -> Option<StructureStart> {
+        (**self).generate_start(cx, cz)
+    }
+
+    fn get_spawn_overrides(&self) -> Option<&'static [SpawnEntry]> {
+        (**self).get_spawn_overrides()
+    }
+
+    fn is_spawn_override_in_pieces(&self) -> bool {
+        (**self).is_spawn_override_in_pieces()
+    }
+
+    fn place_start_in_chunk(&self, start: &mut StructureStart, level: &mut dyn LevelView, rand: &mut JavaRandom, cx: i32, cz: i32) {
+        (**self).place_start_in_chunk(start, level, rand, cx, cz)
+    }
+
+}
+
+
+/// A feature placing the pieces of a piece-based structure in the decorated chunk.
+pub struct PieceStructureFeature<S: PieceStructure> {
+    structure: S
+}
+
+impl<S: PieceStructure> PieceStructureFeature<S> {
 
+    pub fn new(structure: S) -> Self {
+        Self { structure }
+    }
+
+    #[inline]
+    pub fn get_structure(&self) -> &S {
+        &self.structure
+    }
+
+}
+
+impl<S: PieceStructure> Feature for PieceStructureFeature<S> {
+
+    fn generate(&self, level: &mut dyn LevelView, rand: &mut JavaRandom, x: i32, _y: i32, z: i32) -> bool {
+        let (cx, cz) = (x >> 4, z >> 4);
+        let mut starts = self.structure.generate_starts_around(cx, cz);
+        for start in &mut starts {
+            self.structure.place_start_in_chunk(start, level, rand, cx, cz);
+        }
+        !starts.is_empty()
     }
 
 }
@@ -0,0 +1,389 @@
+//! Ocean ruins structure, small or large ruins placed on the ocean floor, large ruins can be
+//! surrounded by a cluster of small ruins.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use mc_core::world::anvil::decode::DecodeError;
+use mc_core::world::level::LevelEnv;
+use mc_core::block::BlockState;
+use mc_core::rand::JavaRandom;
+
+use mc_vanilla::heightmap::OCEAN_FLOOR_WG;
+use mc_vanilla::block::*;
+use mc_vanilla::block::fluid::get_fluid_state;
+
+use crate::view::LevelView;
+
+use super::template::{StructureTemplate, TemplatePlaceSettings, Rotation};
+use super::piece::{BoundingBox, StructurePiece, StructureStart, set_loot_table};
+use super::{new_start_random, PieceStructure, RandomSpread};
+
+
+const WARM_RUINS: [&str; 8] = ["warm_1", "warm_2", "warm_3", "warm_4", "warm_5", "warm_6", "warm_7", "warm_8"];
+const RUINS_BRICK: [&str; 8] = ["brick_1", "brick_2", "brick_3", "brick_4", "brick_5", "brick_6", "brick_7", "brick_8"];
+const RUINS_CRACKED: [&str; 8] = ["cracked_1", "cracked_2", "cracked_3", "cracked_4", "cracked_5", "cracked_6", "cracked_7", "cracked_8"];
+const RUINS_MOSSY: [&str; 8] = ["mossy_1", "mossy_2", "mossy_3", "mossy_4", "mossy_5", "mossy_6", "mossy_7", "mossy_8"];
+const BIG_RUINS_BRICK: [&str; 4] = ["big_brick_1", "big_brick_2", "big_brick_3", "big_brick_8"];
+const BIG_RUINS_MOSSY: [&str; 4] = ["big_mossy_1", "big_mossy_2", "big_mossy_3", "big_mossy_8"];
+const BIG_RUINS_CRACKED: [&str; 4] = ["big_cracked_1", "big_cracked_2", "big_cracked_3", "big_cracked_8"];
+const BIG_WARM_RUINS: [&str; 4] = ["big_warm_4", "big_warm_5", "big_warm_6", "big_warm_7"];
+
+pub const OCEAN_RUIN_BIG_LOOT_TABLE: &str = "minecraft:chests/underwater_ruin_big";
+pub const OCEAN_RUIN_SMALL_LOOT_TABLE: &str = "minecraft:chests/underwater_ruin_small";
+
+pub const OCEAN_RUIN_SPREAD: RandomSpread = RandomSpread {
+    spacing: 20,
+    separation: 8,
+    salt: 14357621,
+    triangular: false
+};
+
+const LARGE_PROBABILITY: f32 = 0.3;
+const CLUSTER_PROBABILITY: f32 = 0.9;
+
+
+/// Kind of ocean ruins, warm ruins are made of sandstone and cold ruins of stone bricks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OceanRuinType {
+    Warm,
+    Cold
+}
+
+impl OceanRuinType {
+
+    /// Return the names of all the vanilla templates used by this kind of ruins.
+    pub fn get_template_names(self) -> Vec<&'static str> {
+        match self {
+            Self::Warm => WARM_RUINS.iter().chain(&BIG_WARM_RUINS).copied().collect(),
+            Self::Cold => RUINS_BRICK.iter()
+                .chain(&RUINS_CRACKED)
+                .chain(&RUINS_MOSSY)
+                .chain(&BIG_RUINS_BRICK)
+                .chain(&BIG_RUINS_CRACKED)
+                .chain(&BIG_RUINS_MOSSY)
+                .copied()
+                .collect()
+        }
+    }
+
+}
+
+
+/// The ocean ruins structure.
+///
+/// Valid for: 1.16.2 to 1.17.1
+pub struct OceanRuins {
+    seed: i64,
+    ty: OceanRuinType,
+    templates: HashMap<&'static str, Arc<StructureTemplate>>
+}
+
+impl OceanRuins {
+
+    /// Construct an ocean ruins structure, the templates are mapped by their vanilla names
+    /// and must contain all templates of the given kind of ruins.
+    pub fn new(seed: i64, ty: OceanRuinType, templates: HashMap<&'static str, Arc<StructureTemplate>>) -> Self {
+        for name in ty.get_template_names() {
+            assert!(templates.contains_key(name), "missing ocean ruin template '{}'", name);
+        }
+        Self { seed, ty, templates }
+    }
+
+    /// Load the vanilla ocean ruins templates from the given directory, typically the
+    /// `data/minecraft/structures/underwater_ruin` directory extracted from the game.
+    pub fn load_vanilla(seed: i64, ty: OceanRuinType, dir: &Path, env: &LevelEnv) -> Result<Self, DecodeError> {
+        let mut templates = HashMap::new();
+        for name in ty.get_template_names() {
+            templates.insert(name, Arc::new(StructureTemplate::from_file(&dir.join(format!("{}.nbt", name)), env)?));
+        }
+        Ok(Self::new(seed, ty, templates))
+    }
+
+    #[inline]
+    pub fn get_type(&self) -> OceanRuinType {
+        self.ty
+    }
+
+    fn new_piece(&self, name: &str, x: i32, z: i32, rotation: Rotation, integrity: f32, large: bool) -> Box<dyn StructurePiece> {
+        let template = Arc::clone(&self.templates[name]);
+        let bb = template.get_bounding_box(x, 90, z, rotation);
+        Box::new(OceanRuinPiece {
+            template,
+            x,
+            y: 90,
+            z,
+            rotation,
+            integrity,
+            large,
+            bb
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn add_piece(&self, pieces: &mut Vec<Box<dyn StructurePiece>>, rand: &mut JavaRandom, x: i32, z: i32, rotation: Rotation, large: bool, integrity: f32) {
+        match self.ty {
+            OceanRuinType::Warm => {
+                let names: &[&str] = if large { &BIG_WARM_RUINS } else { &WARM_RUINS };
+                let name = names[rand.next_int_bounded(names.len() as i32) as usize];
+                pieces.push(self.new_piece(name, x, z, rotation, integrity, large));
+            }
+            OceanRuinType::Cold => {
+                let (brick, cracked, mossy): (&[&str], &[&str], &[&str]) = if large {
+                    (&BIG_RUINS_BRICK, &BIG_RUINS_CRACKED, &BIG_RUINS_MOSSY)
+                } else {
+                    (&RUINS_BRICK, &RUINS_CRACKED, &RUINS_MOSSY)
+                };
+                let index = rand.next_int_bounded(brick.len() as i32) as usize;
+                pieces.push(self.new_piece(brick[index], x, z, rotation, integrity, large));
+                pieces.push(self.new_piece(cracked[index], x, z, rotation, 0.7, large));
+                pieces.push(self.new_piece(mossy[index], x, z, rotation, 0.5, large));
+            }
+        }
+    }
+
+    /// Add small ruins around a large ruin.
+    fn add_cluster(&self, pieces: &mut Vec<Box<dyn StructurePiece>>, rand: &mut JavaRandom, x: i32, z: i32, rotation: Rotation) {
+
+        let (dx, dz) = rotation.transform(15, 15);
+        let bb = BoundingBox::new(x, 90, z, x + dx, 90, z + dz);
+        let (min_x, min_z) = (bb.min_x, bb.min_z);
+
+        let mut positions = vec![
+            (min_x - 16 + next_int_between(rand, 1, 8), min_z + 16 + next_int_between(rand, 1, 7)),
+            (min_x - 16 + next_int_between(rand, 1, 8), min_z + next_int_between(rand, 1, 7)),
+            (min_x - 16 + next_int_between(rand, 1, 8), min_z - 16 + next_int_between(rand, 4, 8)),
+            (min_x + next_int_between(rand, 1, 7), min_z + 16 + next_int_between(rand, 1, 7)),
+            (min_x + next_int_between(rand, 1, 7), min_z - 16 + next_int_between(rand, 4, 6)),
+            (min_x + 16 + next_int_between(rand, 1, 7), min_z + 16 + next_int_between(rand, 3, 8)),
+            (min_x + 16 + next_int_between(rand, 1, 7), min_z + next_int_between(rand, 1, 7)),
+            (min_x + 16 + next_int_between(rand, 1, 7), min_z - 16 + next_int_between(rand, 4, 8)),
+        ];
+
+        let count = next_int_between(rand, 4, 8);
+        for _ in 0..count {
+            if !positions.is_empty() {
+                let (px, pz) = positions.remove(rand.next_int_bounded(positions.len() as i32) as usize);
+                let rotation = Rotation::random(rand);
+                let (dx, dz) = rotation.transform(5, 6);
+                // Like vanilla, this box is at Y 0 and therefore never intersects the large
+                // ruin's box at Y 90.
+                let small_bb = BoundingBox::new(px, 0, pz, px + dx, 0, pz + dz);
+                if !small_bb.intersects(&bb) {
+                    self.add_piece(pieces, rand, px, pz, rotation, false, 0.8);
+                }
+            }
+        }
+
+    }
+
+}
+
+impl PieceStructure for OceanRuins {
+
+    fn get_range(&self) -> i32 {
+        8
+    }
+
+    fn generate_start(&self, cx: i32, cz: i32) -> Option<StructureStart> {
+
+        let mut rand = JavaRandom::new(0);
+        if OCEAN_RUIN_SPREAD.get_start_chunk(self.seed, cx, cz, &mut rand) != (cx, cz) {
+            return None;
+        }
+
+        let mut rand = new_start_random(self.seed, cx, cz);
+        let (x, z) = (cx * 16, cz * 16);
+        let rotation = Rotation::random(&mut rand);
+        let large = rand.next_float() <= LARGE_PROBABILITY;
+        let integrity = if large { 0.9 } else { 0.8 };
+
+        let mut pieces = Vec::new();
+        self.add_piece(&mut pieces, &mut rand, x, z, rotation, large, integrity);
+        if large && rand.next_float() <= CLUSTER_PROBABILITY {
+            self.add_cluster(&mut pieces, &mut rand, x, z, rotation);
+        }
+
+        Some(StructureStart::new(pieces))
+
+    }
+
+}
+
+
+struct OceanRuinPiece {
+    template: Arc<StructureTemplate>,
+    x: i32,
+    y: i32,
+    z: i32,
+    rotation: Rotation,
+    integrity: f32,
+    large: bool,
+    bb: BoundingBox
+}
+
+impl StructurePiece for OceanRuinPiece {
+
+    fn get_name(&self) -> &'static str {
+        "ocean_ruin"
+    }
+
+    fn get_bounding_box(&self) -> &BoundingBox {
+        &self.bb
+    }
+
+    fn offset(&mut self, dx: i32, dy: i32, dz: i32) {
+        self.x += dx;
+        self.y += dy;
+        self.z += dz;
+        self.bb.offset(dx, dy, dz);
+    }
+
+    fn place(&mut self, level: &mut dyn LevelView, rand: &mut JavaRandom, chunk_bb: &BoundingBox) -> bool {
+
+        let y = match level.get_heightmap_column_at(&OCEAN_FLOOR_WG, self.x, self.z) {
+            Ok(y) => y,
+            Err(_) => return true
+        };
+
+        let (size_x, _, size_z) = self.template.get_size();
+        let (dx, dz) = self.rotation.transform(size_x - 1, size_z - 1);
+        self.y = get_ruin_height(level, self.x, y, self.z, self.x + dx, self.z + dz);
+        self.bb = self.template.get_bounding_box(self.x, self.y, self.z, self.rotation);
+
+        let settings = TemplatePlaceSettings::new()
+            .with_rotation(self.rotation)
+            .with_bounding_box(*chunk_bb)
+            .with_integrity(self.integrity);
+
+        self.template.place(level, rand, self.x, self.y, self.z, &settings);
+
+        for marker in self.template.get_markers() {
+            let (dx, dz) = self.rotation.transform(marker.x, marker.z);
+            let (mx, my, mz) = (self.x + dx, self.y + marker.y, self.z + dz);
+            if !chunk_bb.contains(mx, my, mz) {
+                continue;
+            }
+            match marker.metadata.as_str() {
+                "chest" => {
+                    let waterlogged = is_water(level.get_block_at(mx, my, mz).unwrap());
+                    let chest = CHEST.get_default_state().with(&PROP_WATERLOGGED, waterlogged).unwrap();
+                    let _ = level.set_block_at(mx, my, mz, chest);
+                    let loot_table = if self.large { OCEAN_RUIN_BIG_LOOT_TABLE } else { OCEAN_RUIN_SMALL_LOOT_TABLE };
+                    set_loot_table(level, rand, mx, my, mz, loot_table);
+                }
+                "drowned" => {
+                    // TODO: Spawn a persistent drowned.
+                    let state = if my > 63 { AIR.get_default_state() } else { WATER.get_default_state() };
+                    let _ = level.set_block_at(mx, my, mz, state);
+                }
+                _ => {}
+            }
+        }
+
+        true
+
+    }
+
+}
+
+
+/// Return the height to place a ruin at, the ruin is lowered if most of its area is above a
+/// hole.
+fn get_ruin_height(level: &dyn LevelView, x: i32, y: i32, z: i32, corner_x: i32, corner_z: i32) -> i32 {
+
+    let mut min_height = 512;
+    let mut hollow_count = 0;
+    let floor = y - 1;
+
+    for px in x.min(corner_x)..=x.max(corner_x) {
+        for pz in z.min(corner_z)..=z.max(corner_z) {
+            let mut py = y - 1;
+            while py > 1 && level.get_block_at(px, py, pz).is_ok_and(is_air_water_or_ice) {
+                py -= 1;
+            }
+            min_height = min_height.min(py);
+            if py < floor - 2 {
+                hollow_count += 1;
+            }
+        }
+    }
+
+    if floor - min_height > 2 && hollow_count > (x - corner_x).abs() - 2 {
+        min_height + 1
+    } else {
+        y
+    }
+
+}
+
+fn is_water(state: &'static BlockState) -> bool {
+    get_fluid_state(state).is_water()
+}
+
+fn is_air_water_or_ice(state: &'static BlockState) -> bool {
+    state.is_block(&AIR) || state.is_block(&CAVE_AIR) || is_water(state) ||
+        state.is_block(&ICE) || state.is_block(&PACKED_ICE) || state.is_block(&BLUE_ICE) ||
+        state.is_block(&FROSTED_ICE)
+}
+
+/// Return a random integer between the given bounds, both inclusive.
+fn next_int_between(rand: &mut JavaRandom, min: i32, max: i32) -> i32 {
+    if min >= max {
+        min
+    } else {
+        rand.next_int_bounded(max - min + 1) + min
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_vanilla::biome::OCEAN;
+
+    use crate::view::TestLevelView;
+    use super::super::template::{TemplateBlock, TemplateMarker};
+
+    use super::*;
+
+    #[test]
+    fn ocean_ruins() {
+
+        let stone_bricks = STONE_BRICKS.get_default_state();
+        // The marker is at the origin, so the chest is always in the start chunk.
+        let template = Arc::new(StructureTemplate::new((3, 2, 3), (0..3)
+            .flat_map(|x| (0..3).map(move |z| TemplateBlock { x, y: 0, z, state: stone_bricks }))
+            .collect())
+            .with_markers(vec![TemplateMarker { x: 0, y: 1, z: 0, metadata: "chest".to_string() }]));
+
+        let templates = OceanRuinType::Cold.get_template_names().into_iter()
+            .map(|name| (name, Arc::clone(&template)))
+            .collect();
+
+        let ruins = OceanRuins::new(0, OceanRuinType::Cold, templates);
+
+        let (cx, cz, mut start) = (-64..64)
+            .flat_map(|cx| (-64..64).map(move |cz| (cx, cz)))
+            .find_map(|(cx, cz)| ruins.generate_start(cx, cz).map(|start| (cx, cz, start)))
+            .expect("no ruins found");
+
+        // Each cold ruin is made of three variants.
+        assert_eq!(start.get_pieces().len() % 3, 0);
+
+        let mut level = TestLevelView::new(SAND.get_default_state(), 40, &OCEAN)
+            .with_above(WATER.get_default_state());
+        start.place_in_chunk(&mut level, &mut JavaRandom::new(0), cx, cz);
+
+        let (x, z) = (cx * 16, cz * 16);
+        assert!(level.count_blocks(stone_bricks) > 0);
+        assert_eq!(start.get_pieces()[0].get_bounding_box().min_y, 41);
+
+        let chest = CHEST.get_default_state().with(&PROP_WATERLOGGED, true).unwrap();
+        assert!(level.get_block_at(x, 42, z).unwrap() == chest);
+        assert!(level.get_loot_table(x, 42, z).is_some());
+
+    }
+
+}
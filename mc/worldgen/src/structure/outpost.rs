@@ -0,0 +1,144 @@
+//! Pillager outpost structure, assembled from the vanilla outpost template pools.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use mc_core::world::anvil::decode::DecodeError;
+use mc_core::world::level::LevelEnv;
+use mc_core::rand::JavaRandom;
+
+use mc_vanilla::entity::PILLAGER;
+
+use super::jigsaw::{self, JigsawConfig, TemplatePools};
+use super::piece::{StructurePiece, StructureStart};
+use super::{new_start_random, PieceStructure, RandomSpread, SpawnEntry, SurfaceHeight};
+
+
+/// Name of the vanilla pool containing outpost bases.
+pub const OUTPOST_START_POOL: &str = "minecraft:pillager_outpost/base_plates";
+
+pub const OUTPOST_SPREAD: RandomSpread = RandomSpread {
+    spacing: 32,
+    separation: 8,
+    salt: 165745296,
+    triangular: false
+};
+
+static OUTPOST_SPAWNS: [SpawnEntry; 1] = [
+    SpawnEntry { entity_type: &PILLAGER, weight: 1, min_count: 1, max_count: 1 }
+];
+
+
+/// The pillager outpost structure, its start is placed on the surface and its pieces follow
+/// the terrain. Pillagers are the only monsters spawning in the whole area of outposts.
+///
+/// Vanilla also prevents outposts from generating within 10 chunks of a village, villages
+/// are not yet implemented so this check is currently missing.
+///
+/// Valid for: 1.16.2 to 1.16.5
+pub struct PillagerOutpost {
+    seed: i64,
+    pools: Arc<TemplatePools>,
+    surface: SurfaceHeight
+}
+
+impl PillagerOutpost {
+
+    /// Construct a pillager outpost structure using the given pools, which must contain the
+    /// outpost start pool, and the given surface height of the chunk generator.
+    pub fn new(seed: i64, pools: Arc<TemplatePools>, surface: SurfaceHeight) -> Self {
+        Self { seed, pools, surface }
+    }
+
+    /// Load the vanilla outpost pools from the game's `data` directory, see
+    /// `TemplatePools::load`.
+    pub fn load_vanilla(seed: i64, data_dir: &Path, env: &LevelEnv, surface: SurfaceHeight) -> Result<Self, DecodeError> {
+        Ok(Self::new(seed, Arc::new(TemplatePools::load(data_dir, OUTPOST_START_POOL, env)?), surface))
+    }
+
+    /// Return true if the given chunk passes the outpost-specific rarity check, this check
+    /// uses the 16x16 chunks area of the given chunk.
+    fn is_outpost_chunk(&self, cx: i32, cz: i32) -> bool {
+        let mut rand = JavaRandom::new(((cx >> 4) ^ ((cz >> 4) << 4)) as i64 ^ self.seed);
+        rand.next_int();
+        rand.next_int_bounded(5) == 0
+    }
+
+}
+
+impl PieceStructure for PillagerOutpost {
+
+    fn get_range(&self) -> i32 {
+        8
+    }
+
+    fn generate_start(&self, cx: i32, cz: i32) -> Option<StructureStart> {
+
+        let mut rand = JavaRandom::new(0);
+        if OUTPOST_SPREAD.get_start_chunk(self.seed, cx, cz, &mut rand) != (cx, cz) || !self.is_outpost_chunk(cx, cz) {
+            return None;
+        }
+
+        let mut rand = new_start_random(self.seed, cx, cz);
+        let config = JigsawConfig::new(OUTPOST_START_POOL, 7)
+            .with_start_projected(true)
+            .with_expansion_hack(true);
+        let pieces = jigsaw::assemble(&self.pools, &config, cx * 16, 0, cz * 16, &mut rand, &*self.surface);
+        if pieces.is_empty() {
+            None
+        } else {
+            Some(StructureStart::new(pieces.into_iter()
+                .map(|piece| Box::new(piece) as Box<dyn StructurePiece>)
+                .collect()))
+        }
+
+    }
+
+    fn get_spawn_overrides(&self) -> Option<&'static [SpawnEntry]> {
+        Some(&OUTPOST_SPAWNS)
+    }
+
+    fn is_spawn_override_in_pieces(&self) -> bool {
+        false
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_vanilla::block::COBBLESTONE;
+
+    use super::super::template::{StructureTemplate, TemplateBlock};
+    use super::super::jigsaw::{PoolElement, Projection, TemplatePool, EMPTY_POOL};
+    use super::*;
+
+    #[test]
+    fn outpost() {
+
+        let cobblestone = COBBLESTONE.get_default_state();
+        let blocks = (0..3)
+            .flat_map(|x| (0..3).map(move |z| TemplateBlock { x, y: 0, z, state: cobblestone }))
+            .collect();
+
+        let mut pools = TemplatePools::new();
+        pools.insert(OUTPOST_START_POOL, TemplatePool::new(EMPTY_POOL).with_element(PoolElement::Single {
+            template: Arc::new(StructureTemplate::new((3, 1, 3), blocks)),
+            projection: Projection::Rigid
+        }, 1));
+
+        let outpost = PillagerOutpost::new(0, Arc::new(pools), Arc::new(|_, _| 70));
+        let start = (-128..128)
+            .flat_map(|cx| (-128..128).map(move |cz| (cx, cz)))
+            .find_map(|(cx, cz)| outpost.generate_start(cx, cz))
+            .expect("no outpost found");
+
+        // The start is projected on the surface.
+        assert_eq!(start.get_bounding_box().min_y, 69);
+        let bb = *start.get_bounding_box();
+        assert_eq!(outpost.get_spawn_overrides_at(bb.min_x, bb.min_y, bb.min_z).map(|s| s.len()), Some(1));
+
+    }
+
+}
@@ -0,0 +1,267 @@
+//! Common types for structure pieces.
+//!
+//! Piece-based structures, like nether fortresses, are made of a structure start holding a
+//! list of pieces. The start and its pieces are generated at once from the start chunk, and
+//! then each piece is placed chunk by chunk when decorating the chunks it intersects.
+
+use mc_core::block::BlockState;
+use mc_core::rand::JavaRandom;
+use mc_core::pos::Direction;
+
+use mc_vanilla::block::material::VANILLA_BLOCK_MATERIALS;
+use mc_vanilla::block::{AIR, CHEST, PROP_HORIZONTAL_FACING};
+
+use crate::view::LevelView;
+use super::template::{Mirror, Rotation};
+
+pub use mc_core::pos::BoundingBox;
+
+
+/// A piece of a structure, placed chunk by chunk.
+pub trait StructurePiece: Send + Sync {
+
+    /// Name of this piece, mostly used for debugging.
+    fn get_name(&self) -> &'static str;
+
+    fn get_bounding_box(&self) -> &BoundingBox;
+
+    /// Move this piece by the given offset.
+    fn offset(&mut self, dx: i32, dy: i32, dz: i32);
+
+    /// Place the part of this piece that is inside the given chunk bounding box. If this
+    /// returns false, the piece is removed from its structure start.
+    fn place(&mut self, level: &mut dyn LevelView, rand: &mut JavaRandom, chunk_bb: &BoundingBox) -> bool;
+
+}
+
+
+/// A structure start, made of all the pieces of a single structure.
+pub struct StructureStart {
+    pieces: Vec<Box<dyn StructurePiece>>,
+    bounding_box: BoundingBox
+}
+
+impl StructureStart {
+
+    /// Construct a structure start from its pieces, the list must not be empty.
+    pub fn new(pieces: Vec<Box<dyn StructurePiece>>) -> Self {
+        assert!(!pieces.is_empty(), "at least one piece is required");
+        let mut bounding_box = *pieces[0].get_bounding_box();
+        for piece in &pieces[1..] {
+            bounding_box.encapsulate(piece.get_bounding_box());
+        }
+        Self {
+            pieces,
+            bounding_box
+        }
+    }
+
+    #[inline]
+    pub fn get_pieces(&self) -> &[Box<dyn StructurePiece>] {
+        &self.pieces
+    }
+
+    #[inline]
+    pub fn get_bounding_box(&self) -> &BoundingBox {
+        &self.bounding_box
+    }
+
+    /// Move the whole structure vertically to a random height so that it fits between the
+    /// given minimum and maximum Y.
+    pub fn move_inside_heights(&mut self, rand: &mut JavaRandom, min_y: i32, max_y: i32) {
+        let range = max_y - min_y + 1 - self.bounding_box.get_size_y();
+        let y = if range > 1 {
+            min_y + rand.next_int_bounded(range)
+        } else {
+            min_y
+        };
+        let dy = y - self.bounding_box.min_y;
+        self.bounding_box.offset(0, dy, 0);
+        for piece in &mut self.pieces {
+            piece.offset(0, dy, 0);
+        }
+    }
+
+    /// Place all pieces intersecting the given chunk, clipped to the chunk.
+    pub fn place_in_chunk(&mut self, level: &mut dyn LevelView, rand: &mut JavaRandom, cx: i32, cz: i32) {
+        let chunk_bb = BoundingBox::new_chunk(cx, cz, 0, 255);
+        self.pieces.retain_mut(|piece| {
+            !piece.get_bounding_box().intersects(&chunk_bb) || piece.place(level, rand, &chunk_bb)
+        });
+    }
+
+    /// Return true if the given block position is inside one of the pieces.
+    pub fn is_inside_piece(&self, x: i32, y: i32, z: i32) -> bool {
+        self.bounding_box.contains(x, y, z) &&
+            self.pieces.iter().any(|piece| piece.get_bounding_box().contains(x, y, z))
+    }
+
+}
+
+
+/// A helper to place blocks of an oriented piece, clipped to a chunk. Coordinates given to
+/// this placer are relative to the piece and transformed depending on its orientation, like
+/// vanilla's structure pieces. Block states are also mirrored and rotated accordingly.
+pub struct PiecePlacer<'a> {
+    level: &'a mut dyn LevelView,
+    chunk_bb: &'a BoundingBox,
+    bb: BoundingBox,
+    orientation: Direction,
+    mirror: Mirror,
+    rotation: Rotation
+}
+
+impl<'a> PiecePlacer<'a> {
+
+    pub fn new(level: &'a mut dyn LevelView, chunk_bb: &'a BoundingBox, bb: BoundingBox, orientation: Direction) -> Self {
+        let (mirror, rotation) = match orientation {
+            Direction::South => (Mirror::LeftRight, Rotation::None),
+            Direction::West => (Mirror::LeftRight, Rotation::Clockwise90),
+            Direction::East => (Mirror::None, Rotation::Clockwise90),
+            _ => (Mirror::None, Rotation::None)
+        };
+        Self {
+            level,
+            chunk_bb,
+            bb,
+            orientation,
+            mirror,
+            rotation
+        }
+    }
+
+    /// Return the world position of the given relative position.
+    pub fn get_world_pos(&self, x: i32, y: i32, z: i32) -> (i32, i32, i32) {
+        let bb = &self.bb;
+        match self.orientation {
+            Direction::South => (bb.min_x + x, bb.min_y + y, bb.min_z + z),
+            Direction::West => (bb.max_x - z, bb.min_y + y, bb.min_z + x),
+            Direction::East => (bb.min_x + z, bb.min_y + y, bb.min_z + x),
+            _ => (bb.min_x + x, bb.min_y + y, bb.max_z - z)
+        }
+    }
+
+    /// Return true if the given relative position is inside the chunk being placed.
+    pub fn is_inside(&self, x: i32, y: i32, z: i32) -> bool {
+        let (wx, wy, wz) = self.get_world_pos(x, y, z);
+        self.chunk_bb.contains(wx, wy, wz)
+    }
+
+    /// Return the block at the given relative position, or air if outside of the chunk.
+    pub fn get_block(&self, x: i32, y: i32, z: i32) -> &'static BlockState {
+        let (wx, wy, wz) = self.get_world_pos(x, y, z);
+        if self.chunk_bb.contains(wx, wy, wz) {
+            self.level.get_block_at(wx, wy, wz).unwrap_or_else(|_| AIR.get_default_state())
+        } else {
+            AIR.get_default_state()
+        }
+    }
+
+    /// Set the block at the given relative position if inside of the chunk, the state is
+    /// mirrored and rotated depending on the piece's orientation.
+    pub fn set_block(&mut self, x: i32, y: i32, z: i32, state: &'static BlockState) {
+        let (wx, wy, wz) = self.get_world_pos(x, y, z);
+        if self.chunk_bb.contains(wx, wy, wz) {
+            let state = self.rotation.rotate_state(self.mirror.mirror_state(state));
+            let _ = self.level.set_block_at(wx, wy, wz, state);
+        }
+    }
+
+    /// Fill the given relative box (inclusive) with the given state.
+    #[allow(clippy::too_many_arguments)]
+    pub fn fill(&mut self, x0: i32, y0: i32, z0: i32, x1: i32, y1: i32, z1: i32, state: &'static BlockState) {
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                for z in z0..=z1 {
+                    self.set_block(x, y, z, state);
+                }
+            }
+        }
+    }
+
+    /// Fill the column down from the given relative position with the given state, until a
+    /// block that is not air or liquid is found.
+    pub fn fill_column_down(&mut self, x: i32, y: i32, z: i32, state: &'static BlockState) {
+        let (wx, mut wy, wz) = self.get_world_pos(x, y, z);
+        if self.chunk_bb.contains(wx, wy, wz) {
+            while wy > 1 {
+                let block = match self.level.get_block_at(wx, wy, wz) {
+                    Ok(current) => current.get_block(),
+                    Err(_) => break
+                };
+                if block != &AIR && !VANILLA_BLOCK_MATERIALS.is_liquid(block) {
+                    break;
+                }
+                let _ = self.level.set_block_at(wx, wy, wz, state);
+                wy -= 1;
+            }
+        }
+    }
+
+    /// Place a chest with the given loot table at the given relative position, facing away
+    /// from the only solid block around it, if any. Return true if the chest has been placed.
+    pub fn place_chest(&mut self, x: i32, y: i32, z: i32, rand: &mut JavaRandom, loot_table: &'static str) -> bool {
+
+        let (wx, wy, wz) = self.get_world_pos(x, y, z);
+        if !self.chunk_bb.contains(wx, wy, wz) || self.level.get_block_at(wx, wy, wz).map_or(true, |s| s.is_block(&CHEST)) {
+            return false;
+        }
+
+        const HORIZONTAL: [Direction; 4] = [Direction::North, Direction::East, Direction::South, Direction::West];
+
+        let is_solid = |level: &dyn LevelView, direction: Direction| {
+            let (dx, _, dz) = direction.normal(1);
+            level.get_block_at(wx + dx, wy, wz + dz)
+                .is_ok_and(|state| VANILLA_BLOCK_MATERIALS.is_solid(state.get_block()))
+        };
+
+        let mut adjacent_chest = false;
+        let mut solid_direction = None;
+        for direction in HORIZONTAL {
+            let (dx, _, dz) = direction.normal(1);
+            if self.level.get_block_at(wx + dx, wy, wz + dz).is_ok_and(|s| s.is_block(&CHEST)) {
+                adjacent_chest = true;
+                break;
+            }
+            if is_solid(self.level, direction) {
+                if solid_direction.is_some() {
+                    solid_direction = None;
+                    break;
+                }
+                solid_direction = Some(direction);
+            }
+        }
+
+        let facing = match solid_direction {
+            _ if adjacent_chest => Direction::North,
+            Some(direction) => direction.opposite(),
+            None => {
+                let mut facing = Direction::North;
+                if is_solid(self.level, facing) {
+                    facing = facing.opposite();
+                }
+                if is_solid(self.level, facing) {
+                    facing = Rotation::Clockwise90.rotate_direction(facing);
+                }
+                if is_solid(self.level, facing) {
+                    facing = facing.opposite();
+                }
+                facing
+            }
+        };
+
+        let chest = CHEST.get_default_state().with(&PROP_HORIZONTAL_FACING, facing).unwrap();
+        let _ = self.level.set_block_at(wx, wy, wz, chest);
+        set_loot_table(self.level, rand, wx, wy, wz, loot_table);
+        true
+
+    }
+
+}
+
+
+/// Set the loot table of the container at the given position, the loot seed is picked from
+/// the given random, like vanilla.
+pub fn set_loot_table(level: &mut dyn LevelView, rand: &mut JavaRandom, x: i32, y: i32, z: i32, loot_table: &'static str) {
+    level.set_loot_table_at(x, y, z, loot_table, rand.next_long());
+}
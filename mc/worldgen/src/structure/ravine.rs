@@ -2,33 +2,77 @@ use mc_core::math::{mc_cos, mc_sin, JAVA_PI};
 use mc_core::rand::JavaRandom;
 use mc_vanilla::block::*;
 
+use crate::gen::settings::{GeneratorSettings, DEFAULT_SETTINGS};
 use crate::gen::biome::BiomePropertyMap;
 use crate::view::ProtoChunkView;
+use super::debug::PieceDebugger;
+use super::carver::{CarverConfig, CarveContext};
+use super::piece::BoundingBox;
 use super::Structure;
 
 
 pub struct RavineStructure<'a> {
-    biomes_map: &'a BiomePropertyMap
+    biomes_map: &'a BiomePropertyMap,
+    debugger: Option<&'a PieceDebugger>,
+    config: Option<&'a CarverConfig>,
+    settings: &'a GeneratorSettings
 }
 
 impl<'a> RavineStructure<'a> {
+
     pub fn new(biomes_map: &'a BiomePropertyMap) -> Self {
         Self {
-            biomes_map
+            biomes_map,
+            debugger: None,
+            config: None,
+            settings: &DEFAULT_SETTINGS
         }
     }
+
+    /// Record the carved area of each ravine in the given debugger, one bounding box
+    /// per ravine and per generated chunk.
+    pub fn with_debugger(mut self, debugger: &'a PieceDebugger) -> Self {
+        self.debugger = Some(debugger);
+        self
+    }
+
+    /// Generate ravines with the given carver configuration instead of the legacy probability,
+    /// heights and lava level.
+    pub fn with_config(mut self, config: &'a CarverConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Carve ravines with the given generator settings, the legacy lava level and the blocks
+    /// that can be carved are taken from these settings.
+    pub fn with_settings(mut self, settings: &'a GeneratorSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
 }
 
 impl<'a> Structure for RavineStructure<'a> {
 
     fn generate(&mut self, ccx: i32, ccz: i32, chunk: &mut dyn ProtoChunkView, range: i32, rand: &mut JavaRandom) {
 
-        if rand.next_int_bounded(50) == 0 {
+        let start = match self.config {
+            Some(config) => rand.next_float() <= config.probability,
+            None => rand.next_int_bounded(50) == 0
+        };
+
+        if start {
+
+            let height = chunk.as_chunk_ref().get_height();
+            let ctx = CarveContext::new(self.biomes_map, self.debugger, self.config, height, self.settings);
 
             let x = ccx * 16 + rand.next_int_bounded(16);
-            let y = {
-                let v = rand.next_int_bounded(40);
-                rand.next_int_bounded(v + 8) + 20
+            let y = match self.config {
+                Some(config) => config.y.sample(rand, height),
+                None => {
+                    let v = rand.next_int_bounded(40);
+                    rand.next_int_bounded(v + 8) + 20
+                }
             };
             let z = ccz * 16 + rand.next_int_bounded(16);
 
@@ -38,7 +82,7 @@ impl<'a> Structure for RavineStructure<'a> {
             let base_width = (rand.next_float() * 2.0 + rand.next_float()) * 2.0;
 
             let new_seed = rand.next_long();
-            gen_ravine_worker(new_seed, range, chunk, x as f64, y as f64, z as f64, base_width, angle_yaw, angle_pitch, 0, 0, 3.0, self.biomes_map);
+            gen_ravine_worker(new_seed, range, chunk, x as f64, y as f64, z as f64, base_width, angle_yaw, angle_pitch, 0, 0, 3.0, &ctx);
 
         }
 
@@ -60,7 +104,7 @@ fn gen_ravine_worker(
     mut offset: i32,
     mut length: i32,
     height_ratio: f64,
-    biomes_map: &BiomePropertyMap
+    ctx: &CarveContext
 ) {
 
     let mut rand = JavaRandom::new(seed);
@@ -102,12 +146,13 @@ fn gen_ravine_worker(
     let dirt_block = chunk.get_world_info().block_registry.0.expect_from_name("dirt").get_id();
     let water_block = chunk.get_world_info().block_registry.0.expect_from_name("water").get_id();
     let lava_block = chunk.get_world_info().block_registry.0.expect_from_name("lava").get_id();*/
-    let air_block = AIR.get_default_state();
-    let stone_block = STONE.get_default_state();
+    let stone_block = ctx.stone;
     let grass_block = GRASS_BLOCK.get_default_state();
     let dirt_block = DIRT.get_default_state();
-    let water_block = WATER.get_default_state();
-    let lava_block = LAVA.get_default_state();
+    let water_block = ctx.water;
+
+    // Only used for debugging, the area carved in this chunk by this node.
+    let mut carved_bbox: Option<BoundingBox> = None;
 
     'length_loop: for offset in offset..length {
 
@@ -177,6 +222,18 @@ fn gen_ravine_worker(
             }
         }
 
+        if ctx.debugger.is_some() && x_start < x_end && y_start < y_end && z_start < z_end {
+            // Carved blocks are offset by one block to the top, see below.
+            let bbox = BoundingBox::new(
+                cx * 16 + x_start, y_start + 1, cz * 16 + z_start,
+                cx * 16 + x_end - 1, y_end, cz * 16 + z_end - 1
+            );
+            match &mut carved_bbox {
+                Some(carved_bbox) => carved_bbox.encapsulate(&bbox),
+                None => carved_bbox = Some(bbox)
+            }
+        }
+
         for bx in x_start..x_end {
 
             let dx = ((cx * 16 + bx) as f64 + 0.5 - x) / width;
@@ -209,14 +266,14 @@ fn gen_ravine_worker(
                         }
 
                         if state == stone_block || state == dirt_block || state == grass_block {
-                            if by < 10 {
-                                chunk.set_block_at(bx, rby, bz, lava_block).unwrap();
+                            if rby <= ctx.lava_level {
+                                chunk.set_block_at(bx, rby, bz, ctx.lava).unwrap();
                             } else {
-                                chunk.set_block_at(bx, rby, bz, air_block).unwrap();
+                                chunk.set_block_at(bx, rby, bz, ctx.air).unwrap();
                                 if pierced_ground && chunk.get_block_at(bx, by, bz).unwrap() == dirt_block {
                                     // SAFETY: This section is safe only if the user ensure that biomes
                                     // in chunk are valid in the biome map.
-                                    let biome_prop = biomes_map.get(chunk.get_biome_at(bx, by, bz).unwrap()).unwrap();
+                                    let biome_prop = ctx.biomes_map.get(chunk.get_biome_at(bx, by, bz).unwrap()).unwrap();
                                     chunk.set_block_at(bx, by, bz, biome_prop.top_block).unwrap();
                                 }
                             }
@@ -237,4 +294,8 @@ fn gen_ravine_worker(
 
     }
 
+    if let (Some(debugger), Some(carved_bbox)) = (ctx.debugger, carved_bbox) {
+        debugger.record("ravine", "ravine", (cx, cz), carved_bbox);
+    }
+
 }
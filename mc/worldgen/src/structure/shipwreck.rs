@@ -0,0 +1,272 @@
+//! Shipwreck structure, a single random ship template placed on the ocean floor or buried in
+//! beaches.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use mc_core::world::anvil::decode::DecodeError;
+use mc_core::world::level::LevelEnv;
+use mc_core::rand::JavaRandom;
+
+use mc_vanilla::heightmap::{OCEAN_FLOOR_WG, WORLD_SURFACE};
+
+use crate::view::LevelView;
+
+use super::template::{StructureTemplate, TemplatePlaceSettings, Rotation};
+use super::piece::{BoundingBox, StructurePiece, StructureStart, set_loot_table};
+use super::{new_start_random, PieceStructure, RandomSpread};
+
+
+/// Names of the vanilla shipwreck templates in oceans, in their vanilla order.
+pub const VANILLA_OCEAN_SHIPWRECKS: [&str; 20] = [
+    "with_mast", "upsidedown_full", "upsidedown_fronthalf", "upsidedown_backhalf",
+    "sideways_full", "sideways_fronthalf", "sideways_backhalf", "rightsideup_full",
+    "rightsideup_fronthalf", "rightsideup_backhalf", "with_mast_degraded",
+    "upsidedown_full_degraded", "upsidedown_fronthalf_degraded", "upsidedown_backhalf_degraded",
+    "sideways_full_degraded", "sideways_fronthalf_degraded", "sideways_backhalf_degraded",
+    "rightsideup_full_degraded", "rightsideup_fronthalf_degraded", "rightsideup_backhalf_degraded"
+];
+
+/// Names of the vanilla shipwreck templates on beaches, in their vanilla order.
+pub const VANILLA_BEACHED_SHIPWRECKS: [&str; 11] = [
+    "with_mast", "sideways_full", "sideways_fronthalf", "sideways_backhalf",
+    "rightsideup_full", "rightsideup_fronthalf", "rightsideup_backhalf", "with_mast_degraded",
+    "rightsideup_full_degraded", "rightsideup_fronthalf_degraded", "rightsideup_backhalf_degraded"
+];
+
+pub const SHIPWRECK_MAP_LOOT_TABLE: &str = "minecraft:chests/shipwreck_map";
+pub const SHIPWRECK_SUPPLY_LOOT_TABLE: &str = "minecraft:chests/shipwreck_supply";
+pub const SHIPWRECK_TREASURE_LOOT_TABLE: &str = "minecraft:chests/shipwreck_treasure";
+
+pub const SHIPWRECK_SPREAD: RandomSpread = RandomSpread {
+    spacing: 24,
+    separation: 4,
+    salt: 165745295,
+    triangular: false
+};
+
+/// Shipwreck templates are rotated around this position.
+const PIVOT: (i32, i32) = (4, 15);
+
+
+/// The shipwreck structure, beached shipwrecks are buried in the ground and use a subset of
+/// the ocean shipwrecks templates.
+///
+/// Valid for: 1.16.2 to 1.17.1
+pub struct Shipwreck {
+    seed: i64,
+    beached: bool,
+    templates: Vec<Arc<StructureTemplate>>
+}
+
+impl Shipwreck {
+
+    /// Construct a shipwreck structure from its templates, in the order of the vanilla list
+    /// for this kind of shipwreck. The list must not be empty.
+    pub fn new(seed: i64, beached: bool, templates: Vec<Arc<StructureTemplate>>) -> Self {
+        assert!(!templates.is_empty(), "at least one shipwreck is required");
+        Self { seed, beached, templates }
+    }
+
+    /// Load the vanilla shipwreck templates from the given directory, typically the
+    /// `data/minecraft/structures/shipwreck` directory extracted from the game.
+    pub fn load_vanilla(seed: i64, beached: bool, dir: &Path, env: &LevelEnv) -> Result<Self, DecodeError> {
+        let names: &[&str] = if beached { &VANILLA_BEACHED_SHIPWRECKS } else { &VANILLA_OCEAN_SHIPWRECKS };
+        let mut templates = Vec::with_capacity(names.len());
+        for name in names {
+            templates.push(Arc::new(StructureTemplate::from_file(&dir.join(format!("{}.nbt", name)), env)?));
+        }
+        Ok(Self::new(seed, beached, templates))
+    }
+
+}
+
+impl PieceStructure for Shipwreck {
+
+    fn get_range(&self) -> i32 {
+        8
+    }
+
+    fn generate_start(&self, cx: i32, cz: i32) -> Option<StructureStart> {
+
+        let mut rand = JavaRandom::new(0);
+        if SHIPWRECK_SPREAD.get_start_chunk(self.seed, cx, cz, &mut rand) != (cx, cz) {
+            return None;
+        }
+
+        let mut rand = new_start_random(self.seed, cx, cz);
+        let rotation = Rotation::random(&mut rand);
+        let template = &self.templates[rand.next_int_bounded(self.templates.len() as i32) as usize];
+
+        let mut piece = ShipwreckPiece {
+            template: Arc::clone(template),
+            x: cx * 16,
+            y: 90,
+            z: cz * 16,
+            rotation,
+            beached: self.beached,
+            bb: BoundingBox::new(0, 0, 0, 0, 0, 0)
+        };
+        piece.update_bounding_box();
+
+        Some(StructureStart::new(vec![Box::new(piece)]))
+
+    }
+
+}
+
+
+struct ShipwreckPiece {
+    template: Arc<StructureTemplate>,
+    x: i32,
+    y: i32,
+    z: i32,
+    rotation: Rotation,
+    beached: bool,
+    bb: BoundingBox
+}
+
+impl ShipwreckPiece {
+
+    /// Return the origin of the template, taking the rotation pivot into account.
+    fn get_origin(&self) -> (i32, i32, i32) {
+        let (px, pz) = self.rotation.transform(PIVOT.0, PIVOT.1);
+        (self.x + PIVOT.0 - px, self.y, self.z + PIVOT.1 - pz)
+    }
+
+    fn update_bounding_box(&mut self) {
+        let (x, y, z) = self.get_origin();
+        self.bb = self.template.get_bounding_box(x, y, z, self.rotation);
+    }
+
+}
+
+impl StructurePiece for ShipwreckPiece {
+
+    fn get_name(&self) -> &'static str {
+        "shipwreck"
+    }
+
+    fn get_bounding_box(&self) -> &BoundingBox {
+        &self.bb
+    }
+
+    fn offset(&mut self, dx: i32, dy: i32, dz: i32) {
+        self.x += dx;
+        self.y += dy;
+        self.z += dz;
+        self.bb.offset(dx, dy, dz);
+    }
+
+    fn place(&mut self, level: &mut dyn LevelView, rand: &mut JavaRandom, chunk_bb: &BoundingBox) -> bool {
+
+        let heightmap = if self.beached { &WORLD_SURFACE } else { &OCEAN_FLOOR_WG };
+        let (size_x, size_y, size_z) = self.template.get_size();
+
+        // Like vanilla, the height is computed over the unrotated template's area.
+        let mut min_height = 256;
+        let mut total_height = 0;
+        let mut count = 0;
+        for x in self.x..self.x + size_x {
+            for z in self.z..self.z + size_z {
+                if let Ok(height) = level.get_heightmap_column_at(heightmap, x, z) {
+                    min_height = min_height.min(height);
+                    total_height += height;
+                    count += 1;
+                }
+            }
+        }
+
+        if count == 0 {
+            return true;
+        }
+
+        self.y = if self.beached {
+            min_height - size_y / 2 - rand.next_int_bounded(3)
+        } else {
+            total_height / count
+        };
+
+        self.update_bounding_box();
+        let (x, y, z) = self.get_origin();
+
+        let settings = TemplatePlaceSettings::new()
+            .with_rotation(self.rotation)
+            .with_bounding_box(*chunk_bb);
+
+        self.template.place(level, rand, x, y, z, &settings);
+
+        for marker in self.template.get_markers() {
+            let (dx, dz) = self.rotation.transform(marker.x, marker.z);
+            let (mx, my, mz) = (x + dx, y + marker.y, z + dz);
+            if !chunk_bb.contains(mx, my, mz) {
+                continue;
+            }
+            let loot_table = match marker.metadata.as_str() {
+                "map_chest" => SHIPWRECK_MAP_LOOT_TABLE,
+                "treasure_chest" => SHIPWRECK_TREASURE_LOOT_TABLE,
+                "supply_chest" => SHIPWRECK_SUPPLY_LOOT_TABLE,
+                _ => continue
+            };
+            set_loot_table(level, rand, mx, my - 1, mz, loot_table);
+        }
+
+        true
+
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_vanilla::block::{CHEST, SAND, WATER, OAK_PLANKS};
+    use mc_vanilla::biome::OCEAN;
+
+    use crate::view::TestLevelView;
+    use super::super::template::{TemplateBlock, TemplateMarker};
+
+    use super::*;
+
+    #[test]
+    fn shipwreck() {
+
+        let planks = OAK_PLANKS.get_default_state();
+        let mut blocks: Vec<TemplateBlock> = (0..9)
+            .flat_map(|x| (0..20).map(move |z| TemplateBlock { x, y: 0, z, state: planks }))
+            .collect();
+        blocks.push(TemplateBlock { x: 4, y: 1, z: 10, state: CHEST.get_default_state() });
+        let template = StructureTemplate::new((9, 6, 20), blocks)
+            .with_markers(vec![TemplateMarker { x: 4, y: 2, z: 10, metadata: "supply_chest".to_string() }]);
+
+        let template = Arc::new(template);
+        let shipwreck = Shipwreck::new(0, false, vec![Arc::clone(&template)]);
+        assert!((-64..64).any(|cx| (-64..64).any(|cz| shipwreck.generate_start(cx, cz).is_some())));
+
+        let mut piece = ShipwreckPiece {
+            template,
+            x: 0,
+            y: 90,
+            z: 0,
+            rotation: Rotation::Clockwise90,
+            beached: false,
+            bb: BoundingBox::new(0, 0, 0, 0, 0, 0)
+        };
+        piece.update_bounding_box();
+        // Rotated around the pivot.
+        assert_eq!(piece.bb, BoundingBox::new(0, 90, 11, 19, 95, 19));
+
+        let mut level = TestLevelView::new(SAND.get_default_state(), 40, &OCEAN)
+            .with_above(WATER.get_default_state());
+        assert!(piece.place(&mut level, &mut JavaRandom::new(0), &BoundingBox::new(-64, 0, -64, 64, 255, 64)));
+        assert_eq!(piece.bb, BoundingBox::new(0, 41, 11, 19, 46, 19));
+
+        // The whole ship is placed on the ocean floor, with its loot chest.
+        assert_eq!(level.count_blocks(planks), 9 * 20);
+        assert!(level.get_block_at(9, 42, 15).unwrap().is_block(&CHEST));
+        assert_eq!(level.get_loot_table(9, 42, 15), Some(SHIPWRECK_SUPPLY_LOOT_TABLE));
+
+    }
+
+}
@@ -0,0 +1,159 @@
+//! Structure-aware selection of natural spawn entries.
+//!
+//! Some structures replace the monsters that can spawn inside them, like nether fortresses or
+//! pillager outposts. Natural spawners should query the spawn list through `StructureSpawns`
+//! instead of using the biome spawn list directly.
+
+use std::sync::Arc;
+
+use mc_core::rand::JavaRandom;
+
+use super::{PieceStructure, SpawnEntry};
+
+
+/// A registry of the structures of a level that override monster spawns.
+pub struct StructureSpawns {
+    structures: Vec<Arc<dyn PieceStructure>>
+}
+
+impl StructureSpawns {
+
+    pub fn new() -> Self {
+        Self {
+            structures: Vec::new()
+        }
+    }
+
+    /// Add a structure to check, structures are checked in the order they are added and the
+    /// first one containing the position gives the spawn list, like vanilla which checks
+    /// swamp huts, outposts and then monuments.
+    pub fn with_structure(mut self, structure: Arc<dyn PieceStructure>) -> Self {
+        if structure.get_spawn_overrides().is_some() {
+            self.structures.push(structure);
+        }
+        self
+    }
+
+    /// Return the monsters spawn list at the given block position, this is the list of the
+    /// first structure containing this position, or the given biome list if none.
+    pub fn get_monster_spawns_at<'a>(&self, x: i32, y: i32, z: i32, biome_spawns: &'a [SpawnEntry]) -> &'a [SpawnEntry] {
+        self.structures.iter()
+            .find_map(|structure| structure.get_spawn_overrides_at(x, y, z))
+            .unwrap_or(biome_spawns)
+    }
+
+}
+
+impl Default for StructureSpawns {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// Pick a random entry from the given spawn list depending on their weights, like vanilla.
+/// Return none if the list is empty or if all weights are zero.
+pub fn pick_spawn_entry<'a>(rand: &mut JavaRandom, entries: &'a [SpawnEntry]) -> Option<&'a SpawnEntry> {
+    let total_weight: i32 = entries.iter().map(|entry| entry.weight as i32).sum();
+    if total_weight <= 0 {
+        return None;
+    }
+    let mut remaining = rand.next_int_bounded(total_weight);
+    for entry in entries {
+        remaining -= entry.weight as i32;
+        if remaining < 0 {
+            return Some(entry);
+        }
+    }
+    None
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_vanilla::entity::{ZOMBIE, SKELETON, PILLAGER};
+
+    use crate::view::LevelView;
+    use super::super::piece::{BoundingBox, StructurePiece, StructureStart};
+    use super::*;
+
+    struct TestPiece(BoundingBox);
+
+    impl StructurePiece for TestPiece {
+
+        fn get_name(&self) -> &'static str {
+            "test"
+        }
+
+        fn get_bounding_box(&self) -> &BoundingBox {
+            &self.0
+        }
+
+        fn offset(&mut self, dx: i32, dy: i32, dz: i32) {
+            self.0.offset(dx, dy, dz);
+        }
+
+        fn place(&mut self, _level: &mut dyn LevelView, _rand: &mut JavaRandom, _chunk_bb: &BoundingBox) -> bool {
+            true
+        }
+
+    }
+
+    /// A structure in chunk 0/0 made of two pieces with a gap between them.
+    struct TestStructure(Option<&'static [SpawnEntry]>);
+
+    impl PieceStructure for TestStructure {
+
+        fn get_range(&self) -> i32 {
+            1
+        }
+
+        fn generate_start(&self, cx: i32, cz: i32) -> Option<StructureStart> {
+            ((cx, cz) == (0, 0)).then(|| StructureStart::new(vec![
+                Box::new(TestPiece(BoundingBox::new(0, 60, 0, 3, 70, 15))),
+                Box::new(TestPiece(BoundingBox::new(12, 60, 0, 15, 70, 15)))
+            ]))
+        }
+
+        fn get_spawn_overrides(&self) -> Option<&'static [SpawnEntry]> {
+            self.0
+        }
+
+    }
+
+    static BIOME_SPAWNS: [SpawnEntry; 2] = [
+        SpawnEntry { entity_type: &ZOMBIE, weight: 95, min_count: 4, max_count: 4 },
+        SpawnEntry { entity_type: &SKELETON, weight: 100, min_count: 4, max_count: 4 }
+    ];
+
+    static OVERRIDE_SPAWNS: [SpawnEntry; 1] = [
+        SpawnEntry { entity_type: &PILLAGER, weight: 1, min_count: 1, max_count: 1 }
+    ];
+
+    #[test]
+    fn structure_spawns() {
+
+        let spawns = StructureSpawns::new()
+            .with_structure(Arc::new(TestStructure(None)))
+            .with_structure(Arc::new(TestStructure(Some(&OVERRIDE_SPAWNS))));
+
+        // Only inside pieces.
+        assert_eq!(spawns.get_monster_spawns_at(1, 64, 1, &BIOME_SPAWNS).len(), 1);
+        assert_eq!(spawns.get_monster_spawns_at(8, 64, 1, &BIOME_SPAWNS).len(), 2);
+        assert_eq!(spawns.get_monster_spawns_at(1, 80, 1, &BIOME_SPAWNS).len(), 2);
+
+        // An empty override prevents monster spawns.
+        let peaceful = StructureSpawns::new().with_structure(Arc::new(TestStructure(Some(&[]))));
+        assert!(peaceful.get_monster_spawns_at(1, 64, 1, &BIOME_SPAWNS).is_empty());
+        assert!(pick_spawn_entry(&mut JavaRandom::new(0), &[]).is_none());
+
+        let mut rand = JavaRandom::new(0);
+        let zombies = (0..1000)
+            .filter(|_| pick_spawn_entry(&mut rand, &BIOME_SPAWNS).unwrap().entity_type.name == ZOMBIE.name)
+            .count();
+        assert!((400..600).contains(&zombies));
+
+    }
+
+}
@@ -0,0 +1,850 @@
+//! Structure templates, as saved by structure blocks in the NBT format.
+//!
+//! Templates are loaded from their NBT representation, which can be found in the `structures`
+//! directory of the game's data, and can then be placed in a level with a rotation and an
+//! integrity, like the vanilla structure templates placement. Templates can also be captured
+//! from a level and saved, like structure blocks in save mode.
+
+use std::path::Path;
+use std::io::{self, Read};
+use std::fs::{self, File};
+
+use nbt::decode::read_gzip_compound_tag;
+use nbt::encode::write_gzip_compound_tag;
+use nbt::{CompoundTag, Tag};
+
+use mc_core::world::anvil::decode::{decode_block_state, DecodeError};
+use mc_core::world::anvil::encode::{encode_block_state, DATA_VERSION};
+use mc_core::world::level::{Level, LevelEnv, BaseBlockEntity};
+use mc_core::heightmap::HeightmapType;
+use mc_core::block::BlockState;
+use mc_core::rand::JavaRandom;
+use mc_core::pos::Direction;
+
+use mc_vanilla::block::{AIR, STRUCTURE_VOID, STRUCTURE_BLOCK, JIGSAW, StructureMode};
+use mc_vanilla::block_entity::{StructureBlockEntity, StructureRotation, StructureMirror};
+
+use crate::view::LevelView;
+use crate::place::BlockPlacer;
+use super::piece::BoundingBox;
+
+
+/// Rotation of a template around the vertical axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    None,
+    Clockwise90,
+    Clockwise180,
+    CounterClockwise90
+}
+
+impl Rotation {
+
+    /// All rotations, in the vanilla order.
+    pub const ALL: [Rotation; 4] = [Self::None, Self::Clockwise90, Self::Clockwise180, Self::CounterClockwise90];
+
+    /// Pick a random rotation, calling `next_int_bounded(4)` once.
+    pub fn random(rand: &mut JavaRandom) -> Self {
+        Self::ALL[rand.next_int_bounded(4) as usize]
+    }
+
+    /// Return all rotations shuffled, like Java's `Collections.shuffle`.
+    pub fn shuffled(rand: &mut JavaRandom) -> [Rotation; 4] {
+        let mut rotations = Self::ALL;
+        for i in (1..4).rev() {
+            rotations.swap(i, rand.next_int_bounded(i as i32 + 1) as usize);
+        }
+        rotations
+    }
+
+    /// Rotate the given relative horizontal coordinates around the origin.
+    pub fn transform(self, x: i32, z: i32) -> (i32, i32) {
+        match self {
+            Self::None => (x, z),
+            Self::Clockwise90 => (-z, x),
+            Self::Clockwise180 => (-x, -z),
+            Self::CounterClockwise90 => (z, -x)
+        }
+    }
+
+    /// Return the rotation resulting from this rotation followed by the given one.
+    pub fn rotated(self, other: Rotation) -> Self {
+        Self::ALL[(self as usize + other as usize) % 4]
+    }
+
+    /// Return true if this rotation swaps the X and Z axes.
+    pub fn is_swapping_axes(self) -> bool {
+        matches!(self, Self::Clockwise90 | Self::CounterClockwise90)
+    }
+
+    /// Rotate the given horizontal direction, other directions are returned unchanged.
+    pub fn rotate_direction(self, direction: Direction) -> Direction {
+        const ORDER: [Direction; 4] = [Direction::North, Direction::East, Direction::South, Direction::West];
+        match ORDER.iter().position(|&d| d == direction) {
+            Some(index) => ORDER[(index + self as usize) % 4],
+            None => direction
+        }
+    }
+
+    fn rotate_facing(self, facing: &str) -> Option<&'static str> {
+        let index = HORIZONTAL_NAMES.iter().position(|&f| f == facing)?;
+        Some(HORIZONTAL_NAMES[(index + self as usize) % 4])
+    }
+
+    /// Rotate the `axis`, horizontal `facing`, jigsaw `orientation` and horizontal connections
+    /// (`north`, `east`, ...) properties of the given state, if any.
+    pub fn rotate_state(self, state: &'static BlockState) -> &'static BlockState {
+
+        if let Self::None = self {
+            return state;
+        }
+
+        let mut rotated = state;
+        if let Some(props) = state.iter_raw_states() {
+            for (name, value) in props {
+                let new_prop = match (name, value.as_str()) {
+                    ("axis", "x") if self.is_swapping_axes() => Some((name, "z".to_string())),
+                    ("axis", "z") if self.is_swapping_axes() => Some((name, "x".to_string())),
+                    ("facing", facing) => self.rotate_facing(facing).map(|f| (name, f.to_string())),
+                    ("orientation", orientation) => Some((name, map_orientation(orientation, |f| self.rotate_facing(f)))),
+                    (direction, _) => self.rotate_facing(direction).map(|d| (d, value.clone()))
+                };
+                if let Some((new_name, new_value)) = new_prop {
+                    rotated = rotated.with_raw(new_name, &new_value).unwrap_or(rotated);
+                }
+            }
+        }
+
+        rotated
+
+    }
+
+}
+
+impl From<StructureRotation> for Rotation {
+    fn from(rotation: StructureRotation) -> Self {
+        match rotation {
+            StructureRotation::None => Self::None,
+            StructureRotation::Clockwise90 => Self::Clockwise90,
+            StructureRotation::Clockwise180 => Self::Clockwise180,
+            StructureRotation::CounterClockwise90 => Self::CounterClockwise90
+        }
+    }
+}
+
+
+/// Mirror of a structure piece, used by vanilla's oriented pieces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mirror {
+    None,
+    /// Swap north and south.
+    LeftRight,
+    /// Swap east and west.
+    FrontBack
+}
+
+impl Mirror {
+
+    /// Mirror the given relative horizontal coordinates around the origin.
+    pub fn transform(self, x: i32, z: i32) -> (i32, i32) {
+        match self {
+            Self::None => (x, z),
+            Self::LeftRight => (x, -z),
+            Self::FrontBack => (-x, z)
+        }
+    }
+
+    fn mirror_facing(self, facing: &str) -> Option<&'static str> {
+        match (self, facing) {
+            (Self::LeftRight, "north") => Some("south"),
+            (Self::LeftRight, "south") => Some("north"),
+            (Self::FrontBack, "east") => Some("west"),
+            (Self::FrontBack, "west") => Some("east"),
+            _ => None
+        }
+    }
+
+    /// Mirror the horizontal `facing`, jigsaw `orientation` and horizontal connections
+    /// properties of the given state, if any.
+    pub fn mirror_state(self, state: &'static BlockState) -> &'static BlockState {
+
+        if let Self::None = self {
+            return state;
+        }
+
+        let mut mirrored = state;
+        if let Some(props) = state.iter_raw_states() {
+            for (name, value) in props {
+                let new_prop = match (name, value.as_str()) {
+                    ("facing", facing) => self.mirror_facing(facing).map(|f| (name, f.to_string())),
+                    ("orientation", orientation) => Some((name, map_orientation(orientation, |f| self.mirror_facing(f)))),
+                    (direction, _) => self.mirror_facing(direction).map(|d| (d, value.clone()))
+                };
+                if let Some((new_name, new_value)) = new_prop {
+                    mirrored = mirrored.with_raw(new_name, &new_value).unwrap_or(mirrored);
+                }
+            }
+        }
+
+        mirrored
+
+    }
+
+}
+
+impl From<StructureMirror> for Mirror {
+    fn from(mirror: StructureMirror) -> Self {
+        match mirror {
+            StructureMirror::None => Self::None,
+            StructureMirror::LeftRight => Self::LeftRight,
+            StructureMirror::FrontBack => Self::FrontBack
+        }
+    }
+}
+
+
+const HORIZONTAL_NAMES: [&str; 4] = ["north", "east", "south", "west"];
+
+/// Map both directions of a jigsaw orientation, like `north_up`, directions not mapped by the
+/// given function are kept.
+fn map_orientation(orientation: &str, mut map: impl FnMut(&str) -> Option<&'static str>) -> String {
+    orientation.split('_')
+        .map(|part| map(part).unwrap_or(part))
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+
+/// A block of a template, with its position relative to the template's origin.
+#[derive(Debug, Clone, Copy)]
+pub struct TemplateBlock {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    pub state: &'static BlockState
+}
+
+
+/// A jigsaw block of a template, used to connect templates together, see the `jigsaw` module.
+#[derive(Debug, Clone)]
+pub struct TemplateJigsaw {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    /// Direction toward the connected template.
+    pub front: Direction,
+    /// Direction of the jigsaw's top, only relevant for vertical jigsaws that are not rollable.
+    pub top: Direction,
+    /// Name of this jigsaw, targeted by other jigsaws.
+    pub name: String,
+    /// Name of the jigsaws this one can connect to.
+    pub target: String,
+    /// Name of the pool to pick connected templates from.
+    pub pool: String,
+    /// State replacing the jigsaw block once the template is placed.
+    pub final_state: &'static BlockState,
+    /// True if the connected template can be rotated around the front direction.
+    pub rollable: bool
+}
+
+impl TemplateJigsaw {
+
+    /// Return this jigsaw with its position and directions rotated around the origin.
+    pub fn rotated(&self, rotation: Rotation) -> Self {
+        let (x, z) = rotation.transform(self.x, self.z);
+        Self {
+            x,
+            z,
+            front: rotation.rotate_direction(self.front),
+            top: rotation.rotate_direction(self.top),
+            ..self.clone()
+        }
+    }
+
+    /// Return true if the given jigsaw can be connected to this one, they must face each
+    /// other and the target of this jigsaw must be the name of the other one.
+    pub fn can_attach(&self, other: &TemplateJigsaw) -> bool {
+        self.front == other.front.opposite() &&
+            (self.rollable || self.top == other.top) &&
+            self.target == other.name
+    }
+
+}
+
+
+/// A data marker of a template, a structure block in data mode, used by structures to place
+/// additional content like loot chests or entities.
+#[derive(Debug, Clone)]
+pub struct TemplateMarker {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    pub metadata: String
+}
+
+
+/// Settings used when placing a template in a level.
+#[derive(Debug, Clone)]
+pub struct TemplatePlaceSettings {
+    rotation: Rotation,
+    mirror: Mirror,
+    bounding_box: Option<BoundingBox>,
+    integrity: f32,
+    ignore_air: bool,
+    replace_jigsaws: bool,
+    gravity: Option<(&'static HeightmapType, i32)>
+}
+
+impl TemplatePlaceSettings {
+
+    /// Default settings, no rotation or mirror, no bounding box, full integrity, air/structure
+    /// void blocks are ignored and jigsaw blocks are kept.
+    pub fn new() -> Self {
+        Self {
+            rotation: Rotation::None,
+            mirror: Mirror::None,
+            bounding_box: None,
+            integrity: 1.0,
+            ignore_air: true,
+            replace_jigsaws: false,
+            gravity: None
+        }
+    }
+
+    pub fn with_rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Mirror the template around its origin, the mirror is applied before the rotation.
+    pub fn with_mirror(mut self, mirror: Mirror) -> Self {
+        self.mirror = mirror;
+        self
+    }
+
+    /// Only place blocks within this bounding box.
+    pub fn with_bounding_box(mut self, bounding_box: BoundingBox) -> Self {
+        self.bounding_box = Some(bounding_box);
+        self
+    }
+
+    /// Set the probability for each block to be placed, if lower than 1, `next_float` is
+    /// called once for each block.
+    pub fn with_integrity(mut self, integrity: f32) -> Self {
+        self.integrity = integrity;
+        self
+    }
+
+    /// Set if air blocks of the template should be placed, structure void is never placed.
+    pub fn with_air(mut self, place_air: bool) -> Self {
+        self.ignore_air = !place_air;
+        self
+    }
+
+    /// Set if jigsaw blocks should be replaced by their final state, like in world generation.
+    pub fn with_jigsaws_replaced(mut self, replace_jigsaws: bool) -> Self {
+        self.replace_jigsaws = replace_jigsaws;
+        self
+    }
+
+    /// Place each block relative to the given heightmap's height of its column plus the given
+    /// offset instead of the template's origin, like vanilla's gravity processor used by
+    /// terrain matching jigsaw elements.
+    pub fn with_gravity(mut self, heightmap_type: &'static HeightmapType, offset: i32) -> Self {
+        self.gravity = Some((heightmap_type, offset));
+        self
+    }
+
+    #[inline]
+    pub fn get_rotation(&self) -> Rotation {
+        self.rotation
+    }
+
+    #[inline]
+    pub fn get_mirror(&self) -> Mirror {
+        self.mirror
+    }
+
+    /// Transform relative horizontal coordinates with the mirror and then the rotation.
+    pub fn transform(&self, x: i32, z: i32) -> (i32, i32) {
+        let (x, z) = self.mirror.transform(x, z);
+        self.rotation.transform(x, z)
+    }
+
+}
+
+impl Default for TemplatePlaceSettings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// A structure template, made of a size, a list of blocks and the jigsaws and data markers
+/// among these blocks.
+pub struct StructureTemplate {
+    size: (i32, i32, i32),
+    blocks: Vec<TemplateBlock>,
+    jigsaws: Vec<TemplateJigsaw>,
+    markers: Vec<TemplateMarker>
+}
+
+impl StructureTemplate {
+
+    pub fn new(size: (i32, i32, i32), blocks: Vec<TemplateBlock>) -> Self {
+        Self::with_jigsaws(size, blocks, Vec::new())
+    }
+
+    /// Construct a template with jigsaws, jigsaws are sorted by Y, X and then Z, like vanilla.
+    pub fn with_jigsaws(size: (i32, i32, i32), blocks: Vec<TemplateBlock>, mut jigsaws: Vec<TemplateJigsaw>) -> Self {
+        jigsaws.sort_by_key(|jigsaw| (jigsaw.y, jigsaw.x, jigsaw.z));
+        Self { size, blocks, jigsaws, markers: Vec::new() }
+    }
+
+    /// Set the data markers of this template.
+    pub fn with_markers(mut self, markers: Vec<TemplateMarker>) -> Self {
+        self.markers = markers;
+        self
+    }
+
+    /// Decode a template from its gzip-compressed NBT representation.
+    pub fn from_reader(reader: &mut impl Read, env: &LevelEnv) -> Result<Self, DecodeError> {
+        Self::from_nbt(&read_gzip_compound_tag(reader)?, env)
+    }
+
+    /// Decode a template from a gzip-compressed NBT file.
+    pub fn from_file(path: &Path, env: &LevelEnv) -> Result<Self, DecodeError> {
+        let mut file = File::open(path)
+            .map_err(|e| DecodeError::Malformed(format!("Can't open template '{}': {}", path.display(), e)))?;
+        Self::from_reader(&mut file, env)
+    }
+
+    /// Capture a template from the region of a level of the given size, starting at the given
+    /// position. Structure void blocks are not captured and structure blocks in data mode are
+    /// captured as data markers, other block entities and entities are currently ignored.
+    /// Returns `None` if a chunk of the region is not loaded.
+    pub fn capture(level: &Level, x: i32, y: i32, z: i32, size: (i32, i32, i32)) -> Option<Self> {
+
+        let (size_x, size_y, size_z) = size;
+        let region = BoundingBox::new(x, y, z, x + size_x - 1, y + size_y - 1, z + size_z - 1);
+
+        // Blocks are ordered by Y, X and then Z, like vanilla.
+        let mut blocks = Vec::new();
+        for dy in 0..size_y {
+            for dx in 0..size_x {
+                for dz in 0..size_z {
+                    let state = level.chunks.get_block_at(x + dx, y + dy, z + dz).ok()?;
+                    if !state.is_block(&STRUCTURE_VOID) {
+                        blocks.push(TemplateBlock { x: dx, y: dy, z: dz, state });
+                    }
+                }
+            }
+        }
+
+        let mut markers = Vec::new();
+        for (_, (base, structure_block)) in level.entities.ecs.query::<(&BaseBlockEntity, &StructureBlockEntity)>().iter() {
+            if structure_block.get_mode() == StructureMode::Data && region.contains(base.pos.x, base.pos.y, base.pos.z) {
+                markers.push(TemplateMarker {
+                    x: base.pos.x - x,
+                    y: base.pos.y - y,
+                    z: base.pos.z - z,
+                    metadata: structure_block.get_metadata().to_string()
+                });
+            }
+        }
+        markers.sort_by_key(|marker| (marker.y, marker.x, marker.z));
+
+        Some(Self::new(size, blocks).with_markers(markers))
+
+    }
+
+    /// Decode a template from its NBT representation, only blocks are decoded, entities
+    /// and blocks' NBT are currently ignored, except for jigsaw blocks and data markers.
+    pub fn from_nbt(tag_root: &CompoundTag, env: &LevelEnv) -> Result<Self, DecodeError> {
+
+        let size = decode_pos(tag_root, "size")?;
+
+        let mut palette = Vec::new();
+        for tag_state in tag_root.get_compound_tag_vec("palette")? {
+            palette.push(decode_block_state(tag_state, env)?);
+        }
+
+        let mut blocks = Vec::new();
+        let mut jigsaws = Vec::new();
+        let mut markers = Vec::new();
+        for tag_block in tag_root.get_compound_tag_vec("blocks")? {
+            let (x, y, z) = decode_pos(tag_block, "pos")?;
+            let state_index = tag_block.get_i32("state")?;
+            let state = *palette.get(state_index as usize)
+                .ok_or_else(|| DecodeError::Malformed(format!("Invalid palette index {}.", state_index)))?;
+            blocks.push(TemplateBlock { x, y, z, state });
+            if state.is_block(&JIGSAW) {
+                jigsaws.push(decode_jigsaw(tag_block.get_compound_tag("nbt")?, x, y, z, state, env)?);
+            } else if state.is_block(&STRUCTURE_BLOCK) {
+                if let Ok(tag_nbt) = tag_block.get_compound_tag("nbt") {
+                    if let Ok("DATA") = tag_nbt.get_str("mode") {
+                        let metadata = tag_nbt.get_str("metadata")?.to_string();
+                        markers.push(TemplateMarker { x, y, z, metadata });
+                    }
+                }
+            }
+        }
+
+        Ok(Self::with_jigsaws(size, blocks, jigsaws).with_markers(markers))
+
+    }
+
+    /// Encode this template to its NBT representation, data markers are encoded as structure
+    /// blocks in data mode. Jigsaws are encoded as plain blocks.
+    pub fn to_nbt(&self) -> CompoundTag {
+
+        let mut palette: Vec<&'static BlockState> = Vec::new();
+        let mut tag_blocks = Vec::new();
+
+        for block in &self.blocks {
+            let state_index = match palette.iter().position(|&state| state == block.state) {
+                Some(index) => index,
+                None => {
+                    palette.push(block.state);
+                    palette.len() - 1
+                }
+            };
+            let mut tag_block = CompoundTag::new();
+            tag_block.insert("pos", encode_pos(block.x, block.y, block.z));
+            tag_block.insert_i32("state", state_index as i32);
+            if let Some(marker) = self.markers.iter().find(|m| (m.x, m.y, m.z) == (block.x, block.y, block.z)) {
+                let mut tag_nbt = CompoundTag::new();
+                tag_nbt.insert_str("id", "minecraft:structure_block");
+                tag_nbt.insert_str("mode", "DATA");
+                tag_nbt.insert_str("metadata", &marker.metadata);
+                tag_block.insert_compound_tag("nbt", tag_nbt);
+            }
+            tag_blocks.push(tag_block);
+        }
+
+        let mut tag_root = CompoundTag::new();
+        tag_root.insert_i32("DataVersion", DATA_VERSION);
+        tag_root.insert("size", encode_pos(self.size.0, self.size.1, self.size.2));
+        tag_root.insert_compound_tag_vec("palette", palette.into_iter().map(encode_block_state));
+        tag_root.insert_compound_tag_vec("blocks", tag_blocks);
+        tag_root.insert_compound_tag_vec("entities", Vec::new());
+        tag_root
+
+    }
+
+    /// Encode this template to a gzip-compressed NBT file, parent directories are created
+    /// if needed.
+    pub fn write_to_file(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = File::create(path)?;
+        write_gzip_compound_tag(&mut file, &self.to_nbt())
+    }
+
+    /// Return the size of this template, without rotation.
+    #[inline]
+    pub fn get_size(&self) -> (i32, i32, i32) {
+        self.size
+    }
+
+    /// Return the size of this template after the given rotation.
+    pub fn get_rotated_size(&self, rotation: Rotation) -> (i32, i32, i32) {
+        let (x, y, z) = self.size;
+        if rotation.is_swapping_axes() {
+            (z, y, x)
+        } else {
+            (x, y, z)
+        }
+    }
+
+    #[inline]
+    pub fn get_blocks(&self) -> &[TemplateBlock] {
+        &self.blocks
+    }
+
+    #[inline]
+    pub fn get_jigsaws(&self) -> &[TemplateJigsaw] {
+        &self.jigsaws
+    }
+
+    #[inline]
+    pub fn get_markers(&self) -> &[TemplateMarker] {
+        &self.markers
+    }
+
+    /// Return the bounding box of this template when placed at the given position with the
+    /// given rotation.
+    pub fn get_bounding_box(&self, x: i32, y: i32, z: i32, rotation: Rotation) -> BoundingBox {
+        self.get_mirrored_bounding_box(x, y, z, rotation, Mirror::None)
+    }
+
+    /// Return the bounding box of this template when placed at the given position with the
+    /// given rotation and mirror.
+    pub fn get_mirrored_bounding_box(&self, x: i32, y: i32, z: i32, rotation: Rotation, mirror: Mirror) -> BoundingBox {
+        let (size_x, size_y, size_z) = self.size;
+        let (dx, dz) = mirror.transform(size_x - 1, size_z - 1);
+        let (dx, dz) = rotation.transform(dx, dz);
+        BoundingBox::new(x, y, z, x + dx, y + size_y - 1, z + dz)
+    }
+
+    /// Return the position to give to `place` so that the rotated template occupies the
+    /// volume starting at the given position, toward positive X and Z.
+    pub fn get_zero_position(&self, x: i32, y: i32, z: i32, rotation: Rotation) -> (i32, i32, i32) {
+        let (size_x, _, size_z) = self.size;
+        match rotation {
+            Rotation::None => (x, y, z),
+            Rotation::Clockwise90 => (x + size_z - 1, y, z),
+            Rotation::Clockwise180 => (x + size_x - 1, y, z + size_z - 1),
+            Rotation::CounterClockwise90 => (x, y, z + size_x - 1)
+        }
+    }
+
+    /// Place this template in the given level, the given position is the origin of the
+    /// template, around which the template is rotated. Blocks that are outside of the level
+    /// view or outside of the settings' bounding box are ignored, structure blocks are never
+    /// placed. Blocks are placed with a `BlockPlacer`, so they are waterlogged when placed
+    /// into water.
+    pub fn place(&self, level: &mut dyn LevelView, rand: &mut JavaRandom, x: i32, y: i32, z: i32, settings: &TemplatePlaceSettings) {
+
+        let mut placer = BlockPlacer::new(level);
+
+        for block in &self.blocks {
+
+            let state = match self.get_placed_state(block, settings) {
+                Some(state) => state,
+                None => continue
+            };
+
+            let (dx, dz) = settings.transform(block.x, block.z);
+            let (bx, bz) = (x + dx, z + dz);
+            let by = match settings.gravity {
+                Some((heightmap_type, offset)) => match placer.level().get_heightmap_column_at(heightmap_type, bx, bz) {
+                    Ok(height) => height + offset + block.y,
+                    Err(_) => continue
+                },
+                None => y + block.y
+            };
+
+            if let Some(bounding_box) = &settings.bounding_box {
+                if !bounding_box.contains(bx, by, bz) {
+                    continue;
+                }
+            }
+
+            if settings.integrity < 1.0 && rand.next_float() > settings.integrity {
+                continue;
+            }
+
+            placer.place(bx, by, bz, state);
+
+        }
+
+    }
+
+    /// Place this template in the loaded chunks of a level, like `place` but blocks are set
+    /// directly, without waterlogging, and the gravity of the settings is not supported.
+    /// Blocks in unloaded chunks are ignored. Returns the number of blocks placed.
+    pub fn place_in_level(&self, level: &Level, rand: &mut JavaRandom, x: i32, y: i32, z: i32, settings: &TemplatePlaceSettings) -> usize {
+
+        let mut count = 0;
+
+        for block in &self.blocks {
+
+            let state = match self.get_placed_state(block, settings) {
+                Some(state) => state,
+                None => continue
+            };
+
+            let (dx, dz) = settings.transform(block.x, block.z);
+            let (bx, by, bz) = (x + dx, y + block.y, z + dz);
+
+            if let Some(bounding_box) = &settings.bounding_box {
+                if !bounding_box.contains(bx, by, bz) {
+                    continue;
+                }
+            }
+
+            if settings.integrity < 1.0 && rand.next_float() > settings.integrity {
+                continue;
+            }
+
+            if level.chunks.set_block_at(bx, by, bz, state).is_ok() {
+                count += 1;
+            }
+
+        }
+
+        count
+
+    }
+
+    /// Return the mirrored and rotated state of the given block to place with the given
+    /// settings, or `None` if this block must not be placed.
+    fn get_placed_state(&self, block: &TemplateBlock, settings: &TemplatePlaceSettings) -> Option<&'static BlockState> {
+
+        if block.state.is_block(&STRUCTURE_VOID) || block.state.is_block(&STRUCTURE_BLOCK) || (settings.ignore_air && block.state.is_block(&AIR)) {
+            return None;
+        }
+
+        let mut state = block.state;
+        if settings.replace_jigsaws && state.is_block(&JIGSAW) {
+            if let Some(jigsaw) = self.jigsaws.iter().find(|j| (j.x, j.y, j.z) == (block.x, block.y, block.z)) {
+                state = jigsaw.final_state;
+            }
+        }
+
+        Some(settings.rotation.rotate_state(settings.mirror.mirror_state(state)))
+
+    }
+
+}
+
+
+/// Encode a position as a list of 3 integer tags.
+fn encode_pos(x: i32, y: i32, z: i32) -> Tag {
+    Tag::List(vec![Tag::Int(x), Tag::Int(y), Tag::Int(z)])
+}
+
+/// Decode a position stored as a list of 3 integer tags.
+fn decode_pos(tag: &CompoundTag, name: &str) -> Result<(i32, i32, i32), DecodeError> {
+    match tag.iter().find(|(tag_name, _)| tag_name.as_str() == name) {
+        Some((_, Tag::List(list))) => match list[..] {
+            [Tag::Int(x), Tag::Int(y), Tag::Int(z)] => Ok((x, y, z)),
+            _ => Err(DecodeError::Malformed(format!("Invalid position '{}'.", name)))
+        },
+        _ => Err(DecodeError::Malformed(format!("Missing position '{}'.", name)))
+    }
+}
+
+
+fn parse_direction(name: &str) -> Option<Direction> {
+    Some(match name {
+        "north" => Direction::North,
+        "east" => Direction::East,
+        "south" => Direction::South,
+        "west" => Direction::West,
+        "up" => Direction::Up,
+        "down" => Direction::Down,
+        _ => return None
+    })
+}
+
+/// Decode the NBT of a jigsaw block.
+fn decode_jigsaw(tag_nbt: &CompoundTag, x: i32, y: i32, z: i32, state: &'static BlockState, env: &LevelEnv) -> Result<TemplateJigsaw, DecodeError> {
+
+    let orientation = state.iter_raw_states()
+        .and_then(|mut props| props.find(|(name, _)| *name == "orientation"))
+        .map(|(_, value)| value)
+        .ok_or_else(|| DecodeError::Malformed("Jigsaw without orientation.".to_string()))?;
+
+    let (front, top) = match orientation.split_once('_') {
+        Some((front, top)) => (parse_direction(front), parse_direction(top)),
+        None => (None, None)
+    };
+
+    let (front, top) = front.zip(top)
+        .ok_or_else(|| DecodeError::Malformed(format!("Invalid jigsaw orientation '{}'.", orientation)))?;
+
+    let rollable = match tag_nbt.get_str("joint") {
+        Ok(joint) => joint == "rollable",
+        Err(_) => matches!(front, Direction::Up | Direction::Down)
+    };
+
+    Ok(TemplateJigsaw {
+        x,
+        y,
+        z,
+        front,
+        top,
+        name: tag_nbt.get_str("name")?.to_string(),
+        target: tag_nbt.get_str("target")?.to_string(),
+        pool: tag_nbt.get_str("pool")?.to_string(),
+        final_state: parse_block_state(tag_nbt.get_str("final_state")?, env)?,
+        rollable
+    })
+
+}
+
+/// Parse a block state from its string representation, for example `minecraft:chain[axis=x]`.
+pub fn parse_block_state(s: &str, env: &LevelEnv) -> Result<&'static BlockState, DecodeError> {
+
+    let (name, props) = match s.split_once('[') {
+        Some((name, props)) => (name, props.trim_end_matches(']')),
+        None => (s, "")
+    };
+
+    let mut state = env.blocks.get_block_from_name(name)
+        .map(|block| block.get_default_state())
+        .ok_or_else(|| DecodeError::UnknownBlockState(name.to_string()))?;
+
+    for prop in props.split(',').filter(|prop| !prop.is_empty()) {
+        let (prop_name, prop_value) = prop.split_once('=')
+            .ok_or_else(|| DecodeError::Malformed(format!("Invalid block state '{}'.", s)))?;
+        state = state.with_raw(prop_name, prop_value)
+            .ok_or_else(|| DecodeError::UnknownBlockProperty(format!("{}/{}={}", name, prop_name, prop_value)))?;
+    }
+
+    Ok(state)
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_vanilla::ext::VanillaLevelEnv;
+    use mc_core::pos::Axis;
+    use mc_vanilla::block::{BONE_BLOCK, PROP_AXIS};
+    use mc_vanilla::biome::PLAINS;
+
+    use crate::view::TestLevelView;
+
+    use super::*;
+
+    #[test]
+    fn template() {
+
+        let env = LevelEnv::vanilla();
+
+        let mut tag_root = CompoundTag::new();
+        tag_root.insert("size", Tag::List(vec![Tag::Int(3), Tag::Int(1), Tag::Int(2)]));
+        let mut tag_bone = CompoundTag::new();
+        tag_bone.insert_str("Name", "minecraft:bone_block");
+        let mut tag_props = CompoundTag::new();
+        tag_props.insert_str("axis", "x");
+        tag_bone.insert_compound_tag("Properties", tag_props);
+        let mut tag_air = CompoundTag::new();
+        tag_air.insert_str("Name", "minecraft:air");
+        tag_root.insert_compound_tag_vec("palette", vec![tag_bone, tag_air]);
+        let mut blocks = Vec::new();
+        for (x, z, state) in [(0, 0, 0), (1, 0, 0), (2, 1, 0), (1, 1, 1)] {
+            let mut tag_block = CompoundTag::new();
+            tag_block.insert("pos", Tag::List(vec![Tag::Int(x), Tag::Int(0), Tag::Int(z)]));
+            tag_block.insert_i32("state", state);
+            blocks.push(tag_block);
+        }
+        tag_root.insert_compound_tag_vec("blocks", blocks);
+
+        let template = StructureTemplate::from_nbt(&tag_root, &env).unwrap();
+        assert_eq!(template.get_size(), (3, 1, 2));
+        assert_eq!(template.get_blocks().len(), 4);
+        assert_eq!(template.get_rotated_size(Rotation::Clockwise90), (2, 1, 3));
+
+        let mut level = TestLevelView::new(AIR.get_default_state(), -1, &PLAINS);
+        let rotation = Rotation::Clockwise90;
+        let (x, y, z) = template.get_zero_position(10, 5, 10, rotation);
+        template.place(&mut level, &mut JavaRandom::new(0), x, y, z, &TemplatePlaceSettings::new().with_rotation(rotation));
+
+        let bone_z = BONE_BLOCK.get_default_state().with(&PROP_AXIS, Axis::Z).unwrap();
+        assert_eq!(level.count_blocks(bone_z), 3);
+        // The rotated template fits in the 2x3 area starting at the given position.
+        assert!(level.get_block_at(11, 5, 10).unwrap() == bone_z);
+        assert!(level.get_block_at(11, 5, 11).unwrap() == bone_z);
+        assert!(level.get_block_at(10, 5, 12).unwrap() == bone_z);
+
+        assert!(StructureTemplate::from_nbt(&CompoundTag::new(), &env).is_err());
+
+    }
+
+}
@@ -0,0 +1,188 @@
+//! Buried treasure structure, a single chest buried in the sand of beaches.
+
+use mc_core::block::BlockState;
+use mc_core::rand::JavaRandom;
+use mc_core::pos::Direction;
+
+use mc_vanilla::heightmap::OCEAN_FLOOR_WG;
+use mc_vanilla::block::*;
+
+use crate::view::LevelView;
+use crate::seed;
+
+use super::piece::{BoundingBox, PiecePlacer, StructurePiece, StructureStart};
+use super::PieceStructure;
+
+
+/// Loot table of buried treasure chests.
+pub const BURIED_TREASURE_LOOT_TABLE: &str = "minecraft:chests/buried_treasure";
+
+const SALT: i32 = 10387320;
+const PROBABILITY: f32 = 0.01;
+
+
+/// The buried treasure structure, each chunk has a 1% chance of containing a treasure. This
+/// structure should only be decorated in beach biomes.
+///
+/// Valid for: 1.16.2 to 1.17.1
+pub struct BuriedTreasure {
+    seed: i64
+}
+
+impl BuriedTreasure {
+
+    pub fn new(seed: i64) -> Self {
+        Self { seed }
+    }
+
+    /// Return true if the given chunk contains a buried treasure, this is the same check as
+    /// the one used to locate treasures for treasure maps.
+    pub fn is_treasure_chunk(&self, cx: i32, cz: i32) -> bool {
+        let mut rand = JavaRandom::new(seed::region_seed(self.seed, cx, cz, SALT));
+        rand.next_float() < PROBABILITY
+    }
+
+    /// Return the horizontal position of the treasure in the given chunk, this is the
+    /// position targeted by treasure maps.
+    pub fn get_treasure_column(cx: i32, cz: i32) -> (i32, i32) {
+        (cx * 16 + 9, cz * 16 + 9)
+    }
+
+}
+
+impl PieceStructure for BuriedTreasure {
+
+    fn get_range(&self) -> i32 {
+        0
+    }
+
+    fn generate_start(&self, cx: i32, cz: i32) -> Option<StructureStart> {
+        if self.is_treasure_chunk(cx, cz) {
+            let (x, z) = Self::get_treasure_column(cx, cz);
+            Some(StructureStart::new(vec![Box::new(BuriedTreasurePiece {
+                bb: BoundingBox::new(x, 90, z, x, 90, z)
+            })]))
+        } else {
+            None
+        }
+    }
+
+}
+
+
+/// The only piece of buried treasures, its bounding box is moved to the chest once placed.
+struct BuriedTreasurePiece {
+    bb: BoundingBox
+}
+
+impl StructurePiece for BuriedTreasurePiece {
+
+    fn get_name(&self) -> &'static str {
+        "buried_treasure"
+    }
+
+    fn get_bounding_box(&self) -> &BoundingBox {
+        &self.bb
+    }
+
+    fn offset(&mut self, dx: i32, dy: i32, dz: i32) {
+        self.bb.offset(dx, dy, dz);
+    }
+
+    fn place(&mut self, level: &mut dyn LevelView, rand: &mut JavaRandom, chunk_bb: &BoundingBox) -> bool {
+
+        const DIRECTIONS: [Direction; 6] = [
+            Direction::Down, Direction::Up,
+            Direction::North, Direction::South,
+            Direction::West, Direction::East
+        ];
+
+        let (x, z) = (self.bb.min_x, self.bb.min_z);
+        let mut y = match level.get_heightmap_column_at(&OCEAN_FLOOR_WG, x, z) {
+            Ok(y) => y,
+            Err(_) => return false
+        };
+
+        while y > 0 {
+
+            let state = level.get_block_at(x, y, z).unwrap();
+            let below = level.get_block_at(x, y - 1, z).unwrap();
+
+            if below.is_block(&SANDSTONE) || below.is_block(&STONE) || below.is_block(&ANDESITE) ||
+                below.is_block(&GRANITE) || below.is_block(&DIORITE) {
+
+                let cover = if !is_air_or_liquid(state) { state } else { SAND.get_default_state() };
+
+                for direction in DIRECTIONS {
+                    let (dx, dy, dz) = direction.normal(1);
+                    let (rx, ry, rz) = (x + dx, y + dy, z + dz);
+                    if is_air_or_liquid(level.get_block_at(rx, ry, rz).unwrap()) {
+                        let hollow_below = is_air_or_liquid(level.get_block_at(rx, ry - 1, rz).unwrap());
+                        let state = if hollow_below && direction != Direction::Up { below } else { cover };
+                        let _ = level.set_block_at(rx, ry, rz, state);
+                    }
+                }
+
+                self.bb = BoundingBox::new(x, y, z, x, y, z);
+                // The piece has no orientation, the north orientation keeps coordinates.
+                return PiecePlacer::new(level, chunk_bb, self.bb, Direction::North)
+                    .place_chest(0, 0, 0, rand, BURIED_TREASURE_LOOT_TABLE);
+
+            }
+
+            y -= 1;
+
+        }
+
+        false
+
+    }
+
+}
+
+fn is_air_or_liquid(state: &'static BlockState) -> bool {
+    state.is_block(&AIR) || state.is_block(&CAVE_AIR) || state == WATER.get_default_state() || state == LAVA.get_default_state()
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use mc_vanilla::biome::BEACH;
+
+    use crate::view::TestLevelView;
+
+    use super::*;
+
+    #[test]
+    fn buried_treasure() {
+
+        let treasure = BuriedTreasure::new(0);
+
+        let (cx, cz) = (-64..64)
+            .flat_map(|cx| (-64..64).map(move |cz| (cx, cz)))
+            .find(|&(cx, cz)| treasure.is_treasure_chunk(cx, cz))
+            .expect("no treasure found");
+
+        let mut start = treasure.generate_start(cx, cz).unwrap();
+
+        // Sand over sandstone.
+        let mut level = TestLevelView::new(SANDSTONE.get_default_state(), 60, &BEACH);
+        let (x, z) = BuriedTreasure::get_treasure_column(cx, cz);
+        for dx in -2..=2 {
+            for dz in -2..=2 {
+                for y in 61..=63 {
+                    level.set_block_at(x + dx, y, z + dz, SAND.get_default_state()).unwrap();
+                }
+            }
+        }
+
+        start.place_in_chunk(&mut level, &mut JavaRandom::new(0), cx, cz);
+        assert!(level.get_block_at(x, 61, z).unwrap().is_block(&CHEST));
+        assert_eq!(level.get_loot_table(x, 61, z), Some(BURIED_TREASURE_LOOT_TABLE));
+        assert_eq!(*start.get_bounding_box(), BoundingBox::new(x, 90, z, x, 90, z));
+        assert_eq!(*start.get_pieces()[0].get_bounding_box(), BoundingBox::new(x, 61, z, x, 61, z));
+
+    }
+
+}
@@ -10,6 +10,9 @@ use mc_core::world::level::LevelEnv;
 use mc_core::block::BlockState;
 use mc_core::biome::Biome;
 
+use crate::gen::settings::{GeneratorSettings, DEFAULT_SETTINGS};
+use crate::gen::legacy::ChunkBiomeCache;
+
 
 /// A local level view used to generate feature in an partial level view.
 pub trait LevelView {
@@ -17,6 +20,12 @@ pub trait LevelView {
     /// Get a reference to the shared level environment.
     fn get_env(&self) -> &Arc<LevelEnv>;
 
+    /// Get the settings of the generator using this view, vanilla overworld settings are
+    /// returned by default.
+    fn get_settings(&self) -> &GeneratorSettings {
+        &DEFAULT_SETTINGS
+    }
+
     fn get_chunk(&self, cx: i32, cz: i32) -> Option<&Chunk>;
     fn get_chunk_mut(&mut self, cx: i32, cz: i32) -> Option<&mut Chunk>;
 
@@ -37,6 +46,17 @@ pub trait LevelView {
 
     fn get_heightmap_column_at(&self, heightmap_type: &'static HeightmapType, x: i32, z: i32) -> ChunkResult<i32>;
 
+    /// Get the biomes cache of the given chunk, if the view keeps one. Features that need
+    /// many biome lookups can use it directly, `None` by default.
+    fn get_biome_cache(&self, _cx: i32, _cz: i32) -> Option<&ChunkBiomeCache> {
+        None
+    }
+
+    /// Set the loot table of the container at the given position, with the seed used to
+    /// generate its content. This is a hook for views that support block entities, it's
+    /// ignored by default.
+    fn set_loot_table_at(&mut self, _x: i32, _y: i32, _z: i32, _loot_table: &'static str, _seed: i64) {}
+
 }
 
 
@@ -60,6 +80,11 @@ pub trait ProtoChunkView {
 
     fn get_heightmap_column_at(&self, heightmap_type: &'static HeightmapType, x: i32, z: i32) -> ChunkResult<i32>;
 
+    /// Get the biomes cache computed for this chunk by its noise generator, if any.
+    fn get_biome_cache(&self) -> Option<&ChunkBiomeCache> {
+        None
+    }
+
 }
 
 impl ProtoChunkView for ProtoChunk {
@@ -100,3 +125,104 @@ impl ProtoChunkView for ProtoChunk {
     }
 
 }
+
+
+/// A simple level view for tests, backed by a map of blocks over a default block, with a
+/// single biome and no actual chunk.
+#[cfg(test)]
+pub(crate) struct TestLevelView {
+    env: Arc<LevelEnv>,
+    blocks: std::collections::HashMap<(i32, i32, i32), &'static BlockState>,
+    loot_tables: std::collections::HashMap<(i32, i32, i32), &'static str>,
+    default_state: &'static BlockState,
+    ground_state: &'static BlockState,
+    ground_y: i32,
+    biome: &'static Biome
+}
+
+#[cfg(test)]
+impl TestLevelView {
+
+    /// Construct a view filled with `ground` up to `ground_y` (inclusive) and air above.
+    pub fn new(ground: &'static BlockState, ground_y: i32, biome: &'static Biome) -> Self {
+        use mc_vanilla::ext::VanillaLevelEnv;
+        Self {
+            env: LevelEnv::vanilla(),
+            blocks: std::collections::HashMap::new(),
+            loot_tables: std::collections::HashMap::new(),
+            default_state: mc_vanilla::block::AIR.get_default_state(),
+            ground_state: ground,
+            ground_y,
+            biome
+        }
+    }
+
+    /// Fill the view above the ground with the given state instead of air.
+    pub fn with_above(mut self, state: &'static BlockState) -> Self {
+        self.default_state = state;
+        self
+    }
+
+    /// Count the blocks of the given state that have been explicitly set in this view.
+    pub fn count_blocks(&self, state: &'static BlockState) -> usize {
+        self.blocks.values().filter(|&&s| s == state).count()
+    }
+
+    /// Return the loot table set at the given position, if any.
+    pub fn get_loot_table(&self, x: i32, y: i32, z: i32) -> Option<&'static str> {
+        self.loot_tables.get(&(x, y, z)).copied()
+    }
+
+    /// Return the positions of all loot tables set in this view.
+    pub fn get_loot_table_positions(&self) -> Vec<(i32, i32, i32)> {
+        self.loot_tables.keys().copied().collect()
+    }
+
+}
+
+#[cfg(test)]
+impl LevelView for TestLevelView {
+
+    fn get_env(&self) -> &Arc<LevelEnv> {
+        &self.env
+    }
+
+    fn get_chunk(&self, _cx: i32, _cz: i32) -> Option<&Chunk> {
+        None
+    }
+
+    fn get_chunk_mut(&mut self, _cx: i32, _cz: i32) -> Option<&mut Chunk> {
+        None
+    }
+
+    fn set_block_at(&mut self, x: i32, y: i32, z: i32, state: &'static BlockState) -> ChunkResult<()> {
+        self.blocks.insert((x, y, z), state);
+        Ok(())
+    }
+
+    fn get_block_at(&self, x: i32, y: i32, z: i32) -> ChunkResult<&'static BlockState> {
+        Ok(match self.blocks.get(&(x, y, z)) {
+            Some(&state) => state,
+            None if y <= self.ground_y => self.ground_state,
+            None => self.default_state
+        })
+    }
+
+    fn get_biome_at(&self, _x: i32, _y: i32, _z: i32) -> ChunkResult<&'static Biome> {
+        Ok(self.biome)
+    }
+
+    fn get_heightmap_column_at(&self, heightmap_type: &'static HeightmapType, x: i32, z: i32) -> ChunkResult<i32> {
+        for y in (0..256).rev() {
+            if heightmap_type.check_block(self.get_block_at(x, y, z)?, &self.env.blocks) {
+                return Ok(y + 1);
+            }
+        }
+        Ok(0)
+    }
+
+    fn set_loot_table_at(&mut self, x: i32, y: i32, z: i32, loot_table: &'static str, _seed: i64) {
+        self.loot_tables.insert((x, y, z), loot_table);
+    }
+
+}